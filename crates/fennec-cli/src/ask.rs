@@ -0,0 +1,339 @@
+//! `fennec ask`: send one prompt to the provider without the TUI and stream
+//! the answer to stdout. Piped stdin is added to the prompt as context, or
+//! is the prompt when none is given.
+
+use anyhow::Result;
+use fennec_core::config::Config;
+use fennec_core::provider::{ProviderClient, ProviderMessage, ProviderRequest};
+use fennec_core::session::Session;
+use fennec_core::transcript::MessageRole;
+use fennec_memory::{MemoryInjection, MemoryService};
+use fennec_provider::ProviderClientFactory;
+use fennec_security::audit::AuditLogger;
+use fennec_security::{PolicyResult, SandboxPolicy};
+use futures::StreamExt;
+use std::fmt::Write as _;
+use std::io::{IsTerminal, Read, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use tracing::warn;
+use uuid::Uuid;
+
+/// Exit code when the configuration, the sandbox or the arguments rule out
+/// the request
+pub const EXIT_CONFIG_ERROR: u8 = 2;
+/// Exit code when the provider fails or cannot be reached
+pub const EXIT_PROVIDER_ERROR: u8 = 3;
+
+#[derive(clap::Args, Debug)]
+#[command(
+    after_help = "EXIT CODES:\n  0  The answer was printed\n  2  Configuration, sandbox or argument error\n  3  Provider error"
+)]
+pub struct AskArgs {
+    /// Prompt to send; read from stdin when omitted
+    prompt: Option<String>,
+
+    /// Files added to the prompt
+    #[arg(
+        long = "context-file",
+        value_name = "PATH",
+        help = "Add a workspace file to the prompt; may be repeated"
+    )]
+    context_files: Vec<PathBuf>,
+
+    /// Model override
+    #[arg(long, help = "Model to use instead of provider.default_model")]
+    model: Option<String>,
+
+    /// Reply length limit
+    #[arg(long, help = "Most tokens the answer may take")]
+    max_tokens: Option<u32>,
+
+    /// Skip recording the exchange
+    #[arg(long, help = "Do not record the exchange in a transcript")]
+    no_memory: bool,
+}
+
+/// Report an error that keeps the request from being sent
+pub fn config_error(error: &anyhow::Error) -> ExitCode {
+    eprintln!("{:#}", error);
+    ExitCode::from(EXIT_CONFIG_ERROR)
+}
+
+fn provider_error(error: impl std::fmt::Display) -> ExitCode {
+    eprintln!("Provider error: {}", error);
+    ExitCode::from(EXIT_PROVIDER_ERROR)
+}
+
+/// Send the prompt, stream the answer and tell how to exit
+pub async fn run(
+    args: &AskArgs,
+    config: &Config,
+    sandbox_policy: &SandboxPolicy,
+) -> Result<ExitCode> {
+    let stdin = read_piped_stdin()?;
+    let (prompt, stdin) = match (&args.prompt, stdin) {
+        (Some(prompt), stdin) => (prompt.clone(), stdin),
+        (None, Some(stdin)) => (stdin, None),
+        (None, None) => {
+            return Ok(config_error(&anyhow::anyhow!(
+                "No prompt given; pass one as an argument or pipe it to stdin"
+            )))
+        }
+    };
+    let content = match user_content(
+        &prompt,
+        &args.context_files,
+        stdin.as_deref(),
+        sandbox_policy,
+    ) {
+        Ok(content) => content,
+        Err(e) => return Ok(config_error(&e)),
+    };
+
+    let client = match ProviderClientFactory::validate_config(&config.provider)
+        .and_then(|_| ProviderClientFactory::create_client(&config.provider))
+    {
+        Ok(client) => client,
+        Err(e) => return Ok(config_error(&e.into())),
+    };
+    let audit_logger = AuditLogger::new(config).await?;
+
+    // The session only lives for this exchange; memory still brings in
+    // guidance and earlier conversations related to the prompt
+    let session = match std::env::current_dir() {
+        Ok(workspace) => Session::new().with_workspace(workspace),
+        Err(_) => Session::new(),
+    };
+    let session_id = session.id;
+    let memory = match fennec_memory::create_memory_service().await {
+        Ok(memory) => Some(memory),
+        Err(e) => {
+            warn!("Answering without memory: {}", e);
+            None
+        }
+    };
+    let mut messages = Vec::new();
+    if let Some(memory) = &memory {
+        match memory.get_memory_injection(session_id, Some(&prompt)).await {
+            Ok(injection) => messages.extend(memory_message(&injection)),
+            Err(e) => warn!("Answering without memory: {}", e),
+        }
+    }
+    messages.push(ProviderMessage {
+        role: "user".to_string(),
+        content: content.clone(),
+    });
+
+    audit_logger.log_user_message(session_id, &content).await?;
+    let request = build_request(args, config, messages);
+    let answer = match stream_answer(client.as_ref(), request).await {
+        Ok(answer) => answer,
+        Err(e) => {
+            audit_logger
+                .log_error_event(session_id, &format!("Provider error: {}", e))
+                .await?;
+            return Ok(provider_error(e));
+        }
+    };
+    audit_logger
+        .log_assistant_message(session_id, &answer)
+        .await?;
+
+    if let (Some(memory), false) = (&memory, args.no_memory) {
+        if let Err(e) = record_exchange(memory, session, content, answer).await {
+            warn!("The exchange was not recorded: {}", e);
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Stdin when something is piped to it and it is not empty
+fn read_piped_stdin() -> Result<Option<String>> {
+    let mut stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        return Ok(None);
+    }
+    let mut input = String::new();
+    stdin.read_to_string(&mut input)?;
+    Ok((!input.trim().is_empty()).then_some(input))
+}
+
+/// The prompt followed by the context files and piped input
+fn user_content(
+    prompt: &str,
+    context_files: &[PathBuf],
+    stdin: Option<&str>,
+    sandbox_policy: &SandboxPolicy,
+) -> Result<String> {
+    let mut content = prompt.trim().to_string();
+    for path in context_files {
+        match sandbox_policy.check_read_path(path) {
+            PolicyResult::Allow => {}
+            PolicyResult::Deny(reason) | PolicyResult::RequireApproval(reason) => {
+                anyhow::bail!("Cannot read {}: {}", path.display(), reason)
+            }
+        }
+        let text = std::fs::read_to_string(sandbox_policy.workspace_path().join(path))
+            .map_err(|e| anyhow::anyhow!("Cannot read {}: {}", path.display(), e))?;
+        let _ = write!(
+            content,
+            "\n\nFile {}:\n```\n{}\n```",
+            path.display(),
+            text.trim_end()
+        );
+    }
+    if let Some(stdin) = stdin {
+        let _ = write!(
+            content,
+            "\n\nStandard input:\n```\n{}\n```",
+            stdin.trim_end()
+        );
+    }
+    Ok(content)
+}
+
+/// System message with the guidance and earlier conversations memory found
+/// for the prompt, if any
+fn memory_message(injection: &MemoryInjection) -> Option<ProviderMessage> {
+    if injection.guidance.is_empty() && injection.conversation_history.is_empty() {
+        return None;
+    }
+
+    let mut content = String::from("Context from Fennec's memory of this project.");
+    for guidance in &injection.guidance {
+        let _ = write!(
+            content,
+            "\n\n## {}\n{}",
+            guidance.section_title,
+            guidance.content.trim()
+        );
+    }
+    if injection.omitted_guidance > 0 {
+        let _ = write!(
+            content,
+            "\n\n({} more AGENTS.md sections matched but were left out to save space.)",
+            injection.omitted_guidance
+        );
+    }
+    if !injection.conversation_history.is_empty() {
+        content.push_str("\n\n## Earlier conversations");
+        for conversation in &injection.conversation_history {
+            let title = conversation
+                .metadata
+                .title
+                .clone()
+                .unwrap_or_else(|| conversation.session_id.to_string());
+            let excerpt = conversation.summary.as_deref().or_else(|| {
+                conversation
+                    .matching_messages
+                    .first()
+                    .map(|message| message.content.as_str())
+            });
+            let _ = write!(content, "\n- {}", title);
+            if let Some(excerpt) = excerpt {
+                let _ = write!(content, ": {}", excerpt.trim());
+            }
+        }
+    }
+    Some(ProviderMessage {
+        role: "system".to_string(),
+        content,
+    })
+}
+
+fn build_request(
+    args: &AskArgs,
+    config: &Config,
+    messages: Vec<ProviderMessage>,
+) -> ProviderRequest {
+    ProviderRequest {
+        id: Uuid::new_v4(),
+        messages,
+        model: args
+            .model
+            .clone()
+            .unwrap_or_else(|| config.provider.default_model.clone()),
+        stream: true,
+        temperature: None,
+        max_tokens: args.max_tokens,
+    }
+}
+
+/// Print the answer as it arrives, returning all of it
+async fn stream_answer(
+    client: &dyn ProviderClient,
+    request: ProviderRequest,
+) -> fennec_core::Result<String> {
+    let mut stream = client.stream(request).await?;
+    let mut answer = String::new();
+    let mut stdout = std::io::stdout();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        stdout.write_all(chunk.as_bytes())?;
+        stdout.flush()?;
+        answer.push_str(&chunk);
+    }
+    if !answer.ends_with('\n') {
+        writeln!(stdout)?;
+    }
+    Ok(answer)
+}
+
+/// Keep the exchange in a transcript of its own
+async fn record_exchange(
+    memory: &MemoryService,
+    session: Session,
+    content: String,
+    answer: String,
+) -> Result<()> {
+    let session_id = session.id;
+    memory.start_session(session).await?;
+    memory
+        .add_message(session_id, MessageRole::User, content)
+        .await?;
+    memory
+        .add_message(session_id, MessageRole::Assistant, answer)
+        .await?;
+    memory.stop_session(session_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        ask: AskArgs,
+    }
+
+    #[test]
+    fn test_request_takes_model_and_token_overrides() {
+        let config = Config::default();
+        let messages = vec![ProviderMessage {
+            role: "user".to_string(),
+            content: "hello".to_string(),
+        }];
+
+        let args = Cli::parse_from(["ask", "hello"]).ask;
+        let request = build_request(&args, &config, messages.clone());
+        assert_eq!(request.model, config.provider.default_model);
+        assert_eq!(request.max_tokens, None);
+        assert!(request.stream);
+
+        let args = Cli::parse_from([
+            "ask",
+            "hello",
+            "--model",
+            "gpt-4o-mini",
+            "--max-tokens",
+            "64",
+        ])
+        .ask;
+        let request = build_request(&args, &config, messages);
+        assert_eq!(request.model, "gpt-4o-mini");
+        assert_eq!(request.max_tokens, Some(64));
+    }
+}
@@ -0,0 +1,504 @@
+//! `fennec doctor`: check the configuration and the environment Fennec
+//! runs in, printing what passed, what deserves a look and what keeps
+//! Fennec from working, with a hint on how to fix each problem.
+//!
+//! Each check is a function from the [`Setup`] to a [`Check`]; add new ones
+//! to [`CHECKS`].
+
+use anyhow::Result;
+use fennec_core::config::Config;
+use fennec_core::config_layers::ConfigLoader;
+use fennec_core::provider::{ProviderMessage, ProviderRequest};
+use fennec_provider::{ModelCheck, ProviderClientFactory, ProviderError};
+use fennec_telemetry::TelemetryConfig;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Instant;
+use uuid::Uuid;
+
+/// Free space below which the storage directory fails the disk space check
+const MIN_FREE_SPACE: u64 = 100 * 1024 * 1024;
+/// Free space below which the disk space check warns
+const LOW_FREE_SPACE: u64 = 1024 * 1024 * 1024;
+
+#[derive(clap::Args, Debug)]
+pub struct DoctorArgs {
+    /// Also send a minimal request to the provider
+    #[arg(
+        long,
+        help = "Check the provider answers with a one-token request (may incur a small cost)"
+    )]
+    online: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl Status {
+    fn label(self) -> &'static str {
+        match self {
+            Status::Pass => "pass",
+            Status::Warn => "warn",
+            Status::Fail => "FAIL",
+        }
+    }
+}
+
+/// Outcome of one check
+#[derive(Debug)]
+struct Check {
+    name: &'static str,
+    status: Status,
+    details: String,
+    hint: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, details: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Pass,
+            details: details.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(name: &'static str, details: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Warn,
+            details: details.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(name: &'static str, details: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Fail,
+            details: details.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+/// What the checks look at
+struct Setup<'a> {
+    /// The configuration, or why it failed to load
+    loaded: std::result::Result<Config, String>,
+    /// The loaded configuration, or the built-in defaults
+    config: Config,
+    telemetry: &'a TelemetryConfig,
+    workspace: PathBuf,
+}
+
+/// Checks run on every `fennec doctor`, in the order they are printed
+const CHECKS: &[fn(&Setup) -> Check] = &[
+    check_config,
+    check_provider_credentials,
+    check_storage,
+    check_disk_space,
+    check_agents_md,
+    check_terminal,
+    check_log_dir,
+];
+
+/// Run the checks, print the table and fail when any check failed
+pub async fn run(
+    args: &DoctorArgs,
+    loader: ConfigLoader,
+    telemetry: &TelemetryConfig,
+) -> Result<ExitCode> {
+    let loaded = loader.load().await.map_err(|e| e.to_string());
+    let setup = Setup {
+        config: loaded.clone().unwrap_or_default(),
+        loaded,
+        telemetry,
+        workspace: std::env::current_dir()?,
+    };
+
+    let mut checks: Vec<Check> = CHECKS.iter().map(|check| check(&setup)).collect();
+    if args.online {
+        checks.push(check_model_online(&setup).await);
+        checks.push(check_provider_online(&setup).await);
+    }
+
+    print_table(&checks);
+    Ok(if checks.iter().any(|check| check.status == Status::Fail) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+fn print_table(checks: &[Check]) {
+    let width = checks
+        .iter()
+        .map(|check| check.name.len())
+        .max()
+        .unwrap_or(0);
+    println!("{:<6}  {:<width$}  DETAILS", "STATUS", "CHECK");
+    for check in checks {
+        println!(
+            "{:<6}  {:<width$}  {}",
+            check.status.label(),
+            check.name,
+            check.details
+        );
+        if let Some(hint) = &check.hint {
+            println!("{:<6}  {:<width$}  hint: {}", "", "", hint);
+        }
+    }
+
+    let count = |status| checks.iter().filter(|c| c.status == status).count();
+    println!(
+        "\n{} passed, {} warnings, {} failed",
+        count(Status::Pass),
+        count(Status::Warn),
+        count(Status::Fail)
+    );
+}
+
+fn check_config(setup: &Setup) -> Check {
+    const NAME: &str = "config";
+    let config = match &setup.loaded {
+        Ok(config) => config,
+        Err(e) => {
+            return Check::fail(
+                NAME,
+                e.clone(),
+                "Fix the file named above; the other checks use the built-in defaults",
+            )
+        }
+    };
+
+    let files = config.config_files();
+    let details = if files.is_empty() {
+        "No config files; using the built-in defaults".to_string()
+    } else {
+        let files: Vec<_> = files
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        format!("Loaded {}", files.join(", "))
+    };
+    if config.unknown_keys().is_empty() {
+        return Check::pass(NAME, details);
+    }
+    let unknown: Vec<_> = config
+        .unknown_keys()
+        .iter()
+        .map(|(key, layer)| format!("{} ({})", key, layer))
+        .collect();
+    Check::warn(
+        NAME,
+        format!("{}; unknown keys {}", details, unknown.join(", ")),
+        "Unknown keys are ignored; check them for typos",
+    )
+}
+
+fn check_provider_credentials(setup: &Setup) -> Check {
+    const NAME: &str = "provider";
+    let provider = &setup.config.provider;
+    if let Err(e) = ProviderClientFactory::validate_config(provider) {
+        return Check::fail(NAME, e.to_string(), "Fix the [provider] settings");
+    }
+
+    match provider.provider.as_str() {
+        "mock" => Check::warn(
+            NAME,
+            "The mock provider answers offline",
+            "Set provider.provider = \"openai\" for live answers",
+        ),
+        "openai" => match &provider.openai_api_key {
+            Some(key) if !key.trim().is_empty() => {
+                let source = setup
+                    .config
+                    .explain("provider.openai_api_key")
+                    .map(|layer| format!(" by {}", layer))
+                    .unwrap_or_default();
+                Check::pass(
+                    NAME,
+                    format!(
+                        "OpenAI API key set{}; default model {}",
+                        source, provider.default_model
+                    ),
+                )
+            }
+            _ => Check::fail(
+                NAME,
+                "No OpenAI API key; Fennec falls back to the offline mock provider",
+                "Set OPENAI_API_KEY or provider.openai_api_key",
+            ),
+        },
+        other => Check::fail(
+            NAME,
+            format!("Provider '{}' is not supported", other),
+            "Set provider.provider to \"openai\" or \"mock\"",
+        ),
+    }
+}
+
+async fn check_model_online(setup: &Setup<'_>) -> Check {
+    const NAME: &str = "model (online)";
+    let provider = &setup.config.provider;
+    match ProviderClientFactory::validate_model(provider).await {
+        Ok(ModelCheck::Available) => Check::pass(
+            NAME,
+            format!("{} is offered by the provider", provider.default_model),
+        ),
+        Ok(ModelCheck::Unverified { reason }) => Check::warn(
+            NAME,
+            format!("{} not checked: {}", provider.default_model, reason),
+            "Requests fail at first use if the model name is wrong",
+        ),
+        Err(ProviderError::ModelNotFound { model }) => Check::fail(
+            NAME,
+            format!("The provider does not offer model '{}'", model),
+            "Fix provider.default_model",
+        ),
+        Err(e) => Check::fail(
+            NAME,
+            e.to_string(),
+            "Check the API key, provider.base_url and network access",
+        ),
+    }
+}
+
+async fn check_provider_online(setup: &Setup<'_>) -> Check {
+    const NAME: &str = "provider (online)";
+    let provider = &setup.config.provider;
+    if provider.provider == "mock" || provider.openai_api_key.is_none() {
+        return Check::warn(
+            NAME,
+            "Skipped; no live provider is configured",
+            "Set an API key to check the provider",
+        );
+    }
+
+    let client = match ProviderClientFactory::create_client(provider) {
+        Ok(client) => client,
+        Err(e) => return Check::fail(NAME, e.to_string(), "Fix the [provider] settings"),
+    };
+    let request = ProviderRequest {
+        id: Uuid::new_v4(),
+        messages: vec![ProviderMessage {
+            role: "user".to_string(),
+            content: "ping".to_string(),
+        }],
+        model: provider.default_model.clone(),
+        stream: false,
+        temperature: Some(0.0),
+        max_tokens: Some(1),
+    };
+    let started = Instant::now();
+    match client.complete(request).await {
+        Ok(_) => Check::pass(
+            NAME,
+            format!(
+                "{} answered in {}ms",
+                provider.default_model,
+                started.elapsed().as_millis()
+            ),
+        ),
+        Err(e) => Check::fail(
+            NAME,
+            e.to_string(),
+            "Check the API key, provider.base_url and network access",
+        ),
+    }
+}
+
+/// The memory storage directory, relative to the workspace unless absolute
+fn storage_dir(setup: &Setup) -> PathBuf {
+    setup.workspace.join(&setup.config.memory.storage_path)
+}
+
+fn check_storage(setup: &Setup) -> Check {
+    const NAME: &str = "storage";
+    let dir = storage_dir(setup);
+    match writable(&dir) {
+        Ok(details) => Check::pass(NAME, format!("{} {}", dir.display(), details)),
+        Err(e) => Check::fail(
+            NAME,
+            format!("{}: {}", dir.display(), e),
+            "Make the directory writable or point memory.storage_path elsewhere",
+        ),
+    }
+}
+
+fn check_disk_space(setup: &Setup) -> Check {
+    const NAME: &str = "disk space";
+    let dir = storage_dir(setup);
+    let Some(existing) = existing_ancestor(&dir) else {
+        return Check::warn(
+            NAME,
+            format!("No existing directory above {}", dir.display()),
+            "Check memory.storage_path",
+        );
+    };
+    let available = match available_space(existing) {
+        Ok(available) => available,
+        Err(e) => {
+            return Check::warn(
+                NAME,
+                format!("Could not read free space: {}", e),
+                "Make sure the storage directory has room for transcripts",
+            )
+        }
+    };
+
+    let details = format!(
+        "{} MiB free for {}",
+        available / (1024 * 1024),
+        dir.display()
+    );
+    if available < MIN_FREE_SPACE {
+        Check::fail(
+            NAME,
+            details,
+            "Free up space; transcripts and memory files cannot be saved",
+        )
+    } else if available < LOW_FREE_SPACE {
+        Check::warn(NAME, details, "Free up space before it runs out")
+    } else {
+        Check::pass(NAME, details)
+    }
+}
+
+fn check_agents_md(_setup: &Setup) -> Check {
+    const NAME: &str = "AGENTS.md";
+    let Some(path) = fennec_memory::agents::agents_file_paths()
+        .into_iter()
+        .find(|path| path.exists())
+    else {
+        return Check::warn(
+            NAME,
+            "No AGENTS.md found",
+            "Add an AGENTS.md to the workspace to give Fennec project guidance",
+        );
+    };
+
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) => {
+            return Check::fail(
+                NAME,
+                format!("{}: {}", path.display(), e),
+                "Make the file a readable UTF-8 text file",
+            )
+        }
+    };
+    let issues = fennec_memory::agents::lint(&content);
+    if issues.is_empty() {
+        Check::pass(NAME, format!("{} has no issues", path.display()))
+    } else {
+        Check::warn(
+            NAME,
+            format!("{}: {}", path.display(), issues.join("; ")),
+            "Guidance is found by `## ` section; fix the sections listed",
+        )
+    }
+}
+
+fn check_terminal(_setup: &Setup) -> Check {
+    const NAME: &str = "terminal";
+    if !std::io::stdout().is_terminal() {
+        return Check::warn(
+            NAME,
+            "Standard output is not a terminal",
+            "The TUI needs a terminal; fennec ask and fennec exec work without one",
+        );
+    }
+
+    let term = std::env::var("TERM").unwrap_or_default();
+    if term.is_empty() || term == "dumb" {
+        return Check::warn(
+            NAME,
+            format!("TERM is '{}'", term),
+            "Set TERM, e.g. to xterm-256color, for the TUI to draw correctly",
+        );
+    }
+    let colors = match std::env::var("COLORTERM").as_deref() {
+        Ok("truecolor") | Ok("24bit") => "24-bit color",
+        _ if term.contains("256color") => "256 colors",
+        _ => "basic colors",
+    };
+    Check::pass(NAME, format!("{} with {}", term, colors))
+}
+
+fn check_log_dir(setup: &Setup) -> Check {
+    const NAME: &str = "log directory";
+    let logging = &setup.telemetry.logging;
+    if !logging.file_enabled {
+        return Check::pass(NAME, "File logging is off");
+    }
+    match writable(&logging.log_dir) {
+        Ok(details) => Check::pass(NAME, format!("{} {}", logging.log_dir.display(), details)),
+        Err(e) => Check::fail(
+            NAME,
+            format!("{}: {}", logging.log_dir.display(), e),
+            "Make the directory writable or pass --log-dir",
+        ),
+    }
+}
+
+/// Closest directory at or above `path` that exists
+fn existing_ancestor(path: &Path) -> Option<&Path> {
+    path.ancestors().find(|ancestor| ancestor.exists())
+}
+
+/// Whether files can be created in `dir`, or in the closest existing
+/// directory above it when `dir` does not exist yet
+fn writable(dir: &Path) -> std::result::Result<String, String> {
+    let existing = existing_ancestor(dir).ok_or("no part of the path exists")?;
+    if !existing.is_dir() {
+        return Err(format!("{} is not a directory", existing.display()));
+    }
+    let permissions = std::fs::metadata(existing)
+        .map_err(|e| e.to_string())?
+        .permissions();
+    if permissions.readonly() {
+        return Err(format!("{} is read-only", existing.display()));
+    }
+
+    let probe = existing.join(format!(".fennec-doctor-{}", Uuid::new_v4()));
+    std::fs::write(&probe, b"").map_err(|e| format!("cannot create files: {}", e))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(if existing == dir {
+        "is writable".to_string()
+    } else {
+        format!("will be created in {}", existing.display())
+    })
+}
+
+#[cfg(unix)]
+fn available_space(path: &Path) -> std::io::Result<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = std::ffi::CString::new(path.as_os_str().as_bytes())?;
+    // SAFETY: `path` is NUL terminated and `stat` is only read after
+    // statvfs filled it in
+    let stat = unsafe {
+        let mut stat: libc::statvfs = std::mem::zeroed();
+        if libc::statvfs(path.as_ptr(), &mut stat) != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        stat
+    };
+    #[allow(clippy::useless_conversion)]
+    Ok(u64::from(stat.f_bavail) * u64::from(stat.f_frsize))
+}
+
+#[cfg(not(unix))]
+fn available_space(_path: &Path) -> std::io::Result<u64> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "not supported on this platform",
+    ))
+}
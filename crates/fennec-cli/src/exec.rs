@@ -0,0 +1,173 @@
+//! `fennec exec`: run a single registry command from a script, without the
+//! TUI. Nobody is there to answer approval prompts, so operations that would
+//! prompt are denied, except low risk ones with `--auto-approve-low-risk`.
+
+use anyhow::Result;
+use fennec_commands::{
+    create_command_registry_with_config, CommandContext, CommandExecutionResult, MemoryMiddleware,
+};
+use fennec_core::config::{ApprovalPolicy, Config};
+use fennec_security::audit::AuditLogger;
+use fennec_security::{check_command_approval, ApprovalManager, ApprovalStatus, SandboxPolicy};
+use std::process::ExitCode;
+use std::sync::Arc;
+use tracing::warn;
+use uuid::Uuid;
+
+#[derive(clap::Args, Debug)]
+pub struct ExecArgs {
+    /// Registry command to run
+    #[arg(long, help = "Name of the command to run, e.g. search")]
+    command: String,
+
+    /// Command arguments
+    #[arg(
+        long,
+        default_value = "{}",
+        value_parser = parse_json_args,
+        help = "Command arguments as JSON, e.g. '{\"query\":\"TODO\"}'"
+    )]
+    args: serde_json::Value,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "json", help = "Output format")]
+    format: OutputFormat,
+
+    /// Approve low risk operations instead of denying them
+    #[arg(
+        long,
+        help = "Approve low-risk operations; anything else needing approval is denied"
+    )]
+    auto_approve_low_risk: bool,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum OutputFormat {
+    /// The execution result as JSON
+    Json,
+    /// The command's output, or its error on stderr
+    Text,
+}
+
+fn parse_json_args(args: &str) -> Result<serde_json::Value, String> {
+    serde_json::from_str(args).map_err(|e| format!("invalid JSON: {}", e))
+}
+
+/// Run the command, print its result and tell how to exit
+pub async fn run(
+    args: &ExecArgs,
+    config: &Config,
+    sandbox_policy: &SandboxPolicy,
+) -> Result<ExitCode> {
+    let mut approval_policies = config.security.approval_policies.clone();
+    if args.auto_approve_low_risk {
+        approval_policies.low = ApprovalPolicy::AutoApprove;
+    }
+    let approval_manager = ApprovalManager::new(false, false).with_policies(approval_policies);
+    let audit_logger = AuditLogger::new(config).await?;
+
+    let registry = create_command_registry_with_config(config).await?;
+    match fennec_memory::create_memory_service().await {
+        Ok(memory) => {
+            registry
+                .register_middleware(Box::new(MemoryMiddleware::new(Arc::new(memory))))
+                .await
+        }
+        Err(e) => warn!("The command will not be recorded in memory: {}", e),
+    }
+
+    let session_id = Uuid::new_v4();
+    let context = CommandContext {
+        session_id,
+        user_id: None,
+        workspace_path: Some(sandbox_policy.workspace_path().display().to_string()),
+        sandbox_level: sandbox_policy.level().clone(),
+        dry_run: false,
+        preview_only: false,
+        cancellation_token: Default::default(),
+        action_log: None,
+        timeout: None,
+        progress: None,
+    };
+
+    let command = registry
+        .get_command(&args.command)
+        .await
+        .ok_or_else(|| anyhow::anyhow!("Command '{}' not found", args.command))?;
+    audit_logger
+        .log_security_event(
+            Some(session_id),
+            "command_submitted",
+            &format!("Command '{}' submitted from fennec exec", args.command),
+        )
+        .await?;
+
+    // What the command is about to do decides whether it needs approval
+    if command.descriptor().supports_preview {
+        let preview = command.preview(&args.args, &context).await?;
+        let status = check_command_approval(&preview, sandbox_policy, &approval_manager)?;
+        if status != ApprovalStatus::Approved {
+            let reason = format!(
+                "Command '{}' needs approval, which fennec exec cannot ask for",
+                args.command
+            );
+            audit_logger
+                .log_security_event(Some(session_id), "command_denied", &reason)
+                .await?;
+            let result = CommandExecutionResult {
+                command_id: preview.command_id,
+                command_name: args.command.clone(),
+                execution_id: Uuid::new_v4(),
+                success: false,
+                output: String::new(),
+                error: Some(reason),
+                data: None,
+                preview: Some(preview),
+                execution_time_ms: 0,
+                created_at: chrono::Utc::now(),
+                deprecation: None,
+            };
+            return print_result(&result, args.format);
+        }
+    }
+
+    let result = registry
+        .execute_command(&args.command, &args.args, &context)
+        .await?;
+    audit_logger
+        .log_security_event(
+            Some(session_id),
+            "command_completed",
+            &format!(
+                "Command '{}' {} in {}ms",
+                args.command,
+                if result.success {
+                    "succeeded"
+                } else {
+                    "failed"
+                },
+                result.execution_time_ms
+            ),
+        )
+        .await?;
+    print_result(&result, args.format)
+}
+
+fn print_result(result: &CommandExecutionResult, format: OutputFormat) -> Result<ExitCode> {
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(result)?),
+        OutputFormat::Text => {
+            if !result.output.is_empty() {
+                println!("{}", result.output);
+            }
+            if let Some(error) = &result.error {
+                eprintln!("{}", error);
+            }
+        }
+    }
+    Ok(if result.success {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
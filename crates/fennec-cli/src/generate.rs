@@ -0,0 +1,64 @@
+//! `fennec completions` and `fennec man`: shell completions and the man
+//! page, generated from the command line definition so they cover every
+//! subcommand and flag.
+
+use crate::Cli;
+use anyhow::{Context, Result};
+use clap::CommandFactory;
+use clap_complete::Shell;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const BIN_NAME: &str = "fennec";
+
+#[derive(clap::Args, Debug)]
+pub struct CompletionsArgs {
+    /// Shell to complete for
+    #[arg(value_enum)]
+    shell: Shell,
+
+    /// Directory to write to instead of stdout
+    #[arg(long, value_name = "DIR", help = "Write the completions file to DIR")]
+    out: Option<PathBuf>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ManArgs {
+    /// Directory to write to instead of stdout
+    #[arg(long, value_name = "DIR", help = "Write fennec.1 to DIR")]
+    out: Option<PathBuf>,
+}
+
+/// Print or write completions for the requested shell
+pub fn completions(args: &CompletionsArgs) -> Result<ExitCode> {
+    let mut command = Cli::command();
+    match &args.out {
+        Some(dir) => {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+            let path = clap_complete::generate_to(args.shell, &mut command, BIN_NAME, dir)
+                .with_context(|| format!("Failed to write completions to {}", dir.display()))?;
+            eprintln!("Wrote {}", path.display());
+        }
+        None => clap_complete::generate(args.shell, &mut command, BIN_NAME, &mut std::io::stdout()),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Print or write the man page
+pub fn man_page(args: &ManArgs) -> Result<ExitCode> {
+    let mut page = Vec::new();
+    clap_mangen::Man::new(Cli::command()).render(&mut page)?;
+    match &args.out {
+        Some(dir) => {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+            let path = dir.join(format!("{}.1", BIN_NAME));
+            std::fs::write(&path, page)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            eprintln!("Wrote {}", path.display());
+        }
+        None => std::io::Write::write_all(&mut std::io::stdout(), &page)?,
+    }
+    Ok(ExitCode::SUCCESS)
+}
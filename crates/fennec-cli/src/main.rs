@@ -1,23 +1,38 @@
+mod ask;
+mod doctor;
+mod exec;
+mod generate;
+mod trust;
+
 use anyhow::Result;
-use clap::Parser;
-use fennec_core::config::Config;
+use clap::{Parser, Subcommand};
+use fennec_commands::create_command_registry_with_config;
+use fennec_core::config::{ApprovalPolicy, Config};
+use fennec_core::config_layers::ConfigLoader;
 use fennec_orchestration::SessionManager;
 use fennec_security::audit::AuditLogger;
-use fennec_security::{create_sandbox_policy, ApprovalManager};
+use fennec_security::{create_sandbox_policy, ApprovalManager, SandboxPolicy};
 use fennec_telemetry::{LogFormat, LogLevel, TelemetryConfig, TelemetrySystem};
 use fennec_tui::app::App;
+use fennec_tui::StreamingViewConfig;
+use std::process::ExitCode;
+use std::sync::Arc;
 use tracing::{error, info, warn};
 
 #[derive(Parser)]
-#[command(author, version, about, long_about = None)]
+#[command(name = "fennec", author, version, about, long_about = None)]
 #[command(
     after_help = "SANDBOX LEVELS:\n  read-only         Only file reading, no writes or execution\n  workspace-write   Read/write within workspace, limited execution\n  danger-full-access Full system access (with approval)\n\nSECURITY:\n  Use --ask-for-approval to require explicit consent for potentially dangerous operations.\n  The --cd flag validates and restricts operations to the specified working directory."
 )]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Working directory to operate in
     #[arg(
         short = 'C',
         long = "cd",
+        global = true,
         help = "Set working directory and validate it exists"
     )]
     working_dir: Option<std::path::PathBuf>,
@@ -26,6 +41,7 @@ struct Cli {
     #[arg(
         long,
         value_enum,
+        global = true,
         default_value = "workspace-write",
         help = "Set sandbox security level"
     )]
@@ -47,9 +63,19 @@ struct Cli {
     auto_approve_low_risk: bool,
 
     /// Configuration file path
-    #[arg(long, help = "Path to configuration file")]
+    #[arg(long, global = true, help = "Path to configuration file")]
     config: Option<std::path::PathBuf>,
 
+    /// Configuration overrides, taking precedence over files and environment
+    #[arg(
+        long = "set",
+        global = true,
+        value_name = "KEY=VALUE",
+        value_parser = parse_config_override,
+        help = "Override a configuration value, e.g. --set provider.timeout_seconds=60"
+    )]
+    overrides: Vec<(String, String)>,
+
     /// Enable verbose logging (deprecated - use --log-level debug instead)
     #[arg(short, long, help = "Enable verbose logging (deprecated)")]
     verbose: bool,
@@ -103,6 +129,31 @@ struct Cli {
     telemetry_config: Option<std::path::PathBuf>,
 }
 
+/// Subcommands; without one Fennec starts the TUI
+#[derive(Subcommand)]
+enum Command {
+    /// Run one registry command and print its result
+    Exec(exec::ExecArgs),
+    /// Send one prompt to the provider and stream the answer
+    Ask(ask::AskArgs),
+    /// Check the configuration and environment Fennec runs in
+    Doctor(doctor::DoctorArgs),
+    /// Manage the workspaces trusted with write access
+    Trust(trust::TrustArgs),
+    /// Print shell completions
+    Completions(generate::CompletionsArgs),
+    /// Print the man page
+    Man(generate::ManArgs),
+}
+
+fn parse_config_override(setting: &str) -> Result<(String, String), String> {
+    setting
+        .split_once('=')
+        .map(|(key, value)| (key.trim().to_string(), value.to_string()))
+        .filter(|(key, _)| !key.is_empty())
+        .ok_or_else(|| format!("expected KEY=VALUE, got '{}'", setting))
+}
+
 #[derive(clap::ValueEnum, Clone, Debug)]
 enum SandboxMode {
     #[value(name = "read-only", help = "Only file reading, no writes or execution")]
@@ -220,25 +271,8 @@ async fn create_telemetry_config(cli: &Cli) -> Result<TelemetryConfig> {
     Ok(config)
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // Load environment variables before parsing configuration
-    dotenvy::dotenv().ok();
-
-    let cli = Cli::parse();
-
-    // Initialize telemetry system
-    let telemetry_config = create_telemetry_config(&cli).await?;
-    let _telemetry_guard = TelemetrySystem::init(telemetry_config).await.map_err(|e| {
-        eprintln!("Failed to initialize telemetry system: {}", e);
-        anyhow::anyhow!("Telemetry initialization failed: {}", e)
-    })?;
-
-    info!("Starting Fennec AI Assistant");
-    info!("Sandbox level: {:?}", cli.sandbox);
-    info!("Approval required: {}", cli.ask_for_approval);
-
-    // Validate and set working directory if specified
+/// Validate and enter the working directory, if one was given
+fn enter_working_dir(cli: &Cli) -> Result<()> {
     if let Some(working_dir) = &cli.working_dir {
         if !working_dir.exists() {
             error!(
@@ -275,10 +309,42 @@ async fn main() -> Result<()> {
 
         info!("Changed working directory to: {}", canonical_dir.display());
     }
+    Ok(())
+}
+
+/// Loader layering the workspace config and --set flags over the global
+/// config
+fn config_loader(cli: &Cli) -> Result<ConfigLoader> {
+    let mut config_loader = ConfigLoader::new().with_workspace(std::env::current_dir()?);
+    if let Some(config_path) = &cli.config {
+        config_loader = config_loader.with_global_file(config_path);
+    }
+    for (key, value) in &cli.overrides {
+        config_loader = config_loader.with_override(key, value);
+    }
+    Ok(config_loader)
+}
+
+/// Directory holding the key bindings, themes and trust store: next to
+/// the config file when one was given
+fn config_dir(cli: &Cli) -> Result<std::path::PathBuf> {
+    match cli.config.as_deref().and_then(|path| path.parent()) {
+        Some(dir) => Ok(dir.to_path_buf()),
+        None => Ok(Config::default_config_dir()?),
+    }
+}
+
+/// Enter the working directory, then create the sandbox policy and load
+/// the configuration for it
+async fn prepare(cli: &Cli) -> Result<(SandboxPolicy, Config)> {
+    enter_working_dir(cli)?;
+
+    // Write access needs a trusted workspace
+    let sandbox_level = trust::sandbox_level(&config_dir(cli)?, cli.sandbox.clone().into())?;
 
     // Create sandbox policy
     let sandbox_policy = create_sandbox_policy(
-        cli.sandbox.clone().into(),
+        sandbox_level,
         cli.working_dir.as_deref(),
         cli.ask_for_approval,
     )
@@ -294,18 +360,84 @@ async fn main() -> Result<()> {
         sandbox_policy.requires_approval()
     );
 
-    // Create approval manager
-    let approval_manager = ApprovalManager::new(
-        cli.auto_approve_low_risk,
-        true, // Always use interactive mode for CLI
-    );
-
     // Load configuration
-    let config = Config::load(cli.config.as_deref()).await.map_err(|e| {
+    let config = config_loader(cli)?.load().await.map_err(|e| {
         error!("Failed to load configuration: {}", e);
         anyhow::anyhow!("Failed to load configuration: {}", e)
     })?;
 
+    Ok((sandbox_policy, config))
+}
+
+#[tokio::main]
+async fn main() -> Result<ExitCode> {
+    // Load environment variables before parsing configuration
+    dotenvy::dotenv().ok();
+
+    let cli = Cli::parse();
+
+    // Completions and the man page are generated without any setup
+    match &cli.command {
+        Some(Command::Completions(args)) => generate::completions(args),
+        Some(Command::Man(args)) => generate::man_page(args),
+        _ => run(&cli).await,
+    }
+}
+
+/// Set up telemetry, then run the subcommand, or the TUI without one
+async fn run(cli: &Cli) -> Result<ExitCode> {
+    // Initialize telemetry system
+    let telemetry_config = create_telemetry_config(cli).await?;
+    let _telemetry_guard = TelemetrySystem::init(telemetry_config.clone())
+        .await
+        .map_err(|e| {
+            eprintln!("Failed to initialize telemetry system: {}", e);
+            anyhow::anyhow!("Telemetry initialization failed: {}", e)
+        })?;
+
+    info!("Starting Fennec AI Assistant");
+    info!("Sandbox level: {:?}", cli.sandbox);
+    info!("Approval required: {}", cli.ask_for_approval);
+
+    match &cli.command {
+        // The doctor reports a configuration that fails to load instead
+        // of stopping at it
+        Some(Command::Doctor(args)) => {
+            enter_working_dir(cli)?;
+            doctor::run(args, config_loader(cli)?, &telemetry_config).await
+        }
+        Some(Command::Ask(args)) => match prepare(cli).await {
+            Ok((sandbox_policy, config)) => ask::run(args, &config, &sandbox_policy).await,
+            Err(e) => Ok(ask::config_error(&e)),
+        },
+        Some(Command::Exec(args)) => {
+            let (sandbox_policy, config) = prepare(cli).await?;
+            exec::run(args, &config, &sandbox_policy).await
+        }
+        Some(Command::Trust(args)) => {
+            enter_working_dir(cli)?;
+            trust::run(args, &config_dir(cli)?)
+        }
+        Some(Command::Completions(_) | Command::Man(_)) => {
+            unreachable!("generated before telemetry is set up")
+        }
+        None => {
+            let (sandbox_policy, config) = prepare(cli).await?;
+            run_tui(cli, sandbox_policy, config).await
+        }
+    }
+}
+
+/// Run the TUI until the user quits
+async fn run_tui(cli: &Cli, sandbox_policy: SandboxPolicy, config: Config) -> Result<ExitCode> {
+    // Create approval manager, always interactive for the CLI; the flag
+    // overrides the configured policy for low risk operations
+    let mut approval_policies = config.security.approval_policies.clone();
+    if cli.auto_approve_low_risk {
+        approval_policies.low = ApprovalPolicy::AutoApprove;
+    }
+    let approval_manager = ApprovalManager::default().with_policies(approval_policies);
+
     // Initialize audit logger
     let audit_logger = AuditLogger::new(&config).await.map_err(|e| {
         error!("Failed to initialize audit logger: {}", e);
@@ -320,8 +452,17 @@ async fn main() -> Result<()> {
             anyhow::anyhow!("Failed to initialize session manager: {}", e)
         })?;
 
+    // Pick up the sessions open when Fennec last stopped
+    if config.session_checkpoint.enabled {
+        match session_manager.resume().await {
+            Ok(0) => {}
+            Ok(count) => info!("Resumed {} sessions", count),
+            Err(e) => warn!("Failed to resume sessions: {}", e),
+        }
+    }
+
     // Display security warning for dangerous sandbox levels
-    if matches!(cli.sandbox, SandboxMode::DangerFullAccess) {
+    if sandbox_policy.level() == &fennec_security::SandboxLevel::FullAccess {
         warn!("🔴 WARNING: Running in DANGER-FULL-ACCESS mode!");
         warn!("This mode allows potentially dangerous operations.");
         if !cli.ask_for_approval {
@@ -337,10 +478,31 @@ async fn main() -> Result<()> {
             anyhow::anyhow!("Failed to initialize application: {}", e)
         })?;
 
+    // Key bindings and themes live next to the config file
+    if let Ok(config_dir) = config_dir(cli) {
+        app.load_keybindings(&config_dir);
+        app.load_themes(&config_dir, &config.tui.theme);
+    }
+
+    app.set_streaming_config(StreamingViewConfig::from(&config.tui));
+    app.set_mouse_capture(config.tui.mouse_capture)?;
+
+    // Track each open session in memory
+    match fennec_memory::create_memory_service().await {
+        Ok(memory) => app.set_memory_service(Arc::new(memory)).await,
+        Err(e) => warn!("Sessions will not be tracked in memory: {}", e),
+    }
+
+    // Offer registry commands in the command palette
+    match create_command_registry_with_config(&config).await {
+        Ok(registry) => app.set_command_registry(Arc::new(registry)).await,
+        Err(e) => warn!("Command palette will list no commands: {}", e),
+    }
+
     match app.run().await {
         Ok(_) => {
             info!("Fennec exited successfully");
-            Ok(())
+            Ok(ExitCode::SUCCESS)
         }
         Err(e) => {
             error!("Fennec encountered an error: {}", e);
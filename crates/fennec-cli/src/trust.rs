@@ -0,0 +1,112 @@
+//! Workspace trust: write access is only granted in workspaces the user
+//! has trusted, and `fennec trust` manages the list.
+
+use anyhow::{Context, Result};
+use fennec_security::{SandboxLevel, TrustDecision, TrustStore};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(clap::Args, Debug)]
+pub struct TrustArgs {
+    #[command(subcommand)]
+    action: TrustAction,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum TrustAction {
+    /// Trust a workspace, and everything below it, with write access
+    Add {
+        /// Workspace to trust; the working directory if omitted
+        path: Option<PathBuf>,
+    },
+    /// Forget the decision recorded for a workspace
+    Remove {
+        /// Workspace to forget; the working directory if omitted
+        path: Option<PathBuf>,
+    },
+    /// List the recorded decisions
+    List,
+}
+
+/// Run a `fennec trust` action against the store in `config_dir`
+pub fn run(args: &TrustArgs, config_dir: &Path) -> Result<ExitCode> {
+    let mut store = TrustStore::load(config_dir)?;
+    match &args.action {
+        TrustAction::Add { path } => {
+            let path = store.record(&workspace(path.as_deref())?, TrustDecision::Trusted);
+            store.save()?;
+            println!("Trusted {}", path.display());
+        }
+        TrustAction::Remove { path } => {
+            let path = match path {
+                Some(path) => path.clone(),
+                None => std::env::current_dir()?,
+            };
+            if !store.remove(&path) {
+                eprintln!("No decision recorded for {}", path.display());
+                return Ok(ExitCode::FAILURE);
+            }
+            store.save()?;
+            println!("Removed {}", path.display());
+        }
+        TrustAction::List => {
+            if store.entries().is_empty() {
+                println!("No workspaces recorded");
+            }
+            for entry in store.entries() {
+                println!(
+                    "{:<9}  {}  {}",
+                    entry.decision,
+                    entry.decided_at.format("%Y-%m-%d %H:%M"),
+                    entry.path.display()
+                );
+            }
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// The sandbox level to run the working directory at, asking whether to
+/// trust it when it has never been decided on
+pub fn sandbox_level(config_dir: &Path, requested: SandboxLevel) -> Result<SandboxLevel> {
+    let workspace = std::env::current_dir()?;
+    let mut store = TrustStore::load(config_dir)?;
+    let level = store.sandbox_level(&workspace, requested.clone(), prompt)?;
+    if level != requested {
+        eprintln!(
+            "{} is not a trusted workspace, running {}. Use `fennec trust add` to allow writes.",
+            workspace.display(),
+            level
+        );
+    }
+    Ok(level)
+}
+
+/// Ask on the terminal whether to trust `workspace`; `None` without one
+fn prompt(workspace: &Path) -> Option<bool> {
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return None;
+    }
+
+    eprintln!(
+        "Fennec has not been used in {} before.",
+        workspace.display()
+    );
+    eprint!("Trust it and allow Fennec to change files there? [y/N]: ");
+    io::stderr().flush().ok()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer).ok()?;
+    Some(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// The existing directory `path` names, the working directory by default
+fn workspace(path: Option<&Path>) -> Result<PathBuf> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => std::env::current_dir()?,
+    };
+    path.canonicalize()
+        .with_context(|| format!("No workspace at {}", path.display()))
+}
@@ -0,0 +1,146 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use tempfile::TempDir;
+
+/// Run `fennec ask` in `workspace` against the mock provider, keeping its
+/// config, data and logs in `home` and piping `stdin` to it
+fn ask(home: &Path, workspace: &Path, args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_fennec"))
+        .current_dir(workspace)
+        .env("HOME", home)
+        .env("XDG_CONFIG_HOME", home.join(".config"))
+        .env("XDG_DATA_HOME", home.join(".local/share"))
+        .env("FENNEC_PROVIDER", "mock")
+        .env_remove("RUST_LOG")
+        .arg("--no-file-logging")
+        .arg("ask")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run fennec");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+/// Transcripts written under `home`, as their raw contents; the tag index
+/// kept beside them is skipped
+fn transcripts(home: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(home.join(".local/share/fennec/transcripts")) else {
+        return Vec::new();
+    };
+    entries
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_file() && !path.ends_with("tag_index.json"))
+        .map(|path| std::fs::read_to_string(path).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_ask_streams_the_answer_with_context() {
+    let home = TempDir::new().unwrap();
+    let workspace = TempDir::new().unwrap();
+    std::fs::write(workspace.path().join("lib.rs"), "fn parse_header() {}\n").unwrap();
+
+    let output = ask(
+        home.path(),
+        workspace.path(),
+        &[
+            "why does this test fail?",
+            "--context-file",
+            "lib.rs",
+            "--model",
+            "gpt-4o-mini",
+            "--max-tokens",
+            "64",
+        ],
+        "assertion failed: left == right\n",
+    );
+    assert!(output.status.success(), "{:?}", output);
+    let answer = stdout(&output);
+    // The mock provider echoes the prompt it was sent
+    assert!(answer.contains("why does this test fail?"), "{}", answer);
+    assert!(answer.contains("fn parse_header() {}"), "{}", answer);
+    assert!(answer.contains("assertion failed"), "{}", answer);
+
+    let transcripts = transcripts(home.path());
+    assert_eq!(transcripts.len(), 1);
+    assert!(transcripts[0].contains("why does this test fail?"));
+    assert!(transcripts[0].contains("offline mode"));
+}
+
+#[test]
+fn test_ask_reads_the_prompt_from_stdin_and_can_skip_memory() {
+    let home = TempDir::new().unwrap();
+    let workspace = TempDir::new().unwrap();
+
+    let output = ask(
+        home.path(),
+        workspace.path(),
+        &["--no-memory"],
+        "summarize the build steps\n",
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout(&output).contains("summarize the build steps"));
+    assert!(transcripts(home.path()).is_empty());
+
+    let output = ask(home.path(), workspace.path(), &[], "");
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No prompt given"));
+}
+
+#[test]
+fn test_ask_exit_codes_tell_config_from_provider_errors() {
+    let home = TempDir::new().unwrap();
+    let workspace = TempDir::new().unwrap();
+    let outside = TempDir::new().unwrap();
+    std::fs::write(outside.path().join("secret.txt"), "secret").unwrap();
+    let secret = outside.path().join("secret.txt");
+
+    // Reading outside the workspace is a sandbox error
+    let output = ask(
+        home.path(),
+        workspace.path(),
+        &["hi", "--context-file", secret.to_str().unwrap()],
+        "",
+    );
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    assert!(stdout(&output).is_empty());
+
+    let output = ask(
+        home.path(),
+        workspace.path(),
+        &["hi", "--set", "provider.base_url=ftp://localhost"],
+        "",
+    );
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+
+    // Nothing listens on the discard port
+    let output = ask(
+        home.path(),
+        workspace.path(),
+        &[
+            "hi",
+            "--set",
+            "provider.provider=openai",
+            "--set",
+            "provider.openai_api_key=sk-test",
+            "--set",
+            "provider.base_url=http://127.0.0.1:9",
+        ],
+        "",
+    );
+    assert_eq!(output.status.code(), Some(3), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Provider error"));
+}
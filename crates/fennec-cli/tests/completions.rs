@@ -0,0 +1,63 @@
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+fn fennec(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_fennec"))
+        .env_remove("RUST_LOG")
+        .args(args)
+        .output()
+        .expect("failed to run fennec")
+}
+
+#[test]
+fn test_completions_cover_subcommands_and_flags() {
+    for shell in ["bash", "zsh", "fish", "powershell"] {
+        let output = fennec(&["completions", shell]);
+        assert!(output.status.success(), "{}: {:?}", shell, output);
+        let script = String::from_utf8_lossy(&output.stdout);
+        // Fish lists long flags without their dashes
+        for word in [
+            "fennec",
+            "exec",
+            "ask",
+            "doctor",
+            "completions",
+            "sandbox",
+            "context-file",
+            "auto-approve-low-risk",
+            "online",
+        ] {
+            assert!(script.contains(word), "{} completions lack {}", shell, word);
+        }
+    }
+}
+
+#[test]
+fn test_completions_and_man_page_can_be_written_to_a_directory() {
+    let out = TempDir::new().unwrap();
+    let dir = out.path().join("share");
+
+    let output = fennec(&["completions", "bash", "--out", dir.to_str().unwrap()]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(output.stdout.is_empty());
+    let script = std::fs::read_to_string(dir.join("fennec.bash")).unwrap();
+    assert!(script.contains("--max-tokens"));
+
+    let output = fennec(&["man", "--out", dir.to_str().unwrap()]);
+    assert!(output.status.success(), "{:?}", output);
+    let page = std::fs::read_to_string(dir.join("fennec.1")).unwrap();
+    assert!(page.starts_with(".ie"), "{}", page);
+    assert!(page.contains(".TH fennec 1"), "{}", page);
+    assert!(page.contains("doctor"));
+}
+
+#[test]
+fn test_man_page_goes_to_stdout() {
+    let output = fennec(&["man"]);
+    assert!(output.status.success(), "{:?}", output);
+    let page = String::from_utf8_lossy(&output.stdout);
+    assert!(page.contains(".TH fennec 1"));
+    assert!(page.contains("\\-\\-sandbox"), "{}", page);
+    assert!(page.contains("exec"));
+    assert!(page.contains("ask"));
+}
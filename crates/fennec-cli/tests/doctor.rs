@@ -0,0 +1,141 @@
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+/// Variables that would let the provider check pass without the test
+/// asking for it
+const PROVIDER_VARS: &[&str] = &[
+    "FENNEC_PROVIDER",
+    "OPENAI_API_KEY",
+    "OPENAI_BASE_URL",
+    "ANTHROPIC_API_KEY",
+    "ANTHROPIC_BASE_URL",
+    "OPENROUTER_API_KEY",
+    "FENNEC_DEFAULT_MODEL",
+];
+
+/// Run `fennec doctor` in `workspace`, keeping its config, data and logs in
+/// `home`
+fn doctor(home: &Path, workspace: &Path, env: &[(&str, &str)], args: &[&str]) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_fennec"));
+    command
+        .current_dir(workspace)
+        .env("HOME", home)
+        .env("XDG_CONFIG_HOME", home.join(".config"))
+        .env("XDG_DATA_HOME", home.join(".local/share"))
+        .env_remove("RUST_LOG");
+    for var in PROVIDER_VARS {
+        command.env_remove(var);
+    }
+    command
+        .envs(env.iter().copied())
+        .arg("--no-file-logging")
+        .arg("doctor")
+        .args(args)
+        .output()
+        .expect("failed to run fennec")
+}
+
+/// The table row of check `name`
+fn row(output: &Output, name: &str) -> String {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .lines()
+        .find(|line| {
+            line.split_whitespace()
+                .skip(1)
+                .collect::<Vec<_>>()
+                .join(" ")
+                .starts_with(name)
+        })
+        .unwrap_or_else(|| panic!("no {} row in\n{}", name, stdout))
+        .to_string()
+}
+
+#[test]
+fn test_doctor_passes_a_working_setup() {
+    let home = TempDir::new().unwrap();
+    let workspace = TempDir::new().unwrap();
+    std::fs::write(
+        workspace.path().join("AGENTS.md"),
+        "# Guidelines\n\n## Testing\n- `cargo test`\n",
+    )
+    .unwrap();
+
+    let output = doctor(
+        home.path(),
+        workspace.path(),
+        &[("OPENAI_API_KEY", "sk-test")],
+        &[],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert!(row(&output, "config").starts_with("pass"));
+    let provider = row(&output, "provider");
+    assert!(provider.starts_with("pass"), "{}", provider);
+    assert!(provider.contains("OPENAI_API_KEY"), "{}", provider);
+    assert!(!provider.contains("sk-test"));
+    assert!(row(&output, "storage").starts_with("pass"));
+    assert!(row(&output, "disk space").contains("MiB free"));
+    assert!(row(&output, "AGENTS.md").starts_with("pass"));
+    // Output is captured, so there is no terminal to draw on
+    assert!(row(&output, "terminal").starts_with("warn"));
+    assert!(row(&output, "log directory").starts_with("pass"));
+}
+
+#[test]
+fn test_doctor_fails_on_broken_config_and_missing_api_key() {
+    let home = TempDir::new().unwrap();
+    let workspace = TempDir::new().unwrap();
+    std::fs::create_dir(workspace.path().join(".fennec")).unwrap();
+    std::fs::write(
+        workspace.path().join(".fennec/config.toml"),
+        "[provider\ndefault_model = \"gpt-4\"\n",
+    )
+    .unwrap();
+
+    let output = doctor(home.path(), workspace.path(), &[], &[]);
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    let config = row(&output, "config");
+    assert!(config.starts_with("FAIL"), "{}", config);
+    assert!(config.contains("config.toml"), "{}", config);
+    let provider = row(&output, "provider");
+    assert!(provider.starts_with("FAIL"), "{}", provider);
+    assert!(provider.contains("No OpenAI API key"), "{}", provider);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("hint: Set OPENAI_API_KEY"), "{}", stdout);
+    assert!(stdout.contains("2 failed"), "{}", stdout);
+
+    // The online check has nothing to reach without a key
+    std::fs::remove_dir_all(workspace.path().join(".fennec")).unwrap();
+    let output = doctor(home.path(), workspace.path(), &[], &["--online"]);
+    assert!(row(&output, "config").starts_with("pass"));
+    assert!(row(&output, "model (online)").starts_with("warn"));
+    assert!(row(&output, "provider (online)").starts_with("warn"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_doctor_fails_on_read_only_storage() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let home = TempDir::new().unwrap();
+    let workspace = TempDir::new().unwrap();
+    let storage = workspace.path().join("storage");
+    std::fs::create_dir(&storage).unwrap();
+    std::fs::set_permissions(&storage, std::fs::Permissions::from_mode(0o555)).unwrap();
+
+    let output = doctor(
+        home.path(),
+        workspace.path(),
+        &[("OPENAI_API_KEY", "sk-test")],
+        &[
+            "--set",
+            &format!("memory.storage_path={}", storage.display()),
+        ],
+    );
+    std::fs::set_permissions(&storage, std::fs::Permissions::from_mode(0o755)).unwrap();
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    let row = row(&output, "storage");
+    assert!(row.starts_with("FAIL"), "{}", row);
+    assert!(row.contains("read-only"), "{}", row);
+}
@@ -0,0 +1,145 @@
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+/// Run `fennec` in `workspace`, keeping its config, data and logs in `home`
+fn fennec(home: &Path, workspace: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_fennec"))
+        .current_dir(workspace)
+        .env("HOME", home)
+        .env("XDG_CONFIG_HOME", home.join(".config"))
+        .env("XDG_DATA_HOME", home.join(".local/share"))
+        .env_remove("RUST_LOG")
+        .arg("--no-file-logging")
+        .args(args)
+        .output()
+        .expect("failed to run fennec")
+}
+
+fn json(output: &Output) -> serde_json::Value {
+    serde_json::from_slice(&output.stdout).unwrap_or_else(|e| {
+        panic!(
+            "{}: {}\n{}",
+            e,
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        )
+    })
+}
+
+#[test]
+fn test_exec_runs_a_command_without_the_tui() {
+    let home = TempDir::new().unwrap();
+    let workspace = TempDir::new().unwrap();
+    std::fs::write(
+        workspace.path().join("lib.rs"),
+        "fn main() {}\n// TODO: handle errors\n",
+    )
+    .unwrap();
+
+    let output = fennec(
+        home.path(),
+        workspace.path(),
+        &[
+            "exec",
+            "--command",
+            "search",
+            "--args",
+            r#"{"query":"TODO"}"#,
+        ],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    let result = json(&output);
+    assert_eq!(result["command_name"], "search");
+    assert_eq!(result["success"], true);
+    assert!(
+        result["output"]
+            .as_str()
+            .unwrap()
+            .contains("TODO: handle errors"),
+        "{}",
+        result
+    );
+
+    // Plain text prints the command's own output
+    let output = fennec(
+        home.path(),
+        workspace.path(),
+        &[
+            "exec",
+            "--command",
+            "search",
+            "--args",
+            r#"{"query":"TODO"}"#,
+            "--format",
+            "text",
+        ],
+    );
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("TODO: handle errors"));
+
+    // Every run is audited in the workspace
+    let audit = std::fs::read_to_string(workspace.path().join(".fennec/audit.jsonl")).unwrap();
+    assert!(audit.contains("command_completed"), "{}", audit);
+}
+
+#[test]
+fn test_exec_denies_what_would_need_approval() {
+    let home = TempDir::new().unwrap();
+    let workspace = TempDir::new().unwrap();
+    let args = r#"{"path":"notes.txt","content":"hello"}"#;
+    let output = fennec(home.path(), workspace.path(), &["trust", "add"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let output = fennec(
+        home.path(),
+        workspace.path(),
+        &["exec", "--command", "create", "--args", args],
+    );
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    let result = json(&output);
+    assert_eq!(result["success"], false);
+    assert!(result["error"].as_str().unwrap().contains("approval"));
+    assert!(!workspace.path().join("notes.txt").exists());
+
+    // Creating an empty file is low risk
+    let output = fennec(
+        home.path(),
+        workspace.path(),
+        &[
+            "exec",
+            "--command",
+            "create",
+            "--args",
+            args,
+            "--auto-approve-low-risk",
+        ],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        std::fs::read_to_string(workspace.path().join("notes.txt")).unwrap(),
+        "hello"
+    );
+}
+
+#[test]
+fn test_exec_fails_on_unknown_command_and_bad_arguments() {
+    let home = TempDir::new().unwrap();
+    let workspace = TempDir::new().unwrap();
+
+    let output = fennec(
+        home.path(),
+        workspace.path(),
+        &["exec", "--command", "no-such-command"],
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no-such-command"));
+
+    let output = fennec(
+        home.path(),
+        workspace.path(),
+        &["exec", "--command", "search", "--args", "{not json"],
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("invalid JSON"));
+}
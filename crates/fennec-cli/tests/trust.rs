@@ -0,0 +1,131 @@
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+/// Run `fennec` in `workspace`, keeping its config, data and logs in `home`
+fn fennec(home: &Path, workspace: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_fennec"))
+        .current_dir(workspace)
+        .env("HOME", home)
+        .env("XDG_CONFIG_HOME", home.join(".config"))
+        .env("XDG_DATA_HOME", home.join(".local/share"))
+        .env_remove("RUST_LOG")
+        .arg("--no-file-logging")
+        .args(args)
+        .output()
+        .expect("failed to run fennec")
+}
+
+/// Create notes.txt through `fennec exec`, approving low risk operations
+fn create_notes(home: &Path, workspace: &Path) -> Output {
+    fennec(
+        home,
+        workspace,
+        &[
+            "exec",
+            "--command",
+            "create",
+            "--args",
+            r#"{"path":"notes.txt","content":"hello"}"#,
+            "--auto-approve-low-risk",
+        ],
+    )
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn test_untrusted_workspace_runs_read_only() {
+    let home = TempDir::new().unwrap();
+    let workspace = TempDir::new().unwrap();
+
+    // Without a terminal to ask on, the first run is downgraded
+    let output = create_notes(home.path(), workspace.path());
+    assert!(!output.status.success(), "{:?}", output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("not a trusted workspace"), "{}", stderr);
+    assert!(!workspace.path().join("notes.txt").exists());
+
+    // Nothing was recorded, so the next run is downgraded again
+    let output = fennec(home.path(), workspace.path(), &["trust", "list"]);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("No workspaces recorded"));
+    assert!(!create_notes(home.path(), workspace.path()).status.success());
+
+    // Asking for read-only needs no trust
+    let output = fennec(
+        home.path(),
+        workspace.path(),
+        &[
+            "--sandbox",
+            "read-only",
+            "exec",
+            "--command",
+            "search",
+            "--args",
+            r#"{"query":"x"}"#,
+        ],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("not a trusted workspace"));
+}
+
+#[test]
+fn test_trusted_workspace_keeps_write_access() {
+    let home = TempDir::new().unwrap();
+    let workspace = TempDir::new().unwrap();
+    let canonical = workspace.path().canonicalize().unwrap();
+
+    let output = fennec(home.path(), workspace.path(), &["trust", "add"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout(&output).contains(&canonical.display().to_string()));
+
+    let output = fennec(home.path(), workspace.path(), &["trust", "list"]);
+    let list = stdout(&output);
+    assert!(list.starts_with("trusted"), "{}", list);
+    assert!(list.contains(&canonical.display().to_string()), "{}", list);
+
+    // Subdirectories share the workspace's trust
+    let nested = workspace.path().join("src");
+    std::fs::create_dir(&nested).unwrap();
+    let output = create_notes(home.path(), &nested);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        std::fs::read_to_string(nested.join("notes.txt")).unwrap(),
+        "hello"
+    );
+
+    // The store lives in the config directory
+    assert!(home
+        .path()
+        .join(".config/fennec/trusted_workspaces.json")
+        .exists());
+}
+
+#[test]
+fn test_revoked_workspace_loses_write_access() {
+    let home = TempDir::new().unwrap();
+    let workspace = TempDir::new().unwrap();
+    let path = workspace.path().to_str().unwrap();
+
+    assert!(fennec(home.path(), home.path(), &["trust", "add", path])
+        .status
+        .success());
+    let output = fennec(home.path(), home.path(), &["trust", "remove", path]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(
+        stdout(&fennec(home.path(), home.path(), &["trust", "list"]))
+            .contains("No workspaces recorded")
+    );
+
+    let output = create_notes(home.path(), workspace.path());
+    assert!(!output.status.success(), "{:?}", output);
+    assert!(!workspace.path().join("notes.txt").exists());
+
+    // Removing it again has nothing to remove
+    let output = fennec(home.path(), home.path(), &["trust", "remove", path]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No decision recorded"));
+}
@@ -1,8 +1,12 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use directories::ProjectDirs;
+use fennec_core::error::FennecError;
+use fennec_security::ApprovalContextProvider;
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
@@ -10,6 +14,15 @@ use uuid::Uuid;
 /// Maximum number of actions to keep in history
 const MAX_HISTORY_SIZE: usize = 100;
 
+/// Path reported for an empty transaction
+static EMPTY_PATH: PathBuf = PathBuf::new();
+
+/// Default cap on the uncompressed size of a directory snapshot (64 MB)
+const DEFAULT_MAX_SNAPSHOT_SIZE: u64 = 64 * 1024 * 1024;
+
+/// How far back edits count as recent in approval prompts
+const RECENT_EDIT_MINUTES: i64 = 60;
+
 /// Represents the state of a file system entity before or after an action
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ActionState {
@@ -27,11 +40,16 @@ pub enum ActionState {
     FileMoved { from: PathBuf, to: PathBuf },
     /// Directory was created
     DirectoryCreated { path: PathBuf },
-    /// Directory was deleted (with contents)
+    /// Directory was deleted; `snapshot` points at a tar archive of the
+    /// removed tree when the contents can be restored
     DirectoryDeleted {
         path: PathBuf,
-        contents: Vec<(PathBuf, Vec<u8>)>,
+        snapshot: Option<PathBuf>,
     },
+    /// Directory was moved/renamed
+    DirectoryMoved { from: PathBuf, to: PathBuf },
+    /// Several changes applied together; undone and redone as one unit
+    Transaction { states: Vec<ActionState> },
 }
 
 impl ActionState {
@@ -44,6 +62,36 @@ impl ActionState {
             ActionState::FileMoved { to, .. } => to,
             ActionState::DirectoryCreated { path } => path,
             ActionState::DirectoryDeleted { path, .. } => path,
+            ActionState::DirectoryMoved { to, .. } => to,
+            ActionState::Transaction { states } => {
+                states.first().map(ActionState::path).unwrap_or(&EMPTY_PATH)
+            }
+        }
+    }
+
+    /// Every path touched by this state, including both ends of a move and
+    /// each member of a transaction
+    pub fn affected_paths(&self) -> Vec<&PathBuf> {
+        match self {
+            ActionState::FileMoved { from, to } | ActionState::DirectoryMoved { from, to } => {
+                vec![from, to]
+            }
+            ActionState::Transaction { states } => states
+                .iter()
+                .flat_map(ActionState::affected_paths)
+                .collect(),
+            _ => vec![self.path()],
+        }
+    }
+
+    /// Get the directory snapshot archive referenced by this state, if any
+    pub fn snapshot(&self) -> Option<&PathBuf> {
+        match self {
+            ActionState::DirectoryDeleted {
+                snapshot: Some(snapshot),
+                ..
+            } => Some(snapshot),
+            _ => None,
         }
     }
 }
@@ -146,6 +194,185 @@ impl Action {
             description,
         )
     }
+
+    /// Create an action for directory creation
+    pub fn directory_created(command: String, path: PathBuf, description: String) -> Self {
+        Self::new(
+            command,
+            ActionState::DirectoryDeleted {
+                path: path.clone(),
+                snapshot: None,
+            },
+            ActionState::DirectoryCreated { path },
+            description,
+        )
+    }
+
+    /// Create an action for directory deletion
+    ///
+    /// `snapshot` should come from [`ActionLog::snapshot_directory`]; without it
+    /// the deletion is recorded but cannot be undone.
+    pub fn directory_deleted(
+        command: String,
+        path: PathBuf,
+        snapshot: Option<PathBuf>,
+        description: String,
+    ) -> Self {
+        let mut action = Self::new(
+            command,
+            ActionState::DirectoryCreated { path: path.clone() },
+            ActionState::DirectoryDeleted {
+                path,
+                snapshot: snapshot.clone(),
+            },
+            description,
+        );
+        action.reversible = snapshot.is_some();
+        action
+    }
+
+    /// Create an action for directory move/rename
+    pub fn directory_moved(
+        command: String,
+        from: PathBuf,
+        to: PathBuf,
+        description: String,
+    ) -> Self {
+        Self::new(
+            command,
+            ActionState::DirectoryMoved {
+                from: to.clone(),
+                to: from.clone(),
+            },
+            ActionState::DirectoryMoved { from, to },
+            description,
+        )
+    }
+
+    /// Create an action for an entry moved into the workspace trash; undoing
+    /// it moves the entry back out
+    pub fn moved_to_trash(
+        command: String,
+        path: PathBuf,
+        trash_path: PathBuf,
+        is_directory: bool,
+        description: String,
+    ) -> Self {
+        if is_directory {
+            Self::directory_moved(command, path, trash_path, description)
+        } else {
+            Self::file_moved(command, path, trash_path, description)
+        }
+    }
+
+    /// Combine several actions into one that is undone and redone as a unit
+    pub fn transaction(command: String, actions: Vec<Action>, description: String) -> Self {
+        let reversible = actions.iter().all(|action| action.reversible);
+        let (before, after) = actions
+            .into_iter()
+            .map(|action| (action.state_before, action.state_after))
+            .unzip();
+
+        let mut action = Self::new(
+            command,
+            ActionState::Transaction { states: before },
+            ActionState::Transaction { states: after },
+            description,
+        );
+        action.reversible = reversible;
+        action
+    }
+
+    /// Paired before/after states, one pair per change the action made
+    pub fn state_pairs(&self) -> Vec<(&ActionState, &ActionState)> {
+        match (&self.state_before, &self.state_after) {
+            (
+                ActionState::Transaction { states: before },
+                ActionState::Transaction { states: after },
+            ) => before.iter().zip(after).collect(),
+            (before, after) => vec![(before, after)],
+        }
+    }
+
+    /// Every path touched by this action
+    pub fn affected_paths(&self) -> Vec<&PathBuf> {
+        self.state_after.affected_paths()
+    }
+
+    /// Split this action into the changes touching any of `files` and the
+    /// rest; relative paths on either side are resolved against `workspace`
+    ///
+    /// Both halves keep the action's id so a partially undone action can
+    /// still be addressed afterwards.
+    pub fn split_by_paths(
+        &self,
+        files: &[PathBuf],
+        workspace: &Path,
+    ) -> (Option<Action>, Option<Action>) {
+        let files: Vec<PathBuf> = files.iter().map(|f| resolve(f, workspace)).collect();
+        let (matched, rest): (Vec<_>, Vec<_>) =
+            self.state_pairs().into_iter().partition(|(_, after)| {
+                after.affected_paths().iter().any(|path| {
+                    let path = resolve(path, workspace);
+                    files.iter().any(|file| paths_overlap(&path, file))
+                })
+            });
+
+        if rest.is_empty() {
+            return (Some(self.clone()), None);
+        }
+        if matched.is_empty() {
+            return (None, Some(self.clone()));
+        }
+        (Some(self.with_pairs(matched)), Some(self.with_pairs(rest)))
+    }
+
+    /// Copy of this action reduced to `pairs`, recorded as a transaction
+    fn with_pairs(&self, pairs: Vec<(&ActionState, &ActionState)>) -> Action {
+        let (before, after) = pairs
+            .into_iter()
+            .map(|(before, after)| (before.clone(), after.clone()))
+            .unzip();
+
+        Action {
+            state_before: ActionState::Transaction { states: before },
+            state_after: ActionState::Transaction { states: after },
+            ..self.clone()
+        }
+    }
+
+    /// Snapshot archives referenced by this action
+    fn snapshots(&self) -> impl Iterator<Item = &PathBuf> {
+        self.state_before
+            .snapshot()
+            .into_iter()
+            .chain(self.state_after.snapshot())
+    }
+}
+
+/// A later action that touches paths overlapping an action being undone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionConflict {
+    pub action_id: Uuid,
+    pub description: String,
+    pub paths: Vec<PathBuf>,
+}
+
+impl std::fmt::Display for ActionConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let paths: Vec<String> = self
+            .paths
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        write!(
+            f,
+            "{} ({}): {}",
+            self.description,
+            self.action_id,
+            paths.join(", ")
+        )
+    }
 }
 
 /// Manages the action log with undo/redo capabilities
@@ -154,16 +381,14 @@ pub struct ActionLog {
     actions: Arc<RwLock<VecDeque<Action>>>,
     current_index: Arc<RwLock<usize>>,
     max_size: usize,
+    data_dir: Option<PathBuf>,
+    max_snapshot_size: u64,
 }
 
 impl ActionLog {
     /// Create a new action log
     pub fn new() -> Self {
-        Self {
-            actions: Arc::new(RwLock::new(VecDeque::new())),
-            current_index: Arc::new(RwLock::new(0)),
-            max_size: MAX_HISTORY_SIZE,
-        }
+        Self::with_max_size(MAX_HISTORY_SIZE)
     }
 
     /// Create a new action log with custom max size
@@ -172,23 +397,110 @@ impl ActionLog {
             actions: Arc::new(RwLock::new(VecDeque::new())),
             current_index: Arc::new(RwLock::new(0)),
             max_size,
+            data_dir: None,
+            max_snapshot_size: DEFAULT_MAX_SNAPSHOT_SIZE,
         }
     }
 
+    /// Store directory snapshots under `data_dir` instead of the default
+    /// project data directory
+    pub fn with_data_dir(mut self, data_dir: PathBuf) -> Self {
+        self.data_dir = Some(data_dir);
+        self
+    }
+
+    /// Set the maximum total size in bytes of a directory snapshot
+    pub fn with_max_snapshot_size(mut self, max_snapshot_size: u64) -> Self {
+        self.max_snapshot_size = max_snapshot_size;
+        self
+    }
+
+    /// Get the maximum total size in bytes of a directory snapshot
+    pub fn max_snapshot_size(&self) -> u64 {
+        self.max_snapshot_size
+    }
+
+    /// Get the directory where the action log keeps its data
+    pub fn data_dir(&self) -> Result<PathBuf> {
+        if let Some(data_dir) = &self.data_dir {
+            return Ok(data_dir.clone());
+        }
+
+        let proj_dirs =
+            ProjectDirs::from("", "", "fennec").context("Failed to get project directories")?;
+
+        Ok(proj_dirs.data_dir().join("action_log"))
+    }
+
+    /// Archive a directory tree so its deletion can be undone later
+    ///
+    /// Fails without writing anything if the tree is larger than the
+    /// configured snapshot size cap.
+    pub async fn snapshot_directory(&self, path: &Path) -> Result<PathBuf> {
+        let snapshot_dir = self.data_dir()?.join("snapshots");
+        let snapshot_path = snapshot_dir.join(format!("{}.tar", Uuid::new_v4()));
+        let source = path.to_path_buf();
+        let max_size = self.max_snapshot_size;
+
+        let archive_path = snapshot_path.clone();
+        tokio::task::spawn_blocking(move || -> Result<()> {
+            let total_size = directory_size(&source)?;
+            if total_size > max_size {
+                return Err(FennecError::Command(Box::new(std::io::Error::other(format!(
+                    "Directory {} is too large to snapshot for undo ({} bytes, limit {} bytes)",
+                    source.display(),
+                    total_size,
+                    max_size
+                ))))
+                .into());
+            }
+
+            std::fs::create_dir_all(&snapshot_dir).with_context(|| {
+                format!(
+                    "Failed to create snapshot directory: {}",
+                    snapshot_dir.display()
+                )
+            })?;
+
+            let root_name = source
+                .file_name()
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from("."));
+            let file = std::fs::File::create(&archive_path).with_context(|| {
+                format!("Failed to create snapshot: {}", archive_path.display())
+            })?;
+            let mut builder = tar::Builder::new(file);
+            builder.follow_symlinks(false);
+            builder
+                .append_dir_all(&root_name, &source)
+                .and_then(|_| builder.finish())
+                .with_context(|| format!("Failed to archive directory: {}", source.display()))
+                .inspect_err(|_| {
+                    let _ = std::fs::remove_file(&archive_path);
+                })
+        })
+        .await??;
+
+        Ok(snapshot_path)
+    }
+
     /// Record a new action
     pub async fn record(&self, action: Action) {
         let mut actions = self.actions.write().await;
         let mut index = self.current_index.write().await;
 
         // Remove any actions after current index (they've been undone)
-        actions.truncate(*index);
+        let discarded: Vec<Action> = actions.drain(*index..).collect();
+        discard_snapshots(&discarded);
 
         // Add the new action
         actions.push_back(action);
 
         // Maintain max size
         if actions.len() > self.max_size {
-            actions.pop_front();
+            if let Some(evicted) = actions.pop_front() {
+                discard_snapshots(std::slice::from_ref(&evicted));
+            }
         } else {
             *index += 1;
         }
@@ -268,6 +580,7 @@ impl ActionLog {
     pub async fn clear(&self) {
         let mut actions = self.actions.write().await;
         let mut index = self.current_index.write().await;
+        discard_snapshots(actions.make_contiguous());
         actions.clear();
         *index = 0;
     }
@@ -277,6 +590,88 @@ impl ActionLog {
         let actions = self.actions.read().await;
         actions.get(idx).cloned()
     }
+
+    /// Find an action that is still applied (not undone) by id
+    pub async fn find_applied(&self, id: Uuid) -> Option<Action> {
+        let index = *self.current_index.read().await;
+        let actions = self.actions.read().await;
+        actions
+            .iter()
+            .take(index)
+            .find(|action| action.id == id)
+            .cloned()
+    }
+
+    /// Applied actions recorded after `action` whose paths overlap the ones
+    /// it touched; relative paths are resolved against `workspace`
+    ///
+    /// `action` may be a part of a logged action from
+    /// [`Action::split_by_paths`], in which case only its own paths count.
+    pub async fn conflicts_after(&self, action: &Action, workspace: &Path) -> Vec<ActionConflict> {
+        let index = *self.current_index.read().await;
+        let actions = self.actions.read().await;
+
+        let Some(position) = actions
+            .iter()
+            .take(index)
+            .position(|logged| logged.id == action.id)
+        else {
+            return Vec::new();
+        };
+
+        let paths: Vec<PathBuf> = action
+            .affected_paths()
+            .into_iter()
+            .map(|path| resolve(path, workspace))
+            .collect();
+
+        actions
+            .iter()
+            .take(index)
+            .skip(position + 1)
+            .filter_map(|later| {
+                let mut overlapping: Vec<PathBuf> = later
+                    .affected_paths()
+                    .into_iter()
+                    .map(|path| resolve(path, workspace))
+                    .filter(|path| paths.iter().any(|own| paths_overlap(path, own)))
+                    .collect();
+                overlapping.sort();
+                overlapping.dedup();
+
+                (!overlapping.is_empty()).then(|| ActionConflict {
+                    action_id: later.id,
+                    description: later.description.clone(),
+                    paths: overlapping,
+                })
+            })
+            .collect()
+    }
+
+    /// Take `reverted` out of the history after it was undone out of order,
+    /// keeping `remaining` in its place when only part of the action was
+    /// reverted
+    ///
+    /// Unlike [`ActionLog::undo`] this cannot be redone.
+    pub async fn remove_undone(&self, reverted: &Action, remaining: Option<Action>) {
+        let mut actions = self.actions.write().await;
+        let mut index = self.current_index.write().await;
+
+        let Some(position) = actions.iter().position(|logged| logged.id == reverted.id) else {
+            return;
+        };
+
+        match remaining {
+            Some(remaining) => actions[position] = remaining,
+            None => {
+                actions.remove(position);
+                if position < *index {
+                    *index -= 1;
+                }
+            }
+        }
+        discard_snapshots(std::slice::from_ref(reverted));
+    }
 }
 
 impl Default for ActionLog {
@@ -285,6 +680,133 @@ impl Default for ActionLog {
     }
 }
 
+/// Tells approval prompts about recent edits under the paths they touch
+#[async_trait]
+impl ApprovalContextProvider for ActionLog {
+    fn name(&self) -> &str {
+        "action log"
+    }
+
+    async fn describe(&self, workspace: &Path, target: &Path) -> Result<Vec<String>> {
+        let target_path = resolve(target, workspace);
+        let since = Utc::now() - chrono::Duration::minutes(RECENT_EDIT_MINUTES);
+        let index = *self.current_index.read().await;
+        let actions = self.actions.read().await;
+
+        let recent: Vec<&Action> = actions
+            .iter()
+            .take(index)
+            .filter(|action| action.timestamp >= since)
+            .filter(|action| {
+                action
+                    .affected_paths()
+                    .into_iter()
+                    .any(|path| resolve(path, workspace).starts_with(&target_path))
+            })
+            .collect();
+        let Some(latest) = recent.last() else {
+            return Ok(Vec::new());
+        };
+
+        let minutes = (Utc::now() - latest.timestamp).num_minutes();
+        Ok(vec![format!(
+            "{}: {} {} by Fennec in the last hour, the latest `{}` {}",
+            target.display(),
+            recent.len(),
+            if recent.len() == 1 { "edit" } else { "edits" },
+            latest.command,
+            if minutes < 1 {
+                "just now".to_string()
+            } else {
+                format!("{} min ago", minutes)
+            }
+        )])
+    }
+}
+
+/// Restore a directory tree from a snapshot created by
+/// [`ActionLog::snapshot_directory`] so that it reappears at `target`
+pub async fn restore_directory_snapshot(snapshot: &Path, target: &Path) -> Result<()> {
+    let snapshot = snapshot.to_path_buf();
+    let target = target.to_path_buf();
+
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        let parent = target
+            .parent()
+            .context("Cannot restore a directory without a parent")?;
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+
+        let file = std::fs::File::open(&snapshot)
+            .with_context(|| format!("Failed to open snapshot: {}", snapshot.display()))?;
+        let mut archive = tar::Archive::new(file);
+        archive.set_preserve_permissions(true);
+        archive.set_preserve_mtime(true);
+
+        // Snapshots are rooted at the original directory name; unpack them
+        // under that name and move into place if the target was renamed since
+        let staging = create_staging_dir(parent)?;
+        let result = archive
+            .unpack(&staging)
+            .with_context(|| format!("Failed to unpack snapshot: {}", snapshot.display()))
+            .and_then(|_| {
+                let root = std::fs::read_dir(&staging)?
+                    .next()
+                    .context("Snapshot is empty")??
+                    .path();
+                std::fs::rename(&root, &target)
+                    .with_context(|| format!("Failed to restore directory: {}", target.display()))
+            });
+        let _ = std::fs::remove_dir_all(&staging);
+        result
+    })
+    .await?
+}
+
+/// Create a uniquely named scratch directory next to the restore target so
+/// the final rename stays on one filesystem
+fn create_staging_dir(parent: &Path) -> Result<PathBuf> {
+    let staging = parent.join(format!(".fennec-restore-{}", Uuid::new_v4()));
+    std::fs::create_dir(&staging)
+        .with_context(|| format!("Failed to create directory: {}", staging.display()))?;
+    Ok(staging)
+}
+
+/// Total size in bytes of all regular files under `path`
+fn directory_size(path: &Path) -> Result<u64> {
+    let mut total = 0;
+    for entry in walkdir::WalkDir::new(path) {
+        let entry = entry?;
+        if entry.file_type().is_file() {
+            total += entry.metadata()?.len();
+        }
+    }
+    Ok(total)
+}
+
+/// Resolve a logged path against the workspace it was recorded in
+fn resolve(path: &Path, workspace: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        workspace.join(path)
+    }
+}
+
+/// Whether two paths are the same or one contains the other
+fn paths_overlap(a: &Path, b: &Path) -> bool {
+    a.starts_with(b) || b.starts_with(a)
+}
+
+/// Remove snapshot archives belonging to actions that left the history
+fn discard_snapshots(actions: &[Action]) {
+    for snapshot in actions.iter().flat_map(Action::snapshots) {
+        if let Err(e) = std::fs::remove_file(snapshot) {
+            tracing::debug!("Failed to remove snapshot {}: {}", snapshot.display(), e);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -340,6 +862,85 @@ mod tests {
         assert!(!log.can_redo().await);
     }
 
+    #[tokio::test]
+    async fn test_conflicts_after_overlapping_paths() {
+        let log = ActionLog::new();
+        let workspace = Path::new("/workspace");
+
+        let created = Action::file_created(
+            "create".to_string(),
+            PathBuf::from("src/lib.rs"),
+            "Created src/lib.rs".to_string(),
+        );
+        let unrelated = Action::file_created(
+            "create".to_string(),
+            PathBuf::from("README.md"),
+            "Created README.md".to_string(),
+        );
+        let moved = Action::directory_moved(
+            "rename".to_string(),
+            workspace.join("src"),
+            workspace.join("core"),
+            "Renamed src -> core".to_string(),
+        );
+        let moved_id = moved.id;
+
+        log.record(created.clone()).await;
+        log.record(unrelated.clone()).await;
+        log.record(moved).await;
+
+        let conflicts = log.conflicts_after(&created, workspace).await;
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].action_id, moved_id);
+        assert_eq!(conflicts[0].paths, vec![workspace.join("src")]);
+        assert!(log.conflicts_after(&unrelated, workspace).await.is_empty());
+
+        // Undone actions no longer conflict
+        log.undo().await.unwrap();
+        assert!(log.conflicts_after(&created, workspace).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_describes_recent_edits_for_approval() {
+        let log = ActionLog::new();
+        let workspace = Path::new("/workspace");
+        let mut old = Action::file_created(
+            "create".to_string(),
+            PathBuf::from("build/old.o"),
+            "Created build/old.o".to_string(),
+        );
+        old.timestamp = Utc::now() - chrono::Duration::hours(3);
+        log.record(old).await;
+        for (command, path) in [
+            ("write", "build/app"),
+            ("edit", "/workspace/build/deps/a.o"),
+        ] {
+            log.record(Action::file_created(
+                command.to_string(),
+                PathBuf::from(path),
+                format!("Created {}", path),
+            ))
+            .await;
+        }
+        log.record(Action::file_created(
+            "create".to_string(),
+            PathBuf::from("src/main.rs"),
+            "Created src/main.rs".to_string(),
+        ))
+        .await;
+
+        let details = log.describe(workspace, Path::new("build/")).await.unwrap();
+        assert_eq!(
+            details,
+            ["build/: 2 edits by Fennec in the last hour, the latest `edit` just now"]
+        );
+        assert!(log
+            .describe(workspace, Path::new("docs"))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
     #[tokio::test]
     async fn test_max_size() {
         let log = ActionLog::with_max_size(3);
@@ -1,12 +1,28 @@
-use crate::git_integration::generate_commit_template;
+use crate::git_integration::{
+    get_file_changes, infer_commit_type, render_commit_template, DiffTarget, FileChange,
+    COMMIT_SUBJECT_PLACEHOLDER,
+};
 use crate::registry::{CommandContext, CommandDescriptor, CommandExecutor};
 use anyhow::Result;
 use fennec_core::command::{Capability, CommandPreview, CommandResult};
 use fennec_core::error::FennecError;
 use fennec_security::SandboxLevel;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::OnceLock;
 use uuid::Uuid;
 
+/// Types accepted by default, from the Conventional Commits spec and the
+/// Angular convention it grew out of
+pub const DEFAULT_COMMIT_TYPES: &[&str] = &[
+    "feat", "fix", "docs", "style", "refactor", "perf", "test", "build", "ci", "chore", "revert",
+];
+
+/// Share of changed lines a scope needs before it is suggested over `multi`
+const DOMINANT_SCOPE_SHARE: f64 = 0.6;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommitTemplateArgs {
     /// Whether to include testing section
@@ -16,12 +32,236 @@ pub struct CommitTemplateArgs {
     /// Whether to include description section
     #[serde(default = "default_true")]
     pub include_description: bool,
+
+    /// Derive a `type(scope):` scope from the crates the staged diff touches
+    #[serde(default = "default_true")]
+    pub infer_scope: bool,
+
+    /// Fail when `message` violates conventional-commit rules instead of
+    /// only reporting the violations
+    #[serde(default)]
+    pub enforce_conventional: bool,
+
+    /// Allowed commit types (defaults to [`DEFAULT_COMMIT_TYPES`])
+    #[serde(default)]
+    pub types: Option<Vec<String>>,
+
+    /// Commit message to check against conventional-commit rules
+    #[serde(default)]
+    pub message: Option<String>,
+
+    /// Maximum length of the header line
+    #[serde(default = "default_max_subject_length")]
+    pub max_subject_length: usize,
 }
 
 fn default_true() -> bool {
     true
 }
 
+fn default_max_subject_length() -> usize {
+    72
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommitRule {
+    /// Header is not `type(scope)!: subject`
+    HeaderFormat,
+    UnknownType,
+    SubjectTooLong,
+    PlaceholderSubject,
+    TrailingPeriod,
+    ImperativeMood,
+    /// Body is not separated from the header by a blank line
+    BodySeparation,
+}
+
+/// A single conventional-commit rule violation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitViolation {
+    pub rule: CommitRule,
+    pub message: String,
+}
+
+/// Proposed template plus the result of checking a supplied message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitTemplateReport {
+    pub commit_type: String,
+    pub scope: Option<String>,
+    pub template: String,
+    /// Present only when a message was supplied for checking
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub violations: Option<Vec<CommitViolation>>,
+}
+
+fn header_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"^(?P<type>[A-Za-z]+)(?:\((?P<scope>[^()\s]+)\))?!?: (?P<subject>.*)$")
+            .expect("valid conventional commit pattern")
+    })
+}
+
+/// Heuristic for non-imperative subjects: "added", "adding", "adds"
+fn is_non_imperative(word: &str) -> bool {
+    const IMPERATIVE_EXCEPTIONS: &[&str] = &[
+        "need", "embed", "feed", "seed", "shed", "speed", "bring", "ping", "string", "sing",
+        "address", "process", "pass", "access", "bless", "focus", "alias", "bias", "canvas",
+        "redo", "does",
+    ];
+    let word = word.to_lowercase();
+    if word.len() < 4 || IMPERATIVE_EXCEPTIONS.contains(&word.as_str()) {
+        return false;
+    }
+    word.ends_with("ed")
+        || word.ends_with("ing")
+        || (word.ends_with('s') && !word.ends_with("ss") && !word.ends_with("us"))
+}
+
+/// Check a commit message against conventional-commit rules
+pub fn check_conventional_commit(
+    message: &str,
+    types: &[String],
+    max_subject_length: usize,
+) -> Vec<CommitViolation> {
+    let mut violations = Vec::new();
+    let mut lines = message.lines();
+    let header = lines.next().unwrap_or_default().trim_end();
+
+    if let Some(second) = lines.next() {
+        if !second.trim().is_empty() {
+            violations.push(CommitViolation {
+                rule: CommitRule::BodySeparation,
+                message: "Separate the body from the header with a blank line".to_string(),
+            });
+        }
+    }
+
+    if header.chars().count() > max_subject_length {
+        violations.push(CommitViolation {
+            rule: CommitRule::SubjectTooLong,
+            message: format!(
+                "Header is {} characters; keep it within {}",
+                header.chars().count(),
+                max_subject_length
+            ),
+        });
+    }
+
+    let Some(captures) = header_pattern().captures(header) else {
+        violations.insert(
+            0,
+            CommitViolation {
+                rule: CommitRule::HeaderFormat,
+                message: "Header must look like `type(scope): subject`".to_string(),
+            },
+        );
+        return violations;
+    };
+
+    let commit_type = &captures["type"];
+    if !types.iter().any(|t| t == commit_type) {
+        violations.push(CommitViolation {
+            rule: CommitRule::UnknownType,
+            message: format!(
+                "Unknown type `{}` (allowed: {})",
+                commit_type,
+                types.join(", ")
+            ),
+        });
+    }
+
+    let subject = captures["subject"].trim();
+    if subject.is_empty() || subject == COMMIT_SUBJECT_PLACEHOLDER {
+        violations.push(CommitViolation {
+            rule: CommitRule::PlaceholderSubject,
+            message: "Describe the change in the subject".to_string(),
+        });
+        return violations;
+    }
+    if subject.ends_with('.') {
+        violations.push(CommitViolation {
+            rule: CommitRule::TrailingPeriod,
+            message: "Don't end the subject with a period".to_string(),
+        });
+    }
+    if let Some(first_word) = subject.split_whitespace().next() {
+        if is_non_imperative(first_word) {
+            violations.push(CommitViolation {
+                rule: CommitRule::ImperativeMood,
+                message: format!("Use the imperative mood (\"add\", not \"{}\")", first_word),
+            });
+        }
+    }
+
+    violations
+}
+
+/// Scope for a single path: the crate name under `crates/` with any prefix
+/// shared by every workspace crate stripped, otherwise the top-level
+/// directory. Files at the repository root have no scope.
+fn path_scope(path: &Path, crate_prefix: &str) -> Option<String> {
+    let mut components = path.components().map(|c| c.as_os_str().to_string_lossy());
+    let first = components.next()?;
+    let second = components.next();
+    match (first.as_ref(), second) {
+        ("crates", Some(name)) if components.next().is_some() => Some(
+            name.strip_prefix(crate_prefix)
+                .filter(|n| !n.is_empty())
+                .unwrap_or(&name)
+                .to_string(),
+        ),
+        (_, Some(_)) => Some(first.trim_start_matches('.').to_string()),
+        (_, None) => None,
+    }
+}
+
+/// Longest `name-` prefix shared by every crate in `<workspace>/crates`, so
+/// `crates/fennec-tui` scopes as `tui`
+fn shared_crate_prefix(workspace: &Path) -> String {
+    let Ok(entries) = std::fs::read_dir(workspace.join("crates")) else {
+        return String::new();
+    };
+    let names: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_dir())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect();
+    if names.len() < 2 {
+        return String::new();
+    }
+
+    let first = &names[0];
+    first
+        .match_indices('-')
+        .rev()
+        .map(|(idx, _)| &first[..=idx])
+        .find(|prefix| names.iter().all(|n| n.starts_with(prefix)))
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Suggest the scope carrying most of the changed lines, or `multi` when no
+/// single scope dominates
+pub fn infer_scope(workspace: &Path, changes: &[FileChange]) -> Option<String> {
+    let prefix = shared_crate_prefix(workspace);
+    let mut weights: BTreeMap<String, usize> = BTreeMap::new();
+    for change in changes {
+        if let Some(scope) = path_scope(&change.path, &prefix) {
+            *weights.entry(scope).or_default() += (change.insertions + change.deletions).max(1);
+        }
+    }
+
+    let total: usize = weights.values().sum();
+    let (scope, weight) = weights.into_iter().max_by_key(|(_, weight)| *weight)?;
+    if weight == total || weight as f64 / total as f64 >= DOMINANT_SCOPE_SHARE {
+        Some(scope)
+    } else {
+        Some("multi".to_string())
+    }
+}
+
 pub struct CommitTemplateCommand {
     descriptor: CommandDescriptor,
 }
@@ -35,10 +275,12 @@ impl CommitTemplateCommand {
                     .to_string(),
                 version: "1.0.0".to_string(),
                 author: Some("Fennec Contributors".to_string()),
-                capabilities_required: vec![Capability::ExecuteShell],
-                sandbox_level_required: SandboxLevel::ReadOnly,
+                capabilities_required: vec![Capability::ReadFile, Capability::ExecuteShell],
+                sandbox_level_required: SandboxLevel::WorkspaceWrite,
                 supports_preview: false,
                 supports_dry_run: false,
+                timeout: None,
+                alias_of: None,
             },
         }
     }
@@ -47,7 +289,7 @@ impl CommitTemplateCommand {
         &self,
         args: &CommitTemplateArgs,
         context: &CommandContext,
-    ) -> Result<String> {
+    ) -> Result<CommitTemplateReport> {
         let workspace_path = context.workspace_path.as_ref().ok_or_else(|| {
             FennecError::Command(Box::new(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
@@ -55,7 +297,7 @@ impl CommitTemplateCommand {
             )))
         })?;
 
-        let mut template = generate_commit_template(workspace_path)
+        let changes = get_file_changes(workspace_path, DiffTarget::Staged)
             .await
             .map_err(|e| {
                 FennecError::Command(Box::new(std::io::Error::new(
@@ -64,6 +306,33 @@ impl CommitTemplateCommand {
                 )))
             })?;
 
+        let violations = args.message.as_deref().map(|message| {
+            let types: Vec<String> = match &args.types {
+                Some(types) => types.clone(),
+                None => DEFAULT_COMMIT_TYPES.iter().map(|t| t.to_string()).collect(),
+            };
+            check_conventional_commit(message, &types, args.max_subject_length)
+        });
+
+        if changes.is_empty() {
+            return Ok(CommitTemplateReport {
+                commit_type: String::new(),
+                scope: None,
+                template:
+                    "No staged changes found.\n\nPlease stage your changes with 'git add' first."
+                        .to_string(),
+                violations,
+            });
+        }
+
+        let commit_type = infer_commit_type(&changes);
+        let scope = if args.infer_scope {
+            infer_scope(Path::new(workspace_path), &changes)
+        } else {
+            None
+        };
+        let mut template = render_commit_template(&changes, commit_type, scope.as_deref());
+
         // Remove sections if not requested
         if !args.include_testing {
             if let Some(pos) = template.find("## Testing") {
@@ -80,8 +349,27 @@ impl CommitTemplateCommand {
             }
         }
 
-        Ok(template)
+        Ok(CommitTemplateReport {
+            commit_type: commit_type.to_string(),
+            scope,
+            template,
+            violations,
+        })
+    }
+}
+
+fn render_report(report: &CommitTemplateReport) -> String {
+    let mut output = report.template.clone();
+    if let Some(violations) = &report.violations {
+        output.push_str("\n## Message Check\n\n");
+        if violations.is_empty() {
+            output.push_str("✓ Message follows conventional commit rules\n");
+        }
+        for violation in violations {
+            output.push_str(&format!("- ✗ {}\n", violation.message));
+        }
     }
+    output
 }
 
 impl Default for CommitTemplateCommand {
@@ -122,29 +410,48 @@ impl CommandExecutor for CommitTemplateCommand {
         })?;
 
         match self.generate_template(&args, context).await {
-            Ok(output) => Ok(CommandResult {
-                command_id: Uuid::new_v4(),
-                success: true,
-                output,
-                error: None,
-            }),
+            Ok(report) => {
+                let violation_count = report.violations.as_ref().map_or(0, Vec::len);
+                let rejected = args.enforce_conventional && violation_count > 0;
+                Ok(CommandResult {
+                    command_id: Uuid::new_v4(),
+                    success: !rejected,
+                    output: render_report(&report),
+                    error: rejected.then(|| {
+                        format!(
+                            "Commit message violates conventional commit rules ({} violation(s))",
+                            violation_count
+                        )
+                    }),
+                    data: Some(serde_json::to_value(&report)?),
+                })
+            }
             Err(e) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
+                data: None,
             }),
         }
     }
 
     fn validate_args(&self, args: &serde_json::Value) -> Result<()> {
-        let _args: CommitTemplateArgs = serde_json::from_value(args.clone()).map_err(|e| {
+        let args: CommitTemplateArgs = serde_json::from_value(args.clone()).map_err(|e| {
             FennecError::Command(Box::new(std::io::Error::new(
                 std::io::ErrorKind::InvalidInput,
                 format!("Invalid commit-template arguments: {}", e),
             )))
         })?;
 
+        if args.types.as_ref().is_some_and(|types| types.is_empty()) {
+            return Err(FennecError::Command(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "At least one commit type must be allowed",
+            )))
+            .into());
+        }
+
         Ok(())
     }
 }
@@ -152,31 +459,199 @@ impl CommandExecutor for CommitTemplateCommand {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tempfile::TempDir;
     use tokio_util::sync::CancellationToken;
 
-    #[test]
-    fn test_default_true() {
-        assert!(default_true());
+    fn git(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .current_dir(dir)
+            .args(args)
+            .env("GIT_AUTHOR_NAME", "Test")
+            .env("GIT_AUTHOR_EMAIL", "test@example.com")
+            .env("GIT_COMMITTER_NAME", "Test")
+            .env("GIT_COMMITTER_EMAIL", "test@example.com")
+            .output()
+            .unwrap();
+        assert!(status.status.success(), "git {:?} failed", args);
     }
 
-    #[tokio::test]
-    async fn test_commit_template_no_workspace() {
-        let command = CommitTemplateCommand::new();
-        let args = serde_json::json!({});
+    fn write(dir: &Path, path: &str, content: &str) {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    /// Workspace with `fennec-tui` and `fennec-core` crates committed, and
+    /// the given files (path, line count) modified and staged
+    fn staged_repo(changes: &[(&str, usize)]) -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        git(dir, &["init", "-q", "-b", "main"]);
+        write(dir, "crates/fennec-tui/src/lib.rs", "// tui\n");
+        write(dir, "crates/fennec-core/src/lib.rs", "// core\n");
+        write(dir, "README.md", "# Fennec\n");
+        git(dir, &["add", "-A"]);
+        git(dir, &["commit", "-q", "-m", "init"]);
 
-        let context = CommandContext {
+        for (path, lines) in changes {
+            let content: String = (0..*lines).map(|i| format!("// line {}\n", i)).collect();
+            write(dir, path, &content);
+        }
+        git(dir, &["add", "-A"]);
+        temp_dir
+    }
+
+    fn context(workspace: Option<&Path>) -> CommandContext {
+        CommandContext {
             session_id: Uuid::new_v4(),
             user_id: None,
-            workspace_path: None,
+            workspace_path: workspace.map(|p| p.to_string_lossy().to_string()),
             sandbox_level: SandboxLevel::ReadOnly,
             dry_run: false,
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
-        };
+            timeout: None,
+            progress: None,
+        }
+    }
+
+    async fn run(repo: &TempDir, args: serde_json::Value) -> (CommandResult, CommitTemplateReport) {
+        let result = CommitTemplateCommand::new()
+            .execute(&args, &context(Some(repo.path())))
+            .await
+            .unwrap();
+        let report = serde_json::from_value(result.data.clone().unwrap()).unwrap();
+        (result, report)
+    }
+
+    fn default_types() -> Vec<String> {
+        DEFAULT_COMMIT_TYPES.iter().map(|t| t.to_string()).collect()
+    }
+
+    fn rules(message: &str) -> Vec<CommitRule> {
+        check_conventional_commit(message, &default_types(), 72)
+            .into_iter()
+            .map(|v| v.rule)
+            .collect()
+    }
 
-        let result = command.execute(&args, &context).await.unwrap();
+    #[test]
+    fn test_default_true() {
+        assert!(default_true());
+    }
+
+    #[tokio::test]
+    async fn test_commit_template_no_workspace() {
+        let command = CommitTemplateCommand::new();
+        let args = serde_json::json!({});
+
+        let result = command.execute(&args, &context(None)).await.unwrap();
         assert!(!result.success);
         assert!(result.error.is_some());
     }
+
+    #[tokio::test]
+    async fn test_single_crate_scope() {
+        let repo = staged_repo(&[("crates/fennec-tui/src/lib.rs", 10)]);
+        let (result, report) = run(&repo, serde_json::json!({})).await;
+
+        assert!(result.success);
+        assert_eq!(report.scope.as_deref(), Some("tui"));
+        assert!(result.output.starts_with("fix(tui): "));
+        assert!(report.violations.is_none());
+        assert!(!result.output.contains("## Message Check"));
+    }
+
+    #[tokio::test]
+    async fn test_dominant_scope_across_crates() {
+        let repo = staged_repo(&[
+            ("crates/fennec-tui/src/lib.rs", 20),
+            ("crates/fennec-tui/src/app.rs", 10),
+            ("crates/fennec-core/src/lib.rs", 3),
+        ]);
+        let (_, report) = run(&repo, serde_json::json!({})).await;
+        assert_eq!(report.scope.as_deref(), Some("tui"));
+        assert_eq!(report.commit_type, "fix");
+    }
+
+    #[tokio::test]
+    async fn test_balanced_changes_suggest_multi() {
+        let repo = staged_repo(&[
+            ("crates/fennec-tui/src/lib.rs", 10),
+            ("crates/fennec-core/src/lib.rs", 10),
+        ]);
+        let (result, report) = run(&repo, serde_json::json!({})).await;
+        assert_eq!(report.scope.as_deref(), Some("multi"));
+        assert!(result.output.starts_with("fix(multi): "));
+
+        let (result, report) = run(&repo, serde_json::json!({"infer_scope": false})).await;
+        assert_eq!(report.scope, None);
+        assert!(result.output.starts_with("fix: "));
+    }
+
+    #[tokio::test]
+    async fn test_message_check_reports_and_enforces() {
+        let repo = staged_repo(&[("crates/fennec-tui/src/lib.rs", 5)]);
+
+        let (result, report) = run(
+            &repo,
+            serde_json::json!({"message": "feat(tui): add scrollback search"}),
+        )
+        .await;
+        assert!(result.success);
+        assert_eq!(report.violations, Some(vec![]));
+        assert!(result
+            .output
+            .contains("✓ Message follows conventional commit rules"));
+
+        let message = "Added scrollback search.";
+        let (result, report) = run(&repo, serde_json::json!({"message": message})).await;
+        assert!(result.success);
+        assert_eq!(report.violations.unwrap()[0].rule, CommitRule::HeaderFormat);
+
+        let (result, report) = run(
+            &repo,
+            serde_json::json!({
+                "message": "feature: added search.",
+                "enforce_conventional": true,
+            }),
+        )
+        .await;
+        assert!(!result.success);
+        assert_eq!(report.violations.unwrap().len(), 3);
+        assert!(result.error.unwrap().contains("3 violation(s)"));
+        assert!(result.output.starts_with("fix(tui): "));
+    }
+
+    #[test]
+    fn test_check_conventional_commit_rules() {
+        assert!(rules("fix(core)!: handle empty config").is_empty());
+        assert!(rules("docs: address review comments\n\nBody text").is_empty());
+
+        assert_eq!(rules("wip"), vec![CommitRule::HeaderFormat]);
+        assert_eq!(rules("feature: add x"), vec![CommitRule::UnknownType]);
+        assert_eq!(
+            rules("feat: <brief description>"),
+            vec![CommitRule::PlaceholderSubject]
+        );
+        assert_eq!(rules("fix: adds retries"), vec![CommitRule::ImperativeMood]);
+        assert_eq!(
+            rules("fix: fixing retries"),
+            vec![CommitRule::ImperativeMood]
+        );
+        assert_eq!(rules("fix: add retries."), vec![CommitRule::TrailingPeriod]);
+        assert_eq!(
+            rules("fix: add retries\nBody"),
+            vec![CommitRule::BodySeparation]
+        );
+        assert_eq!(
+            rules(&format!("feat: add {}", "x".repeat(80))),
+            vec![CommitRule::SubjectTooLong]
+        );
+
+        let custom = check_conventional_commit("chore: bump", &["feat".to_string()], 72);
+        assert_eq!(custom[0].rule, CommitRule::UnknownType);
+        assert!(custom[0].message.contains("allowed: feat"));
+    }
 }
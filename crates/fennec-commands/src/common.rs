@@ -3,17 +3,20 @@
 
 use anyhow::Result;
 use fennec_core::error::FennecError;
+use std::io::Read;
 use std::path::Path;
 use std::sync::Arc;
 
+use crate::encoding::{detect_encoding, SNIFF_LEN};
 use crate::registry::CommandRegistry;
 use crate::{
     commit_template::CommitTemplateCommand, create::CreateCommand, delete::DeleteCommand,
     diff::DiffCommand, edit::EditCommand, find_symbol::FindSymbolCommand,
     fix_errors::FixErrorsCommand, index::IndexCommand, plan::PlanCommand,
     pr_summary::PrSummaryCommand, quick_actions::QuickActionCommand, rename::RenameCommand,
-    run::RunCommand, search::SearchCommand, summarize::SummarizeCommand,
-    summarize_enhanced::EnhancedSummarizeCommand, test_watch::TestWatchCommand,
+    rename_symbol::RenameSymbolCommand, run::RunCommand, search::SearchCommand,
+    summarize::SummarizeCommand, summarize_enhanced::EnhancedSummarizeCommand,
+    test_watch::TestWatchCommand, trash::TrashCommand,
 };
 
 /// Initialize the command registry with all built-in commands
@@ -29,9 +32,15 @@ pub async fn initialize_builtin_commands() -> Result<CommandRegistry> {
     registry
         .register_builtin(Arc::new(DeleteCommand::new()))
         .await?;
+    registry
+        .register_builtin(Arc::new(TrashCommand::new()))
+        .await?;
     registry
         .register_builtin(Arc::new(RenameCommand::new()))
         .await?;
+    registry
+        .register_builtin(Arc::new(RenameSymbolCommand::new()))
+        .await?;
     registry
         .register_builtin(Arc::new(EditCommand::new()))
         .await?;
@@ -150,8 +159,16 @@ pub fn truncate_text(text: &str, max_length: usize) -> String {
     }
 }
 
-/// Check if a file appears to be a text file based on its extension
+/// Check if a file appears to be a text file, using the detector file
+/// edits use; paths that can't be read are judged by their extension
 pub fn is_text_file(path: &Path) -> bool {
+    let mut prefix = Vec::new();
+    let read = std::fs::File::open(path)
+        .and_then(|file| file.take(SNIFF_LEN as u64).read_to_end(&mut prefix));
+    if read.is_ok() {
+        return detect_encoding(&prefix).is_some();
+    }
+
     let text_extensions = [
         "txt", "md", "rs", "py", "js", "ts", "json", "yaml", "yml", "toml", "xml", "html", "css",
         "sql", "sh", "bash", "zsh", "fish", "ps1", "bat", "cmd", "c", "cpp", "h", "hpp", "java",
@@ -201,6 +218,20 @@ mod tests {
         assert!(!is_text_file(Path::new("binary.exe")));
     }
 
+    #[test]
+    fn test_is_text_file_sniffs_content() {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/encodings");
+        assert!(is_text_file(&fixtures.join("utf16le_crlf.txt")));
+        assert!(is_text_file(&fixtures.join("latin1_crlf.txt")));
+        assert!(!is_text_file(&fixtures.join("binary.png")));
+
+        // Content wins over the extension
+        let temp_dir = tempfile::tempdir().unwrap();
+        let disguised = temp_dir.path().join("lib.rs");
+        std::fs::copy(fixtures.join("binary.png"), &disguised).unwrap();
+        assert!(!is_text_file(&disguised));
+    }
+
     #[test]
     fn test_extract_preview_lines() {
         let content = "line 1\nline 2\nline 3\nline 4";
@@ -215,14 +246,16 @@ mod tests {
         let registry = initialize_builtin_commands().await.unwrap();
         let commands = registry.list_commands().await;
 
-        // Should have all 17 built-in commands (including Sprint 4 features)
-        assert_eq!(commands.len(), 17);
+        // Should have all 19 built-in commands (including Sprint 4 features)
+        assert_eq!(commands.len(), 19);
 
         let command_names: Vec<String> = commands.iter().map(|c| c.name.clone()).collect();
         assert!(command_names.contains(&"plan".to_string()));
         assert!(command_names.contains(&"create".to_string()));
         assert!(command_names.contains(&"delete".to_string()));
+        assert!(command_names.contains(&"trash".to_string()));
         assert!(command_names.contains(&"rename".to_string()));
+        assert!(command_names.contains(&"rename-symbol".to_string()));
         assert!(command_names.contains(&"edit".to_string()));
         assert!(command_names.contains(&"run".to_string()));
         assert!(command_names.contains(&"diff".to_string()));
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Represents a compiler error or warning
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +30,23 @@ pub struct CodeSpan {
     pub text: Vec<SpanText>,
     pub label: Option<String>,
     pub suggested_replacement: Option<String>,
+    /// Byte offsets of the span within the file
+    #[serde(default)]
+    pub byte_start: usize,
+    #[serde(default)]
+    pub byte_end: usize,
+    /// How safe rustc considers `suggested_replacement` to apply unattended
+    #[serde(default)]
+    pub suggestion_applicability: Option<Applicability>,
+}
+
+/// rustc's confidence that a suggestion can be applied without review
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Applicability {
+    MachineApplicable,
+    MaybeIncorrect,
+    HasPlaceholders,
+    Unspecified,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -131,6 +148,12 @@ pub fn parse_cargo_json(json_line: &str) -> Option<CompilerMessage> {
         text: Vec<SpanTextJson>,
         label: Option<String>,
         suggested_replacement: Option<String>,
+        #[serde(default)]
+        byte_start: usize,
+        #[serde(default)]
+        byte_end: usize,
+        #[serde(default)]
+        suggestion_applicability: Option<Applicability>,
     }
 
     #[derive(Deserialize)]
@@ -156,10 +179,8 @@ pub fn parse_cargo_json(json_line: &str) -> Option<CompilerMessage> {
         _ => return None,
     };
 
-    let spans = msg
-        .spans
-        .into_iter()
-        .map(|s| CodeSpan {
+    fn convert_span(s: SpanJson) -> CodeSpan {
+        CodeSpan {
             file_name: PathBuf::from(s.file_name),
             line_start: s.line_start,
             line_end: s.line_end,
@@ -176,8 +197,13 @@ pub fn parse_cargo_json(json_line: &str) -> Option<CompilerMessage> {
                 .collect(),
             label: s.label,
             suggested_replacement: s.suggested_replacement,
-        })
-        .collect();
+            byte_start: s.byte_start,
+            byte_end: s.byte_end,
+            suggestion_applicability: s.suggestion_applicability,
+        }
+    }
+
+    let spans = msg.spans.into_iter().map(convert_span).collect();
 
     fn convert_children(children: Vec<CompilerMessageJson>) -> Vec<CompilerMessage> {
         children
@@ -195,28 +221,7 @@ pub fn parse_cargo_json(json_line: &str) -> Option<CompilerMessage> {
                     level,
                     message: child.message,
                     code: child.code.map(|c| c.code),
-                    spans: child
-                        .spans
-                        .into_iter()
-                        .map(|s| CodeSpan {
-                            file_name: PathBuf::from(s.file_name),
-                            line_start: s.line_start,
-                            line_end: s.line_end,
-                            column_start: s.column_start,
-                            column_end: s.column_end,
-                            text: s
-                                .text
-                                .into_iter()
-                                .map(|t| SpanText {
-                                    text: t.text,
-                                    highlight_start: t.highlight_start,
-                                    highlight_end: t.highlight_end,
-                                })
-                                .collect(),
-                            label: s.label,
-                            suggested_replacement: s.suggested_replacement,
-                        })
-                        .collect(),
+                    spans: child.spans.into_iter().map(convert_span).collect(),
                     children: convert_children(child.children),
                     rendered: child.rendered,
                 })
@@ -264,6 +269,117 @@ pub fn extract_fixes(message: &CompilerMessage) -> Vec<SuggestedFix> {
     fixes
 }
 
+/// A single text replacement from a compiler suggestion
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpanReplacement {
+    pub file_path: PathBuf,
+    pub byte_start: usize,
+    pub byte_end: usize,
+    pub line: usize,
+    pub column: usize,
+    pub replacement: String,
+}
+
+impl SpanReplacement {
+    fn overlaps(&self, other: &SpanReplacement) -> bool {
+        self.file_path == other.file_path
+            && (self.byte_start < other.byte_end && other.byte_start < self.byte_end
+                || self.byte_start == other.byte_start)
+    }
+}
+
+/// A machine-applicable compiler suggestion; all replacements apply together
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MachineFix {
+    pub message: String,
+    pub replacements: Vec<SpanReplacement>,
+}
+
+impl MachineFix {
+    /// Files touched by this fix, in first-seen order
+    pub fn files(&self) -> Vec<&Path> {
+        let mut files: Vec<&Path> = Vec::new();
+        for replacement in &self.replacements {
+            if !files.contains(&replacement.file_path.as_path()) {
+                files.push(&replacement.file_path);
+            }
+        }
+        files
+    }
+
+    fn overlaps(&self, other: &MachineFix) -> bool {
+        self.replacements
+            .iter()
+            .any(|a| other.replacements.iter().any(|b| a.overlaps(b)))
+    }
+}
+
+/// Extract suggestions rustc marks `MachineApplicable`.
+///
+/// Each message's suggested spans form one fix, since multi-part suggestions
+/// are only correct when applied together.
+pub fn extract_machine_applicable_fixes(message: &CompilerMessage) -> Vec<MachineFix> {
+    fn collect(message: &CompilerMessage, parent: Option<&str>, fixes: &mut Vec<MachineFix>) {
+        let replacements: Vec<SpanReplacement> = message
+            .spans
+            .iter()
+            .filter(|span| span.suggestion_applicability == Some(Applicability::MachineApplicable))
+            .filter_map(|span| {
+                span.suggested_replacement
+                    .as_ref()
+                    .map(|replacement| SpanReplacement {
+                        file_path: span.file_name.clone(),
+                        byte_start: span.byte_start,
+                        byte_end: span.byte_end,
+                        line: span.line_start,
+                        column: span.column_start,
+                        replacement: replacement.clone(),
+                    })
+            })
+            .collect();
+
+        if !replacements.is_empty() {
+            let text = match parent {
+                Some(parent) => format!("{}: {}", parent, message.message),
+                None => message.message.clone(),
+            };
+            fixes.push(MachineFix {
+                message: text,
+                replacements,
+            });
+        }
+
+        for child in &message.children {
+            collect(child, Some(parent.unwrap_or(&message.message)), fixes);
+        }
+    }
+
+    let mut fixes = Vec::new();
+    collect(message, None, &mut fixes);
+    fixes
+}
+
+/// Split fixes into those that can be applied together and those skipped
+/// because they overlap an earlier fix. Exact duplicates (rustc reports the
+/// same suggestion once per target) are dropped silently.
+pub fn select_non_overlapping(fixes: Vec<MachineFix>) -> (Vec<MachineFix>, Vec<MachineFix>) {
+    let mut selected: Vec<MachineFix> = Vec::new();
+    let mut skipped = Vec::new();
+
+    for fix in fixes {
+        if selected.contains(&fix) {
+            continue;
+        }
+        if selected.iter().any(|chosen| chosen.overlaps(&fix)) {
+            skipped.push(fix);
+        } else {
+            selected.push(fix);
+        }
+    }
+
+    (selected, skipped)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -367,6 +483,9 @@ mod tests {
                 }],
                 label: None,
                 suggested_replacement: Some("_y".to_string()),
+                byte_start: 0,
+                byte_end: 0,
+                suggestion_applicability: None,
             }],
             children: vec![],
             rendered: None,
@@ -396,4 +515,57 @@ mod tests {
         assert!(formatted.contains("- y"));
         assert!(formatted.contains("+ _y"));
     }
+
+    fn machine_fix(message: &str, file: &str, start: usize, end: usize, text: &str) -> MachineFix {
+        MachineFix {
+            message: message.to_string(),
+            replacements: vec![SpanReplacement {
+                file_path: PathBuf::from(file),
+                byte_start: start,
+                byte_end: end,
+                line: 1,
+                column: start + 1,
+                replacement: text.to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_extract_machine_applicable_fixes() {
+        let json = r#"{"reason":"compiler-message","message":{"level":"warning","message":"unused import: `std::fmt`","code":{"code":"unused_imports"},"spans":[{"file_name":"src/lib.rs","byte_start":4,"byte_end":12,"line_start":1,"line_end":1,"column_start":5,"column_end":13,"text":[],"label":null,"suggested_replacement":null,"suggestion_applicability":null}],"children":[{"level":"help","message":"remove the whole `use` item","code":null,"spans":[{"file_name":"src/lib.rs","byte_start":0,"byte_end":14,"line_start":1,"line_end":2,"column_start":1,"column_end":1,"text":[],"label":null,"suggested_replacement":"","suggestion_applicability":"MachineApplicable"}],"children":[],"rendered":null},{"level":"help","message":"consider something else","code":null,"spans":[{"file_name":"src/lib.rs","byte_start":4,"byte_end":12,"line_start":1,"line_end":1,"column_start":5,"column_end":13,"text":[],"label":null,"suggested_replacement":"std::io","suggestion_applicability":"MaybeIncorrect"}],"children":[],"rendered":null}],"rendered":null}}"#;
+
+        let message = parse_cargo_json(json).unwrap();
+        let fixes = extract_machine_applicable_fixes(&message);
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(
+            fixes[0].message,
+            "unused import: `std::fmt`: remove the whole `use` item"
+        );
+        assert_eq!(fixes[0].replacements[0].byte_end, 14);
+        assert_eq!(fixes[0].replacements[0].replacement, "");
+    }
+
+    #[test]
+    fn test_select_non_overlapping() {
+        let first = machine_fix("a", "src/lib.rs", 10, 20, "x");
+        let duplicate = first.clone();
+        let overlapping = machine_fix("b", "src/lib.rs", 15, 25, "y");
+        let same_insert_point = machine_fix("c", "src/lib.rs", 30, 30, "z");
+        let insert_again = machine_fix("d", "src/lib.rs", 30, 30, "w");
+        let other_file = machine_fix("e", "src/main.rs", 15, 25, "y");
+
+        let (selected, skipped) = select_non_overlapping(vec![
+            first,
+            duplicate,
+            overlapping,
+            same_insert_point,
+            insert_again,
+            other_file,
+        ]);
+
+        let names: Vec<&str> = selected.iter().map(|f| f.message.as_str()).collect();
+        assert_eq!(names, vec!["a", "c", "e"]);
+        let skipped: Vec<&str> = skipped.iter().map(|f| f.message.as_str()).collect();
+        assert_eq!(skipped, vec!["b", "d"]);
+    }
 }
@@ -1,13 +1,15 @@
 use crate::action_log::Action;
+use crate::file_ops::{EditStrategy, FileEditRequest, FileOperations};
 use crate::registry::{CommandContext, CommandDescriptor, CommandExecutor};
+use crate::scaffold::{find_template, template_names, ProjectTemplate, TemplateVariables};
 use anyhow::Result;
 use fennec_core::{
-    command::{Capability, CommandPreview, CommandResult},
+    command::{Capability, CommandPreview, CommandResult, PreviewAction},
     error::FennecError,
 };
 use fennec_security::SandboxLevel;
 use serde::{Deserialize, Serialize};
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use tokio::fs;
 use uuid::Uuid;
 
@@ -18,6 +20,119 @@ pub struct CreateArgs {
     pub content: Option<String>,
     #[serde(default)]
     pub is_directory: bool,
+
+    /// Built-in template to scaffold into `path` instead of creating a
+    /// single file (see [`crate::scaffold::BUILTIN_TEMPLATES`])
+    #[serde(default)]
+    pub template: Option<String>,
+
+    /// Value for `{{name}}` in templates (defaults to the last component of
+    /// `path`, or the workspace directory name)
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Overwrite template files that already exist
+    #[serde(default)]
+    pub force: bool,
+
+    /// Template files to leave out, relative to the workspace or to `path`
+    #[serde(default)]
+    pub skip: Vec<PathBuf>,
+}
+
+/// What scaffolding will do with a single template file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScaffoldAction {
+    Create,
+    /// The file exists and `force` was given
+    Overwrite,
+    /// The file was listed in `skip`
+    Skip,
+    /// The file exists and neither `force` nor `skip` covers it
+    Conflict,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScaffoldFile {
+    /// Path relative to the workspace
+    pub path: PathBuf,
+    pub action: ScaffoldAction,
+    pub bytes: usize,
+    #[serde(skip)]
+    content: String,
+}
+
+/// Files a template expands to, and what will happen to each
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScaffoldPlan {
+    pub template: String,
+    pub variables: TemplateVariables,
+    pub files: Vec<ScaffoldFile>,
+    pub notes: Option<String>,
+}
+
+impl ScaffoldPlan {
+    fn conflicts(&self) -> Vec<&ScaffoldFile> {
+        self.files
+            .iter()
+            .filter(|f| f.action == ScaffoldAction::Conflict)
+            .collect()
+    }
+
+    fn writes(&self) -> impl Iterator<Item = &ScaffoldFile> {
+        self.files
+            .iter()
+            .filter(|f| matches!(f.action, ScaffoldAction::Create | ScaffoldAction::Overwrite))
+    }
+
+    fn render(&self) -> String {
+        let mut output = format!(
+            "Template '{}' (name: {}):\n",
+            self.template, self.variables.name
+        );
+        for file in &self.files {
+            let line = match file.action {
+                ScaffoldAction::Create => {
+                    format!("  + {} ({} bytes)", file.path.display(), file.bytes)
+                }
+                ScaffoldAction::Overwrite => {
+                    format!(
+                        "  ~ {} ({} bytes, overwrite)",
+                        file.path.display(),
+                        file.bytes
+                    )
+                }
+                ScaffoldAction::Skip => format!("  - {} (skipped)", file.path.display()),
+                ScaffoldAction::Conflict => {
+                    format!("  ! {} (already exists)", file.path.display())
+                }
+            };
+            output.push_str(&line);
+            output.push('\n');
+        }
+        output
+    }
+}
+
+/// Drop `.` components so `./src/main.rs` displays as `src/main.rs`
+fn clean_relative(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .collect()
+}
+
+/// Reject a cleaned scaffold path that could leave the workspace: `..`, a
+/// root or a drive prefix
+fn check_inside_workspace(path: &Path) -> Result<()> {
+    if path.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Ok(());
+    }
+    Err(FennecError::Command(Box::new(std::io::Error::new(
+        std::io::ErrorKind::PermissionDenied,
+        "Cannot create files outside workspace",
+    )))
+    .into())
 }
 
 pub struct CreateCommand {
@@ -36,6 +151,8 @@ impl CreateCommand {
                 sandbox_level_required: SandboxLevel::WorkspaceWrite,
                 supports_preview: true,
                 supports_dry_run: true,
+                timeout: None,
+                alias_of: None,
             },
         }
     }
@@ -123,7 +240,7 @@ impl CreateCommand {
 
             // Record action to log
             if let Some(action_log) = &context.action_log {
-                let action = Action::file_created(
+                let action = Action::directory_created(
                     "create".to_string(),
                     target_path.clone(),
                     format!("Created directory: {}", target_path.display()),
@@ -164,6 +281,208 @@ impl CreateCommand {
 
         Ok(result)
     }
+
+    fn resolve_template(name: &str) -> Result<&'static ProjectTemplate> {
+        find_template(name).ok_or_else(|| {
+            FennecError::Command(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "Unknown template '{}' (available: {})",
+                    name,
+                    template_names()
+                ),
+            )))
+            .into()
+        })
+    }
+
+    /// Expand a template into the files it would write under `args.path`
+    fn plan_scaffold(
+        &self,
+        args: &CreateArgs,
+        template: &ProjectTemplate,
+        workspace_path: &Path,
+    ) -> Result<ScaffoldPlan> {
+        let root = if args.path.is_absolute() {
+            args.path
+                .strip_prefix(workspace_path)
+                .map(Path::to_path_buf)
+                .map_err(|_| {
+                    FennecError::Command(Box::new(std::io::Error::new(
+                        std::io::ErrorKind::PermissionDenied,
+                        "Cannot create files outside workspace",
+                    )))
+                })?
+        } else {
+            clean_relative(&args.path)
+        };
+        check_inside_workspace(&root)?;
+
+        let name = args
+            .name
+            .clone()
+            .or_else(|| root.file_name().map(|n| n.to_string_lossy().to_string()))
+            .or_else(|| {
+                workspace_path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+            })
+            .unwrap_or_default();
+        let variables = TemplateVariables::new(&name);
+        if variables.snake_name.is_empty() {
+            return Err(FennecError::Command(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Cannot derive a template name from '{}'", name),
+            )))
+            .into());
+        }
+
+        let files = template
+            .render(&root, &variables)
+            .into_iter()
+            .map(|(path, content)| {
+                let path = clean_relative(&path);
+                check_inside_workspace(&path)?;
+                let skipped = args
+                    .skip
+                    .iter()
+                    .any(|skip| clean_relative(skip) == path || root.join(skip) == path);
+                let action = if skipped {
+                    ScaffoldAction::Skip
+                } else if workspace_path.join(&path).exists() {
+                    if args.force {
+                        ScaffoldAction::Overwrite
+                    } else {
+                        ScaffoldAction::Conflict
+                    }
+                } else {
+                    ScaffoldAction::Create
+                };
+                Ok(ScaffoldFile {
+                    path,
+                    action,
+                    bytes: content.len(),
+                    content,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(ScaffoldPlan {
+            template: template.name.to_string(),
+            notes: template.notes.map(|notes| variables.render(notes)),
+            variables,
+            files,
+        })
+    }
+
+    async fn perform_scaffold(
+        &self,
+        args: &CreateArgs,
+        template_name: &str,
+        context: &CommandContext,
+    ) -> Result<(String, ScaffoldPlan)> {
+        let workspace_path_str = context.workspace_path.as_ref().ok_or_else(|| {
+            FennecError::Command(Box::new(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "No workspace path set",
+            )))
+        })?;
+        let workspace_path = Path::new(workspace_path_str);
+        let template = Self::resolve_template(template_name)?;
+        let plan = self.plan_scaffold(args, template, workspace_path)?;
+
+        let conflicts = plan.conflicts();
+        if !conflicts.is_empty() {
+            let paths: Vec<String> = conflicts
+                .iter()
+                .map(|f| f.path.display().to_string())
+                .collect();
+            return Err(FennecError::Command(Box::new(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!(
+                    "{} file(s) already exist: {}. Use force to overwrite them or skip to leave them out",
+                    paths.len(),
+                    paths.join(", ")
+                ),
+            )))
+            .into());
+        }
+
+        if context.dry_run {
+            return Ok((format!("Would scaffold {}", plan.render()), plan));
+        }
+
+        // Parent directories must exist before FileOperations validates the
+        // targets; remember the ones created so a failed write can undo them
+        let mut created_dirs: Vec<PathBuf> = Vec::new();
+        for file in plan.writes() {
+            let target = workspace_path.join(&file.path);
+            let Some(parent) = target.parent() else {
+                continue;
+            };
+            let mut missing: Vec<PathBuf> = parent
+                .ancestors()
+                .take_while(|dir| !dir.exists())
+                .map(Path::to_path_buf)
+                .collect();
+            if missing.is_empty() {
+                continue;
+            }
+            fs::create_dir_all(parent).await.map_err(|e| {
+                FennecError::Command(Box::new(std::io::Error::new(
+                    e.kind(),
+                    format!("Failed to create parent directories: {}", e),
+                )))
+            })?;
+            missing.reverse();
+            created_dirs.extend(missing);
+        }
+
+        let requests = plan
+            .writes()
+            .map(|file| FileEditRequest {
+                path: workspace_path.join(&file.path),
+                strategy: EditStrategy::Replace {
+                    content: file.content.clone(),
+                },
+                create_backup: file.action == ScaffoldAction::Overwrite,
+                create_if_missing: true,
+                force_binary: false,
+            })
+            .collect();
+
+        let transaction = match FileOperations::with_default_config()
+            .apply_transaction(requests, &context.sandbox_level, Some(workspace_path_str))
+            .await
+        {
+            Ok(transaction) => transaction,
+            Err(e) => {
+                for dir in created_dirs.iter().rev() {
+                    let _ = fs::remove_dir(dir).await;
+                }
+                return Err(e);
+            }
+        };
+
+        let written = transaction.files.len();
+        if let Some(action_log) = &context.action_log {
+            action_log
+                .record(transaction.to_action(
+                    "create".to_string(),
+                    format!(
+                        "Scaffolded {} file(s) from template '{}'",
+                        written, plan.template
+                    ),
+                ))
+                .await;
+        }
+
+        let mut output = format!("Scaffolded {} file(s) from {}", written, plan.render());
+        if let Some(notes) = &plan.notes {
+            output.push_str(&format!("\nNext: {}\n", notes));
+        }
+        Ok((output, plan))
+    }
 }
 
 impl Default for CreateCommand {
@@ -198,6 +517,40 @@ impl CommandExecutor for CreateCommand {
         })?;
         let workspace_path = Path::new(workspace_path_str);
 
+        if let Some(template_name) = &args.template {
+            let template = Self::resolve_template(template_name)?;
+            let plan = self.plan_scaffold(&args, template, workspace_path)?;
+            let file_ops = FileOperations::with_default_config();
+            let mut actions = Vec::new();
+            for file in plan.writes() {
+                let path = workspace_path.join(&file.path);
+                // Overwritten files are diffed against what they hold now
+                let existing = match file.action {
+                    ScaffoldAction::Overwrite => fs::read_to_string(&path).await.ok(),
+                    _ => None,
+                };
+                let (diff, bytes_changed) = match existing {
+                    Some(existing) => (
+                        Some(file_ops.generate_diff(&existing, &file.content)?),
+                        file_ops.bytes_changed(&existing, &file.content),
+                    ),
+                    None => (None, file.content.len() as u64),
+                };
+                actions.push(PreviewAction::WriteFile {
+                    path: path.display().to_string(),
+                    content: file.content.clone(),
+                    diff,
+                    bytes_changed,
+                });
+            }
+            return Ok(CommandPreview {
+                command_id: Uuid::new_v4(),
+                description: format!("Scaffold {}", plan.render()),
+                actions,
+                requires_approval: true,
+            });
+        }
+
         let target_path = if args.path.is_absolute() {
             args.path.clone()
         } else {
@@ -214,10 +567,22 @@ impl CommandExecutor for CreateCommand {
             )
         };
 
+        let actions = if args.is_directory {
+            vec![]
+        } else {
+            let content = args.content.clone().unwrap_or_default();
+            vec![PreviewAction::WriteFile {
+                path: target_path.display().to_string(),
+                bytes_changed: content.len() as u64,
+                content,
+                diff: None,
+            }]
+        };
+
         Ok(CommandPreview {
             command_id: Uuid::new_v4(),
             description,
-            actions: vec![],
+            actions,
             requires_approval: true,
         })
     }
@@ -234,18 +599,39 @@ impl CommandExecutor for CreateCommand {
             )))
         })?;
 
+        if let Some(template_name) = &args.template {
+            return match self.perform_scaffold(&args, template_name, context).await {
+                Ok((output, plan)) => Ok(CommandResult {
+                    command_id: Uuid::new_v4(),
+                    success: true,
+                    output,
+                    error: None,
+                    data: Some(serde_json::to_value(&plan)?),
+                }),
+                Err(e) => Ok(CommandResult {
+                    command_id: Uuid::new_v4(),
+                    success: false,
+                    output: String::new(),
+                    error: Some(e.to_string()),
+                    data: None,
+                }),
+            };
+        }
+
         match self.perform_create(&args, context).await {
             Ok(output) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: true,
                 output,
                 error: None,
+                data: None,
             }),
             Err(e) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
+                data: None,
             }),
         }
     }
@@ -276,6 +662,17 @@ impl CommandExecutor for CreateCommand {
             .into());
         }
 
+        if let Some(template_name) = &args.template {
+            Self::resolve_template(template_name)?;
+            if args.is_directory || args.content.is_some() {
+                return Err(FennecError::Command(Box::new(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "A template cannot be combined with content or is_directory",
+                )))
+                .into());
+            }
+        }
+
         Ok(())
     }
 }
@@ -306,6 +703,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -337,6 +736,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -367,6 +768,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -398,6 +801,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -425,6 +830,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -434,4 +841,242 @@ mod tests {
         let test_file = temp_dir.path().join("dry_run_test.txt");
         assert!(!test_file.exists());
     }
+
+    fn scaffold_context(temp_dir: &TempDir, dry_run: bool) -> CommandContext {
+        CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: Some(temp_dir.path().to_string_lossy().to_string()),
+            sandbox_level: SandboxLevel::WorkspaceWrite,
+            dry_run,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log: None,
+            timeout: None,
+            progress: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scaffold_rust_bin() {
+        let temp_dir = TempDir::new().unwrap();
+        let args = serde_json::json!({"path": "tools/my-tool", "template": "rust-bin"});
+
+        let result = CreateCommand::new()
+            .execute(&args, &scaffold_context(&temp_dir, false))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.output.contains("Scaffolded 3 file(s)"));
+        assert!(result.output.contains("[workspace] members"));
+
+        let root = temp_dir.path().join("tools/my-tool");
+        let manifest = std::fs::read_to_string(root.join("Cargo.toml")).unwrap();
+        assert!(manifest.contains("name = \"my-tool\""));
+        let main = std::fs::read_to_string(root.join("src/main.rs")).unwrap();
+        assert!(main.contains("println!(\"Hello from my-tool!\");"));
+        assert_eq!(
+            std::fs::read_to_string(root.join(".gitignore")).unwrap(),
+            "/target\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scaffold_rust_module() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.path().join("src")).unwrap();
+        let args = serde_json::json!({
+            "path": "src",
+            "template": "rust-module",
+            "name": "HttpClient",
+        });
+
+        let result = CreateCommand::new()
+            .execute(&args, &scaffold_context(&temp_dir, false))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.output.contains("`mod http_client;`"));
+
+        let module = std::fs::read_to_string(temp_dir.path().join("src/http_client.rs")).unwrap();
+        assert!(module.starts_with("//! HttpClient\n"));
+        assert!(module.contains("pub fn http_client() -> &'static str"));
+        assert!(module.contains("#[cfg(test)]\nmod tests {"));
+        assert!(module.contains("fn test_http_client()"));
+    }
+
+    #[tokio::test]
+    async fn test_scaffold_github_workflow() {
+        let temp_dir = TempDir::new().unwrap();
+        let args = serde_json::json!({
+            "path": ".",
+            "template": "github-workflow",
+            "name": "CI",
+        });
+
+        let result = CreateCommand::new()
+            .execute(&args, &scaffold_context(&temp_dir, false))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+
+        let workflow =
+            std::fs::read_to_string(temp_dir.path().join(".github/workflows/ci.yml")).unwrap();
+        assert!(workflow.starts_with("name: CI\n"));
+        assert!(workflow.contains("group: ci-${{ github.ref }}"));
+        assert!(workflow.contains("cargo clippy --workspace --all-targets -- -D warnings"));
+    }
+
+    #[tokio::test]
+    async fn test_scaffold_outside_workspace_is_rejected() {
+        let root = TempDir::new().unwrap();
+        let workspace = root.path().join("workspace");
+        std::fs::create_dir(&workspace).unwrap();
+        let context = CommandContext {
+            workspace_path: Some(workspace.to_string_lossy().to_string()),
+            ..scaffold_context(&root, false)
+        };
+        let escaped = workspace.join("../escaped");
+
+        for path in [
+            "../escaped".to_string(),
+            "app/../../escaped".to_string(),
+            escaped.to_string_lossy().to_string(),
+        ] {
+            let args = serde_json::json!({"path": path, "template": "rust-bin"});
+            let result = CreateCommand::new().execute(&args, &context).await;
+            let error = match result {
+                Ok(result) => result.error.unwrap_or_default(),
+                Err(e) => e.to_string(),
+            };
+            assert!(
+                error.contains("Cannot create files outside workspace"),
+                "{}: {}",
+                path,
+                error
+            );
+        }
+        assert!(!root.path().join("escaped").exists());
+    }
+
+    #[tokio::test]
+    async fn test_scaffold_preview_and_dry_run_write_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let command = CreateCommand::new();
+        let args = serde_json::json!({"path": "app", "template": "rust-bin"});
+        let context = scaffold_context(&temp_dir, true);
+
+        let preview = command.preview(&args, &context).await.unwrap();
+        assert_eq!(preview.actions.len(), 3);
+        assert!(preview.description.contains("+ app/Cargo.toml"));
+        assert!(preview.description.contains("+ app/src/main.rs"));
+
+        let result = command.execute(&args, &context).await.unwrap();
+        assert!(result.success);
+        assert!(result
+            .output
+            .starts_with("Would scaffold Template 'rust-bin'"));
+        assert!(!temp_dir.path().join("app").exists());
+    }
+
+    #[tokio::test]
+    async fn test_preview_writes_carry_size_and_overwrite_diffs() {
+        let temp_dir = TempDir::new().unwrap();
+        let command = CreateCommand::new();
+        let context = scaffold_context(&temp_dir, true);
+
+        let args = serde_json::json!({"path": "notes.txt", "content": "hello"});
+        let preview = command.preview(&args, &context).await.unwrap();
+        let [PreviewAction::WriteFile {
+            content,
+            diff,
+            bytes_changed,
+            ..
+        }] = preview.actions.as_slice()
+        else {
+            panic!("expected one write, got {:?}", preview.actions);
+        };
+        assert_eq!((content.as_str(), *bytes_changed), ("hello", 5));
+        assert!(diff.is_none());
+
+        std::fs::create_dir_all(temp_dir.path().join("app/src")).unwrap();
+        std::fs::write(temp_dir.path().join("app/src/main.rs"), "// mine\n").unwrap();
+        let args = serde_json::json!({"path": "app", "template": "rust-bin", "force": true});
+        let preview = command.preview(&args, &context).await.unwrap();
+        let diffs: Vec<&str> = preview
+            .actions
+            .iter()
+            .filter_map(|action| match action {
+                PreviewAction::WriteFile { diff, .. } => diff.as_deref(),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(diffs.len(), 1, "only the overwritten file has a diff");
+        assert!(diffs[0].contains("-// mine"), "{}", diffs[0]);
+    }
+
+    #[tokio::test]
+    async fn test_scaffold_collisions_need_force_or_skip() {
+        let temp_dir = TempDir::new().unwrap();
+        let command = CreateCommand::new();
+        let root = temp_dir.path().join("app");
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/main.rs"), "// mine").unwrap();
+        let context = scaffold_context(&temp_dir, false);
+
+        let result = command
+            .execute(
+                &serde_json::json!({"path": "app", "template": "rust-bin"}),
+                &context,
+            )
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("app/src/main.rs"));
+        assert!(
+            !root.join("Cargo.toml").exists(),
+            "nothing is written on conflict"
+        );
+
+        let result = command
+            .execute(
+                &serde_json::json!({"path": "app", "template": "rust-bin", "skip": ["src/main.rs"]}),
+                &context,
+            )
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.output.contains("- app/src/main.rs (skipped)"));
+        assert_eq!(
+            std::fs::read_to_string(root.join("src/main.rs")).unwrap(),
+            "// mine"
+        );
+        assert!(root.join("Cargo.toml").exists());
+
+        let result = command
+            .execute(
+                &serde_json::json!({"path": "app", "template": "rust-bin", "force": true}),
+                &context,
+            )
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(std::fs::read_to_string(root.join("src/main.rs"))
+            .unwrap()
+            .contains("Hello from app!"));
+    }
+
+    #[test]
+    fn test_validate_template_args() {
+        let command = CreateCommand::new();
+        let err = command
+            .validate_args(&serde_json::json!({"path": "x", "template": "nope"}))
+            .unwrap_err();
+        assert!(err.to_string().contains("available: rust-bin"));
+        assert!(command
+            .validate_args(
+                &serde_json::json!({"path": "x", "template": "rust-bin", "is_directory": true})
+            )
+            .is_err());
+    }
 }
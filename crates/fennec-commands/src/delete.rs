@@ -1,16 +1,22 @@
 use crate::action_log::Action;
+use crate::file_ops::{FileOperations, TRASH_DIR};
 use crate::registry::{CommandContext, CommandDescriptor, CommandExecutor};
 use anyhow::Result;
 use fennec_core::{
-    command::{Capability, CommandPreview, CommandResult},
+    command::{Capability, CommandPreview, CommandResult, PreviewAction},
     error::FennecError,
 };
 use fennec_security::SandboxLevel;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tokio::fs;
+use tracing::warn;
 use uuid::Uuid;
 
+/// How long trashed entries are kept before deletes purge them
+pub const DEFAULT_TRASH_RETENTION: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeleteArgs {
     pub path: PathBuf,
@@ -18,6 +24,9 @@ pub struct DeleteArgs {
     pub recursive: bool,
     #[serde(default = "default_confirm")]
     pub confirm: bool,
+    /// Remove the target immediately instead of moving it to the trash
+    #[serde(default)]
+    pub permanent: bool,
 }
 
 fn default_confirm() -> bool {
@@ -26,6 +35,8 @@ fn default_confirm() -> bool {
 
 pub struct DeleteCommand {
     descriptor: CommandDescriptor,
+    file_ops: FileOperations,
+    trash_retention: Option<Duration>,
 }
 
 impl DeleteCommand {
@@ -36,14 +47,25 @@ impl DeleteCommand {
                 description: "Delete files or directories with safety checks".to_string(),
                 version: "1.0.0".to_string(),
                 author: Some("Fennec Contributors".to_string()),
-                capabilities_required: vec![Capability::WriteFile],
+                capabilities_required: vec![Capability::ReadFile, Capability::WriteFile],
                 sandbox_level_required: SandboxLevel::WorkspaceWrite,
                 supports_preview: true,
                 supports_dry_run: true,
+                timeout: None,
+                alias_of: None,
             },
+            file_ops: FileOperations::with_default_config(),
+            trash_retention: Some(DEFAULT_TRASH_RETENTION),
         }
     }
 
+    /// Purge trashed entries older than `retention` after each delete;
+    /// `None` keeps them until the trash is emptied by hand
+    pub fn with_trash_retention(mut self, retention: Option<Duration>) -> Self {
+        self.trash_retention = retention;
+        self
+    }
+
     fn is_protected_path(path: &Path) -> bool {
         if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
             // Protect git, cargo, and other critical directories/files
@@ -143,38 +165,66 @@ impl DeleteCommand {
             }
         }
 
+        // Entries already in the trash can only be removed for good
+        let permanent = args.permanent || target_path.starts_with(workspace_path.join(TRASH_DIR));
+
         if context.dry_run {
             return Ok(format!(
-                "Would delete {}: {}",
+                "Would delete {}: {}{}",
                 if is_dir { "directory" } else { "file" },
-                target_path.display()
+                target_path.display(),
+                if permanent { "" } else { " (moved to trash)" }
             ));
         }
 
+        if !permanent {
+            return self
+                .move_to_trash(&target_path, workspace_path, is_dir, context)
+                .await;
+        }
+
         // Perform the deletion
         let result = if is_dir {
-            if args.recursive {
-                fs::remove_dir_all(&target_path).await.map_err(|e| {
-                    FennecError::Command(Box::new(std::io::Error::new(
-                        e.kind(),
-                        format!("Failed to delete directory: {}", e),
-                    )))
-                })?;
+            // Snapshot the tree first so the deletion can be undone; refuse to
+            // delete rather than lose data the log claims it can restore
+            let snapshot = if let Some(action_log) = &context.action_log {
+                Some(
+                    action_log
+                        .snapshot_directory(&target_path)
+                        .await
+                        .map_err(|e| {
+                            FennecError::Command(Box::new(std::io::Error::other(format!(
+                                "Failed to snapshot directory before deletion: {}",
+                                e
+                            ))))
+                        })?,
+                )
             } else {
-                fs::remove_dir(&target_path).await.map_err(|e| {
-                    FennecError::Command(Box::new(std::io::Error::new(
-                        e.kind(),
-                        format!("Failed to delete directory: {}", e),
-                    )))
-                })?;
+                None
+            };
+
+            let removed = if args.recursive {
+                fs::remove_dir_all(&target_path).await
+            } else {
+                fs::remove_dir(&target_path).await
+            };
+            if let Err(e) = removed {
+                if let Some(snapshot) = &snapshot {
+                    let _ = fs::remove_file(snapshot).await;
+                }
+                return Err(FennecError::Command(Box::new(std::io::Error::new(
+                    e.kind(),
+                    format!("Failed to delete directory: {}", e),
+                )))
+                .into());
             }
 
-            // Record action to log (simplified - not storing directory contents for now)
+            // Record action to log with the snapshot for restore capability
             if let Some(action_log) = &context.action_log {
-                let action = Action::file_deleted(
+                let action = Action::directory_deleted(
                     "delete".to_string(),
                     target_path.clone(),
-                    Vec::new(), // Empty content for directories
+                    snapshot,
                     format!("Deleted directory: {}", target_path.display()),
                 );
                 action_log.record(action).await;
@@ -213,6 +263,50 @@ impl DeleteCommand {
 
         Ok(result)
     }
+
+    async fn move_to_trash(
+        &self,
+        target_path: &Path,
+        workspace_path: &Path,
+        is_dir: bool,
+        context: &CommandContext,
+    ) -> Result<String> {
+        let entry = self
+            .file_ops
+            .move_to_trash(target_path, workspace_path)
+            .await?;
+        let kind = if is_dir { "directory" } else { "file" };
+
+        // Undo moves the entry back out of the trash, so large trees don't
+        // need to be snapshotted up front
+        if let Some(action_log) = &context.action_log {
+            let action = Action::moved_to_trash(
+                "delete".to_string(),
+                target_path.to_path_buf(),
+                entry.trash_path.clone(),
+                is_dir,
+                format!("Moved {} to trash: {}", kind, target_path.display()),
+            );
+            action_log.record(action).await;
+        }
+
+        if let Some(retention) = self.trash_retention {
+            if let Err(e) = self
+                .file_ops
+                .empty_trash(workspace_path, Some(retention))
+                .await
+            {
+                warn!("Failed to purge expired trash entries: {}", e);
+            }
+        }
+
+        Ok(format!(
+            "Moved {} to trash: {} (restore with trash id {})",
+            kind,
+            target_path.display(),
+            entry.id
+        ))
+    }
 }
 
 impl Default for DeleteCommand {
@@ -255,16 +349,33 @@ impl CommandExecutor for DeleteCommand {
 
         let is_dir = target_path.is_dir();
         let description = format!(
-            "Delete {}{}: {}",
+            "{} {}{}: {}",
+            if args.permanent {
+                "Permanently delete"
+            } else {
+                "Move to trash"
+            },
             if is_dir { "directory" } else { "file" },
             if args.recursive { " (recursive)" } else { "" },
             target_path.display()
         );
 
+        let mut actions = Vec::new();
+        if target_path.exists() {
+            let counted = target_path.clone();
+            let (file_count, total_bytes) =
+                tokio::task::spawn_blocking(move || count_files(&counted)).await?;
+            actions.push(PreviewAction::DeletePath {
+                path: target_path.display().to_string(),
+                file_count,
+                total_bytes,
+            });
+        }
+
         Ok(CommandPreview {
             command_id: Uuid::new_v4(),
             description,
-            actions: vec![],
+            actions,
             requires_approval: true,
         })
     }
@@ -287,12 +398,14 @@ impl CommandExecutor for DeleteCommand {
                 success: true,
                 output,
                 error: None,
+                data: None,
             }),
             Err(e) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
+                data: None,
             }),
         }
     }
@@ -327,6 +440,18 @@ impl CommandExecutor for DeleteCommand {
     }
 }
 
+/// Files at or under `path` and their total size in bytes
+fn count_files(path: &Path) -> (u64, u64) {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .flatten()
+        .filter(|entry| !entry.file_type().is_dir())
+        .fold((0, 0), |(count, bytes), entry| {
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            (count + 1, bytes + size)
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -355,6 +480,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -384,6 +511,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -415,6 +544,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -422,6 +553,87 @@ mod tests {
         assert!(!test_dir.exists());
     }
 
+    #[tokio::test]
+    async fn test_preview_counts_files_to_delete() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_dir = temp_dir.path().join("test_dir");
+        std::fs::create_dir_all(test_dir.join("subdir")).unwrap();
+        std::fs::write(test_dir.join("file.txt"), "content").unwrap();
+        std::fs::write(test_dir.join("subdir/other.txt"), "more").unwrap();
+
+        let command = DeleteCommand::new();
+        let args = serde_json::json!({
+            "path": "test_dir",
+            "recursive": true,
+            "confirm": true
+        });
+
+        let context = CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: Some(temp_dir.path().to_string_lossy().to_string()),
+            sandbox_level: SandboxLevel::WorkspaceWrite,
+            dry_run: false,
+            preview_only: true,
+            cancellation_token: CancellationToken::new(),
+            action_log: None,
+            timeout: None,
+            progress: None,
+        };
+
+        let preview = command.preview(&args, &context).await.unwrap();
+        let [PreviewAction::DeletePath {
+            path,
+            file_count,
+            total_bytes,
+        }] = preview.actions.as_slice()
+        else {
+            panic!("expected one deletion, got {:?}", preview.actions);
+        };
+        assert_eq!(path, &test_dir.display().to_string());
+        assert_eq!((*file_count, *total_bytes), (2, 11));
+        assert!(test_dir.exists());
+    }
+
+    #[tokio::test]
+    async fn test_delete_directory_over_snapshot_cap_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = TempDir::new().unwrap();
+        let test_dir = temp_dir.path().join("test_dir");
+        std::fs::create_dir(&test_dir).unwrap();
+        std::fs::write(test_dir.join("file.txt"), "more than ten bytes").unwrap();
+
+        let action_log = crate::action_log::ActionLog::new()
+            .with_data_dir(data_dir.path().to_path_buf())
+            .with_max_snapshot_size(10);
+
+        let command = DeleteCommand::new();
+        let args = serde_json::json!({
+            "path": "test_dir",
+            "recursive": true,
+            "confirm": true,
+            "permanent": true
+        });
+
+        let context = CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: Some(temp_dir.path().to_string_lossy().to_string()),
+            sandbox_level: SandboxLevel::WorkspaceWrite,
+            dry_run: false,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log: Some(std::sync::Arc::new(action_log)),
+            timeout: None,
+            progress: None,
+        };
+
+        let result = command.execute(&args, &context).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("too large to snapshot"));
+        assert!(test_dir.join("file.txt").exists());
+    }
+
     #[tokio::test]
     async fn test_delete_nonempty_dir_without_recursive_fails() {
         let temp_dir = TempDir::new().unwrap();
@@ -445,6 +657,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -475,6 +689,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -505,6 +721,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -535,6 +753,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -563,10 +783,87 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
         assert!(!result.success);
         assert!(result.error.unwrap().contains("does not exist"));
     }
+    #[tokio::test]
+    async fn test_delete_moves_to_trash_and_undo_restores() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_file = temp_dir.path().join("test.txt");
+        std::fs::write(&test_file, "content").unwrap();
+
+        let action_log = std::sync::Arc::new(crate::action_log::ActionLog::new());
+        let context = CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: Some(temp_dir.path().to_string_lossy().to_string()),
+            sandbox_level: SandboxLevel::WorkspaceWrite,
+            dry_run: false,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log: Some(action_log.clone()),
+            timeout: None,
+            progress: None,
+        };
+
+        let result = DeleteCommand::new()
+            .execute(&serde_json::json!({"path": "test.txt"}), &context)
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert!(result.output.contains("Moved file to trash"));
+        assert!(!test_file.exists());
+        assert_eq!(
+            FileOperations::with_default_config()
+                .list_trash(temp_dir.path())
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let undo = crate::undo::UndoCommand::new(action_log);
+        let result = undo
+            .execute(&serde_json::json!({"count": 1}), &context)
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(std::fs::read_to_string(&test_file).unwrap(), "content");
+    }
+
+    #[tokio::test]
+    async fn test_permanent_delete_bypasses_trash() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_file = temp_dir.path().join("test.txt");
+        std::fs::write(&test_file, "content").unwrap();
+
+        let context = CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: Some(temp_dir.path().to_string_lossy().to_string()),
+            sandbox_level: SandboxLevel::WorkspaceWrite,
+            dry_run: false,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log: None,
+            timeout: None,
+            progress: None,
+        };
+
+        let result = DeleteCommand::new()
+            .execute(
+                &serde_json::json!({"path": "test.txt", "permanent": true}),
+                &context,
+            )
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert!(!test_file.exists());
+        assert!(!temp_dir.path().join(crate::file_ops::TRASH_DIR).exists());
+    }
 }
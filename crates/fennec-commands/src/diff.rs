@@ -7,10 +7,12 @@ use fennec_core::{
 use fennec_security::SandboxLevel;
 use serde::{Deserialize, Serialize};
 use similar::{ChangeTag, TextDiff};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tokio::fs;
 use uuid::Uuid;
 
+use crate::diff_model::StructuredDiff;
+use crate::hunks::split_diff_into_hunks;
 use crate::registry::{CommandContext, CommandDescriptor, CommandExecutor};
 
 /// Arguments for the diff command
@@ -24,7 +26,7 @@ pub struct DiffArgs {
     pub is_file_path: Option<bool>,
     /// Number of context lines to show
     pub context_lines: Option<usize>,
-    /// Output format (unified, side-by-side, brief)
+    /// Output format (unified, side-by-side, brief, hunks, structured)
     pub format: Option<String>,
 }
 
@@ -45,13 +47,15 @@ impl DiffCommand {
                 sandbox_level_required: SandboxLevel::ReadOnly,
                 supports_preview: true,
                 supports_dry_run: false,
+                timeout: None,
+                alias_of: None,
             },
         }
     }
 
-    /// Generate diff output
-    async fn generate_diff(&self, args: &DiffArgs, _context: &CommandContext) -> Result<String> {
-        let (left_content, right_content) = if args.is_file_path.unwrap_or(true) {
+    /// Load the left and right content, reading files when the inputs are paths
+    async fn load_inputs(&self, args: &DiffArgs) -> Result<(String, String)> {
+        if args.is_file_path.unwrap_or(true) {
             // Read from files
             let left_path = Path::new(&args.left);
             let right_path = Path::new(&args.right);
@@ -86,12 +90,60 @@ impl DiffCommand {
                 .into());
             };
 
-            (left_content, right_content)
+            Ok((left_content, right_content))
         } else {
             // Use provided text content
-            (args.left.clone(), args.right.clone())
+            Ok((args.left.clone(), args.right.clone()))
+        }
+    }
+
+    /// Split the diff into hunks that a caller can accept or reject individually
+    async fn generate_hunks(&self, args: &DiffArgs) -> Result<(String, serde_json::Value)> {
+        let (left_content, right_content) = self.load_inputs(args).await?;
+        let file_path = if args.is_file_path.unwrap_or(true) {
+            PathBuf::from(&args.left)
+        } else {
+            PathBuf::from("left")
         };
 
+        let hunks = split_diff_into_hunks(
+            file_path,
+            &left_content,
+            &right_content,
+            args.context_lines.unwrap_or(3),
+        );
+
+        let mut lines = vec![format!("{} hunk(s)", hunks.len())];
+        lines.extend(hunks.iter().map(|hunk| hunk.summary()));
+
+        Ok((lines.join("\n"), serde_json::json!({ "hunks": hunks })))
+    }
+
+    /// Build the structured line/word model alongside its unified rendering
+    async fn generate_structured(&self, args: &DiffArgs) -> Result<(String, serde_json::Value)> {
+        let (left_content, right_content) = self.load_inputs(args).await?;
+        let (left_name, right_name) = if args.is_file_path.unwrap_or(true) {
+            (args.left.as_str(), args.right.as_str())
+        } else {
+            ("left", "right")
+        };
+
+        let model = StructuredDiff::compute(
+            &left_content,
+            &right_content,
+            args.context_lines.unwrap_or(3),
+        );
+
+        Ok((
+            model.to_unified(left_name, right_name),
+            serde_json::to_value(&model)?,
+        ))
+    }
+
+    /// Generate diff output
+    async fn generate_diff(&self, args: &DiffArgs, _context: &CommandContext) -> Result<String> {
+        let (left_content, right_content) = self.load_inputs(args).await?;
+
         // Generate diff
         let diff = TextDiff::from_lines(&left_content, &right_content);
 
@@ -229,18 +281,44 @@ impl CommandExecutor for DiffCommand {
             )))
         })?;
 
+        let structured = match args.format.as_deref() {
+            Some("hunks") => Some(self.generate_hunks(&args).await),
+            Some("structured") => Some(self.generate_structured(&args).await),
+            _ => None,
+        };
+        if let Some(generated) = structured {
+            return match generated {
+                Ok((output, data)) => Ok(CommandResult {
+                    command_id: Uuid::new_v4(),
+                    success: true,
+                    output,
+                    error: None,
+                    data: Some(data),
+                }),
+                Err(e) => Ok(CommandResult {
+                    command_id: Uuid::new_v4(),
+                    success: false,
+                    output: String::new(),
+                    error: Some(e.to_string()),
+                    data: None,
+                }),
+            };
+        }
+
         match self.generate_diff(&args, context).await {
             Ok(output) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: true,
                 output,
                 error: None,
+                data: None,
             }),
             Err(e) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
+                data: None,
             }),
         }
     }
@@ -262,10 +340,13 @@ impl CommandExecutor for DiffCommand {
         }
 
         if let Some(ref format) = args.format {
-            if !matches!(format.as_str(), "unified" | "side-by-side" | "brief") {
+            if !matches!(
+                format.as_str(),
+                "unified" | "side-by-side" | "brief" | "hunks" | "structured"
+            ) {
                 return Err(FennecError::Command(Box::new(std::io::Error::new(
                     std::io::ErrorKind::Other,
-                    "Format must be one of: unified, side-by-side, brief",
+                    "Format must be one of: unified, side-by-side, brief, hunks, structured",
                 )))
                 .into());
             }
@@ -319,6 +400,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -326,4 +409,73 @@ mod tests {
         assert!(result.output.contains("-World"));
         assert!(result.output.contains("+Universe"));
     }
+
+    #[tokio::test]
+    async fn test_diff_hunks_format() {
+        let command = DiffCommand::new();
+
+        let args = serde_json::json!({
+            "left": "a\nb\nc\nd\ne\nf\ng\nh\n",
+            "right": "a\nB\nc\nd\ne\nf\ng\nH\n",
+            "is_file_path": false,
+            "context_lines": 1,
+            "format": "hunks"
+        });
+        assert!(command.validate_args(&args).is_ok());
+
+        let context = CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: None,
+            sandbox_level: SandboxLevel::ReadOnly,
+            dry_run: false,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log: None,
+            timeout: None,
+            progress: None,
+        };
+
+        let result = command.execute(&args, &context).await.unwrap();
+        assert!(result.success);
+        let hunks: Vec<crate::hunks::Hunk> =
+            serde_json::from_value(result.data.unwrap()["hunks"].clone()).unwrap();
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].new_content, vec!["B"]);
+        assert_eq!(hunks[1].new_content, vec!["H"]);
+    }
+    #[tokio::test]
+    async fn test_diff_structured_format() {
+        let command = DiffCommand::new();
+
+        let args = serde_json::json!({
+            "left": "Hello\nWorld\n",
+            "right": "Hello\nBig World\n",
+            "is_file_path": false,
+            "format": "structured"
+        });
+        assert!(command.validate_args(&args).is_ok());
+
+        let context = CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: None,
+            sandbox_level: SandboxLevel::ReadOnly,
+            dry_run: false,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log: None,
+            timeout: None,
+            progress: None,
+        };
+
+        let result = command.execute(&args, &context).await.unwrap();
+        assert!(result.success);
+        assert!(result.output.contains("@@ -1,2 +1,2 @@"));
+        assert!(result.output.contains("+Big World"));
+
+        let model: StructuredDiff = serde_json::from_value(result.data.unwrap()).unwrap();
+        assert_eq!(model.hunks.len(), 1);
+        assert_eq!(model.hunks[0].rows.len(), 2);
+    }
 }
@@ -0,0 +1,438 @@
+//! Structured diff model for rich rendering.
+//!
+//! Unlike the plain unified text produced by the diff command, the model keeps
+//! one record per line with its old/new line numbers, pairs changed lines for
+//! side-by-side display and marks which words changed inside each pair.
+
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, DiffOp, TextDiff};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LineKind {
+    Equal,
+    Delete,
+    Insert,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SpanKind {
+    Equal,
+    Changed,
+}
+
+/// A byte range of a line's content, for intra-line highlighting
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WordSpan {
+    pub start: usize,
+    pub end: usize,
+    pub kind: SpanKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DiffLine {
+    pub kind: LineKind,
+    /// 1-based line number in the old text
+    pub old_line: Option<usize>,
+    /// 1-based line number in the new text
+    pub new_line: Option<usize>,
+    /// Line content without its line terminator
+    pub content: String,
+    /// Word-level spans, present when the line is paired with a counterpart
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub spans: Vec<WordSpan>,
+    /// The paired lines differ only in whitespace or line endings
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub whitespace_only: bool,
+    /// Line number on the other side of an identical line that moved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moved: Option<usize>,
+}
+
+impl DiffLine {
+    fn new(kind: LineKind, old_line: Option<usize>, new_line: Option<usize>, raw: &str) -> Self {
+        Self {
+            kind,
+            old_line,
+            new_line,
+            content: strip_line_ending(raw).to_string(),
+            spans: Vec::new(),
+            whitespace_only: false,
+            moved: None,
+        }
+    }
+}
+
+/// One row of a side-by-side view, as indexes into [`DiffHunk::lines`]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SideBySideRow {
+    pub old: Option<usize>,
+    pub new: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DiffHunk {
+    pub old_start: usize,
+    pub old_len: usize,
+    pub new_start: usize,
+    pub new_len: usize,
+    pub lines: Vec<DiffLine>,
+    pub rows: Vec<SideBySideRow>,
+}
+
+impl DiffHunk {
+    pub fn header(&self) -> String {
+        format!(
+            "@@ -{},{} +{},{} @@",
+            self.old_start, self.old_len, self.new_start, self.new_len
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct StructuredDiff {
+    pub hunks: Vec<DiffHunk>,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+impl StructuredDiff {
+    /// Diff `old` against `new`, keeping `context` unchanged lines around
+    /// each hunk
+    pub fn compute(old: &str, new: &str, context: usize) -> Self {
+        let diff = TextDiff::from_lines(old, new);
+        let old_lines = diff.old_slices();
+        let new_lines = diff.new_slices();
+        let mut model = Self::default();
+
+        for group in diff.grouped_ops(context) {
+            let (Some(first), Some(last)) = (group.first(), group.last()) else {
+                continue;
+            };
+            let old_range = first.old_range().start..last.old_range().end;
+            let new_range = first.new_range().start..last.new_range().end;
+            let mut hunk = DiffHunk {
+                old_start: old_range.start + 1,
+                old_len: old_range.len(),
+                new_start: new_range.start + 1,
+                new_len: new_range.len(),
+                lines: Vec::new(),
+                rows: Vec::new(),
+            };
+
+            for op in &group {
+                match *op {
+                    DiffOp::Equal {
+                        old_index,
+                        new_index,
+                        len,
+                    } => {
+                        for i in 0..len {
+                            let index = hunk.lines.len();
+                            hunk.lines.push(DiffLine::new(
+                                LineKind::Equal,
+                                Some(old_index + i + 1),
+                                Some(new_index + i + 1),
+                                old_lines[old_index + i],
+                            ));
+                            hunk.rows.push(SideBySideRow {
+                                old: Some(index),
+                                new: Some(index),
+                            });
+                        }
+                    }
+                    DiffOp::Delete {
+                        old_index, old_len, ..
+                    } => {
+                        model.deletions += old_len;
+                        for (i, raw) in
+                            (old_index..).zip(&old_lines[old_index..old_index + old_len])
+                        {
+                            hunk.rows.push(SideBySideRow {
+                                old: Some(hunk.lines.len()),
+                                new: None,
+                            });
+                            hunk.lines.push(DiffLine::new(
+                                LineKind::Delete,
+                                Some(i + 1),
+                                None,
+                                raw,
+                            ));
+                        }
+                    }
+                    DiffOp::Insert {
+                        new_index, new_len, ..
+                    } => {
+                        model.insertions += new_len;
+                        for (i, raw) in
+                            (new_index..).zip(&new_lines[new_index..new_index + new_len])
+                        {
+                            hunk.rows.push(SideBySideRow {
+                                old: None,
+                                new: Some(hunk.lines.len()),
+                            });
+                            hunk.lines.push(DiffLine::new(
+                                LineKind::Insert,
+                                None,
+                                Some(i + 1),
+                                raw,
+                            ));
+                        }
+                    }
+                    DiffOp::Replace {
+                        old_index,
+                        old_len,
+                        new_index,
+                        new_len,
+                    } => {
+                        model.deletions += old_len;
+                        model.insertions += new_len;
+
+                        // Deleted lines first, then inserted, as in unified output
+                        let deleted_at = hunk.lines.len();
+                        for (i, raw) in
+                            (old_index..).zip(&old_lines[old_index..old_index + old_len])
+                        {
+                            hunk.lines.push(DiffLine::new(
+                                LineKind::Delete,
+                                Some(i + 1),
+                                None,
+                                raw,
+                            ));
+                        }
+                        let inserted_at = hunk.lines.len();
+                        for (i, raw) in
+                            (new_index..).zip(&new_lines[new_index..new_index + new_len])
+                        {
+                            hunk.lines.push(DiffLine::new(
+                                LineKind::Insert,
+                                None,
+                                Some(i + 1),
+                                raw,
+                            ));
+                        }
+
+                        // Pair lines by position; the longer side's extras stand alone
+                        for offset in 0..old_len.max(new_len) {
+                            let old = (offset < old_len).then_some(deleted_at + offset);
+                            let new = (offset < new_len).then_some(inserted_at + offset);
+                            if let (Some(old), Some(new)) = (old, new) {
+                                pair_lines(&mut hunk.lines, old, new);
+                            }
+                            hunk.rows.push(SideBySideRow { old, new });
+                        }
+                    }
+                }
+            }
+
+            model.hunks.push(hunk);
+        }
+
+        model.detect_moves();
+        model
+    }
+
+    /// Link unpaired deleted and inserted lines with identical content
+    fn detect_moves(&mut self) {
+        let unpaired = |kind: LineKind, hunks: &[DiffHunk]| -> Vec<(usize, usize)> {
+            let mut found = Vec::new();
+            for (h, hunk) in hunks.iter().enumerate() {
+                for row in &hunk.rows {
+                    let index = match (kind, row.old, row.new) {
+                        (LineKind::Delete, Some(old), None) => old,
+                        (LineKind::Insert, None, Some(new)) => new,
+                        _ => continue,
+                    };
+                    if !hunk.lines[index].content.trim().is_empty() {
+                        found.push((h, index));
+                    }
+                }
+            }
+            found
+        };
+
+        let mut inserts: HashMap<String, Vec<(usize, usize)>> = HashMap::new();
+        for (h, i) in unpaired(LineKind::Insert, &self.hunks).into_iter().rev() {
+            inserts
+                .entry(self.hunks[h].lines[i].content.clone())
+                .or_default()
+                .push((h, i));
+        }
+
+        for (h, i) in unpaired(LineKind::Delete, &self.hunks) {
+            let content = &self.hunks[h].lines[i].content;
+            let Some((ih, ii)) = inserts.get_mut(content).and_then(Vec::pop) else {
+                continue;
+            };
+            let old_line = self.hunks[h].lines[i].old_line;
+            let new_line = self.hunks[ih].lines[ii].new_line;
+            self.hunks[h].lines[i].moved = new_line;
+            self.hunks[ih].lines[ii].moved = old_line;
+        }
+    }
+
+    /// Render as unified diff text with hunk headers
+    pub fn to_unified(&self, old_name: &str, new_name: &str) -> String {
+        let mut output = vec![format!("--- {}", old_name), format!("+++ {}", new_name)];
+        for hunk in &self.hunks {
+            output.push(hunk.header());
+            for line in &hunk.lines {
+                let sign = match line.kind {
+                    LineKind::Equal => ' ',
+                    LineKind::Delete => '-',
+                    LineKind::Insert => '+',
+                };
+                output.push(format!("{}{}", sign, line.content));
+            }
+        }
+        output.join("\n")
+    }
+}
+
+/// Diff a deleted/inserted pair word by word and record the spans on both
+fn pair_lines(lines: &mut [DiffLine], old: usize, new: usize) {
+    let old_content = lines[old].content.clone();
+    let new_content = lines[new].content.clone();
+    let word_diff = TextDiff::from_words(old_content.as_str(), new_content.as_str());
+
+    let mut old_spans = Vec::new();
+    let mut new_spans = Vec::new();
+    let (mut old_pos, mut new_pos) = (0, 0);
+    for change in word_diff.iter_all_changes() {
+        let len = change.value().len();
+        match change.tag() {
+            ChangeTag::Equal => {
+                push_span(&mut old_spans, old_pos, len, SpanKind::Equal);
+                push_span(&mut new_spans, new_pos, len, SpanKind::Equal);
+                old_pos += len;
+                new_pos += len;
+            }
+            ChangeTag::Delete => {
+                push_span(&mut old_spans, old_pos, len, SpanKind::Changed);
+                old_pos += len;
+            }
+            ChangeTag::Insert => {
+                push_span(&mut new_spans, new_pos, len, SpanKind::Changed);
+                new_pos += len;
+            }
+        }
+    }
+
+    let whitespace_only = old_content
+        .split_whitespace()
+        .eq(new_content.split_whitespace());
+    lines[old].spans = old_spans;
+    lines[old].whitespace_only = whitespace_only;
+    lines[new].spans = new_spans;
+    lines[new].whitespace_only = whitespace_only;
+}
+
+/// Append a span, merging it into the previous one when the kinds match
+fn push_span(spans: &mut Vec<WordSpan>, start: usize, len: usize, kind: SpanKind) {
+    if len == 0 {
+        return;
+    }
+    match spans.last_mut() {
+        Some(last) if last.kind == kind && last.end == start => last.end = start + len,
+        _ => spans.push(WordSpan {
+            start,
+            end: start + len,
+            kind,
+        }),
+    }
+}
+
+fn strip_line_ending(line: &str) -> &str {
+    line.strip_suffix('\n')
+        .map(|l| l.strip_suffix('\r').unwrap_or(l))
+        .unwrap_or(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changed(line: &DiffLine) -> Vec<&str> {
+        line.spans
+            .iter()
+            .filter(|span| span.kind == SpanKind::Changed)
+            .map(|span| &line.content[span.start..span.end])
+            .collect()
+    }
+
+    #[test]
+    fn test_word_spans_for_modified_line() {
+        let diff = StructuredDiff::compute(
+            "fn main() {\n    let x = 1;\n}\n",
+            "fn main() {\n    let y = 1;\n}\n",
+            1,
+        );
+
+        assert_eq!((diff.insertions, diff.deletions), (1, 1));
+        let hunk = &diff.hunks[0];
+        assert_eq!(hunk.header(), "@@ -1,3 +1,3 @@");
+        assert_eq!(
+            hunk.rows[1],
+            SideBySideRow {
+                old: Some(1),
+                new: Some(2)
+            }
+        );
+        assert_eq!(changed(&hunk.lines[1]), vec!["x"]);
+        assert_eq!(changed(&hunk.lines[2]), vec!["y"]);
+        assert!(!hunk.lines[1].whitespace_only);
+        assert_eq!(hunk.lines[1].spans.last().unwrap().end, 14);
+    }
+
+    #[test]
+    fn test_moved_line() {
+        let diff = StructuredDiff::compute("a\nb\nc\nd\n", "b\nc\nd\na\n", 3);
+        let lines: Vec<&DiffLine> = diff.hunks.iter().flat_map(|h| &h.lines).collect();
+
+        let deleted = lines.iter().find(|l| l.kind == LineKind::Delete).unwrap();
+        let inserted = lines.iter().find(|l| l.kind == LineKind::Insert).unwrap();
+        assert_eq!(deleted.content, "a");
+        assert_eq!(deleted.moved, Some(4));
+        assert_eq!(inserted.moved, Some(1));
+        assert!(deleted.spans.is_empty());
+    }
+
+    #[test]
+    fn test_whitespace_only_change() {
+        let diff = StructuredDiff::compute("if x {\n\treturn;\n}\n", "if x {\n    return;\n}\n", 0);
+        let hunk = &diff.hunks[0];
+
+        assert_eq!(hunk.lines.len(), 2);
+        assert!(hunk.lines.iter().all(|l| l.whitespace_only));
+        assert_eq!(changed(&hunk.lines[0]), vec!["\t"]);
+        assert_eq!(changed(&hunk.lines[1]), vec!["    "]);
+    }
+
+    #[test]
+    fn test_crlf_line_endings() {
+        let diff = StructuredDiff::compute("one\r\ntwo\r\n", "one\ntwo 2\n", 0);
+        let hunk = &diff.hunks[0];
+
+        // Content is reported without terminators on either side
+        assert!(hunk.lines.iter().all(|l| !l.content.ends_with('\r')));
+        assert_eq!(hunk.rows.len(), 2);
+        let (old_one, new_one) = (&hunk.lines[0], &hunk.lines[2]);
+        assert_eq!(old_one.content, new_one.content);
+        assert!(old_one.whitespace_only && new_one.whitespace_only);
+        assert!(changed(old_one).is_empty());
+        assert_eq!(changed(&hunk.lines[3]), vec![" 2"]);
+    }
+
+    #[test]
+    fn test_serializes_compactly() {
+        let diff = StructuredDiff::compute("a\n", "b\n", 0);
+        let json = serde_json::to_value(&diff).unwrap();
+
+        assert_eq!(json["hunks"][0]["lines"][0]["kind"], "delete");
+        assert!(json["hunks"][0]["lines"][0].get("moved").is_none());
+        let back: StructuredDiff = serde_json::from_value(json).unwrap();
+        assert_eq!(back, diff);
+    }
+}
@@ -10,8 +10,12 @@ use std::path::PathBuf;
 use uuid::Uuid;
 
 use crate::file_ops::{EditStrategy, FileEditRequest, FileOperations, FileOperationsConfig};
+use crate::hunks::{split_diff_into_hunks, Hunk};
 use crate::registry::{CommandContext, CommandDescriptor, CommandExecutor};
 
+/// Lines of unchanged context attached to each proposed hunk
+const HUNK_CONTEXT_LINES: usize = 3;
+
 /// Arguments for the edit command - enhanced with new edit strategies
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EditArgs {
@@ -23,6 +27,10 @@ pub struct EditArgs {
     pub create_if_missing: Option<bool>,
     /// Whether to make a backup before editing
     pub backup: Option<bool>,
+    /// Return the edit as a list of hunks for review instead of writing it
+    pub interactive_hunks: Option<bool>,
+    /// Edit a file that looks binary byte for byte instead of refusing
+    pub force_binary: Option<bool>,
 }
 
 /// Edit strategy arguments that map to the file_ops EditStrategy enum
@@ -45,6 +53,8 @@ pub enum EditStrategyArgs {
         end: Option<usize>,
         content: String,
     },
+    /// Apply the accepted hunks of a diff
+    ApplyHunks { hunks: Vec<Hunk> },
 }
 
 impl From<EditStrategyArgs> for EditStrategy {
@@ -72,6 +82,7 @@ impl From<EditStrategyArgs> for EditStrategy {
                 end,
                 content,
             },
+            EditStrategyArgs::ApplyHunks { hunks } => EditStrategy::ApplyHunks { hunks },
         }
     }
 }
@@ -95,6 +106,8 @@ impl EditCommand {
                 sandbox_level_required: SandboxLevel::WorkspaceWrite,
                 supports_preview: true,
                 supports_dry_run: true,
+                timeout: None,
+                alias_of: None,
             },
             file_ops: FileOperations::new(FileOperationsConfig {
                 backup_directory: None,
@@ -117,6 +130,8 @@ impl EditCommand {
                 sandbox_level_required: SandboxLevel::WorkspaceWrite,
                 supports_preview: true,
                 supports_dry_run: true,
+                timeout: None,
+                alias_of: None,
             },
             file_ops: FileOperations::new(config),
         }
@@ -146,8 +161,13 @@ impl EditCommand {
 
         // Try to generate preview content by reading current file and applying strategy
         let preview_description = if validated_path.exists() {
-            match self.file_ops.safe_read_file(&validated_path).await {
-                Ok(original_content) => {
+            match self
+                .file_ops
+                .read_text(&validated_path, args.force_binary.unwrap_or(false))
+                .await
+            {
+                Ok(original) => {
+                    let original_content = original.content;
                     let strategy: EditStrategy = args.strategy.clone().into();
                     match self
                         .file_ops
@@ -166,8 +186,12 @@ impl EditCommand {
                                                 diff.len()
                                             )
                                         } else {
-                                            diff
+                                            diff.clone()
                                         },
+                                        diff: Some(diff),
+                                        bytes_changed: self
+                                            .file_ops
+                                            .bytes_changed(&original_content, &new_content),
                                     });
                                     format!(
                                         "Edit file: {} with strategy: {:?}",
@@ -183,15 +207,19 @@ impl EditCommand {
                         Err(e) => format!("Edit file: {} (preview failed: {})", args.file_path, e),
                     }
                 }
-                Err(_) => format!(
-                    "Edit file: {} (cannot read current content)",
-                    args.file_path
+                Err(e) => format!(
+                    "Edit file: {} (cannot read current content: {})",
+                    args.file_path, e
                 ),
             }
         } else if args.create_if_missing.unwrap_or(false) {
+            let strategy: EditStrategy = args.strategy.clone().into();
+            let new_content = self.file_ops.apply_edit_strategy("", &strategy)?;
             actions.push(PreviewAction::WriteFile {
                 path: validated_path.to_string_lossy().to_string(),
                 content: "New file will be created".to_string(),
+                diff: None,
+                bytes_changed: new_content.len() as u64,
             });
             format!("Create new file: {}", args.file_path)
         } else {
@@ -213,6 +241,66 @@ impl EditCommand {
         })
     }
 
+    /// Compute the edit and split it into hunks without touching the file
+    ///
+    /// The hunks are returned as structured data so a caller such as the TUI
+    /// can let the user accept or reject each one, then apply the selection
+    /// with the `ApplyHunks` strategy.
+    async fn propose_hunks(
+        &self,
+        args: &EditArgs,
+        context: &CommandContext,
+    ) -> Result<(String, serde_json::Value)> {
+        let validated_path = self
+            .file_ops
+            .validate_file_path(
+                &PathBuf::from(&args.file_path),
+                &context.sandbox_level,
+                context.workspace_path.as_deref(),
+            )
+            .await?;
+
+        let original_content = if validated_path.exists() {
+            self.file_ops
+                .read_text(&validated_path, args.force_binary.unwrap_or(false))
+                .await?
+                .content
+        } else if args.create_if_missing.unwrap_or(false) {
+            String::new()
+        } else {
+            return Err(FennecError::Command(Box::new(std::io::Error::other(format!(
+                "File {} does not exist and create_if_missing is false",
+                validated_path.display()
+            ))))
+            .into());
+        };
+
+        let strategy: EditStrategy = args.strategy.clone().into();
+        let new_content = self
+            .file_ops
+            .apply_edit_strategy(&original_content, &strategy)?;
+        let hunks = split_diff_into_hunks(
+            validated_path.clone(),
+            &original_content,
+            &new_content,
+            HUNK_CONTEXT_LINES,
+        );
+
+        let mut lines = vec![format!(
+            "{} hunk(s) proposed for {}",
+            hunks.len(),
+            validated_path.display()
+        )];
+        lines.extend(hunks.iter().map(|hunk| hunk.summary()));
+
+        let data = serde_json::json!({
+            "file_path": validated_path,
+            "hunks": hunks,
+        });
+
+        Ok((lines.join("\n"), data))
+    }
+
     /// Perform the file edit operation using the new file operations module
     async fn perform_edit(&self, args: &EditArgs, context: &CommandContext) -> Result<String> {
         // Check for cancellation
@@ -232,6 +320,7 @@ impl EditCommand {
             strategy,
             create_backup: args.backup.unwrap_or(false),
             create_if_missing: args.create_if_missing.unwrap_or(false),
+            force_binary: args.force_binary.unwrap_or(false),
         };
 
         if context.dry_run {
@@ -246,7 +335,10 @@ impl EditCommand {
                 .await?;
 
             let original_content = if validated_path.exists() {
-                self.file_ops.safe_read_file(&validated_path).await?
+                self.file_ops
+                    .read_text(&validated_path, request.force_binary)
+                    .await?
+                    .content
             } else if request.create_if_missing {
                 String::new()
             } else {
@@ -334,18 +426,39 @@ impl CommandExecutor for EditCommand {
             )))
         })?;
 
+        if args.interactive_hunks.unwrap_or(false) {
+            return match self.propose_hunks(&args, context).await {
+                Ok((output, data)) => Ok(CommandResult {
+                    command_id: Uuid::new_v4(),
+                    success: true,
+                    output,
+                    error: None,
+                    data: Some(data),
+                }),
+                Err(e) => Ok(CommandResult {
+                    command_id: Uuid::new_v4(),
+                    success: false,
+                    output: String::new(),
+                    error: Some(e.to_string()),
+                    data: None,
+                }),
+            };
+        }
+
         match self.perform_edit(&args, context).await {
             Ok(output) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: true,
                 output,
                 error: None,
+                data: None,
             }),
             Err(e) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
+                data: None,
             }),
         }
     }
@@ -508,6 +621,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -546,6 +661,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -582,6 +699,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -618,6 +737,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -630,4 +751,105 @@ mod tests {
         let unchanged_content = tokio::fs::read_to_string(&test_file).await.unwrap();
         assert_eq!(unchanged_content, initial_content);
     }
+
+    #[tokio::test]
+    async fn test_preview_carries_diff_and_bytes_changed() {
+        let temp_dir = tempdir().unwrap();
+        let test_file = temp_dir.path().join("test.txt");
+        write(&test_file, "line 1\nline 2\nline 3").await.unwrap();
+
+        let command = EditCommand::new();
+        let args = serde_json::json!({
+            "file_path": test_file.to_string_lossy(),
+            "strategy": {
+                "type": "SearchReplace",
+                "data": { "search": "line 2", "replace": "line two" }
+            }
+        });
+        let context = CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: Some(temp_dir.path().to_string_lossy().to_string()),
+            sandbox_level: SandboxLevel::FullAccess,
+            dry_run: false,
+            preview_only: true,
+            cancellation_token: CancellationToken::new(),
+            action_log: None,
+            timeout: None,
+            progress: None,
+        };
+
+        let preview = command.preview(&args, &context).await.unwrap();
+        let Some(PreviewAction::WriteFile {
+            diff,
+            bytes_changed,
+            ..
+        }) = preview.actions.last()
+        else {
+            panic!("expected a write, got {:?}", preview.actions);
+        };
+        let diff = diff.as_deref().unwrap();
+        assert!(diff.contains("-line 2\n+line two"), "{}", diff);
+        // "line 2\n" removed, "line two\n" added
+        assert_eq!(*bytes_changed, 16);
+    }
+
+    #[tokio::test]
+    async fn test_interactive_hunks_round_trip() {
+        let temp_dir = tempdir().unwrap();
+        let test_file = temp_dir.path().join("test.txt");
+        write(
+            &test_file,
+            "fn a() {}\nfn b() {}\nfn c() {}\nfn d() {}\nfn e() {}\n",
+        )
+        .await
+        .unwrap();
+
+        let command = EditCommand::new();
+        let context = CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: Some(temp_dir.path().to_string_lossy().to_string()),
+            sandbox_level: SandboxLevel::FullAccess,
+            dry_run: false,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log: None,
+            timeout: None,
+            progress: None,
+        };
+
+        let args = serde_json::json!({
+            "file_path": test_file.to_string_lossy(),
+            "strategy": {
+                "type": "Replace",
+                "data": { "content": "fn A() {}\nfn b() {}\nfn c() {}\nfn d() {}\nfn E() {}\n" }
+            },
+            "interactive_hunks": true
+        });
+
+        let result = command.execute(&args, &context).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(
+            tokio::fs::read_to_string(&test_file).await.unwrap(),
+            "fn a() {}\nfn b() {}\nfn c() {}\nfn d() {}\nfn e() {}\n"
+        );
+
+        let mut hunks: Vec<Hunk> =
+            serde_json::from_value(result.data.unwrap()["hunks"].clone()).unwrap();
+        assert_eq!(hunks.len(), 2);
+        hunks[0].reject();
+        hunks[1].accept();
+
+        let args = serde_json::json!({
+            "file_path": test_file.to_string_lossy(),
+            "strategy": { "type": "ApplyHunks", "data": { "hunks": hunks } }
+        });
+        let result = command.execute(&args, &context).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(
+            tokio::fs::read_to_string(&test_file).await.unwrap(),
+            "fn a() {}\nfn b() {}\nfn c() {}\nfn d() {}\nfn E() {}\n"
+        );
+    }
 }
@@ -0,0 +1,248 @@
+//! Text encoding detection, so files are edited in the encoding and line
+//! endings they were written in and binaries are not edited as text.
+
+use serde::{Deserialize, Serialize};
+
+/// Bytes looked at when only deciding whether a file is text
+pub const SNIFF_LEN: usize = 8192;
+
+/// Share of control bytes above which a file is taken to be binary
+const MAX_CONTROL_RATIO: f64 = 0.1;
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF16BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+/// Encoding of a text file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextEncoding {
+    Utf8,
+    /// UTF-8 starting with a byte order mark
+    Utf8Bom,
+    /// UTF-16, little endian, starting with a byte order mark
+    Utf16Le,
+    /// UTF-16, big endian, starting with a byte order mark
+    Utf16Be,
+    /// ISO-8859-1, assumed for text that is not valid UTF-8
+    Latin1,
+}
+
+/// Line breaks of a text file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
+}
+
+/// How a text file is stored, so it can be written back the same way
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextFormat {
+    pub encoding: TextEncoding,
+    pub line_ending: LineEnding,
+}
+
+impl Default for TextFormat {
+    fn default() -> Self {
+        Self {
+            encoding: TextEncoding::Utf8,
+            line_ending: LineEnding::Lf,
+        }
+    }
+}
+
+/// Encoding of `bytes`, or `None` when they look binary. `bytes` may be a
+/// prefix of the file, cut anywhere.
+pub fn detect_encoding(bytes: &[u8]) -> Option<TextEncoding> {
+    if bytes.starts_with(UTF8_BOM) {
+        return Some(TextEncoding::Utf8Bom);
+    }
+    if bytes.starts_with(UTF16LE_BOM) {
+        return Some(TextEncoding::Utf16Le);
+    }
+    if bytes.starts_with(UTF16BE_BOM) {
+        return Some(TextEncoding::Utf16Be);
+    }
+    if looks_binary(bytes) {
+        return None;
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(_) => Some(TextEncoding::Utf8),
+        // A character cut off at the end of a prefix
+        Err(e) if e.error_len().is_none() => Some(TextEncoding::Utf8),
+        Err(_) => Some(TextEncoding::Latin1),
+    }
+}
+
+/// NUL bytes, or more control characters than text would have
+fn looks_binary(bytes: &[u8]) -> bool {
+    if bytes.contains(&0) {
+        return true;
+    }
+    let control = bytes
+        .iter()
+        .filter(|&&b| (b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0C | 0x1B)) || b == 0x7F)
+        .count();
+    !bytes.is_empty() && control as f64 / bytes.len() as f64 > MAX_CONTROL_RATIO
+}
+
+/// Decode a whole file. Line breaks are returned as `\n` when the file
+/// uses `\r\n` throughout; mixed line breaks are left as they are.
+/// `None` when the file looks binary or is not valid in its encoding.
+pub fn decode_text(bytes: &[u8]) -> Option<(String, TextFormat)> {
+    let encoding = detect_encoding(bytes)?;
+    let text = match encoding {
+        TextEncoding::Utf8 => String::from_utf8(bytes.to_vec()).ok()?,
+        TextEncoding::Utf8Bom => String::from_utf8(bytes[UTF8_BOM.len()..].to_vec()).ok()?,
+        TextEncoding::Utf16Le => decode_utf16(&bytes[UTF16LE_BOM.len()..], u16::from_le_bytes)?,
+        TextEncoding::Utf16Be => decode_utf16(&bytes[UTF16BE_BOM.len()..], u16::from_be_bytes)?,
+        TextEncoding::Latin1 => decode_latin1(bytes),
+    };
+
+    let line_ending = detect_line_ending(&text);
+    let text = match line_ending {
+        LineEnding::Crlf => text.replace("\r\n", "\n"),
+        LineEnding::Lf => text,
+    };
+    Some((
+        text,
+        TextFormat {
+            encoding,
+            line_ending,
+        },
+    ))
+}
+
+/// Each byte as the character of the same value, which keeps any bytes
+/// intact through [`encode_text`]
+pub fn decode_latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+fn decode_utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> Option<String> {
+    if !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| unit([pair[0], pair[1]]))
+        .collect();
+    String::from_utf16(&units).ok()
+}
+
+/// `\r\n` when every line break is one
+fn detect_line_ending(text: &str) -> LineEnding {
+    let breaks = text.matches('\n').count();
+    if breaks > 0 && text.matches("\r\n").count() == breaks {
+        LineEnding::Crlf
+    } else {
+        LineEnding::Lf
+    }
+}
+
+/// Encode `text`, whose line breaks are `\n`, as `format` says. Fails with
+/// the first character Latin-1 cannot hold.
+pub fn encode_text(text: &str, format: TextFormat) -> Result<Vec<u8>, char> {
+    let text = match format.line_ending {
+        LineEnding::Crlf => std::borrow::Cow::Owned(text.replace('\n', "\r\n")),
+        LineEnding::Lf => std::borrow::Cow::Borrowed(text),
+    };
+    Ok(match format.encoding {
+        TextEncoding::Utf8 => text.into_owned().into_bytes(),
+        TextEncoding::Utf8Bom => [UTF8_BOM, text.as_bytes()].concat(),
+        TextEncoding::Utf16Le => UTF16LE_BOM
+            .iter()
+            .copied()
+            .chain(text.encode_utf16().flat_map(u16::to_le_bytes))
+            .collect(),
+        TextEncoding::Utf16Be => UTF16BE_BOM
+            .iter()
+            .copied()
+            .chain(text.encode_utf16().flat_map(u16::to_be_bytes))
+            .collect(),
+        TextEncoding::Latin1 => text
+            .chars()
+            .map(|c| u8::try_from(c).map_err(|_| c))
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UTF8: &[u8] = include_bytes!("../tests/fixtures/encodings/utf8.txt");
+    const UTF8_BOM: &[u8] = include_bytes!("../tests/fixtures/encodings/utf8_bom.txt");
+    const UTF16LE_CRLF: &[u8] = include_bytes!("../tests/fixtures/encodings/utf16le_crlf.txt");
+    const UTF16BE: &[u8] = include_bytes!("../tests/fixtures/encodings/utf16be.txt");
+    const LATIN1_CRLF: &[u8] = include_bytes!("../tests/fixtures/encodings/latin1_crlf.txt");
+    const MIXED_ENDINGS: &[u8] = include_bytes!("../tests/fixtures/encodings/mixed_endings.txt");
+    const BINARY: &[u8] = include_bytes!("../tests/fixtures/encodings/binary.png");
+
+    #[test]
+    fn test_fixtures_decode_and_encode_back_unchanged() {
+        let cases = [
+            (UTF8, TextEncoding::Utf8, LineEnding::Lf),
+            (UTF8_BOM, TextEncoding::Utf8Bom, LineEnding::Lf),
+            (UTF16LE_CRLF, TextEncoding::Utf16Le, LineEnding::Crlf),
+            (UTF16BE, TextEncoding::Utf16Be, LineEnding::Lf),
+            (LATIN1_CRLF, TextEncoding::Latin1, LineEnding::Crlf),
+            (MIXED_ENDINGS, TextEncoding::Utf8, LineEnding::Lf),
+        ];
+        for (bytes, encoding, line_ending) in cases {
+            let (text, format) = decode_text(bytes).unwrap();
+            assert_eq!(
+                format,
+                TextFormat {
+                    encoding,
+                    line_ending
+                }
+            );
+            assert!(text.contains("line two"), "{:?}", text);
+            if line_ending == LineEnding::Crlf {
+                assert!(!text.contains('\r'), "{:?}", text);
+            }
+            assert_eq!(encode_text(&text, format).unwrap(), bytes, "{:?}", encoding);
+        }
+        assert_eq!(
+            decode_text(UTF8).unwrap().0,
+            "café au lait\nline two\nnaïve — done\n"
+        );
+        assert_eq!(
+            decode_text(LATIN1_CRLF).unwrap().0,
+            "café au lait\nline two\nnaïve, done\n"
+        );
+    }
+
+    #[test]
+    fn test_binary_and_prefix_detection() {
+        assert_eq!(detect_encoding(BINARY), None);
+        assert_eq!(decode_text(BINARY), None);
+        // Latin-1 bytes survive a round trip through characters
+        assert_eq!(
+            encode_text(
+                &decode_latin1(BINARY),
+                TextFormat {
+                    encoding: TextEncoding::Latin1,
+                    line_ending: LineEnding::Lf,
+                }
+            )
+            .unwrap(),
+            BINARY
+        );
+
+        // A prefix may end inside a character
+        assert_eq!(detect_encoding(&UTF8[..3]), Some(TextEncoding::Utf8));
+        assert_eq!(detect_encoding(b""), Some(TextEncoding::Utf8));
+        assert_eq!(detect_encoding(b"\x01\x02\x03 text"), None);
+
+        let latin1 = TextFormat {
+            encoding: TextEncoding::Latin1,
+            line_ending: LineEnding::Lf,
+        };
+        assert_eq!(encode_text("caf\u{e9}", latin1).unwrap(), b"caf\xe9");
+        assert_eq!(encode_text("5 \u{20ac}", latin1), Err('\u{20ac}'));
+    }
+}
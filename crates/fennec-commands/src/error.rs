@@ -1,4 +1,4 @@
-use fennec_core::error::{ErrorCategory, ErrorInfo, ErrorSeverity, RecoveryAction};
+use fennec_core::error::{DomainError, ErrorCategory, ErrorInfo, ErrorSeverity, RecoveryAction};
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, CommandError>;
@@ -101,6 +101,9 @@ pub enum CommandError {
     #[error("Content generation failed: {reason}")]
     ContentGenerationFailed { reason: String, operation: String },
 
+    #[error("'{path}' appears to be binary, so it cannot be edited as text. Use --force-binary to edit its bytes anyway")]
+    BinaryFile { path: String },
+
     #[error("Text encoding error: {reason}. Please check file encoding")]
     EncodingError {
         reason: String,
@@ -153,6 +156,39 @@ pub enum CommandError {
 }
 
 impl ErrorInfo for CommandError {
+    fn code(&self) -> &'static str {
+        match self {
+            CommandError::InvalidArgument { .. } => "FEN-3001",
+            CommandError::MissingArgument { .. } => "FEN-3002",
+            CommandError::InvalidArgumentCombination { .. } => "FEN-3003",
+            CommandError::ArgumentOutOfRange { .. } => "FEN-3004",
+            CommandError::ExecutionFailed { .. } => "FEN-3005",
+            CommandError::Timeout { .. } => "FEN-3006",
+            CommandError::Cancelled { .. } => "FEN-3007",
+            CommandError::PreviewFailed { .. } => "FEN-3008",
+            CommandError::FileNotFound { .. } => "FEN-3009",
+            CommandError::PermissionDenied { .. } => "FEN-3010",
+            CommandError::DirectoryNotFound { .. } => "FEN-3011",
+            CommandError::FileTooLarge { .. } => "FEN-3012",
+            CommandError::UnsupportedFileType { .. } => "FEN-3013",
+            CommandError::SandboxViolation { .. } => "FEN-3014",
+            CommandError::ApprovalRequired { .. } => "FEN-3015",
+            CommandError::SecurityDenied { .. } => "FEN-3016",
+            CommandError::ContentParsingFailed { .. } => "FEN-3017",
+            CommandError::ContentGenerationFailed { .. } => "FEN-3018",
+            CommandError::EncodingError { .. } => "FEN-3019",
+            CommandError::ResourceLimitExceeded { .. } => "FEN-3020",
+            CommandError::DependencyFailed { .. } => "FEN-3021",
+            CommandError::ServiceUnavailable { .. } => "FEN-3022",
+            CommandError::MemoryService { .. } => "FEN-3023",
+            CommandError::ProviderService { .. } => "FEN-3024",
+            CommandError::SecurityService { .. } => "FEN-3025",
+            CommandError::Io { .. } => "FEN-3026",
+            CommandError::Generic { .. } => "FEN-3027",
+            CommandError::BinaryFile { .. } => "FEN-3028",
+        }
+    }
+
     fn category(&self) -> ErrorCategory {
         match self {
             // User input errors
@@ -164,6 +200,7 @@ impl ErrorInfo for CommandError {
             | CommandError::DirectoryNotFound { .. }
             | CommandError::UnsupportedFileType { .. }
             | CommandError::ContentParsingFailed { .. }
+            | CommandError::BinaryFile { .. }
             | CommandError::EncodingError { .. } => ErrorCategory::User,
 
             // Security errors
@@ -205,6 +242,7 @@ impl ErrorInfo for CommandError {
             | CommandError::DirectoryNotFound { .. }
             | CommandError::UnsupportedFileType { .. }
             | CommandError::ContentParsingFailed { .. }
+            | CommandError::BinaryFile { .. }
             | CommandError::EncodingError { .. } => ErrorSeverity::Error,
 
             // Security errors are important but not critical
@@ -351,6 +389,17 @@ impl ErrorInfo for CommandError {
                 ]
             }
 
+            CommandError::BinaryFile { .. } => {
+                vec![
+                    RecoveryAction::RetryWithChanges(
+                        "Use --force-binary to edit the file byte for byte".to_string(),
+                    ),
+                    RecoveryAction::ManualAction(
+                        "Check that this is the file you meant".to_string(),
+                    ),
+                ]
+            }
+
             CommandError::EncodingError {
                 expected_encoding, ..
             } => {
@@ -462,7 +511,7 @@ impl From<std::io::Error> for CommandError {
 
 impl From<CommandError> for fennec_core::FennecError {
     fn from(err: CommandError) -> Self {
-        fennec_core::FennecError::Command(Box::new(err))
+        fennec_core::FennecError::Command(Box::new(DomainError::new(err)))
     }
 }
 
@@ -815,8 +864,13 @@ mod tests {
             reason: "invalid".to_string(),
             expected: "valid".to_string(),
         };
+        let code = cmd_err.code();
+        let user_message = cmd_err.user_message();
         let fennec_err: fennec_core::FennecError = cmd_err.into();
         assert!(matches!(fennec_err, fennec_core::FennecError::Command(_)));
+        assert_eq!(fennec_err.code(), code);
+        assert_eq!(fennec_err.user_message(), user_message);
+        assert!(fennec_err.domain_source::<CommandError>().is_some());
     }
 
     #[test]
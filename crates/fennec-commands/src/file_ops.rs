@@ -1,11 +1,28 @@
+use crate::action_log::Action;
+use crate::encoding::{decode_latin1, decode_text, encode_text, TextEncoding, TextFormat};
+use crate::error::CommandError;
+use crate::hunks::{apply_selected_hunks, Hunk, HunkStatus};
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use fennec_core::error::FennecError;
 use fennec_security::SandboxLevel;
+use fennec_telemetry::metrics;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use tokio::fs;
+use tracing::warn;
 use uuid::Uuid;
 
+/// Trash staging area, relative to the workspace root
+pub const TRASH_DIR: &str = ".fennec/trash";
+
+/// Per-entry metadata file inside a trash entry directory
+const TRASH_MANIFEST: &str = "manifest.json";
+
+/// Subdirectory of a trash entry holding the deleted item
+const TRASH_FILES: &str = "files";
+
 /// Different strategies for editing files
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum EditStrategy {
@@ -25,6 +42,8 @@ pub enum EditStrategy {
         end: Option<usize>,
         content: String,
     },
+    /// Apply the accepted hunks of a diff; rejected and pending hunks are skipped
+    ApplyHunks { hunks: Vec<Hunk> },
 }
 
 /// Request for editing a file
@@ -34,6 +53,16 @@ pub struct FileEditRequest {
     pub strategy: EditStrategy,
     pub create_backup: bool,
     pub create_if_missing: bool,
+    /// Edit a file that looks binary anyway, one character per byte
+    pub force_binary: bool,
+}
+
+/// A text file's content and how it is stored
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedText {
+    /// The text, with `\n` line breaks if the file uses `\r\n` throughout
+    pub content: String,
+    pub format: TextFormat,
 }
 
 /// Result of a file edit operation
@@ -47,6 +76,73 @@ pub struct FileEditResult {
     pub bytes_written: usize,
 }
 
+/// Outcome for a single file within a committed transaction
+#[derive(Debug, Clone)]
+pub struct TransactionFileResult {
+    pub path: PathBuf,
+    /// Whether the file did not exist before the transaction
+    pub created: bool,
+    pub result: FileEditResult,
+}
+
+/// Result of applying several file edits as one all-or-nothing transaction
+#[derive(Debug, Clone)]
+pub struct TransactionResult {
+    pub transaction_id: Uuid,
+    pub files: Vec<TransactionFileResult>,
+}
+
+impl TransactionResult {
+    /// Build a single undoable action covering every file in the transaction
+    pub fn to_action(&self, command: String, description: String) -> Action {
+        let actions = self
+            .files
+            .iter()
+            .map(|file| {
+                if file.created {
+                    Action::file_created(command.clone(), file.path.clone(), String::new())
+                } else {
+                    Action::file_modified(
+                        command.clone(),
+                        file.path.clone(),
+                        file.result.original_content.clone().into_bytes(),
+                        file.result.new_content.clone().into_bytes(),
+                        String::new(),
+                    )
+                }
+            })
+            .collect();
+
+        Action::transaction(command, actions, description)
+    }
+}
+
+/// A deleted file or directory staged in the workspace trash
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrashEntry {
+    /// Name of the entry's directory under [`TRASH_DIR`]
+    pub id: String,
+    /// Where the item lived, relative to the workspace
+    pub original_path: PathBuf,
+    pub deleted_at: DateTime<Utc>,
+    pub is_directory: bool,
+    pub size_bytes: u64,
+    /// Where the item lives inside the trash
+    #[serde(skip)]
+    pub trash_path: PathBuf,
+}
+
+/// A transaction edit that has been validated and written to a temp file
+struct StagedEdit {
+    path: PathBuf,
+    temp_path: PathBuf,
+    original_bytes: Option<Vec<u8>>,
+    format: TextFormat,
+    create_backup: bool,
+    backup_path: Option<PathBuf>,
+    result: FileEditResult,
+}
+
 /// Configuration for file operations
 #[derive(Debug, Clone)]
 pub struct FileOperationsConfig {
@@ -229,6 +325,13 @@ impl FileOperations {
 
     /// Safely read a file with encoding detection
     pub async fn safe_read_file(&self, path: &Path) -> Result<String> {
+        Ok(self.read_text(path, false).await?.content)
+    }
+
+    /// Read a text file and detect how it is stored. Files that look
+    /// binary are refused unless `force_binary` is set, in which case they
+    /// are read as Latin-1 so every byte survives an edit.
+    pub async fn read_text(&self, path: &Path, force_binary: bool) -> Result<DecodedText> {
         // Check file size
         let metadata = fs::metadata(path).await.map_err(|e| {
             FennecError::Command(Box::new(std::io::Error::new(
@@ -257,89 +360,34 @@ impl FileOperations {
             )))
         })?;
 
-        // Detect encoding if enabled
-        if self.config.detect_encoding {
-            self.decode_with_detection(&bytes, path)
-        } else {
+        if !self.config.detect_encoding {
             // Default to UTF-8
-            String::from_utf8(bytes).map_err(|e| {
+            let content = String::from_utf8(bytes).map_err(|e| {
                 FennecError::Command(Box::new(std::io::Error::new(
                     std::io::ErrorKind::Other,
                     format!("File {} is not valid UTF-8: {}", path.display(), e),
                 )))
-                .into()
-            })
-        }
-    }
-
-    /// Decode bytes with encoding detection
-    fn decode_with_detection(&self, bytes: &[u8], path: &Path) -> Result<String> {
-        // Check for UTF-8 BOM
-        if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
-            return String::from_utf8(bytes[3..].to_vec()).map_err(|e| {
-                FennecError::Command(Box::new(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!(
-                        "File {} has UTF-8 BOM but invalid UTF-8 content: {}",
-                        path.display(),
-                        e
-                    ),
-                )))
-                .into()
-            });
-        }
-
-        // Check for UTF-16 BOMs
-        if bytes.starts_with(&[0xFF, 0xFE]) {
-            return <String as StringFromUtf16>::from_utf16le(&bytes[2..]).map_err(|e| {
-                FennecError::Command(Box::new(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!(
-                        "File {} has UTF-16LE BOM but invalid content: {}",
-                        path.display(),
-                        e
-                    ),
-                )))
-                .into()
-            });
-        }
-
-        if bytes.starts_with(&[0xFE, 0xFF]) {
-            return <String as StringFromUtf16>::from_utf16be(&bytes[2..]).map_err(|e| {
-                FennecError::Command(Box::new(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!(
-                        "File {} has UTF-16BE BOM but invalid content: {}",
-                        path.display(),
-                        e
-                    ),
-                )))
-                .into()
+            })?;
+            return Ok(DecodedText {
+                content,
+                format: TextFormat::default(),
             });
         }
 
-        // Try UTF-8 first (most common)
-        if let Ok(utf8_string) = String::from_utf8(bytes.to_vec()) {
-            return Ok(utf8_string);
-        }
-
-        // Check if it's binary data
-        if bytes
-            .iter()
-            .any(|&b| b == 0 || (b < 32 && b != 9 && b != 10 && b != 13))
-        {
-            return Err(FennecError::Command(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!(
-                    "File {} appears to be binary, cannot edit as text",
-                    path.display()
-                ),
-            )))
-            .into());
+        match decode_text(&bytes) {
+            Some((content, format)) => Ok(DecodedText { content, format }),
+            None if force_binary => Ok(DecodedText {
+                content: decode_latin1(&bytes),
+                format: TextFormat {
+                    encoding: TextEncoding::Latin1,
+                    ..TextFormat::default()
+                },
+            }),
+            None => Err(CommandError::BinaryFile {
+                path: path.display().to_string(),
+            }
+            .into()),
         }
-
-        // Fall back to lossy UTF-8 conversion
-        Ok(String::from_utf8_lossy(bytes).to_string())
     }
 
     /// Create a backup of a file
@@ -402,8 +450,49 @@ impl FileOperations {
         Ok(backup_path)
     }
 
-    /// Atomically write content to a file
+    /// Atomically write content to a file, keeping the encoding and line
+    /// endings of the file it replaces
     pub async fn atomic_write_file(&self, path: &Path, content: &str) -> Result<usize> {
+        let format = if self.config.detect_encoding {
+            fs::read(path)
+                .await
+                .ok()
+                .and_then(|bytes| decode_text(&bytes))
+                .map(|(_, format)| format)
+                .unwrap_or_default()
+        } else {
+            TextFormat::default()
+        };
+        self.write_text(path, content, format).await
+    }
+
+    /// Atomically write `content`, whose line breaks are `\n`, stored as
+    /// `format` says
+    pub async fn write_text(
+        &self,
+        path: &Path,
+        content: &str,
+        format: TextFormat,
+    ) -> Result<usize> {
+        let bytes = self.encode(path, content, format)?;
+        let started = Instant::now();
+        let written = self.write_file(path, &bytes).await?;
+        metrics::record_file_write(&path.to_string_lossy(), started.elapsed());
+        Ok(written)
+    }
+
+    fn encode(&self, path: &Path, content: &str, format: TextFormat) -> Result<Vec<u8>> {
+        encode_text(content, format).map_err(|c| {
+            CommandError::EncodingError {
+                reason: format!("{:?} cannot be written as {:?}", c, format.encoding),
+                file_path: path.display().to_string(),
+                expected_encoding: "UTF-8".to_string(),
+            }
+            .into()
+        })
+    }
+
+    async fn write_file(&self, path: &Path, content: &[u8]) -> Result<usize> {
         if !self.config.atomic_writes {
             // Direct write (less safe but simpler)
             fs::write(path, content).await.map_err(|e| {
@@ -553,6 +642,19 @@ impl FileOperations {
 
                 Ok(new_lines.join("\n"))
             }
+
+            EditStrategy::ApplyHunks { hunks } => {
+                let selection: Vec<HunkStatus> = hunks
+                    .iter()
+                    .map(|hunk| match hunk.status {
+                        HunkStatus::Accepted => HunkStatus::Accepted,
+                        HunkStatus::Rejected | HunkStatus::Pending => HunkStatus::Rejected,
+                    })
+                    .collect();
+
+                apply_selected_hunks(original_content, hunks, &selection)
+                    .map_err(|e| FennecError::Command(Box::new(e)).into())
+            }
         }
     }
 
@@ -569,8 +671,9 @@ impl FileOperations {
             .await?;
 
         // Read existing content or create empty if file doesn't exist
-        let original_content = if validated_path.exists() {
-            self.safe_read_file(&validated_path).await?
+        let original = if validated_path.exists() {
+            self.read_text(&validated_path, request.force_binary)
+                .await?
         } else if request.create_if_missing {
             // Create parent directories if needed
             if let Some(parent) = validated_path.parent() {
@@ -581,7 +684,10 @@ impl FileOperations {
                     )))
                 })?;
             }
-            String::new()
+            DecodedText {
+                content: String::new(),
+                format: TextFormat::default(),
+            }
         } else {
             return Err(FennecError::Command(Box::new(std::io::Error::new(
                 std::io::ErrorKind::Other,
@@ -592,6 +698,10 @@ impl FileOperations {
             )))
             .into());
         };
+        let DecodedText {
+            content: original_content,
+            format,
+        } = original;
 
         // Apply the edit strategy
         let new_content = self.apply_edit_strategy(&original_content, &request.strategy)?;
@@ -606,9 +716,9 @@ impl FileOperations {
             None
         };
 
-        // Write the new content atomically
+        // Write the new content atomically, stored as the original was
         let bytes_written = self
-            .atomic_write_file(&validated_path, &new_content)
+            .write_text(&validated_path, &new_content, format)
             .await?;
 
         Ok(FileEditResult {
@@ -621,6 +731,195 @@ impl FileOperations {
         })
     }
 
+    /// Apply several edits as one all-or-nothing transaction
+    ///
+    /// Every edit is validated and written to a temporary file before any
+    /// target is touched; the temporaries are then renamed into place. If any
+    /// step fails, files already replaced are restored and no edit remains.
+    /// Multiple requests for the same path are applied in order.
+    pub async fn apply_transaction(
+        &self,
+        requests: Vec<FileEditRequest>,
+        sandbox_level: &SandboxLevel,
+        workspace_path: Option<&str>,
+    ) -> Result<TransactionResult> {
+        let mut staged: Vec<StagedEdit> = Vec::new();
+
+        let outcome = self
+            .stage_transaction(requests, sandbox_level, workspace_path, &mut staged)
+            .await;
+        let outcome = match outcome {
+            Ok(()) => self.commit_transaction(&staged).await,
+            Err(e) => Err(e),
+        };
+
+        match outcome {
+            Ok(()) => Ok(TransactionResult {
+                transaction_id: Uuid::new_v4(),
+                files: staged
+                    .into_iter()
+                    .map(|edit| TransactionFileResult {
+                        path: edit.path,
+                        created: edit.original_bytes.is_none(),
+                        result: edit.result,
+                    })
+                    .collect(),
+            }),
+            Err(e) => {
+                for edit in &staged {
+                    let _ = fs::remove_file(&edit.temp_path).await;
+                    if let Some(backup_path) = &edit.backup_path {
+                        let _ = fs::remove_file(backup_path).await;
+                    }
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Validate every request and write its new content to a temporary file
+    async fn stage_transaction(
+        &self,
+        requests: Vec<FileEditRequest>,
+        sandbox_level: &SandboxLevel,
+        workspace_path: Option<&str>,
+        staged: &mut Vec<StagedEdit>,
+    ) -> Result<()> {
+        let mut planned: Vec<StagedEdit> = Vec::new();
+
+        for request in requests {
+            let validated_path = self
+                .validate_file_path(&request.path, sandbox_level, workspace_path)
+                .await?;
+
+            // Later edits to the same file build on the earlier ones
+            if let Some(existing) = planned.iter_mut().find(|p| p.path == validated_path) {
+                let new_content = self
+                    .apply_edit_strategy(&existing.result.new_content, &request.strategy)
+                    .map_err(|e| transaction_error(&validated_path, e))?;
+                existing.result.diff =
+                    self.generate_diff(&existing.result.original_content, &new_content)?;
+                existing.result.bytes_written = new_content.len();
+                existing.result.new_content = new_content;
+                existing.create_backup |= request.create_backup;
+                continue;
+            }
+
+            let (original_bytes, original) = if validated_path.exists() {
+                let text = self
+                    .read_text(&validated_path, request.force_binary)
+                    .await
+                    .map_err(|e| transaction_error(&validated_path, e))?;
+                let bytes = fs::read(&validated_path)
+                    .await
+                    .map_err(|e| transaction_error(&validated_path, e.into()))?;
+                (Some(bytes), text)
+            } else if request.create_if_missing {
+                (
+                    None,
+                    DecodedText {
+                        content: String::new(),
+                        format: TextFormat::default(),
+                    },
+                )
+            } else {
+                return Err(FennecError::Command(Box::new(std::io::Error::other(format!(
+                    "File {} does not exist and create_if_missing is false",
+                    validated_path.display()
+                ))))
+                .into());
+            };
+
+            let DecodedText {
+                content: original_content,
+                format,
+            } = original;
+            let new_content = self
+                .apply_edit_strategy(&original_content, &request.strategy)
+                .map_err(|e| transaction_error(&validated_path, e))?;
+            let diff = self.generate_diff(&original_content, &new_content)?;
+
+            planned.push(StagedEdit {
+                temp_path: validated_path
+                    .with_extension(format!("tmp.{}", Uuid::new_v4().simple())),
+                path: validated_path,
+                original_bytes,
+                format,
+                create_backup: request.create_backup,
+                backup_path: None,
+                result: FileEditResult {
+                    success: true,
+                    original_content,
+                    bytes_written: new_content.len(),
+                    new_content,
+                    backup_path: None,
+                    diff,
+                },
+            });
+        }
+
+        // Nothing has been written yet; from here on every staged edit is
+        // tracked before touching disk so the caller can clean it up
+        for edit in planned {
+            staged.push(edit);
+            let edit = staged.last_mut().expect("edit was just staged");
+
+            let bytes = self
+                .encode(&edit.path, &edit.result.new_content, edit.format)
+                .map_err(|e| transaction_error(&edit.path, e))?;
+            edit.result.bytes_written = bytes.len();
+            fs::write(&edit.temp_path, bytes)
+                .await
+                .map_err(|e| transaction_error(&edit.path, e.into()))?;
+            // The rename replaces the file, so carry its mode over
+            if edit.original_bytes.is_some() {
+                let permissions = fs::metadata(&edit.path)
+                    .await
+                    .map_err(|e| transaction_error(&edit.path, e.into()))?
+                    .permissions();
+                fs::set_permissions(&edit.temp_path, permissions)
+                    .await
+                    .map_err(|e| transaction_error(&edit.path, e.into()))?;
+            }
+
+            if edit.create_backup && edit.original_bytes.is_some() {
+                let backup_path = self
+                    .create_backup(&edit.path)
+                    .await
+                    .map_err(|e| transaction_error(&edit.path, e))?;
+                edit.backup_path = Some(backup_path.clone());
+                edit.result.backup_path = Some(backup_path);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Move staged files into place, restoring already-replaced files on failure
+    async fn commit_transaction(&self, staged: &[StagedEdit]) -> Result<()> {
+        for (index, edit) in staged.iter().enumerate() {
+            if let Err(e) = fs::rename(&edit.temp_path, &edit.path).await {
+                for committed in staged[..index].iter().rev() {
+                    let restored = match &committed.original_bytes {
+                        Some(bytes) => fs::write(&committed.path, bytes).await,
+                        None => fs::remove_file(&committed.path).await,
+                    };
+                    if let Err(restore_error) = restored {
+                        tracing::error!(
+                            "Failed to roll back {}: {}",
+                            committed.path.display(),
+                            restore_error
+                        );
+                    }
+                }
+
+                return Err(transaction_error(&edit.path, e.into()));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Generate a diff between old and new content
     pub fn generate_diff(&self, old_content: &str, new_content: &str) -> Result<String> {
         use similar::{ChangeTag, TextDiff};
@@ -646,60 +945,243 @@ impl FileOperations {
 
         Ok(output.join("\n"))
     }
-}
 
-// Helper trait for UTF-16 decoding
-trait StringFromUtf16 {
-    fn from_utf16le(bytes: &[u8]) -> Result<String>;
-    fn from_utf16be(bytes: &[u8]) -> Result<String>;
-}
+    /// Bytes removed plus bytes added going from `old_content` to
+    /// `new_content`, counted by line
+    pub fn bytes_changed(&self, old_content: &str, new_content: &str) -> u64 {
+        use similar::{ChangeTag, TextDiff};
+
+        TextDiff::from_lines(old_content, new_content)
+            .iter_all_changes()
+            .filter(|change| change.tag() != ChangeTag::Equal)
+            .map(|change| change.value().len() as u64)
+            .sum()
+    }
 
-impl StringFromUtf16 for String {
-    fn from_utf16le(bytes: &[u8]) -> Result<String> {
-        if bytes.len() % 2 != 0 {
+    /// Move a file or directory into the workspace trash instead of
+    /// removing it, so it can be restored later
+    pub async fn move_to_trash(&self, path: &Path, workspace: &Path) -> Result<TrashEntry> {
+        let trash_root = workspace.join(TRASH_DIR);
+        let original_path = path.strip_prefix(workspace).map_err(|_| {
+            FennecError::Command(Box::new(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                format!("Cannot trash {}: outside the workspace", path.display()),
+            )))
+        })?;
+        if path.starts_with(&trash_root) || trash_root.starts_with(path) {
             return Err(FennecError::Command(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Invalid UTF-16LE data: odd number of bytes",
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "Cannot move {} into the trash; delete it permanently instead",
+                    path.display()
+                ),
             )))
             .into());
         }
+        let file_name = path.file_name().ok_or_else(|| {
+            FennecError::Command(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Cannot trash {}: no file name", path.display()),
+            )))
+        })?;
 
-        let utf16_data: Vec<u16> = bytes
-            .chunks_exact(2)
-            .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
-            .collect();
-
-        String::from_utf16(&utf16_data).map_err(|e| {
+        let metadata = fs::symlink_metadata(path).await.map_err(|e| {
             FennecError::Command(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("Invalid UTF-16LE content: {}", e),
+                e.kind(),
+                format!("Failed to read {}: {}", path.display(), e),
             )))
-            .into()
-        })
+        })?;
+        let is_directory = metadata.is_dir();
+        let size_bytes = if is_directory {
+            let root = path.to_path_buf();
+            tokio::task::spawn_blocking(move || {
+                walkdir::WalkDir::new(root)
+                    .into_iter()
+                    .filter_map(|entry| entry.ok())
+                    .filter_map(|entry| entry.metadata().ok())
+                    .filter(|metadata| metadata.is_file())
+                    .map(|metadata| metadata.len())
+                    .sum()
+            })
+            .await?
+        } else {
+            metadata.len()
+        };
+
+        let deleted_at = Utc::now();
+        let id = format!(
+            "{}-{}",
+            deleted_at.format("%Y%m%dT%H%M%S%3fZ"),
+            &Uuid::new_v4().simple().to_string()[..8]
+        );
+        let entry_dir = trash_root.join(&id);
+        let trash_path = entry_dir.join(TRASH_FILES).join(file_name);
+        let entry = TrashEntry {
+            id,
+            original_path: original_path.to_path_buf(),
+            deleted_at,
+            is_directory,
+            size_bytes,
+            trash_path: trash_path.clone(),
+        };
+
+        let staged = async {
+            fs::create_dir_all(entry_dir.join(TRASH_FILES)).await?;
+            fs::write(
+                entry_dir.join(TRASH_MANIFEST),
+                serde_json::to_vec_pretty(&entry)?,
+            )
+            .await?;
+            fs::rename(path, &trash_path).await?;
+            anyhow::Ok(())
+        }
+        .await;
+        if let Err(e) = staged {
+            let _ = fs::remove_dir_all(&entry_dir).await;
+            return Err(FennecError::Command(Box::new(std::io::Error::other(format!(
+                "Failed to move {} to trash: {}",
+                path.display(),
+                e
+            ))))
+            .into());
+        }
+
+        Ok(entry)
+    }
+
+    /// Read every trash entry, including ones whose item is gone (e.g. it was
+    /// moved back by undo)
+    async fn read_trash_entries(&self, workspace: &Path) -> Result<Vec<TrashEntry>> {
+        let trash_root = workspace.join(TRASH_DIR);
+        let mut entries = Vec::new();
+        let mut dirs = match fs::read_dir(&trash_root).await {
+            Ok(dirs) => dirs,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(entries),
+            Err(e) => return Err(FennecError::Command(Box::new(e)).into()),
+        };
+
+        while let Some(dir) = dirs
+            .next_entry()
+            .await
+            .map_err(|e| FennecError::Command(Box::new(e)))?
+        {
+            let manifest_path = dir.path().join(TRASH_MANIFEST);
+            let manifest = match fs::read(&manifest_path).await {
+                Ok(manifest) => manifest,
+                Err(e) => {
+                    warn!("Skipping trash entry {}: {}", dir.path().display(), e);
+                    continue;
+                }
+            };
+            match serde_json::from_slice::<TrashEntry>(&manifest) {
+                Ok(mut entry) => {
+                    let Some(file_name) = entry.original_path.file_name() else {
+                        continue;
+                    };
+                    entry.trash_path = dir.path().join(TRASH_FILES).join(file_name);
+                    entries.push(entry);
+                }
+                Err(e) => warn!("Skipping trash entry {}: {}", manifest_path.display(), e),
+            }
+        }
+
+        entries.sort_by(|a, b| a.deleted_at.cmp(&b.deleted_at).then(a.id.cmp(&b.id)));
+        Ok(entries)
+    }
+
+    /// List restorable trash entries, oldest first
+    pub async fn list_trash(&self, workspace: &Path) -> Result<Vec<TrashEntry>> {
+        let mut entries = self.read_trash_entries(workspace).await?;
+        entries.retain(|entry| entry.trash_path.exists());
+        Ok(entries)
     }
 
-    fn from_utf16be(bytes: &[u8]) -> Result<String> {
-        if bytes.len() % 2 != 0 {
+    /// Move a trashed item back to its original location
+    pub async fn restore_from_trash(&self, workspace: &Path, id: &str) -> Result<TrashEntry> {
+        let entry = self
+            .list_trash(workspace)
+            .await?
+            .into_iter()
+            .find(|entry| entry.id == id)
+            .ok_or_else(|| {
+                FennecError::Command(Box::new(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("No trash entry '{}'", id),
+                )))
+            })?;
+
+        let target = workspace.join(&entry.original_path);
+        if fs::symlink_metadata(&target).await.is_ok() {
             return Err(FennecError::Command(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Invalid UTF-16BE data: odd number of bytes",
+                std::io::ErrorKind::AlreadyExists,
+                format!("Cannot restore, path already exists: {}", target.display()),
             )))
             .into());
         }
-
-        let utf16_data: Vec<u16> = bytes
-            .chunks_exact(2)
-            .map(|chunk| u16::from_be_bytes([chunk[0], chunk[1]]))
-            .collect();
-
-        String::from_utf16(&utf16_data).map_err(|e| {
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).await.map_err(|e| {
+                FennecError::Command(Box::new(std::io::Error::new(
+                    e.kind(),
+                    format!("Failed to create parent directories: {}", e),
+                )))
+            })?;
+        }
+        fs::rename(&entry.trash_path, &target).await.map_err(|e| {
             FennecError::Command(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("Invalid UTF-16BE content: {}", e),
+                e.kind(),
+                format!("Failed to restore {}: {}", target.display(), e),
             )))
-            .into()
-        })
+        })?;
+
+        let _ = fs::remove_dir_all(workspace.join(TRASH_DIR).join(&entry.id)).await;
+        Ok(entry)
     }
+
+    /// Permanently remove trash entries deleted more than `older_than` ago,
+    /// or every entry when `older_than` is `None`. Entries whose item was
+    /// already moved back are cleaned up as well. Returns the purged entries.
+    pub async fn empty_trash(
+        &self,
+        workspace: &Path,
+        older_than: Option<Duration>,
+    ) -> Result<Vec<TrashEntry>> {
+        let cutoff = match older_than {
+            Some(age) => Some(Utc::now() - chrono::Duration::from_std(age)?),
+            None => None,
+        };
+
+        let mut purged = Vec::new();
+        for entry in self.read_trash_entries(workspace).await? {
+            let stale = !entry.trash_path.exists();
+            let expired = cutoff.is_none_or(|cutoff| entry.deleted_at <= cutoff);
+            if !stale && !expired {
+                continue;
+            }
+
+            let entry_dir = workspace.join(TRASH_DIR).join(&entry.id);
+            fs::remove_dir_all(&entry_dir).await.map_err(|e| {
+                FennecError::Command(Box::new(std::io::Error::new(
+                    e.kind(),
+                    format!("Failed to purge trash entry {}: {}", entry.id, e),
+                )))
+            })?;
+            if !stale {
+                purged.push(entry);
+            }
+        }
+
+        Ok(purged)
+    }
+}
+
+/// Wrap a per-file failure so the caller knows which edit aborted the transaction
+fn transaction_error(path: &Path, error: anyhow::Error) -> anyhow::Error {
+    FennecError::Command(Box::new(std::io::Error::other(format!(
+        "Transaction aborted at {}: {}",
+        path.display(),
+        error
+    ))))
+    .into()
 }
 
 #[cfg(test)]
@@ -819,6 +1301,7 @@ mod tests {
             },
             create_backup: true,
             create_if_missing: false,
+            force_binary: false,
         };
 
         let result = file_ops
@@ -834,4 +1317,391 @@ mod tests {
         let final_content = file_ops.safe_read_file(&test_file).await.unwrap();
         assert_eq!(final_content, "line 1\nmodified line 2\nline 3");
     }
+
+    fn transaction_request(path: PathBuf, strategy: EditStrategy) -> FileEditRequest {
+        FileEditRequest {
+            path,
+            strategy,
+            create_backup: false,
+            create_if_missing: false,
+            force_binary: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transaction_applies_all_edits() {
+        let temp_dir = tempdir().unwrap();
+        let first = temp_dir.path().join("first.txt");
+        let second = temp_dir.path().join("second.txt");
+        let created = temp_dir.path().join("created.txt");
+        write(&first, "alpha\nbeta").await.unwrap();
+        write(&second, "one\ntwo\nthree").await.unwrap();
+
+        let hunks = crate::hunks::split_diff_into_hunks(
+            second.clone(),
+            "one\ntwo\nthree",
+            "one\n2\nthree",
+            0,
+        )
+        .into_iter()
+        .map(|mut hunk| {
+            hunk.accept();
+            hunk
+        })
+        .collect();
+
+        let file_ops = FileOperations::with_default_config();
+        let requests = vec![
+            transaction_request(
+                first.clone(),
+                EditStrategy::SearchReplace {
+                    search: "beta".to_string(),
+                    replace: "gamma".to_string(),
+                },
+            ),
+            transaction_request(second.clone(), EditStrategy::ApplyHunks { hunks }),
+            FileEditRequest {
+                create_if_missing: true,
+                force_binary: false,
+                ..transaction_request(
+                    created.clone(),
+                    EditStrategy::Replace {
+                        content: "new".to_string(),
+                    },
+                )
+            },
+        ];
+
+        let result = file_ops
+            .apply_transaction(requests, &SandboxLevel::FullAccess, None)
+            .await
+            .unwrap();
+
+        assert_eq!(result.files.len(), 3);
+        assert!(result.files[2].created);
+        assert_eq!(
+            tokio::fs::read_to_string(&first).await.unwrap(),
+            "alpha\ngamma"
+        );
+        assert_eq!(
+            tokio::fs::read_to_string(&second).await.unwrap(),
+            "one\n2\nthree"
+        );
+        assert_eq!(tokio::fs::read_to_string(&created).await.unwrap(), "new");
+
+        let action = result.to_action("edit".to_string(), "Edited 3 files".to_string());
+        match &action.state_after {
+            crate::action_log::ActionState::Transaction { states } => {
+                assert_eq!(states.len(), 3)
+            }
+            other => panic!("expected a transaction, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transaction_rolls_back_on_middle_failure() {
+        let temp_dir = tempdir().unwrap();
+        let paths: Vec<PathBuf> = (0..3)
+            .map(|i| temp_dir.path().join(format!("file{}.txt", i)))
+            .collect();
+        for path in &paths {
+            write(path, "line 1\nline 2\nline 3").await.unwrap();
+        }
+
+        // The middle hunk was computed against content the file no longer has
+        let mut stale_hunk = Hunk::new(
+            "h0".to_string(),
+            paths[1].clone(),
+            1,
+            2,
+            vec!["something else".to_string()],
+            vec!["replacement".to_string()],
+        );
+        stale_hunk.accept();
+
+        let file_ops = FileOperations::with_default_config();
+        let requests = vec![
+            transaction_request(
+                paths[0].clone(),
+                EditStrategy::Append {
+                    content: "line 4".to_string(),
+                },
+            ),
+            transaction_request(
+                paths[1].clone(),
+                EditStrategy::ApplyHunks {
+                    hunks: vec![stale_hunk],
+                },
+            ),
+            transaction_request(
+                paths[2].clone(),
+                EditStrategy::Replace {
+                    content: "replaced".to_string(),
+                },
+            ),
+        ];
+
+        let error = file_ops
+            .apply_transaction(requests, &SandboxLevel::FullAccess, None)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("file1.txt"));
+
+        for path in &paths {
+            assert_eq!(
+                tokio::fs::read_to_string(path).await.unwrap(),
+                "line 1\nline 2\nline 3"
+            );
+        }
+        let leftovers = std::fs::read_dir(temp_dir.path()).unwrap().count();
+        assert_eq!(leftovers, paths.len());
+    }
+
+    #[tokio::test]
+    async fn test_transaction_rolls_back_when_middle_file_unreadable() {
+        let temp_dir = tempdir().unwrap();
+        let first = temp_dir.path().join("first.txt");
+        let middle = temp_dir.path().join("middle");
+        let last = temp_dir.path().join("last.txt");
+        write(&first, "first").await.unwrap();
+        std::fs::create_dir(&middle).unwrap();
+        write(&last, "last").await.unwrap();
+
+        let replace = |content: &str| EditStrategy::Replace {
+            content: content.to_string(),
+        };
+        let requests = vec![
+            FileEditRequest {
+                create_backup: true,
+                ..transaction_request(first.clone(), replace("changed"))
+            },
+            transaction_request(middle.clone(), replace("changed")),
+            transaction_request(last.clone(), replace("changed")),
+        ];
+
+        let file_ops = FileOperations::with_default_config();
+        assert!(file_ops
+            .apply_transaction(requests, &SandboxLevel::FullAccess, None)
+            .await
+            .is_err());
+
+        assert_eq!(tokio::fs::read_to_string(&first).await.unwrap(), "first");
+        assert_eq!(tokio::fs::read_to_string(&last).await.unwrap(), "last");
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_transaction_restores_files_when_a_later_rename_fails() {
+        let temp_dir = tempdir().unwrap();
+        let first = temp_dir.path().join("first.txt");
+        let created = temp_dir.path().join("created.txt");
+        let last = temp_dir.path().join("last.txt");
+        write(&first, "first").await.unwrap();
+        write(&last, "last").await.unwrap();
+
+        let replace = |content: &str| EditStrategy::Replace {
+            content: content.to_string(),
+        };
+        let requests = vec![
+            transaction_request(first.clone(), replace("changed")),
+            FileEditRequest {
+                create_if_missing: true,
+                ..transaction_request(created.clone(), replace("changed"))
+            },
+            transaction_request(last.clone(), replace("changed")),
+        ];
+
+        let file_ops = FileOperations::with_default_config();
+        let mut staged = Vec::new();
+        file_ops
+            .stage_transaction(requests, &SandboxLevel::FullAccess, None, &mut staged)
+            .await
+            .unwrap();
+
+        // The last rename fails after the first two have gone through
+        std::fs::remove_file(&staged[2].temp_path).unwrap();
+        let error = file_ops.commit_transaction(&staged).await.unwrap_err();
+        assert!(error.to_string().contains("last.txt"), "{}", error);
+
+        assert_eq!(tokio::fs::read_to_string(&first).await.unwrap(), "first");
+        assert!(!created.exists());
+        assert_eq!(tokio::fs::read_to_string(&last).await.unwrap(), "last");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_transaction_keeps_file_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempdir().unwrap();
+        let script = temp_dir.path().join("build.sh");
+        write(&script, "#!/bin/sh\necho old\n").await.unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let requests = vec![transaction_request(
+            script.clone(),
+            EditStrategy::SearchReplace {
+                search: "old".to_string(),
+                replace: "new".to_string(),
+            },
+        )];
+        FileOperations::with_default_config()
+            .apply_transaction(requests, &SandboxLevel::FullAccess, None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            tokio::fs::read_to_string(&script).await.unwrap(),
+            "#!/bin/sh\necho new\n"
+        );
+        let mode = std::fs::metadata(&script).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+    }
+    #[tokio::test]
+    async fn test_trash_restore_and_retention_purge() {
+        let temp_dir = tempdir().unwrap();
+        let workspace = temp_dir.path();
+        write(workspace.join("old.txt"), "old").await.unwrap();
+        write(workspace.join("new.txt"), "new").await.unwrap();
+
+        let file_ops = FileOperations::with_default_config();
+        let old = file_ops
+            .move_to_trash(&workspace.join("old.txt"), workspace)
+            .await
+            .unwrap();
+        let new = file_ops
+            .move_to_trash(&workspace.join("new.txt"), workspace)
+            .await
+            .unwrap();
+        assert_eq!(file_ops.list_trash(workspace).await.unwrap().len(), 2);
+
+        // Backdate the first entry past the retention window
+        let manifest = workspace.join(TRASH_DIR).join(&old.id).join(TRASH_MANIFEST);
+        let mut aged = old.clone();
+        aged.deleted_at = Utc::now() - chrono::Duration::days(30);
+        std::fs::write(&manifest, serde_json::to_string(&aged).unwrap()).unwrap();
+
+        let week = Duration::from_secs(7 * 24 * 60 * 60);
+        let purged = file_ops.empty_trash(workspace, Some(week)).await.unwrap();
+        assert_eq!(purged.len(), 1);
+        assert_eq!(purged[0].id, old.id);
+        assert!(!workspace.join(TRASH_DIR).join(&old.id).exists());
+
+        let restored = file_ops
+            .restore_from_trash(workspace, &new.id)
+            .await
+            .unwrap();
+        assert_eq!(restored.original_path, Path::new("new.txt"));
+        assert_eq!(
+            tokio::fs::read_to_string(workspace.join("new.txt"))
+                .await
+                .unwrap(),
+            "new"
+        );
+        assert!(file_ops.list_trash(workspace).await.unwrap().is_empty());
+        assert!(file_ops
+            .restore_from_trash(workspace, &new.id)
+            .await
+            .is_err());
+    }
+
+    const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/encodings");
+
+    async fn edit_fixture(
+        name: &str,
+        strategy: EditStrategy,
+        force_binary: bool,
+    ) -> (Vec<u8>, Result<FileEditResult>) {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join(name);
+        std::fs::copy(Path::new(FIXTURES).join(name), &path).unwrap();
+
+        let request = FileEditRequest {
+            path: path.clone(),
+            strategy,
+            create_backup: false,
+            create_if_missing: false,
+            force_binary,
+        };
+        let result = FileOperations::with_default_config()
+            .edit_file(request, &SandboxLevel::FullAccess, None)
+            .await;
+        (std::fs::read(&path).unwrap(), result)
+    }
+
+    fn line_two_to_line_2() -> EditStrategy {
+        EditStrategy::SearchReplace {
+            search: "line two".to_string(),
+            replace: "line 2".to_string(),
+        }
+    }
+
+    fn replace_bytes(bytes: &[u8], from: &[u8], to: &[u8]) -> Vec<u8> {
+        let at = bytes.windows(from.len()).position(|w| w == from).unwrap();
+        [&bytes[..at], to, &bytes[at + from.len()..]].concat()
+    }
+
+    fn utf16(text: &str, unit: fn(u16) -> [u8; 2]) -> Vec<u8> {
+        text.encode_utf16().flat_map(unit).collect()
+    }
+
+    #[tokio::test]
+    async fn test_edits_keep_encoding_and_line_endings() {
+        let cases: Vec<(&str, Vec<u8>, Vec<u8>)> = vec![
+            ("utf8.txt", b"line two".to_vec(), b"line 2".to_vec()),
+            ("utf8_bom.txt", b"line two".to_vec(), b"line 2".to_vec()),
+            ("latin1_crlf.txt", b"line two".to_vec(), b"line 2".to_vec()),
+            (
+                "mixed_endings.txt",
+                b"line two".to_vec(),
+                b"line 2".to_vec(),
+            ),
+            (
+                "utf16le_crlf.txt",
+                utf16("line two", u16::to_le_bytes),
+                utf16("line 2", u16::to_le_bytes),
+            ),
+            (
+                "utf16be.txt",
+                utf16("line two", u16::to_be_bytes),
+                utf16("line 2", u16::to_be_bytes),
+            ),
+        ];
+
+        for (name, from, to) in cases {
+            let original = std::fs::read(Path::new(FIXTURES).join(name)).unwrap();
+            let (written, result) = edit_fixture(name, line_two_to_line_2(), false).await;
+            assert!(result.unwrap().success, "{}", name);
+            // Everything but the replaced words is byte for byte the same
+            assert_eq!(written, replace_bytes(&original, &from, &to), "{}", name);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_binary_files_need_force_binary() {
+        let original = std::fs::read(Path::new(FIXTURES).join("binary.png")).unwrap();
+
+        let (untouched, result) = edit_fixture("binary.png", line_two_to_line_2(), false).await;
+        let message = result.unwrap_err().to_string();
+        assert!(message.contains("appears to be binary"), "{}", message);
+        assert!(message.contains("--force-binary"), "{}", message);
+        assert_eq!(untouched, original);
+
+        let (written, result) = edit_fixture("binary.png", line_two_to_line_2(), true).await;
+        assert!(result.unwrap().success);
+        assert_eq!(written, replace_bytes(&original, b"line two", b"line 2"));
+    }
+
+    #[tokio::test]
+    async fn test_latin1_edit_rejects_unrepresentable_text() {
+        let original = std::fs::read(Path::new(FIXTURES).join("latin1_crlf.txt")).unwrap();
+        let strategy = EditStrategy::SearchReplace {
+            search: "line two".to_string(),
+            replace: "line \u{20ac}".to_string(),
+        };
+
+        let (written, result) = edit_fixture("latin1_crlf.txt", strategy, false).await;
+        assert!(result.is_err());
+        assert_eq!(written, original);
+    }
 }
@@ -1,16 +1,15 @@
 use crate::registry::{CommandContext, CommandDescriptor, CommandExecutor};
-use crate::symbols::{extract_symbols, Symbol, SymbolIndex, SymbolType};
+use crate::symbols::{MatchKind, Symbol, SymbolIndex, SymbolType, Visibility};
 use anyhow::Result;
 use fennec_core::{
     command::{Capability, CommandPreview, CommandResult},
     error::FennecError,
 };
 use fennec_security::SandboxLevel;
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
-use tokio::fs;
 use uuid::Uuid;
-use walkdir::WalkDir;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FindSymbolArgs {
@@ -19,8 +18,23 @@ pub struct FindSymbolArgs {
     pub symbol_type: Option<String>,
     #[serde(default)]
     pub exact_match: bool,
-    #[serde(default = "default_max_results")]
+    #[serde(default = "default_max_results", alias = "limit")]
     pub max_results: usize,
+    /// Only symbols with this visibility: `pub`, `crate`, `super` or `private`
+    #[serde(default)]
+    pub visibility: Option<String>,
+    /// Only symbols in files matching one of these globs, relative to the
+    /// workspace
+    #[serde(default)]
+    pub paths: Vec<String>,
+}
+
+/// A symbol returned by a search, with its path relative to the workspace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolMatch {
+    pub symbol: Symbol,
+    /// How the query matched; `None` for exact-name searches
+    pub match_kind: Option<MatchKind>,
 }
 
 fn default_max_results() -> usize {
@@ -45,11 +59,13 @@ impl FindSymbolCommand {
                 sandbox_level_required: SandboxLevel::ReadOnly,
                 supports_preview: false,
                 supports_dry_run: false,
+                timeout: None,
+                alias_of: None,
             },
         }
     }
 
-    fn parse_symbol_type(type_str: &str) -> Option<SymbolType> {
+    pub(crate) fn parse_symbol_type(type_str: &str) -> Option<SymbolType> {
         match type_str.to_lowercase().as_str() {
             "function" | "fn" => Some(SymbolType::Function),
             "struct" => Some(SymbolType::Struct),
@@ -63,71 +79,66 @@ impl FindSymbolCommand {
         }
     }
 
+    pub(crate) fn parse_visibility(visibility: &str) -> Option<Visibility> {
+        match visibility.to_lowercase().as_str() {
+            "pub" | "public" => Some(Visibility::Public),
+            "crate" | "pub(crate)" => Some(Visibility::Crate),
+            "super" | "pub(super)" => Some(Visibility::Super),
+            "private" => Some(Visibility::Private),
+            _ => None,
+        }
+    }
+
+    fn build_path_globs(patterns: &[String]) -> Result<Option<GlobSet>> {
+        if patterns.is_empty() {
+            return Ok(None);
+        }
+
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            let glob = Glob::new(pattern).map_err(|e| {
+                FennecError::Command(Box::new(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("Invalid path glob '{}': {}", pattern, e),
+                )))
+            })?;
+            builder.add(glob);
+        }
+
+        let set = builder.build().map_err(|e| {
+            FennecError::Command(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Invalid path globs: {}", e),
+            )))
+        })?;
+        Ok(Some(set))
+    }
+
+    /// Load the persisted symbol index and bring it up to date, re-parsing
+    /// only files whose content changed since the last run.
     async fn build_index(
         &self,
         workspace_path: &Path,
         context: &CommandContext,
     ) -> Result<SymbolIndex> {
-        let mut index = SymbolIndex::new();
-        let mut files_indexed = 0;
-
-        for entry in WalkDir::new(workspace_path)
-            .max_depth(10)
-            .into_iter()
-            .filter_entry(|e| {
-                // Don't filter the root directory itself
-                if e.path() == workspace_path {
-                    return true;
-                }
-                // Filter out hidden directories, target, and node_modules
-                !e.file_name()
-                    .to_str()
-                    .map(|s| s.starts_with('.') || s == "target" || s == "node_modules")
-                    .unwrap_or(false)
-            })
-            .filter_map(|e| e.ok())
-        {
-            if files_indexed % 10 == 0 && context.cancellation_token.is_cancelled() {
+        let mut index = SymbolIndex::load(workspace_path);
+        let files = SymbolIndex::rust_files(workspace_path);
+        let mut changed = index.retain_files(&files);
+
+        for chunk in files.chunks(10) {
+            if context.cancellation_token.is_cancelled() {
                 return Err(FennecError::Command(Box::new(std::io::Error::new(
                     std::io::ErrorKind::Interrupted,
                     "Indexing cancelled",
                 )))
                 .into());
             }
+            changed += index.update_paths(chunk);
+        }
 
-            let path = entry.path();
-            if !path.is_file() {
-                continue;
-            }
-
-            // Only index Rust files
-            if let Some(ext) = path.extension() {
-                if ext != "rs" {
-                    continue;
-                }
-            } else {
-                continue;
-            }
-
-            files_indexed += 1;
-
-            // Read file content
-            match fs::read_to_string(path).await {
-                Ok(content) => {
-                    match extract_symbols(path, &content) {
-                        Ok(symbols) => {
-                            index.add_symbols(symbols);
-                        }
-                        Err(_) => {
-                            // Skip files that fail to parse
-                            continue;
-                        }
-                    }
-                }
-                Err(_) => {
-                    // Skip files that can't be read
-                    continue;
-                }
+        if changed > 0 {
+            if let Err(e) = index.save(workspace_path) {
+                tracing::debug!("Failed to persist symbol index: {}", e);
             }
         }
 
@@ -138,7 +149,7 @@ impl FindSymbolCommand {
         &self,
         args: &FindSymbolArgs,
         context: &CommandContext,
-    ) -> Result<String> {
+    ) -> Result<(String, Vec<SymbolMatch>)> {
         let workspace_path_str = context.workspace_path.as_ref().ok_or_else(|| {
             FennecError::Command(Box::new(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
@@ -146,33 +157,53 @@ impl FindSymbolCommand {
             )))
         })?;
         let workspace_path = Path::new(workspace_path_str);
+        let path_globs = Self::build_path_globs(&args.paths)?;
 
         // Build symbol index
         let index = self.build_index(workspace_path, context).await?;
 
         if index.is_empty() {
-            return Ok("No symbols found in workspace".to_string());
+            return Ok(("No symbols found in workspace".to_string(), Vec::new()));
         }
 
-        // Search for symbols
-        let mut results: Vec<&Symbol> = if args.exact_match {
-            index.find_by_name(&args.query)
+        // Search for symbols, best matches first
+        let mut results: Vec<(Option<MatchKind>, &Symbol)> = if args.exact_match {
+            index
+                .find_by_name(&args.query)
+                .into_iter()
+                .map(|symbol| (None, symbol))
+                .collect()
         } else {
-            index.find_by_name_partial(&args.query)
+            index
+                .find_fuzzy(&args.query)
+                .into_iter()
+                .map(|(kind, symbol)| (Some(kind), symbol))
+                .collect()
         };
 
-        // Filter by symbol type if specified
-        if let Some(ref type_str) = args.symbol_type {
-            if let Some(symbol_type) = Self::parse_symbol_type(type_str) {
-                results.retain(|s| s.symbol_type == symbol_type);
-            }
+        if let Some(symbol_type) = args
+            .symbol_type
+            .as_deref()
+            .and_then(Self::parse_symbol_type)
+        {
+            results.retain(|(_, s)| s.symbol_type == symbol_type);
+        }
+        if let Some(visibility) = args.visibility.as_deref().and_then(Self::parse_visibility) {
+            results.retain(|(_, s)| s.visibility == visibility);
+        }
+        if let Some(globs) = &path_globs {
+            results.retain(|(_, s)| {
+                globs.is_match(s.path.strip_prefix(workspace_path).unwrap_or(&s.path))
+            });
         }
 
-        // Limit results
         results.truncate(args.max_results);
 
         if results.is_empty() {
-            return Ok(format!("No symbols found matching '{}'", args.query));
+            return Ok((
+                format!("No symbols found matching '{}'", args.query),
+                Vec::new(),
+            ));
         }
 
         // Format output
@@ -181,8 +212,9 @@ impl FindSymbolCommand {
             results.len(),
             args.query
         );
+        let mut matches = Vec::with_capacity(results.len());
 
-        for symbol in &results {
+        for (match_kind, symbol) in results {
             let relative_path = symbol
                 .path
                 .strip_prefix(workspace_path)
@@ -200,33 +232,44 @@ impl FindSymbolCommand {
             };
 
             let vis_str = match symbol.visibility {
-                crate::symbols::Visibility::Public => "pub ",
-                crate::symbols::Visibility::Crate => "pub(crate) ",
-                crate::symbols::Visibility::Super => "pub(super) ",
-                crate::symbols::Visibility::Private => "",
+                Visibility::Public => "pub ",
+                Visibility::Crate => "pub(crate) ",
+                Visibility::Super => "pub(super) ",
+                Visibility::Private => "",
             };
 
             output.push_str(&format!(
-                "  {} {}{} {} @ {}:{}\n",
+                "  {} {}{} @ {}:{}\n",
                 type_str,
                 vis_str,
-                symbol.name,
-                "".to_string(), // Could add signature info here
+                symbol.qualified_name(),
                 relative_path.display(),
                 symbol.line
             ));
 
+            if let Some(ref signature) = symbol.signature {
+                output.push_str(&format!("    {}\n", signature));
+            }
+
             if let Some(ref doc) = symbol.doc_comment {
                 let doc_preview = doc.lines().next().unwrap_or("");
                 if !doc_preview.is_empty() {
                     output.push_str(&format!("    // {}\n", doc_preview));
                 }
             }
+
+            matches.push(SymbolMatch {
+                symbol: Symbol {
+                    path: relative_path.to_path_buf(),
+                    ..symbol.clone()
+                },
+                match_kind,
+            });
         }
 
         output.push_str(&format!("\nIndexed {} total symbols\n", index.len()));
 
-        Ok(output)
+        Ok((output, matches))
     }
 }
 
@@ -281,17 +324,19 @@ impl CommandExecutor for FindSymbolCommand {
         })?;
 
         match self.perform_search(&args, context).await {
-            Ok(output) => Ok(CommandResult {
+            Ok((output, matches)) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: true,
                 output,
                 error: None,
+                data: serde_json::to_value(&matches).ok(),
             }),
             Err(e) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
+                data: None,
             }),
         }
     }
@@ -321,6 +366,21 @@ impl CommandExecutor for FindSymbolCommand {
             }
         }
 
+        if let Some(ref visibility) = args.visibility {
+            if Self::parse_visibility(visibility).is_none() {
+                return Err(FennecError::Command(Box::new(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "Invalid visibility: {}. Valid values: pub, crate, super, private",
+                        visibility
+                    ),
+                )))
+                .into());
+            }
+        }
+
+        Self::build_path_globs(&args.paths)?;
+
         Ok(())
     }
 }
@@ -364,6 +424,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -403,6 +465,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -410,4 +474,154 @@ mod tests {
         assert!(result.output.contains("MyStruct"));
         assert!(!result.output.contains("my_function"));
     }
+
+    /// A small crate with nested modules and mixed visibility
+    fn fixture_crate() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("src/transcript")).unwrap();
+        std::fs::write(
+            root.join("src/lib.rs"),
+            "pub mod transcript;\n\npub trait TrackStore {}\n\npub fn restore_all() {}\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("src/transcript/mod.rs"), "pub mod store;\n").unwrap();
+        std::fs::write(
+            root.join("src/transcript/store.rs"),
+            r#"pub struct TranscriptStore {
+    entries: Vec<String>,
+}
+
+pub(crate) fn trim_stream(
+    input: &str,
+    limit: usize,
+) -> String {
+    input.chars().take(limit).collect()
+}
+
+fn transcript_stats() {}
+"#,
+        )
+        .unwrap();
+        temp_dir
+    }
+
+    fn fixture_context(root: &Path) -> CommandContext {
+        CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: Some(root.to_string_lossy().to_string()),
+            sandbox_level: SandboxLevel::ReadOnly,
+            dry_run: false,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log: None,
+            timeout: None,
+            progress: None,
+        }
+    }
+
+    async fn search(root: &Path, args: serde_json::Value) -> Vec<SymbolMatch> {
+        let result = FindSymbolCommand::new()
+            .execute(&args, &fixture_context(root))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        serde_json::from_value(result.data.unwrap()).unwrap()
+    }
+
+    fn names(matches: &[SymbolMatch]) -> Vec<&str> {
+        matches.iter().map(|m| m.symbol.name.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_find_symbol_camel_hump_and_ranking() {
+        let crate_dir = fixture_crate();
+        let root = crate_dir.path();
+
+        let matches = search(root, serde_json::json!({ "query": "TrStr" })).await;
+        let store = matches
+            .iter()
+            .find(|m| m.symbol.name == "TranscriptStore")
+            .expect("camel-hump query should find TranscriptStore");
+        assert_eq!(store.match_kind, Some(MatchKind::CamelHump));
+        assert_eq!(store.symbol.module_path, "transcript::store");
+        assert_eq!(
+            store.symbol.signature.as_deref(),
+            Some("pub struct TranscriptStore")
+        );
+        assert_eq!(store.symbol.path, Path::new("src/transcript/store.rs"));
+
+        // Multi-line signatures are collapsed onto one line
+        let trim = matches
+            .iter()
+            .find(|m| m.symbol.name == "trim_stream")
+            .unwrap();
+        assert_eq!(
+            trim.symbol.signature.as_deref(),
+            Some("pub(crate) fn trim_stream(input: &str, limit: usize) -> String")
+        );
+
+        // Prefix beats camel-hump beats substring
+        let matches = search(root, serde_json::json!({ "query": "stor" })).await;
+        assert_eq!(
+            names(&matches),
+            vec!["store", "TrackStore", "TranscriptStore", "restore_all"]
+        );
+        let kinds: Vec<Option<MatchKind>> = matches.iter().map(|m| m.match_kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                Some(MatchKind::Prefix),
+                Some(MatchKind::CamelHump),
+                Some(MatchKind::CamelHump),
+                Some(MatchKind::Substring)
+            ]
+        );
+
+        let matches = search(root, serde_json::json!({ "query": "stor", "limit": 2 })).await;
+        assert_eq!(names(&matches), vec!["store", "TrackStore"]);
+    }
+
+    #[tokio::test]
+    async fn test_find_symbol_visibility_kind_and_path_filters() {
+        let crate_dir = fixture_crate();
+        let root = crate_dir.path();
+
+        let matches = search(
+            root,
+            serde_json::json!({ "query": "tr", "visibility": "crate" }),
+        )
+        .await;
+        assert_eq!(names(&matches), vec!["trim_stream"]);
+
+        let matches = search(
+            root,
+            serde_json::json!({ "query": "tr", "visibility": "private" }),
+        )
+        .await;
+        assert_eq!(names(&matches), vec!["transcript_stats"]);
+
+        let matches = search(
+            root,
+            serde_json::json!({ "query": "TrStr", "symbol_type": "trait" }),
+        )
+        .await;
+        assert_eq!(names(&matches), vec!["TrackStore"]);
+
+        let matches = search(
+            root,
+            serde_json::json!({ "query": "TrStr", "paths": ["src/transcript/**"] }),
+        )
+        .await;
+        assert_eq!(names(&matches), vec!["trim_stream", "TranscriptStore"]);
+
+        let command = FindSymbolCommand::new();
+        assert!(command
+            .validate_args(&serde_json::json!({ "query": "x", "visibility": "exported" }))
+            .is_err());
+        assert!(command
+            .validate_args(&serde_json::json!({ "query": "x", "paths": ["src/[*"] }))
+            .is_err());
+    }
 }
@@ -1,10 +1,17 @@
-use crate::compiler_errors::{extract_fixes, parse_cargo_json, FixConfidence, SuggestedFix};
+use crate::compiler_errors::{
+    extract_fixes, extract_machine_applicable_fixes, parse_cargo_json, select_non_overlapping,
+    CompilerMessage, FixConfidence, MachineFix, MessageLevel, SuggestedFix,
+};
+use crate::file_ops::{EditStrategy, FileEditRequest, FileOperations, FileOperationsConfig};
+use crate::hunks::{split_diff_into_hunks, Hunk};
 use crate::registry::{CommandContext, CommandDescriptor, CommandExecutor};
 use anyhow::Result;
 use fennec_core::command::{Capability, CommandPreview, CommandResult};
 use fennec_core::error::FennecError;
 use fennec_security::SandboxLevel;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
@@ -20,13 +27,17 @@ pub struct FixErrorsArgs {
     #[serde(default = "default_min_confidence")]
     pub min_confidence: String,
 
-    /// Maximum number of fixes to display
+    /// Maximum number of fixes to display (or apply, with `only_machine_applicable`)
     #[serde(default = "default_max_fixes")]
     pub max_fixes: usize,
 
     /// Additional cargo arguments
     #[serde(default)]
     pub cargo_args: Vec<String>,
+
+    /// Apply rustc's machine-applicable suggestions directly instead of listing fixes
+    #[serde(default)]
+    pub only_machine_applicable: bool,
 }
 
 fn default_check_type() -> String {
@@ -41,8 +52,45 @@ fn default_max_fixes() -> usize {
     20
 }
 
+const HUNK_CONTEXT_LINES: usize = 3;
+
+/// A machine-applicable fix together with the hunks it produced
+#[derive(Debug, Clone, Serialize)]
+pub struct AppliedFix {
+    pub fix: MachineFix,
+    pub hunks: Vec<Hunk>,
+}
+
+/// Outcome of applying machine-applicable compiler suggestions
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MachineFixReport {
+    pub applied: Vec<AppliedFix>,
+    /// Fixes overlapping an already-selected fix on the same span
+    pub skipped_overlapping: Vec<MachineFix>,
+    /// Fixes whose spans no longer match the file on disk
+    pub skipped_stale: Vec<MachineFix>,
+    /// Fixes left over once `max_fixes` was reached
+    pub over_limit: usize,
+    pub dry_run: bool,
+}
+
+/// Byte-offset edits already applied to a file, as (start, end, delta)
+/// in the coordinates rustc reported
+type AppliedEdits = Vec<(usize, usize, isize)>;
+
+/// Map an offset from rustc's view of the file to the current content
+fn adjust_offset(edits: &AppliedEdits, offset: usize) -> usize {
+    let delta: isize = edits
+        .iter()
+        .filter(|(_, end, _)| *end <= offset)
+        .map(|(_, _, delta)| delta)
+        .sum();
+    offset.saturating_add_signed(delta)
+}
+
 pub struct FixErrorsCommand {
     descriptor: CommandDescriptor,
+    file_ops: FileOperations,
 }
 
 impl FixErrorsCommand {
@@ -53,11 +101,18 @@ impl FixErrorsCommand {
                 description: "Analyze Rust compiler errors and suggest fixes".to_string(),
                 version: "1.0.0".to_string(),
                 author: Some("Fennec Contributors".to_string()),
-                capabilities_required: vec![Capability::ExecuteShell],
+                capabilities_required: vec![
+                    Capability::ReadFile,
+                    Capability::WriteFile,
+                    Capability::ExecuteShell,
+                ],
                 sandbox_level_required: SandboxLevel::WorkspaceWrite,
                 supports_preview: false,
-                supports_dry_run: false,
+                supports_dry_run: true,
+                timeout: None,
+                alias_of: None,
             },
+            file_ops: FileOperations::new(FileOperationsConfig::default()),
         }
     }
 
@@ -75,6 +130,17 @@ impl FixErrorsCommand {
         cargo_args: &[String],
         context: &CommandContext,
     ) -> Result<Vec<SuggestedFix>> {
+        let messages = self.run_cargo(check_type, cargo_args, context).await?;
+        Ok(messages.iter().flat_map(extract_fixes).collect())
+    }
+
+    /// Run cargo with JSON diagnostics and collect the compiler messages
+    async fn run_cargo(
+        &self,
+        check_type: &str,
+        cargo_args: &[String],
+        context: &CommandContext,
+    ) -> Result<Vec<CompilerMessage>> {
         let workspace_path = context.workspace_path.as_ref().ok_or_else(|| {
             FennecError::Command(Box::new(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
@@ -123,7 +189,7 @@ impl FixErrorsCommand {
         })?;
 
         let mut reader = BufReader::new(stdout).lines();
-        let mut all_fixes = Vec::new();
+        let mut messages = Vec::new();
 
         // Parse output line by line
         while let Some(line) = reader
@@ -143,15 +209,290 @@ impl FixErrorsCommand {
 
             // Try to parse as JSON compiler message
             if let Some(message) = parse_cargo_json(&line) {
-                let fixes = extract_fixes(&message);
-                all_fixes.extend(fixes);
+                messages.push(message);
             }
         }
 
         // Wait for the command to complete
         let _ = child.wait().await;
 
-        Ok(all_fixes)
+        Ok(messages)
+    }
+
+    /// Apply machine-applicable suggestions from `messages`, one undoable
+    /// action per fix. Overlapping suggestions keep only the first.
+    async fn apply_machine_fixes(
+        &self,
+        messages: &[CompilerMessage],
+        max_fixes: usize,
+        context: &CommandContext,
+    ) -> Result<MachineFixReport> {
+        let workspace_path = context.workspace_path.as_ref().ok_or_else(|| {
+            FennecError::Command(Box::new(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "No workspace path set",
+            )))
+        })?;
+        let workspace = Path::new(workspace_path);
+
+        let fixes = messages
+            .iter()
+            .flat_map(extract_machine_applicable_fixes)
+            .collect();
+        let (selected, skipped_overlapping) = select_non_overlapping(fixes);
+        let mut report = MachineFixReport {
+            skipped_overlapping,
+            over_limit: selected.len().saturating_sub(max_fixes),
+            dry_run: context.dry_run,
+            ..Default::default()
+        };
+
+        let mut contents: HashMap<PathBuf, String> = HashMap::new();
+        let mut applied_edits: HashMap<PathBuf, AppliedEdits> = HashMap::new();
+
+        for fix in selected.into_iter().take(max_fixes) {
+            if context.cancellation_token.is_cancelled() {
+                return Err(FennecError::Command(Box::new(std::io::Error::new(
+                    std::io::ErrorKind::Interrupted,
+                    "Operation cancelled",
+                )))
+                .into());
+            }
+
+            let mut updates: Vec<(PathBuf, String, String)> = Vec::new();
+            let mut stale = false;
+            for file in fix.files() {
+                let path = if file.is_absolute() {
+                    file.to_path_buf()
+                } else {
+                    workspace.join(file)
+                };
+                let original = match contents.get(&path) {
+                    Some(content) => content.clone(),
+                    None => match self.file_ops.safe_read_file(&path).await {
+                        Ok(content) => content,
+                        Err(_) => {
+                            stale = true;
+                            break;
+                        }
+                    },
+                };
+
+                let edits = applied_edits.get(file).cloned().unwrap_or_default();
+                let mut replacements: Vec<_> = fix
+                    .replacements
+                    .iter()
+                    .filter(|r| r.file_path == file)
+                    .collect();
+                replacements.sort_by_key(|r| std::cmp::Reverse(r.byte_start));
+
+                let mut updated = original.clone();
+                for replacement in replacements {
+                    let start = adjust_offset(&edits, replacement.byte_start);
+                    let end = adjust_offset(&edits, replacement.byte_end);
+                    if start > end
+                        || end > updated.len()
+                        || !updated.is_char_boundary(start)
+                        || !updated.is_char_boundary(end)
+                    {
+                        stale = true;
+                        break;
+                    }
+                    updated.replace_range(start..end, &replacement.replacement);
+                }
+                if stale {
+                    break;
+                }
+                updates.push((path, original, updated));
+            }
+
+            if stale {
+                report.skipped_stale.push(fix);
+                continue;
+            }
+
+            let hunks: Vec<Hunk> = updates
+                .iter()
+                .flat_map(|(path, original, updated)| {
+                    split_diff_into_hunks(path.clone(), original, updated, HUNK_CONTEXT_LINES)
+                })
+                .collect();
+
+            if !context.dry_run {
+                let requests = updates
+                    .iter()
+                    .map(|(path, _, updated)| FileEditRequest {
+                        path: path.clone(),
+                        strategy: EditStrategy::Replace {
+                            content: updated.clone(),
+                        },
+                        create_backup: false,
+                        create_if_missing: false,
+                        force_binary: false,
+                    })
+                    .collect();
+                let transaction = self
+                    .file_ops
+                    .apply_transaction(requests, &context.sandbox_level, Some(workspace_path))
+                    .await?;
+
+                if let Some(action_log) = &context.action_log {
+                    action_log
+                        .record(transaction.to_action(
+                            "fix-errors".to_string(),
+                            format!("Applied compiler fix: {}", fix.message),
+                        ))
+                        .await;
+                }
+            }
+
+            for replacement in &fix.replacements {
+                let delta = replacement.replacement.len() as isize
+                    - (replacement.byte_end - replacement.byte_start) as isize;
+                applied_edits
+                    .entry(replacement.file_path.clone())
+                    .or_default()
+                    .push((replacement.byte_start, replacement.byte_end, delta));
+            }
+            for (path, _, updated) in updates {
+                contents.insert(path, updated);
+            }
+            report.applied.push(AppliedFix { fix, hunks });
+        }
+
+        Ok(report)
+    }
+
+    fn fix_location(fix: &MachineFix) -> String {
+        fix.replacements
+            .first()
+            .map(|r| format!("{}:{}:{}", r.file_path.display(), r.line, r.column))
+            .unwrap_or_default()
+    }
+
+    /// Render the report plus the diagnostics remaining after the re-check
+    fn format_machine_report(
+        report: &MachineFixReport,
+        check_type: &str,
+        remaining: &[CompilerMessage],
+    ) -> String {
+        let verb = if report.dry_run {
+            "Would apply"
+        } else {
+            "Applied"
+        };
+        let mut output = format!(
+            "{} {} machine-applicable fix(es) from cargo {}\n",
+            verb,
+            report.applied.len(),
+            check_type
+        );
+
+        for (idx, applied) in report.applied.iter().enumerate() {
+            output.push_str(&format!(
+                "\n{}. {} @ {}\n",
+                idx + 1,
+                applied.fix.message,
+                Self::fix_location(&applied.fix)
+            ));
+            for hunk in &applied.hunks {
+                output.push_str(&format!("   {}\n", hunk.summary()));
+            }
+        }
+
+        if !report.skipped_overlapping.is_empty() {
+            output.push_str(&format!(
+                "\nSkipped {} overlapping fix(es):\n",
+                report.skipped_overlapping.len()
+            ));
+            for fix in &report.skipped_overlapping {
+                output.push_str(&format!(
+                    "  - {} @ {}\n",
+                    fix.message,
+                    Self::fix_location(fix)
+                ));
+            }
+        }
+        if !report.skipped_stale.is_empty() {
+            output.push_str(&format!(
+                "\nSkipped {} fix(es) that no longer match the source:\n",
+                report.skipped_stale.len()
+            ));
+            for fix in &report.skipped_stale {
+                output.push_str(&format!(
+                    "  - {} @ {}\n",
+                    fix.message,
+                    Self::fix_location(fix)
+                ));
+            }
+        }
+        if report.over_limit > 0 {
+            output.push_str(&format!(
+                "\n{} more fix(es) not applied (max_fixes reached)\n",
+                report.over_limit
+            ));
+        }
+
+        let is_error = |m: &&CompilerMessage| {
+            m.level == MessageLevel::Error && !m.message.starts_with("aborting due to")
+        };
+        let errors: Vec<&CompilerMessage> = remaining.iter().filter(is_error).collect();
+        let warnings = remaining
+            .iter()
+            .filter(|m| m.level == MessageLevel::Warning)
+            .count();
+        output.push_str(&format!(
+            "\nRemaining after cargo {}: {} error(s), {} warning(s)\n",
+            check_type,
+            errors.len(),
+            warnings
+        ));
+        for error in errors.iter().take(10) {
+            let location = error
+                .spans
+                .first()
+                .map(|s| {
+                    format!(
+                        " @ {}:{}:{}",
+                        s.file_name.display(),
+                        s.line_start,
+                        s.column_start
+                    )
+                })
+                .unwrap_or_default();
+            let code = error
+                .code
+                .as_ref()
+                .map(|c| format!("[{}]", c))
+                .unwrap_or_default();
+            output.push_str(&format!("  error{}: {}{}\n", code, error.message, location));
+        }
+
+        output
+    }
+
+    /// Deterministic mode: apply rustc suggestions, then re-run the check
+    async fn apply_machine_applicable(
+        &self,
+        args: &FixErrorsArgs,
+        context: &CommandContext,
+    ) -> Result<(String, serde_json::Value)> {
+        let messages = self
+            .run_cargo(&args.check_type, &args.cargo_args, context)
+            .await?;
+        let report = self
+            .apply_machine_fixes(&messages, args.max_fixes, context)
+            .await?;
+
+        let remaining = if report.applied.is_empty() || report.dry_run {
+            messages
+        } else {
+            self.run_cargo(&args.check_type, &args.cargo_args, context)
+                .await?
+        };
+
+        let output = Self::format_machine_report(&report, &args.check_type, &remaining);
+        Ok((output, serde_json::to_value(&report)?))
     }
 
     async fn analyze_and_suggest(
@@ -234,9 +575,18 @@ impl CommandExecutor for FixErrorsCommand {
             )))
         })?;
 
+        let description = if args.only_machine_applicable {
+            format!(
+                "Run cargo {} and apply up to {} machine-applicable fixes",
+                args.check_type, args.max_fixes
+            )
+        } else {
+            format!("Run cargo {} and suggest fixes", args.check_type)
+        };
+
         Ok(CommandPreview {
             command_id: Uuid::new_v4(),
-            description: format!("Run cargo {} and suggest fixes", args.check_type),
+            description,
             actions: vec![],
             requires_approval: false,
         })
@@ -254,18 +604,30 @@ impl CommandExecutor for FixErrorsCommand {
             )))
         })?;
 
-        match self.analyze_and_suggest(&args, context).await {
-            Ok(output) => Ok(CommandResult {
+        let outcome = if args.only_machine_applicable {
+            self.apply_machine_applicable(&args, context)
+                .await
+                .map(|(output, data)| (output, Some(data)))
+        } else {
+            self.analyze_and_suggest(&args, context)
+                .await
+                .map(|output| (output, None))
+        };
+
+        match outcome {
+            Ok((output, data)) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: true,
                 output,
                 error: None,
+                data,
             }),
             Err(e) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
+                data: None,
             }),
         }
     }
@@ -309,8 +671,130 @@ impl CommandExecutor for FixErrorsCommand {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::action_log::ActionLog;
+    use std::sync::Arc;
+    use tempfile::TempDir;
     use tokio_util::sync::CancellationToken;
 
+    const FIXTURE_SOURCE: &str = "use std::fmt;\nuse std::io;\n\nfn main() {\n    let mut x = 5;\n    println!(\"{}\", x);\n}\n";
+
+    /// A `cargo check --message-format=json` line whose help child suggests
+    /// replacing `needle` in the fixture with `replacement`
+    fn cargo_line(message: &str, needle: &str, replacement: &str, applicability: &str) -> String {
+        let start = FIXTURE_SOURCE.find(needle).unwrap();
+        let end = start + needle.len();
+        let line = FIXTURE_SOURCE[..start].matches('\n').count() + 1;
+        let column = start
+            - FIXTURE_SOURCE[..start]
+                .rfind('\n')
+                .map(|i| i + 1)
+                .unwrap_or(0)
+            + 1;
+        let span = |suggestion: serde_json::Value, applicability: serde_json::Value| {
+            serde_json::json!({
+                "file_name": "src/main.rs",
+                "byte_start": start,
+                "byte_end": end,
+                "line_start": line,
+                "line_end": line,
+                "column_start": column,
+                "column_end": column + needle.len(),
+                "text": [],
+                "label": null,
+                "suggested_replacement": suggestion,
+                "suggestion_applicability": applicability
+            })
+        };
+
+        serde_json::json!({
+            "reason": "compiler-message",
+            "message": {
+                "level": "warning",
+                "message": message,
+                "code": null,
+                "spans": [span(serde_json::Value::Null, serde_json::Value::Null)],
+                "children": [{
+                    "level": "help",
+                    "message": "apply the suggestion",
+                    "code": null,
+                    "spans": [span(replacement.into(), applicability.into())],
+                    "children": [],
+                    "rendered": null
+                }],
+                "rendered": null
+            }
+        })
+        .to_string()
+    }
+
+    fn fixture_messages() -> Vec<CompilerMessage> {
+        [
+            cargo_line(
+                "unused import: `std::fmt`",
+                "use std::fmt;\n",
+                "",
+                "MachineApplicable",
+            ),
+            cargo_line(
+                "unused import: `std::io`",
+                "use std::io;\n",
+                "",
+                "MachineApplicable",
+            ),
+            cargo_line(
+                "variable does not need to be mutable",
+                "mut ",
+                "",
+                "MachineApplicable",
+            ),
+            // Overlaps the `mut` fix above, so only one of them may apply.
+            cargo_line(
+                "redundant binding",
+                "let mut x",
+                "let y",
+                "MachineApplicable",
+            ),
+            // Not machine-applicable: must never be applied.
+            cargo_line("maybe rename", "println", "eprintln", "MaybeIncorrect"),
+            // rustc repeats suggestions once per target.
+            cargo_line(
+                "unused import: `std::fmt`",
+                "use std::fmt;\n",
+                "",
+                "MachineApplicable",
+            ),
+        ]
+        .iter()
+        .filter_map(|line| parse_cargo_json(line))
+        .collect()
+    }
+
+    fn fixture_workspace() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+        std::fs::write(temp_dir.path().join("src/main.rs"), FIXTURE_SOURCE).unwrap();
+        temp_dir
+    }
+
+    fn fixture_context(
+        root: &Path,
+        dry_run: bool,
+        action_log: Option<Arc<ActionLog>>,
+    ) -> CommandContext {
+        CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: Some(root.to_string_lossy().to_string()),
+            sandbox_level: SandboxLevel::WorkspaceWrite,
+            dry_run,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log,
+            timeout: None,
+            progress: None,
+        }
+    }
+
     #[test]
     fn test_parse_confidence() {
         assert_eq!(
@@ -373,10 +857,86 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
         assert!(!result.success);
         assert!(result.error.is_some());
     }
+
+    #[tokio::test]
+    async fn test_apply_machine_applicable_fixes() {
+        let temp_dir = fixture_workspace();
+        let action_log = Arc::new(ActionLog::new());
+        let context = fixture_context(temp_dir.path(), false, Some(action_log.clone()));
+        let command = FixErrorsCommand::new();
+
+        let report = command
+            .apply_machine_fixes(&fixture_messages(), 20, &context)
+            .await
+            .unwrap();
+
+        assert_eq!(report.applied.len(), 3);
+        assert_eq!(report.skipped_overlapping.len(), 1);
+        assert_eq!(
+            report.skipped_overlapping[0].message,
+            "redundant binding: apply the suggestion"
+        );
+        assert!(report.applied.iter().all(|a| !a.hunks.is_empty()));
+
+        let content = std::fs::read_to_string(temp_dir.path().join("src/main.rs")).unwrap();
+        assert_eq!(
+            content,
+            "\nfn main() {\n    let x = 5;\n    println!(\"{}\", x);\n}\n"
+        );
+
+        // One undoable entry per applied fix.
+        let history = action_log.get_history().await;
+        assert_eq!(history.len(), 3);
+        assert!(history.iter().all(|a| a.command == "fix-errors"));
+
+        let remaining = vec![parse_cargo_json(
+            r#"{"reason":"compiler-message","message":{"level":"error","message":"cannot find value `y` in this scope","code":{"code":"E0425"},"spans":[{"file_name":"src/main.rs","line_start":3,"line_end":3,"column_start":20,"column_end":21,"text":[],"label":null,"suggested_replacement":null}],"children":[],"rendered":null}}"#,
+        )
+        .unwrap()];
+        let output = FixErrorsCommand::format_machine_report(&report, "check", &remaining);
+        assert!(output.starts_with("Applied 3 machine-applicable fix(es) from cargo check"));
+        assert!(output.contains("Skipped 1 overlapping fix(es)"));
+        assert!(output.contains("Remaining after cargo check: 1 error(s), 0 warning(s)"));
+        assert!(
+            output.contains("error[E0425]: cannot find value `y` in this scope @ src/main.rs:3:20")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_machine_fixes_dry_run_and_limit() {
+        let temp_dir = fixture_workspace();
+        let command = FixErrorsCommand::new();
+
+        let context = fixture_context(temp_dir.path(), true, None);
+        let report = command
+            .apply_machine_fixes(&fixture_messages(), 1, &context)
+            .await
+            .unwrap();
+
+        assert!(report.dry_run);
+        assert_eq!(report.applied.len(), 1);
+        assert_eq!(report.over_limit, 2);
+        assert_eq!(
+            std::fs::read_to_string(temp_dir.path().join("src/main.rs")).unwrap(),
+            FIXTURE_SOURCE
+        );
+
+        // Suggestions computed against different file contents are skipped.
+        std::fs::write(temp_dir.path().join("src/main.rs"), "fn main() {}\n").unwrap();
+        let context = fixture_context(temp_dir.path(), false, None);
+        let report = command
+            .apply_machine_fixes(&fixture_messages(), 20, &context)
+            .await
+            .unwrap();
+        assert_eq!(report.skipped_stale.len(), 3);
+        assert!(report.applied.is_empty());
+    }
 }
@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
+use tokio::sync::mpsc;
 
 /// Represents a git commit
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,6 +25,30 @@ pub struct FileChange {
     pub change_type: ChangeType,
     pub insertions: usize,
     pub deletions: usize,
+    /// Previous path for renames
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<PathBuf>,
+    /// Binary files have no line counts
+    #[serde(default)]
+    pub binary: bool,
+}
+
+/// Which changes a diff is computed over
+#[derive(Debug, Clone, Copy)]
+pub enum DiffTarget<'a> {
+    /// Changes staged in the index
+    Staged,
+    /// A revision range such as `main...HEAD`
+    Range(&'a str),
+}
+
+impl DiffTarget<'_> {
+    fn apply(&self, cmd: &mut Command) {
+        match self {
+            DiffTarget::Staged => cmd.arg("--cached"),
+            DiffTarget::Range(range) => cmd.arg(range),
+        };
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -191,6 +217,323 @@ pub async fn get_diff(
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
+async fn run_git_diff(
+    repo_path: &str,
+    target: DiffTarget<'_>,
+    flags: &[&str],
+    path: Option<&str>,
+) -> Result<String, std::io::Error> {
+    let mut cmd = Command::new("git");
+    cmd.current_dir(repo_path);
+    cmd.arg("diff");
+    target.apply(&mut cmd);
+    cmd.args(flags);
+    if let Some(path) = path {
+        cmd.arg("--").arg(path);
+    }
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::null());
+
+    let output = cmd.output().await?;
+    if !output.status.success() {
+        return Err(std::io::Error::other("Failed to get git diff"));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Per-file change type and line counts, with rename detection
+pub async fn get_file_changes(
+    repo_path: &str,
+    target: DiffTarget<'_>,
+) -> Result<Vec<FileChange>, std::io::Error> {
+    let numstat = run_git_diff(repo_path, target, &["-M", "--numstat", "-z"], None).await?;
+    let name_status = run_git_diff(repo_path, target, &["-M", "--name-status", "-z"], None).await?;
+    Ok(parse_file_changes(&numstat, &name_status))
+}
+
+/// Full-context diff of a single file, for callers that need to know which
+/// section of the file a change landed in
+pub async fn get_file_diff(
+    repo_path: &str,
+    target: DiffTarget<'_>,
+    path: &str,
+) -> Result<String, std::io::Error> {
+    run_git_diff(repo_path, target, &["-M", "--unified=100000"], Some(path)).await
+}
+
+/// Combine `git diff --numstat -z` and `git diff --name-status -z` output
+fn parse_file_changes(numstat: &str, name_status: &str) -> Vec<FileChange> {
+    let mut changes = Vec::new();
+    let mut fields = name_status.split('\0').filter(|f| !f.is_empty());
+    while let Some(status) = fields.next() {
+        let (change_type, old_path) = match status.chars().next() {
+            Some('A') => (ChangeType::Added, None),
+            Some('D') => (ChangeType::Deleted, None),
+            Some('R') | Some('C') => (ChangeType::Renamed, fields.next()),
+            _ => (ChangeType::Modified, None),
+        };
+        let Some(path) = fields.next() else {
+            break;
+        };
+        changes.push(FileChange {
+            path: PathBuf::from(path),
+            change_type,
+            insertions: 0,
+            deletions: 0,
+            old_path: old_path.map(PathBuf::from),
+            binary: false,
+        });
+    }
+
+    // Renamed entries are "<ins>\t<del>\t" followed by old and new paths as
+    // separate fields
+    let mut fields = numstat.split('\0');
+    while let Some(record) = fields.next() {
+        let mut parts = record.splitn(3, '\t');
+        let (Some(insertions), Some(deletions), Some(path)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        let path = if path.is_empty() {
+            let _old = fields.next();
+            fields.next().unwrap_or_default()
+        } else {
+            path
+        };
+
+        if let Some(change) = changes.iter_mut().find(|c| c.path == Path::new(path)) {
+            change.binary = insertions == "-";
+            change.insertions = insertions.parse().unwrap_or(0);
+            change.deletions = deletions.parse().unwrap_or(0);
+        }
+    }
+
+    changes
+}
+
+/// Run git in `repo_path` and return its stdout, reporting stderr on failure
+async fn run_git(repo_path: &str, args: &[&str]) -> Result<String, std::io::Error> {
+    let output = Command::new("git")
+        .current_dir(repo_path)
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(std::io::Error::other(format!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Best common ancestor of two revisions, as a full commit hash
+pub async fn merge_base(repo_path: &str, a: &str, b: &str) -> Result<String, std::io::Error> {
+    let output = run_git(repo_path, &["merge-base", a, b]).await?;
+    Ok(output.trim().to_string())
+}
+
+/// Files changed on `head` since it diverged from `base`, with rename
+/// detection; work that landed on `base` in the meantime is excluded
+pub async fn changes_between(
+    repo_path: &str,
+    base: &str,
+    head: &str,
+) -> Result<Vec<FileChange>, std::io::Error> {
+    let base = merge_base(repo_path, base, head).await?;
+    let range = format!("{}..{}", base, head);
+    get_file_changes(repo_path, DiffTarget::Range(&range)).await
+}
+
+/// Commits reachable from `head` but not from `base`, newest first
+pub async fn commits_between(
+    repo_path: &str,
+    base: &str,
+    head: &str,
+    limit: Option<usize>,
+) -> Result<Vec<GitCommit>, std::io::Error> {
+    let range = format!("{}..{}", base, head);
+    let limit = limit.map(|limit| format!("--max-count={}", limit));
+
+    // Each commit starts with a record separator followed by NUL-separated
+    // header fields; the numstat lines for the commit come after the last NUL
+    let mut args = vec![
+        "log",
+        "--no-color",
+        "--format=%x1e%H%x00%an%x00%ae%x00%aI%x00%s%x00",
+        "--numstat",
+    ];
+    if let Some(limit) = &limit {
+        args.push(limit);
+    }
+    args.push(&range);
+    args.push("--");
+
+    let output = run_git(repo_path, &args).await?;
+    Ok(parse_log_records(&output))
+}
+
+/// Parse `git log` output produced by [`commits_between`]
+fn parse_log_records(log: &str) -> Vec<GitCommit> {
+    log.split('\x1e')
+        .filter_map(|record| {
+            let mut fields = record.splitn(6, '\0');
+            let hash = fields.next()?.trim();
+            if hash.is_empty() {
+                return None;
+            }
+            let author = fields.next()?;
+            let email = fields.next()?;
+            let date = fields.next()?;
+            let message = fields.next()?;
+
+            let mut files_changed = 0;
+            let mut insertions = 0;
+            let mut deletions = 0;
+            for line in fields.next().unwrap_or_default().lines() {
+                let mut parts = line.splitn(3, '\t');
+                let (Some(added), Some(removed), Some(_path)) =
+                    (parts.next(), parts.next(), parts.next())
+                else {
+                    continue;
+                };
+                // Binary files report "-" for both counts
+                files_changed += 1;
+                insertions += added.parse::<usize>().unwrap_or(0);
+                deletions += removed.parse::<usize>().unwrap_or(0);
+            }
+
+            Some(GitCommit {
+                hash: hash.to_string(),
+                author: author.to_string(),
+                email: email.to_string(),
+                date: date.to_string(),
+                message: message.to_string(),
+                files_changed,
+                insertions,
+                deletions,
+            })
+        })
+        .collect()
+}
+
+/// Working tree state of a path as reported by `git status`, ordered by
+/// precedence when summarizing a directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum GitFileStatus {
+    Ignored,
+    Untracked,
+    /// Changes staged in the index and none in the working tree
+    Staged,
+    /// Unstaged changes in the working tree
+    Modified,
+    /// Unmerged paths
+    Conflicted,
+}
+
+impl GitFileStatus {
+    /// Classify the two-letter `XY` code of porcelain status output
+    pub fn from_porcelain(index: u8, worktree: u8) -> Option<Self> {
+        match (index, worktree) {
+            (b'?', b'?') => Some(Self::Untracked),
+            (b'!', b'!') => Some(Self::Ignored),
+            (b'U', _) | (_, b'U') | (b'A', b'A') | (b'D', b'D') => Some(Self::Conflicted),
+            (_, b'M' | b'D' | b'T') => Some(Self::Modified),
+            (b'M' | b'A' | b'D' | b'R' | b'C' | b'T', b' ') => Some(Self::Staged),
+            _ => None,
+        }
+    }
+}
+
+/// Entries sent per batch by [`stream_status`]
+const STATUS_BATCH_SIZE: usize = 256;
+
+/// Run `git status` for the repository containing `path`, sending entries in
+/// batches as git produces them so callers can show results before a large
+/// repository has been fully scanned. Paths are absolute; ignored paths are
+/// included only when `include_ignored` is set. Returns early without error
+/// when the receiver is dropped.
+pub async fn stream_status(
+    path: &Path,
+    include_ignored: bool,
+    batches: mpsc::UnboundedSender<Vec<(PathBuf, GitFileStatus)>>,
+) -> Result<(), std::io::Error> {
+    let path = path.to_string_lossy();
+    let toplevel = PathBuf::from(
+        run_git(&path, &["rev-parse", "--show-toplevel"])
+            .await?
+            .trim(),
+    );
+
+    // Without optional locks git leaves the index alone, so a watcher on the
+    // repository isn't triggered by the status run itself
+    let mut cmd = Command::new("git");
+    cmd.current_dir(path.as_ref())
+        .args([
+            "--no-optional-locks",
+            "status",
+            "--porcelain=v1",
+            "-z",
+            "--untracked-files=all",
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true);
+    if include_ignored {
+        cmd.arg("--ignored");
+    }
+    let mut child = cmd.spawn()?;
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| std::io::Error::other("git status has no stdout"))?;
+    let mut reader = BufReader::new(stdout);
+
+    let mut batch = Vec::new();
+    let mut record = Vec::new();
+    loop {
+        record.clear();
+        if reader.read_until(0, &mut record).await? == 0 {
+            break;
+        }
+        let record = record.strip_suffix(&[0]).unwrap_or(&record);
+        if record.len() < 4 {
+            continue;
+        }
+        let (index, worktree) = (record[0], record[1]);
+        let entry_path = String::from_utf8_lossy(&record[3..]).to_string();
+        // Renames and copies are followed by their original path
+        if matches!(index, b'R' | b'C') {
+            let mut original = Vec::new();
+            reader.read_until(0, &mut original).await?;
+        }
+
+        if let Some(status) = GitFileStatus::from_porcelain(index, worktree) {
+            batch.push((toplevel.join(entry_path), status));
+        }
+        if batch.len() >= STATUS_BATCH_SIZE && batches.send(std::mem::take(&mut batch)).is_err() {
+            return Ok(());
+        }
+    }
+    if !batch.is_empty() && batches.send(batch).is_err() {
+        return Ok(());
+    }
+
+    let status = child.wait().await?;
+    if !status.success() {
+        return Err(std::io::Error::other(format!(
+            "git status failed with {}",
+            status
+        )));
+    }
+    Ok(())
+}
+
 /// Generate a PR summary from commits
 pub fn generate_pr_summary(commits: &[GitCommit]) -> String {
     if commits.is_empty() {
@@ -257,101 +600,92 @@ pub fn generate_pr_summary(commits: &[GitCommit]) -> String {
 
 /// Generate a commit message template based on staged changes
 pub async fn generate_commit_template(repo_path: &str) -> Result<String, std::io::Error> {
-    // Get list of staged files
-    let output = Command::new("git")
-        .current_dir(repo_path)
-        .arg("diff")
-        .arg("--cached")
-        .arg("--name-status")
-        .output()
-        .await?;
-
-    if !output.status.success() {
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "Failed to get staged changes",
-        ));
-    }
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let lines: Vec<&str> = stdout.lines().collect();
-
-    if lines.is_empty() {
+    let changes = get_file_changes(repo_path, DiffTarget::Staged).await?;
+    if changes.is_empty() {
         return Ok(
             "No staged changes found.\n\nPlease stage your changes with 'git add' first."
                 .to_string(),
         );
     }
 
-    // Analyze changes
-    let mut added_files = Vec::new();
-    let mut modified_files = Vec::new();
-    let mut deleted_files = Vec::new();
-
-    for line in lines {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() >= 2 {
-            let status = parts[0];
-            let file = parts[1];
+    Ok(render_commit_template(
+        &changes,
+        infer_commit_type(&changes),
+        None,
+    ))
+}
 
-            match status {
-                "A" => added_files.push(file),
-                "M" => modified_files.push(file),
-                "D" => deleted_files.push(file),
-                _ => {}
-            }
-        }
+/// Guess a conventional-commit type from the shape of a change set
+pub fn infer_commit_type(changes: &[FileChange]) -> &'static str {
+    let count = |kind: ChangeType| changes.iter().filter(|c| c.change_type == kind).count();
+    let added = count(ChangeType::Added);
+    let deleted = count(ChangeType::Deleted);
+    let modified: Vec<String> = changes
+        .iter()
+        .filter(|c| matches!(c.change_type, ChangeType::Modified | ChangeType::Renamed))
+        .map(|c| c.path.display().to_string())
+        .collect();
+
+    if added > 0 && modified.is_empty() && deleted == 0 {
+        "feat"
+    } else if deleted > 0 && added == 0 && modified.is_empty() {
+        "chore"
+    } else if modified.iter().any(|f| f.contains("test")) {
+        "test"
+    } else if modified
+        .iter()
+        .any(|f| f.contains(".md") || f.contains("README"))
+    {
+        "docs"
+    } else {
+        "fix"
     }
+}
 
-    // Determine commit type
-    let commit_type =
-        if !added_files.is_empty() && modified_files.is_empty() && deleted_files.is_empty() {
-            "feat"
-        } else if !deleted_files.is_empty() && added_files.is_empty() && modified_files.is_empty() {
-            "chore"
-        } else if modified_files.iter().any(|f| f.contains("test")) {
-            "test"
-        } else if modified_files
+/// Render a commit message template with a `type(scope): ` header
+pub fn render_commit_template(
+    changes: &[FileChange],
+    commit_type: &str,
+    scope: Option<&str>,
+) -> String {
+    let files = |kind: ChangeType| -> Vec<String> {
+        changes
             .iter()
-            .any(|f| f.contains(".md") || f.contains("README"))
-        {
-            "docs"
-        } else {
-            "fix"
-        };
+            .filter(|c| c.change_type == kind)
+            .map(|c| c.path.display().to_string())
+            .collect()
+    };
+    let added_files = files(ChangeType::Added);
+    let mut modified_files = files(ChangeType::Modified);
+    modified_files.extend(files(ChangeType::Renamed));
+    let deleted_files = files(ChangeType::Deleted);
 
     // Generate template
     let mut template = String::new();
-    template.push_str(&format!("{}: ", commit_type));
+    match scope {
+        Some(scope) => template.push_str(&format!("{}({}): ", commit_type, scope)),
+        None => template.push_str(&format!("{}: ", commit_type)),
+    }
 
     // Add brief description placeholder
-    template.push_str("<brief description>\n\n");
+    template.push_str(COMMIT_SUBJECT_PLACEHOLDER);
+    template.push_str("\n\n");
 
     // Add detailed description
     template.push_str("## Changes\n\n");
 
-    if !added_files.is_empty() {
-        template.push_str("**Added:**\n");
-        for file in &added_files {
-            template.push_str(&format!("- {}\n", file));
-        }
-        template.push('\n');
-    }
-
-    if !modified_files.is_empty() {
-        template.push_str("**Modified:**\n");
-        for file in &modified_files {
-            template.push_str(&format!("- {}\n", file));
-        }
-        template.push('\n');
-    }
-
-    if !deleted_files.is_empty() {
-        template.push_str("**Deleted:**\n");
-        for file in &deleted_files {
-            template.push_str(&format!("- {}\n", file));
+    for (label, list) in [
+        ("Added", &added_files),
+        ("Modified", &modified_files),
+        ("Deleted", &deleted_files),
+    ] {
+        if !list.is_empty() {
+            template.push_str(&format!("**{}:**\n", label));
+            for file in list {
+                template.push_str(&format!("- {}\n", file));
+            }
+            template.push('\n');
         }
-        template.push('\n');
     }
 
     template.push_str("## Description\n\n");
@@ -360,13 +694,30 @@ pub async fn generate_commit_template(repo_path: &str) -> Result<String, std::io
     template.push_str("## Testing\n\n");
     template.push_str("<how this was tested>\n");
 
-    Ok(template)
+    template
 }
 
+/// Subject placeholder left in generated commit templates
+pub const COMMIT_SUBJECT_PLACEHOLDER: &str = "<brief description>";
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_porcelain_status_codes() {
+        let status = |code: &[u8; 2]| GitFileStatus::from_porcelain(code[0], code[1]);
+        assert_eq!(status(b"??"), Some(GitFileStatus::Untracked));
+        assert_eq!(status(b"!!"), Some(GitFileStatus::Ignored));
+        assert_eq!(status(b"UU"), Some(GitFileStatus::Conflicted));
+        assert_eq!(status(b"AA"), Some(GitFileStatus::Conflicted));
+        assert_eq!(status(b" M"), Some(GitFileStatus::Modified));
+        assert_eq!(status(b"MM"), Some(GitFileStatus::Modified));
+        assert_eq!(status(b"A "), Some(GitFileStatus::Staged));
+        assert_eq!(status(b"R "), Some(GitFileStatus::Staged));
+        assert_eq!(status(b"  "), None);
+    }
+
     #[test]
     fn test_parse_git_log_single_commit() {
         let log = "abc123|John Doe|john@example.com|2024-01-15 10:00:00|feat: Add new feature\n 1 file changed, 5 insertions(+), 2 deletions(-)";
@@ -427,4 +778,138 @@ mod tests {
         let summary = generate_pr_summary(&[]);
         assert_eq!(summary, "No commits found.");
     }
+
+    #[test]
+    fn test_parse_log_records() {
+        let log = "\x1eabc123\x00Jane | Doe\x00jane@example.com\x002024-01-15T10:00:00+00:00\x00fix: a|b\x00\n\n3\t1\tsrc/lib.rs\n-\t-\tlogo.png\n\x1edef456\x00John\x00john@example.com\x002024-01-14T09:00:00+00:00\x00empty\x00\n";
+
+        let commits = parse_log_records(log);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].author, "Jane | Doe");
+        assert_eq!(commits[0].message, "fix: a|b");
+        assert_eq!(commits[0].files_changed, 2);
+        assert_eq!((commits[0].insertions, commits[0].deletions), (3, 1));
+        assert_eq!(commits[1].hash, "def456");
+        assert_eq!(commits[1].files_changed, 0);
+    }
+
+    fn git(dir: &Path, args: &[&str]) {
+        let output = std::process::Command::new("git")
+            .current_dir(dir)
+            .args(args)
+            .env("GIT_AUTHOR_NAME", "Test")
+            .env("GIT_AUTHOR_EMAIL", "test@example.com")
+            .env("GIT_AUTHOR_DATE", "2024-01-15T10:00:00+00:00")
+            .env("GIT_COMMITTER_NAME", "Test")
+            .env("GIT_COMMITTER_EMAIL", "test@example.com")
+            .env("GIT_COMMITTER_DATE", "2024-01-15T10:00:00+00:00")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+    }
+
+    fn commit(dir: &Path, files: &[(&str, &str)], message: &str) {
+        for (path, content) in files {
+            std::fs::write(dir.join(path), content).unwrap();
+        }
+        git(dir, &["add", "-A"]);
+        git(dir, &["commit", "-q", "-m", message]);
+    }
+
+    /// `main` and `feature` diverge after a shared root commit; `feature`
+    /// renames a file and adds another while `main` moves on separately
+    fn branched_repo() -> tempfile::TempDir {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        git(dir, &["init", "-q", "-b", "main"]);
+        commit(
+            dir,
+            &[
+                (
+                    "lib.rs",
+                    "pub fn one() -> u32 {\n    1\n}\n\npub fn two() -> u32 {\n    2\n}\n",
+                ),
+                ("README.md", "# Fixture\n"),
+            ],
+            "initial",
+        );
+
+        git(dir, &["checkout", "-q", "-b", "feature"]);
+        git(dir, &["mv", "lib.rs", "core.rs"]);
+        commit(dir, &[], "refactor: rename lib.rs to core.rs");
+        commit(
+            dir,
+            &[("extra.rs", "pub fn three() {}\n")],
+            "feat: add extra",
+        );
+
+        git(dir, &["checkout", "-q", "main"]);
+        commit(
+            dir,
+            &[("README.md", "# Fixture\n\nMore docs.\n")],
+            "docs: expand readme",
+        );
+
+        temp_dir
+    }
+
+    #[tokio::test]
+    async fn test_branch_range_helpers() {
+        let repo = branched_repo();
+        let dir = repo.path().to_str().unwrap();
+
+        let base = merge_base(dir, "main", "feature").await.unwrap();
+        let root = run_git(dir, &["rev-list", "--max-parents=0", "HEAD"])
+            .await
+            .unwrap();
+        assert_eq!(base, root.trim());
+
+        let commits = commits_between(dir, "main", "feature", None).await.unwrap();
+        let subjects: Vec<&str> = commits.iter().map(|c| c.message.as_str()).collect();
+        assert_eq!(
+            subjects,
+            vec!["feat: add extra", "refactor: rename lib.rs to core.rs"]
+        );
+        assert_eq!(commits[0].author, "Test");
+        assert_eq!(commits[0].date, "2024-01-15T10:00:00+00:00");
+        assert_eq!((commits[0].files_changed, commits[0].insertions), (1, 1));
+
+        let limited = commits_between(dir, "main", "feature", Some(1))
+            .await
+            .unwrap();
+        assert_eq!(limited.len(), 1);
+
+        // The README change on main is not part of the feature branch
+        let mut changes = changes_between(dir, "main", "feature").await.unwrap();
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].path, PathBuf::from("core.rs"));
+        assert_eq!(changes[0].change_type, ChangeType::Renamed);
+        assert_eq!(changes[0].old_path, Some(PathBuf::from("lib.rs")));
+        assert_eq!(changes[1].path, PathBuf::from("extra.rs"));
+        assert_eq!(changes[1].change_type, ChangeType::Added);
+
+        let error = merge_base(dir, "main", "missing").await.unwrap_err();
+        assert!(error.to_string().contains("git merge-base failed"));
+    }
+
+    #[test]
+    fn test_parse_file_changes() {
+        let name_status =
+            "M\0src/lib.rs\0A\0docs/new.md\0D\0tests/old.rs\0R087\0a.rs\0b.rs\0M\0logo.png\0";
+        let numstat = "5\t2\tsrc/lib.rs\x003\t0\tdocs/new.md\x000\t10\ttests/old.rs\x001\t1\t\0a.rs\0b.rs\0-\t-\tlogo.png\0";
+
+        let changes = parse_file_changes(numstat, name_status);
+        assert_eq!(changes.len(), 5);
+        assert_eq!(changes[0].change_type, ChangeType::Modified);
+        assert_eq!((changes[0].insertions, changes[0].deletions), (5, 2));
+        assert_eq!(changes[1].change_type, ChangeType::Added);
+        assert_eq!(changes[2].change_type, ChangeType::Deleted);
+        assert_eq!(changes[2].deletions, 10);
+        assert_eq!(changes[3].change_type, ChangeType::Renamed);
+        assert_eq!(changes[3].path, PathBuf::from("b.rs"));
+        assert_eq!(changes[3].old_path, Some(PathBuf::from("a.rs")));
+        assert_eq!((changes[3].insertions, changes[3].deletions), (1, 1));
+        assert!(changes[4].binary);
+    }
 }
@@ -1,27 +1,355 @@
-use crate::action_log::ActionLog;
+use crate::action_log::{Action, ActionLog};
 use crate::registry::{CommandContext, CommandDescriptor, CommandExecutor};
 use anyhow::Result;
-use fennec_core::command::{Capability, CommandPreview, CommandResult};
+use chrono::{DateTime, NaiveDate, Utc};
+use fennec_core::{
+    command::{Capability, CommandPreview, CommandResult},
+    error::FennecError,
+};
 use fennec_security::SandboxLevel;
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::sync::Arc;
+use tokio::sync::RwLock;
 use uuid::Uuid;
 
+/// Version of the JSON document emitted by `history --format json`
+pub const HISTORY_SCHEMA_VERSION: u32 = 1;
+
+/// Maximum number of executions kept by [`ExecutionHistory`]
+const MAX_EXECUTION_HISTORY: usize = 1000;
+
+/// Maximum length of the argument summary shown for an entry
+const ARGS_SUMMARY_LEN: usize = 120;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoryArgs {
     #[serde(default = "default_limit")]
     pub limit: usize,
+    /// Number of newest matching entries to skip
+    #[serde(default)]
+    pub offset: usize,
     #[serde(default)]
     pub show_all: bool,
+    /// Only show entries for this command
+    #[serde(default)]
+    pub command: Option<String>,
+    /// Only show entries at or after this time (RFC 3339 or YYYY-MM-DD)
+    #[serde(default)]
+    pub since: Option<String>,
+    /// Only show entries at or before this time (RFC 3339 or YYYY-MM-DD,
+    /// inclusive of the whole day)
+    #[serde(default)]
+    pub until: Option<String>,
+    #[serde(default)]
+    pub status: Option<HistoryStatus>,
+    /// Case-insensitive text matched against args, description, errors and
+    /// affected files
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub format: HistoryFormat,
+}
+
+impl Default for HistoryArgs {
+    fn default() -> Self {
+        Self {
+            limit: default_limit(),
+            offset: 0,
+            show_all: false,
+            command: None,
+            since: None,
+            until: None,
+            status: None,
+            text: None,
+            format: HistoryFormat::default(),
+        }
+    }
 }
 
 fn default_limit() -> usize {
     20
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryStatus {
+    Success,
+    Failure,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// A command execution observed by the registry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionRecord {
+    pub id: Uuid,
+    pub command: String,
+    /// When execution started
+    pub timestamp: DateTime<Utc>,
+    pub args_summary: String,
+    pub success: bool,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+impl ExecutionRecord {
+    /// Whether `action` was recorded by this execution
+    fn produced(&self, action: &Action) -> bool {
+        // Durations are truncated to whole milliseconds
+        let end = self.timestamp + chrono::Duration::milliseconds(self.duration_ms as i64 + 1);
+        action.command == self.command
+            && action.timestamp >= self.timestamp
+            && action.timestamp <= end
+    }
+}
+
+/// Bounded log of command executions, fed by
+/// [`HistoryMiddleware`](crate::middleware::HistoryMiddleware)
+#[derive(Debug)]
+pub struct ExecutionHistory {
+    records: RwLock<VecDeque<ExecutionRecord>>,
+    max_size: usize,
+}
+
+impl ExecutionHistory {
+    pub fn new() -> Self {
+        Self::with_max_size(MAX_EXECUTION_HISTORY)
+    }
+
+    pub fn with_max_size(max_size: usize) -> Self {
+        Self {
+            records: RwLock::new(VecDeque::new()),
+            max_size,
+        }
+    }
+
+    pub async fn record(&self, record: ExecutionRecord) {
+        let mut records = self.records.write().await;
+        records.push_back(record);
+        while records.len() > self.max_size {
+            records.pop_front();
+        }
+    }
+
+    /// All records in chronological order
+    pub async fn records(&self) -> Vec<ExecutionRecord> {
+        self.records.read().await.iter().cloned().collect()
+    }
+}
+
+impl Default for ExecutionHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Summarize command arguments on one line for history listings
+pub fn summarize_args(args: &serde_json::Value) -> String {
+    let summary = match args {
+        serde_json::Value::Object(map) if map.is_empty() => String::new(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    };
+    if summary.chars().count() <= ARGS_SUMMARY_LEN {
+        summary
+    } else {
+        let truncated: String = summary.chars().take(ARGS_SUMMARY_LEN - 3).collect();
+        format!("{}...", truncated)
+    }
+}
+
+/// One row of the unified history: a command execution together with the
+/// file changes it made
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// 1-based position in the unfiltered history
+    pub index: usize,
+    pub command: String,
+    pub timestamp: DateTime<Utc>,
+    pub args_summary: Option<String>,
+    pub description: Option<String>,
+    pub success: bool,
+    pub error: Option<String>,
+    pub duration_ms: Option<u64>,
+    pub affected_files: Vec<String>,
+    /// Whether the entry's file changes are applied; `None` when it made none
+    pub applied: Option<bool>,
+}
+
+/// JSON document emitted by `history --format json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryPage {
+    pub schema_version: u32,
+    /// Entries matching the filters, before pagination
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    /// Matching entries in chronological order
+    pub entries: Vec<HistoryEntry>,
+}
+
+/// Merge executions and action-log entries into one chronological list.
+/// Actions recorded during an execution are folded into it; the rest stand
+/// alone.
+fn unify(
+    records: Vec<ExecutionRecord>,
+    actions: &[Action],
+    current_index: usize,
+) -> Vec<HistoryEntry> {
+    let mut entries: Vec<(HistoryEntry, Option<ExecutionRecord>)> = records
+        .into_iter()
+        .map(|record| {
+            let entry = HistoryEntry {
+                index: 0,
+                command: record.command.clone(),
+                timestamp: record.timestamp,
+                args_summary: Some(record.args_summary.clone()).filter(|s| !s.is_empty()),
+                description: None,
+                success: record.success,
+                error: record.error.clone(),
+                duration_ms: Some(record.duration_ms),
+                affected_files: Vec::new(),
+                applied: None,
+            };
+            (entry, Some(record))
+        })
+        .collect();
+
+    for (idx, action) in actions.iter().enumerate() {
+        let applied = idx < current_index;
+        let files = action
+            .state_after
+            .affected_paths()
+            .into_iter()
+            .map(|p| p.display().to_string());
+
+        let owner = entries.iter_mut().rev().find(|(_, record)| {
+            record
+                .as_ref()
+                .is_some_and(|record| record.produced(action))
+        });
+        match owner {
+            Some((entry, _)) => {
+                entry.affected_files.extend(files);
+                entry
+                    .description
+                    .get_or_insert_with(|| action.description.clone());
+                entry.applied = Some(entry.applied.unwrap_or(true) && applied);
+            }
+            None => entries.push((
+                HistoryEntry {
+                    index: 0,
+                    command: action.command.clone(),
+                    timestamp: action.timestamp,
+                    args_summary: None,
+                    description: Some(action.description.clone()),
+                    success: true,
+                    error: None,
+                    duration_ms: None,
+                    affected_files: files.collect(),
+                    applied: Some(applied),
+                },
+                None,
+            )),
+        }
+    }
+
+    let mut entries: Vec<HistoryEntry> = entries.into_iter().map(|(entry, _)| entry).collect();
+    entries.sort_by_key(|entry| entry.timestamp);
+    for (idx, entry) in entries.iter_mut().enumerate() {
+        entry.index = idx + 1;
+    }
+    entries
+}
+
+/// Parse a `since`/`until` bound. Date-only values cover the whole day.
+fn parse_bound(value: &str, end_of_day: bool) -> Result<DateTime<Utc>> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+        FennecError::Command(Box::new(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Invalid date '{}': expected RFC 3339 or YYYY-MM-DD", value),
+        )))
+    })?;
+    let time = if end_of_day {
+        date.and_hms_milli_opt(23, 59, 59, 999)
+    } else {
+        date.and_hms_opt(0, 0, 0)
+    };
+    Ok(time.expect("valid time of day").and_utc())
+}
+
+/// Compiled form of the filter arguments
+struct HistoryFilter {
+    command: Option<String>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    status: Option<HistoryStatus>,
+    text: Option<String>,
+}
+
+impl HistoryFilter {
+    fn new(args: &HistoryArgs) -> Result<Self> {
+        Ok(Self {
+            command: args.command.clone(),
+            since: args
+                .since
+                .as_deref()
+                .map(|s| parse_bound(s, false))
+                .transpose()?,
+            until: args
+                .until
+                .as_deref()
+                .map(|s| parse_bound(s, true))
+                .transpose()?,
+            status: args.status,
+            text: args.text.as_ref().map(|t| t.to_lowercase()),
+        })
+    }
+
+    fn matches(&self, entry: &HistoryEntry) -> bool {
+        if self.command.as_ref().is_some_and(|c| *c != entry.command) {
+            return false;
+        }
+        if self.since.is_some_and(|since| entry.timestamp < since) {
+            return false;
+        }
+        if self.until.is_some_and(|until| entry.timestamp > until) {
+            return false;
+        }
+        match self.status {
+            Some(HistoryStatus::Success) if !entry.success => return false,
+            Some(HistoryStatus::Failure) if entry.success => return false,
+            _ => {}
+        }
+        match &self.text {
+            Some(text) => [
+                entry.args_summary.as_deref(),
+                entry.description.as_deref(),
+                entry.error.as_deref(),
+            ]
+            .into_iter()
+            .flatten()
+            .chain(entry.affected_files.iter().map(String::as_str))
+            .any(|field| field.to_lowercase().contains(text.as_str())),
+            None => true,
+        }
+    }
+}
+
 pub struct HistoryCommand {
     descriptor: CommandDescriptor,
     action_log: Arc<ActionLog>,
+    executions: Option<Arc<ExecutionHistory>>,
 }
 
 impl HistoryCommand {
@@ -36,63 +364,131 @@ impl HistoryCommand {
                 sandbox_level_required: SandboxLevel::ReadOnly,
                 supports_preview: false,
                 supports_dry_run: false,
+                timeout: None,
+                alias_of: None,
             },
             action_log,
+            executions: None,
         }
     }
 
-    async fn get_history(&self, args: &HistoryArgs) -> Result<String> {
-        let actions = self.action_log.get_history().await;
-        let current_index = self.action_log.current_index().await;
+    /// Include command executions (args, result, duration) alongside file
+    /// changes
+    pub fn with_execution_history(mut self, executions: Arc<ExecutionHistory>) -> Self {
+        self.executions = Some(executions);
+        self
+    }
 
-        if actions.is_empty() {
-            return Ok("No actions in history".to_string());
-        }
+    /// Build the filtered, paginated page of history entries
+    pub async fn query(&self, args: &HistoryArgs) -> Result<HistoryPage> {
+        self.query_log(&self.action_log, args).await
+    }
+
+    /// The caller's action log if it has one, else the command's own
+    fn action_log<'a>(&'a self, context: &'a CommandContext) -> &'a ActionLog {
+        context.action_log.as_deref().unwrap_or(&self.action_log)
+    }
 
-        let limit = if args.show_all {
-            actions.len()
-        } else {
-            args.limit.min(actions.len())
+    async fn query_log(&self, action_log: &ActionLog, args: &HistoryArgs) -> Result<HistoryPage> {
+        let filter = HistoryFilter::new(args)?;
+        let records = match &self.executions {
+            Some(executions) => executions.records().await,
+            None => Vec::new(),
         };
+        let actions = action_log.get_history().await;
+        let current_index = action_log.current_index().await;
+
+        let matching: Vec<HistoryEntry> = unify(records, &actions, current_index)
+            .into_iter()
+            .filter(|entry| filter.matches(entry))
+            .collect();
+
+        // Pages count back from the newest entry
+        let total = matching.len();
+        let limit = if args.show_all { total } else { args.limit };
+        let end = total.saturating_sub(args.offset);
+        let start = end.saturating_sub(limit);
+
+        Ok(HistoryPage {
+            schema_version: HISTORY_SCHEMA_VERSION,
+            total,
+            offset: args.offset,
+            limit,
+            entries: matching[start..end].to_vec(),
+        })
+    }
+
+    async fn get_history(
+        &self,
+        action_log: &ActionLog,
+        args: &HistoryArgs,
+    ) -> Result<(String, HistoryPage)> {
+        let page = self.query_log(action_log, args).await?;
+
+        if args.format == HistoryFormat::Json {
+            return Ok((serde_json::to_string_pretty(&page)?, page));
+        }
+
+        if page.total == 0 {
+            return Ok(("No actions in history".to_string(), page));
+        }
 
         let mut output = String::new();
         output.push_str(&format!(
-            "Action History ({} total, showing last {}):\n",
-            actions.len(),
-            limit
+            "Action History ({} total, showing last {}",
+            page.total,
+            page.entries.len()
         ));
+        if page.offset > 0 {
+            output.push_str(&format!(", skipping newest {}", page.offset));
+        }
+        output.push_str("):\n");
         output.push_str(&format!(
             "Current position: {} (can undo: {}, can redo: {})\n\n",
-            current_index,
-            self.action_log.can_undo_count().await,
-            self.action_log.can_redo_count().await
+            action_log.current_index().await,
+            action_log.can_undo_count().await,
+            action_log.can_redo_count().await
         ));
 
-        let start = actions.len().saturating_sub(limit);
-        for (idx, action) in actions.iter().enumerate().skip(start) {
-            let marker = if idx < current_index { "✓" } else { "○" };
+        for entry in &page.entries {
+            let marker = match (entry.applied, entry.success) {
+                (Some(true), _) => "✓",
+                (Some(false), _) => "○",
+                (None, true) => "•",
+                (None, false) => "✗",
+            };
 
-            let timestamp = action.timestamp.format("%Y-%m-%d %H:%M:%S");
+            let timestamp = entry.timestamp.format("%Y-%m-%d %H:%M:%S");
 
             output.push_str(&format!(
                 "{} [{}] {} - {} ({})\n",
                 marker,
-                idx + 1,
+                entry.index,
                 timestamp,
-                action.description,
-                action.command
+                entry.description.as_deref().unwrap_or(&entry.command),
+                entry.command
             ));
 
-            // Show the state change
-            output.push_str(&format!(
-                "   Path: {}\n",
-                action.state_after.path().display()
-            ));
+            if let Some(args_summary) = &entry.args_summary {
+                output.push_str(&format!("   Args: {}\n", args_summary));
+            }
+            if let Some(duration_ms) = entry.duration_ms {
+                match &entry.error {
+                    Some(error) => output.push_str(&format!(
+                        "   Result: failed in {}ms: {}\n",
+                        duration_ms, error
+                    )),
+                    None => output.push_str(&format!("   Result: ok in {}ms\n", duration_ms)),
+                }
+            }
+            if !entry.affected_files.is_empty() {
+                output.push_str(&format!("   Path: {}\n", entry.affected_files.join(", ")));
+            }
         }
 
-        output.push_str("\nLegend: ✓ = applied, ○ = undone\n");
+        output.push_str("\nLegend: ✓ = applied, ○ = undone, • = succeeded, ✗ = failed\n");
 
-        Ok(output)
+        Ok((output, page))
     }
 }
 
@@ -102,6 +498,19 @@ impl Default for HistoryCommand {
     }
 }
 
+fn parse_args(args: &serde_json::Value) -> Result<HistoryArgs> {
+    if args.is_null() {
+        return Ok(HistoryArgs::default());
+    }
+    serde_json::from_value(args.clone()).map_err(|e| {
+        FennecError::Command(Box::new(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Invalid history arguments: {}", e),
+        )))
+        .into()
+    })
+}
+
 #[async_trait::async_trait]
 impl CommandExecutor for HistoryCommand {
     fn descriptor(&self) -> &CommandDescriptor {
@@ -111,9 +520,9 @@ impl CommandExecutor for HistoryCommand {
     async fn preview(
         &self,
         _args: &serde_json::Value,
-        _context: &CommandContext,
+        context: &CommandContext,
     ) -> Result<CommandPreview> {
-        let count = self.action_log.get_history().await.len();
+        let count = self.action_log(context).get_history().await.len();
 
         Ok(CommandPreview {
             command_id: Uuid::new_v4(),
@@ -126,31 +535,34 @@ impl CommandExecutor for HistoryCommand {
     async fn execute(
         &self,
         args: &serde_json::Value,
-        _context: &CommandContext,
+        context: &CommandContext,
     ) -> Result<CommandResult> {
-        let args: HistoryArgs = serde_json::from_value(args.clone()).unwrap_or(HistoryArgs {
-            limit: default_limit(),
-            show_all: false,
-        });
+        let result = match parse_args(args) {
+            Ok(args) => self.get_history(self.action_log(context), &args).await,
+            Err(e) => Err(e),
+        };
 
-        match self.get_history(&args).await {
-            Ok(output) => Ok(CommandResult {
+        match result {
+            Ok((output, page)) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: true,
                 output,
                 error: None,
+                data: Some(serde_json::to_value(&page)?),
             }),
             Err(e) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
+                data: None,
             }),
         }
     }
 
-    fn validate_args(&self, _args: &serde_json::Value) -> Result<()> {
-        // History command args are all optional with defaults
+    fn validate_args(&self, args: &serde_json::Value) -> Result<()> {
+        // All filters are optional, but dates must parse
+        HistoryFilter::new(&parse_args(args)?)?;
         Ok(())
     }
 }
@@ -178,6 +590,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -211,6 +625,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -243,6 +659,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -278,6 +696,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -286,4 +706,202 @@ mod tests {
         assert!(result.output.contains("✓")); // Applied actions
         assert!(result.output.contains("○")); // Undone action
     }
+
+    const SEEDED_COMMANDS: [&str; 4] = ["edit", "run", "search", "create"];
+
+    fn seeded_time(i: usize) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2024-03-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+            + chrono::Duration::hours(i as i64)
+    }
+
+    /// 100 executions an hour apart; every fifth fails and every `create`
+    /// records a file action
+    async fn seeded_history() -> HistoryCommand {
+        let executions = Arc::new(ExecutionHistory::new());
+        let action_log = Arc::new(ActionLog::new());
+
+        for i in 0..100 {
+            let command = SEEDED_COMMANDS[i % 4];
+            let success = i % 5 != 0;
+            executions
+                .record(ExecutionRecord {
+                    id: Uuid::new_v4(),
+                    command: command.to_string(),
+                    timestamp: seeded_time(i),
+                    args_summary: summarize_args(
+                        &serde_json::json!({ "target": format!("item{}", i) }),
+                    ),
+                    success,
+                    error: (!success).then(|| format!("boom {}", i)),
+                    duration_ms: 50,
+                })
+                .await;
+
+            if command == "create" {
+                let mut action = Action::file_created(
+                    "create".to_string(),
+                    PathBuf::from(format!("src/gen{}.rs", i)),
+                    format!("Created gen{}.rs", i),
+                );
+                action.timestamp = seeded_time(i) + chrono::Duration::milliseconds(10);
+                action_log.record(action).await;
+            }
+        }
+
+        HistoryCommand::new(action_log).with_execution_history(executions)
+    }
+
+    fn args(value: serde_json::Value) -> HistoryArgs {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_history_unifies_executions_and_actions() {
+        let history = seeded_history().await;
+        let page = history
+            .query(&args(serde_json::json!({ "show_all": true })))
+            .await
+            .unwrap();
+
+        // Actions fold into the execution that produced them
+        assert_eq!(page.total, 100);
+        let create = &page.entries[3];
+        assert_eq!(create.command, "create");
+        assert_eq!(
+            create.args_summary.as_deref(),
+            Some(r#"{"target":"item3"}"#)
+        );
+        assert_eq!(create.affected_files, vec!["src/gen3.rs"]);
+        assert_eq!(create.description.as_deref(), Some("Created gen3.rs"));
+        assert_eq!(create.duration_ms, Some(50));
+        assert_eq!(create.applied, Some(true));
+        assert_eq!(page.entries[0].applied, None);
+    }
+
+    #[tokio::test]
+    async fn test_history_filters_compose() {
+        let history = seeded_history().await;
+
+        let page = history
+            .query(&args(serde_json::json!({
+                "command": "run",
+                "status": "failure",
+                "since": "2024-03-02",
+                "until": "2024-03-04",
+                "show_all": true
+            })))
+            .await
+            .unwrap();
+
+        // Hours 24..=95 cover Mar 2 through Mar 4; `run` is i % 4 == 1 and
+        // failures are i % 5 == 0
+        let expected: Vec<usize> = (24..96).filter(|i| i % 4 == 1 && i % 5 == 0).collect();
+        assert_eq!(expected, vec![25, 45, 65, 85]);
+        assert_eq!(page.total, expected.len());
+        let indices: Vec<usize> = page.entries.iter().map(|e| e.index - 1).collect();
+        assert_eq!(indices, expected);
+
+        // Text search runs over args, errors and affected files
+        let page = history
+            .query(&args(
+                serde_json::json!({ "text": "GEN7", "show_all": true }),
+            ))
+            .await
+            .unwrap();
+        let indices: Vec<usize> = page.entries.iter().map(|e| e.index - 1).collect();
+        assert_eq!(indices, vec![7, 71, 75, 79]);
+
+        let page = history
+            .query(&args(serde_json::json!({
+                "text": "boom",
+                "command": "search",
+                "show_all": true
+            })))
+            .await
+            .unwrap();
+        assert_eq!(page.total, 5);
+        assert!(page
+            .entries
+            .iter()
+            .all(|e| !e.success && e.command == "search"));
+    }
+
+    #[tokio::test]
+    async fn test_history_pagination() {
+        let history = seeded_history().await;
+
+        let page = history
+            .query(&args(serde_json::json!({ "limit": 10, "offset": 20 })))
+            .await
+            .unwrap();
+        assert_eq!(page.total, 100);
+        let indices: Vec<usize> = page.entries.iter().map(|e| e.index).collect();
+        assert_eq!(indices, (71..=80).collect::<Vec<_>>());
+
+        // Filters apply before pagination
+        let page = history
+            .query(&args(
+                serde_json::json!({ "status": "success", "limit": 5, "offset": 78 }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(page.total, 80);
+        assert_eq!(page.entries.len(), 2);
+
+        let page = history
+            .query(&args(serde_json::json!({ "offset": 500 })))
+            .await
+            .unwrap();
+        assert!(page.entries.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_history_json_format() {
+        let history = seeded_history().await;
+        let context = CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: None,
+            sandbox_level: SandboxLevel::ReadOnly,
+            dry_run: false,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log: None,
+            timeout: None,
+            progress: None,
+        };
+
+        let result = history
+            .execute(
+                &serde_json::json!({ "format": "json", "limit": 3 }),
+                &context,
+            )
+            .await
+            .unwrap();
+        assert!(result.success);
+
+        let document: serde_json::Value = serde_json::from_str(&result.output).unwrap();
+        assert_eq!(document["schema_version"], HISTORY_SCHEMA_VERSION);
+        assert_eq!(document["total"], 100);
+        assert_eq!(document["entries"].as_array().unwrap().len(), 3);
+        let entry = &document["entries"][2];
+        assert_eq!(entry["index"], 100);
+        assert_eq!(entry["command"], "create");
+        assert_eq!(entry["success"], true);
+        assert_eq!(entry["duration_ms"], 50);
+        assert_eq!(entry["affected_files"][0], "src/gen99.rs");
+    }
+
+    #[test]
+    fn test_history_rejects_bad_dates() {
+        let command = HistoryCommand::default();
+        assert!(command
+            .validate_args(&serde_json::json!({ "since": "yesterday" }))
+            .is_err());
+        assert!(command
+            .validate_args(&serde_json::json!({ "until": "2024-03-01T12:00:00Z" }))
+            .is_ok());
+    }
 }
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use similar::{ChangeTag, TextDiff};
 use std::path::PathBuf;
+use thiserror::Error;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum HunkStatus {
@@ -9,6 +10,33 @@ pub enum HunkStatus {
     Rejected,
 }
 
+/// Reasons a hunk selection cannot be applied
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum HunkSelectionError {
+    #[error("Selection has {selections} entries but there are {hunks} hunks")]
+    LengthMismatch { hunks: usize, selections: usize },
+
+    #[error("Hunk {id} is still pending; accept or reject it before applying")]
+    Undecided { id: String },
+
+    #[error("Hunk {id} starts before the preceding hunk {previous}; hunks must be in file order")]
+    OutOfOrder { id: String, previous: String },
+
+    #[error("Hunks {previous} and {id} overlap and cannot both be applied")]
+    Overlapping { id: String, previous: String },
+
+    #[error("Hunk {id} covers lines {start}-{end} but the original has {available} lines")]
+    OutOfRange {
+        id: String,
+        start: usize,
+        end: usize,
+        available: usize,
+    },
+
+    #[error("Hunk {id} does not match the original content at line {line}")]
+    ContentMismatch { id: String, line: usize },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Hunk {
     pub id: String,
@@ -131,9 +159,11 @@ pub fn split_diff_into_hunks(
     let mut hunk_id = 0;
 
     let old_lines: Vec<&str> = old_content.lines().collect();
-    let new_lines: Vec<&str> = new_content.lines().collect();
 
-    for (_idx, change) in diff.iter_all_changes().enumerate() {
+    // Hunk positions are always expressed in original-file line numbers
+    let mut old_pos = 0;
+
+    for change in diff.iter_all_changes() {
         match change.tag() {
             ChangeTag::Delete => {
                 if let Some((_start, _, ref mut old, ref mut _new)) = current_hunk {
@@ -141,14 +171,14 @@ pub fn split_diff_into_hunks(
                     old.push(change.value().trim_end().to_string());
                 } else {
                     // Start new hunk
-                    let start_line = change.old_index().unwrap_or(0);
                     current_hunk = Some((
-                        start_line,
-                        start_line,
+                        old_pos,
+                        old_pos,
                         vec![change.value().trim_end().to_string()],
                         Vec::new(),
                     ));
                 }
+                old_pos += 1;
             }
             ChangeTag::Insert => {
                 if let Some((_start, _end, ref mut _old, ref mut new)) = current_hunk {
@@ -156,10 +186,9 @@ pub fn split_diff_into_hunks(
                     new.push(change.value().trim_end().to_string());
                 } else {
                     // Start new hunk
-                    let start_line = change.new_index().unwrap_or(0);
                     current_hunk = Some((
-                        start_line,
-                        start_line,
+                        old_pos,
+                        old_pos,
                         Vec::new(),
                         vec![change.value().trim_end().to_string()],
                     ));
@@ -169,7 +198,7 @@ pub fn split_diff_into_hunks(
                 // Equal line - might end current hunk
                 if let Some((start, _, old, new)) = current_hunk.take() {
                     // Finalize current hunk
-                    let end_line = change.old_index().unwrap_or(start);
+                    let end_line = old_pos;
 
                     // Get context before
                     let context_start = start.saturating_sub(context_lines);
@@ -203,13 +232,14 @@ pub fn split_diff_into_hunks(
                     hunks.push(hunk);
                     hunk_id += 1;
                 }
+                old_pos += 1;
             }
         }
     }
 
     // Finalize any remaining hunk
     if let Some((start, _, old, new)) = current_hunk {
-        let end_line = old_lines.len().max(new_lines.len());
+        let end_line = old_pos;
 
         let context_start = start.saturating_sub(context_lines);
         let context_before: Vec<String> = old_lines
@@ -274,6 +304,97 @@ pub fn apply_hunks(original_content: &str, hunks: &[Hunk]) -> String {
     lines.join("\n")
 }
 
+/// Apply only the hunks marked accepted in `selection`
+///
+/// `selection[i]` is the decision for `hunks[i]`. Hunk line numbers refer to
+/// `original`, so skipping a hunk never shifts the ones after it. Hunks must
+/// be in file order and must not overlap; adjacent hunks are fine. A trailing
+/// newline in `original` is preserved.
+pub fn apply_selected_hunks(
+    original: &str,
+    hunks: &[Hunk],
+    selection: &[HunkStatus],
+) -> Result<String, HunkSelectionError> {
+    if hunks.len() != selection.len() {
+        return Err(HunkSelectionError::LengthMismatch {
+            hunks: hunks.len(),
+            selections: selection.len(),
+        });
+    }
+
+    let lines: Vec<&str> = original.lines().collect();
+    let mut output: Vec<&str> = Vec::with_capacity(lines.len());
+    let mut cursor = 0;
+    let mut previous: Option<&Hunk> = None;
+
+    for (hunk, status) in hunks.iter().zip(selection) {
+        if let Some(previous) = previous {
+            if hunk.start_line < previous.start_line {
+                return Err(HunkSelectionError::OutOfOrder {
+                    id: hunk.id.clone(),
+                    previous: previous.id.clone(),
+                });
+            }
+            if hunk.start_line < previous.end_line {
+                return Err(HunkSelectionError::Overlapping {
+                    id: hunk.id.clone(),
+                    previous: previous.id.clone(),
+                });
+            }
+        }
+        if hunk.end_line < hunk.start_line || hunk.end_line > lines.len() {
+            return Err(HunkSelectionError::OutOfRange {
+                id: hunk.id.clone(),
+                start: hunk.start_line,
+                end: hunk.end_line,
+                available: lines.len(),
+            });
+        }
+        previous = Some(hunk);
+
+        output.extend_from_slice(&lines[cursor..hunk.start_line]);
+        match status {
+            HunkStatus::Accepted => {
+                let current = &lines[hunk.start_line..hunk.end_line];
+                if current.len() != hunk.old_content.len() {
+                    return Err(HunkSelectionError::ContentMismatch {
+                        id: hunk.id.clone(),
+                        line: hunk.start_line + 1,
+                    });
+                }
+                if let Some(offset) = current
+                    .iter()
+                    .zip(&hunk.old_content)
+                    .position(|(actual, expected)| actual != expected)
+                {
+                    return Err(HunkSelectionError::ContentMismatch {
+                        id: hunk.id.clone(),
+                        line: hunk.start_line + offset + 1,
+                    });
+                }
+                output.extend(hunk.new_content.iter().map(String::as_str));
+            }
+            HunkStatus::Rejected => {
+                output.extend_from_slice(&lines[hunk.start_line..hunk.end_line]);
+            }
+            HunkStatus::Pending => {
+                return Err(HunkSelectionError::Undecided {
+                    id: hunk.id.clone(),
+                });
+            }
+        }
+        cursor = hunk.end_line;
+    }
+
+    output.extend_from_slice(&lines[cursor..]);
+
+    let mut result = output.join("\n");
+    if original.ends_with('\n') && !result.is_empty() {
+        result.push('\n');
+    }
+    Ok(result)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -385,4 +506,134 @@ mod tests {
         assert!(result.contains("line 3")); // Should not be modified
         assert!(!result.contains("modified line 3"));
     }
+
+    fn selection_fixture() -> (&'static str, Vec<Hunk>) {
+        let original = "a\nb\nc\nd\ne\nf\n";
+        let modified = "a\nB\nc\nd\nE\nF\ninserted\n";
+        let hunks = split_diff_into_hunks(PathBuf::from("test.txt"), original, modified, 1);
+        (original, hunks)
+    }
+
+    #[test]
+    fn test_apply_selected_hunks_interleaved() {
+        let (original, hunks) = selection_fixture();
+        assert_eq!(hunks.len(), 2);
+
+        let accept_all = [HunkStatus::Accepted, HunkStatus::Accepted];
+        assert_eq!(
+            apply_selected_hunks(original, &hunks, &accept_all).unwrap(),
+            "a\nB\nc\nd\nE\nF\ninserted\n"
+        );
+
+        let reject_first = [HunkStatus::Rejected, HunkStatus::Accepted];
+        assert_eq!(
+            apply_selected_hunks(original, &hunks, &reject_first).unwrap(),
+            "a\nb\nc\nd\nE\nF\ninserted\n"
+        );
+
+        let reject_second = [HunkStatus::Accepted, HunkStatus::Rejected];
+        assert_eq!(
+            apply_selected_hunks(original, &hunks, &reject_second).unwrap(),
+            "a\nB\nc\nd\ne\nf\n"
+        );
+
+        let reject_all = [HunkStatus::Rejected, HunkStatus::Rejected];
+        assert_eq!(
+            apply_selected_hunks(original, &hunks, &reject_all).unwrap(),
+            original
+        );
+    }
+
+    #[test]
+    fn test_apply_selected_adjacent_hunks() {
+        let original = "one\ntwo\nthree\n";
+        let first = Hunk::new(
+            "h0".to_string(),
+            PathBuf::from("test.txt"),
+            0,
+            1,
+            vec!["one".to_string()],
+            vec!["1".to_string(), "1.5".to_string()],
+        );
+        let second = Hunk::new(
+            "h1".to_string(),
+            PathBuf::from("test.txt"),
+            1,
+            2,
+            vec!["two".to_string()],
+            vec![],
+        );
+        let hunks = [first, second];
+
+        assert_eq!(
+            apply_selected_hunks(
+                original,
+                &hunks,
+                &[HunkStatus::Accepted, HunkStatus::Accepted]
+            )
+            .unwrap(),
+            "1\n1.5\nthree\n"
+        );
+        assert_eq!(
+            apply_selected_hunks(
+                original,
+                &hunks,
+                &[HunkStatus::Rejected, HunkStatus::Accepted]
+            )
+            .unwrap(),
+            "one\nthree\n"
+        );
+    }
+
+    #[test]
+    fn test_apply_selected_hunks_errors() {
+        let (original, hunks) = selection_fixture();
+
+        assert_eq!(
+            apply_selected_hunks(original, &hunks, &[HunkStatus::Accepted]),
+            Err(HunkSelectionError::LengthMismatch {
+                hunks: 2,
+                selections: 1
+            })
+        );
+        assert!(matches!(
+            apply_selected_hunks(
+                original,
+                &hunks,
+                &[HunkStatus::Accepted, HunkStatus::Pending]
+            ),
+            Err(HunkSelectionError::Undecided { .. })
+        ));
+
+        let reversed = vec![hunks[1].clone(), hunks[0].clone()];
+        assert!(matches!(
+            apply_selected_hunks(
+                original,
+                &reversed,
+                &[HunkStatus::Accepted, HunkStatus::Accepted]
+            ),
+            Err(HunkSelectionError::OutOfOrder { .. })
+        ));
+
+        let mut overlapping = hunks[0].clone();
+        overlapping.id = "h9".to_string();
+        let overlapping = vec![hunks[0].clone(), overlapping];
+        assert!(matches!(
+            apply_selected_hunks(
+                original,
+                &overlapping,
+                &[HunkStatus::Accepted, HunkStatus::Rejected]
+            ),
+            Err(HunkSelectionError::Overlapping { .. })
+        ));
+
+        assert!(matches!(
+            apply_selected_hunks(
+                "a\nx\nc\nd\ne\nf\n",
+                &hunks,
+                &[HunkStatus::Accepted, HunkStatus::Rejected]
+            ),
+            Err(HunkSelectionError::ContentMismatch { line: 2, .. })
+        ));
+    }
 }
@@ -43,6 +43,8 @@ impl IndexCommand {
                 sandbox_level_required: SandboxLevel::ReadOnly,
                 supports_preview: false,
                 supports_dry_run: false,
+                timeout: None,
+                alias_of: None,
             },
         }
     }
@@ -315,12 +317,14 @@ impl CommandExecutor for IndexCommand {
                 success: true,
                 output,
                 error: None,
+                data: None,
             }),
             Err(e) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
+                data: None,
             }),
         }
     }
@@ -446,6 +450,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -523,6 +529,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let preview = command.preview(&args, &context).await.unwrap();
@@ -546,6 +554,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.preview(&args, &context).await;
@@ -6,7 +6,9 @@ pub mod create;
 pub mod delete;
 pub mod dependency_graph;
 pub mod diff;
+pub mod diff_model;
 pub mod edit;
+pub mod encoding;
 pub mod error;
 pub mod file_ops;
 pub mod find_symbol;
@@ -15,63 +17,123 @@ pub mod git_integration;
 pub mod history;
 pub mod hunks;
 pub mod index;
+pub mod middleware;
 pub mod plan;
 pub mod pr_summary;
+pub mod progress;
 pub mod project_index;
 pub mod quick_actions;
 pub mod redo;
 pub mod registry;
 pub mod rename;
+pub mod rename_symbol;
+pub mod result_store;
 pub mod run;
+pub mod scaffold;
 pub mod search;
 pub mod summarize;
 pub mod summarize_enhanced;
+pub mod summary_tree;
 pub mod symbols;
+#[cfg(feature = "structural-search")]
+pub mod syntax;
+pub mod test_report;
 pub mod test_watch;
+pub mod trash;
 pub mod undo;
 
 #[cfg(test)]
 mod tests;
 
 // Re-export key types and functions for easy use
-pub use action_log::{Action, ActionLog, ActionState};
+pub use action_log::{Action, ActionConflict, ActionLog, ActionState};
 pub use common::{format_file_size, initialize_builtin_commands, is_text_file, truncate_text};
 pub use error::{CommandError, Result as CommandResult};
-pub use hunks::{apply_hunks, split_diff_into_hunks, Hunk, HunkStatus};
+pub use hunks::{
+    apply_hunks, apply_selected_hunks, split_diff_into_hunks, Hunk, HunkSelectionError, HunkStatus,
+};
+pub use middleware::{
+    AuditMiddleware, CommandMiddleware, HistoryMiddleware, MemoryMiddleware, MiddlewareDecision,
+    ResultStoreMiddleware,
+};
+pub use progress::{CommandProgress, ProgressReporter, ProgressStep};
 pub use registry::{
-    CommandContext, CommandDescriptor, CommandExecutionResult, CommandExecutor, CommandRegistry,
+    check_capabilities, minimum_sandbox_level, CapabilityDenial, CommandContext, CommandDescriptor,
+    CommandExecutionResult, CommandExecutor, CommandOutputEvent, CommandRegistry, DeniedCapability,
+    OutputSender,
 };
+pub use result_store::{replay, ResultStore, StoredResult};
 
 // Re-export individual commands
-pub use commit_template::{CommitTemplateArgs, CommitTemplateCommand};
-pub use compiler_errors::{CompilerMessage, FixConfidence, MessageLevel, SuggestedFix};
-pub use create::{CreateArgs, CreateCommand};
+pub use commit_template::{
+    check_conventional_commit, infer_scope, CommitRule, CommitTemplateArgs, CommitTemplateCommand,
+    CommitTemplateReport, CommitViolation, DEFAULT_COMMIT_TYPES,
+};
+pub use compiler_errors::{
+    Applicability, CompilerMessage, FixConfidence, MachineFix, MessageLevel, SpanReplacement,
+    SuggestedFix,
+};
+pub use create::{CreateArgs, CreateCommand, ScaffoldAction, ScaffoldFile, ScaffoldPlan};
 pub use delete::{DeleteArgs, DeleteCommand};
 pub use dependency_graph::{CargoPackage, Dependency, DependencyGraph};
 pub use diff::{DiffArgs, DiffCommand};
+pub use diff_model::{
+    DiffHunk, DiffLine, LineKind, SideBySideRow, SpanKind, StructuredDiff, WordSpan,
+};
 pub use edit::{EditArgs, EditCommand};
+pub use encoding::{
+    decode_text, detect_encoding, encode_text, LineEnding, TextEncoding, TextFormat,
+};
 pub use file_ops::{
-    EditStrategy, FileEditRequest, FileEditResult, FileOperations, FileOperationsConfig,
+    DecodedText, EditStrategy, FileEditRequest, FileEditResult, FileOperations,
+    FileOperationsConfig, TransactionFileResult, TransactionResult, TrashEntry, TRASH_DIR,
+};
+pub use find_symbol::{FindSymbolArgs, FindSymbolCommand, SymbolMatch};
+pub use fix_errors::{AppliedFix, FixErrorsArgs, FixErrorsCommand, MachineFixReport};
+pub use git_integration::{ChangeType, DiffTarget, FileChange, GitCommit, GitFileStatus};
+pub use history::{
+    ExecutionHistory, ExecutionRecord, HistoryArgs, HistoryCommand, HistoryEntry, HistoryFormat,
+    HistoryPage, HistoryStatus, HISTORY_SCHEMA_VERSION,
 };
-pub use find_symbol::{FindSymbolArgs, FindSymbolCommand};
-pub use fix_errors::{FixErrorsArgs, FixErrorsCommand};
-pub use git_integration::{ChangeType, FileChange, GitCommit};
-pub use history::{HistoryArgs, HistoryCommand};
 pub use index::{IndexArgs, IndexCommand};
-pub use plan::{PlanArgs, PlanCommand};
-pub use pr_summary::{PrSummaryArgs, PrSummaryCommand};
+pub use plan::{GeneratedPlan, PlanArgs, PlanCommand, UNPARSED_PLAN_TAG};
+pub use pr_summary::{
+    FileGroup, PrNarrator, PrReport, PrSummaryArgs, PrSummaryCommand, PrSummaryFormat, RiskCallout,
+    RiskKind,
+};
 pub use project_index::{ImpactAnalysis, ProjectIndex, ProjectStatistics};
 pub use quick_actions::{QuickAction, QuickActionArgs, QuickActionCommand};
 pub use redo::{RedoArgs, RedoCommand};
 pub use rename::{RenameArgs, RenameCommand};
-pub use run::{RunArgs, RunCommand};
-pub use search::{SearchArgs, SearchCommand, SearchResult};
+pub use rename_symbol::{
+    FileRenamePlan, RenamePlan, RenameSymbolArgs, RenameSymbolCommand, SymbolLocation,
+};
+pub use run::{RunArgs, RunCommand, RunReport, DEFAULT_MAX_OUTPUT_BYTES};
+pub use scaffold::{
+    find_template, ProjectTemplate, TemplateFile, TemplateVariables, BUILTIN_TEMPLATES,
+};
+pub use search::{NodeSpan, SearchArgs, SearchCommand, SearchResult, SearchSort};
 pub use summarize::{SummarizeArgs, SummarizeCommand};
 pub use summarize_enhanced::{
     EnhancedSummarizeArgs, EnhancedSummarizeCommand, OutputDestination, SummaryDepth, SummaryType,
 };
-pub use symbols::{Symbol, SymbolIndex, SymbolType, Visibility as SymbolVisibility};
-pub use test_watch::{TestWatchArgs, TestWatchCommand};
+pub use summary_tree::{
+    DirectorySummary, FileSummary, SummaryCache, SummaryTree, TreeSummarizer, SUMMARY_FILE_NAME,
+};
+pub use symbols::{
+    fuzzy_match, MatchKind, Symbol, SymbolIndex, SymbolType, Visibility as SymbolVisibility,
+};
+#[cfg(feature = "structural-search")]
+pub use syntax::{SyntaxCapture, SyntaxError, SyntaxLanguage, SyntaxQuery};
+pub use test_report::{
+    merge_test_streams, parse_test_output, render_junit, TestCaseResult, TestOutcome,
+    TestRunSummary,
+};
+pub use test_watch::{
+    FailureTracker, RunScope, TestReportTarget, TestRunRecord, TestWatchArgs, TestWatchCommand,
+    TestWatchReport,
+};
+pub use trash::{TrashArgs, TrashCommand};
 pub use undo::{UndoArgs, UndoCommand};
 
 /// Create a fully initialized command registry with all built-in commands
@@ -106,6 +168,8 @@ pub use undo::{UndoArgs, UndoCommand};
 ///         preview_only: false,
 ///         cancellation_token: CancellationToken::new(),
 ///         action_log: None,
+///         timeout: None,
+///         progress: None,
 ///     };
 ///     
 ///     let args = serde_json::json!({
@@ -122,3 +186,45 @@ pub use undo::{UndoArgs, UndoCommand};
 pub async fn create_command_registry() -> anyhow::Result<CommandRegistry> {
     initialize_builtin_commands().await
 }
+
+/// Create a command registry whose default timeout comes from `config`
+pub async fn create_command_registry_with_config(
+    config: &fennec_core::config::Config,
+) -> anyhow::Result<CommandRegistry> {
+    let registry = initialize_builtin_commands()
+        .await?
+        .with_default_timeout(config.commands.default_timeout());
+    registry
+        .register_builtin(std::sync::Arc::new(
+            DeleteCommand::new().with_trash_retention(config.commands.trash_retention()),
+        ))
+        .await?;
+
+    // Executions are kept in memory for the life of the registry. The audit
+    // trail only stores a hash of each command's arguments, so it cannot
+    // rebuild the history listing.
+    let executions = std::sync::Arc::new(ExecutionHistory::new());
+    registry
+        .register_builtin(std::sync::Arc::new(
+            HistoryCommand::default().with_execution_history(executions.clone()),
+        ))
+        .await?;
+    registry
+        .register_middleware(Box::new(HistoryMiddleware::new(executions)))
+        .await;
+
+    if config.commands.stored_results_per_session > 0 {
+        match ResultStore::with_default_dir() {
+            Ok(store) => {
+                let store = store.with_max_results(config.commands.stored_results_per_session);
+                registry
+                    .register_middleware(Box::new(ResultStoreMiddleware::new(std::sync::Arc::new(
+                        store,
+                    ))))
+                    .await;
+            }
+            Err(e) => tracing::warn!("Command results will not be persisted: {}", e),
+        }
+    }
+    Ok(registry)
+}
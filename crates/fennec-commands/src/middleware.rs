@@ -0,0 +1,490 @@
+//! Cross-cutting hooks that run around every command executed through the
+//! registry.
+//!
+//! Middleware is invoked in registration order: each `before` hook may veto
+//! the command, and `after` hooks observe the final execution result.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use fennec_memory::{ExecutionResult, MemoryService};
+use fennec_security::{audit_command_execution, AuditSystem, AuditedCommandContext};
+use std::sync::Arc;
+use tracing::debug;
+
+use crate::history::{summarize_args, ExecutionHistory, ExecutionRecord};
+use crate::registry::{CommandContext, CommandDescriptor, CommandExecutionResult};
+use crate::result_store::ResultStore;
+
+/// Outcome of a middleware `before` hook
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MiddlewareDecision {
+    Continue,
+    /// Stop the command from running, with a reason surfaced to the caller
+    Veto(String),
+}
+
+/// Hook invoked around command execution
+#[async_trait]
+pub trait CommandMiddleware: Send + Sync {
+    /// Name used when reporting vetoes and hook failures
+    fn name(&self) -> &str;
+
+    /// Called before the command runs
+    async fn before(
+        &self,
+        _context: &CommandContext,
+        _descriptor: &CommandDescriptor,
+        _args: &serde_json::Value,
+    ) -> Result<MiddlewareDecision> {
+        Ok(MiddlewareDecision::Continue)
+    }
+
+    /// Called once the command has produced a result. Errors are logged and
+    /// never change the result.
+    async fn after(
+        &self,
+        _context: &CommandContext,
+        _descriptor: &CommandDescriptor,
+        _args: &serde_json::Value,
+        _result: &CommandExecutionResult,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Writes every executed command to the session's audit trail
+pub struct AuditMiddleware {
+    audit_system: Arc<AuditSystem>,
+}
+
+impl AuditMiddleware {
+    pub fn new(audit_system: Arc<AuditSystem>) -> Self {
+        Self { audit_system }
+    }
+}
+
+#[async_trait]
+impl CommandMiddleware for AuditMiddleware {
+    fn name(&self) -> &str {
+        "audit"
+    }
+
+    async fn after(
+        &self,
+        context: &CommandContext,
+        descriptor: &CommandDescriptor,
+        args: &serde_json::Value,
+        result: &CommandExecutionResult,
+    ) -> Result<()> {
+        let audit_context = AuditedCommandContext {
+            session_id: context.session_id,
+            user_id: context.user_id.clone(),
+            workspace_path: context.workspace_path.clone(),
+            sandbox_level: context.sandbox_level.clone(),
+            dry_run: context.dry_run,
+            preview_only: context.preview_only,
+        };
+        let command_result = fennec_core::command::CommandResult {
+            command_id: result.command_id,
+            success: result.success,
+            output: result.output.clone(),
+            error: result.error.clone(),
+            data: result.data.clone(),
+        };
+
+        audit_command_execution(
+            self.audit_system.clone(),
+            &descriptor.name,
+            args,
+            &descriptor.capabilities_required,
+            &audit_context,
+            || async move { Ok(command_result) },
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+/// Records every executed command in the session transcript
+pub struct MemoryMiddleware {
+    memory: Arc<MemoryService>,
+}
+
+impl MemoryMiddleware {
+    pub fn new(memory: Arc<MemoryService>) -> Self {
+        Self { memory }
+    }
+}
+
+#[async_trait]
+impl CommandMiddleware for MemoryMiddleware {
+    fn name(&self) -> &str {
+        "memory"
+    }
+
+    async fn after(
+        &self,
+        context: &CommandContext,
+        descriptor: &CommandDescriptor,
+        _args: &serde_json::Value,
+        result: &CommandExecutionResult,
+    ) -> Result<()> {
+        let summary = if result.success {
+            result.output.lines().next().unwrap_or_default().to_string()
+        } else {
+            result.error.clone().unwrap_or_default()
+        };
+        // Commands that spawn processes report their exit code in `data`
+        let exit_code = result
+            .data
+            .as_ref()
+            .and_then(|data| data.get("exit_code"))
+            .and_then(|code| code.as_i64())
+            .map(|code| code as i32);
+
+        let execution_id = self
+            .memory
+            .record_command_execution(
+                context.session_id,
+                descriptor.name.clone(),
+                ExecutionResult {
+                    success: result.success,
+                    summary,
+                    details: None,
+                    files_affected: Vec::new(),
+                    follow_up_actions: Vec::new(),
+                },
+                (!result.output.is_empty()).then(|| result.output.clone()),
+                result.error.clone(),
+                Some(std::time::Duration::from_millis(result.execution_time_ms)),
+                exit_code,
+            )
+            .await?;
+        debug!(
+            "Recorded '{}' as command execution {} in session {}",
+            descriptor.name, execution_id, context.session_id
+        );
+        Ok(())
+    }
+}
+
+/// Feeds executed commands into an [`ExecutionHistory`] for the history command
+pub struct HistoryMiddleware {
+    history: Arc<ExecutionHistory>,
+}
+
+impl HistoryMiddleware {
+    pub fn new(history: Arc<ExecutionHistory>) -> Self {
+        Self { history }
+    }
+}
+
+#[async_trait]
+impl CommandMiddleware for HistoryMiddleware {
+    fn name(&self) -> &str {
+        "history"
+    }
+
+    async fn after(
+        &self,
+        _context: &CommandContext,
+        descriptor: &CommandDescriptor,
+        args: &serde_json::Value,
+        result: &CommandExecutionResult,
+    ) -> Result<()> {
+        self.history
+            .record(ExecutionRecord {
+                id: result.execution_id,
+                command: descriptor.name.clone(),
+                timestamp: result.created_at,
+                args_summary: summarize_args(args),
+                success: result.success,
+                error: result.error.clone(),
+                duration_ms: result.execution_time_ms,
+            })
+            .await;
+        Ok(())
+    }
+}
+
+/// Persists executed commands in a [`ResultStore`] so they can be reopened
+/// or replayed after the session ends
+pub struct ResultStoreMiddleware {
+    store: Arc<ResultStore>,
+}
+
+impl ResultStoreMiddleware {
+    pub fn new(store: Arc<ResultStore>) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl CommandMiddleware for ResultStoreMiddleware {
+    fn name(&self) -> &str {
+        "result-store"
+    }
+
+    async fn after(
+        &self,
+        context: &CommandContext,
+        _descriptor: &CommandDescriptor,
+        args: &serde_json::Value,
+        result: &CommandExecutionResult,
+    ) -> Result<()> {
+        self.store.record(context, args, result).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::{CommandExecutor, CommandRegistry};
+    use fennec_core::command::{Capability, CommandPreview, CommandResult};
+    use fennec_security::SandboxLevel;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+    use tokio_util::sync::CancellationToken;
+    use uuid::Uuid;
+
+    struct EchoCommand {
+        descriptor: CommandDescriptor,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl CommandExecutor for EchoCommand {
+        fn descriptor(&self) -> &CommandDescriptor {
+            &self.descriptor
+        }
+
+        async fn preview(
+            &self,
+            _args: &serde_json::Value,
+            _context: &CommandContext,
+        ) -> Result<CommandPreview> {
+            unreachable!()
+        }
+
+        async fn execute(
+            &self,
+            _args: &serde_json::Value,
+            _context: &CommandContext,
+        ) -> Result<CommandResult> {
+            self.log.lock().unwrap().push("execute".to_string());
+            Ok(CommandResult {
+                command_id: Uuid::new_v4(),
+                success: true,
+                output: "echoed".to_string(),
+                error: None,
+                data: None,
+            })
+        }
+
+        fn validate_args(&self, _args: &serde_json::Value) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    struct RecordingMiddleware {
+        name: &'static str,
+        veto: bool,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl CommandMiddleware for RecordingMiddleware {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn before(
+            &self,
+            _context: &CommandContext,
+            _descriptor: &CommandDescriptor,
+            _args: &serde_json::Value,
+        ) -> Result<MiddlewareDecision> {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("{}.before", self.name));
+            Ok(if self.veto {
+                MiddlewareDecision::Veto("not today".to_string())
+            } else {
+                MiddlewareDecision::Continue
+            })
+        }
+
+        async fn after(
+            &self,
+            _context: &CommandContext,
+            _descriptor: &CommandDescriptor,
+            _args: &serde_json::Value,
+            result: &CommandExecutionResult,
+        ) -> Result<()> {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("{}.after:{}", self.name, result.output));
+            Ok(())
+        }
+    }
+
+    async fn registry_with(log: &Arc<Mutex<Vec<String>>>) -> CommandRegistry {
+        let registry = CommandRegistry::new();
+        registry
+            .register_builtin(Arc::new(EchoCommand {
+                descriptor: CommandDescriptor {
+                    name: "echo".to_string(),
+                    description: "Echo".to_string(),
+                    version: "1.0.0".to_string(),
+                    author: None,
+                    capabilities_required: vec![Capability::ReadFile],
+                    sandbox_level_required: SandboxLevel::ReadOnly,
+                    supports_preview: false,
+                    supports_dry_run: false,
+                    timeout: None,
+                    alias_of: None,
+                },
+                log: log.clone(),
+            }))
+            .await
+            .unwrap();
+        registry
+    }
+
+    fn context(session_id: Uuid) -> CommandContext {
+        CommandContext {
+            session_id,
+            user_id: None,
+            workspace_path: None,
+            sandbox_level: SandboxLevel::ReadOnly,
+            dry_run: false,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log: None,
+            timeout: None,
+            progress: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_middleware_runs_in_registration_order() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let registry = registry_with(&log).await;
+        for name in ["first", "second"] {
+            registry
+                .register_middleware(Box::new(RecordingMiddleware {
+                    name,
+                    veto: false,
+                    log: log.clone(),
+                }))
+                .await;
+        }
+
+        let result = registry
+            .execute_command("echo", &serde_json::json!({}), &context(Uuid::new_v4()))
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "first.before",
+                "second.before",
+                "execute",
+                "first.after:echoed",
+                "second.after:echoed",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_middleware_veto_stops_execution() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let registry = registry_with(&log).await;
+        for (name, veto) in [("policy", true), ("never", false)] {
+            registry
+                .register_middleware(Box::new(RecordingMiddleware {
+                    name,
+                    veto,
+                    log: log.clone(),
+                }))
+                .await;
+        }
+
+        let result = registry
+            .execute_command("echo", &serde_json::json!({}), &context(Uuid::new_v4()))
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        assert_eq!(
+            result.error.as_deref(),
+            Some("Command 'echo' vetoed by policy middleware: not today")
+        );
+        assert_eq!(*log.lock().unwrap(), vec!["policy.before"]);
+    }
+
+    #[tokio::test]
+    async fn test_audit_middleware_writes_trail() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = fennec_core::config::Config::default();
+        config.security.audit_log_path = Some(temp_dir.path().to_path_buf());
+        config.security.audit_log_enabled = true;
+
+        let audit_system = Arc::new(AuditSystem::new(&config).await.unwrap());
+        let session_id = Uuid::new_v4();
+        audit_system
+            .start_session(session_id, None, None)
+            .await
+            .unwrap();
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let registry = registry_with(&log).await;
+        registry
+            .register_middleware(Box::new(AuditMiddleware::new(audit_system.clone())))
+            .await;
+
+        let result = registry
+            .execute_command("echo", &serde_json::json!({"x": 1}), &context(session_id))
+            .await
+            .unwrap();
+        assert!(result.success);
+
+        let manager = audit_system.get_session(session_id).await.unwrap();
+        let trail = tokio::fs::read_to_string(manager.file_path())
+            .await
+            .unwrap();
+        assert!(trail.contains("CommandRequested"));
+        assert!(trail.contains("CommandCompleted"));
+        assert!(trail.contains("echo"));
+    }
+
+    #[tokio::test]
+    async fn test_history_middleware_records_execution() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let registry = registry_with(&log).await;
+        let history = Arc::new(ExecutionHistory::new());
+        registry
+            .register_middleware(Box::new(HistoryMiddleware::new(history.clone())))
+            .await;
+
+        registry
+            .execute_command(
+                "echo",
+                &serde_json::json!({"path": "a.rs"}),
+                &context(Uuid::new_v4()),
+            )
+            .await
+            .unwrap();
+
+        let records = history.records().await;
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].command, "echo");
+        assert_eq!(records[0].args_summary, r#"{"path":"a.rs"}"#);
+        assert!(records[0].success);
+    }
+}
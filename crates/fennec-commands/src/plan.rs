@@ -3,14 +3,23 @@ use async_trait::async_trait;
 use fennec_core::{
     command::{Capability, CommandPreview, CommandResult, PreviewAction},
     error::FennecError,
+    provider::{ProviderClient, ProviderMessage, ProviderRequest},
 };
 use fennec_memory::agents::AgentsService;
+use fennec_memory::{
+    CommandPlan, PlanEvent, PlanPriority, PlanStatus, PlanStep, PlanStore, StepStatus,
+};
 use fennec_security::SandboxLevel;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
 use uuid::Uuid;
 
 use crate::registry::{CommandContext, CommandDescriptor, CommandExecutor};
 
+/// Tag added to plans whose provider reply could not be split into steps
+pub const UNPARSED_PLAN_TAG: &str = "unparsed";
+
 /// Arguments for the plan command
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlanArgs {
@@ -22,12 +31,53 @@ pub struct PlanArgs {
     pub include_implementation: Option<bool>,
     /// Target complexity level (simple, moderate, complex)
     pub complexity: Option<String>,
+    /// Store the plan in the plan store so it can be referenced by id
+    #[serde(default)]
+    pub register: bool,
+    /// Priority recorded on the plan
+    #[serde(default)]
+    pub priority: PlanPriority,
+}
+
+/// Structured plan returned in the command's data alongside the prose
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneratedPlan {
+    pub plan: CommandPlan,
+    /// Set once the plan has been registered in the plan store
+    pub plan_id: Option<Uuid>,
+    /// The provider reply could not be parsed into steps and was kept as a
+    /// single step
+    pub fallback: bool,
+}
+
+/// A step as described by the provider or a built-in template
+#[derive(Debug, Clone, PartialEq)]
+struct PlannedStep {
+    title: String,
+    description: String,
+    complexity: String,
+    suggested_commands: Vec<String>,
+}
+
+/// Step shape the provider is asked to reply with
+#[derive(Debug, Deserialize)]
+struct RawStep {
+    title: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default, alias = "estimated_complexity")]
+    complexity: Option<String>,
+    #[serde(default, alias = "commands")]
+    suggested_commands: Vec<String>,
 }
 
 /// Plan command for creating structured task plans
 pub struct PlanCommand {
     descriptor: CommandDescriptor,
     agents_service: AgentsService,
+    plan_store: Arc<RwLock<Option<PlanStore>>>,
+    provider: Option<Arc<dyn ProviderClient>>,
+    model: String,
 }
 
 impl PlanCommand {
@@ -45,13 +95,48 @@ impl PlanCommand {
                 sandbox_level_required: SandboxLevel::ReadOnly,
                 supports_preview: true,
                 supports_dry_run: true,
+                timeout: None,
+                alias_of: None,
             },
             agents_service,
+            plan_store: Arc::new(RwLock::new(None)),
+            provider: None,
+            model: String::new(),
         })
     }
 
+    /// Ask `provider` for the plan steps; without one they come from a
+    /// template matching the requested complexity
+    pub fn with_provider(mut self, provider: Arc<dyn ProviderClient>, model: &str) -> Self {
+        self.provider = Some(provider);
+        self.model = model.to_string();
+        self
+    }
+
+    /// Register plans in `store` rather than the default plan store
+    pub fn with_plan_store(mut self, store: PlanStore) -> Self {
+        self.plan_store = Arc::new(RwLock::new(Some(store)));
+        self
+    }
+
+    /// Receive changes to the plans this command registers
+    pub async fn subscribe_plans(&self) -> Result<broadcast::Receiver<PlanEvent>> {
+        let mut store = self.plan_store.write().await;
+        if store.is_none() {
+            *store = Some(PlanStore::new()?);
+        }
+        Ok(store
+            .as_ref()
+            .expect("plan store initialized above")
+            .subscribe())
+    }
+
     /// Generate a structured plan based on the task and available guidance
-    async fn generate_plan(&self, args: &PlanArgs, context: &CommandContext) -> Result<String> {
+    async fn generate_plan(
+        &self,
+        args: &PlanArgs,
+        context: &CommandContext,
+    ) -> Result<(String, GeneratedPlan)> {
         let mut plan_parts = Vec::new();
 
         // Add task description
@@ -85,83 +170,33 @@ impl PlanCommand {
             plan_parts.push(String::new());
         }
 
-        // Generate plan structure based on complexity
         let complexity = args.complexity.as_deref().unwrap_or("moderate");
-        plan_parts.push("## Plan Structure".to_string());
-
-        match complexity {
-            "simple" => {
-                plan_parts.extend(vec![
-                    "### 1. Preparation".to_string(),
-                    "- Review requirements and constraints".to_string(),
-                    "- Gather necessary resources".to_string(),
-                    String::new(),
-                    "### 2. Implementation".to_string(),
-                    "- Execute the main task".to_string(),
-                    "- Monitor progress".to_string(),
-                    String::new(),
-                    "### 3. Validation".to_string(),
-                    "- Test and verify results".to_string(),
-                    "- Document outcomes".to_string(),
-                ]);
-            }
-            "complex" => {
-                plan_parts.extend(vec![
-                    "### 1. Analysis & Planning".to_string(),
-                    "- Break down the task into components".to_string(),
-                    "- Identify dependencies and risks".to_string(),
-                    "- Create detailed timeline".to_string(),
-                    String::new(),
-                    "### 2. Design & Architecture".to_string(),
-                    "- Design overall approach".to_string(),
-                    "- Plan integration points".to_string(),
-                    "- Consider scalability and maintainability".to_string(),
-                    String::new(),
-                    "### 3. Implementation".to_string(),
-                    "- Implement core functionality".to_string(),
-                    "- Add error handling and logging".to_string(),
-                    "- Implement tests".to_string(),
-                    String::new(),
-                    "### 4. Integration & Testing".to_string(),
-                    "- Integration testing".to_string(),
-                    "- Performance testing".to_string(),
-                    "- User acceptance testing".to_string(),
-                    String::new(),
-                    "### 5. Deployment & Monitoring".to_string(),
-                    "- Deploy to staging/production".to_string(),
-                    "- Set up monitoring and alerts".to_string(),
-                    "- Document deployment process".to_string(),
-                ]);
-            }
-            _ => {
-                // moderate
-                plan_parts.extend(vec![
-                    "### 1. Planning & Research".to_string(),
-                    "- Understand requirements thoroughly".to_string(),
-                    "- Research best practices and existing solutions".to_string(),
-                    "- Identify potential challenges".to_string(),
-                    String::new(),
-                    "### 2. Design & Setup".to_string(),
-                    "- Design the approach and architecture".to_string(),
-                    "- Set up development environment".to_string(),
-                    "- Create project structure".to_string(),
-                    String::new(),
-                    "### 3. Implementation".to_string(),
-                    "- Implement core functionality".to_string(),
-                    "- Add proper error handling".to_string(),
-                    "- Write tests as you go".to_string(),
-                    String::new(),
-                    "### 4. Testing & Refinement".to_string(),
-                    "- Comprehensive testing".to_string(),
-                    "- Performance optimization".to_string(),
-                    "- Code review and refactoring".to_string(),
-                    String::new(),
-                    "### 5. Documentation & Deployment".to_string(),
-                    "- Write documentation".to_string(),
-                    "- Prepare for deployment".to_string(),
-                    "- Create maintenance plan".to_string(),
-                ]);
+        let guidance_titles: Vec<&str> = guidance_matches
+            .iter()
+            .take(3)
+            .map(|guidance| guidance.section_title.as_str())
+            .collect();
+        let (steps, fallback) = match &self.provider {
+            Some(provider) => {
+                let reply = self
+                    .request_steps(provider.as_ref(), args, &guidance_titles)
+                    .await?;
+                match parse_plan_steps(&reply) {
+                    Some(steps) => (steps, false),
+                    None => (vec![fallback_step(args, complexity, &reply)], true),
+                }
             }
+            None => (template_steps(complexity), false),
+        };
+
+        plan_parts.push("## Plan Structure".to_string());
+        plan_parts.extend(render_steps(&steps));
+        if fallback {
+            plan_parts.push(String::new());
+            plan_parts.push(
+                "*Note: The provider reply could not be split into steps and was kept as one*"
+                    .to_string(),
+            );
         }
 
         // Add implementation steps if requested
@@ -198,7 +233,339 @@ impl PlanCommand {
             plan_parts.push("*Note: This plan was generated in dry-run mode*".to_string());
         }
 
-        Ok(plan_parts.join("\n"))
+        let plan = build_command_plan(args, context, complexity, steps, fallback);
+        Ok((
+            plan_parts.join("\n"),
+            GeneratedPlan {
+                plan,
+                plan_id: None,
+                fallback,
+            },
+        ))
+    }
+
+    async fn request_steps(
+        &self,
+        provider: &dyn ProviderClient,
+        args: &PlanArgs,
+        guidance_titles: &[&str],
+    ) -> Result<String> {
+        let mut system = format!(
+            "You break software tasks into an ordered plan of {} complexity. Reply with a JSON \
+             array only, one object per step with the keys \"title\", \"description\", \
+             \"complexity\" (low, medium or high) and \"suggested_commands\" (a list of \
+             strings).",
+            args.complexity.as_deref().unwrap_or("moderate")
+        );
+        if !guidance_titles.is_empty() {
+            system.push_str(&format!(
+                " Follow the project guidance on: {}.",
+                guidance_titles.join(", ")
+            ));
+        }
+
+        let mut prompt = format!("Task: {}", args.task);
+        if let Some(ctx) = &args.context {
+            prompt.push_str(&format!("\n\nContext: {}", ctx));
+        }
+
+        let request = ProviderRequest {
+            id: Uuid::new_v4(),
+            messages: vec![
+                ProviderMessage {
+                    role: "system".to_string(),
+                    content: system,
+                },
+                ProviderMessage {
+                    role: "user".to_string(),
+                    content: prompt,
+                },
+            ],
+            model: self.model.clone(),
+            stream: false,
+            temperature: None,
+            max_tokens: None,
+        };
+
+        let response = provider.complete(request).await.map_err(|e| {
+            FennecError::Command(Box::new(std::io::Error::other(format!(
+                "Provider request failed: {}",
+                e
+            ))))
+        })?;
+        Ok(response.content)
+    }
+
+    /// Generate the plan and, when asked to, register it in the plan store
+    async fn perform_plan(
+        &self,
+        args: &PlanArgs,
+        context: &CommandContext,
+    ) -> Result<(String, GeneratedPlan)> {
+        let (mut output, mut generated) = self.generate_plan(args, context).await?;
+        if !args.register {
+            return Ok((output, generated));
+        }
+
+        if context.dry_run {
+            output.push_str("\n\nWould register this plan in the plan store");
+            return Ok((output, generated));
+        }
+
+        let mut store = self.plan_store.write().await;
+        if store.is_none() {
+            *store = Some(PlanStore::new()?);
+        }
+        let plan_id = store
+            .as_mut()
+            .expect("plan store initialized above")
+            .register_plan(generated.plan.clone())
+            .await?;
+
+        output.push_str(&format!("\n\nRegistered as plan {}", plan_id));
+        generated.plan_id = Some(plan_id);
+        Ok((output, generated))
+    }
+}
+
+/// Extract the step list from a provider reply, tolerating prose or code
+/// fences around the JSON array
+fn parse_plan_steps(reply: &str) -> Option<Vec<PlannedStep>> {
+    let start = reply.find('[')?;
+    let end = reply.rfind(']')?;
+    if end < start {
+        return None;
+    }
+
+    let raw: Vec<RawStep> = serde_json::from_str(&reply[start..=end]).ok()?;
+    if raw.is_empty() || raw.iter().any(|step| step.title.trim().is_empty()) {
+        return None;
+    }
+
+    Some(
+        raw.into_iter()
+            .map(|step| PlannedStep {
+                title: step.title.trim().to_string(),
+                description: step.description.trim().to_string(),
+                complexity: normalize_complexity(step.complexity.as_deref()),
+                suggested_commands: step.suggested_commands,
+            })
+            .collect(),
+    )
+}
+
+/// Map the provider's complexity wording onto low, medium or high
+fn normalize_complexity(complexity: Option<&str>) -> String {
+    match complexity.map(|c| c.trim().to_lowercase()).as_deref() {
+        Some("low" | "simple" | "easy") => "low",
+        Some("high" | "complex" | "hard") => "high",
+        _ => "medium",
+    }
+    .to_string()
+}
+
+/// The whole provider reply as one step, for replies that are not a step list
+fn fallback_step(args: &PlanArgs, complexity: &str, reply: &str) -> PlannedStep {
+    PlannedStep {
+        title: args.task.clone(),
+        description: reply.trim().to_string(),
+        complexity: normalize_complexity(Some(complexity)),
+        suggested_commands: Vec::new(),
+    }
+}
+
+/// Built-in plan structure for each complexity level
+fn template_steps(complexity: &str) -> Vec<PlannedStep> {
+    let sections: &[(&str, &[&str])] = match complexity {
+        "simple" => &[
+            (
+                "Preparation",
+                &[
+                    "Review requirements and constraints",
+                    "Gather necessary resources",
+                ],
+            ),
+            (
+                "Implementation",
+                &["Execute the main task", "Monitor progress"],
+            ),
+            (
+                "Validation",
+                &["Test and verify results", "Document outcomes"],
+            ),
+        ],
+        "complex" => &[
+            (
+                "Analysis & Planning",
+                &[
+                    "Break down the task into components",
+                    "Identify dependencies and risks",
+                    "Create detailed timeline",
+                ],
+            ),
+            (
+                "Design & Architecture",
+                &[
+                    "Design overall approach",
+                    "Plan integration points",
+                    "Consider scalability and maintainability",
+                ],
+            ),
+            (
+                "Implementation",
+                &[
+                    "Implement core functionality",
+                    "Add error handling and logging",
+                    "Implement tests",
+                ],
+            ),
+            (
+                "Integration & Testing",
+                &[
+                    "Integration testing",
+                    "Performance testing",
+                    "User acceptance testing",
+                ],
+            ),
+            (
+                "Deployment & Monitoring",
+                &[
+                    "Deploy to staging/production",
+                    "Set up monitoring and alerts",
+                    "Document deployment process",
+                ],
+            ),
+        ],
+        // moderate
+        _ => &[
+            (
+                "Planning & Research",
+                &[
+                    "Understand requirements thoroughly",
+                    "Research best practices and existing solutions",
+                    "Identify potential challenges",
+                ],
+            ),
+            (
+                "Design & Setup",
+                &[
+                    "Design the approach and architecture",
+                    "Set up development environment",
+                    "Create project structure",
+                ],
+            ),
+            (
+                "Implementation",
+                &[
+                    "Implement core functionality",
+                    "Add proper error handling",
+                    "Write tests as you go",
+                ],
+            ),
+            (
+                "Testing & Refinement",
+                &[
+                    "Comprehensive testing",
+                    "Performance optimization",
+                    "Code review and refactoring",
+                ],
+            ),
+            (
+                "Documentation & Deployment",
+                &[
+                    "Write documentation",
+                    "Prepare for deployment",
+                    "Create maintenance plan",
+                ],
+            ),
+        ],
+    };
+
+    sections
+        .iter()
+        .map(|(title, bullets)| PlannedStep {
+            title: title.to_string(),
+            description: bullets
+                .iter()
+                .map(|bullet| format!("- {}", bullet))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            complexity: normalize_complexity(Some(complexity)),
+            suggested_commands: Vec::new(),
+        })
+        .collect()
+}
+
+fn render_steps(steps: &[PlannedStep]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (i, step) in steps.iter().enumerate() {
+        if i > 0 {
+            lines.push(String::new());
+        }
+        lines.push(format!("### {}. {}", i + 1, step.title));
+        if !step.description.is_empty() {
+            lines.push(step.description.clone());
+        }
+        lines.push(format!("- Estimated complexity: {}", step.complexity));
+        if !step.suggested_commands.is_empty() {
+            let commands: Vec<String> = step
+                .suggested_commands
+                .iter()
+                .map(|command| format!("`{}`", command))
+                .collect();
+            lines.push(format!("- Suggested commands: {}", commands.join(", ")));
+        }
+    }
+    lines
+}
+
+fn build_command_plan(
+    args: &PlanArgs,
+    context: &CommandContext,
+    complexity: &str,
+    steps: Vec<PlannedStep>,
+    fallback: bool,
+) -> CommandPlan {
+    let now = chrono::Utc::now();
+    let mut tags = vec!["plan".to_string(), complexity.to_string()];
+    if fallback {
+        tags.push(UNPARSED_PLAN_TAG.to_string());
+    }
+
+    CommandPlan {
+        id: Uuid::new_v4(),
+        session_id: context.session_id,
+        title: args.task.clone(),
+        description: args.context.clone().unwrap_or_else(|| args.task.clone()),
+        steps: steps
+            .into_iter()
+            .enumerate()
+            .map(|(order, step)| PlanStep {
+                id: Uuid::new_v4(),
+                order: order as u32,
+                title: step.title,
+                description: step.description,
+                status: StepStatus::Pending,
+                estimated_effort: Some(step.complexity),
+                estimated_duration: None,
+                actual_duration: None,
+                dependencies: Vec::new(),
+                suggested_commands: step.suggested_commands,
+                command_associations: Vec::new(),
+                notes: Vec::new(),
+                started_at: None,
+                completed_at: None,
+            })
+            .collect(),
+        status: PlanStatus::Ready,
+        created_at: now,
+        updated_at: now,
+        execution_results: Vec::new(),
+        templates_used: Vec::new(),
+        tags,
+        priority: args.priority.clone(),
+        estimated_effort: Some(complexity.to_string()),
+        actual_duration: None,
     }
 }
 
@@ -208,6 +575,19 @@ impl CommandExecutor for PlanCommand {
         &self.descriptor
     }
 
+    fn required_capabilities(&self, args: &serde_json::Value) -> Vec<Capability> {
+        let mut capabilities = self.descriptor.capabilities_required.clone();
+
+        // Registered plans are written to the plan store
+        let registers = serde_json::from_value::<PlanArgs>(args.clone())
+            .map(|args| args.register)
+            .unwrap_or(false);
+        if registers {
+            capabilities.push(Capability::WriteFile);
+        }
+        capabilities
+    }
+
     async fn preview(
         &self,
         args: &serde_json::Value,
@@ -222,7 +602,14 @@ impl CommandExecutor for PlanCommand {
 
         Ok(CommandPreview {
             command_id: Uuid::new_v4(),
-            description: format!("Generate a structured plan for: {}", args.task),
+            description: if args.register {
+                format!(
+                    "Generate a structured plan for: {} and register it in the plan store",
+                    args.task
+                )
+            } else {
+                format!("Generate a structured plan for: {}", args.task)
+            },
             actions: vec![PreviewAction::ReadFile {
                 path: "AGENTS.md (if available)".to_string(),
             }],
@@ -249,21 +636,24 @@ impl CommandExecutor for PlanCommand {
                 success: false,
                 output: String::new(),
                 error: Some("Command was cancelled".to_string()),
+                data: None,
             });
         }
 
-        match self.generate_plan(&args, context).await {
-            Ok(plan) => Ok(CommandResult {
+        match self.perform_plan(&args, context).await {
+            Ok((output, plan)) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: true,
-                output: plan,
+                output,
                 error: None,
+                data: serde_json::to_value(&plan).ok(),
             }),
             Err(e) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: false,
                 output: String::new(),
                 error: Some(format!("Failed to generate plan: {}", e)),
+                data: None,
             }),
         }
     }
@@ -348,6 +738,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -358,4 +750,140 @@ mod tests {
         assert!(result.output.contains("Context"));
         assert!(result.output.contains("Implementation Checklist"));
     }
+
+    fn plan_context() -> CommandContext {
+        CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: None,
+            sandbox_level: SandboxLevel::ReadOnly,
+            dry_run: false,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log: None,
+            timeout: None,
+            progress: None,
+        }
+    }
+
+    async fn mock_plan_command(store_dir: &std::path::Path) -> PlanCommand {
+        PlanCommand::new()
+            .await
+            .unwrap()
+            .with_provider(
+                Arc::new(fennec_provider::MockProviderClient::default()),
+                "mock",
+            )
+            .with_plan_store(PlanStore::with_storage_dir(store_dir).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_provider_steps_are_registered() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let command = mock_plan_command(temp_dir.path()).await;
+        let context = plan_context();
+
+        // The mock provider echoes the prompt, so the context is the reply
+        let steps = serde_json::json!([
+            {
+                "title": "Add cache layer",
+                "description": "Wrap the lookup in an LRU cache",
+                "complexity": "Complex",
+                "suggested_commands": ["edit src/lookup.rs"]
+            },
+            {
+                "title": "Benchmark",
+                "estimated_complexity": "low",
+                "commands": ["run cargo bench"]
+            }
+        ]);
+        let args = serde_json::json!({
+            "task": "Speed up lookups",
+            "context": steps.to_string(),
+            "register": true,
+            "priority": "High"
+        });
+
+        let result = command.execute(&args, &context).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.output.contains("### 1. Add cache layer"));
+        assert!(result
+            .output
+            .contains("- Suggested commands: `run cargo bench`"));
+
+        let generated: GeneratedPlan = serde_json::from_value(result.data.unwrap()).unwrap();
+        assert!(!generated.fallback);
+        let plan_id = generated.plan_id.expect("plan registered");
+        assert!(result.output.contains(&plan_id.to_string()));
+
+        let titles: Vec<&str> = generated
+            .plan
+            .steps
+            .iter()
+            .map(|step| step.title.as_str())
+            .collect();
+        assert_eq!(titles, ["Add cache layer", "Benchmark"]);
+        assert_eq!(
+            generated.plan.steps[0].estimated_effort.as_deref(),
+            Some("high")
+        );
+        assert_eq!(
+            generated.plan.steps[1].estimated_effort.as_deref(),
+            Some("low")
+        );
+
+        let mut store = PlanStore::with_storage_dir(temp_dir.path()).unwrap();
+        let stored = store.load_plan(plan_id).await.unwrap().unwrap();
+        assert_eq!(stored.session_id, context.session_id);
+        assert_eq!(stored.priority, PlanPriority::High);
+        assert_eq!(
+            stored.steps[0].suggested_commands,
+            vec!["edit src/lookup.rs".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_malformed_steps_fall_back_to_single_step() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let command = mock_plan_command(temp_dir.path()).await;
+
+        for malformed in [
+            r#"[{"title": "Unclosed step""#,
+            r#"[{"description": "Missing a title"}]"#,
+            "First profile it, then add a cache",
+        ] {
+            let args = serde_json::json!({
+                "task": "Speed up lookups",
+                "context": malformed,
+                "register": true
+            });
+
+            let result = command.execute(&args, &plan_context()).await.unwrap();
+            assert!(result.success, "{:?}", result.error);
+            assert!(result.output.contains("could not be split into steps"));
+
+            let generated: GeneratedPlan = serde_json::from_value(result.data.unwrap()).unwrap();
+            assert!(generated.fallback, "{}", malformed);
+            assert!(generated.plan_id.is_some());
+            assert_eq!(generated.plan.steps.len(), 1);
+            assert_eq!(generated.plan.steps[0].title, "Speed up lookups");
+            assert!(generated.plan.steps[0].description.contains(malformed));
+            assert!(generated.plan.tags.contains(&UNPARSED_PLAN_TAG.to_string()));
+            assert_eq!(generated.plan.priority, PlanPriority::Medium);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_template_plan_is_structured_but_not_registered() {
+        let command = PlanCommand::new().await.unwrap();
+        let args = serde_json::json!({"task": "Write docs", "complexity": "simple"});
+
+        let result = command.execute(&args, &plan_context()).await.unwrap();
+        let generated: GeneratedPlan = serde_json::from_value(result.data.unwrap()).unwrap();
+        assert!(!generated.fallback);
+        assert!(generated.plan_id.is_none());
+        assert_eq!(generated.plan.steps.len(), 3);
+        assert_eq!(generated.plan.steps[2].title, "Validation");
+        assert!(result.output.contains("### 3. Validation"));
+    }
 }
@@ -1,10 +1,17 @@
-use crate::git_integration::{generate_pr_summary, get_commits, get_current_branch};
+use crate::git_integration::{
+    changes_between, commits_between, generate_pr_summary, get_current_branch, get_file_changes,
+    get_file_diff, ChangeType, DiffTarget, FileChange, GitCommit,
+};
 use crate::registry::{CommandContext, CommandDescriptor, CommandExecutor};
 use anyhow::Result;
 use fennec_core::command::{Capability, CommandPreview, CommandResult};
 use fennec_core::error::FennecError;
 use fennec_security::SandboxLevel;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+use tracing::warn;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,14 +27,93 @@ pub struct PrSummaryArgs {
     /// Maximum number of commits to include
     #[serde(default = "default_max_commits")]
     pub max_commits: usize,
+
+    /// Summarize staged changes instead of a branch range
+    #[serde(default)]
+    pub staged: bool,
+
+    /// Skip the generated narrative and emit only computed sections
+    #[serde(default)]
+    pub no_ai: bool,
+
+    #[serde(default)]
+    pub format: PrSummaryFormat,
 }
 
 fn default_max_commits() -> usize {
     50
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrSummaryFormat {
+    #[default]
+    Markdown,
+    Json,
+}
+
+/// Changed files that share a crate or top-level directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileGroup {
+    pub name: String,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub files: Vec<FileChange>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskKind {
+    Migration,
+    DependencyChange,
+    CiWorkflow,
+    TestDeletion,
+}
+
+impl RiskKind {
+    fn label(&self) -> &'static str {
+        match self {
+            RiskKind::Migration => "Migration",
+            RiskKind::DependencyChange => "Dependency change",
+            RiskKind::CiWorkflow => "CI workflow",
+            RiskKind::TestDeletion => "Test deletion",
+        }
+    }
+}
+
+/// A change reviewers should look at closely
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskCallout {
+    pub kind: RiskKind,
+    pub path: String,
+    pub detail: String,
+}
+
+/// Everything the summary is built from; emitted as-is for `--format json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrReport {
+    pub base: String,
+    pub head: String,
+    pub staged: bool,
+    pub commits: Vec<GitCommit>,
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub groups: Vec<FileGroup>,
+    pub risks: Vec<RiskCallout>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub narrative: Option<String>,
+}
+
+/// Writes the prose overview of a PR, typically backed by an LLM provider
+#[async_trait::async_trait]
+pub trait PrNarrator: Send + Sync {
+    async fn narrate(&self, report: &PrReport) -> Result<String>;
+}
+
 pub struct PrSummaryCommand {
     descriptor: CommandDescriptor,
+    narrator: Option<Arc<dyn PrNarrator>>,
 }
 
 impl PrSummaryCommand {
@@ -38,31 +124,29 @@ impl PrSummaryCommand {
                 description: "Generate a pull request summary from git commits".to_string(),
                 version: "1.0.0".to_string(),
                 author: Some("Fennec Contributors".to_string()),
-                capabilities_required: vec![Capability::ExecuteShell],
-                sandbox_level_required: SandboxLevel::ReadOnly,
+                capabilities_required: vec![Capability::ReadFile, Capability::ExecuteShell],
+                sandbox_level_required: SandboxLevel::WorkspaceWrite,
                 supports_preview: false,
                 supports_dry_run: false,
+                timeout: None,
+                alias_of: None,
             },
+            narrator: None,
         }
     }
 
-    async fn generate_summary(
-        &self,
-        args: &PrSummaryArgs,
-        context: &CommandContext,
-    ) -> Result<String> {
-        let workspace_path = context.workspace_path.as_ref().ok_or_else(|| {
-            FennecError::Command(Box::new(std::io::Error::new(
-                std::io::ErrorKind::NotFound,
-                "No workspace path set",
-            )))
-        })?;
+    /// Add a generated narrative to summaries unless `no_ai` is set
+    pub fn with_narrator(mut self, narrator: Arc<dyn PrNarrator>) -> Self {
+        self.narrator = Some(narrator);
+        self
+    }
 
+    async fn build_report(&self, args: &PrSummaryArgs, workspace_path: &str) -> Result<PrReport> {
         // Get current branch if not specified
         let current_branch = if args.head_branch.is_none() {
             match get_current_branch(workspace_path).await {
-                Ok(branch) => branch,
-                Err(_) => "HEAD".to_string(),
+                Ok(branch) if !branch.is_empty() => branch,
+                _ => "HEAD".to_string(),
             }
         } else {
             args.head_branch.clone().unwrap()
@@ -74,39 +158,355 @@ impl PrSummaryCommand {
             "main".to_string()
         });
 
-        // Get commits between base and head
-        let branch_range = if current_branch == "HEAD" {
-            format!("{}..HEAD", base_branch)
+        let git_error = |what: &str, e: std::io::Error| {
+            FennecError::Command(Box::new(std::io::Error::new(
+                e.kind(),
+                format!("Failed to get {}: {}", what, e),
+            )))
+        };
+
+        // Diff from the merge base so unrelated base-branch work is excluded
+        let diff_range = format!("{}...{}", base_branch, current_branch);
+        let target = if args.staged {
+            DiffTarget::Staged
         } else {
-            format!("{}..{}", base_branch, current_branch)
+            DiffTarget::Range(&diff_range)
         };
 
-        let commits = get_commits(workspace_path, Some(&branch_range), Some(args.max_commits))
+        let (commits, changes) = if args.staged {
+            let changes = get_file_changes(workspace_path, target)
+                .await
+                .map_err(|e| git_error("changed files", e))?;
+            (Vec::new(), changes)
+        } else {
+            let commits = commits_between(
+                workspace_path,
+                &base_branch,
+                &current_branch,
+                Some(args.max_commits),
+            )
             .await
-            .map_err(|e| {
-                FennecError::Command(Box::new(std::io::Error::new(
-                    e.kind(),
-                    format!("Failed to get git commits: {}", e),
-                )))
-            })?;
-
-        if commits.is_empty() {
-            return Ok(format!(
-                "No commits found between '{}' and '{}'.\n\nThis could mean:\n- The branches are up to date\n- The base branch doesn't exist\n- The current branch is not ahead of the base branch",
-                base_branch, current_branch
-            ));
+            .map_err(|e| git_error("git commits", e))?;
+            let changes = changes_between(workspace_path, &base_branch, &current_branch)
+                .await
+                .map_err(|e| git_error("changed files", e))?;
+            (commits, changes)
+        };
+
+        let risks = detect_risks(workspace_path, target, &changes).await;
+
+        let mut report = PrReport {
+            base: base_branch,
+            head: current_branch,
+            staged: args.staged,
+            commits,
+            files_changed: changes.len(),
+            insertions: changes.iter().map(|c| c.insertions).sum(),
+            deletions: changes.iter().map(|c| c.deletions).sum(),
+            groups: group_changes(Path::new(workspace_path), changes),
+            risks,
+            narrative: None,
+        };
+
+        if !args.no_ai {
+            if let Some(narrator) = &self.narrator {
+                match narrator.narrate(&report).await {
+                    Ok(narrative) => report.narrative = Some(narrative),
+                    // The computed sections stand on their own
+                    Err(e) => warn!("PR narrative generation failed: {}", e),
+                }
+            }
         }
 
-        // Generate summary
-        let mut output = format!(
-            "# Pull Request Summary\n\n**From**: `{}` **To**: `{}`\n\n",
-            current_branch, base_branch
+        Ok(report)
+    }
+
+    async fn generate_summary(
+        &self,
+        args: &PrSummaryArgs,
+        context: &CommandContext,
+    ) -> Result<(String, PrReport)> {
+        let workspace_path = context.workspace_path.as_ref().ok_or_else(|| {
+            FennecError::Command(Box::new(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "No workspace path set",
+            )))
+        })?;
+
+        let report = self.build_report(args, workspace_path).await?;
+
+        let output = match args.format {
+            PrSummaryFormat::Json => serde_json::to_string_pretty(&report)?,
+            PrSummaryFormat::Markdown => render_markdown(&report),
+        };
+        Ok((output, report))
+    }
+}
+
+/// Group changes by the nearest enclosing crate, falling back to the
+/// top-level directory
+fn group_changes(workspace: &Path, changes: Vec<FileChange>) -> Vec<FileGroup> {
+    let mut groups: BTreeMap<String, FileGroup> = BTreeMap::new();
+    for change in changes {
+        let name = group_name(workspace, &change.path);
+        let group = groups.entry(name.clone()).or_insert_with(|| FileGroup {
+            name,
+            insertions: 0,
+            deletions: 0,
+            files: Vec::new(),
+        });
+        group.insertions += change.insertions;
+        group.deletions += change.deletions;
+        group.files.push(change);
+    }
+    groups.into_values().collect()
+}
+
+fn group_name(workspace: &Path, path: &Path) -> String {
+    let crate_dir = path
+        .ancestors()
+        .skip(1)
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .find(|dir| workspace.join(dir).join("Cargo.toml").is_file());
+    if let Some(dir) = crate_dir {
+        return dir.display().to_string();
+    }
+
+    let mut components = path.components();
+    match (components.next(), components.next()) {
+        (Some(first), Some(_)) => first.as_os_str().to_string_lossy().to_string(),
+        _ => "(root)".to_string(),
+    }
+}
+
+fn is_test_path(path: &Path) -> bool {
+    let in_test_dir = path
+        .parent()
+        .into_iter()
+        .flat_map(|p| p.components())
+        .any(|c| matches!(c.as_os_str().to_str(), Some("tests" | "test" | "__tests__")));
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    in_test_dir
+        || stem == "tests"
+        || stem.starts_with("test_")
+        || stem.ends_with("_test")
+        || stem.ends_with("_tests")
+        || stem.ends_with(".test")
+        || stem.ends_with(".spec")
+}
+
+fn is_ci_workflow(path: &Path) -> bool {
+    path.starts_with(".github/workflows")
+        || path.starts_with(".circleci")
+        || path == Path::new(".gitlab-ci.yml")
+        || path == Path::new("azure-pipelines.yml")
+}
+
+fn is_migration(path: &Path) -> bool {
+    path.components().any(|c| {
+        let name = c.as_os_str().to_string_lossy().to_lowercase();
+        name == "migrations" || name == "migration"
+    })
+}
+
+fn change_verb(change: &ChangeType) -> &'static str {
+    match change {
+        ChangeType::Added => "added",
+        ChangeType::Modified => "modified",
+        ChangeType::Deleted => "deleted",
+        ChangeType::Renamed => "renamed",
+    }
+}
+
+async fn detect_risks(
+    workspace_path: &str,
+    target: DiffTarget<'_>,
+    changes: &[FileChange],
+) -> Vec<RiskCallout> {
+    let mut risks = Vec::new();
+    for change in changes {
+        let path = change.path.display().to_string();
+        let verb = change_verb(&change.change_type);
+
+        if is_migration(&change.path) {
+            risks.push(RiskCallout {
+                kind: RiskKind::Migration,
+                path: path.clone(),
+                detail: format!("Migration {}", verb),
+            });
+        }
+        if is_ci_workflow(&change.path) {
+            risks.push(RiskCallout {
+                kind: RiskKind::CiWorkflow,
+                path: path.clone(),
+                detail: format!("CI configuration {}", verb),
+            });
+        }
+        if change.change_type == ChangeType::Deleted && is_test_path(&change.path) {
+            risks.push(RiskCallout {
+                kind: RiskKind::TestDeletion,
+                path: path.clone(),
+                detail: format!("Test file deleted ({} lines)", change.deletions),
+            });
+        }
+        if change.path.file_name().is_some_and(|n| n == "Cargo.toml") {
+            match get_file_diff(workspace_path, target, &path).await {
+                Ok(diff) => {
+                    let deps = changed_dependencies(&diff);
+                    if !deps.is_empty() {
+                        risks.push(RiskCallout {
+                            kind: RiskKind::DependencyChange,
+                            path: path.clone(),
+                            detail: format!("Dependencies changed: {}", deps.join(", ")),
+                        });
+                    }
+                }
+                Err(e) => warn!("Could not inspect {} for dependency changes: {}", path, e),
+            }
+        }
+    }
+    risks
+}
+
+/// Dependencies touched by a full-context Cargo.toml diff, prefixed with `+`
+/// (added), `-` (removed) or `~` (changed)
+fn changed_dependencies(diff: &str) -> Vec<String> {
+    let mut section = String::new();
+    let mut touched: BTreeMap<String, (bool, bool)> = BTreeMap::new();
+
+    for line in diff.lines().skip_while(|l| !l.starts_with("@@")) {
+        let (marker, content) = match line.chars().next() {
+            Some(c @ ('+' | '-' | ' ')) => (c, &line[1..]),
+            _ => continue,
+        };
+        let content = content.trim();
+        if let Some(header) = content.strip_prefix('[').and_then(|h| h.strip_suffix(']')) {
+            section = header.trim().to_string();
+            if marker == ' ' {
+                continue;
+            }
+        }
+        if marker == ' ' || content.is_empty() || content.starts_with('#') {
+            continue;
+        }
+
+        // `[dependencies.serde]` tables name the dependency in the header
+        let name = if let Some((table, dep)) = section.rsplit_once('.') {
+            if table.ends_with("dependencies") {
+                Some(dep.trim_matches('"').to_string())
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+        let name = match name {
+            Some(name) => name,
+            None if section.ends_with("dependencies") && !content.starts_with('[') => {
+                match content.split_once('=') {
+                    Some((key, _)) => key.trim().trim_matches('"').to_string(),
+                    None => continue,
+                }
+            }
+            None => continue,
+        };
+
+        let entry = touched.entry(name).or_default();
+        match marker {
+            '+' => entry.0 = true,
+            _ => entry.1 = true,
+        }
+    }
+
+    touched
+        .into_iter()
+        .map(|(name, (added, removed))| match (added, removed) {
+            (true, true) => format!("~{}", name),
+            (true, false) => format!("+{}", name),
+            _ => format!("-{}", name),
+        })
+        .collect()
+}
+
+fn render_markdown(report: &PrReport) -> String {
+    if report.commits.is_empty() && report.groups.is_empty() {
+        if report.staged {
+            return "No staged changes found.\n\nPlease stage your changes with 'git add' first."
+                .to_string();
+        }
+        return format!(
+            "No commits found between '{}' and '{}'.\n\nThis could mean:\n- The branches are up to date\n- The base branch doesn't exist\n- The current branch is not ahead of the base branch",
+            report.base, report.head
         );
+    }
 
-        output.push_str(&generate_pr_summary(&commits));
+    let mut output = if report.staged {
+        format!(
+            "# Pull Request Summary\n\n**Staged changes** on `{}`\n\n",
+            report.head
+        )
+    } else {
+        format!(
+            "# Pull Request Summary\n\n**From**: `{}` **To**: `{}`\n\n",
+            report.head, report.base
+        )
+    };
+
+    if let Some(narrative) = &report.narrative {
+        output.push_str("## Overview\n\n");
+        output.push_str(narrative.trim());
+        output.push_str("\n\n");
+    }
+
+    if !report.commits.is_empty() {
+        output.push_str(&generate_pr_summary(&report.commits));
+    }
+
+    output.push_str("## Files Changed\n\n");
+    output.push_str(&format!(
+        "**{} file(s)**, +{} / -{}\n\n",
+        report.files_changed, report.insertions, report.deletions
+    ));
+    for group in &report.groups {
+        output.push_str(&format!(
+            "### {} (+{} / -{})\n\n| File | Change | + | - |\n|------|--------|---|---|\n",
+            group.name, group.insertions, group.deletions
+        ));
+        for file in &group.files {
+            let name = match &file.old_path {
+                Some(old) => format!("`{}` → `{}`", old.display(), file.path.display()),
+                None => format!("`{}`", file.path.display()),
+            };
+            let (added, removed) = if file.binary {
+                ("bin".to_string(), "bin".to_string())
+            } else {
+                (file.insertions.to_string(), file.deletions.to_string())
+            };
+            output.push_str(&format!(
+                "| {} | {:?} | {} | {} |\n",
+                name, file.change_type, added, removed
+            ));
+        }
+        output.push('\n');
+    }
 
-        Ok(output)
+    output.push_str("## Risk Callouts\n\n");
+    if report.risks.is_empty() {
+        output.push_str("None detected.\n");
     }
+    for risk in &report.risks {
+        output.push_str(&format!(
+            "- ⚠️ **{}** `{}`: {}\n",
+            risk.kind.label(),
+            risk.path,
+            risk.detail
+        ));
+    }
+
+    output
 }
 
 impl Default for PrSummaryCommand {
@@ -133,12 +533,17 @@ impl CommandExecutor for PrSummaryCommand {
             )))
         })?;
 
-        let base = args.base_branch.unwrap_or_else(|| "main".to_string());
-        let head = args.head_branch.unwrap_or_else(|| "current".to_string());
+        let description = if args.staged {
+            "Generate PR summary from staged changes".to_string()
+        } else {
+            let base = args.base_branch.unwrap_or_else(|| "main".to_string());
+            let head = args.head_branch.unwrap_or_else(|| "current".to_string());
+            format!("Generate PR summary from {} to {}", head, base)
+        };
 
         Ok(CommandPreview {
             command_id: Uuid::new_v4(),
-            description: format!("Generate PR summary from {} to {}", head, base),
+            description,
             actions: vec![],
             requires_approval: false,
         })
@@ -157,17 +562,19 @@ impl CommandExecutor for PrSummaryCommand {
         })?;
 
         match self.generate_summary(&args, context).await {
-            Ok(output) => Ok(CommandResult {
+            Ok((output, report)) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: true,
                 output,
                 error: None,
+                data: Some(serde_json::to_value(&report)?),
             }),
             Err(e) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
+                data: None,
             }),
         }
     }
@@ -208,10 +615,227 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
         assert!(!result.success);
         assert!(result.error.is_some());
     }
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .current_dir(dir)
+            .args(args)
+            .env("GIT_AUTHOR_NAME", "Test")
+            .env("GIT_AUTHOR_EMAIL", "test@example.com")
+            .env("GIT_COMMITTER_NAME", "Test")
+            .env("GIT_COMMITTER_EMAIL", "test@example.com")
+            .output()
+            .unwrap();
+        assert!(status.status.success(), "git {:?} failed", args);
+    }
+
+    fn write(dir: &Path, path: &str, content: &str) {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    /// A committed workspace with a staged change touching dependencies, CI,
+    /// a migration and a deleted test
+    fn fixture_repo() -> tempfile::TempDir {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        git(dir, &["init", "-q", "-b", "main"]);
+
+        write(
+            dir,
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/core\"]\n\n[workspace.dependencies]\nserde = \"1.0\"\nrand = \"0.8\"\n",
+        );
+        write(
+            dir,
+            "crates/core/Cargo.toml",
+            "[package]\nname = \"core\"\nversion = \"0.1.0\"\n\n[dependencies]\nserde.workspace = true\n",
+        );
+        write(
+            dir,
+            "crates/core/src/lib.rs",
+            "pub fn one() -> u32 {\n    1\n}\n",
+        );
+        write(
+            dir,
+            "crates/core/tests/smoke.rs",
+            "#[test]\nfn smoke() {}\n",
+        );
+        write(dir, "README.md", "# Fixture\n");
+        git(dir, &["add", "-A"]);
+        git(dir, &["commit", "-q", "-m", "initial"]);
+
+        // Bump serde, drop rand, add tokio; the package version bump is not a
+        // dependency change
+        write(
+            dir,
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/core\"]\n\n[workspace.dependencies]\nserde = \"1.1\"\ntokio = \"1\"\n",
+        );
+        write(
+            dir,
+            "crates/core/Cargo.toml",
+            "[package]\nname = \"core\"\nversion = \"0.2.0\"\n\n[dependencies]\nserde.workspace = true\n",
+        );
+        write(
+            dir,
+            "crates/core/src/lib.rs",
+            "pub fn one() -> u32 {\n    1\n}\n\npub fn two() -> u32 {\n    2\n}\n",
+        );
+        write(dir, ".github/workflows/ci.yml", "on: push\njobs: {}\n");
+        write(dir, "migrations/001_init.sql", "CREATE TABLE t (id INT);\n");
+        std::fs::remove_file(dir.join("crates/core/tests/smoke.rs")).unwrap();
+        git(dir, &["add", "-A"]);
+
+        temp_dir
+    }
+
+    fn context(dir: &Path) -> CommandContext {
+        CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: Some(dir.to_string_lossy().to_string()),
+            sandbox_level: SandboxLevel::ReadOnly,
+            dry_run: false,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log: None,
+            timeout: None,
+            progress: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pr_summary_staged_json() {
+        let repo = fixture_repo();
+        let command = PrSummaryCommand::new();
+        let args = serde_json::json!({ "staged": true, "format": "json", "no_ai": true });
+
+        let result = command.execute(&args, &context(repo.path())).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        let report: PrReport = serde_json::from_str(&result.output).unwrap();
+
+        assert!(report.staged);
+        assert_eq!(report.files_changed, 6);
+        assert_eq!(report.insertions, 2 + 1 + 4 + 2 + 1);
+        assert_eq!(report.deletions, 2 + 1 + 2);
+
+        let groups: Vec<(&str, usize)> = report
+            .groups
+            .iter()
+            .map(|g| (g.name.as_str(), g.files.len()))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                ("(root)", 1),
+                (".github", 1),
+                ("crates/core", 3),
+                ("migrations", 1)
+            ]
+        );
+        let core = &report.groups[2];
+        assert_eq!((core.insertions, core.deletions), (5, 3));
+
+        let risks: Vec<(RiskKind, &str)> = report
+            .risks
+            .iter()
+            .map(|r| (r.kind, r.path.as_str()))
+            .collect();
+        assert_eq!(
+            risks,
+            vec![
+                (RiskKind::CiWorkflow, ".github/workflows/ci.yml"),
+                (RiskKind::DependencyChange, "Cargo.toml"),
+                (RiskKind::TestDeletion, "crates/core/tests/smoke.rs"),
+                (RiskKind::Migration, "migrations/001_init.sql"),
+            ]
+        );
+        assert_eq!(
+            report.risks[1].detail,
+            "Dependencies changed: -rand, ~serde, +tokio"
+        );
+        assert!(report.narrative.is_none());
+    }
+
+    struct FixedNarrator;
+
+    #[async_trait::async_trait]
+    impl PrNarrator for FixedNarrator {
+        async fn narrate(&self, report: &PrReport) -> Result<String> {
+            Ok(format!("Touches {} files.", report.files_changed))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pr_summary_markdown_and_narrative() {
+        let repo = fixture_repo();
+        let command = PrSummaryCommand::new().with_narrator(Arc::new(FixedNarrator));
+
+        let result = command
+            .execute(
+                &serde_json::json!({ "staged": true }),
+                &context(repo.path()),
+            )
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert!(result.output.contains("## Overview\n\nTouches 6 files."));
+        assert!(result.output.contains("**6 file(s)**, +10 / -5"));
+        assert!(result.output.contains("### crates/core (+5 / -3)"));
+        assert!(result
+            .output
+            .contains("| `crates/core/src/lib.rs` | Modified | 4 | 0 |"));
+        assert!(result
+            .output
+            .contains("- ⚠️ **Test deletion** `crates/core/tests/smoke.rs`"));
+
+        // --no-ai skips the narrator entirely
+        let result = command
+            .execute(
+                &serde_json::json!({ "staged": true, "no_ai": true }),
+                &context(repo.path()),
+            )
+            .await
+            .unwrap();
+        assert!(!result.output.contains("## Overview"));
+        assert!(result.output.contains("## Risk Callouts"));
+    }
+
+    #[tokio::test]
+    async fn test_pr_summary_branch_range() {
+        let repo = fixture_repo();
+        let dir = repo.path();
+        git(dir, &["checkout", "-q", "-b", "feature"]);
+        git(dir, &["commit", "-q", "-m", "feat: add two"]);
+
+        let result = PrSummaryCommand::new()
+            .execute(
+                &serde_json::json!({ "base_branch": "main", "format": "json" }),
+                &context(dir),
+            )
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        let report: PrReport = serde_json::from_str(&result.output).unwrap();
+        assert_eq!(report.head, "feature");
+        assert_eq!(report.commits.len(), 1);
+        assert_eq!(report.files_changed, 6);
+        assert_eq!(report.risks.len(), 4);
+    }
+
+    #[test]
+    fn test_changed_dependencies_tables() {
+        let diff = "@@ -1,6 +1,6 @@\n [dependencies.serde]\n-version = \"1.0\"\n+version = \"1.1\"\n [features]\n-default = []\n+default = [\"x\"]\n";
+        assert_eq!(changed_dependencies(diff), vec!["~serde"]);
+    }
 }
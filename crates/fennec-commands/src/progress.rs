@@ -0,0 +1,112 @@
+//! Progress reported by commands while they run, for callers that show a
+//! live view of long commands.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// Step of a command made of several, e.g. one run of a test watcher
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProgressStep {
+    pub name: String,
+    /// Position of the step, starting at 1
+    pub index: usize,
+    /// Number of steps, when known in advance
+    pub total: Option<usize>,
+}
+
+/// How far a running command has got
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CommandProgress {
+    /// Share of the work done, from 0 to 100
+    pub percent: Option<f32>,
+    pub message: Option<String>,
+    pub step: Option<ProgressStep>,
+}
+
+type ProgressSink = dyn Fn(&CommandProgress) + Send + Sync;
+
+/// Handle a command reports its progress through. Each report updates part
+/// of the progress and passes the whole of it on.
+#[derive(Clone)]
+pub struct ProgressReporter {
+    current: Arc<Mutex<CommandProgress>>,
+    sink: Arc<ProgressSink>,
+}
+
+impl fmt::Debug for ProgressReporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressReporter")
+            .field("current", &self.current)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProgressReporter {
+    /// Reporter passing every update to `sink`
+    pub fn new(sink: impl Fn(&CommandProgress) + Send + Sync + 'static) -> Self {
+        Self {
+            current: Arc::new(Mutex::new(CommandProgress::default())),
+            sink: Arc::new(sink),
+        }
+    }
+
+    /// Set the share of the work done, clamped to 0–100
+    pub fn percent(&self, percent: f32) {
+        self.update(|p| p.percent = Some(percent.clamp(0.0, 100.0)));
+    }
+
+    pub fn message(&self, message: impl Into<String>) {
+        let message = message.into();
+        self.update(|p| p.message = Some(message));
+    }
+
+    /// Start step `index` of `total`
+    pub fn step(&self, name: impl Into<String>, index: usize, total: Option<usize>) {
+        let step = ProgressStep {
+            name: name.into(),
+            index,
+            total,
+        };
+        self.update(|p| p.step = Some(step));
+    }
+
+    /// Progress reported so far
+    pub fn current(&self) -> CommandProgress {
+        self.current.lock().unwrap().clone()
+    }
+
+    fn update(&self, change: impl FnOnce(&mut CommandProgress)) {
+        let progress = {
+            let mut current = self.current.lock().unwrap();
+            change(&mut current);
+            current.clone()
+        };
+        (self.sink)(&progress);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reports_accumulate() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let reporter = ProgressReporter::new({
+            let seen = seen.clone();
+            move |p: &CommandProgress| seen.lock().unwrap().push(p.clone())
+        });
+
+        reporter.step("build", 1, Some(2));
+        reporter.percent(150.0);
+        reporter.message("compiling");
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 3);
+        let last = &seen[2];
+        assert_eq!(last.percent, Some(100.0));
+        assert_eq!(last.message.as_deref(), Some("compiling"));
+        assert_eq!(last.step.as_ref().map(|s| s.index), Some(1));
+    }
+}
@@ -133,6 +133,8 @@ impl QuickActionCommand {
                 sandbox_level_required: SandboxLevel::ReadOnly,
                 supports_preview: true,
                 supports_dry_run: false,
+                timeout: None,
+                alias_of: None,
             },
             actions: get_builtin_actions(),
         }
@@ -258,12 +260,14 @@ impl CommandExecutor for QuickActionCommand {
                 success: true,
                 output,
                 error: None,
+                data: None,
             }),
             Err(e) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
+                data: None,
             }),
         }
     }
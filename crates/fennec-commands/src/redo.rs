@@ -38,6 +38,8 @@ impl RedoCommand {
                 sandbox_level_required: SandboxLevel::WorkspaceWrite,
                 supports_preview: true,
                 supports_dry_run: true,
+                timeout: None,
+                alias_of: None,
             },
             action_log,
         }
@@ -72,138 +74,173 @@ impl RedoCommand {
             }
 
             // Apply the forward action (state_after)
-            match &action.state_after {
-                ActionState::FileCreated { path } => {
-                    // Remove the file (if redo is creating it, it means undo deleted it)
-                    if let ActionState::FileDeleted { content, .. } = &action.state_before {
-                        let full_path = if path.is_absolute() {
-                            path.clone()
-                        } else {
-                            workspace_path.join(path)
-                        };
-
-                        if let Some(parent) = full_path.parent() {
-                            fs::create_dir_all(parent).await.map_err(|e| {
-                                FennecError::Command(Box::new(std::io::Error::new(
-                                    e.kind(),
-                                    format!("Failed to create parent directories: {}", e),
-                                )))
-                            })?;
-                        }
-
-                        fs::write(&full_path, content).await.map_err(|e| {
-                            FennecError::Command(Box::new(std::io::Error::new(
-                                e.kind(),
-                                format!("Failed to create file: {}", e),
-                            )))
-                        })?;
+            match (&action.state_before, &action.state_after) {
+                (
+                    ActionState::Transaction { states: before },
+                    ActionState::Transaction { states: after },
+                ) => {
+                    for (before, after) in before.iter().zip(after) {
+                        self.apply_state(after, before, &action.description, workspace_path)
+                            .await?;
                     }
                 }
-                ActionState::FileModified { path, content, .. } => {
-                    // Restore the modified content
-                    let full_path = if path.is_absolute() {
-                        path.clone()
-                    } else {
-                        workspace_path.join(path)
-                    };
-
-                    fs::write(&full_path, content).await.map_err(|e| {
-                        FennecError::Command(Box::new(std::io::Error::new(
-                            e.kind(),
-                            format!("Failed to restore file content: {}", e),
-                        )))
-                    })?;
+                (before, after) => {
+                    self.apply_state(after, before, &action.description, workspace_path)
+                        .await?;
                 }
-                ActionState::FileDeleted { path, .. } => {
-                    // Delete the file
+            }
+
+            redone_actions.push(format!("Redid: {}", action.description));
+        }
+
+        if redone_actions.is_empty() {
+            Ok("No actions to redo".to_string())
+        } else {
+            Ok(redone_actions.join("\n"))
+        }
+    }
+
+    /// Re-apply the state recorded after an action, using `other` (the state
+    /// before it) for any content the replay needs
+    async fn apply_state(
+        &self,
+        state: &ActionState,
+        other: &ActionState,
+        description: &str,
+        workspace_path: &std::path::Path,
+    ) -> Result<()> {
+        match state {
+            ActionState::FileCreated { path } => {
+                // Remove the file (if redo is creating it, it means undo deleted it)
+                if let ActionState::FileDeleted { content, .. } = other {
                     let full_path = if path.is_absolute() {
                         path.clone()
                     } else {
                         workspace_path.join(path)
                     };
 
-                    if full_path.exists() {
-                        fs::remove_file(&full_path).await.map_err(|e| {
+                    if let Some(parent) = full_path.parent() {
+                        fs::create_dir_all(parent).await.map_err(|e| {
                             FennecError::Command(Box::new(std::io::Error::new(
                                 e.kind(),
-                                format!("Failed to remove file: {}", e),
+                                format!("Failed to create parent directories: {}", e),
                             )))
                         })?;
                     }
-                }
-                ActionState::FileMoved { from, to } => {
-                    // Apply the move
-                    let from_full = if from.is_absolute() {
-                        from.clone()
-                    } else {
-                        workspace_path.join(from)
-                    };
-                    let to_full = if to.is_absolute() {
-                        to.clone()
-                    } else {
-                        workspace_path.join(to)
-                    };
 
-                    if from_full.exists() {
-                        if let Some(parent) = to_full.parent() {
-                            fs::create_dir_all(parent).await.map_err(|e| {
-                                FennecError::Command(Box::new(std::io::Error::new(
-                                    e.kind(),
-                                    format!("Failed to create parent directories: {}", e),
-                                )))
-                            })?;
-                        }
-
-                        fs::rename(&from_full, &to_full).await.map_err(|e| {
-                            FennecError::Command(Box::new(std::io::Error::new(
-                                e.kind(),
-                                format!("Failed to rename: {}", e),
-                            )))
-                        })?;
-                    }
+                    fs::write(&full_path, content).await.map_err(|e| {
+                        FennecError::Command(Box::new(std::io::Error::new(
+                            e.kind(),
+                            format!("Failed to create file: {}", e),
+                        )))
+                    })?;
                 }
-                ActionState::DirectoryCreated { path } => {
-                    // Create the directory
-                    let full_path = if path.is_absolute() {
-                        path.clone()
-                    } else {
-                        workspace_path.join(path)
-                    };
-
-                    fs::create_dir_all(&full_path).await.map_err(|e| {
+            }
+            ActionState::FileModified { path, content, .. } => {
+                // Restore the modified content
+                let full_path = if path.is_absolute() {
+                    path.clone()
+                } else {
+                    workspace_path.join(path)
+                };
+
+                fs::write(&full_path, content).await.map_err(|e| {
+                    FennecError::Command(Box::new(std::io::Error::new(
+                        e.kind(),
+                        format!("Failed to restore file content: {}", e),
+                    )))
+                })?;
+            }
+            ActionState::FileDeleted { path, .. } => {
+                // Delete the file
+                let full_path = if path.is_absolute() {
+                    path.clone()
+                } else {
+                    workspace_path.join(path)
+                };
+
+                if full_path.exists() {
+                    fs::remove_file(&full_path).await.map_err(|e| {
                         FennecError::Command(Box::new(std::io::Error::new(
                             e.kind(),
-                            format!("Failed to create directory: {}", e),
+                            format!("Failed to remove file: {}", e),
                         )))
                     })?;
                 }
-                ActionState::DirectoryDeleted { path, .. } => {
-                    // Delete the directory
-                    let full_path = if path.is_absolute() {
-                        path.clone()
-                    } else {
-                        workspace_path.join(path)
-                    };
-
-                    if full_path.exists() {
-                        fs::remove_dir_all(&full_path).await.map_err(|e| {
+            }
+            ActionState::FileMoved { from, to } | ActionState::DirectoryMoved { from, to } => {
+                // Apply the move
+                let from_full = if from.is_absolute() {
+                    from.clone()
+                } else {
+                    workspace_path.join(from)
+                };
+                let to_full = if to.is_absolute() {
+                    to.clone()
+                } else {
+                    workspace_path.join(to)
+                };
+
+                if from_full.exists() {
+                    if let Some(parent) = to_full.parent() {
+                        fs::create_dir_all(parent).await.map_err(|e| {
                             FennecError::Command(Box::new(std::io::Error::new(
                                 e.kind(),
-                                format!("Failed to remove directory: {}", e),
+                                format!("Failed to create parent directories: {}", e),
                             )))
                         })?;
                     }
+
+                    fs::rename(&from_full, &to_full).await.map_err(|e| {
+                        FennecError::Command(Box::new(std::io::Error::new(
+                            e.kind(),
+                            format!("Failed to rename: {}", e),
+                        )))
+                    })?;
                 }
             }
-
-            redone_actions.push(format!("Redid: {}", action.description));
+            ActionState::DirectoryCreated { path } => {
+                // Create the directory
+                let full_path = if path.is_absolute() {
+                    path.clone()
+                } else {
+                    workspace_path.join(path)
+                };
+
+                fs::create_dir_all(&full_path).await.map_err(|e| {
+                    FennecError::Command(Box::new(std::io::Error::new(
+                        e.kind(),
+                        format!("Failed to create directory: {}", e),
+                    )))
+                })?;
+            }
+            ActionState::DirectoryDeleted { path, .. } => {
+                // Delete the directory
+                let full_path = if path.is_absolute() {
+                    path.clone()
+                } else {
+                    workspace_path.join(path)
+                };
+
+                if full_path.exists() {
+                    fs::remove_dir_all(&full_path).await.map_err(|e| {
+                        FennecError::Command(Box::new(std::io::Error::new(
+                            e.kind(),
+                            format!("Failed to remove directory: {}", e),
+                        )))
+                    })?;
+                }
+            }
+            ActionState::Transaction { .. } => {
+                return Err(FennecError::Command(Box::new(std::io::Error::other(format!(
+                    "Cannot redo '{}': nested transactions are not supported",
+                    description
+                ))))
+                .into());
+            }
         }
 
-        if redone_actions.is_empty() {
-            Ok("No actions to redo".to_string())
-        } else {
-            Ok(redone_actions.join("\n"))
-        }
+        Ok(())
     }
 }
 
@@ -268,12 +305,14 @@ impl CommandExecutor for RedoCommand {
                 success: true,
                 output,
                 error: None,
+                data: None,
             }),
             Err(e) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
+                data: None,
             }),
         }
     }
@@ -334,6 +373,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -357,6 +398,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -393,6 +436,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -3,14 +3,25 @@ use async_trait::async_trait;
 use fennec_core::{
     command::{Capability, CommandPreview, CommandResult},
     error::FennecError,
+    provider::ToolDefinition,
 };
 use fennec_security::SandboxLevel;
+use fennec_telemetry::{metrics, spans, RequestContext};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::{mpsc, RwLock};
+use tracing::warn;
 use uuid::Uuid;
 
+use crate::error::CommandError;
+use crate::middleware::{CommandMiddleware, MiddlewareDecision};
+
+/// How long a timed-out command gets to wind down after its token is
+/// cancelled before its partial result is abandoned
+const TIMEOUT_GRACE_PERIOD: Duration = Duration::from_secs(2);
+
 /// Descriptor for a command containing metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommandDescriptor {
@@ -22,6 +33,12 @@ pub struct CommandDescriptor {
     pub sandbox_level_required: SandboxLevel,
     pub supports_preview: bool,
     pub supports_dry_run: bool,
+    /// Maximum run time; overrides the registry default when set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<Duration>,
+    /// Set in listings when `name` is a deprecated alias of this command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias_of: Option<String>,
 }
 
 /// Context passed to commands during execution
@@ -35,6 +52,10 @@ pub struct CommandContext {
     pub preview_only: bool,
     pub cancellation_token: tokio_util::sync::CancellationToken,
     pub action_log: Option<std::sync::Arc<crate::action_log::ActionLog>>,
+    /// Per-invocation timeout, taking precedence over the descriptor's
+    pub timeout: Option<Duration>,
+    /// Where a long command reports how far it has got
+    pub progress: Option<crate::progress::ProgressReporter>,
 }
 
 /// Result of command execution including metadata
@@ -46,11 +67,128 @@ pub struct CommandExecutionResult {
     pub success: bool,
     pub output: String,
     pub error: Option<String>,
+    /// Structured payload returned by the command, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
     pub preview: Option<CommandPreview>,
     pub execution_time_ms: u64,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Warning given when the command was invoked through an alias
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecation: Option<String>,
+}
+
+/// Incremental output emitted while a command is still running
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CommandOutputEvent {
+    Stdout {
+        line: String,
+    },
+    Stderr {
+        line: String,
+    },
+    /// Output exceeded the configured cap; further lines are dropped
+    Truncated {
+        limit_bytes: usize,
+    },
+}
+
+/// A capability the sandbox refused, and the level that would allow it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeniedCapability {
+    pub capability: Capability,
+    pub required_level: SandboxLevel,
+}
+
+/// Structured refusal returned when a command's declared capabilities exceed
+/// the sandbox level of the context it was dispatched in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilityDenial {
+    pub command: String,
+    pub sandbox_level: SandboxLevel,
+    /// Whether only the capabilities needed for a preview were checked
+    pub preview_only: bool,
+    pub denied: Vec<DeniedCapability>,
 }
 
+impl std::fmt::Display for CapabilityDenial {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let denied: Vec<String> = self
+            .denied
+            .iter()
+            .map(|d| format!("{:?} (requires {:?})", d.capability, d.required_level))
+            .collect();
+        write!(
+            f,
+            "Command '{}' needs {} but only {:?} is available",
+            self.command,
+            denied.join(", "),
+            self.sandbox_level
+        )
+    }
+}
+
+/// Lowest sandbox level that grants `capability`
+///
+/// Shell execution is granted from workspace-write up because `run` applies
+/// its own command allowlist on top of the sandbox.
+pub fn minimum_sandbox_level(capability: &Capability) -> SandboxLevel {
+    match capability {
+        Capability::ReadFile => SandboxLevel::ReadOnly,
+        Capability::WriteFile | Capability::ExecuteShell => SandboxLevel::WorkspaceWrite,
+        Capability::NetworkAccess => SandboxLevel::FullAccess,
+    }
+}
+
+/// Whether running at `available` satisfies a requirement of `required`
+pub fn sandbox_allows(available: &SandboxLevel, required: &SandboxLevel) -> bool {
+    matches!(
+        (required, available),
+        (SandboxLevel::ReadOnly, _)
+            | (
+                SandboxLevel::WorkspaceWrite,
+                SandboxLevel::WorkspaceWrite | SandboxLevel::FullAccess,
+            )
+            | (SandboxLevel::FullAccess, SandboxLevel::FullAccess)
+    )
+}
+
+/// Check `capabilities` against the sandbox level of `context`, reporting
+/// every capability it does not grant
+pub fn check_capabilities(
+    command: &str,
+    capabilities: &[Capability],
+    context: &CommandContext,
+) -> std::result::Result<(), CapabilityDenial> {
+    let mut denied: Vec<DeniedCapability> = Vec::new();
+    for capability in capabilities {
+        let required_level = minimum_sandbox_level(capability);
+        if !sandbox_allows(&context.sandbox_level, &required_level)
+            && !denied.iter().any(|d| &d.capability == capability)
+        {
+            denied.push(DeniedCapability {
+                capability: capability.clone(),
+                required_level,
+            });
+        }
+    }
+
+    if denied.is_empty() {
+        Ok(())
+    } else {
+        Err(CapabilityDenial {
+            command: command.to_string(),
+            sandbox_level: context.sandbox_level.clone(),
+            preview_only: context.preview_only,
+            denied,
+        })
+    }
+}
+
+/// Sender half used by streaming commands to publish output events
+pub type OutputSender = mpsc::UnboundedSender<CommandOutputEvent>;
+
 /// Trait that all commands must implement
 #[async_trait]
 pub trait CommandExecutor: Send + Sync {
@@ -71,20 +209,71 @@ pub trait CommandExecutor: Send + Sync {
         context: &CommandContext,
     ) -> Result<CommandResult>;
 
+    /// Execute the command, publishing output to `events` as it is produced.
+    ///
+    /// Commands without incremental output fall back to [`Self::execute`].
+    async fn execute_streaming(
+        &self,
+        args: &serde_json::Value,
+        context: &CommandContext,
+        events: OutputSender,
+    ) -> Result<CommandResult> {
+        let _ = events;
+        self.execute(args, context).await
+    }
+
     /// Validate command arguments
     fn validate_args(&self, args: &serde_json::Value) -> Result<()>;
 
+    /// Capabilities the command needs to run with `args`
+    ///
+    /// Defaults to the descriptor's declaration; commands whose needs depend
+    /// on their arguments (e.g. an optional output file) override this.
+    fn required_capabilities(&self, args: &serde_json::Value) -> Vec<Capability> {
+        let _ = args;
+        self.descriptor().capabilities_required.clone()
+    }
+
+    /// Capabilities still needed when only previewing with `args`
+    ///
+    /// Previews never write, spawn processes or reach the network, so by
+    /// default only read access is kept.
+    fn preview_capabilities(&self, args: &serde_json::Value) -> Vec<Capability> {
+        self.required_capabilities(args)
+            .into_iter()
+            .filter(|capability| *capability == Capability::ReadFile)
+            .collect()
+    }
+
+    /// JSON schema of the arguments, used when the command is offered to a
+    /// model as a tool
+    ///
+    /// Defaults to an object accepting any properties; `validate_args`
+    /// still checks what the model sends.
+    fn args_schema(&self) -> serde_json::Value {
+        serde_json::json!({"type": "object", "additionalProperties": true})
+    }
+
     /// Check if this command can run with the given sandbox level
     fn can_run_in_sandbox(&self, level: &SandboxLevel) -> bool {
-        match (&self.descriptor().sandbox_level_required, level) {
-            (SandboxLevel::ReadOnly, _) => true,
-            (
-                SandboxLevel::WorkspaceWrite,
-                SandboxLevel::WorkspaceWrite | SandboxLevel::FullAccess,
-            ) => true,
-            (SandboxLevel::FullAccess, SandboxLevel::FullAccess) => true,
-            _ => false,
-        }
+        let descriptor = self.descriptor();
+        sandbox_allows(level, &descriptor.sandbox_level_required)
+            && descriptor
+                .capabilities_required
+                .iter()
+                .all(|capability| sandbox_allows(level, &minimum_sandbox_level(capability)))
+    }
+}
+
+async fn invoke(
+    command: &dyn CommandExecutor,
+    args: &serde_json::Value,
+    context: &CommandContext,
+    events: Option<OutputSender>,
+) -> Result<CommandResult> {
+    match events {
+        Some(events) => command.execute_streaming(args, context, events).await,
+        None => command.execute(args, context).await,
     }
 }
 
@@ -94,6 +283,10 @@ pub struct CommandRegistry {
     commands: Arc<RwLock<HashMap<String, Arc<dyn CommandExecutor>>>>,
     builtin_commands: Arc<RwLock<HashMap<String, Arc<dyn CommandExecutor>>>>,
     custom_commands: Arc<RwLock<HashMap<String, Arc<dyn CommandExecutor>>>>,
+    default_timeout: Option<Duration>,
+    middleware: Arc<RwLock<Vec<Arc<dyn CommandMiddleware>>>>,
+    /// Deprecated names, mapped to the commands they stand for
+    aliases: Arc<RwLock<HashMap<String, String>>>,
 }
 
 impl CommandRegistry {
@@ -101,9 +294,20 @@ impl CommandRegistry {
         Self::default()
     }
 
+    /// Set the timeout applied to commands that don't declare their own
+    pub fn with_default_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.default_timeout = timeout;
+        self
+    }
+
+    pub fn default_timeout(&self) -> Option<Duration> {
+        self.default_timeout
+    }
+
     /// Register a built-in command
     pub async fn register_builtin(&self, executor: Arc<dyn CommandExecutor>) -> Result<()> {
         let name = executor.descriptor().name.clone();
+        self.check_not_alias(&name).await?;
 
         {
             let mut builtin = self.builtin_commands.write().await;
@@ -121,6 +325,7 @@ impl CommandRegistry {
     /// Register a custom command (can override built-ins)
     pub async fn register_custom(&self, executor: Arc<dyn CommandExecutor>) -> Result<()> {
         let name = executor.descriptor().name.clone();
+        self.check_not_alias(&name).await?;
 
         {
             let mut custom = self.custom_commands.write().await;
@@ -135,19 +340,85 @@ impl CommandRegistry {
         Ok(())
     }
 
-    /// Get a command by name
+    /// Keep the old name `alias` working for the command `target`. Running
+    /// a command through an alias warns that the alias is deprecated.
+    pub async fn register_alias(&self, alias: &str, target: &str) -> Result<()> {
+        let commands = self.commands.read().await;
+        let mut aliases = self.aliases.write().await;
+        let conflict = if commands.contains_key(alias) {
+            Some(format!("'{}' is already a command", alias))
+        } else if let Some(existing) = aliases.get(alias).filter(|t| *t != target) {
+            Some(format!("'{}' is already an alias of '{}'", alias, existing))
+        } else if !commands.contains_key(target) {
+            Some(format!("command '{}' not found", target))
+        } else {
+            None
+        };
+        if let Some(conflict) = conflict {
+            return Err(FennecError::Command(Box::new(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("Cannot alias '{}' to '{}': {}", alias, target, conflict),
+            )))
+            .into());
+        }
+
+        aliases.insert(alias.to_string(), target.to_string());
+        Ok(())
+    }
+
+    async fn check_not_alias(&self, name: &str) -> Result<()> {
+        match self.aliases.read().await.get(name) {
+            Some(target) => Err(FennecError::Command(Box::new(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("Cannot register '{}': it is an alias of '{}'", name, target),
+            )))
+            .into()),
+            None => Ok(()),
+        }
+    }
+
+    /// The command `name` stands for, and the deprecation warning to give
+    /// when it is an alias
+    async fn resolve_name(&self, name: &str) -> (String, Option<String>) {
+        match self.aliases.read().await.get(name) {
+            Some(target) => (
+                target.clone(),
+                Some(format!(
+                    "'{}' is a deprecated alias of '{}' and may be removed; use '{}' instead",
+                    name, target, target
+                )),
+            ),
+            None => (name.to_string(), None),
+        }
+    }
+
+    /// Get a command by name or alias
     pub async fn get_command(&self, name: &str) -> Option<Arc<dyn CommandExecutor>> {
+        let (name, _) = self.resolve_name(name).await;
         let commands = self.commands.read().await;
-        commands.get(name).cloned()
+        commands.get(&name).cloned()
     }
 
-    /// List all available commands
+    /// List all available commands, followed by their aliases sorted by
+    /// name, each with `alias_of` set
     pub async fn list_commands(&self) -> Vec<CommandDescriptor> {
         let commands = self.commands.read().await;
-        commands
+        let mut descriptors: Vec<CommandDescriptor> = commands
             .values()
             .map(|cmd| cmd.descriptor().clone())
-            .collect()
+            .collect();
+
+        let aliases = self.aliases.read().await;
+        let mut aliases: Vec<(&String, &String)> = aliases.iter().collect();
+        aliases.sort();
+        descriptors.extend(aliases.into_iter().filter_map(|(alias, target)| {
+            commands.get(target).map(|cmd| CommandDescriptor {
+                name: alias.clone(),
+                alias_of: Some(target.clone()),
+                ..cmd.descriptor().clone()
+            })
+        }));
+        descriptors
     }
 
     /// List commands by capability
@@ -173,12 +444,88 @@ impl CommandRegistry {
             .collect()
     }
 
+    /// Describe the commands that can run at `level` as tools a model can
+    /// call; a tool call's name is the command to execute
+    pub async fn tool_definitions(&self, level: &SandboxLevel) -> Vec<ToolDefinition> {
+        let commands = self.commands.read().await;
+        let mut tools: Vec<ToolDefinition> = commands
+            .values()
+            .filter(|cmd| cmd.can_run_in_sandbox(level))
+            .map(|cmd| {
+                let descriptor = cmd.descriptor();
+                ToolDefinition::new(
+                    descriptor.name.clone(),
+                    descriptor.description.clone(),
+                    cmd.args_schema(),
+                )
+            })
+            .collect();
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+        tools
+    }
+
     /// Execute a command with preview and validation
     pub async fn execute_command(
         &self,
         name: &str,
         args: &serde_json::Value,
         context: &CommandContext,
+    ) -> Result<CommandExecutionResult> {
+        self.run_command(name, args, context, None).await
+    }
+
+    /// Execute a command, forwarding incremental output to `events`
+    pub async fn execute_command_streaming(
+        &self,
+        name: &str,
+        args: &serde_json::Value,
+        context: &CommandContext,
+        events: OutputSender,
+    ) -> Result<CommandExecutionResult> {
+        self.run_command(name, args, context, Some(events)).await
+    }
+
+    async fn run_command(
+        &self,
+        name: &str,
+        args: &serde_json::Value,
+        context: &CommandContext,
+        events: Option<OutputSender>,
+    ) -> Result<CommandExecutionResult> {
+        let (name, deprecation) = self.resolve_name(name).await;
+        let name = name.as_str();
+        if let Some(deprecation) = &deprecation {
+            warn!("{}", deprecation);
+        }
+
+        let operation = format!("command:{}", name);
+        let request_context = match spans::current_context() {
+            Some(parent) => parent.child(operation),
+            None => RequestContext::new(operation),
+        }
+        .with_session_id(context.session_id.to_string());
+        // Recorded within the command's context, so a slow command warning
+        // carries its correlation id
+        spans::run_command_in_context(name, request_context, async {
+            let mut result = self.dispatch(name, args, context, events).await?;
+            result.deprecation = deprecation;
+            // Unknown commands are left out so made-up names don't become labels
+            metrics::record_command_execution(
+                name,
+                result.success,
+                Duration::from_millis(result.execution_time_ms),
+            );
+            Ok(result)
+        })
+        .await
+    }
+
+    async fn dispatch(
+        &self,
+        name: &str,
+        args: &serde_json::Value,
+        context: &CommandContext,
+        events: Option<OutputSender>,
     ) -> Result<CommandExecutionResult> {
         let start_time = std::time::Instant::now();
         let execution_id = Uuid::new_v4();
@@ -197,13 +544,34 @@ impl CommandRegistry {
             success: false,
             output: String::new(),
             error: None,
+            data: None,
             preview: None,
             execution_time_ms: 0,
             created_at: chrono::Utc::now(),
+            deprecation: None,
         };
 
+        // Check declared capabilities before dispatch; a preview only needs
+        // what it takes to describe the change, not to apply it
+        let capabilities = if context.preview_only {
+            command.preview_capabilities(args)
+        } else {
+            command.required_capabilities(args)
+        };
+        if let Err(denial) = check_capabilities(name, &capabilities, context) {
+            result.error = Some(denial.to_string());
+            result.data = serde_json::to_value(&denial).ok();
+            result.execution_time_ms = start_time.elapsed().as_millis() as u64;
+            return Ok(result);
+        }
+
         // Validate sandbox permissions
-        if !command.can_run_in_sandbox(&context.sandbox_level) {
+        if !context.preview_only
+            && !sandbox_allows(
+                &context.sandbox_level,
+                &command.descriptor().sandbox_level_required,
+            )
+        {
             result.error = Some(format!(
                 "Command '{}' requires {:?} but only {:?} is available",
                 name,
@@ -243,22 +611,118 @@ impl CommandRegistry {
 
         // Execute the command if not preview-only
         if !context.preview_only {
-            match command.execute(args, context).await {
+            let middleware = self.middleware.read().await.clone();
+            let descriptor = command.descriptor();
+
+            for hook in &middleware {
+                let refusal = match hook.before(context, descriptor, args).await {
+                    Ok(MiddlewareDecision::Continue) => None,
+                    Ok(MiddlewareDecision::Veto(reason)) => Some(format!(
+                        "Command '{}' vetoed by {} middleware: {}",
+                        name,
+                        hook.name(),
+                        reason
+                    )),
+                    Err(e) => Some(format!("{} middleware failed: {}", hook.name(), e)),
+                };
+                if let Some(refusal) = refusal {
+                    result.error = Some(refusal);
+                    result.execution_time_ms = start_time.elapsed().as_millis() as u64;
+                    return Ok(result);
+                }
+            }
+
+            match self
+                .execute_with_timeout(name, command.as_ref(), args, context, events)
+                .await
+            {
                 Ok(command_result) => {
                     result.success = command_result.success;
                     result.output = command_result.output;
                     result.error = command_result.error;
+                    result.data = command_result.data;
                 }
                 Err(e) => {
                     result.error = Some(e.to_string());
                 }
             }
+
+            result.execution_time_ms = start_time.elapsed().as_millis() as u64;
+            for hook in &middleware {
+                if let Err(e) = hook.after(context, descriptor, args, &result).await {
+                    warn!("{} middleware failed after '{}': {}", hook.name(), name, e);
+                }
+            }
         }
 
         result.execution_time_ms = start_time.elapsed().as_millis() as u64;
         Ok(result)
     }
 
+    /// Add middleware that runs around every command, after any already
+    /// registered
+    pub async fn register_middleware(&self, middleware: Box<dyn CommandMiddleware>) {
+        self.middleware.write().await.push(Arc::from(middleware));
+    }
+
+    /// Run a command under its effective timeout: the context override, then
+    /// the descriptor's, then the registry default
+    async fn execute_with_timeout(
+        &self,
+        name: &str,
+        command: &dyn CommandExecutor,
+        args: &serde_json::Value,
+        context: &CommandContext,
+        events: Option<OutputSender>,
+    ) -> Result<CommandResult> {
+        let Some(limit) = context
+            .timeout
+            .or(command.descriptor().timeout)
+            .or(self.default_timeout)
+        else {
+            return invoke(command, args, context, events).await;
+        };
+
+        // Cancel a child token so the caller's token stays usable
+        let context = CommandContext {
+            cancellation_token: context.cancellation_token.child_token(),
+            ..context.clone()
+        };
+        let execution = invoke(command, args, &context, events);
+        tokio::pin!(execution);
+
+        match tokio::time::timeout(limit, &mut execution).await {
+            Ok(result) => result,
+            Err(_) => {
+                context.cancellation_token.cancel();
+                let partial = tokio::time::timeout(TIMEOUT_GRACE_PERIOD, &mut execution)
+                    .await
+                    .ok()
+                    .and_then(Result::ok);
+                warn!("Command '{}' timed out after {:?}", name, limit);
+
+                let error = CommandError::Timeout {
+                    timeout_ms: limit.as_millis() as u64,
+                    command: name.to_string(),
+                };
+                Ok(match partial {
+                    Some(partial) => CommandResult {
+                        success: false,
+                        error: Some(error.to_string()),
+                        ..partial
+                    },
+                    None => CommandResult {
+                        command_id: Uuid::new_v4(),
+                        success: false,
+                        output: String::new(),
+                        error: Some(error.to_string()),
+                        data: None,
+                    },
+                })
+            }
+        }
+    }
+
     /// Remove a command from the registry
     pub async fn unregister_command(&self, name: &str) -> Result<()> {
         {
@@ -333,6 +797,7 @@ mod tests {
                 success: true,
                 output: "Test command executed".to_string(),
                 error: None,
+                data: None,
             })
         }
 
@@ -355,6 +820,8 @@ mod tests {
                 sandbox_level_required: SandboxLevel::ReadOnly,
                 supports_preview: true,
                 supports_dry_run: true,
+                timeout: None,
+                alias_of: None,
             },
         });
 
@@ -373,6 +840,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = registry
@@ -382,5 +851,455 @@ mod tests {
 
         assert!(result.success);
         assert_eq!(result.command_name, "test");
+        assert!(result.deprecation.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_execution_through_alias_warns() {
+        let registry = CommandRegistry::new();
+        registry
+            .register_builtin(capability_command(vec![Capability::ReadFile]))
+            .await
+            .unwrap();
+        registry.register_alias("download", "fetch").await.unwrap();
+        // Registering the same alias again is harmless
+        registry.register_alias("download", "fetch").await.unwrap();
+
+        let result = registry
+            .execute_command(
+                "download",
+                &serde_json::json!({}),
+                &sandbox_context(SandboxLevel::ReadOnly, false),
+            )
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, "Test command executed");
+        assert_eq!(result.command_name, "fetch");
+        let deprecation = result.deprecation.unwrap();
+        assert!(
+            deprecation.contains("'download' is a deprecated alias of 'fetch'"),
+            "{}",
+            deprecation
+        );
+        assert_eq!(
+            registry
+                .get_command("download")
+                .await
+                .unwrap()
+                .descriptor()
+                .name,
+            "fetch"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_conflicting_aliases_are_rejected() {
+        let registry = CommandRegistry::new();
+        registry
+            .register_builtin(capability_command(vec![Capability::ReadFile]))
+            .await
+            .unwrap();
+        registry
+            .register_builtin(sleep_command(None).0)
+            .await
+            .unwrap();
+        registry.register_alias("download", "fetch").await.unwrap();
+
+        for (alias, target, reason) in [
+            ("sleep", "fetch", "'sleep' is already a command"),
+            (
+                "download",
+                "sleep",
+                "'download' is already an alias of 'fetch'",
+            ),
+            ("get", "missing", "command 'missing' not found"),
+            ("get", "download", "command 'download' not found"),
+        ] {
+            let error = registry.register_alias(alias, target).await.unwrap_err();
+            assert!(error.to_string().contains(reason), "{}", error);
+        }
+
+        // Nor can a command take an alias's name
+        let mut descriptor = capability_command(vec![]).descriptor().clone();
+        descriptor.name = "download".to_string();
+        let error = registry
+            .register_custom(Arc::new(TestCommand { descriptor }))
+            .await
+            .unwrap_err();
+        assert!(
+            error.to_string().contains("it is an alias of 'fetch'"),
+            "{}",
+            error
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_commands_marks_aliases() {
+        let registry = CommandRegistry::new();
+        registry
+            .register_builtin(capability_command(vec![Capability::ReadFile]))
+            .await
+            .unwrap();
+        registry.register_alias("get", "fetch").await.unwrap();
+        registry.register_alias("download", "fetch").await.unwrap();
+
+        let listed: Vec<(String, Option<String>)> = registry
+            .list_commands()
+            .await
+            .into_iter()
+            .map(|descriptor| (descriptor.name, descriptor.alias_of))
+            .collect();
+        assert_eq!(
+            listed,
+            [
+                ("fetch".to_string(), None),
+                ("download".to_string(), Some("fetch".to_string())),
+                ("get".to_string(), Some("fetch".to_string())),
+            ]
+        );
+
+        // Tools are offered under their current names only
+        let tools = registry.tool_definitions(&SandboxLevel::ReadOnly).await;
+        assert_eq!(tools.len(), 1);
+    }
+
+    /// Sleeps until cancelled, reporting what it "printed" before stopping
+    struct SleepCommand {
+        descriptor: CommandDescriptor,
+        observed_cancel: Arc<std::sync::atomic::AtomicBool>,
+    }
+
+    #[async_trait]
+    impl CommandExecutor for SleepCommand {
+        fn descriptor(&self) -> &CommandDescriptor {
+            &self.descriptor
+        }
+
+        async fn preview(
+            &self,
+            _args: &serde_json::Value,
+            _context: &CommandContext,
+        ) -> Result<CommandPreview> {
+            unreachable!()
+        }
+
+        async fn execute(
+            &self,
+            _args: &serde_json::Value,
+            context: &CommandContext,
+        ) -> Result<CommandResult> {
+            tokio::select! {
+                _ = context.cancellation_token.cancelled() => {
+                    self.observed_cancel
+                        .store(true, std::sync::atomic::Ordering::SeqCst);
+                }
+                _ = tokio::time::sleep(Duration::from_secs(30)) => {}
+            }
+            Ok(CommandResult {
+                command_id: Uuid::new_v4(),
+                success: true,
+                output: "partial output".to_string(),
+                error: None,
+                data: None,
+            })
+        }
+
+        fn validate_args(&self, _args: &serde_json::Value) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn sleep_command(
+        timeout: Option<Duration>,
+    ) -> (Arc<SleepCommand>, Arc<std::sync::atomic::AtomicBool>) {
+        let observed_cancel = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let command = Arc::new(SleepCommand {
+            descriptor: CommandDescriptor {
+                name: "sleep".to_string(),
+                description: "Sleeps until cancelled".to_string(),
+                version: "1.0.0".to_string(),
+                author: None,
+                capabilities_required: vec![],
+                sandbox_level_required: SandboxLevel::ReadOnly,
+                supports_preview: false,
+                supports_dry_run: false,
+                timeout,
+                alias_of: None,
+            },
+            observed_cancel: observed_cancel.clone(),
+        });
+        (command, observed_cancel)
+    }
+
+    fn timeout_context(timeout: Option<Duration>) -> CommandContext {
+        CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: None,
+            sandbox_level: SandboxLevel::ReadOnly,
+            dry_run: false,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log: None,
+            timeout,
+            progress: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_descriptor_timeout_cancels_command() {
+        let registry = CommandRegistry::new();
+        let (command, observed_cancel) = sleep_command(Some(Duration::from_millis(100)));
+        registry.register_builtin(command).await.unwrap();
+
+        let context = timeout_context(None);
+        let started = std::time::Instant::now();
+        let result = registry
+            .execute_command("sleep", &serde_json::json!({}), &context)
+            .await
+            .unwrap();
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(!result.success);
+        assert_eq!(
+            result.error.as_deref(),
+            Some("Command timed out after 100ms")
+        );
+        assert_eq!(result.output, "partial output");
+        assert!(observed_cancel.load(std::sync::atomic::Ordering::SeqCst));
+        // Only the command's child token is cancelled, not the caller's
+        assert!(!context.cancellation_token.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_timeout_precedence() {
+        // The context override beats both the descriptor and the registry default
+        let registry = CommandRegistry::new().with_default_timeout(Some(Duration::from_secs(60)));
+        let (command, observed_cancel) = sleep_command(Some(Duration::from_secs(60)));
+        registry.register_builtin(command).await.unwrap();
+
+        let result = registry
+            .execute_command(
+                "sleep",
+                &serde_json::json!({}),
+                &timeout_context(Some(Duration::from_millis(50))),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            result.error.as_deref(),
+            Some("Command timed out after 50ms")
+        );
+        assert!(observed_cancel.load(std::sync::atomic::Ordering::SeqCst));
+
+        // Without descriptor or context timeouts the registry default applies
+        let registry = CommandRegistry::new().with_default_timeout(Some(Duration::from_millis(50)));
+        let (command, _) = sleep_command(None);
+        registry.register_builtin(command).await.unwrap();
+
+        let result = registry
+            .execute_command("sleep", &serde_json::json!({}), &timeout_context(None))
+            .await
+            .unwrap();
+        assert_eq!(
+            result.error.as_deref(),
+            Some("Command timed out after 50ms")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tool_definitions_follow_sandbox_level() {
+        let registry = CommandRegistry::new();
+        registry
+            .register_builtin(capability_command(vec![
+                Capability::ReadFile,
+                Capability::WriteFile,
+            ]))
+            .await
+            .unwrap();
+        registry
+            .register_builtin(Arc::new(TestCommand {
+                descriptor: CommandDescriptor {
+                    name: "read".to_string(),
+                    description: "Reads a file".to_string(),
+                    ..capability_command(vec![Capability::ReadFile])
+                        .descriptor
+                        .clone()
+                },
+            }))
+            .await
+            .unwrap();
+
+        let tools = registry.tool_definitions(&SandboxLevel::ReadOnly).await;
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].name, "read");
+        assert_eq!(tools[0].description, "Reads a file");
+        assert_eq!(tools[0].parameters["type"], "object");
+
+        let tools = registry
+            .tool_definitions(&SandboxLevel::WorkspaceWrite)
+            .await;
+        let names: Vec<&str> = tools.iter().map(|tool| tool.name.as_str()).collect();
+        assert_eq!(names, ["fetch", "read"]);
+    }
+
+    fn capability_command(capabilities: Vec<Capability>) -> Arc<TestCommand> {
+        Arc::new(TestCommand {
+            descriptor: CommandDescriptor {
+                name: "fetch".to_string(),
+                description: "Downloads a file into the workspace".to_string(),
+                version: "1.0.0".to_string(),
+                author: None,
+                capabilities_required: capabilities,
+                sandbox_level_required: SandboxLevel::ReadOnly,
+                supports_preview: true,
+                supports_dry_run: false,
+                timeout: None,
+                alias_of: None,
+            },
+        })
+    }
+
+    fn sandbox_context(sandbox_level: SandboxLevel, preview_only: bool) -> CommandContext {
+        CommandContext {
+            sandbox_level,
+            preview_only,
+            ..timeout_context(None)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_capability_precheck_per_sandbox_level() {
+        let registry = CommandRegistry::new();
+        registry
+            .register_builtin(capability_command(vec![
+                Capability::ReadFile,
+                Capability::WriteFile,
+                Capability::NetworkAccess,
+            ]))
+            .await
+            .unwrap();
+        let args = serde_json::json!({});
+
+        let result = registry
+            .execute_command(
+                "fetch",
+                &args,
+                &sandbox_context(SandboxLevel::ReadOnly, false),
+            )
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert_eq!(
+            result.error.as_deref(),
+            Some(
+                "Command 'fetch' needs WriteFile (requires WorkspaceWrite), NetworkAccess (requires FullAccess) but only ReadOnly is available"
+            )
+        );
+        let denial: CapabilityDenial = serde_json::from_value(result.data.unwrap()).unwrap();
+        assert_eq!(denial.sandbox_level, SandboxLevel::ReadOnly);
+        assert!(!denial.preview_only);
+        assert_eq!(
+            denial.denied,
+            vec![
+                DeniedCapability {
+                    capability: Capability::WriteFile,
+                    required_level: SandboxLevel::WorkspaceWrite,
+                },
+                DeniedCapability {
+                    capability: Capability::NetworkAccess,
+                    required_level: SandboxLevel::FullAccess,
+                },
+            ]
+        );
+
+        let result = registry
+            .execute_command(
+                "fetch",
+                &args,
+                &sandbox_context(SandboxLevel::WorkspaceWrite, false),
+            )
+            .await
+            .unwrap();
+        let denial: CapabilityDenial = serde_json::from_value(result.data.unwrap()).unwrap();
+        assert_eq!(denial.denied.len(), 1);
+        assert_eq!(denial.denied[0].capability, Capability::NetworkAccess);
+
+        let result = registry
+            .execute_command(
+                "fetch",
+                &args,
+                &sandbox_context(SandboxLevel::FullAccess, false),
+            )
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+
+        assert!(registry
+            .list_commands_for_sandbox(&SandboxLevel::WorkspaceWrite)
+            .await
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_preview_only_skips_apply_capabilities() {
+        let registry = CommandRegistry::new();
+        registry
+            .register_builtin(capability_command(vec![
+                Capability::ReadFile,
+                Capability::WriteFile,
+                Capability::ExecuteShell,
+            ]))
+            .await
+            .unwrap();
+
+        let result = registry
+            .execute_command(
+                "fetch",
+                &serde_json::json!({}),
+                &sandbox_context(SandboxLevel::ReadOnly, true),
+            )
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.preview.is_some());
+
+        // Reading is still required to describe the change
+        let context = sandbox_context(SandboxLevel::ReadOnly, true);
+        let denial =
+            check_capabilities("fetch", &[Capability::NetworkAccess], &context).unwrap_err();
+        assert!(denial.preview_only);
+        assert!(check_capabilities("fetch", &[Capability::ReadFile], &context).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_builtin_declarations_match_sandbox_levels() {
+        let registry = crate::create_command_registry().await.unwrap();
+        let read_only: Vec<String> = registry
+            .list_commands_for_sandbox(&SandboxLevel::ReadOnly)
+            .await
+            .into_iter()
+            .map(|descriptor| descriptor.name)
+            .collect();
+        for name in ["edit", "delete", "run", "pr-summary", "commit-template"] {
+            assert!(!read_only.contains(&name.to_string()), "{}", name);
+        }
+
+        // Every built-in's declared capabilities are granted at the level it
+        // says it needs
+        for descriptor in registry.list_commands().await {
+            let context = sandbox_context(descriptor.sandbox_level_required.clone(), false);
+            assert!(
+                check_capabilities(
+                    &descriptor.name,
+                    &descriptor.capabilities_required,
+                    &context
+                )
+                .is_ok(),
+                "{} under-declares its sandbox level",
+                descriptor.name
+            );
+        }
     }
 }
@@ -33,6 +33,8 @@ impl RenameCommand {
                 sandbox_level_required: SandboxLevel::WorkspaceWrite,
                 supports_preview: true,
                 supports_dry_run: true,
+                timeout: None,
+                alias_of: None,
             },
         }
     }
@@ -114,6 +116,8 @@ impl RenameCommand {
             ));
         }
 
+        let is_dir = from_path.is_dir();
+
         // Perform the rename
         fs::rename(&from_path, &to_path).await.map_err(|e| {
             FennecError::Command(Box::new(std::io::Error::new(
@@ -124,12 +128,22 @@ impl RenameCommand {
 
         // Record action to log
         if let Some(action_log) = &context.action_log {
-            let action = Action::file_moved(
-                "rename".to_string(),
-                from_path.clone(),
-                to_path.clone(),
-                format!("Renamed: {} -> {}", from_path.display(), to_path.display()),
-            );
+            let description = format!("Renamed: {} -> {}", from_path.display(), to_path.display());
+            let action = if is_dir {
+                Action::directory_moved(
+                    "rename".to_string(),
+                    from_path.clone(),
+                    to_path.clone(),
+                    description,
+                )
+            } else {
+                Action::file_moved(
+                    "rename".to_string(),
+                    from_path.clone(),
+                    to_path.clone(),
+                    description,
+                )
+            };
             action_log.record(action).await;
         }
 
@@ -213,12 +227,14 @@ impl CommandExecutor for RenameCommand {
                 success: true,
                 output,
                 error: None,
+                data: None,
             }),
             Err(e) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
+                data: None,
             }),
         }
     }
@@ -289,6 +305,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -324,6 +342,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -356,6 +376,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -385,6 +407,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -413,6 +437,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -441,6 +467,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
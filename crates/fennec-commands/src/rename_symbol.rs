@@ -0,0 +1,875 @@
+use crate::file_ops::{EditStrategy, FileEditRequest, FileOperations, FileOperationsConfig};
+use crate::find_symbol::FindSymbolCommand;
+use crate::hunks::{split_diff_into_hunks, Hunk};
+use crate::registry::{CommandContext, CommandDescriptor, CommandExecutor};
+use crate::symbols::{Symbol, SymbolIndex, SymbolType};
+use anyhow::Result;
+use fennec_core::{
+    command::{Capability, CommandPreview, CommandResult, PreviewAction},
+    error::FennecError,
+};
+use fennec_security::SandboxLevel;
+use proc_macro2::{TokenStream, TokenTree};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use uuid::Uuid;
+
+const HUNK_CONTEXT_LINES: usize = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenameSymbolArgs {
+    /// Current name of the symbol
+    pub symbol: String,
+    /// New name for the symbol
+    pub new_name: String,
+    /// Restrict the definition lookup to a symbol type (fn, struct, enum, ...)
+    #[serde(default)]
+    pub symbol_type: Option<String>,
+    /// File containing the definition, used to pick between same-named symbols
+    #[serde(default)]
+    pub file: Option<PathBuf>,
+}
+
+/// A 1-based line/column position in a workspace file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolLocation {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Changes a symbol rename makes to one file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileRenamePlan {
+    pub path: PathBuf,
+    pub references: Vec<SymbolLocation>,
+    pub hunks: Vec<Hunk>,
+    #[serde(skip)]
+    updated_content: String,
+}
+
+/// Every edit needed to rename a symbol across the workspace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenamePlan {
+    pub definition: Symbol,
+    pub new_name: String,
+    pub files: Vec<FileRenamePlan>,
+    /// Files that mention the name but refer to a different definition of it
+    pub skipped: Vec<PathBuf>,
+}
+
+impl RenamePlan {
+    pub fn reference_count(&self) -> usize {
+        self.files.iter().map(|f| f.references.len()).sum()
+    }
+}
+
+/// An identifier token found in a source file
+struct IdentOccurrence {
+    line: usize,
+    column: usize,
+    in_use: bool,
+    /// Path segment directly before the identifier (`store` in `store::Name`)
+    qualifier: Option<String>,
+}
+
+/// The path segment preceding `tokens[idx]`, if it is written as `segment::`
+fn path_qualifier(tokens: &[TokenTree], idx: usize) -> Option<String> {
+    if idx < 3 {
+        return None;
+    }
+    match (&tokens[idx - 3], &tokens[idx - 2], &tokens[idx - 1]) {
+        (TokenTree::Ident(segment), TokenTree::Punct(a), TokenTree::Punct(b))
+            if a.as_char() == ':' && b.as_char() == ':' =>
+        {
+            Some(segment.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Collect identifier tokens equal to `name`, noting whether each sits inside
+/// a `use` item and which path segment qualifies it. Comments and string
+/// literals never produce identifier tokens.
+fn collect_idents(
+    stream: TokenStream,
+    name: &str,
+    in_use: bool,
+    group_qualifier: Option<&str>,
+    out: &mut Vec<IdentOccurrence>,
+) {
+    let tokens: Vec<TokenTree> = stream.into_iter().collect();
+    let mut use_active = in_use;
+    for (idx, tree) in tokens.iter().enumerate() {
+        match tree {
+            TokenTree::Ident(ident) => {
+                if ident == "use" {
+                    use_active = true;
+                } else if ident == name {
+                    let start = ident.span().start();
+                    // Inside `use a::{Name, ..}` the qualifier precedes the braces.
+                    let qualifier = path_qualifier(&tokens, idx).or_else(|| {
+                        if use_active && (idx == 0 || is_list_item(&tokens, idx)) {
+                            group_qualifier.map(str::to_string)
+                        } else {
+                            None
+                        }
+                    });
+                    out.push(IdentOccurrence {
+                        line: start.line,
+                        column: start.column,
+                        in_use: use_active,
+                        qualifier,
+                    });
+                }
+            }
+            TokenTree::Punct(punct) => {
+                if punct.as_char() == ';' && !in_use {
+                    use_active = false;
+                }
+            }
+            TokenTree::Group(group) => {
+                let qualifier = path_qualifier(&tokens, idx).filter(|_| use_active);
+                collect_idents(group.stream(), name, use_active, qualifier.as_deref(), out)
+            }
+            TokenTree::Literal(_) => {}
+        }
+    }
+}
+
+/// Whether `tokens[idx]` starts an item of a comma-separated list
+fn is_list_item(tokens: &[TokenTree], idx: usize) -> bool {
+    matches!(&tokens[idx - 1], TokenTree::Punct(p) if p.as_char() == ',')
+}
+
+/// Name a definition's module is referred to by in paths (`crate` for roots)
+fn module_name(path: &Path) -> Option<String> {
+    match path.file_stem()?.to_str()? {
+        "lib" | "main" => Some("crate".to_string()),
+        "mod" => path.parent()?.file_name()?.to_str().map(|s| s.to_string()),
+        stem => Some(stem.to_string()),
+    }
+}
+
+fn find_idents(path: &Path, content: &str, name: &str) -> Result<Vec<IdentOccurrence>> {
+    let stream = TokenStream::from_str(content).map_err(|e| {
+        FennecError::Command(Box::new(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Failed to tokenize {}: {}", path.display(), e),
+        )))
+    })?;
+    let mut occurrences = Vec::new();
+    collect_idents(stream, name, false, None, &mut occurrences);
+    Ok(occurrences)
+}
+
+/// Byte offset of a 1-based line and 0-based character column
+fn byte_offset(content: &str, line: usize, column: usize) -> Option<usize> {
+    let line_start = if line <= 1 {
+        0
+    } else {
+        content.match_indices('\n').nth(line - 2)?.0 + 1
+    };
+    let line_text = &content[line_start..];
+    line_text
+        .char_indices()
+        .nth(column)
+        .map(|(offset, _)| line_start + offset)
+}
+
+fn command_error(kind: std::io::ErrorKind, message: String) -> anyhow::Error {
+    FennecError::Command(Box::new(std::io::Error::new(kind, message))).into()
+}
+
+pub struct RenameSymbolCommand {
+    descriptor: CommandDescriptor,
+    file_ops: FileOperations,
+}
+
+impl RenameSymbolCommand {
+    pub fn new() -> Self {
+        Self {
+            descriptor: CommandDescriptor {
+                name: "rename-symbol".to_string(),
+                description: "Rename a Rust symbol and update its references across the workspace"
+                    .to_string(),
+                version: "1.0.0".to_string(),
+                author: Some("Fennec Contributors".to_string()),
+                capabilities_required: vec![Capability::ReadFile, Capability::WriteFile],
+                sandbox_level_required: SandboxLevel::WorkspaceWrite,
+                supports_preview: true,
+                supports_dry_run: true,
+                timeout: None,
+                alias_of: None,
+            },
+            file_ops: FileOperations::new(FileOperationsConfig::default()),
+        }
+    }
+
+    fn parse_args(args: &serde_json::Value) -> Result<RenameSymbolArgs> {
+        serde_json::from_value(args.clone()).map_err(|e| {
+            command_error(
+                std::io::ErrorKind::InvalidInput,
+                format!("Invalid rename-symbol arguments: {}", e),
+            )
+        })
+    }
+
+    fn workspace_path(context: &CommandContext) -> Result<PathBuf> {
+        context
+            .workspace_path
+            .as_ref()
+            .map(PathBuf::from)
+            .ok_or_else(|| {
+                command_error(
+                    std::io::ErrorKind::NotFound,
+                    "No workspace path set".to_string(),
+                )
+            })
+    }
+
+    fn relative<'a>(workspace_path: &Path, path: &'a Path) -> &'a Path {
+        path.strip_prefix(workspace_path).unwrap_or(path)
+    }
+
+    /// Resolve the single definition being renamed
+    fn find_definition(
+        index: &SymbolIndex,
+        args: &RenameSymbolArgs,
+        workspace_path: &Path,
+    ) -> Result<Symbol> {
+        let symbol_type = match &args.symbol_type {
+            Some(type_str) => Some(FindSymbolCommand::parse_symbol_type(type_str).ok_or_else(
+                || {
+                    command_error(
+                        std::io::ErrorKind::InvalidInput,
+                        format!("Unknown symbol type: {}", type_str),
+                    )
+                },
+            )?),
+            None => None,
+        };
+        let file = args.file.as_ref().map(|f| {
+            if f.is_absolute() {
+                f.clone()
+            } else {
+                workspace_path.join(f)
+            }
+        });
+
+        let candidates: Vec<&Symbol> = index
+            .find_by_name(&args.symbol)
+            .into_iter()
+            .filter(|s| s.symbol_type != SymbolType::Impl)
+            .filter(|s| symbol_type.as_ref().is_none_or(|t| &s.symbol_type == t))
+            .filter(|s| file.as_ref().is_none_or(|f| &s.path == f))
+            .collect();
+
+        match candidates.as_slice() {
+            [] => Err(command_error(
+                std::io::ErrorKind::NotFound,
+                format!("No definition of `{}` found in workspace", args.symbol),
+            )),
+            [definition] if definition.symbol_type == SymbolType::Module => Err(command_error(
+                std::io::ErrorKind::Unsupported,
+                format!(
+                    "`{}` is a module; renaming modules requires moving files and is not supported",
+                    args.symbol
+                ),
+            )),
+            [definition] => Ok((*definition).clone()),
+            many => {
+                let locations: Vec<String> = many
+                    .iter()
+                    .map(|s| {
+                        format!(
+                            "  {}:{} ({:?})",
+                            Self::relative(workspace_path, &s.path).display(),
+                            s.line,
+                            s.symbol_type
+                        )
+                    })
+                    .collect();
+                Err(command_error(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "`{}` is defined {} times; pass `file` or `symbol_type` to choose one:\n{}",
+                        args.symbol,
+                        many.len(),
+                        locations.join("\n")
+                    ),
+                ))
+            }
+        }
+    }
+
+    /// Build the rename plan: definition, per-file edits and hunks.
+    ///
+    /// Aborts if `new_name` is already defined or imported in any affected file.
+    pub async fn plan_rename(
+        &self,
+        args: &RenameSymbolArgs,
+        workspace_path: &Path,
+    ) -> Result<RenamePlan> {
+        let mut index = SymbolIndex::load(workspace_path);
+        if index.refresh(workspace_path) > 0 {
+            if let Err(e) = index.save(workspace_path) {
+                tracing::debug!("Failed to persist symbol index: {}", e);
+            }
+        }
+
+        let definition = Self::find_definition(&index, args, workspace_path)?;
+        let definitions: Vec<&Symbol> = index
+            .find_by_name(&args.symbol)
+            .into_iter()
+            .filter(|s| s.symbol_type != SymbolType::Impl)
+            .collect();
+        let defining_files: HashSet<&PathBuf> = definitions.iter().map(|s| &s.path).collect();
+
+        let mut files = Vec::new();
+        let mut skipped = Vec::new();
+        let mut conflicts = Vec::new();
+
+        for path in SymbolIndex::rust_files(workspace_path) {
+            let content = match self.file_ops.safe_read_file(&path).await {
+                Ok(content) => content,
+                Err(_) => continue,
+            };
+            if !content.contains(args.symbol.as_str()) {
+                continue;
+            }
+
+            let occurrences = find_idents(&path, &content, &args.symbol)?;
+            if occurrences.is_empty() {
+                continue;
+            }
+            let occurrences = if defining_files.len() > 1 {
+                Self::attribute_references(occurrences, &path, &definition, &definitions)
+            } else {
+                occurrences
+            };
+            if occurrences.is_empty() {
+                skipped.push(path);
+                continue;
+            }
+
+            if content.contains(args.new_name.as_str()) {
+                conflicts.extend(
+                    find_idents(&path, &content, &args.new_name)?
+                        .into_iter()
+                        .filter(|o| o.in_use)
+                        .map(|o| SymbolLocation {
+                            path: path.clone(),
+                            line: o.line,
+                            column: o.column + 1,
+                        }),
+                );
+            }
+            conflicts.extend(
+                index
+                    .find_in_file(&path)
+                    .into_iter()
+                    .filter(|s| s.name == args.new_name)
+                    .map(|s| SymbolLocation {
+                        path: s.path.clone(),
+                        line: s.line,
+                        column: 0,
+                    }),
+            );
+
+            let mut updated = content.clone();
+            let mut references = Vec::new();
+            for occurrence in occurrences.iter().rev() {
+                let start = byte_offset(&content, occurrence.line, occurrence.column)
+                    .filter(|&start| content[start..].starts_with(args.symbol.as_str()))
+                    .ok_or_else(|| {
+                        command_error(
+                            std::io::ErrorKind::InvalidData,
+                            format!(
+                                "Could not locate `{}` at {}:{}:{}",
+                                args.symbol,
+                                path.display(),
+                                occurrence.line,
+                                occurrence.column + 1
+                            ),
+                        )
+                    })?;
+                updated.replace_range(start..start + args.symbol.len(), &args.new_name);
+                references.push(SymbolLocation {
+                    path: path.clone(),
+                    line: occurrence.line,
+                    column: occurrence.column + 1,
+                });
+            }
+            references.reverse();
+
+            let hunks = split_diff_into_hunks(path.clone(), &content, &updated, HUNK_CONTEXT_LINES);
+            files.push(FileRenamePlan {
+                path,
+                references,
+                hunks,
+                updated_content: updated,
+            });
+        }
+
+        if !conflicts.is_empty() {
+            conflicts.sort_by(|a, b| (&a.path, a.line).cmp(&(&b.path, b.line)));
+            conflicts.dedup();
+            let locations: Vec<String> = conflicts
+                .iter()
+                .map(|c| {
+                    let rel = Self::relative(workspace_path, &c.path).display();
+                    if c.column == 0 {
+                        format!("  {}:{}", rel, c.line)
+                    } else {
+                        format!("  {}:{}:{}", rel, c.line, c.column)
+                    }
+                })
+                .collect();
+            return Err(command_error(
+                std::io::ErrorKind::AlreadyExists,
+                format!(
+                    "Cannot rename `{}` to `{}`: `{}` is already in scope at:\n{}",
+                    args.symbol,
+                    args.new_name,
+                    args.new_name,
+                    locations.join("\n")
+                ),
+            ));
+        }
+
+        Ok(RenamePlan {
+            definition,
+            new_name: args.new_name.clone(),
+            files,
+            skipped,
+        })
+    }
+
+    /// Keep the occurrences that refer to `definition` when several symbols
+    /// share its name.
+    ///
+    /// A `module::Name` path is attributed to the definition living in that
+    /// module. Unqualified uses follow the file's own binding: its local
+    /// definition if it has one, otherwise whatever its `use` items import.
+    fn attribute_references(
+        occurrences: Vec<IdentOccurrence>,
+        path: &Path,
+        definition: &Symbol,
+        definitions: &[&Symbol],
+    ) -> Vec<IdentOccurrence> {
+        let resolve = |qualifier: &str| {
+            definitions
+                .iter()
+                .find(|d| module_name(&d.path).as_deref() == Some(qualifier))
+                .map(|d| d.path == definition.path)
+        };
+
+        let binds_here = if definitions.iter().any(|d| d.path == path) {
+            Some(path == definition.path)
+        } else {
+            occurrences
+                .iter()
+                .filter(|o| o.in_use)
+                .find_map(|o| o.qualifier.as_deref().and_then(resolve))
+        };
+
+        occurrences
+            .into_iter()
+            .filter(|o| match o.qualifier.as_deref().and_then(resolve) {
+                Some(ours) => ours,
+                None => binds_here == Some(true),
+            })
+            .collect()
+    }
+
+    fn summarize_plan(plan: &RenamePlan, workspace_path: &Path, applied: bool) -> String {
+        let mut output = format!(
+            "{} `{}` -> `{}`: {} reference(s) in {} file(s)\n",
+            if applied { "Renamed" } else { "Would rename" },
+            plan.definition.name,
+            plan.new_name,
+            plan.reference_count(),
+            plan.files.len()
+        );
+        for file in &plan.files {
+            output.push_str(&format!(
+                "\n{} ({} reference(s))\n",
+                Self::relative(workspace_path, &file.path).display(),
+                file.references.len()
+            ));
+            for hunk in &file.hunks {
+                output.push_str(&format!("  {}\n", hunk.summary()));
+            }
+        }
+        if !plan.skipped.is_empty() {
+            output.push_str(&format!(
+                "\nSkipped (refer to a different `{}`):\n",
+                plan.definition.name
+            ));
+            for path in &plan.skipped {
+                output.push_str(&format!(
+                    "  {}\n",
+                    Self::relative(workspace_path, path).display()
+                ));
+            }
+        }
+        output
+    }
+
+    async fn perform_rename(
+        &self,
+        args: &RenameSymbolArgs,
+        context: &CommandContext,
+    ) -> Result<(String, serde_json::Value)> {
+        let workspace_path = Self::workspace_path(context)?;
+        let plan = self.plan_rename(args, &workspace_path).await?;
+        let data = serde_json::to_value(&plan)?;
+
+        if context.dry_run {
+            return Ok((Self::summarize_plan(&plan, &workspace_path, false), data));
+        }
+
+        if context.cancellation_token.is_cancelled() {
+            return Err(command_error(
+                std::io::ErrorKind::Interrupted,
+                "Rename cancelled".to_string(),
+            ));
+        }
+
+        let requests = plan
+            .files
+            .iter()
+            .map(|file| FileEditRequest {
+                path: file.path.clone(),
+                strategy: EditStrategy::Replace {
+                    content: file.updated_content.clone(),
+                },
+                create_backup: false,
+                create_if_missing: false,
+                force_binary: false,
+            })
+            .collect();
+        let workspace_str = workspace_path.to_string_lossy();
+        let transaction = self
+            .file_ops
+            .apply_transaction(requests, &context.sandbox_level, Some(&workspace_str))
+            .await?;
+
+        if let Some(action_log) = &context.action_log {
+            let description = format!(
+                "Renamed symbol `{}` -> `{}` in {} file(s)",
+                plan.definition.name,
+                plan.new_name,
+                plan.files.len()
+            );
+            action_log
+                .record(transaction.to_action("rename-symbol".to_string(), description))
+                .await;
+        }
+
+        let mut index = SymbolIndex::load(&workspace_path);
+        let changed: Vec<PathBuf> = plan.files.iter().map(|f| f.path.clone()).collect();
+        if index.update_paths(&changed) > 0 {
+            if let Err(e) = index.save(&workspace_path) {
+                tracing::debug!("Failed to persist symbol index: {}", e);
+            }
+        }
+
+        Ok((Self::summarize_plan(&plan, &workspace_path, true), data))
+    }
+}
+
+impl Default for RenameSymbolCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl CommandExecutor for RenameSymbolCommand {
+    fn descriptor(&self) -> &CommandDescriptor {
+        &self.descriptor
+    }
+
+    async fn preview(
+        &self,
+        args: &serde_json::Value,
+        context: &CommandContext,
+    ) -> Result<CommandPreview> {
+        let args = Self::parse_args(args)?;
+        let workspace_path = Self::workspace_path(context)?;
+        let plan = self.plan_rename(&args, &workspace_path).await?;
+
+        Ok(CommandPreview {
+            command_id: Uuid::new_v4(),
+            description: Self::summarize_plan(&plan, &workspace_path, false),
+            actions: plan
+                .files
+                .iter()
+                .map(|file| PreviewAction::WriteFile {
+                    path: file.path.display().to_string(),
+                    content: file.updated_content.clone(),
+                    diff: Some(
+                        file.hunks
+                            .iter()
+                            .map(Hunk::to_unified_diff)
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    ),
+                    bytes_changed: file
+                        .hunks
+                        .iter()
+                        .flat_map(|hunk| hunk.old_content.iter().chain(&hunk.new_content))
+                        .map(|line| line.len() as u64 + 1)
+                        .sum(),
+                })
+                .collect(),
+            requires_approval: true,
+        })
+    }
+
+    async fn execute(
+        &self,
+        args: &serde_json::Value,
+        context: &CommandContext,
+    ) -> Result<CommandResult> {
+        let args = Self::parse_args(args)?;
+
+        match self.perform_rename(&args, context).await {
+            Ok((output, data)) => Ok(CommandResult {
+                command_id: Uuid::new_v4(),
+                success: true,
+                output,
+                error: None,
+                data: Some(data),
+            }),
+            Err(e) => Ok(CommandResult {
+                command_id: Uuid::new_v4(),
+                success: false,
+                output: String::new(),
+                error: Some(e.to_string()),
+                data: None,
+            }),
+        }
+    }
+
+    fn validate_args(&self, args: &serde_json::Value) -> Result<()> {
+        let args = Self::parse_args(args)?;
+
+        for name in [&args.symbol, &args.new_name] {
+            if syn::parse_str::<syn::Ident>(name).is_err() {
+                return Err(command_error(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("`{}` is not a valid Rust identifier", name),
+                ));
+            }
+        }
+        if args.symbol == args.new_name {
+            return Err(command_error(
+                std::io::ErrorKind::InvalidInput,
+                "New name must differ from the current name".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action_log::ActionLog;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+    use tokio_util::sync::CancellationToken;
+
+    fn fixture_crate() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("src/store")).unwrap();
+        std::fs::write(
+            root.join("src/lib.rs"),
+            "pub mod store;\npub mod report;\n\npub use store::TranscriptStore;\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("src/store/mod.rs"),
+            r#"/// Holds transcript lines. TranscriptStore is append-only.
+pub struct TranscriptStore {
+    lines: Vec<String>,
+}
+
+impl TranscriptStore {
+    pub fn new() -> TranscriptStore {
+        TranscriptStore { lines: Vec::new() }
+    }
+}
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("src/report.rs"),
+            r#"use crate::store::TranscriptStore;
+
+pub fn render(store: &crate::store::TranscriptStore) -> String {
+    let label = "TranscriptStore";
+    format!("{}: {}", label, std::mem::size_of::<TranscriptStore>())
+}
+"#,
+        )
+        .unwrap();
+        temp_dir
+    }
+
+    fn context(root: &Path, dry_run: bool, action_log: Option<Arc<ActionLog>>) -> CommandContext {
+        CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: Some(root.to_string_lossy().to_string()),
+            sandbox_level: SandboxLevel::WorkspaceWrite,
+            dry_run,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log,
+            timeout: None,
+            progress: None,
+        }
+    }
+
+    #[test]
+    fn test_byte_offset() {
+        let content = "ab\nçd\n";
+        assert_eq!(byte_offset(content, 1, 1), Some(1));
+        assert_eq!(byte_offset(content, 2, 1), Some(5));
+        assert_eq!(byte_offset(content, 4, 0), None);
+    }
+
+    #[tokio::test]
+    async fn test_rename_symbol_across_modules() {
+        let temp_dir = fixture_crate();
+        let root = temp_dir.path();
+        let action_log = Arc::new(ActionLog::new());
+        let command = RenameSymbolCommand::new();
+        let args = serde_json::json!({ "symbol": "TranscriptStore", "new_name": "Transcript" });
+        command.validate_args(&args).unwrap();
+
+        let result = command
+            .execute(&args, &context(root, false, Some(action_log.clone())))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.output.contains("8 reference(s) in 3 file(s)"));
+
+        let lib = std::fs::read_to_string(root.join("src/lib.rs")).unwrap();
+        assert!(lib.contains("pub use store::Transcript;"));
+
+        let store = std::fs::read_to_string(root.join("src/store/mod.rs")).unwrap();
+        assert!(store.contains("pub struct Transcript {"));
+        assert!(store.contains("impl Transcript {"));
+        assert!(store.contains("pub fn new() -> Transcript {"));
+        // Doc comments are not code references.
+        assert!(store.contains("TranscriptStore is append-only"));
+
+        let report = std::fs::read_to_string(root.join("src/report.rs")).unwrap();
+        assert!(report.contains("use crate::store::Transcript;"));
+        assert!(report.contains("store: &crate::store::Transcript)"));
+        assert!(report.contains("size_of::<Transcript>()"));
+        // String literals are left alone.
+        assert!(report.contains("\"TranscriptStore\""));
+
+        let actions = action_log.get_history().await;
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].command, "rename-symbol");
+    }
+
+    #[tokio::test]
+    async fn test_rename_symbol_dry_run_reports_hunks() {
+        let temp_dir = fixture_crate();
+        let root = temp_dir.path();
+        let original = std::fs::read_to_string(root.join("src/report.rs")).unwrap();
+
+        let command = RenameSymbolCommand::new();
+        let args = serde_json::json!({ "symbol": "TranscriptStore", "new_name": "Transcript" });
+        let result = command
+            .execute(&args, &context(root, true, None))
+            .await
+            .unwrap();
+
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.output.starts_with("Would rename"));
+        let data = result.data.unwrap();
+        assert_eq!(data["files"].as_array().unwrap().len(), 3);
+        assert!(!data["files"][0]["hunks"].as_array().unwrap().is_empty());
+        assert_eq!(
+            std::fs::read_to_string(root.join("src/report.rs")).unwrap(),
+            original
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rename_symbol_collision_aborts() {
+        let temp_dir = fixture_crate();
+        let root = temp_dir.path();
+        std::fs::write(
+            root.join("src/report.rs"),
+            "use crate::store::TranscriptStore;\nuse std::fmt::Display;\n\npub fn show(_: &TranscriptStore) {}\n",
+        )
+        .unwrap();
+        let store_before = std::fs::read_to_string(root.join("src/store/mod.rs")).unwrap();
+
+        let command = RenameSymbolCommand::new();
+        let args = serde_json::json!({ "symbol": "TranscriptStore", "new_name": "Display" });
+        let result = command
+            .execute(&args, &context(root, false, None))
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        let error = result.error.unwrap();
+        assert!(error.contains("`Display` is already in scope"));
+        assert!(error.contains("src/report.rs:2:15"));
+        assert_eq!(
+            std::fs::read_to_string(root.join("src/store/mod.rs")).unwrap(),
+            store_before
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rename_symbol_rejects_ambiguous_and_invalid() {
+        let temp_dir = fixture_crate();
+        let root = temp_dir.path();
+        std::fs::write(root.join("src/other.rs"), "pub struct TranscriptStore;\n").unwrap();
+
+        let command = RenameSymbolCommand::new();
+        let args = serde_json::json!({ "symbol": "TranscriptStore", "new_name": "Transcript" });
+        let result = command
+            .execute(&args, &context(root, true, None))
+            .await
+            .unwrap();
+        assert!(result.error.unwrap().contains("is defined 2 times"));
+
+        let args = serde_json::json!({
+            "symbol": "TranscriptStore",
+            "new_name": "Transcript",
+            "file": "src/other.rs"
+        });
+        let result = command
+            .execute(&args, &context(root, true, None))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        // Files bound to the store definition are skipped, not rewritten.
+        let data = result.data.unwrap();
+        let files = data["files"].as_array().unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(files[0]["path"].as_str().unwrap().ends_with("other.rs"));
+        assert_eq!(data["skipped"].as_array().unwrap().len(), 3);
+
+        assert!(command
+            .validate_args(&serde_json::json!({ "symbol": "Foo", "new_name": "fn" }))
+            .is_err());
+    }
+}
@@ -0,0 +1,380 @@
+//! On-disk store of recent command results, so large outputs (search
+//! results, plans) survive the TUI closing and can be replayed later.
+//!
+//! Layout under the store root:
+//!
+//! ```text
+//! <session_id>/index.json          newest results, oldest first
+//! <session_id>/spill/<id>.json     full results too large to keep inline
+//! ```
+
+use anyhow::{Context, Result};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::sync::Mutex;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::registry::{CommandContext, CommandExecutionResult, CommandRegistry};
+
+/// Results kept per session when no limit is given
+pub const DEFAULT_MAX_STORED_RESULTS: usize = 50;
+
+/// Output bytes kept in the index before the full result is spilled to disk
+pub const DEFAULT_MAX_INLINE_OUTPUT: usize = 16 * 1024;
+
+const INDEX_FILE: &str = "index.json";
+const SPILL_DIR: &str = "spill";
+
+/// A persisted execution together with what is needed to replay it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredResult {
+    pub session_id: Uuid,
+    /// The result as executed; output is truncated and data dropped when the
+    /// full result was spilled
+    pub result: CommandExecutionResult,
+    pub args: serde_json::Value,
+    pub workspace_path: Option<String>,
+    /// Size of the untruncated output
+    pub output_bytes: usize,
+    /// File holding the complete result, when it was too large to inline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spill_path: Option<PathBuf>,
+}
+
+impl StoredResult {
+    pub fn execution_id(&self) -> Uuid {
+        self.result.execution_id
+    }
+
+    pub fn is_truncated(&self) -> bool {
+        self.spill_path.is_some()
+    }
+
+    /// The complete result, read back from the spill file when needed
+    pub async fn full_result(&self) -> Result<CommandExecutionResult> {
+        let Some(spill_path) = &self.spill_path else {
+            return Ok(self.result.clone());
+        };
+        let content = fs::read_to_string(spill_path)
+            .await
+            .with_context(|| format!("Failed to read spilled result {}", spill_path.display()))?;
+        Ok(serde_json::from_str(&content)?)
+    }
+}
+
+/// Bounded per-session store of [`CommandExecutionResult`]s, fed by
+/// [`ResultStoreMiddleware`](crate::middleware::ResultStoreMiddleware)
+#[derive(Debug)]
+pub struct ResultStore {
+    root: PathBuf,
+    max_results: usize,
+    max_inline_output: usize,
+    // Serializes index read-modify-write cycles
+    lock: Mutex<()>,
+}
+
+impl ResultStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            max_results: DEFAULT_MAX_STORED_RESULTS,
+            max_inline_output: DEFAULT_MAX_INLINE_OUTPUT,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Store results under the project data directory
+    pub fn with_default_dir() -> Result<Self> {
+        let proj_dirs =
+            ProjectDirs::from("", "", "fennec").context("Failed to get project directories")?;
+        Ok(Self::new(proj_dirs.data_dir().join("results")))
+    }
+
+    /// Keep at most `max_results` results per session, evicting the oldest
+    pub fn with_max_results(mut self, max_results: usize) -> Self {
+        self.max_results = max_results.max(1);
+        self
+    }
+
+    /// Spill results whose output is longer than `max_inline_output` bytes
+    pub fn with_max_inline_output(mut self, max_inline_output: usize) -> Self {
+        self.max_inline_output = max_inline_output;
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn session_dir(&self, session_id: Uuid) -> PathBuf {
+        self.root.join(session_id.to_string())
+    }
+
+    async fn read_index(&self, session_id: Uuid) -> Result<Vec<StoredResult>> {
+        let index_path = self.session_dir(session_id).join(INDEX_FILE);
+        match fs::read_to_string(&index_path).await {
+            Ok(content) => serde_json::from_str(&content)
+                .with_context(|| format!("Corrupt result index {}", index_path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn write_index(&self, session_id: Uuid, entries: &[StoredResult]) -> Result<()> {
+        let session_dir = self.session_dir(session_id);
+        fs::create_dir_all(&session_dir).await?;
+        // Write-then-rename so a crash never leaves a half-written index
+        let temp_path = session_dir.join(format!("{}.tmp", INDEX_FILE));
+        fs::write(&temp_path, serde_json::to_vec_pretty(entries)?).await?;
+        fs::rename(&temp_path, session_dir.join(INDEX_FILE)).await?;
+        Ok(())
+    }
+
+    /// Persist a result, spilling it to its own file if the output is large
+    pub async fn record(
+        &self,
+        context: &CommandContext,
+        args: &serde_json::Value,
+        result: &CommandExecutionResult,
+    ) -> Result<StoredResult> {
+        let _guard = self.lock.lock().await;
+        let session_id = context.session_id;
+
+        let mut stored = StoredResult {
+            session_id,
+            result: result.clone(),
+            args: args.clone(),
+            workspace_path: context.workspace_path.clone(),
+            output_bytes: result.output.len(),
+            spill_path: None,
+        };
+
+        if result.output.len() > self.max_inline_output {
+            let spill_dir = self.session_dir(session_id).join(SPILL_DIR);
+            fs::create_dir_all(&spill_dir).await?;
+            let spill_path = spill_dir.join(format!("{}.json", result.execution_id));
+            fs::write(&spill_path, serde_json::to_vec(result)?)
+                .await
+                .with_context(|| format!("Failed to spill result {}", spill_path.display()))?;
+
+            let mut end = self.max_inline_output;
+            while !result.output.is_char_boundary(end) {
+                end -= 1;
+            }
+            stored.result.output = format!(
+                "{}\n... [output truncated at {} of {} bytes; full result in {}]",
+                &result.output[..end],
+                end,
+                result.output.len(),
+                spill_path.display()
+            );
+            stored.result.data = None;
+            stored.spill_path = Some(spill_path);
+        }
+
+        let mut entries = self.read_index(session_id).await.unwrap_or_else(|e| {
+            warn!("Starting a fresh result index for {}: {}", session_id, e);
+            Vec::new()
+        });
+        entries.retain(|entry| entry.execution_id() != result.execution_id);
+        entries.push(stored.clone());
+
+        let excess = entries.len().saturating_sub(self.max_results);
+        for evicted in entries.drain(..excess) {
+            if let Some(spill_path) = evicted.spill_path {
+                if let Err(e) = fs::remove_file(&spill_path).await {
+                    warn!("Failed to remove {}: {}", spill_path.display(), e);
+                }
+            }
+        }
+
+        self.write_index(session_id, &entries).await?;
+        Ok(stored)
+    }
+
+    /// Stored results for a session, newest first
+    pub async fn list(&self, session_id: Uuid) -> Result<Vec<StoredResult>> {
+        let mut entries = self.read_index(session_id).await?;
+        entries.reverse();
+        Ok(entries)
+    }
+
+    /// Find a stored result by execution id in any session
+    pub async fn get(&self, execution_id: Uuid) -> Result<Option<StoredResult>> {
+        let mut sessions = match fs::read_dir(&self.root).await {
+            Ok(sessions) => sessions,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        while let Some(session) = sessions.next_entry().await? {
+            let Ok(session_id) = Uuid::parse_str(&session.file_name().to_string_lossy()) else {
+                continue;
+            };
+            match self.read_index(session_id).await {
+                Ok(entries) => {
+                    if let Some(entry) = entries
+                        .into_iter()
+                        .find(|entry| entry.execution_id() == execution_id)
+                    {
+                        return Ok(Some(entry));
+                    }
+                }
+                Err(e) => warn!("Skipping result index for {}: {}", session_id, e),
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Run a stored command again with the same arguments. The new run is
+/// recorded like any other execution.
+pub async fn replay(
+    registry: &CommandRegistry,
+    stored: &StoredResult,
+    context: &CommandContext,
+) -> Result<CommandExecutionResult> {
+    registry
+        .execute_command(&stored.result.command_name, &stored.args, context)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::ResultStoreMiddleware;
+    use fennec_security::SandboxLevel;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+    use tokio_util::sync::CancellationToken;
+
+    fn context(session_id: Uuid) -> CommandContext {
+        CommandContext {
+            session_id,
+            user_id: None,
+            workspace_path: None,
+            sandbox_level: SandboxLevel::ReadOnly,
+            dry_run: false,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log: None,
+            timeout: None,
+            progress: None,
+        }
+    }
+
+    fn result(output: &str) -> CommandExecutionResult {
+        CommandExecutionResult {
+            command_id: Uuid::new_v4(),
+            command_name: "search".to_string(),
+            execution_id: Uuid::new_v4(),
+            success: true,
+            output: output.to_string(),
+            error: None,
+            data: Some(serde_json::json!({"matches": 3})),
+            preview: None,
+            execution_time_ms: 5,
+            created_at: chrono::Utc::now(),
+            deprecation: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_large_output_spills_to_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = ResultStore::new(temp_dir.path().to_path_buf()).with_max_inline_output(16);
+        let ctx = context(Uuid::new_v4());
+
+        let small = result("short");
+        let large = result(&"match\n".repeat(100));
+        store
+            .record(&ctx, &serde_json::json!({}), &small)
+            .await
+            .unwrap();
+        let stored = store
+            .record(&ctx, &serde_json::json!({"query": "match"}), &large)
+            .await
+            .unwrap();
+
+        assert!(stored.is_truncated());
+        assert_eq!(stored.output_bytes, 600);
+        assert!(stored
+            .result
+            .output
+            .contains("[output truncated at 16 of 600 bytes"));
+        assert!(stored.result.data.is_none());
+
+        let fetched = store.get(large.execution_id).await.unwrap().unwrap();
+        let full = fetched.full_result().await.unwrap();
+        assert_eq!(full.output, large.output);
+        assert_eq!(full.data, large.data);
+
+        let listed = store.list(ctx.session_id).await.unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].execution_id(), large.execution_id);
+        assert!(!listed[1].is_truncated());
+    }
+
+    #[tokio::test]
+    async fn test_eviction_removes_spill_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = ResultStore::new(temp_dir.path().to_path_buf())
+            .with_max_results(2)
+            .with_max_inline_output(4);
+        let ctx = context(Uuid::new_v4());
+
+        let first = store
+            .record(&ctx, &serde_json::json!({}), &result("0123456789"))
+            .await
+            .unwrap();
+        for _ in 0..2 {
+            store
+                .record(&ctx, &serde_json::json!({}), &result("ok"))
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(store.list(ctx.session_id).await.unwrap().len(), 2);
+        assert!(!first.spill_path.unwrap().exists());
+        assert!(store
+            .get(first.result.execution_id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_replay_deterministic_command() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = Arc::new(ResultStore::new(temp_dir.path().to_path_buf()));
+        let registry = crate::registry::CommandRegistry::new();
+        registry
+            .register_builtin(Arc::new(crate::diff::DiffCommand::new()))
+            .await
+            .unwrap();
+        registry
+            .register_middleware(Box::new(ResultStoreMiddleware::new(store.clone())))
+            .await;
+
+        let ctx = context(Uuid::new_v4());
+        let args = serde_json::json!({
+            "left": "a\nb\n",
+            "right": "a\nc\n",
+            "is_file_path": false
+        });
+        let original = registry.execute_command("diff", &args, &ctx).await.unwrap();
+        assert!(original.success);
+
+        let stored = store.get(original.execution_id).await.unwrap().unwrap();
+        assert_eq!(stored.args, args);
+
+        let replayed = replay(&registry, &stored, &ctx).await.unwrap();
+        assert_ne!(replayed.execution_id, original.execution_id);
+        assert_eq!(replayed.output, original.output);
+        assert_eq!(store.list(ctx.session_id).await.unwrap().len(), 2);
+    }
+}
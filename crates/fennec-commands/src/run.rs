@@ -4,13 +4,17 @@ use fennec_core::{
     command::{Capability, CommandPreview, CommandResult, PreviewAction},
     error::FennecError,
 };
-use fennec_security::SandboxLevel;
+use fennec_security::{classify_command, SandboxLevel};
 use serde::{Deserialize, Serialize};
 use std::process::Stdio;
-use tokio::process::Command;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
 use uuid::Uuid;
 
-use crate::registry::{CommandContext, CommandDescriptor, CommandExecutor};
+use crate::progress::ProgressReporter;
+use crate::registry::{
+    CommandContext, CommandDescriptor, CommandExecutor, CommandOutputEvent, OutputSender,
+};
 
 /// Arguments for the run command
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +29,9 @@ pub struct RunArgs {
     pub timeout_seconds: Option<u64>,
     /// Whether to capture output
     pub capture_output: Option<bool>,
+    /// Cap on captured output in bytes; later lines are dropped
+    #[serde(default)]
+    pub max_output_bytes: Option<usize>,
 }
 
 /// Run command for executing shell commands
@@ -44,6 +51,8 @@ impl RunCommand {
                 sandbox_level_required: SandboxLevel::WorkspaceWrite,
                 supports_preview: true,
                 supports_dry_run: true,
+                timeout: None,
+                alias_of: None,
             },
         }
     }
@@ -108,22 +117,29 @@ impl RunCommand {
         Ok(())
     }
 
-    /// Execute the command
-    async fn execute_command(&self, args: &RunArgs, context: &CommandContext) -> Result<String> {
+    /// Spawn the command and stream its output until it exits, times out or
+    /// is cancelled
+    async fn execute_command(
+        &self,
+        args: &RunArgs,
+        context: &CommandContext,
+        events: Option<&OutputSender>,
+    ) -> Result<RunOutcome> {
         self.validate_command(&args.command, context)?;
 
         if context.dry_run {
-            return Ok(format!("Would execute: {}", args.command));
+            return Ok(RunOutcome::DryRun(format!(
+                "Would execute: {}",
+                args.command
+            )));
         }
 
         // Parse command and arguments
         let parts: Vec<&str> = args.command.split_whitespace().collect();
         if parts.is_empty() {
-            return Err(FennecError::Command(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Empty command",
-            )))
-            .into());
+            return Err(
+                FennecError::Command(Box::new(std::io::Error::other("Empty command"))).into(),
+            );
         }
 
         let mut cmd = Command::new(parts[0]);
@@ -150,63 +166,363 @@ impl RunCommand {
             cmd.stdout(Stdio::piped());
             cmd.stderr(Stdio::piped());
         }
+        cmd.stdin(Stdio::null());
+        cmd.kill_on_drop(true);
+
+        // Run in a fresh process group so cancellation also reaches anything
+        // the command spawns
+        #[cfg(unix)]
+        cmd.process_group(0);
 
-        // Execute with timeout
         let timeout = std::time::Duration::from_secs(args.timeout_seconds.unwrap_or(30));
+        let started = std::time::Instant::now();
 
-        let output = tokio::time::timeout(timeout, cmd.output())
-            .await
-            .map_err(|_| {
-                FennecError::Command(Box::new(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("Command timed out after {} seconds", timeout.as_secs()),
-                )))
-            })?
-            .map_err(|e| {
-                FennecError::Command(Box::new(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("Failed to execute command: {}", e),
-                )))
-            })?;
+        let mut child = cmd.spawn().map_err(|e| {
+            FennecError::Command(Box::new(std::io::Error::other(format!(
+                "Failed to execute command: {}",
+                e
+            ))))
+        })?;
 
-        // Check for cancellation
-        if context.cancellation_token.is_cancelled() {
-            return Err(FennecError::Command(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Command execution was cancelled",
-            )))
-            .into());
+        let mut capture = OutputCapture::new(
+            args.max_output_bytes.unwrap_or(DEFAULT_MAX_OUTPUT_BYTES),
+            events,
+            context.progress.as_ref(),
+        );
+        let mut stdout = child.stdout.take().map(OutputLines::new);
+        let mut stderr = child.stderr.take().map(OutputLines::new);
+
+        let deadline = tokio::time::sleep(timeout);
+        tokio::pin!(deadline);
+
+        let mut interruption = None;
+        while stdout.is_some() || stderr.is_some() {
+            tokio::select! {
+                line = next_line(&mut stdout) => match line {
+                    Some(line) => capture.push(OutputStream::Stdout, line),
+                    None => stdout = None,
+                },
+                line = next_line(&mut stderr) => match line {
+                    Some(line) => capture.push(OutputStream::Stderr, line),
+                    None => stderr = None,
+                },
+                _ = context.cancellation_token.cancelled() => {
+                    interruption = Some(Interruption::Cancelled);
+                    break;
+                }
+                _ = &mut deadline => {
+                    interruption = Some(Interruption::TimedOut);
+                    break;
+                }
+            }
+        }
+
+        // Output is closed, but the process may still be running
+        let status = if interruption.is_none() {
+            tokio::select! {
+                status = child.wait() => Some(status),
+                _ = context.cancellation_token.cancelled() => {
+                    interruption = Some(Interruption::Cancelled);
+                    None
+                }
+                _ = &mut deadline => {
+                    interruption = Some(Interruption::TimedOut);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let status = match status {
+            Some(status) => status,
+            None => {
+                kill_process_group(&mut child);
+                child.wait().await
+            }
+        }
+        .map_err(|e| {
+            FennecError::Command(Box::new(std::io::Error::other(format!(
+                "Failed to wait for command: {}",
+                e
+            ))))
+        })?;
+
+        Ok(RunOutcome::Finished(RunOutput {
+            report: RunReport {
+                exit_code: status.code(),
+                success: status.success() && interruption.is_none(),
+                duration_ms: started.elapsed().as_millis() as u64,
+                truncated: capture.truncated,
+                cancelled: interruption == Some(Interruption::Cancelled),
+                timed_out: interruption == Some(Interruption::TimedOut),
+            },
+            timeout_seconds: timeout.as_secs(),
+            stdout: capture.stdout,
+            stderr: capture.stderr,
+            limit_bytes: capture.limit,
+        }))
+    }
+
+    async fn run(
+        &self,
+        args: &serde_json::Value,
+        context: &CommandContext,
+        events: Option<OutputSender>,
+    ) -> Result<CommandResult> {
+        let args: RunArgs = serde_json::from_value(args.clone()).map_err(|e| {
+            FennecError::Command(Box::new(std::io::Error::other(format!(
+                "Invalid run arguments: {}",
+                e
+            ))))
+        })?;
+
+        match self.execute_command(&args, context, events.as_ref()).await {
+            Ok(RunOutcome::DryRun(output)) => Ok(CommandResult {
+                command_id: Uuid::new_v4(),
+                success: true,
+                output,
+                error: None,
+                data: None,
+            }),
+            Ok(RunOutcome::Finished(output)) => Ok(CommandResult {
+                command_id: Uuid::new_v4(),
+                success: output.report.success,
+                error: output.failure(),
+                data: Some(serde_json::to_value(&output.report)?),
+                output: output.render(),
+            }),
+            Err(e) => Ok(CommandResult {
+                command_id: Uuid::new_v4(),
+                success: false,
+                output: String::new(),
+                error: Some(e.to_string()),
+                data: None,
+            }),
         }
+    }
+}
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
+/// Default cap on captured stdout and stderr, in bytes
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 1024 * 1024;
+
+/// Summary of a finished process, returned as the command's `data`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunReport {
+    /// Exit code, or `None` when the process was killed by a signal
+    pub exit_code: Option<i32>,
+    pub success: bool,
+    pub duration_ms: u64,
+    /// Whether output beyond `max_output_bytes` was dropped
+    pub truncated: bool,
+    pub cancelled: bool,
+    pub timed_out: bool,
+}
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Interruption {
+    Cancelled,
+    TimedOut,
+}
+
+enum RunOutcome {
+    DryRun(String),
+    Finished(RunOutput),
+}
+
+struct RunOutput {
+    report: RunReport,
+    timeout_seconds: u64,
+    stdout: String,
+    stderr: String,
+    limit_bytes: usize,
+}
+
+impl RunOutput {
+    fn render(&self) -> String {
         let mut result = Vec::new();
-        result.push(format!("Exit code: {}", output.status.code().unwrap_or(-1)));
+        result.push(format!(
+            "Exit code: {}",
+            self.report.exit_code.unwrap_or(-1)
+        ));
 
-        if !stdout.is_empty() {
+        if !self.stdout.is_empty() {
             result.push("--- STDOUT ---".to_string());
-            result.push(stdout.to_string());
+            result.push(self.stdout.clone());
         }
 
-        if !stderr.is_empty() {
+        if !self.stderr.is_empty() {
             result.push("--- STDERR ---".to_string());
-            result.push(stderr.to_string());
+            result.push(self.stderr.clone());
         }
 
-        if !output.status.success() {
-            return Err(FennecError::Command(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!(
-                    "Command failed with exit code: {}",
-                    output.status.code().unwrap_or(-1)
-                ),
-            )))
-            .into());
+        if self.report.truncated {
+            result.push(truncation_marker(self.limit_bytes));
+        }
+
+        result.join("\n")
+    }
+
+    fn failure(&self) -> Option<String> {
+        if self.report.cancelled {
+            Some("Command execution was cancelled".to_string())
+        } else if self.report.timed_out {
+            Some(format!(
+                "Command timed out after {} seconds",
+                self.timeout_seconds
+            ))
+        } else if !self.report.success {
+            Some(format!(
+                "Command failed with exit code: {}",
+                self.report.exit_code.unwrap_or(-1)
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+fn truncation_marker(limit_bytes: usize) -> String {
+    format!("[output truncated after {} bytes]", limit_bytes)
+}
+
+#[derive(Debug, Clone, Copy)]
+enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// Accumulates output up to a byte budget and forwards each line to the
+/// event channel as it arrives
+struct OutputCapture<'a> {
+    limit: usize,
+    used: usize,
+    truncated: bool,
+    stdout: String,
+    stderr: String,
+    events: Option<&'a OutputSender>,
+    /// Lines read from either stream, including dropped ones
+    lines: usize,
+    progress: Option<&'a ProgressReporter>,
+}
+
+impl<'a> OutputCapture<'a> {
+    fn new(
+        limit: usize,
+        events: Option<&'a OutputSender>,
+        progress: Option<&'a ProgressReporter>,
+    ) -> Self {
+        Self {
+            limit,
+            used: 0,
+            truncated: false,
+            stdout: String::new(),
+            stderr: String::new(),
+            events,
+            lines: 0,
+            progress,
         }
+    }
+
+    fn push(&mut self, stream: OutputStream, line: String) {
+        self.lines += 1;
+        if let Some(progress) = self.progress {
+            progress.message(format!("{} lines of output", self.lines));
+        }
+
+        if self.truncated {
+            return;
+        }
+
+        if self.used + line.len() + 1 > self.limit {
+            self.truncated = true;
+            self.emit(CommandOutputEvent::Truncated {
+                limit_bytes: self.limit,
+            });
+            return;
+        }
+        self.used += line.len() + 1;
+
+        let buffer = match stream {
+            OutputStream::Stdout => &mut self.stdout,
+            OutputStream::Stderr => &mut self.stderr,
+        };
+        buffer.push_str(&line);
+        buffer.push('\n');
 
-        Ok(result.join("\n"))
+        self.emit(match stream {
+            OutputStream::Stdout => CommandOutputEvent::Stdout { line },
+            OutputStream::Stderr => CommandOutputEvent::Stderr { line },
+        });
     }
+
+    fn emit(&self, event: CommandOutputEvent) {
+        if let Some(events) = self.events {
+            // A dropped receiver only means nobody is watching live output
+            let _ = events.send(event);
+        }
+    }
+}
+
+/// Lines of a child's output, decoded lossily so invalid UTF-8 doesn't end
+/// the stream
+struct OutputLines<R> {
+    reader: BufReader<R>,
+    /// Bytes of the line being read; kept across calls so a read cancelled
+    /// by `select!` loses nothing
+    buf: Vec<u8>,
+}
+
+impl<R: AsyncRead + Unpin> OutputLines<R> {
+    fn new(stream: R) -> Self {
+        Self {
+            reader: BufReader::new(stream),
+            buf: Vec::new(),
+        }
+    }
+
+    /// The next line without its line ending, or `None` at the end of the
+    /// stream
+    async fn next_line(&mut self) -> Option<String> {
+        let read = self.reader.read_until(b'\n', &mut self.buf).await;
+        if matches!(read, Err(_) | Ok(0)) && self.buf.is_empty() {
+            return None;
+        }
+        if self.buf.last() == Some(&b'\n') {
+            self.buf.pop();
+            if self.buf.last() == Some(&b'\r') {
+                self.buf.pop();
+            }
+        }
+        let line = String::from_utf8_lossy(&self.buf).into_owned();
+        self.buf.clear();
+        Some(line)
+    }
+}
+
+/// Read the next line from an optional stream; a closed stream never resolves
+/// so `select!` keeps polling the others
+async fn next_line<R: AsyncRead + Unpin>(lines: &mut Option<OutputLines<R>>) -> Option<String> {
+    match lines {
+        Some(lines) => lines.next_line().await,
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(unix)]
+fn kill_process_group(child: &mut Child) {
+    if let Some(pid) = child.id() {
+        // SAFETY: signalling a process group has no memory-safety requirements;
+        // the child was spawned as the leader of its own group
+        unsafe {
+            libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_process_group(child: &mut Child) {
+    let _ = child.start_kill();
 }
 
 #[async_trait]
@@ -234,6 +550,7 @@ impl CommandExecutor for RunCommand {
             description: format!("Execute command: {}", args.command),
             actions: vec![PreviewAction::ExecuteShell {
                 command: args.command.clone(),
+                risk_reasons: classify_command(&args.command).1,
             }],
             requires_approval: true, // Shell execution should require approval
         })
@@ -244,27 +561,16 @@ impl CommandExecutor for RunCommand {
         args: &serde_json::Value,
         context: &CommandContext,
     ) -> Result<CommandResult> {
-        let args: RunArgs = serde_json::from_value(args.clone()).map_err(|e| {
-            FennecError::Command(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("Invalid run arguments: {}", e),
-            )))
-        })?;
+        self.run(args, context, None).await
+    }
 
-        match self.execute_command(&args, context).await {
-            Ok(output) => Ok(CommandResult {
-                command_id: Uuid::new_v4(),
-                success: true,
-                output,
-                error: None,
-            }),
-            Err(e) => Ok(CommandResult {
-                command_id: Uuid::new_v4(),
-                success: false,
-                output: String::new(),
-                error: Some(e.to_string()),
-            }),
-        }
+    async fn execute_streaming(
+        &self,
+        args: &serde_json::Value,
+        context: &CommandContext,
+        events: OutputSender,
+    ) -> Result<CommandResult> {
+        self.run(args, context, Some(events)).await
     }
 
     fn validate_args(&self, args: &serde_json::Value) -> Result<()> {
@@ -327,6 +633,37 @@ mod tests {
         assert!(command.validate_args(&long_timeout).is_err());
     }
 
+    #[tokio::test]
+    async fn test_preview_explains_risk() {
+        let command = RunCommand::new();
+        let context = CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: None,
+            sandbox_level: SandboxLevel::FullAccess,
+            dry_run: false,
+            preview_only: true,
+            cancellation_token: CancellationToken::new(),
+            action_log: None,
+            timeout: None,
+            progress: None,
+        };
+
+        let args = serde_json::json!({ "command": "rsync -a src/ backup/" });
+        let preview = command.preview(&args, &context).await.unwrap();
+        let [PreviewAction::ExecuteShell { risk_reasons, .. }] = preview.actions.as_slice() else {
+            panic!("expected one shell command, got {:?}", preview.actions);
+        };
+        assert_eq!(risk_reasons, &["matches high risk pattern `rsync`"]);
+
+        let args = serde_json::json!({ "command": "echo hello" });
+        let preview = command.preview(&args, &context).await.unwrap();
+        let [PreviewAction::ExecuteShell { risk_reasons, .. }] = preview.actions.as_slice() else {
+            panic!("expected one shell command, got {:?}", preview.actions);
+        };
+        assert!(risk_reasons.is_empty(), "{:?}", risk_reasons);
+    }
+
     #[tokio::test]
     async fn test_run_command_security() {
         let command = RunCommand::new();
@@ -340,6 +677,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         // Dangerous command should be rejected
@@ -372,10 +711,220 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
         assert!(result.success);
         assert!(result.output.contains("hello world"));
     }
+
+    fn streaming_context(workspace: &std::path::Path) -> CommandContext {
+        CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: Some(workspace.to_string_lossy().to_string()),
+            sandbox_level: SandboxLevel::FullAccess,
+            dry_run: false,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log: None,
+            timeout: None,
+            progress: None,
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_command_keeps_output_after_invalid_utf8() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let output = temp_dir.path().join("output.txt");
+        std::fs::write(&output, b"bad \xff byte\nsecond\nthird\n").unwrap();
+
+        let result = RunCommand::new()
+            .execute(
+                &serde_json::json!({ "command": format!("cat {}", output.display()) }),
+                &streaming_context(temp_dir.path()),
+            )
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        assert!(
+            result.output.contains("bad \u{FFFD} byte\nsecond\nthird"),
+            "{}",
+            result.output
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_command_reports_line_counts() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut context = streaming_context(temp_dir.path());
+        context.progress = Some(ProgressReporter::new({
+            let seen = seen.clone();
+            move |p: &crate::progress::CommandProgress| {
+                seen.lock().unwrap().extend(p.message.clone());
+            }
+        }));
+
+        let result = RunCommand::new()
+            .execute(&serde_json::json!({ "command": "seq 3" }), &context)
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(
+            seen.lock().unwrap().last().map(String::as_str),
+            Some("3 lines of output")
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_command_streams_lines_incrementally() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("slow.sh"),
+            "for i in 1 2 3; do echo line$i; sleep 0.3; done\necho oops >&2\n",
+        )
+        .unwrap();
+
+        let context = streaming_context(temp_dir.path());
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let handle = tokio::spawn(async move {
+            RunCommand::new()
+                .execute_streaming(
+                    &serde_json::json!({ "command": "sh slow.sh" }),
+                    &context,
+                    tx,
+                )
+                .await
+                .unwrap()
+        });
+
+        let first = rx.recv().await.unwrap();
+        assert_eq!(
+            first,
+            CommandOutputEvent::Stdout {
+                line: "line1".to_string()
+            }
+        );
+        assert!(!handle.is_finished(), "first line arrived only after exit");
+
+        let result = handle.await.unwrap();
+        let mut rest = Vec::new();
+        while let Some(event) = rx.recv().await {
+            rest.push(event);
+        }
+        assert_eq!(rest.len(), 3);
+        assert_eq!(
+            rest[2],
+            CommandOutputEvent::Stderr {
+                line: "oops".to_string()
+            }
+        );
+
+        assert!(result.success);
+        assert!(result.output.contains("line3"));
+        let report: RunReport = serde_json::from_value(result.data.unwrap()).unwrap();
+        assert_eq!(report.exit_code, Some(0));
+        assert!(!report.truncated);
+        assert!(report.duration_ms >= 900);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_run_command_cancellation_kills_process_group() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("hang.sh"),
+            "sleep 30 &\necho $! > child.pid\necho started\nwait\n",
+        )
+        .unwrap();
+
+        let context = streaming_context(temp_dir.path());
+        let token = context.cancellation_token.clone();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let started = std::time::Instant::now();
+        let handle = tokio::spawn(async move {
+            RunCommand::new()
+                .execute_streaming(
+                    &serde_json::json!({ "command": "sh hang.sh" }),
+                    &context,
+                    tx,
+                )
+                .await
+                .unwrap()
+        });
+
+        assert_eq!(
+            rx.recv().await.unwrap(),
+            CommandOutputEvent::Stdout {
+                line: "started".to_string()
+            }
+        );
+        token.cancel();
+
+        let result = handle.await.unwrap();
+        assert!(started.elapsed() < std::time::Duration::from_secs(10));
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("cancelled"));
+        let report: RunReport = serde_json::from_value(result.data.unwrap()).unwrap();
+        assert!(report.cancelled);
+        assert_eq!(report.exit_code, None);
+
+        // The background `sleep` shares the group and must be gone too
+        let pid = std::fs::read_to_string(temp_dir.path().join("child.pid")).unwrap();
+        let stat = format!("/proc/{}/stat", pid.trim());
+        let mut alive = true;
+        for _ in 0..50 {
+            alive = std::fs::read_to_string(&stat)
+                .map(|s| !s.contains(") Z "))
+                .unwrap_or(false);
+            if !alive {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert!(!alive, "grandchild survived cancellation");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_command_output_cap() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("noisy.sh"),
+            "for i in $(seq 1 100); do echo line$i; done\n",
+        )
+        .unwrap();
+
+        let context = streaming_context(temp_dir.path());
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let args = serde_json::json!({ "command": "sh noisy.sh", "max_output_bytes": 30 });
+        let result = RunCommand::new()
+            .execute_streaming(&args, &context, tx)
+            .await
+            .unwrap();
+
+        let mut events = Vec::new();
+        while let Some(event) = rx.recv().await {
+            events.push(event);
+        }
+        assert_eq!(
+            events.last(),
+            Some(&CommandOutputEvent::Truncated { limit_bytes: 30 })
+        );
+        assert_eq!(events.len(), 6);
+
+        assert!(result.success);
+        assert!(result.output.contains("line5"));
+        assert!(!result.output.contains("line6"));
+        assert!(result.output.contains("[output truncated after 30 bytes]"));
+        let report: RunReport = serde_json::from_value(result.data.unwrap()).unwrap();
+        assert!(report.truncated);
+    }
 }
@@ -0,0 +1,233 @@
+//! Built-in project templates used by the create command to scaffold
+//! multi-file structures.
+//!
+//! Template paths and contents may reference `{{name}}` and `{{snake_name}}`;
+//! any other `{{...}}` sequence (such as GitHub Actions expressions) is left
+//! untouched.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// A single file produced by a template
+#[derive(Debug, Clone, Copy)]
+pub struct TemplateFile {
+    /// Path relative to the scaffold root
+    pub path: &'static str,
+    pub content: &'static str,
+}
+
+/// A named multi-file template
+#[derive(Debug, Clone, Copy)]
+pub struct ProjectTemplate {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub files: &'static [TemplateFile],
+    /// Follow-up steps shown after the files are written
+    pub notes: Option<&'static str>,
+}
+
+const RUST_BIN: ProjectTemplate = ProjectTemplate {
+    name: "rust-bin",
+    description: "Rust binary crate with a main.rs",
+    files: &[
+        TemplateFile {
+            path: "Cargo.toml",
+            content: r#"[package]
+name = "{{name}}"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+"#,
+        },
+        TemplateFile {
+            path: "src/main.rs",
+            content: r#"fn main() {
+    println!("Hello from {{name}}!");
+}
+"#,
+        },
+        TemplateFile {
+            path: ".gitignore",
+            content: "/target\n",
+        },
+    ],
+    notes: Some("Add the crate to [workspace] members if it lives inside a workspace."),
+};
+
+const RUST_MODULE: ProjectTemplate = ProjectTemplate {
+    name: "rust-module",
+    description: "Rust library module with a colocated test module",
+    files: &[TemplateFile {
+        path: "{{snake_name}}.rs",
+        content: r#"//! {{name}}
+
+/// Entry point for the {{snake_name}} module
+pub fn {{snake_name}}() -> &'static str {
+    "{{name}}"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_{{snake_name}}() {
+        assert_eq!({{snake_name}}(), "{{name}}");
+    }
+}
+"#,
+    }],
+    notes: Some("Declare it with `mod {{snake_name}};` in the parent module."),
+};
+
+const GITHUB_WORKFLOW: ProjectTemplate = ProjectTemplate {
+    name: "github-workflow",
+    description: "GitHub Actions workflow running fmt, clippy and tests",
+    files: &[TemplateFile {
+        path: ".github/workflows/{{snake_name}}.yml",
+        content: r#"name: {{name}}
+
+on:
+  push:
+    branches: [main]
+  pull_request:
+
+concurrency:
+  group: {{snake_name}}-${{ github.ref }}
+  cancel-in-progress: true
+
+env:
+  CARGO_TERM_COLOR: always
+
+jobs:
+  check:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+      - uses: dtolnay/rust-toolchain@stable
+        with:
+          components: rustfmt, clippy
+      - uses: Swatinem/rust-cache@v2
+      - run: cargo fmt --all -- --check
+      - run: cargo clippy --workspace --all-targets -- -D warnings
+      - run: cargo test --workspace
+"#,
+    }],
+    notes: None,
+};
+
+/// Templates shipped with Fennec
+pub const BUILTIN_TEMPLATES: &[ProjectTemplate] = &[RUST_BIN, RUST_MODULE, GITHUB_WORKFLOW];
+
+/// Look up a built-in template by name
+pub fn find_template(name: &str) -> Option<&'static ProjectTemplate> {
+    BUILTIN_TEMPLATES.iter().find(|t| t.name == name)
+}
+
+/// Comma-separated names of the built-in templates, for error messages
+pub fn template_names() -> String {
+    BUILTIN_TEMPLATES
+        .iter()
+        .map(|t| t.name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Convert a name such as `my-tool` or `HttpClient` to `my_tool` / `http_client`
+pub fn to_snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len());
+    let mut prev: Option<char> = None;
+
+    for c in name.chars() {
+        if c.is_alphanumeric() {
+            let boundary =
+                c.is_uppercase() && prev.is_some_and(|p| p.is_lowercase() || p.is_ascii_digit());
+            if boundary && !snake.ends_with('_') {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else if !snake.is_empty() && !snake.ends_with('_') {
+            snake.push('_');
+        }
+        prev = Some(c);
+    }
+
+    let snake = snake.trim_end_matches('_');
+    if snake.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", snake)
+    } else {
+        snake.to_string()
+    }
+}
+
+/// Values substituted into template paths and contents
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateVariables {
+    pub name: String,
+    pub snake_name: String,
+}
+
+impl TemplateVariables {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            snake_name: to_snake_case(name),
+        }
+    }
+
+    /// Replace known `{{variable}}` placeholders in `text`
+    pub fn render(&self, text: &str) -> String {
+        text.replace("{{name}}", &self.name)
+            .replace("{{snake_name}}", &self.snake_name)
+    }
+}
+
+impl ProjectTemplate {
+    /// Render every file, returning paths relative to `root`
+    pub fn render(&self, root: &Path, variables: &TemplateVariables) -> Vec<(PathBuf, String)> {
+        self.files
+            .iter()
+            .map(|file| {
+                (
+                    root.join(variables.render(file.path)),
+                    variables.render(file.content),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_snake_case() {
+        assert_eq!(to_snake_case("my-tool"), "my_tool");
+        assert_eq!(to_snake_case("HttpClient"), "http_client");
+        assert_eq!(to_snake_case("CI Checks"), "ci_checks");
+        assert_eq!(to_snake_case("v2Parser"), "v2_parser");
+        assert_eq!(to_snake_case("3d-render"), "_3d_render");
+        assert_eq!(to_snake_case("--"), "");
+    }
+
+    #[test]
+    fn test_render_leaves_unknown_expressions() {
+        let variables = TemplateVariables::new("ci");
+        let files = find_template("github-workflow")
+            .unwrap()
+            .render(Path::new("."), &variables);
+
+        assert_eq!(files[0].0, Path::new("./.github/workflows/ci.yml"));
+        assert!(files[0].1.starts_with("name: ci\n"));
+        assert!(files[0].1.contains("group: ci-${{ github.ref }}"));
+    }
+
+    #[test]
+    fn test_find_template() {
+        assert!(find_template("rust-bin").is_some());
+        assert!(find_template("nope").is_none());
+        assert_eq!(template_names(), "rust-bin, rust-module, github-workflow");
+    }
+}
@@ -5,13 +5,60 @@ use fennec_core::{
     error::FennecError,
 };
 use fennec_security::SandboxLevel;
+use fennec_telemetry::instrument_with_context;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
-use walkdir::WalkDir;
+
+/// Directories that are never searched, even when no `.gitignore` excludes them.
+const ALWAYS_SKIPPED_DIRS: &[&str] = &[".git", "target", "node_modules"];
+
+/// Buffered matches before workers block waiting for the consumer.
+const RESULT_CHANNEL_CAPACITY: usize = 256;
+
+/// Added to the score of matches in a file whose name matches the query.
+const FILENAME_MATCH_BOOST: f64 = 2.0;
+
+/// Added to the score of matches in a file whose directory path matches the query.
+const PATH_MATCH_BOOST: f64 = 1.0;
+
+/// Lines either side of a match within which other matches count as close.
+const PROXIMITY_WINDOW: usize = 5;
+
+/// Added for an adjacent match, shrinking linearly to the edge of the window.
+const PROXIMITY_BOOST: f64 = 0.5;
+
+/// Cap on the proximity bonus, so one dense block cannot swamp filename matches.
+const MAX_PROXIMITY_BOOST: f64 = 2.0;
+
+/// Multiplier for matches outside test code when `prefer_non_test` is set.
+const NON_TEST_BOOST: f64 = 1.1;
+
+/// Directory names that hold test code.
+const TEST_DIRS: &[&str] = &["test", "tests", "benches", "spec", "__tests__"];
+
+/// Order of content search results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchSort {
+    /// Path, then line number
+    Path,
+    /// Highest score first, then path and line number
+    #[default]
+    Score,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchArgs {
+    /// Text to search for; in structural mode, an optional filter on captured text
+    #[serde(default)]
     pub query: String,
     #[serde(default)]
     pub pattern: Option<String>,
@@ -25,18 +72,139 @@ pub struct SearchArgs {
     pub context_lines: usize,
     #[serde(default)]
     pub filename_only: bool,
+    /// Glob patterns (relative to the workspace) a file must match to be searched
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Glob patterns (relative to the workspace) that exclude files and directories
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Skip files larger than this many bytes
+    #[serde(default)]
+    pub max_filesize: Option<u64>,
+    /// Search hidden files and directories (still honours `.gitignore`)
+    #[serde(default)]
+    pub hidden: bool,
+    /// Number of files searched concurrently (defaults to the available CPUs)
+    #[serde(default)]
+    pub concurrency: Option<usize>,
+    /// Tree-sitter query for structural search (requires the `structural-search` feature)
+    #[serde(default)]
+    pub syntax_query: Option<String>,
+    /// Language the syntax query is written for (defaults to Rust)
+    #[serde(default)]
+    pub syntax_language: Option<String>,
+    /// Order of content matches (`path` or `score`)
+    #[serde(default)]
+    pub sort: SearchSort,
+    /// Rank matches in non-test code slightly above matches in tests
+    #[serde(default = "default_prefer_non_test")]
+    pub prefer_non_test: bool,
 }
 
 fn default_max_results() -> usize {
     100
 }
 
+fn default_prefer_non_test() -> bool {
+    true
+}
+
+fn default_concurrency() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResult {
     pub file_path: PathBuf,
     pub line_number: usize,
     pub line_content: String,
     pub match_count: usize,
+    /// Capture name for structural matches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capture: Option<String>,
+    /// Node span for structural matches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<NodeSpan>,
+    /// Relevance from 0 to 1, relative to the best match of the search
+    #[serde(default)]
+    pub score: f64,
+}
+
+/// Zero-based row/column span of a syntax node
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeSpan {
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub start_byte: usize,
+    pub end_byte: usize,
+}
+
+#[cfg(feature = "structural-search")]
+type StructuralQuery = crate::syntax::SyntaxQuery;
+#[cfg(not(feature = "structural-search"))]
+type StructuralQuery = std::convert::Infallible;
+
+/// Tests a file or directory name against the search query.
+type NameMatcher = Box<dyn Fn(&str) -> bool>;
+
+/// Include/exclude glob filters applied to workspace-relative paths.
+#[derive(Debug, Clone, Default)]
+struct PathFilter {
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+}
+
+impl PathFilter {
+    fn new(include: &[String], exclude: &[String]) -> Result<Self> {
+        Ok(Self {
+            include: Self::build_set(include)?,
+            exclude: Self::build_set(exclude)?,
+        })
+    }
+
+    fn build_set(patterns: &[String]) -> Result<Option<GlobSet>> {
+        if patterns.is_empty() {
+            return Ok(None);
+        }
+
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            let glob = Glob::new(pattern).map_err(|e| {
+                FennecError::Command(Box::new(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("Invalid glob pattern '{}': {}", pattern, e),
+                )))
+            })?;
+            builder.add(glob);
+        }
+
+        let set = builder.build().map_err(|e| {
+            FennecError::Command(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Invalid glob patterns: {}", e),
+            )))
+        })?;
+        Ok(Some(set))
+    }
+
+    fn is_excluded(&self, relative: &Path) -> bool {
+        self.exclude
+            .as_ref()
+            .map(|set| set.is_match(relative))
+            .unwrap_or(false)
+    }
+
+    fn is_included(&self, relative: &Path) -> bool {
+        self.include
+            .as_ref()
+            .map(|set| set.is_match(relative))
+            .unwrap_or(true)
+            && !self.is_excluded(relative)
+    }
 }
 
 pub struct SearchCommand {
@@ -57,6 +225,8 @@ impl SearchCommand {
                 sandbox_level_required: SandboxLevel::ReadOnly,
                 supports_preview: true,
                 supports_dry_run: false,
+                timeout: None,
+                alias_of: None,
             },
         }
     }
@@ -171,6 +341,9 @@ impl SearchCommand {
                     line_number: idx + 1,
                     line_content: line.to_string(),
                     match_count,
+                    capture: None,
+                    span: None,
+                    score: 0.0,
                 });
             }
         }
@@ -178,40 +351,169 @@ impl SearchCommand {
         Ok(results)
     }
 
-    fn search_filenames(
-        workspace_path: &Path,
+    /// Compile the structural query, if one was requested.
+    #[cfg(feature = "structural-search")]
+    fn compile_syntax_query(args: &SearchArgs) -> Result<Option<Arc<StructuralQuery>>> {
+        use crate::syntax::{SyntaxLanguage, SyntaxQuery};
+
+        let Some(source) = &args.syntax_query else {
+            return Ok(None);
+        };
+        let invalid = |e: crate::syntax::SyntaxError| {
+            FennecError::Command(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                e.to_string(),
+            )))
+        };
+        let language = SyntaxLanguage::from_name(args.syntax_language.as_deref().unwrap_or("rust"))
+            .map_err(invalid)?;
+        let query = SyntaxQuery::new(language, source).map_err(invalid)?;
+        Ok(Some(Arc::new(query)))
+    }
+
+    #[cfg(not(feature = "structural-search"))]
+    fn compile_syntax_query(args: &SearchArgs) -> Result<Option<Arc<StructuralQuery>>> {
+        if args.syntax_query.is_some() {
+            return Err(FennecError::Command(Box::new(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "Structural search is unavailable: built without the `structural-search` feature",
+            )))
+            .into());
+        }
+        Ok(None)
+    }
+
+    #[cfg(feature = "structural-search")]
+    fn syntax_matches_path(query: &StructuralQuery, path: &Path) -> bool {
+        query.language().matches_path(path)
+    }
+
+    #[cfg(not(feature = "structural-search"))]
+    fn syntax_matches_path(query: &StructuralQuery, _path: &Path) -> bool {
+        match *query {}
+    }
+
+    /// Run a structural query over one file, keeping captures whose text
+    /// contains `query` (all captures when `query` is empty).
+    #[cfg(feature = "structural-search")]
+    fn search_syntax_in_file(
+        path: &Path,
+        syntax_query: &StructuralQuery,
         query: &str,
         case_insensitive: bool,
-        max_results: usize,
-    ) -> Result<Vec<PathBuf>> {
-        let mut results = Vec::new();
-        let search_query = if case_insensitive {
+    ) -> Result<Vec<SearchResult>> {
+        let content = std::fs::read_to_string(path)?;
+        let captures = syntax_query.captures(&content).map_err(|e| {
+            FennecError::Command(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                e.to_string(),
+            )))
+        })?;
+        let needle = if case_insensitive {
             query.to_lowercase()
         } else {
             query.to_string()
         };
 
-        for entry in WalkDir::new(workspace_path)
-            .max_depth(10)
+        Ok(captures
             .into_iter()
-            .filter_entry(|e| {
-                !e.file_name()
-                    .to_str()
-                    .map(|s| s.starts_with('.') || s == "target" || s == "node_modules")
-                    .unwrap_or(false)
+            .filter(|c| {
+                needle.is_empty()
+                    || if case_insensitive {
+                        c.text.to_lowercase().contains(&needle)
+                    } else {
+                        c.text.contains(&needle)
+                    }
+            })
+            .map(|c| SearchResult {
+                file_path: path.to_path_buf(),
+                line_number: c.span.start_line + 1,
+                line_content: c.text.lines().next().unwrap_or_default().to_string(),
+                match_count: 1,
+                capture: Some(c.capture),
+                span: Some(c.span),
+                score: 0.0,
             })
-            .filter_map(|e| e.ok())
-        {
-            if let Some(filename) = entry.file_name().to_str() {
-                let compare_name = if case_insensitive {
+            .collect())
+    }
+
+    #[cfg(not(feature = "structural-search"))]
+    fn search_syntax_in_file(
+        _path: &Path,
+        syntax_query: &StructuralQuery,
+        _query: &str,
+        _case_insensitive: bool,
+    ) -> Result<Vec<SearchResult>> {
+        match *syntax_query {}
+    }
+
+    /// Walk the workspace honouring `.gitignore`, hidden-file and glob settings,
+    /// yielding the files that are eligible for searching.
+    fn walk_files(
+        workspace_path: &Path,
+        args: &SearchArgs,
+    ) -> Result<impl Iterator<Item = PathBuf>> {
+        let filter = PathFilter::new(&args.include, &args.exclude)?;
+        let root = workspace_path.to_path_buf();
+        let file_root = root.clone();
+        let dir_filter = filter.clone();
+
+        let walker = WalkBuilder::new(workspace_path)
+            .max_depth(Some(10))
+            .hidden(!args.hidden)
+            .git_ignore(true)
+            .git_exclude(true)
+            .parents(true)
+            .require_git(false)
+            .max_filesize(args.max_filesize)
+            .filter_entry(move |entry| {
+                if entry.depth() == 0 {
+                    return true;
+                }
+                let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+                if is_dir
+                    && entry
+                        .file_name()
+                        .to_str()
+                        .map(|name| ALWAYS_SKIPPED_DIRS.contains(&name))
+                        .unwrap_or(false)
+                {
+                    return false;
+                }
+                let relative = entry.path().strip_prefix(&root).unwrap_or(entry.path());
+                !dir_filter.is_excluded(relative)
+            })
+            .build();
+
+        Ok(walker
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().map(|t| t.is_file()).unwrap_or(false))
+            .map(|entry| entry.into_path())
+            .filter(move |path| {
+                let relative = path.strip_prefix(&file_root).unwrap_or(path);
+                filter.is_included(relative)
+            }))
+    }
+
+    fn search_filenames(workspace_path: &Path, args: &SearchArgs) -> Result<Vec<PathBuf>> {
+        let mut results = Vec::new();
+        let search_query = if args.case_insensitive {
+            args.query.to_lowercase()
+        } else {
+            args.query.clone()
+        };
+
+        for path in Self::walk_files(workspace_path, args)? {
+            if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
+                let compare_name = if args.case_insensitive {
                     filename.to_lowercase()
                 } else {
                     filename.to_string()
                 };
 
                 if compare_name.contains(&search_query) {
-                    results.push(entry.path().to_path_buf());
-                    if results.len() >= max_results {
+                    results.push(path);
+                    if results.len() >= args.max_results {
                         break;
                     }
                 }
@@ -221,6 +523,297 @@ impl SearchCommand {
         Ok(results)
     }
 
+    /// Search file contents in parallel, streaming matches as they are found.
+    ///
+    /// The workspace is walked on a blocking thread while up to `concurrency`
+    /// workers read and match files. The returned handle resolves to the number
+    /// of files searched, or an `Interrupted` error if the token was cancelled.
+    pub fn search_stream(
+        workspace_path: &Path,
+        args: &SearchArgs,
+        cancellation_token: CancellationToken,
+    ) -> Result<(mpsc::Receiver<SearchResult>, JoinHandle<Result<usize>>)> {
+        let files = Self::walk_files(workspace_path, args)?;
+        let syntax_query = Self::compile_syntax_query(args)?;
+        let concurrency = args.concurrency.unwrap_or_else(default_concurrency).max(1);
+        let (result_tx, result_rx) = mpsc::channel(RESULT_CHANNEL_CAPACITY);
+        let args = args.clone();
+
+        let handle = tokio::spawn(instrument_with_context(async move {
+            let (path_tx, path_rx) = std::sync::mpsc::sync_channel::<PathBuf>(concurrency * 4);
+            let path_rx = Arc::new(Mutex::new(path_rx));
+            let files_searched = Arc::new(AtomicUsize::new(0));
+            let matches_found = Arc::new(AtomicUsize::new(0));
+
+            let walker_token = cancellation_token.clone();
+            let pattern = args.pattern.clone();
+            let walker_query = syntax_query.clone();
+            let walker = tokio::task::spawn_blocking(move || {
+                for path in files {
+                    if walker_token.is_cancelled() {
+                        break;
+                    }
+                    if !Self::should_search_file(&path, &pattern) || !Self::is_text_file(&path) {
+                        continue;
+                    }
+                    if let Some(query) = &walker_query {
+                        if !Self::syntax_matches_path(query, &path) {
+                            continue;
+                        }
+                    }
+                    if path_tx.send(path).is_err() {
+                        break;
+                    }
+                }
+            });
+
+            let mut workers = Vec::with_capacity(concurrency);
+            for _ in 0..concurrency {
+                let path_rx = Arc::clone(&path_rx);
+                let files_searched = Arc::clone(&files_searched);
+                let matches_found = Arc::clone(&matches_found);
+                let result_tx = result_tx.clone();
+                let token = cancellation_token.clone();
+                let args = args.clone();
+                let syntax_query = syntax_query.clone();
+
+                workers.push(tokio::task::spawn_blocking(move || loop {
+                    if token.is_cancelled()
+                        || matches_found.load(Ordering::Relaxed) >= args.max_results
+                    {
+                        break;
+                    }
+                    let path = match path_rx.lock() {
+                        Ok(rx) => match rx.recv() {
+                            Ok(path) => path,
+                            Err(_) => break,
+                        },
+                        Err(_) => break,
+                    };
+
+                    files_searched.fetch_add(1, Ordering::Relaxed);
+                    let results = match &syntax_query {
+                        Some(query) => Self::search_syntax_in_file(
+                            &path,
+                            query,
+                            &args.query,
+                            args.case_insensitive,
+                        ),
+                        None => Self::search_in_file(
+                            &path,
+                            &args.query,
+                            args.case_insensitive,
+                            args.regex,
+                            args.context_lines,
+                        ),
+                    };
+                    if let Ok(results) = results {
+                        matches_found.fetch_add(results.len(), Ordering::Relaxed);
+                        for result in results {
+                            if result_tx.blocking_send(result).is_err() {
+                                return;
+                            }
+                        }
+                    }
+                }));
+            }
+            drop(result_tx);
+
+            for worker in workers {
+                let _ = worker.await;
+            }
+            // Workers that stopped early drop their end of the path channel,
+            // which unblocks the walker if it is waiting to send.
+            drop(path_rx);
+            let _ = walker.await;
+
+            if cancellation_token.is_cancelled() {
+                return Err(FennecError::Command(Box::new(std::io::Error::new(
+                    std::io::ErrorKind::Interrupted,
+                    "Search cancelled",
+                )))
+                .into());
+            }
+
+            Ok(files_searched.load(Ordering::Relaxed))
+        }));
+
+        Ok((result_rx, handle))
+    }
+
+    /// Whether a file or directory name matches the search query.
+    fn name_matcher(args: &SearchArgs) -> Option<NameMatcher> {
+        if args.query.is_empty() || args.syntax_query.is_some() {
+            return None;
+        }
+        if args.regex {
+            let pattern = if args.case_insensitive {
+                format!("(?i){}", args.query)
+            } else {
+                args.query.clone()
+            };
+            let regex = regex::Regex::new(&pattern).ok()?;
+            Some(Box::new(move |name| regex.is_match(name)))
+        } else if args.case_insensitive {
+            let query = args.query.to_lowercase();
+            Some(Box::new(move |name| name.to_lowercase().contains(&query)))
+        } else {
+            let query = args.query.clone();
+            Some(Box::new(move |name| name.contains(&query)))
+        }
+    }
+
+    /// Whether a workspace-relative path is test code.
+    fn is_test_path(relative: &Path) -> bool {
+        let in_test_dir = relative
+            .parent()
+            .map(|dir| {
+                dir.components().any(|c| {
+                    c.as_os_str()
+                        .to_str()
+                        .map(|name| TEST_DIRS.contains(&name))
+                        .unwrap_or(false)
+                })
+            })
+            .unwrap_or(false);
+        let stem = relative
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        in_test_dir
+            || stem.starts_with("test_")
+            || stem.ends_with("_test")
+            || stem.ends_with("_tests")
+            || stem.ends_with(".test")
+            || stem.ends_with(".spec")
+    }
+
+    /// Score every result, normalized so the best match scores 1.
+    ///
+    /// A match scores 1 plus a little for each extra occurrence on its line,
+    /// plus boosts for the query appearing in the file name or directory
+    /// path and for other matches close by in the same file. Matches outside
+    /// test code are then scaled up slightly if `prefer_non_test` is set.
+    fn score_results(workspace_path: &Path, args: &SearchArgs, results: &mut [SearchResult]) {
+        results.sort_by(|a, b| {
+            a.file_path
+                .cmp(&b.file_path)
+                .then(a.line_number.cmp(&b.line_number))
+        });
+        let matches_name = Self::name_matcher(args);
+
+        let mut start = 0;
+        while start < results.len() {
+            let end = start
+                + results[start..]
+                    .iter()
+                    .take_while(|r| r.file_path == results[start].file_path)
+                    .count();
+            let relative = results[start]
+                .file_path
+                .strip_prefix(workspace_path)
+                .unwrap_or(&results[start].file_path)
+                .to_path_buf();
+
+            let mut path_boost = 0.0;
+            if let Some(matches_name) = &matches_name {
+                let file_name = relative
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or_default();
+                let dir = relative
+                    .parent()
+                    .map(|p| p.to_string_lossy().replace('\\', "/"))
+                    .unwrap_or_default();
+                if matches_name(file_name) {
+                    path_boost += FILENAME_MATCH_BOOST;
+                } else if !dir.is_empty() && matches_name(&dir) {
+                    path_boost += PATH_MATCH_BOOST;
+                }
+            }
+            let multiplier = if args.prefer_non_test && !Self::is_test_path(&relative) {
+                NON_TEST_BOOST
+            } else {
+                1.0
+            };
+
+            let lines: Vec<usize> = results[start..end].iter().map(|r| r.line_number).collect();
+            for (i, result) in results[start..end].iter_mut().enumerate() {
+                let proximity: f64 = lines
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .map(|(_, &line)| line.abs_diff(result.line_number))
+                    .filter(|&distance| distance <= PROXIMITY_WINDOW)
+                    .map(|distance| {
+                        PROXIMITY_BOOST * (PROXIMITY_WINDOW + 1 - distance.max(1)) as f64
+                            / PROXIMITY_WINDOW as f64
+                    })
+                    .sum();
+                let occurrences = 1.0 + 0.25 * (result.match_count.clamp(1, 5) - 1) as f64;
+                result.score =
+                    (occurrences + path_boost + proximity.min(MAX_PROXIMITY_BOOST)) * multiplier;
+            }
+            start = end;
+        }
+
+        let best = results.iter().map(|r| r.score).fold(0.0, f64::max);
+        if best > 0.0 {
+            for result in results.iter_mut() {
+                result.score /= best;
+            }
+        }
+    }
+
+    /// Score and order results as `args.sort` asks. Equal scores fall back
+    /// to path and line order, so the output is deterministic.
+    fn rank_results(workspace_path: &Path, args: &SearchArgs, results: &mut [SearchResult]) {
+        Self::score_results(workspace_path, args, results);
+        if args.sort == SearchSort::Score {
+            results.sort_by(|a, b| {
+                b.score
+                    .total_cmp(&a.score)
+                    .then_with(|| a.file_path.cmp(&b.file_path))
+                    .then(a.line_number.cmp(&b.line_number))
+            });
+        }
+    }
+
+    /// Run a content search to completion, returning ranked matches together
+    /// with the number of files searched.
+    ///
+    /// Every match is collected before ranking and truncating to
+    /// `max_results`; stopping the workers early would keep whichever
+    /// matches happened to arrive first.
+    async fn search_contents(
+        workspace_path: &Path,
+        args: &SearchArgs,
+        cancellation_token: &CancellationToken,
+    ) -> Result<(Vec<SearchResult>, usize)> {
+        let uncapped = SearchArgs {
+            max_results: usize::MAX,
+            ..args.clone()
+        };
+        let (mut results_rx, handle) =
+            Self::search_stream(workspace_path, &uncapped, cancellation_token.clone())?;
+
+        let mut all_results = Vec::new();
+        while let Some(result) = results_rx.recv().await {
+            all_results.push(result);
+        }
+        let files_searched = handle.await.map_err(|e| {
+            FennecError::Command(Box::new(std::io::Error::other(format!(
+                "Search task failed: {}",
+                e
+            ))))
+        })??;
+
+        Self::rank_results(workspace_path, args, &mut all_results);
+        all_results.truncate(args.max_results);
+
+        Ok((all_results, files_searched))
+    }
+
     async fn perform_search(&self, args: &SearchArgs, context: &CommandContext) -> Result<String> {
         let workspace_path_str = context.workspace_path.as_ref().ok_or_else(|| {
             FennecError::Command(Box::new(std::io::Error::new(
@@ -239,12 +832,7 @@ impl SearchCommand {
         }
 
         if args.filename_only {
-            let results = Self::search_filenames(
-                workspace_path,
-                &args.query,
-                args.case_insensitive,
-                args.max_results,
-            )?;
+            let results = Self::search_filenames(workspace_path, args)?;
 
             if results.is_empty() {
                 Ok("No files found matching query".to_string())
@@ -262,51 +850,8 @@ impl SearchCommand {
                 Ok(output)
             }
         } else {
-            let mut all_results = Vec::new();
-            let mut files_searched = 0;
-
-            for entry in WalkDir::new(workspace_path)
-                .max_depth(10)
-                .into_iter()
-                .filter_entry(|e| {
-                    !e.file_name()
-                        .to_str()
-                        .map(|s| s.starts_with('.') || s == "target" || s == "node_modules")
-                        .unwrap_or(false)
-                })
-                .filter_map(|e| e.ok())
-            {
-                if files_searched % 50 == 0 && context.cancellation_token.is_cancelled() {
-                    return Err(FennecError::Command(Box::new(std::io::Error::new(
-                        std::io::ErrorKind::Interrupted,
-                        "Search cancelled",
-                    )))
-                    .into());
-                }
-
-                let path = entry.path();
-                if !path.is_file()
-                    || !Self::should_search_file(path, &args.pattern)
-                    || !Self::is_text_file(path)
-                {
-                    continue;
-                }
-
-                files_searched += 1;
-
-                if let Ok(results) = Self::search_in_file(
-                    path,
-                    &args.query,
-                    args.case_insensitive,
-                    args.regex,
-                    args.context_lines,
-                ) {
-                    all_results.extend(results);
-                    if all_results.len() >= args.max_results {
-                        break;
-                    }
-                }
-            }
+            let (all_results, files_searched) =
+                Self::search_contents(workspace_path, args, &context.cancellation_token).await?;
 
             if all_results.is_empty() {
                 Ok(format!(
@@ -321,7 +866,31 @@ impl SearchCommand {
                     files_searched
                 );
                 for result in &all_results {
-                    if let Ok(rel_path) = result.file_path.strip_prefix(workspace_path) {
+                    let Ok(rel_path) = result.file_path.strip_prefix(workspace_path) else {
+                        continue;
+                    };
+                    if let (Some(capture), Some(span)) = (&result.capture, &result.span) {
+                        output.push_str(&format!(
+                            "{}:{} @{} [{}:{}-{}:{}]\n  > {}\n\n",
+                            rel_path.display(),
+                            result.line_number,
+                            capture,
+                            span.start_line + 1,
+                            span.start_column + 1,
+                            span.end_line + 1,
+                            span.end_column + 1,
+                            result.line_content.trim()
+                        ));
+                    } else if args.sort == SearchSort::Score {
+                        output.push_str(&format!(
+                            "{}:{} ({} matches, score {:.2})\n  > {}\n\n",
+                            rel_path.display(),
+                            result.line_number,
+                            result.match_count,
+                            result.score,
+                            result.line_content.trim()
+                        ));
+                    } else {
                         output.push_str(&format!(
                             "{}:{} ({} matches)\n  > {}\n\n",
                             rel_path.display(),
@@ -363,6 +932,12 @@ impl CommandExecutor for SearchCommand {
 
         let description = if args.filename_only {
             format!("Search for files matching '{}'", args.query)
+        } else if let Some(syntax_query) = &args.syntax_query {
+            format!(
+                "Structural search ({}) for `{}`",
+                args.syntax_language.as_deref().unwrap_or("rust"),
+                syntax_query
+            )
         } else {
             format!(
                 "Search for '{}' in {} files{}",
@@ -402,12 +977,14 @@ impl CommandExecutor for SearchCommand {
                 success: true,
                 output,
                 error: None,
+                data: None,
             }),
             Err(e) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
+                data: None,
             }),
         }
     }
@@ -420,7 +997,7 @@ impl CommandExecutor for SearchCommand {
             )))
         })?;
 
-        if args.query.trim().is_empty() {
+        if args.query.trim().is_empty() && args.syntax_query.is_none() {
             return Err(FennecError::Command(Box::new(std::io::Error::new(
                 std::io::ErrorKind::InvalidInput,
                 "Search query cannot be empty",
@@ -428,6 +1005,9 @@ impl CommandExecutor for SearchCommand {
             .into());
         }
 
+        PathFilter::new(&args.include, &args.exclude)?;
+        Self::compile_syntax_query(&args)?;
+
         Ok(())
     }
 }
@@ -497,10 +1077,400 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
         assert!(result.success);
         assert!(result.output.contains("Hello"));
     }
+
+    fn search_args(query: &str) -> SearchArgs {
+        serde_json::from_value(serde_json::json!({ "query": query })).unwrap()
+    }
+
+    fn gitignore_fixture() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::write(root.join(".gitignore"), "build/\n*.log\nsecret.txt\n").unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("build")).unwrap();
+        std::fs::create_dir_all(root.join("docs")).unwrap();
+        std::fs::create_dir_all(root.join(".hidden")).unwrap();
+        std::fs::write(root.join("src/main.rs"), "// needle in source\n").unwrap();
+        std::fs::write(root.join("docs/guide.md"), "needle in docs\n").unwrap();
+        std::fs::write(root.join("build/output.rs"), "needle in build\n").unwrap();
+        std::fs::write(root.join("debug.log"), "needle in log\n").unwrap();
+        std::fs::write(root.join("secret.txt"), "needle in secret\n").unwrap();
+        std::fs::write(root.join(".hidden/notes.txt"), "needle hidden\n").unwrap();
+        temp_dir
+    }
+
+    async fn matched_paths(root: &Path, args: &SearchArgs) -> Vec<String> {
+        let (results, _) = SearchCommand::search_contents(root, args, &CancellationToken::new())
+            .await
+            .unwrap();
+        let mut paths: Vec<String> = results
+            .iter()
+            .map(|r| {
+                r.file_path
+                    .strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[tokio::test]
+    async fn test_search_respects_gitignore() {
+        let temp_dir = gitignore_fixture();
+        let paths = matched_paths(temp_dir.path(), &search_args("needle")).await;
+
+        assert_eq!(paths, vec!["docs/guide.md", "src/main.rs"]);
+
+        let files = SearchCommand::search_filenames(temp_dir.path(), &search_args("o")).unwrap();
+        assert!(files
+            .iter()
+            .all(|p| !p.starts_with(temp_dir.path().join("build"))));
+    }
+
+    #[tokio::test]
+    async fn test_search_include_exclude_globs() {
+        let temp_dir = gitignore_fixture();
+
+        let mut args = search_args("needle");
+        args.include = vec!["src/**".to_string()];
+        assert_eq!(
+            matched_paths(temp_dir.path(), &args).await,
+            vec!["src/main.rs"]
+        );
+
+        let mut args = search_args("needle");
+        args.exclude = vec!["docs".to_string()];
+        assert_eq!(
+            matched_paths(temp_dir.path(), &args).await,
+            vec!["src/main.rs"]
+        );
+
+        let mut args = search_args("needle");
+        args.include = vec!["[".to_string()];
+        assert!(
+            SearchCommand::search_contents(temp_dir.path(), &args, &CancellationToken::new())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_hidden_and_max_filesize() {
+        let temp_dir = gitignore_fixture();
+
+        let mut args = search_args("needle");
+        args.hidden = true;
+        assert_eq!(
+            matched_paths(temp_dir.path(), &args).await,
+            vec![".hidden/notes.txt", "docs/guide.md", "src/main.rs"]
+        );
+
+        std::fs::write(
+            temp_dir.path().join("src/large.rs"),
+            format!("needle\n{}", "x".repeat(4096)),
+        )
+        .unwrap();
+        let mut args = search_args("needle");
+        args.max_filesize = Some(1024);
+        assert_eq!(
+            matched_paths(temp_dir.path(), &args).await,
+            vec!["docs/guide.md", "src/main.rs"]
+        );
+    }
+
+    fn generated_tree(files: usize) -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        for i in 0..files {
+            let dir = temp_dir.path().join(format!("mod_{:02}", i % 50));
+            std::fs::create_dir_all(&dir).unwrap();
+            let body = if i % 10 == 0 {
+                format!("fn item_{}() {{}}\n// needle {}\n", i, i)
+            } else {
+                format!("fn item_{}() {{}}\n", i)
+            };
+            std::fs::write(dir.join(format!("file_{:05}.rs", i)), body).unwrap();
+        }
+        temp_dir
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_parallel_search_large_tree() {
+        let temp_dir = generated_tree(5_000);
+        let mut args = search_args("needle");
+        args.max_results = 10_000;
+
+        let (results, files_searched) =
+            SearchCommand::search_contents(temp_dir.path(), &args, &CancellationToken::new())
+                .await
+                .unwrap();
+
+        assert_eq!(files_searched, 5_000);
+        assert_eq!(results.len(), 500);
+        assert!(results
+            .windows(2)
+            .all(|pair| pair[0].file_path <= pair[1].file_path));
+
+        // Output must not depend on the degree of parallelism.
+        args.concurrency = Some(1);
+        let (sequential, _) =
+            SearchCommand::search_contents(temp_dir.path(), &args, &CancellationToken::new())
+                .await
+                .unwrap();
+        let paths = |r: &[SearchResult]| r.iter().map(|m| m.file_path.clone()).collect::<Vec<_>>();
+        assert_eq!(paths(&results), paths(&sequential));
+
+        // Nor when only the first few matches are kept
+        args.max_results = 5;
+        for concurrency in [4, 1] {
+            args.concurrency = Some(concurrency);
+            let (capped, files_searched) =
+                SearchCommand::search_contents(temp_dir.path(), &args, &CancellationToken::new())
+                    .await
+                    .unwrap();
+            assert_eq!(files_searched, 5_000);
+            assert_eq!(paths(&capped), paths(&results[..5]));
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_parallel_search_cancellation() {
+        let temp_dir = generated_tree(5_000);
+        let mut args = search_args("needle");
+        args.max_results = 10_000;
+        args.concurrency = Some(2);
+
+        let token = CancellationToken::new();
+        let (mut results_rx, handle) =
+            SearchCommand::search_stream(temp_dir.path(), &args, token.clone()).unwrap();
+
+        // Take the first streamed match, then cancel the rest of the search.
+        assert!(results_rx.recv().await.is_some());
+        token.cancel();
+
+        let mut remaining = 0;
+        while results_rx.recv().await.is_some() {
+            remaining += 1;
+        }
+        let err = handle.await.unwrap().unwrap_err();
+        assert!(err.to_string().contains("Search cancelled"));
+        assert!(remaining < 499, "search kept running after cancellation");
+    }
+
+    fn ranking_fixture() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for dir in ["a", "b", "src", "tests"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        let cluster = "widget\nwidget, widget\nwidget\n";
+        std::fs::write(root.join("a/notes.md"), "one widget\n").unwrap();
+        std::fs::write(root.join("b/cluster.rs"), cluster).unwrap();
+        std::fs::write(root.join("src/widget.rs"), "// widget\n").unwrap();
+        std::fs::write(root.join("tests/cluster.rs"), cluster).unwrap();
+        temp_dir
+    }
+
+    async fn ranked(root: &Path, args: &SearchArgs) -> Vec<(String, usize, f64)> {
+        let (results, _) = SearchCommand::search_contents(root, args, &CancellationToken::new())
+            .await
+            .unwrap();
+        results
+            .iter()
+            .map(|r| {
+                let path = r.file_path.strip_prefix(root).unwrap();
+                (
+                    path.to_string_lossy().replace('\\', "/"),
+                    r.line_number,
+                    r.score,
+                )
+            })
+            .collect()
+    }
+
+    fn locations(ranked: &[(String, usize, f64)]) -> Vec<String> {
+        ranked
+            .iter()
+            .map(|(path, line, _)| format!("{}:{}", path, line))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_search_ranks_by_score() {
+        let temp_dir = ranking_fixture();
+        let mut args = search_args("widget");
+
+        let ranked_results = ranked(temp_dir.path(), &args).await;
+        assert_eq!(
+            locations(&ranked_results),
+            [
+                "src/widget.rs:1",
+                "b/cluster.rs:2",
+                "tests/cluster.rs:2",
+                "b/cluster.rs:1",
+                "b/cluster.rs:3",
+                "tests/cluster.rs:1",
+                "tests/cluster.rs:3",
+                "a/notes.md:1",
+            ]
+        );
+        assert_eq!(ranked_results[0].2, 1.0);
+        assert!(ranked_results
+            .windows(2)
+            .all(|pair| pair[0].2 >= pair[1].2 && pair[1].2 > 0.0));
+
+        // Without the non-test boost the copies tie and fall back to path order
+        args.prefer_non_test = false;
+        let ranked_results = ranked(temp_dir.path(), &args).await;
+        assert_eq!(
+            locations(&ranked_results)[1..5],
+            [
+                "b/cluster.rs:2",
+                "tests/cluster.rs:2",
+                "b/cluster.rs:1",
+                "b/cluster.rs:3",
+            ]
+        );
+        assert_eq!(ranked_results[1].2, ranked_results[2].2);
+
+        let args: SearchArgs =
+            serde_json::from_value(serde_json::json!({ "query": "widget", "sort": "path" }))
+                .unwrap();
+        assert_eq!(
+            locations(&ranked(temp_dir.path(), &args).await),
+            [
+                "a/notes.md:1",
+                "b/cluster.rs:1",
+                "b/cluster.rs:2",
+                "b/cluster.rs:3",
+                "src/widget.rs:1",
+                "tests/cluster.rs:1",
+                "tests/cluster.rs:2",
+                "tests/cluster.rs:3",
+            ]
+        );
+    }
+
+    #[cfg(feature = "structural-search")]
+    fn rust_fixture() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+        std::fs::write(
+            temp_dir.path().join("src/store.rs"),
+            r#"pub struct TranscriptStore {
+    entries: Vec<String>,
+}
+
+struct Cursor(usize);
+
+pub fn append(store: &mut TranscriptStore, line: String) {
+    store.entries.push(line);
+}
+
+fn count(store: &TranscriptStore) -> usize {
+    store.entries.len()
+}
+"#,
+        )
+        .unwrap();
+        // Mentions the same names but is not Rust, so it must be skipped.
+        std::fs::write(
+            temp_dir.path().join("notes.md"),
+            "fn append(store: &mut TranscriptStore)\n",
+        )
+        .unwrap();
+        temp_dir
+    }
+
+    #[cfg(feature = "structural-search")]
+    #[tokio::test]
+    async fn test_structural_search_functions() {
+        let temp_dir = rust_fixture();
+        let mut args = search_args("&mut TranscriptStore");
+        args.syntax_query =
+            Some("(function_item parameters: (parameters) @params) @function".to_string());
+
+        let (results, files_searched) =
+            SearchCommand::search_contents(temp_dir.path(), &args, &CancellationToken::new())
+                .await
+                .unwrap();
+
+        assert_eq!(files_searched, 1);
+        assert_eq!(results.len(), 2);
+        let function = results
+            .iter()
+            .find(|r| r.capture.as_deref() == Some("function"))
+            .unwrap();
+        assert_eq!(function.line_number, 7);
+        assert!(function.line_content.starts_with("pub fn append"));
+        let span = function.span.as_ref().unwrap();
+        assert_eq!((span.start_line, span.end_line), (6, 8));
+        assert!(results
+            .iter()
+            .all(|r| r.file_path.ends_with("src/store.rs")));
+    }
+
+    #[cfg(feature = "structural-search")]
+    #[tokio::test]
+    async fn test_structural_search_structs() {
+        let temp_dir = rust_fixture();
+        let command = SearchCommand::new();
+        let args = serde_json::json!({
+            "query": "",
+            "syntax_query": "(struct_item name: (type_identifier) @name)"
+        });
+        command.validate_args(&args).unwrap();
+
+        let context = CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: Some(temp_dir.path().to_string_lossy().to_string()),
+            sandbox_level: SandboxLevel::ReadOnly,
+            dry_run: false,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log: None,
+            timeout: None,
+            progress: None,
+        };
+
+        let result = command.execute(&args, &context).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.output.contains("src/store.rs:1 @name [1:12-1:27]"));
+        assert!(result.output.contains("> TranscriptStore"));
+        assert!(result.output.contains("src/store.rs:5 @name [5:8-5:14]"));
+    }
+
+    #[test]
+    fn test_structural_search_rejects_unsupported_language() {
+        let command = SearchCommand::new();
+        let err = command
+            .validate_args(&serde_json::json!({
+                "syntax_query": "(function_definition) @f",
+                "syntax_language": "python"
+            }))
+            .unwrap_err();
+
+        if cfg!(feature = "structural-search") {
+            assert!(err
+                .to_string()
+                .contains("does not support language 'python'"));
+        } else {
+            assert!(err.to_string().contains("structural-search"));
+        }
+
+        assert!(command
+            .validate_args(&serde_json::json!({ "syntax_query": "(function_item" }))
+            .is_err());
+    }
 }
@@ -106,6 +106,8 @@ impl SummarizeCommand {
                 sandbox_level_required: SandboxLevel::ReadOnly,
                 supports_preview: true,
                 supports_dry_run: false,
+                timeout: None,
+                alias_of: None,
             },
             memory_service: None,
             memory_file_service: None,
@@ -129,6 +131,8 @@ impl SummarizeCommand {
                 sandbox_level_required: SandboxLevel::ReadOnly,
                 supports_preview: true,
                 supports_dry_run: false,
+                timeout: None,
+                alias_of: None,
             },
             memory_service: Some(memory_service),
             memory_file_service: Some(memory_file_service),
@@ -428,12 +432,14 @@ impl CommandExecutor for SummarizeCommand {
                 success: true,
                 output,
                 error: None,
+                data: None,
             }),
             Err(e) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
+                data: None,
             }),
         }
     }
@@ -509,6 +515,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
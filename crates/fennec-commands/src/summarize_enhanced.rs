@@ -3,19 +3,22 @@ use async_trait::async_trait;
 use fennec_core::{
     command::{Capability, CommandPreview, CommandResult, PreviewAction},
     error::FennecError,
+    provider::ProviderClient,
     transcript::MessageRole,
 };
 use fennec_memory::{MemoryFileService, MemoryFileType, MemoryService, SessionMemory};
 use fennec_security::SandboxLevel;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::fs;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 use walkdir::WalkDir;
 
+use crate::git_integration::{changes_between, commits_between};
 use crate::registry::{CommandContext, CommandDescriptor, CommandExecutor};
+use crate::summary_tree::{SummaryCache, SummaryTree, TreeSummarizer, SUMMARY_CACHE_FILE};
 
 /// Arguments for the enhanced summarize command
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +45,16 @@ pub struct EnhancedSummarizeArgs {
     pub save_to_memory: Option<bool>,
     /// Tags to associate with memory file (if saved)
     pub memory_tags: Option<Vec<String>>,
+    /// Summarize every file under a directory and synthesize per-directory overviews
+    #[serde(default)]
+    pub recursive: Option<bool>,
+    /// Maximum provider requests in flight for recursive summaries
+    #[serde(default)]
+    pub concurrency: Option<usize>,
+    /// For project summaries, also list the commits and changed files since
+    /// the current branch diverged from this ref
+    #[serde(default)]
+    pub base_ref: Option<String>,
 }
 
 /// Types of summaries that can be generated
@@ -72,6 +85,9 @@ pub enum OutputDestination {
     CustomFile(String),
     /// Both console and memory file
     Both(String), // memory filename
+    /// Write a SUMMARY.md per directory under this path, mirroring the
+    /// source layout (recursive directory summaries only)
+    SummaryTree(String),
 }
 
 /// Depth levels for summaries
@@ -92,6 +108,8 @@ pub struct EnhancedSummarizeCommand {
     descriptor: CommandDescriptor,
     memory_service: Arc<RwLock<Option<MemoryService>>>,
     memory_file_service: Arc<RwLock<Option<MemoryFileService>>>,
+    provider: Option<Arc<dyn ProviderClient>>,
+    model: String,
 }
 
 impl EnhancedSummarizeCommand {
@@ -107,9 +125,13 @@ impl EnhancedSummarizeCommand {
                 sandbox_level_required: SandboxLevel::ReadOnly,
                 supports_preview: true,
                 supports_dry_run: false,
+                timeout: None,
+                alias_of: None,
             },
             memory_service: Arc::new(RwLock::new(None)),
             memory_file_service: Arc::new(RwLock::new(None)),
+            provider: None,
+            model: String::new(),
         }
     }
 
@@ -129,12 +151,77 @@ impl EnhancedSummarizeCommand {
                 sandbox_level_required: SandboxLevel::ReadOnly,
                 supports_preview: true,
                 supports_dry_run: false,
+                timeout: None,
+                alias_of: None,
             },
             memory_service: Arc::new(RwLock::new(Some(memory_service))),
             memory_file_service: Arc::new(RwLock::new(Some(memory_file_service))),
+            provider: None,
+            model: String::new(),
         })
     }
 
+    /// Use `provider` to write recursive file and directory summaries;
+    /// without one they fall back to doc comments and directory shape
+    pub fn with_provider(mut self, provider: Arc<dyn ProviderClient>, model: &str) -> Self {
+        self.provider = Some(provider);
+        self.model = model.to_string();
+        self
+    }
+
+    /// Resolve the target of a recursive directory summary, if that is what
+    /// `args` asks for. Relative targets are taken from the workspace.
+    fn recursive_root(args: &EnhancedSummarizeArgs, context: &CommandContext) -> Option<PathBuf> {
+        let is_file_summary = matches!(args.summary_type, None | Some(SummaryType::File))
+            && args.is_path != Some(false);
+        if !is_file_summary || !args.recursive.unwrap_or(false) {
+            return None;
+        }
+
+        let target = Path::new(&args.target);
+        let root = match &context.workspace_path {
+            Some(workspace) if target.is_relative() => Path::new(workspace).join(target),
+            _ => target.to_path_buf(),
+        };
+        root.is_dir().then_some(root)
+    }
+
+    /// Summarize every file under `root`, reusing cached summaries for
+    /// unchanged content
+    async fn summarize_tree(
+        &self,
+        root: &Path,
+        args: &EnhancedSummarizeArgs,
+        context: &CommandContext,
+    ) -> Result<SummaryTree> {
+        let cache_path = context
+            .workspace_path
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| root.to_path_buf())
+            .join(SUMMARY_CACHE_FILE);
+        let mut cache = SummaryCache::load(&cache_path).await;
+
+        let summarizer = TreeSummarizer::new(self.provider.clone(), self.model.as_str())
+            .with_concurrency(
+                args.concurrency
+                    .unwrap_or(crate::summary_tree::DEFAULT_SUMMARY_CONCURRENCY),
+            )
+            .with_depth(args.depth_level.clone().unwrap_or(SummaryDepth::Standard))
+            .with_extensions(args.include_extensions.clone());
+        let tree = summarizer.summarize(root, &mut cache).await?;
+
+        if let Err(e) = cache.save(&cache_path).await {
+            tracing::warn!(
+                "Failed to save summary cache {}: {}",
+                cache_path.display(),
+                e
+            );
+        }
+
+        Ok(tree)
+    }
+
     /// Generate summary based on the target type
     async fn generate_summary(
         &self,
@@ -264,6 +351,11 @@ impl EnhancedSummarizeCommand {
         self.add_project_structure_summary(&mut summary, &workspace_path_buf, depth)
             .await?;
 
+        if let Some(base_ref) = &args.base_ref {
+            self.add_branch_changes(&mut summary, workspace_path, base_ref, depth)
+                .await?;
+        }
+
         // Add recent activity if memory service is available
         let memory_service_guard = self.memory_service.read().await;
         if let Some(memory_service) = memory_service_guard.as_ref() {
@@ -780,6 +872,72 @@ impl EnhancedSummarizeCommand {
         Ok(())
     }
 
+    async fn add_branch_changes(
+        &self,
+        summary: &mut Vec<String>,
+        workspace_path: &str,
+        base_ref: &str,
+        depth: &SummaryDepth,
+    ) -> Result<()> {
+        let git_error = |e: std::io::Error| {
+            FennecError::Command(Box::new(std::io::Error::new(
+                e.kind(),
+                format!("Failed to compare against '{}': {}", base_ref, e),
+            )))
+        };
+
+        let limit = match depth {
+            SummaryDepth::Brief => 5,
+            SummaryDepth::Standard => 20,
+            SummaryDepth::Detailed | SummaryDepth::Comprehensive => 100,
+        };
+        let commits = commits_between(workspace_path, base_ref, "HEAD", Some(limit))
+            .await
+            .map_err(git_error)?;
+        let changes = changes_between(workspace_path, base_ref, "HEAD")
+            .await
+            .map_err(git_error)?;
+
+        summary.push(format!("## Changes Since {}", base_ref));
+        summary.push(format!(
+            "- **Commits:** {}{}",
+            commits.len(),
+            if commits.len() == limit { "+" } else { "" }
+        ));
+        summary.push(format!(
+            "- **Files changed:** {} (+{} / -{})",
+            changes.len(),
+            changes.iter().map(|c| c.insertions).sum::<usize>(),
+            changes.iter().map(|c| c.deletions).sum::<usize>()
+        ));
+        summary.push(String::new());
+
+        for commit in &commits {
+            summary.push(format!(
+                "- {} {} ({})",
+                &commit.hash[..commit.hash.len().min(7)],
+                commit.message,
+                commit.author
+            ));
+        }
+
+        if !matches!(depth, SummaryDepth::Brief) && !changes.is_empty() {
+            summary.push(String::new());
+            for change in &changes {
+                let path = match &change.old_path {
+                    Some(old_path) => {
+                        format!("{} → {}", old_path.display(), change.path.display())
+                    }
+                    None => change.path.display().to_string(),
+                };
+                summary.push(format!("- {:?}: {}", change.change_type, path));
+            }
+        }
+
+        summary.push(String::new());
+        Ok(())
+    }
+
     async fn add_recent_project_activity(
         &self,
         summary: &mut Vec<String>,
@@ -948,6 +1106,27 @@ impl CommandExecutor for EnhancedSummarizeCommand {
         &self.descriptor
     }
 
+    fn required_capabilities(&self, args: &serde_json::Value) -> Vec<Capability> {
+        let mut capabilities = self.descriptor.capabilities_required.clone();
+
+        // Recursive summaries keep a cache in the workspace; the other
+        // destinations write memory, progress or custom files
+        let writes = serde_json::from_value::<EnhancedSummarizeArgs>(args.clone())
+            .map(|args| {
+                args.recursive == Some(true)
+                    || args.save_to_memory == Some(true)
+                    || !matches!(
+                        args.output_destination,
+                        None | Some(OutputDestination::Console)
+                    )
+            })
+            .unwrap_or(false);
+        if writes {
+            capabilities.push(Capability::WriteFile);
+        }
+        capabilities
+    }
+
     async fn preview(
         &self,
         args: &serde_json::Value,
@@ -988,7 +1167,11 @@ impl CommandExecutor for EnhancedSummarizeCommand {
                         });
                     } else if path.is_dir() {
                         actions.push(PreviewAction::ReadFile {
-                            path: format!("{} (directory contents)", args.target),
+                            path: if args.recursive.unwrap_or(false) {
+                                format!("{} (every file, recursively)", args.target)
+                            } else {
+                                format!("{} (directory contents)", args.target)
+                            },
                         });
                     }
                 }
@@ -1029,8 +1212,19 @@ impl CommandExecutor for EnhancedSummarizeCommand {
             )))
         })?;
 
-        match self.generate_summary(&args, context).await {
-            Ok(summary) => {
+        let generated = match Self::recursive_root(&args, context) {
+            Some(root) => self
+                .summarize_tree(&root, &args, context)
+                .await
+                .map(|tree| (tree.render(), Some(tree))),
+            None => self
+                .generate_summary(&args, context)
+                .await
+                .map(|summary| (summary, None)),
+        };
+
+        match generated {
+            Ok((summary, tree)) => {
                 let mut output_parts = Vec::new();
                 let mut success_messages = Vec::new();
 
@@ -1076,6 +1270,30 @@ impl CommandExecutor for EnhancedSummarizeCommand {
                             ));
                         }
                     }
+                    Some(OutputDestination::SummaryTree(path)) => {
+                        // Mirror the source layout with one SUMMARY.md per directory
+                        let output_root = match &context.workspace_path {
+                            Some(workspace) if Path::new(path).is_relative() => {
+                                Path::new(workspace).join(path)
+                            }
+                            _ => PathBuf::from(path),
+                        };
+                        match &tree {
+                            Some(tree) => {
+                                if let Ok(written) = tree.write_summary_files(&output_root).await {
+                                    success_messages.push(format!(
+                                        "✅ Wrote {} summary files under {}",
+                                        written.len(),
+                                        output_root.display()
+                                    ));
+                                }
+                            }
+                            None => success_messages.push(
+                                "⚠️  SUMMARY.md trees require a recursive directory summary"
+                                    .to_string(),
+                            ),
+                        }
+                    }
                 }
 
                 // Auto-save to memory if requested
@@ -1100,6 +1318,7 @@ impl CommandExecutor for EnhancedSummarizeCommand {
                     success: true,
                     output: output_parts.join("\n"),
                     error: None,
+                    data: tree.and_then(|tree| serde_json::to_value(tree).ok()),
                 })
             }
             Err(e) => Ok(CommandResult {
@@ -1107,6 +1326,7 @@ impl CommandExecutor for EnhancedSummarizeCommand {
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
+                data: None,
             }),
         }
     }
@@ -1147,6 +1367,13 @@ impl CommandExecutor for EnhancedSummarizeCommand {
             }
         }
 
+        if args.concurrency == Some(0) {
+            return Err(FennecError::Command(Box::new(std::io::Error::other(
+                "concurrency must be greater than 0",
+            )))
+            .into());
+        }
+
         Ok(())
     }
 }
@@ -1194,6 +1421,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -1222,6 +1451,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -1229,4 +1460,155 @@ mod tests {
         assert!(result.output.contains("Session Summary"));
         assert!(result.output.contains("Memory service not available"));
     }
+    /// Delegates to [`fennec_provider::MockProviderClient`], counting requests
+    #[derive(Default)]
+    struct CountingProvider {
+        calls: std::sync::atomic::AtomicUsize,
+        inner: fennec_provider::MockProviderClient,
+    }
+
+    #[async_trait]
+    impl ProviderClient for CountingProvider {
+        async fn complete(
+            &self,
+            request: fennec_core::provider::ProviderRequest,
+        ) -> fennec_core::Result<fennec_core::provider::ProviderResponse> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.inner.complete(request).await
+        }
+
+        async fn stream(
+            &self,
+            request: fennec_core::provider::ProviderRequest,
+        ) -> fennec_core::Result<
+            Box<dyn futures::Stream<Item = fennec_core::Result<String>> + Unpin + Send>,
+        > {
+            self.inner.stream(request).await
+        }
+    }
+
+    fn workspace_context(workspace: &Path) -> CommandContext {
+        CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: Some(workspace.to_string_lossy().to_string()),
+            sandbox_level: SandboxLevel::ReadOnly,
+            dry_run: false,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log: None,
+            timeout: None,
+            progress: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recursive_summary_cache_skips_provider_calls() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let workspace = temp_dir.path();
+        std::fs::create_dir_all(workspace.join("src/util")).unwrap();
+        std::fs::write(workspace.join("src/main.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(workspace.join("src/util/mod.rs"), "pub fn add() {}\n").unwrap();
+        std::fs::write(workspace.join("src/util/io.rs"), "pub fn read() {}\n").unwrap();
+
+        let provider = Arc::new(CountingProvider::default());
+        let command = EnhancedSummarizeCommand::new().with_provider(provider.clone(), "mock");
+        let context = workspace_context(workspace);
+        let args = serde_json::json!({
+            "target": "src",
+            "recursive": true,
+            "concurrency": 2
+        });
+
+        // 3 files, then the src/util and src directories
+        let result = command.execute(&args, &context).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.output.contains("## util/"));
+        assert!(result.output.contains("(offline mode) I received"));
+        assert_eq!(provider.calls.load(std::sync::atomic::Ordering::SeqCst), 5);
+
+        let result = command.execute(&args, &context).await.unwrap();
+        let tree: SummaryTree = serde_json::from_value(result.data.unwrap()).unwrap();
+        assert_eq!(tree.provider_calls, 0);
+        assert_eq!(tree.cache_hits, 5);
+        assert_eq!(provider.calls.load(std::sync::atomic::Ordering::SeqCst), 5);
+
+        // Only the changed file and the directories above it are redone
+        std::fs::write(workspace.join("src/util/io.rs"), "pub fn write() {}\n").unwrap();
+        let result = command.execute(&args, &context).await.unwrap();
+        let tree: SummaryTree = serde_json::from_value(result.data.unwrap()).unwrap();
+        assert_eq!(tree.provider_calls, 3);
+        assert_eq!(tree.cache_hits, 2);
+    }
+
+    #[tokio::test]
+    async fn test_recursive_summary_writes_summary_tree() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let workspace = temp_dir.path();
+        std::fs::create_dir_all(workspace.join("src/util")).unwrap();
+        std::fs::write(workspace.join("src/lib.rs"), "//! Library root\n").unwrap();
+        std::fs::write(workspace.join("src/util/mod.rs"), "//! Helpers\n").unwrap();
+
+        let command = EnhancedSummarizeCommand::new();
+        let args = serde_json::json!({
+            "target": "src",
+            "recursive": true,
+            "output_destination": {"SummaryTree": "docs/summary"}
+        });
+
+        let result = command
+            .execute(&args, &workspace_context(workspace))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.output.contains("Wrote 2 summary files"));
+
+        let root = std::fs::read_to_string(workspace.join("docs/summary/SUMMARY.md")).unwrap();
+        assert!(root.contains("- `lib.rs` — Library root"));
+        assert!(root.contains("[util/](util/SUMMARY.md)"));
+        let util = std::fs::read_to_string(workspace.join("docs/summary/util/SUMMARY.md")).unwrap();
+        assert!(util.contains("- `mod.rs` — Helpers"));
+    }
+
+    #[tokio::test]
+    async fn test_project_summary_lists_branch_changes() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let workspace = temp_dir.path();
+        let git = |args: &[&str]| {
+            let output = std::process::Command::new("git")
+                .current_dir(workspace)
+                .args(args)
+                .env("GIT_AUTHOR_NAME", "Test")
+                .env("GIT_AUTHOR_EMAIL", "test@example.com")
+                .env("GIT_COMMITTER_NAME", "Test")
+                .env("GIT_COMMITTER_EMAIL", "test@example.com")
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {:?} failed", args);
+        };
+
+        git(&["init", "-q", "-b", "main"]);
+        std::fs::write(workspace.join("old.rs"), "pub fn old() {}\n").unwrap();
+        git(&["add", "-A"]);
+        git(&["commit", "-q", "-m", "initial"]);
+        git(&["checkout", "-q", "-b", "feature"]);
+        git(&["mv", "old.rs", "new.rs"]);
+        git(&["commit", "-q", "-m", "refactor: rename old.rs"]);
+
+        let command = EnhancedSummarizeCommand::new();
+        let args = serde_json::json!({
+            "target": ".",
+            "summary_type": "Project",
+            "base_ref": "main"
+        });
+        let result = command
+            .execute(&args, &workspace_context(workspace))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.output.contains("## Changes Since main"));
+        assert!(result.output.contains("- **Commits:** 1"));
+        assert!(result.output.contains("refactor: rename old.rs (Test)"));
+        assert!(result.output.contains("- Renamed: old.rs → new.rs"));
+    }
 }
@@ -0,0 +1,645 @@
+//! Recursive directory summaries for `summarize_enhanced`.
+//!
+//! Every text file under the root is summarized on its own, then each
+//! directory gets an overview synthesized from its files and subdirectories,
+//! bottom-up. Results are cached by content hash so re-runs only pay for
+//! files (and the directories above them) that actually changed.
+
+use anyhow::Result;
+use fennec_core::{
+    error::FennecError,
+    provider::{ProviderClient, ProviderMessage, ProviderRequest},
+};
+use futures::stream::{self, StreamExt};
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::fs;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::common::is_text_file;
+use crate::summarize_enhanced::SummaryDepth;
+
+/// Cache location, relative to the workspace (or the summarized root)
+pub const SUMMARY_CACHE_FILE: &str = ".fennec/summary_cache.json";
+
+/// File written into every directory of a summary tree
+pub const SUMMARY_FILE_NAME: &str = "SUMMARY.md";
+
+/// Provider requests in flight at once when no limit is given
+pub const DEFAULT_SUMMARY_CONCURRENCY: usize = 4;
+
+/// Model name used in cache keys when summaries are built without a provider
+const HEURISTIC_MODEL: &str = "heuristic";
+
+/// File contents beyond this are cut from the prompt
+const MAX_PROMPT_BYTES: usize = 24 * 1024;
+
+/// Summaries keyed by a hash of the model, the kind of summary and its input
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SummaryCache {
+    entries: HashMap<String, String>,
+}
+
+impl SummaryCache {
+    /// Load a cache file, starting empty if it is missing or unreadable
+    pub async fn load(path: &Path) -> Self {
+        match fs::read_to_string(path).await {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!("Ignoring corrupt summary cache {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub async fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        fs::write(path, serde_json::to_string(self)?).await?;
+        Ok(())
+    }
+
+    pub fn key(model: &str, kind: &str, input: &str) -> String {
+        format!(
+            "{:x}",
+            md5::compute(format!("{}\0{}\0{}", model, kind, input))
+        )
+    }
+
+    pub fn get(&self, key: &str) -> Option<&String> {
+        self.entries.get(key)
+    }
+
+    pub fn insert(&mut self, key: String, summary: String) {
+        self.entries.insert(key, summary);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Summary of a single file, with its path relative to the tree root
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileSummary {
+    pub path: PathBuf,
+    pub summary: String,
+    pub cached: bool,
+}
+
+/// Overview of one directory; `path` is empty for the root
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DirectorySummary {
+    pub path: PathBuf,
+    pub overview: String,
+    pub files: Vec<FileSummary>,
+    pub subdirectories: Vec<PathBuf>,
+    pub cached: bool,
+}
+
+/// Every directory summary under a root, parents before children
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummaryTree {
+    pub root: PathBuf,
+    pub directories: Vec<DirectorySummary>,
+    pub provider_calls: usize,
+    pub cache_hits: usize,
+}
+
+impl SummaryTree {
+    pub fn file_count(&self) -> usize {
+        self.directories.iter().map(|dir| dir.files.len()).sum()
+    }
+
+    fn directory(&self, path: &Path) -> Option<&DirectorySummary> {
+        self.directories.iter().find(|dir| dir.path == path)
+    }
+
+    /// Render the whole tree as a single markdown document
+    pub fn render(&self) -> String {
+        let mut lines = Vec::new();
+        lines.push(format!("# Directory Summary: {}", self.root.display()));
+        lines.push(String::new());
+        lines.push(format!(
+            "**Files summarized:** {} ({} cache hits, {} provider calls)",
+            self.file_count(),
+            self.cache_hits,
+            self.provider_calls
+        ));
+
+        for dir in &self.directories {
+            lines.push(String::new());
+            lines.push(format!("## {}", display_dir(&dir.path)));
+            lines.push(String::new());
+            lines.push(dir.overview.clone());
+            if !dir.files.is_empty() {
+                lines.push(String::new());
+                for file in &dir.files {
+                    lines.push(format!("- `{}` — {}", file.path.display(), file.summary));
+                }
+            }
+        }
+
+        lines.join("\n")
+    }
+
+    /// Write a `SUMMARY.md` into `output_root` for every directory, mirroring
+    /// the source layout. Returns the files written.
+    pub async fn write_summary_files(&self, output_root: &Path) -> Result<Vec<PathBuf>> {
+        let mut written = Vec::new();
+
+        for dir in &self.directories {
+            let mut lines = vec![format!("# {}", display_dir(&dir.path)), String::new()];
+            lines.push(dir.overview.clone());
+
+            if !dir.files.is_empty() {
+                lines.push(String::new());
+                lines.push("## Files".to_string());
+                lines.push(String::new());
+                for file in &dir.files {
+                    let name = file.path.file_name().unwrap_or_default().to_string_lossy();
+                    lines.push(format!("- `{}` — {}", name, file.summary));
+                }
+            }
+
+            if !dir.subdirectories.is_empty() {
+                lines.push(String::new());
+                lines.push("## Subdirectories".to_string());
+                lines.push(String::new());
+                for sub in &dir.subdirectories {
+                    let name = sub.file_name().unwrap_or_default().to_string_lossy();
+                    let overview = self
+                        .directory(sub)
+                        .and_then(|sub| sub.overview.lines().next())
+                        .unwrap_or_default();
+                    lines.push(format!(
+                        "- [{}/]({}/{}) — {}",
+                        name, name, SUMMARY_FILE_NAME, overview
+                    ));
+                }
+            }
+            lines.push(String::new());
+
+            let target_dir = output_root.join(&dir.path);
+            fs::create_dir_all(&target_dir).await?;
+            let target = target_dir.join(SUMMARY_FILE_NAME);
+            fs::write(&target, lines.join("\n")).await?;
+            written.push(target);
+        }
+
+        Ok(written)
+    }
+}
+
+fn display_dir(path: &Path) -> String {
+    if path.as_os_str().is_empty() {
+        "./".to_string()
+    } else {
+        format!("{}/", path.display())
+    }
+}
+
+/// Builds a [`SummaryTree`], asking the provider (when set) for each summary
+pub struct TreeSummarizer {
+    provider: Option<Arc<dyn ProviderClient>>,
+    model: String,
+    concurrency: usize,
+    depth: SummaryDepth,
+    include_extensions: Option<Vec<String>>,
+}
+
+impl TreeSummarizer {
+    pub fn new(provider: Option<Arc<dyn ProviderClient>>, model: impl Into<String>) -> Self {
+        let model = model.into();
+        Self {
+            model: if provider.is_some() {
+                model
+            } else {
+                HEURISTIC_MODEL.to_string()
+            },
+            provider,
+            concurrency: DEFAULT_SUMMARY_CONCURRENCY,
+            depth: SummaryDepth::Standard,
+            include_extensions: None,
+        }
+    }
+
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    pub fn with_depth(mut self, depth: SummaryDepth) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn with_extensions(mut self, extensions: Option<Vec<String>>) -> Self {
+        self.include_extensions = extensions;
+        self
+    }
+
+    pub async fn summarize(&self, root: &Path, cache: &mut SummaryCache) -> Result<SummaryTree> {
+        let files = self.collect_files(root).await;
+
+        let mut provider_calls = 0;
+        let mut cache_hits = 0;
+
+        // Per-file summaries
+        let file_inputs: Vec<(PathBuf, String)> = files
+            .into_iter()
+            .map(|(path, content)| {
+                let prompt = self.file_prompt(&path, &content);
+                (path, prompt)
+            })
+            .collect();
+        let file_results = self
+            .run_batch(
+                "file",
+                file_inputs,
+                cache,
+                &mut provider_calls,
+                &mut cache_hits,
+            )
+            .await?;
+
+        // Every directory holding a file, plus their ancestors up to the root
+        let mut directories: BTreeSet<PathBuf> = BTreeSet::new();
+        directories.insert(PathBuf::new());
+        let mut files_by_dir: BTreeMap<PathBuf, Vec<FileSummary>> = BTreeMap::new();
+        for file in file_results
+            .into_iter()
+            .map(|(path, summary, cached)| FileSummary {
+                path,
+                summary,
+                cached,
+            })
+        {
+            let parent = file
+                .path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+            for ancestor in parent.ancestors() {
+                directories.insert(ancestor.to_path_buf());
+            }
+            files_by_dir.entry(parent).or_default().push(file);
+        }
+
+        let mut subdirs_by_dir: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
+        for dir in directories.iter().filter(|dir| !dir.as_os_str().is_empty()) {
+            let parent = dir.parent().map(Path::to_path_buf).unwrap_or_default();
+            subdirs_by_dir.entry(parent).or_default().push(dir.clone());
+        }
+
+        // Directory overviews, deepest level first so children are ready
+        let mut overviews: HashMap<PathBuf, (String, bool)> = HashMap::new();
+        let max_depth = directories
+            .iter()
+            .map(|dir| dir.components().count())
+            .max()
+            .unwrap_or(0);
+        for level in (0..=max_depth).rev() {
+            let inputs: Vec<(PathBuf, String)> = directories
+                .iter()
+                .filter(|dir| dir.components().count() == level)
+                .map(|dir| {
+                    let files = files_by_dir.get(dir).map(Vec::as_slice).unwrap_or_default();
+                    let subdirs: Vec<(PathBuf, String)> = subdirs_by_dir
+                        .get(dir)
+                        .into_iter()
+                        .flatten()
+                        .map(|sub| (sub.clone(), overviews[sub].0.clone()))
+                        .collect();
+                    (dir.clone(), self.directory_prompt(dir, files, &subdirs))
+                })
+                .collect();
+
+            for (path, overview, cached) in self
+                .run_batch(
+                    "directory",
+                    inputs,
+                    cache,
+                    &mut provider_calls,
+                    &mut cache_hits,
+                )
+                .await?
+            {
+                overviews.insert(path, (overview, cached));
+            }
+        }
+
+        let directories = directories
+            .into_iter()
+            .map(|dir| {
+                let (overview, cached) = overviews.remove(&dir).unwrap_or_default();
+                DirectorySummary {
+                    files: files_by_dir.remove(&dir).unwrap_or_default(),
+                    subdirectories: subdirs_by_dir.remove(&dir).unwrap_or_default(),
+                    path: dir,
+                    overview,
+                    cached,
+                }
+            })
+            .collect();
+
+        Ok(SummaryTree {
+            root: root.to_path_buf(),
+            directories,
+            provider_calls,
+            cache_hits,
+        })
+    }
+
+    /// Text files under `root` (relative path and content), honoring
+    /// .gitignore and skipping hidden entries and generated SUMMARY.md files
+    async fn collect_files(&self, root: &Path) -> Vec<(PathBuf, String)> {
+        let walker = WalkBuilder::new(root)
+            .hidden(true)
+            .git_ignore(true)
+            .require_git(false)
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .build();
+
+        let mut files = Vec::new();
+        for entry in walker.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if !entry.file_type().is_some_and(|t| t.is_file())
+                || !is_text_file(path)
+                || entry.file_name() == SUMMARY_FILE_NAME
+            {
+                continue;
+            }
+            if let Some(extensions) = &self.include_extensions {
+                let ext = path
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .map(str::to_lowercase);
+                if !ext.is_some_and(|ext| extensions.contains(&ext)) {
+                    continue;
+                }
+            }
+
+            match fs::read_to_string(path).await {
+                Ok(content) => {
+                    let relative = path.strip_prefix(root).unwrap_or(path).to_path_buf();
+                    files.push((relative, content));
+                }
+                Err(e) => debug!("Skipping {}: {}", path.display(), e),
+            }
+        }
+        files
+    }
+
+    fn length_hint(&self) -> &'static str {
+        match self.depth {
+            SummaryDepth::Brief => "one sentence",
+            SummaryDepth::Standard => "two or three sentences",
+            SummaryDepth::Detailed => "a short paragraph covering responsibilities and key types",
+            SummaryDepth::Comprehensive => {
+                "a paragraph covering responsibilities, key types and notable risks or TODOs"
+            }
+        }
+    }
+
+    fn file_prompt(&self, path: &Path, content: &str) -> String {
+        let mut end = content.len().min(MAX_PROMPT_BYTES);
+        while !content.is_char_boundary(end) {
+            end -= 1;
+        }
+        let truncated = if end < content.len() {
+            "\n... (truncated)"
+        } else {
+            ""
+        };
+        format!(
+            "File: {}\n\n```\n{}{}\n```",
+            path.display(),
+            &content[..end],
+            truncated
+        )
+    }
+
+    fn directory_prompt(
+        &self,
+        path: &Path,
+        files: &[FileSummary],
+        subdirectories: &[(PathBuf, String)],
+    ) -> String {
+        let mut prompt = format!("Directory: {}\n", display_dir(path));
+        if !files.is_empty() {
+            prompt.push_str("\nFiles:\n");
+            for file in files {
+                prompt.push_str(&format!("- {}: {}\n", file.path.display(), file.summary));
+            }
+        }
+        if !subdirectories.is_empty() {
+            prompt.push_str("\nSubdirectories:\n");
+            for (sub, overview) in subdirectories {
+                prompt.push_str(&format!("- {}: {}\n", display_dir(sub), overview));
+            }
+        }
+        prompt
+    }
+
+    /// Resolve a batch of prompts, serving cache hits directly and sending
+    /// the rest to the provider at most `concurrency` at a time
+    async fn run_batch(
+        &self,
+        kind: &str,
+        inputs: Vec<(PathBuf, String)>,
+        cache: &mut SummaryCache,
+        provider_calls: &mut usize,
+        cache_hits: &mut usize,
+    ) -> Result<Vec<(PathBuf, String, bool)>> {
+        let mut results = Vec::with_capacity(inputs.len());
+        let mut misses = Vec::new();
+
+        for (path, prompt) in inputs {
+            let key = SummaryCache::key(&self.model, kind, &prompt);
+            match cache.get(&key) {
+                Some(summary) => {
+                    *cache_hits += 1;
+                    results.push((path, summary.clone(), true));
+                }
+                None => misses.push((path, prompt, key)),
+            }
+        }
+
+        let generated: Vec<Result<(PathBuf, String, String)>> = stream::iter(misses)
+            .map(|(path, prompt, key)| async move {
+                let summary = self.generate(kind, &path, &prompt).await?;
+                Ok((path, summary, key))
+            })
+            .buffered(self.concurrency)
+            .collect()
+            .await;
+
+        for result in generated {
+            let (path, summary, key) = result?;
+            if self.provider.is_some() {
+                *provider_calls += 1;
+            }
+            cache.insert(key, summary.clone());
+            results.push((path, summary, false));
+        }
+
+        results.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(results)
+    }
+
+    async fn generate(&self, kind: &str, path: &Path, prompt: &str) -> Result<String> {
+        let Some(provider) = &self.provider else {
+            return Ok(heuristic_summary(kind, prompt));
+        };
+
+        let request = ProviderRequest {
+            id: Uuid::new_v4(),
+            messages: vec![
+                ProviderMessage {
+                    role: "system".to_string(),
+                    content: format!(
+                        "You write onboarding documentation for a codebase. Summarize the {} \
+                         below in {}. Reply with the summary only.",
+                        kind,
+                        self.length_hint()
+                    ),
+                },
+                ProviderMessage {
+                    role: "user".to_string(),
+                    content: prompt.to_string(),
+                },
+            ],
+            model: self.model.clone(),
+            stream: false,
+            temperature: Some(0.0),
+            max_tokens: None,
+        };
+
+        let response = provider.complete(request).await.map_err(|e| {
+            FennecError::Command(Box::new(std::io::Error::other(format!(
+                "Failed to summarize {}: {}",
+                path.display(),
+                e
+            ))))
+        })?;
+        Ok(response.content.trim().to_string())
+    }
+}
+
+/// Offline stand-in for a provider summary: the leading doc comment of a
+/// file, or the shape of a directory
+fn heuristic_summary(kind: &str, prompt: &str) -> String {
+    if kind == "directory" {
+        let files = prompt
+            .split("\nFiles:\n")
+            .nth(1)
+            .map(|section| {
+                section
+                    .lines()
+                    .take_while(|line| line.starts_with("- "))
+                    .count()
+            })
+            .unwrap_or(0);
+        let subdirectories = prompt
+            .split("\nSubdirectories:\n")
+            .nth(1)
+            .map(|section| section.lines().filter(|l| l.starts_with("- ")).count())
+            .unwrap_or(0);
+        return format!(
+            "Contains {} files and {} subdirectories.",
+            files, subdirectories
+        );
+    }
+
+    let body = prompt.split_once("```\n").map(|(_, b)| b).unwrap_or(prompt);
+    let body = body.strip_suffix("\n```").unwrap_or(body);
+    let doc: Vec<&str> = body
+        .lines()
+        .map(str::trim)
+        .take_while(|line| {
+            line.starts_with("//!") || line.starts_with("///") || line.starts_with('#')
+        })
+        .map(|line| line.trim_start_matches(['/', '!', '#']).trim())
+        .filter(|line| !line.is_empty())
+        .collect();
+    let lines = body.lines().count();
+
+    if doc.is_empty() {
+        format!("{} lines.", lines)
+    } else {
+        format!("{} ({} lines)", doc.join(" "), lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_heuristic_tree_mirrors_layout() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("src/net")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "//! Crate root\npub mod net;\n").unwrap();
+        std::fs::write(root.join("src/net/mod.rs"), "//! Networking\n").unwrap();
+        std::fs::write(root.join("logo.png"), [0u8, 1, 2]).unwrap();
+
+        let mut cache = SummaryCache::default();
+        let tree = TreeSummarizer::new(None, "unused")
+            .summarize(root, &mut cache)
+            .await
+            .unwrap();
+
+        let paths: Vec<_> = tree.directories.iter().map(|d| d.path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::new(),
+                PathBuf::from("src"),
+                PathBuf::from("src/net")
+            ]
+        );
+        assert_eq!(tree.file_count(), 2);
+        assert_eq!(tree.provider_calls, 0);
+        assert_eq!(tree.directories[1].files[0].summary, "Crate root (2 lines)");
+        assert_eq!(
+            tree.directories[1].overview,
+            "Contains 1 files and 1 subdirectories."
+        );
+
+        let out = TempDir::new().unwrap();
+        let written = tree.write_summary_files(out.path()).await.unwrap();
+        assert_eq!(written.len(), 3);
+        let src = std::fs::read_to_string(out.path().join("src/SUMMARY.md")).unwrap();
+        assert!(src.starts_with("# src/\n"));
+        assert!(src.contains("- `lib.rs` — Crate root"));
+        assert!(src.contains("- [net/](net/SUMMARY.md) — Contains 1 files"));
+    }
+
+    #[tokio::test]
+    async fn test_cache_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(SUMMARY_CACHE_FILE);
+
+        let mut cache = SummaryCache::load(&path).await;
+        assert!(cache.is_empty());
+        let key = SummaryCache::key("m", "file", "fn main() {}");
+        assert_ne!(key, SummaryCache::key("other", "file", "fn main() {}"));
+        cache.insert(key.clone(), "Entry point".to_string());
+        cache.save(&path).await.unwrap();
+
+        let cache = SummaryCache::load(&path).await;
+        assert_eq!(cache.get(&key).map(String::as_str), Some("Entry point"));
+    }
+}
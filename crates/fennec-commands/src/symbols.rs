@@ -1,8 +1,16 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use syn::visit::{self, Visit};
 use syn::{ItemEnum, ItemFn, ItemImpl, ItemMod, ItemStruct, ItemTrait, ItemType};
+use walkdir::WalkDir;
+
+/// On-disk schema version of the persisted symbol index. Bump this whenever
+/// `Symbol` or the persisted layout changes; older files are rebuilt.
+pub const SYMBOL_INDEX_SCHEMA_VERSION: u32 = 2;
+
+/// Location of the persisted index relative to the workspace root
+const SYMBOL_INDEX_FILE: &str = ".fennec/symbol_index.json";
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum SymbolType {
@@ -32,6 +40,13 @@ pub struct Symbol {
     pub line: usize,
     pub visibility: Visibility,
     pub doc_comment: Option<String>,
+    /// Enclosing module path within its crate, e.g. `transcript::store`;
+    /// empty at the crate root
+    #[serde(default)]
+    pub module_path: String,
+    /// Declaration line from source up to the body, e.g. `pub fn load(path: &Path) -> Self`
+    #[serde(default)]
+    pub signature: Option<String>,
 }
 
 impl Symbol {
@@ -49,6 +64,17 @@ impl Symbol {
             line,
             visibility,
             doc_comment: None,
+            module_path: String::new(),
+            signature: None,
+        }
+    }
+
+    /// Name qualified with its module path
+    pub fn qualified_name(&self) -> String {
+        if self.module_path.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}::{}", self.module_path, self.name)
         }
     }
 
@@ -69,11 +95,17 @@ impl SymbolVisitor {
     fn new(file_path: PathBuf) -> Self {
         Self {
             symbols: Vec::new(),
+            current_module: file_module_path(&file_path),
             file_path,
-            current_module: Vec::new(),
         }
     }
 
+    /// Add a symbol declared in the module currently being visited
+    fn record(&mut self, mut symbol: Symbol) {
+        symbol.module_path = self.current_module.join("::");
+        self.symbols.push(symbol);
+    }
+
     fn extract_visibility(vis: &syn::Visibility) -> Visibility {
         match vis {
             syn::Visibility::Public(_) => Visibility::Public,
@@ -113,7 +145,7 @@ impl<'ast> Visit<'ast> for SymbolVisitor {
             visibility,
         );
 
-        self.symbols.push(symbol);
+        self.record(symbol);
         visit::visit_item_fn(self, node);
     }
 
@@ -129,7 +161,7 @@ impl<'ast> Visit<'ast> for SymbolVisitor {
             visibility,
         );
 
-        self.symbols.push(symbol);
+        self.record(symbol);
         visit::visit_item_struct(self, node);
     }
 
@@ -145,7 +177,7 @@ impl<'ast> Visit<'ast> for SymbolVisitor {
             visibility,
         );
 
-        self.symbols.push(symbol);
+        self.record(symbol);
         visit::visit_item_enum(self, node);
     }
 
@@ -161,7 +193,7 @@ impl<'ast> Visit<'ast> for SymbolVisitor {
             visibility,
         );
 
-        self.symbols.push(symbol);
+        self.record(symbol);
         visit::visit_item_trait(self, node);
     }
 
@@ -177,7 +209,7 @@ impl<'ast> Visit<'ast> for SymbolVisitor {
             visibility,
         );
 
-        self.symbols.push(symbol);
+        self.record(symbol);
         visit::visit_item_type(self, node);
     }
 
@@ -193,7 +225,7 @@ impl<'ast> Visit<'ast> for SymbolVisitor {
             visibility,
         );
 
-        self.symbols.push(symbol);
+        self.record(symbol);
 
         // Track module nesting
         self.current_module.push(node.ident.to_string());
@@ -214,7 +246,7 @@ impl<'ast> Visit<'ast> for SymbolVisitor {
                     line,
                     Visibility::Private, // Impls don't have visibility
                 );
-                self.symbols.push(symbol);
+                self.record(symbol);
             }
         }
 
@@ -230,7 +262,127 @@ pub fn extract_symbols(file_path: &Path, content: &str) -> Result<Vec<Symbol>, S
     let mut visitor = SymbolVisitor::new(file_path.to_path_buf());
     visitor.visit_file(&syntax_tree);
 
-    Ok(visitor.symbols)
+    let lines: Vec<&str> = content.lines().collect();
+    let mut symbols = visitor.symbols;
+    for symbol in &mut symbols {
+        symbol.signature = signature_at(&lines, symbol.line);
+    }
+    Ok(symbols)
+}
+
+/// Module path implied by a file's location under its crate's `src/`
+/// directory; `lib.rs`, `main.rs` and `mod.rs` name their parent module
+fn file_module_path(file_path: &Path) -> Vec<String> {
+    let components: Vec<String> = file_path
+        .with_extension("")
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    let start = components
+        .iter()
+        .rposition(|c| c == "src")
+        .map(|i| i + 1)
+        .unwrap_or(components.len().saturating_sub(1));
+
+    let mut module: Vec<String> = components[start..].to_vec();
+    if matches!(
+        module.last().map(String::as_str),
+        Some("lib" | "main" | "mod")
+    ) {
+        module.pop();
+    }
+    module
+}
+
+/// One-line declaration starting at 1-based `line`: everything up to the
+/// opening brace or terminating semicolon, with whitespace collapsed
+fn signature_at(lines: &[&str], line: usize) -> Option<String> {
+    let mut signature = String::new();
+    for text in lines.iter().skip(line.checked_sub(1)?).take(8) {
+        let end = text.find(['{', ';']);
+        let part = &text[..end.unwrap_or(text.len())];
+        if !signature.is_empty() {
+            signature.push(' ');
+        }
+        signature.push_str(part.trim());
+        if end.is_some() {
+            break;
+        }
+    }
+
+    let signature = signature.split_whitespace().collect::<Vec<_>>().join(" ");
+    let signature = signature
+        .replace("( ", "(")
+        .replace(", )", ")")
+        .replace(" )", ")");
+    (!signature.is_empty()).then_some(signature)
+}
+
+/// How a fuzzy query matched a symbol name, best first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    /// The name starts with the query (case-insensitive)
+    Prefix,
+    /// Each hump of the query starts a word of the name, e.g. `TrStr` in
+    /// `TranscriptStore` or `ld_cfg` in `load_config`
+    CamelHump,
+    /// The query appears somewhere in the name (case-insensitive)
+    Substring,
+}
+
+/// Match `query` against a symbol `name`
+pub fn fuzzy_match(query: &str, name: &str) -> Option<MatchKind> {
+    let query_lower = query.to_lowercase();
+    let name_lower = name.to_lowercase();
+
+    if name_lower.starts_with(&query_lower) {
+        Some(MatchKind::Prefix)
+    } else if camel_hump_match(query, name) {
+        Some(MatchKind::CamelHump)
+    } else if name_lower.contains(&query_lower) {
+        Some(MatchKind::Substring)
+    } else {
+        None
+    }
+}
+
+/// Split an identifier into words at `_` and lower-to-upper case changes
+fn humps(identifier: &str) -> Vec<Vec<char>> {
+    let mut humps: Vec<Vec<char>> = Vec::new();
+    let mut previous_lower = false;
+    for c in identifier.chars() {
+        if c == '_' {
+            previous_lower = false;
+            humps.push(Vec::new());
+            continue;
+        }
+        if c.is_uppercase() && previous_lower || humps.is_empty() {
+            humps.push(Vec::new());
+        }
+        previous_lower = c.is_lowercase() || c.is_ascii_digit();
+        if let Some(hump) = humps.last_mut() {
+            hump.extend(c.to_lowercase());
+        }
+    }
+    humps.retain(|hump| !hump.is_empty());
+    humps
+}
+
+/// Each query hump must start a later name hump, with its remaining
+/// characters appearing in order within that hump
+fn camel_hump_match(query: &str, name: &str) -> bool {
+    let name_humps = humps(name);
+    let mut remaining = name_humps.iter();
+
+    humps(query).iter().all(|query_hump| {
+        remaining.any(|name_hump| {
+            name_hump.first() == query_hump.first() && {
+                let mut chars = name_hump[1..].iter();
+                query_hump[1..].iter().all(|c| chars.any(|n| n == c))
+            }
+        })
+    })
 }
 
 /// Symbol index for fast lookup
@@ -240,6 +392,27 @@ pub struct SymbolIndex {
     by_name: HashMap<String, Vec<usize>>,
     by_type: HashMap<SymbolType, Vec<usize>>,
     by_file: HashMap<PathBuf, Vec<usize>>,
+    /// Content hash of every indexed file, used to skip unchanged files
+    #[serde(default)]
+    file_hashes: HashMap<PathBuf, String>,
+    /// Files parsed since this index was created or loaded
+    #[serde(skip)]
+    files_parsed: usize,
+}
+
+/// Persisted form of a [`SymbolIndex`]
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedSymbolIndex {
+    version: u32,
+    root: PathBuf,
+    files: Vec<PersistedFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedFile {
+    path: PathBuf,
+    hash: String,
+    symbols: Vec<Symbol>,
 }
 
 impl SymbolIndex {
@@ -249,7 +422,186 @@ impl SymbolIndex {
             by_name: HashMap::new(),
             by_type: HashMap::new(),
             by_file: HashMap::new(),
+            file_hashes: HashMap::new(),
+            files_parsed: 0,
+        }
+    }
+
+    /// Path of the persisted index for a workspace
+    pub fn index_path(workspace_path: &Path) -> PathBuf {
+        workspace_path.join(SYMBOL_INDEX_FILE)
+    }
+
+    /// Load the persisted index for a workspace.
+    ///
+    /// A missing, unreadable, or out-of-date index (different schema version
+    /// or workspace root) yields an empty index so the next refresh rebuilds it.
+    pub fn load(workspace_path: &Path) -> Self {
+        let Ok(content) = std::fs::read_to_string(Self::index_path(workspace_path)) else {
+            return Self::new();
+        };
+
+        let persisted: PersistedSymbolIndex = match serde_json::from_str(&content) {
+            Ok(persisted) => persisted,
+            Err(e) => {
+                tracing::debug!("Discarding unreadable symbol index: {}", e);
+                return Self::new();
+            }
+        };
+        if persisted.version != SYMBOL_INDEX_SCHEMA_VERSION || persisted.root != workspace_path {
+            tracing::debug!(
+                "Rebuilding symbol index (schema version {} -> {})",
+                persisted.version,
+                SYMBOL_INDEX_SCHEMA_VERSION
+            );
+            return Self::new();
+        }
+
+        let mut index = Self::new();
+        for file in persisted.files {
+            index.file_hashes.insert(file.path, file.hash);
+            index.add_symbols(file.symbols);
+        }
+        index
+    }
+
+    /// Persist the index under the workspace `.fennec/` directory
+    pub fn save(&self, workspace_path: &Path) -> std::io::Result<()> {
+        let mut files: Vec<PersistedFile> = self
+            .file_hashes
+            .iter()
+            .map(|(path, hash)| PersistedFile {
+                path: path.clone(),
+                hash: hash.clone(),
+                symbols: self.find_in_file(path).into_iter().cloned().collect(),
+            })
+            .collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let persisted = PersistedSymbolIndex {
+            version: SYMBOL_INDEX_SCHEMA_VERSION,
+            root: workspace_path.to_path_buf(),
+            files,
+        };
+        let json = serde_json::to_string(&persisted).map_err(std::io::Error::other)?;
+
+        let index_path = Self::index_path(workspace_path);
+        if let Some(parent) = index_path.parent() {
+            std::fs::create_dir_all(parent)?;
         }
+        let temp_path = index_path.with_extension("json.tmp");
+        std::fs::write(&temp_path, json)?;
+        std::fs::rename(&temp_path, &index_path)
+    }
+
+    /// Rust source files in a workspace, skipping hidden, `target` and
+    /// `node_modules` directories
+    pub fn rust_files(workspace_path: &Path) -> Vec<PathBuf> {
+        WalkDir::new(workspace_path)
+            .max_depth(10)
+            .into_iter()
+            .filter_entry(|e| {
+                if e.path() == workspace_path {
+                    return true;
+                }
+                !e.file_name()
+                    .to_str()
+                    .map(|s| s.starts_with('.') || s == "target" || s == "node_modules")
+                    .unwrap_or(false)
+            })
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| e.into_path())
+            .filter(|p| p.extension().map(|ext| ext == "rs").unwrap_or(false))
+            .collect()
+    }
+
+    /// Re-index the given paths, re-parsing only files whose content hash
+    /// changed. Paths that no longer exist are dropped from the index.
+    ///
+    /// Returns the number of files whose entries changed.
+    pub fn update_paths(&mut self, changed: &[PathBuf]) -> usize {
+        let mut stale = HashSet::new();
+        let mut fresh = Vec::new();
+
+        for path in changed {
+            let content = match std::fs::read(path) {
+                Ok(content) => content,
+                Err(_) => {
+                    if self.file_hashes.remove(path).is_some() {
+                        stale.insert(path.clone());
+                    }
+                    continue;
+                }
+            };
+            if path.extension().map(|ext| ext != "rs").unwrap_or(true) {
+                continue;
+            }
+
+            let hash = format!("{:x}", md5::compute(&content));
+            if self.file_hashes.get(path) == Some(&hash) {
+                continue;
+            }
+
+            self.files_parsed += 1;
+            // Files that fail to parse are still recorded so they are not
+            // re-parsed until their content changes.
+            let symbols = String::from_utf8(content)
+                .ok()
+                .and_then(|text| extract_symbols(path, &text).ok())
+                .unwrap_or_default();
+            self.file_hashes.insert(path.clone(), hash);
+            stale.insert(path.clone());
+            fresh.extend(symbols);
+        }
+
+        let changed_files = stale.len();
+        if !stale.is_empty() {
+            self.symbols.retain(|s| !stale.contains(&s.path));
+            self.reindex();
+        }
+        self.add_symbols(fresh);
+        changed_files
+    }
+
+    /// Bring the index in line with the workspace: drop deleted files and
+    /// re-parse new or modified ones. Returns the number of files changed.
+    pub fn refresh(&mut self, workspace_path: &Path) -> usize {
+        let files = Self::rust_files(workspace_path);
+        let removed = self.retain_files(&files);
+        removed + self.update_paths(&files)
+    }
+
+    /// Drop every indexed file not in `files`, returning how many were removed
+    pub fn retain_files(&mut self, files: &[PathBuf]) -> usize {
+        let keep: HashSet<&PathBuf> = files.iter().collect();
+        let removed: HashSet<PathBuf> = self
+            .file_hashes
+            .keys()
+            .filter(|path| !keep.contains(path))
+            .cloned()
+            .collect();
+
+        if !removed.is_empty() {
+            self.file_hashes.retain(|path, _| !removed.contains(path));
+            self.symbols.retain(|s| !removed.contains(&s.path));
+            self.reindex();
+        }
+        removed.len()
+    }
+
+    /// Number of files parsed since this index was created or loaded
+    pub fn files_parsed(&self) -> usize {
+        self.files_parsed
+    }
+
+    /// Rebuild the lookup tables from `symbols`
+    fn reindex(&mut self) {
+        let symbols = std::mem::take(&mut self.symbols);
+        self.by_name.clear();
+        self.by_type.clear();
+        self.by_file.clear();
+        self.add_symbols(symbols);
     }
 
     /// Add a symbol to the index
@@ -301,6 +653,24 @@ impl SymbolIndex {
             .collect()
     }
 
+    /// Find symbols whose names fuzzily match `query`, best matches first:
+    /// prefix before camel-hump before substring, then shorter names
+    pub fn find_fuzzy(&self, query: &str) -> Vec<(MatchKind, &Symbol)> {
+        let mut matches: Vec<(MatchKind, &Symbol)> = self
+            .symbols
+            .iter()
+            .filter_map(|symbol| fuzzy_match(query, &symbol.name).map(|kind| (kind, symbol)))
+            .collect();
+        matches.sort_by(|(a_kind, a), (b_kind, b)| {
+            a_kind
+                .cmp(b_kind)
+                .then(a.name.len().cmp(&b.name.len()))
+                .then_with(|| a.name.cmp(&b.name))
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        matches
+    }
+
     /// Find symbols by type
     pub fn find_by_type(&self, symbol_type: &SymbolType) -> Vec<&Symbol> {
         self.by_type
@@ -338,6 +708,7 @@ impl SymbolIndex {
         self.by_name.clear();
         self.by_type.clear();
         self.by_file.clear();
+        self.file_hashes.clear();
     }
 }
 
@@ -483,4 +854,103 @@ mod tests {
         let results = index.find_by_name_partial("hello");
         assert_eq!(results.len(), 2);
     }
+
+    #[test]
+    fn test_fuzzy_match_kinds() {
+        assert_eq!(
+            fuzzy_match("trans", "TranscriptStore"),
+            Some(MatchKind::Prefix)
+        );
+        assert_eq!(
+            fuzzy_match("TrStr", "TranscriptStore"),
+            Some(MatchKind::CamelHump)
+        );
+        assert_eq!(
+            fuzzy_match("ld_cfg", "load_config"),
+            Some(MatchKind::CamelHump)
+        );
+        assert_eq!(
+            fuzzy_match("script", "TranscriptStore"),
+            Some(MatchKind::Substring)
+        );
+        assert_eq!(fuzzy_match("StTr", "TranscriptStore"), None);
+    }
+
+    #[test]
+    fn test_file_module_path() {
+        assert_eq!(
+            file_module_path(Path::new("/ws/crates/core/src/transcript/store.rs")),
+            vec!["transcript", "store"]
+        );
+        assert_eq!(
+            file_module_path(Path::new("/ws/src/transcript/mod.rs")),
+            vec!["transcript"]
+        );
+        assert!(file_module_path(Path::new("/ws/src/lib.rs")).is_empty());
+        assert_eq!(file_module_path(Path::new("build.rs")), vec!["build"]);
+    }
+
+    fn write_workspace_file(root: &Path, name: &str, content: &str) -> PathBuf {
+        let path = root.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_incremental_index_reparses_only_changed_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write_workspace_file(root, "src/a.rs", "pub fn alpha() {}\n");
+        let b = write_workspace_file(root, "src/b.rs", "pub fn beta() {}\n");
+        write_workspace_file(root, "src/c.rs", "pub struct Gamma;\n");
+
+        let mut index = SymbolIndex::new();
+        assert_eq!(index.refresh(root), 3);
+        assert_eq!(index.files_parsed(), 3);
+        index.save(root).unwrap();
+
+        // A reloaded index needs no parsing when nothing changed.
+        let mut index = SymbolIndex::load(root);
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.refresh(root), 0);
+        assert_eq!(index.files_parsed(), 0);
+
+        std::fs::write(&b, "pub fn beta_renamed() {}\n").unwrap();
+        assert_eq!(index.update_paths(std::slice::from_ref(&b)), 1);
+        assert_eq!(index.files_parsed(), 1);
+        assert!(index.find_by_name("beta").is_empty());
+        assert_eq!(index.find_by_name("beta_renamed").len(), 1);
+        assert_eq!(index.find_by_name("alpha").len(), 1);
+
+        std::fs::remove_file(&b).unwrap();
+        assert_eq!(index.update_paths(std::slice::from_ref(&b)), 1);
+        assert!(index.find_in_file(&b).is_empty());
+        assert_eq!(index.len(), 2);
+    }
+
+    #[test]
+    fn test_index_load_rebuilds_on_schema_change() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write_workspace_file(root, "lib.rs", "pub fn alpha() {}\n");
+
+        let mut index = SymbolIndex::new();
+        index.refresh(root);
+        index.save(root).unwrap();
+
+        let index_path = SymbolIndex::index_path(root);
+        let mut persisted: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&index_path).unwrap()).unwrap();
+        persisted["version"] = serde_json::json!(SYMBOL_INDEX_SCHEMA_VERSION + 1);
+        std::fs::write(&index_path, persisted.to_string()).unwrap();
+
+        let mut index = SymbolIndex::load(root);
+        assert!(index.is_empty());
+        assert_eq!(index.refresh(root), 1);
+        assert_eq!(index.files_parsed(), 1);
+
+        std::fs::write(&index_path, "not json").unwrap();
+        assert!(SymbolIndex::load(root).is_empty());
+    }
 }
@@ -0,0 +1,186 @@
+//! Tree-sitter grammar registry and structural query execution.
+//!
+//! Grammars are resolved here so syntax-aware commands (structural search,
+//! symbol indexing) share one place that knows which languages are supported.
+
+use crate::search::NodeSpan;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use streaming_iterator::StreamingIterator;
+use thiserror::Error;
+use tree_sitter::{Language, Parser, Query, QueryCursor};
+
+/// Languages with a bundled tree-sitter grammar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyntaxLanguage {
+    Rust,
+}
+
+impl SyntaxLanguage {
+    /// All languages with a bundled grammar
+    pub const ALL: &'static [SyntaxLanguage] = &[SyntaxLanguage::Rust];
+
+    /// Resolve a language by name (case-insensitive)
+    pub fn from_name(name: &str) -> Result<Self, SyntaxError> {
+        match name.to_lowercase().as_str() {
+            "rust" | "rs" => Ok(SyntaxLanguage::Rust),
+            _ => Err(SyntaxError::UnsupportedLanguage(name.to_string())),
+        }
+    }
+
+    /// Resolve a language from a file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("rs") => Some(SyntaxLanguage::Rust),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SyntaxLanguage::Rust => "rust",
+        }
+    }
+
+    /// Load the tree-sitter grammar for this language
+    pub fn grammar(&self) -> Language {
+        match self {
+            SyntaxLanguage::Rust => tree_sitter_rust::LANGUAGE.into(),
+        }
+    }
+
+    /// Whether a file should be parsed with this language's grammar
+    pub fn matches_path(&self, path: &Path) -> bool {
+        Self::from_path(path) == Some(*self)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SyntaxError {
+    #[error(
+        "Structural search does not support language '{0}' (supported: {})",
+        supported_languages()
+    )]
+    UnsupportedLanguage(String),
+    #[error("Invalid tree-sitter query: {0}")]
+    InvalidQuery(String),
+    #[error("Failed to parse source: {0}")]
+    Parse(String),
+}
+
+fn supported_languages() -> String {
+    SyntaxLanguage::ALL
+        .iter()
+        .map(|l| l.name())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A single captured node from a structural query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyntaxCapture {
+    pub capture: String,
+    pub span: NodeSpan,
+    pub text: String,
+}
+
+/// A compiled tree-sitter query bound to a language
+pub struct SyntaxQuery {
+    language: SyntaxLanguage,
+    query: Query,
+}
+
+impl SyntaxQuery {
+    pub fn new(language: SyntaxLanguage, source: &str) -> Result<Self, SyntaxError> {
+        let query = Query::new(&language.grammar(), source)
+            .map_err(|e| SyntaxError::InvalidQuery(e.to_string()))?;
+        Ok(Self { language, query })
+    }
+
+    pub fn language(&self) -> SyntaxLanguage {
+        self.language
+    }
+
+    /// Run the query over `source`, returning captures in document order
+    pub fn captures(&self, source: &str) -> Result<Vec<SyntaxCapture>, SyntaxError> {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&self.language.grammar())
+            .map_err(|e| SyntaxError::Parse(e.to_string()))?;
+        let tree = parser
+            .parse(source, None)
+            .ok_or_else(|| SyntaxError::Parse("parser returned no tree".to_string()))?;
+
+        let names = self.query.capture_names();
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(&self.query, tree.root_node(), source.as_bytes());
+        let mut captures = Vec::new();
+
+        while let Some(query_match) = matches.next() {
+            for capture in query_match.captures {
+                let node = capture.node;
+                let start = node.start_position();
+                let end = node.end_position();
+                captures.push(SyntaxCapture {
+                    capture: names[capture.index as usize].to_string(),
+                    span: NodeSpan {
+                        start_line: start.row,
+                        start_column: start.column,
+                        end_line: end.row,
+                        end_column: end.column,
+                        start_byte: node.start_byte(),
+                        end_byte: node.end_byte(),
+                    },
+                    text: source[node.byte_range()].to_string(),
+                });
+            }
+        }
+
+        captures.sort_by_key(|c| (c.span.start_byte, c.span.end_byte));
+        Ok(captures)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_resolution() {
+        assert_eq!(
+            SyntaxLanguage::from_name("Rust").unwrap(),
+            SyntaxLanguage::Rust
+        );
+        assert_eq!(
+            SyntaxLanguage::from_path(Path::new("src/lib.rs")),
+            Some(SyntaxLanguage::Rust)
+        );
+        assert_eq!(SyntaxLanguage::from_path(Path::new("main.py")), None);
+
+        let err = SyntaxLanguage::from_name("python").unwrap_err();
+        assert!(err.to_string().contains("supported: rust"));
+    }
+
+    #[test]
+    fn test_query_captures() {
+        let source = "struct Point { x: i32 }\nfn origin() -> Point { Point { x: 0 } }\n";
+        let query = SyntaxQuery::new(
+            SyntaxLanguage::Rust,
+            "(function_item name: (identifier) @name)",
+        )
+        .unwrap();
+
+        let captures = query.captures(source).unwrap();
+        assert_eq!(captures.len(), 1);
+        assert_eq!(captures[0].capture, "name");
+        assert_eq!(captures[0].text, "origin");
+        assert_eq!(captures[0].span.start_line, 1);
+        assert_eq!(captures[0].span.start_column, 3);
+
+        assert!(matches!(
+            SyntaxQuery::new(SyntaxLanguage::Rust, "(not_a_node) @x"),
+            Err(SyntaxError::InvalidQuery(_))
+        ));
+    }
+}
@@ -0,0 +1,478 @@
+//! Parsing of `cargo test` / `cargo nextest` output into per-test results and
+//! rendering of those results as JUnit XML.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TestOutcome {
+    Passed,
+    Failed,
+    Ignored,
+}
+
+/// Result of a single test case
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestCaseResult {
+    /// Test binary the case belongs to (crate or integration test name)
+    pub suite: String,
+    pub name: String,
+    pub outcome: TestOutcome,
+    /// Only reported by nextest
+    pub duration_secs: Option<f64>,
+    /// Captured output of a failing test
+    pub message: Option<String>,
+}
+
+/// Parsed outcome of one test command invocation
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TestRunSummary {
+    /// Whether the test command exited successfully
+    pub success: bool,
+    pub duration_ms: u64,
+    pub tests: Vec<TestCaseResult>,
+}
+
+impl TestRunSummary {
+    pub fn count(&self, outcome: TestOutcome) -> usize {
+        self.tests.iter().filter(|t| t.outcome == outcome).count()
+    }
+
+    /// Names of failed tests, deduplicated across suites
+    pub fn failed_names(&self) -> Vec<String> {
+        let mut seen = HashSet::new();
+        self.tests
+            .iter()
+            .filter(|t| t.outcome == TestOutcome::Failed)
+            .filter(|t| seen.insert(t.name.as_str()))
+            .map(|t| t.name.clone())
+            .collect()
+    }
+}
+
+struct Patterns {
+    cargo_running: Regex,
+    cargo_doc_tests: Regex,
+    cargo_test: Regex,
+    cargo_failure_header: Regex,
+    nextest_status: Regex,
+    nextest_output_header: Regex,
+}
+
+fn patterns() -> &'static Patterns {
+    static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| Patterns {
+        cargo_running: Regex::new(r"^\s*Running (?:unittests )?(?:\S+ \()?([^()\s]+)\)?\s*$")
+            .expect("valid pattern"),
+        cargo_doc_tests: Regex::new(r"^\s*Doc-tests (\S+)").expect("valid pattern"),
+        cargo_test: Regex::new(r"^test (.+) \.\.\. (ok|FAILED|ignored)\b").expect("valid pattern"),
+        cargo_failure_header: Regex::new(r"^---- (.+) stdout ----$").expect("valid pattern"),
+        nextest_status: Regex::new(
+            r"^\s*(PASS|FAIL|SKIP|TIMEOUT|LEAK|SIG[A-Z]+)\s+\[\s*(?:([\d.]+)s)?\s*\]\s+(\S+)\s+(.+?)\s*$",
+        )
+        .expect("valid pattern"),
+        nextest_output_header: Regex::new(r"^\s*(?:-+|─+) STD(?:OUT|ERR):\s+(\S+)\s+(.+?)\s*(?:-+|─+)\s*$")
+            .expect("valid pattern"),
+    })
+}
+
+/// Suite name for a test binary path such as
+/// `target/debug/deps/fennec_commands-1a2b3c4d`
+fn suite_from_binary(path: &str) -> String {
+    let file = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    let file = file.strip_suffix(".exe").unwrap_or(file);
+    match file.rsplit_once('-') {
+        Some((name, hash)) if hash.chars().all(|c| c.is_ascii_hexdigit()) => name.to_string(),
+        _ => file.to_string(),
+    }
+}
+
+/// Rebuild a single transcript from separately captured streams.
+///
+/// `cargo test` prints the `Running <binary>` headers on stderr and the test
+/// results on stdout, so each `running N tests` block on stdout is paired
+/// with the next header from stderr. nextest reports on stderr only, which is
+/// appended as-is.
+pub fn merge_test_streams(stdout: &str, stderr: &str) -> String {
+    let patterns = patterns();
+    let mut headers = stderr.lines().filter(|line| {
+        patterns.cargo_running.is_match(line) || patterns.cargo_doc_tests.is_match(line)
+    });
+
+    let mut merged = String::with_capacity(stdout.len() + stderr.len());
+    for line in stdout.lines() {
+        if line.starts_with("running ") && (line.ends_with(" test") || line.ends_with(" tests")) {
+            if let Some(header) = headers.next() {
+                merged.push_str(header);
+                merged.push('\n');
+            }
+        }
+        merged.push_str(line);
+        merged.push('\n');
+    }
+    merged.push_str(stderr);
+    merged
+}
+
+/// Parse `cargo test` or `cargo nextest run` output into per-test results.
+///
+/// Lines that don't belong to either format are ignored, so compiler output
+/// interleaved on the same stream is harmless.
+pub fn parse_test_output(output: &str) -> Vec<TestCaseResult> {
+    let patterns = patterns();
+    let mut tests: Vec<TestCaseResult> = Vec::new();
+    let mut seen: HashSet<(String, String)> = HashSet::new();
+    let mut suite = String::new();
+    // nextest embeds libtest output for failures, which must not be parsed
+    // as separate results
+    let mut nextest = false;
+    // Failure output being collected: (suite, test name, lines)
+    let mut capture: Option<(String, String, Vec<&str>)> = None;
+
+    let finish_capture = |capture: &mut Option<(String, String, Vec<&str>)>,
+                          tests: &mut Vec<TestCaseResult>| {
+        if let Some((suite, name, lines)) = capture.take() {
+            let message = lines.join("\n").trim().to_string();
+            if message.is_empty() {
+                return;
+            }
+            if let Some(test) = tests
+                .iter_mut()
+                .rev()
+                .find(|t| t.name == name && t.suite == suite)
+            {
+                match &mut test.message {
+                    Some(existing) => {
+                        existing.push('\n');
+                        existing.push_str(&message);
+                    }
+                    None => test.message = Some(message),
+                }
+            }
+        }
+    };
+
+    for line in output.lines() {
+        if let Some(caps) = patterns.cargo_running.captures(line) {
+            finish_capture(&mut capture, &mut tests);
+            suite = suite_from_binary(&caps[1]);
+            continue;
+        }
+        if let Some(caps) = patterns.cargo_doc_tests.captures(line) {
+            finish_capture(&mut capture, &mut tests);
+            suite = format!("{} (doc)", &caps[1]);
+            continue;
+        }
+
+        let cargo_test = (!nextest)
+            .then(|| patterns.cargo_test.captures(line))
+            .flatten();
+        let parsed = if let Some(caps) = cargo_test {
+            let outcome = match &caps[2] {
+                "ok" => TestOutcome::Passed,
+                "FAILED" => TestOutcome::Failed,
+                _ => TestOutcome::Ignored,
+            };
+            Some((suite.clone(), caps[1].to_string(), outcome, None))
+        } else if let Some(caps) = patterns.nextest_status.captures(line) {
+            nextest = true;
+            let outcome = match &caps[1] {
+                "PASS" | "LEAK" => TestOutcome::Passed,
+                "SKIP" => TestOutcome::Ignored,
+                _ => TestOutcome::Failed,
+            };
+            let duration = caps.get(2).and_then(|d| d.as_str().parse().ok());
+            Some((caps[3].to_string(), caps[4].to_string(), outcome, duration))
+        } else {
+            None
+        };
+
+        if let Some((suite, name, outcome, duration_secs)) = parsed {
+            finish_capture(&mut capture, &mut tests);
+            // nextest repeats failures in its final summary
+            if !seen.insert((suite.clone(), name.clone())) {
+                continue;
+            }
+            tests.push(TestCaseResult {
+                suite,
+                name,
+                outcome,
+                duration_secs,
+                message: None,
+            });
+            continue;
+        }
+
+        if let Some(caps) = patterns.cargo_failure_header.captures(line) {
+            finish_capture(&mut capture, &mut tests);
+            capture = Some((suite.clone(), caps[1].to_string(), Vec::new()));
+            continue;
+        }
+        if let Some(caps) = patterns.nextest_output_header.captures(line) {
+            finish_capture(&mut capture, &mut tests);
+            capture = Some((caps[1].to_string(), caps[2].to_string(), Vec::new()));
+            continue;
+        }
+
+        if let Some((_, _, lines)) = &mut capture {
+            // The list of failed names that closes a cargo failure section
+            if line == "failures:" || line.starts_with("test result:") {
+                finish_capture(&mut capture, &mut tests);
+            } else {
+                lines.push(line);
+            }
+        }
+    }
+    finish_capture(&mut capture, &mut tests);
+
+    tests
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than tab/newline are invalid in XML 1.0
+            c if c.is_control() && c != '\n' && c != '\t' && c != '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Render a run as a JUnit XML document, one `<testsuite>` per test binary
+pub fn render_junit(summary: &TestRunSummary) -> String {
+    let mut suites: Vec<(&str, Vec<&TestCaseResult>)> = Vec::new();
+    for test in &summary.tests {
+        match suites.iter_mut().find(|(name, _)| *name == test.suite) {
+            Some((_, cases)) => cases.push(test),
+            None => suites.push((&test.suite, vec![test])),
+        }
+    }
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"fennec-test-watch\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.3}\">\n",
+        summary.tests.len(),
+        summary.count(TestOutcome::Failed),
+        summary.count(TestOutcome::Ignored),
+        summary.duration_ms as f64 / 1000.0
+    ));
+
+    for (suite, cases) in suites {
+        let failures = cases
+            .iter()
+            .filter(|c| c.outcome == TestOutcome::Failed)
+            .count();
+        let skipped = cases
+            .iter()
+            .filter(|c| c.outcome == TestOutcome::Ignored)
+            .count();
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\">\n",
+            escape_xml(suite),
+            cases.len(),
+            failures,
+            skipped
+        ));
+
+        for case in cases {
+            let time = case
+                .duration_secs
+                .map(|secs| format!(" time=\"{:.3}\"", secs))
+                .unwrap_or_default();
+            let open = format!(
+                "    <testcase name=\"{}\" classname=\"{}\"{}",
+                escape_xml(&case.name),
+                escape_xml(&case.suite),
+                time
+            );
+            match case.outcome {
+                TestOutcome::Passed => xml.push_str(&format!("{}/>\n", open)),
+                TestOutcome::Ignored => {
+                    xml.push_str(&format!("{}>\n      <skipped/>\n    </testcase>\n", open))
+                }
+                TestOutcome::Failed => {
+                    let message = case.message.as_deref().unwrap_or_default();
+                    let summary_line = message.lines().next().unwrap_or("test failed");
+                    xml.push_str(&format!(
+                        "{}>\n      <failure message=\"{}\">{}</failure>\n    </testcase>\n",
+                        open,
+                        escape_xml(summary_line),
+                        escape_xml(message)
+                    ));
+                }
+            }
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CARGO_OUTPUT: &str = r#"   Compiling demo v0.1.0 (/work/demo)
+    Finished `test` profile [unoptimized + debuginfo] target(s) in 0.52s
+     Running unittests src/lib.rs (target/debug/deps/demo-3f2a9c1b7d4e5f60)
+
+running 4 tests
+test parser::tests::parses_empty ... ok
+test parser::tests::parses_nested ... FAILED
+test slow::tests::benchmark ... ignored, too slow for CI
+test tests::adds ... ok
+
+failures:
+
+---- parser::tests::parses_nested stdout ----
+thread 'parser::tests::parses_nested' panicked at src/parser.rs:42:9:
+assertion `left == right` failed
+  left: 2
+ right: 3
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+
+
+failures:
+    parser::tests::parses_nested
+
+test result: FAILED. 2 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.01s
+
+     Running tests/cli.rs (target/debug/deps/cli-0a1b2c3d4e5f6789)
+
+running 1 test
+test runs_help ... ok
+
+test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+   Doc-tests demo
+
+running 1 test
+test src/lib.rs - add (line 5) ... ok
+
+test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.20s
+
+error: test failed, to rerun pass `--lib`
+"#;
+
+    const NEXTEST_OUTPUT: &str = r#"    Finished `test` profile [unoptimized + debuginfo] target(s) in 0.10s
+    Starting 3 tests across 2 binaries (1 test skipped)
+        PASS [   0.004s] demo parser::tests::parses_empty
+        FAIL [   0.011s] demo parser::tests::parses_nested
+
+--- STDOUT:              demo parser::tests::parses_nested ---
+
+running 1 test
+test parser::tests::parses_nested ... FAILED
+
+--- STDERR:              demo parser::tests::parses_nested ---
+thread 'parser::tests::parses_nested' panicked at src/parser.rs:42:9:
+assertion failed
+
+        SKIP [         ] demo slow::tests::benchmark
+        PASS [   0.020s] demo::cli runs_help
+------------
+     Summary [   0.031s] 3 tests run: 2 passed, 1 failed, 1 skipped
+        FAIL [   0.011s] demo parser::tests::parses_nested
+error: test run failed
+"#;
+
+    #[test]
+    fn test_parse_cargo_test_output() {
+        let tests = parse_test_output(CARGO_OUTPUT);
+        let names: Vec<_> = tests
+            .iter()
+            .map(|t| (t.suite.as_str(), t.name.as_str(), t.outcome))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("demo", "parser::tests::parses_empty", TestOutcome::Passed),
+                ("demo", "parser::tests::parses_nested", TestOutcome::Failed),
+                ("demo", "slow::tests::benchmark", TestOutcome::Ignored),
+                ("demo", "tests::adds", TestOutcome::Passed),
+                ("cli", "runs_help", TestOutcome::Passed),
+                (
+                    "demo (doc)",
+                    "src/lib.rs - add (line 5)",
+                    TestOutcome::Passed
+                ),
+            ]
+        );
+
+        let message = tests[1].message.as_deref().unwrap();
+        assert!(message.starts_with("thread 'parser::tests::parses_nested' panicked"));
+        assert!(message.contains("right: 3"));
+        assert!(!message.contains("failures:"));
+        assert!(tests[0].message.is_none());
+    }
+
+    #[test]
+    fn test_parse_nextest_output() {
+        let tests = parse_test_output(NEXTEST_OUTPUT);
+        assert_eq!(tests.len(), 4, "summary repeats must be deduplicated");
+
+        let failed = &tests[1];
+        assert_eq!(failed.suite, "demo");
+        assert_eq!(failed.outcome, TestOutcome::Failed);
+        assert_eq!(failed.duration_secs, Some(0.011));
+        let message = failed.message.as_deref().unwrap();
+        assert!(message.contains("assertion failed"));
+
+        assert_eq!(tests[2].outcome, TestOutcome::Ignored);
+        assert_eq!(tests[2].duration_secs, None);
+        assert_eq!(tests[3].suite, "demo::cli");
+    }
+
+    #[test]
+    fn test_merge_separate_streams() {
+        let stdout = "\nrunning 1 test\ntest a ... ok\n\nrunning 1 test\ntest b ... FAILED\n";
+        let stderr = "   Compiling demo v0.1.0\n     Running unittests src/lib.rs (target/debug/deps/demo-0123abcd)\n     Running tests/cli.rs (target/debug/deps/cli-4567ef01)\nerror: test failed\n";
+
+        let tests = parse_test_output(&merge_test_streams(stdout, stderr));
+        assert_eq!(tests.len(), 2);
+        assert_eq!(
+            (tests[0].suite.as_str(), tests[0].name.as_str()),
+            ("demo", "a")
+        );
+        assert_eq!(
+            (tests[1].suite.as_str(), tests[1].name.as_str()),
+            ("cli", "b")
+        );
+    }
+
+    #[test]
+    fn test_render_junit() {
+        let summary = TestRunSummary {
+            success: false,
+            duration_ms: 1500,
+            tests: parse_test_output(CARGO_OUTPUT),
+        };
+        assert_eq!(summary.failed_names(), vec!["parser::tests::parses_nested"]);
+
+        let xml = render_junit(&summary);
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
+        assert!(xml.contains(
+            "<testsuites name=\"fennec-test-watch\" tests=\"6\" failures=\"1\" skipped=\"1\" time=\"1.500\">"
+        ));
+        assert!(xml.contains("<testsuite name=\"demo\" tests=\"4\" failures=\"1\" skipped=\"1\">"));
+        assert!(xml.contains(
+            "<failure message=\"thread &apos;parser::tests::parses_nested&apos; panicked at src/parser.rs:42:9:\">"
+        ));
+        assert!(xml.contains(
+            "<testcase name=\"slow::tests::benchmark\" classname=\"demo\">\n      <skipped/>"
+        ));
+        assert!(xml.contains("<testsuite name=\"demo (doc)\""));
+        assert!(xml.trim_end().ends_with("</testsuites>"));
+    }
+}
@@ -1,13 +1,23 @@
-use crate::registry::{CommandContext, CommandDescriptor, CommandExecutor};
+use crate::registry::{
+    CommandContext, CommandDescriptor, CommandExecutor, CommandOutputEvent, OutputSender,
+};
+use crate::test_report::{
+    merge_test_streams, parse_test_output, render_junit, TestOutcome, TestRunSummary,
+};
 use anyhow::Result;
 use fennec_core::command::{Capability, CommandPreview, CommandResult};
 use fennec_core::error::FennecError;
 use fennec_security::SandboxLevel;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 use std::process::Stdio;
-use tokio::io::{AsyncBufReadExt, BufReader};
+use std::time::{Duration, Instant};
 use tokio::process::Command;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,6 +41,16 @@ pub struct TestWatchArgs {
     /// Maximum duration to watch in seconds (0 = unlimited)
     #[serde(default)]
     pub max_duration_seconds: u64,
+
+    /// On each change, rerun only the tests that failed last time, then the
+    /// full suite once they pass
+    #[serde(default)]
+    pub failures_first: bool,
+
+    /// Report written after every run, as `junit:<path>` (relative paths
+    /// resolve against the workspace)
+    #[serde(default)]
+    pub report: Option<String>,
 }
 
 fn default_test_command() -> String {
@@ -45,6 +65,217 @@ fn default_debounce_ms() -> u64 {
     500
 }
 
+/// Where to write a machine-readable report after each run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TestReportTarget {
+    Junit(PathBuf),
+}
+
+impl TestReportTarget {
+    /// Parse a `<format>:<path>` report spec
+    pub fn parse(spec: &str) -> Result<Self> {
+        match spec.split_once(':') {
+            Some(("junit", path)) if !path.trim().is_empty() => {
+                Ok(TestReportTarget::Junit(PathBuf::from(path.trim())))
+            }
+            _ => Err(FennecError::Command(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Invalid report '{}': expected 'junit:<path>'", spec),
+            )))
+            .into()),
+        }
+    }
+}
+
+/// Which tests a run executes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "tests", rename_all = "snake_case")]
+pub enum RunScope {
+    Full,
+    /// Only the named tests, matched exactly
+    Failures(Vec<String>),
+}
+
+impl RunScope {
+    /// Test command arguments for this scope, appending exact-match filters
+    /// after any `--` the caller already passes to the test harness
+    pub fn test_args(&self, base: &[String]) -> Vec<String> {
+        let mut args = base.to_vec();
+        if let RunScope::Failures(names) = self {
+            if !args.iter().any(|arg| arg == "--") {
+                args.push("--".to_string());
+            }
+            args.extend(names.iter().cloned());
+            args.push("--exact".to_string());
+        }
+        args
+    }
+}
+
+/// Remembers which tests are failing so later runs can target them first
+#[derive(Debug, Clone, Default)]
+pub struct FailureTracker {
+    failing: Vec<String>,
+}
+
+impl FailureTracker {
+    pub fn failing(&self) -> &[String] {
+        &self.failing
+    }
+
+    /// Scope of the next run: known failures if there are any, else everything
+    pub fn next_scope(&self) -> RunScope {
+        if self.failing.is_empty() {
+            RunScope::Full
+        } else {
+            RunScope::Failures(self.failing.clone())
+        }
+    }
+
+    /// Update the failure set from a finished run. Returns `true` when a
+    /// targeted run cleared every known failure and the full suite should
+    /// run next.
+    pub fn record(&mut self, scope: &RunScope, summary: &TestRunSummary) -> bool {
+        // Nothing ran (e.g. the build failed); what was failing still is
+        if summary.tests.is_empty() && !summary.success {
+            return false;
+        }
+
+        let failed = summary.failed_names();
+        match scope {
+            RunScope::Full => {
+                self.failing = failed;
+                false
+            }
+            RunScope::Failures(targeted) => {
+                // Targeted tests that passed or no longer exist drop out
+                self.failing = targeted
+                    .iter()
+                    .filter(|name| failed.contains(name))
+                    .cloned()
+                    .collect();
+                self.failing.is_empty()
+            }
+        }
+    }
+}
+
+/// One test command invocation within a watch session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestRunRecord {
+    pub run: usize,
+    pub scope: RunScope,
+    /// Changed files that triggered the run (empty for the initial run)
+    pub trigger: Vec<PathBuf>,
+    pub success: bool,
+    pub passed: usize,
+    pub failed: usize,
+    pub ignored: usize,
+    pub duration_ms: u64,
+    pub failing: Vec<String>,
+}
+
+/// Structured result of a watch session
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TestWatchReport {
+    pub runs: Vec<TestRunRecord>,
+    /// Tests still failing when watching stopped
+    pub failing: Vec<String>,
+    pub report_path: Option<PathBuf>,
+}
+
+/// Human-readable session log, mirrored to the output stream when streaming
+struct WatchLog<'a> {
+    output: String,
+    events: Option<&'a OutputSender>,
+}
+
+impl WatchLog<'_> {
+    fn line(&mut self, line: impl Into<String>) {
+        let line = line.into();
+        self.output.push_str(&line);
+        self.output.push('\n');
+        if let Some(events) = self.events {
+            let _ = events.send(CommandOutputEvent::Stdout { line });
+        }
+    }
+}
+
+fn build_watch_globs(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern).map_err(|e| {
+            FennecError::Command(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Invalid watch pattern '{}': {}", pattern, e),
+            )))
+        })?;
+        builder.add(glob);
+    }
+
+    Ok(builder.build().map_err(|e| {
+        FennecError::Command(Box::new(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Invalid watch patterns: {}", e),
+        )))
+    })?)
+}
+
+/// Wait for the next burst of file changes, returning once no further
+/// change has arrived for `debounce`. Returns `None` when watching should
+/// stop.
+async fn next_change(
+    changes: &mut mpsc::UnboundedReceiver<PathBuf>,
+    debounce: Duration,
+    cancellation_token: &CancellationToken,
+    deadline: Option<Instant>,
+) -> Option<Vec<PathBuf>> {
+    let stop = async {
+        match deadline {
+            Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::pin!(stop);
+
+    let mut changed: Vec<PathBuf> = Vec::new();
+    loop {
+        let pending_change = !changed.is_empty();
+        let quiet = async move {
+            if pending_change {
+                tokio::time::sleep(debounce).await
+            } else {
+                std::future::pending().await
+            }
+        };
+
+        tokio::select! {
+            _ = cancellation_token.cancelled() => return None,
+            _ = &mut stop => return None,
+            change = changes.recv() => match change {
+                Some(path) => {
+                    if !changed.contains(&path) {
+                        changed.push(path);
+                    }
+                }
+                None => return None,
+            },
+            _ = quiet => return Some(changed),
+        }
+    }
+}
+
+fn truncate_output(output: &str, limit: usize) -> String {
+    if output.len() <= limit {
+        return output.to_string();
+    }
+    let mut end = limit;
+    while !output.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}...\n(output truncated)", &output[..end])
+}
+
 pub struct TestWatchCommand {
     descriptor: CommandDescriptor,
 }
@@ -61,16 +292,20 @@ impl TestWatchCommand {
                 sandbox_level_required: SandboxLevel::WorkspaceWrite,
                 supports_preview: false,
                 supports_dry_run: true,
+                timeout: None,
+                alias_of: None,
             },
         }
     }
 
+    /// Run the test command once. Returns `None` if cancelled mid-run.
     async fn run_tests(
         &self,
         test_command: &str,
         test_args: &[String],
         workspace_path: &str,
-    ) -> Result<(bool, String)> {
+        cancellation_token: &CancellationToken,
+    ) -> Result<Option<(TestRunSummary, String)>> {
         let parts: Vec<&str> = test_command.split_whitespace().collect();
         if parts.is_empty() {
             return Err(FennecError::Command(Box::new(std::io::Error::new(
@@ -95,145 +330,330 @@ impl TestWatchCommand {
 
         cmd.stdout(Stdio::piped());
         cmd.stderr(Stdio::piped());
+        cmd.kill_on_drop(true);
 
-        let mut child = cmd.spawn().map_err(|e| {
+        let started = Instant::now();
+        let child = cmd.spawn().map_err(|e| {
             FennecError::Command(Box::new(std::io::Error::new(
                 e.kind(),
                 format!("Failed to spawn test command: {}", e),
             )))
         })?;
 
-        // Capture output
-        let stdout = child.stdout.take().ok_or_else(|| {
-            FennecError::Command(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Failed to capture stdout",
-            )))
-        })?;
-
-        let stderr = child.stderr.take().ok_or_else(|| {
-            FennecError::Command(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Failed to capture stderr",
-            )))
-        })?;
-
-        let mut stdout_lines = BufReader::new(stdout).lines();
-        let mut stderr_lines = BufReader::new(stderr).lines();
-
-        let mut output = String::new();
-
-        // Read stdout
-        while let Some(line) = stdout_lines
-            .next_line()
-            .await
-            .map_err(|e| FennecError::Command(Box::new(e)))?
-        {
-            output.push_str(&line);
-            output.push('\n');
-        }
+        // Both pipes are drained concurrently so a chatty stderr (compiler
+        // output) can't block the test binary writing to stdout
+        let output = tokio::select! {
+            output = child.wait_with_output() => {
+                output.map_err(|e| FennecError::Command(Box::new(e)))?
+            }
+            _ = cancellation_token.cancelled() => return Ok(None),
+        };
 
-        // Read stderr
-        while let Some(line) = stderr_lines
-            .next_line()
-            .await
-            .map_err(|e| FennecError::Command(Box::new(e)))?
-        {
-            output.push_str(&line);
-            output.push('\n');
-        }
+        let transcript = merge_test_streams(
+            &String::from_utf8_lossy(&output.stdout),
+            &String::from_utf8_lossy(&output.stderr),
+        );
+        let summary = TestRunSummary {
+            success: output.status.success(),
+            duration_ms: started.elapsed().as_millis() as u64,
+            tests: parse_test_output(&transcript),
+        };
+        Ok(Some((summary, transcript)))
+    }
 
-        // Wait for completion
-        let status = child
-            .wait()
-            .await
-            .map_err(|e| FennecError::Command(Box::new(e)))?;
+    fn should_watch_path(path: &Path, patterns: &GlobSet) -> bool {
+        // Skip hidden files and directories, and build output
+        let ignored = path.components().any(|component| match component {
+            Component::Normal(name) => {
+                let name = name.to_string_lossy();
+                name.starts_with('.') || name == "target"
+            }
+            _ => false,
+        });
 
-        Ok((status.success(), output))
+        !ignored && patterns.is_match(path)
     }
 
-    #[allow(dead_code)]
-    fn should_watch_path(path: &Path, patterns: &[String]) -> bool {
-        let path_str = path.to_string_lossy();
-
-        // Skip hidden files and directories
-        if path_str.contains("/.") {
-            return false;
-        }
+    fn spawn_watcher(
+        root: &Path,
+        patterns: GlobSet,
+    ) -> Result<(RecommendedWatcher, mpsc::UnboundedReceiver<PathBuf>)> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let watch_root = root.to_path_buf();
+
+        let mut watcher =
+            notify::recommended_watcher(move |res: std::result::Result<Event, notify::Error>| {
+                match res {
+                    Ok(event) => {
+                        if !matches!(
+                            event.kind,
+                            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                        ) {
+                            return;
+                        }
+                        for path in event.paths {
+                            let relative = path.strip_prefix(&watch_root).unwrap_or(&path);
+                            if Self::should_watch_path(relative, &patterns) {
+                                let _ = tx.send(relative.to_path_buf());
+                            }
+                        }
+                    }
+                    Err(e) => warn!("File watch error: {:?}", e),
+                }
+            })
+            .map_err(|e| {
+                FennecError::Command(Box::new(std::io::Error::other(format!(
+                    "Failed to start file watcher: {}",
+                    e
+                ))))
+            })?;
+
+        watcher.watch(root, RecursiveMode::Recursive).map_err(|e| {
+            FennecError::Command(Box::new(std::io::Error::other(format!(
+                "Failed to watch {}: {}",
+                root.display(),
+                e
+            ))))
+        })?;
 
-        // Skip target directory
-        if path_str.contains("/target/") {
-            return false;
-        }
+        Ok((watcher, rx))
+    }
 
-        // Check patterns
-        for pattern in patterns {
-            if pattern == "**/*.rs" && path_str.ends_with(".rs") {
-                return true;
-            }
-            if pattern == "**/Cargo.toml" && path_str.ends_with("Cargo.toml") {
-                return true;
-            }
-            // Add more pattern matching as needed
+    async fn write_report(
+        target: &TestReportTarget,
+        workspace: &Path,
+        summary: &TestRunSummary,
+    ) -> Result<PathBuf> {
+        let TestReportTarget::Junit(path) = target;
+        let path = workspace.join(path);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| FennecError::Command(Box::new(e)))?;
         }
-
-        false
+        tokio::fs::write(&path, render_junit(summary))
+            .await
+            .map_err(|e| {
+                FennecError::Command(Box::new(std::io::Error::new(
+                    e.kind(),
+                    format!("Failed to write report {}: {}", path.display(), e),
+                )))
+            })?;
+        Ok(path)
     }
 
     async fn watch_and_test(
         &self,
         args: &TestWatchArgs,
         context: &CommandContext,
-    ) -> Result<String> {
+        events: Option<&OutputSender>,
+    ) -> Result<(String, TestWatchReport)> {
         let workspace_path = context.workspace_path.as_ref().ok_or_else(|| {
             FennecError::Command(Box::new(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
                 "No workspace path set",
             )))
         })?;
+        let report_target = args
+            .report
+            .as_deref()
+            .map(TestReportTarget::parse)
+            .transpose()?;
+        let patterns = build_watch_globs(&args.watch_patterns)?;
 
         if context.dry_run {
-            return Ok(format!(
+            let mut output = format!(
                 "DRY RUN: Would watch files matching {:?} and run '{}' on changes",
                 args.watch_patterns, args.test_command
-            ));
+            );
+            if args.failures_first {
+                output.push_str(", rerunning previous failures first");
+            }
+            if let Some(report) = &args.report {
+                output.push_str(&format!(" and write a report to {}", report));
+            }
+            return Ok((output, TestWatchReport::default()));
         }
 
-        let mut output = String::new();
-        output.push_str(&format!(
-            "🔍 Watching files in {} for changes...\n",
+        let workspace = Path::new(workspace_path);
+        let watch_root = workspace
+            .canonicalize()
+            .unwrap_or_else(|_| workspace.to_path_buf());
+        let (_watcher, mut changes) = Self::spawn_watcher(&watch_root, patterns)?;
+        let deadline = (args.max_duration_seconds > 0)
+            .then(|| Instant::now() + Duration::from_secs(args.max_duration_seconds));
+
+        let mut log = WatchLog {
+            output: String::new(),
+            events,
+        };
+        log.line(format!(
+            "🔍 Watching files in {} for changes...",
             workspace_path
         ));
-        output.push_str(&format!("📋 Test command: {}\n", args.test_command));
-        output.push_str(&format!("⏱️  Debounce: {}ms\n\n", args.debounce_ms));
-
-        // Run tests initially
-        output.push_str("▶️  Running initial tests...\n\n");
-        let (success, test_output) = self
-            .run_tests(&args.test_command, &args.test_args, workspace_path)
-            .await?;
-
-        let status_icon = if success { "✅" } else { "❌" };
-        output.push_str(&format!(
-            "{} Tests {}\n\n",
-            status_icon,
-            if success { "passed" } else { "failed" }
-        ));
+        log.line(format!("📋 Test command: {}", args.test_command));
+        log.line(format!("⏱️  Debounce: {}ms", args.debounce_ms));
+        log.line("");
+
+        let mut report = TestWatchReport::default();
+        let mut tracker = FailureTracker::default();
+        let mut trigger: Vec<PathBuf> = Vec::new();
+
+        'watch: loop {
+            let mut scope = if args.failures_first {
+                tracker.next_scope()
+            } else {
+                RunScope::Full
+            };
+
+            loop {
+                let run = report.runs.len() + 1;
+                if let Some(progress) = &context.progress {
+                    progress.step(format!("Run {}", run), run, None);
+                }
+                match &scope {
+                    RunScope::Full => log.line(format!("▶️  Run {}: full suite", run)),
+                    RunScope::Failures(names) => log.line(format!(
+                        "▶️  Run {}: {} previously failing test(s)",
+                        run,
+                        names.len()
+                    )),
+                }
+                if !trigger.is_empty() {
+                    let shown: Vec<String> = trigger
+                        .iter()
+                        .take(5)
+                        .map(|p| p.display().to_string())
+                        .collect();
+                    let more = trigger.len().saturating_sub(shown.len());
+                    log.line(format!(
+                        "   Changed: {}{}",
+                        shown.join(", "),
+                        if more > 0 {
+                            format!(" (+{} more)", more)
+                        } else {
+                            String::new()
+                        }
+                    ));
+                }
+
+                let Some((summary, transcript)) = self
+                    .run_tests(
+                        &args.test_command,
+                        &scope.test_args(&args.test_args),
+                        workspace_path,
+                        &context.cancellation_token,
+                    )
+                    .await?
+                else {
+                    log.line("⏹️  Test run cancelled");
+                    break 'watch;
+                };
+
+                let full_run_next = tracker.record(&scope, &summary);
+                let (passed, failed, ignored) = (
+                    summary.count(TestOutcome::Passed),
+                    summary.count(TestOutcome::Failed),
+                    summary.count(TestOutcome::Ignored),
+                );
+                if let Some(progress) = &context.progress {
+                    progress.message(format!(
+                        "{} passed, {} failed, {} ignored",
+                        passed, failed, ignored
+                    ));
+                }
+                log.line(format!(
+                    "{} Tests {} ({} passed, {} failed, {} ignored)",
+                    if summary.success { "✅" } else { "❌" },
+                    if summary.success { "passed" } else { "failed" },
+                    passed,
+                    failed,
+                    ignored
+                ));
+                for name in summary.failed_names() {
+                    log.line(format!("   ✗ {}", name));
+                }
+                if summary.tests.is_empty() && !summary.success {
+                    // Usually a build failure; show what the command printed
+                    log.line(truncate_output(transcript.trim(), 1000));
+                }
+
+                if let Some(target) = &report_target {
+                    let path = Self::write_report(target, workspace, &summary).await?;
+                    log.line(format!("📄 JUnit report written to {}", path.display()));
+                    report.report_path = Some(path);
+                }
+                log.line("");
+
+                report.runs.push(TestRunRecord {
+                    run,
+                    scope: scope.clone(),
+                    trigger: trigger.clone(),
+                    success: summary.success,
+                    passed,
+                    failed,
+                    ignored,
+                    duration_ms: summary.duration_ms,
+                    failing: summary.failed_names(),
+                });
+
+                if !full_run_next {
+                    break;
+                }
+                log.line("✔️  Previous failures pass, running the full suite");
+                scope = RunScope::Full;
+            }
 
-        // Limit output length
-        let truncated_output = if test_output.len() > 1000 {
-            format!("{}...\n(output truncated)", &test_output[..1000])
-        } else {
-            test_output
-        };
-        output.push_str(&truncated_output);
-        output.push_str("\n\n");
+            match next_change(
+                &mut changes,
+                Duration::from_millis(args.debounce_ms),
+                &context.cancellation_token,
+                deadline,
+            )
+            .await
+            {
+                Some(changed) => trigger = changed,
+                None => break,
+            }
+        }
 
-        output.push_str("Note: File watching in background mode is not yet implemented.\n");
-        output.push_str("This is a simplified version that runs tests once.\n");
-        output.push_str("For continuous watching, use 'cargo watch' or similar tools.\n");
+        report.failing = tracker.failing().to_vec();
+        log.line(format!(
+            "⏹️  Stopped watching after {} run(s)",
+            report.runs.len()
+        ));
+        Ok((log.output, report))
+    }
+
+    async fn run(
+        &self,
+        args: &serde_json::Value,
+        context: &CommandContext,
+        events: Option<OutputSender>,
+    ) -> Result<CommandResult> {
+        let args: TestWatchArgs = serde_json::from_value(args.clone()).map_err(|e| {
+            FennecError::Command(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Invalid test-watch arguments: {}", e),
+            )))
+        })?;
 
-        Ok(output)
+        match self.watch_and_test(&args, context, events.as_ref()).await {
+            Ok((output, report)) => Ok(CommandResult {
+                command_id: Uuid::new_v4(),
+                success: true,
+                output,
+                error: None,
+                data: Some(serde_json::to_value(&report)?),
+            }),
+            Err(e) => Ok(CommandResult {
+                command_id: Uuid::new_v4(),
+                success: false,
+                output: String::new(),
+                error: Some(e.to_string()),
+                data: None,
+            }),
+        }
     }
 }
 
@@ -274,27 +694,16 @@ impl CommandExecutor for TestWatchCommand {
         args: &serde_json::Value,
         context: &CommandContext,
     ) -> Result<CommandResult> {
-        let args: TestWatchArgs = serde_json::from_value(args.clone()).map_err(|e| {
-            FennecError::Command(Box::new(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                format!("Invalid test-watch arguments: {}", e),
-            )))
-        })?;
+        self.run(args, context, None).await
+    }
 
-        match self.watch_and_test(&args, context).await {
-            Ok(output) => Ok(CommandResult {
-                command_id: Uuid::new_v4(),
-                success: true,
-                output,
-                error: None,
-            }),
-            Err(e) => Ok(CommandResult {
-                command_id: Uuid::new_v4(),
-                success: false,
-                output: String::new(),
-                error: Some(e.to_string()),
-            }),
-        }
+    async fn execute_streaming(
+        &self,
+        args: &serde_json::Value,
+        context: &CommandContext,
+        events: OutputSender,
+    ) -> Result<CommandResult> {
+        self.run(args, context, Some(events)).await
     }
 
     fn validate_args(&self, args: &serde_json::Value) -> Result<()> {
@@ -313,6 +722,11 @@ impl CommandExecutor for TestWatchCommand {
             .into());
         }
 
+        if let Some(report) = &args.report {
+            TestReportTarget::parse(report)?;
+        }
+        build_watch_globs(&args.watch_patterns)?;
+
         Ok(())
     }
 }
@@ -320,7 +734,7 @@ impl CommandExecutor for TestWatchCommand {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tokio_util::sync::CancellationToken;
+    use crate::test_report::TestCaseResult;
 
     #[test]
     fn test_default_values() {
@@ -331,7 +745,7 @@ mod tests {
 
     #[test]
     fn test_should_watch_path() {
-        let patterns = default_watch_patterns();
+        let patterns = build_watch_globs(&default_watch_patterns()).unwrap();
 
         assert!(TestWatchCommand::should_watch_path(
             Path::new("src/main.rs"),
@@ -349,6 +763,187 @@ mod tests {
             Path::new(".git/config"),
             &patterns
         ));
+        assert!(!TestWatchCommand::should_watch_path(
+            Path::new("target/debug/build/out/generated.rs"),
+            &patterns
+        ));
+        assert!(TestWatchCommand::should_watch_path(
+            Path::new("crates/core/tests/target_tests.rs"),
+            &patterns
+        ));
+    }
+
+    fn summary(success: bool, results: &[(&str, TestOutcome)]) -> TestRunSummary {
+        TestRunSummary {
+            success,
+            duration_ms: 0,
+            tests: results
+                .iter()
+                .map(|(name, outcome)| TestCaseResult {
+                    suite: "demo".to_string(),
+                    name: name.to_string(),
+                    outcome: *outcome,
+                    duration_secs: None,
+                    message: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_failure_tracker() {
+        use TestOutcome::{Failed, Passed};
+        let mut tracker = FailureTracker::default();
+        assert_eq!(tracker.next_scope(), RunScope::Full);
+
+        let full = summary(false, &[("a", Passed), ("b", Failed), ("c", Failed)]);
+        assert!(!tracker.record(&RunScope::Full, &full));
+        let targeted = tracker.next_scope();
+        assert_eq!(
+            targeted,
+            RunScope::Failures(vec!["b".to_string(), "c".to_string()])
+        );
+
+        // One fixed: keep targeting the other
+        assert!(!tracker.record(&targeted, &summary(false, &[("b", Passed), ("c", Failed)])));
+        assert_eq!(tracker.failing(), ["c"]);
+
+        // A build failure reports no tests and leaves the set alone
+        let targeted = tracker.next_scope();
+        assert!(!tracker.record(&targeted, &summary(false, &[])));
+        assert_eq!(tracker.failing(), ["c"]);
+
+        // All targeted tests pass: the full suite runs next
+        assert!(tracker.record(&targeted, &summary(true, &[("c", Passed)])));
+        assert_eq!(tracker.next_scope(), RunScope::Full);
+
+        // The full run discovers a new failure
+        assert!(!tracker.record(&RunScope::Full, &summary(false, &[("d", Failed)])));
+        assert_eq!(tracker.failing(), ["d"]);
+    }
+
+    #[test]
+    fn test_scope_args() {
+        let failures = RunScope::Failures(vec!["tests::a".to_string()]);
+        assert_eq!(
+            failures.test_args(&["--lib".to_string()]),
+            vec!["--lib", "--", "tests::a", "--exact"]
+        );
+        assert_eq!(
+            failures.test_args(&["--".to_string(), "--nocapture".to_string()]),
+            vec!["--", "--nocapture", "tests::a", "--exact"]
+        );
+        assert_eq!(
+            RunScope::Full.test_args(&["--lib".to_string()]),
+            vec!["--lib"]
+        );
+    }
+
+    #[test]
+    fn test_report_target_parse() {
+        assert_eq!(
+            TestReportTarget::parse("junit:target/report.xml").unwrap(),
+            TestReportTarget::Junit(PathBuf::from("target/report.xml"))
+        );
+        assert!(TestReportTarget::parse("junit:").is_err());
+        assert!(TestReportTarget::parse("html:out.html").is_err());
+        assert!(TestWatchCommand::new()
+            .validate_args(&serde_json::json!({"report": "xml"}))
+            .is_err());
+    }
+
+    /// Fake test runner: `demo::flaky` fails until `fixed` exists. Runs
+    /// filtered with `--exact` only report the filtered tests.
+    const FAKE_RUNNER: &str = r#"#!/bin/sh
+echo "     Running unittests src/lib.rs (target/debug/deps/demo-0123abcd)" >&2
+if [ -f fixed ]; then flaky=ok; else flaky=FAILED; fi
+echo
+case "$*" in
+  *--exact*)
+    echo "running 1 test"
+    echo "test demo::flaky ... $flaky"
+    ;;
+  *)
+    echo "running 2 tests"
+    echo "test demo::flaky ... $flaky"
+    echo "test demo::stable ... ok"
+    ;;
+esac
+[ "$flaky" = ok ]
+"#;
+
+    #[tokio::test]
+    async fn test_watch_reruns_failures_then_full_suite() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("runner.sh"), FAKE_RUNNER).unwrap();
+        std::fs::create_dir(temp_dir.path().join("src")).unwrap();
+
+        let cancellation_token = CancellationToken::new();
+        let context = CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: Some(temp_dir.path().to_string_lossy().to_string()),
+            sandbox_level: SandboxLevel::WorkspaceWrite,
+            dry_run: false,
+            preview_only: false,
+            cancellation_token: cancellation_token.clone(),
+            action_log: None,
+            timeout: None,
+            progress: None,
+        };
+        let args = serde_json::json!({
+            "test_command": "sh runner.sh",
+            "debounce_ms": 50,
+            "failures_first": true,
+            "report": "junit:reports/junit.xml",
+        });
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let handle = tokio::spawn(async move {
+            TestWatchCommand::new()
+                .execute_streaming(&args, &context, tx)
+                .await
+                .unwrap()
+        });
+
+        let mut completed_runs = 0;
+        while let Some(CommandOutputEvent::Stdout { line }) = rx.recv().await {
+            if !line.starts_with("📄") {
+                continue;
+            }
+            completed_runs += 1;
+            if completed_runs == 1 {
+                // Fix the test, then touch a watched file
+                std::fs::write(temp_dir.path().join("fixed"), "").unwrap();
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                std::fs::write(temp_dir.path().join("src/lib.rs"), "// change").unwrap();
+            }
+            if completed_runs == 3 {
+                cancellation_token.cancel();
+            }
+        }
+
+        let result = handle.await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        let report: TestWatchReport = serde_json::from_value(result.data.unwrap()).unwrap();
+
+        let scopes: Vec<&RunScope> = report.runs.iter().map(|r| &r.scope).collect();
+        assert_eq!(
+            scopes,
+            vec![
+                &RunScope::Full,
+                &RunScope::Failures(vec!["demo::flaky".to_string()]),
+                &RunScope::Full,
+            ]
+        );
+        assert_eq!(report.runs[0].failing, vec!["demo::flaky"]);
+        assert_eq!(report.runs[1].trigger, vec![PathBuf::from("src/lib.rs")]);
+        assert_eq!((report.runs[1].passed, report.runs[2].passed), (1, 2));
+        assert!(report.failing.is_empty());
+
+        let junit = std::fs::read_to_string(temp_dir.path().join("reports/junit.xml")).unwrap();
+        assert!(junit.contains("tests=\"2\" failures=\"0\""));
+        assert!(result.output.contains("Stopped watching after 3 run(s)"));
     }
 
     #[tokio::test]
@@ -365,6 +960,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
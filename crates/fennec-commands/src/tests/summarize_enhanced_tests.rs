@@ -24,6 +24,8 @@ fn create_test_context(workspace_path: Option<PathBuf>) -> CommandContext {
         preview_only: false,
         cancellation_token: CancellationToken::new(),
         action_log: None,
+        timeout: None,
+        progress: None,
     }
 }
 
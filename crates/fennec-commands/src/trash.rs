@@ -0,0 +1,393 @@
+use crate::action_log::Action;
+use crate::file_ops::{FileOperations, TrashEntry};
+use crate::registry::{CommandContext, CommandDescriptor, CommandExecutor};
+use anyhow::Result;
+use fennec_core::command::{Capability, CommandPreview, CommandResult};
+use fennec_core::error::FennecError;
+use fennec_security::SandboxLevel;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashArgs {
+    /// One of `list`, `restore` or `empty`
+    #[serde(default = "default_action")]
+    pub action: String,
+
+    /// Trash entry to restore
+    #[serde(default)]
+    pub id: Option<String>,
+
+    /// When emptying, only purge entries deleted at least this many days ago
+    #[serde(default)]
+    pub older_than_days: Option<u64>,
+}
+
+fn default_action() -> String {
+    "list".to_string()
+}
+
+/// Inspect, restore from and empty the workspace trash used by `delete`
+pub struct TrashCommand {
+    descriptor: CommandDescriptor,
+    file_ops: FileOperations,
+}
+
+impl TrashCommand {
+    pub fn new() -> Self {
+        Self {
+            descriptor: CommandDescriptor {
+                name: "trash".to_string(),
+                description: "List, restore or empty files moved to the workspace trash"
+                    .to_string(),
+                version: "1.0.0".to_string(),
+                author: Some("Fennec Contributors".to_string()),
+                capabilities_required: vec![Capability::ReadFile, Capability::WriteFile],
+                sandbox_level_required: SandboxLevel::WorkspaceWrite,
+                supports_preview: true,
+                supports_dry_run: true,
+                timeout: None,
+                alias_of: None,
+            },
+            file_ops: FileOperations::with_default_config(),
+        }
+    }
+
+    fn parse_args(args: &serde_json::Value) -> Result<TrashArgs> {
+        serde_json::from_value(args.clone()).map_err(|e| {
+            FennecError::Command(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Invalid trash arguments: {}", e),
+            )))
+            .into()
+        })
+    }
+
+    fn older_than(args: &TrashArgs) -> Option<Duration> {
+        args.older_than_days
+            .map(|days| Duration::from_secs(days * 24 * 60 * 60))
+    }
+
+    async fn perform_trash(
+        &self,
+        args: &TrashArgs,
+        context: &CommandContext,
+    ) -> Result<(String, Vec<TrashEntry>)> {
+        let workspace_path_str = context.workspace_path.as_ref().ok_or_else(|| {
+            FennecError::Command(Box::new(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "No workspace path set",
+            )))
+        })?;
+        let workspace_path = Path::new(workspace_path_str);
+
+        match args.action.as_str() {
+            "list" => {
+                let entries = self.file_ops.list_trash(workspace_path).await?;
+                Ok((format_entries("Trash", &entries), entries))
+            }
+            "restore" => {
+                let id = args.id.as_deref().unwrap_or_default();
+                if context.dry_run {
+                    let entry = self
+                        .file_ops
+                        .list_trash(workspace_path)
+                        .await?
+                        .into_iter()
+                        .find(|entry| entry.id == id)
+                        .ok_or_else(|| {
+                            FennecError::Command(Box::new(std::io::Error::new(
+                                std::io::ErrorKind::NotFound,
+                                format!("No trash entry '{}'", id),
+                            )))
+                        })?;
+                    let output =
+                        format!("Would restore {} from trash", entry.original_path.display());
+                    return Ok((output, vec![entry]));
+                }
+
+                let entry = self.file_ops.restore_from_trash(workspace_path, id).await?;
+                let target = workspace_path.join(&entry.original_path);
+
+                if let Some(action_log) = &context.action_log {
+                    let description = format!("Restored from trash: {}", target.display());
+                    let action = if entry.is_directory {
+                        Action::directory_moved(
+                            "trash".to_string(),
+                            entry.trash_path.clone(),
+                            target.clone(),
+                            description,
+                        )
+                    } else {
+                        Action::file_moved(
+                            "trash".to_string(),
+                            entry.trash_path.clone(),
+                            target.clone(),
+                            description,
+                        )
+                    };
+                    action_log.record(action).await;
+                }
+
+                Ok((format!("Restored {}", target.display()), vec![entry]))
+            }
+            "empty" => {
+                let older_than = Self::older_than(args);
+                if context.dry_run {
+                    let cutoff = match older_than {
+                        Some(age) => Some(chrono::Utc::now() - chrono::Duration::from_std(age)?),
+                        None => None,
+                    };
+                    let entries: Vec<TrashEntry> = self
+                        .file_ops
+                        .list_trash(workspace_path)
+                        .await?
+                        .into_iter()
+                        .filter(|entry| cutoff.is_none_or(|cutoff| entry.deleted_at <= cutoff))
+                        .collect();
+                    return Ok((format_entries("Would purge", &entries), entries));
+                }
+
+                let purged = self
+                    .file_ops
+                    .empty_trash(workspace_path, older_than)
+                    .await?;
+                Ok((format_entries("Purged", &purged), purged))
+            }
+            other => Err(FennecError::Command(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "Unknown trash action: {}. Available actions: list, restore, empty",
+                    other
+                ),
+            )))
+            .into()),
+        }
+    }
+}
+
+/// Render entries one per line under a `heading (N):` header
+fn format_entries(heading: &str, entries: &[TrashEntry]) -> String {
+    if entries.is_empty() {
+        return format!("{}: no entries", heading);
+    }
+
+    let mut output = format!("{} ({}):\n", heading, entries.len());
+    for entry in entries {
+        output.push_str(&format!(
+            "  {}  {}{}  {} bytes  deleted {}\n",
+            entry.id,
+            entry.original_path.display(),
+            if entry.is_directory { "/" } else { "" },
+            entry.size_bytes,
+            entry.deleted_at.format("%Y-%m-%d %H:%M:%S UTC"),
+        ));
+    }
+    output
+}
+
+impl Default for TrashCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl CommandExecutor for TrashCommand {
+    fn descriptor(&self) -> &CommandDescriptor {
+        &self.descriptor
+    }
+
+    async fn preview(
+        &self,
+        args: &serde_json::Value,
+        _context: &CommandContext,
+    ) -> Result<CommandPreview> {
+        let args = Self::parse_args(args)?;
+
+        let description = match args.action.as_str() {
+            "restore" => format!(
+                "Restore trash entry {}",
+                args.id.as_deref().unwrap_or_default()
+            ),
+            "empty" => match args.older_than_days {
+                Some(days) => format!("Purge trash entries older than {} days", days),
+                None => "Permanently purge every trash entry".to_string(),
+            },
+            _ => "List trash entries".to_string(),
+        };
+
+        Ok(CommandPreview {
+            command_id: Uuid::new_v4(),
+            description,
+            actions: vec![],
+            requires_approval: args.action != "list",
+        })
+    }
+
+    async fn execute(
+        &self,
+        args: &serde_json::Value,
+        context: &CommandContext,
+    ) -> Result<CommandResult> {
+        let args = Self::parse_args(args)?;
+
+        match self.perform_trash(&args, context).await {
+            Ok((output, entries)) => Ok(CommandResult {
+                command_id: Uuid::new_v4(),
+                success: true,
+                output,
+                error: None,
+                data: serde_json::to_value(&entries).ok(),
+            }),
+            Err(e) => Ok(CommandResult {
+                command_id: Uuid::new_v4(),
+                success: false,
+                output: String::new(),
+                error: Some(e.to_string()),
+                data: None,
+            }),
+        }
+    }
+
+    fn validate_args(&self, args: &serde_json::Value) -> Result<()> {
+        let args = Self::parse_args(args)?;
+
+        match args.action.as_str() {
+            "list" | "empty" => Ok(()),
+            "restore" if args.id.as_deref().is_some_and(|id| !id.is_empty()) => Ok(()),
+            "restore" => Err(FennecError::Command(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Restoring requires a trash entry id",
+            )))
+            .into()),
+            other => Err(FennecError::Command(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Unknown trash action: {}", other),
+            )))
+            .into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action_log::ActionLog;
+    use crate::delete::DeleteCommand;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+    use tokio_util::sync::CancellationToken;
+
+    fn context(workspace: &Path, action_log: Option<Arc<ActionLog>>) -> CommandContext {
+        CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: Some(workspace.to_string_lossy().to_string()),
+            sandbox_level: SandboxLevel::WorkspaceWrite,
+            dry_run: false,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log,
+            timeout: None,
+            progress: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_and_restore_deleted_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("notes.txt");
+        std::fs::write(&file, "keep me").unwrap();
+        let ctx = context(temp_dir.path(), None);
+
+        let deleted = DeleteCommand::new()
+            .execute(&serde_json::json!({"path": "notes.txt"}), &ctx)
+            .await
+            .unwrap();
+        assert!(deleted.success, "{:?}", deleted.error);
+        assert!(!file.exists());
+
+        let command = TrashCommand::new();
+        let listed = command
+            .execute(&serde_json::json!({"action": "list"}), &ctx)
+            .await
+            .unwrap();
+        let entries: Vec<TrashEntry> = serde_json::from_value(listed.data.unwrap()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].original_path, Path::new("notes.txt"));
+        assert!(listed.output.contains("notes.txt"));
+
+        let restored = command
+            .execute(
+                &serde_json::json!({"action": "restore", "id": entries[0].id}),
+                &ctx,
+            )
+            .await
+            .unwrap();
+        assert!(restored.success, "{:?}", restored.error);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "keep me");
+
+        let listed = command
+            .execute(&serde_json::json!({"action": "list"}), &ctx)
+            .await
+            .unwrap();
+        assert_eq!(listed.output, "Trash: no entries");
+    }
+
+    #[tokio::test]
+    async fn test_empty_respects_dry_run() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.path().join("old")).unwrap();
+        std::fs::write(temp_dir.path().join("old/a.txt"), "a").unwrap();
+        let mut ctx = context(temp_dir.path(), None);
+
+        DeleteCommand::new()
+            .execute(&serde_json::json!({"path": "old", "recursive": true}), &ctx)
+            .await
+            .unwrap();
+
+        let command = TrashCommand::new();
+        ctx.dry_run = true;
+        let result = command
+            .execute(&serde_json::json!({"action": "empty"}), &ctx)
+            .await
+            .unwrap();
+        assert!(result.output.starts_with("Would purge (1):"));
+
+        ctx.dry_run = false;
+        let result = command
+            .execute(
+                &serde_json::json!({"action": "empty", "older_than_days": 1}),
+                &ctx,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.output, "Purged: no entries");
+
+        let result = command
+            .execute(&serde_json::json!({"action": "empty"}), &ctx)
+            .await
+            .unwrap();
+        assert!(result.output.starts_with("Purged (1):"));
+        assert!(FileOperations::with_default_config()
+            .list_trash(temp_dir.path())
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_validate_args() {
+        let command = TrashCommand::new();
+        assert!(command
+            .validate_args(&serde_json::json!({"action": "restore"}))
+            .is_err());
+        assert!(command
+            .validate_args(&serde_json::json!({"action": "shred"}))
+            .is_err());
+        assert!(command.validate_args(&serde_json::json!({})).is_ok());
+    }
+}
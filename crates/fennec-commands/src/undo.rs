@@ -1,4 +1,5 @@
-use crate::action_log::{ActionLog, ActionState};
+use crate::action_log::{restore_directory_snapshot, Action, ActionLog, ActionState};
+use crate::hunks::{split_diff_into_hunks, Hunk};
 use crate::registry::{CommandContext, CommandDescriptor, CommandExecutor};
 use anyhow::Result;
 use fennec_core::{
@@ -7,6 +8,7 @@ use fennec_core::{
 };
 use fennec_security::SandboxLevel;
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::fs;
 use uuid::Uuid;
@@ -15,6 +17,20 @@ use uuid::Uuid;
 pub struct UndoArgs {
     #[serde(default = "default_count")]
     pub count: usize,
+
+    /// Undo this specific past action instead of the most recent ones; fails
+    /// if a later action touched the same files
+    #[serde(default)]
+    pub action_id: Option<Uuid>,
+
+    /// Only revert the changes to these files (or directories) within the
+    /// targeted action
+    #[serde(default)]
+    pub files: Vec<PathBuf>,
+
+    /// Return the hunks that would be reverted without touching disk
+    #[serde(default)]
+    pub preview: bool,
 }
 
 fn default_count() -> usize {
@@ -38,19 +54,43 @@ impl UndoCommand {
                 sandbox_level_required: SandboxLevel::WorkspaceWrite,
                 supports_preview: true,
                 supports_dry_run: true,
+                timeout: None,
+                alias_of: None,
             },
             action_log,
         }
     }
 
-    async fn perform_undo(&self, args: &UndoArgs, context: &CommandContext) -> Result<String> {
+    async fn perform_undo(
+        &self,
+        args: &UndoArgs,
+        context: &CommandContext,
+    ) -> Result<(String, Option<serde_json::Value>)> {
         let workspace_path_str = context.workspace_path.as_ref().ok_or_else(|| {
             FennecError::Command(Box::new(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
                 "No workspace path set",
             )))
         })?;
-        let workspace_path = std::path::Path::new(workspace_path_str);
+        let workspace_path = Path::new(workspace_path_str);
+
+        if args.action_id.is_some() || !args.files.is_empty() {
+            return self
+                .perform_selective_undo(args, context, workspace_path)
+                .await;
+        }
+
+        if args.preview {
+            let history = self.action_log.get_history().await;
+            let index = self.action_log.current_index().await;
+            let actions: Vec<Action> = history[..index]
+                .iter()
+                .rev()
+                .take(args.count)
+                .cloned()
+                .collect();
+            return Ok(preview_revert(&actions, workspace_path).await);
+        }
 
         let mut undone_actions = Vec::new();
 
@@ -71,158 +111,362 @@ impl UndoCommand {
                 continue;
             }
 
-            // Apply the reverse action (state_before)
-            match &action.state_before {
-                ActionState::FileCreated { path } => {
-                    // Reverse of deletion: restore the file
-                    if let ActionState::FileDeleted { content, .. } = &action.state_after {
-                        let full_path = if path.is_absolute() {
-                            path.clone()
-                        } else {
-                            workspace_path.join(path)
-                        };
-
-                        if let Some(parent) = full_path.parent() {
-                            fs::create_dir_all(parent).await.map_err(|e| {
-                                FennecError::Command(Box::new(std::io::Error::new(
-                                    e.kind(),
-                                    format!("Failed to create parent directories: {}", e),
-                                )))
-                            })?;
-                        }
-
-                        fs::write(&full_path, content).await.map_err(|e| {
-                            FennecError::Command(Box::new(std::io::Error::new(
-                                e.kind(),
-                                format!("Failed to restore file: {}", e),
-                            )))
-                        })?;
-                    }
-                }
-                ActionState::FileModified { path, content, .. } => {
-                    // Restore previous content
-                    let full_path = if path.is_absolute() {
-                        path.clone()
-                    } else {
-                        workspace_path.join(path)
-                    };
+            self.revert_action(&action, workspace_path).await?;
+            undone_actions.push(format!("Undid: {}", action.description));
+        }
 
-                    fs::write(&full_path, content).await.map_err(|e| {
-                        FennecError::Command(Box::new(std::io::Error::new(
-                            e.kind(),
-                            format!("Failed to restore file content: {}", e),
-                        )))
-                    })?;
+        if undone_actions.is_empty() {
+            Ok(("No actions to undo".to_string(), None))
+        } else {
+            Ok((undone_actions.join("\n"), None))
+        }
+    }
+
+    /// Undo one past action, or just its changes to `args.files`, provided
+    /// no later action touched the same paths
+    async fn perform_selective_undo(
+        &self,
+        args: &UndoArgs,
+        context: &CommandContext,
+        workspace_path: &Path,
+    ) -> Result<(String, Option<serde_json::Value>)> {
+        let action = match args.action_id {
+            Some(id) => self.action_log.find_applied(id).await.ok_or_else(|| {
+                FennecError::Command(Box::new(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("Action {} is not in the undo history", id),
+                )))
+            })?,
+            None => {
+                let index = self.action_log.current_index().await;
+                match index.checked_sub(1) {
+                    Some(last) => self.action_log.get_action(last).await,
+                    None => None,
                 }
-                ActionState::FileDeleted { path, .. } => {
-                    // Reverse of creation: delete the file
+                .ok_or_else(|| {
+                    FennecError::Command(Box::new(std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        "No actions to undo",
+                    )))
+                })?
+            }
+        };
+
+        let (target, remaining) = if args.files.is_empty() {
+            (Some(action.clone()), None)
+        } else {
+            action.split_by_paths(&args.files, workspace_path)
+        };
+        let target = target.ok_or_else(|| {
+            let files: Vec<String> = args
+                .files
+                .iter()
+                .map(|file| file.display().to_string())
+                .collect();
+            FennecError::Command(Box::new(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!(
+                    "Action '{}' did not change any of: {}",
+                    action.description,
+                    files.join(", ")
+                ),
+            )))
+        })?;
+
+        let conflicts = self
+            .action_log
+            .conflicts_after(&target, workspace_path)
+            .await;
+        if !conflicts.is_empty() {
+            let list: Vec<String> = conflicts
+                .iter()
+                .map(|conflict| format!("  - {}", conflict))
+                .collect();
+            return Err(FennecError::Command(Box::new(std::io::Error::other(format!(
+                "Cannot undo '{}': later actions changed the same files:\n{}",
+                action.description,
+                list.join("\n")
+            ))))
+            .into());
+        }
+
+        if args.preview {
+            return Ok(preview_revert(std::slice::from_ref(&target), workspace_path).await);
+        }
+
+        if context.dry_run {
+            return Ok((format!("Would undo: {}", target.description), None));
+        }
+
+        self.revert_action(&target, workspace_path).await?;
+        self.action_log.remove_undone(&target, remaining).await;
+
+        Ok((format!("Undid: {}", target.description), None))
+    }
+
+    /// Apply the reverse of every change in `action`
+    async fn revert_action(&self, action: &Action, workspace_path: &Path) -> Result<()> {
+        // Undo in reverse order so later changes in a transaction are
+        // reverted first
+        for (before, after) in action.state_pairs().into_iter().rev() {
+            self.revert_state(before, after, &action.description, workspace_path)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Restore the state recorded before an action, using `other` (the state
+    /// after it) for any content the reversal needs
+    async fn revert_state(
+        &self,
+        state: &ActionState,
+        other: &ActionState,
+        description: &str,
+        workspace_path: &Path,
+    ) -> Result<()> {
+        match state {
+            ActionState::FileCreated { path } => {
+                // Reverse of deletion: restore the file
+                if let ActionState::FileDeleted { content, .. } = other {
                     let full_path = if path.is_absolute() {
                         path.clone()
                     } else {
                         workspace_path.join(path)
                     };
 
-                    if full_path.exists() {
-                        fs::remove_file(&full_path).await.map_err(|e| {
+                    if let Some(parent) = full_path.parent() {
+                        fs::create_dir_all(parent).await.map_err(|e| {
                             FennecError::Command(Box::new(std::io::Error::new(
                                 e.kind(),
-                                format!("Failed to remove file: {}", e),
+                                format!("Failed to create parent directories: {}", e),
                             )))
                         })?;
                     }
-                }
-                ActionState::FileMoved { from, to } => {
-                    // Reverse the move
-                    let from_full = if from.is_absolute() {
-                        from.clone()
-                    } else {
-                        workspace_path.join(from)
-                    };
-                    let to_full = if to.is_absolute() {
-                        to.clone()
-                    } else {
-                        workspace_path.join(to)
-                    };
 
-                    if to_full.exists() {
-                        fs::rename(&to_full, &from_full).await.map_err(|e| {
-                            FennecError::Command(Box::new(std::io::Error::new(
-                                e.kind(),
-                                format!("Failed to reverse rename: {}", e),
-                            )))
-                        })?;
-                    }
+                    fs::write(&full_path, content).await.map_err(|e| {
+                        FennecError::Command(Box::new(std::io::Error::new(
+                            e.kind(),
+                            format!("Failed to restore file: {}", e),
+                        )))
+                    })?;
                 }
-                ActionState::DirectoryCreated { path } => {
-                    // Reverse of directory deletion: restore directory
-                    if let ActionState::DirectoryDeleted { contents, .. } = &action.state_after {
-                        let full_path = if path.is_absolute() {
-                            path.clone()
-                        } else {
-                            workspace_path.join(path)
-                        };
-
-                        fs::create_dir_all(&full_path).await.map_err(|e| {
-                            FennecError::Command(Box::new(std::io::Error::new(
-                                e.kind(),
-                                format!("Failed to create directory: {}", e),
-                            )))
-                        })?;
-
-                        // Restore all files in the directory
-                        for (file_path, content) in contents {
-                            let file_full_path = if file_path.is_absolute() {
-                                file_path.clone()
-                            } else {
-                                workspace_path.join(file_path)
-                            };
-
-                            if let Some(parent) = file_full_path.parent() {
-                                fs::create_dir_all(parent).await.map_err(|e| {
-                                    FennecError::Command(Box::new(std::io::Error::new(
-                                        e.kind(),
-                                        format!("Failed to create parent directories: {}", e),
-                                    )))
-                                })?;
-                            }
-
-                            fs::write(&file_full_path, content).await.map_err(|e| {
-                                FennecError::Command(Box::new(std::io::Error::new(
-                                    e.kind(),
-                                    format!("Failed to restore file: {}", e),
-                                )))
-                            })?;
-                        }
-                    }
+            }
+            ActionState::FileModified { path, content, .. } => {
+                // Restore previous content
+                let full_path = if path.is_absolute() {
+                    path.clone()
+                } else {
+                    workspace_path.join(path)
+                };
+
+                fs::write(&full_path, content).await.map_err(|e| {
+                    FennecError::Command(Box::new(std::io::Error::new(
+                        e.kind(),
+                        format!("Failed to restore file content: {}", e),
+                    )))
+                })?;
+            }
+            ActionState::FileDeleted { path, .. } => {
+                // Reverse of creation: delete the file
+                let full_path = if path.is_absolute() {
+                    path.clone()
+                } else {
+                    workspace_path.join(path)
+                };
+
+                if full_path.exists() {
+                    fs::remove_file(&full_path).await.map_err(|e| {
+                        FennecError::Command(Box::new(std::io::Error::new(
+                            e.kind(),
+                            format!("Failed to remove file: {}", e),
+                        )))
+                    })?;
                 }
-                ActionState::DirectoryDeleted { path, .. } => {
-                    // Reverse of directory creation: delete directory
-                    let full_path = if path.is_absolute() {
-                        path.clone()
-                    } else {
-                        workspace_path.join(path)
-                    };
-
-                    if full_path.exists() {
-                        fs::remove_dir_all(&full_path).await.map_err(|e| {
+            }
+            ActionState::FileMoved { from, to } | ActionState::DirectoryMoved { from, to } => {
+                // state_before records the reverse move: `from` is where the
+                // entry lives now, `to` is where it has to go back to
+                let from_full = if from.is_absolute() {
+                    from.clone()
+                } else {
+                    workspace_path.join(from)
+                };
+                let to_full = if to.is_absolute() {
+                    to.clone()
+                } else {
+                    workspace_path.join(to)
+                };
+
+                if from_full.exists() {
+                    if let Some(parent) = to_full.parent() {
+                        fs::create_dir_all(parent).await.map_err(|e| {
                             FennecError::Command(Box::new(std::io::Error::new(
                                 e.kind(),
-                                format!("Failed to remove directory: {}", e),
+                                format!("Failed to create parent directories: {}", e),
                             )))
                         })?;
                     }
+
+                    fs::rename(&from_full, &to_full).await.map_err(|e| {
+                        FennecError::Command(Box::new(std::io::Error::new(
+                            e.kind(),
+                            format!("Failed to reverse rename: {}", e),
+                        )))
+                    })?;
+                } else if !to_full.exists() {
+                    // e.g. a trashed entry that has since been purged
+                    return Err(FennecError::Command(Box::new(std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        format!(
+                            "Cannot undo '{}': {} no longer exists",
+                            description,
+                            from_full.display()
+                        ),
+                    )))
+                    .into());
                 }
             }
+            ActionState::DirectoryCreated { path } => {
+                // Reverse of directory deletion: restore the tree from its snapshot
+                let snapshot = other.snapshot().ok_or_else(|| {
+                    FennecError::Command(Box::new(std::io::Error::other(format!(
+                        "Cannot undo '{}': no snapshot of the deleted directory was kept",
+                        description
+                    ))))
+                })?;
+
+                let full_path = if path.is_absolute() {
+                    path.clone()
+                } else {
+                    workspace_path.join(path)
+                };
+
+                if full_path.exists() {
+                    return Err(FennecError::Command(Box::new(std::io::Error::new(
+                        std::io::ErrorKind::AlreadyExists,
+                        format!(
+                            "Cannot restore directory, path already exists: {}",
+                            full_path.display()
+                        ),
+                    )))
+                    .into());
+                }
 
-            undone_actions.push(format!("Undid: {}", action.description));
+                restore_directory_snapshot(snapshot, &full_path)
+                    .await
+                    .map_err(|e| {
+                        FennecError::Command(Box::new(std::io::Error::other(format!(
+                            "Failed to restore directory: {}",
+                            e
+                        ))))
+                    })?;
+            }
+            ActionState::DirectoryDeleted { path, .. } => {
+                // Reverse of directory creation: delete directory
+                let full_path = if path.is_absolute() {
+                    path.clone()
+                } else {
+                    workspace_path.join(path)
+                };
+
+                if full_path.exists() {
+                    fs::remove_dir_all(&full_path).await.map_err(|e| {
+                        FennecError::Command(Box::new(std::io::Error::new(
+                            e.kind(),
+                            format!("Failed to remove directory: {}", e),
+                        )))
+                    })?;
+                }
+            }
+            ActionState::Transaction { .. } => {
+                return Err(FennecError::Command(Box::new(std::io::Error::other(format!(
+                    "Cannot undo '{}': nested transactions are not supported",
+                    description
+                ))))
+                .into());
+            }
         }
 
-        if undone_actions.is_empty() {
-            Ok("No actions to undo".to_string())
-        } else {
-            Ok(undone_actions.join("\n"))
+        Ok(())
+    }
+}
+
+/// Describe what undoing `actions` would do, with the content hunks each
+/// reverted file change would apply to what is on disk now
+async fn preview_revert(
+    actions: &[Action],
+    workspace_path: &Path,
+) -> (String, Option<serde_json::Value>) {
+    if actions.is_empty() {
+        return ("No actions to undo".to_string(), None);
+    }
+
+    let mut output = Vec::new();
+    let mut hunks = Vec::new();
+
+    for action in actions {
+        output.push(format!("Would undo: {}", action.description));
+        for (before, after) in action.state_pairs().into_iter().rev() {
+            for hunk in revert_hunks(before, after, workspace_path).await {
+                output.push(format!("  {}", hunk.summary()));
+                output.push(hunk.to_unified_diff().trim_end().to_string());
+                hunks.push(hunk);
+            }
         }
     }
+
+    let data = serde_json::json!({
+        "actions": actions.iter().map(|action| action.id).collect::<Vec<_>>(),
+        "hunks": hunks,
+    });
+    (output.join("\n"), Some(data))
+}
+
+/// Hunks that restoring `state` would apply to a file; moves and directory
+/// changes have none
+async fn revert_hunks(
+    state: &ActionState,
+    other: &ActionState,
+    workspace_path: &Path,
+) -> Vec<Hunk> {
+    let (path, from, to) = match (state, other) {
+        (
+            ActionState::FileModified { path, content, .. },
+            ActionState::FileModified { content: after, .. },
+        ) => {
+            let from = read_current(path, workspace_path)
+                .await
+                .unwrap_or_else(|| String::from_utf8_lossy(after).into_owned());
+            (path, from, String::from_utf8_lossy(content).into_owned())
+        }
+        (ActionState::FileCreated { path }, ActionState::FileDeleted { content, .. }) => (
+            path,
+            String::new(),
+            String::from_utf8_lossy(content).into_owned(),
+        ),
+        (ActionState::FileDeleted { path, .. }, _) => (
+            path,
+            read_current(path, workspace_path).await.unwrap_or_default(),
+            String::new(),
+        ),
+        _ => return Vec::new(),
+    };
+
+    split_diff_into_hunks(path.clone(), &from, &to, 3)
+}
+
+/// Current on-disk content of a logged file, if it can still be read
+async fn read_current(path: &Path, workspace_path: &Path) -> Option<String> {
+    let full_path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        workspace_path.join(path)
+    };
+
+    fs::read(&full_path)
+        .await
+        .map(|content| String::from_utf8_lossy(&content).into_owned())
+        .ok()
 }
 
 impl Default for UndoCommand {
@@ -252,7 +496,22 @@ impl CommandExecutor for UndoCommand {
         let can_undo = self.action_log.can_undo_count().await;
         let count = args.count.min(can_undo);
 
-        let description = if count == 0 {
+        let description = if let Some(id) = args.action_id {
+            if args.files.is_empty() {
+                format!("Undo action {}", id)
+            } else {
+                format!(
+                    "Undo changes to {} file(s) in action {}",
+                    args.files.len(),
+                    id
+                )
+            }
+        } else if !args.files.is_empty() {
+            format!(
+                "Undo changes to {} file(s) in last action",
+                args.files.len()
+            )
+        } else if count == 0 {
             "No actions to undo".to_string()
         } else if count == 1 {
             "Undo last action".to_string()
@@ -281,17 +540,19 @@ impl CommandExecutor for UndoCommand {
         })?;
 
         match self.perform_undo(&args, context).await {
-            Ok(output) => Ok(CommandResult {
+            Ok((output, data)) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: true,
                 output,
                 error: None,
+                data,
             }),
             Err(e) => Ok(CommandResult {
                 command_id: Uuid::new_v4(),
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
+                data: None,
             }),
         }
     }
@@ -349,6 +610,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -356,6 +619,340 @@ mod tests {
         assert!(!test_file.exists());
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_undo_recursive_directory_delete() {
+        use crate::delete::DeleteCommand;
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = TempDir::new().unwrap();
+        let module_dir = temp_dir.path().join("module");
+        std::fs::create_dir_all(module_dir.join("nested/deeper")).unwrap();
+        std::fs::write(module_dir.join("mod.rs"), "pub mod nested;").unwrap();
+        std::fs::write(module_dir.join("nested/lib.rs"), "fn main() {}").unwrap();
+        std::fs::write(module_dir.join("nested/deeper/run.sh"), "#!/bin/sh").unwrap();
+        std::fs::set_permissions(
+            module_dir.join("nested/deeper/run.sh"),
+            std::fs::Permissions::from_mode(0o755),
+        )
+        .unwrap();
+
+        let action_log = Arc::new(ActionLog::new().with_data_dir(data_dir.path().to_path_buf()));
+        let context = CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: Some(temp_dir.path().to_string_lossy().to_string()),
+            sandbox_level: SandboxLevel::WorkspaceWrite,
+            dry_run: false,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log: Some(action_log.clone()),
+            timeout: None,
+            progress: None,
+        };
+
+        let delete_args = serde_json::json!({ "path": "module", "recursive": true });
+        let result = DeleteCommand::new()
+            .execute(&delete_args, &context)
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(!module_dir.exists());
+
+        let command = UndoCommand::new(action_log);
+        let result = command
+            .execute(&serde_json::json!({ "count": 1 }), &context)
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+
+        assert_eq!(
+            std::fs::read_to_string(module_dir.join("mod.rs")).unwrap(),
+            "pub mod nested;"
+        );
+        assert_eq!(
+            std::fs::read_to_string(module_dir.join("nested/lib.rs")).unwrap(),
+            "fn main() {}"
+        );
+        let mode = std::fs::metadata(module_dir.join("nested/deeper/run.sh"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o755);
+    }
+
+    #[tokio::test]
+    async fn test_undo_directory_rename() {
+        let temp_dir = TempDir::new().unwrap();
+        let old_dir = temp_dir.path().join("old_module");
+        let new_dir = temp_dir.path().join("new_module");
+        std::fs::create_dir(&old_dir).unwrap();
+        std::fs::write(old_dir.join("lib.rs"), "content").unwrap();
+        std::fs::rename(&old_dir, &new_dir).unwrap();
+
+        let action_log = Arc::new(ActionLog::new());
+        action_log
+            .record(Action::directory_moved(
+                "rename".to_string(),
+                old_dir.clone(),
+                new_dir.clone(),
+                "Renamed old_module -> new_module".to_string(),
+            ))
+            .await;
+
+        let command = UndoCommand::new(action_log);
+        let context = CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: Some(temp_dir.path().to_string_lossy().to_string()),
+            sandbox_level: SandboxLevel::WorkspaceWrite,
+            dry_run: false,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log: None,
+            timeout: None,
+            progress: None,
+        };
+
+        let result = command
+            .execute(&serde_json::json!({ "count": 1 }), &context)
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(!new_dir.exists());
+        assert!(old_dir.join("lib.rs").exists());
+    }
+
+    #[tokio::test]
+    async fn test_undo_transaction_restores_all_files() {
+        use crate::file_ops::{EditStrategy, FileEditRequest, FileOperations};
+
+        let temp_dir = TempDir::new().unwrap();
+        let first = temp_dir.path().join("first.txt");
+        let second = temp_dir.path().join("second.txt");
+        std::fs::write(&first, "first").unwrap();
+
+        let requests = vec![
+            FileEditRequest {
+                path: first.clone(),
+                strategy: EditStrategy::Replace {
+                    content: "changed".to_string(),
+                },
+                create_backup: false,
+                create_if_missing: false,
+                force_binary: false,
+            },
+            FileEditRequest {
+                path: second.clone(),
+                strategy: EditStrategy::Replace {
+                    content: "created".to_string(),
+                },
+                create_backup: false,
+                create_if_missing: true,
+                force_binary: false,
+            },
+        ];
+        let result = FileOperations::with_default_config()
+            .apply_transaction(requests, &SandboxLevel::FullAccess, None)
+            .await
+            .unwrap();
+
+        let action_log = Arc::new(ActionLog::new());
+        action_log
+            .record(result.to_action("edit".to_string(), "Edited 2 files".to_string()))
+            .await;
+        assert_eq!(action_log.can_undo_count().await, 1);
+
+        let command = UndoCommand::new(action_log);
+        let context = CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: Some(temp_dir.path().to_string_lossy().to_string()),
+            sandbox_level: SandboxLevel::WorkspaceWrite,
+            dry_run: false,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log: None,
+            timeout: None,
+            progress: None,
+        };
+
+        let result = command
+            .execute(&serde_json::json!({ "count": 1 }), &context)
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(std::fs::read_to_string(&first).unwrap(), "first");
+        assert!(!second.exists());
+    }
+
+    fn workspace_context(workspace: &Path) -> CommandContext {
+        CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: Some(workspace.to_string_lossy().to_string()),
+            sandbox_level: SandboxLevel::WorkspaceWrite,
+            dry_run: false,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log: None,
+            timeout: None,
+            progress: None,
+        }
+    }
+
+    /// Write `new` over `path` and log it as an edit
+    async fn edit(action_log: &ActionLog, path: &Path, new: &str) -> Uuid {
+        let old = std::fs::read(path).unwrap();
+        std::fs::write(path, new).unwrap();
+        let action = Action::file_modified(
+            "edit".to_string(),
+            path.to_path_buf(),
+            old,
+            new.as_bytes().to_vec(),
+            format!("Edited {}", path.file_name().unwrap().to_string_lossy()),
+        );
+        let id = action.id;
+        action_log.record(action).await;
+        id
+    }
+
+    #[tokio::test]
+    async fn test_selective_undo_of_stacked_edits() {
+        let temp_dir = TempDir::new().unwrap();
+        let shared = temp_dir.path().join("shared.rs");
+        let other = temp_dir.path().join("other.rs");
+        std::fs::write(&shared, "fn a() {}\n").unwrap();
+        std::fs::write(&other, "fn b() {}\n").unwrap();
+
+        let action_log = Arc::new(ActionLog::new());
+        let first = edit(&action_log, &shared, "fn a() { 1 }\n").await;
+        let second = edit(&action_log, &other, "fn b() { 2 }\n").await;
+        let third = edit(&action_log, &shared, "fn a() { 3 }\n").await;
+
+        let command = UndoCommand::new(action_log.clone());
+        let context = workspace_context(temp_dir.path());
+
+        // The first edit is buried under a later edit of the same file
+        let result = command
+            .execute(&serde_json::json!({ "action_id": first }), &context)
+            .await
+            .unwrap();
+        assert!(!result.success);
+        let error = result.error.unwrap();
+        assert!(error.contains("later actions changed the same files"));
+        assert!(error.contains(&third.to_string()));
+        assert!(!error.contains(&second.to_string()));
+        assert_eq!(std::fs::read_to_string(&shared).unwrap(), "fn a() { 3 }\n");
+
+        // The second edit touched nothing later actions did
+        let result = command
+            .execute(&serde_json::json!({ "action_id": second }), &context)
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(std::fs::read_to_string(&other).unwrap(), "fn b() {}\n");
+        assert_eq!(std::fs::read_to_string(&shared).unwrap(), "fn a() { 3 }\n");
+        assert_eq!(action_log.can_undo_count().await, 2);
+        assert!(action_log.find_applied(second).await.is_none());
+
+        // Once the third edit is undone the first no longer conflicts
+        let result = command
+            .execute(&serde_json::json!({ "count": 1 }), &context)
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        let result = command
+            .execute(&serde_json::json!({ "action_id": first }), &context)
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(std::fs::read_to_string(&shared).unwrap(), "fn a() {}\n");
+    }
+
+    #[tokio::test]
+    async fn test_undo_preview_leaves_disk_untouched() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("lib.rs");
+        std::fs::write(&file, "one\ntwo\n").unwrap();
+
+        let action_log = Arc::new(ActionLog::new());
+        let id = edit(&action_log, &file, "one\nTWO\n").await;
+
+        let command = UndoCommand::new(action_log.clone());
+        let result = command
+            .execute(
+                &serde_json::json!({ "action_id": id, "preview": true }),
+                &workspace_context(temp_dir.path()),
+            )
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.output.contains("-TWO"));
+        assert!(result.output.contains("+two"));
+
+        let hunks: Vec<Hunk> =
+            serde_json::from_value(result.data.unwrap()["hunks"].clone()).unwrap();
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0].old_content, vec!["TWO"]);
+        assert_eq!(hunks[0].new_content, vec!["two"]);
+
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "one\nTWO\n");
+        assert_eq!(action_log.can_undo_count().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_undo_only_selected_files_of_action() {
+        let temp_dir = TempDir::new().unwrap();
+        let keep = temp_dir.path().join("keep.txt");
+        let revert = temp_dir.path().join("revert.txt");
+        std::fs::write(&keep, "keep v1").unwrap();
+        std::fs::write(&revert, "revert v1").unwrap();
+
+        let scratch = ActionLog::new();
+        edit(&scratch, &keep, "keep v2").await;
+        edit(&scratch, &revert, "revert v2").await;
+        let transaction = Action::transaction(
+            "edit".to_string(),
+            scratch.get_history().await,
+            "Edited 2 files".to_string(),
+        );
+        let id = transaction.id;
+
+        let action_log = Arc::new(ActionLog::new());
+        action_log.record(transaction).await;
+        // A later edit of keep.txt does not block reverting revert.txt
+        edit(&action_log, &keep, "keep v3").await;
+
+        let command = UndoCommand::new(action_log.clone());
+        let context = workspace_context(temp_dir.path());
+        let result = command
+            .execute(
+                &serde_json::json!({ "action_id": id, "files": ["revert.txt"] }),
+                &context,
+            )
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(std::fs::read_to_string(&revert).unwrap(), "revert v1");
+        assert_eq!(std::fs::read_to_string(&keep).unwrap(), "keep v3");
+
+        // The rest of the action stays in the history under the same id
+        let remaining = action_log.find_applied(id).await.unwrap();
+        assert_eq!(remaining.affected_paths(), vec![&keep]);
+
+        let result = command
+            .execute(
+                &serde_json::json!({ "action_id": id, "files": ["keep.txt"] }),
+                &context,
+            )
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("Edited keep.txt"));
+    }
+
     #[tokio::test]
     async fn test_undo_no_actions() {
         let temp_dir = TempDir::new().unwrap();
@@ -373,6 +970,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -406,6 +1005,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let result = command.execute(&args, &context).await.unwrap();
@@ -0,0 +1,290 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use fennec_commands::{CommandContext, CommandDescriptor, CommandExecutor, CommandRegistry};
+use fennec_core::command::{CommandPreview, CommandResult};
+use fennec_core::provider::{ProviderClient, ProviderMessage, ProviderRequest};
+use fennec_memory::MemoryService;
+use fennec_provider::{OpenAIClient, OpenAIConfig};
+use fennec_security::SandboxLevel;
+use fennec_telemetry::instrument_with_context;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+use uuid::Uuid;
+
+#[derive(Debug)]
+struct CapturedSpan {
+    name: &'static str,
+    parent: Option<&'static str>,
+    fields: HashMap<String, String>,
+}
+
+#[derive(Debug)]
+struct CapturedEvent {
+    message: String,
+    /// Names of the spans the event was recorded in, outermost first
+    scope: Vec<&'static str>,
+}
+
+/// Layer recording spans and events as they are created
+#[derive(Clone, Default)]
+struct Capture {
+    spans: Arc<Mutex<Vec<CapturedSpan>>>,
+    events: Arc<Mutex<Vec<CapturedEvent>>>,
+}
+
+impl Capture {
+    fn span(&self, name: &str) -> CapturedSpan {
+        let mut spans = self.spans.lock().unwrap();
+        let index = spans
+            .iter()
+            .position(|span| span.name == name)
+            .unwrap_or_else(|| panic!("no {} span in {:?}", name, spans));
+        spans.remove(index)
+    }
+
+    fn event(&self, message: &str) -> CapturedEvent {
+        let mut events = self.events.lock().unwrap();
+        let index = events
+            .iter()
+            .position(|event| event.message == message)
+            .unwrap_or_else(|| panic!("no event '{}' in {:?}", message, events));
+        events.remove(index)
+    }
+}
+
+#[derive(Default)]
+struct Fields(HashMap<String, String>);
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value));
+    }
+}
+
+impl<S> Layer<S> for Capture
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let span = ctx.span(id).unwrap();
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        self.spans.lock().unwrap().push(CapturedSpan {
+            name: span.name(),
+            parent: span.parent().map(|parent| parent.name()),
+            fields: fields.0,
+        });
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let scope = ctx
+            .event_scope(event)
+            .map(|scope| scope.from_root().map(|span| span.name()).collect())
+            .unwrap_or_default();
+        self.events.lock().unwrap().push(CapturedEvent {
+            message: fields.0.remove("message").unwrap_or_default(),
+            scope,
+        });
+    }
+}
+
+/// Command asking a model and searching memory, with part of its work done
+/// in a spawned task
+struct AskCommand {
+    descriptor: CommandDescriptor,
+    provider: OpenAIClient,
+    memory: MemoryService,
+}
+
+#[async_trait]
+impl CommandExecutor for AskCommand {
+    fn descriptor(&self) -> &CommandDescriptor {
+        &self.descriptor
+    }
+
+    async fn preview(
+        &self,
+        _args: &serde_json::Value,
+        _context: &CommandContext,
+    ) -> Result<CommandPreview> {
+        unreachable!("the test never previews")
+    }
+
+    async fn execute(
+        &self,
+        _args: &serde_json::Value,
+        _context: &CommandContext,
+    ) -> Result<CommandResult> {
+        let response = self
+            .provider
+            .complete(ProviderRequest {
+                id: Uuid::new_v4(),
+                messages: vec![ProviderMessage {
+                    role: "user".to_string(),
+                    content: "hello".to_string(),
+                }],
+                model: "gpt-4o".to_string(),
+                stream: false,
+                temperature: None,
+                max_tokens: None,
+            })
+            .await?;
+        self.memory.search("hello", Some(5)).await?;
+        tokio::spawn(instrument_with_context(async {
+            tracing::info!("indexed answer");
+        }))
+        .await?;
+
+        Ok(CommandResult {
+            command_id: Uuid::new_v4(),
+            success: true,
+            output: response.content,
+            error: None,
+            data: None,
+        })
+    }
+
+    fn validate_args(&self, _args: &serde_json::Value) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Serve one canned chat completion, returning the base URL to send it to
+async fn serve_completion() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buffer = [0; 4096];
+        // Read the headers and the body they announce
+        loop {
+            let read = socket.read(&mut buffer).await.unwrap();
+            request.extend_from_slice(&buffer[..read]);
+            let text = String::from_utf8_lossy(&request);
+            if let Some(end) = text.find("\r\n\r\n") {
+                let length = text[..end]
+                    .lines()
+                    .find_map(|line| {
+                        line.to_ascii_lowercase()
+                            .strip_prefix("content-length:")
+                            .map(|value| value.trim().parse::<usize>().unwrap())
+                    })
+                    .unwrap_or(0);
+                if request.len() >= end + 4 + length {
+                    break;
+                }
+            }
+        }
+        let body = serde_json::json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 0,
+            "model": "gpt-4o",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "hi there"},
+                "finish_reason": "stop"
+            }],
+            "usage": {"prompt_tokens": 5, "completion_tokens": 2, "total_tokens": 7}
+        })
+        .to_string();
+        let response = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+    });
+    format!("http://{}", address)
+}
+
+#[tokio::test]
+async fn test_command_execution_spans_share_its_correlation_id() -> Result<()> {
+    let capture = Capture::default();
+    let _subscriber =
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+
+    let provider = OpenAIClient::new(OpenAIConfig {
+        api_key: "test-key".to_string(),
+        base_url: serve_completion().await,
+        max_retries: 0,
+        ..Default::default()
+    })?;
+    let registry = CommandRegistry::new();
+    registry
+        .register_builtin(Arc::new(AskCommand {
+            descriptor: CommandDescriptor {
+                name: "ask".to_string(),
+                description: "Ask a model".to_string(),
+                version: "1.0.0".to_string(),
+                author: None,
+                capabilities_required: vec![],
+                sandbox_level_required: SandboxLevel::ReadOnly,
+                supports_preview: false,
+                supports_dry_run: false,
+                timeout: None,
+                alias_of: None,
+            },
+            provider,
+            memory: MemoryService::new().await?,
+        }))
+        .await?;
+
+    let session_id = Uuid::new_v4();
+    let context = CommandContext {
+        session_id,
+        user_id: None,
+        workspace_path: None,
+        sandbox_level: SandboxLevel::ReadOnly,
+        dry_run: false,
+        preview_only: false,
+        cancellation_token: CancellationToken::new(),
+        action_log: None,
+        timeout: None,
+        progress: None,
+    };
+    let result = registry
+        .execute_command("ask", &serde_json::json!({}), &context)
+        .await?;
+    assert!(result.success, "{:?}", result.error);
+    assert_eq!(result.output, "hi there");
+
+    let command = capture.span("command");
+    assert_eq!(command.parent, None);
+    assert_eq!(command.fields["command"], "ask");
+    assert_eq!(command.fields["session_id"], session_id.to_string());
+    let correlation_id = &command.fields["correlation_id"];
+
+    // `ProviderClient::complete` opens its own span around the request
+    assert_eq!(capture.span("complete").parent, Some("command"));
+    let provider = capture.span("provider_request");
+    assert_eq!(provider.parent, Some("complete"));
+    assert_eq!(provider.fields["provider"], "openai");
+    assert_eq!(provider.fields["model"], "gpt-4o");
+    assert_eq!(&provider.fields["correlation_id"], correlation_id);
+
+    let memory = capture.span("memory");
+    assert_eq!(memory.parent, Some("command"));
+    assert_eq!(memory.fields["operation"], "search");
+    assert_eq!(&memory.fields["correlation_id"], correlation_id);
+
+    assert_eq!(capture.event("indexed answer").scope, ["command"]);
+    Ok(())
+}
@@ -21,6 +21,8 @@ fn create_test_context(
         preview_only: false,
         cancellation_token: CancellationToken::new(),
         action_log: None,
+        timeout: None,
+        progress: None,
     }
 }
 
@@ -32,11 +34,13 @@ async fn test_command_registry_initialization() -> Result<()> {
     let commands = registry.list_commands().await;
     let command_names: Vec<String> = commands.iter().map(|c| c.name.clone()).collect();
 
-    // Expect all 17 built-in commands (including Sprint 4 features)
+    // Expect all 19 built-in commands (including Sprint 4 features)
     assert!(command_names.contains(&"plan".to_string()));
     assert!(command_names.contains(&"create".to_string()));
     assert!(command_names.contains(&"delete".to_string()));
+    assert!(command_names.contains(&"trash".to_string()));
     assert!(command_names.contains(&"rename".to_string()));
+    assert!(command_names.contains(&"rename-symbol".to_string()));
     assert!(command_names.contains(&"edit".to_string()));
     assert!(command_names.contains(&"run".to_string()));
     assert!(command_names.contains(&"diff".to_string()));
@@ -52,7 +56,7 @@ async fn test_command_registry_initialization() -> Result<()> {
     assert!(command_names.contains(&"summarize_enhanced".to_string()));
 
     // Ensure we didn't unintentionally register duplicates
-    assert_eq!(command_names.len(), 17);
+    assert_eq!(command_names.len(), 19);
 
     Ok(())
 }
@@ -395,3 +399,35 @@ async fn test_command_filtering_by_sandbox_level() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_configured_registry_records_execution_history() -> Result<()> {
+    let mut config = fennec_core::config::Config::default();
+    config.commands.stored_results_per_session = 0;
+    let registry = fennec_commands::create_command_registry_with_config(&config).await?;
+    let temp_dir = tempdir()?;
+    let context = create_test_context(
+        SandboxLevel::ReadOnly,
+        false,
+        Some(temp_dir.path().to_string_lossy().to_string()),
+    );
+
+    registry
+        .execute_command("search", &serde_json::json!({"query": "needle"}), &context)
+        .await?;
+    let result = registry
+        .execute_command("history", &serde_json::json!({"format": "json"}), &context)
+        .await?;
+
+    assert!(result.success, "{:?}", result.error);
+    let page: fennec_commands::HistoryPage = serde_json::from_value(result.data.unwrap())?;
+    assert_eq!(page.total, 1);
+    assert_eq!(page.entries[0].command, "search");
+    assert!(page.entries[0].success);
+    assert_eq!(
+        page.entries[0].args_summary.as_deref(),
+        Some(r#"{"query":"needle"}"#)
+    );
+
+    Ok(())
+}
@@ -0,0 +1,372 @@
+//! Failure injection: each scenario scripts the provider's behavior call by
+//! call and drives a full command through it, asserting what the user sees.
+
+use anyhow::Result;
+use fennec_commands::{
+    CommandContext, CommandExecutor, EnhancedSummarizeCommand, GeneratedPlan, PlanCommand,
+    UNPARSED_PLAN_TAG,
+};
+use fennec_core::provider::{ProviderClient, ProviderMessage, ProviderRequest};
+use fennec_provider::{FallbackProviderClient, FallbackTarget, MockProviderClient, ProviderError};
+use fennec_security::SandboxLevel;
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::json;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+/// Steps the plan command can parse out of a provider reply
+const PLAN_STEPS: &str = r#"[{"title": "Add retry budget", "description": "Cap retries per request"}, {"title": "Test it"}]"#;
+
+/// What the scripted provider does for one call
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "behavior", rename_all = "snake_case")]
+enum ProviderBehavior {
+    /// Reply with `content`
+    Respond { content: String },
+    /// Fail with a timeout after waiting `after_ms`
+    Timeout { after_ms: u64 },
+    /// Reject the call as rate limited
+    RateLimited { retry_after: u64 },
+    /// Stream `chunks`, then drop the connection
+    Disconnect { chunks: Vec<String> },
+    /// Reply with `content` that is not the JSON the caller asked for
+    Malformed { content: String },
+    /// Fail in a way no retry can fix
+    Fatal { reason: String },
+}
+
+/// An ordered list of provider behaviors, one per call, e.g.
+/// `{"name": "...", "calls": [{"behavior": "timeout", "after_ms": 10}]}`
+#[derive(Debug, Clone, Deserialize)]
+struct ProviderScenario {
+    name: String,
+    calls: Vec<ProviderBehavior>,
+}
+
+/// A provider acting out `behaviors`, one per call; calls beyond the script
+/// fail
+fn scripted(behaviors: Vec<ProviderBehavior>) -> Arc<MockProviderClient> {
+    let mut builder = MockProviderClient::builder();
+    for behavior in behaviors {
+        builder = match behavior {
+            ProviderBehavior::Respond { content } | ProviderBehavior::Malformed { content } => {
+                builder.text(content)
+            }
+            ProviderBehavior::Timeout { after_ms } => builder
+                .error(ProviderError::Timeout {
+                    operation: "chat completion".to_string(),
+                    timeout_ms: after_ms,
+                })
+                .with_latency(Duration::from_millis(after_ms)),
+            ProviderBehavior::RateLimited { retry_after } => {
+                builder.error(ProviderError::RateLimit {
+                    provider: "mock".to_string(),
+                    message: "too many requests".to_string(),
+                    retry_after,
+                    daily_limit: None,
+                    current_usage: None,
+                })
+            }
+            ProviderBehavior::Disconnect { chunks } => builder.disconnect(
+                chunks,
+                ProviderError::StreamError {
+                    operation: "chat completion".to_string(),
+                    reason: "connection reset by peer".to_string(),
+                },
+            ),
+            ProviderBehavior::Fatal { reason } => {
+                builder.error(ProviderError::AuthenticationFailed {
+                    provider: "mock".to_string(),
+                    reason,
+                })
+            }
+        };
+    }
+    Arc::new(builder.build())
+}
+
+/// `provider` with each call retried up to `attempts` times in total, the
+/// way the fallback chain retries a transient failure
+fn with_retries(provider: &Arc<MockProviderClient>, attempts: usize) -> FallbackProviderClient {
+    let targets = (0..attempts)
+        .map(|_| FallbackTarget::new(provider.clone(), "mock"))
+        .collect();
+    FallbackProviderClient::new(targets)
+}
+
+/// Plan command asking `provider`, retried up to `attempts` times per call
+async fn plan_command(provider: &Arc<MockProviderClient>, attempts: usize) -> Result<PlanCommand> {
+    let retrying = with_retries(provider, attempts);
+    Ok(PlanCommand::new()
+        .await?
+        .with_provider(Arc::new(retrying), "mock"))
+}
+
+/// Recursive summarize command asking `provider`, retried up to `attempts`
+/// times per call
+fn summarize_command(
+    provider: &Arc<MockProviderClient>,
+    attempts: usize,
+) -> EnhancedSummarizeCommand {
+    let retrying = with_retries(provider, attempts);
+    EnhancedSummarizeCommand::new().with_provider(Arc::new(retrying), "mock")
+}
+
+fn summarize_args() -> serde_json::Value {
+    json!({"target": "src", "recursive": true, "concurrency": 1})
+}
+
+fn plan_args() -> serde_json::Value {
+    json!({"task": "Harden the client"})
+}
+
+fn context(workspace: &Path) -> CommandContext {
+    CommandContext {
+        session_id: Uuid::new_v4(),
+        user_id: None,
+        workspace_path: Some(workspace.to_string_lossy().to_string()),
+        sandbox_level: SandboxLevel::ReadOnly,
+        dry_run: false,
+        preview_only: false,
+        cancellation_token: CancellationToken::new(),
+        action_log: None,
+        timeout: None,
+        progress: None,
+    }
+}
+
+/// Workspace holding a one-file library to summarize
+fn workspace() -> Result<TempDir> {
+    let temp_dir = TempDir::new()?;
+    std::fs::create_dir(temp_dir.path().join("src"))?;
+    std::fs::write(temp_dir.path().join("src/lib.rs"), "pub fn parse() {}\n")?;
+    Ok(temp_dir)
+}
+
+/// A timeout is retried and the retry's plan is what the user gets
+#[tokio::test]
+async fn test_scenario_timeout_then_success() -> Result<()> {
+    let workspace = workspace()?;
+    let provider = scripted(vec![
+        ProviderBehavior::Timeout { after_ms: 20 },
+        ProviderBehavior::Respond {
+            content: PLAN_STEPS.to_string(),
+        },
+    ]);
+    let command = plan_command(&provider, 3).await?;
+
+    let result = command
+        .execute(&plan_args(), &context(workspace.path()))
+        .await?;
+
+    assert!(result.success, "{:?}", result.error);
+    assert!(
+        result.output.contains("### 1. Add retry budget"),
+        "{}",
+        result.output
+    );
+    let generated: GeneratedPlan = serde_json::from_value(result.data.unwrap())?;
+    assert!(!generated.fallback);
+    assert_eq!(
+        provider.requests().len(),
+        2,
+        "timeout, then the successful retry"
+    );
+    assert_eq!(provider.remaining(), 0);
+
+    Ok(())
+}
+
+/// A rate limited summary call is retried without losing the summary
+#[tokio::test]
+async fn test_scenario_rate_limit_then_success() -> Result<()> {
+    let workspace = workspace()?;
+    let provider = scripted(vec![
+        ProviderBehavior::RateLimited { retry_after: 1 },
+        ProviderBehavior::Respond {
+            content: "Parses the input.".to_string(),
+        },
+        ProviderBehavior::Respond {
+            content: "The library root.".to_string(),
+        },
+    ]);
+    let command = summarize_command(&provider, 2);
+
+    let result = command
+        .execute(&summarize_args(), &context(workspace.path()))
+        .await?;
+
+    assert!(result.success, "{:?}", result.error);
+    assert!(
+        result.output.contains("Parses the input."),
+        "{}",
+        result.output
+    );
+    assert!(
+        result.output.contains("The library root."),
+        "{}",
+        result.output
+    );
+    assert_eq!(provider.requests().len(), 3);
+
+    Ok(())
+}
+
+/// A stream that drops mid-reply keeps the text already shown and reports
+/// the disconnect; it is not retried, as that would repeat the shown text
+#[tokio::test]
+async fn test_scenario_mid_stream_disconnect_preserves_partial_reply() -> Result<()> {
+    let provider = scripted(vec![ProviderBehavior::Disconnect {
+        chunks: vec!["Step one: ".to_string(), "add a retry budget".to_string()],
+    }]);
+    let retrying = with_retries(&provider, 3);
+
+    let request = ProviderRequest {
+        id: Uuid::new_v4(),
+        messages: vec![ProviderMessage {
+            role: "user".to_string(),
+            content: "Plan the retry work".to_string(),
+        }],
+        model: "mock".to_string(),
+        temperature: None,
+        max_tokens: None,
+        stream: true,
+    };
+
+    // Read the way the chat view does: show each chunk as it arrives
+    let mut stream = retrying.stream(request).await?;
+    let mut shown = String::new();
+    let mut failure = None;
+    while let Some(chunk) = stream.next().await {
+        match chunk {
+            Ok(text) => shown.push_str(&text),
+            Err(error) => {
+                failure = Some(error);
+                break;
+            }
+        }
+    }
+
+    assert_eq!(shown, "Step one: add a retry budget");
+    let failure = failure.expect("the disconnect is surfaced");
+    assert!(
+        failure.to_string().contains("connection reset by peer"),
+        "{}",
+        failure
+    );
+    assert_eq!(provider.requests().len(), 1);
+
+    Ok(())
+}
+
+/// A reply that is not the JSON step list asked for still gives a plan,
+/// kept as one step and tagged as unparsed
+#[tokio::test]
+async fn test_scenario_malformed_json_falls_back_to_one_step() -> Result<()> {
+    let workspace = workspace()?;
+    let provider = scripted(vec![ProviderBehavior::Malformed {
+        content: r#"[{"title": "Add retry budget""#.to_string(),
+    }]);
+    let command = plan_command(&provider, 3).await?;
+
+    let result = command
+        .execute(&plan_args(), &context(workspace.path()))
+        .await?;
+
+    assert!(result.success, "{:?}", result.error);
+    assert!(
+        result.output.contains("could not be split into steps"),
+        "{}",
+        result.output
+    );
+    let generated: GeneratedPlan = serde_json::from_value(result.data.unwrap())?;
+    assert!(generated.fallback);
+    assert_eq!(generated.plan.steps.len(), 1);
+    assert!(generated.plan.tags.contains(&UNPARSED_PLAN_TAG.to_string()));
+    assert_eq!(
+        provider.requests().len(),
+        1,
+        "a reply that arrived is not retried"
+    );
+
+    Ok(())
+}
+
+/// When every retry times out the command fails with the timeout, not a
+/// generic error
+#[tokio::test]
+async fn test_scenario_persistent_timeouts_surface_a_clean_error() -> Result<()> {
+    let workspace = workspace()?;
+    let provider = scripted(vec![ProviderBehavior::Timeout { after_ms: 5 }; 3]);
+    let command = plan_command(&provider, 3).await?;
+
+    let result = command
+        .execute(&plan_args(), &context(workspace.path()))
+        .await?;
+
+    assert!(!result.success);
+    let error = result.error.expect("the failure is reported");
+    assert!(error.contains("Provider request failed"), "{}", error);
+    assert!(error.contains("exceeded 5ms"), "{}", error);
+    assert_eq!(provider.requests().len(), 3);
+
+    Ok(())
+}
+
+/// A failure no retry can fix is reported at once
+#[tokio::test]
+async fn test_scenario_fatal_error_is_not_retried() -> Result<()> {
+    let workspace = workspace()?;
+    let provider = scripted(vec![ProviderBehavior::Fatal {
+        reason: "API key revoked".to_string(),
+    }]);
+    let command = summarize_command(&provider, 3);
+
+    let result = command
+        .execute(&summarize_args(), &context(workspace.path()))
+        .await?;
+
+    assert!(!result.success);
+    let error = result.error.expect("the failure is reported");
+    assert!(error.contains("Failed to summarize"), "{}", error);
+    assert!(error.contains("API key revoked"), "{}", error);
+    assert_eq!(provider.requests().len(), 1);
+
+    Ok(())
+}
+
+/// Scenarios can be written as JSON fixtures
+#[tokio::test]
+async fn test_scenario_script_from_json() -> Result<()> {
+    let workspace = workspace()?;
+    let scenario: ProviderScenario = serde_json::from_value(json!({
+        "name": "rate limited, timed out, then answered",
+        "calls": [
+            {"behavior": "rate_limited", "retry_after": 1},
+            {"behavior": "timeout", "after_ms": 5},
+            {"behavior": "respond", "content": PLAN_STEPS}
+        ]
+    }))?;
+    let provider = scripted(scenario.calls.clone());
+    let command = plan_command(&provider, 3).await?;
+
+    let result = command
+        .execute(&plan_args(), &context(workspace.path()))
+        .await?;
+
+    assert!(result.success, "{:?}", result.error);
+    assert!(
+        result.output.contains("### 2. Test it"),
+        "{}: {}",
+        scenario.name,
+        result.output
+    );
+    assert_eq!(provider.requests().len(), scenario.calls.len());
+
+    Ok(())
+}
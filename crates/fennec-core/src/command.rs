@@ -27,9 +27,31 @@ pub struct CommandPreview {
 
 #[derive(Debug, Clone, Serialize, Deserialize, Hash)]
 pub enum PreviewAction {
-    ReadFile { path: String },
-    WriteFile { path: String, content: String },
-    ExecuteShell { command: String },
+    ReadFile {
+        path: String,
+    },
+    WriteFile {
+        path: String,
+        content: String,
+        /// Unified diff against the file's current content, if it exists
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        diff: Option<String>,
+        /// Bytes removed plus bytes added
+        #[serde(default)]
+        bytes_changed: u64,
+    },
+    /// Removing a file, or a directory and everything in it
+    DeletePath {
+        path: String,
+        file_count: u64,
+        total_bytes: u64,
+    },
+    ExecuteShell {
+        command: String,
+        /// Why the command classifier considers the command risky
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        risk_reasons: Vec<String>,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,4 +60,7 @@ pub struct CommandResult {
     pub success: bool,
     pub output: String,
     pub error: Option<String>,
+    /// Structured payload for callers that render results themselves (e.g. the TUI)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
 }
@@ -1,8 +1,11 @@
+use crate::command::Capability;
+use crate::config_layers::{ConfigLayer, ConfigLoader, ConfigSources};
+use crate::provider::TaskKind;
 use crate::Result;
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use tracing::info;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -10,8 +13,24 @@ pub struct Config {
     pub security: SecurityConfig,
     pub memory: MemoryConfig,
     pub tui: TuiConfig,
+    #[serde(default)]
+    pub commands: CommandsConfig,
+    #[serde(default)]
+    pub usage: UsageConfig,
+    #[serde(default)]
+    pub response_cache: ResponseCacheConfig,
+    #[serde(default)]
+    pub provider_logging: ProviderLoggingConfig,
+    #[serde(default)]
+    pub session_checkpoint: SessionCheckpointConfig,
+    #[serde(default)]
+    pub session_idle: SessionIdleConfig,
+    #[serde(default)]
+    pub compaction: CompactionConfig,
     #[cfg(feature = "telemetry")]
     pub telemetry: Option<TelemetryConfigRef>,
+    #[serde(skip)]
+    pub(crate) sources: ConfigSources,
 }
 
 #[cfg(feature = "telemetry")]
@@ -38,18 +57,129 @@ pub struct ProviderConfig {
 
     pub default_model: String,
     pub base_url: Option<String>,
+    /// Limit on a whole non-streaming request, from sending it to reading
+    /// the reply
     pub timeout_seconds: u64,
+    /// Limit on establishing a connection; 0 waits indefinitely
+    #[serde(default = "default_connect_timeout_seconds")]
+    pub connect_timeout_seconds: u64,
+    /// Abort a streamed reply when no data arrives for this long, however
+    /// long the stream has been running; 0 disables the check
+    #[serde(default = "default_stream_idle_timeout_seconds")]
+    pub stream_idle_timeout_seconds: u64,
+    /// Models tried in order when the default model is unavailable or its
+    /// context window is exceeded
+    #[serde(default)]
+    pub fallback_models: Vec<String>,
+    /// Provider, model and sampling parameters per kind of task; kinds
+    /// without a route go to `provider` and `default_model`
+    #[serde(default)]
+    pub routes: HashMap<TaskKind, TaskRoute>,
+}
+
+/// Where requests for one kind of task are sent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskRoute {
+    /// Provider name; the configured default provider when unset
+    #[serde(default)]
+    pub provider: Option<String>,
+    pub model: String,
+    /// Sampling temperature for requests that don't set their own
+    #[serde(default)]
+    pub temperature: Option<f32>,
+}
+
+impl TaskRoute {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            provider: None,
+            model: model.into(),
+            temperature: None,
+        }
+    }
+
+    pub fn with_provider(mut self, provider: impl Into<String>) -> Self {
+        self.provider = Some(provider.into());
+        self
+    }
+
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
 }
 
 fn default_provider() -> String {
     "openai".to_string()
 }
 
+fn default_connect_timeout_seconds() -> u64 {
+    10
+}
+
+fn default_stream_idle_timeout_seconds() -> u64 {
+    60
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SecurityConfig {
     pub default_sandbox_level: String,
     pub audit_log_enabled: bool,
     pub audit_log_path: Option<PathBuf>,
+    #[serde(default)]
+    pub approval_policies: ApprovalPolicies,
+}
+
+/// What to do with an operation that needs approval
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalPolicy {
+    AutoApprove,
+    /// Ask the user
+    #[default]
+    Prompt,
+    AutoDeny,
+}
+
+impl std::fmt::Display for ApprovalPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApprovalPolicy::AutoApprove => write!(f, "auto_approve"),
+            ApprovalPolicy::Prompt => write!(f, "prompt"),
+            ApprovalPolicy::AutoDeny => write!(f, "auto_deny"),
+        }
+    }
+}
+
+/// Approval policy per risk level, and per capability for operations that
+/// need one. Critical operations are never approved automatically.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ApprovalPolicies {
+    #[serde(default)]
+    pub low: ApprovalPolicy,
+    #[serde(default)]
+    pub medium: ApprovalPolicy,
+    #[serde(default)]
+    pub high: ApprovalPolicy,
+    #[serde(default)]
+    pub critical: ApprovalPolicy,
+    /// Policies that take precedence over the risk level for operations
+    /// needing the capability; the strictest applies when several match
+    #[serde(default)]
+    pub capabilities: HashMap<Capability, ApprovalPolicy>,
+}
+
+impl ApprovalPolicies {
+    /// Reject tables that would approve critical operations without asking
+    pub fn validate(&self) -> Result<()> {
+        if self.critical == ApprovalPolicy::AutoApprove {
+            return Err(crate::FennecError::ConfigInvalid {
+                issue: "security.approval_policies.critical cannot be auto_approve".to_string(),
+                suggestion: "Use \"prompt\" or \"auto_deny\" for critical operations".to_string(),
+            });
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,6 +193,297 @@ pub struct MemoryConfig {
 pub struct TuiConfig {
     pub theme: String,
     pub key_bindings: KeyBindings,
+    /// Wrap long lines of code blocks instead of scrolling them horizontally
+    #[serde(default)]
+    pub wrap_code_blocks: bool,
+    /// Capture the mouse for scrolling and clicking; turn off to keep the
+    /// terminal's own text selection
+    #[serde(default = "default_mouse_capture")]
+    pub mouse_capture: bool,
+}
+
+fn default_mouse_capture() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandsConfig {
+    /// Timeout for commands that don't declare their own; 0 disables it
+    #[serde(default = "default_command_timeout_seconds")]
+    pub default_timeout_seconds: u64,
+    /// Days deleted files stay in the workspace trash before being purged;
+    /// 0 keeps them until the trash is emptied by hand
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: u64,
+    /// Command results persisted per session for later replay; 0 disables
+    /// the result store
+    #[serde(default = "default_stored_results_per_session")]
+    pub stored_results_per_session: usize,
+}
+
+fn default_command_timeout_seconds() -> u64 {
+    600
+}
+
+fn default_trash_retention_days() -> u64 {
+    7
+}
+
+fn default_stored_results_per_session() -> usize {
+    50
+}
+
+impl Default for CommandsConfig {
+    fn default() -> Self {
+        Self {
+            default_timeout_seconds: default_command_timeout_seconds(),
+            trash_retention_days: default_trash_retention_days(),
+            stored_results_per_session: default_stored_results_per_session(),
+        }
+    }
+}
+
+impl CommandsConfig {
+    pub fn default_timeout(&self) -> Option<std::time::Duration> {
+        (self.default_timeout_seconds > 0)
+            .then(|| std::time::Duration::from_secs(self.default_timeout_seconds))
+    }
+
+    pub fn trash_retention(&self) -> Option<std::time::Duration> {
+        (self.trash_retention_days > 0)
+            .then(|| std::time::Duration::from_secs(self.trash_retention_days * 24 * 60 * 60))
+    }
+}
+
+/// Token prices and per-session spending limits
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageConfig {
+    /// Prices keyed by model name, or by a prefix such as `gpt-4o` covering
+    /// dated variants; usage of unlisted models is counted but not costed
+    #[serde(default)]
+    pub prices: HashMap<String, ModelPrice>,
+    /// Warn once a session has spent this many US dollars
+    #[serde(default)]
+    pub soft_budget_usd: Option<f64>,
+    /// Refuse further provider requests once a session has spent this many
+    /// US dollars
+    #[serde(default)]
+    pub hard_budget_usd: Option<f64>,
+}
+
+/// US dollars per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub prompt_per_million: f64,
+    pub completion_per_million: f64,
+}
+
+impl UsageConfig {
+    /// Price of `model`, matching the longest configured prefix when there
+    /// is no exact entry
+    pub fn price_for(&self, model: &str) -> Option<ModelPrice> {
+        if let Some(price) = self.prices.get(model) {
+            return Some(*price);
+        }
+        self.prices
+            .iter()
+            .filter(|(name, _)| model.starts_with(name.as_str()))
+            .max_by_key(|(name, _)| name.len())
+            .map(|(_, price)| *price)
+    }
+}
+
+/// On-disk cache of provider responses to repeated identical requests
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseCacheConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Defaults to `response-cache` under the memory storage path
+    #[serde(default)]
+    pub directory: Option<PathBuf>,
+    #[serde(default = "default_response_cache_ttl_seconds")]
+    pub ttl_seconds: u64,
+    /// Oldest entries are evicted once the cache grows past this size
+    #[serde(default = "default_response_cache_max_size_bytes")]
+    pub max_size_bytes: u64,
+    /// Also cache requests sampled with a temperature above zero, whose
+    /// replies would otherwise vary between calls
+    #[serde(default)]
+    pub allow_nondeterministic: bool,
+}
+
+fn default_response_cache_ttl_seconds() -> u64 {
+    7 * 24 * 60 * 60
+}
+
+fn default_response_cache_max_size_bytes() -> u64 {
+    50 * 1024 * 1024
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: None,
+            ttl_seconds: default_response_cache_ttl_seconds(),
+            max_size_bytes: default_response_cache_max_size_bytes(),
+            allow_nondeterministic: false,
+        }
+    }
+}
+
+/// Debug logging of the requests sent to and responses received from the
+/// provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderLoggingConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Logged strings longer than this are cut short
+    #[serde(default = "default_provider_logging_max_content_chars")]
+    pub max_content_chars: usize,
+    /// Log payloads verbatim, without truncation or redaction of their
+    /// content. Prompts routinely contain file contents and secrets, so this
+    /// is only meant for short local debugging sessions. API keys in headers
+    /// are redacted regardless.
+    #[serde(default)]
+    pub unsafe_log_full_payloads: bool,
+}
+
+fn default_provider_logging_max_content_chars() -> usize {
+    500
+}
+
+impl Default for ProviderLoggingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_content_chars: default_provider_logging_max_content_chars(),
+            unsafe_log_full_payloads: false,
+        }
+    }
+}
+
+/// Snapshots of the open sessions written to disk so they can be resumed
+/// after a restart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionCheckpointConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Defaults to `session-checkpoint.json` under the memory storage path
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+    /// How often a checkpoint is written besides when sessions start, end
+    /// or receive a reply
+    #[serde(default = "default_checkpoint_interval_seconds")]
+    pub interval_seconds: u64,
+    /// Checkpoints older than this are discarded instead of resumed
+    #[serde(default = "default_checkpoint_max_age_seconds")]
+    pub max_age_seconds: u64,
+}
+
+fn default_checkpoint_interval_seconds() -> u64 {
+    60
+}
+
+fn default_checkpoint_max_age_seconds() -> u64 {
+    24 * 60 * 60
+}
+
+impl Default for SessionCheckpointConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: None,
+            interval_seconds: default_checkpoint_interval_seconds(),
+            max_age_seconds: default_checkpoint_max_age_seconds(),
+        }
+    }
+}
+
+/// Warning about and ending sessions nobody has used for a while
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionIdleConfig {
+    #[serde(default = "default_idle_enabled")]
+    pub enabled: bool,
+    /// Minutes without messages or commands before a session is reported
+    /// as idle
+    #[serde(default = "default_idle_warn_after_minutes")]
+    pub warn_after_minutes: u64,
+    /// Minutes without messages or commands before a session is ended
+    #[serde(default = "default_idle_end_after_minutes")]
+    pub end_after_minutes: u64,
+}
+
+fn default_idle_enabled() -> bool {
+    true
+}
+
+fn default_idle_warn_after_minutes() -> u64 {
+    30
+}
+
+fn default_idle_end_after_minutes() -> u64 {
+    120
+}
+
+impl Default for SessionIdleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_idle_enabled(),
+            warn_after_minutes: default_idle_warn_after_minutes(),
+            end_after_minutes: default_idle_end_after_minutes(),
+        }
+    }
+}
+
+/// Shrinking long conversations before they outgrow the model's context
+/// window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactionConfig {
+    #[serde(default = "default_compaction_enabled")]
+    pub enabled: bool,
+    /// Estimated prompt tokens above which the oldest messages are compacted
+    #[serde(default = "default_compaction_max_prompt_tokens")]
+    pub max_prompt_tokens: usize,
+    /// Most recent messages always sent as they are
+    #[serde(default = "default_compaction_keep_recent_messages")]
+    pub keep_recent_messages: usize,
+    #[serde(default)]
+    pub strategy: CompactionStrategy,
+}
+
+/// What becomes of the messages taken out of the prompt
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompactionStrategy {
+    /// Replace them with a summary written by the provider
+    #[default]
+    Summarize,
+    /// Drop them, leaving a note that they were left out
+    Truncate,
+}
+
+fn default_compaction_enabled() -> bool {
+    true
+}
+
+fn default_compaction_max_prompt_tokens() -> usize {
+    100_000
+}
+
+fn default_compaction_keep_recent_messages() -> usize {
+    6
+}
+
+impl Default for CompactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_compaction_enabled(),
+            max_prompt_tokens: default_compaction_max_prompt_tokens(),
+            keep_recent_messages: default_compaction_keep_recent_messages(),
+            strategy: CompactionStrategy::default(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -83,11 +504,16 @@ impl Default for Config {
                 default_model: "gpt-4".to_string(),
                 base_url: None,
                 timeout_seconds: 30,
+                connect_timeout_seconds: default_connect_timeout_seconds(),
+                stream_idle_timeout_seconds: default_stream_idle_timeout_seconds(),
+                fallback_models: Vec::new(),
+                routes: HashMap::new(),
             },
             security: SecurityConfig {
                 default_sandbox_level: "workspace-write".to_string(),
                 audit_log_enabled: true,
                 audit_log_path: None,
+                approval_policies: ApprovalPolicies::default(),
             },
             memory: MemoryConfig {
                 storage_path: PathBuf::from(".fennec"),
@@ -101,49 +527,59 @@ impl Default for Config {
                     help: "F1".to_string(),
                     clear: "Ctrl+L".to_string(),
                 },
+                wrap_code_blocks: false,
+                mouse_capture: true,
             },
+            commands: CommandsConfig::default(),
+            usage: UsageConfig::default(),
+            response_cache: ResponseCacheConfig::default(),
+            provider_logging: ProviderLoggingConfig::default(),
+            session_checkpoint: SessionCheckpointConfig::default(),
+            session_idle: SessionIdleConfig::default(),
+            compaction: CompactionConfig::default(),
             #[cfg(feature = "telemetry")]
             telemetry: Some(TelemetryConfigRef {
                 config_path: None,
                 enabled: true,
             }),
+            sources: ConfigSources::default(),
         }
     }
 }
 
 impl Config {
+    /// Load the configuration layered from the global config file (or
+    /// `config_path`), the current directory's `.fennec/config.toml` and
+    /// environment variables. See [`ConfigLoader`] for adding other layers.
     pub async fn load(config_path: Option<&Path>) -> Result<Self> {
-        let config_file = match config_path {
-            Some(path) => path.to_path_buf(),
-            None => Self::default_config_path()?,
-        };
-
-        if config_file.exists() {
-            info!("Loading config from: {}", config_file.display());
-            let content = tokio::fs::read_to_string(&config_file).await.map_err(|e| {
-                crate::FennecError::FileRead {
-                    path: config_file.display().to_string(),
-                    source: e,
-                }
-            })?;
-            let mut config: Config =
-                toml::from_str(&content).map_err(|e| crate::FennecError::ConfigLoadFailed {
-                    path: config_file.display().to_string(),
-                    source: Box::new(e),
-                })?;
-
-            // Override with environment variables
-            config.load_env_overrides();
-            Ok(config)
-        } else {
-            info!("No config file found, using defaults");
-            let mut config = Self::default();
-            config.load_env_overrides();
-            Ok(config)
+        let mut loader = ConfigLoader::new();
+        if let Some(path) = config_path {
+            loader = loader.with_global_file(path);
         }
+        if let Ok(workspace) = std::env::current_dir() {
+            loader = loader.with_workspace(workspace);
+        }
+        loader.load().await
+    }
+
+    /// Layer the value of the dotted `key` was loaded from, e.g.
+    /// `explain("provider.default_model")`
+    pub fn explain(&self, key: &str) -> Option<&ConfigLayer> {
+        self.sources.layer(key)
     }
 
-    fn default_config_path() -> Result<PathBuf> {
+    /// Keys set in a layer that no configuration value exists for
+    pub fn unknown_keys(&self) -> &[(String, ConfigLayer)] {
+        self.sources.unknown_keys()
+    }
+
+    /// Config files the configuration was loaded from, the global one first
+    pub fn config_files(&self) -> Vec<&Path> {
+        self.sources.files()
+    }
+
+    /// Directory holding `config.toml` and other user configuration files
+    pub fn default_config_dir() -> Result<PathBuf> {
         let project_dirs = ProjectDirs::from("com", "fennec", "fennec").ok_or_else(|| {
             crate::FennecError::ConfigInvalid {
                 issue: "Could not determine config directory".to_string(),
@@ -151,47 +587,17 @@ impl Config {
             }
         })?;
 
-        Ok(project_dirs.config_dir().join("config.toml"))
+        Ok(project_dirs.config_dir().to_path_buf())
     }
 
-    fn load_env_overrides(&mut self) {
-        // Provider selection
-        if let Ok(provider) = std::env::var("FENNEC_PROVIDER") {
-            self.provider.provider = provider;
-        }
-
-        // OpenAI
-        if let Ok(api_key) = std::env::var("OPENAI_API_KEY") {
-            self.provider.openai_api_key = Some(api_key);
-        }
-        if let Ok(base_url) = std::env::var("OPENAI_BASE_URL") {
-            self.provider.base_url = Some(base_url);
-        }
-
-        // Anthropic
-        if let Ok(api_key) = std::env::var("ANTHROPIC_API_KEY") {
-            self.provider.anthropic_api_key = Some(api_key);
-        }
-        if let Ok(base_url) = std::env::var("ANTHROPIC_BASE_URL") {
-            self.provider.base_url = Some(base_url);
-        }
-
-        // OpenRouter
-        if let Ok(api_key) = std::env::var("OPENROUTER_API_KEY") {
-            self.provider.openrouter_api_key = Some(api_key);
-        }
-
-        // General settings
-        if let Ok(model) = std::env::var("FENNEC_DEFAULT_MODEL") {
-            self.provider.default_model = model;
-        }
+    pub(crate) fn default_config_path() -> Result<PathBuf> {
+        Ok(Self::default_config_dir()?.join("config.toml"))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::env;
     use tokio::fs;
 
     #[test]
@@ -359,15 +765,86 @@ mod tests {
         ));
     }
 
+    const APPROVAL_CONFIG_BASE: &str = r#"
+        [provider]
+        default_model = "gpt-4"
+        timeout_seconds = 30
+
+        [memory]
+        storage_path = "/tmp/fennec"
+        max_transcript_size = 1000
+        enable_agents_md = true
+
+        [tui]
+        theme = "default"
+
+        [tui.key_bindings]
+        quit = "Ctrl+C"
+        help = "F1"
+        clear = "Ctrl+L"
+
+        [security]
+        default_sandbox_level = "workspace-write"
+        audit_log_enabled = true
+    "#;
+
     #[tokio::test]
-    async fn test_load_env_overrides() {
-        // Set environment variables
-        env::set_var("OPENAI_API_KEY", "test-api-key");
-        env::set_var("OPENAI_BASE_URL", "https://test.openai.com");
-        env::set_var("FENNEC_DEFAULT_MODEL", "gpt-3.5-turbo");
+    async fn test_load_approval_policies() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        let toml_content = format!(
+            "{}\n[security.approval_policies]\nlow = \"auto_approve\"\nhigh = \"auto_deny\"\n\n\
+             [security.approval_policies.capabilities]\nNetworkAccess = \"auto_deny\"\n",
+            APPROVAL_CONFIG_BASE
+        );
+        fs::write(&config_path, toml_content).await.unwrap();
 
-        let mut config = Config::default();
-        config.load_env_overrides();
+        let policies = Config::load(Some(&config_path))
+            .await
+            .unwrap()
+            .security
+            .approval_policies;
+        assert_eq!(policies.low, ApprovalPolicy::AutoApprove);
+        assert_eq!(policies.medium, ApprovalPolicy::Prompt);
+        assert_eq!(policies.high, ApprovalPolicy::AutoDeny);
+        assert_eq!(
+            policies.capabilities.get(&Capability::NetworkAccess),
+            Some(&ApprovalPolicy::AutoDeny)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_auto_approving_critical_operations_is_rejected() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        let toml_content = format!(
+            "{}\n[security.approval_policies]\ncritical = \"auto_approve\"\n",
+            APPROVAL_CONFIG_BASE
+        );
+        fs::write(&config_path, toml_content).await.unwrap();
+
+        let result = Config::load(Some(&config_path)).await;
+        assert!(matches!(
+            result.unwrap_err(),
+            crate::FennecError::ConfigInvalid { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_load_env_overrides() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let env = [
+            ("OPENAI_API_KEY", "test-api-key"),
+            ("OPENAI_BASE_URL", "https://test.openai.com"),
+            ("FENNEC_DEFAULT_MODEL", "gpt-3.5-turbo"),
+        ];
+
+        let config = ConfigLoader::new()
+            .with_global_file(temp_dir.path().join("config.toml"))
+            .with_env(env.iter().map(|(k, v)| (k.to_string(), v.to_string())))
+            .load()
+            .await
+            .unwrap();
 
         assert_eq!(
             config.provider.openai_api_key,
@@ -378,11 +855,6 @@ mod tests {
             Some("https://test.openai.com".to_string())
         );
         assert_eq!(config.provider.default_model, "gpt-3.5-turbo");
-
-        // Clean up
-        env::remove_var("OPENAI_API_KEY");
-        env::remove_var("OPENAI_BASE_URL");
-        env::remove_var("FENNEC_DEFAULT_MODEL");
     }
 
     #[test]
@@ -416,4 +888,25 @@ mod tests {
         assert_eq!(config.provider.default_model, cloned.provider.default_model);
         assert_eq!(config.tui.theme, cloned.tui.theme);
     }
+
+    #[test]
+    fn test_usage_price_prefers_longest_prefix() {
+        let price = |prompt| ModelPrice {
+            prompt_per_million: prompt,
+            completion_per_million: prompt * 4.0,
+        };
+        let usage = UsageConfig {
+            prices: HashMap::from([
+                ("gpt-4".to_string(), price(30.0)),
+                ("gpt-4o".to_string(), price(2.5)),
+                ("gpt-4o-mini".to_string(), price(0.15)),
+            ]),
+            ..Default::default()
+        };
+
+        assert_eq!(usage.price_for("gpt-4o-mini"), Some(price(0.15)));
+        assert_eq!(usage.price_for("gpt-4o-2024-08-06"), Some(price(2.5)));
+        assert_eq!(usage.price_for("gpt-4-turbo"), Some(price(30.0)));
+        assert_eq!(usage.price_for("claude-3"), None);
+    }
 }
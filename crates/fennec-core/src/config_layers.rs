@@ -0,0 +1,481 @@
+//! Configuration assembled from layers, each overriding the ones before it:
+//! built-in defaults, the global config file, the workspace's
+//! `.fennec/config.toml`, environment variables and command line flags.
+
+use crate::config::Config;
+use crate::{FennecError, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use toml::Value;
+use tracing::{info, warn};
+
+/// Environment variables read into the configuration and the key each
+/// sets; later entries win when several set the same key
+const ENV_OVERRIDES: &[(&str, &str)] = &[
+    ("FENNEC_PROVIDER", "provider.provider"),
+    ("OPENAI_API_KEY", "provider.openai_api_key"),
+    ("OPENAI_BASE_URL", "provider.base_url"),
+    ("ANTHROPIC_API_KEY", "provider.anthropic_api_key"),
+    ("ANTHROPIC_BASE_URL", "provider.base_url"),
+    ("OPENROUTER_API_KEY", "provider.openrouter_api_key"),
+    ("FENNEC_DEFAULT_MODEL", "provider.default_model"),
+];
+
+/// Where a configuration value came from, lowest precedence first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigLayer {
+    Default,
+    GlobalFile(PathBuf),
+    WorkspaceFile(PathBuf),
+    Environment(String),
+    CommandLine,
+}
+
+impl fmt::Display for ConfigLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigLayer::Default => write!(f, "built-in default"),
+            ConfigLayer::GlobalFile(path) => write!(f, "global config {}", path.display()),
+            ConfigLayer::WorkspaceFile(path) => {
+                write!(f, "workspace config {}", path.display())
+            }
+            ConfigLayer::Environment(var) => write!(f, "environment variable {}", var),
+            ConfigLayer::CommandLine => write!(f, "command line"),
+        }
+    }
+}
+
+/// Layer each value of a loaded configuration came from, by dotted key
+#[derive(Debug, Clone, Default)]
+pub struct ConfigSources {
+    layers: BTreeMap<String, ConfigLayer>,
+    unknown_keys: Vec<(String, ConfigLayer)>,
+}
+
+impl ConfigSources {
+    pub fn layer(&self, key: &str) -> Option<&ConfigLayer> {
+        self.layers.get(key)
+    }
+
+    /// Keys no configuration value exists for, with the layer setting them
+    pub fn unknown_keys(&self) -> &[(String, ConfigLayer)] {
+        &self.unknown_keys
+    }
+
+    /// Config files any value was taken from, the global one first
+    pub fn files(&self) -> Vec<&Path> {
+        let mut global = Vec::new();
+        let mut workspace = Vec::new();
+        for layer in self.layers.values() {
+            match layer {
+                ConfigLayer::GlobalFile(path) => global.push(path.as_path()),
+                ConfigLayer::WorkspaceFile(path) => workspace.push(path.as_path()),
+                _ => {}
+            }
+        }
+        global.append(&mut workspace);
+        global.dedup();
+        global
+    }
+}
+
+/// Builds a [`Config`] from its layers
+#[derive(Debug, Clone, Default)]
+pub struct ConfigLoader {
+    global_file: Option<PathBuf>,
+    workspace: Option<PathBuf>,
+    env: Option<Vec<(String, String)>>,
+    overrides: Vec<(String, String)>,
+}
+
+impl ConfigLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read the global config from `path` instead of the default location
+    pub fn with_global_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.global_file = Some(path.into());
+        self
+    }
+
+    /// Read the `.fennec/config.toml` of `workspace`
+    pub fn with_workspace(mut self, workspace: impl Into<PathBuf>) -> Self {
+        self.workspace = Some(workspace.into());
+        self
+    }
+
+    /// Take environment variables from `vars` instead of the process
+    pub fn with_env(mut self, vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        self.env = Some(vars.into_iter().collect());
+        self
+    }
+
+    /// Set `key`, e.g. `provider.timeout_seconds`, from the command line.
+    /// `value` is read as TOML, or as a string when it isn't valid TOML.
+    pub fn with_override(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.overrides.push((key.into(), value.into()));
+        self
+    }
+
+    /// Path of the workspace config file of `workspace`
+    pub fn workspace_file(workspace: &Path) -> PathBuf {
+        workspace.join(".fennec").join("config.toml")
+    }
+
+    pub async fn load(self) -> Result<Config> {
+        let mut merged = Value::try_from(Config::default()).map_err(|e| FennecError::Unknown {
+            message: "Failed to serialize the default configuration".to_string(),
+            source: Some(Box::new(e)),
+        })?;
+        let mut sources = ConfigSources::default();
+        record_leaves(&merged, "", &ConfigLayer::Default, &mut sources);
+
+        let global_file = match self.global_file {
+            Some(path) => path,
+            None => Config::default_config_path()?,
+        };
+        let mut files = vec![ConfigLayer::GlobalFile(global_file)];
+        if let Some(workspace) = &self.workspace {
+            files.push(ConfigLayer::WorkspaceFile(Self::workspace_file(workspace)));
+        }
+        for layer in files {
+            let (ConfigLayer::GlobalFile(path) | ConfigLayer::WorkspaceFile(path)) = &layer else {
+                unreachable!("only file layers are listed");
+            };
+            if !path.exists() {
+                continue;
+            }
+            info!("Loading config from: {}", path.display());
+            let content =
+                tokio::fs::read_to_string(path)
+                    .await
+                    .map_err(|e| FennecError::FileRead {
+                        path: path.display().to_string(),
+                        source: e,
+                    })?;
+            let value: Value =
+                toml::from_str(&content).map_err(|e| FennecError::ConfigLoadFailed {
+                    path: path.display().to_string(),
+                    source: Box::new(e),
+                })?;
+            merge(&mut merged, value, "", &layer, &mut sources);
+            check(&merged, &layer, &mut sources)?;
+        }
+
+        let env = match self.env {
+            Some(vars) => vars,
+            None => std::env::vars().collect(),
+        };
+        let env: BTreeMap<String, String> = env.into_iter().collect();
+        let mut env_set = false;
+        for (var, key) in ENV_OVERRIDES {
+            if let Some(value) = env.get(*var) {
+                let layer = ConfigLayer::Environment(var.to_string());
+                set_key(&mut merged, key, Value::String(value.clone()));
+                sources.layers.insert(key.to_string(), layer);
+                env_set = true;
+            }
+        }
+        if env_set {
+            check(
+                &merged,
+                &ConfigLayer::Environment("FENNEC_*".to_string()),
+                &mut sources,
+            )?;
+        }
+
+        if !self.overrides.is_empty() {
+            for (key, value) in &self.overrides {
+                set_key(&mut merged, key, parse_value(value));
+                sources.layers.insert(key.clone(), ConfigLayer::CommandLine);
+            }
+            check(&merged, &ConfigLayer::CommandLine, &mut sources)?;
+        }
+
+        let mut config = deserialize(merged, &ConfigLayer::Default, &mut Vec::new())?;
+        if let Err(FennecError::ConfigInvalid { issue, suggestion }) =
+            config.security.approval_policies.validate()
+        {
+            let origin = sources
+                .layer("security.approval_policies.critical")
+                .cloned()
+                .unwrap_or(ConfigLayer::Default);
+            return Err(FennecError::ConfigInvalid {
+                issue: format!("{} (set by {})", issue, origin),
+                suggestion,
+            });
+        }
+        config.sources = sources;
+        Ok(config)
+    }
+}
+
+/// Deserialize the configuration merged so far, reporting errors against
+/// `layer`, which was the last merged in, and warning about keys it set
+/// that no configuration value exists for
+fn check(merged: &Value, layer: &ConfigLayer, sources: &mut ConfigSources) -> Result<()> {
+    let mut unknown = Vec::new();
+    deserialize(merged.clone(), layer, &mut unknown)?;
+    for key in unknown {
+        let set_by_layer = sources
+            .layers
+            .iter()
+            .any(|(k, l)| l == layer && (*k == key || k.starts_with(&format!("{}.", key))));
+        if set_by_layer && !sources.unknown_keys.iter().any(|(k, _)| *k == key) {
+            warn!("Unknown configuration key '{}' in {}", key, layer);
+            sources.unknown_keys.push((key, layer.clone()));
+        }
+    }
+    Ok(())
+}
+
+fn deserialize(value: Value, layer: &ConfigLayer, unknown: &mut Vec<String>) -> Result<Config> {
+    let mut record = |path: serde_ignored::Path| unknown.push(path.to_string());
+    let deserializer = serde_ignored::Deserializer::new(value, &mut record);
+    serde_path_to_error::deserialize(deserializer).map_err(|e| FennecError::ConfigLoadFailed {
+        path: layer.to_string(),
+        source: Box::new(e),
+    })
+}
+
+/// Merge `overlay` into `base`, recording the layer of each value it sets
+fn merge(
+    base: &mut Value,
+    overlay: Value,
+    prefix: &str,
+    layer: &ConfigLayer,
+    sources: &mut ConfigSources,
+) {
+    match (base, overlay) {
+        (Value::Table(base), Value::Table(overlay)) => {
+            for (key, value) in overlay {
+                let path = join(prefix, &key);
+                match base.get_mut(&key) {
+                    Some(existing) if existing.is_table() && value.is_table() => {
+                        merge(existing, value, &path, layer, sources);
+                    }
+                    _ => {
+                        record_leaves(&value, &path, layer, sources);
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => {
+            record_leaves(&overlay, prefix, layer, sources);
+            *base = overlay;
+        }
+    }
+}
+
+fn record_leaves(value: &Value, prefix: &str, layer: &ConfigLayer, sources: &mut ConfigSources) {
+    match value {
+        Value::Table(table) => {
+            for (key, value) in table {
+                record_leaves(value, &join(prefix, key), layer, sources);
+            }
+        }
+        _ => {
+            sources.layers.insert(prefix.to_string(), layer.clone());
+        }
+    }
+}
+
+/// Set the dotted `key` of `root`, creating the tables on the way
+fn set_key(root: &mut Value, key: &str, value: Value) {
+    let mut current = root;
+    let mut parts = key.split('.').peekable();
+    while let Some(part) = parts.next() {
+        if !current.is_table() {
+            *current = Value::Table(toml::map::Map::new());
+        }
+        let Value::Table(table) = current else {
+            unreachable!("replaced by a table above");
+        };
+        if parts.peek().is_none() {
+            table.insert(part.to_string(), value);
+            return;
+        }
+        current = table
+            .entry(part.to_string())
+            .or_insert_with(|| Value::Table(toml::map::Map::new()));
+    }
+}
+
+fn parse_value(value: &str) -> Value {
+    toml::from_str::<toml::map::Map<String, Value>>(&format!("value = {}", value))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| Value::String(value.to_string()))
+}
+
+fn join(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const GLOBAL: &str = r#"
+        [provider]
+        default_model = "gpt-4o"
+        timeout_seconds = 45
+        connect_timeout_seconds = 5
+
+        [security]
+        default_sandbox_level = "read-only"
+        audit_log_enabled = true
+
+        [memory]
+        storage_path = "/tmp/fennec"
+        max_transcript_size = 1000
+        enable_agents_md = true
+
+        [tui]
+        theme = "dark"
+
+        [tui.key_bindings]
+        quit = "Ctrl+C"
+        help = "F1"
+        clear = "Ctrl+L"
+    "#;
+
+    struct Layers {
+        _dir: TempDir,
+        global: PathBuf,
+        workspace: PathBuf,
+    }
+
+    async fn layers(workspace_config: &str) -> Layers {
+        let dir = TempDir::new().unwrap();
+        let global = dir.path().join("config.toml");
+        tokio::fs::write(&global, GLOBAL).await.unwrap();
+        let workspace = dir.path().join("project");
+        let workspace_file = ConfigLoader::workspace_file(&workspace);
+        tokio::fs::create_dir_all(workspace_file.parent().unwrap())
+            .await
+            .unwrap();
+        tokio::fs::write(&workspace_file, workspace_config)
+            .await
+            .unwrap();
+        Layers {
+            _dir: dir,
+            global,
+            workspace,
+        }
+    }
+
+    fn loader(layers: &Layers, env: &[(&str, &str)]) -> ConfigLoader {
+        ConfigLoader::new()
+            .with_global_file(&layers.global)
+            .with_workspace(&layers.workspace)
+            .with_env(env.iter().map(|(k, v)| (k.to_string(), v.to_string())))
+    }
+
+    #[tokio::test]
+    async fn test_later_layers_take_precedence() {
+        let layers = layers(
+            r#"
+            [provider]
+            default_model = "gpt-4.1"
+            timeout_seconds = 90
+
+            [tui]
+            theme = "light"
+            "#,
+        )
+        .await;
+
+        let config = loader(&layers, &[("FENNEC_DEFAULT_MODEL", "gpt-5")])
+            .with_override("provider.timeout_seconds", "120")
+            .with_override("security.default_sandbox_level", "danger-full-access")
+            .load()
+            .await
+            .unwrap();
+
+        assert_eq!(config.provider.default_model, "gpt-5");
+        assert_eq!(config.provider.timeout_seconds, 120);
+        assert_eq!(config.provider.connect_timeout_seconds, 5);
+        assert_eq!(config.tui.theme, "light");
+        assert_eq!(config.security.default_sandbox_level, "danger-full-access");
+        assert_eq!(config.memory.max_transcript_size, 1000);
+        assert_eq!(config.commands.default_timeout_seconds, 600);
+
+        let workspace_file = ConfigLoader::workspace_file(&layers.workspace);
+        assert_eq!(
+            config.explain("provider.default_model"),
+            Some(&ConfigLayer::Environment(
+                "FENNEC_DEFAULT_MODEL".to_string()
+            ))
+        );
+        assert_eq!(
+            config.explain("provider.timeout_seconds"),
+            Some(&ConfigLayer::CommandLine)
+        );
+        assert_eq!(
+            config.explain("tui.theme"),
+            Some(&ConfigLayer::WorkspaceFile(workspace_file.clone()))
+        );
+        assert_eq!(
+            config.explain("provider.connect_timeout_seconds"),
+            Some(&ConfigLayer::GlobalFile(layers.global.clone()))
+        );
+        assert_eq!(
+            config.explain("commands.default_timeout_seconds"),
+            Some(&ConfigLayer::Default)
+        );
+        assert_eq!(config.explain("provider.nonexistent"), None);
+        assert_eq!(
+            config.config_files(),
+            vec![layers.global.as_path(), workspace_file.as_path()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unknown_keys_warn_instead_of_failing() {
+        let layers = layers("[tui]\nthme = \"light\"\n").await;
+
+        let config = loader(&layers, &[]).load().await.unwrap();
+
+        assert_eq!(config.tui.theme, "dark");
+        assert_eq!(
+            config.unknown_keys(),
+            [(
+                "tui.thme".to_string(),
+                ConfigLayer::WorkspaceFile(ConfigLoader::workspace_file(&layers.workspace))
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_errors_name_the_file_and_key() {
+        let layers = layers("[provider]\ntimeout_seconds = \"soon\"\n").await;
+
+        let error = loader(&layers, &[]).load().await.unwrap_err().to_string();
+
+        assert!(error.contains(".fennec/config.toml"), "{}", error);
+        assert!(error.contains("provider.timeout_seconds"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_policy_names_the_layer_setting_it() {
+        let layers = layers("").await;
+
+        let error = loader(&layers, &[])
+            .with_override("security.approval_policies.critical", "auto_approve")
+            .load()
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, FennecError::ConfigInvalid { .. }));
+        assert!(error.to_string().contains("command line"));
+    }
+}
@@ -50,8 +50,25 @@ pub enum RecoveryAction {
     None,
 }
 
+impl RecoveryAction {
+    /// What the action suggests doing, if it says more than to retry
+    pub fn suggestion(&self) -> Option<&str> {
+        match self {
+            RecoveryAction::RetryWithChanges(suggestion)
+            | RecoveryAction::CheckConfiguration(suggestion)
+            | RecoveryAction::CheckPermissions(suggestion)
+            | RecoveryAction::ContactSupport(suggestion)
+            | RecoveryAction::ManualAction(suggestion) => Some(suggestion),
+            RecoveryAction::Retry | RecoveryAction::None => None,
+        }
+    }
+}
+
 /// Trait for error types that provide user-friendly information
-pub trait ErrorInfo {
+pub trait ErrorInfo: std::error::Error {
+    /// Stable code identifying the kind of error, e.g. `FEN-1001`
+    fn code(&self) -> &'static str;
+
     /// Get the error category for appropriate handling
     fn category(&self) -> ErrorCategory;
 
@@ -78,6 +95,70 @@ pub trait ErrorInfo {
     fn debug_context(&self) -> Option<String> {
         None
     }
+
+    /// What the user can do about the error, e.g. "Set OPENAI_API_KEY"
+    fn hint(&self) -> Option<String> {
+        self.recovery_actions()
+            .iter()
+            .find_map(|action| action.suggestion().map(str::to_string))
+    }
+
+    /// Full technical description with the code, the chain of causes and
+    /// any debug context, for logs and bug reports
+    fn developer_message(&self) -> String {
+        let mut message = format!("[{}] {}", self.code(), self);
+        let mut source = self.source();
+        while let Some(cause) = source {
+            message.push_str(&format!("\n  caused by: {}", cause));
+            source = cause.source();
+        }
+        if let Some(context) = self.debug_context() {
+            message.push_str(&format!("\n  context: {}", context));
+        }
+        message
+    }
+}
+
+/// An error of another crate carried by a domain variant of
+/// [`FennecError`], such as [`FennecError::Provider`], keeping its code,
+/// user message and hint
+#[derive(Debug)]
+pub struct DomainError {
+    code: &'static str,
+    user_message: String,
+    hint: Option<String>,
+    error: Box<dyn std::error::Error + Send + Sync>,
+}
+
+impl DomainError {
+    pub fn new<E>(error: E) -> Self
+    where
+        E: ErrorInfo + Send + Sync + 'static,
+    {
+        Self {
+            code: error.code(),
+            user_message: error.user_message(),
+            hint: error.hint(),
+            error: Box::new(error),
+        }
+    }
+
+    /// The original error
+    pub fn inner(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        self.error.as_ref()
+    }
+}
+
+impl fmt::Display for DomainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl std::error::Error for DomainError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
 }
 
 #[derive(Error, Debug)]
@@ -127,6 +208,12 @@ pub enum FennecError {
     #[error("Session '{session_id}' is already active.")]
     SessionAlreadyActive { session_id: String },
 
+    #[error("Session '{session_id}' has already ended.")]
+    SessionAlreadyEnded { session_id: String },
+
+    #[error("Message '{message_id}' not found in the transcript.")]
+    MessageNotFound { message_id: String },
+
     // Workspace errors
     #[error("Workspace not found at '{path}'.")]
     WorkspaceNotFound { path: String },
@@ -179,7 +266,72 @@ pub enum FennecError {
     },
 }
 
+impl FennecError {
+    /// The error of type `T`, e.g. a `ProviderError`, carried by a domain
+    /// variant
+    pub fn domain_source<T: std::error::Error + 'static>(&self) -> Option<&T> {
+        let source = self.domain_error()?;
+        match source.downcast_ref::<DomainError>() {
+            Some(domain) => domain.inner().downcast_ref::<T>(),
+            None => source.downcast_ref::<T>(),
+        }
+    }
+
+    fn domain_error(&self) -> Option<&(dyn std::error::Error + Send + Sync + 'static)> {
+        match self {
+            FennecError::Provider(e)
+            | FennecError::Command(e)
+            | FennecError::Security(e)
+            | FennecError::Memory(e)
+            | FennecError::Tui(e)
+            | FennecError::Orchestration(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// The code, user message and hint of the error a domain variant
+    /// carries, if it has them
+    fn domain_info(&self) -> Option<&DomainError> {
+        self.domain_error()?.downcast_ref::<DomainError>()
+    }
+}
+
 impl ErrorInfo for FennecError {
+    fn code(&self) -> &'static str {
+        if let Some(domain) = self.domain_info() {
+            return domain.code;
+        }
+        match self {
+            FennecError::ConfigNotFound { .. } => "FEN-1001",
+            FennecError::ConfigInvalid { .. } => "FEN-1002",
+            FennecError::ConfigLoadFailed { .. } => "FEN-1003",
+            FennecError::FileRead { .. } => "FEN-1101",
+            FennecError::FileWrite { .. } => "FEN-1102",
+            FennecError::FileNotFound { .. } => "FEN-1103",
+            FennecError::PermissionDenied { .. } => "FEN-1104",
+            FennecError::SessionNotFound { .. } => "FEN-1201",
+            FennecError::SessionLimitExceeded { .. } => "FEN-1202",
+            FennecError::SessionAlreadyActive { .. } => "FEN-1203",
+            FennecError::SessionAlreadyEnded { .. } => "FEN-1204",
+            FennecError::MessageNotFound { .. } => "FEN-1205",
+            FennecError::WorkspaceNotFound { .. } => "FEN-1301",
+            FennecError::InvalidWorkspace { .. } => "FEN-1302",
+            FennecError::ServiceUnavailable { .. } => "FEN-1401",
+            FennecError::ServiceInitFailed { .. } => "FEN-1402",
+            FennecError::Io(_) => "FEN-1501",
+            FennecError::Serialization(_) => "FEN-1502",
+            FennecError::TomlParsing(_) => "FEN-1503",
+            // Domain errors without codes of their own
+            FennecError::Provider(_) => "FEN-2000",
+            FennecError::Command(_) => "FEN-3000",
+            FennecError::Security(_) => "FEN-4000",
+            FennecError::Memory(_) => "FEN-5000",
+            FennecError::Tui(_) => "FEN-6000",
+            FennecError::Orchestration(_) => "FEN-7000",
+            FennecError::Unknown { .. } => "FEN-9999",
+        }
+    }
+
     fn category(&self) -> ErrorCategory {
         match self {
             // Configuration errors are typically user errors
@@ -195,6 +347,8 @@ impl ErrorInfo for FennecError {
             // Session and workspace errors are typically user
             FennecError::SessionNotFound { .. }
             | FennecError::SessionAlreadyActive { .. }
+            | FennecError::SessionAlreadyEnded { .. }
+            | FennecError::MessageNotFound { .. }
             | FennecError::WorkspaceNotFound { .. }
             | FennecError::InvalidWorkspace { .. } => ErrorCategory::User,
 
@@ -235,9 +389,10 @@ impl ErrorInfo for FennecError {
             FennecError::FileRead { .. } | FennecError::FileWrite { .. } => ErrorSeverity::Error,
 
             // Session management issues
-            FennecError::SessionNotFound { .. } | FennecError::SessionAlreadyActive { .. } => {
-                ErrorSeverity::Error
-            }
+            FennecError::SessionNotFound { .. }
+            | FennecError::SessionAlreadyActive { .. }
+            | FennecError::SessionAlreadyEnded { .. }
+            | FennecError::MessageNotFound { .. } => ErrorSeverity::Error,
             FennecError::SessionLimitExceeded { .. } => ErrorSeverity::Critical,
 
             // Workspace issues
@@ -366,15 +521,53 @@ impl ErrorInfo for FennecError {
     }
 
     fn user_message(&self) -> String {
+        if let Some(domain) = self.domain_info() {
+            return domain.user_message.clone();
+        }
         match self {
             FennecError::ConfigNotFound { .. } => "Configuration file not found. Please create a configuration file or run 'fennec init'.".to_string(),
             FennecError::ConfigInvalid { issue, .. } => format!("Configuration is invalid: {}. Please check your settings.", issue),
+            FennecError::ConfigLoadFailed { .. } => "The configuration could not be loaded. Please check it for mistakes.".to_string(),
+            FennecError::FileRead { .. } => "A file could not be read.".to_string(),
+            FennecError::FileWrite { .. } => "A file could not be saved.".to_string(),
             FennecError::FileNotFound { .. } => "The requested file could not be found. Please check the file path.".to_string(),
             FennecError::PermissionDenied { .. } => "Permission denied. Please check file permissions or run with appropriate privileges.".to_string(),
+            FennecError::SessionNotFound { .. } => "That session is no longer open.".to_string(),
             FennecError::SessionLimitExceeded { .. } => "Too many active sessions. Please close some sessions before creating new ones.".to_string(),
+            FennecError::SessionAlreadyActive { .. } => "That session is already active.".to_string(),
+            FennecError::SessionAlreadyEnded { .. } => "That session has already ended. Start a new session to continue.".to_string(),
+            FennecError::MessageNotFound { .. } => "That message is no longer in the conversation.".to_string(),
             FennecError::WorkspaceNotFound { .. } => "Workspace directory not found. Please create the workspace or update your configuration.".to_string(),
+            FennecError::InvalidWorkspace { .. } => "The workspace can't be used. Please choose another directory.".to_string(),
             FennecError::ServiceUnavailable { service, .. } => format!("{} service is currently unavailable. Please try again later.", service),
-            _ => "An error occurred while processing your request.".to_string(),
+            FennecError::ServiceInitFailed { service, .. } => format!("{} service could not be started.", service),
+            FennecError::Io(_) => "A file system operation failed.".to_string(),
+            FennecError::Serialization(_) => "Data was not in the expected format.".to_string(),
+            FennecError::TomlParsing(_) => "A TOML file could not be parsed. Please check its syntax.".to_string(),
+            FennecError::Provider(_) => "The request to the AI provider failed.".to_string(),
+            FennecError::Command(_) => "The command failed.".to_string(),
+            FennecError::Security(_) => "The operation was blocked by the security policy.".to_string(),
+            FennecError::Memory(_) => "Saved memory could not be accessed.".to_string(),
+            FennecError::Tui(_) => "The terminal interface ran into a problem.".to_string(),
+            FennecError::Orchestration(_) => "The request could not be completed.".to_string(),
+            FennecError::Unknown { .. } => "An unexpected error occurred. Please try again.".to_string(),
+        }
+    }
+
+    fn hint(&self) -> Option<String> {
+        if let Some(domain) = self.domain_info() {
+            return domain.hint.clone();
+        }
+        match self {
+            FennecError::ConfigInvalid { suggestion, .. } => Some(suggestion.clone()),
+            FennecError::ConfigLoadFailed { path, .. } => {
+                Some(format!("Fix the value in {}", path))
+            }
+            FennecError::SessionAlreadyEnded { .. } => Some("Start a new session".to_string()),
+            _ => self
+                .recovery_actions()
+                .iter()
+                .find_map(|action| action.suggestion().map(str::to_string)),
         }
     }
 
@@ -897,4 +1090,137 @@ mod tests {
         );
         assert_ne!(RecoveryAction::Retry, RecoveryAction::None);
     }
+
+    fn every_variant() -> Vec<FennecError> {
+        let io = || std::io::Error::other("disk on fire");
+        let boxed = || -> Box<dyn std::error::Error + Send + Sync> { Box::new(io()) };
+        vec![
+            FennecError::ConfigNotFound {
+                path: "/config".to_string(),
+            },
+            FennecError::ConfigInvalid {
+                issue: "bad".to_string(),
+                suggestion: "fix it".to_string(),
+            },
+            FennecError::ConfigLoadFailed {
+                path: "/config".to_string(),
+                source: boxed(),
+            },
+            FennecError::FileRead {
+                path: "/file".to_string(),
+                source: io(),
+            },
+            FennecError::FileWrite {
+                path: "/file".to_string(),
+                source: io(),
+            },
+            FennecError::FileNotFound {
+                path: "/file".to_string(),
+            },
+            FennecError::PermissionDenied {
+                path: "/file".to_string(),
+            },
+            FennecError::SessionNotFound {
+                session_id: "s".to_string(),
+            },
+            FennecError::SessionLimitExceeded { current: 5, max: 3 },
+            FennecError::SessionAlreadyActive {
+                session_id: "s".to_string(),
+            },
+            FennecError::SessionAlreadyEnded {
+                session_id: "s".to_string(),
+            },
+            FennecError::MessageNotFound {
+                message_id: "m".to_string(),
+            },
+            FennecError::WorkspaceNotFound {
+                path: "/ws".to_string(),
+            },
+            FennecError::InvalidWorkspace {
+                reason: "r".to_string(),
+            },
+            FennecError::ServiceUnavailable {
+                service: "memory".to_string(),
+                reason: "r".to_string(),
+            },
+            FennecError::ServiceInitFailed {
+                service: "memory".to_string(),
+                reason: "r".to_string(),
+            },
+            FennecError::Io(io()),
+            FennecError::Serialization(serde_json::from_str::<u32>("x").unwrap_err()),
+            FennecError::TomlParsing(toml::from_str::<toml::Value>("= 1").unwrap_err()),
+            FennecError::Provider(boxed()),
+            FennecError::Command(boxed()),
+            FennecError::Security(boxed()),
+            FennecError::Memory(boxed()),
+            FennecError::Tui(boxed()),
+            FennecError::Orchestration(boxed()),
+            FennecError::Unknown {
+                message: "m".to_string(),
+                source: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_every_variant_has_a_code_and_user_message() {
+        let errors = every_variant();
+        let mut codes = std::collections::HashSet::new();
+        for err in &errors {
+            let code = err.code();
+            assert!(code.starts_with("FEN-") && code.len() == 8, "{}", code);
+            assert!(codes.insert(code), "{} is used twice", code);
+            assert!(!err.user_message().is_empty(), "{}", code);
+            assert!(err.developer_message().starts_with(&format!("[{}]", code)));
+        }
+    }
+
+    #[test]
+    fn test_user_messages_leave_out_details() {
+        let err = FennecError::FileRead {
+            path: "/home/me/secret.txt".to_string(),
+            source: std::io::Error::other("disk on fire"),
+        };
+        assert!(!err.user_message().contains("secret"));
+        let details = err.developer_message();
+        assert!(details.contains("/home/me/secret.txt"));
+        assert!(details.contains("caused by: disk on fire"));
+    }
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("quota used up")]
+    struct QuotaError;
+
+    impl ErrorInfo for QuotaError {
+        fn code(&self) -> &'static str {
+            "FEN-2099"
+        }
+
+        fn category(&self) -> ErrorCategory {
+            ErrorCategory::Network
+        }
+
+        fn severity(&self) -> ErrorSeverity {
+            ErrorSeverity::Error
+        }
+
+        fn recovery_actions(&self) -> Vec<RecoveryAction> {
+            vec![RecoveryAction::ManualAction("Raise the quota".to_string())]
+        }
+
+        fn user_message(&self) -> String {
+            "The quota is used up.".to_string()
+        }
+    }
+
+    #[test]
+    fn test_domain_errors_keep_their_code_and_messages() {
+        let err = FennecError::Provider(Box::new(DomainError::new(QuotaError)));
+        assert_eq!(err.code(), "FEN-2099");
+        assert_eq!(err.user_message(), "The quota is used up.");
+        assert_eq!(err.hint().as_deref(), Some("Raise the quota"));
+        assert_eq!(err.to_string(), "Provider error: quota used up");
+        assert!(err.domain_source::<QuotaError>().is_some());
+    }
 }
@@ -1,5 +1,6 @@
 pub mod command;
 pub mod config;
+pub mod config_layers;
 pub mod error;
 pub mod provider;
 pub mod session;
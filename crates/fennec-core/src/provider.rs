@@ -1,3 +1,4 @@
+use crate::transcript::{Message, MessageRole};
 use crate::Result;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -8,6 +9,42 @@ pub struct ProviderRequest {
     pub messages: Vec<ProviderMessage>,
     pub model: String,
     pub stream: bool,
+    /// Sampling temperature; `None` leaves it to the provider default
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    /// Most tokens the reply may take; `None` leaves it to the provider
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+}
+
+/// Kind of work a provider request serves, used to pick the provider and
+/// model handling it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskKind {
+    Summarize,
+    Plan,
+    CodeEdit,
+    Chat,
+    Embedding,
+}
+
+impl TaskKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskKind::Summarize => "summarize",
+            TaskKind::Plan => "plan",
+            TaskKind::CodeEdit => "code_edit",
+            TaskKind::Chat => "chat",
+            TaskKind::Embedding => "embedding",
+        }
+    }
+}
+
+impl std::fmt::Display for TaskKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,11 +53,53 @@ pub struct ProviderMessage {
     pub content: String,
 }
 
+impl ProviderMessage {
+    /// Provider form of a transcript message, with its attachments
+    /// described in the content or left out according to `attachments`
+    pub fn from_transcript(message: &Message, attachments: AttachmentSupport) -> Self {
+        let role = match message.role {
+            MessageRole::User => "user",
+            MessageRole::Assistant => "assistant",
+            MessageRole::System => "system",
+        };
+        let mut content = message.content.clone();
+        if attachments == AttachmentSupport::Inline && !message.attachments.is_empty() {
+            content.push_str("\n\nAttached files:");
+            for attachment in &message.attachments {
+                content.push_str(&format!(
+                    "\n- {} ({}, sha256 {})",
+                    attachment.path.display(),
+                    attachment.mime,
+                    attachment.hash
+                ));
+            }
+        }
+
+        Self {
+            role: role.to_string(),
+            content,
+        }
+    }
+}
+
+/// What a provider does with the files attached to messages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AttachmentSupport {
+    /// Attachments are listed in the message content
+    #[default]
+    Inline,
+    /// Attachments are left out
+    Unsupported,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProviderResponse {
     pub id: Uuid,
     pub content: String,
     pub usage: Option<Usage>,
+    /// Model that produced the reply, when known
+    #[serde(default)]
+    pub model: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +109,29 @@ pub struct Usage {
     pub total_tokens: u32,
 }
 
+/// A function the model may call, described by a JSON schema for its
+/// arguments
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: String,
+    pub parameters: serde_json::Value,
+}
+
+impl ToolDefinition {
+    pub fn new(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        parameters: serde_json::Value,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            parameters,
+        }
+    }
+}
+
 #[async_trait::async_trait]
 pub trait ProviderClient: Send + Sync {
     async fn complete(&self, request: ProviderRequest) -> Result<ProviderResponse>;
@@ -37,4 +139,48 @@ pub trait ProviderClient: Send + Sync {
         &self,
         request: ProviderRequest,
     ) -> Result<Box<dyn futures::Stream<Item = Result<String>> + Unpin + Send>>;
+
+    /// How attachments of transcript messages should be sent
+    fn attachment_support(&self) -> AttachmentSupport {
+        AttachmentSupport::Inline
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::{Attachment, Transcript};
+    use std::path::PathBuf;
+
+    fn message_with_attachment() -> Message {
+        let mut transcript = Transcript::new(Uuid::new_v4());
+        transcript.add_message(MessageRole::User, "Review this".to_string());
+        let mut message = transcript.messages.remove(0);
+        message.attachments.push(Attachment {
+            path: PathBuf::from("src/main.rs"),
+            hash: "abc123".to_string(),
+            mime: "text/x-rust".to_string(),
+        });
+        message
+    }
+
+    #[test]
+    fn test_attachments_are_rendered_inline() {
+        let message =
+            ProviderMessage::from_transcript(&message_with_attachment(), AttachmentSupport::Inline);
+        assert_eq!(message.role, "user");
+        assert_eq!(
+            message.content,
+            "Review this\n\nAttached files:\n- src/main.rs (text/x-rust, sha256 abc123)"
+        );
+    }
+
+    #[test]
+    fn test_attachments_are_dropped_when_unsupported() {
+        let message = ProviderMessage::from_transcript(
+            &message_with_attachment(),
+            AttachmentSupport::Unsupported,
+        );
+        assert_eq!(message.content, "Review this");
+    }
 }
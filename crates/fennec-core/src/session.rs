@@ -1,12 +1,42 @@
+use crate::{FennecError, Result};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use uuid::Uuid;
 
+/// Where a session is in its life
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionState {
+    #[default]
+    Active,
+    /// Nothing has happened in the session for a while
+    Idle,
+    Ended,
+}
+
+impl std::fmt::Display for SessionState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionState::Active => write!(f, "active"),
+            SessionState::Idle => write!(f, "idle"),
+            SessionState::Ended => write!(f, "ended"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Session {
     pub id: Uuid,
     pub created_at: chrono::DateTime<chrono::Utc>,
-    pub updated_at: chrono::DateTime<chrono::Utc>,
+    /// Last message, command or input in the session
+    #[serde(alias = "updated_at", default = "chrono::Utc::now")]
+    pub last_active_at: chrono::DateTime<chrono::Utc>,
     pub title: Option<String>,
+    /// Workspace the session was started in
+    #[serde(default)]
+    pub workspace_path: Option<PathBuf>,
+    #[serde(default)]
+    pub state: SessionState,
 }
 
 impl Session {
@@ -15,9 +45,55 @@ impl Session {
         Self {
             id: Uuid::new_v4(),
             created_at: now,
-            updated_at: now,
+            last_active_at: now,
             title: None,
+            workspace_path: None,
+            state: SessionState::Active,
+        }
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn with_workspace(mut self, path: impl Into<PathBuf>) -> Self {
+        self.workspace_path = Some(path.into());
+        self
+    }
+
+    pub fn is_ended(&self) -> bool {
+        self.state == SessionState::Ended
+    }
+
+    /// Note activity in the session, making it active again if it was idle
+    pub fn touch(&mut self) -> Result<()> {
+        self.ensure_open()?;
+        self.last_active_at = chrono::Utc::now();
+        self.state = SessionState::Active;
+        Ok(())
+    }
+
+    /// Mark the session idle until the next activity
+    pub fn mark_idle(&mut self) -> Result<()> {
+        self.ensure_open()?;
+        self.state = SessionState::Idle;
+        Ok(())
+    }
+
+    pub fn end(&mut self) -> Result<()> {
+        self.ensure_open()?;
+        self.state = SessionState::Ended;
+        Ok(())
+    }
+
+    fn ensure_open(&self) -> Result<()> {
+        if self.is_ended() {
+            return Err(FennecError::SessionAlreadyEnded {
+                session_id: self.id.to_string(),
+            });
         }
+        Ok(())
     }
 }
 
@@ -36,7 +112,7 @@ mod tests {
         let session = Session::new();
         assert!(!session.id.is_nil());
         assert!(session.title.is_none());
-        assert_eq!(session.created_at, session.updated_at);
+        assert_eq!(session.created_at, session.last_active_at);
     }
 
     #[test]
@@ -59,7 +135,7 @@ mod tests {
         let cloned = session.clone();
         assert_eq!(session.id, cloned.id);
         assert_eq!(session.created_at, cloned.created_at);
-        assert_eq!(session.updated_at, cloned.updated_at);
+        assert_eq!(session.last_active_at, cloned.last_active_at);
         assert_eq!(session.title, cloned.title);
     }
 
@@ -76,7 +152,7 @@ mod tests {
         let serialized = serde_json::to_string(&session).unwrap();
         assert!(serialized.contains("id"));
         assert!(serialized.contains("created_at"));
-        assert!(serialized.contains("updated_at"));
+        assert!(serialized.contains("last_active_at"));
     }
 
     #[test]
@@ -86,7 +162,7 @@ mod tests {
         let deserialized: Session = serde_json::from_str(&serialized).unwrap();
         assert_eq!(session.id, deserialized.id);
         assert_eq!(session.created_at, deserialized.created_at);
-        assert_eq!(session.updated_at, deserialized.updated_at);
+        assert_eq!(session.last_active_at, deserialized.last_active_at);
     }
 
     #[test]
@@ -96,4 +172,51 @@ mod tests {
         assert!(debug.contains("Session"));
         assert!(debug.contains("id"));
     }
+
+    #[test]
+    fn test_idle_session_becomes_active_on_activity() {
+        let mut session = Session::new();
+        let created = session.last_active_at;
+
+        session.mark_idle().unwrap();
+        assert_eq!(session.state, SessionState::Idle);
+
+        session.touch().unwrap();
+        assert_eq!(session.state, SessionState::Active);
+        assert!(session.last_active_at >= created);
+    }
+
+    #[test]
+    fn test_ended_session_cannot_change_state() {
+        let mut session = Session::new();
+        session.mark_idle().unwrap();
+        session.end().unwrap();
+        assert!(session.is_ended());
+
+        assert!(matches!(
+            session.end(),
+            Err(FennecError::SessionAlreadyEnded { .. })
+        ));
+        assert!(session.touch().is_err());
+        assert!(session.mark_idle().is_err());
+        assert_eq!(session.state, SessionState::Ended);
+    }
+
+    #[test]
+    fn test_sessions_saved_without_metadata_still_load() {
+        let saved = r#"{
+            "id": "6f1c1d0e-8a4b-4c1e-9d55-1b2f7d3c9a10",
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-01T01:00:00Z",
+            "title": null
+        }"#;
+
+        let session: Session = serde_json::from_str(saved).unwrap();
+        assert_eq!(
+            session.last_active_at.to_rfc3339(),
+            "2024-01-01T01:00:00+00:00"
+        );
+        assert_eq!(session.workspace_path, None);
+        assert_eq!(session.state, SessionState::Active);
+    }
 }
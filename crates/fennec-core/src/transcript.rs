@@ -1,12 +1,88 @@
+use crate::{FennecError, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
 use uuid::Uuid;
 
+/// Metadata key of a summary standing in for compacted messages; its value
+/// is how many messages the summary replaced
+pub const COMPACTED_MESSAGES_KEY: &str = "compacted_messages";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
     pub id: Uuid,
     pub role: MessageRole,
     pub content: String,
     pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Earlier versions of the content, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub edits: Vec<MessageEdit>,
+    /// When the message was deleted; deleted messages stay in place with
+    /// their content cleared so indexes holding their id can drop it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Files the message refers to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+    /// Free-form data about the message, e.g. the result of a tool call
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub metadata: HashMap<String, serde_json::Value>,
+    /// Tokens the content takes up, when counted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_count: Option<usize>,
+}
+
+impl Message {
+    fn new(role: MessageRole, content: String) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            role,
+            content,
+            timestamp: chrono::Utc::now(),
+            edits: Vec::new(),
+            deleted_at: None,
+            attachments: Vec::new(),
+            metadata: HashMap::new(),
+            token_count: None,
+        }
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    /// How many messages this summary replaced, if it is the summary of a
+    /// compaction
+    pub fn compacted_messages(&self) -> Option<usize> {
+        self.metadata
+            .get(COMPACTED_MESSAGES_KEY)
+            .and_then(|count| count.as_u64())
+            .map(|count| count as usize)
+    }
+}
+
+/// File attached to a message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+    pub path: PathBuf,
+    /// SHA-256 of the file contents when attached, hex encoded
+    pub hash: String,
+    pub mime: String,
+}
+
+/// Content a message had before it was edited
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MessageEdit {
+    pub content: String,
+    /// When this content was replaced
+    pub edited_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Transcript and message a branch was created from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscriptParent {
+    pub session_id: Uuid,
+    pub message_id: Uuid,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +96,9 @@ pub enum MessageRole {
 pub struct Transcript {
     pub messages: Vec<Message>,
     pub session_id: Uuid,
+    /// Where this transcript was branched from, if it is a branch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<TranscriptParent>,
 }
 
 impl Transcript {
@@ -27,17 +106,95 @@ impl Transcript {
         Self {
             messages: Vec::new(),
             session_id,
+            parent: None,
         }
     }
 
     pub fn add_message(&mut self, role: MessageRole, content: String) {
-        let message = Message {
-            id: Uuid::new_v4(),
-            role,
-            content,
-            timestamp: chrono::Utc::now(),
-        };
-        self.messages.push(message);
+        self.messages.push(Message::new(role, content));
+    }
+
+    /// Messages that have not been deleted
+    pub fn active_messages(&self) -> impl Iterator<Item = &Message> {
+        self.messages.iter().filter(|m| !m.is_deleted())
+    }
+
+    /// Replace the content of message `id`, keeping the old content in its
+    /// edit history
+    pub fn edit_message(&mut self, id: Uuid, new_content: String) -> Result<()> {
+        let message = self.active_message_mut(id)?;
+        let previous = std::mem::replace(&mut message.content, new_content);
+        message.edits.push(MessageEdit {
+            content: previous,
+            edited_at: chrono::Utc::now(),
+        });
+        Ok(())
+    }
+
+    /// Delete message `id`, leaving a tombstone with only its id, role and
+    /// timestamp
+    pub fn delete_message(&mut self, id: Uuid) -> Result<()> {
+        let message = self.active_message_mut(id)?;
+        message.content.clear();
+        message.edits.clear();
+        message.attachments.clear();
+        message.metadata.clear();
+        message.token_count = None;
+        message.deleted_at = Some(chrono::Utc::now());
+        Ok(())
+    }
+
+    /// New transcript, with a session of its own, holding the history up to
+    /// and including `message_id`
+    pub fn branch_from(&self, message_id: Uuid) -> Result<Transcript> {
+        let end = self
+            .messages
+            .iter()
+            .position(|m| m.id == message_id)
+            .ok_or_else(|| FennecError::MessageNotFound {
+                message_id: message_id.to_string(),
+            })?;
+
+        Ok(Transcript {
+            messages: self.messages[..=end].to_vec(),
+            session_id: Uuid::new_v4(),
+            parent: Some(TranscriptParent {
+                session_id: self.session_id,
+                message_id,
+            }),
+        })
+    }
+
+    /// Replace the messages `ids` with a system message holding `summary`,
+    /// placed where the first of them was, and return the summary's id
+    pub fn compact(&mut self, ids: &[Uuid], summary: String) -> Result<Uuid> {
+        let start = self
+            .messages
+            .iter()
+            .position(|m| ids.contains(&m.id))
+            .ok_or_else(|| FennecError::MessageNotFound {
+                message_id: ids.first().map(Uuid::to_string).unwrap_or_default(),
+            })?;
+        let before = self.messages.len();
+        self.messages.retain(|m| !ids.contains(&m.id));
+
+        let mut message = Message::new(MessageRole::System, summary);
+        message.metadata.insert(
+            COMPACTED_MESSAGES_KEY.to_string(),
+            serde_json::json!(before - self.messages.len()),
+        );
+        let id = message.id;
+        self.messages.insert(start, message);
+        Ok(id)
+    }
+
+    fn active_message_mut(&mut self, id: Uuid) -> Result<&mut Message> {
+        self.messages
+            .iter_mut()
+            .find(|m| m.id == id && !m.is_deleted())
+            .ok_or_else(|| FennecError::MessageNotFound {
+                message_id: id.to_string(),
+            })
     }
 }
 
@@ -139,6 +296,11 @@ mod tests {
             role: MessageRole::User,
             content: "test".to_string(),
             timestamp: chrono::Utc::now(),
+            edits: Vec::new(),
+            deleted_at: None,
+            attachments: Vec::new(),
+            metadata: HashMap::new(),
+            token_count: None,
         };
 
         let cloned = message.clone();
@@ -210,6 +372,11 @@ mod tests {
             role: MessageRole::User,
             content: "test".to_string(),
             timestamp: chrono::Utc::now(),
+            edits: Vec::new(),
+            deleted_at: None,
+            attachments: Vec::new(),
+            metadata: HashMap::new(),
+            token_count: None,
         };
 
         let debug = format!("{:?}", message);
@@ -242,4 +409,157 @@ mod tests {
         transcript.add_message(MessageRole::User, long_content.clone());
         assert_eq!(transcript.messages[0].content, long_content);
     }
+
+    #[test]
+    fn test_edit_message_keeps_history() {
+        let mut transcript = Transcript::new(Uuid::new_v4());
+        transcript.add_message(MessageRole::User, "first".to_string());
+        let id = transcript.messages[0].id;
+
+        transcript.edit_message(id, "second".to_string()).unwrap();
+        transcript.edit_message(id, "third".to_string()).unwrap();
+
+        let message = &transcript.messages[0];
+        assert_eq!(message.content, "third");
+        let history: Vec<_> = message.edits.iter().map(|e| e.content.as_str()).collect();
+        assert_eq!(history, ["first", "second"]);
+        assert!(matches!(
+            transcript.edit_message(Uuid::new_v4(), "x".to_string()),
+            Err(FennecError::MessageNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_delete_message_leaves_tombstone() {
+        let mut transcript = Transcript::new(Uuid::new_v4());
+        transcript.add_message(MessageRole::User, "secret".to_string());
+        transcript.add_message(MessageRole::Assistant, "reply".to_string());
+        let id = transcript.messages[0].id;
+        transcript
+            .edit_message(id, "still secret".to_string())
+            .unwrap();
+
+        transcript.delete_message(id).unwrap();
+
+        assert_eq!(transcript.messages.len(), 2);
+        let tombstone = &transcript.messages[0];
+        assert!(tombstone.is_deleted());
+        assert!(tombstone.content.is_empty() && tombstone.edits.is_empty());
+        assert_eq!(transcript.active_messages().count(), 1);
+        assert!(transcript.edit_message(id, "again".to_string()).is_err());
+        assert!(transcript.delete_message(id).is_err());
+    }
+
+    #[test]
+    fn test_branch_from_shares_history_up_to_message() {
+        let mut transcript = Transcript::new(Uuid::new_v4());
+        transcript.add_message(MessageRole::User, "one".to_string());
+        transcript.add_message(MessageRole::Assistant, "two".to_string());
+        transcript.add_message(MessageRole::User, "three".to_string());
+        let branch_point = transcript.messages[1].id;
+
+        let mut branch = transcript.branch_from(branch_point).unwrap();
+        branch.add_message(MessageRole::User, "three, differently".to_string());
+
+        assert_ne!(branch.session_id, transcript.session_id);
+        assert_eq!(
+            branch.parent,
+            Some(TranscriptParent {
+                session_id: transcript.session_id,
+                message_id: branch_point,
+            })
+        );
+        let contents: Vec<_> = branch.messages.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, ["one", "two", "three, differently"]);
+        assert_eq!(transcript.messages.len(), 3);
+
+        let nested = branch.branch_from(branch.messages[0].id).unwrap();
+        assert_eq!(nested.parent.unwrap().session_id, branch.session_id);
+    }
+
+    #[test]
+    fn test_transcript_without_new_fields_deserializes() {
+        let session_id = Uuid::new_v4();
+        let json = format!(
+            r#"{{"messages":[{{"id":"{}","role":"User","content":"hi","timestamp":"2024-01-01T00:00:00Z"}}],"session_id":"{}"}}"#,
+            Uuid::new_v4(),
+            session_id
+        );
+
+        let transcript: Transcript = serde_json::from_str(&json).unwrap();
+        assert_eq!(transcript.session_id, session_id);
+        assert!(transcript.parent.is_none());
+        assert!(transcript.messages[0].edits.is_empty());
+        assert!(!transcript.messages[0].is_deleted());
+
+        let serialized = serde_json::to_string(&transcript).unwrap();
+        assert!(!serialized.contains("edits") && !serialized.contains("parent"));
+    }
+
+    #[test]
+    fn test_compact_replaces_messages_with_a_summary() {
+        let mut transcript = Transcript::new(Uuid::new_v4());
+        for i in 0..4 {
+            transcript.add_message(MessageRole::User, format!("message {}", i));
+        }
+        let ids: Vec<Uuid> = transcript.messages.iter().map(|m| m.id).collect();
+
+        let summary_id = transcript
+            .compact(&ids[..3], "The first three".to_string())
+            .unwrap();
+
+        assert_eq!(transcript.messages.len(), 2);
+        let summary = &transcript.messages[0];
+        assert_eq!(summary.id, summary_id);
+        assert!(matches!(summary.role, MessageRole::System));
+        assert_eq!(summary.content, "The first three");
+        assert_eq!(summary.compacted_messages(), Some(3));
+        assert_eq!(transcript.messages[1].id, ids[3]);
+        assert_eq!(transcript.messages[1].compacted_messages(), None);
+
+        assert!(transcript
+            .compact(&[Uuid::new_v4()], "Nothing".to_string())
+            .is_err());
+    }
+
+    #[test]
+    fn test_old_transcript_fixture_loads() {
+        let transcript: Transcript =
+            serde_json::from_str(include_str!("../tests/fixtures/transcript_old.json")).unwrap();
+
+        assert_eq!(transcript.messages.len(), 2);
+        let message = &transcript.messages[1];
+        assert_eq!(message.content, "The lockfile is out of date.");
+        assert!(message.attachments.is_empty());
+        assert!(message.metadata.is_empty());
+        assert_eq!(message.token_count, None);
+    }
+
+    #[test]
+    fn test_transcript_fixture_with_attachments_round_trips() {
+        let transcript: Transcript = serde_json::from_str(include_str!(
+            "../tests/fixtures/transcript_attachments.json"
+        ))
+        .unwrap();
+
+        let request = &transcript.messages[0];
+        assert_eq!(
+            request.attachments,
+            [Attachment {
+                path: PathBuf::from("src/lib.rs"),
+                hash: "3a7bd3e2360a3d29eea436fcfb7e44c735d117c42d1c1835420b6b9942dd4f1b"
+                    .to_string(),
+                mime: "text/x-rust".to_string(),
+            }]
+        );
+        assert_eq!(request.token_count, Some(4));
+        let reply = &transcript.messages[1];
+        assert_eq!(reply.metadata["tool_call"]["exit_code"], 0);
+
+        let reloaded: Transcript =
+            serde_json::from_str(&serde_json::to_string(&transcript).unwrap()).unwrap();
+        assert_eq!(reloaded.messages[0].attachments, request.attachments);
+        assert_eq!(reloaded.messages[1].metadata, reply.metadata);
+        assert_eq!(reloaded.messages[1].token_count, Some(6));
+    }
 }
@@ -30,6 +30,10 @@ pub struct AgentSection {
     pub content: String,
     /// Subsections if any
     pub subsections: Vec<AgentSection>,
+    /// Marked `priority: always` in the front-matter under its heading, so
+    /// it is injected whatever the query
+    #[serde(default)]
+    pub always: bool,
 }
 
 /// Service for loading and managing AGENTS.md files
@@ -111,19 +115,7 @@ impl AgentsService {
 
     /// Get ordered list of configuration file paths to check
     fn get_config_paths(&self) -> Vec<PathBuf> {
-        let mut paths = Vec::new();
-
-        // First priority: repo root ./AGENTS.md
-        if let Ok(current_dir) = std::env::current_dir() {
-            paths.push(current_dir.join("AGENTS.md"));
-        }
-
-        // Second priority: global ~/.fennec/AGENTS.md
-        if let Some(proj_dirs) = ProjectDirs::from("", "", "fennec") {
-            paths.push(proj_dirs.config_dir().join("AGENTS.md"));
-        }
-
-        paths
+        agents_file_paths()
     }
 
     /// Load configuration from a specific path
@@ -156,11 +148,14 @@ impl AgentsService {
         let mut sections = HashMap::new();
         let mut current_section: Option<AgentSection> = None;
         let mut current_content = Vec::new();
+        // Front-matter may open on the first non-blank line under a heading
+        let mut front_matter = FrontMatter::Allowed;
 
         for line in content.lines() {
             if line.starts_with("## ") {
                 // Save previous section if exists
-                if let Some(section) = current_section.take() {
+                if let Some(mut section) = current_section.take() {
+                    section.content = current_content.join("\n");
                     sections.insert(section.title.clone(), section);
                 }
 
@@ -170,8 +165,27 @@ impl AgentsService {
                     title: title.clone(),
                     content: String::new(),
                     subsections: Vec::new(),
+                    always: false,
                 });
                 current_content.clear();
+                front_matter = FrontMatter::Allowed;
+            } else if front_matter != FrontMatter::Done
+                && current_section.is_some()
+                && (front_matter == FrontMatter::Open || line.trim() == "---")
+            {
+                match front_matter {
+                    FrontMatter::Allowed => front_matter = FrontMatter::Open,
+                    _ if line.trim() == "---" => front_matter = FrontMatter::Done,
+                    _ => {
+                        if let Some((key, value)) = line.split_once(':') {
+                            if key.trim() == "priority" && value.trim() == "always" {
+                                if let Some(ref mut section) = current_section {
+                                    section.always = true;
+                                }
+                            }
+                        }
+                    }
+                }
             } else if line.starts_with("### ") && current_section.is_some() {
                 // Handle subsection
                 let subsection_title = line.trim_start_matches("### ").trim().to_string();
@@ -184,10 +198,15 @@ impl AgentsService {
                         title: subsection_title,
                         content: String::new(), // Would collect content until next heading
                         subsections: Vec::new(),
+                        always: false,
                     });
                 }
                 current_content.push(line.to_string());
+                front_matter = FrontMatter::Done;
             } else {
+                if !line.trim().is_empty() {
+                    front_matter = FrontMatter::Done;
+                }
                 current_content.push(line.to_string());
             }
         }
@@ -293,6 +312,36 @@ impl AgentsService {
         matches
     }
 
+    /// Sections marked `priority: always`, as matches with a score of zero
+    pub fn always_guidance(&self) -> Vec<GuidanceMatch> {
+        let Some(config) = self.get_config() else {
+            return Vec::new();
+        };
+
+        let mut matches: Vec<GuidanceMatch> = config
+            .sections
+            .values()
+            .filter(|section| section.always)
+            .map(|section| GuidanceMatch {
+                section_title: section.title.clone(),
+                content: section.content.clone(),
+                score: 0,
+                match_type: MatchType::Always,
+            })
+            .collect();
+        matches.sort_by(|a, b| a.section_title.cmp(&b.section_title));
+        matches
+    }
+
+    /// Load guidance from the AGENTS.md at `path` instead of the usual
+    /// locations
+    pub async fn load_path(&self, path: &Path) -> Result<()> {
+        let config = self.load_config_from_path(path).await?;
+        self.config_sender
+            .send(Some(config))
+            .map_err(|_| anyhow::anyhow!("Failed to send config update"))
+    }
+
     /// Get all available guidance sections
     pub fn get_all_guidance(&self) -> Vec<String> {
         let config = match self.get_config() {
@@ -310,6 +359,57 @@ impl AgentsService {
     }
 }
 
+/// AGENTS.md files guidance is read from, in order of priority; the first
+/// one that exists is used
+pub fn agents_file_paths() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+
+    // First priority: repo root ./AGENTS.md
+    if let Ok(current_dir) = std::env::current_dir() {
+        paths.push(current_dir.join("AGENTS.md"));
+    }
+
+    // Second priority: global ~/.fennec/AGENTS.md
+    if let Some(proj_dirs) = ProjectDirs::from("", "", "fennec") {
+        paths.push(proj_dirs.config_dir().join("AGENTS.md"));
+    }
+
+    paths
+}
+
+/// Problems in the content of an AGENTS.md file that keep guidance from
+/// being found. Guidance is looked up by `## ` section, so sections must
+/// exist, have content and have distinct titles.
+pub fn lint(content: &str) -> Vec<String> {
+    let mut issues = Vec::new();
+    let mut sections: Vec<(&str, bool)> = Vec::new();
+    for line in content.lines() {
+        if let Some(title) = line.strip_prefix("## ") {
+            sections.push((title.trim(), false));
+        } else if let Some((_, has_content)) = sections.last_mut() {
+            *has_content |= !line.trim().is_empty();
+        }
+    }
+
+    if sections.is_empty() {
+        issues.push("No `## ` sections; guidance is looked up by section".to_string());
+    }
+    for (i, (title, has_content)) in sections.iter().enumerate() {
+        if title.is_empty() {
+            issues.push(format!("Section {} has no title", i + 1));
+        } else if sections[..i].iter().any(|(earlier, _)| earlier == title) {
+            issues.push(format!(
+                "Section '{}' appears more than once; only the last is used",
+                title
+            ));
+        }
+        if !has_content {
+            issues.push(format!("Section '{}' is empty", title));
+        }
+    }
+    issues
+}
+
 /// Represents a search match for guidance
 #[derive(Debug, Clone)]
 pub struct GuidanceMatch {
@@ -324,6 +424,21 @@ pub enum MatchType {
     Title,
     Content,
     Subsection,
+    /// A section marked `priority: always`, whatever the query
+    Always,
+    /// A due reminder note rather than an AGENTS.md section
+    Reminder,
+}
+
+/// Where the parser is with the front-matter of the current section
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FrontMatter {
+    /// Nothing but blank lines since the heading, so it may still open
+    Allowed,
+    /// Between the `---` lines
+    Open,
+    /// Closed, or too late to open
+    Done,
 }
 
 #[cfg(test)]
@@ -350,6 +465,38 @@ mod tests {
         assert!(sections.contains_key("Build, Test, and Development Commands"));
     }
 
+    #[tokio::test]
+    async fn test_parse_front_matter_and_section_content() {
+        let service = AgentsService::new().await.unwrap();
+        let content = "## Safety\n---\npriority: always\n---\nNever push to main.\n\n## Style\nUse rustfmt.\n---\npriority: always\n---\n";
+
+        let sections = service.parse_markdown(content).unwrap();
+        let safety = &sections["Safety"];
+        assert!(safety.always);
+        assert_eq!(safety.content.trim(), "Never push to main.");
+        // Front-matter only counts right under the heading
+        let style = &sections["Style"];
+        assert!(!style.always);
+        assert!(style.content.contains("Use rustfmt."));
+        assert!(style.content.contains("priority: always"));
+    }
+
+    #[test]
+    fn test_lint() {
+        let content =
+            "# Guidelines\n\n## Testing\n- `cargo test`\n\n## Style\n\n## Testing\nRun clippy\n";
+        assert_eq!(
+            lint(content),
+            vec![
+                "Section 'Style' is empty".to_string(),
+                "Section 'Testing' appears more than once; only the last is used".to_string(),
+            ]
+        );
+
+        assert_eq!(lint("# Guidelines\nUse tabs\n").len(), 1);
+        assert!(lint("## Testing\n- `cargo test`\n").is_empty());
+    }
+
     #[tokio::test]
     async fn test_search_guidance() {
         let service = AgentsService::new().await.unwrap();
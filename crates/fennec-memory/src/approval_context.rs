@@ -0,0 +1,118 @@
+//! Tells approval prompts which earlier sessions talked about the paths an
+//! operation touches.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use fennec_security::ApprovalContextProvider;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::transcript::TranscriptStore;
+
+/// Approval context from the files mentioned in stored transcripts
+pub struct TranscriptMentions {
+    transcript_store: Arc<RwLock<TranscriptStore>>,
+}
+
+impl TranscriptMentions {
+    pub fn new(transcript_store: Arc<RwLock<TranscriptStore>>) -> Self {
+        Self { transcript_store }
+    }
+}
+
+#[async_trait]
+impl ApprovalContextProvider for TranscriptMentions {
+    fn name(&self) -> &str {
+        "memory"
+    }
+
+    async fn describe(&self, workspace: &Path, target: &Path) -> Result<Vec<String>> {
+        let sessions = self
+            .transcript_store
+            .read()
+            .await
+            .sessions_mentioning(workspace, target)
+            .await?;
+        let Some(latest) = sessions.first() else {
+            return Ok(Vec::new());
+        };
+
+        let title = latest
+            .title
+            .clone()
+            .unwrap_or_else(|| latest.session_id.to_string()[..8].to_string());
+        Ok(vec![format!(
+            "{}: mentioned in {} {}, most recently \"{}\" on {}",
+            target.display(),
+            sessions.len(),
+            if sessions.len() == 1 {
+                "session"
+            } else {
+                "sessions"
+            },
+            title,
+            latest.updated_at.format("%Y-%m-%d")
+        )])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fennec_core::session::Session;
+    use fennec_core::transcript::MessageRole;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_describes_sessions_mentioning_the_target() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = temp_dir.path().join("workspace");
+        std::fs::create_dir_all(workspace.join("build")).unwrap();
+        std::fs::write(workspace.join("build/app.bin"), "").unwrap();
+        std::fs::write(workspace.join("README.md"), "").unwrap();
+        let storage_dir = temp_dir.path().join("transcripts");
+        std::fs::create_dir_all(&storage_dir).unwrap();
+        let mut store = TranscriptStore::with_storage_dir(storage_dir).unwrap();
+
+        for content in [
+            "Why is build/app.bin so large?",
+            "Please update README.md",
+            "Strip the symbols from build/app.bin",
+        ] {
+            let session = Session::new().with_workspace(&workspace);
+            store.record_session(&session).await.unwrap();
+            store
+                .add_message(session.id, MessageRole::User, content.to_string())
+                .await
+                .unwrap();
+        }
+        // The same path in another workspace does not count
+        let elsewhere = temp_dir.path().join("elsewhere");
+        std::fs::create_dir_all(elsewhere.join("build")).unwrap();
+        std::fs::write(elsewhere.join("build/app.bin"), "").unwrap();
+        let session = Session::new().with_workspace(&elsewhere);
+        store.record_session(&session).await.unwrap();
+        store
+            .add_message(session.id, MessageRole::User, "build/app.bin".to_string())
+            .await
+            .unwrap();
+
+        let mentions = TranscriptMentions::new(Arc::new(RwLock::new(store)));
+        let details = mentions
+            .describe(&workspace, Path::new("build/"))
+            .await
+            .unwrap();
+        assert_eq!(details.len(), 1);
+        assert!(
+            details[0].starts_with("build/: mentioned in 2 sessions, most recently"),
+            "{}",
+            details[0]
+        );
+        assert!(mentions
+            .describe(&workspace, Path::new("src"))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -606,7 +606,12 @@ pub struct ClineMemoryFileService {
 impl ClineMemoryFileService {
     /// Create a new Cline memory file service
     pub fn new() -> Result<Self> {
-        let storage_dir = Self::get_storage_dir()?;
+        Self::with_storage_dir(Self::get_storage_dir()?)
+    }
+
+    /// Create a service keeping project memory files under `storage_dir`
+    pub fn with_storage_dir(storage_dir: impl Into<PathBuf>) -> Result<Self> {
+        let storage_dir = storage_dir.into();
 
         // Ensure storage directory exists
         std::fs::create_dir_all(&storage_dir).with_context(|| {
@@ -1475,39 +1480,7 @@ impl ClineMemoryFileService {
         let mut technologies = Vec::new();
         let content_lower = content.to_lowercase();
 
-        let tech_keywords = [
-            "rust",
-            "python",
-            "javascript",
-            "typescript",
-            "react",
-            "vue",
-            "angular",
-            "node",
-            "go",
-            "java",
-            "c++",
-            "docker",
-            "kubernetes",
-            "aws",
-            "gcp",
-            "postgres",
-            "mysql",
-            "redis",
-            "mongodb",
-            "git",
-            "github",
-            "tokio",
-            "axum",
-            "warp",
-            "actix",
-            "serde",
-            "clap",
-            "tracing",
-            "anyhow",
-        ];
-
-        for tech in tech_keywords {
+        for tech in crate::extraction::TECHNOLOGIES {
             if content_lower.contains(tech) {
                 technologies.push(tech.to_string());
             }
@@ -19,6 +19,7 @@ use uuid::Uuid;
 
 use fennec_core::transcript::Message;
 
+use crate::scope::MemoryScope;
 use crate::service::{
     AdvancedSearchCriteria, ConversationContext, MemoryService, MemoryType, ScoringStrategy,
     SessionFilter, TimeFilter, UnifiedSearchResult,
@@ -33,6 +34,8 @@ pub struct ContextEngine {
     config: ContextConfig,
     /// Context cache for performance
     context_cache: std::sync::Arc<tokio::sync::RwLock<ContextCache>>,
+    /// Pool of memory other sessions' context is drawn from
+    scope: MemoryScope,
 }
 
 /// Configuration for context injection behavior
@@ -52,6 +55,8 @@ pub struct ContextConfig {
     pub discovery_strategies: Vec<ContextDiscoveryStrategy>,
     /// Default scoring strategy
     pub default_scoring_strategy: ScoringStrategy,
+    /// Score added to items from each memory type; negative to demote
+    pub type_boosts: HashMap<MemoryType, f64>,
 }
 
 impl Default for ContextConfig {
@@ -71,6 +76,7 @@ impl Default for ContextConfig {
             default_scoring_strategy: ScoringStrategy::ContextAware {
                 conversation_context: ConversationContext::default(),
             },
+            type_boosts: HashMap::new(),
         }
     }
 }
@@ -180,6 +186,90 @@ pub struct ContextItemMetadata {
     pub content_classification: ContentClassification,
     /// Freshness score based on age
     pub freshness_score: f64,
+    /// How the relevance score was reached, kept for `ContextBundle::explain`
+    #[serde(default)]
+    pub scoring: ContextScoring,
+}
+
+/// Intermediate values recorded while an item is scored and constrained
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContextScoring {
+    /// Search query that surfaced the item; empty when it was listed
+    pub query: String,
+    /// Relevance the search gave it, before adjustments
+    pub base_relevance: f64,
+    /// Adjustments applied to the base relevance, in order
+    pub adjustments: Vec<ScoreAdjustment>,
+    /// Position after ranking, from zero
+    pub rank: usize,
+    /// Tokens taken by this item and every item ranked above it
+    pub cumulative_tokens: usize,
+    /// Token limit the bundle was cut to
+    pub token_limit: Option<usize>,
+    /// Item limit the bundle was cut to
+    pub item_limit: Option<usize>,
+}
+
+/// A boost or penalty applied to an item's relevance
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreAdjustment {
+    pub reason: String,
+    pub amount: f64,
+}
+
+/// Why an item ended up in a context bundle
+#[derive(Debug, Clone)]
+pub struct ContextExplanation {
+    pub item_id: String,
+    pub title: String,
+    pub discovery_strategy: String,
+    /// Search query that surfaced the item, if there was one
+    pub query: Option<String>,
+    pub base_relevance: f64,
+    pub adjustments: Vec<ScoreAdjustment>,
+    pub final_relevance: f64,
+    pub freshness_score: f64,
+    pub rank: usize,
+    pub cumulative_tokens: usize,
+    pub token_limit: Option<usize>,
+    pub item_limit: Option<usize>,
+    /// Within a tenth of the token limit, or the last item allowed
+    pub nearly_truncated: bool,
+}
+
+impl std::fmt::Display for ContextExplanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} ({})", self.title, self.item_id)?;
+        match &self.query {
+            Some(query) => writeln!(
+                f,
+                "Found by {} searching for \"{}\"",
+                self.discovery_strategy, query
+            )?,
+            None => writeln!(f, "Found by {}", self.discovery_strategy)?,
+        }
+        writeln!(f, "Base relevance {:.2}", self.base_relevance)?;
+        for adjustment in &self.adjustments {
+            writeln!(f, "  {:+.2} {}", adjustment.amount, adjustment.reason)?;
+        }
+        writeln!(
+            f,
+            "Final relevance {:.2}, freshness {:.2}",
+            self.final_relevance, self.freshness_score
+        )?;
+        write!(f, "Ranked #{}", self.rank + 1)?;
+        if let Some(limit) = self.item_limit {
+            write!(f, " of at most {}", limit)?;
+        }
+        match self.token_limit {
+            Some(limit) => write!(f, ", {} of {} tokens", self.cumulative_tokens, limit)?,
+            None => write!(f, ", {} tokens", self.cumulative_tokens)?,
+        }
+        if self.nearly_truncated {
+            write!(f, " (nearly truncated)")?;
+        }
+        Ok(())
+    }
 }
 
 /// Classification of context content
@@ -209,6 +299,37 @@ pub struct ContextBundle {
     pub metadata: ContextBundleMetadata,
 }
 
+impl ContextBundle {
+    /// Why the item `item_id` was included, from what was recorded while
+    /// scoring it
+    pub fn explain(&self, item_id: &str) -> Option<ContextExplanation> {
+        let item = self.items.iter().find(|item| item.id == item_id)?;
+        let scoring = &item.metadata.scoring;
+        let near_token_limit = scoring
+            .token_limit
+            .is_some_and(|limit| scoring.cumulative_tokens * 10 >= limit * 9);
+        let last_allowed = scoring
+            .item_limit
+            .is_some_and(|limit| scoring.rank + 1 >= limit);
+
+        Some(ContextExplanation {
+            item_id: item.id.clone(),
+            title: item.title.clone(),
+            discovery_strategy: item.metadata.discovery_strategy.clone(),
+            query: (!scoring.query.is_empty()).then(|| scoring.query.clone()),
+            base_relevance: scoring.base_relevance,
+            adjustments: scoring.adjustments.clone(),
+            final_relevance: item.relevance_score,
+            freshness_score: item.metadata.freshness_score,
+            rank: scoring.rank,
+            cumulative_tokens: scoring.cumulative_tokens,
+            token_limit: scoring.token_limit,
+            item_limit: scoring.item_limit,
+            nearly_truncated: near_token_limit || last_allowed,
+        })
+    }
+}
+
 /// Summary of context bundle
 #[derive(Debug, Clone)]
 pub struct ContextSummary {
@@ -331,10 +452,12 @@ impl ContextEngine {
         let config = ContextConfig::default();
         let context_cache = std::sync::Arc::new(tokio::sync::RwLock::new(ContextCache::new(100)));
 
+        let scope = memory_service.scope().clone();
         Self {
             memory_service,
             config,
             context_cache,
+            scope,
         }
     }
 
@@ -345,13 +468,22 @@ impl ContextEngine {
     ) -> Self {
         let context_cache = std::sync::Arc::new(tokio::sync::RwLock::new(ContextCache::new(100)));
 
+        let scope = memory_service.scope().clone();
         Self {
             memory_service,
             config,
             context_cache,
+            scope,
         }
     }
 
+    /// Draw context from other sessions in `scope` rather than the memory
+    /// service's own
+    pub fn with_scope(mut self, scope: MemoryScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
     /// Discover and inject relevant context
     pub async fn inject_context(&self, request: ContextRequest) -> Result<ContextBundle> {
         let start_time = std::time::Instant::now();
@@ -451,7 +583,7 @@ impl ContextEngine {
         // Search for related content based on analysis
         for query in analysis.suggested_queries {
             let search_criteria = AdvancedSearchCriteria {
-                query,
+                query: query.clone(),
                 session_filter: Some(SessionFilter::ExcludeCurrentSession(request.session_id)),
                 time_filter: Some(TimeFilter::LastDays(7)), // Focus on recent content
                 memory_types: request.preferred_types.clone(),
@@ -466,6 +598,7 @@ impl ContextEngine {
             context_items.extend(self.convert_search_results_to_context_items(
                 search_results.results,
                 "conversation_analysis",
+                &query,
             ));
         }
 
@@ -484,8 +617,8 @@ impl ContextEngine {
 
         for keyword in keywords {
             let search_criteria = AdvancedSearchCriteria {
-                query: keyword,
-                session_filter: Some(SessionFilter::CrossSession),
+                query: keyword.clone(),
+                session_filter: Some(SessionFilter::Scope(self.scope.clone())),
                 time_filter: Some(TimeFilter::LastDays(30)),
                 memory_types: vec![MemoryType::Transcripts, MemoryType::MemoryFiles],
                 scoring_strategy: ScoringStrategy::FuzzyMatch,
@@ -497,6 +630,7 @@ impl ContextEngine {
             context_items.extend(self.convert_search_results_to_context_items(
                 search_results.results,
                 "keyword_extraction",
+                &keyword,
             ));
         }
 
@@ -516,8 +650,8 @@ impl ContextEngine {
 
         for topic in topics {
             let search_criteria = AdvancedSearchCriteria {
-                query: topic,
-                session_filter: Some(SessionFilter::CrossSession),
+                query: topic.clone(),
+                session_filter: Some(SessionFilter::Scope(self.scope.clone())),
                 time_filter: Some(TimeFilter::LastDays(14)),
                 memory_types: vec![MemoryType::Guidance, MemoryType::MemoryFiles],
                 scoring_strategy: ScoringStrategy::Weighted {
@@ -530,12 +664,11 @@ impl ContextEngine {
             };
 
             let search_results = self.memory_service.search_advanced(search_criteria).await?;
-            context_items.extend(
-                self.convert_search_results_to_context_items(
-                    search_results.results,
-                    "topic_modeling",
-                ),
-            );
+            context_items.extend(self.convert_search_results_to_context_items(
+                search_results.results,
+                "topic_modeling",
+                &topic,
+            ));
         }
 
         Ok(context_items)
@@ -563,7 +696,11 @@ impl ContextEngine {
         };
 
         let search_results = self.memory_service.search_advanced(search_criteria).await?;
-        Ok(self.convert_search_results_to_context_items(search_results.results, "session_history"))
+        Ok(self.convert_search_results_to_context_items(
+            search_results.results,
+            "session_history",
+            "",
+        ))
     }
 
     /// Discover context from explicit query
@@ -576,7 +713,7 @@ impl ContextEngine {
         if let Some(ref query) = request.explicit_query {
             let search_criteria = AdvancedSearchCriteria {
                 query: query.clone(),
-                session_filter: Some(SessionFilter::CrossSession),
+                session_filter: Some(SessionFilter::Scope(self.scope.clone())),
                 time_filter: None, // No time filter for explicit queries
                 memory_types: request.preferred_types.clone(),
                 scoring_strategy: self.config.default_scoring_strategy.clone(),
@@ -585,8 +722,11 @@ impl ContextEngine {
             };
 
             let search_results = self.memory_service.search_advanced(search_criteria).await?;
-            Ok(self
-                .convert_search_results_to_context_items(search_results.results, "explicit_query"))
+            Ok(self.convert_search_results_to_context_items(
+                search_results.results,
+                "explicit_query",
+                query,
+            ))
         } else {
             Ok(Vec::new())
         }
@@ -598,6 +738,9 @@ impl ContextEngine {
         use std::hash::{Hash, Hasher};
 
         let mut hasher = DefaultHasher::new();
+        // New messages or commands make earlier bundles stale
+        self.memory_service.revision().hash(&mut hasher);
+        self.scope.hash(&mut hasher);
         request.session_id.hash(&mut hasher);
         request.use_case.hash(&mut hasher);
         request.explicit_query.hash(&mut hasher);
@@ -709,6 +852,7 @@ impl ContextEngine {
         &self,
         results: Vec<UnifiedSearchResult>,
         discovery_strategy: &str,
+        query: &str,
     ) -> Vec<ContextItem> {
         results
             .into_iter()
@@ -727,6 +871,10 @@ impl ContextEngine {
                     matching_keywords: Vec::new(), // Would be populated with actual matches
                     content_classification: self.classify_content(&result.content_preview),
                     freshness_score: self.calculate_freshness_score(result.timestamp),
+                    scoring: ContextScoring {
+                        query: query.to_string(),
+                        ..Default::default()
+                    },
                 },
             })
             .collect()
@@ -831,14 +979,35 @@ impl ContextEngine {
 
     /// Score and rank context items
     fn score_and_rank_items(&self, items: &mut Vec<ContextItem>, request: &ContextRequest) {
-        // Apply additional scoring based on use case
+        // Apply additional scoring based on use case and configured boosts
         for item in items.iter_mut() {
+            let mut adjustments = Vec::new();
             let use_case_bonus = self.calculate_use_case_bonus(item, &request.use_case);
-            item.relevance_score = (item.relevance_score + use_case_bonus).min(1.0);
+            if use_case_bonus != 0.0 {
+                adjustments.push(ScoreAdjustment {
+                    reason: format!("{:?} use case bonus", request.use_case),
+                    amount: use_case_bonus,
+                });
+            }
+            if let Some(&boost) = self.config.type_boosts.get(&item.source_type) {
+                adjustments.push(ScoreAdjustment {
+                    reason: format!("configured {:?} boost", item.source_type),
+                    amount: boost,
+                });
+            }
+
+            let base_relevance = item.relevance_score;
+            let adjusted = base_relevance + adjustments.iter().map(|a| a.amount).sum::<f64>();
+            item.relevance_score = adjusted.clamp(0.0, 1.0);
+            item.metadata.scoring.base_relevance = base_relevance;
+            item.metadata.scoring.adjustments = adjustments;
         }
 
         // Sort by relevance score
         items.sort_by(|a, b| b.relevance_score.total_cmp(&a.relevance_score));
+        for (rank, item) in items.iter_mut().enumerate() {
+            item.metadata.scoring.rank = rank;
+        }
     }
 
     /// Calculate bonus score based on use case
@@ -898,6 +1067,15 @@ impl ContextEngine {
             items = final_items;
         }
 
+        // Record how close each item came to being cut
+        let mut total_tokens = 0;
+        for item in items.iter_mut() {
+            total_tokens += item.metadata.estimated_tokens;
+            item.metadata.scoring.cumulative_tokens = total_tokens;
+            item.metadata.scoring.token_limit = constraints.max_tokens;
+            item.metadata.scoring.item_limit = constraints.max_items;
+        }
+
         items
     }
 
@@ -1119,6 +1297,11 @@ mod tests {
             role: MessageRole::User,
             content: "test message".to_string(),
             timestamp: chrono::Utc::now(),
+            edits: Vec::new(),
+            deleted_at: None,
+            attachments: Vec::new(),
+            metadata: HashMap::new(),
+            token_count: None,
         }];
 
         let request = ContextRequest {
@@ -1229,6 +1412,7 @@ mod tests {
                 matching_keywords: vec!["rust".to_string()],
                 content_classification: ContentClassification::Technical,
                 freshness_score: 0.9,
+                scoring: ContextScoring::default(),
             },
         };
 
@@ -1255,6 +1439,7 @@ mod tests {
                 matching_keywords: vec![],
                 content_classification: ContentClassification::Conversational,
                 freshness_score: 0.5,
+                scoring: ContextScoring::default(),
             },
         };
 
@@ -1272,6 +1457,7 @@ mod tests {
             matching_keywords: vec!["ai".to_string(), "rust".to_string()],
             content_classification: ContentClassification::Learning,
             freshness_score: 0.75,
+            scoring: ContextScoring::default(),
         };
 
         let json = serde_json::to_string(&metadata).unwrap();
@@ -1605,12 +1791,22 @@ mod tests {
                 role: MessageRole::User,
                 content: "Implement authentication with security features".to_string(),
                 timestamp: chrono::Utc::now(),
+                edits: Vec::new(),
+                deleted_at: None,
+                attachments: Vec::new(),
+                metadata: HashMap::new(),
+                token_count: None,
             },
             Message {
                 id: Uuid::new_v4(),
                 role: MessageRole::Assistant,
                 content: "I'll help you implement authentication".to_string(),
                 timestamp: chrono::Utc::now(),
+                edits: Vec::new(),
+                deleted_at: None,
+                attachments: Vec::new(),
+                metadata: HashMap::new(),
+                token_count: None,
             },
         ];
 
@@ -1813,6 +2009,58 @@ mod tests {
         assert!(items[1].relevance_score >= items[2].relevance_score);
     }
 
+    #[test]
+    fn test_explain_reflects_configured_boost_and_constraints() {
+        let memory_service = create_test_memory_service();
+        let mut config = ContextConfig::default();
+        config.type_boosts.insert(MemoryType::MemoryFiles, 0.25);
+        let engine = ContextEngine::with_config(memory_service, config);
+
+        let mut boosted = create_test_context_item("notes-file", 0.5);
+        boosted.source_type = MemoryType::MemoryFiles;
+        boosted.metadata.discovery_strategy = "keyword_extraction".to_string();
+        boosted.metadata.scoring.query = "migrations".to_string();
+        let mut items = vec![create_test_context_item("transcript", 0.6), boosted];
+
+        let mut request = create_test_context_request_with_constraints(None, Some(210));
+        request.use_case = ContextUseCase::CommandPreview;
+        engine.score_and_rank_items(&mut items, &request);
+        let items = engine.apply_size_constraints(items, &request);
+        let bundle = engine.build_context_bundle(items, &request, vec![], "test");
+
+        let explanation = bundle.explain("notes-file").unwrap();
+        assert_eq!(explanation.discovery_strategy, "keyword_extraction");
+        assert_eq!(explanation.query.as_deref(), Some("migrations"));
+        assert_eq!(explanation.base_relevance, 0.5);
+        assert_eq!(
+            explanation.adjustments,
+            vec![
+                ScoreAdjustment {
+                    reason: "CommandPreview use case bonus".to_string(),
+                    amount: 0.15,
+                },
+                ScoreAdjustment {
+                    reason: "configured MemoryFiles boost".to_string(),
+                    amount: 0.25,
+                },
+            ]
+        );
+        assert!((explanation.final_relevance - 0.9).abs() < 1e-9);
+        // The boost lifted it above the transcript
+        assert_eq!(explanation.rank, 0);
+        assert!(!explanation.nearly_truncated);
+        assert!(explanation
+            .to_string()
+            .contains("+0.25 configured MemoryFiles boost"));
+
+        let explanation = bundle.explain("transcript").unwrap();
+        assert_eq!(explanation.query, None);
+        assert_eq!(explanation.cumulative_tokens, 200);
+        assert_eq!(explanation.token_limit, Some(210));
+        assert!(explanation.nearly_truncated);
+        assert!(bundle.explain("missing").is_none());
+    }
+
     #[test]
     fn test_generate_cache_key() {
         let memory_service = create_test_memory_service();
@@ -1847,6 +2095,11 @@ mod tests {
             role: MessageRole::User,
             content: "Let's implement a new authentication system".to_string(),
             timestamp: chrono::Utc::now(),
+            edits: Vec::new(),
+            deleted_at: None,
+            attachments: Vec::new(),
+            metadata: HashMap::new(),
+            token_count: None,
         }];
 
         let analysis = engine.analyze_conversation_patterns(&messages);
@@ -1863,6 +2116,11 @@ mod tests {
             role: MessageRole::User,
             content: "I'm getting an error when running the tests".to_string(),
             timestamp: chrono::Utc::now(),
+            edits: Vec::new(),
+            deleted_at: None,
+            attachments: Vec::new(),
+            metadata: HashMap::new(),
+            token_count: None,
         }];
 
         let analysis = engine.analyze_conversation_patterns(&messages);
@@ -1879,6 +2137,11 @@ mod tests {
             role: MessageRole::User,
             content: "Can you explain how async/await works in Rust?".to_string(),
             timestamp: chrono::Utc::now(),
+            edits: Vec::new(),
+            deleted_at: None,
+            attachments: Vec::new(),
+            metadata: HashMap::new(),
+            token_count: None,
         }];
 
         let analysis = engine.analyze_conversation_patterns(&messages);
@@ -1941,6 +2204,7 @@ mod tests {
                 matching_keywords: vec![],
                 content_classification: ContentClassification::Technical,
                 freshness_score: 0.8,
+                scoring: ContextScoring::default(),
             },
         }
     }
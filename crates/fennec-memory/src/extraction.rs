@@ -0,0 +1,186 @@
+//! Conversation context extraction: files, technologies and decisions
+//! picked out of message text so transcripts carry them without callers
+//! sending `ConversationContextUpdate`s.
+
+use regex::Regex;
+use std::path::Path;
+use std::sync::LazyLock;
+
+/// Technologies recognised in messages, matched as whole words
+pub const TECHNOLOGIES: &[&str] = &[
+    "rust",
+    "python",
+    "javascript",
+    "typescript",
+    "react",
+    "vue",
+    "angular",
+    "node",
+    "golang",
+    "java",
+    "c++",
+    "docker",
+    "kubernetes",
+    "aws",
+    "gcp",
+    "postgres",
+    "mysql",
+    "redis",
+    "mongodb",
+    "git",
+    "github",
+    "tokio",
+    "axum",
+    "warp",
+    "actix",
+    "serde",
+    "clap",
+    "tracing",
+    "anyhow",
+];
+
+/// Phrases that mark a sentence as a decision
+const DECISION_MARKERS: &[&str] = &[
+    "we decided",
+    "decided to",
+    "let's go with",
+    "lets go with",
+    "we'll go with",
+    "we will go with",
+    "we agreed",
+    "the decision is",
+];
+
+/// Something shaped like a relative or absolute path with an extension
+static PATH_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:\.{1,2}/|/)?(?:[\w.-]+/)*[\w-][\w.-]*\.[A-Za-z0-9]{1,10}\b")
+        .expect("path pattern is valid")
+});
+
+/// The end of a sentence; dots inside file names are not
+static SENTENCE_END: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[.!?](?:\s+|$)|\n").expect("sentence pattern is valid"));
+
+/// Context found in one message
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExtractedContext {
+    pub files: Vec<String>,
+    pub technologies: Vec<String>,
+    pub decisions: Vec<String>,
+}
+
+/// Extract context from `content`. Paths are only kept when they exist
+/// under `workspace`, so without one no files are extracted.
+pub fn extract_context(content: &str, workspace: Option<&Path>) -> ExtractedContext {
+    ExtractedContext {
+        files: workspace
+            .map(|workspace| file_paths(content, workspace))
+            .unwrap_or_default(),
+        technologies: technologies(content),
+        decisions: decisions(content),
+    }
+}
+
+/// Dictionary technologies named in `content`, in dictionary order
+pub fn technologies(content: &str) -> Vec<String> {
+    let content = content.to_lowercase();
+    let words: Vec<&str> = content
+        .split(|c: char| !(c.is_alphanumeric() || c == '+'))
+        .filter(|word| !word.is_empty())
+        .collect();
+    TECHNOLOGIES
+        .iter()
+        .filter(|tech| words.contains(tech))
+        .map(|tech| tech.to_string())
+        .collect()
+}
+
+/// Paths in `content` that name an existing file under `workspace`
+pub fn file_paths(content: &str, workspace: &Path) -> Vec<String> {
+    let mut files: Vec<String> = Vec::new();
+    for found in PATH_PATTERN.find_iter(content) {
+        let path = found.as_str().trim_end_matches('.');
+        let exists = if Path::new(path).is_absolute() {
+            Path::new(path).starts_with(workspace) && Path::new(path).is_file()
+        } else {
+            workspace.join(path).is_file()
+        };
+        if exists && !files.iter().any(|file| file == path) {
+            files.push(path.to_string());
+        }
+    }
+    files
+}
+
+/// Sentences of `content` that record a decision
+pub fn decisions(content: &str) -> Vec<String> {
+    SENTENCE_END
+        .split(content)
+        .map(str::trim)
+        .filter(|sentence| {
+            let lower = sentence.to_lowercase();
+            DECISION_MARKERS.iter().any(|marker| lower.contains(marker))
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// Append the entries of `new` missing from `existing`
+pub(crate) fn merge_unique(existing: &mut Vec<String>, new: Vec<String>) {
+    for entry in new {
+        if !existing.contains(&entry) {
+            existing.push(entry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_extracts_from_realistic_messages() {
+        let workspace = TempDir::new().unwrap();
+        std::fs::create_dir_all(workspace.path().join("src/db")).unwrap();
+        std::fs::write(workspace.path().join("src/db/pool.rs"), "").unwrap();
+        std::fs::write(workspace.path().join("Cargo.toml"), "").unwrap();
+
+        let message = "The pool in src/db/pool.rs leaks connections under tokio, and \
+                       Cargo.toml pins an old postgres driver. We decided to switch to \
+                       deadpool. Also see src/db/missing.rs.";
+        let context = extract_context(message, Some(workspace.path()));
+        assert_eq!(context.files, ["src/db/pool.rs", "Cargo.toml"]);
+        assert_eq!(context.technologies, ["postgres", "tokio"]);
+        assert_eq!(context.decisions, ["We decided to switch to deadpool"]);
+
+        let context = extract_context("Let's go with Redis in cache.rs!\nShip it.", None);
+        assert!(context.files.is_empty());
+        assert_eq!(context.technologies, ["redis"]);
+        assert_eq!(context.decisions, ["Let's go with Redis in cache.rs"]);
+    }
+
+    #[test]
+    fn test_control_message_extracts_nothing() {
+        let workspace = TempDir::new().unwrap();
+        std::fs::write(workspace.path().join("notes.txt"), "").unwrap();
+
+        // Substrings of dictionary words and dotted prose are not matches
+        let message = "Good morning! I'm going to trust the gut feeling on e.g. this one, \
+                       version 2.0 looked nodular. Thanks, will report back.";
+        assert_eq!(
+            extract_context(message, Some(workspace.path())),
+            ExtractedContext::default()
+        );
+    }
+
+    #[test]
+    fn test_merge_unique_skips_existing_entries() {
+        let mut existing = vec!["rust".to_string()];
+        merge_unique(
+            &mut existing,
+            vec!["rust".to_string(), "tokio".to_string(), "tokio".to_string()],
+        );
+        assert_eq!(existing, ["rust", "tokio"]);
+    }
+}
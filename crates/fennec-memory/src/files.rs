@@ -29,8 +29,12 @@ pub struct MemoryFile {
     pub file_type: MemoryFileType,
 }
 
+/// Sidecar file in the storage directory holding the registered custom
+/// file types
+const CUSTOM_TYPES_FILE: &str = "custom_types.json";
+
 /// Types of memory files supported
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum MemoryFileType {
     /// Project-specific context and knowledge
     ProjectContext,
@@ -46,6 +50,82 @@ pub enum MemoryFileType {
     Knowledge,
     /// Task templates and workflows
     Templates,
+    /// Architecture decision records
+    ArchitectureDecisions,
+    /// What a newcomer to the project needs to know
+    Onboarding,
+    /// Step-by-step operational procedures
+    Runbook,
+    /// Project terms and their meanings
+    Glossary,
+    /// A type registered with [`MemoryFileService::register_custom_type`]
+    Custom(String),
+}
+
+impl MemoryFileType {
+    /// Every type other than [`MemoryFileType::Custom`]
+    pub const BUILT_IN: [MemoryFileType; 11] = [
+        MemoryFileType::ProjectContext,
+        MemoryFileType::DebuggingPatterns,
+        MemoryFileType::CodePatterns,
+        MemoryFileType::Architecture,
+        MemoryFileType::Learning,
+        MemoryFileType::Knowledge,
+        MemoryFileType::Templates,
+        MemoryFileType::ArchitectureDecisions,
+        MemoryFileType::Onboarding,
+        MemoryFileType::Runbook,
+        MemoryFileType::Glossary,
+    ];
+
+    /// Name to show for the type; a custom type shows its own name, see
+    /// [`MemoryFileService::display_name`] for registered display names
+    pub fn display_name(&self) -> &str {
+        match self {
+            MemoryFileType::ProjectContext => "Project context",
+            MemoryFileType::DebuggingPatterns => "Debugging patterns",
+            MemoryFileType::CodePatterns => "Code patterns",
+            MemoryFileType::Architecture => "Architecture",
+            MemoryFileType::Learning => "Learning",
+            MemoryFileType::Knowledge => "Knowledge",
+            MemoryFileType::Templates => "Templates",
+            MemoryFileType::ArchitectureDecisions => "Architecture decisions",
+            MemoryFileType::Onboarding => "Onboarding",
+            MemoryFileType::Runbook => "Runbook",
+            MemoryFileType::Glossary => "Glossary",
+            MemoryFileType::Custom(name) => name,
+        }
+    }
+
+    /// Markdown a new file of a built-in type starts from
+    pub fn default_template(&self) -> Option<&'static str> {
+        match self {
+            MemoryFileType::ArchitectureDecisions => Some(
+                "# Decision\n\n## Status\n\nProposed\n\n## Context\n\n## Decision\n\n## Consequences\n",
+            ),
+            MemoryFileType::Onboarding => Some(
+                "# Onboarding\n\n## Setup\n\n## Project layout\n\n## First tasks\n\n## Who to ask\n",
+            ),
+            MemoryFileType::Runbook => Some(
+                "# Runbook\n\n## When to use\n\n## Steps\n\n1. \n\n## Verification\n\n## Rollback\n",
+            ),
+            MemoryFileType::Glossary => Some("# Glossary\n\n| Term | Meaning |\n| --- | --- |\n"),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for MemoryFileType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.display_name())
+    }
+}
+
+/// Display name and template registered for a custom file type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomFileType {
+    pub display_name: String,
+    pub template: Option<String>,
 }
 
 /// Service for managing Cline-style memory files
@@ -57,12 +137,19 @@ pub struct MemoryFileService {
     cache: HashMap<Uuid, MemoryFile>,
     /// Maximum cache size
     max_cache_size: usize,
+    /// Registered custom file types by name
+    custom_types: HashMap<String, CustomFileType>,
 }
 
 impl MemoryFileService {
     /// Create a new memory file service
     pub fn new() -> Result<Self> {
-        let storage_dir = Self::get_storage_dir()?;
+        Self::with_storage_dir(Self::get_storage_dir()?)
+    }
+
+    /// Create a memory file service that keeps its files in `storage_dir`
+    pub fn with_storage_dir(storage_dir: impl Into<PathBuf>) -> Result<Self> {
+        let storage_dir = storage_dir.into();
 
         // Ensure storage directory exists
         std::fs::create_dir_all(&storage_dir).with_context(|| {
@@ -72,13 +159,101 @@ impl MemoryFileService {
             )
         })?;
 
+        let custom_types_path = storage_dir.join(CUSTOM_TYPES_FILE);
+        let custom_types = match std::fs::read_to_string(&custom_types_path) {
+            Ok(json) => serde_json::from_str(&json).with_context(|| {
+                format!(
+                    "Failed to deserialize custom file types: {}",
+                    custom_types_path.display()
+                )
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => {
+                return Err(e).with_context(|| {
+                    format!(
+                        "Failed to read custom file types: {}",
+                        custom_types_path.display()
+                    )
+                })
+            }
+        };
+
         Ok(Self {
             storage_dir,
             cache: HashMap::new(),
             max_cache_size: 50,
+            custom_types,
         })
     }
 
+    /// Register, or re-register, a custom file type with the name shown for
+    /// it and the template new files of the type start from
+    pub async fn register_custom_type(
+        &mut self,
+        name: &str,
+        display_name: &str,
+        template: Option<String>,
+    ) -> Result<MemoryFileType> {
+        self.custom_types.insert(
+            name.to_string(),
+            CustomFileType {
+                display_name: display_name.to_string(),
+                template,
+            },
+        );
+
+        let path = self.storage_dir.join(CUSTOM_TYPES_FILE);
+        let json = serde_json::to_string_pretty(&self.custom_types)
+            .context("Failed to serialize custom file types")?;
+        fs::write(&path, json)
+            .await
+            .with_context(|| format!("Failed to write custom file types: {}", path.display()))?;
+
+        info!("Registered custom memory file type: {}", name);
+        Ok(MemoryFileType::Custom(name.to_string()))
+    }
+
+    /// Registered custom file types by name
+    pub fn custom_types(&self) -> &HashMap<String, CustomFileType> {
+        &self.custom_types
+    }
+
+    /// Name to show for `file_type`, using the registered display name of
+    /// a custom type
+    pub fn display_name(&self, file_type: &MemoryFileType) -> String {
+        match file_type {
+            MemoryFileType::Custom(name) => self
+                .custom_types
+                .get(name)
+                .map(|custom| custom.display_name.clone())
+                .unwrap_or_else(|| name.clone()),
+            built_in => built_in.display_name().to_string(),
+        }
+    }
+
+    /// Markdown a new file of `file_type` starts from, if it has a template
+    pub fn default_template(&self, file_type: &MemoryFileType) -> Option<String> {
+        match file_type {
+            MemoryFileType::Custom(name) => self
+                .custom_types
+                .get(name)
+                .and_then(|custom| custom.template.clone()),
+            built_in => built_in.default_template().map(str::to_string),
+        }
+    }
+
+    /// Create a memory file starting from the default template of its type
+    pub async fn create_from_template(
+        &mut self,
+        name: String,
+        file_type: MemoryFileType,
+        tags: Vec<String>,
+    ) -> Result<Uuid> {
+        let content = self.default_template(&file_type).unwrap_or_default();
+        self.create_memory_file(name, content, file_type, tags)
+            .await
+    }
+
     /// Get the storage directory for memory files
     fn get_storage_dir() -> Result<PathBuf> {
         let proj_dirs =
@@ -205,17 +380,41 @@ impl MemoryFileService {
         Ok(files)
     }
 
+    /// List the memory files of one type
+    pub async fn list_by_type(
+        &self,
+        file_type: &MemoryFileType,
+    ) -> Result<Vec<MemoryFileMetadata>> {
+        let mut files = self.list_memory_files().await?;
+        files.retain(|file| &file.file_type == file_type);
+        Ok(files)
+    }
+
     /// Search memory files by content, name, or tags
     pub async fn search_memory_files(
         &mut self,
         query: &str,
         limit: Option<usize>,
+    ) -> Result<Vec<MemoryFileSearchResult>> {
+        self.search_memory_files_filtered(query, None, limit).await
+    }
+
+    /// Search memory files by content, name, or tags, only among files of
+    /// `file_type` when given
+    pub async fn search_memory_files_filtered(
+        &mut self,
+        query: &str,
+        file_type: Option<&MemoryFileType>,
+        limit: Option<usize>,
     ) -> Result<Vec<MemoryFileSearchResult>> {
         let mut results = Vec::new();
         use fuzzy_matcher::FuzzyMatcher;
         let matcher = fuzzy_matcher::skim::SkimMatcherV2::default();
 
-        let files = self.list_memory_files().await?;
+        let files = match file_type {
+            Some(file_type) => self.list_by_type(file_type).await?,
+            None => self.list_memory_files().await?,
+        };
 
         for file_meta in files {
             let mut best_score = 0i64;
@@ -446,11 +645,7 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let storage_dir = temp_dir.path().to_owned();
 
-        let mut service = MemoryFileService {
-            storage_dir,
-            cache: HashMap::new(),
-            max_cache_size: 50,
-        };
+        let mut service = MemoryFileService::with_storage_dir(storage_dir).unwrap();
 
         let file_id = service
             .create_memory_file(
@@ -473,11 +668,7 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let storage_dir = temp_dir.path().to_owned();
 
-        let mut service = MemoryFileService {
-            storage_dir,
-            cache: HashMap::new(),
-            max_cache_size: 50,
-        };
+        let mut service = MemoryFileService::with_storage_dir(storage_dir).unwrap();
 
         // Create a test file
         service
@@ -495,4 +686,124 @@ mod tests {
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].name, "Rust Programming");
     }
+
+    #[tokio::test]
+    async fn test_custom_type_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut service = MemoryFileService::with_storage_dir(temp_dir.path()).unwrap();
+
+        let postmortem = service
+            .register_custom_type(
+                "postmortem",
+                "Incident postmortem",
+                Some("# Incident\n\n## Timeline\n".to_string()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(postmortem, MemoryFileType::Custom("postmortem".to_string()));
+        let file_id = service
+            .create_from_template("Outage".to_string(), postmortem.clone(), Vec::new())
+            .await
+            .unwrap();
+
+        // A fresh service reads both the registration and the file back
+        let mut reopened = MemoryFileService::with_storage_dir(temp_dir.path()).unwrap();
+        assert_eq!(reopened.display_name(&postmortem), "Incident postmortem");
+        let loaded = reopened.load_memory_file(file_id).await.unwrap().unwrap();
+        assert_eq!(loaded.file_type, postmortem);
+        assert_eq!(loaded.content, "# Incident\n\n## Timeline\n");
+        assert_eq!(reopened.list_memory_files().await.unwrap().len(), 1);
+
+        // Unregistered custom types fall back to their own name
+        let unknown = MemoryFileType::Custom("scratch".to_string());
+        assert_eq!(reopened.display_name(&unknown), "scratch");
+        assert_eq!(reopened.default_template(&unknown), None);
+        assert_eq!(
+            reopened.display_name(&MemoryFileType::ArchitectureDecisions),
+            "Architecture decisions"
+        );
+        assert!(reopened
+            .default_template(&MemoryFileType::Runbook)
+            .unwrap()
+            .contains("## Rollback"));
+    }
+
+    #[test]
+    fn test_file_types_stay_compatible_with_stored_files() {
+        let stored = r#"{
+            "id": "6f1c1d4e-8a9b-4c1e-9d2a-3b4c5d6e7f80",
+            "name": "Old file",
+            "content": "Written before custom types",
+            "tags": [],
+            "created_at": "2025-01-01T00:00:00Z",
+            "updated_at": "2025-01-01T00:00:00Z",
+            "related_sessions": [],
+            "file_type": "DebuggingPatterns"
+        }"#;
+        let file: MemoryFile = serde_json::from_str(stored).unwrap();
+        assert_eq!(file.file_type, MemoryFileType::DebuggingPatterns);
+
+        for file_type in MemoryFileType::BUILT_IN {
+            let json = serde_json::to_string(&file_type).unwrap();
+            assert_eq!(json, format!("\"{:?}\"", file_type));
+            assert_eq!(
+                serde_json::from_str::<MemoryFileType>(&json).unwrap(),
+                file_type
+            );
+        }
+        let custom = MemoryFileType::Custom("postmortem".to_string());
+        let json = serde_json::to_string(&custom).unwrap();
+        assert_eq!(
+            serde_json::from_str::<MemoryFileType>(&json).unwrap(),
+            custom
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_and_search_by_type() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut service = MemoryFileService::with_storage_dir(temp_dir.path()).unwrap();
+        let postmortem = MemoryFileType::Custom("postmortem".to_string());
+
+        for (name, file_type) in [
+            ("Deploy runbook", MemoryFileType::Runbook),
+            ("Deploy outage", postmortem.clone()),
+            ("Deploy terms", MemoryFileType::Glossary),
+        ] {
+            service
+                .create_memory_file(name.to_string(), String::new(), file_type, Vec::new())
+                .await
+                .unwrap();
+        }
+
+        let runbooks = service
+            .list_by_type(&MemoryFileType::Runbook)
+            .await
+            .unwrap();
+        assert_eq!(runbooks.len(), 1);
+        assert_eq!(runbooks[0].name, "Deploy runbook");
+        let postmortems = service.list_by_type(&postmortem).await.unwrap();
+        assert_eq!(postmortems.len(), 1);
+        assert_eq!(postmortems[0].name, "Deploy outage");
+        assert!(service
+            .list_by_type(&MemoryFileType::Onboarding)
+            .await
+            .unwrap()
+            .is_empty());
+
+        assert_eq!(
+            service
+                .search_memory_files("deploy", None)
+                .await
+                .unwrap()
+                .len(),
+            3
+        );
+        let results = service
+            .search_memory_files_filtered("deploy", Some(&postmortem), None)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].file_type, postmortem);
+    }
 }
@@ -531,7 +531,7 @@ mod tests {
     use super::*;
     use crate::context::{
         CacheStatus, ContentClassification, ContextBundleMetadata, ContextDiscoveryStrategy,
-        ContextImportance, ContextItem, ContextItemMetadata, ContextQualityMetrics,
+        ContextImportance, ContextItem, ContextItemMetadata, ContextQualityMetrics, ContextScoring,
         ContextSizeInfo, ContextSummary,
     };
 
@@ -688,6 +688,7 @@ mod tests {
     fn test_enhanced_memory_injection_creation() {
         let traditional = crate::service::MemoryInjection {
             guidance: vec![],
+            omitted_guidance: 0,
             conversation_history: vec![],
             session_context: ConversationContext::default(),
             estimated_tokens: 0,
@@ -1054,6 +1055,7 @@ mod tests {
                 matching_keywords: vec![],
                 content_classification: ContentClassification::Technical,
                 freshness_score: 0.9,
+                scoring: ContextScoring::default(),
             },
         }
     }
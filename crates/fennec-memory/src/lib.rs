@@ -60,12 +60,15 @@
 //! ```
 
 pub mod agents;
+pub mod approval_context;
 pub mod cline_files;
 pub mod context;
+pub mod extraction;
 pub mod files;
 pub mod integration;
 pub mod notes;
 pub mod plans;
+pub mod scope;
 pub mod service;
 pub mod transcript;
 
@@ -78,22 +81,27 @@ pub use service::{
 };
 
 pub use transcript::{
-    ConversationContext as TranscriptConversationContext, ConversationContextUpdate,
-    ExecutionResult, MemoryTranscript, SegmentType, TimelineEvent, TimelineEventType,
-    TranscriptMetadata, TranscriptSearchFilters, TranscriptSearchResult, TranscriptSegment,
-    TranscriptStore,
+    match_spans, CommandRecord, ConversationContext as TranscriptConversationContext,
+    ConversationContextUpdate, ExecutionResult, MemoryTranscript, MessageHit, SegmentType,
+    TimelineEvent, TimelineEventType, TranscriptMetadata, TranscriptSearchFilters,
+    TranscriptSearchResult, TranscriptSegment, TranscriptStore,
 };
 
+pub use approval_context::TranscriptMentions;
+
+pub use scope::MemoryScope;
+
 pub use agents::{AgentSection, AgentsConfig, AgentsService, GuidanceMatch, MatchType};
 
 pub use files::{
-    MatchLocation, MemoryFile, MemoryFileMetadata, MemoryFileSearchResult, MemoryFileService,
-    MemoryFileType,
+    CustomFileType, MatchLocation, MemoryFile, MemoryFileMetadata, MemoryFileSearchResult,
+    MemoryFileService, MemoryFileType,
 };
 
 pub use plans::{
-    CommandAssociation, CommandPlan, ExecutionResult as PlanExecutionResult, PlanMatchLocation,
-    PlanPriority, PlanSearchResult, PlanStatus, PlanStep, PlanStore, PlanTemplate, StepStatus,
+    Clock, CommandAssociation, CommandPlan, ExecutionResult as PlanExecutionResult, PlanEvent,
+    PlanMatchLocation, PlanMetrics, PlanPriority, PlanSearchResult, PlanStatus, PlanStep,
+    PlanStore, PlanTemplate, StepMetrics, StepStatus, SystemClock,
 };
 
 pub use notes::{
@@ -103,8 +111,9 @@ pub use notes::{
 
 pub use context::{
     ContentClassification, ContextBundle, ContextConfig, ContextDiscoveryStrategy, ContextEngine,
-    ContextImportance, ContextItem, ContextItemMetadata, ContextRequest, ContextSizeConstraints,
-    ContextSizeInfo, ContextSummary, ContextUseCase,
+    ContextExplanation, ContextImportance, ContextItem, ContextItemMetadata, ContextRequest,
+    ContextScoring, ContextSizeConstraints, ContextSizeInfo, ContextSummary, ContextUseCase,
+    ScoreAdjustment,
 };
 
 pub use integration::{
@@ -38,10 +38,40 @@ pub struct UserNote {
     pub is_pinned: bool,
     /// Reminder date for follow-up (optional)
     pub reminder_date: Option<chrono::DateTime<chrono::Utc>>,
+    /// When a reminder falls due; for other notes, when they expire
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// A due reminder stays quiet until then
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snoozed_until: Option<chrono::DateTime<chrono::Utc>>,
     /// Color coding for visual organization
     pub color: Option<String>,
 }
 
+impl UserNote {
+    /// Whether this is a reminder that is due at `now` and not snoozed
+    pub fn is_due(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.category == NoteCategory::Reminder
+            && self.due_at.is_some_and(|due| due <= now)
+            && self.snoozed_until.is_none_or(|until| until <= now)
+    }
+
+    /// Whether this is a note other than a reminder that has expired at
+    /// `now`
+    pub fn is_expired(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.category != NoteCategory::Reminder && self.due_at.is_some_and(|due| due <= now)
+    }
+
+    /// Factor search scores are scaled by at `now`, dropping expired notes
+    fn freshness_penalty(&self, now: chrono::DateTime<chrono::Utc>) -> f64 {
+        if self.is_expired(now) {
+            0.0
+        } else {
+            1.0
+        }
+    }
+}
+
 /// Categories for organizing notes
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum NoteCategory {
@@ -90,7 +120,12 @@ pub struct NotesStore {
 impl NotesStore {
     /// Create a new notes store
     pub fn new() -> Result<Self> {
-        let storage_dir = Self::get_storage_dir()?;
+        Self::with_storage_dir(Self::get_storage_dir()?)
+    }
+
+    /// Create a notes store that keeps its notes in `storage_dir`
+    pub fn with_storage_dir(storage_dir: impl Into<PathBuf>) -> Result<Self> {
+        let storage_dir = storage_dir.into();
 
         // Ensure storage directory exists
         std::fs::create_dir_all(&storage_dir).with_context(|| {
@@ -141,6 +176,8 @@ impl NotesStore {
             priority: NotePriority::Medium,
             is_pinned: false,
             reminder_date: None,
+            due_at: None,
+            snoozed_until: None,
             color: None,
         };
 
@@ -355,8 +392,43 @@ impl NotesStore {
         self.update_note(note).await
     }
 
-    /// List all notes for a session
-    pub async fn list_session_notes(&mut self, session_id: Uuid) -> Result<Vec<NoteMetadata>> {
+    /// Set when a note falls due, clearing any snooze
+    pub async fn set_due(
+        &mut self,
+        note_id: Uuid,
+        due_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<()> {
+        let mut note = self
+            .load_note(note_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Note not found: {}", note_id))?;
+
+        note.due_at = due_at;
+        note.snoozed_until = None;
+        self.update_note(note).await
+    }
+
+    /// Keep a due reminder from surfacing until `until`
+    pub async fn snooze(
+        &mut self,
+        note_id: Uuid,
+        until: chrono::DateTime<chrono::Utc>,
+    ) -> Result<()> {
+        let mut note = self
+            .load_note(note_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Note not found: {}", note_id))?;
+
+        note.snoozed_until = Some(until);
+        self.update_note(note).await
+    }
+
+    /// Reminders due at `now` that are not snoozed, highest priority first,
+    /// then the longest overdue
+    pub async fn list_due_notes(
+        &mut self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<NoteMetadata>> {
         let mut notes = Vec::new();
 
         let mut dir = fs::read_dir(&self.storage_dir).await.with_context(|| {
@@ -372,7 +444,7 @@ impl NotesStore {
                 if let Some(note_id_str) = path.file_stem().and_then(|s| s.to_str()) {
                     if let Ok(note_id) = Uuid::parse_str(note_id_str) {
                         if let Ok(Some(note)) = self.load_note(note_id).await {
-                            if note.session_id == Some(session_id) {
+                            if note.is_due(now) {
                                 notes.push(NoteMetadata::from_note(&note));
                             }
                         }
@@ -381,6 +453,41 @@ impl NotesStore {
             }
         }
 
+        notes.sort_by(|a, b| b.priority.cmp(&a.priority).then(a.due_at.cmp(&b.due_at)));
+        Ok(notes)
+    }
+
+    /// List all notes for a session
+    pub async fn list_session_notes(&mut self, session_id: Uuid) -> Result<Vec<NoteMetadata>> {
+        let mut notes = self.list_notes().await?;
+        notes.retain(|note| note.session_id == Some(session_id));
+        Ok(notes)
+    }
+
+    /// List notes across all sessions
+    pub async fn list_notes(&mut self) -> Result<Vec<NoteMetadata>> {
+        let mut notes = Vec::new();
+
+        let mut dir = fs::read_dir(&self.storage_dir).await.with_context(|| {
+            format!(
+                "Failed to read notes directory: {}",
+                self.storage_dir.display()
+            )
+        })?;
+
+        while let Some(entry) = dir.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) == Some("json") {
+                if let Some(note_id_str) = path.file_stem().and_then(|s| s.to_str()) {
+                    if let Ok(note_id) = Uuid::parse_str(note_id_str) {
+                        if let Ok(Some(note)) = self.load_note(note_id).await {
+                            notes.push(NoteMetadata::from_note(&note));
+                        }
+                    }
+                }
+            }
+        }
+
         // Sort by updated time (most recent first)
         notes.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
         Ok(notes)
@@ -494,6 +601,16 @@ impl NotesStore {
         &mut self,
         query: &str,
         filters: NoteSearchFilters,
+    ) -> Result<Vec<NoteSearchResult>> {
+        self.search_notes_at(query, filters, chrono::Utc::now())
+            .await
+    }
+
+    async fn search_notes_at(
+        &mut self,
+        query: &str,
+        filters: NoteSearchFilters,
+        now: chrono::DateTime<chrono::Utc>,
     ) -> Result<Vec<NoteSearchResult>> {
         let mut results = Vec::new();
         use fuzzy_matcher::FuzzyMatcher;
@@ -564,6 +681,8 @@ impl NotesStore {
                                 }
                             }
 
+                            let best_score =
+                                (best_score as f64 * note.freshness_penalty(now)) as i64;
                             if best_score > 0 {
                                 results.push(NoteSearchResult {
                                     note_id: note.id,
@@ -701,6 +820,10 @@ pub struct NoteMetadata {
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
     pub reminder_date: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub due_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub snoozed_until: Option<chrono::DateTime<chrono::Utc>>,
     pub content_length: usize,
     pub linked_plans_count: usize,
     pub linked_commands_count: usize,
@@ -720,6 +843,8 @@ impl NoteMetadata {
             created_at: note.created_at,
             updated_at: note.updated_at,
             reminder_date: note.reminder_date,
+            due_at: note.due_at,
+            snoozed_until: note.snoozed_until,
             content_length: note.content.len(),
             linked_plans_count: note.linked_plans.len(),
             linked_commands_count: note.linked_commands.len(),
@@ -905,4 +1030,136 @@ mod tests {
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].title, "Rust Learning");
     }
+
+    #[tokio::test]
+    async fn test_due_and_snoozed_reminders() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = NotesStore::with_storage_dir(temp_dir.path()).unwrap();
+        let now = chrono::DateTime::parse_from_rfc3339("2026-03-01T09:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let hours = chrono::Duration::hours;
+
+        let reminder = store
+            .create_note(
+                None,
+                "Rotate keys".to_string(),
+                "Rotate the staging API keys".to_string(),
+                NoteCategory::Reminder,
+            )
+            .await
+            .unwrap();
+        let urgent = store
+            .create_note(
+                None,
+                "Ship fix".to_string(),
+                "Ship the parser fix".to_string(),
+                NoteCategory::Reminder,
+            )
+            .await
+            .unwrap();
+        store
+            .set_priority(urgent, NotePriority::Critical)
+            .await
+            .unwrap();
+        // Only reminders fall due
+        let insight = store
+            .create_note(
+                None,
+                "Old insight".to_string(),
+                "Content".to_string(),
+                NoteCategory::Insight,
+            )
+            .await
+            .unwrap();
+        for note_id in [reminder, urgent, insight] {
+            store.set_due(note_id, Some(now + hours(1))).await.unwrap();
+        }
+        store.set_due(reminder, Some(now - hours(2))).await.unwrap();
+
+        let due = store.list_due_notes(now).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].id, reminder);
+        assert_eq!(due[0].due_at, Some(now - hours(2)));
+
+        let later = now + hours(1);
+        let due: Vec<Uuid> = store
+            .list_due_notes(later)
+            .await
+            .unwrap()
+            .iter()
+            .map(|note| note.id)
+            .collect();
+        assert_eq!(due, vec![urgent, reminder]);
+
+        // Snoozed until three hours from now, then due again
+        store.snooze(reminder, now + hours(3)).await.unwrap();
+        let due = store.list_due_notes(later).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].id, urgent);
+        assert_eq!(store.list_due_notes(now + hours(3)).await.unwrap().len(), 2);
+
+        // A new due date clears the snooze
+        store.set_due(reminder, Some(now)).await.unwrap();
+        let note = store.load_note(reminder).await.unwrap().unwrap();
+        assert_eq!(note.snoozed_until, None);
+        assert!(note.is_due(now));
+        store.set_due(reminder, None).await.unwrap();
+        assert!(store
+            .list_due_notes(later)
+            .await
+            .unwrap()
+            .iter()
+            .all(|n| n.id != reminder));
+    }
+
+    #[tokio::test]
+    async fn test_expired_notes_drop_out_of_search() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = NotesStore::with_storage_dir(temp_dir.path()).unwrap();
+        let now = chrono::Utc::now();
+
+        let note_id = store
+            .create_note(
+                None,
+                "Deploy freeze".to_string(),
+                "No deploys during the migration".to_string(),
+                NoteCategory::Decision,
+            )
+            .await
+            .unwrap();
+        let reminder = store
+            .create_note(
+                None,
+                "Deploy again".to_string(),
+                "Lift the deploy freeze".to_string(),
+                NoteCategory::Reminder,
+            )
+            .await
+            .unwrap();
+        for id in [note_id, reminder] {
+            store
+                .set_due(id, Some(now + chrono::Duration::days(1)))
+                .await
+                .unwrap();
+        }
+
+        let results = store
+            .search_notes_at("deploy", NoteSearchFilters::default(), now)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+
+        // Past its due date the decision has expired; the reminder is due
+        let later = now + chrono::Duration::days(2);
+        let results = store
+            .search_notes_at("deploy", NoteSearchFilters::default(), later)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].note_id, reminder);
+        let note = store.load_note(note_id).await.unwrap().unwrap();
+        assert!(note.is_expired(later));
+        assert!(!note.is_due(later));
+    }
 }
@@ -2,9 +2,12 @@ use anyhow::{Context, Result};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::fs;
+use tokio::sync::broadcast;
 use tracing::{debug, info};
 use uuid::Uuid;
 
@@ -48,16 +51,26 @@ pub struct PlanStep {
     pub id: Uuid,
     /// Order of execution (0-based)
     pub order: u32,
+    /// Short heading for this step
+    #[serde(default)]
+    pub title: String,
     /// Description of what this step accomplishes
     pub description: String,
     /// Current status of this step
     pub status: StepStatus,
     /// Estimated effort for this step
     pub estimated_effort: Option<String>,
+    /// Estimated time this step will take, compared against
+    /// `actual_duration` by [`PlanStore::plan_metrics`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_duration: Option<Duration>,
     /// Actual time taken to complete this step
     pub actual_duration: Option<Duration>,
     /// Other steps this step depends on
     pub dependencies: Vec<Uuid>,
+    /// Commands suggested for carrying out this step
+    #[serde(default)]
+    pub suggested_commands: Vec<String>,
     /// Commands associated with this step
     pub command_associations: Vec<CommandAssociation>,
     /// Notes specific to this step
@@ -105,9 +118,10 @@ pub enum StepStatus {
 }
 
 /// Priority level for plans
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub enum PlanPriority {
     Low,
+    #[default]
     Medium,
     High,
     Critical,
@@ -149,6 +163,104 @@ pub struct ExecutionResult {
     pub follow_up_actions: Vec<String>,
 }
 
+/// Change to a stored plan, as seen by [`PlanStore::subscribe`]
+#[derive(Debug, Clone)]
+pub enum PlanEvent {
+    /// A plan was created or registered
+    Created(CommandPlan),
+    /// A plan, its steps or their statuses changed
+    Updated(CommandPlan),
+    /// A plan was deleted
+    Deleted(Uuid),
+}
+
+/// Number of events a slow subscriber may fall behind before missing some
+const PLAN_EVENT_CAPACITY: usize = 64;
+
+/// Source of the current time for step start and end timestamps
+pub trait Clock: fmt::Debug + Send + Sync {
+    fn now(&self) -> chrono::DateTime<chrono::Utc>;
+}
+
+/// The real clock
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc::now()
+    }
+}
+
+/// Estimated against actual effort of a plan, from [`PlanStore::plan_metrics`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanMetrics {
+    pub plan_id: Uuid,
+    /// Sum of the step estimates
+    pub total_estimated: Duration,
+    /// Sum of the time finished steps actually took
+    pub total_actual: Duration,
+    /// Metrics of each step, in plan order
+    pub steps: Vec<StepMetrics>,
+    /// Share of steps completed or skipped, from 0 to 100
+    pub completion_percentage: f64,
+}
+
+/// Estimated against actual effort of one step
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepMetrics {
+    pub step_id: Uuid,
+    pub order: u32,
+    pub status: StepStatus,
+    pub estimated: Option<Duration>,
+    pub actual: Option<Duration>,
+    /// Actual minus estimated, negative when the step finished early;
+    /// `None` unless both are known
+    pub variance: Option<chrono::Duration>,
+}
+
+impl PlanMetrics {
+    pub fn from_plan(plan: &CommandPlan) -> Self {
+        let steps: Vec<StepMetrics> = plan
+            .steps
+            .iter()
+            .map(|step| StepMetrics {
+                step_id: step.id,
+                order: step.order,
+                status: step.status.clone(),
+                estimated: step.estimated_duration,
+                actual: step.actual_duration,
+                variance: step
+                    .actual_duration
+                    .and_then(|actual| chrono::Duration::from_std(actual).ok())
+                    .zip(
+                        step.estimated_duration
+                            .and_then(|estimated| chrono::Duration::from_std(estimated).ok()),
+                    )
+                    .map(|(actual, estimated)| actual - estimated),
+            })
+            .collect();
+
+        let done = steps
+            .iter()
+            .filter(|step| matches!(step.status, StepStatus::Completed | StepStatus::Skipped))
+            .count();
+        let completion_percentage = if steps.is_empty() {
+            0.0
+        } else {
+            done as f64 * 100.0 / steps.len() as f64
+        };
+
+        Self {
+            plan_id: plan.id,
+            total_estimated: steps.iter().filter_map(|step| step.estimated).sum(),
+            total_actual: steps.iter().filter_map(|step| step.actual).sum(),
+            steps,
+            completion_percentage,
+        }
+    }
+}
+
 /// Storage service for managing command plans
 #[derive(Debug)]
 pub struct PlanStore {
@@ -158,12 +270,21 @@ pub struct PlanStore {
     cache: HashMap<Uuid, CommandPlan>,
     /// Maximum cache size
     max_cache_size: usize,
+    /// Changes to plans, for subscribers such as the TUI
+    events: broadcast::Sender<PlanEvent>,
+    /// Time step transitions are recorded at
+    clock: Arc<dyn Clock>,
 }
 
 impl PlanStore {
     /// Create a new plan store
     pub fn new() -> Result<Self> {
-        let storage_dir = Self::get_storage_dir()?;
+        Self::with_storage_dir(Self::get_storage_dir()?)
+    }
+
+    /// Create a plan store that keeps its plans in `storage_dir`
+    pub fn with_storage_dir(storage_dir: impl Into<PathBuf>) -> Result<Self> {
+        let storage_dir = storage_dir.into();
 
         // Ensure storage directory exists
         std::fs::create_dir_all(&storage_dir).with_context(|| {
@@ -177,9 +298,27 @@ impl PlanStore {
             storage_dir,
             cache: HashMap::new(),
             max_cache_size: 100,
+            events: broadcast::channel(PLAN_EVENT_CAPACITY).0,
+            clock: Arc::new(SystemClock),
         })
     }
 
+    /// Record step transitions at the time `clock` gives
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Receive every later change to the plans of this store
+    pub fn subscribe(&self) -> broadcast::Receiver<PlanEvent> {
+        self.events.subscribe()
+    }
+
+    fn publish(&self, event: PlanEvent) {
+        // Nobody listening is fine
+        let _ = self.events.send(event);
+    }
+
     /// Get the storage directory for plans
     fn get_storage_dir() -> Result<PathBuf> {
         let proj_dirs =
@@ -216,12 +355,26 @@ impl PlanStore {
         };
 
         self.store_plan(&plan).await?;
+        self.publish(PlanEvent::Created(plan.clone()));
         self.cache.insert(plan_id, plan);
 
         info!("Created plan: {} ({})", plan_id, title);
         Ok(plan_id)
     }
 
+    /// Store a plan built elsewhere, e.g. by the `plan` command
+    pub async fn register_plan(&mut self, plan: CommandPlan) -> Result<Uuid> {
+        let plan_id = plan.id;
+
+        self.store_plan(&plan).await?;
+        info!("Registered plan: {} ({})", plan_id, plan.title);
+        self.publish(PlanEvent::Created(plan.clone()));
+        self.cache.insert(plan_id, plan);
+        self.manage_cache_size();
+
+        Ok(plan_id)
+    }
+
     /// Load a plan by ID
     pub async fn load_plan(&mut self, plan_id: Uuid) -> Result<Option<CommandPlan>> {
         // Check cache first
@@ -258,6 +411,7 @@ impl PlanStore {
         updated_plan.updated_at = chrono::Utc::now();
 
         self.store_plan(&updated_plan).await?;
+        self.publish(PlanEvent::Updated(updated_plan.clone()));
         self.cache.insert(updated_plan.id, updated_plan);
 
         debug!("Updated plan: {}", plan_id);
@@ -282,11 +436,14 @@ impl PlanStore {
         let step = PlanStep {
             id: step_id,
             order,
+            title: String::new(),
             description,
             status: StepStatus::Pending,
             estimated_effort: None,
+            estimated_duration: None,
             actual_duration: None,
             dependencies,
+            suggested_commands: Vec::new(),
             command_associations: Vec::new(),
             notes: Vec::new(),
             started_at: None,
@@ -318,19 +475,20 @@ impl PlanStore {
             .find(|s| s.id == step_id)
             .ok_or_else(|| anyhow::anyhow!("Step not found: {}", step_id))?;
 
-        let now = chrono::Utc::now();
+        let now = self.clock.now();
         step.status = status.clone();
 
         match status {
             StepStatus::InProgress => {
                 step.started_at = Some(now);
+                step.completed_at = None;
+                step.actual_duration = None;
             }
             StepStatus::Completed | StepStatus::Failed | StepStatus::Skipped => {
+                // A step finished without being started took no time
+                let started = *step.started_at.get_or_insert(now);
                 step.completed_at = Some(now);
-                if let Some(started) = step.started_at {
-                    step.actual_duration =
-                        Some(Duration::from_secs((now - started).num_seconds() as u64));
-                }
+                step.actual_duration = Some((now - started).to_std().unwrap_or_default());
             }
             _ => {}
         }
@@ -344,6 +502,37 @@ impl PlanStore {
         Ok(())
     }
 
+    /// Set how long a step is expected to take
+    pub async fn set_step_estimate(
+        &mut self,
+        plan_id: Uuid,
+        step_id: Uuid,
+        estimate: Option<Duration>,
+    ) -> Result<()> {
+        let mut plan = self
+            .load_plan(plan_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Plan not found: {}", plan_id))?;
+
+        let step = plan
+            .steps
+            .iter_mut()
+            .find(|s| s.id == step_id)
+            .ok_or_else(|| anyhow::anyhow!("Step not found: {}", step_id))?;
+        step.estimated_duration = estimate;
+
+        self.update_plan(plan).await
+    }
+
+    /// Estimated against actual effort of a plan and its steps
+    pub async fn plan_metrics(&mut self, plan_id: Uuid) -> Result<PlanMetrics> {
+        let plan = self
+            .load_plan(plan_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Plan not found: {}", plan_id))?;
+        Ok(PlanMetrics::from_plan(&plan))
+    }
+
     /// Associate a command execution with a plan step
     pub async fn associate_command(
         &mut self,
@@ -382,6 +571,13 @@ impl PlanStore {
 
     /// List all plans for a session
     pub async fn list_session_plans(&mut self, session_id: Uuid) -> Result<Vec<CommandPlan>> {
+        let mut plans = self.list_plans().await?;
+        plans.retain(|plan| plan.session_id == session_id);
+        Ok(plans)
+    }
+
+    /// List plans across all sessions
+    pub async fn list_plans(&mut self) -> Result<Vec<CommandPlan>> {
         let mut plans = Vec::new();
 
         let mut dir = fs::read_dir(&self.storage_dir).await.with_context(|| {
@@ -397,9 +593,7 @@ impl PlanStore {
                 if let Some(plan_id_str) = path.file_stem().and_then(|s| s.to_str()) {
                     if let Ok(plan_id) = Uuid::parse_str(plan_id_str) {
                         if let Ok(Some(plan)) = self.load_plan(plan_id).await {
-                            if plan.session_id == session_id {
-                                plans.push(plan);
-                            }
+                            plans.push(plan);
                         }
                     }
                 }
@@ -515,6 +709,7 @@ impl PlanStore {
                 .await
                 .with_context(|| format!("Failed to delete plan file: {}", file_path.display()))?;
             info!("Deleted plan: {}", plan_id);
+            self.publish(PlanEvent::Deleted(plan_id));
         }
 
         Ok(())
@@ -673,11 +868,7 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let storage_dir = temp_dir.path().to_owned();
 
-        let mut store = PlanStore {
-            storage_dir,
-            cache: HashMap::new(),
-            max_cache_size: 100,
-        };
+        let mut store = PlanStore::with_storage_dir(storage_dir).unwrap();
 
         let session_id = Uuid::new_v4();
         let plan_id = store
@@ -700,11 +891,7 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let storage_dir = temp_dir.path().to_owned();
 
-        let mut store = PlanStore {
-            storage_dir,
-            cache: HashMap::new(),
-            max_cache_size: 100,
-        };
+        let mut store = PlanStore::with_storage_dir(storage_dir).unwrap();
 
         let session_id = Uuid::new_v4();
         let plan_id = store
@@ -732,11 +919,7 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let storage_dir = temp_dir.path().to_owned();
 
-        let mut store = PlanStore {
-            storage_dir,
-            cache: HashMap::new(),
-            max_cache_size: 100,
-        };
+        let mut store = PlanStore::with_storage_dir(storage_dir).unwrap();
 
         let session_id = Uuid::new_v4();
         let plan_id = store
@@ -768,11 +951,7 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let storage_dir = temp_dir.path().to_owned();
 
-        let mut store = PlanStore {
-            storage_dir,
-            cache: HashMap::new(),
-            max_cache_size: 100,
-        };
+        let mut store = PlanStore::with_storage_dir(storage_dir).unwrap();
 
         let session_id = Uuid::new_v4();
         store
@@ -788,4 +967,148 @@ mod tests {
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].title, "Rust Implementation");
     }
+
+    #[tokio::test]
+    async fn test_subscribers_see_plan_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = PlanStore::with_storage_dir(temp_dir.path()).unwrap();
+        let mut events = store.subscribe();
+
+        let plan_id = store
+            .create_plan(Uuid::new_v4(), "Plan".to_string(), String::new())
+            .await
+            .unwrap();
+        let step_id = store
+            .add_step(plan_id, "Step".to_string(), Vec::new())
+            .await
+            .unwrap();
+        store
+            .update_step_status(plan_id, step_id, StepStatus::Skipped)
+            .await
+            .unwrap();
+        store.delete_plan(plan_id).await.unwrap();
+
+        assert!(
+            matches!(events.try_recv().unwrap(), PlanEvent::Created(plan) if plan.id == plan_id)
+        );
+        match events.try_recv().unwrap() {
+            PlanEvent::Updated(plan) => assert_eq!(plan.steps.len(), 1),
+            other => panic!("unexpected event: {other:?}"),
+        }
+        match events.try_recv().unwrap() {
+            PlanEvent::Updated(plan) => assert_eq!(plan.steps[0].status, StepStatus::Skipped),
+            other => panic!("unexpected event: {other:?}"),
+        }
+        assert!(matches!(events.try_recv().unwrap(), PlanEvent::Deleted(id) if id == plan_id));
+        assert!(events.try_recv().is_err());
+    }
+
+    #[derive(Debug)]
+    struct ManualClock(std::sync::Mutex<chrono::DateTime<chrono::Utc>>);
+
+    impl ManualClock {
+        fn advance(&self, by: Duration) {
+            *self.0.lock().unwrap() += chrono::Duration::from_std(by).unwrap();
+        }
+    }
+
+    impl Clock for ManualClock {
+        fn now(&self) -> chrono::DateTime<chrono::Utc> {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_plan_metrics_compare_estimates_with_actuals() {
+        const MINUTE: Duration = Duration::from_secs(60);
+        let temp_dir = TempDir::new().unwrap();
+        let start = chrono::Utc::now();
+        let clock = Arc::new(ManualClock(std::sync::Mutex::new(start)));
+        let mut store = PlanStore::with_storage_dir(temp_dir.path())
+            .unwrap()
+            .with_clock(clock.clone());
+
+        let plan_id = store
+            .create_plan(Uuid::new_v4(), "Plan".to_string(), String::new())
+            .await
+            .unwrap();
+        let mut steps = Vec::new();
+        for (description, estimate) in [("Write", 30), ("Review", 10), ("Ship", 5), ("Docs", 0)] {
+            let step_id = store
+                .add_step(plan_id, description.to_string(), Vec::new())
+                .await
+                .unwrap();
+            if estimate > 0 {
+                store
+                    .set_step_estimate(plan_id, step_id, Some(MINUTE * estimate))
+                    .await
+                    .unwrap();
+            }
+            steps.push(step_id);
+        }
+
+        // Write overruns by ten minutes
+        store
+            .update_step_status(plan_id, steps[0], StepStatus::InProgress)
+            .await
+            .unwrap();
+        clock.advance(MINUTE * 40);
+        store
+            .update_step_status(plan_id, steps[0], StepStatus::Completed)
+            .await
+            .unwrap();
+
+        // Review finishes early
+        store
+            .update_step_status(plan_id, steps[1], StepStatus::InProgress)
+            .await
+            .unwrap();
+        clock.advance(MINUTE * 4);
+        store
+            .update_step_status(plan_id, steps[1], StepStatus::Completed)
+            .await
+            .unwrap();
+
+        // Ship goes straight from pending to completed
+        clock.advance(MINUTE);
+        store
+            .update_step_status(plan_id, steps[2], StepStatus::Completed)
+            .await
+            .unwrap();
+
+        let plan = store.load_plan(plan_id).await.unwrap().unwrap();
+        assert_eq!(plan.steps[0].started_at, Some(start));
+        let shipped = start + chrono::Duration::minutes(45);
+        assert_eq!(plan.steps[2].started_at, Some(shipped));
+        assert_eq!(plan.steps[2].completed_at, Some(shipped));
+
+        let metrics = store.plan_metrics(plan_id).await.unwrap();
+        assert_eq!(metrics.total_estimated, MINUTE * 45);
+        assert_eq!(metrics.total_actual, MINUTE * 44);
+        assert_eq!(metrics.completion_percentage, 75.0);
+        let variances: Vec<Option<i64>> = metrics
+            .steps
+            .iter()
+            .map(|step| step.variance.map(|v| v.num_minutes()))
+            .collect();
+        assert_eq!(variances, vec![Some(10), Some(-6), Some(-5), None]);
+        assert_eq!(metrics.steps[2].actual, Some(Duration::ZERO));
+        assert_eq!(metrics.steps[3].estimated, None);
+
+        // Restarting a finished step clears its actual until it finishes again
+        store
+            .update_step_status(plan_id, steps[3], StepStatus::InProgress)
+            .await
+            .unwrap();
+        store
+            .update_step_status(plan_id, steps[0], StepStatus::InProgress)
+            .await
+            .unwrap();
+        let metrics = store.plan_metrics(plan_id).await.unwrap();
+        assert_eq!(metrics.steps[0].actual, None);
+        assert_eq!(metrics.total_actual, MINUTE * 4);
+        assert_eq!(metrics.completion_percentage, 50.0);
+
+        assert!(store.plan_metrics(Uuid::new_v4()).await.is_err());
+    }
 }
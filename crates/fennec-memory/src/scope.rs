@@ -0,0 +1,95 @@
+//! Where memory is kept: one pool shared by every workspace, or a pool per
+//! workspace so conversations about one project never surface in another.
+
+use anyhow::{Context, Result};
+use directories::ProjectDirs;
+use ring::digest::{digest, SHA256};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Directory under the data directory holding the per-workspace pools
+const WORKSPACES_DIR: &str = "workspaces";
+
+/// Hex digits of the workspace path hash used to name its directory
+const WORKSPACE_KEY_LEN: usize = 16;
+
+/// Which pool of memory a service reads and writes
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryScope {
+    /// One pool shared by every workspace
+    #[default]
+    Global,
+    /// A pool of its own for the workspace at this path
+    Workspace(PathBuf),
+}
+
+impl MemoryScope {
+    /// The pool of `workspace`. The path is canonicalized when it exists, so
+    /// different spellings of one directory share a pool.
+    pub fn workspace(workspace: impl AsRef<Path>) -> Self {
+        let workspace = workspace.as_ref();
+        Self::Workspace(
+            workspace
+                .canonicalize()
+                .unwrap_or_else(|_| workspace.to_path_buf()),
+        )
+    }
+
+    /// Directory under `data_dir` holding this scope's transcripts, notes
+    /// and memory files
+    pub fn storage_root(&self, data_dir: &Path) -> PathBuf {
+        match self {
+            Self::Global => data_dir.to_path_buf(),
+            Self::Workspace(workspace) => {
+                data_dir.join(WORKSPACES_DIR).join(workspace_key(workspace))
+            }
+        }
+    }
+}
+
+/// Platform data directory memory is kept under unless configured otherwise
+pub fn default_data_dir() -> Result<PathBuf> {
+    let proj_dirs =
+        ProjectDirs::from("", "", "fennec").context("Failed to get project directories")?;
+    Ok(proj_dirs.data_dir().to_path_buf())
+}
+
+/// Stable name for a workspace's directory
+fn workspace_key(workspace: &Path) -> String {
+    let hash = digest(&SHA256, workspace.to_string_lossy().as_bytes());
+    hex::encode(hash.as_ref())[..WORKSPACE_KEY_LEN].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storage_roots() {
+        let data_dir = Path::new("/data/fennec");
+        assert_eq!(MemoryScope::Global.storage_root(data_dir), data_dir);
+
+        let client = MemoryScope::workspace("/work/client-a");
+        let root = client.storage_root(data_dir);
+        assert!(root.starts_with("/data/fennec/workspaces"));
+        assert_eq!(root.file_name().unwrap().len(), WORKSPACE_KEY_LEN);
+        assert_eq!(root, client.storage_root(data_dir));
+        assert_ne!(
+            root,
+            MemoryScope::workspace("/work/client-b").storage_root(data_dir)
+        );
+    }
+
+    #[test]
+    fn test_workspace_spellings_share_a_scope() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let workspace = temp_dir.path().join("project");
+        std::fs::create_dir(&workspace).unwrap();
+
+        assert_eq!(
+            MemoryScope::workspace(&workspace),
+            MemoryScope::workspace(workspace.join("..").join("project"))
+        );
+    }
+}
@@ -1,22 +1,31 @@
 use anyhow::Result;
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::{watch, RwLock};
-use tracing::{debug, error, info, warn};
+use tracing::{debug, error, info, warn, Instrument};
 use uuid::Uuid;
 
 use fennec_core::{
-    error::{ErrorCategory, ErrorInfo, ErrorSeverity, RecoveryAction},
+    error::{DomainError, ErrorCategory, ErrorInfo, ErrorSeverity, RecoveryAction},
     session::Session,
     transcript::{MessageRole, Transcript},
     FennecError,
 };
+use fennec_security::{
+    AuditEventData, AuditSystem, MemoryDeletionData, MemoryExportData, MemorySearchData,
+};
+use fennec_telemetry::{metrics, spans};
 
 use crate::{
-    agents::{AgentsConfig, AgentsService, GuidanceMatch},
+    agents::{AgentsConfig, AgentsService, GuidanceMatch, MatchType},
+    approval_context::TranscriptMentions,
     cline_files::{Achievement, ClineFileType, ClineMemoryFileService, MemoryEvent, ProjectStatus},
     files::MemoryFileService,
-    transcript::{TranscriptSearchResult, TranscriptStore},
+    notes::{NotePriority, NotesStore},
+    scope::{default_data_dir, MemoryScope},
+    transcript::{CommandRecord, TranscriptSearchResult, TranscriptStore},
 };
 
 /// Core memory service that orchestrates all memory functionality
@@ -30,12 +39,27 @@ pub struct MemoryService {
     memory_file_service: Arc<RwLock<MemoryFileService>>,
     /// Cline-style memory file service
     cline_memory_service: Arc<RwLock<ClineMemoryFileService>>,
+    /// User notes, for the reminders that are due
+    notes_store: Arc<RwLock<NotesStore>>,
     /// Active sessions being tracked
     active_sessions: Arc<RwLock<HashMap<Uuid, SessionMemory>>>,
     /// Configuration for memory behavior
     config: MemoryConfig,
+    /// Bumped once per write to session memory, so cached context built
+    /// before it is not reused
+    revision: AtomicU64,
+    /// Where searches, deletions and exports are audited, if anywhere
+    audit: Option<Arc<AuditSystem>>,
+    /// Directory the memory of every scope is kept under
+    data_dir: PathBuf,
 }
 
+/// Directories of each store under a scope's storage root
+const TRANSCRIPTS_DIR: &str = "transcripts";
+const MEMORY_FILES_DIR: &str = "memory_files";
+const PROJECTS_DIR: &str = "projects";
+const NOTES_DIR: &str = "notes";
+
 /// Configuration for memory service behavior
 #[derive(Debug, Clone)]
 pub struct MemoryConfig {
@@ -45,8 +69,18 @@ pub struct MemoryConfig {
     pub auto_generate_summaries: bool,
     /// Context window size for guidance injection
     pub guidance_context_window: usize,
+    /// Estimated tokens of AGENTS.md guidance injected into a prompt;
+    /// sections marked `priority: always` are injected even past it
+    pub guidance_token_budget: usize,
     /// Maximum number of search results to return
     pub max_search_results: usize,
+    /// Whether stored transcripts pick up files, technologies and decisions
+    /// from their messages
+    pub extract_conversation_context: bool,
+    /// Whether memory is shared by all workspaces or kept per workspace
+    pub scope: MemoryScope,
+    /// Directory memory is kept under; the platform data directory if unset
+    pub data_dir: Option<PathBuf>,
 }
 
 impl Default for MemoryConfig {
@@ -55,7 +89,11 @@ impl Default for MemoryConfig {
             max_messages_in_memory: 1000,
             auto_generate_summaries: true,
             guidance_context_window: 50,
+            guidance_token_budget: 1500,
             max_search_results: 10,
+            extract_conversation_context: true,
+            scope: MemoryScope::Global,
+            data_dir: None,
         }
     }
 }
@@ -100,8 +138,12 @@ impl Default for ConversationContext {
 /// Memory injection data for AI prompts
 #[derive(Debug, Clone)]
 pub struct MemoryInjection {
-    /// Relevant guidance from AGENTS.md
+    /// Relevant guidance from AGENTS.md, after any overdue Critical and
+    /// High priority reminders
     pub guidance: Vec<GuidanceMatch>,
+    /// Matching AGENTS.md sections left out to stay within the guidance
+    /// token budget
+    pub omitted_guidance: usize,
     /// Relevant conversation history
     pub conversation_history: Vec<TranscriptSearchResult>,
     /// Current session context
@@ -138,8 +180,11 @@ pub enum SessionFilter {
     ExcludeCurrentSession(Uuid),
     /// Specific sessions only
     SpecificSessions(Vec<Uuid>),
-    /// Cross-session (all sessions)
+    /// Cross-session (all sessions in the service's scope)
     CrossSession,
+    /// All sessions in another scope, such as [`MemoryScope::Global`] from
+    /// a service kept per workspace
+    Scope(MemoryScope),
 }
 
 /// Time-based filtering options
@@ -260,30 +305,92 @@ pub struct SearchMetadata {
 impl MemoryService {
     /// Create a new memory service
     pub async fn new() -> Result<Self> {
-        let agents_service = Arc::new(AgentsService::new().await?);
-        let transcript_store = Arc::new(RwLock::new(TranscriptStore::new()?));
-        let memory_file_service = Arc::new(RwLock::new(MemoryFileService::new()?));
-        let cline_memory_service = Arc::new(RwLock::new(ClineMemoryFileService::new()?));
-        let active_sessions = Arc::new(RwLock::new(HashMap::new()));
-        let config = MemoryConfig::default();
+        Self::with_config(MemoryConfig::default()).await
+    }
+
+    /// Approval context from earlier sessions, for registering with
+    /// `fennec_security::ApprovalEnrichment`
+    pub fn approval_context_provider(&self) -> Arc<dyn fennec_security::ApprovalContextProvider> {
+        Arc::new(TranscriptMentions::new(self.transcript_store.clone()))
+    }
+
+    /// Keep transcripts in `transcript_store`
+    pub fn with_transcript_store(mut self, transcript_store: TranscriptStore) -> Self {
+        self.transcript_store = Arc::new(RwLock::new(transcript_store));
+        self
+    }
+
+    /// Use `notes_store` for the reminders injected into prompts
+    pub fn with_notes_store(mut self, notes_store: NotesStore) -> Self {
+        self.notes_store = Arc::new(RwLock::new(notes_store));
+        self
+    }
 
-        info!("Memory service initialized with Cline-style memory files");
+    /// Keep project memory files in `cline_memory_service`
+    pub fn with_cline_memory_service(
+        mut self,
+        cline_memory_service: ClineMemoryFileService,
+    ) -> Self {
+        self.cline_memory_service = Arc::new(RwLock::new(cline_memory_service));
+        self
+    }
+
+    /// Record searches, deletions and exports of memory in `audit`
+    pub fn with_audit(mut self, audit: Arc<AuditSystem>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Create a new memory service with custom configuration, keeping its
+    /// memory in the directory of the configured scope
+    pub async fn with_config(config: MemoryConfig) -> Result<Self> {
+        let data_dir = match &config.data_dir {
+            Some(data_dir) => data_dir.clone(),
+            None => default_data_dir()?,
+        };
+        let root = config.scope.storage_root(&data_dir);
+
+        let agents_service = Arc::new(AgentsService::new().await?);
+        let mut transcript_store = TranscriptStore::with_storage_dir(root.join(TRANSCRIPTS_DIR))?;
+        transcript_store.set_context_extraction(config.extract_conversation_context);
+        let memory_file_service = MemoryFileService::with_storage_dir(root.join(MEMORY_FILES_DIR))?;
+        let cline_memory_service =
+            ClineMemoryFileService::with_storage_dir(root.join(PROJECTS_DIR))?;
+        let notes_store = NotesStore::with_storage_dir(root.join(NOTES_DIR))?;
+
+        info!(
+            "Memory service initialized with Cline-style memory files in {}",
+            root.display()
+        );
 
         Ok(Self {
             agents_service,
-            transcript_store,
-            memory_file_service,
-            cline_memory_service,
-            active_sessions,
+            transcript_store: Arc::new(RwLock::new(transcript_store)),
+            memory_file_service: Arc::new(RwLock::new(memory_file_service)),
+            cline_memory_service: Arc::new(RwLock::new(cline_memory_service)),
+            notes_store: Arc::new(RwLock::new(notes_store)),
+            active_sessions: Arc::new(RwLock::new(HashMap::new())),
             config,
+            revision: AtomicU64::new(0),
+            audit: None,
+            data_dir,
         })
     }
 
-    /// Create a new memory service with custom configuration
-    pub async fn with_config(config: MemoryConfig) -> Result<Self> {
-        let mut service = Self::new().await?;
-        service.config = config;
-        Ok(service)
+    /// The pool of memory this service reads and writes
+    pub fn scope(&self) -> &MemoryScope {
+        &self.config.scope
+    }
+
+    /// Storage root of the scope `criteria` asks for, when it is not this
+    /// service's own
+    fn other_scope_root(&self, criteria: &AdvancedSearchCriteria) -> Option<PathBuf> {
+        match &criteria.session_filter {
+            Some(SessionFilter::Scope(scope)) if scope != self.scope() => {
+                Some(scope.storage_root(&self.data_dir))
+            }
+            _ => None,
+        }
     }
 
     /// Start tracking a session
@@ -291,9 +398,11 @@ impl MemoryService {
         let session_id = session.id;
         debug!("Starting memory tracking for session: {}", session_id);
 
-        // Load existing transcript if available
+        // Load existing transcript if available, noting the session's
+        // title and workspace with it
         let transcript = {
             let mut store = self.transcript_store.write().await;
+            store.record_session(&session).await?;
             store
                 .load_transcript(session_id)
                 .await?
@@ -340,6 +449,30 @@ impl MemoryService {
         Ok(())
     }
 
+    /// Close out a session: store its summary, tell the project's memory
+    /// files it ended and stop tracking it
+    pub async fn end_session(
+        &self,
+        session: Session,
+        project_id: Option<Uuid>,
+        summary: Option<String>,
+    ) -> Result<()> {
+        let session_id = session.id;
+        if let Some(summary) = &summary {
+            self.set_session_summary(session_id, summary.clone())
+                .await?;
+        }
+        if let Some(project_id) = project_id {
+            self.emit_memory_event(MemoryEvent::SessionEnded {
+                project_id,
+                session,
+                summary,
+            })
+            .await?;
+        }
+        self.stop_session(session_id).await
+    }
+
     /// Add a message to a session
     pub async fn add_message(
         &self,
@@ -347,35 +480,60 @@ impl MemoryService {
         role: MessageRole,
         content: String,
     ) -> Result<()> {
-        // Update active session if it exists
-        {
-            let mut sessions = self.active_sessions.write().await;
-            if let Some(session_memory) = sessions.get_mut(&session_id) {
-                session_memory
-                    .transcript
-                    .add_message(role.clone(), content.clone());
-                session_memory.is_dirty = true;
-
-                // Update conversation context
-                self.update_conversation_context(&mut session_memory.context, &content);
-
-                // Trim transcript if it's getting too large
-                if session_memory.transcript.messages.len() > self.config.max_messages_in_memory {
-                    let excess = session_memory.transcript.messages.len()
-                        - self.config.max_messages_in_memory;
-                    session_memory.transcript.messages.drain(0..excess);
+        self.add_messages(session_id, vec![(role, content)]).await
+    }
+
+    /// Add `messages` to a session in order, persisting the transcript once
+    pub async fn add_messages(
+        &self,
+        session_id: Uuid,
+        messages: Vec<(MessageRole, String)>,
+    ) -> Result<()> {
+        async move {
+            let count = messages.len();
+
+            // Update active session if it exists
+            {
+                let mut sessions = self.active_sessions.write().await;
+                if let Some(session_memory) = sessions.get_mut(&session_id) {
+                    for (role, content) in &messages {
+                        session_memory
+                            .transcript
+                            .add_message(role.clone(), content.clone());
+
+                        // Update conversation context
+                        self.update_conversation_context(&mut session_memory.context, content);
+                    }
+                    session_memory.is_dirty = true;
+
+                    // Trim transcript if it's getting too large
+                    if session_memory.transcript.messages.len() > self.config.max_messages_in_memory
+                    {
+                        let excess = session_memory.transcript.messages.len()
+                            - self.config.max_messages_in_memory;
+                        session_memory.transcript.messages.drain(0..excess);
+                    }
                 }
             }
-        }
 
-        // Also update persistent storage
-        {
-            let mut store = self.transcript_store.write().await;
-            store.add_message(session_id, role, content).await?;
+            // Also update persistent storage
+            {
+                let mut store = self.transcript_store.write().await;
+                store.add_messages(session_id, messages).await?;
+            }
+            self.revision.fetch_add(1, Ordering::Relaxed);
+
+            debug!("Added {} messages to session: {}", count, session_id);
+            Ok(())
         }
+        .instrument(spans::memory_span("add_message"))
+        .await
+    }
 
-        debug!("Added message to session: {}", session_id);
-        Ok(())
+    /// Revision of session memory; changes whenever messages or command
+    /// executions are added
+    pub fn revision(&self) -> u64 {
+        self.revision.load(Ordering::Relaxed)
     }
 
     /// Get memory injection data for AI prompts
@@ -384,80 +542,110 @@ impl MemoryService {
         session_id: Uuid,
         query: Option<&str>,
     ) -> Result<MemoryInjection> {
-        debug!("Generating memory injection for session: {}", session_id);
-
-        let session_context = {
-            let sessions = self.active_sessions.read().await;
-            sessions
-                .get(&session_id)
-                .map(|sm| sm.context.clone())
-                .unwrap_or_default()
-        };
-
-        // Get relevant guidance from AGENTS.md
-        let guidance = if let Some(query) = query {
-            self.agents_service.search_guidance(query)
-        } else {
-            // Use session context to find relevant guidance
-            let mut guidance = Vec::new();
-            for topic in &session_context.recent_topics {
-                guidance.extend(self.agents_service.search_guidance(topic));
-            }
-            for tech in &session_context.technologies {
-                guidance.extend(self.agents_service.search_guidance(tech));
-            }
-            guidance
-        };
-
-        // Get relevant conversation history
-        let conversation_history = if let Some(query) = query {
-            let store = self.transcript_store.write().await;
-            store
-                .search_transcripts(query, Some(self.config.max_search_results))
-                .await?
-        } else {
-            // Search based on current session context
-            let mut results = Vec::new();
-            let store = self.transcript_store.write().await;
-
-            for topic in &session_context.recent_topics {
-                let search_results = store.search_transcripts(topic, Some(3)).await?;
-                results.extend(search_results);
-            }
-
-            // Deduplicate and limit results
-            results.sort_by(|a, b| b.score.cmp(&a.score));
-            results.truncate(self.config.max_search_results);
-            results
-        };
+        async move {
+            debug!("Generating memory injection for session: {}", session_id);
+
+            let session_context = {
+                let sessions = self.active_sessions.read().await;
+                sessions
+                    .get(&session_id)
+                    .map(|sm| sm.context.clone())
+                    .unwrap_or_default()
+            };
+
+            // Get relevant guidance from AGENTS.md
+            let guidance = if let Some(query) = query {
+                self.agents_service.search_guidance(query)
+            } else {
+                // Use session context to find relevant guidance
+                let mut guidance = Vec::new();
+                for topic in &session_context.recent_topics {
+                    guidance.extend(self.agents_service.search_guidance(topic));
+                }
+                for tech in &session_context.technologies {
+                    guidance.extend(self.agents_service.search_guidance(tech));
+                }
+                guidance
+            };
 
-        // Estimate tokens
-        let estimated_tokens = self.estimate_injection_tokens(&guidance, &conversation_history);
+            // Get relevant conversation history
+            let conversation_history = if let Some(query) = query {
+                let store = self.transcript_store.write().await;
+                store
+                    .search_transcripts(query, Some(self.config.max_search_results))
+                    .await?
+            } else {
+                // Search based on current session context
+                let mut results = Vec::new();
+                let store = self.transcript_store.write().await;
+
+                for topic in &session_context.recent_topics {
+                    let search_results = store.search_transcripts(topic, Some(3)).await?;
+                    results.extend(search_results);
+                }
 
-        Ok(MemoryInjection {
-            guidance: guidance.into_iter().take(5).collect(), // Limit guidance items
-            conversation_history,
-            session_context,
-            estimated_tokens,
-        })
+                // Deduplicate and limit results
+                results.sort_by_key(|result| std::cmp::Reverse(result.score));
+                results.truncate(self.config.max_search_results);
+                results
+            };
+
+            let (guidance, omitted_guidance) = assemble_guidance(
+                self.agents_service.always_guidance(),
+                guidance,
+                self.config.guidance_token_budget,
+            );
+
+            // Overdue reminders come first and do not count against the
+            // guidance budget
+            let mut reminders = self.overdue_reminders(chrono::Utc::now()).await?;
+            reminders.extend(guidance);
+            let guidance = reminders;
+
+            // Estimate tokens
+            let estimated_tokens = self.estimate_injection_tokens(&guidance, &conversation_history);
+
+            Ok(MemoryInjection {
+                guidance,
+                omitted_guidance,
+                conversation_history,
+                session_context,
+                estimated_tokens,
+            })
+        }
+        .instrument(spans::memory_span("memory_injection"))
+        .await
     }
 
     /// Search through all memory
     pub async fn search(&self, query: &str, limit: Option<usize>) -> Result<MemorySearchResults> {
-        debug!("Searching memory with query: {}", query);
-
-        // Search guidance
-        let guidance_matches = self.agents_service.search_guidance(query);
+        async move {
+            let start_time = std::time::Instant::now();
+            debug!("Searching memory with query: {}", query);
 
-        // Search transcripts
-        let store = self.transcript_store.write().await;
-        let transcript_matches = store.search_transcripts(query, limit).await?;
+            // Search guidance
+            let guidance_matches = self.agents_service.search_guidance(query);
 
-        Ok(MemorySearchResults {
-            guidance_matches,
-            transcript_matches,
-            query: query.to_string(),
-        })
+            // Search transcripts
+            let store = self.transcript_store.write().await;
+            let transcript_matches = store.search_transcripts(query, limit).await?;
+            drop(store);
+            metrics::record_memory_search("basic", start_time.elapsed());
+            self.audit_search(
+                query,
+                guidance_matches.len() + transcript_matches.len(),
+                &[MemoryType::Guidance, MemoryType::Transcripts],
+            )
+            .await;
+
+            Ok(MemorySearchResults {
+                guidance_matches,
+                transcript_matches,
+                query: query.to_string(),
+            })
+        }
+        .instrument(spans::memory_span("search"))
+        .await
     }
 
     /// Enhanced context-aware search across all memory types
@@ -465,75 +653,84 @@ impl MemoryService {
         &self,
         criteria: AdvancedSearchCriteria,
     ) -> Result<EnhancedSearchResults> {
-        let start_time = std::time::Instant::now();
-        debug!("Advanced search with criteria: {:?}", criteria);
-
-        let mut all_results = Vec::new();
-        let mut sources_searched = Vec::new();
-
-        // Search each memory type if requested
-        for memory_type in &criteria.memory_types {
-            sources_searched.push(memory_type.clone());
-
-            match memory_type {
-                MemoryType::Transcripts => {
-                    let transcript_results = self.search_transcripts_advanced(&criteria).await?;
-                    all_results.extend(transcript_results);
-                }
-                MemoryType::Guidance => {
-                    let guidance_results = self.search_guidance_advanced(&criteria);
-                    all_results.extend(guidance_results);
-                }
-                MemoryType::MemoryFiles => {
-                    let memory_file_results = self.search_memory_files_advanced(&criteria).await?;
-                    all_results.extend(memory_file_results);
+        async move {
+            let start_time = std::time::Instant::now();
+            debug!("Advanced search with criteria: {:?}", criteria);
+
+            let mut all_results = Vec::new();
+            let mut sources_searched = Vec::new();
+
+            // Search each memory type if requested
+            for memory_type in &criteria.memory_types {
+                sources_searched.push(memory_type.clone());
+
+                match memory_type {
+                    MemoryType::Transcripts => {
+                        let transcript_results =
+                            self.search_transcripts_advanced(&criteria).await?;
+                        all_results.extend(transcript_results);
+                    }
+                    MemoryType::Guidance => {
+                        let guidance_results = self.search_guidance_advanced(&criteria);
+                        all_results.extend(guidance_results);
+                    }
+                    MemoryType::MemoryFiles => {
+                        let memory_file_results =
+                            self.search_memory_files_advanced(&criteria).await?;
+                        all_results.extend(memory_file_results);
+                    }
                 }
             }
-        }
-
-        // Apply scoring strategy
-        self.apply_scoring_strategy(&mut all_results, &criteria);
 
-        // Apply time filtering
-        if let Some(ref time_filter) = criteria.time_filter {
-            all_results = self.apply_time_filter(all_results, time_filter);
-        }
-
-        // Apply session filtering
-        if let Some(ref session_filter) = criteria.session_filter {
-            all_results = self.apply_session_filter(all_results, session_filter);
-        }
+            // Apply scoring strategy
+            self.apply_scoring_strategy(&mut all_results, &criteria);
 
-        // Apply minimum score threshold
-        if let Some(min_score) = criteria.min_score {
-            all_results.retain(|result| result.relevance_score >= min_score);
-        }
+            // Apply time filtering
+            if let Some(ref time_filter) = criteria.time_filter {
+                all_results = self.apply_time_filter(all_results, time_filter);
+            }
 
-        // Sort by relevance score (highest first)
-        all_results.sort_by(|a, b| b.relevance_score.total_cmp(&a.relevance_score));
+            // Apply session filtering
+            if let Some(ref session_filter) = criteria.session_filter {
+                all_results = self.apply_session_filter(all_results, session_filter);
+            }
 
-        let total_found = all_results.len();
+            // Apply minimum score threshold
+            if let Some(min_score) = criteria.min_score {
+                all_results.retain(|result| result.relevance_score >= min_score);
+            }
 
-        // Apply limit
-        if let Some(limit) = criteria.limit {
-            all_results.truncate(limit);
-        }
+            // Sort by relevance score (highest first)
+            all_results.sort_by(|a, b| b.relevance_score.total_cmp(&a.relevance_score));
 
-        let execution_time = start_time.elapsed();
+            let total_found = all_results.len();
 
-        let search_metadata = SearchMetadata {
-            total_found,
-            returned_count: all_results.len(),
-            execution_time_ms: execution_time.as_millis() as u64,
-            sources_searched,
-            scoring_strategy: criteria.scoring_strategy.clone(),
-        };
+            // Apply limit
+            if let Some(limit) = criteria.limit {
+                all_results.truncate(limit);
+            }
 
-        Ok(EnhancedSearchResults {
-            results: all_results,
-            criteria,
-            search_metadata,
-        })
+            let execution_time = start_time.elapsed();
+            metrics::record_memory_search("advanced", execution_time);
+            self.audit_search(&criteria.query, all_results.len(), &criteria.memory_types)
+                .await;
+
+            let search_metadata = SearchMetadata {
+                total_found,
+                returned_count: all_results.len(),
+                execution_time_ms: execution_time.as_millis() as u64,
+                sources_searched,
+                scoring_strategy: criteria.scoring_strategy.clone(),
+            };
+
+            Ok(EnhancedSearchResults {
+                results: all_results,
+                criteria,
+                search_metadata,
+            })
+        }
+        .instrument(spans::memory_span("search_advanced"))
+        .await
     }
 
     /// Get all available guidance sections
@@ -561,12 +758,27 @@ impl MemoryService {
         }
 
         // Delete from storage
-        {
+        let messages_deleted = {
             let mut store = self.transcript_store.write().await;
+            let messages = store
+                .load_transcript(session_id)
+                .await?
+                .map(|transcript| transcript.transcript.messages.len());
             store.delete_transcript(session_id).await?;
-        }
+            messages
+        };
 
         info!("Deleted session: {}", session_id);
+        self.audit_event(
+            Some(session_id),
+            AuditEventData::MemoryDeletion(MemoryDeletionData {
+                scope: "session".to_string(),
+                target: session_id.to_string(),
+                transcripts_deleted: usize::from(messages_deleted.is_some()),
+                messages_deleted: messages_deleted.unwrap_or(0),
+            }),
+        )
+        .await;
         Ok(())
     }
 
@@ -600,6 +812,107 @@ impl MemoryService {
         store.set_summary(session_id, summary).await
     }
 
+    /// Note in the stored transcript of a session that its next `compacted`
+    /// messages were replaced by `summary` in the prompt
+    pub async fn record_compaction(
+        &self,
+        session_id: Uuid,
+        compacted: usize,
+        summary: String,
+    ) -> Result<Uuid> {
+        let mut store = self.transcript_store.write().await;
+        store
+            .add_compaction_segment(session_id, compacted, summary)
+            .await
+    }
+
+    /// Load the stored transcript of a session
+    pub async fn load_transcript(
+        &self,
+        session_id: Uuid,
+    ) -> Result<Option<crate::transcript::MemoryTranscript>> {
+        let mut store = self.transcript_store.write().await;
+        store.load_transcript(session_id).await
+    }
+
+    /// Messages and command executions of a session in the order they
+    /// happened
+    pub async fn get_session_timeline(
+        &self,
+        session_id: Uuid,
+    ) -> Result<Vec<crate::transcript::TimelineEvent>> {
+        let mut store = self.transcript_store.write().await;
+        store.get_session_timeline(session_id).await
+    }
+
+    /// List all memory files
+    pub async fn list_memory_files(&self) -> Result<Vec<crate::files::MemoryFileMetadata>> {
+        let service = self.memory_file_service.read().await;
+        service.list_memory_files().await
+    }
+
+    /// Search memory files by content, name, or tags
+    pub async fn search_memory_files(
+        &self,
+        query: &str,
+        limit: Option<usize>,
+    ) -> Result<Vec<crate::files::MemoryFileSearchResult>> {
+        let mut service = self.memory_file_service.write().await;
+        service.search_memory_files(query, limit).await
+    }
+
+    /// Load a memory file with its content
+    pub async fn load_memory_file(&self, id: Uuid) -> Result<Option<crate::files::MemoryFile>> {
+        let mut service = self.memory_file_service.write().await;
+        service.load_memory_file(id).await
+    }
+
+    /// Record a command execution in a session's transcript
+    #[allow(clippy::too_many_arguments)]
+    pub async fn record_command_execution(
+        &self,
+        session_id: Uuid,
+        command: String,
+        result: crate::transcript::ExecutionResult,
+        output: Option<String>,
+        error: Option<String>,
+        duration: Option<std::time::Duration>,
+        exit_code: Option<i32>,
+    ) -> Result<Uuid> {
+        let record = CommandRecord {
+            command,
+            result,
+            output,
+            error,
+            duration,
+            exit_code,
+            triggered_by_message: None,
+        };
+        let ids = self
+            .record_command_executions(session_id, vec![record])
+            .await?;
+        Ok(ids[0])
+    }
+
+    /// Record `records` in a session's transcript in order, persisting it
+    /// once, and return their execution ids in the same order
+    pub async fn record_command_executions(
+        &self,
+        session_id: Uuid,
+        records: Vec<CommandRecord>,
+    ) -> Result<Vec<Uuid>> {
+        let mut store = self.transcript_store.write().await;
+        // A session may run a command before its first message
+        if store.load_transcript(session_id).await?.is_none() {
+            store
+                .update_transcript(session_id, Transcript::new(session_id))
+                .await?;
+        }
+        let ids = store.add_command_executions(session_id, records).await?;
+        self.revision.fetch_add(1, Ordering::Relaxed);
+        Ok(ids)
+    }
+
     /// Get session memory if active
     pub async fn get_session_memory(&self, session_id: Uuid) -> Option<SessionMemory> {
         let sessions = self.active_sessions.read().await;
@@ -675,6 +988,30 @@ impl MemoryService {
         }
     }
 
+    /// Critical and High priority reminders due at `now`, as guidance
+    async fn overdue_reminders(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<GuidanceMatch>> {
+        let mut store = self.notes_store.write().await;
+        let mut reminders = Vec::new();
+        for metadata in store.list_due_notes(now).await? {
+            if metadata.priority < NotePriority::High {
+                continue;
+            }
+            if let Some(note) = store.load_note(metadata.id).await? {
+                let due = note.due_at.unwrap_or(now).format("%Y-%m-%d %H:%M");
+                reminders.push(GuidanceMatch {
+                    section_title: format!("Reminder: {} (due {})", note.title, due),
+                    content: note.content,
+                    score: 0,
+                    match_type: MatchType::Reminder,
+                });
+            }
+        }
+        Ok(reminders)
+    }
+
     /// Estimate token count for memory injection
     fn estimate_injection_tokens(
         &self,
@@ -701,15 +1038,28 @@ impl MemoryService {
         &self,
         criteria: &AdvancedSearchCriteria,
     ) -> Result<Vec<UnifiedSearchResult>> {
-        let store = self.transcript_store.read().await;
-        let transcript_results = store.search_transcripts(&criteria.query, None).await?;
+        let transcript_results = match self.other_scope_root(criteria) {
+            Some(root) => {
+                TranscriptStore::with_storage_dir(root.join(TRANSCRIPTS_DIR))?
+                    .search_transcripts(&criteria.query, None)
+                    .await?
+            }
+            None => {
+                let store = self.transcript_store.read().await;
+                store.search_transcripts(&criteria.query, None).await?
+            }
+        };
 
         let mut unified_results = Vec::new();
         for result in transcript_results {
             unified_results.push(UnifiedSearchResult {
                 memory_type: MemoryType::Transcripts,
                 id: result.session_id.to_string(),
-                title: format!("Session {}", result.session_id),
+                title: result
+                    .metadata
+                    .title
+                    .clone()
+                    .unwrap_or_else(|| format!("Session {}", result.session_id)),
                 content_preview: self.generate_content_preview(&result.matching_messages),
                 full_content: None, // Will be populated if needed
                 relevance_score: self.normalize_fuzzy_score(result.score),
@@ -759,10 +1109,19 @@ impl MemoryService {
         &self,
         criteria: &AdvancedSearchCriteria,
     ) -> Result<Vec<UnifiedSearchResult>> {
-        let mut file_service = self.memory_file_service.write().await;
-        let file_results = file_service
-            .search_memory_files(&criteria.query, None)
-            .await?;
+        let file_results = match self.other_scope_root(criteria) {
+            Some(root) => {
+                MemoryFileService::with_storage_dir(root.join(MEMORY_FILES_DIR))?
+                    .search_memory_files(&criteria.query, None)
+                    .await?
+            }
+            None => {
+                let mut file_service = self.memory_file_service.write().await;
+                file_service
+                    .search_memory_files(&criteria.query, None)
+                    .await?
+            }
+        };
 
         let mut unified_results = Vec::new();
         for result in file_results {
@@ -941,7 +1300,7 @@ impl MemoryService {
                 SessionFilter::SpecificSessions(session_ids) => result
                     .session_id
                     .map_or(false, |id| session_ids.contains(&id)),
-                SessionFilter::CrossSession => {
+                SessionFilter::CrossSession | SessionFilter::Scope(_) => {
                     true // Include all sessions
                 }
             }
@@ -1028,6 +1387,18 @@ impl MemoryService {
             .await
     }
 
+    /// Render any of a project's Cline-style files as markdown
+    pub async fn render_cline_file(
+        &self,
+        project_id: Uuid,
+        file_type: ClineFileType,
+    ) -> Result<Option<String>> {
+        let mut cline_service = self.cline_memory_service.write().await;
+        cline_service
+            .render_to_markdown(project_id, file_type)
+            .await
+    }
+
     /// Update project goals
     pub async fn update_project_goals(&self, project_id: Uuid, goals: Vec<String>) -> Result<()> {
         let event = MemoryEvent::ProjectGoalUpdated { project_id, goals };
@@ -1086,14 +1457,69 @@ impl MemoryService {
 
     /// Archive a project's memory files
     pub async fn archive_project(&self, project_id: Uuid) -> Result<std::path::PathBuf> {
-        let mut cline_service = self.cline_memory_service.write().await;
-        cline_service.archive_project(project_id).await
+        let archive = {
+            let mut cline_service = self.cline_memory_service.write().await;
+            cline_service.archive_project(project_id).await?
+        };
+        self.audit_export(project_id, &archive).await;
+        Ok(archive)
     }
 
     /// Create backup of project files
     pub async fn backup_project(&self, project_id: Uuid) -> Result<std::path::PathBuf> {
-        let cline_service = self.cline_memory_service.read().await;
-        cline_service.backup_files(project_id).await
+        let backup = {
+            let cline_service = self.cline_memory_service.read().await;
+            cline_service.backup_files(project_id).await?
+        };
+        self.audit_export(project_id, &backup).await;
+        Ok(backup)
+    }
+
+    async fn audit_search(&self, query: &str, result_count: usize, memory_types: &[MemoryType]) {
+        self.audit_event(
+            None,
+            AuditEventData::MemorySearch(MemorySearchData {
+                query: query.to_string(),
+                result_count,
+                memory_types: memory_types
+                    .iter()
+                    .map(|memory_type| format!("{:?}", memory_type))
+                    .collect(),
+            }),
+        )
+        .await;
+    }
+
+    /// Audit project memory files copied out to `destination`
+    async fn audit_export(&self, project_id: Uuid, destination: &std::path::Path) {
+        if self.audit.is_none() {
+            return;
+        }
+        let file_count = walkdir::WalkDir::new(destination)
+            .into_iter()
+            .flatten()
+            .filter(|entry| entry.file_type().is_file())
+            .count();
+        self.audit_event(
+            None,
+            AuditEventData::MemoryExport(MemoryExportData {
+                scope: "project".to_string(),
+                target: project_id.to_string(),
+                destination: destination.display().to_string(),
+                file_count,
+            }),
+        )
+        .await;
+    }
+
+    /// Record `event_data` in the attached audit system; failing to audit
+    /// does not fail the operation
+    async fn audit_event(&self, session_id: Option<Uuid>, event_data: AuditEventData) {
+        if let Some(audit) = &self.audit {
+            if let Err(e) = audit.record(session_id, event_data).await {
+                warn!("Failed to audit memory operation: {}", e);
+            }
+        }
     }
 
     /// List all projects with memory files
@@ -1103,7 +1529,6 @@ impl MemoryService {
     }
 
     /// Emit memory event to update Cline files (internal method)
-    #[allow(dead_code)]
     async fn emit_memory_event(&self, event: MemoryEvent) -> Result<()> {
         let mut cline_service = self.cline_memory_service.write().await;
         cline_service.handle_event(event).await?;
@@ -1120,6 +1545,58 @@ impl MemoryService {
     }
 }
 
+/// Rough token count of a guidance section, four characters per token
+fn guidance_tokens(guidance: &GuidanceMatch) -> usize {
+    guidance.content.len() / 4
+}
+
+/// The guidance to inject and how many matching sections were left out.
+/// `always` sections come first whatever their size; the other sections
+/// are ranked by their best match and added while they fit in `budget`
+/// tokens.
+fn assemble_guidance(
+    always: Vec<GuidanceMatch>,
+    matches: Vec<GuidanceMatch>,
+    budget: usize,
+) -> (Vec<GuidanceMatch>, usize) {
+    let mut best: HashMap<String, GuidanceMatch> = HashMap::new();
+    for guidance in matches {
+        match best.get(&guidance.section_title) {
+            Some(existing) if existing.score >= guidance.score => {}
+            _ => {
+                best.insert(guidance.section_title.clone(), guidance);
+            }
+        }
+    }
+    let mut ranked: Vec<GuidanceMatch> = best
+        .into_values()
+        .filter(|guidance| {
+            !always
+                .iter()
+                .any(|a| a.section_title == guidance.section_title)
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.section_title.cmp(&b.section_title))
+    });
+
+    let mut used: usize = always.iter().map(guidance_tokens).sum();
+    let mut assembled = always;
+    let mut omitted = 0;
+    for guidance in ranked {
+        let tokens = guidance_tokens(&guidance);
+        if used + tokens <= budget {
+            used += tokens;
+            assembled.push(guidance);
+        } else {
+            omitted += 1;
+        }
+    }
+    (assembled, omitted)
+}
+
 /// Combined search results from memory
 #[derive(Debug)]
 pub struct MemorySearchResults {
@@ -1284,6 +1761,50 @@ pub enum MemoryError {
 }
 
 impl ErrorInfo for MemoryError {
+    fn code(&self) -> &'static str {
+        match self {
+            MemoryError::SessionNotFound { .. } => "FEN-5001",
+            MemoryError::SessionNotActive { .. } => "FEN-5002",
+            MemoryError::SessionLimitExceeded { .. } => "FEN-5003",
+            MemoryError::SessionAlreadyExists { .. } => "FEN-5004",
+            MemoryError::StorageInitFailed { .. } => "FEN-5005",
+            MemoryError::StorageCorrupted { .. } => "FEN-5006",
+            MemoryError::StorageSaveFailed { .. } => "FEN-5007",
+            MemoryError::StorageLoadFailed { .. } => "FEN-5008",
+            MemoryError::StorageCapacityExceeded { .. } => "FEN-5009",
+            MemoryError::SearchFailed { .. } => "FEN-5010",
+            MemoryError::IndexCorrupted { .. } => "FEN-5011",
+            MemoryError::IndexInitFailed { .. } => "FEN-5012",
+            MemoryError::InvalidSearchQuery { .. } => "FEN-5013",
+            MemoryError::SearchTimeout { .. } => "FEN-5014",
+            MemoryError::FileNotFound { .. } => "FEN-5015",
+            MemoryError::FileWatchFailed { .. } => "FEN-5016",
+            MemoryError::FileParsingFailed { .. } => "FEN-5017",
+            MemoryError::UnsupportedFileFormat { .. } => "FEN-5018",
+            MemoryError::TranscriptAddFailed { .. } => "FEN-5019",
+            MemoryError::TranscriptSegmentNotFound { .. } => "FEN-5020",
+            MemoryError::TranscriptCorrupted { .. } => "FEN-5021",
+            MemoryError::TranscriptSummaryFailed { .. } => "FEN-5022",
+            MemoryError::InvalidConfiguration { .. } => "FEN-5023",
+            MemoryError::ConfigurationNotFound { .. } => "FEN-5024",
+            MemoryError::ConfigurationLoadFailed { .. } => "FEN-5025",
+            MemoryError::AgentsConfigNotFound { .. } => "FEN-5026",
+            MemoryError::AgentsConfigParsingFailed { .. } => "FEN-5027",
+            MemoryError::GuidanceMatchingFailed { .. } => "FEN-5028",
+            MemoryError::InjectionFailed { .. } => "FEN-5029",
+            MemoryError::ContextExtractionFailed { .. } => "FEN-5030",
+            MemoryError::SerializationFailed { .. } => "FEN-5031",
+            MemoryError::DataValidationFailed { .. } => "FEN-5032",
+            MemoryError::DataCorrupted { .. } => "FEN-5033",
+            MemoryError::ServiceInitFailed { .. } => "FEN-5034",
+            MemoryError::ServiceUnavailable { .. } => "FEN-5035",
+            MemoryError::MemoryLimitExceeded { .. } => "FEN-5036",
+            MemoryError::OperationTimeout { .. } => "FEN-5037",
+            MemoryError::ConcurrencyLimitExceeded { .. } => "FEN-5038",
+            MemoryError::Io { .. } => "FEN-5039",
+        }
+    }
+
     fn category(&self) -> ErrorCategory {
         match self {
             // User errors
@@ -1499,7 +2020,7 @@ impl ErrorInfo for MemoryError {
 
 impl From<MemoryError> for FennecError {
     fn from(err: MemoryError) -> Self {
-        FennecError::Memory(Box::new(err))
+        FennecError::Memory(Box::new(DomainError::new(err)))
     }
 }
 
@@ -1526,11 +2047,19 @@ mod tests {
     #[tokio::test]
     async fn test_session_lifecycle() {
         let service = MemoryService::new().await.unwrap();
-        let session = Session::new();
+        let session = Session::new()
+            .with_title("Lifecycle")
+            .with_workspace("/tmp/project");
         let session_id = session.id;
 
         // Start session
         service.start_session(session).await.unwrap();
+        let stored = service.load_transcript(session_id).await.unwrap().unwrap();
+        assert_eq!(stored.metadata.title.as_deref(), Some("Lifecycle"));
+        assert_eq!(
+            stored.metadata.workspace_path,
+            Some(std::path::PathBuf::from("/tmp/project"))
+        );
 
         // Add message
         service
@@ -1547,6 +2076,7 @@ mod tests {
 
         // Stop session
         service.stop_session(session_id).await.unwrap();
+        service.delete_session(session_id).await.unwrap();
     }
 
     #[tokio::test]
@@ -1557,4 +2087,510 @@ mod tests {
         let results = service.search("test query", Some(5)).await.unwrap();
         assert_eq!(results.query, "test query");
     }
+
+    #[tokio::test]
+    async fn test_overdue_urgent_reminders_are_injected_as_guidance() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut notes = NotesStore::with_storage_dir(temp_dir.path()).unwrap();
+        let now = chrono::Utc::now();
+        let hours = chrono::Duration::hours;
+
+        for (title, priority, due_in) in [
+            ("Overdue critical", NotePriority::Critical, -2),
+            ("Overdue low", NotePriority::Low, -2),
+            ("Upcoming high", NotePriority::High, 2),
+        ] {
+            let id = notes
+                .create_note(
+                    None,
+                    title.to_string(),
+                    format!("{} content", title),
+                    crate::notes::NoteCategory::Reminder,
+                )
+                .await
+                .unwrap();
+            notes.set_priority(id, priority).await.unwrap();
+            notes.set_due(id, Some(now + hours(due_in))).await.unwrap();
+        }
+
+        let service = MemoryService::new().await.unwrap().with_notes_store(notes);
+
+        let titles = |guidance: &[GuidanceMatch]| -> Vec<String> {
+            guidance
+                .iter()
+                .filter(|g| matches!(g.match_type, MatchType::Reminder))
+                .map(|g| g.section_title.clone())
+                .collect()
+        };
+        let reminders = service.overdue_reminders(now).await.unwrap();
+        assert_eq!(titles(&reminders).len(), 1);
+        assert!(titles(&reminders)[0].starts_with("Reminder: Overdue critical (due "));
+        assert_eq!(reminders[0].content, "Overdue critical content");
+
+        // Once the high priority reminder falls due it is injected too
+        let reminders = service.overdue_reminders(now + hours(3)).await.unwrap();
+        assert_eq!(titles(&reminders).len(), 2);
+
+        let session = Session::new();
+        let session_id = session.id;
+        service.start_session(session).await.unwrap();
+        let injection = service
+            .get_memory_injection(session_id, Some("anything"))
+            .await
+            .unwrap();
+        assert_eq!(titles(&injection.guidance).len(), 1);
+        assert!(matches!(
+            injection.guidance[0].match_type,
+            MatchType::Reminder
+        ));
+        service.stop_session(session_id).await.unwrap();
+        service.delete_session(session_id).await.unwrap();
+    }
+
+    /// A long AGENTS.md: filler sections first, one `priority: always`
+    /// section, and the section a migrations question needs last
+    fn long_agents_fixture() -> String {
+        let mut content = String::from(
+            "# Guidelines\n\n## Safety\n---\npriority: always\n---\nNever push to main.\n",
+        );
+        for i in 0..30 {
+            content.push_str(&format!(
+                "\n## Topic {}\n{}\n",
+                i,
+                "The database layer owns schema changes; see the migrations notes. ".repeat(8)
+            ));
+        }
+        content.push_str("\n## Database migrations\nRun `sqlx migrate run` before the tests.\n");
+        content
+    }
+
+    #[tokio::test]
+    async fn test_guidance_is_ranked_and_capped_by_budget() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let agents_path = temp_dir.path().join("AGENTS.md");
+        std::fs::write(&agents_path, long_agents_fixture()).unwrap();
+        let notes = NotesStore::with_storage_dir(temp_dir.path().join("notes")).unwrap();
+        let mut service = MemoryService::new().await.unwrap().with_notes_store(notes);
+        service
+            .agents_service
+            .load_path(&agents_path)
+            .await
+            .unwrap();
+        service.config.guidance_token_budget = 300;
+
+        let injection = service
+            .get_memory_injection(Uuid::new_v4(), Some("database migrations"))
+            .await
+            .unwrap();
+        let titles: Vec<&str> = injection
+            .guidance
+            .iter()
+            .map(|g| g.section_title.as_str())
+            .collect();
+
+        // The always section leads even with no score, then the best match,
+        // though it comes last in the file
+        assert_eq!(titles[..2], ["Safety", "Database migrations"]);
+        assert_eq!(injection.guidance[0].score, 0);
+        assert!(matches!(
+            injection.guidance[0].match_type,
+            MatchType::Always
+        ));
+        assert!(injection.omitted_guidance > 0, "{:?}", titles);
+        let used: usize = injection.guidance.iter().map(guidance_tokens).sum();
+        assert!(used <= 300, "{} tokens", used);
+        let mut unique = titles.clone();
+        unique.dedup();
+        assert_eq!(unique.len(), titles.len());
+    }
+
+    #[test]
+    fn test_always_sections_exceed_the_budget_alone() {
+        let section = |title: &str, score: i64, len: usize| GuidanceMatch {
+            section_title: title.to_string(),
+            content: "x".repeat(len),
+            score,
+            match_type: MatchType::Content,
+        };
+
+        let (guidance, omitted) = assemble_guidance(
+            vec![section("Safety", 0, 400)],
+            vec![
+                section("Style", 50, 40),
+                section("Safety", 90, 400),
+                section("Testing", 80, 40),
+                section("Testing", 20, 40),
+            ],
+            50,
+        );
+        let titles: Vec<&str> = guidance.iter().map(|g| g.section_title.as_str()).collect();
+        assert_eq!(titles, ["Safety"]);
+        assert_eq!(omitted, 2);
+
+        let (guidance, omitted) = assemble_guidance(
+            Vec::new(),
+            vec![section("Style", 50, 40), section("Testing", 80, 40)],
+            15,
+        );
+        let titles: Vec<&str> = guidance.iter().map(|g| g.section_title.as_str()).collect();
+        assert_eq!(titles, ["Testing"]);
+        assert_eq!(omitted, 1);
+    }
+
+    /// A service keeping its transcripts in `dir`
+    async fn service_in(dir: &std::path::Path) -> MemoryService {
+        MemoryService::new()
+            .await
+            .unwrap()
+            .with_transcript_store(
+                TranscriptStore::with_storage_dir(dir.join("transcripts")).unwrap(),
+            )
+            .with_notes_store(NotesStore::with_storage_dir(dir.join("notes")).unwrap())
+            .with_cline_memory_service(
+                ClineMemoryFileService::with_storage_dir(dir.join("projects")).unwrap(),
+            )
+    }
+
+    fn replayed_messages() -> Vec<(MessageRole, String)> {
+        (0..12)
+            .map(|i| {
+                let role = if i % 2 == 0 {
+                    MessageRole::User
+                } else {
+                    MessageRole::Assistant
+                };
+                (role, format!("step {} of the tokio replay", i))
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_add_messages_writes_once_with_the_same_result() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let one_by_one = service_in(&temp_dir.path().join("single")).await;
+        let batched = service_in(&temp_dir.path().join("batch")).await;
+        let session_id = Uuid::new_v4();
+
+        for (role, content) in replayed_messages() {
+            one_by_one
+                .add_message(session_id, role, content)
+                .await
+                .unwrap();
+        }
+        batched
+            .add_messages(session_id, replayed_messages())
+            .await
+            .unwrap();
+
+        assert_eq!(one_by_one.transcript_store.read().await.write_count(), 12);
+        assert_eq!(batched.transcript_store.read().await.write_count(), 1);
+        assert_eq!(one_by_one.revision(), 12);
+        assert_eq!(batched.revision(), 1);
+
+        let single = one_by_one
+            .load_transcript(session_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let batch = batched.load_transcript(session_id).await.unwrap().unwrap();
+        let contents = |transcript: &crate::transcript::MemoryTranscript| {
+            transcript
+                .transcript
+                .messages
+                .iter()
+                .map(|m| (format!("{:?}", m.role), m.content.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(contents(&batch), contents(&single));
+        assert_eq!(
+            batch.transcript.messages[11].content,
+            "step 11 of the tokio replay"
+        );
+        assert_eq!(batch.metadata.message_count, single.metadata.message_count);
+        assert_eq!(
+            batch.metadata.estimated_tokens,
+            single.metadata.estimated_tokens
+        );
+        assert_eq!(
+            batch.conversation_context.technologies_mentioned,
+            single.conversation_context.technologies_mentioned
+        );
+    }
+
+    #[tokio::test]
+    async fn test_record_command_executions_writes_once_in_order() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let service = service_in(temp_dir.path()).await;
+        let session_id = Uuid::new_v4();
+        service
+            .add_message(session_id, MessageRole::User, "run the checks".to_string())
+            .await
+            .unwrap();
+        let writes_before = service.transcript_store.read().await.write_count();
+
+        let records: Vec<CommandRecord> = ["cargo fmt", "cargo clippy", "cargo test"]
+            .into_iter()
+            .map(|command| CommandRecord {
+                command: command.to_string(),
+                result: crate::transcript::ExecutionResult {
+                    success: true,
+                    summary: format!("{} passed", command),
+                    details: None,
+                    files_affected: Vec::new(),
+                    follow_up_actions: Vec::new(),
+                },
+                output: None,
+                error: None,
+                duration: None,
+                exit_code: Some(0),
+                triggered_by_message: None,
+            })
+            .collect();
+        let ids = service
+            .record_command_executions(session_id, records)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            service.transcript_store.read().await.write_count(),
+            writes_before + 1
+        );
+        let transcript = service.load_transcript(session_id).await.unwrap().unwrap();
+        let recorded: Vec<(Uuid, &str)> = transcript
+            .command_executions
+            .iter()
+            .map(|execution| (execution.id, execution.command.as_str()))
+            .collect();
+        assert_eq!(
+            recorded,
+            [
+                (ids[0], "cargo fmt"),
+                (ids[1], "cargo clippy"),
+                (ids[2], "cargo test")
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_forget_and_export_are_audited() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = fennec_core::config::Config::default();
+        config.security.audit_log_enabled = false;
+        let sink = Arc::new(fennec_security::InMemoryAuditSink::new());
+        let audit = AuditSystem::new(&config)
+            .await
+            .unwrap()
+            .with_sink(sink.clone());
+        let service = service_in(temp_dir.path())
+            .await
+            .with_audit(Arc::new(audit));
+
+        let session = Session::new();
+        let session_id = session.id;
+        service.start_session(session).await.unwrap();
+        service
+            .add_messages(session_id, replayed_messages())
+            .await
+            .unwrap();
+
+        service
+            .search("tokio password=hunter2", Some(5))
+            .await
+            .unwrap();
+        service.delete_session(session_id).await.unwrap();
+        let project_id = Uuid::new_v4();
+        service.initialize_project_memory(project_id).await.unwrap();
+        let backup = service.backup_project(project_id).await.unwrap();
+
+        let events = sink.events();
+        assert_eq!(events.len(), 3, "{:?}", events);
+        let AuditEventData::MemorySearch(search) = &events[0].data else {
+            panic!("expected a search, got {:?}", events[0].data);
+        };
+        assert_eq!(search.query, "tokio password=[REDACTED]");
+        assert_eq!(search.memory_types, ["Guidance", "Transcripts"]);
+        let AuditEventData::MemoryDeletion(deletion) = &events[1].data else {
+            panic!("expected a deletion, got {:?}", events[1].data);
+        };
+        assert_eq!(events[1].metadata.session_id, session_id);
+        assert_eq!(deletion.scope, "session");
+        assert_eq!(deletion.target, session_id.to_string());
+        assert_eq!(
+            (deletion.transcripts_deleted, deletion.messages_deleted),
+            (1, 12)
+        );
+        let AuditEventData::MemoryExport(export) = &events[2].data else {
+            panic!("expected an export, got {:?}", events[2].data);
+        };
+        assert_eq!(export.scope, "project");
+        assert_eq!(export.target, project_id.to_string());
+        assert_eq!(export.destination, backup.display().to_string());
+        assert!(export.file_count > 0);
+    }
+
+    #[test]
+    fn test_memory_error_keeps_its_code_as_fennec_error() {
+        let error = MemoryError::SessionNotFound {
+            session_id: Uuid::new_v4(),
+        };
+        let code = error.code();
+        assert!(code.starts_with("FEN-5"));
+
+        let fennec_error: FennecError = error.into();
+        assert_eq!(fennec_error.code(), code);
+        assert_eq!(
+            fennec_error.user_message(),
+            "Session not found. Please start a new session."
+        );
+    }
+
+    /// A service kept under `data_dir` in `scope`, with one session
+    /// holding `message`
+    async fn scoped_service(
+        data_dir: &std::path::Path,
+        scope: MemoryScope,
+        message: &str,
+    ) -> (MemoryService, Uuid) {
+        let config = MemoryConfig {
+            scope,
+            data_dir: Some(data_dir.to_path_buf()),
+            ..MemoryConfig::default()
+        };
+        let service = MemoryService::with_config(config).await.unwrap();
+        let session = Session::new();
+        let session_id = session.id;
+        service.start_session(session).await.unwrap();
+        service
+            .add_message(session_id, MessageRole::User, message.to_string())
+            .await
+            .unwrap();
+        (service, session_id)
+    }
+
+    fn transcript_search(query: &str, session_filter: SessionFilter) -> AdvancedSearchCriteria {
+        AdvancedSearchCriteria {
+            query: query.to_string(),
+            session_filter: Some(session_filter),
+            time_filter: None,
+            memory_types: vec![MemoryType::Transcripts, MemoryType::MemoryFiles],
+            scoring_strategy: ScoringStrategy::FuzzyMatch,
+            limit: None,
+            min_score: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_workspace_scopes_do_not_leak() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("data");
+        let client_a = MemoryScope::workspace(temp_dir.path().join("client-a"));
+        let client_b = MemoryScope::workspace(temp_dir.path().join("client-b"));
+        let (service_a, session_a) = scoped_service(
+            &data_dir,
+            client_a.clone(),
+            "Acmecorp invoices are reconciled nightly",
+        )
+        .await;
+        let (service_b, session_b) = scoped_service(
+            &data_dir,
+            client_b,
+            "Globexcorp dashboards are rendered weekly",
+        )
+        .await;
+        assert_eq!(service_a.scope(), &client_a);
+        assert!(client_a
+            .storage_root(&data_dir)
+            .join(TRANSCRIPTS_DIR)
+            .join(format!("{}.json", session_a))
+            .exists());
+
+        // Each workspace finds its own conversation and nothing of the other
+        for (service, own, other) in [
+            (&service_a, "Acmecorp", "Globexcorp"),
+            (&service_b, "Globexcorp", "Acmecorp"),
+        ] {
+            let found = service.search(own, None).await.unwrap();
+            assert_eq!(found.transcript_matches.len(), 1);
+            assert!(service
+                .search(other, None)
+                .await
+                .unwrap()
+                .transcript_matches
+                .is_empty());
+            assert!(service
+                .search_advanced(transcript_search(other, SessionFilter::CrossSession))
+                .await
+                .unwrap()
+                .results
+                .is_empty());
+        }
+
+        let injection = service_b
+            .get_memory_injection(session_b, Some("Acmecorp"))
+            .await
+            .unwrap();
+        assert!(injection.conversation_history.is_empty());
+        let injection = service_a
+            .get_memory_injection(session_a, Some("Acmecorp"))
+            .await
+            .unwrap();
+        assert_eq!(injection.conversation_history.len(), 1);
+
+        let engine = crate::context::ContextEngine::new(Arc::new(service_b));
+        let bundle = engine
+            .inject_context(crate::context::ContextRequest {
+                session_id: session_b,
+                conversation_context: ConversationContext::default(),
+                recent_messages: Vec::new(),
+                explicit_query: Some("Acmecorp".to_string()),
+                preferred_types: vec![MemoryType::Transcripts],
+                use_case: crate::context::ContextUseCase::AIPrompt,
+                size_constraints: None,
+            })
+            .await
+            .unwrap();
+        assert!(bundle
+            .items
+            .iter()
+            .all(|item| !item.content.contains("Acmecorp")));
+    }
+
+    #[tokio::test]
+    async fn test_global_scope_is_searched_only_on_request() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("data");
+        let (_global, _) = scoped_service(
+            &data_dir,
+            MemoryScope::Global,
+            "Initechcorp reports need cover sheets",
+        )
+        .await;
+        let (workspace, _) = scoped_service(
+            &data_dir,
+            MemoryScope::workspace(temp_dir.path().join("client")),
+            "Client notes",
+        )
+        .await;
+
+        let cross_session = workspace
+            .search_advanced(transcript_search(
+                "Initechcorp",
+                SessionFilter::CrossSession,
+            ))
+            .await
+            .unwrap();
+        assert!(cross_session.results.is_empty());
+
+        let global = workspace
+            .search_advanced(transcript_search(
+                "Initechcorp",
+                SessionFilter::Scope(MemoryScope::Global),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(global.results.len(), 1);
+        assert!(global.results[0]
+            .content_preview
+            .contains("Initechcorp reports"));
+    }
 }
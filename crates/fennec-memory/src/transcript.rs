@@ -1,14 +1,25 @@
 use anyhow::{Context, Result};
 use directories::ProjectDirs;
+use fennec_core::session::Session;
 use fennec_core::transcript::{Message, MessageRole, Transcript};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use tokio::fs;
 use tracing::{debug, info};
 use uuid::Uuid;
 
+use crate::extraction;
+
+/// Sidecar file in the storage directory mapping each tag to the sessions
+/// carrying it
+const TAG_INDEX_FILE: &str = "tag_index.json";
+
+/// Tag to the sessions carrying it, as kept in [`TAG_INDEX_FILE`]
+type TagIndex = BTreeMap<String, BTreeSet<Uuid>>;
+
 /// Extended transcript with memory-specific metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryTranscript {
@@ -45,6 +56,12 @@ pub struct TranscriptMetadata {
     pub estimated_tokens: usize,
     /// Whether this transcript is active (current session)
     pub is_active: bool,
+    /// Title of the session
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Workspace the session was started in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace_path: Option<PathBuf>,
 }
 
 /// Conversation context extracted from the transcript
@@ -152,6 +169,9 @@ pub enum SegmentType {
     Review,
     /// General conversation
     General,
+    /// Messages compacted out of the prompt sent to the provider; they stay
+    /// in the transcript
+    Compaction,
 }
 
 /// Filters for searching transcripts
@@ -171,6 +191,49 @@ pub struct TranscriptSearchFilters {
     pub limit: Option<usize>,
 }
 
+impl TranscriptSearchFilters {
+    /// Whether `transcript` passes every filter other than the limit
+    fn matches(&self, transcript: &MemoryTranscript) -> bool {
+        if self
+            .session_id
+            .is_some_and(|id| id != transcript.metadata.session_id)
+        {
+            return false;
+        }
+
+        if let Some((start_date, end_date)) = self.date_range {
+            if transcript.metadata.created_at < start_date
+                || transcript.metadata.created_at > end_date
+            {
+                return false;
+            }
+        }
+
+        if let Some(ref technologies) = self.technologies {
+            if !technologies.iter().any(|tech| {
+                transcript
+                    .conversation_context
+                    .technologies_mentioned
+                    .contains(tech)
+            }) {
+                return false;
+            }
+        }
+
+        if let Some(ref segment_type) = self.segment_type {
+            if !transcript
+                .segments
+                .iter()
+                .any(|s| &s.segment_type == segment_type)
+            {
+                return false;
+            }
+        }
+
+        !self.active_only || transcript.metadata.is_active
+    }
+}
+
 /// Storage service for managing conversation transcripts
 #[derive(Debug)]
 pub struct TranscriptStore {
@@ -180,12 +243,22 @@ pub struct TranscriptStore {
     cache: HashMap<Uuid, MemoryTranscript>,
     /// Maximum cache size
     max_cache_size: usize,
+    /// Whether added messages are scanned for files, technologies and
+    /// decisions
+    extract_context: bool,
+    /// Transcripts written to disk by this store
+    writes: usize,
 }
 
 impl TranscriptStore {
     /// Create a new transcript store
     pub fn new() -> Result<Self> {
-        let storage_dir = Self::get_storage_dir()?;
+        Self::with_storage_dir(Self::get_storage_dir()?)
+    }
+
+    /// Create a transcript store kept in `storage_dir`
+    pub fn with_storage_dir(storage_dir: impl Into<PathBuf>) -> Result<Self> {
+        let storage_dir = storage_dir.into();
 
         // Ensure storage directory exists
         std::fs::create_dir_all(&storage_dir).with_context(|| {
@@ -199,9 +272,16 @@ impl TranscriptStore {
             storage_dir,
             cache: HashMap::new(),
             max_cache_size: 100, // Keep up to 100 transcripts in memory
+            extract_context: true,
+            writes: 0,
         })
     }
 
+    /// Turn extracting conversation context from added messages on or off
+    pub fn set_context_extraction(&mut self, enabled: bool) {
+        self.extract_context = enabled;
+    }
+
     /// Get the storage directory for transcripts
     fn get_storage_dir() -> Result<PathBuf> {
         let proj_dirs =
@@ -216,6 +296,8 @@ impl TranscriptStore {
 
         // Write to disk
         self.write_transcript_to_disk(&transcript).await?;
+        self.writes += 1;
+        self.index_tags(session_id, &transcript.tags).await?;
 
         // Update cache
         self.cache.insert(session_id, transcript);
@@ -229,6 +311,11 @@ impl TranscriptStore {
         Ok(())
     }
 
+    /// Number of transcripts this store has written to disk
+    pub fn write_count(&self) -> usize {
+        self.writes
+    }
+
     /// Load a transcript by session ID
     pub async fn load_transcript(&mut self, session_id: Uuid) -> Result<Option<MemoryTranscript>> {
         // Check cache first
@@ -280,6 +367,8 @@ impl TranscriptStore {
                         message_count: transcript.messages.len(),
                         estimated_tokens: Self::estimate_tokens(&transcript),
                         is_active: true,
+                        title: None,
+                        workspace_path: None,
                     },
                     conversation_context: ConversationContext::default(),
                     command_executions: Vec::new(),
@@ -297,6 +386,15 @@ impl TranscriptStore {
         session_id: Uuid,
         role: MessageRole,
         content: String,
+    ) -> Result<()> {
+        self.add_messages(session_id, vec![(role, content)]).await
+    }
+
+    /// Add `messages` to a transcript in order, writing it once
+    pub async fn add_messages(
+        &mut self,
+        session_id: Uuid,
+        messages: Vec<(MessageRole, String)>,
     ) -> Result<()> {
         let mut memory_transcript =
             self.load_transcript(session_id)
@@ -313,13 +411,31 @@ impl TranscriptStore {
                         message_count: 0,
                         estimated_tokens: 0,
                         is_active: true,
+                        title: None,
+                        workspace_path: None,
                     },
                     conversation_context: ConversationContext::default(),
                     command_executions: Vec::new(),
                     segments: Vec::new(),
                 });
 
-        memory_transcript.transcript.add_message(role, content);
+        for (role, content) in messages {
+            if self.extract_context {
+                let extracted = extraction::extract_context(
+                    &content,
+                    memory_transcript.metadata.workspace_path.as_deref(),
+                );
+                let context = &mut memory_transcript.conversation_context;
+                extraction::merge_unique(&mut context.files_mentioned, extracted.files);
+                extraction::merge_unique(
+                    &mut context.technologies_mentioned,
+                    extracted.technologies,
+                );
+                extraction::merge_unique(&mut context.decisions_made, extracted.decisions);
+            }
+
+            memory_transcript.transcript.add_message(role, content);
+        }
         memory_transcript.metadata.updated_at = chrono::Utc::now();
         memory_transcript.metadata.message_count = memory_transcript.transcript.messages.len();
         memory_transcript.metadata.estimated_tokens =
@@ -328,6 +444,36 @@ impl TranscriptStore {
         self.store_transcript(memory_transcript).await
     }
 
+    /// Store the title and workspace of `session` with its transcript
+    pub async fn record_session(&mut self, session: &Session) -> Result<()> {
+        let mut memory_transcript =
+            self.load_transcript(session.id)
+                .await?
+                .unwrap_or_else(|| MemoryTranscript {
+                    transcript: Transcript::new(session.id),
+                    tags: Vec::new(),
+                    summary: None,
+                    topics: Vec::new(),
+                    metadata: TranscriptMetadata {
+                        session_id: session.id,
+                        created_at: session.created_at,
+                        updated_at: chrono::Utc::now(),
+                        message_count: 0,
+                        estimated_tokens: 0,
+                        is_active: true,
+                        title: None,
+                        workspace_path: None,
+                    },
+                    conversation_context: ConversationContext::default(),
+                    command_executions: Vec::new(),
+                    segments: Vec::new(),
+                });
+
+        memory_transcript.metadata.title = session.title.clone();
+        memory_transcript.metadata.workspace_path = session.workspace_path.clone();
+        self.store_transcript(memory_transcript).await
+    }
+
     /// List all stored transcripts
     pub async fn list_transcripts(&self) -> Result<Vec<TranscriptMetadata>> {
         let mut transcripts = Vec::new();
@@ -359,6 +505,58 @@ impl TranscriptStore {
         Ok(transcripts)
     }
 
+    /// Transcripts of sessions in `workspace` that mentioned `target` or a
+    /// file under it, most recently updated first
+    pub async fn sessions_mentioning(
+        &self,
+        workspace: &Path,
+        target: &Path,
+    ) -> Result<Vec<TranscriptMetadata>> {
+        let target = workspace.join(target);
+        let canonical = workspace.canonicalize().ok();
+        let mut sessions = Vec::new();
+
+        let mut dir = fs::read_dir(&self.storage_dir).await.with_context(|| {
+            format!(
+                "Failed to read storage directory: {}",
+                self.storage_dir.display()
+            )
+        })?;
+
+        while let Some(entry) = dir.next_entry().await? {
+            let path = entry.path();
+            let Some(session_id) = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| Uuid::parse_str(s).ok())
+            else {
+                continue;
+            };
+            let Ok(Some(transcript)) = self.load_transcript_from_disk(session_id).await else {
+                continue;
+            };
+            let same_workspace = transcript
+                .metadata
+                .workspace_path
+                .as_deref()
+                .is_some_and(|path| path == workspace || path.canonicalize().ok() == canonical);
+            if !same_workspace {
+                continue;
+            }
+            let mentioned = transcript
+                .conversation_context
+                .files_mentioned
+                .iter()
+                .any(|file| workspace.join(file).starts_with(&target));
+            if mentioned {
+                sessions.push(transcript.metadata);
+            }
+        }
+
+        sessions.sort_by_key(|metadata| std::cmp::Reverse(metadata.updated_at));
+        Ok(sessions)
+    }
+
     /// Search transcripts by content
     pub async fn search_transcripts(
         &self,
@@ -455,10 +653,117 @@ impl TranscriptStore {
             })?;
             info!("Deleted transcript for session: {}", session_id);
         }
+        self.index_tags(session_id, &[]).await?;
 
         Ok(())
     }
 
+    /// Transcripts tagged with `tag`, most recently updated first
+    pub async fn list_transcripts_by_tag(&mut self, tag: &str) -> Result<Vec<TranscriptMetadata>> {
+        let index = self.load_tag_index().await?;
+        let mut transcripts = Vec::new();
+        for session_id in index.get(tag).into_iter().flatten() {
+            if let Some(transcript) = self.load_transcript(*session_id).await? {
+                transcripts.push(transcript.metadata);
+            }
+        }
+
+        transcripts.sort_by_key(|metadata| std::cmp::Reverse(metadata.updated_at));
+        Ok(transcripts)
+    }
+
+    /// Every tag in use with the number of transcripts carrying it, most
+    /// used first
+    pub async fn list_all_tags(&self) -> Result<Vec<(String, usize)>> {
+        let mut tags: Vec<(String, usize)> = self
+            .load_tag_index()
+            .await?
+            .into_iter()
+            .map(|(tag, sessions)| (tag, sessions.len()))
+            .collect();
+        tags.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        Ok(tags)
+    }
+
+    /// Remove `tag` from a transcript, returning whether it carried it
+    pub async fn remove_tag(&mut self, session_id: Uuid, tag: &str) -> Result<bool> {
+        let Some(mut transcript) = self.load_transcript(session_id).await? else {
+            return Ok(false);
+        };
+        let before = transcript.tags.len();
+        transcript.tags.retain(|t| t != tag);
+        if transcript.tags.len() == before {
+            return Ok(false);
+        }
+
+        transcript.metadata.updated_at = chrono::Utc::now();
+        self.store_transcript(transcript).await?;
+        Ok(true)
+    }
+
+    /// Record `tags` as the tags of `session_id` in the tag index, only
+    /// rewriting it when something changed
+    async fn index_tags(&self, session_id: Uuid, tags: &[String]) -> Result<()> {
+        let mut index = self.load_tag_index().await?;
+        let mut changed = false;
+
+        index.retain(|tag, sessions| {
+            if !tags.contains(tag) {
+                changed |= sessions.remove(&session_id);
+            }
+            !sessions.is_empty()
+        });
+        for tag in tags {
+            changed |= index.entry(tag.clone()).or_default().insert(session_id);
+        }
+
+        if changed {
+            self.write_tag_index(&index).await?;
+        }
+        Ok(())
+    }
+
+    /// Load the tag index, building it from the stored transcripts when
+    /// there is none yet
+    async fn load_tag_index(&self) -> Result<TagIndex> {
+        let path = self.storage_dir.join(TAG_INDEX_FILE);
+        match fs::read_to_string(&path).await {
+            Ok(json) => serde_json::from_str(&json).with_context(|| {
+                format!("Failed to deserialize tag index from: {}", path.display())
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let index = self.rebuild_tag_index().await?;
+                self.write_tag_index(&index).await?;
+                Ok(index)
+            }
+            Err(e) => {
+                Err(e).with_context(|| format!("Failed to read tag index from: {}", path.display()))
+            }
+        }
+    }
+
+    /// Build the tag index by reading every stored transcript
+    async fn rebuild_tag_index(&self) -> Result<TagIndex> {
+        let mut index = TagIndex::new();
+        for metadata in self.list_transcripts().await? {
+            if let Some(transcript) = self.load_transcript_from_disk(metadata.session_id).await? {
+                for tag in transcript.tags {
+                    index.entry(tag).or_default().insert(metadata.session_id);
+                }
+            }
+        }
+        debug!("Rebuilt tag index with {} tags", index.len());
+        Ok(index)
+    }
+
+    async fn write_tag_index(&self, index: &TagIndex) -> Result<()> {
+        let path = self.storage_dir.join(TAG_INDEX_FILE);
+        let json = serde_json::to_string_pretty(index).context("Failed to serialize tag index")?;
+        fs::write(&path, json)
+            .await
+            .with_context(|| format!("Failed to write tag index to: {}", path.display()))
+    }
+
     /// Get the file path for a transcript
     fn get_transcript_path(&self, session_id: Uuid) -> PathBuf {
         self.storage_dir.join(format!("{}.json", session_id))
@@ -524,6 +829,80 @@ impl TranscriptStore {
             .sum()
     }
 
+    /// Replace the content of a message, keeping the old content in its
+    /// edit history
+    pub async fn edit_message(
+        &mut self,
+        session_id: Uuid,
+        message_id: Uuid,
+        new_content: String,
+    ) -> Result<()> {
+        let mut transcript = self
+            .load_transcript(session_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Transcript not found: {}", session_id))?;
+        transcript
+            .transcript
+            .edit_message(message_id, new_content)?;
+        Self::refresh_metadata(&mut transcript);
+        self.store_transcript(transcript).await
+    }
+
+    /// Delete a message, leaving a tombstone in its place
+    pub async fn delete_message(&mut self, session_id: Uuid, message_id: Uuid) -> Result<()> {
+        let mut transcript = self
+            .load_transcript(session_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Transcript not found: {}", session_id))?;
+        transcript.transcript.delete_message(message_id)?;
+        Self::refresh_metadata(&mut transcript);
+        self.store_transcript(transcript).await
+    }
+
+    /// Store a new transcript branching off `session_id` after
+    /// `message_id`, keeping the tags and context of the original
+    pub async fn branch_transcript(
+        &mut self,
+        session_id: Uuid,
+        message_id: Uuid,
+    ) -> Result<MemoryTranscript> {
+        let original = self
+            .load_transcript(session_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Transcript not found: {}", session_id))?;
+        let branch = original.transcript.branch_from(message_id)?;
+        let now = chrono::Utc::now();
+        let mut memory_transcript = MemoryTranscript {
+            metadata: TranscriptMetadata {
+                session_id: branch.session_id,
+                created_at: now,
+                updated_at: now,
+                message_count: 0,
+                estimated_tokens: 0,
+                is_active: true,
+                title: original.metadata.title,
+                workspace_path: original.metadata.workspace_path,
+            },
+            transcript: branch,
+            tags: original.tags,
+            summary: None,
+            topics: original.topics,
+            conversation_context: original.conversation_context,
+            command_executions: Vec::new(),
+            segments: Vec::new(),
+        };
+        Self::refresh_metadata(&mut memory_transcript);
+
+        self.store_transcript(memory_transcript.clone()).await?;
+        Ok(memory_transcript)
+    }
+
+    fn refresh_metadata(transcript: &mut MemoryTranscript) {
+        transcript.metadata.updated_at = chrono::Utc::now();
+        transcript.metadata.message_count = transcript.transcript.active_messages().count();
+        transcript.metadata.estimated_tokens = Self::estimate_tokens(&transcript.transcript);
+    }
+
     /// Add tags to a transcript
     pub async fn add_tags(&mut self, session_id: Uuid, tags: Vec<String>) -> Result<()> {
         if let Some(mut transcript) = self.load_transcript(session_id).await? {
@@ -549,6 +928,7 @@ impl TranscriptStore {
     }
 
     /// Add a command execution record to a transcript
+    #[allow(clippy::too_many_arguments)]
     pub async fn add_command_execution(
         &mut self,
         session_id: Uuid,
@@ -560,33 +940,60 @@ impl TranscriptStore {
         exit_code: Option<i32>,
         triggered_by_message: Option<Uuid>,
     ) -> Result<Uuid> {
-        let mut transcript = self
-            .load_transcript(session_id)
-            .await?
-            .ok_or_else(|| anyhow::anyhow!("Transcript not found: {}", session_id))?;
-
-        let execution_id = Uuid::new_v4();
-        let execution = CommandExecution {
-            id: execution_id,
+        let record = CommandRecord {
             command,
-            timestamp: chrono::Utc::now(),
             result,
             output,
             error,
             duration,
             exit_code,
-            working_directory: std::env::current_dir()
-                .ok()
-                .map(|p| p.to_string_lossy().to_string()),
-            environment: std::env::vars().collect(),
             triggered_by_message,
         };
+        let ids = self
+            .add_command_executions(session_id, vec![record])
+            .await?;
+        Ok(ids[0])
+    }
+
+    /// Add `records` to a transcript in order, writing it once, and return
+    /// their execution ids in the same order
+    pub async fn add_command_executions(
+        &mut self,
+        session_id: Uuid,
+        records: Vec<CommandRecord>,
+    ) -> Result<Vec<Uuid>> {
+        let mut transcript = self
+            .load_transcript(session_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Transcript not found: {}", session_id))?;
+
+        let working_directory = std::env::current_dir()
+            .ok()
+            .map(|p| p.to_string_lossy().to_string());
+        let environment = redacted_environment();
+        let mut ids = Vec::with_capacity(records.len());
+        for record in records {
+            let execution_id = Uuid::new_v4();
+            transcript.command_executions.push(CommandExecution {
+                id: execution_id,
+                command: record.command,
+                timestamp: chrono::Utc::now(),
+                result: record.result,
+                output: record.output,
+                error: record.error,
+                duration: record.duration,
+                exit_code: record.exit_code,
+                working_directory: working_directory.clone(),
+                environment: environment.clone(),
+                triggered_by_message: record.triggered_by_message,
+            });
+            ids.push(execution_id);
+        }
 
-        transcript.command_executions.push(execution);
         transcript.metadata.updated_at = chrono::Utc::now();
         self.store_transcript(transcript).await?;
 
-        Ok(execution_id)
+        Ok(ids)
     }
 
     /// Update conversation context
@@ -714,6 +1121,54 @@ impl TranscriptStore {
         Ok(())
     }
 
+    /// Mark the next `compacted` messages after the last compaction as
+    /// replaced by `summary` in the prompt, returning the segment's id
+    pub async fn add_compaction_segment(
+        &mut self,
+        session_id: Uuid,
+        compacted: usize,
+        summary: String,
+    ) -> Result<Uuid> {
+        let mut transcript = self
+            .load_transcript(session_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Transcript not found: {}", session_id))?;
+
+        let messages: Vec<&Message> = transcript.transcript.active_messages().collect();
+        let start = transcript
+            .segments
+            .iter()
+            .filter(|s| s.segment_type == SegmentType::Compaction)
+            .filter_map(|s| s.end_message_id)
+            .filter_map(|id| messages.iter().position(|m| m.id == id))
+            .max()
+            .map_or(0, |end| end + 1);
+        let span = &messages[start.min(messages.len())..(start + compacted).min(messages.len())];
+        let (Some(first), Some(last)) = (span.first(), span.last()) else {
+            anyhow::bail!("No messages left to compact in session {}", session_id);
+        };
+
+        let segment_id = Uuid::new_v4();
+        let segment = TranscriptSegment {
+            id: segment_id,
+            start_message_id: first.id,
+            end_message_id: Some(last.id),
+            title: format!("Compacted {} messages", span.len()),
+            summary,
+            context: ConversationContext::default(),
+            key_outcomes: Vec::new(),
+            segment_type: SegmentType::Compaction,
+            created_at: chrono::Utc::now(),
+            estimated_tokens: span.iter().map(|m| m.content.len() / 4).sum(),
+        };
+
+        transcript.segments.push(segment);
+        transcript.metadata.updated_at = chrono::Utc::now();
+        self.store_transcript(transcript).await?;
+
+        Ok(segment_id)
+    }
+
     /// Search transcripts with advanced filters
     pub async fn search_transcripts_filtered(
         &mut self,
@@ -737,43 +1192,7 @@ impl TranscriptStore {
                 if let Some(session_id_str) = path.file_stem().and_then(|s| s.to_str()) {
                     if let Ok(session_id) = Uuid::parse_str(session_id_str) {
                         if let Ok(Some(transcript)) = self.load_transcript(session_id).await {
-                            // Apply filters
-                            if let Some(filter_session) = filters.session_id {
-                                if transcript.metadata.session_id != filter_session {
-                                    continue;
-                                }
-                            }
-
-                            if let Some((start_date, end_date)) = filters.date_range {
-                                if transcript.metadata.created_at < start_date
-                                    || transcript.metadata.created_at > end_date
-                                {
-                                    continue;
-                                }
-                            }
-
-                            if let Some(ref technologies) = filters.technologies {
-                                if !technologies.iter().any(|tech| {
-                                    transcript
-                                        .conversation_context
-                                        .technologies_mentioned
-                                        .contains(tech)
-                                }) {
-                                    continue;
-                                }
-                            }
-
-                            if let Some(ref segment_type) = filters.segment_type {
-                                if !transcript
-                                    .segments
-                                    .iter()
-                                    .any(|s| &s.segment_type == segment_type)
-                                {
-                                    continue;
-                                }
-                            }
-
-                            if filters.active_only && !transcript.metadata.is_active {
+                            if !filters.matches(&transcript) {
                                 continue;
                             }
 
@@ -850,6 +1269,84 @@ impl TranscriptStore {
         Ok(results)
     }
 
+    /// Search individual messages across all transcripts passing
+    /// `filters`, best matches first whichever session they are in
+    pub async fn search_messages(
+        &mut self,
+        query: &str,
+        filters: TranscriptSearchFilters,
+    ) -> Result<Vec<MessageHit>> {
+        use fuzzy_matcher::FuzzyMatcher;
+        let matcher = fuzzy_matcher::skim::SkimMatcherV2::default();
+
+        // A single session needs no directory scan
+        let session_ids = match filters.session_id {
+            Some(session_id) => vec![session_id],
+            None => self.stored_session_ids().await?,
+        };
+
+        let mut hits = Vec::new();
+        for session_id in session_ids {
+            let Some(transcript) = self.load_transcript(session_id).await? else {
+                continue;
+            };
+            if !filters.matches(&transcript) {
+                continue;
+            }
+
+            for message in transcript.transcript.active_messages() {
+                if let Some((score, indices)) = matcher.fuzzy_indices(&message.content, query) {
+                    hits.push(MessageHit {
+                        session_id,
+                        message_id: message.id,
+                        role: message.role.clone(),
+                        timestamp: message.timestamp,
+                        score,
+                        spans: spans_from_indices(indices),
+                    });
+                }
+            }
+        }
+
+        // Highest score first, newer messages first among equal scores
+        hits.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| b.timestamp.cmp(&a.timestamp))
+        });
+        if let Some(limit) = filters.limit {
+            hits.truncate(limit);
+        }
+
+        Ok(hits)
+    }
+
+    /// Ids of every transcript stored on disk
+    async fn stored_session_ids(&self) -> Result<Vec<Uuid>> {
+        let mut session_ids = Vec::new();
+        let mut dir = fs::read_dir(&self.storage_dir).await.with_context(|| {
+            format!(
+                "Failed to read storage directory: {}",
+                self.storage_dir.display()
+            )
+        })?;
+
+        while let Some(entry) = dir.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) == Some("json") {
+                if let Some(session_id) = path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .and_then(|s| Uuid::parse_str(s).ok())
+                {
+                    session_ids.push(session_id);
+                }
+            }
+        }
+
+        Ok(session_ids)
+    }
+
     /// Get timeline of activities for a session
     pub async fn get_session_timeline(&mut self, session_id: Uuid) -> Result<Vec<TimelineEvent>> {
         let transcript = self
@@ -860,7 +1357,7 @@ impl TranscriptStore {
         let mut events = Vec::new();
 
         // Add message events
-        for message in &transcript.transcript.messages {
+        for message in transcript.transcript.active_messages() {
             events.push(TimelineEvent {
                 timestamp: message.timestamp,
                 event_type: TimelineEventType::Message {
@@ -904,6 +1401,27 @@ impl TranscriptStore {
     }
 }
 
+/// Parts of an environment variable name marking its value as a secret
+const SECRET_ENV_MARKERS: &[&str] = &["KEY", "TOKEN", "SECRET", "PASSWORD", "PASSWD", "CREDENTIAL"];
+
+/// The process environment with the values of secret-looking variables
+/// replaced, so command records can be stored and shared safely
+fn redacted_environment() -> HashMap<String, String> {
+    redact_environment(std::env::vars())
+}
+
+fn redact_environment(vars: impl Iterator<Item = (String, String)>) -> HashMap<String, String> {
+    vars.map(|(name, value)| {
+        let upper = name.to_uppercase();
+        if SECRET_ENV_MARKERS.iter().any(|m| upper.contains(m)) {
+            (name, "[REDACTED]".to_string())
+        } else {
+            (name, value)
+        }
+    })
+    .collect()
+}
+
 impl Default for TranscriptStore {
     fn default() -> Self {
         Self::new().expect("Failed to create default TranscriptStore")
@@ -920,6 +1438,53 @@ pub struct TranscriptSearchResult {
     pub summary: Option<String>,
 }
 
+/// A single message matching a search
+#[derive(Debug, Clone)]
+pub struct MessageHit {
+    pub session_id: Uuid,
+    pub message_id: Uuid,
+    pub role: MessageRole,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub score: i64,
+    /// Matched parts of the content, as returned by [`match_spans`]
+    pub spans: Vec<Range<usize>>,
+}
+
+/// Parts of `text` matched by `query` under the fuzzy matching used by
+/// transcript search, as ranges of char indices in ascending order. Returns
+/// `None` when `text` does not match.
+pub fn match_spans(text: &str, query: &str) -> Option<Vec<Range<usize>>> {
+    use fuzzy_matcher::FuzzyMatcher;
+    let matcher = fuzzy_matcher::skim::SkimMatcherV2::default();
+    let (_, indices) = matcher.fuzzy_indices(text, query)?;
+    Some(spans_from_indices(indices))
+}
+
+/// Ascending char indices grouped into contiguous ranges
+fn spans_from_indices(indices: Vec<usize>) -> Vec<Range<usize>> {
+    let mut spans: Vec<Range<usize>> = Vec::new();
+    for index in indices {
+        match spans.last_mut() {
+            Some(span) if span.end == index => span.end += 1,
+            _ => spans.push(index..index + 1),
+        }
+    }
+    spans
+}
+
+/// A command execution to add to a transcript
+#[derive(Debug, Clone)]
+pub struct CommandRecord {
+    pub command: String,
+    pub result: ExecutionResult,
+    pub output: Option<String>,
+    pub error: Option<String>,
+    pub duration: Option<Duration>,
+    pub exit_code: Option<i32>,
+    /// Message that triggered the command, if any
+    pub triggered_by_message: Option<Uuid>,
+}
+
 /// Update structure for conversation context
 #[derive(Debug, Clone, Default)]
 pub struct ConversationContextUpdate {
@@ -1024,6 +1589,8 @@ mod tests {
             message_count: 10,
             estimated_tokens: 500,
             is_active: true,
+            title: None,
+            workspace_path: None,
         };
 
         assert_eq!(metadata.session_id, session_id);
@@ -1252,6 +1819,8 @@ mod tests {
             storage_dir,
             cache: HashMap::new(),
             max_cache_size: 100,
+            extract_context: true,
+            writes: 0,
         };
 
         let session_id = Uuid::new_v4();
@@ -1269,6 +1838,8 @@ mod tests {
                 message_count: 0,
                 estimated_tokens: 0,
                 is_active: true,
+                title: None,
+                workspace_path: None,
             },
             conversation_context: ConversationContext::default(),
             command_executions: Vec::new(),
@@ -1297,6 +1868,8 @@ mod tests {
             storage_dir,
             cache: HashMap::new(),
             max_cache_size: 100,
+            extract_context: true,
+            writes: 0,
         };
 
         let session_id = Uuid::new_v4();
@@ -1313,6 +1886,8 @@ mod tests {
             storage_dir,
             cache: HashMap::new(),
             max_cache_size: 100,
+            extract_context: true,
+            writes: 0,
         };
 
         let session_id = Uuid::new_v4();
@@ -1338,6 +1913,8 @@ mod tests {
             storage_dir,
             cache: HashMap::new(),
             max_cache_size: 100,
+            extract_context: true,
+            writes: 0,
         };
 
         let session_id = Uuid::new_v4();
@@ -1372,6 +1949,8 @@ mod tests {
             storage_dir,
             cache: HashMap::new(),
             max_cache_size: 100,
+            extract_context: true,
+            writes: 0,
         };
 
         let session_id = Uuid::new_v4();
@@ -1399,6 +1978,8 @@ mod tests {
             storage_dir,
             cache: HashMap::new(),
             max_cache_size: 100,
+            extract_context: true,
+            writes: 0,
         };
 
         let session_id = Uuid::new_v4();
@@ -1428,6 +2009,8 @@ mod tests {
             storage_dir,
             cache: HashMap::new(),
             max_cache_size: 100,
+            extract_context: true,
+            writes: 0,
         };
 
         let session_id = Uuid::new_v4();
@@ -1451,6 +2034,151 @@ mod tests {
         assert_eq!(transcript.tags.len(), 2);
     }
 
+    #[tokio::test]
+    async fn test_tag_index_follows_tag_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = TranscriptStore {
+            storage_dir: temp_dir.path().to_owned(),
+            cache: HashMap::new(),
+            max_cache_size: 100,
+            extract_context: true,
+            writes: 0,
+        };
+
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+        for session_id in [first, second] {
+            store
+                .add_message(session_id, MessageRole::User, "Test".to_string())
+                .await
+                .unwrap();
+        }
+        store
+            .add_tags(first, vec!["rust".to_string(), "bug".to_string()])
+            .await
+            .unwrap();
+        store
+            .add_tags(second, vec!["rust".to_string()])
+            .await
+            .unwrap();
+
+        // The most recently updated transcript comes first
+        let tagged = store.list_transcripts_by_tag("rust").await.unwrap();
+        let ids: Vec<Uuid> = tagged.iter().map(|m| m.session_id).collect();
+        assert_eq!(ids, vec![second, first]);
+        assert_eq!(
+            store.list_all_tags().await.unwrap(),
+            vec![("rust".to_string(), 2), ("bug".to_string(), 1)]
+        );
+
+        assert!(store.remove_tag(first, "bug").await.unwrap());
+        assert!(!store.remove_tag(first, "bug").await.unwrap());
+        assert!(!store.remove_tag(Uuid::new_v4(), "rust").await.unwrap());
+        let transcript = store.load_transcript(first).await.unwrap().unwrap();
+        assert_eq!(transcript.tags, vec!["rust".to_string()]);
+        assert!(store
+            .list_transcripts_by_tag("bug")
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            store.list_all_tags().await.unwrap(),
+            vec![("rust".to_string(), 2)]
+        );
+
+        // Storing a transcript directly keeps the index in step too
+        let mut transcript = store.load_transcript(second).await.unwrap().unwrap();
+        transcript.tags = vec!["docs".to_string()];
+        store.store_transcript(transcript).await.unwrap();
+        assert_eq!(
+            store.list_all_tags().await.unwrap(),
+            vec![("docs".to_string(), 1), ("rust".to_string(), 1)]
+        );
+
+        // The sidecar index is not mistaken for a transcript
+        assert_eq!(store.list_transcripts().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_tag_counts_after_deletes() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = TranscriptStore {
+            storage_dir: temp_dir.path().to_owned(),
+            cache: HashMap::new(),
+            max_cache_size: 100,
+            extract_context: true,
+            writes: 0,
+        };
+
+        let sessions: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+        for session_id in &sessions {
+            store
+                .add_message(*session_id, MessageRole::User, "Test".to_string())
+                .await
+                .unwrap();
+            store
+                .add_tags(*session_id, vec!["shared".to_string()])
+                .await
+                .unwrap();
+        }
+        store
+            .add_tags(sessions[0], vec!["only".to_string()])
+            .await
+            .unwrap();
+
+        store.delete_transcript(sessions[0]).await.unwrap();
+        assert_eq!(
+            store.list_all_tags().await.unwrap(),
+            vec![("shared".to_string(), 2)]
+        );
+        let tagged = store.list_transcripts_by_tag("shared").await.unwrap();
+        assert!(tagged.iter().all(|m| m.session_id != sessions[0]));
+        assert_eq!(tagged.len(), 2);
+
+        // A fresh store reads the same index back from disk
+        let mut reopened = TranscriptStore {
+            storage_dir: temp_dir.path().to_owned(),
+            cache: HashMap::new(),
+            max_cache_size: 100,
+            extract_context: true,
+            writes: 0,
+        };
+        reopened.delete_transcript(sessions[1]).await.unwrap();
+        assert_eq!(
+            reopened.list_all_tags().await.unwrap(),
+            vec![("shared".to_string(), 1)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_tag_index_is_rebuilt() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = TranscriptStore {
+            storage_dir: temp_dir.path().to_owned(),
+            cache: HashMap::new(),
+            max_cache_size: 100,
+            extract_context: true,
+            writes: 0,
+        };
+
+        let session_id = Uuid::new_v4();
+        store
+            .add_message(session_id, MessageRole::User, "Test".to_string())
+            .await
+            .unwrap();
+        store
+            .add_tags(session_id, vec!["kept".to_string()])
+            .await
+            .unwrap();
+
+        std::fs::remove_file(temp_dir.path().join(TAG_INDEX_FILE)).unwrap();
+        assert_eq!(
+            store.list_all_tags().await.unwrap(),
+            vec![("kept".to_string(), 1)]
+        );
+        assert!(temp_dir.path().join(TAG_INDEX_FILE).exists());
+    }
+
     #[tokio::test]
     async fn test_set_summary() {
         let temp_dir = TempDir::new().unwrap();
@@ -1460,6 +2188,8 @@ mod tests {
             storage_dir,
             cache: HashMap::new(),
             max_cache_size: 100,
+            extract_context: true,
+            writes: 0,
         };
 
         let session_id = Uuid::new_v4();
@@ -1488,6 +2218,8 @@ mod tests {
             storage_dir,
             cache: HashMap::new(),
             max_cache_size: 100,
+            extract_context: true,
+            writes: 0,
         };
 
         let session_id = Uuid::new_v4();
@@ -1517,6 +2249,8 @@ mod tests {
             storage_dir,
             cache: HashMap::new(),
             max_cache_size: 2, // Small cache size
+            extract_context: true,
+            writes: 0,
         };
 
         // Add 3 transcripts - should evict oldest
@@ -1569,6 +2303,8 @@ mod tests {
             storage_dir,
             cache: HashMap::new(),
             max_cache_size: 100,
+            extract_context: true,
+            writes: 0,
         };
 
         let session_id = Uuid::new_v4();
@@ -1602,6 +2338,8 @@ mod tests {
             storage_dir,
             cache: HashMap::new(),
             max_cache_size: 100,
+            extract_context: true,
+            writes: 0,
         };
 
         // Add multiple transcripts
@@ -1632,6 +2370,8 @@ mod tests {
             storage_dir,
             cache: HashMap::new(),
             max_cache_size: 100,
+            extract_context: true,
+            writes: 0,
         };
 
         let session_id = Uuid::new_v4();
@@ -1659,6 +2399,8 @@ mod tests {
             storage_dir,
             cache: HashMap::new(),
             max_cache_size: 100,
+            extract_context: true,
+            writes: 0,
         };
 
         // Add multiple matching transcripts
@@ -1688,6 +2430,8 @@ mod tests {
             storage_dir,
             cache: HashMap::new(),
             max_cache_size: 100,
+            extract_context: true,
+            writes: 0,
         };
 
         let session_id = Uuid::new_v4();
@@ -1733,6 +2477,8 @@ mod tests {
             storage_dir,
             cache: HashMap::new(),
             max_cache_size: 100,
+            extract_context: true,
+            writes: 0,
         };
 
         let session_id = Uuid::new_v4();
@@ -1765,6 +2511,71 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_add_message_extracts_conversation_context() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = temp_dir.path().join("workspace");
+        std::fs::create_dir_all(workspace.join("src")).unwrap();
+        std::fs::write(workspace.join("src/main.rs"), "fn main() {}").unwrap();
+        let storage_dir = temp_dir.path().join("transcripts");
+        std::fs::create_dir_all(&storage_dir).unwrap();
+        let mut store = TranscriptStore {
+            storage_dir,
+            cache: HashMap::new(),
+            max_cache_size: 100,
+            extract_context: true,
+            writes: 0,
+        };
+        let session = Session::new().with_workspace(&workspace);
+        store.record_session(&session).await.unwrap();
+
+        for (role, content) in [
+            (MessageRole::User, "The tokio runtime panics in src/main.rs"),
+            (
+                MessageRole::Assistant,
+                "We decided to build the runtime by hand in src/main.rs. Tokio stays.",
+            ),
+            (MessageRole::User, "Sounds good, thanks for going over it."),
+        ] {
+            store
+                .add_message(session.id, role, content.to_string())
+                .await
+                .unwrap();
+        }
+
+        let context = store
+            .load_transcript(session.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .conversation_context;
+        assert_eq!(context.files_mentioned, ["src/main.rs"]);
+        assert_eq!(context.technologies_mentioned, ["tokio"]);
+        assert_eq!(
+            context.decisions_made,
+            ["We decided to build the runtime by hand in src/main.rs"]
+        );
+
+        // With extraction off nothing new is picked up
+        store.set_context_extraction(false);
+        store
+            .add_message(
+                session.id,
+                MessageRole::User,
+                "Let's go with redis".to_string(),
+            )
+            .await
+            .unwrap();
+        let context = store
+            .load_transcript(session.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .conversation_context;
+        assert_eq!(context.technologies_mentioned, ["tokio"]);
+        assert_eq!(context.decisions_made.len(), 1);
+    }
+
     #[tokio::test]
     async fn test_create_segment() {
         let temp_dir = TempDir::new().unwrap();
@@ -1774,6 +2585,8 @@ mod tests {
             storage_dir,
             cache: HashMap::new(),
             max_cache_size: 100,
+            extract_context: true,
+            writes: 0,
         };
 
         let session_id = Uuid::new_v4();
@@ -1811,6 +2624,8 @@ mod tests {
             storage_dir,
             cache: HashMap::new(),
             max_cache_size: 100,
+            extract_context: true,
+            writes: 0,
         };
 
         let session_id = Uuid::new_v4();
@@ -1861,6 +2676,63 @@ mod tests {
         assert_eq!(segment.key_outcomes.len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_compaction_segments_follow_each_other() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = TranscriptStore::with_storage_dir(temp_dir.path()).unwrap();
+        let session_id = Uuid::new_v4();
+        store
+            .add_messages(
+                session_id,
+                (0..5)
+                    .map(|i| (MessageRole::User, format!("message {}", i)))
+                    .collect(),
+            )
+            .await
+            .unwrap();
+
+        store
+            .add_compaction_segment(session_id, 2, "first two".to_string())
+            .await
+            .unwrap();
+        store
+            .add_compaction_segment(session_id, 10, "the rest".to_string())
+            .await
+            .unwrap();
+        assert!(store
+            .add_compaction_segment(session_id, 1, "nothing".to_string())
+            .await
+            .is_err());
+
+        let transcript = store.load_transcript(session_id).await.unwrap().unwrap();
+        let ids: Vec<Uuid> = transcript
+            .transcript
+            .messages
+            .iter()
+            .map(|m| m.id)
+            .collect();
+        let spans: Vec<_> = transcript
+            .segments
+            .iter()
+            .map(|s| {
+                assert_eq!(s.segment_type, SegmentType::Compaction);
+                (
+                    s.start_message_id,
+                    s.end_message_id.unwrap(),
+                    s.title.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                (ids[0], ids[1], "Compacted 2 messages"),
+                (ids[2], ids[4], "Compacted 3 messages")
+            ]
+        );
+        assert_eq!(transcript.transcript.messages.len(), 5);
+    }
+
     #[tokio::test]
     async fn test_get_session_timeline() {
         let temp_dir = TempDir::new().unwrap();
@@ -1870,6 +2742,8 @@ mod tests {
             storage_dir,
             cache: HashMap::new(),
             max_cache_size: 100,
+            extract_context: true,
+            writes: 0,
         };
 
         let session_id = Uuid::new_v4();
@@ -1905,6 +2779,8 @@ mod tests {
                 message_count: 0,
                 estimated_tokens: 0,
                 is_active: true,
+                title: None,
+                workspace_path: None,
             },
             conversation_context: ConversationContext::default(),
             command_executions: Vec::new(),
@@ -1917,4 +2793,178 @@ mod tests {
         assert_eq!(deserialized.metadata.session_id, session_id);
         assert_eq!(deserialized.tags, vec!["test"]);
     }
+
+    #[tokio::test]
+    async fn test_edits_deletions_and_branches_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = TranscriptStore {
+            storage_dir: temp_dir.path().to_owned(),
+            cache: HashMap::new(),
+            max_cache_size: 100,
+            extract_context: true,
+            writes: 0,
+        };
+        let session_id = Uuid::new_v4();
+        for content in ["one", "two", "three"] {
+            store
+                .add_message(session_id, MessageRole::User, content.to_string())
+                .await
+                .unwrap();
+        }
+        let ids: Vec<Uuid> = store
+            .load_transcript(session_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .transcript
+            .messages
+            .iter()
+            .map(|m| m.id)
+            .collect();
+
+        store
+            .edit_message(session_id, ids[0], "one, edited".to_string())
+            .await
+            .unwrap();
+        store.delete_message(session_id, ids[2]).await.unwrap();
+        let branch = store.branch_transcript(session_id, ids[1]).await.unwrap();
+
+        // Read back from disk rather than the cache
+        store.cache.clear();
+        let original = store.load_transcript(session_id).await.unwrap().unwrap();
+        assert_eq!(original.metadata.message_count, 2);
+        let messages = &original.transcript.messages;
+        assert_eq!(messages[0].content, "one, edited");
+        assert_eq!(messages[0].edits[0].content, "one");
+        assert!(messages[2].is_deleted());
+        let timeline = store.get_session_timeline(session_id).await.unwrap();
+        assert_eq!(timeline.len(), 2);
+
+        let branch_id = branch.metadata.session_id;
+        let loaded = store.load_transcript(branch_id).await.unwrap().unwrap();
+        assert_eq!(loaded.transcript.session_id, branch_id);
+        assert_eq!(
+            loaded
+                .transcript
+                .parent
+                .map(|p| (p.session_id, p.message_id)),
+            Some((session_id, ids[1]))
+        );
+        assert_eq!(loaded.transcript.messages.len(), 2);
+        assert_eq!(loaded.transcript.messages[0].edits.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_search_messages_ranks_across_sessions() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = TranscriptStore {
+            storage_dir: temp_dir.path().to_owned(),
+            cache: HashMap::new(),
+            max_cache_size: 100,
+            extract_context: true,
+            writes: 0,
+        };
+
+        let session_a = Uuid::new_v4();
+        let session_b = Uuid::new_v4();
+        for content in ["the islands had boxes", "nothing to see", "sandbox escape"] {
+            store
+                .add_message(session_a, MessageRole::User, content.to_string())
+                .await
+                .unwrap();
+        }
+        for content in ["unrelated", "fix the sandbox policy"] {
+            store
+                .add_message(session_b, MessageRole::Assistant, content.to_string())
+                .await
+                .unwrap();
+        }
+        let escape = store
+            .load_transcript(session_a)
+            .await
+            .unwrap()
+            .unwrap()
+            .transcript
+            .messages[2]
+            .id;
+        store.delete_message(session_a, escape).await.unwrap();
+
+        let hits = store
+            .search_messages("sandbox", TranscriptSearchFilters::default())
+            .await
+            .unwrap();
+        // The exact match in B beats A's scattered one; the deleted message
+        // and the non-matching ones are left out
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].session_id, session_b);
+        assert!(matches!(hits[0].role, MessageRole::Assistant));
+        assert_eq!(hits[1].session_id, session_a);
+        assert!(hits[0].score > hits[1].score);
+
+        let b = store.load_transcript(session_b).await.unwrap().unwrap();
+        let message = &b.transcript.messages[1];
+        assert_eq!(hits[0].message_id, message.id);
+        assert_eq!(hits[0].timestamp, message.timestamp);
+        assert_eq!(hits[0].spans, vec![8..15]);
+        let matched: String = message
+            .content
+            .chars()
+            .skip(hits[0].spans[0].start)
+            .take(hits[0].spans[0].len())
+            .collect();
+        assert_eq!(matched, "sandbox");
+
+        // Every span of the scattered match covers query characters in order
+        let a = store.load_transcript(session_a).await.unwrap().unwrap();
+        let chars: Vec<char> = a.transcript.messages[0].content.chars().collect();
+        let matched: String = hits[1]
+            .spans
+            .iter()
+            .flat_map(|span| chars[span.clone()].iter())
+            .collect();
+        assert_eq!(matched, "sandbox");
+        assert!(hits[1].spans.len() > 1);
+
+        let filters = TranscriptSearchFilters {
+            limit: Some(1),
+            ..Default::default()
+        };
+        let hits = store.search_messages("sandbox", filters).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, session_b);
+
+        let filters = TranscriptSearchFilters {
+            session_id: Some(session_a),
+            ..Default::default()
+        };
+        let hits = store.search_messages("sandbox", filters).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, session_a);
+    }
+
+    #[test]
+    fn test_match_spans_groups_contiguous_matches() {
+        let spans = match_spans("fix the sandbox policy", "sandbox").unwrap();
+        assert_eq!((spans.len(), spans[0].clone()), (1, 8..15));
+        assert_eq!(match_spans("añb", "ab"), Some(vec![0..1, 2..3]));
+        assert_eq!(match_spans("hello", "xyz"), None);
+    }
+
+    #[test]
+    fn test_secret_environment_values_are_redacted() {
+        let vars = [
+            ("ANTHROPIC_API_KEY", "sk-ant-123"),
+            ("GITHUB_TOKEN", "ghp_456"),
+            ("db_password", "hunter2"),
+            ("PATH", "/usr/bin"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+
+        let environment = redact_environment(vars);
+        assert_eq!(environment["ANTHROPIC_API_KEY"], "[REDACTED]");
+        assert_eq!(environment["GITHUB_TOKEN"], "[REDACTED]");
+        assert_eq!(environment["db_password"], "[REDACTED]");
+        assert_eq!(environment["PATH"], "/usr/bin");
+    }
 }
@@ -0,0 +1,651 @@
+//! Backups of files taken before commands change them.
+//!
+//! Backups are incremental: each one stores only the files whose content
+//! changed since the previous backup and names that backup as its parent,
+//! so a file it didn't store is found by following the chain. Contents live
+//! once under `objects/`, named by their SHA-256 hash, and each backup's
+//! manifest under `manifests/`.
+
+use anyhow::Result;
+use fennec_security::{
+    approval::{ApprovalManager, ApprovalRequest, ApprovalStatus, RiskLevel},
+    audit::AuditLogger,
+};
+use ring::digest::{digest, SHA256};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+/// Information about a backup created for an execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub id: Uuid,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Every file the backup covers, stored in it or further up the chain
+    pub affected_files: Vec<PathBuf>,
+    /// The backup's manifest
+    pub backup_path: PathBuf,
+    pub description: String,
+    /// Backup this one records changes against
+    #[serde(default)]
+    pub parent: Option<Uuid>,
+    /// Files stored in this backup because they changed since the parent
+    #[serde(default)]
+    pub entries: BTreeMap<PathBuf, BackupEntry>,
+}
+
+impl BackupInfo {
+    /// Bytes of file content stored by this backup itself
+    pub fn stored_size(&self) -> u64 {
+        self.entries.values().map(|entry| entry.size).sum()
+    }
+}
+
+/// Content of one file in a backup
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupEntry {
+    pub hash: String,
+    pub size: u64,
+}
+
+/// Configuration for backup retention
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupRetentionConfig {
+    pub max_backups: usize,
+    pub max_age_days: u64,
+    pub cleanup_interval_hours: u64,
+}
+
+impl Default for BackupRetentionConfig {
+    fn default() -> Self {
+        Self {
+            max_backups: 100,
+            max_age_days: 30,
+            cleanup_interval_hours: 24,
+        }
+    }
+}
+
+/// Backup manager for creating and managing file backups
+pub struct BackupManager {
+    backup_root: PathBuf,
+    retention_config: BackupRetentionConfig,
+    audit_logger: Arc<AuditLogger>,
+    approval_manager: Option<Arc<ApprovalManager>>,
+}
+
+impl BackupManager {
+    pub fn new(
+        backup_root: PathBuf,
+        retention_config: BackupRetentionConfig,
+        audit_logger: Arc<AuditLogger>,
+    ) -> Self {
+        Self {
+            backup_root,
+            retention_config,
+            audit_logger,
+            approval_manager: None,
+        }
+    }
+
+    /// Ask through `approval_manager` before a restore overwrites changes
+    /// that are in no backup. Without one those files are left alone.
+    pub fn with_approval_manager(mut self, approval_manager: Arc<ApprovalManager>) -> Self {
+        self.approval_manager = Some(approval_manager);
+        self
+    }
+
+    /// Create a backup for files that will be modified by a command
+    pub async fn create_backup(
+        &self,
+        files: &[PathBuf],
+        description: String,
+    ) -> Result<BackupInfo> {
+        let backups = self.load_manifests().await?;
+        let parent = backups.values().max_by_key(|b| b.timestamp).map(|b| b.id);
+
+        let mut affected_files = Vec::new();
+        let mut entries = BTreeMap::new();
+        for file_path in files {
+            if !file_path.exists() {
+                continue;
+            }
+            let content = read_with_retry(file_path).await?;
+            let entry = BackupEntry {
+                hash: hash_content(&content),
+                size: content.len() as u64,
+            };
+            affected_files.push(file_path.clone());
+
+            let unchanged = parent
+                .and_then(|id| resolve_entry(&backups, id, file_path))
+                .is_some_and(|previous| previous.hash == entry.hash);
+            if unchanged {
+                debug!("Unchanged since last backup: {}", file_path.display());
+                continue;
+            }
+
+            let object = self.object_path(&entry.hash);
+            if !object.exists() {
+                tokio::fs::create_dir_all(self.backup_root.join("objects")).await?;
+                tokio::fs::write(&object, &content).await?;
+            }
+            debug!("Backed up file: {}", file_path.display());
+            entries.insert(file_path.clone(), entry);
+        }
+
+        let backup_id = Uuid::new_v4();
+        let backup_info = BackupInfo {
+            id: backup_id,
+            timestamp: chrono::Utc::now(),
+            affected_files,
+            backup_path: self.manifest_path(backup_id),
+            description: description.clone(),
+            parent,
+            entries,
+        };
+        self.write_manifest(&backup_info).await?;
+
+        // Log backup creation
+        self.audit_logger
+            .log_security_event(
+                None,
+                "backup_created",
+                &format!("Backup created: {} ({})", backup_id, description),
+            )
+            .await?;
+
+        info!(
+            "Created backup: {} with {} files, {} changed",
+            backup_id,
+            backup_info.affected_files.len(),
+            backup_info.entries.len()
+        );
+
+        Ok(backup_info)
+    }
+
+    /// All backups, oldest first
+    pub async fn list_backups(&self) -> Result<Vec<BackupInfo>> {
+        let mut backups: Vec<BackupInfo> = self.load_manifests().await?.into_values().collect();
+        backups.sort_by_key(|b| b.timestamp);
+        Ok(backups)
+    }
+
+    /// Restore all files of a backup, overwriting whatever is there now
+    pub async fn restore_backup(&self, backup_info: &BackupInfo) -> Result<()> {
+        let backups = self.load_manifests().await?;
+        if !backups.contains_key(&backup_info.id) {
+            return Err(anyhow::anyhow!(
+                "Backup {} does not exist at {}",
+                backup_info.id,
+                backup_info.backup_path.display()
+            ));
+        }
+
+        for file_path in &backup_info.affected_files {
+            let entry = resolve_entry(&backups, backup_info.id, file_path)
+                .ok_or_else(|| missing_entry(backup_info.id, file_path))?;
+            self.write_from_object(file_path, &entry).await?;
+        }
+
+        self.log_restore(backup_info).await?;
+        info!(
+            "Restored backup: {} with {} files",
+            backup_info.id,
+            backup_info.affected_files.len()
+        );
+
+        Ok(())
+    }
+
+    /// Restore `paths`, or every file, of backup `backup_id` and return the
+    /// files written. A file with changes that are in no backup is only
+    /// overwritten once approved.
+    pub async fn restore(
+        &self,
+        backup_id: Uuid,
+        paths: Option<Vec<PathBuf>>,
+    ) -> Result<Vec<PathBuf>> {
+        let backups = self.load_manifests().await?;
+        let backup_info = backups
+            .get(&backup_id)
+            .ok_or_else(|| anyhow::anyhow!("Backup {} not found", backup_id))?;
+        let latest = backups
+            .values()
+            .max_by_key(|b| b.timestamp)
+            .map(|b| b.id)
+            .unwrap_or(backup_id);
+
+        let paths = paths.unwrap_or_else(|| backup_info.affected_files.clone());
+        let mut restored = Vec::new();
+        for file_path in paths {
+            if !backup_info.affected_files.contains(&file_path) {
+                return Err(anyhow::anyhow!(
+                    "{} is not in backup {}",
+                    file_path.display(),
+                    backup_id
+                ));
+            }
+            let entry = resolve_entry(&backups, backup_id, &file_path)
+                .ok_or_else(|| missing_entry(backup_id, &file_path))?;
+
+            if file_path.exists() {
+                let current = hash_content(&read_with_retry(&file_path).await?);
+                if current == entry.hash {
+                    debug!("Already as backed up: {}", file_path.display());
+                    continue;
+                }
+                let backed_up = resolve_entry(&backups, latest, &file_path)
+                    .is_some_and(|newest| newest.hash == current);
+                if !backed_up && !self.approve_overwrite(backup_info, &file_path).await? {
+                    info!("Kept unsaved changes to {}", file_path.display());
+                    continue;
+                }
+            }
+
+            self.write_from_object(&file_path, &entry).await?;
+            restored.push(file_path);
+        }
+
+        self.log_restore(backup_info).await?;
+        info!(
+            "Restored {} files from backup: {}",
+            restored.len(),
+            backup_id
+        );
+
+        Ok(restored)
+    }
+
+    /// Clean up old backups based on retention policy. A pruned backup's
+    /// files still needed by the next one in its chain move into it.
+    pub async fn cleanup_backups(&self) -> Result<()> {
+        let mut backups = self.load_manifests().await?;
+        let mut order: Vec<(chrono::DateTime<chrono::Utc>, Uuid)> =
+            backups.values().map(|b| (b.timestamp, b.id)).collect();
+        order.sort();
+
+        let cutoff_date =
+            chrono::Utc::now() - chrono::Duration::days(self.retention_config.max_age_days as i64);
+        let excess = order
+            .len()
+            .saturating_sub(self.retention_config.max_backups);
+        let pruned: Vec<Uuid> = order
+            .iter()
+            .enumerate()
+            .filter(|(index, (timestamp, _))| *index < excess || *timestamp < cutoff_date)
+            .map(|(_, (_, id))| *id)
+            .collect();
+        if pruned.is_empty() {
+            return Ok(());
+        }
+
+        // Oldest first, so entries moved into a child move on with it
+        for id in &pruned {
+            let Some(backup) = backups.remove(id) else {
+                continue;
+            };
+            for child in backups.values_mut().filter(|b| b.parent == Some(backup.id)) {
+                for file_path in &child.affected_files {
+                    if child.entries.contains_key(file_path) {
+                        continue;
+                    }
+                    if let Some(entry) = backup.entries.get(file_path) {
+                        child.entries.insert(file_path.clone(), entry.clone());
+                    }
+                }
+                child.parent = backup.parent;
+                self.write_manifest(child).await?;
+            }
+            tokio::fs::remove_file(&backup.backup_path).await?;
+            debug!("Cleaned up backup: {}", backup.id);
+        }
+
+        // Contents no remaining backup refers to
+        let referenced: HashSet<&str> = backups
+            .values()
+            .flat_map(|b| b.entries.values().map(|entry| entry.hash.as_str()))
+            .collect();
+        let objects_dir = self.backup_root.join("objects");
+        if objects_dir.exists() {
+            let mut objects = tokio::fs::read_dir(&objects_dir).await?;
+            while let Some(object) = objects.next_entry().await? {
+                let name = object.file_name();
+                if !referenced.contains(name.to_string_lossy().as_ref()) {
+                    tokio::fs::remove_file(object.path()).await?;
+                }
+            }
+        }
+
+        info!("Cleaned up {} old backups", pruned.len());
+        Ok(())
+    }
+
+    fn manifest_path(&self, id: Uuid) -> PathBuf {
+        self.backup_root
+            .join("manifests")
+            .join(format!("{}.json", id))
+    }
+
+    fn object_path(&self, hash: &str) -> PathBuf {
+        self.backup_root.join("objects").join(hash)
+    }
+
+    async fn write_manifest(&self, backup_info: &BackupInfo) -> Result<()> {
+        tokio::fs::create_dir_all(self.backup_root.join("manifests")).await?;
+        let metadata_json = serde_json::to_string_pretty(backup_info)?;
+        tokio::fs::write(&backup_info.backup_path, metadata_json).await?;
+        Ok(())
+    }
+
+    async fn load_manifests(&self) -> Result<HashMap<Uuid, BackupInfo>> {
+        let mut backups = HashMap::new();
+        let manifests_dir = self.backup_root.join("manifests");
+        if !manifests_dir.exists() {
+            return Ok(backups);
+        }
+
+        let mut entries = tokio::fs::read_dir(&manifests_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let content = tokio::fs::read_to_string(entry.path()).await?;
+            match serde_json::from_str::<BackupInfo>(&content) {
+                Ok(backup) => {
+                    backups.insert(backup.id, backup);
+                }
+                Err(e) => warn!(
+                    "Skipping unreadable backup {}: {}",
+                    entry.path().display(),
+                    e
+                ),
+            }
+        }
+        Ok(backups)
+    }
+
+    async fn write_from_object(&self, file_path: &Path, entry: &BackupEntry) -> Result<()> {
+        let content = tokio::fs::read(self.object_path(&entry.hash)).await?;
+        if let Some(parent) = file_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        // Retry on Windows file locking issues
+        let mut attempts = 0;
+        let max_attempts = 3;
+        loop {
+            match tokio::fs::write(file_path, &content).await {
+                Ok(_) => break,
+                Err(e) if attempts < max_attempts && e.raw_os_error() == Some(32) => {
+                    attempts += 1;
+                    tokio::time::sleep(tokio::time::Duration::from_millis(10 * attempts)).await;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        debug!("Restored file: {}", file_path.display());
+        Ok(())
+    }
+
+    async fn approve_overwrite(&self, backup_info: &BackupInfo, file_path: &Path) -> Result<bool> {
+        let Some(approval_manager) = &self.approval_manager else {
+            warn!(
+                "Not restoring {}: it has changes that are in no backup",
+                file_path.display()
+            );
+            return Ok(false);
+        };
+
+        let request = ApprovalRequest {
+            operation: "Restore Backup".to_string(),
+            description: format!(
+                "Overwrite {}, which has changes that are in no backup",
+                file_path.display()
+            ),
+            risk_level: RiskLevel::Medium,
+            details: vec![
+                format!("File: {}", file_path.display()),
+                format!("Backup: {} ({})", backup_info.id, backup_info.description),
+                format!("Taken: {}", backup_info.timestamp.to_rfc3339()),
+            ],
+            diff: None,
+            targets: vec![file_path.to_path_buf()],
+        };
+        let status = approval_manager.request_approval_async(&request).await?;
+        Ok(status == ApprovalStatus::Approved)
+    }
+
+    async fn log_restore(&self, backup_info: &BackupInfo) -> Result<()> {
+        self.audit_logger
+            .log_security_event(
+                None,
+                "backup_restored",
+                &format!(
+                    "Backup restored: {} ({})",
+                    backup_info.id, backup_info.description
+                ),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+/// Content of `file_path` as of backup `id`, following its chain of parents
+fn resolve_entry(
+    backups: &HashMap<Uuid, BackupInfo>,
+    id: Uuid,
+    file_path: &Path,
+) -> Option<BackupEntry> {
+    let mut next = Some(id);
+    // A chain can't be longer than the number of backups
+    for _ in 0..=backups.len() {
+        let backup = backups.get(&next?)?;
+        if let Some(entry) = backup.entries.get(file_path) {
+            return Some(entry.clone());
+        }
+        next = backup.parent;
+    }
+    None
+}
+
+fn missing_entry(backup_id: Uuid, file_path: &Path) -> anyhow::Error {
+    anyhow::anyhow!(
+        "Backup {} has lost the content of {}",
+        backup_id,
+        file_path.display()
+    )
+}
+
+fn hash_content(content: &[u8]) -> String {
+    hex::encode(digest(&SHA256, content).as_ref())
+}
+
+/// Read a file, retrying on Windows file locking issues
+async fn read_with_retry(file_path: &Path) -> Result<Vec<u8>> {
+    let mut attempts = 0;
+    let max_attempts = 5;
+    loop {
+        match tokio::fs::read(file_path).await {
+            Ok(content) => return Ok(content),
+            Err(e) if attempts < max_attempts && e.raw_os_error() == Some(32) => {
+                attempts += 1;
+                tokio::time::sleep(tokio::time::Duration::from_millis(50 * attempts)).await;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use fennec_security::approval::{ApprovalDecision, ApprovalPrompt};
+    use tempfile::TempDir;
+
+    struct FixedPrompt(ApprovalDecision);
+
+    #[async_trait]
+    impl ApprovalPrompt for FixedPrompt {
+        async fn prompt(&self, _request: &ApprovalRequest) -> Result<ApprovalDecision> {
+            Ok(self.0)
+        }
+    }
+
+    async fn create_manager(temp_dir: &TempDir, retention: BackupRetentionConfig) -> BackupManager {
+        let audit_logger = AuditLogger::with_path(temp_dir.path().join("audit.log"))
+            .await
+            .unwrap();
+        BackupManager::new(
+            temp_dir.path().join("backups"),
+            retention,
+            Arc::new(audit_logger),
+        )
+    }
+
+    async fn read(path: &Path) -> String {
+        tokio::fs::read_to_string(path).await.unwrap()
+    }
+
+    /// Three backups of `a` and `b`; `b` changes only before the last one
+    async fn create_chain(manager: &BackupManager, a: &Path, b: &Path) -> Vec<BackupInfo> {
+        let files = [a.to_path_buf(), b.to_path_buf()];
+        let mut chain = Vec::new();
+        for (a_content, b_content) in [("a1", "b1"), ("a2", "b1"), ("a3", "b3")] {
+            tokio::fs::write(a, a_content).await.unwrap();
+            tokio::fs::write(b, b_content).await.unwrap();
+            chain.push(
+                manager
+                    .create_backup(&files, format!("{} {}", a_content, b_content))
+                    .await
+                    .unwrap(),
+            );
+        }
+        chain
+    }
+
+    #[tokio::test]
+    #[cfg_attr(target_os = "windows", ignore = "Flaky on Windows due to file locking")]
+    async fn test_backup_creation() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_manager(&temp_dir, BackupRetentionConfig::default()).await;
+        let test_file = temp_dir
+            .path()
+            .join(format!("test_{}.txt", uuid::Uuid::new_v4()));
+
+        // Create a test file
+        tokio::fs::write(&test_file, "test content").await.unwrap();
+
+        let backup_info = manager
+            .create_backup(std::slice::from_ref(&test_file), "Test backup".to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(backup_info.affected_files.len(), 1);
+        assert_eq!(backup_info.affected_files[0], test_file);
+        assert!(backup_info.backup_path.exists());
+
+        // Test restoration
+        tokio::fs::write(&test_file, "modified content")
+            .await
+            .unwrap();
+        manager.restore_backup(&backup_info).await.unwrap();
+        assert_eq!(read(&test_file).await, "test content");
+    }
+
+    #[tokio::test]
+    async fn test_restore_single_file_from_middle_of_chain() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_manager(&temp_dir, BackupRetentionConfig::default()).await;
+        let a = temp_dir.path().join("a.txt");
+        let b = temp_dir.path().join("b.txt");
+        let chain = create_chain(&manager, &a, &b).await;
+
+        // Only changed files are stored, each chained to the previous backup
+        assert_eq!(chain[1].parent, Some(chain[0].id));
+        assert_eq!(chain[1].entries.keys().collect::<Vec<_>>(), [&a]);
+        assert_eq!(chain[2].entries.len(), 2);
+        let listed = manager.list_backups().await.unwrap();
+        let ids: Vec<Uuid> = listed.iter().map(|b| b.id).collect();
+        assert_eq!(ids, chain.iter().map(|b| b.id).collect::<Vec<_>>());
+        assert_eq!(listed[1].stored_size(), 2);
+
+        // `b` of the middle backup lives in the first one
+        let restored = manager
+            .restore(chain[1].id, Some(vec![b.clone()]))
+            .await
+            .unwrap();
+        assert_eq!(restored, vec![b.clone()]);
+        assert_eq!(read(&b).await, "b1");
+        assert_eq!(read(&a).await, "a3");
+    }
+
+    #[tokio::test]
+    async fn test_unsaved_changes_need_approval() {
+        let temp_dir = TempDir::new().unwrap();
+        let a = temp_dir.path().join("a.txt");
+        let b = temp_dir.path().join("b.txt");
+        let manager = create_manager(&temp_dir, BackupRetentionConfig::default()).await;
+        let chain = create_chain(&manager, &a, &b).await;
+        tokio::fs::write(&a, "unsaved").await.unwrap();
+
+        // No approval manager to ask
+        let restored = manager.restore(chain[0].id, None).await.unwrap();
+        assert_eq!(restored, vec![b.clone()]);
+        assert_eq!(read(&a).await, "unsaved");
+
+        let denied = Arc::new(
+            ApprovalManager::new(false, true)
+                .with_prompt(Arc::new(FixedPrompt(ApprovalDecision::Deny))),
+        );
+        let manager = create_manager(&temp_dir, BackupRetentionConfig::default())
+            .await
+            .with_approval_manager(denied);
+        assert!(manager
+            .restore(chain[0].id, Some(vec![a.clone()]))
+            .await
+            .unwrap()
+            .is_empty());
+
+        let approved = Arc::new(
+            ApprovalManager::new(false, true)
+                .with_prompt(Arc::new(FixedPrompt(ApprovalDecision::Approve))),
+        );
+        let manager = create_manager(&temp_dir, BackupRetentionConfig::default())
+            .await
+            .with_approval_manager(approved);
+        manager
+            .restore(chain[0].id, Some(vec![a.clone()]))
+            .await
+            .unwrap();
+        assert_eq!(read(&a).await, "a1");
+    }
+
+    #[tokio::test]
+    async fn test_pruning_keeps_chains_whole() {
+        let temp_dir = TempDir::new().unwrap();
+        let a = temp_dir.path().join("a.txt");
+        let b = temp_dir.path().join("b.txt");
+        let retention = BackupRetentionConfig {
+            max_backups: 2,
+            ..BackupRetentionConfig::default()
+        };
+        let manager = create_manager(&temp_dir, retention).await;
+        let chain = create_chain(&manager, &a, &b).await;
+
+        manager.cleanup_backups().await.unwrap();
+        let remaining = manager.list_backups().await.unwrap();
+        assert_eq!(remaining.len(), 2);
+        assert_eq!(remaining[0].id, chain[1].id);
+        assert_eq!(remaining[0].parent, None);
+
+        // `b` of the middle backup moved out of the pruned first one
+        manager
+            .restore(chain[1].id, Some(vec![b.clone()]))
+            .await
+            .unwrap();
+        assert_eq!(read(&b).await, "b1");
+    }
+}
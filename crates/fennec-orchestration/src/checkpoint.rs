@@ -0,0 +1,175 @@
+//! Snapshots of the open sessions, written to disk so that a restarted
+//! process can pick up where the last one stopped.
+
+use crate::execution::{CommandState, ExecutionInfo};
+use fennec_core::{session::Session, transcript::Transcript, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+use tracing::{debug, info};
+use uuid::Uuid;
+
+/// Open sessions at one point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionCheckpoint {
+    pub saved_at: chrono::DateTime<chrono::Utc>,
+    pub current_session: Option<Uuid>,
+    pub sessions: Vec<SessionSnapshot>,
+}
+
+/// One open session in a [`SessionCheckpoint`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    pub session: Session,
+    pub transcript: Transcript,
+    /// Last message of the transcript that was answered. Messages after it
+    /// were still waiting for a reply and are dropped on resume.
+    pub last_message_id: Option<Uuid>,
+    /// Commands submitted in the session
+    pub executions: Vec<ExecutionInfo>,
+}
+
+impl SessionSnapshot {
+    pub fn new(session: Session, transcript: Transcript, executions: Vec<ExecutionInfo>) -> Self {
+        let last_message_id = transcript
+            .messages
+            .iter()
+            .rev()
+            .find(|m| !matches!(m.role, fennec_core::transcript::MessageRole::User))
+            .map(|m| m.id);
+        Self {
+            session,
+            transcript,
+            last_message_id,
+            executions,
+        }
+    }
+
+    /// State of each command submitted in the session
+    pub fn command_states(&self) -> HashMap<Uuid, CommandState> {
+        self.executions
+            .iter()
+            .map(|e| (e.id, e.state.clone()))
+            .collect()
+    }
+
+    /// Commands still waiting to be approved
+    pub fn pending_approvals(&self) -> Vec<Uuid> {
+        self.executions
+            .iter()
+            .filter(|e| e.requires_approval && e.state == CommandState::Pending)
+            .map(|e| e.id)
+            .collect()
+    }
+
+    /// The transcript up to the last answered message
+    pub fn answered_transcript(&self) -> Transcript {
+        let mut transcript = self.transcript.clone();
+        let keep = match self.last_message_id {
+            Some(id) => transcript
+                .messages
+                .iter()
+                .position(|m| m.id == id)
+                .map_or(transcript.messages.len(), |i| i + 1),
+            None => 0,
+        };
+        transcript.messages.truncate(keep);
+        transcript
+    }
+}
+
+impl SessionCheckpoint {
+    /// Write the checkpoint to `path`, replacing the previous one only once
+    /// the new one is complete
+    pub async fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let temp_path = path.with_extension("json.tmp");
+        tokio::fs::write(&temp_path, serde_json::to_vec_pretty(self)?).await?;
+        tokio::fs::rename(&temp_path, path).await?;
+        debug!(
+            "Saved checkpoint of {} sessions to {}",
+            self.sessions.len(),
+            path.display()
+        );
+        Ok(())
+    }
+
+    /// Read the checkpoint at `path`. One older than `max_age` is deleted
+    /// and treated as missing.
+    pub async fn load(path: &Path, max_age: Duration) -> Result<Option<Self>> {
+        let content = match tokio::fs::read(path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let checkpoint: Self = serde_json::from_slice(&content)?;
+
+        let age = chrono::Utc::now() - checkpoint.saved_at;
+        if age.to_std().unwrap_or_default() > max_age {
+            info!(
+                "Discarding checkpoint from {} as stale",
+                checkpoint.saved_at.to_rfc3339()
+            );
+            Self::remove(path).await?;
+            return Ok(None);
+        }
+        Ok(Some(checkpoint))
+    }
+
+    /// Delete the checkpoint at `path`, if there is one
+    pub async fn remove(path: &Path) -> Result<()> {
+        match tokio::fs::remove_file(path).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fennec_core::transcript::MessageRole;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_unanswered_messages_are_dropped() {
+        let session = Session::new();
+        let mut transcript = Transcript::new(session.id);
+        transcript.add_message(MessageRole::User, "hi".to_string());
+        transcript.add_message(MessageRole::Assistant, "hello".to_string());
+        transcript.add_message(MessageRole::User, "no reply yet".to_string());
+
+        let snapshot = SessionSnapshot::new(session, transcript, Vec::new());
+        let contents: Vec<String> = snapshot
+            .answered_transcript()
+            .messages
+            .into_iter()
+            .map(|m| m.content)
+            .collect();
+        assert_eq!(contents, ["hi", "hello"]);
+    }
+
+    #[tokio::test]
+    async fn test_stale_checkpoint_is_discarded() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("checkpoint.json");
+        let checkpoint = SessionCheckpoint {
+            saved_at: chrono::Utc::now() - chrono::Duration::hours(2),
+            current_session: None,
+            sessions: Vec::new(),
+        };
+        checkpoint.save(&path).await.unwrap();
+
+        let day = Duration::from_secs(24 * 60 * 60);
+        assert!(SessionCheckpoint::load(&path, day).await.unwrap().is_some());
+        let hour = Duration::from_secs(60 * 60);
+        assert!(SessionCheckpoint::load(&path, hour)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(!path.exists());
+    }
+}
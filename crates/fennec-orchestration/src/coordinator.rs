@@ -1 +1,447 @@
-// Coordinator implementation will go here
+//! Ties the session manager, the command registry and the memory service
+//! together, so that every exchange with the provider and every command run
+//! in a session ends up in the session's stored transcript.
+//!
+//! Before a message is sent, a conversation grown past the configured
+//! prompt size is compacted: its oldest messages are replaced by a summary
+//! in the session's working transcript, and the stored transcript gets a
+//! segment marking what was compacted while keeping every message.
+
+use crate::idle::IdleEvent;
+use crate::session::SessionManager;
+use anyhow::Result;
+use fennec_commands::{CommandContext, CommandExecutionResult, CommandRegistry};
+use fennec_core::{
+    config::{CompactionConfig, CompactionStrategy},
+    provider::{AttachmentSupport, ProviderMessage, ProviderRequest, TaskKind},
+    session::Session,
+    transcript::{Message, MessageRole, Transcript},
+};
+use fennec_memory::{transcript::ExecutionResult, MemoryService};
+use fennec_provider::usage::estimate_prompt_tokens;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+const SUMMARY_INSTRUCTIONS: &str = "Summarize the following conversation between a user and \
+     a coding assistant in a few sentences. Mention the goal, the commands that were run and \
+     where things were left.";
+
+const COMPACTION_INSTRUCTIONS: &str = "Summarize the following earlier part of a conversation \
+     between a user and a coding assistant so the conversation can go on without it. Keep the \
+     goal, decisions made, files and commands involved and anything left open.";
+
+/// Longest line of a user message quoted by the heuristic summary
+const HEURISTIC_LINE_CHARS: usize = 200;
+
+/// Runs chat messages and commands for the user and records them in memory.
+/// Recording is best effort: a failure to record is logged and never fails
+/// the operation the user asked for.
+pub struct Coordinator {
+    sessions: Arc<SessionManager>,
+    registry: Arc<CommandRegistry>,
+    memory: Option<Arc<MemoryService>>,
+    project_id: Option<Uuid>,
+    compaction: CompactionConfig,
+}
+
+impl Coordinator {
+    pub fn new(sessions: Arc<SessionManager>, registry: Arc<CommandRegistry>) -> Self {
+        let compaction = sessions.config().compaction.clone();
+        Self {
+            sessions,
+            registry,
+            memory: None,
+            project_id: None,
+            compaction,
+        }
+    }
+
+    /// Record sessions in `memory`
+    pub fn with_memory(mut self, memory: Arc<MemoryService>) -> Self {
+        self.memory = Some(memory);
+        self
+    }
+
+    /// Report ended sessions to the memory files of `project_id`
+    pub fn with_project(mut self, project_id: Uuid) -> Self {
+        self.project_id = Some(project_id);
+        self
+    }
+
+    /// Compact conversations according to `compaction` instead of the
+    /// session manager's configuration
+    pub fn with_compaction(mut self, compaction: CompactionConfig) -> Self {
+        self.compaction = compaction;
+        self
+    }
+
+    pub fn sessions(&self) -> Arc<SessionManager> {
+        self.sessions.clone()
+    }
+
+    pub fn registry(&self) -> Arc<CommandRegistry> {
+        self.registry.clone()
+    }
+
+    /// Start a new session and begin recording it
+    pub async fn start_session(&self) -> Result<Uuid> {
+        let session_id = self.sessions.start_session().await?;
+        if let (Some(memory), Some(session)) = (&self.memory, self.sessions.current_session().await)
+        {
+            if let Err(e) = memory.start_session(session).await {
+                warn!("Failed to start recording session {}: {}", session_id, e);
+            }
+        }
+        Ok(session_id)
+    }
+
+    /// Send `content` to the provider and record both sides of the exchange
+    pub async fn send_message(&self, content: String) -> Result<String> {
+        if let Some(session_id) = self.sessions.current_session_id().await {
+            if let Err(e) = self.compact_if_needed(session_id, &content).await {
+                warn!("Failed to compact session {}: {}", session_id, e);
+            }
+        }
+        let reply = self.sessions.send_message(content.clone()).await?;
+        if let Some(session_id) = self.sessions.current_session_id().await {
+            self.record_message(session_id, MessageRole::User, content)
+                .await;
+            self.record_message(session_id, MessageRole::Assistant, reply.clone())
+                .await;
+        }
+        Ok(reply)
+    }
+
+    /// Run the registry command `name` and record its outcome in the
+    /// session of `context`
+    pub async fn execute_command(
+        &self,
+        name: &str,
+        args: &serde_json::Value,
+        context: &CommandContext,
+    ) -> Result<CommandExecutionResult> {
+        self.sessions.keep_alive(context.session_id).await;
+        let started = Instant::now();
+        let result = self.registry.execute_command(name, args, context).await;
+
+        if let Some(memory) = &self.memory {
+            let command = format!("{} {}", name, args);
+            let (execution, output, error) = match &result {
+                Ok(r) => (
+                    ExecutionResult {
+                        success: r.success,
+                        summary: r.output.lines().next().unwrap_or_default().to_string(),
+                        details: r.error.clone(),
+                        files_affected: Vec::new(),
+                        follow_up_actions: Vec::new(),
+                    },
+                    Some(r.output.clone()),
+                    r.error.clone(),
+                ),
+                Err(e) => (
+                    ExecutionResult {
+                        success: false,
+                        summary: format!("Failed to run {}", name),
+                        details: Some(e.to_string()),
+                        files_affected: Vec::new(),
+                        follow_up_actions: Vec::new(),
+                    },
+                    None,
+                    Some(e.to_string()),
+                ),
+            };
+            let exit_code = if execution.success { 0 } else { 1 };
+            if let Err(e) = memory
+                .record_command_execution(
+                    context.session_id,
+                    command,
+                    execution,
+                    output,
+                    error,
+                    Some(started.elapsed()),
+                    Some(exit_code),
+                )
+                .await
+            {
+                warn!(
+                    "Failed to record command {} in session {}: {}",
+                    name, context.session_id, e
+                );
+            }
+        }
+
+        result
+    }
+
+    /// End the current session, storing a summary of it and updating the
+    /// project's memory files
+    pub async fn end_session(&self) -> Result<()> {
+        if let Some(session) = self.sessions.current_session().await {
+            let transcript = self
+                .sessions
+                .transcript(session.id)
+                .await
+                .unwrap_or_else(|| Transcript::new(session.id));
+            self.finish_recording(session, transcript).await;
+        }
+        self.sessions.end_session().await?;
+        Ok(())
+    }
+
+    /// End sessions that have been idle for too long, recording them like
+    /// [`Self::end_session`], and pass on warnings about idle sessions
+    pub async fn check_idle(&self) -> Result<Vec<IdleEvent>> {
+        let events = self.sessions.check_idle().await?;
+        for event in &events {
+            if let IdleEvent::Ended {
+                session,
+                transcript,
+            } = event
+            {
+                self.finish_recording(session.clone(), transcript.clone())
+                    .await;
+            }
+        }
+        Ok(events)
+    }
+
+    /// Store a summary of `session` and stop recording it
+    async fn finish_recording(&self, session: Session, transcript: Transcript) {
+        let Some(memory) = &self.memory else {
+            return;
+        };
+        let session_id = session.id;
+        let summary = match self.summarize(&transcript).await {
+            Ok(summary) => summary,
+            Err(e) => {
+                warn!("Failed to summarize session {}: {}", session_id, e);
+                None
+            }
+        };
+        if let Err(e) = memory.end_session(session, self.project_id, summary).await {
+            warn!("Failed to finish recording session {}: {}", session_id, e);
+        }
+    }
+
+    async fn record_message(&self, session_id: Uuid, role: MessageRole, content: String) {
+        if let Some(memory) = &self.memory {
+            if let Err(e) = memory.add_message(session_id, role, content).await {
+                warn!("Failed to record message in session {}: {}", session_id, e);
+            }
+        }
+    }
+
+    /// Compact the conversation of `session_id` if it would not fit the
+    /// configured prompt size together with `pending`, the message about to
+    /// be sent. Returns whether it was compacted.
+    pub async fn compact_if_needed(&self, session_id: Uuid, pending: &str) -> Result<bool> {
+        let settings = &self.compaction;
+        if !settings.enabled {
+            return Ok(false);
+        }
+        let Some(transcript) = self.sessions.transcript(session_id).await else {
+            return Ok(false);
+        };
+        let messages: Vec<&Message> = transcript.active_messages().collect();
+        let pending_tokens = estimate_prompt_tokens(&[ProviderMessage {
+            role: "user".to_string(),
+            content: pending.to_string(),
+        }]) as usize;
+        let prompt_tokens =
+            messages.iter().map(|m| message_tokens(m)).sum::<usize>() + pending_tokens;
+        if prompt_tokens <= settings.max_prompt_tokens {
+            return Ok(false);
+        }
+
+        // Take out the oldest messages until the rest fits in half the
+        // limit, leaving room for the summary and for the conversation to
+        // grow. The most recent messages are only taken when they alone
+        // are over the limit.
+        let protected = messages.len().saturating_sub(settings.keep_recent_messages);
+        let mut remaining = prompt_tokens;
+        let mut end = 0;
+        while end < messages.len() {
+            let limit = if end < protected {
+                settings.max_prompt_tokens / 2
+            } else {
+                settings.max_prompt_tokens
+            };
+            if remaining <= limit {
+                break;
+            }
+            remaining -= message_tokens(messages[end]);
+            end += 1;
+        }
+        if end == 0 {
+            return Ok(false);
+        }
+        let span = &messages[..end];
+
+        let summary = match settings.strategy {
+            CompactionStrategy::Summarize => {
+                let summary = match self.summarize_span(session_id, span).await {
+                    Ok(summary) => summary,
+                    Err(e) => {
+                        warn!(
+                            "Failed to summarize session {} for compaction, using the \
+                             heuristic summary: {}",
+                            session_id, e
+                        );
+                        heuristic_summary(span, settings.max_prompt_tokens)
+                    }
+                };
+                format!("Summary of the earlier conversation:\n{}", summary)
+            }
+            CompactionStrategy::Truncate => format!(
+                "{} earlier messages were left out to fit the context window.",
+                span.len()
+            ),
+        };
+
+        let ids: Vec<Uuid> = span.iter().map(|m| m.id).collect();
+        self.sessions
+            .compact_transcript(session_id, &ids, summary.clone())
+            .await?;
+        info!(
+            "Compacted {} messages of session {} ({} prompt tokens)",
+            span.len(),
+            session_id,
+            prompt_tokens
+        );
+
+        // Earlier summaries and other system messages were never recorded
+        let recorded = span
+            .iter()
+            .filter(|m| !matches!(m.role, MessageRole::System))
+            .count();
+        if let Some(memory) = self.memory.as_ref().filter(|_| recorded > 0) {
+            if let Err(e) = memory
+                .record_compaction(session_id, recorded, summary)
+                .await
+            {
+                warn!(
+                    "Failed to record compaction in session {}: {}",
+                    session_id, e
+                );
+            }
+        }
+        Ok(true)
+    }
+
+    /// Summary of a session's conversation by the provider routed for
+    /// summaries, or `None` when nothing was said
+    async fn summarize(&self, transcript: &Transcript) -> Result<Option<String>> {
+        let messages: Vec<&Message> = transcript.active_messages().collect();
+        if messages.is_empty() {
+            return Ok(None);
+        }
+        let summary = self
+            .complete_summary(transcript.session_id, SUMMARY_INSTRUCTIONS, &messages, None)
+            .await?;
+        debug!("Summarized session {}", transcript.session_id);
+        Ok(Some(summary))
+    }
+
+    /// Summary of the compacted `span` by the provider routed for summaries,
+    /// short enough to leave room in the prompt
+    async fn summarize_span(&self, session_id: Uuid, span: &[&Message]) -> Result<String> {
+        let max_tokens = (self.compaction.max_prompt_tokens / 4) as u32;
+        self.complete_summary(
+            session_id,
+            COMPACTION_INSTRUCTIONS,
+            span,
+            Some(max_tokens.max(1)),
+        )
+        .await
+    }
+
+    async fn complete_summary(
+        &self,
+        session_id: Uuid,
+        instructions: &str,
+        messages: &[&Message],
+        max_tokens: Option<u32>,
+    ) -> Result<String> {
+        let conversation = messages
+            .iter()
+            .map(|m| format!("{:?}: {}", m.role, m.content))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let request = ProviderRequest {
+            id: Uuid::new_v4(),
+            messages: vec![
+                ProviderMessage {
+                    role: "system".to_string(),
+                    content: instructions.to_string(),
+                },
+                ProviderMessage {
+                    role: "user".to_string(),
+                    content: conversation,
+                },
+            ],
+            // The router sets the model of the summarize route
+            model: String::new(),
+            stream: false,
+            temperature: None,
+            max_tokens,
+        };
+
+        let provider = self
+            .sessions
+            .provider_for_session(session_id, TaskKind::Summarize);
+        let response = provider.complete(request).await?;
+        Ok(response.content)
+    }
+}
+
+/// Estimated prompt tokens `message` takes up
+fn message_tokens(message: &Message) -> usize {
+    estimate_prompt_tokens(&[ProviderMessage::from_transcript(
+        message,
+        AttachmentSupport::Inline,
+    )]) as usize
+}
+
+/// Summary of `messages` made without the provider: earlier summaries and
+/// the first line of each user message, the most recent ones kept when they
+/// don't all fit in a quarter of `max_prompt_tokens`
+fn heuristic_summary(messages: &[&Message], max_prompt_tokens: usize) -> String {
+    let lines: Vec<String> = messages
+        .iter()
+        .filter_map(|m| match m.role {
+            MessageRole::System if m.compacted_messages().is_some() => Some(m.content.clone()),
+            MessageRole::User => {
+                let line: String = m
+                    .content
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .chars()
+                    .take(HEURISTIC_LINE_CHARS)
+                    .collect();
+                Some(format!("- The user asked: {}", line))
+            }
+            _ => None,
+        })
+        .collect();
+
+    // About four characters per token
+    let mut budget = max_prompt_tokens;
+    let mut kept = Vec::new();
+    for line in lines.into_iter().rev() {
+        let len = line.chars().count() + 1;
+        if len > budget {
+            break;
+        }
+        budget -= len;
+        kept.push(line);
+    }
+    kept.reverse();
+    format!(
+        "{} earlier messages, of which these requests are the latest:\n{}",
+        messages.len(),
+        kept.join("\n")
+    )
+}
@@ -1,18 +1,26 @@
+use crate::backup::{BackupInfo, BackupManager};
 use anyhow::Result;
-use fennec_commands::{CommandContext, CommandExecutionResult, CommandRegistry};
-use fennec_core::{command::CommandPreview, config::Config};
+use fennec_commands::{
+    CommandContext, CommandExecutionResult, CommandProgress, CommandRegistry, ProgressReporter,
+};
+use fennec_core::{
+    command::{Capability, CommandPreview},
+    config::{ApprovalPolicies, ApprovalPolicy, Config},
+};
 use fennec_security::{
     approval::{
-        ApprovalManager, ApprovalRequest, ApprovalStatus as SecurityApprovalStatus, RiskLevel,
+        select_approval_policy, ApprovalManager, ApprovalPrompt, ApprovalRequest,
+        ApprovalStatus as SecurityApprovalStatus, RiskLevel,
     },
     audit::AuditLogger,
     SandboxLevel,
 };
+use fennec_telemetry::instrument_with_context;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{watch, RwLock};
 use tokio::time::Duration;
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
@@ -51,34 +59,16 @@ pub struct ExecutionInfo {
     pub approval_timeout: Option<Duration>,
     pub backup_info: Option<BackupInfo>,
     pub session_id: Uuid,
+    /// Capabilities the command needs with its arguments
+    #[serde(default)]
+    pub capabilities: Vec<Capability>,
 }
 
-/// Information about a backup created for an execution
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BackupInfo {
-    pub id: Uuid,
-    pub timestamp: chrono::DateTime<chrono::Utc>,
-    pub affected_files: Vec<PathBuf>,
-    pub backup_path: PathBuf,
-    pub description: String,
-}
-
-/// Configuration for backup retention
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BackupRetentionConfig {
-    pub max_backups: usize,
-    pub max_age_days: u64,
-    pub cleanup_interval_hours: u64,
-}
-
-impl Default for BackupRetentionConfig {
-    fn default() -> Self {
-        Self {
-            max_backups: 100,
-            max_age_days: 30,
-            cleanup_interval_hours: 24,
-        }
-    }
+/// Live view of an execution: its state and what the command last reported
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionProgress {
+    pub state: CommandState,
+    pub progress: CommandProgress,
 }
 
 /// Approval status for command execution
@@ -124,9 +114,14 @@ pub trait ApprovalHandler: Send + Sync {
 }
 
 /// Default approval handler that integrates with the security approval system
+///
+/// Requests are first decided by the approval policy table; only those it
+/// leaves to the user reach the prompt.
 pub struct DefaultApprovalHandler {
     approval_rules: HashMap<String, SandboxLevel>,
     approval_manager: ApprovalManager,
+    policies: ApprovalPolicies,
+    audit_logger: Option<Arc<AuditLogger>>,
 }
 
 impl Default for DefaultApprovalHandler {
@@ -142,6 +137,8 @@ impl Default for DefaultApprovalHandler {
         Self {
             approval_rules,
             approval_manager: ApprovalManager::default(),
+            policies: ApprovalPolicies::default(),
+            audit_logger: None,
         }
     }
 }
@@ -152,12 +149,38 @@ impl DefaultApprovalHandler {
         approval_rules.insert("run".to_string(), SandboxLevel::ReadOnly);
         approval_rules.insert("edit".to_string(), SandboxLevel::WorkspaceWrite);
 
+        let mut policies = ApprovalPolicies::default();
+        if auto_approve_low_risk {
+            policies.low = ApprovalPolicy::AutoApprove;
+        }
+
         Self {
             approval_rules,
-            approval_manager: ApprovalManager::new(auto_approve_low_risk, interactive_mode),
+            // The policy table decides before the manager is asked
+            approval_manager: ApprovalManager::new(false, interactive_mode),
+            policies,
+            audit_logger: None,
         }
     }
 
+    /// Decide requests by `policies` before prompting
+    pub fn with_policies(mut self, policies: ApprovalPolicies) -> Self {
+        self.policies = policies;
+        self
+    }
+
+    /// Record which policy decided each request in `audit_logger`
+    pub fn with_audit_logger(mut self, audit_logger: Arc<AuditLogger>) -> Self {
+        self.audit_logger = Some(audit_logger);
+        self
+    }
+
+    /// Ask `prompt`, e.g. a TUI dialog, instead of the terminal
+    pub fn with_prompt(mut self, prompt: Arc<dyn ApprovalPrompt>) -> Self {
+        self.approval_manager = self.approval_manager.with_prompt(prompt);
+        self
+    }
+
     /// Create an approval request from execution info
     fn create_approval_request(&self, execution_info: &ExecutionInfo) -> ApprovalRequest {
         let risk_level = self.assess_risk_level(execution_info);
@@ -166,6 +189,7 @@ impl DefaultApprovalHandler {
             format!("Execution ID: {}", execution_info.id),
             format!("Session ID: {}", execution_info.session_id),
         ];
+        let mut targets = Vec::new();
 
         // Add preview details if available
         if let Some(preview) = &execution_info.preview {
@@ -175,15 +199,23 @@ impl DefaultApprovalHandler {
                     fennec_core::command::PreviewAction::ReadFile { path } => {
                         details.push(format!("Will read file: {}", path));
                     }
-                    fennec_core::command::PreviewAction::WriteFile { path, content } => {
+                    fennec_core::command::PreviewAction::WriteFile { path, content, .. } => {
                         details.push(format!(
                             "Will write to file: {} ({} bytes)",
                             path,
                             content.len()
                         ));
+                        targets.push(std::path::PathBuf::from(path));
+                    }
+                    fennec_core::command::PreviewAction::DeletePath {
+                        path, file_count, ..
+                    } => {
+                        details.push(format!("Will delete: {} ({} files)", path, file_count));
+                        targets.push(std::path::PathBuf::from(path));
                     }
-                    fennec_core::command::PreviewAction::ExecuteShell { command } => {
+                    fennec_core::command::PreviewAction::ExecuteShell { command, .. } => {
                         details.push(format!("Will execute: {}", command));
+                        targets.extend(fennec_security::shell_command_targets(command));
                     }
                 }
             }
@@ -194,6 +226,8 @@ impl DefaultApprovalHandler {
             description: format!("Execute '{}' command", execution_info.command_name),
             risk_level,
             details,
+            diff: None,
+            targets,
         }
     }
 
@@ -217,10 +251,51 @@ impl ApprovalHandler for DefaultApprovalHandler {
     ) -> Result<ApprovalStatus> {
         let approval_request = self.create_approval_request(execution_info);
 
+        let (policy, source) = select_approval_policy(
+            &self.policies,
+            &approval_request.risk_level,
+            &execution_info.capabilities,
+        );
+        if let Some(audit_logger) = &self.audit_logger {
+            if let Err(e) = audit_logger
+                .log_approval_policy(
+                    execution_info.session_id,
+                    &execution_info.command_name,
+                    &source.to_string(),
+                    &policy.to_string(),
+                )
+                .await
+            {
+                warn!(
+                    "Failed to audit approval policy for {}: {}",
+                    execution_info.id, e
+                );
+            }
+        }
+        debug!(
+            "Approval policy for {} ({}): {}",
+            execution_info.id, source, policy
+        );
+        match policy {
+            ApprovalPolicy::AutoApprove => {
+                return Ok(ApprovalStatus::Approved {
+                    approved_at: chrono::Utc::now(),
+                })
+            }
+            ApprovalPolicy::AutoDeny => {
+                return Ok(ApprovalStatus::Denied {
+                    reason: format!("Denied by the approval policy for {}", source),
+                })
+            }
+            ApprovalPolicy::Prompt => {}
+        }
+
         // Use tokio::time::timeout to implement timeout functionality
-        match tokio::time::timeout(timeout, async {
-            self.approval_manager.request_approval(&approval_request)
-        })
+        match tokio::time::timeout(
+            timeout,
+            self.approval_manager
+                .request_approval_async(&approval_request),
+        )
         .await
         {
             Ok(Ok(security_status)) => Ok(security_status.into()),
@@ -243,218 +318,6 @@ impl ApprovalHandler for DefaultApprovalHandler {
     }
 }
 
-/// Backup manager for creating and managing file backups
-pub struct BackupManager {
-    backup_root: PathBuf,
-    retention_config: BackupRetentionConfig,
-    audit_logger: Arc<AuditLogger>,
-}
-
-impl BackupManager {
-    pub fn new(
-        backup_root: PathBuf,
-        retention_config: BackupRetentionConfig,
-        audit_logger: Arc<AuditLogger>,
-    ) -> Self {
-        Self {
-            backup_root,
-            retention_config,
-            audit_logger,
-        }
-    }
-
-    /// Create a backup for files that will be modified by a command
-    pub async fn create_backup(
-        &self,
-        files: &[PathBuf],
-        description: String,
-    ) -> Result<BackupInfo> {
-        let backup_id = Uuid::new_v4();
-        let timestamp = chrono::Utc::now();
-        let backup_dir = self
-            .backup_root
-            .join(timestamp.format("%Y-%m-%d").to_string())
-            .join(backup_id.to_string());
-
-        tokio::fs::create_dir_all(&backup_dir).await?;
-
-        let mut backed_up_files = Vec::new();
-
-        for file_path in files {
-            if file_path.exists() {
-                let relative_path = file_path.strip_prefix("/").unwrap_or(file_path);
-                let backup_file = backup_dir.join(relative_path);
-
-                if let Some(parent) = backup_file.parent() {
-                    tokio::fs::create_dir_all(parent).await?;
-                }
-
-                // Retry copy operation on Windows file locking issues
-                let mut attempts = 0;
-                let max_attempts = 5;
-                loop {
-                    match tokio::fs::copy(file_path, &backup_file).await {
-                        Ok(_) => break,
-                        Err(e) if attempts < max_attempts && e.raw_os_error() == Some(32) => {
-                            attempts += 1;
-                            tokio::time::sleep(tokio::time::Duration::from_millis(50 * attempts))
-                                .await;
-                        }
-                        Err(e) => return Err(e.into()),
-                    }
-                }
-                backed_up_files.push(file_path.clone());
-
-                debug!(
-                    "Backed up file: {} -> {}",
-                    file_path.display(),
-                    backup_file.display()
-                );
-            }
-        }
-
-        let backup_info = BackupInfo {
-            id: backup_id,
-            timestamp,
-            affected_files: backed_up_files,
-            backup_path: backup_dir,
-            description: description.clone(),
-        };
-
-        // Write backup metadata
-        let metadata_file = backup_info.backup_path.join("metadata.json");
-        let metadata_json = serde_json::to_string_pretty(&backup_info)?;
-        tokio::fs::write(&metadata_file, metadata_json).await?;
-
-        // Log backup creation
-        self.audit_logger
-            .log_security_event(
-                None,
-                "backup_created",
-                &format!("Backup created: {} ({})", backup_id, description),
-            )
-            .await?;
-
-        info!(
-            "Created backup: {} with {} files",
-            backup_id,
-            backup_info.affected_files.len()
-        );
-
-        Ok(backup_info)
-    }
-
-    /// Restore files from a backup
-    pub async fn restore_backup(&self, backup_info: &BackupInfo) -> Result<()> {
-        if !backup_info.backup_path.exists() {
-            return Err(anyhow::anyhow!(
-                "Backup path does not exist: {}",
-                backup_info.backup_path.display()
-            ));
-        }
-
-        for file_path in &backup_info.affected_files {
-            let relative_path = file_path.strip_prefix("/").unwrap_or(file_path);
-            let backup_file = backup_info.backup_path.join(relative_path);
-
-            if backup_file.exists() {
-                if let Some(parent) = file_path.parent() {
-                    tokio::fs::create_dir_all(parent).await?;
-                }
-
-                // Retry copy operation on Windows file locking issues
-                let mut attempts = 0;
-                let max_attempts = 3;
-                loop {
-                    match tokio::fs::copy(&backup_file, file_path).await {
-                        Ok(_) => break,
-                        Err(e) if attempts < max_attempts && e.raw_os_error() == Some(32) => {
-                            attempts += 1;
-                            tokio::time::sleep(tokio::time::Duration::from_millis(10 * attempts))
-                                .await;
-                        }
-                        Err(e) => return Err(e.into()),
-                    }
-                }
-                debug!(
-                    "Restored file: {} -> {}",
-                    backup_file.display(),
-                    file_path.display()
-                );
-            }
-        }
-
-        // Log backup restoration
-        self.audit_logger
-            .log_security_event(
-                None,
-                "backup_restored",
-                &format!(
-                    "Backup restored: {} ({})",
-                    backup_info.id, backup_info.description
-                ),
-            )
-            .await?;
-
-        info!(
-            "Restored backup: {} with {} files",
-            backup_info.id,
-            backup_info.affected_files.len()
-        );
-
-        Ok(())
-    }
-
-    /// Clean up old backups based on retention policy
-    pub async fn cleanup_backups(&self) -> Result<()> {
-        if !self.backup_root.exists() {
-            return Ok(());
-        }
-
-        let cutoff_date =
-            chrono::Utc::now() - chrono::Duration::days(self.retention_config.max_age_days as i64);
-        let mut backup_count = 0;
-        let mut cleaned_count = 0;
-
-        let mut entries = tokio::fs::read_dir(&self.backup_root).await?;
-        while let Some(entry) = entries.next_entry().await? {
-            if entry.file_type().await?.is_dir() {
-                let mut day_entries = tokio::fs::read_dir(entry.path()).await?;
-                while let Some(backup_entry) = day_entries.next_entry().await? {
-                    if backup_entry.file_type().await?.is_dir() {
-                        backup_count += 1;
-
-                        // Check metadata for creation time
-                        let metadata_file = backup_entry.path().join("metadata.json");
-                        if let Ok(metadata_content) =
-                            tokio::fs::read_to_string(&metadata_file).await
-                        {
-                            if let Ok(backup_info) =
-                                serde_json::from_str::<BackupInfo>(&metadata_content)
-                            {
-                                if backup_info.timestamp < cutoff_date
-                                    || backup_count > self.retention_config.max_backups
-                                {
-                                    tokio::fs::remove_dir_all(backup_entry.path()).await?;
-                                    cleaned_count += 1;
-
-                                    debug!("Cleaned up backup: {}", backup_info.id);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        if cleaned_count > 0 {
-            info!("Cleaned up {} old backups", cleaned_count);
-        }
-
-        Ok(())
-    }
-}
-
 /// Main command execution engine
 pub struct CommandExecutionEngine {
     command_registry: Arc<CommandRegistry>,
@@ -462,9 +325,38 @@ pub struct CommandExecutionEngine {
     backup_manager: Arc<BackupManager>,
     audit_logger: Arc<AuditLogger>,
     executions: Arc<RwLock<HashMap<Uuid, ExecutionInfo>>>,
+    progress: Arc<Mutex<HashMap<Uuid, Arc<watch::Sender<ExecutionProgress>>>>>,
+    /// Most permissive level commands run at, once the sandbox has been
+    /// tightened mid-session
+    sandbox_ceiling: Arc<RwLock<Option<SandboxLevel>>>,
     config: Config,
 }
 
+/// Lower `ceiling` to `level` for the rest of the session. Raising it is
+/// refused; that takes a restart with the new level. Returns whether the
+/// ceiling dropped.
+pub(crate) fn lower_sandbox_ceiling(
+    ceiling: &mut Option<SandboxLevel>,
+    level: &SandboxLevel,
+) -> fennec_core::Result<bool> {
+    match ceiling {
+        Some(current) if level > current => Err(fennec_core::FennecError::Security(Box::new(
+            std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                format!(
+                    "Cannot raise the sandbox from {} to {} while running; restart with the new --sandbox level",
+                    current, level
+                ),
+            ),
+        ))),
+        Some(current) if level == current => Ok(false),
+        _ => {
+            *ceiling = Some(level.clone());
+            Ok(true)
+        }
+    }
+}
+
 impl CommandExecutionEngine {
     /// Create a new command execution engine
     pub fn new(
@@ -480,18 +372,40 @@ impl CommandExecutionEngine {
             backup_manager,
             audit_logger,
             executions: Arc::new(RwLock::new(HashMap::new())),
+            progress: Arc::new(Mutex::new(HashMap::new())),
+            sandbox_ceiling: Arc::new(RwLock::new(None)),
             config,
         }
     }
 
+    /// Run commands at no more than `level` from now on, whatever level
+    /// they are submitted with. The ceiling only ever drops; returns whether
+    /// `level` lowered it.
+    pub async fn set_sandbox_level(&self, level: SandboxLevel) -> fennec_core::Result<bool> {
+        let lowered = lower_sandbox_ceiling(&mut *self.sandbox_ceiling.write().await, &level)?;
+        if lowered {
+            info!("Commands now run at sandbox level {} at most", level);
+        }
+        Ok(lowered)
+    }
+
+    /// `level`, lowered to the ceiling set by [`Self::set_sandbox_level`]
+    async fn cap_sandbox_level(&self, level: SandboxLevel) -> SandboxLevel {
+        match self.sandbox_ceiling.read().await.clone() {
+            Some(ceiling) => level.min(ceiling),
+            None => level,
+        }
+    }
+
     /// Submit a command for execution
     pub async fn submit_command(
         &self,
         command_name: String,
         args: serde_json::Value,
-        context: CommandContext,
+        mut context: CommandContext,
     ) -> Result<Uuid> {
         let execution_id = Uuid::new_v4();
+        context.sandbox_level = self.cap_sandbox_level(context.sandbox_level).await;
         let now = chrono::Utc::now();
 
         // Check if command exists
@@ -503,6 +417,7 @@ impl CommandExecutionEngine {
 
         // Generate preview
         let preview = command.preview(&args, &context).await?;
+        let capabilities = command.required_capabilities(&args);
 
         // Determine if approval is required
         let requires_approval = self
@@ -526,6 +441,7 @@ impl CommandExecutionEngine {
             },
             backup_info: None,
             session_id: context.session_id,
+            capabilities,
         };
 
         // Store execution info
@@ -533,6 +449,7 @@ impl CommandExecutionEngine {
             let mut executions = self.executions.write().await;
             executions.insert(execution_id, execution_info.clone());
         }
+        self.publish_state(execution_id, CommandState::Pending);
 
         // Log command submission
         self.audit_logger
@@ -553,7 +470,7 @@ impl CommandExecutionEngine {
 
         // If no approval required, execute immediately
         if !requires_approval {
-            tokio::spawn({
+            tokio::spawn(instrument_with_context({
                 let engine = self.clone_arc();
                 let context = context.clone();
                 async move {
@@ -561,7 +478,7 @@ impl CommandExecutionEngine {
                         error!("Failed to execute command {}: {}", execution_id, e);
                     }
                 }
-            });
+            }));
         }
 
         Ok(execution_id)
@@ -594,6 +511,7 @@ impl CommandExecutionEngine {
             let mut executions = self.executions.write().await;
             executions.insert(execution_id, execution_info);
         }
+        self.publish_state(execution_id, CommandState::Approved);
 
         // Log approval
         self.audit_logger
@@ -607,24 +525,27 @@ impl CommandExecutionEngine {
         info!("Command execution approved: {}", execution_id);
 
         // Start execution
-        tokio::spawn({
+        let sandbox_level = self.cap_sandbox_level(SandboxLevel::WorkspaceWrite).await;
+        tokio::spawn(instrument_with_context({
             let engine = self.clone_arc();
             let context = CommandContext {
                 session_id,
                 user_id: None,
                 workspace_path: None,
-                sandbox_level: SandboxLevel::WorkspaceWrite, // TODO: Get from execution context
+                sandbox_level,
                 dry_run: false,
                 preview_only: false,
                 cancellation_token: tokio_util::sync::CancellationToken::new(),
                 action_log: None,
+                timeout: None,
+                progress: None,
             };
             async move {
                 if let Err(e) = engine.execute_command_internal(execution_id, context).await {
                     error!("Failed to execute approved command {}: {}", execution_id, e);
                 }
             }
-        });
+        }));
 
         Ok(())
     }
@@ -655,6 +576,7 @@ impl CommandExecutionEngine {
             let mut executions = self.executions.write().await;
             executions.insert(execution_id, execution_info.clone());
         }
+        self.publish_state(execution_id, CommandState::Cancelled);
 
         // Log denial
         self.audit_logger
@@ -670,6 +592,40 @@ impl CommandExecutionEngine {
         Ok(())
     }
 
+    /// Follow the state of `execution_id` and the progress its command
+    /// reports while it runs
+    pub fn watch_progress(&self, execution_id: Uuid) -> Option<watch::Receiver<ExecutionProgress>> {
+        self.progress
+            .lock()
+            .unwrap()
+            .get(&execution_id)
+            .map(|sender| sender.subscribe())
+    }
+
+    /// Send the new state of `execution_id` to its watchers
+    fn publish_state(&self, execution_id: Uuid, state: CommandState) {
+        let mut progress = self.progress.lock().unwrap();
+        match progress.get(&execution_id) {
+            Some(sender) => sender.send_modify(|p| p.state = state),
+            None => {
+                let (sender, _) = watch::channel(ExecutionProgress {
+                    state,
+                    progress: CommandProgress::default(),
+                });
+                progress.insert(execution_id, Arc::new(sender));
+            }
+        }
+    }
+
+    /// Reporter forwarding a command's progress to the watchers of
+    /// `execution_id`
+    fn progress_reporter(&self, execution_id: Uuid) -> Option<ProgressReporter> {
+        let sender = self.progress.lock().unwrap().get(&execution_id)?.clone();
+        Some(ProgressReporter::new(move |reported: &CommandProgress| {
+            sender.send_modify(|p| p.progress = reported.clone());
+        }))
+    }
+
     /// Get execution status
     pub async fn get_execution_status(&self, execution_id: Uuid) -> Option<ExecutionInfo> {
         let executions = self.executions.read().await;
@@ -686,6 +642,30 @@ impl CommandExecutionEngine {
             .collect()
     }
 
+    /// Take back an execution saved before a restart. One that was approved
+    /// or running never finished and is marked failed; a pending one waits
+    /// for approval again.
+    pub async fn restore_execution(&self, mut execution_info: ExecutionInfo) {
+        if matches!(
+            execution_info.state,
+            CommandState::Approved | CommandState::Executing
+        ) {
+            execution_info.state = CommandState::Failed {
+                reason: "Interrupted by restart".to_string(),
+            };
+            execution_info.updated_at = chrono::Utc::now();
+        }
+        debug!(
+            "Restored execution {} ({:?})",
+            execution_info.id, execution_info.state
+        );
+        self.publish_state(execution_info.id, execution_info.state.clone());
+        self.executions
+            .write()
+            .await
+            .insert(execution_info.id, execution_info);
+    }
+
     /// Rollback a command execution using its backup
     pub async fn rollback_execution(&self, execution_id: Uuid) -> Result<()> {
         let execution_info = {
@@ -723,7 +703,7 @@ impl CommandExecutionEngine {
     async fn execute_command_internal(
         &self,
         execution_id: Uuid,
-        context: CommandContext,
+        mut context: CommandContext,
     ) -> Result<()> {
         let execution_info = {
             let executions = self.executions.read().await;
@@ -741,6 +721,7 @@ impl CommandExecutionEngine {
                 exec.updated_at = chrono::Utc::now();
             }
         }
+        self.publish_state(execution_id, CommandState::Executing);
 
         // Create backup if needed for destructive operations
         let backup_info = if self.is_destructive_command(&execution_info.command_name) {
@@ -771,10 +752,25 @@ impl CommandExecutionEngine {
         }
 
         // Execute the command
-        let result = self
+        context.progress = self.progress_reporter(execution_id);
+        let result = match self
             .command_registry
             .execute_command(&execution_info.command_name, &execution_info.args, &context)
-            .await?;
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                let state = CommandState::Failed {
+                    reason: e.to_string(),
+                };
+                if let Some(exec) = self.executions.write().await.get_mut(&execution_id) {
+                    exec.state = state.clone();
+                    exec.updated_at = chrono::Utc::now();
+                }
+                self.publish_state(execution_id, state);
+                return Err(e);
+            }
+        };
 
         // Update execution state based on result
         let error_message = result.error.as_deref().unwrap_or("Unknown error");
@@ -789,11 +785,12 @@ impl CommandExecutionEngine {
         {
             let mut executions = self.executions.write().await;
             if let Some(exec) = executions.get_mut(&execution_id) {
-                exec.state = final_state;
+                exec.state = final_state.clone();
                 exec.result = Some(result.clone());
                 exec.updated_at = chrono::Utc::now();
             }
         }
+        self.publish_state(execution_id, final_state);
 
         // Log execution completion
         self.audit_logger
@@ -833,6 +830,9 @@ impl CommandExecutionEngine {
                     fennec_core::command::PreviewAction::WriteFile { path, .. } => {
                         files.push(PathBuf::from(path));
                     }
+                    fennec_core::command::PreviewAction::DeletePath { .. } => {
+                        // Deleted paths may be whole directories, which backups don't hold
+                    }
                     fennec_core::command::PreviewAction::ReadFile { .. } => {
                         // Read operations don't need backup
                     }
@@ -855,6 +855,8 @@ impl CommandExecutionEngine {
             backup_manager: self.backup_manager.clone(),
             audit_logger: self.audit_logger.clone(),
             executions: self.executions.clone(),
+            progress: self.progress.clone(),
+            sandbox_ceiling: self.sandbox_ceiling.clone(),
             config: self.config.clone(),
         })
     }
@@ -868,6 +870,8 @@ impl Clone for CommandExecutionEngine {
             backup_manager: self.backup_manager.clone(),
             audit_logger: self.audit_logger.clone(),
             executions: self.executions.clone(),
+            progress: self.progress.clone(),
+            sandbox_ceiling: self.sandbox_ceiling.clone(),
             config: self.config.clone(),
         }
     }
@@ -876,6 +880,7 @@ impl Clone for CommandExecutionEngine {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::backup::BackupRetentionConfig;
     use fennec_commands::create_command_registry;
     use tempfile::TempDir;
     use tokio_util::sync::CancellationToken;
@@ -919,6 +924,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         let execution_id = engine
@@ -935,6 +942,47 @@ mod tests {
         assert!(!status.requires_approval); // plan command shouldn't require approval in ReadOnly
     }
 
+    #[tokio::test]
+    async fn test_sandbox_ceiling_caps_submitted_commands() {
+        let (engine, _temp_dir) = create_test_engine().await.unwrap();
+        assert_eq!(
+            engine.cap_sandbox_level(SandboxLevel::FullAccess).await,
+            SandboxLevel::FullAccess
+        );
+
+        assert!(engine
+            .set_sandbox_level(SandboxLevel::ReadOnly)
+            .await
+            .unwrap());
+        assert_eq!(
+            engine.cap_sandbox_level(SandboxLevel::FullAccess).await,
+            SandboxLevel::ReadOnly
+        );
+
+        // Clones made for running commands see the ceiling too
+        assert_eq!(
+            engine
+                .clone_arc()
+                .cap_sandbox_level(SandboxLevel::WorkspaceWrite)
+                .await,
+            SandboxLevel::ReadOnly
+        );
+
+        // The ceiling never goes back up
+        assert!(engine
+            .set_sandbox_level(SandboxLevel::FullAccess)
+            .await
+            .is_err());
+        assert!(!engine
+            .set_sandbox_level(SandboxLevel::ReadOnly)
+            .await
+            .unwrap());
+        assert_eq!(
+            engine.cap_sandbox_level(SandboxLevel::FullAccess).await,
+            SandboxLevel::ReadOnly
+        );
+    }
+
     #[tokio::test]
     async fn test_approval_workflow() {
         let (engine, _temp_dir) = create_test_engine().await.unwrap();
@@ -948,6 +996,8 @@ mod tests {
             preview_only: false,
             cancellation_token: CancellationToken::new(),
             action_log: None,
+            timeout: None,
+            progress: None,
         };
 
         // Submit a command that requires approval
@@ -977,46 +1027,221 @@ mod tests {
         ));
     }
 
-    #[tokio::test]
-    #[cfg_attr(target_os = "windows", ignore = "Flaky on Windows due to file locking")]
-    async fn test_backup_creation() {
-        let temp_dir = TempDir::new().unwrap();
-        let audit_log_path = temp_dir.path().join("audit.log");
-        let backup_path = temp_dir.path().join("backups");
-        let test_file = temp_dir
-            .path()
-            .join(format!("test_{}.txt", uuid::Uuid::new_v4()));
+    /// Reports a scripted sequence of progress, one step per message on
+    /// its channel
+    struct ScriptedCommand {
+        descriptor: fennec_commands::CommandDescriptor,
+        steps: tokio::sync::Mutex<tokio::sync::mpsc::UnboundedReceiver<f32>>,
+    }
 
-        // Create a test file
-        tokio::fs::write(&test_file, "test content").await.unwrap();
+    #[async_trait::async_trait]
+    impl fennec_commands::CommandExecutor for ScriptedCommand {
+        fn descriptor(&self) -> &fennec_commands::CommandDescriptor {
+            &self.descriptor
+        }
 
-        // Add a small delay to ensure file handle is released on Windows after write
-        tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+        async fn preview(
+            &self,
+            _args: &serde_json::Value,
+            _context: &CommandContext,
+        ) -> Result<CommandPreview> {
+            Ok(CommandPreview {
+                command_id: Uuid::new_v4(),
+                description: "Scripted progress".to_string(),
+                actions: Vec::new(),
+                requires_approval: false,
+            })
+        }
 
-        let audit_logger = Arc::new(AuditLogger::with_path(audit_log_path).await.unwrap());
-        let backup_manager =
-            BackupManager::new(backup_path, BackupRetentionConfig::default(), audit_logger);
+        async fn execute(
+            &self,
+            _args: &serde_json::Value,
+            context: &CommandContext,
+        ) -> Result<fennec_core::command::CommandResult> {
+            let progress = context.progress.as_ref().expect("engine passes a reporter");
+            let mut steps = self.steps.lock().await;
+            let mut index = 0;
+            while let Some(percent) = steps.recv().await {
+                index += 1;
+                progress.step("chunk", index, Some(3));
+                progress.percent(percent);
+            }
+            Ok(fennec_core::command::CommandResult {
+                command_id: Uuid::new_v4(),
+                success: true,
+                output: "done".to_string(),
+                error: None,
+                data: None,
+            })
+        }
+
+        fn validate_args(&self, _args: &serde_json::Value) -> Result<()> {
+            Ok(())
+        }
+    }
 
-        let backup_info = backup_manager
-            .create_backup(&[test_file.clone()], "Test backup".to_string())
+    #[tokio::test]
+    async fn test_progress_is_streamed_to_watchers() {
+        let temp_dir = TempDir::new().unwrap();
+        let audit_logger = Arc::new(
+            AuditLogger::with_path(temp_dir.path().join("audit.log"))
+                .await
+                .unwrap(),
+        );
+        let (script, steps) = tokio::sync::mpsc::unbounded_channel();
+        let registry = Arc::new(CommandRegistry::new());
+        registry
+            .register_custom(Arc::new(ScriptedCommand {
+                descriptor: fennec_commands::CommandDescriptor {
+                    name: "scripted".to_string(),
+                    description: "Reports scripted progress".to_string(),
+                    version: "1.0.0".to_string(),
+                    author: None,
+                    capabilities_required: Vec::new(),
+                    sandbox_level_required: SandboxLevel::ReadOnly,
+                    supports_preview: true,
+                    supports_dry_run: false,
+                    timeout: None,
+                    alias_of: None,
+                },
+                steps: tokio::sync::Mutex::new(steps),
+            }))
             .await
             .unwrap();
+        let engine = CommandExecutionEngine::new(
+            registry,
+            Arc::new(DefaultApprovalHandler::default()),
+            Arc::new(BackupManager::new(
+                temp_dir.path().join("backups"),
+                BackupRetentionConfig::default(),
+                audit_logger.clone(),
+            )),
+            audit_logger,
+            Config::default(),
+        );
 
-        assert_eq!(backup_info.affected_files.len(), 1);
-        assert_eq!(backup_info.affected_files[0], test_file);
-        assert!(backup_info.backup_path.exists());
-
-        // Test restoration
-        tokio::fs::write(&test_file, "modified content")
+        let context = CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: None,
+            sandbox_level: SandboxLevel::ReadOnly,
+            dry_run: false,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log: None,
+            timeout: None,
+            progress: None,
+        };
+        let execution_id = engine
+            .submit_command("scripted".to_string(), serde_json::json!({}), context)
             .await
             .unwrap();
+        let mut progress = engine.watch_progress(execution_id).unwrap();
+
+        async fn observe(
+            progress: &mut watch::Receiver<ExecutionProgress>,
+            until: impl FnMut(&ExecutionProgress) -> bool,
+        ) -> ExecutionProgress {
+            tokio::time::timeout(Duration::from_secs(5), progress.wait_for(until))
+                .await
+                .expect("progress update")
+                .unwrap()
+                .clone()
+        }
+
+        observe(&mut progress, |p| p.state == CommandState::Executing).await;
+        for (index, percent) in [25.0, 60.0, 100.0].into_iter().enumerate() {
+            script.send(percent).unwrap();
+            let seen = observe(&mut progress, |p| p.progress.percent == Some(percent)).await;
+            assert_eq!(seen.state, CommandState::Executing);
+            assert_eq!(seen.progress.step.unwrap().index, index + 1);
+        }
+        drop(script);
 
-        // Add a small delay to ensure file handle is released on Windows
-        tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+        let finished = observe(&mut progress, |p| p.state == CommandState::Completed).await;
+        assert_eq!(finished.progress.percent, Some(100.0));
+    }
+
+    fn pending_execution(command_name: &str, capabilities: Vec<Capability>) -> ExecutionInfo {
+        let now = chrono::Utc::now();
+        ExecutionInfo {
+            id: Uuid::new_v4(),
+            command_name: command_name.to_string(),
+            args: serde_json::json!({}),
+            state: CommandState::Pending,
+            preview: None,
+            result: None,
+            created_at: now,
+            updated_at: now,
+            requires_approval: true,
+            approval_timeout: None,
+            backup_info: None,
+            session_id: Uuid::new_v4(),
+            capabilities,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_approval_policies_decide_before_prompting() {
+        let temp_dir = TempDir::new().unwrap();
+        let audit_log_path = temp_dir.path().join("audit.log");
+        let audit_logger = Arc::new(AuditLogger::with_path(&audit_log_path).await.unwrap());
+        let mut policies = ApprovalPolicies::default();
+        policies
+            .capabilities
+            .insert(Capability::NetworkAccess, ApprovalPolicy::AutoDeny);
+        // Non-interactive, so anything reaching the prompt is denied
+        let handler = DefaultApprovalHandler::new(true, false)
+            .with_policies(ApprovalPolicies {
+                low: ApprovalPolicy::AutoApprove,
+                ..policies
+            })
+            .with_audit_logger(audit_logger);
+        let timeout = Duration::from_secs(5);
+
+        let status = handler
+            .request_approval(
+                &pending_execution("diff", vec![Capability::ReadFile]),
+                timeout,
+            )
+            .await
+            .unwrap();
+        assert!(matches!(status, ApprovalStatus::Approved { .. }));
 
-        backup_manager.restore_backup(&backup_info).await.unwrap();
+        let status = handler
+            .request_approval(
+                &pending_execution("diff", vec![Capability::NetworkAccess]),
+                timeout,
+            )
+            .await
+            .unwrap();
+        match status {
+            ApprovalStatus::Denied { reason } => assert!(reason.contains("NetworkAccess")),
+            other => panic!("expected a denial, got {:?}", other),
+        }
 
-        let restored_content = tokio::fs::read_to_string(&test_file).await.unwrap();
-        assert_eq!(restored_content, "test content");
+        let log = tokio::fs::read_to_string(&audit_log_path).await.unwrap();
+        let decisions: Vec<(String, String)> = log
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .map(|event| {
+                let details = &event["details"];
+                assert_eq!(details["security_event_type"], "approval_policy");
+                (
+                    details["source"].as_str().unwrap().to_string(),
+                    details["policy"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            decisions,
+            [
+                ("risk level LOW".to_string(), "auto_approve".to_string()),
+                (
+                    "capability NetworkAccess".to_string(),
+                    "auto_deny".to_string()
+                ),
+            ]
+        );
     }
 }
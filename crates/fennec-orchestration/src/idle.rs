@@ -0,0 +1,142 @@
+//! Detection of sessions nobody has sent a message or run a command in for
+//! a while.
+
+use fennec_core::{session::Session, transcript::Transcript};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Source of the current time for idle detection
+pub trait Clock: fmt::Debug + Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// The real clock
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Something that happened to an idle session
+#[derive(Debug, Clone)]
+pub enum IdleEvent {
+    /// The session has been idle long enough to warn about; it is ended
+    /// after `ends_in` more without activity
+    Warning { session_id: Uuid, ends_in: Duration },
+    /// The session was idle for too long and has been ended
+    Ended {
+        session: Session,
+        transcript: Transcript,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Activity {
+    last: Instant,
+    warned: bool,
+}
+
+/// Time since the last activity of each open session
+#[derive(Debug)]
+pub(crate) struct IdleTracker {
+    warn_after: Duration,
+    end_after: Duration,
+    sessions: HashMap<Uuid, Activity>,
+}
+
+/// What [`IdleTracker::poll`] found
+#[derive(Debug, Default)]
+pub(crate) struct IdlePoll {
+    pub warnings: Vec<(Uuid, Duration)>,
+    pub expired: Vec<Uuid>,
+}
+
+impl IdleTracker {
+    pub fn new(warn_after: Duration, end_after: Duration) -> Self {
+        Self {
+            warn_after,
+            end_after,
+            sessions: HashMap::new(),
+        }
+    }
+
+    /// Note activity in `session_id` at `now`, starting its idle time over
+    pub fn touch(&mut self, session_id: Uuid, now: Instant) {
+        self.sessions.insert(
+            session_id,
+            Activity {
+                last: now,
+                warned: false,
+            },
+        );
+    }
+
+    pub fn forget(&mut self, session_id: Uuid) {
+        self.sessions.remove(&session_id);
+    }
+
+    /// Sessions that just became idle enough to warn about, each reported
+    /// once, and sessions idle for long enough to end
+    pub fn poll(&mut self, now: Instant) -> IdlePoll {
+        let mut poll = IdlePoll::default();
+        for (session_id, activity) in &mut self.sessions {
+            let idle = now.saturating_duration_since(activity.last);
+            if idle >= self.end_after {
+                poll.expired.push(*session_id);
+            } else if idle >= self.warn_after && !activity.warned {
+                activity.warned = true;
+                poll.warnings.push((*session_id, self.end_after - idle));
+            }
+        }
+        for session_id in &poll.expired {
+            self.sessions.remove(session_id);
+        }
+        poll
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn test_idle_session_is_warned_once_then_expires() {
+        let start = Instant::now();
+        let session_id = Uuid::new_v4();
+        let mut tracker = IdleTracker::new(30 * MINUTE, 60 * MINUTE);
+        tracker.touch(session_id, start);
+
+        let poll = tracker.poll(start + 29 * MINUTE);
+        assert!(poll.warnings.is_empty() && poll.expired.is_empty());
+
+        let poll = tracker.poll(start + 40 * MINUTE);
+        assert_eq!(poll.warnings, [(session_id, 20 * MINUTE)]);
+        assert!(tracker.poll(start + 45 * MINUTE).warnings.is_empty());
+
+        let poll = tracker.poll(start + 60 * MINUTE);
+        assert_eq!(poll.expired, [session_id]);
+        assert!(tracker.poll(start + 90 * MINUTE).expired.is_empty());
+    }
+
+    #[test]
+    fn test_activity_resets_idle_time() {
+        let start = Instant::now();
+        let session_id = Uuid::new_v4();
+        let mut tracker = IdleTracker::new(30 * MINUTE, 60 * MINUTE);
+        tracker.touch(session_id, start);
+        assert_eq!(tracker.poll(start + 35 * MINUTE).warnings.len(), 1);
+
+        tracker.touch(session_id, start + 50 * MINUTE);
+        let poll = tracker.poll(start + 70 * MINUTE);
+        assert!(poll.warnings.is_empty() && poll.expired.is_empty());
+        assert_eq!(tracker.poll(start + 85 * MINUTE).warnings.len(), 1);
+        assert_eq!(tracker.poll(start + 110 * MINUTE).expired, [session_id]);
+    }
+}
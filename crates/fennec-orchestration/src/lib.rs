@@ -1,10 +1,26 @@
+pub mod backup;
+pub mod checkpoint;
 pub mod coordinator;
 pub mod execution;
+pub mod idle;
+pub mod maintenance;
 pub mod router;
 pub mod session;
 
+pub use backup::{BackupEntry, BackupInfo, BackupManager, BackupRetentionConfig};
+pub use checkpoint::{SessionCheckpoint, SessionSnapshot};
+pub use coordinator::Coordinator;
 pub use execution::{
-    ApprovalHandler, ApprovalStatus, BackupInfo, BackupManager, BackupRetentionConfig,
-    CommandExecutionEngine, CommandState, DefaultApprovalHandler, ExecutionInfo,
+    ApprovalHandler, ApprovalStatus, CommandExecutionEngine, CommandState, DefaultApprovalHandler,
+    ExecutionInfo, ExecutionProgress,
+};
+pub use fennec_provider::{BudgetStatus, UsageReport};
+pub use idle::{Clock, IdleEvent, SystemClock};
+pub use maintenance::{
+    BusySignal, JobOutcome, JobRun, JobStatus, MaintenanceJob, MaintenanceScheduler,
+};
+pub use router::{
+    CommandIntent, IntentClassifier, IntentRouter, ProviderIntentClassifier, RouterThresholds,
+    RoutingDecision,
 };
 pub use session::SessionManager;
@@ -0,0 +1,500 @@
+//! Periodic housekeeping: compacting transcripts, pruning trash and old
+//! backups, trimming caches. Jobs run on an interval with some jitter, are
+//! skipped while the system is busy, and can be run by hand.
+
+use crate::idle::{Clock, SystemClock};
+use anyhow::{anyhow, Result};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+
+/// A housekeeping task run by the [`MaintenanceScheduler`]
+#[async_trait::async_trait]
+pub trait MaintenanceJob: Send + Sync {
+    /// Unique name, used by [`MaintenanceScheduler::run_now`]
+    fn name(&self) -> &str;
+
+    /// Do the work, stopping early once `cancel` is cancelled. Returns a
+    /// short summary of what was done.
+    async fn run(&self, cancel: CancellationToken) -> Result<String>;
+}
+
+/// Whether the system is too busy for housekeeping right now
+pub type BusySignal = Arc<dyn Fn() -> bool + Send + Sync>;
+
+/// How a job run ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobOutcome {
+    Succeeded(String),
+    Failed(String),
+    Panicked(String),
+    Cancelled,
+}
+
+/// The result of one run of a job
+#[derive(Debug, Clone)]
+pub struct JobRun {
+    pub job: String,
+    pub started_at: Instant,
+    pub duration: Duration,
+    /// Whether it was started by [`MaintenanceScheduler::run_now`]
+    pub manual: bool,
+    pub outcome: JobOutcome,
+}
+
+/// A registered job and when it runs next
+#[derive(Debug, Clone)]
+pub struct JobStatus {
+    pub name: String,
+    pub interval: Duration,
+    pub next_run: Instant,
+    pub running: bool,
+    pub last_run: Option<JobRun>,
+    /// Times the job was due but skipped because the system was busy
+    pub busy_skips: u64,
+}
+
+struct Entry {
+    job: Arc<dyn MaintenanceJob>,
+    interval: Duration,
+    jitter: Duration,
+    next_run: Instant,
+    runs: u64,
+    running: Option<CancellationToken>,
+    last_run: Option<JobRun>,
+    busy_skips: u64,
+}
+
+impl Entry {
+    /// Next run after `now`: the interval plus a share of the jitter that
+    /// differs between jobs and runs, so jobs registered together drift apart
+    fn schedule_after(&mut self, now: Instant) {
+        let mut hasher = DefaultHasher::new();
+        self.job.name().hash(&mut hasher);
+        self.runs.hash(&mut hasher);
+        let fraction = (hasher.finish() % 1_000) as f64 / 1_000.0;
+        self.next_run = now + self.interval + self.jitter.mul_f64(fraction);
+    }
+}
+
+/// Runs registered [`MaintenanceJob`]s when they are due. A job that fails
+/// or panics is recorded and rescheduled without affecting the others.
+pub struct MaintenanceScheduler {
+    jobs: Mutex<BTreeMap<String, Entry>>,
+    clock: Arc<dyn Clock>,
+    busy: Option<BusySignal>,
+    shutdown: CancellationToken,
+}
+
+impl MaintenanceScheduler {
+    pub fn new() -> Self {
+        Self {
+            jobs: Mutex::new(BTreeMap::new()),
+            clock: Arc::new(SystemClock),
+            busy: None,
+            shutdown: CancellationToken::new(),
+        }
+    }
+
+    /// Decide when jobs are due with `clock`
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Skip due jobs while `busy` returns true
+    pub fn with_busy_signal(mut self, busy: BusySignal) -> Self {
+        self.busy = Some(busy);
+        self
+    }
+
+    /// Run `job` every `interval`, plus up to `jitter`. The first run is one
+    /// interval from now.
+    pub fn register(
+        &self,
+        job: Arc<dyn MaintenanceJob>,
+        interval: Duration,
+        jitter: Duration,
+    ) -> Result<()> {
+        let name = job.name().to_string();
+        let mut jobs = self.jobs.lock().unwrap();
+        if jobs.contains_key(&name) {
+            return Err(anyhow!("Maintenance job '{}' is already registered", name));
+        }
+        let mut entry = Entry {
+            job,
+            interval,
+            jitter,
+            next_run: self.clock.now(),
+            runs: 0,
+            running: None,
+            last_run: None,
+            busy_skips: 0,
+        };
+        entry.schedule_after(self.clock.now());
+        jobs.insert(name, entry);
+        Ok(())
+    }
+
+    /// All registered jobs, by name
+    pub fn status(&self) -> Vec<JobStatus> {
+        self.jobs
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, entry)| JobStatus {
+                name: name.clone(),
+                interval: entry.interval,
+                next_run: entry.next_run,
+                running: entry.running.is_some(),
+                last_run: entry.last_run.clone(),
+                busy_skips: entry.busy_skips,
+            })
+            .collect()
+    }
+
+    /// Run the jobs that are due, one after another, unless the system is
+    /// busy. Returns the runs that happened.
+    pub async fn tick(&self) -> Vec<JobRun> {
+        let now = self.clock.now();
+        let due: Vec<String> = self
+            .jobs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, entry)| entry.running.is_none() && entry.next_run <= now)
+            .map(|(name, _)| name.clone())
+            .collect();
+        if due.is_empty() {
+            return Vec::new();
+        }
+
+        if self.busy.as_ref().is_some_and(|busy| busy()) {
+            debug!("Skipping {} due maintenance jobs: system busy", due.len());
+            let mut jobs = self.jobs.lock().unwrap();
+            for name in &due {
+                if let Some(entry) = jobs.get_mut(name) {
+                    entry.busy_skips += 1;
+                }
+            }
+            return Vec::new();
+        }
+
+        let mut runs = Vec::new();
+        for name in due {
+            if self.shutdown.is_cancelled() {
+                break;
+            }
+            if let Ok(run) = self.run(&name, false).await {
+                runs.push(run);
+            }
+        }
+        runs
+    }
+
+    /// Run `name` now, busy or not, and restart its interval
+    pub async fn run_now(&self, name: &str) -> Result<JobRun> {
+        self.run(name, true).await
+    }
+
+    /// Cancel the run of `name` in progress. Returns whether one was.
+    pub fn cancel(&self, name: &str) -> bool {
+        match self.jobs.lock().unwrap().get(name) {
+            Some(Entry {
+                running: Some(token),
+                ..
+            }) => {
+                token.cancel();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Cancel running jobs and stop the loop started by [`Self::spawn`]
+    pub fn shutdown(&self) {
+        self.shutdown.cancel();
+    }
+
+    /// Call [`Self::tick`] every `poll_interval` until [`Self::shutdown`]
+    pub fn spawn(self: Arc<Self>, poll_interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(poll_interval);
+            loop {
+                tokio::select! {
+                    _ = self.shutdown.cancelled() => break,
+                    _ = interval.tick() => {
+                        self.tick().await;
+                    }
+                }
+            }
+        })
+    }
+
+    async fn run(&self, name: &str, manual: bool) -> Result<JobRun> {
+        let (job, token) = {
+            let mut jobs = self.jobs.lock().unwrap();
+            let entry = jobs
+                .get_mut(name)
+                .ok_or_else(|| anyhow!("No maintenance job named '{}'", name))?;
+            if entry.running.is_some() {
+                return Err(anyhow!("Maintenance job '{}' is already running", name));
+            }
+            let token = self.shutdown.child_token();
+            entry.running = Some(token.clone());
+            (entry.job.clone(), token)
+        };
+
+        let started_at = self.clock.now();
+        let job_token = token.clone();
+        // Run on its own task so a panic is caught rather than unwinding here
+        let mut handle = tokio::spawn(async move { job.run(job_token).await });
+        let outcome = tokio::select! {
+            joined = &mut handle => match joined {
+                Ok(Ok(summary)) => JobOutcome::Succeeded(summary),
+                Ok(Err(e)) => JobOutcome::Failed(e.to_string()),
+                Err(e) if e.is_panic() => JobOutcome::Panicked(panic_message(e.into_panic())),
+                Err(_) => JobOutcome::Cancelled,
+            },
+            _ = token.cancelled() => {
+                handle.abort();
+                JobOutcome::Cancelled
+            }
+        };
+
+        let finished_at = self.clock.now();
+        let run = JobRun {
+            job: name.to_string(),
+            started_at,
+            duration: finished_at.saturating_duration_since(started_at),
+            manual,
+            outcome,
+        };
+        match &run.outcome {
+            JobOutcome::Succeeded(summary) => debug!("Maintenance job {}: {}", name, summary),
+            JobOutcome::Cancelled => debug!("Maintenance job {} cancelled", name),
+            JobOutcome::Failed(e) | JobOutcome::Panicked(e) => {
+                warn!("Maintenance job {} failed: {}", name, e)
+            }
+        }
+
+        if let Some(entry) = self.jobs.lock().unwrap().get_mut(name) {
+            entry.running = None;
+            entry.runs += 1;
+            entry.last_run = Some(run.clone());
+            entry.schedule_after(finished_at);
+        }
+        Ok(run)
+    }
+}
+
+impl Default for MaintenanceScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "panicked".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    const HOUR: Duration = Duration::from_secs(60 * 60);
+
+    #[derive(Debug)]
+    struct ManualClock(Mutex<Instant>);
+
+    impl ManualClock {
+        fn advance(&self, by: Duration) {
+            *self.0.lock().unwrap() += by;
+        }
+    }
+
+    impl Clock for ManualClock {
+        fn now(&self) -> Instant {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    enum Behaviour {
+        Succeed,
+        Panic,
+        WaitForCancel,
+    }
+
+    struct CountingJob {
+        name: &'static str,
+        runs: AtomicUsize,
+        behaviour: Behaviour,
+    }
+
+    impl CountingJob {
+        fn new(name: &'static str, behaviour: Behaviour) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                runs: AtomicUsize::new(0),
+                behaviour,
+            })
+        }
+
+        fn runs(&self) -> usize {
+            self.runs.load(Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl MaintenanceJob for CountingJob {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn run(&self, cancel: CancellationToken) -> Result<String> {
+            let run = self.runs.fetch_add(1, Ordering::SeqCst) + 1;
+            match self.behaviour {
+                Behaviour::Succeed => Ok(format!("run {}", run)),
+                Behaviour::Panic => panic!("index corrupt"),
+                Behaviour::WaitForCancel => {
+                    cancel.cancelled().await;
+                    Ok("stopped".to_string())
+                }
+            }
+        }
+    }
+
+    fn scheduler() -> (MaintenanceScheduler, Arc<ManualClock>) {
+        let clock = Arc::new(ManualClock(Mutex::new(Instant::now())));
+        let scheduler = MaintenanceScheduler::new().with_clock(clock.clone());
+        (scheduler, clock)
+    }
+
+    #[tokio::test]
+    async fn test_jobs_run_when_due() {
+        let (scheduler, clock) = scheduler();
+        let compaction = CountingJob::new("compact_transcripts", Behaviour::Succeed);
+        let trim = CountingJob::new("trim_caches", Behaviour::Succeed);
+        scheduler
+            .register(compaction.clone(), HOUR, Duration::ZERO)
+            .unwrap();
+        scheduler.register(trim.clone(), 3 * HOUR, HOUR).unwrap();
+        assert!(scheduler
+            .register(compaction.clone(), HOUR, Duration::ZERO)
+            .is_err());
+
+        assert!(scheduler.tick().await.is_empty());
+        clock.advance(HOUR);
+        let runs = scheduler.tick().await;
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].outcome, JobOutcome::Succeeded("run 1".to_string()));
+        assert!(scheduler.tick().await.is_empty());
+
+        // The trim job is due somewhere between three and four hours in
+        let trim_due = scheduler
+            .status()
+            .into_iter()
+            .find(|s| s.name == "trim_caches")
+            .unwrap()
+            .next_run;
+        let start = clock.now() - HOUR;
+        assert!(trim_due >= start + 3 * HOUR && trim_due <= start + 4 * HOUR);
+
+        clock.advance(3 * HOUR);
+        let names: Vec<String> = scheduler.tick().await.into_iter().map(|r| r.job).collect();
+        assert_eq!(names, ["compact_transcripts", "trim_caches"]);
+        assert_eq!((compaction.runs(), trim.runs()), (2, 1));
+    }
+
+    #[tokio::test]
+    async fn test_busy_system_defers_jobs() {
+        let busy = Arc::new(AtomicBool::new(true));
+        let signal = busy.clone();
+        let (scheduler, clock) = scheduler();
+        let scheduler = scheduler.with_busy_signal(Arc::new(move || signal.load(Ordering::SeqCst)));
+        let job = CountingJob::new("prune", Behaviour::Succeed);
+        scheduler
+            .register(job.clone(), HOUR, Duration::ZERO)
+            .unwrap();
+
+        clock.advance(HOUR);
+        assert!(scheduler.tick().await.is_empty());
+        assert_eq!(scheduler.status()[0].busy_skips, 1);
+
+        // Manual runs ignore the busy signal
+        let run = scheduler.run_now("prune").await.unwrap();
+        assert!(run.manual);
+        assert_eq!(job.runs(), 1);
+        assert!(scheduler.run_now("missing").await.is_err());
+
+        // ...and restart the interval
+        busy.store(false, Ordering::SeqCst);
+        assert!(scheduler.tick().await.is_empty());
+        clock.advance(HOUR);
+        assert_eq!(scheduler.tick().await.len(), 1);
+        let status = &scheduler.status()[0];
+        assert!(!status.last_run.as_ref().unwrap().manual);
+        assert_eq!(job.runs(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_panicking_job_is_isolated() {
+        let (scheduler, clock) = scheduler();
+        let broken = CountingJob::new("optimize_index", Behaviour::Panic);
+        let healthy = CountingJob::new("purge_trash", Behaviour::Succeed);
+        scheduler
+            .register(broken.clone(), HOUR, Duration::ZERO)
+            .unwrap();
+        scheduler
+            .register(healthy.clone(), HOUR, Duration::ZERO)
+            .unwrap();
+
+        clock.advance(HOUR);
+        let runs = scheduler.tick().await;
+        assert_eq!(runs.len(), 2);
+        assert_eq!(
+            runs[0].outcome,
+            JobOutcome::Panicked("index corrupt".to_string())
+        );
+        assert_eq!(runs[1].outcome, JobOutcome::Succeeded("run 1".to_string()));
+
+        // The panicking job stays scheduled
+        clock.advance(HOUR);
+        assert_eq!(scheduler.tick().await.len(), 2);
+        assert_eq!((broken.runs(), healthy.runs()), (2, 2));
+    }
+
+    #[tokio::test]
+    async fn test_running_job_can_be_cancelled() {
+        let (scheduler, _clock) = scheduler();
+        let scheduler = Arc::new(scheduler);
+        let job = CountingJob::new("compact_transcripts", Behaviour::WaitForCancel);
+        scheduler
+            .register(job.clone(), HOUR, Duration::ZERO)
+            .unwrap();
+
+        let running = scheduler.clone();
+        let handle = tokio::spawn(async move { running.run_now("compact_transcripts").await });
+        while !scheduler.status()[0].running {
+            tokio::task::yield_now().await;
+        }
+        assert!(scheduler.run_now("compact_transcripts").await.is_err());
+
+        assert!(scheduler.cancel("compact_transcripts"));
+        let run = handle.await.unwrap().unwrap();
+        assert_eq!(run.outcome, JobOutcome::Cancelled);
+        assert!(!scheduler.status()[0].running);
+        assert!(!scheduler.cancel("compact_transcripts"));
+    }
+}
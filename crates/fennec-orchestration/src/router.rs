@@ -1 +1,529 @@
-// Router implementation will go here
+//! Deciding whether free-text input is chat or asks for a command.
+//!
+//! [`IntentRouter`] tries rules first: a `/` prefix names a command
+//! outright, and a leading imperative verb from the command vocabulary,
+//! as in "rename FooBar to Baz everywhere", maps onto a registry command.
+//! Input no rule matches can be put to an [`IntentClassifier`], such as
+//! [`ProviderIntentClassifier`]. The resulting [`RoutingDecision`] tells the
+//! coordinator whether to chat, run the command, or confirm it first.
+
+use async_trait::async_trait;
+use fennec_core::provider::{ProviderClient, ProviderMessage, ProviderRequest};
+use fennec_core::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+/// Confidence of a command named with a `/` prefix
+const SLASH_CONFIDENCE: f32 = 1.0;
+
+/// Confidence of a command recognised by a verb rule; worth confirming
+const RULE_CONFIDENCE: f32 = 0.75;
+
+/// Arguments filled from the words after a command, in order. The last
+/// one takes the rest of the input.
+const POSITIONAL_ARGS: &[(&str, &[&str])] = &[
+    ("run", &["command"]),
+    ("search", &["query"]),
+    ("find-symbol", &["query"]),
+    ("plan", &["task"]),
+    ("summarize", &["target"]),
+    ("rename-symbol", &["symbol", "new_name"]),
+    ("rename", &["from", "to"]),
+    ("create", &["path"]),
+    ("delete", &["path"]),
+];
+
+/// Leading phrases asking for a command, longest first where they share a
+/// prefix, with the argument the rest of the input fills
+const VERB_RULES: &[(&str, &str, &str)] = &[
+    ("run", "run", "command"),
+    ("execute", "run", "command"),
+    ("search for", "search", "query"),
+    ("grep for", "search", "query"),
+    ("search", "search", "query"),
+    ("grep", "search", "query"),
+    ("find symbol", "find-symbol", "query"),
+    ("where is", "find-symbol", "query"),
+    ("locate", "find-symbol", "query"),
+    ("make a plan for", "plan", "task"),
+    ("plan out", "plan", "task"),
+    ("plan", "plan", "task"),
+    ("summarize", "summarize", "target"),
+    ("summarise", "summarize", "target"),
+];
+
+/// Thresholds turning a classification into a [`RoutingDecision`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RouterThresholds {
+    /// Below this the input is treated as chat
+    pub chat_below: f32,
+    /// From this up the command runs without confirmation
+    pub execute_from: f32,
+}
+
+impl Default for RouterThresholds {
+    fn default() -> Self {
+        Self {
+            chat_below: 0.5,
+            execute_from: 0.9,
+        }
+    }
+}
+
+/// A command the input seems to ask for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandIntent {
+    pub command: String,
+    pub args: Value,
+    /// From 0 to 1
+    pub confidence: f32,
+}
+
+/// What to do with a piece of input
+#[derive(Debug, Clone, PartialEq)]
+pub enum RoutingDecision {
+    /// Send it to the model as a chat message
+    Chat,
+    /// Run the command
+    Execute(CommandIntent),
+    /// Ask the user before running the command
+    Confirm(CommandIntent),
+}
+
+impl RoutingDecision {
+    pub fn intent(&self) -> Option<&CommandIntent> {
+        match self {
+            RoutingDecision::Chat => None,
+            RoutingDecision::Execute(intent) | RoutingDecision::Confirm(intent) => Some(intent),
+        }
+    }
+}
+
+/// Second opinion on input the rules don't recognise
+#[async_trait]
+pub trait IntentClassifier: Send + Sync {
+    /// The command among `commands` that `input` asks for, if any
+    async fn classify(&self, input: &str, commands: &[String]) -> Result<Option<CommandIntent>>;
+}
+
+/// Routes input to chat or to a registry command
+pub struct IntentRouter {
+    commands: Vec<String>,
+    thresholds: RouterThresholds,
+    classifier: Option<Arc<dyn IntentClassifier>>,
+}
+
+impl IntentRouter {
+    /// A router choosing among the registry commands named `commands`
+    pub fn new(commands: Vec<String>) -> Self {
+        Self {
+            commands,
+            thresholds: RouterThresholds::default(),
+            classifier: None,
+        }
+    }
+
+    pub fn with_thresholds(mut self, thresholds: RouterThresholds) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    /// Ask `classifier` about input no rule matches
+    pub fn with_classifier(mut self, classifier: Arc<dyn IntentClassifier>) -> Self {
+        self.classifier = Some(classifier);
+        self
+    }
+
+    /// Decide what `input` asks for. A failing classifier leaves it as chat.
+    pub async fn route(&self, input: &str) -> RoutingDecision {
+        let input = input.trim();
+        if let Some(intent) = self.match_rules(input) {
+            return self.decide(intent);
+        }
+
+        let Some(classifier) = &self.classifier else {
+            return RoutingDecision::Chat;
+        };
+        match classifier.classify(input, &self.commands).await {
+            Ok(Some(intent)) if self.is_known(&intent.command) => self.decide(intent),
+            Ok(Some(intent)) => {
+                debug!("Classifier suggested unknown command '{}'", intent.command);
+                RoutingDecision::Chat
+            }
+            Ok(None) => RoutingDecision::Chat,
+            Err(e) => {
+                warn!("Intent classification failed: {}", e);
+                RoutingDecision::Chat
+            }
+        }
+    }
+
+    /// Intent found by the rule-based first pass
+    pub fn match_rules(&self, input: &str) -> Option<CommandIntent> {
+        if let Some(rest) = input.strip_prefix('/') {
+            let (name, rest) = split_word(rest);
+            if !self.is_known(name) {
+                return None;
+            }
+            return Some(CommandIntent {
+                command: name.to_string(),
+                args: slash_args(name, rest),
+                confidence: SLASH_CONFIDENCE,
+            });
+        }
+
+        // Questions about a command are chat
+        if input.ends_with('?') {
+            return None;
+        }
+        let lower = input.to_ascii_lowercase();
+        let start = ["please ", "can you ", "could you "]
+            .iter()
+            .find(|prefix| lower.starts_with(*prefix))
+            .map_or(0, |prefix| prefix.len());
+        let text = &input[start..];
+        let lower = &lower[start..];
+
+        self.match_rename(text, lower)
+            .or_else(|| self.match_verb(text, lower))
+    }
+
+    fn match_rename(&self, text: &str, lower: &str) -> Option<CommandIntent> {
+        if !lower.starts_with("rename ") {
+            return None;
+        }
+        let rest = text["rename ".len()..].trim();
+        let rest = strip_suffix_ignore_case(rest, " everywhere").unwrap_or(rest);
+        let rest = rest
+            .strip_prefix("symbol ")
+            .or_else(|| rest.strip_prefix("file "))
+            .unwrap_or(rest);
+        let (from, to) = rest.split_once(" to ")?;
+        let (from, to) = (unquote(from.trim()), unquote(to.trim()));
+        if from.is_empty() || to.is_empty() || to.contains(char::is_whitespace) {
+            return None;
+        }
+
+        let (command, args) = if is_path_like(from) || is_path_like(to) {
+            ("rename", json!({"from": from, "to": to}))
+        } else {
+            ("rename-symbol", json!({"symbol": from, "new_name": to}))
+        };
+        self.is_known(command).then(|| CommandIntent {
+            command: command.to_string(),
+            args,
+            confidence: RULE_CONFIDENCE,
+        })
+    }
+
+    fn match_verb(&self, text: &str, lower: &str) -> Option<CommandIntent> {
+        VERB_RULES.iter().find_map(|(phrase, command, arg)| {
+            let rest = lower.strip_prefix(phrase)?;
+            if !rest.starts_with(' ') || !self.is_known(command) {
+                return None;
+            }
+            let value = unquote(text[phrase.len()..].trim());
+            if value.is_empty() {
+                return None;
+            }
+            let mut args = Map::new();
+            args.insert(arg.to_string(), Value::String(value.to_string()));
+            Some(CommandIntent {
+                command: command.to_string(),
+                args: Value::Object(args),
+                confidence: RULE_CONFIDENCE,
+            })
+        })
+    }
+
+    fn decide(&self, intent: CommandIntent) -> RoutingDecision {
+        if intent.confidence < self.thresholds.chat_below {
+            RoutingDecision::Chat
+        } else if intent.confidence >= self.thresholds.execute_from {
+            RoutingDecision::Execute(intent)
+        } else {
+            RoutingDecision::Confirm(intent)
+        }
+    }
+
+    fn is_known(&self, command: &str) -> bool {
+        self.commands.iter().any(|known| known == command)
+    }
+}
+
+/// Classifies input by asking the provider for a JSON routing decision
+pub struct ProviderIntentClassifier {
+    provider: Arc<dyn ProviderClient>,
+    model: String,
+}
+
+/// Reply expected from the provider
+#[derive(Debug, Deserialize)]
+struct ClassifierReply {
+    command: Option<String>,
+    #[serde(default)]
+    args: Value,
+    confidence: f32,
+}
+
+impl ProviderIntentClassifier {
+    pub fn new(provider: Arc<dyn ProviderClient>, model: impl Into<String>) -> Self {
+        Self {
+            provider,
+            model: model.into(),
+        }
+    }
+
+    fn reply_schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["command", "confidence"],
+            "properties": {
+                "command": {"type": ["string", "null"]},
+                "args": {"type": "object"},
+                "confidence": {"type": "number"}
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl IntentClassifier for ProviderIntentClassifier {
+    async fn classify(&self, input: &str, commands: &[String]) -> Result<Option<CommandIntent>> {
+        let schema = Self::reply_schema();
+        let instructions = format!(
+            "Decide whether the user's message asks to run one of these commands: {}. \
+             Reply with only a JSON object matching this schema, with `command` null \
+             for ordinary conversation and `confidence` between 0 and 1:\n{}",
+            commands.join(", "),
+            schema
+        );
+        let request = ProviderRequest {
+            id: Uuid::new_v4(),
+            messages: vec![
+                ProviderMessage {
+                    role: "system".to_string(),
+                    content: instructions,
+                },
+                ProviderMessage {
+                    role: "user".to_string(),
+                    content: input.to_string(),
+                },
+            ],
+            model: self.model.clone(),
+            stream: false,
+            temperature: Some(0.0),
+            max_tokens: None,
+        };
+
+        let response = self.provider.complete(request).await?;
+        let reply: ClassifierReply =
+            fennec_provider::structured::parse_structured(&response.content, &schema).map_err(
+                |errors| fennec_core::FennecError::Orchestration(errors.join("; ").into()),
+            )?;
+
+        Ok(reply.command.map(|command| CommandIntent {
+            command,
+            args: match reply.args {
+                Value::Null => Value::Object(Map::new()),
+                args => args,
+            },
+            confidence: reply.confidence.clamp(0.0, 1.0),
+        }))
+    }
+}
+
+/// Arguments of `/command rest`: a JSON object as is, otherwise words
+/// filling the command's positional arguments
+fn slash_args(command: &str, rest: &str) -> Value {
+    let rest = rest.trim();
+    if rest.starts_with('{') {
+        if let Ok(args @ Value::Object(_)) = serde_json::from_str(rest) {
+            return args;
+        }
+    }
+
+    let mut args = Map::new();
+    let names = POSITIONAL_ARGS
+        .iter()
+        .find(|(name, _)| *name == command)
+        .map_or(&[][..], |(_, names)| *names);
+    let mut remaining = rest;
+    for (index, name) in names.iter().enumerate() {
+        if remaining.is_empty() {
+            break;
+        }
+        let value = if index + 1 == names.len() {
+            std::mem::take(&mut remaining)
+        } else {
+            let (word, rest) = split_word(remaining);
+            remaining = rest;
+            word
+        };
+        args.insert(name.to_string(), Value::String(unquote(value).to_string()));
+    }
+    Value::Object(args)
+}
+
+/// First word of `text` and the rest after it
+fn split_word(text: &str) -> (&str, &str) {
+    let text = text.trim_start();
+    match text.split_once(char::is_whitespace) {
+        Some((word, rest)) => (word, rest.trim_start()),
+        None => (text, ""),
+    }
+}
+
+fn unquote(text: &str) -> &str {
+    for quote in ['"', '\'', '`'] {
+        if let Some(inner) = text
+            .strip_prefix(quote)
+            .and_then(|rest| rest.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    text
+}
+
+fn strip_suffix_ignore_case<'a>(text: &'a str, suffix: &str) -> Option<&'a str> {
+    let split = text.len().checked_sub(suffix.len())?;
+    (text.is_char_boundary(split) && text[split..].eq_ignore_ascii_case(suffix))
+        .then(|| &text[..split])
+}
+
+fn is_path_like(text: &str) -> bool {
+    text.contains('/') || text.contains('\\') || text.contains('.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fennec_provider::MockProviderClient;
+
+    fn router() -> IntentRouter {
+        let commands = [
+            "run",
+            "search",
+            "find-symbol",
+            "plan",
+            "summarize",
+            "rename",
+            "rename-symbol",
+            "undo",
+        ];
+        IntentRouter::new(commands.iter().map(|c| c.to_string()).collect())
+    }
+
+    /// Input, expected command and arguments, and whether it runs unconfirmed
+    type Case = (&'static str, Option<(&'static str, Value)>, bool);
+
+    #[tokio::test]
+    async fn test_rule_based_routes() {
+        let cases: &[Case] = &[
+            ("hello there", None, false),
+            (
+                "/run cargo test --all",
+                Some(("run", json!({"command": "cargo test --all"}))),
+                true,
+            ),
+            ("/undo", Some(("undo", json!({}))), true),
+            (
+                "/rename-symbol Foo Bar",
+                Some(("rename-symbol", json!({"symbol": "Foo", "new_name": "Bar"}))),
+                true,
+            ),
+            (
+                r#"/search {"query": "todo", "case_insensitive": true}"#,
+                Some(("search", json!({"query": "todo", "case_insensitive": true}))),
+                true,
+            ),
+            ("/etc/hosts looks wrong", None, false),
+            (
+                "rename FooBar to Baz everywhere",
+                Some((
+                    "rename-symbol",
+                    json!({"symbol": "FooBar", "new_name": "Baz"}),
+                )),
+                false,
+            ),
+            (
+                "Please rename src/old.rs to src/new.rs",
+                Some(("rename", json!({"from": "src/old.rs", "to": "src/new.rs"}))),
+                false,
+            ),
+            (
+                "search for \"unwrap()\"",
+                Some(("search", json!({"query": "unwrap()"}))),
+                false,
+            ),
+            (
+                "Run cargo fmt",
+                Some(("run", json!({"command": "cargo fmt"}))),
+                false,
+            ),
+            (
+                "where is SessionManager",
+                Some(("find-symbol", json!({"query": "SessionManager"}))),
+                false,
+            ),
+            (
+                "make a plan for adding OAuth",
+                Some(("plan", json!({"task": "adding OAuth"}))),
+                false,
+            ),
+            ("how do I rename Foo to Bar?", None, false),
+            ("running tests is slow", None, false),
+        ];
+
+        let router = router();
+        for (input, expected, execute) in cases {
+            let decision = router.route(input).await;
+            match expected {
+                None => assert_eq!(decision, RoutingDecision::Chat, "{}", input),
+                Some((command, args)) => {
+                    let intent = decision.intent().unwrap_or_else(|| panic!("{}", input));
+                    assert_eq!(intent.command, *command, "{}", input);
+                    assert_eq!(&intent.args, args, "{}", input);
+                    assert_eq!(
+                        matches!(decision, RoutingDecision::Execute(_)),
+                        *execute,
+                        "{}",
+                        input
+                    );
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_provider_fallback_and_threshold() {
+        let mock = MockProviderClient::builder()
+            .text(r#"{"command": "summarize", "args": {"target": "src"}, "confidence": 0.8}"#)
+            .text(r#"{"command": "plan", "args": {"task": "x"}, "confidence": 0.3}"#)
+            .text(r#"{"command": null, "confidence": 0.9}"#)
+            .text("not json")
+            .build();
+        let router = router().with_classifier(Arc::new(ProviderIntentClassifier::new(
+            Arc::new(mock),
+            "gpt-4",
+        )));
+
+        let decision = router.route("give me the gist of src").await;
+        assert_eq!(
+            decision,
+            RoutingDecision::Confirm(CommandIntent {
+                command: "summarize".to_string(),
+                args: json!({"target": "src"}),
+                confidence: 0.8,
+            })
+        );
+        // Too unsure, no command, and an unusable reply are all chat
+        for input in ["maybe think about x", "thanks!", "what now"] {
+            assert_eq!(router.route(input).await, RoutingDecision::Chat);
+        }
+    }
+}
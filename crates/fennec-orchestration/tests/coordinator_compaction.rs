@@ -0,0 +1,175 @@
+use anyhow::Result;
+use fennec_commands::create_command_registry;
+use fennec_core::config::{CompactionConfig, CompactionStrategy, Config};
+use fennec_core::provider::{ProviderMessage, ProviderRequest};
+use fennec_memory::{transcript::SegmentType, MemoryService};
+use fennec_orchestration::{Coordinator, SessionManager};
+use fennec_provider::usage::estimate_prompt_tokens;
+use fennec_provider::{MockProviderClient, ProviderError};
+use fennec_security::audit::AuditLogger;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+const MAX_PROMPT_TOKENS: usize = 200;
+
+/// Replies of about 50 tokens, so a few exchanges fill the prompt
+fn reply(i: usize) -> String {
+    format!("Reply {}: {}", i, "the build works now. ".repeat(10))
+}
+
+fn question(i: usize) -> String {
+    format!("Question {} about the lockfile", i)
+}
+
+async fn coordinator(
+    strategy: CompactionStrategy,
+    mock: MockProviderClient,
+) -> Result<(Coordinator, Arc<MockProviderClient>, TempDir)> {
+    let temp_dir = TempDir::new()?;
+    let audit_logger = AuditLogger::with_path(temp_dir.path().join("audit.log")).await?;
+    let mut config = Config::default();
+    config.compaction = CompactionConfig {
+        enabled: true,
+        max_prompt_tokens: MAX_PROMPT_TOKENS,
+        keep_recent_messages: 2,
+        strategy,
+    };
+    let mock = Arc::new(mock);
+    let sessions = Arc::new(SessionManager::with_provider(
+        config,
+        audit_logger,
+        mock.clone(),
+    ));
+    let coordinator = Coordinator::new(sessions, Arc::new(create_command_registry().await?));
+    Ok((coordinator, mock, temp_dir))
+}
+
+fn is_compaction(request: &ProviderRequest) -> bool {
+    request.messages[0]
+        .content
+        .starts_with("Summarize the following earlier part")
+}
+
+fn tokens(role: &str, content: String) -> u32 {
+    estimate_prompt_tokens(&[ProviderMessage {
+        role: role.to_string(),
+        content,
+    }])
+}
+
+#[tokio::test]
+async fn test_conversation_is_compacted_at_the_limit() -> Result<()> {
+    let mock = MockProviderClient::builder()
+        .text(reply(1))
+        .text(reply(2))
+        .text(reply(3))
+        .text("They got the build working after fixing the lockfile.")
+        .text(reply(4))
+        .build();
+    let (coordinator, mock, _temp_dir) = coordinator(CompactionStrategy::Summarize, mock).await?;
+    let memory = Arc::new(MemoryService::new().await?);
+    let coordinator = coordinator.with_memory(memory.clone());
+    let session_id = coordinator.start_session().await?;
+
+    for i in 1..=3 {
+        coordinator.send_message(question(i)).await?;
+    }
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 3);
+    assert!(!requests.iter().any(is_compaction));
+
+    // The fourth question takes the prompt over the limit
+    let uncompacted = estimate_prompt_tokens(&requests[2].messages)
+        + tokens("assistant", reply(3))
+        + tokens("user", question(4));
+    assert!(uncompacted as usize > MAX_PROMPT_TOKENS);
+    coordinator.send_message(question(4)).await?;
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 5);
+    assert!(is_compaction(&requests[3]));
+    assert!(requests[3].messages[1].content.contains(&question(1)));
+    let prompt = &requests[4].messages;
+    assert!((estimate_prompt_tokens(prompt) as usize) < MAX_PROMPT_TOKENS);
+    assert_eq!(prompt[0].role, "system");
+    assert_eq!(
+        prompt[0].content,
+        "Summary of the earlier conversation:\nThey got the build working after fixing the lockfile."
+    );
+    assert_eq!(prompt.last().unwrap().content, question(4));
+
+    let working = coordinator.sessions().transcript(session_id).await.unwrap();
+    let compacted = working.messages[0].compacted_messages().unwrap();
+    assert_eq!(working.messages.len(), 8 - compacted + 1);
+
+    // The stored transcript keeps every message and marks the compacted ones
+    let stored = memory.load_transcript(session_id).await?.unwrap();
+    assert_eq!(stored.transcript.messages.len(), 8);
+    let segment = &stored.segments[0];
+    assert_eq!(segment.segment_type, SegmentType::Compaction);
+    assert_eq!(segment.start_message_id, stored.transcript.messages[0].id);
+    assert_eq!(
+        segment.end_message_id,
+        Some(stored.transcript.messages[compacted - 1].id)
+    );
+    assert_eq!(segment.summary, prompt[0].content);
+
+    memory.delete_session(session_id).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_failed_summary_falls_back_to_the_heuristic_one() -> Result<()> {
+    let mock = MockProviderClient::builder()
+        .text(reply(1))
+        .text(reply(2))
+        .text(reply(3))
+        .error(ProviderError::ServiceUnavailable {
+            provider: "mock".to_string(),
+            reason: "overloaded".to_string(),
+        })
+        .text(reply(4))
+        .build();
+    let (coordinator, mock, _temp_dir) = coordinator(CompactionStrategy::Summarize, mock).await?;
+    coordinator.start_session().await?;
+
+    for i in 1..=4 {
+        coordinator.send_message(question(i)).await?;
+    }
+
+    let requests = mock.requests();
+    assert!(is_compaction(&requests[3]));
+    let prompt = &requests[4].messages;
+    assert!((estimate_prompt_tokens(prompt) as usize) < MAX_PROMPT_TOKENS);
+    assert!(prompt[0]
+        .content
+        .contains(&format!("The user asked: {}", question(1))));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_truncation_drops_the_oldest_messages() -> Result<()> {
+    let mock = MockProviderClient::builder()
+        .text(reply(1))
+        .text(reply(2))
+        .text(reply(3))
+        .text(reply(4))
+        .build();
+    let (coordinator, mock, _temp_dir) = coordinator(CompactionStrategy::Truncate, mock).await?;
+    coordinator.start_session().await?;
+
+    for i in 1..=4 {
+        coordinator.send_message(question(i)).await?;
+    }
+
+    // Nothing is summarized
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 4);
+    let prompt = &requests[3].messages;
+    assert!((estimate_prompt_tokens(prompt) as usize) < MAX_PROMPT_TOKENS);
+    assert!(prompt[0]
+        .content
+        .ends_with("earlier messages were left out to fit the context window."));
+    assert!(!prompt.iter().any(|m| m.content == question(1)));
+    Ok(())
+}
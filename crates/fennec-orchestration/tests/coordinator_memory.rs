@@ -0,0 +1,109 @@
+use anyhow::Result;
+use fennec_commands::{create_command_registry, CommandContext};
+use fennec_core::{config::Config, transcript::MessageRole};
+use fennec_memory::{transcript::TimelineEventType, MemoryService};
+use fennec_orchestration::{Coordinator, SessionManager};
+use fennec_provider::MockProviderClient;
+use fennec_security::{audit::AuditLogger, SandboxLevel};
+use std::sync::Arc;
+use tempfile::TempDir;
+use tokio_util::sync::CancellationToken;
+
+fn command_context(session_id: uuid::Uuid) -> CommandContext {
+    CommandContext {
+        session_id,
+        user_id: None,
+        workspace_path: None,
+        sandbox_level: SandboxLevel::ReadOnly,
+        dry_run: false,
+        preview_only: false,
+        cancellation_token: CancellationToken::new(),
+        action_log: None,
+        timeout: None,
+        progress: None,
+    }
+}
+
+#[tokio::test]
+async fn test_session_history_is_recorded_in_memory() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let audit_logger = AuditLogger::with_path(temp_dir.path().join("audit.log")).await?;
+    let mock = MockProviderClient::builder()
+        .text("Let me diff those for you.")
+        .text("The second line changed.")
+        .text("Compared two texts; one line differed.")
+        .build();
+    let sessions = Arc::new(SessionManager::with_provider(
+        Config::default(),
+        audit_logger,
+        Arc::new(mock),
+    ));
+    let memory = Arc::new(MemoryService::new().await?);
+    let coordinator = Coordinator::new(sessions, Arc::new(create_command_registry().await?))
+        .with_memory(memory.clone());
+
+    let session_id = coordinator.start_session().await?;
+    coordinator
+        .send_message("What changed between these?".to_string())
+        .await?;
+    let args = serde_json::json!({
+        "left": "Hello\nWorld",
+        "right": "Hello\nUniverse",
+        "is_file_path": false,
+        "format": "unified"
+    });
+    let result = coordinator
+        .execute_command("diff", &args, &command_context(session_id))
+        .await?;
+    assert!(result.success);
+    coordinator
+        .send_message("Summarize the diff".to_string())
+        .await?;
+    coordinator.end_session().await?;
+
+    let history: Vec<String> = memory
+        .get_session_timeline(session_id)
+        .await?
+        .into_iter()
+        .map(|event| match event.event_type {
+            TimelineEventType::Message {
+                role,
+                content_preview,
+            } => match role {
+                MessageRole::User => format!("user: {}", content_preview),
+                _ => format!("assistant: {}", content_preview),
+            },
+            TimelineEventType::CommandExecution {
+                command, success, ..
+            } => {
+                assert!(success);
+                format!("command: {}", command.split(' ').next().unwrap())
+            }
+            other => panic!("unexpected timeline event {:?}", other),
+        })
+        .collect();
+    assert_eq!(
+        history,
+        [
+            "user: What changed between these?",
+            "assistant: Let me diff those for you.",
+            "command: diff",
+            "user: Summarize the diff",
+            "assistant: The second line changed.",
+        ]
+    );
+
+    let stored = memory.load_transcript(session_id).await?.unwrap();
+    assert_eq!(
+        stored.summary.as_deref(),
+        Some("Compared two texts; one line differed.")
+    );
+    let environment = &stored.command_executions[0].environment;
+    assert!(environment
+        .iter()
+        .filter(|(name, _)| name.to_uppercase().contains("KEY"))
+        .all(|(_, value)| value == "[REDACTED]"));
+
+    memory.delete_session(session_id).await?;
+    Ok(())
+}
@@ -0,0 +1,365 @@
+//! On-disk cache of provider responses.
+//!
+//! [`CachingProviderClient`] answers a request it has already seen from
+//! disk instead of calling the provider again. Entries are keyed by a hash
+//! of the model, messages and sampling parameters, expire after a TTL and
+//! are evicted oldest first once the cache outgrows its size cap. Requests
+//! sampled with a temperature above zero bypass the cache unless
+//! non-deterministic caching is allowed, and streams are never cached.
+
+use fennec_core::config::ResponseCacheConfig;
+use fennec_core::provider::{
+    AttachmentSupport, ProviderClient, ProviderRequest, ProviderResponse, Usage,
+};
+use futures::Stream;
+use ring::digest::{digest, SHA256};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+/// Hash identifying requests that should get the same reply
+pub fn cache_key(request: &ProviderRequest) -> String {
+    let material = serde_json::json!({
+        "model": request.model,
+        "messages": request.messages,
+        "temperature": request.temperature,
+        "max_tokens": request.max_tokens,
+    });
+    hex::encode(digest(&SHA256, material.to_string().as_bytes()).as_ref())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    /// Seconds since the Unix epoch
+    created_at: u64,
+    model: String,
+    response: ProviderResponse,
+}
+
+/// Provider wrapper serving repeated identical requests from disk
+pub struct CachingProviderClient {
+    inner: Arc<dyn ProviderClient>,
+    directory: PathBuf,
+    ttl: Duration,
+    max_size_bytes: u64,
+    allow_nondeterministic: bool,
+    /// Serializes writes so eviction sees a consistent directory
+    write_lock: Mutex<()>,
+}
+
+impl CachingProviderClient {
+    pub fn new(
+        inner: Arc<dyn ProviderClient>,
+        directory: impl Into<PathBuf>,
+        config: &ResponseCacheConfig,
+    ) -> Self {
+        Self {
+            inner,
+            directory: directory.into(),
+            ttl: Duration::from_secs(config.ttl_seconds),
+            max_size_bytes: config.max_size_bytes,
+            allow_nondeterministic: config.allow_nondeterministic,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Whether replies to `request` may be served from the cache
+    pub fn is_cacheable(&self, request: &ProviderRequest) -> bool {
+        if request.stream {
+            return false;
+        }
+        self.allow_nondeterministic || request.temperature.is_some_and(|t| t <= 0.0)
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.directory.join(format!("{}.json", key))
+    }
+
+    async fn lookup(&self, key: &str) -> Option<CacheEntry> {
+        let path = self.entry_path(key);
+        let data = tokio::fs::read(&path).await.ok()?;
+        let entry: CacheEntry = match serde_json::from_slice(&data) {
+            Ok(entry) => entry,
+            Err(e) => {
+                warn!(
+                    "Discarding unreadable cache entry {}: {}",
+                    path.display(),
+                    e
+                );
+                let _ = tokio::fs::remove_file(&path).await;
+                return None;
+            }
+        };
+
+        if now_secs().saturating_sub(entry.created_at) >= self.ttl.as_secs() {
+            debug!("Cache entry {} expired", key);
+            let _ = tokio::fs::remove_file(&path).await;
+            return None;
+        }
+        Some(entry)
+    }
+
+    async fn store(
+        &self,
+        key: &str,
+        model: &str,
+        response: &ProviderResponse,
+    ) -> std::io::Result<()> {
+        let entry = CacheEntry {
+            created_at: now_secs(),
+            model: model.to_string(),
+            response: response.clone(),
+        };
+        let data = serde_json::to_vec(&entry)?;
+
+        let _guard = self.write_lock.lock().await;
+        tokio::fs::create_dir_all(&self.directory).await?;
+        tokio::fs::write(self.entry_path(key), data).await?;
+        self.evict(key).await
+    }
+
+    /// Remove the oldest entries, other than `keep`, until the cache fits
+    /// within its size cap
+    async fn evict(&self, keep: &str) -> std::io::Result<()> {
+        let keep = format!("{}.json", keep);
+        let mut entries = Vec::new();
+        let mut total = 0u64;
+
+        let mut dir = tokio::fs::read_dir(&self.directory).await?;
+        while let Some(item) = dir.next_entry().await? {
+            let path = item.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let metadata = item.metadata().await?;
+            total += metadata.len();
+            if item.file_name().to_string_lossy() != keep {
+                let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
+                entries.push((modified, metadata.len(), path));
+            }
+        }
+
+        entries.sort();
+        for (_, size, path) in entries {
+            if total <= self.max_size_bytes {
+                break;
+            }
+            debug!("Evicting cache entry {}", path.display());
+            tokio::fs::remove_file(&path).await?;
+            total -= size;
+        }
+        Ok(())
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[async_trait::async_trait]
+impl ProviderClient for CachingProviderClient {
+    async fn complete(&self, request: ProviderRequest) -> fennec_core::Result<ProviderResponse> {
+        if !self.is_cacheable(&request) {
+            return self.inner.complete(request).await;
+        }
+
+        let key = cache_key(&request);
+        if let Some(entry) = self.lookup(&key).await {
+            debug!("Serving {} request {} from cache", entry.model, request.id);
+            // Nothing was spent on a cached reply
+            return Ok(ProviderResponse {
+                id: request.id,
+                content: entry.response.content,
+                usage: Some(Usage {
+                    prompt_tokens: 0,
+                    completion_tokens: 0,
+                    total_tokens: 0,
+                }),
+                model: entry.response.model.or(Some(entry.model)),
+            });
+        }
+
+        let model = request.model.clone();
+        let response = self.inner.complete(request).await?;
+        if let Err(e) = self.store(&key, &model, &response).await {
+            warn!("Failed to cache provider response: {}", e);
+        }
+        Ok(response)
+    }
+
+    async fn stream(
+        &self,
+        request: ProviderRequest,
+    ) -> fennec_core::Result<Box<dyn Stream<Item = fennec_core::Result<String>> + Unpin + Send>>
+    {
+        self.inner.stream(request).await
+    }
+
+    fn attachment_support(&self) -> AttachmentSupport {
+        self.inner.attachment_support()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockProviderClient;
+    use fennec_core::provider::ProviderMessage;
+    use tempfile::TempDir;
+    use uuid::Uuid;
+
+    fn request(content: &str, temperature: Option<f32>) -> ProviderRequest {
+        ProviderRequest {
+            id: Uuid::new_v4(),
+            messages: vec![ProviderMessage {
+                role: "user".to_string(),
+                content: content.to_string(),
+            }],
+            model: "mock".to_string(),
+            stream: false,
+            temperature,
+            max_tokens: None,
+        }
+    }
+
+    fn client(
+        inner: Arc<MockProviderClient>,
+        dir: &TempDir,
+        config: ResponseCacheConfig,
+    ) -> CachingProviderClient {
+        CachingProviderClient::new(inner, dir.path().join("cache"), &config)
+    }
+
+    #[tokio::test]
+    async fn test_identical_request_is_served_from_cache() {
+        let dir = TempDir::new().unwrap();
+        let inner = Arc::new(MockProviderClient::default());
+        let cache = client(inner.clone(), &dir, ResponseCacheConfig::default());
+
+        let first = cache.complete(request("hello", Some(0.0))).await.unwrap();
+        let second_request = request("hello", Some(0.0));
+        let second_id = second_request.id;
+        let second = cache.complete(second_request).await.unwrap();
+
+        assert_eq!(inner.requests().len(), 1);
+        assert_eq!(second.content, first.content);
+        assert_eq!(second.id, second_id);
+        assert_eq!(second.usage.unwrap().total_tokens, 0);
+
+        // The cache survives a new client over the same directory
+        let reopened = client(inner.clone(), &dir, ResponseCacheConfig::default());
+        reopened
+            .complete(request("hello", Some(0.0)))
+            .await
+            .unwrap();
+        assert_eq!(inner.requests().len(), 1);
+
+        // A different prompt or temperature is a different entry
+        cache.complete(request("goodbye", Some(0.0))).await.unwrap();
+        assert_eq!(inner.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_nondeterministic_and_expired_requests_reach_provider() {
+        let dir = TempDir::new().unwrap();
+        let inner = Arc::new(MockProviderClient::default());
+        let cache = client(inner.clone(), &dir, ResponseCacheConfig::default());
+
+        for _ in 0..2 {
+            cache.complete(request("hello", Some(0.7))).await.unwrap();
+            cache.complete(request("hello", None)).await.unwrap();
+        }
+        assert_eq!(inner.requests().len(), 4);
+
+        let allowed = client(
+            inner.clone(),
+            &dir,
+            ResponseCacheConfig {
+                allow_nondeterministic: true,
+                ..Default::default()
+            },
+        );
+        allowed.complete(request("hello", Some(0.7))).await.unwrap();
+        allowed.complete(request("hello", Some(0.7))).await.unwrap();
+        assert_eq!(inner.requests().len(), 5);
+
+        let expiring = client(
+            inner.clone(),
+            &dir,
+            ResponseCacheConfig {
+                ttl_seconds: 0,
+                ..Default::default()
+            },
+        );
+        expiring
+            .complete(request("later", Some(0.0)))
+            .await
+            .unwrap();
+        expiring
+            .complete(request("later", Some(0.0)))
+            .await
+            .unwrap();
+        assert_eq!(inner.requests().len(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_eviction_honors_size_cap() {
+        let dir = TempDir::new().unwrap();
+        let inner = Arc::new(MockProviderClient::default());
+        let probe = client(inner.clone(), &dir, ResponseCacheConfig::default());
+        probe
+            .complete(request("prompt 0", Some(0.0)))
+            .await
+            .unwrap();
+        let entry_size = std::fs::read_dir(probe.directory())
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .metadata()
+            .unwrap()
+            .len();
+        std::fs::remove_dir_all(probe.directory()).unwrap();
+
+        let max_size_bytes = entry_size * 3;
+        let cache = client(
+            inner.clone(),
+            &dir,
+            ResponseCacheConfig {
+                max_size_bytes,
+                ..Default::default()
+            },
+        );
+        for i in 0..6 {
+            cache
+                .complete(request(&format!("prompt {}", i), Some(0.0)))
+                .await
+                .unwrap();
+        }
+
+        let sizes: Vec<u64> = std::fs::read_dir(cache.directory())
+            .unwrap()
+            .map(|entry| entry.unwrap().metadata().unwrap().len())
+            .collect();
+        assert!(sizes.len() < 6, "{} entries kept", sizes.len());
+        assert!(sizes.iter().sum::<u64>() <= max_size_bytes);
+
+        // The newest entry is kept
+        let calls = inner.requests().len();
+        cache
+            .complete(request("prompt 5", Some(0.0)))
+            .await
+            .unwrap();
+        assert_eq!(inner.requests().len(), calls);
+    }
+}
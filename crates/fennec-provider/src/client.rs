@@ -1,28 +1,160 @@
+use crate::embeddings::{
+    EmbeddingClient, HashEmbeddingClient, OpenAIEmbeddingClient, DEFAULT_EMBEDDING_MODEL,
+};
 use crate::error::{ProviderError, Result};
+use crate::fallback::{FallbackProviderClient, FallbackTarget};
+use crate::middleware::ProviderMiddleware;
 use crate::mock::MockProviderClient;
 use crate::openai::OpenAIClient;
 use fennec_core::config::ProviderConfig;
-use fennec_core::provider::ProviderClient;
-use std::sync::Arc;
+use fennec_core::provider::{ProviderClient, TaskKind};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, LazyLock, Mutex};
 use tracing::{info, warn};
 
+/// Model ids listed by each endpoint and API key, kept for the rest of the
+/// session so validation lists them once
+static MODEL_LISTS: LazyLock<Mutex<HashMap<u64, Arc<Vec<String>>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Outcome of [`ProviderClientFactory::validate_model`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModelCheck {
+    /// The provider lists the model
+    Available,
+    /// The model could not be checked and is used as configured
+    Unverified { reason: String },
+}
+
 /// Factory for creating provider clients
 pub struct ProviderClientFactory;
 
 impl ProviderClientFactory {
     /// Create a provider client based on configuration
     pub fn create_client(config: &ProviderConfig) -> Result<Arc<dyn ProviderClient>> {
+        Self::create_client_with_middleware(config, &[])
+    }
+
+    /// Create a provider client reporting its requests and responses to
+    /// `middleware`
+    pub fn create_client_with_middleware(
+        config: &ProviderConfig,
+        middleware: &[Arc<dyn ProviderMiddleware>],
+    ) -> Result<Arc<dyn ProviderClient>> {
+        let client = Self::create_base_client(config, middleware)?;
+        Ok(Self::with_fallback(config, client))
+    }
+
+    /// Create the client for the configured default provider, without the
+    /// fallback chain
+    pub(crate) fn create_base_client(
+        config: &ProviderConfig,
+        middleware: &[Arc<dyn ProviderMiddleware>],
+    ) -> Result<Arc<dyn ProviderClient>> {
         // For now, we only support OpenAI, but this can be extended
         // to support other providers based on configuration
 
-        if config.openai_api_key.is_some() {
-            info!("Creating OpenAI provider client");
-            let client = OpenAIClient::from_provider_config(config)?;
-            Ok(Arc::new(client))
+        let client: Arc<dyn ProviderClient> = if config.provider == "mock" {
+            info!("Using mock provider client");
+            Arc::new(MockProviderClient::default())
+        } else if config.openai_api_key.is_some() {
+            Self::openai_client(config, middleware)?
         } else {
             info!("No provider credentials found; using mock provider client");
-            Ok(Arc::new(MockProviderClient::default()))
+            Arc::new(MockProviderClient::default())
+        };
+        Ok(client)
+    }
+
+    /// Create the client for provider `name`, as referenced by a task route
+    pub fn create_named_client(
+        name: &str,
+        config: &ProviderConfig,
+        middleware: &[Arc<dyn ProviderMiddleware>],
+    ) -> Result<Arc<dyn ProviderClient>> {
+        Self::check_provider(name, config)?;
+        if name == config.provider {
+            return Self::create_base_client(config, middleware);
+        }
+        match name {
+            "mock" => Ok(Arc::new(MockProviderClient::default())),
+            // check_provider only lets "openai" through otherwise
+            _ => Self::openai_client(config, middleware),
+        }
+    }
+
+    fn openai_client(
+        config: &ProviderConfig,
+        middleware: &[Arc<dyn ProviderMiddleware>],
+    ) -> Result<Arc<dyn ProviderClient>> {
+        info!("Creating OpenAI provider client");
+        let client = middleware.iter().cloned().fold(
+            OpenAIClient::from_provider_config(config)?,
+            OpenAIClient::with_middleware,
+        );
+        Ok(Arc::new(client))
+    }
+
+    /// Wrap `client` in the configured model fallback chain, if any
+    fn with_fallback(
+        config: &ProviderConfig,
+        client: Arc<dyn ProviderClient>,
+    ) -> Arc<dyn ProviderClient> {
+        if config.fallback_models.is_empty() {
+            return client;
+        }
+
+        info!(
+            "Falling back from {} to {}",
+            config.default_model,
+            config.fallback_models.join(", ")
+        );
+        let targets = std::iter::once(&config.default_model)
+            .chain(&config.fallback_models)
+            .map(|model| FallbackTarget::new(client.clone(), model.clone()))
+            .collect();
+        Arc::new(FallbackProviderClient::new(targets))
+    }
+
+    /// Check that a client for provider `name` can be created from `config`
+    fn check_provider(name: &str, config: &ProviderConfig) -> Result<()> {
+        if name == config.provider {
+            return Ok(());
+        }
+        match name {
+            "mock" => Ok(()),
+            "openai" if config.openai_api_key.is_some() => Ok(()),
+            "openai" => Err(ProviderError::ConfigurationMissing {
+                provider: name.to_string(),
+            }),
+            _ => Err(ProviderError::ProviderNotSupported {
+                provider: name.to_string(),
+                available: format!("{}, openai, mock", config.provider),
+            }),
+        }
+    }
+
+    /// Create the embedding client for the `embedding` task route, or for
+    /// the default provider and [`DEFAULT_EMBEDDING_MODEL`] without one.
+    /// Like chat, a provider without credentials falls back to local hash
+    /// embeddings.
+    pub fn create_embedding_client(config: &ProviderConfig) -> Result<Arc<dyn EmbeddingClient>> {
+        let route = config.routes.get(&TaskKind::Embedding);
+        let provider = route
+            .and_then(|route| route.provider.as_deref())
+            .unwrap_or(&config.provider);
+        Self::check_provider(provider, config)?;
+
+        if provider == "mock" || config.openai_api_key.is_none() {
+            info!("Using local hash embeddings");
+            return Ok(Arc::new(HashEmbeddingClient::default()));
         }
+        let model = route.map_or(DEFAULT_EMBEDDING_MODEL, |route| route.model.as_str());
+        info!("Creating OpenAI embedding client for {}", model);
+        let client = Self::create_openai_client(config)?;
+        Ok(Arc::new(OpenAIEmbeddingClient::new(client, model)))
     }
 
     /// Create an OpenAI client specifically
@@ -32,6 +164,69 @@ impl ProviderClientFactory {
         Ok(Arc::new(client))
     }
 
+    /// Ids of the models the configured OpenAI endpoint offers, sorted.
+    /// Listed once per endpoint and key, then served from the cache.
+    pub async fn available_models(config: &ProviderConfig) -> Result<Arc<Vec<String>>> {
+        let mut hasher = DefaultHasher::new();
+        config.base_url.hash(&mut hasher);
+        config.openai_api_key.hash(&mut hasher);
+        let key = hasher.finish();
+        if let Some(models) = MODEL_LISTS.lock().unwrap().get(&key) {
+            return Ok(models.clone());
+        }
+
+        let client = OpenAIClient::from_provider_config(config)?;
+        let mut models: Vec<String> = client
+            .list_models()
+            .await?
+            .data
+            .into_iter()
+            .map(|model| model.id)
+            .collect();
+        models.sort();
+        let models = Arc::new(models);
+        MODEL_LISTS.lock().unwrap().insert(key, models.clone());
+        Ok(models)
+    }
+
+    /// Check that the provider offers the configured default model, so a
+    /// typo shows up before the first request. Keys that may not list
+    /// models only get a warning.
+    pub async fn validate_model(config: &ProviderConfig) -> Result<ModelCheck> {
+        if config.provider == "mock" {
+            return Ok(ModelCheck::Unverified {
+                reason: "the mock provider accepts any model".to_string(),
+            });
+        }
+        if config.openai_api_key.is_none() {
+            return Ok(ModelCheck::Unverified {
+                reason: "no API key is set".to_string(),
+            });
+        }
+
+        let models = match Self::available_models(config).await {
+            Ok(models) => models,
+            Err(ProviderError::AuthorizationDenied { .. }) => {
+                warn!(
+                    "The API key may not list models; using '{}' unchecked",
+                    config.default_model
+                );
+                return Ok(ModelCheck::Unverified {
+                    reason: "the API key is not allowed to list models".to_string(),
+                });
+            }
+            Err(e) => return Err(e),
+        };
+
+        if models.contains(&config.default_model) {
+            Ok(ModelCheck::Available)
+        } else {
+            Err(ProviderError::ModelNotFound {
+                model: config.default_model.clone(),
+            })
+        }
+    }
+
     /// Validate provider configuration
     pub fn validate_config(config: &ProviderConfig) -> Result<()> {
         if config.openai_api_key.is_none() {
@@ -48,6 +243,22 @@ impl ProviderClientFactory {
             warn!("Timeout is set to 0, this may cause issues");
         }
 
+        for (kind, route) in &config.routes {
+            if route.model.is_empty() {
+                return Err(ProviderError::ConfigurationInvalid {
+                    provider: route
+                        .provider
+                        .clone()
+                        .unwrap_or_else(|| config.provider.clone()),
+                    setting: format!("routes.{}.model", kind),
+                    issue: "must not be empty".to_string(),
+                });
+            }
+            if let Some(provider) = &route.provider {
+                Self::check_provider(provider, config)?;
+            }
+        }
+
         // Validate base URL format if provided
         if let Some(base_url) = &config.base_url {
             if !base_url.starts_with("http://") && !base_url.starts_with("https://") {
@@ -68,16 +279,24 @@ impl ProviderClientFactory {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use fennec_core::config::ProviderConfig;
+    use crate::test_server::{MockResponse, MockServer};
+    use fennec_core::config::{ProviderConfig, TaskRoute};
     use fennec_core::provider::{ProviderMessage, ProviderRequest};
 
     #[test]
     fn test_validate_config_missing_api_key() {
         let config = ProviderConfig {
+            provider: "openai".to_string(),
             openai_api_key: None,
+            anthropic_api_key: None,
+            openrouter_api_key: None,
             default_model: "gpt-4".to_string(),
             base_url: None,
             timeout_seconds: 30,
+            connect_timeout_seconds: 10,
+            stream_idle_timeout_seconds: 60,
+            fallback_models: Vec::new(),
+            routes: Default::default(),
         };
 
         let result = ProviderClientFactory::validate_config(&config);
@@ -87,10 +306,17 @@ mod tests {
     #[test]
     fn test_validate_config_empty_model() {
         let config = ProviderConfig {
+            provider: "openai".to_string(),
             openai_api_key: Some("test-key".to_string()),
+            anthropic_api_key: None,
+            openrouter_api_key: None,
             default_model: String::new(),
             base_url: None,
             timeout_seconds: 30,
+            connect_timeout_seconds: 10,
+            stream_idle_timeout_seconds: 60,
+            fallback_models: Vec::new(),
+            routes: Default::default(),
         };
 
         let result = ProviderClientFactory::validate_config(&config);
@@ -100,10 +326,17 @@ mod tests {
     #[test]
     fn test_validate_config_invalid_base_url() {
         let config = ProviderConfig {
+            provider: "openai".to_string(),
             openai_api_key: Some("test-key".to_string()),
+            anthropic_api_key: None,
+            openrouter_api_key: None,
             default_model: "gpt-4".to_string(),
             base_url: Some("invalid-url".to_string()),
             timeout_seconds: 30,
+            connect_timeout_seconds: 10,
+            stream_idle_timeout_seconds: 60,
+            fallback_models: Vec::new(),
+            routes: Default::default(),
         };
 
         let result = ProviderClientFactory::validate_config(&config);
@@ -113,10 +346,17 @@ mod tests {
     #[test]
     fn test_validate_config_valid() {
         let config = ProviderConfig {
+            provider: "openai".to_string(),
             openai_api_key: Some("test-key".to_string()),
+            anthropic_api_key: None,
+            openrouter_api_key: None,
             default_model: "gpt-4".to_string(),
             base_url: Some("https://api.openai.com/v1".to_string()),
             timeout_seconds: 30,
+            connect_timeout_seconds: 10,
+            stream_idle_timeout_seconds: 60,
+            fallback_models: Vec::new(),
+            routes: Default::default(),
         };
 
         let result = ProviderClientFactory::validate_config(&config);
@@ -126,10 +366,17 @@ mod tests {
     #[tokio::test]
     async fn test_create_client_without_api_key_uses_mock() {
         let config = ProviderConfig {
+            provider: "openai".to_string(),
             openai_api_key: None,
+            anthropic_api_key: None,
+            openrouter_api_key: None,
             default_model: "gpt-4".to_string(),
             base_url: None,
             timeout_seconds: 30,
+            connect_timeout_seconds: 10,
+            stream_idle_timeout_seconds: 60,
+            fallback_models: Vec::new(),
+            routes: Default::default(),
         };
 
         let client = ProviderClientFactory::create_client(&config).expect("mock provider");
@@ -141,9 +388,187 @@ mod tests {
             }],
             model: "mock".to_string(),
             stream: false,
+            temperature: None,
+            max_tokens: None,
         };
 
         let response = client.complete(request).await.expect("mock response");
         assert!(response.content.contains("offline mode"));
     }
+
+    #[tokio::test]
+    async fn test_mock_provider_is_used_even_with_api_key() {
+        let config = ProviderConfig {
+            provider: "mock".to_string(),
+            openai_api_key: Some("sk-test".to_string()),
+            anthropic_api_key: None,
+            openrouter_api_key: None,
+            default_model: "gpt-4".to_string(),
+            base_url: None,
+            timeout_seconds: 30,
+            connect_timeout_seconds: 10,
+            stream_idle_timeout_seconds: 60,
+            fallback_models: Vec::new(),
+            routes: Default::default(),
+        };
+
+        let client = ProviderClientFactory::create_client(&config).expect("mock provider");
+        let request = ProviderRequest {
+            id: uuid::Uuid::new_v4(),
+            messages: vec![ProviderMessage {
+                role: "user".to_string(),
+                content: "Hello".to_string(),
+            }],
+            model: "gpt-4".to_string(),
+            stream: false,
+            temperature: None,
+            max_tokens: None,
+        };
+
+        let response = client.complete(request).await.expect("mock response");
+        assert!(response.content.contains("offline mode"));
+    }
+
+    fn mock_server_config(server: &MockServer, model: &str) -> ProviderConfig {
+        ProviderConfig {
+            provider: "openai".to_string(),
+            openai_api_key: Some("sk-test".to_string()),
+            anthropic_api_key: None,
+            openrouter_api_key: None,
+            default_model: model.to_string(),
+            base_url: Some(server.base_url.clone()),
+            timeout_seconds: 30,
+            connect_timeout_seconds: 10,
+            stream_idle_timeout_seconds: 60,
+            fallback_models: Vec::new(),
+            routes: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validate_model_against_listed_models() {
+        let model = |id: &str| serde_json::json!({"id": id, "object": "model", "created": 0, "owned_by": "openai"});
+        // Only one listing is served; later checks must come from the cache
+        let server = MockServer::start(vec![MockResponse::json(serde_json::json!({
+            "object": "list",
+            "data": [model("gpt-4o-mini"), model("gpt-4o")]
+        }))])
+        .await;
+
+        let config = mock_server_config(&server, "gpt-4o");
+        assert_eq!(
+            ProviderClientFactory::validate_model(&config)
+                .await
+                .unwrap(),
+            ModelCheck::Available
+        );
+        assert_eq!(
+            *ProviderClientFactory::available_models(&config)
+                .await
+                .unwrap(),
+            ["gpt-4o", "gpt-4o-mini"]
+        );
+
+        let typo = mock_server_config(&server, "gpt-4o-mnii");
+        let error = ProviderClientFactory::validate_model(&typo)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&error, ProviderError::ModelNotFound { model } if model == "gpt-4o-mnii"),
+            "{:?}",
+            error
+        );
+    }
+
+    #[tokio::test]
+    async fn test_validate_model_warns_when_listing_is_forbidden() {
+        let server = MockServer::start(vec![MockResponse::Json {
+            status: 403,
+            body: serde_json::json!({
+                "error": {
+                    "message": "You have insufficient permissions for this operation.",
+                    "type": "invalid_request_error",
+                    "param": null,
+                    "code": null
+                }
+            })
+            .to_string(),
+        }])
+        .await;
+
+        let check = ProviderClientFactory::validate_model(&mock_server_config(&server, "gpt-4o"))
+            .await
+            .unwrap();
+        assert!(
+            matches!(check, ModelCheck::Unverified { .. }),
+            "{:?}",
+            check
+        );
+    }
+
+    #[tokio::test]
+    async fn test_validate_model_skips_mock_provider() {
+        let config = ProviderConfig {
+            provider: "mock".to_string(),
+            openai_api_key: None,
+            anthropic_api_key: None,
+            openrouter_api_key: None,
+            default_model: "anything".to_string(),
+            base_url: None,
+            timeout_seconds: 30,
+            connect_timeout_seconds: 10,
+            stream_idle_timeout_seconds: 60,
+            fallback_models: Vec::new(),
+            routes: Default::default(),
+        };
+        assert!(matches!(
+            ProviderClientFactory::validate_model(&config)
+                .await
+                .unwrap(),
+            ModelCheck::Unverified { .. }
+        ));
+    }
+
+    #[test]
+    fn test_create_embedding_client_follows_config() {
+        let mut config = ProviderConfig {
+            provider: "mock".to_string(),
+            openai_api_key: None,
+            anthropic_api_key: None,
+            openrouter_api_key: None,
+            default_model: "gpt-4o".to_string(),
+            base_url: None,
+            timeout_seconds: 30,
+            connect_timeout_seconds: 10,
+            stream_idle_timeout_seconds: 60,
+            fallback_models: Vec::new(),
+            routes: Default::default(),
+        };
+        let client = ProviderClientFactory::create_embedding_client(&config).unwrap();
+        assert_eq!(client.model(), "local-hash");
+        assert_eq!(client.dimensions(), 256);
+
+        config.provider = "openai".to_string();
+        config.openai_api_key = Some("sk-test".to_string());
+        let client = ProviderClientFactory::create_embedding_client(&config).unwrap();
+        assert_eq!(client.model(), "text-embedding-3-small");
+        assert_eq!(client.dimensions(), 1536);
+
+        config.routes.insert(
+            TaskKind::Embedding,
+            TaskRoute::new("text-embedding-3-large"),
+        );
+        let client = ProviderClientFactory::create_embedding_client(&config).unwrap();
+        assert_eq!(client.model(), "text-embedding-3-large");
+        assert_eq!(client.dimensions(), 3072);
+
+        config.routes.insert(
+            TaskKind::Embedding,
+            TaskRoute::new("voyage-code-2").with_provider("voyage"),
+        );
+        assert!(matches!(
+            ProviderClientFactory::create_embedding_client(&config),
+            Err(ProviderError::ProviderNotSupported { .. })
+        ));
+    }
 }
@@ -0,0 +1,378 @@
+//! Text embeddings for semantic memory.
+//!
+//! [`EmbeddingClient`] turns texts into vectors of a fixed length, which it
+//! reports so an index can refuse vectors from an incompatible model.
+//! [`OpenAIEmbeddingClient`] calls the OpenAI Embeddings API, splitting
+//! large inputs into batches the API accepts. [`HashEmbeddingClient`] hashes
+//! words into a vector locally, for tests and offline use; it captures word
+//! overlap, not meaning.
+
+use async_trait::async_trait;
+use std::sync::Arc;
+use tracing::debug;
+
+use crate::error::{ProviderError, Result};
+use crate::models::EmbeddingRequest;
+use crate::openai::OpenAIClient;
+
+/// Most inputs the OpenAI Embeddings API takes in one request
+pub const MAX_EMBEDDING_BATCH: usize = 2048;
+
+/// Embedding model used when no route names one
+pub const DEFAULT_EMBEDDING_MODEL: &str = "text-embedding-3-small";
+
+/// Vector length of [`HashEmbeddingClient::default`]
+pub const HASH_EMBEDDING_DIMENSIONS: usize = 256;
+
+/// Vector lengths of the OpenAI embedding models
+const OPENAI_DIMENSIONS: &[(&str, usize)] = &[
+    ("text-embedding-3-small", 1536),
+    ("text-embedding-3-large", 3072),
+    ("text-embedding-ada-002", 1536),
+];
+
+#[async_trait]
+pub trait EmbeddingClient: Send + Sync {
+    /// Model producing the vectors; vectors of different models don't mix
+    fn model(&self) -> &str;
+
+    /// Length of every vector returned
+    fn dimensions(&self) -> usize;
+
+    /// One vector per text, in the order of `texts`
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+}
+
+/// Embeddings from the OpenAI Embeddings API
+pub struct OpenAIEmbeddingClient {
+    client: Arc<OpenAIClient>,
+    model: String,
+    dimensions: usize,
+    /// Whether `dimensions` is sent to the API, for text-embedding-3 models
+    shortened: bool,
+    batch_size: usize,
+}
+
+impl OpenAIEmbeddingClient {
+    /// Embed with `model`, which must be a known OpenAI embedding model
+    /// unless [`Self::with_dimensions`] follows
+    pub fn new(client: Arc<OpenAIClient>, model: impl Into<String>) -> Self {
+        let model = model.into();
+        let dimensions = OPENAI_DIMENSIONS
+            .iter()
+            .find(|(name, _)| *name == model)
+            .map(|(_, dimensions)| *dimensions)
+            .unwrap_or(0);
+        Self {
+            client,
+            model,
+            dimensions,
+            shortened: false,
+            batch_size: MAX_EMBEDDING_BATCH,
+        }
+    }
+
+    /// Ask the API for vectors of `dimensions`; text-embedding-3 models
+    /// shorten theirs, and this also sets the length of unknown models
+    pub fn with_dimensions(mut self, dimensions: usize) -> Self {
+        self.dimensions = dimensions;
+        self.shortened = true;
+        self
+    }
+
+    /// Send at most `batch_size` texts per request, capped at
+    /// [`MAX_EMBEDDING_BATCH`]
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.clamp(1, MAX_EMBEDDING_BATCH);
+        self
+    }
+
+    /// Check that the vector length is known
+    fn check_dimensions(&self) -> Result<()> {
+        if self.dimensions == 0 {
+            return Err(ProviderError::ModelConfigInvalid {
+                setting: "dimensions".to_string(),
+                value: format!("unknown for embedding model '{}'", self.model),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EmbeddingClient for OpenAIEmbeddingClient {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        self.check_dimensions()?;
+
+        let mut vectors = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(self.batch_size) {
+            debug!("Embedding a batch of {} texts", batch.len());
+            let request = EmbeddingRequest {
+                model: self.model.clone(),
+                input: batch.to_vec(),
+                dimensions: self.shortened.then_some(self.dimensions),
+            };
+            let mut data = self.client.embeddings(&request).await?.data;
+            if data.len() != batch.len() {
+                return Err(ProviderError::IncompleteResponse {
+                    received: data.len(),
+                    expected: batch.len(),
+                });
+            }
+            data.sort_by_key(|item| item.index);
+
+            for item in data {
+                if item.embedding.len() != self.dimensions {
+                    return Err(ProviderError::ResponseParsingFailed {
+                        expected: format!("{}-dimensional embeddings", self.dimensions),
+                        actual: format!("{} dimensions", item.embedding.len()),
+                    });
+                }
+                vectors.push(item.embedding);
+            }
+        }
+        Ok(vectors)
+    }
+}
+
+/// Deterministic embeddings computed locally by hashing words into a
+/// vector, so texts sharing words end up close
+#[derive(Debug, Clone)]
+pub struct HashEmbeddingClient {
+    dimensions: usize,
+}
+
+impl HashEmbeddingClient {
+    pub fn new(dimensions: usize) -> Self {
+        Self {
+            dimensions: dimensions.max(1),
+        }
+    }
+
+    /// Unit-length vector of `text`; all zeros when it has no words
+    pub fn embed_text(&self, text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0f32; self.dimensions];
+        for word in text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+        {
+            let hash = fnv1a(word.to_lowercase().as_bytes());
+            let bucket = (hash % self.dimensions as u64) as usize;
+            // The sign keeps colliding words from only ever adding up
+            vector[bucket] += if hash >> 63 == 0 { 1.0 } else { -1.0 };
+        }
+
+        let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|x| *x /= norm);
+        }
+        vector
+    }
+}
+
+impl Default for HashEmbeddingClient {
+    fn default() -> Self {
+        Self::new(HASH_EMBEDDING_DIMENSIONS)
+    }
+}
+
+#[async_trait]
+impl EmbeddingClient for HashEmbeddingClient {
+    fn model(&self) -> &str {
+        "local-hash"
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|text| self.embed_text(text)).collect())
+    }
+}
+
+/// 64-bit FNV-1a, which unlike the std hasher is stable across releases
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{MockResponse, MockServer};
+    use crate::OpenAIConfig;
+    use std::time::Duration;
+
+    fn texts(texts: &[&str]) -> Vec<String> {
+        texts.iter().map(|text| text.to_string()).collect()
+    }
+
+    fn cosine(a: &[f32], b: &[f32]) -> f32 {
+        a.iter().zip(b).map(|(x, y)| x * y).sum()
+    }
+
+    #[tokio::test]
+    async fn test_hash_embeddings_are_deterministic_unit_vectors() {
+        let client = HashEmbeddingClient::new(64);
+        let vectors = client
+            .embed(&texts(&[
+                "parse the config file",
+                "Parse the CONFIG file",
+                "rotate telemetry logs",
+                "",
+            ]))
+            .await
+            .unwrap();
+
+        assert_eq!(client.dimensions(), 64);
+        assert!(vectors.iter().all(|vector| vector.len() == 64));
+        assert_eq!(vectors[0], vectors[1]);
+        assert_eq!(vectors[0], client.embed_text("parse the config file"));
+        assert!((cosine(&vectors[0], &vectors[0]) - 1.0).abs() < 1e-5);
+        assert!(cosine(&vectors[0], &vectors[2]) < 0.5);
+        assert!(vectors[3].iter().all(|x| *x == 0.0));
+    }
+
+    fn openai(server: &MockServer) -> Arc<OpenAIClient> {
+        Arc::new(
+            OpenAIClient::new(OpenAIConfig {
+                api_key: "test-key".to_string(),
+                base_url: server.base_url.clone(),
+                max_retries: 2,
+                initial_retry_delay: Duration::from_millis(1),
+                max_retry_delay: Duration::from_millis(10),
+                ..Default::default()
+            })
+            .unwrap(),
+        )
+    }
+
+    /// A response embedding inputs `first..first + count` as `[i, i]`,
+    /// listed in reverse order
+    fn embeddings(first: usize, count: usize) -> MockResponse {
+        let data: Vec<_> = (0..count)
+            .rev()
+            .map(|index| {
+                let value = (first + index) as f32;
+                serde_json::json!({"object": "embedding", "index": index, "embedding": [value, value]})
+            })
+            .collect();
+        MockResponse::json(serde_json::json!({
+            "object": "list",
+            "data": data,
+            "model": "text-embedding-3-small",
+            "usage": {"prompt_tokens": 3, "total_tokens": 3}
+        }))
+    }
+
+    fn error(status: u16, message: &str) -> MockResponse {
+        MockResponse::Json {
+            status,
+            body: serde_json::json!({
+                "error": {"message": message, "type": "invalid_request_error", "param": null, "code": null}
+            })
+            .to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_openai_embeddings_are_batched_in_order() {
+        let server =
+            MockServer::start(vec![embeddings(0, 2), embeddings(2, 2), embeddings(4, 1)]).await;
+        let client = OpenAIEmbeddingClient::new(openai(&server), "text-embedding-3-small")
+            .with_dimensions(2)
+            .with_batch_size(2);
+
+        let vectors = client
+            .embed(&texts(&["a", "b", "c", "d", "e"]))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            vectors,
+            (0..5).map(|i| vec![i as f32; 2]).collect::<Vec<_>>()
+        );
+        let requests = server.requests();
+        let inputs: Vec<_> = requests.iter().map(|request| &request["input"]).collect();
+        assert_eq!(
+            inputs,
+            [
+                &serde_json::json!(["a", "b"]),
+                &serde_json::json!(["c", "d"]),
+                &serde_json::json!(["e"])
+            ]
+        );
+        assert!(requests
+            .iter()
+            .all(|request| request["model"] == "text-embedding-3-small"
+                && request["dimensions"] == 2));
+    }
+
+    #[tokio::test]
+    async fn test_openai_embeddings_retry_rate_limits() {
+        let server = MockServer::start(vec![
+            error(429, "Rate limit reached for requests"),
+            embeddings(0, 1),
+        ])
+        .await;
+        let client =
+            OpenAIEmbeddingClient::new(openai(&server), "custom-embedder").with_dimensions(2);
+
+        let vectors = client.embed(&texts(&["a"])).await.unwrap();
+
+        assert_eq!(vectors, [vec![0.0, 0.0]]);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_openai_embedding_errors() {
+        let server = MockServer::start(vec![
+            error(401, "Incorrect API key provided"),
+            error(400, "'$.input' is invalid"),
+            embeddings(0, 1),
+        ])
+        .await;
+        let client = OpenAIEmbeddingClient::new(openai(&server), "text-embedding-3-small");
+        assert_eq!(client.dimensions(), 1536);
+
+        let error = client.embed(&texts(&["a"])).await.unwrap_err();
+        assert!(
+            matches!(error, ProviderError::AuthenticationFailed { .. }),
+            "{:?}",
+            error
+        );
+        let error = client.embed(&texts(&[""])).await.unwrap_err();
+        assert!(
+            matches!(error, ProviderError::InvalidRequest { .. }),
+            "{:?}",
+            error
+        );
+        // Two dimensions where the model makes 1536
+        let error = client.embed(&texts(&["a"])).await.unwrap_err();
+        assert!(
+            matches!(error, ProviderError::ResponseParsingFailed { .. }),
+            "{:?}",
+            error
+        );
+
+        // Nothing is sent for a model of unknown length
+        let unknown = OpenAIEmbeddingClient::new(openai(&server), "custom-embedder");
+        let error = unknown.embed(&texts(&["a"])).await.unwrap_err();
+        assert!(
+            matches!(error, ProviderError::ModelConfigInvalid { .. }),
+            "{:?}",
+            error
+        );
+        assert_eq!(server.requests().len(), 3);
+    }
+}
@@ -1,4 +1,4 @@
-use fennec_core::error::{ErrorCategory, ErrorInfo, ErrorSeverity, RecoveryAction};
+use fennec_core::error::{DomainError, ErrorCategory, ErrorInfo, ErrorSeverity, RecoveryAction};
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, ProviderError>;
@@ -16,9 +16,18 @@ pub enum ProviderError {
     #[error("Network connection failed: {endpoint} - {reason}")]
     ConnectionFailed { endpoint: String, reason: String },
 
+    /// The whole request took longer than the request timeout
     #[error("Network timeout: {operation} exceeded {timeout_ms}ms")]
     Timeout { operation: String, timeout_ms: u64 },
 
+    #[error("Connection timeout: {endpoint} not reached within {timeout_ms}ms")]
+    ConnectTimeout { endpoint: String, timeout_ms: u64 },
+
+    /// A streamed reply stopped sending data while the connection stayed
+    /// open
+    #[error("Stream stalled: {operation} received no data for {idle_ms}ms")]
+    StreamIdle { operation: String, idle_ms: u64 },
+
     #[error("SSL/TLS error: {details}")]
     TlsError { details: String },
 
@@ -56,6 +65,9 @@ pub enum ProviderError {
         reset_date: String,
     },
 
+    #[error("Session budget exhausted: ${spent_usd:.4} spent of ${limit_usd:.2}")]
+    BudgetExceeded { spent_usd: f64, limit_usd: f64 },
+
     #[error("Token limit exceeded: {used}/{limit} tokens. {suggestion}")]
     TokenLimit {
         used: u64,
@@ -169,6 +181,8 @@ impl ProviderError {
                 is_temporary: true, ..
             } => true,
             ProviderError::Timeout { .. } => true,
+            ProviderError::ConnectTimeout { .. } => true,
+            ProviderError::StreamIdle { .. } => true,
             ProviderError::Http { .. } => true,
             ProviderError::ConnectionFailed { .. } => true,
             ProviderError::ServiceUnavailable { .. } => true,
@@ -201,6 +215,48 @@ impl ProviderError {
 }
 
 impl ErrorInfo for ProviderError {
+    fn code(&self) -> &'static str {
+        match self {
+            ProviderError::Http { .. } => "FEN-2001",
+            ProviderError::ConnectionFailed { .. } => "FEN-2002",
+            ProviderError::Timeout { .. } => "FEN-2003",
+            ProviderError::ConnectTimeout { .. } => "FEN-2004",
+            ProviderError::StreamIdle { .. } => "FEN-2005",
+            ProviderError::TlsError { .. } => "FEN-2006",
+            ProviderError::AuthenticationFailed { .. } => "FEN-2007",
+            ProviderError::ApiKeyInvalid { .. } => "FEN-2008",
+            ProviderError::AuthorizationDenied { .. } => "FEN-2009",
+            ProviderError::ApiKeyExpired { .. } => "FEN-2010",
+            ProviderError::RateLimit { .. } => "FEN-2011",
+            ProviderError::QuotaExceeded { .. } => "FEN-2012",
+            ProviderError::BudgetExceeded { .. } => "FEN-2013",
+            ProviderError::TokenLimit { .. } => "FEN-2014",
+            ProviderError::InvalidRequest { .. } => "FEN-2015",
+            ProviderError::RequestTooLarge { .. } => "FEN-2016",
+            ProviderError::UnsupportedContentType { .. } => "FEN-2017",
+            ProviderError::MissingParameter { .. } => "FEN-2018",
+            ProviderError::ModelNotFound { .. } => "FEN-2019",
+            ProviderError::ModelCapabilityUnsupported { .. } => "FEN-2020",
+            ProviderError::ModelUnavailable { .. } => "FEN-2021",
+            ProviderError::ModelConfigInvalid { .. } => "FEN-2022",
+            ProviderError::ServerError { .. } => "FEN-2023",
+            ProviderError::ServiceUnavailable { .. } => "FEN-2024",
+            ProviderError::ServiceMaintenance { .. } => "FEN-2025",
+            ProviderError::StreamError { .. } => "FEN-2026",
+            ProviderError::ResponseParsingFailed { .. } => "FEN-2027",
+            ProviderError::IncompleteResponse { .. } => "FEN-2028",
+            ProviderError::InvalidResponseFormat { .. } => "FEN-2029",
+            ProviderError::ConfigurationMissing { .. } => "FEN-2030",
+            ProviderError::ConfigurationInvalid { .. } => "FEN-2031",
+            ProviderError::ProviderNotSupported { .. } => "FEN-2032",
+            ProviderError::ContentFiltered { .. } => "FEN-2033",
+            ProviderError::ContentTooLarge { .. } => "FEN-2034",
+            ProviderError::ContentEncodingError { .. } => "FEN-2035",
+            ProviderError::Json { .. } => "FEN-2036",
+            ProviderError::Generic { .. } => "FEN-2037",
+        }
+    }
+
     fn category(&self) -> ErrorCategory {
         match self {
             // User errors - configuration and request issues
@@ -215,6 +271,7 @@ impl ErrorInfo for ProviderError {
             | ProviderError::UnsupportedContentType { .. }
             | ProviderError::RequestTooLarge { .. }
             | ProviderError::ContentTooLarge { .. }
+            | ProviderError::BudgetExceeded { .. }
             | ProviderError::TokenLimit { .. } => ErrorCategory::User,
 
             // Security errors
@@ -228,6 +285,8 @@ impl ErrorInfo for ProviderError {
             ProviderError::Http { .. }
             | ProviderError::ConnectionFailed { .. }
             | ProviderError::Timeout { .. }
+            | ProviderError::ConnectTimeout { .. }
+            | ProviderError::StreamIdle { .. }
             | ProviderError::TlsError { .. }
             | ProviderError::RateLimit { .. }
             | ProviderError::QuotaExceeded { .. }
@@ -264,6 +323,8 @@ impl ErrorInfo for ProviderError {
             // Warnings for temporary or recoverable issues
             ProviderError::RateLimit { .. }
             | ProviderError::Timeout { .. }
+            | ProviderError::ConnectTimeout { .. }
+            | ProviderError::StreamIdle { .. }
             | ProviderError::ServiceUnavailable { .. }
             | ProviderError::ModelUnavailable { .. }
             | ProviderError::IncompleteResponse { .. } => ErrorSeverity::Warning,
@@ -318,6 +379,15 @@ impl ErrorInfo for ProviderError {
                 ]
             }
 
+            ProviderError::BudgetExceeded { .. } => {
+                vec![
+                    RecoveryAction::CheckConfiguration(
+                        "Raise usage.hard_budget_usd in the configuration".to_string(),
+                    ),
+                    RecoveryAction::ManualAction("Start a new session".to_string()),
+                ]
+            }
+
             ProviderError::TokenLimit { suggestion, .. } => {
                 vec![
                     RecoveryAction::RetryWithChanges(suggestion.clone()),
@@ -383,6 +453,28 @@ impl ErrorInfo for ProviderError {
                 ]
             }
 
+            ProviderError::ConnectTimeout { endpoint, .. } => {
+                vec![
+                    RecoveryAction::Retry,
+                    RecoveryAction::CheckConfiguration(format!(
+                        "Check that {} is reachable",
+                        endpoint
+                    )),
+                    RecoveryAction::RetryWithChanges(
+                        "Increase provider.connect_timeout_seconds".to_string(),
+                    ),
+                ]
+            }
+
+            ProviderError::StreamIdle { .. } => {
+                vec![
+                    RecoveryAction::Retry,
+                    RecoveryAction::RetryWithChanges(
+                        "Increase provider.stream_idle_timeout_seconds".to_string(),
+                    ),
+                ]
+            }
+
             // Most network errors can be retried
             ProviderError::Http { .. }
             | ProviderError::ConnectionFailed { .. }
@@ -403,6 +495,23 @@ impl ErrorInfo for ProviderError {
         }
     }
 
+    fn hint(&self) -> Option<String> {
+        match self {
+            ProviderError::ApiKeyInvalid { provider }
+            | ProviderError::ConfigurationMissing { provider }
+            | ProviderError::AuthenticationFailed { provider, .. } => {
+                Some(match api_key_variable(provider) {
+                    Some(variable) => format!("Set {} to a valid API key", variable),
+                    None => format!("Configure a valid API key for {}", provider),
+                })
+            }
+            _ => self
+                .recovery_actions()
+                .iter()
+                .find_map(|action| action.suggestion().map(str::to_string)),
+        }
+    }
+
     fn user_message(&self) -> String {
         match self {
             ProviderError::ApiKeyInvalid { provider } => format!(
@@ -421,6 +530,10 @@ impl ErrorInfo for ProviderError {
                 "{} monthly quota exceeded. Please upgrade your plan or wait for reset.",
                 provider
             ),
+            ProviderError::BudgetExceeded { limit_usd, .. } => format!(
+                "This session reached its ${:.2} spending limit. No further requests will be sent.",
+                limit_usd
+            ),
             ProviderError::TokenLimit { .. } => {
                 "Request too long. Please reduce the size of your input.".to_string()
             }
@@ -437,6 +550,12 @@ impl ErrorInfo for ProviderError {
             ProviderError::Timeout { .. } => {
                 "Request timed out. Please check your connection and try again.".to_string()
             }
+            ProviderError::ConnectTimeout { .. } => {
+                "Could not connect to the AI service. Please check your connection.".to_string()
+            }
+            ProviderError::StreamIdle { .. } => {
+                "The AI service stopped responding mid-reply. Please try again.".to_string()
+            }
             ProviderError::ConfigurationMissing { provider } => format!(
                 "{} is not configured. Please set up your provider configuration.",
                 provider
@@ -499,7 +618,17 @@ impl From<serde_json::Error> for ProviderError {
 
 impl From<ProviderError> for fennec_core::FennecError {
     fn from(err: ProviderError) -> Self {
-        fennec_core::FennecError::Provider(Box::new(err))
+        fennec_core::FennecError::Provider(Box::new(DomainError::new(err)))
+    }
+}
+
+/// Environment variable holding the API key of `provider`
+fn api_key_variable(provider: &str) -> Option<&'static str> {
+    match provider.to_lowercase().as_str() {
+        "openai" => Some("OPENAI_API_KEY"),
+        "anthropic" => Some("ANTHROPIC_API_KEY"),
+        "openrouter" => Some("OPENROUTER_API_KEY"),
+        _ => None,
     }
 }
 
@@ -752,10 +881,20 @@ mod tests {
     #[test]
     fn test_fennec_error_conversion() {
         let provider_err = ProviderError::ApiKeyInvalid {
-            provider: "test".to_string(),
+            provider: "openai".to_string(),
         };
         let fennec_err: fennec_core::FennecError = provider_err.into();
         assert!(matches!(fennec_err, fennec_core::FennecError::Provider(_)));
+        assert_eq!(fennec_err.code(), "FEN-2008");
+        assert_eq!(
+            fennec_err.hint().as_deref(),
+            Some("Set OPENAI_API_KEY to a valid API key")
+        );
+        assert!(fennec_err.developer_message().starts_with("[FEN-2008]"));
+        assert!(matches!(
+            fennec_err.domain_source::<ProviderError>(),
+            Some(ProviderError::ApiKeyInvalid { .. })
+        ));
     }
 
     // Test helper functions
@@ -0,0 +1,466 @@
+//! Automatic fallback across an ordered chain of models.
+//!
+//! [`FallbackProviderClient`] tries each [`FallbackTarget`] in turn. How a
+//! failure is handled depends on its [`FailureClass`]: transient failures
+//! move on to the next model, context-length failures move on to the next
+//! model with a larger window, and anything else is returned as is. When
+//! every model rejects the request as too long, an optional
+//! [`ContextReducer`] gets one chance to shrink it before the chain is
+//! retried.
+
+use async_trait::async_trait;
+use fennec_core::provider::{AttachmentSupport, ProviderClient, ProviderRequest, ProviderResponse};
+use fennec_core::FennecError;
+use futures::Stream;
+use std::future::Future;
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+use crate::error::ProviderError;
+
+type TextStream = Box<dyn Stream<Item = fennec_core::Result<String>> + Unpin + Send>;
+
+/// How a failed attempt affects the rest of the chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureClass {
+    /// The model is overloaded or unreachable; try the next one
+    Retryable,
+    /// The request does not fit the model's context window
+    ContextLength,
+    /// Another model would fail the same way
+    Fatal,
+}
+
+impl FailureClass {
+    pub fn of(error: &FennecError) -> Self {
+        match error.domain_source::<ProviderError>() {
+            Some(ProviderError::TokenLimit { .. } | ProviderError::RequestTooLarge { .. }) => {
+                Self::ContextLength
+            }
+            Some(
+                ProviderError::ModelUnavailable { .. }
+                | ProviderError::ModelNotFound { .. }
+                | ProviderError::ServiceMaintenance { .. },
+            ) => Self::Retryable,
+            Some(error) if error.is_retryable() => Self::Retryable,
+            _ => Self::Fatal,
+        }
+    }
+}
+
+/// One model in a fallback chain
+#[derive(Clone)]
+pub struct FallbackTarget {
+    pub client: Arc<dyn ProviderClient>,
+    pub model: String,
+    /// Context window in tokens, when known; models no larger than one that
+    /// already overflowed are skipped
+    pub context_window: Option<u32>,
+}
+
+impl FallbackTarget {
+    pub fn new(client: Arc<dyn ProviderClient>, model: impl Into<String>) -> Self {
+        Self {
+            client,
+            model: model.into(),
+            context_window: None,
+        }
+    }
+
+    pub fn with_context_window(mut self, tokens: u32) -> Self {
+        self.context_window = Some(tokens);
+        self
+    }
+}
+
+/// Shrinks a request that no model in the chain could fit, e.g. by
+/// summarizing older messages
+#[async_trait]
+pub trait ContextReducer: Send + Sync {
+    /// A shorter version of `request`, or `None` when it cannot be reduced
+    async fn reduce(&self, request: &ProviderRequest) -> Option<ProviderRequest>;
+}
+
+/// Provider wrapper falling back along an ordered list of models. The
+/// response's `model` names the model that served the request.
+pub struct FallbackProviderClient {
+    targets: Vec<FallbackTarget>,
+    reducer: Option<Arc<dyn ContextReducer>>,
+}
+
+impl FallbackProviderClient {
+    /// Chain of `targets`, the preferred model first
+    pub fn new(targets: Vec<FallbackTarget>) -> Self {
+        Self {
+            targets,
+            reducer: None,
+        }
+    }
+
+    pub fn with_context_reducer(mut self, reducer: Arc<dyn ContextReducer>) -> Self {
+        self.reducer = Some(reducer);
+        self
+    }
+
+    pub fn targets(&self) -> &[FallbackTarget] {
+        &self.targets
+    }
+
+    /// Run `attempt` along the chain, reducing the request once if every
+    /// model rejected it as too long
+    async fn run<T, F, Fut>(
+        &self,
+        mut request: ProviderRequest,
+        attempt: F,
+    ) -> fennec_core::Result<(T, String)>
+    where
+        F: Fn(Arc<dyn ProviderClient>, ProviderRequest) -> Fut,
+        Fut: Future<Output = fennec_core::Result<T>>,
+    {
+        let mut reduced = false;
+        loop {
+            let error = match self.try_targets(&request, &attempt).await {
+                Ok(served) => return Ok(served),
+                Err(error) => error,
+            };
+
+            if reduced || FailureClass::of(&error) != FailureClass::ContextLength {
+                return Err(error);
+            }
+            let Some(reducer) = &self.reducer else {
+                return Err(error);
+            };
+            match reducer.reduce(&request).await {
+                Some(smaller) => {
+                    info!("Request exceeds every model's context window; retrying reduced");
+                    request = smaller;
+                    reduced = true;
+                }
+                None => return Err(error),
+            }
+        }
+    }
+
+    async fn try_targets<T, F, Fut>(
+        &self,
+        request: &ProviderRequest,
+        attempt: &F,
+    ) -> fennec_core::Result<(T, String)>
+    where
+        F: Fn(Arc<dyn ProviderClient>, ProviderRequest) -> Fut,
+        Fut: Future<Output = fennec_core::Result<T>>,
+    {
+        let mut last_error = None;
+        // Largest window known to be too small for this request
+        let mut overflowed_window: Option<u32> = None;
+
+        for target in &self.targets {
+            if let (Some(overflowed), Some(window)) = (overflowed_window, target.context_window) {
+                if window <= overflowed {
+                    debug!(
+                        "Skipping {}: its {} token window is too small",
+                        target.model, window
+                    );
+                    continue;
+                }
+            }
+
+            let mut request = request.clone();
+            request.model = target.model.clone();
+            let error = match attempt(target.client.clone(), request).await {
+                Ok(value) => return Ok((value, target.model.clone())),
+                Err(error) => error,
+            };
+
+            match FailureClass::of(&error) {
+                FailureClass::Fatal => return Err(error),
+                FailureClass::Retryable => {
+                    warn!("Model {} failed, falling back: {}", target.model, error);
+                }
+                FailureClass::ContextLength => {
+                    warn!("Request does not fit {}: {}", target.model, error);
+                    if let Some(window) = target.context_window {
+                        overflowed_window = overflowed_window.max(Some(window));
+                    }
+                }
+            }
+            last_error = Some(error);
+        }
+
+        Err(last_error.unwrap_or_else(|| {
+            ProviderError::ConfigurationMissing {
+                provider: "fallback".to_string(),
+            }
+            .into()
+        }))
+    }
+}
+
+#[async_trait]
+impl ProviderClient for FallbackProviderClient {
+    async fn complete(&self, request: ProviderRequest) -> fennec_core::Result<ProviderResponse> {
+        let (response, model) = self
+            .run(request, |client, request| async move {
+                client.complete(request).await
+            })
+            .await?;
+        Ok(ProviderResponse {
+            model: response.model.or(Some(model)),
+            ..response
+        })
+    }
+
+    async fn stream(&self, request: ProviderRequest) -> fennec_core::Result<TextStream> {
+        let (stream, model) = self
+            .run(request, |client, request| async move {
+                client.stream(request).await
+            })
+            .await?;
+        debug!("Streaming from {}", model);
+        Ok(stream)
+    }
+
+    /// Attachments are only sent when every model in the chain takes them,
+    /// as any of them may end up answering
+    fn attachment_support(&self) -> AttachmentSupport {
+        if self
+            .targets
+            .iter()
+            .all(|t| t.client.attachment_support() == AttachmentSupport::Inline)
+        {
+            AttachmentSupport::Inline
+        } else {
+            AttachmentSupport::Unsupported
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockProviderClient;
+    use fennec_core::provider::ProviderMessage;
+    use futures::StreamExt;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use uuid::Uuid;
+
+    /// Fails requests for the scripted models and echoes the rest, keeping
+    /// the order in which models were asked
+    #[derive(Default)]
+    struct ScriptedProvider {
+        failures: HashMap<String, fn(&ProviderRequest) -> Option<ProviderError>>,
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl ScriptedProvider {
+        fn failing(
+            mut self,
+            model: &str,
+            failure: fn(&ProviderRequest) -> Option<ProviderError>,
+        ) -> Self {
+            self.failures.insert(model.to_string(), failure);
+            self
+        }
+
+        fn calls(&self) -> Vec<String> {
+            self.calls.lock().unwrap().clone()
+        }
+
+        fn check(&self, request: &ProviderRequest) -> fennec_core::Result<()> {
+            self.calls.lock().unwrap().push(request.model.clone());
+            match self.failures.get(&request.model).and_then(|f| f(request)) {
+                Some(error) => Err(error.into()),
+                None => Ok(()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ProviderClient for ScriptedProvider {
+        async fn complete(
+            &self,
+            request: ProviderRequest,
+        ) -> fennec_core::Result<ProviderResponse> {
+            self.check(&request)?;
+            MockProviderClient::default().complete(request).await
+        }
+
+        async fn stream(&self, request: ProviderRequest) -> fennec_core::Result<TextStream> {
+            self.check(&request)?;
+            MockProviderClient::default().stream(request).await
+        }
+    }
+
+    fn unavailable(request: &ProviderRequest) -> Option<ProviderError> {
+        Some(ProviderError::ServiceUnavailable {
+            provider: "mock".to_string(),
+            reason: format!("{} overloaded", request.model),
+        })
+    }
+
+    fn rate_limited(_: &ProviderRequest) -> Option<ProviderError> {
+        Some(ProviderError::RateLimit {
+            provider: "mock".to_string(),
+            message: "slow down".to_string(),
+            retry_after: 1,
+            daily_limit: None,
+            current_usage: None,
+        })
+    }
+
+    fn unauthorized(_: &ProviderRequest) -> Option<ProviderError> {
+        Some(ProviderError::AuthenticationFailed {
+            provider: "mock".to_string(),
+            reason: "bad key".to_string(),
+        })
+    }
+
+    fn too_long(request: &ProviderRequest) -> Option<ProviderError> {
+        (request.messages.len() > 1).then(|| ProviderError::TokenLimit {
+            used: 9000,
+            limit: 8192,
+            suggestion: "Shorten the conversation".to_string(),
+        })
+    }
+
+    fn request(messages: &[&str]) -> ProviderRequest {
+        ProviderRequest {
+            id: Uuid::new_v4(),
+            messages: messages
+                .iter()
+                .map(|content| ProviderMessage {
+                    role: "user".to_string(),
+                    content: content.to_string(),
+                })
+                .collect(),
+            model: "primary".to_string(),
+            stream: false,
+            temperature: None,
+            max_tokens: None,
+        }
+    }
+
+    fn chain(
+        provider: &Arc<ScriptedProvider>,
+        models: &[(&str, Option<u32>)],
+    ) -> FallbackProviderClient {
+        FallbackProviderClient::new(
+            models
+                .iter()
+                .map(|(model, window)| {
+                    let target = FallbackTarget::new(provider.clone(), *model);
+                    match window {
+                        Some(tokens) => target.with_context_window(*tokens),
+                        None => target,
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_retryable_failures_walk_the_chain_in_order() {
+        let provider = Arc::new(
+            ScriptedProvider::default()
+                .failing("gpt-4o", unavailable)
+                .failing("gpt-4o-mini", rate_limited),
+        );
+        let client = chain(
+            &provider,
+            &[
+                ("gpt-4o", None),
+                ("gpt-4o-mini", None),
+                ("gpt-3.5-turbo", None),
+            ],
+        );
+
+        let response = client.complete(request(&["hello"])).await.unwrap();
+        assert_eq!(response.model.as_deref(), Some("gpt-3.5-turbo"));
+        assert!(response.content.contains("hello"));
+        assert_eq!(
+            provider.calls(),
+            vec!["gpt-4o", "gpt-4o-mini", "gpt-3.5-turbo"]
+        );
+
+        let mut stream = client.stream(request(&["streamed"])).await.unwrap();
+        assert!(stream.next().await.unwrap().is_ok());
+        assert_eq!(provider.calls().len(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_fatal_failure_stops_the_chain() {
+        let provider = Arc::new(
+            ScriptedProvider::default()
+                .failing("gpt-4o", unavailable)
+                .failing("gpt-4o-mini", unauthorized),
+        );
+        let client = chain(
+            &provider,
+            &[
+                ("gpt-4o", None),
+                ("gpt-4o-mini", None),
+                ("gpt-3.5-turbo", None),
+            ],
+        );
+
+        let error = client.complete(request(&["hello"])).await.unwrap_err();
+        assert!(error.to_string().contains("bad key"), "{}", error);
+        assert_eq!(provider.calls(), vec!["gpt-4o", "gpt-4o-mini"]);
+
+        // Exhausting the chain reports the last failure
+        let provider = Arc::new(ScriptedProvider::default().failing("gpt-4o", unavailable));
+        let client = chain(&provider, &[("gpt-4o", None)]);
+        let error = client.complete(request(&["hello"])).await.unwrap_err();
+        assert_eq!(FailureClass::of(&error), FailureClass::Retryable);
+    }
+
+    #[tokio::test]
+    async fn test_context_overflow_skips_to_a_larger_window() {
+        let provider = Arc::new(
+            ScriptedProvider::default()
+                .failing("small", too_long)
+                .failing("small-mini", too_long),
+        );
+        let client = chain(
+            &provider,
+            &[
+                ("small", Some(8_192)),
+                ("small-mini", Some(4_096)),
+                ("large", Some(128_000)),
+            ],
+        );
+
+        let response = client.complete(request(&["one", "two"])).await.unwrap();
+        assert_eq!(response.model.as_deref(), Some("large"));
+        assert_eq!(provider.calls(), vec!["small", "large"]);
+    }
+
+    struct KeepLastMessage;
+
+    #[async_trait]
+    impl ContextReducer for KeepLastMessage {
+        async fn reduce(&self, request: &ProviderRequest) -> Option<ProviderRequest> {
+            if request.messages.len() <= 1 {
+                return None;
+            }
+            let mut reduced = request.clone();
+            reduced.messages.drain(..request.messages.len() - 1);
+            Some(reduced)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reducer_retries_when_no_model_fits() {
+        let provider = Arc::new(ScriptedProvider::default().failing("small", too_long));
+        let client = chain(&provider, &[("small", Some(8_192))]);
+        let error = client.complete(request(&["one", "two"])).await.unwrap_err();
+        assert_eq!(FailureClass::of(&error), FailureClass::ContextLength);
+
+        let client = client.with_context_reducer(Arc::new(KeepLastMessage));
+        let response = client.complete(request(&["one", "two"])).await.unwrap();
+        assert_eq!(response.model.as_deref(), Some("small"));
+        assert!(response.content.contains("two"));
+        assert_eq!(provider.calls(), vec!["small", "small", "small"]);
+    }
+}
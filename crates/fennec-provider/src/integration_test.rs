@@ -25,10 +25,17 @@ async fn test_openai_integration() {
 
     // Create provider config
     let provider_config = ProviderConfig {
+        provider: "openai".to_string(),
         openai_api_key: Some(api_key),
+        anthropic_api_key: None,
+        openrouter_api_key: None,
         default_model: "gpt-3.5-turbo".to_string(),
         base_url: None,
         timeout_seconds: 30,
+        connect_timeout_seconds: 10,
+        stream_idle_timeout_seconds: 60,
+        fallback_models: Vec::new(),
+        routes: Default::default(),
     };
 
     // Test 1: Create client using factory
@@ -46,6 +53,8 @@ async fn test_openai_integration() {
         }],
         model: "gpt-3.5-turbo".to_string(),
         stream: false,
+        temperature: None,
+        max_tokens: None,
     };
 
     let response = client
@@ -67,6 +76,8 @@ async fn test_openai_integration() {
         }],
         model: "gpt-3.5-turbo".to_string(),
         stream: true,
+        temperature: None,
+        max_tokens: None,
     };
 
     let mut stream = client
@@ -115,10 +126,17 @@ async fn test_openai_integration() {
 #[ignore]
 async fn test_invalid_api_key() {
     let provider_config = ProviderConfig {
+        provider: "openai".to_string(),
         openai_api_key: Some("invalid-key".to_string()),
+        anthropic_api_key: None,
+        openrouter_api_key: None,
         default_model: "gpt-3.5-turbo".to_string(),
         base_url: None,
         timeout_seconds: 30,
+        connect_timeout_seconds: 10,
+        stream_idle_timeout_seconds: 60,
+        fallback_models: Vec::new(),
+        routes: Default::default(),
     };
 
     let client = ProviderClientFactory::create_client(&provider_config)
@@ -132,6 +150,8 @@ async fn test_invalid_api_key() {
         }],
         model: "gpt-3.5-turbo".to_string(),
         stream: false,
+        temperature: None,
+        max_tokens: None,
     };
 
     let result = client.complete(request).await;
@@ -158,7 +178,9 @@ async fn test_openai_client_direct() {
     let config = OpenAIConfig {
         api_key,
         base_url: "https://api.openai.com/v1".to_string(),
-        timeout: std::time::Duration::from_secs(30),
+        request_timeout: std::time::Duration::from_secs(30),
+        connect_timeout: Some(std::time::Duration::from_secs(10)),
+        stream_idle_timeout: Some(std::time::Duration::from_secs(60)),
         max_retries: 3,
         initial_retry_delay: std::time::Duration::from_millis(500),
         max_retry_delay: std::time::Duration::from_secs(60),
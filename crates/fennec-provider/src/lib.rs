@@ -1,19 +1,41 @@
+pub mod cache;
 pub mod client;
+pub mod embeddings;
 pub mod error;
+pub mod fallback;
+pub mod middleware;
 pub mod mock;
 pub mod models;
 pub mod openai;
+pub mod router;
 pub mod streaming;
+pub mod structured;
+pub mod tools;
+pub mod usage;
 
 #[cfg(test)]
 mod integration_test;
+#[cfg(test)]
+mod test_server;
 
 // Re-export commonly used types
-pub use client::ProviderClientFactory;
+pub use cache::CachingProviderClient;
+pub use client::{ModelCheck, ProviderClientFactory};
+pub use embeddings::{EmbeddingClient, HashEmbeddingClient, OpenAIEmbeddingClient};
 pub use error::{ProviderError, Result};
+pub use fallback::{ContextReducer, FailureClass, FallbackProviderClient, FallbackTarget};
 pub use fennec_core::provider::ProviderClient;
-pub use mock::MockProviderClient;
+pub use middleware::{LoggingMiddleware, ProviderMiddleware};
+pub use mock::{MockProviderBuilder, MockProviderClient, MockReply};
 pub use openai::{OpenAIClient, OpenAIConfig};
+pub use router::{ProviderRouter, Route, RoutedClient};
+pub use streaming::{FinishReason, StreamedResponse};
+pub use structured::StructuredResponse;
+pub use tools::{
+    StreamAccumulator, ToolAwareResponse, ToolDefinition, ToolHandler, ToolInvocation,
+    DEFAULT_MAX_TOOL_ROUNDS,
+};
+pub use usage::{BudgetStatus, ModelUsage, UsageReport, UsageTracker, UsageTrackingClient};
 
 #[cfg(test)]
 mod integration_tests {
@@ -30,10 +52,17 @@ mod integration_tests {
         }
 
         let config = ProviderConfig {
+            provider: "openai".to_string(),
             openai_api_key: std::env::var("OPENAI_API_KEY").ok(),
+            anthropic_api_key: None,
+            openrouter_api_key: None,
             default_model: "gpt-3.5-turbo".to_string(),
             base_url: None,
             timeout_seconds: 30,
+            connect_timeout_seconds: 10,
+            stream_idle_timeout_seconds: 60,
+            fallback_models: Vec::new(),
+            routes: Default::default(),
         };
 
         let validation_result = ProviderClientFactory::validate_config(&config);
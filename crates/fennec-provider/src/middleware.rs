@@ -0,0 +1,343 @@
+//! Hooks observing the HTTP exchanges of a provider client.
+//!
+//! [`crate::OpenAIClient`] reports every request it sends and every response
+//! it receives to its [`ProviderMiddleware`], tagging both with the same
+//! [`CorrelationId`] so a reply can be matched with the prompt that produced
+//! it. Retries of a request reuse its correlation id.
+//!
+//! [`LoggingMiddleware`] logs the exchanges at debug level under the
+//! `fennec_provider::wire` target. Credentials in headers are always
+//! redacted; payloads are passed through the telemetry sanitizer and long
+//! strings are truncated unless full payload logging is explicitly enabled.
+
+use fennec_core::config::ProviderLoggingConfig;
+use fennec_telemetry::config::PrivacyConfig;
+use fennec_telemetry::sanitization::DataSanitizer;
+use fennec_telemetry::CorrelationId;
+use reqwest::header::HeaderMap;
+use serde_json::Value;
+use std::time::Duration;
+
+use crate::error::{ProviderError, Result};
+
+/// Target of the log lines written by [`LoggingMiddleware`]
+pub const WIRE_LOG_TARGET: &str = "fennec_provider::wire";
+
+/// Headers carrying credentials, besides any whose name mentions a key,
+/// token or secret
+const CREDENTIAL_HEADERS: &[&str] = &["authorization", "proxy-authorization", "cookie"];
+
+/// A request about to be sent
+#[derive(Debug)]
+pub struct WireRequest<'a> {
+    pub method: &'a str,
+    pub url: &'a str,
+    pub headers: &'a HeaderMap,
+    pub body: &'a Value,
+}
+
+/// What came back for a request
+#[derive(Debug)]
+pub struct WireResponse<'a> {
+    /// `None` when no response arrived
+    pub status: Option<u16>,
+    pub elapsed: Duration,
+    pub body: WireBody<'a>,
+}
+
+#[derive(Debug)]
+pub enum WireBody<'a> {
+    Text(&'a str),
+    /// A streamed body, which is consumed by the caller rather than logged
+    Stream,
+    /// The request failed before a response was received
+    Failed(&'a ProviderError),
+}
+
+/// Observer of the requests a provider client sends and the responses it
+/// receives
+pub trait ProviderMiddleware: Send + Sync {
+    fn on_request(&self, correlation_id: &CorrelationId, request: &WireRequest<'_>);
+
+    fn on_response(&self, correlation_id: &CorrelationId, response: &WireResponse<'_>);
+}
+
+/// Middleware logging sanitized, truncated exchanges through telemetry
+pub struct LoggingMiddleware {
+    sanitizer: DataSanitizer,
+    max_content_chars: usize,
+    unsafe_log_full_payloads: bool,
+}
+
+impl LoggingMiddleware {
+    pub fn new(config: &ProviderLoggingConfig) -> Result<Self> {
+        // Payload field names such as `max_tokens` and `prompt_tokens` would
+        // match the default `token` field, so only unambiguous names are
+        // redacted wholesale; the value patterns still apply to all text.
+        let privacy = PrivacyConfig {
+            redacted_fields: ["password", "api_key", "secret", "authorization"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            ..Default::default()
+        };
+        Self::with_privacy(config, &privacy)
+    }
+
+    pub fn with_privacy(config: &ProviderLoggingConfig, privacy: &PrivacyConfig) -> Result<Self> {
+        let sanitizer =
+            DataSanitizer::new(privacy).map_err(|e| ProviderError::ConfigurationInvalid {
+                provider: "logging".to_string(),
+                setting: "redaction_patterns".to_string(),
+                issue: e.to_string(),
+            })?;
+        Ok(Self {
+            sanitizer,
+            max_content_chars: config.max_content_chars,
+            unsafe_log_full_payloads: config.unsafe_log_full_payloads,
+        })
+    }
+
+    fn redact_headers(&self, headers: &HeaderMap) -> String {
+        let mut rendered: Vec<String> = headers
+            .iter()
+            .map(|(name, value)| {
+                let name = name.as_str();
+                let value = if is_credential_header(name) {
+                    "[REDACTED]".to_string()
+                } else {
+                    self.sanitizer
+                        .sanitize_text(&String::from_utf8_lossy(value.as_bytes()))
+                };
+                format!("{}: {}", name, value)
+            })
+            .collect();
+        rendered.sort();
+        rendered.join(", ")
+    }
+
+    fn render_payload(&self, payload: Value) -> String {
+        if self.unsafe_log_full_payloads {
+            return payload.to_string();
+        }
+        let mut payload = self.sanitizer.sanitize_json(payload);
+        truncate_strings(&mut payload, self.max_content_chars);
+        payload.to_string()
+    }
+
+    fn render_text(&self, text: &str) -> String {
+        match serde_json::from_str::<Value>(text) {
+            Ok(payload) => self.render_payload(payload),
+            Err(_) => self.render_payload(Value::String(text.to_string())),
+        }
+    }
+}
+
+impl ProviderMiddleware for LoggingMiddleware {
+    fn on_request(&self, correlation_id: &CorrelationId, request: &WireRequest<'_>) {
+        fennec_telemetry::debug!(
+            target: WIRE_LOG_TARGET,
+            correlation_id = %correlation_id,
+            "provider request {} {} headers=[{}] body={}",
+            request.method,
+            request.url,
+            self.redact_headers(request.headers),
+            self.render_payload(request.body.clone())
+        );
+    }
+
+    fn on_response(&self, correlation_id: &CorrelationId, response: &WireResponse<'_>) {
+        let status = response
+            .status
+            .map(|status| status.to_string())
+            .unwrap_or_else(|| "none".to_string());
+        let body = match response.body {
+            WireBody::Text(text) => self.render_text(text),
+            WireBody::Stream => "<stream>".to_string(),
+            WireBody::Failed(error) => {
+                format!("<failed: {}>", self.render_text(&error.to_string()))
+            }
+        };
+        fennec_telemetry::debug!(
+            target: WIRE_LOG_TARGET,
+            correlation_id = %correlation_id,
+            "provider response status={} elapsed_ms={} body={}",
+            status,
+            response.elapsed.as_millis(),
+            body
+        );
+    }
+}
+
+fn is_credential_header(name: &str) -> bool {
+    CREDENTIAL_HEADERS.contains(&name)
+        || ["key", "token", "secret"]
+            .iter()
+            .any(|word| name.contains(word))
+}
+
+/// Cut every string in `value` down to `max_chars` characters
+fn truncate_strings(value: &mut Value, max_chars: usize) {
+    match value {
+        Value::String(text) => {
+            let total = text.chars().count();
+            if total > max_chars {
+                let kept: String = text.chars().take(max_chars).collect();
+                *text = format!("{}...[{} more chars]", kept, total - max_chars);
+            }
+        }
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| truncate_strings(item, max_chars)),
+        Value::Object(fields) => fields
+            .values_mut()
+            .for_each(|field| truncate_strings(field, max_chars)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ChatCompletionRequest, ChatMessage};
+    use crate::test_server::{MockResponse, MockServer};
+    use crate::{OpenAIClient, OpenAIConfig};
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    /// Log output written by the subscriber installed in a test
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+    impl CapturedLogs {
+        fn contents(&self) -> String {
+            String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+        }
+    }
+
+    impl Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Send one chat completion through a logging client, returning the log
+    async fn logged_exchange(config: ProviderLoggingConfig, prompt: &str) -> String {
+        let server = MockServer::start(vec![MockResponse::json(serde_json::json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1,
+            "model": "gpt-4o",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "Done. The token=abc123xyz is rotated."},
+                "finish_reason": "stop"
+            }]
+        }))])
+        .await;
+        let client = OpenAIClient::new(OpenAIConfig {
+            api_key: "sk-live-secret-key".to_string(),
+            base_url: server.base_url.clone(),
+            max_retries: 0,
+            ..Default::default()
+        })
+        .unwrap()
+        .with_middleware(Arc::new(LoggingMiddleware::new(&config).unwrap()));
+
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        client
+            .chat_completion(ChatCompletionRequest {
+                model: "gpt-4o".to_string(),
+                messages: vec![ChatMessage::new("user", prompt)],
+                stream: None,
+                max_tokens: Some(64),
+                temperature: None,
+                top_p: None,
+                frequency_penalty: None,
+                presence_penalty: None,
+                stop: None,
+                user: None,
+                tools: None,
+                response_format: None,
+            })
+            .await
+            .unwrap();
+        logs.contents()
+    }
+
+    fn correlation_ids(logs: &str) -> Vec<String> {
+        logs.lines()
+            .filter(|line| line.contains(WIRE_LOG_TARGET))
+            .filter_map(|line| {
+                let start = line.find("correlation_id=")? + "correlation_id=".len();
+                Some(line[start..].split_whitespace().next()?.to_string())
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_logged_exchange_is_redacted_and_truncated() {
+        let prompt = format!(
+            "Deploy with api_key=sk-prompt-9876 then {}",
+            "x".repeat(200)
+        );
+        let logs = logged_exchange(
+            ProviderLoggingConfig {
+                enabled: true,
+                max_content_chars: 60,
+                unsafe_log_full_payloads: false,
+            },
+            &prompt,
+        )
+        .await;
+
+        assert!(logs.contains("provider request POST"), "{}", logs);
+        assert!(logs.contains("provider response status=200"), "{}", logs);
+        assert!(logs.contains("authorization: [REDACTED]"), "{}", logs);
+        assert!(!logs.contains("sk-live-secret-key"), "{}", logs);
+        assert!(!logs.contains("sk-prompt-9876"), "{}", logs);
+        assert!(!logs.contains("abc123xyz"), "{}", logs);
+        assert!(logs.contains("more chars]"), "{}", logs);
+        assert!(!logs.contains(&"x".repeat(100)), "{}", logs);
+        assert!(logs.contains("\"max_tokens\":64"), "{}", logs);
+
+        let ids = correlation_ids(&logs);
+        assert_eq!(ids.len(), 2, "{}", logs);
+        assert_eq!(ids[0], ids[1]);
+    }
+
+    #[tokio::test]
+    async fn test_unsafe_flag_logs_full_payload_but_not_api_key() {
+        let prompt = format!(
+            "Deploy with api_key=sk-prompt-9876 then {}",
+            "x".repeat(200)
+        );
+        let logs = logged_exchange(
+            ProviderLoggingConfig {
+                enabled: true,
+                max_content_chars: 60,
+                unsafe_log_full_payloads: true,
+            },
+            &prompt,
+        )
+        .await;
+
+        assert!(logs.contains(&prompt), "{}", logs);
+        assert!(logs.contains("token=abc123xyz"), "{}", logs);
+        assert!(!logs.contains("more chars]"), "{}", logs);
+        assert!(!logs.contains("sk-live-secret-key"), "{}", logs);
+    }
+}
@@ -4,25 +4,224 @@ use fennec_core::provider::{
 };
 use fennec_core::Result;
 use futures::stream;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
 use uuid::Uuid;
 
-/// Simple fallback provider that echoes the last user message.
-/// Useful for development when no external provider credentials are configured.
+use crate::error::ProviderError;
+use crate::tools::ToolInvocation;
+
+/// A scripted reply of [`MockProviderClient`]
+#[derive(Debug)]
+pub enum MockReply {
+    Text(String),
+    /// The provider trait has no tool call channel, so calls are returned
+    /// as their JSON encoding: `{"tool_calls": [{"id", "name", "arguments"}]}`
+    ToolCalls(Vec<ToolInvocation>),
+    Error(ProviderError),
+    /// Chunks streamed with `interval` before each; joined when completed
+    Stream {
+        chunks: Vec<String>,
+        interval: Duration,
+    },
+    /// Streams `chunks`, then fails with `error` as if the connection
+    /// dropped mid-reply; completing fails with `error` straight away
+    Disconnect {
+        chunks: Vec<String>,
+        error: ProviderError,
+    },
+}
+
+#[derive(Debug)]
+struct ScriptedReply {
+    reply: MockReply,
+    latency: Duration,
+    usage: Option<Usage>,
+}
+
+/// Provider for development and tests.
+///
+/// Without a script it echoes the last user message, which is useful when
+/// no external provider credentials are configured. Built through
+/// [`MockProviderClient::builder`] it serves a sequence of scripted replies
+/// instead. Every request is recorded either way.
+#[derive(Debug, Default)]
+pub struct MockProviderClient {
+    script: Option<Mutex<VecDeque<ScriptedReply>>>,
+    requests: Mutex<Vec<ProviderRequest>>,
+    expect_all_consumed: bool,
+}
+
+impl MockProviderClient {
+    pub fn builder() -> MockProviderBuilder {
+        MockProviderBuilder::default()
+    }
+
+    /// Requests received so far, oldest first
+    pub fn requests(&self) -> Vec<ProviderRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Scripted replies not yet served
+    pub fn remaining(&self) -> usize {
+        self.script
+            .as_ref()
+            .map(|script| script.lock().unwrap().len())
+            .unwrap_or(0)
+    }
+
+    /// Record `request` and take the next scripted reply, waiting out its
+    /// latency. `None` means the client is not scripted.
+    async fn next_reply(&self, request: &ProviderRequest) -> Option<Result<ScriptedReply>> {
+        self.requests.lock().unwrap().push(request.clone());
+        let script = self.script.as_ref()?;
+        let next = script.lock().unwrap().pop_front();
+        let Some(scripted) = next else {
+            return Some(Err(ProviderError::Generic {
+                message: format!(
+                    "MockProviderClient script exhausted after {} requests",
+                    self.requests.lock().unwrap().len() - 1
+                ),
+                provider: "mock".to_string(),
+                context: None,
+            }
+            .into()));
+        };
+        if !scripted.latency.is_zero() {
+            tokio::time::sleep(scripted.latency).await;
+        }
+        Some(Ok(scripted))
+    }
+}
+
+impl Drop for MockProviderClient {
+    fn drop(&mut self) {
+        if self.expect_all_consumed && !std::thread::panicking() {
+            let remaining = self.remaining();
+            assert!(
+                remaining == 0,
+                "MockProviderClient dropped with {} unconsumed scripted replies",
+                remaining
+            );
+        }
+    }
+}
+
+/// Builds a [`MockProviderClient`] serving replies in the order they are
+/// added
 #[derive(Debug, Default)]
-pub struct MockProviderClient;
+pub struct MockProviderBuilder {
+    replies: VecDeque<ScriptedReply>,
+    expect_all_consumed: bool,
+}
+
+impl MockProviderBuilder {
+    pub fn reply(mut self, reply: MockReply) -> Self {
+        self.replies.push_back(ScriptedReply {
+            reply,
+            latency: Duration::ZERO,
+            usage: None,
+        });
+        self
+    }
+
+    pub fn text(self, text: impl Into<String>) -> Self {
+        self.reply(MockReply::Text(text.into()))
+    }
+
+    pub fn tool_calls(self, calls: Vec<ToolInvocation>) -> Self {
+        self.reply(MockReply::ToolCalls(calls))
+    }
+
+    pub fn error(self, error: ProviderError) -> Self {
+        self.reply(MockReply::Error(error))
+    }
+
+    pub fn stream<I, S>(self, chunks: I, interval: Duration) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.reply(MockReply::Stream {
+            chunks: chunks.into_iter().map(Into::into).collect(),
+            interval,
+        })
+    }
+
+    pub fn disconnect<I, S>(self, chunks: I, error: ProviderError) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.reply(MockReply::Disconnect {
+            chunks: chunks.into_iter().map(Into::into).collect(),
+            error,
+        })
+    }
+
+    /// Delay the most recently added reply by `latency`
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        if let Some(last) = self.replies.back_mut() {
+            last.latency = latency;
+        }
+        self
+    }
+
+    /// Report `usage` for the most recently added reply instead of the
+    /// default estimate
+    pub fn with_usage(mut self, usage: Usage) -> Self {
+        if let Some(last) = self.replies.back_mut() {
+            last.usage = Some(usage);
+        }
+        self
+    }
+
+    /// Panic when the client is dropped before every reply was served
+    pub fn expect_all_consumed(mut self) -> Self {
+        self.expect_all_consumed = true;
+        self
+    }
+
+    pub fn build(self) -> MockProviderClient {
+        MockProviderClient {
+            script: Some(Mutex::new(self.replies)),
+            requests: Mutex::new(Vec::new()),
+            expect_all_consumed: self.expect_all_consumed,
+        }
+    }
+}
 
 #[async_trait]
 impl ProviderClient for MockProviderClient {
     async fn complete(&self, request: ProviderRequest) -> Result<ProviderResponse> {
-        let reply = generate_reply(&request.messages)?;
+        let (content, usage) = match self.next_reply(&request).await {
+            None => (generate_reply(&request.messages)?, None),
+            Some(scripted) => {
+                let scripted = scripted?;
+                let content = match scripted.reply {
+                    MockReply::Text(text) => text,
+                    MockReply::ToolCalls(calls) => {
+                        serde_json::json!({ "tool_calls": calls }).to_string()
+                    }
+                    MockReply::Error(error) | MockReply::Disconnect { error, .. } => {
+                        return Err(error.into())
+                    }
+                    MockReply::Stream { chunks, .. } => chunks.concat(),
+                };
+                (content, scripted.usage)
+            }
+        };
+
         Ok(ProviderResponse {
             id: Uuid::new_v4(),
-            content: reply,
-            usage: Some(Usage {
+            content,
+            usage: usage.or(Some(Usage {
                 prompt_tokens: request.messages.len() as u32 * 10,
                 completion_tokens: 12,
                 total_tokens: request.messages.len() as u32 * 10 + 12,
-            }),
+            })),
+            model: Some(request.model),
         })
     }
 
@@ -30,16 +229,51 @@ impl ProviderClient for MockProviderClient {
         &self,
         request: ProviderRequest,
     ) -> Result<Box<dyn futures::Stream<Item = Result<String>> + Unpin + Send>> {
-        let reply = generate_reply(&request.messages)?;
-        // Simulate incremental output.
-        let parts: Vec<Result<String>> = reply
-            .split_whitespace()
-            .map(|word| Ok(format!("{} ", word)))
+        let (chunks, interval, failure) = match self.next_reply(&request).await {
+            None => (
+                words(&generate_reply(&request.messages)?),
+                Duration::ZERO,
+                None,
+            ),
+            Some(scripted) => match scripted?.reply {
+                MockReply::Text(text) => (words(&text), Duration::ZERO, None),
+                MockReply::ToolCalls(calls) => (
+                    vec![serde_json::json!({ "tool_calls": calls }).to_string()],
+                    Duration::ZERO,
+                    None,
+                ),
+                MockReply::Error(error) => return Err(error.into()),
+                MockReply::Stream { chunks, interval } => (chunks, interval, None),
+                MockReply::Disconnect { chunks, error } => (chunks, Duration::ZERO, Some(error)),
+            },
+        };
+
+        // Simulate incremental output, ending in the failure if any
+        let parts: Vec<Result<String>> = chunks
+            .into_iter()
+            .map(Ok)
+            .chain(failure.map(|error| Err(error.into())))
             .collect();
-        Ok(Box::new(stream::iter(parts)))
+        if interval.is_zero() {
+            return Ok(Box::new(stream::iter(parts)));
+        }
+        Ok(Box::new(Box::pin(stream::unfold(
+            parts.into_iter(),
+            move |mut parts| async move {
+                let part = parts.next()?;
+                tokio::time::sleep(interval).await;
+                Some((part, parts))
+            },
+        ))))
     }
 }
 
+fn words(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|word| format!("{} ", word))
+        .collect()
+}
+
 fn generate_reply(messages: &[ProviderMessage]) -> Result<String> {
     let fallback = "I'm running without a configured provider. Set OPENAI_API_KEY or another provider to get live responses.".to_string();
     let Some(last) = messages.iter().rev().find(|m| m.role == "user") else {
@@ -51,3 +285,137 @@ fn generate_reply(messages: &[ProviderMessage]) -> Result<String> {
         last.content.trim()
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use std::time::Instant;
+
+    fn request(content: &str) -> ProviderRequest {
+        ProviderRequest {
+            id: Uuid::new_v4(),
+            messages: vec![ProviderMessage {
+                role: "user".to_string(),
+                content: content.to_string(),
+            }],
+            model: "mock".to_string(),
+            stream: false,
+            temperature: Some(0.2),
+            max_tokens: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_script_is_served_in_order_and_requests_recorded() {
+        let mock = MockProviderClient::builder()
+            .text("first")
+            .with_usage(Usage {
+                prompt_tokens: 1,
+                completion_tokens: 2,
+                total_tokens: 3,
+            })
+            .tool_calls(vec![ToolInvocation {
+                id: "call_1".to_string(),
+                name: "search".to_string(),
+                arguments: serde_json::json!({"query": "main"}),
+            }])
+            .error(ProviderError::ModelNotFound {
+                model: "mock".to_string(),
+            })
+            .build();
+
+        let first = mock.complete(request("one")).await.unwrap();
+        assert_eq!(first.content, "first");
+        assert_eq!(first.usage.unwrap().total_tokens, 3);
+
+        let calls = mock.complete(request("two")).await.unwrap();
+        let calls: serde_json::Value = serde_json::from_str(&calls.content).unwrap();
+        assert_eq!(calls["tool_calls"][0]["name"], "search");
+        assert_eq!(calls["tool_calls"][0]["arguments"]["query"], "main");
+
+        let error = mock.complete(request("three")).await.unwrap_err();
+        assert!(error.to_string().contains("not found"), "{}", error);
+
+        let exhausted = mock.complete(request("four")).await.unwrap_err();
+        assert!(exhausted.to_string().contains("script exhausted after 3"));
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests[1].messages[0].content, "two");
+        assert_eq!(requests[1].temperature, Some(0.2));
+    }
+
+    #[tokio::test]
+    async fn test_latency_and_stream_timing() {
+        let mock = MockProviderClient::builder()
+            .text("slow")
+            .with_latency(Duration::from_millis(40))
+            .stream(["a", "b", "c"], Duration::from_millis(15))
+            .build();
+
+        let started = Instant::now();
+        mock.complete(request("one")).await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(40));
+
+        let started = Instant::now();
+        let chunks: Vec<String> = mock
+            .stream(request("two"))
+            .await
+            .unwrap()
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        assert_eq!(chunks, vec!["a", "b", "c"]);
+        assert!(started.elapsed() >= Duration::from_millis(45));
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_fails_after_the_streamed_chunks() {
+        let mock = MockProviderClient::builder()
+            .disconnect(
+                ["partial ", "reply"],
+                ProviderError::StreamError {
+                    operation: "chat".to_string(),
+                    reason: "connection reset".to_string(),
+                },
+            )
+            .disconnect(
+                ["never seen"],
+                ProviderError::StreamError {
+                    operation: "chat".to_string(),
+                    reason: "connection reset".to_string(),
+                },
+            )
+            .build();
+
+        let parts: Vec<Result<String>> = mock.stream(request("one")).await.unwrap().collect().await;
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0].as_ref().unwrap(), "partial ");
+        assert_eq!(parts[1].as_ref().unwrap(), "reply");
+        let error = parts[2].as_ref().unwrap_err();
+        assert!(error.to_string().contains("connection reset"), "{}", error);
+
+        let error = mock.complete(request("two")).await.unwrap_err();
+        assert!(error.to_string().contains("connection reset"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_unscripted_client_echoes() {
+        let mock = MockProviderClient::default();
+        let response = mock.complete(request("hello")).await.unwrap();
+        assert_eq!(response.content, "(offline mode) I received: \"hello\"");
+        assert_eq!(mock.requests().len(), 1);
+        assert_eq!(mock.remaining(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "1 unconsumed scripted replies")]
+    fn test_unconsumed_script_panics_on_drop() {
+        let mock = MockProviderClient::builder()
+            .text("never requested")
+            .expect_all_consumed()
+            .build();
+        drop(mock);
+    }
+}
@@ -1,22 +1,35 @@
 use crate::error::{ProviderError, Result};
+use crate::middleware::{ProviderMiddleware, WireBody, WireRequest, WireResponse};
 use crate::models::*;
-use crate::streaming::SseStream;
+use crate::streaming::{self, SseStream, StreamedResponse};
+use crate::structured::{self, parse_structured, supports_json_schema, StructuredResponse};
+use crate::tools::{StreamAccumulator, ToolAwareResponse, ToolDefinition, ToolHandler};
 use fennec_core::config::ProviderConfig;
 use fennec_core::provider::{ProviderClient, ProviderRequest, ProviderResponse};
+use fennec_telemetry::{metrics, spans, CorrelationId};
 use futures::{Stream, StreamExt};
 use reqwest::{header, Client, Response};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::Semaphore;
 use tokio::time::{sleep, timeout};
-use tracing::{debug, error, info, instrument, warn};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, instrument, warn, Instrument};
 
 /// OpenAI API client configuration
 #[derive(Debug, Clone)]
 pub struct OpenAIConfig {
     pub api_key: String,
     pub base_url: String,
-    pub timeout: Duration,
+    /// Limit on a whole non-streaming request
+    pub request_timeout: Duration,
+    /// Limit on establishing a connection; `None` waits indefinitely
+    pub connect_timeout: Option<Duration>,
+    /// Limit on the gap between pieces of a streamed reply; `None` lets a
+    /// stalled stream hang
+    pub stream_idle_timeout: Option<Duration>,
     pub max_retries: u32,
     pub initial_retry_delay: Duration,
     pub max_retry_delay: Duration,
@@ -28,7 +41,9 @@ impl Default for OpenAIConfig {
         Self {
             api_key: String::new(),
             base_url: "https://api.openai.com/v1".to_string(),
-            timeout: Duration::from_secs(30),
+            request_timeout: Duration::from_secs(30),
+            connect_timeout: Some(Duration::from_secs(10)),
+            stream_idle_timeout: Some(Duration::from_secs(60)),
             max_retries: 3,
             initial_retry_delay: Duration::from_millis(500),
             max_retry_delay: Duration::from_secs(60),
@@ -45,17 +60,33 @@ impl From<&ProviderConfig> for OpenAIConfig {
                 .base_url
                 .clone()
                 .unwrap_or_else(|| "https://api.openai.com/v1".to_string()),
-            timeout: Duration::from_secs(config.timeout_seconds),
+            request_timeout: Duration::from_secs(config.timeout_seconds),
+            connect_timeout: (config.connect_timeout_seconds > 0)
+                .then(|| Duration::from_secs(config.connect_timeout_seconds)),
+            stream_idle_timeout: (config.stream_idle_timeout_seconds > 0)
+                .then(|| Duration::from_secs(config.stream_idle_timeout_seconds)),
             ..Default::default()
         }
     }
 }
 
+/// Limit applied while waiting for response headers
+#[derive(Debug, Clone, Copy)]
+enum Wait {
+    /// The request timeout, which also bounds reading the body
+    Request,
+    /// The stream idle timeout, as no data has arrived yet
+    StreamIdle,
+}
+
 /// OpenAI API client
 pub struct OpenAIClient {
     client: Client,
     config: OpenAIConfig,
     semaphore: Arc<Semaphore>,
+    /// Headers sent with every request, as shown to middleware
+    headers: header::HeaderMap,
+    middleware: Vec<Arc<dyn ProviderMiddleware>>,
 }
 
 impl OpenAIClient {
@@ -86,9 +117,13 @@ impl OpenAIClient {
             header::HeaderValue::from_static("fennec/0.1.0"),
         );
 
-        let client = Client::builder()
-            .timeout(config.timeout)
-            .default_headers(headers)
+        // No overall timeout on the client: it would cut off long streams.
+        // Each request applies the limit that fits it instead.
+        let mut builder = Client::builder().default_headers(headers.clone());
+        if let Some(connect_timeout) = config.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        let client = builder
             .build()
             .map_err(|e| ProviderError::ConfigurationInvalid {
                 provider: "openai".to_string(),
@@ -102,6 +137,8 @@ impl OpenAIClient {
             client,
             config,
             semaphore,
+            headers,
+            middleware: Vec::new(),
         })
     }
 
@@ -109,11 +146,169 @@ impl OpenAIClient {
         Self::new(config.into())
     }
 
-    #[instrument(skip(self, request), fields(model = %request.model))]
+    /// Report chat completion exchanges to `middleware`
+    pub fn with_middleware(mut self, middleware: Arc<dyn ProviderMiddleware>) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+
     pub async fn chat_completion(
         &self,
         request: ChatCompletionRequest,
     ) -> Result<ChatCompletionResponse> {
+        let span = spans::provider_span("openai", &request.model);
+        async move {
+            let _permit = self
+                .semaphore
+                .acquire()
+                .await
+                .map_err(|e| ProviderError::Generic {
+                    message: format!("Failed to acquire semaphore: {}", e),
+                    provider: "openai".to_string(),
+                    context: None,
+                })?;
+
+            let url = format!("{}/chat/completions", self.config.base_url);
+            debug!("Making chat completion request to: {}", url);
+            let correlation_id = spans::correlation_id();
+            let requested = Instant::now();
+
+            let result: Result<ChatCompletionResponse> = self
+                .retry_with_backoff(|| async {
+                    let (response, started) = self
+                        .send_observed(
+                            &correlation_id,
+                            &url,
+                            &request,
+                            "chat_completion",
+                            Wait::Request,
+                        )
+                        .await?;
+                    let status = response.status().as_u16();
+                    let remaining = self
+                        .config
+                        .request_timeout
+                        .saturating_sub(started.elapsed());
+                    let response_text = timeout(remaining, response.text())
+                        .await
+                        .map_err(|_| self.request_timed_out("chat_completion"))?
+                        .map_err(|e| ProviderError::Http {
+                            operation: "read_response_text".to_string(),
+                            source: e,
+                        })?;
+                    self.observe_response(
+                        &correlation_id,
+                        Some(status),
+                        started,
+                        WireBody::Text(&response_text),
+                    );
+
+                    self.parse_response_text(status, response_text)
+                })
+                .await;
+
+            metrics::record_provider_request(
+                "openai",
+                &request.model,
+                result.is_ok(),
+                requested.elapsed(),
+            );
+            if let Some(usage) = result.as_ref().ok().and_then(|r| r.usage.as_ref()) {
+                metrics::record_token_usage(
+                    "openai",
+                    &request.model,
+                    u64::from(usage.prompt_tokens),
+                    u64::from(usage.completion_tokens),
+                );
+            }
+            result
+        }
+        .instrument(span)
+        .await
+    }
+
+    pub async fn chat_completion_stream(
+        &self,
+        mut request: ChatCompletionRequest,
+    ) -> Result<impl Stream<Item = Result<ChatCompletionChunk>>> {
+        let span = spans::provider_span("openai", &request.model);
+        async move {
+            let _permit = self
+                .semaphore
+                .acquire()
+                .await
+                .map_err(|e| ProviderError::Generic {
+                    message: format!("Failed to acquire semaphore: {}", e),
+                    provider: "openai".to_string(),
+                    context: None,
+                })?;
+
+            request.stream = Some(true);
+            let url = format!("{}/chat/completions", self.config.base_url);
+            debug!("Making streaming chat completion request to: {}", url);
+            let correlation_id = spans::correlation_id();
+
+            let (response, started) = self
+                .send_observed(
+                    &correlation_id,
+                    &url,
+                    &request,
+                    "stream_chat_completion",
+                    Wait::StreamIdle,
+                )
+                .await?;
+            let status = response.status().as_u16();
+
+            if !response.status().is_success() {
+                let response_text = response.text().await.map_err(|e| ProviderError::Http {
+                    operation: "read_error_response_text".to_string(),
+                    source: e,
+                })?;
+                self.observe_response(
+                    &correlation_id,
+                    Some(status),
+                    started,
+                    WireBody::Text(&response_text),
+                );
+                let error = self.parse_error_text(status, response_text)?;
+                return Err(error);
+            }
+            self.observe_response(&correlation_id, Some(status), started, WireBody::Stream);
+
+            let mut sse_stream = SseStream::new(response);
+            if let Some(idle_timeout) = self.config.stream_idle_timeout {
+                sse_stream = sse_stream.with_idle_timeout(idle_timeout);
+            }
+            Ok(sse_stream.parse_events())
+        }
+        .instrument(span)
+        .await
+    }
+
+    /// Stream a chat completion until it ends or `cancel` fires, handing
+    /// text to `on_token` as it arrives. A cancelled request is aborted and
+    /// returns the partial reply rather than an error.
+    #[instrument(skip(self, request, cancel, on_token), fields(model = %request.model))]
+    pub async fn stream_chat<F>(
+        &self,
+        request: ChatCompletionRequest,
+        cancel: CancellationToken,
+        on_token: F,
+    ) -> Result<StreamedResponse>
+    where
+        F: FnMut(&str) + Send,
+    {
+        let chunks = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => return Ok(StreamedResponse::cancelled("")),
+            chunks = self.chat_completion_stream(request) => chunks?,
+        };
+        streaming::stream_chat(chunks, cancel, on_token).await
+    }
+
+    /// Models the API key can use. Keys restricted from listing models get
+    /// [`ProviderError::AuthorizationDenied`].
+    pub async fn list_models(&self) -> Result<ModelsResponse> {
         let _permit = self
             .semaphore
             .acquire()
@@ -124,34 +319,34 @@ impl OpenAIClient {
                 context: None,
             })?;
 
-        let url = format!("{}/chat/completions", self.config.base_url);
-        debug!("Making chat completion request to: {}", url);
+        let url = format!("{}/models", self.config.base_url);
+        debug!("Listing models from: {}", url);
 
         self.retry_with_backoff(|| async {
-            let response = timeout(
-                self.config.timeout,
-                self.client.post(&url).json(&request).send(),
-            )
+            timeout(self.config.request_timeout, async {
+                let response = self
+                    .client
+                    .get(&url)
+                    .send()
+                    .await
+                    .map_err(|e| self.send_error("list_models", &url, e))?;
+                if response.status() == reqwest::StatusCode::FORBIDDEN {
+                    return Err(ProviderError::AuthorizationDenied {
+                        operation: "list_models".to_string(),
+                        required_permission: "the api.model.read scope".to_string(),
+                    });
+                }
+                self.handle_response(response).await
+            })
             .await
-            .map_err(|_| ProviderError::Timeout {
-                operation: "chat_completion".to_string(),
-                timeout_ms: self.config.timeout.as_millis() as u64,
-            })?
-            .map_err(|e| ProviderError::Http {
-                operation: "chat_completion_request".to_string(),
-                source: e,
-            })?;
-
-            self.handle_response(response).await
+            .map_err(|_| self.request_timed_out("list_models"))?
         })
         .await
     }
 
-    #[instrument(skip(self, request), fields(model = %request.model))]
-    pub async fn chat_completion_stream(
-        &self,
-        mut request: ChatCompletionRequest,
-    ) -> Result<impl Stream<Item = Result<ChatCompletionChunk>>> {
+    /// Embed `request.input` in one request; rate limited requests are
+    /// retried
+    pub async fn embeddings(&self, request: &EmbeddingRequest) -> Result<EmbeddingResponse> {
         let _permit = self
             .semaphore
             .acquire()
@@ -162,97 +357,351 @@ impl OpenAIClient {
                 context: None,
             })?;
 
-        request.stream = Some(true);
-        let url = format!("{}/chat/completions", self.config.base_url);
-        debug!("Making streaming chat completion request to: {}", url);
+        let url = format!("{}/embeddings", self.config.base_url);
+        debug!(
+            "Embedding {} inputs with {} at: {}",
+            request.input.len(),
+            request.model,
+            url
+        );
 
-        let response = timeout(
-            self.config.timeout,
-            self.client.post(&url).json(&request).send(),
-        )
+        self.retry_with_backoff(|| async {
+            timeout(self.config.request_timeout, async {
+                let response = self
+                    .client
+                    .post(&url)
+                    .json(request)
+                    .send()
+                    .await
+                    .map_err(|e| self.send_error("embeddings", &url, e))?;
+                self.handle_response(response).await
+            })
+            .await
+            .map_err(|_| self.request_timed_out("embeddings"))?
+        })
         .await
-        .map_err(|_| ProviderError::Timeout {
-            operation: "stream_chat_completion".to_string(),
-            timeout_ms: self.config.timeout.as_millis() as u64,
-        })?
-        .map_err(|e| ProviderError::Http {
-            operation: "stream_chat_completion_request".to_string(),
-            source: e,
-        })?;
+    }
 
-        if !response.status().is_success() {
-            let error = self.parse_error_response(response).await?;
-            return Err(error);
+    /// Send `messages` offering `tools`, returning either text or the tool
+    /// calls the model wants made
+    #[instrument(skip(self, messages, tools), fields(model = %model))]
+    pub async fn chat_with_tools(
+        &self,
+        model: &str,
+        messages: Vec<ChatMessage>,
+        tools: &[ToolDefinition],
+    ) -> Result<ToolAwareResponse> {
+        let response = self
+            .chat_completion(Self::tool_request(model, messages, tools))
+            .await?;
+
+        let choice = response
+            .choices
+            .into_iter()
+            .next()
+            .ok_or_else(|| ProviderError::Generic {
+                message: "No choices in response".to_string(),
+                provider: "openai".to_string(),
+                context: None,
+            })?;
+        ToolAwareResponse::from_message(choice.message, choice.finish_reason, response.usage)
+    }
+
+    /// Streaming variant of [`Self::chat_with_tools`]; text is handed to
+    /// `on_content` as it arrives while tool call fragments are collected
+    #[instrument(skip(self, messages, tools, on_content), fields(model = %model))]
+    pub async fn chat_with_tools_stream<F>(
+        &self,
+        model: &str,
+        messages: Vec<ChatMessage>,
+        tools: &[ToolDefinition],
+        mut on_content: F,
+    ) -> Result<ToolAwareResponse>
+    where
+        F: FnMut(&str) + Send,
+    {
+        let stream = self
+            .chat_completion_stream(Self::tool_request(model, messages, tools))
+            .await?;
+        futures::pin_mut!(stream);
+
+        let mut accumulator = StreamAccumulator::new();
+        while let Some(chunk) = stream.next().await {
+            if let Some(text) = accumulator.push(&chunk?) {
+                on_content(&text);
+            }
         }
+        accumulator.finish()
+    }
+
+    /// Keep answering the model's tool calls through `handler` until it
+    /// replies without any, or `max_rounds` requests have been made.
+    /// Every assistant and tool message is appended to `messages`.
+    pub async fn run_tool_loop(
+        &self,
+        model: &str,
+        messages: &mut Vec<ChatMessage>,
+        tools: &[ToolDefinition],
+        handler: &dyn ToolHandler,
+        max_rounds: usize,
+    ) -> Result<ToolAwareResponse> {
+        for round in 1..=max_rounds {
+            let response = self.chat_with_tools(model, messages.clone(), tools).await?;
+            messages.push(response.message.clone());
+            if !response.wants_tools() {
+                return Ok(response);
+            }
 
-        let sse_stream = SseStream::new(response);
-        Ok(sse_stream.parse_events())
+            for call in &response.tool_calls {
+                debug!("Round {}: running tool {} ({})", round, call.name, call.id);
+                let output = handler.call_tool(call).await;
+                messages.push(ChatMessage::tool_result(&call.id, output));
+            }
+        }
+
+        Err(ProviderError::Generic {
+            message: format!("Model still requested tools after {} rounds", max_rounds),
+            provider: "openai".to_string(),
+            context: None,
+        })
     }
 
-    pub async fn list_models(&self) -> Result<ModelsResponse> {
-        let _permit = self
-            .semaphore
-            .acquire()
+    /// Ask for a reply shaped like `T`. The schema travels in
+    /// `response_format` when the model supports structured outputs and in a
+    /// system message otherwise; a reply failing validation gets one repair
+    /// round trip listing what was wrong.
+    #[instrument(skip(self, messages), fields(model = %model))]
+    pub async fn chat_structured<T>(
+        &self,
+        model: &str,
+        mut messages: Vec<ChatMessage>,
+    ) -> Result<StructuredResponse<T>>
+    where
+        T: DeserializeOwned + JsonSchema,
+    {
+        let (name, schema) = structured::schema_for::<T>();
+        let mut response_format = if supports_json_schema(model) {
+            Some(structured::response_format(&name, &schema))
+        } else {
+            messages.insert(0, structured::schema_prompt(&schema));
+            None
+        };
+
+        let raw = match self
+            .structured_round(model, messages.clone(), response_format.clone())
             .await
-            .map_err(|e| ProviderError::Generic {
-                message: format!("Failed to acquire semaphore: {}", e),
+        {
+            Err(ProviderError::InvalidRequest { issue, .. })
+                if response_format.is_some() && issue.contains("response_format") =>
+            {
+                debug!(
+                    "{} rejected response_format, describing the schema instead",
+                    model
+                );
+                messages.insert(0, structured::schema_prompt(&schema));
+                response_format = None;
+                self.structured_round(model, messages.clone(), None).await?
+            }
+            result => result?,
+        };
+
+        let errors = match parse_structured::<T>(&raw, &schema) {
+            Ok(value) => {
+                return Ok(StructuredResponse {
+                    value,
+                    raw,
+                    repaired: false,
+                })
+            }
+            Err(errors) => errors,
+        };
+        warn!("Structured reply failed validation: {}", errors.join("; "));
+
+        messages.push(ChatMessage::new("assistant", raw));
+        messages.push(structured::repair_prompt(&errors));
+        let raw = self
+            .structured_round(model, messages, response_format)
+            .await?;
+        match parse_structured::<T>(&raw, &schema) {
+            Ok(value) => Ok(StructuredResponse {
+                value,
+                raw,
+                repaired: true,
+            }),
+            Err(errors) => Err(structured::unrecoverable(&name, &raw, &errors)),
+        }
+    }
+
+    async fn structured_round(
+        &self,
+        model: &str,
+        messages: Vec<ChatMessage>,
+        response_format: Option<ResponseFormat>,
+    ) -> Result<String> {
+        let request = ChatCompletionRequest {
+            response_format,
+            ..Self::tool_request(model, messages, &[])
+        };
+        let response = self.chat_completion(request).await?;
+        response
+            .choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message.content)
+            .ok_or_else(|| ProviderError::Generic {
+                message: "No choices in response".to_string(),
                 provider: "openai".to_string(),
                 context: None,
-            })?;
+            })
+    }
 
-        let url = format!("{}/models", self.config.base_url);
-        debug!("Listing models from: {}", url);
+    fn tool_request(
+        model: &str,
+        messages: Vec<ChatMessage>,
+        tools: &[ToolDefinition],
+    ) -> ChatCompletionRequest {
+        ChatCompletionRequest {
+            model: model.to_string(),
+            messages,
+            max_tokens: Some(4096),
+            temperature: Some(0.7),
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            stop: None,
+            stream: Some(false),
+            user: None,
+            tools: (!tools.is_empty()).then(|| tools.iter().cloned().map(ToolSpec::from).collect()),
+            response_format: None,
+        }
+    }
 
-        self.retry_with_backoff(|| async {
-            let response = timeout(self.config.timeout, self.client.get(&url).send())
+    /// Send a chat completion request, reporting it and any failure to
+    /// reach the provider to the middleware
+    async fn send_observed(
+        &self,
+        correlation_id: &CorrelationId,
+        url: &str,
+        request: &ChatCompletionRequest,
+        operation: &str,
+        wait: Wait,
+    ) -> Result<(Response, Instant)> {
+        if !self.middleware.is_empty() {
+            let body = serde_json::to_value(request).unwrap_or_default();
+            let wire_request = WireRequest {
+                method: "POST",
+                url,
+                headers: &self.headers,
+                body: &body,
+            };
+            for middleware in &self.middleware {
+                middleware.on_request(correlation_id, &wire_request);
+            }
+        }
+
+        let started = Instant::now();
+        let send = self.client.post(url).json(request).send();
+        let sent = match wait {
+            Wait::Request => timeout(self.config.request_timeout, send)
                 .await
-                .map_err(|_| ProviderError::Timeout {
-                    operation: "embed".to_string(),
-                    timeout_ms: self.config.timeout.as_millis() as u64,
-                })?
-                .map_err(|e| ProviderError::Http {
-                    operation: "embed_request".to_string(),
-                    source: e,
-                })?;
+                .map_err(|_| self.request_timed_out(operation)),
+            Wait::StreamIdle => match self.config.stream_idle_timeout {
+                Some(idle_timeout) => {
+                    timeout(idle_timeout, send)
+                        .await
+                        .map_err(|_| ProviderError::StreamIdle {
+                            operation: operation.to_string(),
+                            idle_ms: idle_timeout.as_millis() as u64,
+                        })
+                }
+                None => Ok(send.await),
+            },
+        }
+        .and_then(|sent| sent.map_err(|e| self.send_error(operation, url, e)));
 
-            self.handle_response(response).await
-        })
-        .await
+        match sent {
+            Ok(response) => Ok((response, started)),
+            Err(error) => {
+                self.observe_response(correlation_id, None, started, WireBody::Failed(&error));
+                Err(error)
+            }
+        }
+    }
+
+    fn request_timed_out(&self, operation: &str) -> ProviderError {
+        ProviderError::Timeout {
+            operation: operation.to_string(),
+            timeout_ms: self.config.request_timeout.as_millis() as u64,
+        }
+    }
+
+    /// Classify a failure to send a request, telling connect timeouts apart
+    fn send_error(&self, operation: &str, url: &str, error: reqwest::Error) -> ProviderError {
+        match self.config.connect_timeout {
+            Some(connect_timeout) if error.is_connect() && error.is_timeout() => {
+                ProviderError::ConnectTimeout {
+                    endpoint: url.to_string(),
+                    timeout_ms: connect_timeout.as_millis() as u64,
+                }
+            }
+            _ => ProviderError::Http {
+                operation: format!("{}_request", operation),
+                source: error,
+            },
+        }
+    }
+
+    fn observe_response(
+        &self,
+        correlation_id: &CorrelationId,
+        status: Option<u16>,
+        started: Instant,
+        body: WireBody<'_>,
+    ) {
+        if self.middleware.is_empty() {
+            return;
+        }
+        let wire_response = WireResponse {
+            status,
+            elapsed: started.elapsed(),
+            body,
+        };
+        for middleware in &self.middleware {
+            middleware.on_response(correlation_id, &wire_response);
+        }
     }
 
     async fn handle_response<T>(&self, response: Response) -> Result<T>
     where
         T: serde::de::DeserializeOwned,
     {
-        let status = response.status();
-
-        if status.is_success() {
-            let response_text = response.text().await.map_err(|e| ProviderError::Http {
-                operation: "read_response_text".to_string(),
-                source: e,
-            })?;
-            debug!("Received response: {}", response_text);
+        let status = response.status().as_u16();
+        let response_text = response.text().await.map_err(|e| ProviderError::Http {
+            operation: "read_response_text".to_string(),
+            source: e,
+        })?;
+        self.parse_response_text(status, response_text)
+    }
 
+    fn parse_response_text<T>(&self, status: u16, response_text: String) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        if (200..300).contains(&status) {
             serde_json::from_str(&response_text).map_err(|e| {
-                error!("Failed to parse response: {}, text: {}", e, response_text);
+                error!("Failed to parse response: {}", e);
                 ProviderError::Json {
                     operation: "parse_response".to_string(),
                     source: e,
                 }
             })
         } else {
-            let error = self.parse_error_response(response).await?;
+            let error = self.parse_error_text(status, response_text)?;
             Err(error)
         }
     }
 
-    async fn parse_error_response(&self, response: Response) -> Result<ProviderError> {
-        let status_code = response.status().as_u16();
-        let response_text = response.text().await.map_err(|e| ProviderError::Http {
-            operation: "read_error_response_text".to_string(),
-            source: e,
-        })?;
-
+    fn parse_error_text(&self, status_code: u16, response_text: String) -> Result<ProviderError> {
         // Try to parse as OpenAI error format
         if let Ok(openai_error) = serde_json::from_str::<OpenAIError>(&response_text) {
             let error_details = openai_error.error;
@@ -275,6 +724,13 @@ impl OpenAIClient {
                         current_usage: None,
                     })
                 }
+                400 if error_details.code.as_deref() == Some("context_length_exceeded") => {
+                    Err(ProviderError::TokenLimit {
+                        used: 0,
+                        limit: 0,
+                        suggestion: error_details.message,
+                    })
+                }
                 400 => Err(ProviderError::InvalidRequest {
                     field: "request".to_string(),
                     issue: error_details.message,
@@ -363,7 +819,11 @@ impl OpenAIClient {
                     );
 
                     // Use error-specific retry delay if available
-                    let retry_delay = err.retry_after().map(Duration::from_secs).unwrap_or(delay);
+                    let retry_delay = err
+                        .retry_after()
+                        .map(Duration::from_secs)
+                        .unwrap_or(delay)
+                        .min(self.config.max_retry_delay);
 
                     info!("Retrying in {:?}", retry_delay);
                     sleep(retry_delay).await;
@@ -386,13 +846,15 @@ impl ProviderClient for OpenAIClient {
             model: request.model.clone(),
             messages: request.messages.into_iter().map(Into::into).collect(),
             stream: Some(false),
-            max_tokens: Some(4096), // Default max tokens
-            temperature: Some(0.7), // Default temperature
+            max_tokens: request.max_tokens.or(Some(4096)),
+            temperature: request.temperature.or(Some(0.7)),
             top_p: None,
             frequency_penalty: None,
             presence_penalty: None,
             stop: None,
             user: None,
+            tools: None,
+            response_format: None,
         };
 
         let response = self.chat_completion(chat_request).await?;
@@ -402,6 +864,7 @@ impl ProviderClient for OpenAIClient {
                 id: request.id,
                 content: choice.message.content.clone(),
                 usage: response.usage.map(Into::into),
+                model: Some(response.model.clone()),
             })
         } else {
             Err(ProviderError::Generic {
@@ -425,13 +888,15 @@ impl ProviderClient for OpenAIClient {
             model: request.model.clone(),
             messages: request.messages.into_iter().map(Into::into).collect(),
             stream: Some(true),
-            max_tokens: Some(4096),
-            temperature: Some(0.7),
+            max_tokens: request.max_tokens.or(Some(4096)),
+            temperature: request.temperature.or(Some(0.7)),
             top_p: None,
             frequency_penalty: None,
             presence_penalty: None,
             stop: None,
             user: None,
+            tools: None,
+            response_format: None,
         };
 
         let stream = self.chat_completion_stream(chat_request).await?;
@@ -462,21 +927,77 @@ impl ProviderClient for OpenAIClient {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_server::{MockResponse, MockServer};
     use tokio;
 
     #[tokio::test]
     async fn test_openai_config_from_provider_config() {
         let provider_config = ProviderConfig {
+            provider: "openai".to_string(),
             openai_api_key: Some("test-key".to_string()),
+            anthropic_api_key: None,
+            openrouter_api_key: None,
             default_model: "gpt-4".to_string(),
             base_url: Some("https://api.test.com/v1".to_string()),
             timeout_seconds: 60,
+            connect_timeout_seconds: 10,
+            stream_idle_timeout_seconds: 60,
+            fallback_models: Vec::new(),
+            routes: Default::default(),
         };
 
         let openai_config = OpenAIConfig::from(&provider_config);
         assert_eq!(openai_config.api_key, "test-key");
         assert_eq!(openai_config.base_url, "https://api.test.com/v1");
-        assert_eq!(openai_config.timeout, Duration::from_secs(60));
+        assert_eq!(openai_config.request_timeout, Duration::from_secs(60));
+        assert_eq!(openai_config.connect_timeout, Some(Duration::from_secs(10)));
+        assert_eq!(
+            openai_config.stream_idle_timeout,
+            Some(Duration::from_secs(60))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout_fires_when_server_never_answers() {
+        let server = MockServer::start(vec![MockResponse::Hang]).await;
+        let client = OpenAIClient::new(OpenAIConfig {
+            api_key: "test-key".to_string(),
+            base_url: server.base_url.clone(),
+            request_timeout: Duration::from_millis(200),
+            max_retries: 0,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let error = client
+            .chat_completion(ChatCompletionRequest {
+                model: "gpt-4o".to_string(),
+                messages: vec![ChatMessage::new("user", "Hello")],
+                max_tokens: None,
+                temperature: None,
+                top_p: None,
+                frequency_penalty: None,
+                presence_penalty: None,
+                stop: None,
+                stream: None,
+                user: None,
+                tools: None,
+                response_format: None,
+            })
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(
+                error,
+                ProviderError::Timeout {
+                    timeout_ms: 200,
+                    ..
+                }
+            ),
+            "{:?}",
+            error
+        );
     }
 
     #[tokio::test]
@@ -495,4 +1016,320 @@ mod tests {
             panic!("Expected ConfigurationMissing error");
         }
     }
+
+    fn mock_client(server: &MockServer) -> OpenAIClient {
+        OpenAIClient::new(OpenAIConfig {
+            api_key: "test-key".to_string(),
+            base_url: server.base_url.clone(),
+            max_retries: 0,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn search_tool() -> ToolDefinition {
+        ToolDefinition::new(
+            "search",
+            "Search the workspace",
+            serde_json::json!({
+                "type": "object",
+                "properties": {"query": {"type": "string"}},
+                "required": ["query"]
+            }),
+        )
+    }
+
+    fn tool_call_completion() -> serde_json::Value {
+        serde_json::json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1,
+            "model": "gpt-4o",
+            "choices": [{
+                "index": 0,
+                "message": {
+                    "role": "assistant",
+                    "content": null,
+                    "tool_calls": [{
+                        "id": "call_1",
+                        "type": "function",
+                        "function": {"name": "search", "arguments": "{\"query\":\"TODO\"}"}
+                    }]
+                },
+                "finish_reason": "tool_calls"
+            }],
+            "usage": {"prompt_tokens": 40, "completion_tokens": 12, "total_tokens": 52}
+        })
+    }
+
+    fn text_completion(text: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": "chatcmpl-2",
+            "object": "chat.completion",
+            "created": 2,
+            "model": "gpt-4o",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": text},
+                "finish_reason": "stop"
+            }]
+        })
+    }
+
+    struct CountingHandler(std::sync::atomic::AtomicUsize);
+
+    #[async_trait::async_trait]
+    impl ToolHandler for CountingHandler {
+        async fn call_tool(&self, call: &crate::tools::ToolInvocation) -> String {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            format!("3 matches for {}", call.arguments["query"])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_with_tools_surfaces_parsed_calls() {
+        let server = MockServer::start(vec![MockResponse::json(tool_call_completion())]).await;
+        let client = mock_client(&server);
+
+        let response = client
+            .chat_with_tools(
+                "gpt-4o",
+                vec![ChatMessage::new("user", "Find the TODOs")],
+                &[search_tool()],
+            )
+            .await
+            .unwrap();
+
+        assert!(response.wants_tools());
+        assert_eq!(response.content, "");
+        assert_eq!(response.tool_calls[0].name, "search");
+        assert_eq!(
+            response.tool_calls[0].arguments,
+            serde_json::json!({"query": "TODO"})
+        );
+        assert_eq!(response.usage.as_ref().unwrap().total_tokens, 52);
+
+        let request = &server.requests()[0];
+        assert_eq!(request["tools"][0]["type"], "function");
+        assert_eq!(request["tools"][0]["function"]["name"], "search");
+        assert_eq!(
+            request["tools"][0]["function"]["parameters"]["required"][0],
+            "query"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tool_loop_feeds_results_back() {
+        let server = MockServer::start(vec![
+            MockResponse::json(tool_call_completion()),
+            MockResponse::json(text_completion("There are 3 TODOs.")),
+        ])
+        .await;
+        let client = mock_client(&server);
+        let handler = CountingHandler(Default::default());
+
+        let mut messages = vec![ChatMessage::new("user", "Find the TODOs")];
+        let response = client
+            .run_tool_loop("gpt-4o", &mut messages, &[search_tool()], &handler, 4)
+            .await
+            .unwrap();
+
+        assert_eq!(response.content, "There are 3 TODOs.");
+        assert_eq!(handler.0.load(std::sync::atomic::Ordering::SeqCst), 1);
+        let roles: Vec<&str> = messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, ["user", "assistant", "tool", "assistant"]);
+
+        // The second request carries the assistant tool call and its result
+        let second = &server.requests()[1];
+        assert_eq!(second["messages"][1]["tool_calls"][0]["id"], "call_1");
+        assert_eq!(second["messages"][2]["role"], "tool");
+        assert_eq!(second["messages"][2]["tool_call_id"], "call_1");
+        assert_eq!(second["messages"][2]["content"], "3 matches for \"TODO\"");
+    }
+
+    #[tokio::test]
+    async fn test_tool_loop_stops_after_max_rounds() {
+        let server = MockServer::start(vec![
+            MockResponse::json(tool_call_completion()),
+            MockResponse::json(tool_call_completion()),
+        ])
+        .await;
+        let client = mock_client(&server);
+        let handler = CountingHandler(Default::default());
+
+        let mut messages = vec![ChatMessage::new("user", "Find the TODOs")];
+        let result = client
+            .run_tool_loop("gpt-4o", &mut messages, &[search_tool()], &handler, 2)
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(handler.0.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_streamed_tool_call_is_accumulated() {
+        let chunk = |delta: serde_json::Value, finish: Option<&str>| {
+            serde_json::json!({
+                "id": "chatcmpl-3",
+                "object": "chat.completion.chunk",
+                "created": 3,
+                "model": "gpt-4o",
+                "choices": [{"index": 0, "delta": delta, "finish_reason": finish}]
+            })
+        };
+        let server = MockServer::start(vec![MockResponse::sse(vec![
+            chunk(serde_json::json!({"role": "assistant", "content": "Searching"}), None),
+            chunk(
+                serde_json::json!({"tool_calls": [{"index": 0, "id": "call_9", "type": "function",
+                    "function": {"name": "search", "arguments": ""}}]}),
+                None,
+            ),
+            chunk(
+                serde_json::json!({"tool_calls": [{"index": 0, "function": {"arguments": "{\"qu"}}]}),
+                None,
+            ),
+            chunk(
+                serde_json::json!({"tool_calls": [{"index": 0, "function": {"arguments": "ery\": \"fn main\"}"}}]}),
+                None,
+            ),
+            chunk(serde_json::json!({}), Some("tool_calls")),
+        ])])
+        .await;
+        let client = mock_client(&server);
+
+        let mut streamed = String::new();
+        let response = client
+            .chat_with_tools_stream(
+                "gpt-4o",
+                vec![ChatMessage::new("user", "Where is main?")],
+                &[search_tool()],
+                |text| streamed.push_str(text),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(streamed, "Searching");
+        assert_eq!(response.content, "Searching");
+        assert_eq!(response.finish_reason.as_deref(), Some("tool_calls"));
+        assert_eq!(response.tool_calls.len(), 1);
+        assert_eq!(response.tool_calls[0].id, "call_9");
+        assert_eq!(
+            response.tool_calls[0].arguments,
+            serde_json::json!({"query": "fn main"})
+        );
+        assert_eq!(server.requests()[0]["stream"], true);
+    }
+
+    #[derive(Debug, serde::Deserialize, JsonSchema)]
+    struct Review {
+        verdict: String,
+        issues: Vec<String>,
+    }
+
+    #[tokio::test]
+    async fn test_chat_structured_uses_response_format() {
+        let server = MockServer::start(vec![MockResponse::json(text_completion(
+            r#"{"verdict": "approve", "issues": []}"#,
+        ))])
+        .await;
+        let client = mock_client(&server);
+
+        let response = client
+            .chat_structured::<Review>("gpt-4o", vec![ChatMessage::new("user", "Review it")])
+            .await
+            .unwrap();
+
+        assert_eq!(response.value.verdict, "approve");
+        assert!(response.value.issues.is_empty());
+        assert!(!response.repaired);
+        assert_eq!(response.raw, r#"{"verdict": "approve", "issues": []}"#);
+
+        let request = &server.requests()[0];
+        assert_eq!(request["response_format"]["type"], "json_schema");
+        assert_eq!(request["response_format"]["json_schema"]["name"], "Review");
+        assert_eq!(
+            request["response_format"]["json_schema"]["schema"]["required"],
+            serde_json::json!(["issues", "verdict"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_structured_repairs_invalid_reply() {
+        let server = MockServer::start(vec![
+            MockResponse::json(text_completion(r#"Sure: {"verdict": "approve"}"#)),
+            MockResponse::json(text_completion(
+                "```json\n{\"verdict\": \"reject\", \"issues\": [\"no tests\"]}\n```",
+            )),
+        ])
+        .await;
+        let client = mock_client(&server);
+
+        let response = client
+            .chat_structured::<Review>("gpt-3.5-turbo", vec![ChatMessage::new("user", "Review it")])
+            .await
+            .unwrap();
+
+        assert!(response.repaired);
+        assert_eq!(response.value.verdict, "reject");
+        assert_eq!(response.value.issues, vec!["no tests".to_string()]);
+
+        // Without structured output support the schema is in the prompt
+        let requests = server.requests();
+        assert!(requests[0].get("response_format").is_none());
+        let system = requests[0]["messages"][0]["content"].as_str().unwrap();
+        assert!(system.contains("\"verdict\""), "{}", system);
+
+        let repair = &requests[1]["messages"];
+        let repair = repair.as_array().unwrap();
+        assert_eq!(repair[repair.len() - 2]["role"], "assistant");
+        let feedback = repair[repair.len() - 1]["content"].as_str().unwrap();
+        assert!(
+            feedback.contains("missing required field 'issues'"),
+            "{}",
+            feedback
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_structured_gives_up_after_one_repair() {
+        let server = MockServer::start(vec![
+            MockResponse::json(text_completion("I cannot do that")),
+            MockResponse::json(text_completion(r#"{"verdict": 3, "issues": []}"#)),
+        ])
+        .await;
+        let client = mock_client(&server);
+
+        let error = client
+            .chat_structured::<Review>("gpt-4o", vec![ChatMessage::new("user", "Review it")])
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, ProviderError::ResponseParsingFailed { .. }));
+        let message = error.to_string();
+        assert!(
+            message.contains("$.verdict: expected string"),
+            "{}",
+            message
+        );
+        assert!(message.contains(r#"{"verdict": 3"#), "{}", message);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_list_models_forbidden_is_authorization_denied() {
+        let server = MockServer::start(vec![MockResponse::Json {
+            status: 403,
+            body: r#"{"error": {"message": "Missing scopes: api.model.read", "type": "invalid_request_error"}}"#
+                .to_string(),
+        }])
+        .await;
+
+        let error = mock_client(&server).list_models().await.unwrap_err();
+        assert!(
+            matches!(&error, ProviderError::AuthorizationDenied { operation, .. } if operation == "list_models"),
+            "{:?}",
+            error
+        );
+        assert!(!error.is_retryable());
+    }
 }
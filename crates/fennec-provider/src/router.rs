@@ -0,0 +1,516 @@
+//! Routing of provider requests by the kind of task they serve.
+//!
+//! [`ProviderRouter`] maps each [`TaskKind`] to a provider, model and
+//! sampling parameters, so e.g. summaries can go to a cheap model while code
+//! edits go to a strong one. Kinds without a route use the default route,
+//! which is the configured default provider and model including its fallback
+//! chain. Routes can be overridden for a single session.
+//!
+//! Callers pick a kind and get a [`RoutedClient`], an ordinary
+//! [`ProviderClient`] that rewrites the model and parameters of every request
+//! it forwards.
+
+use async_trait::async_trait;
+use fennec_core::config::{ProviderConfig, TaskRoute};
+use fennec_core::provider::{
+    AttachmentSupport, ProviderClient, ProviderRequest, ProviderResponse, TaskKind,
+};
+use futures::Stream;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::client::ProviderClientFactory;
+use crate::error::{ProviderError, Result};
+use crate::middleware::ProviderMiddleware;
+
+type TextStream = Box<dyn Stream<Item = fennec_core::Result<String>> + Unpin + Send>;
+
+/// A route resolved to the client serving it
+#[derive(Clone)]
+pub struct Route {
+    pub provider: String,
+    pub model: String,
+    pub temperature: Option<f32>,
+    client: Arc<dyn ProviderClient>,
+}
+
+impl std::fmt::Debug for Route {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Route")
+            .field("provider", &self.provider)
+            .field("model", &self.model)
+            .field("temperature", &self.temperature)
+            .finish()
+    }
+}
+
+/// Chooses the provider and model for a request by its [`TaskKind`]
+pub struct ProviderRouter {
+    default_route: Route,
+    /// Clients by provider name, used by routes
+    clients: HashMap<String, Arc<dyn ProviderClient>>,
+    routes: HashMap<TaskKind, Route>,
+    session_routes: RwLock<HashMap<Uuid, HashMap<TaskKind, Route>>>,
+}
+
+impl ProviderRouter {
+    /// Router sending every kind of task to `client` as `model`
+    pub fn new(
+        provider: impl Into<String>,
+        client: Arc<dyn ProviderClient>,
+        model: impl Into<String>,
+    ) -> Self {
+        let provider = provider.into();
+        Self {
+            clients: HashMap::from([(provider.clone(), client.clone())]),
+            default_route: Route {
+                provider,
+                model: model.into(),
+                temperature: None,
+                client,
+            },
+            routes: HashMap::new(),
+            session_routes: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Router for the default provider and task routes of `config`, creating
+    /// a client for every provider they reference
+    pub fn from_config(
+        config: &ProviderConfig,
+        middleware: &[Arc<dyn ProviderMiddleware>],
+    ) -> Result<Self> {
+        let default_client =
+            ProviderClientFactory::create_client_with_middleware(config, middleware)?;
+        let mut router = Self::new(&config.provider, default_client, &config.default_model);
+        if config.routes.is_empty() {
+            return Ok(router);
+        }
+
+        // Routes name their own model, so they bypass the default model's
+        // fallback chain
+        router = router.with_provider(
+            &config.provider,
+            ProviderClientFactory::create_base_client(config, middleware)?,
+        );
+        for (kind, route) in &config.routes {
+            if let Some(provider) = &route.provider {
+                if !router.clients.contains_key(provider) {
+                    let client =
+                        ProviderClientFactory::create_named_client(provider, config, middleware)?;
+                    router = router.with_provider(provider, client);
+                }
+            }
+            router = router.with_route(*kind, route.clone())?;
+        }
+        Ok(router)
+    }
+
+    /// Make `client` available to routes under the name `provider`
+    pub fn with_provider(
+        mut self,
+        provider: impl Into<String>,
+        client: Arc<dyn ProviderClient>,
+    ) -> Self {
+        self.clients.insert(provider.into(), client);
+        self
+    }
+
+    /// Send tasks of `kind` along `route`. Fails when the route names a
+    /// provider the router has no client for.
+    pub fn with_route(mut self, kind: TaskKind, route: TaskRoute) -> Result<Self> {
+        let route = self.resolve(kind, route)?;
+        info!(
+            "Routing {} tasks to {}/{}",
+            kind, route.provider, route.model
+        );
+        self.routes.insert(kind, route);
+        Ok(self)
+    }
+
+    /// Apply `wrap` to every client, e.g. to put a cache in front of them
+    pub fn wrap_clients<F>(mut self, wrap: F) -> Self
+    where
+        F: Fn(Arc<dyn ProviderClient>) -> Arc<dyn ProviderClient>,
+    {
+        let mut wrapped: HashMap<*const (), Arc<dyn ProviderClient>> = HashMap::new();
+        let mut wrap_once = |client: &Arc<dyn ProviderClient>| {
+            wrapped
+                .entry(Arc::as_ptr(client) as *const ())
+                .or_insert_with(|| wrap(client.clone()))
+                .clone()
+        };
+
+        self.default_route.client = wrap_once(&self.default_route.client);
+        for client in self.clients.values_mut() {
+            *client = wrap_once(client);
+        }
+        for route in self.routes.values_mut() {
+            route.client = wrap_once(&route.client);
+        }
+        for routes in self.session_routes.get_mut().unwrap().values_mut() {
+            for route in routes.values_mut() {
+                route.client = wrap_once(&route.client);
+            }
+        }
+        self
+    }
+
+    /// Route taken by tasks of `kind` outside any session
+    pub fn route(&self, kind: TaskKind) -> Route {
+        self.routes
+            .get(&kind)
+            .unwrap_or(&self.default_route)
+            .clone()
+    }
+
+    /// Route taken by tasks of `kind` in `session_id`, honouring the
+    /// session's overrides
+    pub fn session_route(&self, session_id: Uuid, kind: TaskKind) -> Route {
+        let overridden = self
+            .session_routes
+            .read()
+            .unwrap()
+            .get(&session_id)
+            .and_then(|routes| routes.get(&kind).cloned());
+        overridden.unwrap_or_else(|| self.route(kind))
+    }
+
+    /// Client for tasks of `kind`
+    pub fn client(&self, kind: TaskKind) -> RoutedClient {
+        RoutedClient::new(kind, self.route(kind))
+    }
+
+    /// Client for tasks of `kind` in `session_id`
+    pub fn session_client(&self, session_id: Uuid, kind: TaskKind) -> RoutedClient {
+        RoutedClient::new(kind, self.session_route(session_id, kind))
+    }
+
+    /// Send tasks of `kind` in `session_id` along `route` instead of the
+    /// configured one
+    pub fn set_session_route(
+        &self,
+        session_id: Uuid,
+        kind: TaskKind,
+        route: TaskRoute,
+    ) -> Result<()> {
+        let route = self.resolve(kind, route)?;
+        info!(
+            "Routing {} tasks of session {} to {}/{}",
+            kind, session_id, route.provider, route.model
+        );
+        self.session_routes
+            .write()
+            .unwrap()
+            .entry(session_id)
+            .or_default()
+            .insert(kind, route);
+        Ok(())
+    }
+
+    /// Drop the overrides of `session_id`
+    pub fn clear_session_routes(&self, session_id: Uuid) {
+        self.session_routes.write().unwrap().remove(&session_id);
+    }
+
+    fn resolve(&self, kind: TaskKind, route: TaskRoute) -> Result<Route> {
+        if route.model.is_empty() {
+            return Err(ProviderError::ConfigurationInvalid {
+                provider: route
+                    .provider
+                    .unwrap_or_else(|| self.default_route.provider.clone()),
+                setting: format!("routes.{}.model", kind),
+                issue: "must not be empty".to_string(),
+            });
+        }
+
+        let provider = route
+            .provider
+            .unwrap_or_else(|| self.default_route.provider.clone());
+        let Some(client) = self.clients.get(&provider) else {
+            let mut available: Vec<&str> = self.clients.keys().map(String::as_str).collect();
+            available.sort_unstable();
+            return Err(ProviderError::ProviderNotSupported {
+                provider,
+                available: available.join(", "),
+            });
+        };
+        Ok(Route {
+            client: client.clone(),
+            provider,
+            model: route.model,
+            temperature: route.temperature,
+        })
+    }
+}
+
+/// Provider client serving one kind of task along its route
+#[derive(Clone)]
+pub struct RoutedClient {
+    kind: TaskKind,
+    route: Route,
+}
+
+impl RoutedClient {
+    fn new(kind: TaskKind, route: Route) -> Self {
+        Self { kind, route }
+    }
+
+    pub fn kind(&self) -> TaskKind {
+        self.kind
+    }
+
+    pub fn route(&self) -> &Route {
+        &self.route
+    }
+
+    fn routed(&self, mut request: ProviderRequest) -> ProviderRequest {
+        debug!(
+            "Routing {} request to {}/{}",
+            self.kind, self.route.provider, self.route.model
+        );
+        request.model = self.route.model.clone();
+        request.temperature = request.temperature.or(self.route.temperature);
+        request
+    }
+}
+
+#[async_trait]
+impl ProviderClient for RoutedClient {
+    async fn complete(&self, request: ProviderRequest) -> fennec_core::Result<ProviderResponse> {
+        self.route.client.complete(self.routed(request)).await
+    }
+
+    async fn stream(&self, request: ProviderRequest) -> fennec_core::Result<TextStream> {
+        self.route.client.stream(self.routed(request)).await
+    }
+
+    fn attachment_support(&self) -> AttachmentSupport {
+        self.route.client.attachment_support()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockProviderClient;
+    use fennec_core::provider::ProviderMessage;
+
+    fn request() -> ProviderRequest {
+        ProviderRequest {
+            id: Uuid::new_v4(),
+            messages: vec![ProviderMessage {
+                role: "user".to_string(),
+                content: "Hello".to_string(),
+            }],
+            model: "unrouted".to_string(),
+            stream: false,
+            temperature: None,
+            max_tokens: None,
+        }
+    }
+
+    fn config() -> ProviderConfig {
+        ProviderConfig {
+            provider: "openai".to_string(),
+            openai_api_key: None,
+            anthropic_api_key: None,
+            openrouter_api_key: None,
+            default_model: "gpt-4o".to_string(),
+            base_url: None,
+            timeout_seconds: 30,
+            connect_timeout_seconds: 10,
+            stream_idle_timeout_seconds: 60,
+            fallback_models: Vec::new(),
+            routes: HashMap::new(),
+        }
+    }
+
+    /// Router with a strong default provider and a cheap one, returning both
+    /// clients to inspect the requests they received
+    fn router() -> (
+        ProviderRouter,
+        Arc<MockProviderClient>,
+        Arc<MockProviderClient>,
+    ) {
+        let strong = Arc::new(MockProviderClient::default());
+        let cheap = Arc::new(MockProviderClient::default());
+        let router = ProviderRouter::new("strong", strong.clone(), "big-model")
+            .with_provider("cheap", cheap.clone())
+            .with_route(
+                TaskKind::Summarize,
+                TaskRoute::new("small-model")
+                    .with_provider("cheap")
+                    .with_temperature(0.1),
+            )
+            .unwrap()
+            .with_route(TaskKind::CodeEdit, TaskRoute::new("code-model"))
+            .unwrap();
+        (router, strong, cheap)
+    }
+
+    #[tokio::test]
+    async fn test_tasks_follow_their_routes() {
+        let (router, strong, cheap) = router();
+
+        router
+            .client(TaskKind::Summarize)
+            .complete(request())
+            .await
+            .unwrap();
+        router
+            .client(TaskKind::CodeEdit)
+            .complete(request())
+            .await
+            .unwrap();
+        router
+            .client(TaskKind::Chat)
+            .complete(request())
+            .await
+            .unwrap();
+
+        let cheap_requests = cheap.requests();
+        assert_eq!(cheap_requests.len(), 1);
+        assert_eq!(cheap_requests[0].model, "small-model");
+        assert_eq!(cheap_requests[0].temperature, Some(0.1));
+
+        let strong_models: Vec<String> = strong.requests().into_iter().map(|r| r.model).collect();
+        assert_eq!(strong_models, ["code-model", "big-model"]);
+    }
+
+    #[tokio::test]
+    async fn test_request_temperature_wins_over_route() {
+        let (router, _strong, cheap) = router();
+        let mut request = request();
+        request.temperature = Some(0.9);
+
+        router
+            .client(TaskKind::Summarize)
+            .complete(request)
+            .await
+            .unwrap();
+
+        assert_eq!(cheap.requests()[0].temperature, Some(0.9));
+    }
+
+    #[test]
+    fn test_session_overrides_apply_to_that_session_only() {
+        let (router, _strong, _cheap) = router();
+        let session = Uuid::new_v4();
+        let other = Uuid::new_v4();
+
+        router
+            .set_session_route(
+                session,
+                TaskKind::Chat,
+                TaskRoute::new("small-model").with_provider("cheap"),
+            )
+            .unwrap();
+
+        let route = router.session_route(session, TaskKind::Chat);
+        assert_eq!(
+            (route.provider.as_str(), route.model.as_str()),
+            ("cheap", "small-model")
+        );
+        assert_eq!(
+            router.session_route(other, TaskKind::Chat).model,
+            "big-model"
+        );
+        assert_eq!(
+            router.session_route(session, TaskKind::Summarize).model,
+            "small-model"
+        );
+
+        router.clear_session_routes(session);
+        assert_eq!(
+            router.session_route(session, TaskKind::Chat).model,
+            "big-model"
+        );
+    }
+
+    #[test]
+    fn test_route_to_missing_provider_is_rejected() {
+        let (router, _strong, _cheap) = router();
+
+        let error = router
+            .set_session_route(
+                Uuid::new_v4(),
+                TaskKind::Plan,
+                TaskRoute::new("claude").with_provider("anthropic"),
+            )
+            .unwrap_err();
+        match error {
+            ProviderError::ProviderNotSupported {
+                provider,
+                available,
+            } => {
+                assert_eq!(provider, "anthropic");
+                assert_eq!(available, "cheap, strong");
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_config_with_unconstructible_provider_is_rejected() {
+        let mut config = config();
+        config.routes.insert(
+            TaskKind::Embedding,
+            TaskRoute::new("embed-large").with_provider("anthropic"),
+        );
+
+        assert!(matches!(
+            ProviderClientFactory::validate_config(&config),
+            Err(ProviderError::ProviderNotSupported { .. })
+        ));
+        assert!(matches!(
+            ProviderRouter::from_config(&config, &[]),
+            Err(ProviderError::ProviderNotSupported { .. })
+        ));
+
+        // OpenAI is known, but cannot be reached without a key unless it is
+        // the default provider
+        config.provider = "mock".to_string();
+        config.routes.insert(
+            TaskKind::Embedding,
+            TaskRoute::new("text-embedding-3-small").with_provider("openai"),
+        );
+        assert!(matches!(
+            ProviderClientFactory::validate_config(&config),
+            Err(ProviderError::ConfigurationMissing { .. })
+        ));
+    }
+
+    #[test]
+    fn test_from_config_routes_by_kind() {
+        let mut config = config();
+        config
+            .routes
+            .insert(TaskKind::Summarize, TaskRoute::new("gpt-4o-mini"));
+        config.routes.insert(
+            TaskKind::Plan,
+            TaskRoute::new("planner").with_provider("mock"),
+        );
+
+        ProviderClientFactory::validate_config(&config).unwrap();
+        let router = ProviderRouter::from_config(&config, &[]).unwrap();
+
+        let summarize = router.route(TaskKind::Summarize);
+        assert_eq!(
+            (summarize.provider.as_str(), summarize.model.as_str()),
+            ("openai", "gpt-4o-mini")
+        );
+        let plan = router.route(TaskKind::Plan);
+        assert_eq!(
+            (plan.provider.as_str(), plan.model.as_str()),
+            ("mock", "planner")
+        );
+        let chat = router.route(TaskKind::Chat);
+        assert_eq!(
+            (chat.provider.as_str(), chat.model.as_str()),
+            ("openai", "gpt-4o")
+        );
+    }
+}
@@ -1,60 +1,181 @@
 use futures::{Stream, StreamExt};
 use reqwest::Response;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::future::Future;
 use std::pin::Pin;
 use std::task::{Context, Poll};
-use tracing::{debug, error, warn};
+use std::time::Duration;
+use tokio::time::{Instant, Sleep};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
 
 use crate::error::{ProviderError, Result};
 use crate::models::ChatCompletionChunk;
+use crate::tools::{StreamAccumulator, ToolInvocation};
 
-/// A stream of Server-Sent Events from OpenAI's streaming API
+/// Why a streamed reply ended
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FinishReason {
+    Stop,
+    Length,
+    ToolCalls,
+    ContentFilter,
+    /// The caller cancelled; the content is whatever arrived before that
+    Cancelled,
+    /// The stream ended without saying why
+    Unknown,
+}
+
+impl FinishReason {
+    /// Map the `finish_reason` reported by the API
+    pub fn from_api(reason: Option<&str>) -> Self {
+        match reason {
+            Some("stop") => Self::Stop,
+            Some("length") => Self::Length,
+            Some("tool_calls") | Some("function_call") => Self::ToolCalls,
+            Some("content_filter") => Self::ContentFilter,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// A streamed reply, complete or cut short by cancellation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamedResponse {
+    pub content: String,
+    /// Tool calls of a completed reply; half-received calls of a cancelled
+    /// one are dropped
+    pub tool_calls: Vec<ToolInvocation>,
+    pub finish_reason: FinishReason,
+}
+
+impl StreamedResponse {
+    pub(crate) fn cancelled(content: impl Into<String>) -> Self {
+        Self {
+            content: content.into(),
+            tool_calls: Vec::new(),
+            finish_reason: FinishReason::Cancelled,
+        }
+    }
+
+    /// Whether the reply was cut short and should be recorded as truncated
+    pub fn is_partial(&self) -> bool {
+        self.finish_reason == FinishReason::Cancelled
+    }
+}
+
+/// Read `chunks` to the end, handing each piece of text to `on_token`.
+///
+/// When `cancel` fires the stream is dropped at once, which closes the
+/// underlying HTTP response, and the text received so far is returned with
+/// [`FinishReason::Cancelled`].
+pub async fn stream_chat<S, F>(
+    chunks: S,
+    cancel: CancellationToken,
+    mut on_token: F,
+) -> Result<StreamedResponse>
+where
+    S: Stream<Item = Result<ChatCompletionChunk>>,
+    F: FnMut(&str),
+{
+    futures::pin_mut!(chunks);
+    let mut accumulator = StreamAccumulator::new();
+
+    loop {
+        let next = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => {
+                info!(
+                    "Stream cancelled after {} characters",
+                    accumulator.content().len()
+                );
+                return Ok(StreamedResponse::cancelled(accumulator.content()));
+            }
+            next = chunks.next() => next,
+        };
+
+        match next {
+            Some(chunk) => {
+                if let Some(text) = accumulator.push(&chunk?) {
+                    on_token(&text);
+                }
+            }
+            None => break,
+        }
+    }
+
+    let response = accumulator.finish()?;
+    Ok(StreamedResponse {
+        content: response.content,
+        tool_calls: response.tool_calls,
+        finish_reason: FinishReason::from_api(response.finish_reason.as_deref()),
+    })
+}
+
+/// A stream of Server-Sent Events from OpenAI's streaming API, yielding
+/// the data of each event
 pub struct SseStream {
     inner: Pin<Box<dyn Stream<Item = reqwest::Result<bytes::Bytes>> + Send>>,
-    buffer: String,
+    parser: SseParser,
+    idle: Option<IdleTimer>,
+    /// Set once the body has ended or stalled; nothing more is read after that
+    done: bool,
+}
+
+/// Deadline pushed back whenever data arrives
+struct IdleTimer {
+    timeout: Duration,
+    deadline: Pin<Box<Sleep>>,
 }
 
 impl SseStream {
     pub fn new(response: Response) -> Self {
-        let stream = response.bytes_stream();
+        Self::from_bytes(response.bytes_stream())
+    }
 
+    fn from_bytes(
+        stream: impl Stream<Item = reqwest::Result<bytes::Bytes>> + Send + 'static,
+    ) -> Self {
         Self {
             inner: Box::pin(stream),
-            buffer: String::new(),
+            parser: SseParser::default(),
+            idle: None,
+            done: false,
         }
     }
 
-    /// Parse SSE events and extract OpenAI chat completion chunks
+    /// Fail with [`ProviderError::StreamIdle`] when no bytes arrive for
+    /// `timeout`, even though the connection is still open
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle = Some(IdleTimer {
+            timeout,
+            deadline: Box::pin(tokio::time::sleep(timeout)),
+        });
+        self
+    }
+
+    /// Parse SSE events and extract OpenAI chat completion chunks. Events
+    /// that are not chunks are skipped with a warning.
     pub fn parse_events(self) -> impl Stream<Item = Result<ChatCompletionChunk>> {
-        self.filter_map(|line_result| async move {
-            match line_result {
-                Ok(line) => {
-                    if line.starts_with("data: ") {
-                        let data = &line[6..]; // Remove "data: " prefix
-
-                        if data == "[DONE]" {
-                            debug!("Received stream completion marker");
-                            return None;
-                        }
+        self.filter_map(|event| async move {
+            match event {
+                Ok(data) => {
+                    if data.trim() == "[DONE]" {
+                        debug!("Received stream completion marker");
+                        return None;
+                    }
 
-                        match serde_json::from_str::<ChatCompletionChunk>(data) {
-                            Ok(chunk) => {
-                                debug!("Parsed SSE chunk: {:?}", chunk.id);
-                                Some(Ok(chunk))
-                            }
-                            Err(e) => {
-                                warn!("Failed to parse SSE chunk: {}, data: {}", e, data);
-                                Some(Err(ProviderError::StreamError {
-                                    operation: "parse_chunk".to_string(),
-                                    reason: format!("Failed to parse chunk: {}", e),
-                                }))
-                            }
+                    match serde_json::from_str::<ChatCompletionChunk>(&data) {
+                        Ok(chunk) => {
+                            debug!("Parsed SSE chunk: {:?}", chunk.id);
+                            Some(Ok(chunk))
+                        }
+                        Err(e) => {
+                            warn!("Skipping unparseable SSE chunk: {}, data: {}", e, data);
+                            None
                         }
-                    } else if line.starts_with("event: ") || line.is_empty() {
-                        // Skip event lines and empty lines
-                        None
-                    } else {
-                        warn!("Unexpected SSE line format: {}", line);
-                        None
                     }
                 }
                 Err(e) => {
@@ -92,34 +213,22 @@ impl Stream for SseStream {
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         loop {
+            // Hand out parsed events before reading more; one network chunk
+            // often carries several events
+            if let Some(event) = self.parser.next_event() {
+                return Poll::Ready(Some(Ok(event)));
+            }
+            if self.done {
+                return Poll::Ready(None);
+            }
+
             match self.inner.as_mut().poll_next(cx) {
                 Poll::Ready(Some(Ok(bytes))) => {
-                    // Convert bytes to string and add to buffer
-                    match String::from_utf8(bytes.to_vec()) {
-                        Ok(text) => {
-                            self.buffer.push_str(&text);
-
-                            // Process complete lines
-                            if let Some(newline_pos) = self.buffer.find('\n') {
-                                let line = self.buffer[..newline_pos].trim().to_string();
-                                self.buffer = self.buffer[newline_pos + 1..].to_string();
-
-                                if !line.is_empty() {
-                                    return Poll::Ready(Some(Ok(line)));
-                                }
-                                // Continue to next iteration for more lines
-                            } else {
-                                // No complete line yet, continue polling
-                                continue;
-                            }
-                        }
-                        Err(e) => {
-                            return Poll::Ready(Some(Err(ProviderError::StreamError {
-                                operation: "decode_utf8".to_string(),
-                                reason: format!("Invalid UTF-8 in stream: {}", e),
-                            })));
-                        }
+                    if let Some(idle) = self.idle.as_mut() {
+                        let next_deadline = Instant::now() + idle.timeout;
+                        idle.deadline.as_mut().reset(next_deadline);
                     }
+                    self.parser.push(&bytes);
                 }
                 Poll::Ready(Some(Err(e))) => {
                     return Poll::Ready(Some(Err(ProviderError::Http {
@@ -128,30 +237,364 @@ impl Stream for SseStream {
                     })));
                 }
                 Poll::Ready(None) => {
-                    // Check if there's remaining data in buffer
-                    if !self.buffer.is_empty() {
-                        let line = self.buffer.trim().to_string();
-                        self.buffer.clear();
-                        if !line.is_empty() {
-                            return Poll::Ready(Some(Ok(line)));
-                        }
-                    }
-                    return Poll::Ready(None);
+                    // The last event may not be followed by a blank line
+                    self.parser.finish();
+                    self.done = true;
                 }
                 Poll::Pending => {
-                    return Poll::Pending;
+                    let Some(idle) = self.idle.as_mut() else {
+                        return Poll::Pending;
+                    };
+                    if idle.deadline.as_mut().poll(cx).is_pending() {
+                        return Poll::Pending;
+                    }
+                    let stalled_for = idle.timeout;
+                    warn!("No stream data for {:?}; aborting", stalled_for);
+                    self.done = true;
+                    return Poll::Ready(Some(Err(ProviderError::StreamIdle {
+                        operation: "stream_chunk_read".to_string(),
+                        idle_ms: stalled_for.as_millis() as u64,
+                    })));
+                }
+            }
+        }
+    }
+}
+
+/// Incremental Server-Sent Events parser. Bytes may be cut anywhere, even
+/// inside a UTF-8 character, since only whole lines are decoded.
+#[derive(Debug, Default)]
+struct SseParser {
+    /// Bytes of the line not yet ended
+    line: Vec<u8>,
+    /// `data` lines of the event being read
+    data: Vec<String>,
+    /// The last byte was `\r`, so a `\n` right after it ends no new line
+    after_cr: bool,
+    events: VecDeque<String>,
+}
+
+impl SseParser {
+    fn push(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            if std::mem::take(&mut self.after_cr) && byte == b'\n' {
+                continue;
+            }
+            match byte {
+                b'\n' => self.end_line(),
+                b'\r' => {
+                    self.after_cr = true;
+                    self.end_line();
                 }
+                _ => self.line.push(byte),
+            }
+        }
+    }
+
+    /// End of input: complete the last line and event
+    fn finish(&mut self) {
+        if !self.line.is_empty() {
+            self.end_line();
+        }
+        self.dispatch();
+    }
+
+    fn next_event(&mut self) -> Option<String> {
+        self.events.pop_front()
+    }
+
+    fn end_line(&mut self) {
+        let line = match String::from_utf8(std::mem::take(&mut self.line)) {
+            Ok(line) => line,
+            Err(e) => {
+                warn!("Skipping SSE line that is not UTF-8: {}", e);
+                return;
             }
+        };
+        if line.is_empty() {
+            self.dispatch();
+            return;
+        }
+        // Comments, such as `: keep-alive`
+        if line.starts_with(':') {
+            return;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line.as_str(), ""),
+        };
+        match field {
+            "data" => self.data.push(value.to_string()),
+            "event" | "id" | "retry" => {}
+            _ => warn!("Unexpected SSE line format: {}", line),
+        }
+    }
+
+    /// A blank line ends an event; its data lines are joined by `\n`
+    fn dispatch(&mut self) {
+        if !self.data.is_empty() {
+            self.events.push_back(self.data.join("\n"));
+            self.data.clear();
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::models::{ChatCompletionRequest, ChatMessage};
+    use crate::test_server::{MockResponse, MockServer};
+    use crate::{OpenAIClient, OpenAIConfig};
+    use std::time::{Duration, Instant};
+
+    const RECORDED_STREAM: &[u8] = include_bytes!("../tests/fixtures/streams/chat.sse");
+
+    fn events(bytes: &[u8]) -> Vec<String> {
+        let mut parser = SseParser::default();
+        parser.push(bytes);
+        parser.finish();
+        std::iter::from_fn(|| parser.next_event()).collect()
+    }
+
+    #[test]
+    fn test_sse_parsing() {
+        assert_eq!(
+            events(b": keep-alive\n\nevent: message\ndata: {\"a\":\ndata:1}\n\nid: 7\r\ndata: two\r\n\r\ndata: [DONE]"),
+            ["{\"a\":\n1}", "two", "[DONE]"]
+        );
+        // Data without a blank line before the end of input still counts
+        assert_eq!(events(b"data: last\n"), ["last"]);
+        assert!(events(b": only a comment\n\n\n").is_empty());
+    }
+
+    /// Stream `chunks` as a response body would and collect the reply
+    async fn reassemble(chunks: Vec<&[u8]>) -> StreamedResponse {
+        let body = futures::stream::iter(
+            chunks
+                .into_iter()
+                .map(|chunk| Ok(bytes::Bytes::copy_from_slice(chunk)))
+                .collect::<Vec<_>>(),
+        );
+        stream_chat(
+            SseStream::from_bytes(body).parse_events(),
+            CancellationToken::new(),
+            |_| {},
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_recorded_stream_survives_any_chunking() {
+        let whole = reassemble(vec![RECORDED_STREAM]).await;
+        // The malformed event is skipped rather than ending the stream
+        assert_eq!(whole.content, "Héllo wörld 🦊 — done");
+        assert_eq!(whole.finish_reason, FinishReason::Stop);
+
+        for split in 0..=RECORDED_STREAM.len() {
+            let (head, tail) = RECORDED_STREAM.split_at(split);
+            let response = reassemble(vec![head, tail]).await;
+            assert_eq!(response.content, whole.content, "split at {}", split);
+            assert_eq!(
+                response.finish_reason, whole.finish_reason,
+                "split at {}",
+                split
+            );
+        }
+
+        let response = reassemble(RECORDED_STREAM.chunks(1).collect()).await;
+        assert_eq!(response.content, whole.content);
+        assert_eq!(response.finish_reason, whole.finish_reason);
+    }
+
+    fn token_chunk(text: &str, finish: Option<&str>) -> serde_json::Value {
+        serde_json::json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion.chunk",
+            "created": 1,
+            "model": "gpt-4o",
+            "choices": [{"index": 0, "delta": {"content": text}, "finish_reason": finish}]
+        })
+    }
+
+    async fn slow_server(delay: Duration) -> MockServer {
+        let chunks = vec![
+            token_chunk("Hel", None),
+            token_chunk("lo, ", None),
+            token_chunk("wor", None),
+            token_chunk("ld", None),
+            token_chunk("", Some("stop")),
+        ];
+        MockServer::start(vec![MockResponse::sse(chunks).with_delay(delay)]).await
+    }
+
+    fn client(server: &MockServer) -> OpenAIClient {
+        OpenAIClient::new(OpenAIConfig {
+            api_key: "test-key".to_string(),
+            base_url: server.base_url.clone(),
+            max_retries: 0,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn timed_client(
+        server: &MockServer,
+        request_timeout: Duration,
+        stream_idle_timeout: Duration,
+    ) -> OpenAIClient {
+        OpenAIClient::new(OpenAIConfig {
+            api_key: "test-key".to_string(),
+            base_url: server.base_url.clone(),
+            max_retries: 0,
+            request_timeout,
+            stream_idle_timeout: Some(stream_idle_timeout),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn request() -> ChatCompletionRequest {
+        ChatCompletionRequest {
+            model: "gpt-4o".to_string(),
+            messages: vec![ChatMessage::new("user", "Say hello")],
+            max_tokens: None,
+            temperature: None,
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            stop: None,
+            stream: Some(true),
+            user: None,
+            tools: None,
+            response_format: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_chat_runs_to_completion() {
+        let server = slow_server(Duration::ZERO).await;
+        let mut tokens = Vec::new();
+
+        let response = client(&server)
+            .stream_chat(request(), CancellationToken::new(), |text| {
+                tokens.push(text.to_string())
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(response.content, "Hello, world");
+        assert_eq!(tokens, ["Hel", "lo, ", "wor", "ld"]);
+        assert_eq!(response.finish_reason, FinishReason::Stop);
+        assert!(!response.is_partial());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_returns_partial_response_promptly() {
+        // Each event is 300ms apart, so the full reply takes 1.5s
+        let server = slow_server(Duration::from_millis(300)).await;
+        let cancel = CancellationToken::new();
+        let trigger = cancel.clone();
+        let mut received = 0;
+
+        let started = Instant::now();
+        let response = client(&server)
+            .stream_chat(request(), cancel, |_| {
+                received += 1;
+                if received == 2 {
+                    trigger.cancel();
+                }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(response.content, "Hello, ");
+        assert_eq!(response.finish_reason, FinishReason::Cancelled);
+        assert!(response.is_partial());
+        assert!(
+            started.elapsed() < Duration::from_millis(1000),
+            "took {:?}",
+            started.elapsed()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancel_while_waiting_for_next_chunk() {
+        let server = slow_server(Duration::from_secs(5)).await;
+        let cancel = CancellationToken::new();
+        let trigger = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            trigger.cancel();
+        });
+
+        let started = Instant::now();
+        let response = client(&server)
+            .stream_chat(request(), cancel, |_| {})
+            .await
+            .unwrap();
+
+        assert_eq!(response.content, "");
+        assert!(response.is_partial());
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_idle_timeout_fires_when_server_sends_nothing() {
+        let server = MockServer::start(vec![MockResponse::Hang]).await;
+        let client = timed_client(&server, Duration::from_secs(30), Duration::from_millis(200));
+
+        let started = Instant::now();
+        let error = client
+            .stream_chat(request(), CancellationToken::new(), |_| {})
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(error, ProviderError::StreamIdle { idle_ms: 200, .. }),
+            "{:?}",
+            error
+        );
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_idle_timeout_fires_when_stream_stalls() {
+        let server = slow_server(Duration::from_secs(5)).await;
+        let client = timed_client(&server, Duration::from_secs(30), Duration::from_millis(200));
+
+        let started = Instant::now();
+        let error = client
+            .stream_chat(request(), CancellationToken::new(), |_| {})
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(error, ProviderError::StreamIdle { .. }),
+            "{:?}",
+            error
+        );
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
 
     #[tokio::test]
-    async fn test_sse_parsing() {
-        // This would test SSE parsing with mock data
-        // Implementation details would depend on your testing strategy
+    async fn test_slow_steady_stream_is_not_cut_off() {
+        // Five events 150ms apart: each gap is within the idle timeout, and
+        // the stream as a whole outlasts the request timeout
+        let server = slow_server(Duration::from_millis(150)).await;
+        let client = timed_client(
+            &server,
+            Duration::from_millis(300),
+            Duration::from_millis(500),
+        );
+
+        let response = client
+            .stream_chat(request(), CancellationToken::new(), |_| {})
+            .await
+            .unwrap();
+
+        assert_eq!(response.content, "Hello, world");
+        assert_eq!(response.finish_reason, FinishReason::Stop);
     }
 }
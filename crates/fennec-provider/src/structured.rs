@@ -0,0 +1,303 @@
+//! JSON replies constrained by a schema.
+//!
+//! [`crate::OpenAIClient::chat_structured`] derives a JSON schema from the
+//! target type, asks for it through `response_format` on models that
+//! support structured outputs and through the prompt otherwise, then
+//! validates the reply here. A reply that fails validation is sent back
+//! once with the errors so the model can repair it.
+
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::error::ProviderError;
+use crate::models::{ChatMessage, JsonSchemaFormat, ResponseFormat};
+
+/// Model families accepting `json_schema` response formats
+const JSON_SCHEMA_MODEL_PREFIXES: &[&str] = &["gpt-4o", "gpt-4.1", "gpt-5", "o1", "o3", "o4"];
+
+/// A reply parsed into `T`, with the text it was parsed from
+#[derive(Debug, Clone)]
+pub struct StructuredResponse<T> {
+    pub value: T,
+    pub raw: String,
+    /// Whether the first reply was invalid and a repair round trip was made
+    pub repaired: bool,
+}
+
+/// Whether `model` can be sent a `json_schema` response format
+pub fn supports_json_schema(model: &str) -> bool {
+    JSON_SCHEMA_MODEL_PREFIXES
+        .iter()
+        .any(|prefix| model.starts_with(prefix))
+}
+
+/// Schema of `T` with the name used to refer to it
+pub(crate) fn schema_for<T: JsonSchema>() -> (String, Value) {
+    let schema = schemars::schema_for!(T);
+    let name: String = T::schema_name()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    (
+        name,
+        serde_json::to_value(schema).unwrap_or(Value::Bool(true)),
+    )
+}
+
+pub(crate) fn response_format(name: &str, schema: &Value) -> ResponseFormat {
+    ResponseFormat::JsonSchema {
+        json_schema: JsonSchemaFormat {
+            name: name.to_string(),
+            schema: schema.clone(),
+            strict: false,
+        },
+    }
+}
+
+/// System message describing the schema, for models without structured
+/// output support
+pub(crate) fn schema_prompt(schema: &Value) -> ChatMessage {
+    ChatMessage::new(
+        "system",
+        format!(
+            "Reply with a single JSON value matching this JSON schema and nothing else:\n{}",
+            serde_json::to_string_pretty(schema).unwrap_or_default()
+        ),
+    )
+}
+
+/// Follow-up asking the model to fix a reply that failed validation
+pub(crate) fn repair_prompt(errors: &[String]) -> ChatMessage {
+    let mut content = "That reply does not match the required JSON schema:\n".to_string();
+    for error in errors {
+        content.push_str(&format!("- {}\n", error));
+    }
+    content.push_str("Reply again with only the corrected JSON.");
+    ChatMessage::new("user", content)
+}
+
+/// Parse `text` as `T`, returning every problem found when it does not fit
+/// `schema`
+pub fn parse_structured<T: DeserializeOwned>(
+    text: &str,
+    schema: &Value,
+) -> std::result::Result<T, Vec<String>> {
+    let json = extract_json(text);
+    let value: Value =
+        serde_json::from_str(json).map_err(|e| vec![format!("reply is not valid JSON: {}", e)])?;
+
+    let mut errors = Vec::new();
+    validate(schema, schema, &value, "$", &mut errors);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    serde_json::from_value(value).map_err(|e| vec![e.to_string()])
+}
+
+/// The JSON inside `text`, without surrounding prose or code fences
+fn extract_json(text: &str) -> &str {
+    let trimmed = text.trim();
+    let start = trimmed.find(['{', '[']);
+    let end = trimmed.rfind(['}', ']']);
+    match (start, end) {
+        (Some(start), Some(end)) if start < end => &trimmed[start..=end],
+        _ => trimmed,
+    }
+}
+
+/// Check `value` against the subset of JSON schema that generated schemas
+/// use: `$ref`, `type`, `enum`, `required`, `properties`, `items`, `anyOf`,
+/// `oneOf` and `allOf`
+pub fn validate(schema: &Value, root: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    let schema = match schema {
+        Value::Bool(true) => return,
+        Value::Bool(false) => {
+            errors.push(format!("{}: no value is allowed here", path));
+            return;
+        }
+        Value::Object(schema) => schema,
+        _ => return,
+    };
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        match resolve(root, reference) {
+            Some(target) => validate(target, root, value, path, errors),
+            None => errors.push(format!("{}: unknown schema reference {}", path, reference)),
+        }
+    }
+
+    for branch in schema
+        .get("allOf")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        validate(branch, root, value, path, errors);
+    }
+    for key in ["anyOf", "oneOf"] {
+        if let Some(branches) = schema.get(key).and_then(Value::as_array) {
+            let matches = branches.iter().any(|branch| {
+                let mut branch_errors = Vec::new();
+                validate(branch, root, value, path, &mut branch_errors);
+                branch_errors.is_empty()
+            });
+            if !matches {
+                errors.push(format!("{}: matches none of the allowed shapes", path));
+            }
+        }
+    }
+
+    if let Some(expected) = schema.get("type") {
+        let allowed: Vec<&str> = match expected {
+            Value::String(kind) => vec![kind.as_str()],
+            Value::Array(kinds) => kinds.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|kind| has_type(value, kind)) {
+            errors.push(format!(
+                "{}: expected {}, found {}",
+                path,
+                allowed.join(" or "),
+                type_name(value)
+            ));
+            return;
+        }
+    }
+
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        if !options.contains(value) {
+            errors.push(format!(
+                "{}: {} is not one of {}",
+                path,
+                value,
+                Value::Array(options.clone())
+            ));
+        }
+    }
+
+    if let Value::Object(object) = value {
+        for field in schema
+            .get("required")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            if let Some(field) = field.as_str() {
+                if !object.contains_key(field) {
+                    errors.push(format!("{}: missing required field '{}'", path, field));
+                }
+            }
+        }
+        if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+            for (field, field_value) in object {
+                if let Some(field_schema) = properties.get(field) {
+                    validate(
+                        field_schema,
+                        root,
+                        field_value,
+                        &format!("{}.{}", path, field),
+                        errors,
+                    );
+                }
+            }
+        }
+    }
+
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (index, item) in items.iter().enumerate() {
+            validate(
+                item_schema,
+                root,
+                item,
+                &format!("{}[{}]", path, index),
+                errors,
+            );
+        }
+    }
+}
+
+fn resolve<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    let pointer = reference.strip_prefix('#')?;
+    root.pointer(pointer)
+}
+
+fn has_type(value: &Value, kind: &str) -> bool {
+    match kind {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+pub(crate) fn unrecoverable(name: &str, raw: &str, errors: &[String]) -> ProviderError {
+    ProviderError::ResponseParsingFailed {
+        expected: format!("JSON matching the {} schema", name),
+        actual: format!("{} ({})", raw, errors.join("; ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, JsonSchema)]
+    struct Step {
+        title: String,
+        priority: Priority,
+        estimate_minutes: Option<u32>,
+    }
+
+    #[derive(Debug, Deserialize, JsonSchema)]
+    #[serde(rename_all = "lowercase")]
+    enum Priority {
+        Low,
+        High,
+    }
+
+    #[test]
+    fn test_validation_reports_each_problem() {
+        let (name, schema) = schema_for::<Vec<Step>>();
+        assert_eq!(name, "Array_of_Step");
+
+        let errors = parse_structured::<Vec<Step>>(
+            r#"[{"title": "a", "priority": "low"}, {"priority": "urgent", "estimate_minutes": "ten"}]"#,
+            &schema,
+        )
+        .unwrap_err();
+        assert_eq!(errors.len(), 3, "{:?}", errors);
+        assert!(errors
+            .iter()
+            .any(|e| e.contains("$[1]: missing required field 'title'")));
+        assert!(errors.iter().any(|e| e.starts_with("$[1].priority")));
+        assert!(errors
+            .iter()
+            .any(|e| e.starts_with("$[1].estimate_minutes")));
+
+        let steps = parse_structured::<Vec<Step>>(
+            "Here you go:\n```json\n[{\"title\": \"a\", \"priority\": \"high\"}]\n```",
+            &schema,
+        )
+        .unwrap();
+        assert_eq!(steps[0].title, "a");
+        assert!(matches!(steps[0].priority, Priority::High));
+    }
+}
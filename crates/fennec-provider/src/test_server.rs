@@ -0,0 +1,170 @@
+//! Minimal HTTP server replaying recorded provider responses in tests.
+//!
+//! Each accepted connection serves one request with the next queued
+//! response, then closes. Request bodies are kept so tests can check what
+//! was sent.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// A recorded response
+#[derive(Debug, Clone)]
+pub enum MockResponse {
+    /// A complete JSON body with the given status
+    Json { status: u16, body: String },
+    /// Server-sent events, written one chunk at a time with `delay` before
+    /// each
+    Sse {
+        events: Vec<String>,
+        delay: Duration,
+    },
+    /// Read the request and never answer, keeping the connection open
+    Hang,
+}
+
+impl MockResponse {
+    pub fn json(body: serde_json::Value) -> Self {
+        Self::Json {
+            status: 200,
+            body: body.to_string(),
+        }
+    }
+
+    /// Stream each value as a `data:` event, followed by `[DONE]`
+    pub fn sse(chunks: Vec<serde_json::Value>) -> Self {
+        let mut events: Vec<String> = chunks
+            .into_iter()
+            .map(|chunk| format!("data: {}\n\n", chunk))
+            .collect();
+        events.push("data: [DONE]\n\n".to_string());
+        Self::Sse {
+            events,
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Wait `delay` before each streamed event
+    pub fn with_delay(self, delay: Duration) -> Self {
+        match self {
+            Self::Sse { events, .. } => Self::Sse { events, delay },
+            json => json,
+        }
+    }
+}
+
+pub struct MockServer {
+    pub base_url: String,
+    requests: Arc<Mutex<Vec<serde_json::Value>>>,
+}
+
+impl MockServer {
+    /// Serve `responses` in order
+    pub async fn start(responses: Vec<MockResponse>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let queue = Arc::new(Mutex::new(VecDeque::from(responses)));
+
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let recorded = recorded.clone();
+                let queue = queue.clone();
+                tokio::spawn(async move {
+                    let _ = serve(socket, recorded, queue).await;
+                });
+            }
+        });
+
+        Self { base_url, requests }
+    }
+
+    /// JSON bodies of the requests received so far
+    pub fn requests(&self) -> Vec<serde_json::Value> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn serve(
+    mut socket: TcpStream,
+    recorded: Arc<Mutex<Vec<serde_json::Value>>>,
+    queue: Arc<Mutex<VecDeque<MockResponse>>>,
+) -> std::io::Result<()> {
+    let body = read_request_body(&mut socket).await?;
+    if let Ok(json) = serde_json::from_slice(&body) {
+        recorded.lock().unwrap().push(json);
+    }
+
+    let response = queue.lock().unwrap().pop_front();
+    match response {
+        Some(MockResponse::Json { status, body }) => {
+            let head = format!(
+                "HTTP/1.1 {} MOCK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                status,
+                body.len()
+            );
+            socket.write_all(head.as_bytes()).await?;
+            socket.write_all(body.as_bytes()).await?;
+        }
+        Some(MockResponse::Sse { events, delay }) => {
+            socket
+                .write_all(
+                    b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n",
+                )
+                .await?;
+            for event in events {
+                if !delay.is_zero() {
+                    tokio::time::sleep(delay).await;
+                }
+                socket
+                    .write_all(format!("{:x}\r\n{}\r\n", event.len(), event).as_bytes())
+                    .await?;
+                socket.flush().await?;
+            }
+            socket.write_all(b"0\r\n\r\n").await?;
+        }
+        Some(MockResponse::Hang) => {
+            std::future::pending::<()>().await;
+        }
+        None => {
+            socket
+                .write_all(b"HTTP/1.1 500 MOCK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                .await?;
+        }
+    }
+    socket.shutdown().await
+}
+
+async fn read_request_body(socket: &mut TcpStream) -> std::io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+    let header_end = loop {
+        let n = socket.read(&mut buf).await?;
+        if n == 0 {
+            return Ok(Vec::new());
+        }
+        data.extend_from_slice(&buf[..n]);
+        if let Some(pos) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let headers = String::from_utf8_lossy(&data[..header_end]).to_lowercase();
+    let content_length = headers
+        .lines()
+        .find_map(|line| line.strip_prefix("content-length:"))
+        .and_then(|value| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+
+    while data.len() < header_end + content_length {
+        let n = socket.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        data.extend_from_slice(&buf[..n]);
+    }
+    Ok(data[header_end..].to_vec())
+}
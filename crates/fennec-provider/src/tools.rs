@@ -0,0 +1,241 @@
+//! Tool (function) calling on top of chat completions.
+//!
+//! The model answers either with text or with tool calls. Callers run the
+//! requested tools and send the results back as `tool` messages until the
+//! model produces a final answer; [`crate::OpenAIClient::run_tool_loop`]
+//! drives that exchange with a [`ToolHandler`].
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::error::{ProviderError, Result};
+use crate::models::{ChatCompletionChunk, ChatMessage, FunctionCall, ToolCall, Usage};
+
+pub use fennec_core::provider::ToolDefinition;
+
+/// Round trips `run_tool_loop` allows before giving up on a final answer
+pub const DEFAULT_MAX_TOOL_ROUNDS: usize = 8;
+
+/// A tool call with its arguments parsed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolInvocation {
+    pub id: String,
+    pub name: String,
+    pub arguments: serde_json::Value,
+}
+
+/// Reply to a chat request that offered tools
+#[derive(Debug, Clone)]
+pub struct ToolAwareResponse {
+    /// Text of the reply; usually empty when tools were called
+    pub content: String,
+    pub tool_calls: Vec<ToolInvocation>,
+    pub finish_reason: Option<String>,
+    pub usage: Option<Usage>,
+    /// The assistant message as it should be appended to the conversation
+    pub message: ChatMessage,
+}
+
+impl ToolAwareResponse {
+    pub(crate) fn from_message(
+        message: ChatMessage,
+        finish_reason: Option<String>,
+        usage: Option<Usage>,
+    ) -> Result<Self> {
+        let tool_calls = parse_tool_calls(message.tool_calls.as_deref().unwrap_or_default())?;
+        Ok(Self {
+            content: message.content.clone(),
+            tool_calls,
+            finish_reason,
+            usage,
+            message,
+        })
+    }
+
+    /// Whether the model is waiting on tool results
+    pub fn wants_tools(&self) -> bool {
+        !self.tool_calls.is_empty()
+    }
+}
+
+/// Runs the tools the model asks for
+#[async_trait]
+pub trait ToolHandler: Send + Sync {
+    /// Execute `call` and return the content sent back to the model.
+    /// Failures should be described in the returned text so the model can
+    /// react to them.
+    async fn call_tool(&self, call: &ToolInvocation) -> String;
+}
+
+/// Parse the JSON-encoded arguments of each call
+pub fn parse_tool_calls(calls: &[ToolCall]) -> Result<Vec<ToolInvocation>> {
+    calls
+        .iter()
+        .map(|call| {
+            let raw = call.function.arguments.trim();
+            let arguments = if raw.is_empty() {
+                serde_json::Value::Object(Default::default())
+            } else {
+                serde_json::from_str(raw).map_err(|e| ProviderError::ResponseParsingFailed {
+                    expected: format!("JSON arguments for tool '{}'", call.function.name),
+                    actual: format!("{} ({})", raw, e),
+                })?
+            };
+            Ok(ToolInvocation {
+                id: call.id.clone(),
+                name: call.function.name.clone(),
+                arguments,
+            })
+        })
+        .collect()
+}
+
+/// Folds streamed chunks back into a complete reply, joining the tool call
+/// fragments that share an index
+#[derive(Debug, Default)]
+pub struct StreamAccumulator {
+    content: String,
+    tool_calls: BTreeMap<u32, ToolCall>,
+    finish_reason: Option<String>,
+}
+
+impl StreamAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a chunk, returning the text it contributed, if any
+    pub fn push(&mut self, chunk: &ChatCompletionChunk) -> Option<String> {
+        let choice = chunk.choices.first()?;
+        if let Some(reason) = &choice.finish_reason {
+            self.finish_reason = Some(reason.clone());
+        }
+
+        for delta in choice.delta.tool_calls.iter().flatten() {
+            let call = self
+                .tool_calls
+                .entry(delta.index)
+                .or_insert_with(|| ToolCall {
+                    id: String::new(),
+                    kind: "function".to_string(),
+                    function: FunctionCall {
+                        name: String::new(),
+                        arguments: String::new(),
+                    },
+                });
+            if let Some(id) = &delta.id {
+                call.id.push_str(id);
+            }
+            if let Some(kind) = &delta.kind {
+                call.kind = kind.clone();
+            }
+            if let Some(function) = &delta.function {
+                if let Some(name) = &function.name {
+                    call.function.name.push_str(name);
+                }
+                if let Some(arguments) = &function.arguments {
+                    call.function.arguments.push_str(arguments);
+                }
+            }
+        }
+
+        let text = choice
+            .delta
+            .content
+            .clone()
+            .filter(|text| !text.is_empty())?;
+        self.content.push_str(&text);
+        Some(text)
+    }
+
+    /// Text received so far
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// The reply received so far
+    pub fn finish(self) -> Result<ToolAwareResponse> {
+        let tool_calls: Vec<ToolCall> = self.tool_calls.into_values().collect();
+        let message = ChatMessage {
+            tool_calls: (!tool_calls.is_empty()).then_some(tool_calls),
+            ..ChatMessage::new("assistant", self.content)
+        };
+        ToolAwareResponse::from_message(message, self.finish_reason, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(delta: serde_json::Value, finish_reason: Option<&str>) -> ChatCompletionChunk {
+        serde_json::from_value(serde_json::json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion.chunk",
+            "created": 1,
+            "model": "gpt-4o",
+            "choices": [{"index": 0, "delta": delta, "finish_reason": finish_reason}]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_accumulator_joins_interleaved_tool_call_fragments() {
+        let mut acc = StreamAccumulator::new();
+        let chunks = [
+            serde_json::json!({"role": "assistant", "tool_calls": [
+                {"index": 0, "id": "call_a", "type": "function",
+                 "function": {"name": "search", "arguments": ""}}
+            ]}),
+            serde_json::json!({"tool_calls": [
+                {"index": 1, "id": "call_b", "type": "function",
+                 "function": {"name": "plan", "arguments": "{\"task\":"}}
+            ]}),
+            serde_json::json!({"tool_calls": [
+                {"index": 0, "function": {"arguments": "{\"query\": \"fn main\"}"}}
+            ]}),
+            serde_json::json!({"tool_calls": [
+                {"index": 1, "function": {"arguments": " \"ship\"}"}}
+            ]}),
+        ];
+        for delta in chunks {
+            assert_eq!(acc.push(&chunk(delta, None)), None);
+        }
+        acc.push(&chunk(serde_json::json!({}), Some("tool_calls")));
+
+        let response = acc.finish().unwrap();
+        assert!(response.wants_tools());
+        assert_eq!(response.finish_reason.as_deref(), Some("tool_calls"));
+        assert_eq!(
+            response.tool_calls,
+            vec![
+                ToolInvocation {
+                    id: "call_a".to_string(),
+                    name: "search".to_string(),
+                    arguments: serde_json::json!({"query": "fn main"}),
+                },
+                ToolInvocation {
+                    id: "call_b".to_string(),
+                    name: "plan".to_string(),
+                    arguments: serde_json::json!({"task": "ship"}),
+                },
+            ]
+        );
+        assert_eq!(response.message.tool_calls.as_ref().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_invalid_arguments_are_reported() {
+        let calls = vec![ToolCall {
+            id: "call_a".to_string(),
+            kind: "function".to_string(),
+            function: FunctionCall {
+                name: "search".to_string(),
+                arguments: "{\"query\": ".to_string(),
+            },
+        }];
+        let error = parse_tool_calls(&calls).unwrap_err();
+        assert!(error.to_string().contains("search"), "{}", error);
+    }
+}
@@ -0,0 +1,479 @@
+//! Token usage and cost accounting per session.
+//!
+//! [`UsageTrackingClient`] wraps a provider for one session and records the
+//! usage of every completion and stream into a shared [`UsageTracker`].
+//! Streams rarely report usage, so their counts are estimated from the text
+//! sent and received.
+
+use fennec_core::config::{ModelPrice, UsageConfig};
+use fennec_core::provider::{
+    AttachmentSupport, ProviderClient, ProviderMessage, ProviderRequest, ProviderResponse, Usage,
+};
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::error::{ProviderError, Result};
+
+/// Tokens added per message for role and formatting markers
+const MESSAGE_OVERHEAD_TOKENS: u32 = 4;
+
+/// Rough token count of `text`, at about four characters per token
+pub fn estimate_tokens(text: &str) -> u32 {
+    text.chars().count().div_ceil(4) as u32
+}
+
+/// Rough prompt token count of `messages`
+pub fn estimate_prompt_tokens(messages: &[ProviderMessage]) -> u32 {
+    messages
+        .iter()
+        .map(|message| estimate_tokens(&message.content) + MESSAGE_OVERHEAD_TOKENS)
+        .sum()
+}
+
+/// Usage of one model within a session
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelUsage {
+    pub model: String,
+    pub requests: u64,
+    /// Requests whose counts were estimated rather than reported
+    pub estimated_requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// `None` when no price is configured for the model
+    pub cost_usd: Option<f64>,
+}
+
+/// Where a session stands against the configured budgets
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum BudgetStatus {
+    WithinBudget,
+    SoftLimitReached { spent_usd: f64, limit_usd: f64 },
+    HardLimitReached { spent_usd: f64, limit_usd: f64 },
+}
+
+/// Accumulated usage and cost of a session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageReport {
+    pub session_id: Uuid,
+    /// Per-model usage, sorted by model name
+    pub models: Vec<ModelUsage>,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    /// Cost of the priced models
+    pub cost_usd: f64,
+    /// Models used without a configured price
+    pub unpriced_models: Vec<String>,
+    pub budget: BudgetStatus,
+}
+
+impl UsageReport {
+    /// Compact running total for a status bar, e.g. `12.4k tok · $0.0310`
+    pub fn status_line(&self) -> String {
+        let tokens = if self.total_tokens >= 1000 {
+            format!("{:.1}k tok", self.total_tokens as f64 / 1000.0)
+        } else {
+            format!("{} tok", self.total_tokens)
+        };
+        let mut line = format!("{} · ${:.4}", tokens, self.cost_usd);
+        if !self.unpriced_models.is_empty() {
+            line.push('+');
+        }
+        match self.budget {
+            BudgetStatus::WithinBudget => {}
+            BudgetStatus::SoftLimitReached { .. } => line.push_str(" (over budget)"),
+            BudgetStatus::HardLimitReached { .. } => line.push_str(" (budget exhausted)"),
+        }
+        line
+    }
+}
+
+/// Usage of every session, priced with the configured table
+#[derive(Debug, Default)]
+pub struct UsageTracker {
+    config: UsageConfig,
+    sessions: Mutex<HashMap<Uuid, BTreeMap<String, ModelUsage>>>,
+}
+
+impl UsageTracker {
+    pub fn new(config: UsageConfig) -> Self {
+        Self {
+            config,
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Add `usage` of `model` to the session, returning where the session
+    /// now stands against its budgets
+    pub fn record(
+        &self,
+        session_id: Uuid,
+        model: &str,
+        usage: &Usage,
+        estimated: bool,
+    ) -> BudgetStatus {
+        let before = self.budget_status(self.session_cost(session_id));
+        {
+            let mut sessions = self.sessions.lock().unwrap();
+            let entry = sessions
+                .entry(session_id)
+                .or_default()
+                .entry(model.to_string())
+                .or_insert_with(|| ModelUsage {
+                    model: model.to_string(),
+                    ..Default::default()
+                });
+            entry.requests += 1;
+            if estimated {
+                entry.estimated_requests += 1;
+            }
+            entry.prompt_tokens += u64::from(usage.prompt_tokens);
+            entry.completion_tokens += u64::from(usage.completion_tokens);
+            entry.cost_usd = self
+                .config
+                .price_for(model)
+                .map(|price| cost(&price, entry.prompt_tokens, entry.completion_tokens));
+        }
+        debug!(
+            "Recorded {} prompt and {} completion tokens for {} in session {}",
+            usage.prompt_tokens, usage.completion_tokens, model, session_id
+        );
+
+        let after = self.budget_status(self.session_cost(session_id));
+        if after != before {
+            if let BudgetStatus::SoftLimitReached {
+                spent_usd,
+                limit_usd,
+            } = after
+            {
+                warn!(
+                    "Session {} has spent ${:.4}, over its ${:.2} soft budget",
+                    session_id, spent_usd, limit_usd
+                );
+            }
+        }
+        after
+    }
+
+    /// Refuse to go on once the session has reached its hard budget
+    pub fn check_budget(&self, session_id: Uuid) -> Result<()> {
+        match self.budget_status(self.session_cost(session_id)) {
+            BudgetStatus::HardLimitReached {
+                spent_usd,
+                limit_usd,
+            } => Err(ProviderError::BudgetExceeded {
+                spent_usd,
+                limit_usd,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn get_session_usage(&self, session_id: Uuid) -> UsageReport {
+        let models: Vec<ModelUsage> = self
+            .sessions
+            .lock()
+            .unwrap()
+            .get(&session_id)
+            .map(|models| models.values().cloned().collect())
+            .unwrap_or_default();
+
+        let prompt_tokens = models.iter().map(|m| m.prompt_tokens).sum();
+        let completion_tokens = models.iter().map(|m| m.completion_tokens).sum();
+        let cost_usd = models.iter().filter_map(|m| m.cost_usd).sum();
+        UsageReport {
+            session_id,
+            requests: models.iter().map(|m| m.requests).sum(),
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+            cost_usd,
+            unpriced_models: models
+                .iter()
+                .filter(|m| m.cost_usd.is_none())
+                .map(|m| m.model.clone())
+                .collect(),
+            budget: self.budget_status(cost_usd),
+            models,
+        }
+    }
+
+    fn session_cost(&self, session_id: Uuid) -> f64 {
+        self.sessions
+            .lock()
+            .unwrap()
+            .get(&session_id)
+            .map(|models| models.values().filter_map(|m| m.cost_usd).sum())
+            .unwrap_or(0.0)
+    }
+
+    fn budget_status(&self, spent_usd: f64) -> BudgetStatus {
+        match (self.config.hard_budget_usd, self.config.soft_budget_usd) {
+            (Some(limit_usd), _) if spent_usd >= limit_usd => BudgetStatus::HardLimitReached {
+                spent_usd,
+                limit_usd,
+            },
+            (_, Some(limit_usd)) if spent_usd >= limit_usd => BudgetStatus::SoftLimitReached {
+                spent_usd,
+                limit_usd,
+            },
+            _ => BudgetStatus::WithinBudget,
+        }
+    }
+}
+
+fn cost(price: &ModelPrice, prompt_tokens: u64, completion_tokens: u64) -> f64 {
+    (prompt_tokens as f64 * price.prompt_per_million
+        + completion_tokens as f64 * price.completion_per_million)
+        / 1_000_000.0
+}
+
+/// Provider wrapper recording the usage of one session
+pub struct UsageTrackingClient {
+    inner: Arc<dyn ProviderClient>,
+    tracker: Arc<UsageTracker>,
+    session_id: Uuid,
+}
+
+impl UsageTrackingClient {
+    pub fn new(
+        inner: Arc<dyn ProviderClient>,
+        tracker: Arc<UsageTracker>,
+        session_id: Uuid,
+    ) -> Self {
+        Self {
+            inner,
+            tracker,
+            session_id,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ProviderClient for UsageTrackingClient {
+    async fn complete(&self, request: ProviderRequest) -> fennec_core::Result<ProviderResponse> {
+        self.tracker.check_budget(self.session_id)?;
+
+        let model = request.model.clone();
+        let prompt_tokens = estimate_prompt_tokens(&request.messages);
+        let response = self.inner.complete(request).await?;
+
+        match &response.usage {
+            Some(usage) => {
+                self.tracker.record(self.session_id, &model, usage, false);
+            }
+            None => {
+                let completion_tokens = estimate_tokens(&response.content);
+                let usage = Usage {
+                    prompt_tokens,
+                    completion_tokens,
+                    total_tokens: prompt_tokens + completion_tokens,
+                };
+                self.tracker.record(self.session_id, &model, &usage, true);
+            }
+        }
+        Ok(response)
+    }
+
+    async fn stream(
+        &self,
+        request: ProviderRequest,
+    ) -> fennec_core::Result<Box<dyn Stream<Item = fennec_core::Result<String>> + Unpin + Send>>
+    {
+        self.tracker.check_budget(self.session_id)?;
+
+        let model = request.model.clone();
+        let prompt_tokens = estimate_prompt_tokens(&request.messages);
+        let inner = self.inner.stream(request).await?;
+        Ok(Box::new(MeteredStream {
+            inner,
+            tracker: self.tracker.clone(),
+            session_id: self.session_id,
+            model,
+            prompt_tokens,
+            completion: String::new(),
+        }))
+    }
+
+    fn attachment_support(&self) -> AttachmentSupport {
+        self.inner.attachment_support()
+    }
+}
+
+/// Collects streamed text and records the estimated usage once the stream
+/// is finished or dropped
+struct MeteredStream {
+    inner: Box<dyn Stream<Item = fennec_core::Result<String>> + Unpin + Send>,
+    tracker: Arc<UsageTracker>,
+    session_id: Uuid,
+    model: String,
+    prompt_tokens: u32,
+    completion: String,
+}
+
+impl Stream for MeteredStream {
+    type Item = fennec_core::Result<String>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let poll = Pin::new(&mut self.inner).poll_next(cx);
+        if let Poll::Ready(Some(Ok(text))) = &poll {
+            let text = text.clone();
+            self.completion.push_str(&text);
+        }
+        poll
+    }
+}
+
+impl Drop for MeteredStream {
+    fn drop(&mut self) {
+        let completion_tokens = estimate_tokens(&self.completion);
+        let usage = Usage {
+            prompt_tokens: self.prompt_tokens,
+            completion_tokens,
+            total_tokens: self.prompt_tokens + completion_tokens,
+        };
+        self.tracker
+            .record(self.session_id, &self.model, &usage, true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockProviderClient;
+    use futures::StreamExt;
+
+    fn tracker(soft: Option<f64>, hard: Option<f64>) -> Arc<UsageTracker> {
+        Arc::new(UsageTracker::new(UsageConfig {
+            prices: HashMap::from([(
+                "mock".to_string(),
+                ModelPrice {
+                    prompt_per_million: 1_000.0,
+                    completion_per_million: 2_000.0,
+                },
+            )]),
+            soft_budget_usd: soft,
+            hard_budget_usd: hard,
+        }))
+    }
+
+    fn request(model: &str, content: &str) -> ProviderRequest {
+        ProviderRequest {
+            id: Uuid::new_v4(),
+            messages: vec![ProviderMessage {
+                role: "user".to_string(),
+                content: content.to_string(),
+            }],
+            model: model.to_string(),
+            stream: false,
+            temperature: None,
+            max_tokens: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reported_usage_is_accumulated_and_priced() {
+        let tracker = tracker(None, None);
+        let session_id = Uuid::new_v4();
+        let client = UsageTrackingClient::new(
+            Arc::new(MockProviderClient::default()),
+            tracker.clone(),
+            session_id,
+        );
+
+        // The mock reports 10 prompt tokens per message and 12 completion
+        client.complete(request("mock", "hello")).await.unwrap();
+        client.complete(request("mock", "again")).await.unwrap();
+        client.complete(request("unpriced", "hi")).await.unwrap();
+
+        let report = tracker.get_session_usage(session_id);
+        assert_eq!(report.requests, 3);
+        assert_eq!(report.prompt_tokens, 30);
+        assert_eq!(report.completion_tokens, 36);
+        assert_eq!(report.total_tokens, 66);
+        assert_eq!(report.unpriced_models, vec!["unpriced".to_string()]);
+
+        let mock = &report.models[0];
+        assert_eq!(mock.model, "mock");
+        assert_eq!(mock.estimated_requests, 0);
+        // 20 * $1000/M + 24 * $2000/M
+        assert!(
+            (report.cost_usd - 0.068).abs() < 1e-9,
+            "{}",
+            report.cost_usd
+        );
+        assert_eq!(report.status_line(), "66 tok · $0.0680+");
+
+        // Other sessions are tracked separately
+        assert_eq!(tracker.get_session_usage(Uuid::new_v4()).requests, 0);
+    }
+
+    #[tokio::test]
+    async fn test_stream_usage_is_estimated() {
+        let tracker = tracker(None, None);
+        let session_id = Uuid::new_v4();
+        let client = UsageTrackingClient::new(
+            Arc::new(MockProviderClient::default()),
+            tracker.clone(),
+            session_id,
+        );
+
+        let mut stream = client.stream(request("mock", "abcdefgh")).await.unwrap();
+        let mut text = String::new();
+        while let Some(chunk) = stream.next().await {
+            text.push_str(&chunk.unwrap());
+        }
+        drop(stream);
+
+        let report = tracker.get_session_usage(session_id);
+        assert_eq!(report.requests, 1);
+        assert_eq!(report.models[0].estimated_requests, 1);
+        assert_eq!(report.prompt_tokens, 2 + u64::from(MESSAGE_OVERHEAD_TOKENS));
+        assert_eq!(report.completion_tokens, u64::from(estimate_tokens(&text)));
+    }
+
+    #[tokio::test]
+    async fn test_budget_warns_then_stops() {
+        // Each mock request costs $0.034
+        let tracker = tracker(Some(0.05), Some(0.1));
+        let session_id = Uuid::new_v4();
+        let client = UsageTrackingClient::new(
+            Arc::new(MockProviderClient::default()),
+            tracker.clone(),
+            session_id,
+        );
+
+        client.complete(request("mock", "one")).await.unwrap();
+        assert_eq!(
+            tracker.get_session_usage(session_id).budget,
+            BudgetStatus::WithinBudget
+        );
+
+        client.complete(request("mock", "two")).await.unwrap();
+        assert!(matches!(
+            tracker.get_session_usage(session_id).budget,
+            BudgetStatus::SoftLimitReached { .. }
+        ));
+
+        client.complete(request("mock", "three")).await.unwrap();
+        let report = tracker.get_session_usage(session_id);
+        assert!(matches!(
+            report.budget,
+            BudgetStatus::HardLimitReached { .. }
+        ));
+        assert!(report.status_line().ends_with("(budget exhausted)"));
+
+        let refused = client.complete(request("mock", "four")).await;
+        assert!(refused.is_err());
+        assert!(client.stream(request("mock", "five")).await.is_err());
+        assert_eq!(tracker.get_session_usage(session_id).requests, 3);
+    }
+}
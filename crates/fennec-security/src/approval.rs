@@ -1,8 +1,14 @@
+use crate::enrichment::{format_size, shell_command_targets, ApprovalEnrichment};
 use crate::sandbox::SandboxPolicy;
 use anyhow::Result;
-use fennec_core::command::{CommandPreview, PreviewAction};
+use async_trait::async_trait;
+use fennec_core::command::{Capability, CommandPreview, PreviewAction};
+use fennec_core::config::{ApprovalPolicies, ApprovalPolicy};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 
 /// Approval status for operations requiring user consent
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -20,6 +26,30 @@ pub struct ApprovalRequest {
     pub description: String,
     pub risk_level: RiskLevel,
     pub details: Vec<String>,
+    /// Unified diff of the changes the operation makes, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+    /// Paths the operation touches, relative to the workspace, which
+    /// [`ApprovalEnrichment`] describes in the details
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<PathBuf>,
+}
+
+/// Answer given to an approval prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ApprovalDecision {
+    Approve,
+    /// Approve this and later requests for the same operation until the
+    /// session ends
+    ApproveForSession,
+    Deny,
+}
+
+/// Asks the user to decide on an approval request, e.g. through a dialog in
+/// the TUI instead of the blocking terminal prompt
+#[async_trait]
+pub trait ApprovalPrompt: Send + Sync {
+    async fn prompt(&self, request: &ApprovalRequest) -> Result<ApprovalDecision>;
 }
 
 /// Risk level classification for operations
@@ -42,44 +72,211 @@ impl std::fmt::Display for RiskLevel {
     }
 }
 
+/// Entry of an approval policy table that decided a request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicySource {
+    RiskLevel(RiskLevel),
+    Capability(Capability),
+}
+
+impl std::fmt::Display for PolicySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolicySource::RiskLevel(level) => write!(f, "risk level {}", level),
+            PolicySource::Capability(capability) => write!(f, "capability {:?}", capability),
+        }
+    }
+}
+
+/// Policy of `policies` for an operation of `risk_level` needing
+/// `capabilities`, and the entry it came from
+///
+/// Capability entries take precedence over the risk level, the strictest
+/// one winning. Critical operations are prompted for whatever the table
+/// says, so a table built in code can't approve them either.
+pub fn select_approval_policy(
+    policies: &ApprovalPolicies,
+    risk_level: &RiskLevel,
+    capabilities: &[Capability],
+) -> (ApprovalPolicy, PolicySource) {
+    let strictness = |policy: &ApprovalPolicy| match policy {
+        ApprovalPolicy::AutoApprove => 0,
+        ApprovalPolicy::Prompt => 1,
+        ApprovalPolicy::AutoDeny => 2,
+    };
+
+    let (policy, source) = capabilities
+        .iter()
+        .filter_map(|capability| {
+            policies
+                .capabilities
+                .get(capability)
+                .map(|policy| (*policy, PolicySource::Capability(capability.clone())))
+        })
+        .max_by_key(|(policy, _)| strictness(policy))
+        .unwrap_or_else(|| {
+            let policy = match risk_level {
+                RiskLevel::Low => policies.low,
+                RiskLevel::Medium => policies.medium,
+                RiskLevel::High => policies.high,
+                RiskLevel::Critical => policies.critical,
+            };
+            (policy, PolicySource::RiskLevel(risk_level.clone()))
+        });
+
+    if *risk_level == RiskLevel::Critical && policy == ApprovalPolicy::AutoApprove {
+        return (ApprovalPolicy::Prompt, source);
+    }
+    (policy, source)
+}
+
 /// Approval manager for handling user consent workflows
-#[derive(Debug)]
 pub struct ApprovalManager {
-    auto_approve_low_risk: bool,
+    policies: ApprovalPolicies,
     interactive_mode: bool,
+    prompt: Option<Arc<dyn ApprovalPrompt>>,
+    enrichment: Option<Arc<ApprovalEnrichment>>,
+    /// Operations approved for the rest of the session
+    session_approvals: Mutex<HashSet<String>>,
+}
+
+impl std::fmt::Debug for ApprovalManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApprovalManager")
+            .field("policies", &self.policies)
+            .field("interactive_mode", &self.interactive_mode)
+            .field("has_prompt", &self.prompt.is_some())
+            .field("enrichment", &self.enrichment)
+            .field("session_approvals", &self.session_approvals)
+            .finish()
+    }
 }
 
 impl Default for ApprovalManager {
     fn default() -> Self {
-        Self {
-            auto_approve_low_risk: false,
-            interactive_mode: true,
-        }
+        Self::new(false, true)
     }
 }
 
 impl ApprovalManager {
-    /// Create a new approval manager
+    /// Create a new approval manager prompting for every risk level, or
+    /// approving low risk operations when `auto_approve_low_risk` is set
     pub fn new(auto_approve_low_risk: bool, interactive_mode: bool) -> Self {
+        let mut policies = ApprovalPolicies::default();
+        if auto_approve_low_risk {
+            policies.low = ApprovalPolicy::AutoApprove;
+        }
+
         Self {
-            auto_approve_low_risk,
+            policies,
             interactive_mode,
+            prompt: None,
+            enrichment: None,
+            session_approvals: Mutex::new(HashSet::new()),
         }
     }
 
-    /// Request approval for an operation
+    /// Decide requests by their risk level according to `policies` before
+    /// prompting
+    pub fn with_policies(mut self, policies: ApprovalPolicies) -> Self {
+        self.policies = policies;
+        self
+    }
+
+    /// Ask `prompt` instead of the terminal in
+    /// [`request_approval_async`](Self::request_approval_async)
+    pub fn with_prompt(mut self, prompt: Arc<dyn ApprovalPrompt>) -> Self {
+        self.prompt = Some(prompt);
+        self
+    }
+
+    /// Describe the targets of requests with `enrichment` before they are
+    /// prompted for in [`request_approval_async`](Self::request_approval_async)
+    pub fn with_enrichment(mut self, enrichment: Arc<ApprovalEnrichment>) -> Self {
+        self.enrichment = Some(enrichment);
+        self
+    }
+
+    /// Request approval for an operation, prompting on the terminal
     pub fn request_approval(&self, request: &ApprovalRequest) -> Result<ApprovalStatus> {
-        // Auto-approve low risk operations if configured
-        if self.auto_approve_low_risk && request.risk_level == RiskLevel::Low {
-            return Ok(ApprovalStatus::Approved);
+        let status = match self.decide_without_prompt(request) {
+            Some(status) => status,
+            None => self.prompt_user_approval(request)?,
+        };
+        record_decision(request, &status);
+        Ok(status)
+    }
+
+    /// Request approval for an operation through the configured prompt,
+    /// falling back to the terminal when there is none
+    pub async fn request_approval_async(
+        &self,
+        request: &ApprovalRequest,
+    ) -> Result<ApprovalStatus> {
+        let status = match self.decide_without_prompt(request) {
+            Some(status) => status,
+            None => match &self.enrichment {
+                Some(enrichment) => {
+                    let mut request = request.clone();
+                    enrichment.enrich(&mut request).await;
+                    self.ask_prompt(&request).await?
+                }
+                None => self.ask_prompt(request).await?,
+            },
+        };
+        record_decision(request, &status);
+        Ok(status)
+    }
+
+    async fn ask_prompt(&self, request: &ApprovalRequest) -> Result<ApprovalStatus> {
+        let Some(prompt) = &self.prompt else {
+            return self.prompt_user_approval(request);
+        };
+
+        match prompt.prompt(request).await? {
+            ApprovalDecision::Approve => Ok(ApprovalStatus::Approved),
+            ApprovalDecision::ApproveForSession => {
+                // Critical operations are approved one at a time
+                if request.risk_level != RiskLevel::Critical {
+                    self.session_approvals
+                        .lock()
+                        .unwrap()
+                        .insert(request.operation.clone());
+                }
+                Ok(ApprovalStatus::Approved)
+            }
+            ApprovalDecision::Deny => Ok(ApprovalStatus::Denied),
+        }
+    }
+
+    /// Forget operations approved for the session
+    pub fn clear_session_approvals(&self) {
+        self.session_approvals.lock().unwrap().clear();
+    }
+
+    /// Status decided by configuration and earlier answers, if any
+    fn decide_without_prompt(&self, request: &ApprovalRequest) -> Option<ApprovalStatus> {
+        match select_approval_policy(&self.policies, &request.risk_level, &[]).0 {
+            ApprovalPolicy::AutoApprove => return Some(ApprovalStatus::Approved),
+            ApprovalPolicy::AutoDeny => return Some(ApprovalStatus::Denied),
+            ApprovalPolicy::Prompt => {}
+        }
+
+        if self
+            .session_approvals
+            .lock()
+            .unwrap()
+            .contains(&request.operation)
+        {
+            return Some(ApprovalStatus::Approved);
         }
 
         if !self.interactive_mode {
             // In non-interactive mode, deny all requests that require approval
-            return Ok(ApprovalStatus::Denied);
+            return Some(ApprovalStatus::Denied);
         }
 
-        self.prompt_user_approval(request)
+        None
     }
 
     /// Prompt user for approval via terminal interface
@@ -254,6 +451,8 @@ pub fn create_file_write_approval(path: &str, sandbox_policy: &SandboxPolicy) ->
             format!("Sandbox level: {}", sandbox_policy.level()),
             format!("Workspace: {}", sandbox_policy.workspace_path().display()),
         ],
+        diff: None,
+        targets: vec![PathBuf::from(path)],
     }
 }
 
@@ -270,6 +469,8 @@ pub fn create_shell_command_approval(command: &str) -> ApprovalRequest {
             "This will execute arbitrary code on your system".to_string(),
             "Ensure you trust the source of this command".to_string(),
         ],
+        diff: None,
+        targets: shell_command_targets(command),
     }
 }
 
@@ -290,11 +491,22 @@ pub fn create_network_access_approval(url: &str) -> ApprovalRequest {
             "This will send data over the network".to_string(),
             "Ensure you trust the destination".to_string(),
         ],
+        diff: None,
+        targets: Vec::new(),
     }
 }
 
+/// Files a deletion may remove before it is critical
+const LARGE_DELETION_FILES: u64 = 100;
+
 /// Classify the risk level of a shell command
 fn classify_command_risk(command: &str) -> RiskLevel {
+    classify_command(command).0
+}
+
+/// Risk level of a shell command and why: the patterns of that level it
+/// matches
+pub fn classify_command(command: &str) -> (RiskLevel, Vec<String>) {
     let command_lower = command.to_lowercase();
 
     // Critical risk commands
@@ -373,26 +585,48 @@ fn classify_command_risk(command: &str) -> RiskLevel {
         "python -c",
     ];
 
-    if critical_patterns
-        .iter()
-        .any(|&pattern| command_lower.contains(pattern))
-    {
-        RiskLevel::Critical
-    } else if high_patterns
-        .iter()
-        .any(|&pattern| command_lower.contains(pattern))
-    {
-        RiskLevel::High
-    } else if medium_patterns
-        .iter()
-        .any(|&pattern| command_lower.contains(pattern))
-    {
-        RiskLevel::Medium
-    } else {
-        RiskLevel::Low
+    for (risk_level, patterns) in [
+        (RiskLevel::Critical, &critical_patterns[..]),
+        (RiskLevel::High, &high_patterns[..]),
+        (RiskLevel::Medium, &medium_patterns[..]),
+    ] {
+        let reasons: Vec<String> = patterns
+            .iter()
+            .filter(|&&pattern| command_lower.contains(pattern))
+            .map(|pattern| {
+                format!(
+                    "matches {} risk pattern `{}`",
+                    risk_level.to_string().to_lowercase(),
+                    pattern
+                )
+            })
+            .collect();
+        if !reasons.is_empty() {
+            return (risk_level, reasons);
+        }
+    }
+    (RiskLevel::Low, Vec::new())
+}
+
+/// Risk of deleting `file_count` files
+fn deletion_risk(file_count: u64) -> RiskLevel {
+    match file_count {
+        0 | 1 => RiskLevel::Medium,
+        count if count <= LARGE_DELETION_FILES => RiskLevel::High,
+        _ => RiskLevel::Critical,
     }
 }
 
+fn record_decision(request: &ApprovalRequest, status: &ApprovalStatus) {
+    let decision = match status {
+        ApprovalStatus::Pending => "pending",
+        ApprovalStatus::Approved => "approved",
+        ApprovalStatus::Denied => "denied",
+        ApprovalStatus::TimedOut => "timed_out",
+    };
+    fennec_telemetry::metrics::record_approval_decision(&request.risk_level.to_string(), decision);
+}
+
 /// Process a command preview and check if approval is needed
 pub fn check_command_approval(
     preview: &CommandPreview,
@@ -420,6 +654,8 @@ fn create_approval_request_from_preview(
 
     let mut risk_level = RiskLevel::Low;
     let mut operation_types = Vec::new();
+    let mut targets = Vec::new();
+    let mut diffs = Vec::new();
 
     for action in &preview.actions {
         match action {
@@ -427,15 +663,57 @@ fn create_approval_request_from_preview(
                 details.push(format!("Read file: {}", path));
                 operation_types.push("File Reading");
             }
-            PreviewAction::WriteFile { path, .. } => {
-                details.push(format!("Write file: {}", path));
+            PreviewAction::WriteFile {
+                path,
+                diff,
+                bytes_changed,
+                ..
+            } => {
+                details.push(format!(
+                    "Write file: {} ({} changed)",
+                    path,
+                    format_size(*bytes_changed)
+                ));
+                targets.push(PathBuf::from(path));
                 operation_types.push("File Writing");
-                risk_level = risk_level.max(RiskLevel::Medium);
+                // Writes without a diff create a file rather than change one
+                if let Some(diff) = diff {
+                    diffs.push(diff.clone());
+                    risk_level = risk_level.max(RiskLevel::Medium);
+                }
             }
-            PreviewAction::ExecuteShell { command } => {
+            PreviewAction::DeletePath {
+                path,
+                file_count,
+                total_bytes,
+            } => {
+                details.push(format!(
+                    "Delete: {} ({} {}, {})",
+                    path,
+                    file_count,
+                    if *file_count == 1 { "file" } else { "files" },
+                    format_size(*total_bytes)
+                ));
+                targets.push(PathBuf::from(path));
+                operation_types.push("File Deletion");
+                risk_level = risk_level.max(deletion_risk(*file_count));
+            }
+            PreviewAction::ExecuteShell {
+                command,
+                risk_reasons,
+            } => {
                 details.push(format!("Execute: {}", command));
                 operation_types.push("Shell Execution");
-                risk_level = risk_level.max(classify_command_risk(command));
+                let (command_risk, reasons) = classify_command(command);
+                // Reasons parsed by the command itself take precedence
+                let reasons = if risk_reasons.is_empty() {
+                    &reasons
+                } else {
+                    risk_reasons
+                };
+                details.extend(reasons.iter().map(|reason| format!("Risk: {}", reason)));
+                risk_level = risk_level.max(command_risk);
+                targets.extend(shell_command_targets(command));
             }
         }
     }
@@ -451,6 +729,8 @@ fn create_approval_request_from_preview(
         description: preview.description.clone(),
         risk_level,
         details,
+        diff: (!diffs.is_empty()).then(|| diffs.join("\n")),
+        targets,
     }
 }
 
@@ -520,14 +800,14 @@ mod tests {
     #[test]
     fn test_approval_manager_default() {
         let manager = ApprovalManager::default();
-        assert!(!manager.auto_approve_low_risk);
+        assert_eq!(manager.policies.low, ApprovalPolicy::Prompt);
         assert!(manager.interactive_mode);
     }
 
     #[test]
     fn test_approval_manager_new() {
         let manager = ApprovalManager::new(true, false);
-        assert!(manager.auto_approve_low_risk);
+        assert_eq!(manager.policies.low, ApprovalPolicy::AutoApprove);
         assert!(!manager.interactive_mode);
     }
 
@@ -539,12 +819,107 @@ mod tests {
             description: "Test operation".to_string(),
             risk_level: RiskLevel::Low,
             details: vec![],
+            diff: None,
+            targets: Vec::new(),
         };
 
         let status = manager.request_approval(&request).unwrap();
         assert_eq!(status, ApprovalStatus::Approved);
     }
 
+    /// Prompt answering every request with the same decision
+    struct FixedPrompt {
+        decision: ApprovalDecision,
+        asked: Mutex<usize>,
+        last_details: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl ApprovalPrompt for FixedPrompt {
+        async fn prompt(&self, request: &ApprovalRequest) -> Result<ApprovalDecision> {
+            *self.asked.lock().unwrap() += 1;
+            *self.last_details.lock().unwrap() = request.details.clone();
+            Ok(self.decision)
+        }
+    }
+
+    fn fixed_prompt(decision: ApprovalDecision) -> Arc<FixedPrompt> {
+        Arc::new(FixedPrompt {
+            decision,
+            asked: Mutex::new(0),
+            last_details: Mutex::new(Vec::new()),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_prompt_decides_async_requests() {
+        let request = create_network_access_approval("https://example.com");
+
+        let manager =
+            ApprovalManager::new(false, true).with_prompt(fixed_prompt(ApprovalDecision::Deny));
+        assert_eq!(
+            manager.request_approval_async(&request).await.unwrap(),
+            ApprovalStatus::Denied
+        );
+
+        let manager =
+            ApprovalManager::new(false, true).with_prompt(fixed_prompt(ApprovalDecision::Approve));
+        assert_eq!(
+            manager.request_approval_async(&request).await.unwrap(),
+            ApprovalStatus::Approved
+        );
+    }
+
+    #[tokio::test]
+    async fn test_prompt_sees_enriched_details() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(workspace.path().join("build")).unwrap();
+        std::fs::write(workspace.path().join("build/app"), "").unwrap();
+        let prompt = fixed_prompt(ApprovalDecision::Deny);
+        let manager = ApprovalManager::new(false, true)
+            .with_prompt(prompt.clone())
+            .with_enrichment(Arc::new(ApprovalEnrichment::new(workspace.path())));
+
+        let request = create_shell_command_approval("rm -rf build/");
+        manager.request_approval_async(&request).await.unwrap();
+        let details = prompt.last_details.lock().unwrap().clone();
+        assert!(details.contains(&"build/: directory with 1 file (0 bytes)".to_string()));
+        // The details are added to a copy, not the caller's request
+        assert!(details.starts_with(&request.details));
+        assert!(request.details.len() < details.len());
+    }
+
+    #[tokio::test]
+    async fn test_approve_for_session_skips_later_prompts() {
+        let prompt = fixed_prompt(ApprovalDecision::ApproveForSession);
+        let manager = ApprovalManager::new(false, true).with_prompt(prompt.clone());
+        let request = create_network_access_approval("https://example.com");
+
+        for _ in 0..3 {
+            assert_eq!(
+                manager.request_approval_async(&request).await.unwrap(),
+                ApprovalStatus::Approved
+            );
+        }
+        assert_eq!(*prompt.asked.lock().unwrap(), 1);
+
+        manager.clear_session_approvals();
+        manager.request_approval_async(&request).await.unwrap();
+        assert_eq!(*prompt.asked.lock().unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_critical_operations_are_never_approved_for_session() {
+        let prompt = fixed_prompt(ApprovalDecision::ApproveForSession);
+        let manager = ApprovalManager::new(false, true).with_prompt(prompt.clone());
+        let request = create_shell_command_approval("rm -rf build");
+        assert_eq!(request.risk_level, RiskLevel::Critical);
+
+        manager.request_approval_async(&request).await.unwrap();
+        manager.request_approval_async(&request).await.unwrap();
+        assert_eq!(*prompt.asked.lock().unwrap(), 2);
+    }
+
     #[test]
     fn test_non_interactive_denies() {
         let manager = ApprovalManager::new(false, false);
@@ -553,6 +928,8 @@ mod tests {
             description: "Test operation".to_string(),
             risk_level: RiskLevel::Medium,
             details: vec![],
+            diff: None,
+            targets: Vec::new(),
         };
 
         let status = manager.request_approval(&request).unwrap();
@@ -705,6 +1082,8 @@ mod tests {
             description: "Test desc".to_string(),
             risk_level: RiskLevel::Low,
             details: vec!["detail1".to_string()],
+            diff: None,
+            targets: Vec::new(),
         };
 
         let cloned = request.clone();
@@ -719,6 +1098,8 @@ mod tests {
             description: "Test desc".to_string(),
             risk_level: RiskLevel::Medium,
             details: vec!["detail1".to_string()],
+            diff: None,
+            targets: Vec::new(),
         };
 
         let serialized = serde_json::to_string(&request).unwrap();
@@ -764,6 +1145,76 @@ mod tests {
         assert_eq!(status, ApprovalStatus::Approved);
     }
 
+    #[test]
+    fn test_preview_request_shows_diffs_deletions_and_risk_reasons() {
+        use fennec_core::command::CommandPreview;
+        use uuid::Uuid;
+
+        let sandbox = SandboxPolicy::new(
+            crate::SandboxLevel::WorkspaceWrite,
+            PathBuf::from("/tmp"),
+            false,
+        );
+        let preview = CommandPreview {
+            command_id: Uuid::new_v4(),
+            description: "Clean up".to_string(),
+            actions: vec![
+                PreviewAction::WriteFile {
+                    path: "src/lib.rs".to_string(),
+                    content: "pub mod a;\n".to_string(),
+                    diff: Some("-pub mod old;\n+pub mod a;".to_string()),
+                    bytes_changed: 24,
+                },
+                PreviewAction::DeletePath {
+                    path: "target".to_string(),
+                    file_count: 40,
+                    total_bytes: 3 * 1024 * 1024,
+                },
+                PreviewAction::ExecuteShell {
+                    command: "make".to_string(),
+                    risk_reasons: vec!["compiles and runs build scripts".to_string()],
+                },
+            ],
+            requires_approval: true,
+        };
+
+        let request = create_approval_request_from_preview(&preview, &sandbox);
+        assert_eq!(request.risk_level, RiskLevel::High);
+        assert_eq!(request.diff.as_deref(), Some("-pub mod old;\n+pub mod a;"));
+        assert_eq!(
+            request.targets,
+            [PathBuf::from("src/lib.rs"), PathBuf::from("target")]
+        );
+        for detail in [
+            "Write file: src/lib.rs (24 bytes changed)",
+            "Delete: target (40 files, 3.0 MiB)",
+            "Risk: compiles and runs build scripts",
+        ] {
+            assert!(
+                request.details.iter().any(|d| d == detail),
+                "{:?}",
+                request.details
+            );
+        }
+
+        // Creating a file changes nothing that exists
+        let preview = CommandPreview {
+            actions: vec![PreviewAction::WriteFile {
+                path: "notes.txt".to_string(),
+                content: "hello".to_string(),
+                diff: None,
+                bytes_changed: 5,
+            }],
+            ..preview
+        };
+        let request = create_approval_request_from_preview(&preview, &sandbox);
+        assert_eq!(request.risk_level, RiskLevel::Low);
+        assert!(request.diff.is_none());
+
+        assert_eq!(deletion_risk(1), RiskLevel::Medium);
+        assert_eq!(deletion_risk(LARGE_DELETION_FILES + 1), RiskLevel::Critical);
+    }
+
     #[test]
     fn test_classify_command_risk_case_insensitive() {
         assert_eq!(classify_command_risk("RM -RF /"), RiskLevel::Critical);
@@ -801,4 +1252,61 @@ mod tests {
         let cloned = risk.clone();
         assert_eq!(cloned, RiskLevel::Medium);
     }
+
+    #[test]
+    fn test_capability_policies_take_precedence() {
+        let mut policies = ApprovalPolicies {
+            low: ApprovalPolicy::AutoApprove,
+            ..Default::default()
+        };
+        policies
+            .capabilities
+            .insert(Capability::ReadFile, ApprovalPolicy::AutoApprove);
+        policies
+            .capabilities
+            .insert(Capability::NetworkAccess, ApprovalPolicy::AutoDeny);
+
+        assert_eq!(
+            select_approval_policy(&policies, &RiskLevel::Low, &[]),
+            (
+                ApprovalPolicy::AutoApprove,
+                PolicySource::RiskLevel(RiskLevel::Low)
+            )
+        );
+        assert_eq!(
+            select_approval_policy(
+                &policies,
+                &RiskLevel::Low,
+                &[Capability::ReadFile, Capability::NetworkAccess]
+            ),
+            (
+                ApprovalPolicy::AutoDeny,
+                PolicySource::Capability(Capability::NetworkAccess)
+            )
+        );
+    }
+
+    #[test]
+    fn test_critical_operations_are_never_auto_approved() {
+        let mut policies = ApprovalPolicies {
+            critical: ApprovalPolicy::AutoApprove,
+            ..Default::default()
+        };
+        policies
+            .capabilities
+            .insert(Capability::ExecuteShell, ApprovalPolicy::AutoApprove);
+
+        for capabilities in [vec![], vec![Capability::ExecuteShell]] {
+            let (policy, _) =
+                select_approval_policy(&policies, &RiskLevel::Critical, &capabilities);
+            assert_eq!(policy, ApprovalPolicy::Prompt);
+        }
+
+        let manager = ApprovalManager::new(false, false).with_policies(policies);
+        let request = create_shell_command_approval("sudo rm -rf /tmp/x");
+        assert_eq!(
+            manager.request_approval(&request).unwrap(),
+            ApprovalStatus::Denied
+        );
+    }
 }
@@ -1,11 +1,13 @@
+use async_trait::async_trait;
 use fennec_core::{command::Capability, config::Config, Result};
+use fennec_telemetry::{config::PrivacyConfig, sanitization::DataSanitizer};
 use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
     path::PathBuf,
     sync::{
         atomic::{AtomicU64, Ordering},
-        Arc,
+        Arc, LazyLock, Mutex,
     },
 };
 use tokio::{
@@ -203,6 +205,32 @@ pub struct ValidationErrorData {
     pub error_details: String,
 }
 
+/// Memory events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemorySearchData {
+    pub query: String,
+    pub result_count: usize,
+    pub memory_types: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryDeletionData {
+    /// What was deleted, e.g. `session`
+    pub scope: String,
+    pub target: String,
+    pub transcripts_deleted: usize,
+    pub messages_deleted: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryExportData {
+    /// What was exported, e.g. `project`
+    pub scope: String,
+    pub target: String,
+    pub destination: String,
+    pub file_count: usize,
+}
+
 /// Comprehensive audit event enum
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "event_type", content = "event_data")]
@@ -239,6 +267,11 @@ pub enum AuditEventData {
     CommandError(CommandErrorData),
     SystemError(SystemErrorData),
     ValidationError(ValidationErrorData),
+
+    // Memory events
+    MemorySearch(MemorySearchData),
+    MemoryDeletion(MemoryDeletionData),
+    MemoryExport(MemoryExportData),
 }
 
 /// Complete audit event structure
@@ -431,12 +464,51 @@ impl SessionAuditManager {
     }
 }
 
+/// Redacts secrets from events passed to [`AuditSystem::record`]
+static SANITIZER: LazyLock<DataSanitizer> = LazyLock::new(|| {
+    DataSanitizer::new(&PrivacyConfig::default()).expect("default privacy config is valid")
+});
+
+/// Receives events recorded through [`AuditSystem::record`], besides the
+/// session audit files
+#[async_trait]
+pub trait AuditSink: Send + Sync + std::fmt::Debug {
+    async fn record(&self, event: &AuditEvent) -> Result<()>;
+}
+
+/// Sink keeping events in memory, e.g. for tests
+#[derive(Debug, Default)]
+pub struct InMemoryAuditSink {
+    events: Mutex<Vec<AuditEvent>>,
+}
+
+impl InMemoryAuditSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Events recorded so far, oldest first
+    pub fn events(&self) -> Vec<AuditEvent> {
+        self.events.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl AuditSink for InMemoryAuditSink {
+    async fn record(&self, event: &AuditEvent) -> Result<()> {
+        self.events.lock().unwrap().push(event.clone());
+        Ok(())
+    }
+}
+
 /// Global audit system that manages multiple sessions
 #[derive(Debug)]
 pub struct AuditSystem {
     base_audit_path: PathBuf,
     sessions: Arc<RwLock<HashMap<Uuid, Arc<SessionAuditManager>>>>,
     enabled: bool,
+    sinks: Vec<Arc<dyn AuditSink>>,
+    sequence_counter: AtomicU64,
 }
 
 impl AuditSystem {
@@ -452,6 +524,8 @@ impl AuditSystem {
             base_audit_path,
             sessions: Arc::new(RwLock::new(HashMap::new())),
             enabled: config.security.audit_log_enabled,
+            sinks: Vec::new(),
+            sequence_counter: AtomicU64::new(1),
         };
 
         // Create base directory
@@ -464,6 +538,47 @@ impl AuditSystem {
         Ok(system)
     }
 
+    /// Also send events recorded through [`record`](Self::record) to `sink`
+    pub fn with_sink(mut self, sink: Arc<dyn AuditSink>) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    /// Record an event that may not belong to a session, e.g. a memory
+    /// search. Secrets are redacted first; the event goes to the session's
+    /// audit file if `session_id` has one, and to every sink.
+    pub async fn record(&self, session_id: Option<Uuid>, event_data: AuditEventData) -> Result<()> {
+        let event_data = sanitize_event_data(event_data)?;
+
+        if let Some(session_id) = session_id {
+            if let Some(manager) = self.get_session(session_id).await {
+                manager.log_event(event_data.clone(), None).await?;
+            }
+        }
+
+        if self.sinks.is_empty() {
+            return Ok(());
+        }
+        let event = AuditEvent {
+            metadata: AuditEventMetadata {
+                timestamp: chrono::Utc::now(),
+                event_id: Uuid::new_v4(),
+                session_id: session_id.unwrap_or_else(Uuid::nil),
+                sequence_number: self.sequence_counter.fetch_add(1, Ordering::SeqCst),
+                correlation_id: None,
+                user_id: None,
+                workspace_path: None,
+            },
+            data: event_data,
+        };
+        for sink in &self.sinks {
+            if let Err(e) = sink.record(&event).await {
+                warn!("Audit sink {:?} failed: {}", sink, e);
+            }
+        }
+        Ok(())
+    }
+
     /// Start a new audit session
     pub async fn start_session(
         &self,
@@ -524,6 +639,14 @@ impl AuditSystem {
     }
 }
 
+/// `event_data` with secrets in its strings redacted
+fn sanitize_event_data(event_data: AuditEventData) -> Result<AuditEventData> {
+    let json = serde_json::to_value(&event_data)
+        .map_err(|e| fennec_core::FennecError::Security(Box::new(e)))?;
+    serde_json::from_value(SANITIZER.sanitize_json(json))
+        .map_err(|e| fennec_core::FennecError::Security(Box::new(e)))
+}
+
 /// Audit query filters and parameters
 #[derive(Debug, Clone, Default)]
 pub struct AuditQueryFilter {
@@ -923,6 +1046,9 @@ impl AuditQueryEngine {
                 AuditEventData::CommandError(_) => "CommandError",
                 AuditEventData::SystemError(_) => "SystemError",
                 AuditEventData::ValidationError(_) => "ValidationError",
+                AuditEventData::MemorySearch(_) => "MemorySearch",
+                AuditEventData::MemoryDeletion(_) => "MemoryDeletion",
+                AuditEventData::MemoryExport(_) => "MemoryExport",
                 _ => "Unknown",
             };
 
@@ -1123,6 +1249,60 @@ mod legacy {
             self.write_log_entry(&event).await
         }
 
+        /// Log the end of a session with its totals (legacy)
+        pub async fn log_session_end(
+            &self,
+            session_id: Uuid,
+            reason: &str,
+            data: &SessionEndData,
+        ) -> Result<()> {
+            if !self.enabled {
+                return Ok(());
+            }
+
+            let event = json!({
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+                "event_type": "session_event",
+                "session_id": session_id,
+                "details": {
+                    "action": "session_ended",
+                    "reason": reason,
+                    "duration_ms": data.duration_ms,
+                    "command_count": data.command_count,
+                    "error_count": data.error_count
+                }
+            });
+
+            self.write_log_entry(&event).await
+        }
+
+        /// Log which approval policy decided an operation (legacy)
+        pub async fn log_approval_policy(
+            &self,
+            session_id: Uuid,
+            operation: &str,
+            source: &str,
+            policy: &str,
+        ) -> Result<()> {
+            if !self.enabled {
+                return Ok(());
+            }
+
+            let event = json!({
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+                "event_type": "security_event",
+                "session_id": session_id,
+                "details": {
+                    "security_event_type": "approval_policy",
+                    "operation": operation,
+                    "source": source,
+                    "policy": policy
+                }
+            });
+
+            self.write_log_entry(&event).await
+        }
+
         /// Log a user message (legacy)
         pub async fn log_user_message(&self, session_id: Uuid, content: &str) -> Result<()> {
             if !self.enabled {
@@ -1334,6 +1514,52 @@ mod tests {
         assert!(content.contains("\"error_count\":1"));
     }
 
+    #[tokio::test]
+    async fn test_recorded_events_are_sanitized_and_reach_sinks() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.security.audit_log_path = Some(temp_dir.path().to_path_buf());
+        let sink = Arc::new(InMemoryAuditSink::new());
+        let audit_system = AuditSystem::new(&config)
+            .await
+            .unwrap()
+            .with_sink(sink.clone());
+        let session_id = Uuid::new_v4();
+        let manager = audit_system
+            .start_session(session_id, None, None)
+            .await
+            .unwrap();
+
+        for session in [None, Some(session_id)] {
+            audit_system
+                .record(
+                    session,
+                    AuditEventData::MemorySearch(MemorySearchData {
+                        query: "deploy with api_key=sk-live-1234 to prod".to_string(),
+                        result_count: 2,
+                        memory_types: vec!["transcripts".to_string()],
+                    }),
+                )
+                .await
+                .unwrap();
+        }
+
+        let events = sink.events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].metadata.session_id, Uuid::nil());
+        assert_eq!(events[1].metadata.session_id, session_id);
+        let AuditEventData::MemorySearch(data) = &events[0].data else {
+            panic!("unexpected event {:?}", events[0].data);
+        };
+        assert_eq!(data.query, "deploy with api_key=[REDACTED] to prod");
+
+        let content = tokio::fs::read_to_string(manager.file_path())
+            .await
+            .unwrap();
+        assert_eq!(content.matches("MemorySearch").count(), 1);
+        assert!(!content.contains("sk-live-1234"));
+    }
+
     #[tokio::test]
     async fn test_utils() {
         // Test checksum generation
@@ -2,11 +2,14 @@ use crate::audit::{
     utils, AuditEventData, AuditSystem, CommandApprovedData, CommandCompletedData,
     CommandPreviewData, CommandRejectedData, CommandRequestedData, CommandStartedData,
     FileCreateData, FileDeleteData, FileReadData, FileWriteData, PermissionCheckData,
+    SandboxViolationData,
 };
+use crate::sandbox::{PolicyResult, SandboxPolicy};
 use fennec_core::{
     command::{Capability, CommandPreview, CommandResult},
     Result,
 };
+use std::path::Path;
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -165,6 +168,40 @@ impl AuditableCommandExecutor {
         Ok(())
     }
 
+    /// Log an action the sandbox policy blocked
+    pub async fn log_sandbox_violation(
+        &self,
+        attempted_action: &str,
+        blocked_reason: &str,
+        severity: &str,
+    ) -> Result<()> {
+        if let Some(manager) = self.audit_system.get_session(self.session_id).await {
+            let event_data = AuditEventData::SandboxViolation(SandboxViolationData {
+                attempted_action: attempted_action.to_string(),
+                blocked_reason: blocked_reason.to_string(),
+                severity: severity.to_string(),
+            });
+
+            manager.log_event(event_data, None).await?;
+        }
+        Ok(())
+    }
+
+    /// Check `command` against `policy`, logging a violation when it is
+    /// denied
+    pub async fn check_shell_command(
+        &self,
+        policy: &SandboxPolicy,
+        command: &str,
+    ) -> Result<PolicyResult> {
+        let result = policy.check_shell_command(command);
+        if let PolicyResult::Deny(reason) = &result {
+            self.log_sandbox_violation(&format!("execute {}", command), reason, "high")
+                .await?;
+        }
+        Ok(result)
+    }
+
     /// Log file read operation
     pub async fn log_file_read(
         &self,
@@ -253,16 +290,56 @@ impl AuditableCommandExecutor {
 #[derive(Debug)]
 pub struct AuditableFileOperations {
     executor: Arc<AuditableCommandExecutor>,
+    sandbox_policy: Option<SandboxPolicy>,
 }
 
 impl AuditableFileOperations {
     /// Create a new auditable file operations wrapper
     pub fn new(executor: Arc<AuditableCommandExecutor>) -> Self {
-        Self { executor }
+        Self {
+            executor,
+            sandbox_policy: None,
+        }
+    }
+
+    /// Refuse operations `policy` denies, logging each as a sandbox
+    /// violation
+    pub fn with_sandbox_policy(mut self, policy: SandboxPolicy) -> Self {
+        self.sandbox_policy = Some(policy);
+        self
+    }
+
+    /// Fail when the sandbox policy denies `operation` on `path`
+    async fn enforce(
+        &self,
+        operation: &str,
+        path: &str,
+        check: fn(&SandboxPolicy, &Path) -> PolicyResult,
+    ) -> Result<()> {
+        let Some(policy) = &self.sandbox_policy else {
+            return Ok(());
+        };
+        let PolicyResult::Deny(reason) = check(policy, Path::new(path)) else {
+            return Ok(());
+        };
+
+        let severity = if operation == "read" {
+            "medium"
+        } else {
+            "high"
+        };
+        self.executor
+            .log_sandbox_violation(&format!("{} {}", operation, path), &reason, severity)
+            .await?;
+        Err(fennec_core::FennecError::Security(Box::new(
+            std::io::Error::new(std::io::ErrorKind::PermissionDenied, reason),
+        )))
     }
 
     /// Read a file with audit logging
     pub async fn read_file(&self, path: &str) -> Result<Vec<u8>> {
+        self.enforce("read", path, SandboxPolicy::check_read_path)
+            .await?;
         let data = tokio::fs::read(path)
             .await
             .map_err(|e| fennec_core::FennecError::Security(Box::new(e)))?;
@@ -277,6 +354,8 @@ impl AuditableFileOperations {
 
     /// Write a file with audit logging
     pub async fn write_file(&self, path: &str, content: &[u8], create_backup: bool) -> Result<()> {
+        self.enforce("write", path, SandboxPolicy::check_write_path)
+            .await?;
         let path_buf = std::path::Path::new(path);
         let exists = path_buf.exists();
         let checksum_before = if exists {
@@ -329,6 +408,8 @@ impl AuditableFileOperations {
 
     /// Delete a file with audit logging
     pub async fn delete_file(&self, path: &str, create_backup: bool) -> Result<()> {
+        self.enforce("delete", path, SandboxPolicy::check_write_path)
+            .await?;
         let path_buf = std::path::Path::new(path);
         if !path_buf.exists() {
             return Err(fennec_core::FennecError::Security(Box::new(
@@ -578,6 +659,7 @@ mod tests {
             success: true,
             output: "Command completed successfully".to_string(),
             error: None,
+            data: None,
         };
         context.complete_execution(&result).await.unwrap();
 
@@ -590,4 +672,64 @@ mod tests {
         assert!(content.contains("CommandCompleted"));
         assert!(content.contains(&context.execution_id.to_string()));
     }
+
+    #[tokio::test]
+    async fn test_sandboxed_file_operations_log_violations() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = temp_dir.path().join("workspace");
+        std::fs::create_dir(&workspace).unwrap();
+        let mut config = Config::default();
+        config.security.audit_log_path = Some(temp_dir.path().join("audit"));
+        config.security.audit_log_enabled = true;
+
+        let audit_system = Arc::new(AuditSystem::new(&config).await.unwrap());
+        let session_id = Uuid::new_v4();
+        let manager = audit_system
+            .start_session(session_id, None, None)
+            .await
+            .unwrap();
+        let executor = Arc::new(AuditableCommandExecutor::new(
+            audit_system.clone(),
+            session_id,
+        ));
+        let policy = crate::SandboxPolicy::new(
+            crate::SandboxLevel::WorkspaceWrite,
+            workspace.clone(),
+            false,
+        );
+        let file_ops =
+            AuditableFileOperations::new(executor.clone()).with_sandbox_policy(policy.clone());
+
+        let inside = workspace.join("notes.txt");
+        file_ops
+            .write_file(inside.to_str().unwrap(), b"ok", false)
+            .await
+            .unwrap();
+
+        let outside = temp_dir.path().join("outside.txt");
+        let error = file_ops
+            .write_file(outside.to_str().unwrap(), b"escaped", false)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("outside workspace"), "{}", error);
+        assert!(!outside.exists());
+
+        let result = executor
+            .check_shell_command(&policy, "cat /etc/passwd")
+            .await
+            .unwrap();
+        assert!(matches!(result, crate::PolicyResult::Deny(_)));
+
+        let content = tokio::fs::read_to_string(manager.file_path())
+            .await
+            .unwrap();
+        assert_eq!(
+            content.matches("SandboxViolation").count(),
+            2,
+            "{}",
+            content
+        );
+        assert!(content.contains(&format!("write {}", outside.display())));
+        assert!(content.contains("execute cat /etc/passwd"));
+    }
 }
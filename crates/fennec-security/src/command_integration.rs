@@ -164,6 +164,7 @@ mod tests {
             success: true,
             output: "Command executed successfully".to_string(),
             error: None,
+            data: None,
         })
     }
 
@@ -173,6 +174,7 @@ mod tests {
             success: false,
             output: "Command failed".to_string(),
             error: Some("Simulated error".to_string()),
+            data: None,
         })
     }
 
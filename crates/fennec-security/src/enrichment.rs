@@ -0,0 +1,454 @@
+//! Approval enrichment: before the user is asked to approve an operation,
+//! providers add what they know about the paths it touches to the
+//! request's details, e.g. how many files `rm -rf build/` would remove.
+
+use crate::approval::ApprovalRequest;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+use tracing::{debug, warn};
+
+/// How long enrichment may delay a prompt before providers are skipped
+pub const DEFAULT_ENRICHMENT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Files counted under a directory before giving up
+const MAX_COUNTED_FILES: usize = 100_000;
+
+/// Knows something about paths an operation touches
+#[async_trait]
+pub trait ApprovalContextProvider: Send + Sync {
+    /// Name used in logs
+    fn name(&self) -> &str;
+
+    /// Details about `target`, an existing path in `workspace` written as in
+    /// the request; empty when there is nothing worth telling
+    async fn describe(&self, workspace: &Path, target: &Path) -> Result<Vec<String>>;
+}
+
+/// Runs the registered providers over an approval request's targets
+pub struct ApprovalEnrichment {
+    workspace: PathBuf,
+    providers: RwLock<Vec<Arc<dyn ApprovalContextProvider>>>,
+    timeout: Duration,
+}
+
+impl std::fmt::Debug for ApprovalEnrichment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let providers: Vec<String> = self
+            .providers()
+            .iter()
+            .map(|provider| provider.name().to_string())
+            .collect();
+        f.debug_struct("ApprovalEnrichment")
+            .field("workspace", &self.workspace)
+            .field("providers", &providers)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl ApprovalEnrichment {
+    /// Enrichment for requests in `workspace` with the filesystem and git
+    /// providers registered
+    pub fn new(workspace: impl Into<PathBuf>) -> Self {
+        let enrichment = Self::without_providers(workspace);
+        enrichment.register(Arc::new(FileStatsProvider));
+        enrichment.register(Arc::new(GitStatusProvider));
+        enrichment
+    }
+
+    /// Enrichment for requests in `workspace` with no providers yet
+    pub fn without_providers(workspace: impl Into<PathBuf>) -> Self {
+        Self {
+            workspace: workspace.into(),
+            providers: RwLock::new(Vec::new()),
+            timeout: DEFAULT_ENRICHMENT_TIMEOUT,
+        }
+    }
+
+    /// Give up on providers still running after `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Add `provider`; its details follow those of providers added earlier
+    pub fn register(&self, provider: Arc<dyn ApprovalContextProvider>) {
+        self.providers.write().unwrap().push(provider);
+    }
+
+    pub fn workspace(&self) -> &Path {
+        &self.workspace
+    }
+
+    fn providers(&self) -> Vec<Arc<dyn ApprovalContextProvider>> {
+        self.providers.read().unwrap().clone()
+    }
+
+    /// Append what the providers know about `request`'s targets to its
+    /// details. Targets outside the workspace or that do not exist are
+    /// skipped, and so are providers that fail or are still running when
+    /// the timeout runs out.
+    pub async fn enrich(&self, request: &mut ApprovalRequest) {
+        let targets: Vec<PathBuf> = request
+            .targets
+            .iter()
+            .filter(|target| self.is_in_workspace(target))
+            .cloned()
+            .collect();
+        let providers = self.providers();
+        if targets.is_empty() || providers.is_empty() {
+            return;
+        }
+
+        let mut tasks = JoinSet::new();
+        for (target_index, target) in targets.iter().enumerate() {
+            for (provider_index, provider) in providers.iter().enumerate() {
+                let provider = provider.clone();
+                let workspace = self.workspace.clone();
+                let target = target.clone();
+                tasks.spawn(async move {
+                    let details = provider.describe(&workspace, &target).await;
+                    ((target_index, provider_index), provider, details)
+                });
+            }
+        }
+
+        let deadline = Instant::now() + self.timeout;
+        let mut found = Vec::new();
+        loop {
+            match tokio::time::timeout_at(deadline, tasks.join_next()).await {
+                Ok(Some(Ok((order, _, Ok(details))))) => found.push((order, details)),
+                Ok(Some(Ok((_, provider, Err(e))))) => {
+                    debug!("Approval context from {} failed: {}", provider.name(), e);
+                }
+                Ok(Some(Err(e))) => debug!("Approval context provider panicked: {}", e),
+                Ok(None) => break,
+                Err(_) => {
+                    warn!(
+                        "Approval context took longer than {:?}, skipping {} providers",
+                        self.timeout,
+                        tasks.len()
+                    );
+                    tasks.abort_all();
+                    break;
+                }
+            }
+        }
+
+        found.sort_by_key(|(order, _)| *order);
+        request
+            .details
+            .extend(found.into_iter().flat_map(|(_, details)| details));
+    }
+
+    fn is_in_workspace(&self, target: &Path) -> bool {
+        let (Ok(workspace), Ok(target)) = (
+            self.workspace.canonicalize(),
+            self.workspace.join(target).canonicalize(),
+        ) else {
+            return false;
+        };
+        target.starts_with(workspace)
+    }
+}
+
+/// Paths a shell command names as arguments, e.g. `build/` for
+/// `rm -rf build/`
+pub fn shell_command_targets(command: &str) -> Vec<PathBuf> {
+    let mut targets = Vec::new();
+    let mut program = true;
+    for word in command.split_whitespace() {
+        if matches!(word, "&&" | "||" | ";" | "|") {
+            program = true;
+            continue;
+        }
+        // Environment assignments come before the program
+        if word.contains('=') || std::mem::take(&mut program) || word.starts_with('-') {
+            continue;
+        }
+        let word = word.trim_matches(|c| c == '"' || c == '\'');
+        if !word.is_empty() {
+            targets.push(PathBuf::from(word));
+        }
+    }
+    targets
+}
+
+/// How many files a directory holds, or how large a file is
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FileStatsProvider;
+
+#[async_trait]
+impl ApprovalContextProvider for FileStatsProvider {
+    fn name(&self) -> &str {
+        "filesystem"
+    }
+
+    async fn describe(&self, workspace: &Path, target: &Path) -> Result<Vec<String>> {
+        let path = workspace.join(target);
+        let display = target.display().to_string();
+        let detail = tokio::task::spawn_blocking(move || -> Result<String> {
+            let metadata = std::fs::metadata(&path)?;
+            if !metadata.is_dir() {
+                return Ok(format!(
+                    "{}: file of {}",
+                    display,
+                    format_size(metadata.len())
+                ));
+            }
+            let (files, bytes) = count_files(&path);
+            if files >= MAX_COUNTED_FILES {
+                Ok(format!(
+                    "{}: directory with more than {} files",
+                    display, MAX_COUNTED_FILES
+                ))
+            } else {
+                Ok(format!(
+                    "{}: directory with {} {} ({})",
+                    display,
+                    files,
+                    if files == 1 { "file" } else { "files" },
+                    format_size(bytes)
+                ))
+            }
+        })
+        .await??;
+        Ok(vec![detail])
+    }
+}
+
+/// Files under `dir` and their total size, without following symlinks
+fn count_files(dir: &Path) -> (usize, u64) {
+    let mut files = 0;
+    let mut bytes = 0;
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                pending.push(entry.path());
+            } else {
+                files += 1;
+                bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
+                if files >= MAX_COUNTED_FILES {
+                    return (files, bytes);
+                }
+            }
+        }
+    }
+    (files, bytes)
+}
+
+pub(crate) fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+    if bytes < 1024 {
+        return format!("{} bytes", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Whether git tracks a path and has uncommitted changes to it
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GitStatusProvider;
+
+impl GitStatusProvider {
+    async fn git(workspace: &Path, args: &[&str]) -> Result<std::process::Output> {
+        Ok(tokio::process::Command::new("git")
+            .arg("-C")
+            .arg(workspace)
+            .args(args)
+            .kill_on_drop(true)
+            .output()
+            .await?)
+    }
+}
+
+#[async_trait]
+impl ApprovalContextProvider for GitStatusProvider {
+    fn name(&self) -> &str {
+        "git"
+    }
+
+    async fn describe(&self, workspace: &Path, target: &Path) -> Result<Vec<String>> {
+        let target_arg = target.to_string_lossy();
+        let tracked = Self::git(workspace, &["ls-files", "--", &target_arg]).await?;
+        if !tracked.status.success() {
+            // Not a git repository
+            return Ok(Vec::new());
+        }
+        let tracked = String::from_utf8_lossy(&tracked.stdout).lines().count();
+        let display = target.display();
+
+        if tracked == 0 {
+            let ignored = Self::git(workspace, &["check-ignore", "-q", "--", &target_arg]).await?;
+            return Ok(vec![if ignored.status.success() {
+                format!("{}: ignored by git", display)
+            } else {
+                format!("{}: not tracked by git", display)
+            }]);
+        }
+
+        let status = Self::git(
+            workspace,
+            &[
+                "status",
+                "--porcelain",
+                "--untracked-files=no",
+                "--",
+                &target_arg,
+            ],
+        )
+        .await?;
+        let changed = String::from_utf8_lossy(&status.stdout).lines().count();
+        let detail = if workspace.join(target).is_dir() {
+            format!(
+                "{}: {} tracked {}, {} with uncommitted changes",
+                display,
+                tracked,
+                if tracked == 1 { "file" } else { "files" },
+                changed
+            )
+        } else if changed > 0 {
+            format!("{}: tracked by git, with uncommitted changes", display)
+        } else {
+            format!("{}: tracked by git, no uncommitted changes", display)
+        };
+        Ok(vec![detail])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approval::{create_shell_command_approval, RiskLevel};
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn git(workspace: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(workspace)
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?}", args);
+    }
+
+    /// A git workspace with a tracked `src/`, one file of it edited, and an
+    /// ignored `build/` of three files
+    fn fixture_workspace() -> TempDir {
+        let workspace = TempDir::new().unwrap();
+        let root = workspace.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("build/deps")).unwrap();
+        std::fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(root.join("src/lib.rs"), "").unwrap();
+        std::fs::write(root.join(".gitignore"), "build/\n").unwrap();
+        std::fs::write(root.join("build/app"), vec![0u8; 2048]).unwrap();
+        std::fs::write(root.join("build/deps/a.o"), vec![0u8; 512]).unwrap();
+        std::fs::write(root.join("build/deps/b.o"), vec![0u8; 512]).unwrap();
+        git(root, &["init", "-q"]);
+        git(root, &["add", "."]);
+        git(root, &["commit", "-q", "-m", "init"]);
+        std::fs::write(root.join("src/main.rs"), "fn main() { todo!() }\n").unwrap();
+        workspace
+    }
+
+    struct SlowProvider;
+
+    #[async_trait]
+    impl ApprovalContextProvider for SlowProvider {
+        fn name(&self) -> &str {
+            "slow"
+        }
+
+        async fn describe(&self, _workspace: &Path, _target: &Path) -> Result<Vec<String>> {
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            Ok(vec!["too late".to_string()])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_enrichment_describes_files_and_git_status() {
+        let workspace = fixture_workspace();
+        let enrichment =
+            ApprovalEnrichment::new(workspace.path()).with_timeout(Duration::from_secs(10));
+
+        let mut request = create_shell_command_approval("rm -rf build/ src/ missing/");
+        assert_eq!(request.risk_level, RiskLevel::Critical);
+        let before = request.details.len();
+        enrichment.enrich(&mut request).await;
+        assert_eq!(
+            request.details[before..],
+            [
+                "build/: directory with 3 files (3.0 KiB)",
+                "build/: ignored by git",
+                "src/: directory with 2 files (22 bytes)",
+                "src/: 2 tracked files, 1 with uncommitted changes",
+            ]
+        );
+
+        let mut request = create_shell_command_approval("cat src/lib.rs src/main.rs");
+        enrichment.enrich(&mut request).await;
+        let details = request.details.join("\n");
+        assert!(
+            details.contains("src/lib.rs: file of 0 bytes"),
+            "{}",
+            details
+        );
+        assert!(
+            details.contains("src/lib.rs: tracked by git, no uncommitted changes"),
+            "{}",
+            details
+        );
+        assert!(
+            details.contains("src/main.rs: tracked by git, with uncommitted changes"),
+            "{}",
+            details
+        );
+    }
+
+    #[tokio::test]
+    async fn test_slow_providers_and_outside_targets_are_skipped() {
+        let workspace = fixture_workspace();
+        let outside = TempDir::new().unwrap();
+        let enrichment = ApprovalEnrichment::without_providers(workspace.path())
+            .with_timeout(Duration::from_millis(200));
+        enrichment.register(Arc::new(SlowProvider));
+        enrichment.register(Arc::new(FileStatsProvider));
+
+        let mut request =
+            create_shell_command_approval(&format!("rm -r build/app {}", outside.path().display()));
+        let before = request.details.len();
+        let started = std::time::Instant::now();
+        enrichment.enrich(&mut request).await;
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(request.details[before..], ["build/app: file of 2.0 KiB"]);
+    }
+
+    #[test]
+    fn test_shell_command_targets() {
+        assert_eq!(
+            shell_command_targets("rm -rf 'build/' && RUST_LOG=debug cargo test --all"),
+            [PathBuf::from("build/"), PathBuf::from("test")]
+        );
+        assert!(shell_command_targets("ls").is_empty());
+    }
+}
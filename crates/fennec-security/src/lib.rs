@@ -2,11 +2,15 @@ pub mod approval;
 pub mod audit;
 pub mod audit_integration;
 pub mod command_integration;
+pub mod enrichment;
 pub mod sandbox;
+pub mod trust;
 
 pub use approval::{
-    check_command_approval, create_file_write_approval, create_network_access_approval,
-    create_shell_command_approval, ApprovalManager, ApprovalRequest, ApprovalStatus, RiskLevel,
+    check_command_approval, classify_command, create_file_write_approval,
+    create_network_access_approval, create_shell_command_approval, select_approval_policy,
+    ApprovalDecision, ApprovalManager, ApprovalPrompt, ApprovalRequest, ApprovalStatus,
+    PolicySource, RiskLevel,
 };
 pub use audit::{
     // Utilities
@@ -24,6 +28,7 @@ pub use audit::{
     AuditQueryEngine,
     AuditQueryFilter,
     AuditQueryResult,
+    AuditSink,
     AuditSystem,
 
     CommandApprovedData,
@@ -43,6 +48,10 @@ pub use audit::{
     FileDeleteData,
     FileReadData,
     FileWriteData,
+    InMemoryAuditSink,
+    MemoryDeletionData,
+    MemoryExportData,
+    MemorySearchData,
     PermissionCheckData,
     SandboxViolationData,
     SecurityWarningData,
@@ -63,7 +72,12 @@ pub use audit_integration::{
 pub use command_integration::{
     audit_command_execution, AuditedCommandContext, AuditedCommandResult, GenericAuditedExecutor,
 };
+pub use enrichment::{
+    shell_command_targets, ApprovalContextProvider, ApprovalEnrichment, FileStatsProvider,
+    GitStatusProvider, DEFAULT_ENRICHMENT_TIMEOUT,
+};
 pub use sandbox::{create_sandbox_policy, PolicyResult, SandboxLevel, SandboxPolicy};
+pub use trust::{TrustDecision, TrustEntry, TrustStore, TRUST_STORE_FILE};
 
 #[cfg(test)]
 mod tests {
@@ -114,6 +128,30 @@ mod tests {
         assert!(!policy.requires_approval());
     }
 
+    #[test]
+    fn test_sandbox_can_only_be_downgraded() {
+        let workspace = create_test_workspace();
+        let policy = create_test_policy(SandboxLevel::WorkspaceWrite, &workspace, true);
+
+        let tightened = policy.downgraded(SandboxLevel::ReadOnly).unwrap();
+        assert_eq!(tightened.level(), &SandboxLevel::ReadOnly);
+        assert_eq!(tightened.workspace_path(), policy.workspace_path());
+        assert!(tightened.requires_approval());
+        assert_eq!(
+            tightened.check_capability(&Capability::WriteFile),
+            PolicyResult::Deny("Capability WriteFile is not allowed in read-only mode".to_string())
+        );
+
+        // Staying put is allowed, loosening is not
+        assert!(policy.downgraded(SandboxLevel::WorkspaceWrite).is_ok());
+        let error = policy
+            .downgraded(SandboxLevel::FullAccess)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("restart"), "{}", error);
+        assert!(tightened.downgraded(SandboxLevel::WorkspaceWrite).is_err());
+    }
+
     #[test]
     fn test_capability_read_only_sandbox() {
         let workspace = create_test_workspace();
@@ -383,6 +421,61 @@ mod tests {
         }
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_new_file_under_symlinked_directory_is_outside() {
+        let workspace = create_test_workspace();
+        let outside = create_test_workspace();
+        std::os::unix::fs::symlink(outside.path(), workspace.path().join("link")).unwrap();
+        let policy = create_test_policy(SandboxLevel::WorkspaceWrite, &workspace, false);
+
+        for path in ["link/new.txt", "link/sub/new.txt", "link/../new.txt"] {
+            assert!(
+                matches!(
+                    policy.check_write_path(&PathBuf::from(path)),
+                    PolicyResult::Deny(_)
+                ),
+                "{} escapes through the symlink",
+                path
+            );
+        }
+        assert_eq!(
+            policy.check_write_path(&PathBuf::from("src/new.txt")),
+            PolicyResult::Allow
+        );
+    }
+
+    #[test]
+    fn test_variable_references_are_denied() {
+        let workspace = create_test_workspace();
+        let policy = create_test_policy(SandboxLevel::WorkspaceWrite, &workspace, false);
+
+        for path in [
+            "~/.bashrc",
+            "$HOME/.ssh/id_rsa",
+            "${HOME}/x",
+            "%APPDATA%\\x",
+        ] {
+            assert!(
+                matches!(
+                    policy.check_read_path(&PathBuf::from(path)),
+                    PolicyResult::Deny(reason) if reason.contains("never expanded")
+                ),
+                "{} was not denied",
+                path
+            );
+        }
+        // A lone dollar or percent sign is just part of a name
+        for path in ["price$.txt", "100%.txt", "a$1.txt"] {
+            assert_eq!(
+                policy.check_read_path(&PathBuf::from(path)),
+                PolicyResult::Allow,
+                "{}",
+                path
+            );
+        }
+    }
+
     #[test]
     fn test_create_sandbox_policy_function() {
         let workspace = create_test_workspace();
@@ -421,6 +514,8 @@ mod tests {
             description: "Low risk operation".to_string(),
             risk_level: RiskLevel::Low,
             details: vec![],
+            diff: None,
+            targets: Vec::new(),
         };
 
         let result = manager.request_approval(&low_risk_request).unwrap();
@@ -431,6 +526,8 @@ mod tests {
             description: "High risk operation".to_string(),
             risk_level: RiskLevel::High,
             details: vec![],
+            diff: None,
+            targets: Vec::new(),
         };
 
         let result = manager.request_approval(&high_risk_request).unwrap();
@@ -546,9 +643,12 @@ mod tests {
                 PreviewAction::WriteFile {
                     path: "output.txt".to_string(),
                     content: "test".to_string(),
+                    diff: None,
+                    bytes_changed: 4,
                 },
                 PreviewAction::ExecuteShell {
                     command: "echo hello".to_string(),
+                    risk_reasons: Vec::new(),
                 },
             ],
             requires_approval: true,
@@ -4,7 +4,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+/// How much the assistant may touch, ordered from most to least restrictive
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum SandboxLevel {
     ReadOnly,
     WorkspaceWrite,
@@ -99,6 +100,23 @@ impl SandboxPolicy {
         self.require_approval
     }
 
+    /// The same policy at `level`, for tightening the sandbox mid-session.
+    /// Loosening it is refused; that takes a restart, so it is always a
+    /// deliberate choice made on the command line.
+    pub fn downgraded(&self, level: SandboxLevel) -> Result<SandboxPolicy> {
+        if level > self.level {
+            return Err(anyhow!(
+                "Cannot raise the sandbox from {} to {} while running; restart with the new --sandbox level",
+                self.level,
+                level
+            ));
+        }
+        Ok(Self {
+            level,
+            ..self.clone()
+        })
+    }
+
     /// Check if a capability is allowed by the current sandbox level
     pub fn check_capability(&self, capability: &Capability) -> PolicyResult {
         let matrix = PolicyMatrix::default();
@@ -260,6 +278,15 @@ impl SandboxPolicy {
             self.workspace_path.join(path)
         };
 
+        // Nothing expands variables in paths, so a reference is either a
+        // mistake or an attempt to reach somewhere else once a shell does
+        if let Some(reference) = variable_reference(path) {
+            return Err(anyhow!(
+                "Path refers to {}, which is never expanded",
+                reference
+            ));
+        }
+
         // Canonicalize to resolve any .. or . components
         let canonical_path = absolute_path.canonicalize().or_else(|_| {
             // If canonicalize fails (e.g., file doesn't exist), manually resolve
@@ -277,11 +304,21 @@ impl SandboxPolicy {
         Ok(canonical_path)
     }
 
-    /// Manually resolve path components to handle non-existent paths
+    /// Manually resolve path components to handle non-existent paths. The
+    /// deepest part that exists is canonicalized, so a new file under a
+    /// symlinked directory resolves to where it would really be written.
     fn resolve_path_components(&self, path: &Path) -> Result<PathBuf> {
-        let mut resolved = PathBuf::new();
-
-        for component in path.components() {
+        let components: Vec<_> = path.components().collect();
+        let (mut resolved, rest) = (0..=components.len())
+            .rev()
+            .find_map(|len| {
+                let existing: PathBuf = components[..len].iter().collect();
+                let canonical = existing.canonicalize().ok()?;
+                Some((canonical, &components[len..]))
+            })
+            .unwrap_or((PathBuf::new(), &components[..]));
+
+        for component in rest.iter().copied() {
             match component {
                 std::path::Component::ParentDir => {
                     if !resolved.pop() {
@@ -361,6 +398,41 @@ impl SandboxPolicy {
     }
 }
 
+/// The first `~`, `$VAR`, `${VAR}` or `%VAR%` reference in `path`
+fn variable_reference(path: &Path) -> Option<String> {
+    let text = path.to_string_lossy();
+    if text.starts_with('~') {
+        return text.split(['/', '\\']).next().map(str::to_string);
+    }
+
+    let chars: Vec<char> = text.chars().collect();
+    for (i, c) in chars.iter().enumerate() {
+        match c {
+            '$' => {
+                let next = chars.get(i + 1).copied().unwrap_or(' ');
+                if next == '{' || next == '(' || next == '_' || next.is_ascii_alphabetic() {
+                    let end = chars[i + 1..]
+                        .iter()
+                        .position(|c| !(c.is_ascii_alphanumeric() || "_{}()".contains(*c)))
+                        .map_or(chars.len(), |len| i + 1 + len);
+                    return Some(chars[i..end].iter().collect());
+                }
+            }
+            '%' => {
+                let name_len = chars[i + 1..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_alphanumeric() || **c == '_')
+                    .count();
+                if name_len > 0 && chars.get(i + 1 + name_len) == Some(&'%') {
+                    return Some(chars[i..i + name_len + 2].iter().collect());
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
 /// Validate working directory and create sandbox policy
 pub fn create_sandbox_policy(
     level: SandboxLevel,
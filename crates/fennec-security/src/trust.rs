@@ -0,0 +1,276 @@
+use crate::sandbox::SandboxLevel;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// File in the config directory the trust decisions are kept in
+pub const TRUST_STORE_FILE: &str = "trusted_workspaces.json";
+
+/// Whether the user trusts Fennec to change files in a workspace
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrustDecision {
+    Trusted,
+    Untrusted,
+}
+
+impl std::fmt::Display for TrustDecision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TrustDecision::Trusted => write!(f, "trusted"),
+            TrustDecision::Untrusted => write!(f, "untrusted"),
+        }
+    }
+}
+
+/// A decision recorded for one workspace and everything below it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TrustEntry {
+    pub path: PathBuf,
+    pub decision: TrustDecision,
+    pub decided_at: DateTime<Utc>,
+}
+
+/// Workspaces the user has decided whether to trust, persisted as JSON
+#[derive(Debug, Clone)]
+pub struct TrustStore {
+    path: PathBuf,
+    entries: Vec<TrustEntry>,
+}
+
+impl TrustStore {
+    /// Load the store kept in `config_dir`, empty if it does not exist yet
+    pub fn load(config_dir: &Path) -> Result<Self> {
+        let path = config_dir.join(TRUST_STORE_FILE);
+        let entries = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)
+                .with_context(|| format!("Invalid trust store {}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        Ok(Self { path, entries })
+    }
+
+    /// Write the store back to its file
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let content = serde_json::to_string_pretty(&self.entries)?;
+        std::fs::write(&self.path, content)
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn entries(&self) -> &[TrustEntry] {
+        &self.entries
+    }
+
+    /// The decision covering `workspace`; the deepest recorded ancestor wins
+    pub fn decision(&self, workspace: &Path) -> Option<TrustDecision> {
+        let workspace = canonical(workspace);
+        self.entries
+            .iter()
+            .filter(|entry| workspace.starts_with(&entry.path))
+            .max_by_key(|entry| entry.path.components().count())
+            .map(|entry| entry.decision)
+    }
+
+    pub fn is_trusted(&self, workspace: &Path) -> bool {
+        self.decision(workspace) == Some(TrustDecision::Trusted)
+    }
+
+    /// Record `decision` for `workspace`, replacing any earlier one, and
+    /// return the canonical path it was recorded under
+    pub fn record(&mut self, workspace: &Path, decision: TrustDecision) -> PathBuf {
+        let path = canonical(workspace);
+        self.entries.retain(|entry| entry.path != path);
+        self.entries.push(TrustEntry {
+            path: path.clone(),
+            decision,
+            decided_at: Utc::now(),
+        });
+        self.entries.sort_by(|a, b| a.path.cmp(&b.path));
+        path
+    }
+
+    /// Forget the decision recorded for exactly `workspace`
+    pub fn remove(&mut self, workspace: &Path) -> bool {
+        let path = canonical(workspace);
+        let before = self.entries.len();
+        self.entries.retain(|entry| entry.path != path);
+        self.entries.len() != before
+    }
+
+    /// The sandbox level to run `workspace` at when `requested` was asked
+    /// for. Write access needs a trusted workspace; for one never decided
+    /// on, `ask` is called and its answer recorded. `ask` returns `None`
+    /// when nobody can answer, which runs read-only without recording.
+    pub fn sandbox_level(
+        &mut self,
+        workspace: &Path,
+        requested: SandboxLevel,
+        ask: impl FnOnce(&Path) -> Option<bool>,
+    ) -> Result<SandboxLevel> {
+        if requested == SandboxLevel::ReadOnly {
+            return Ok(requested);
+        }
+
+        match self.decision(workspace) {
+            Some(TrustDecision::Trusted) => Ok(requested),
+            Some(TrustDecision::Untrusted) => {
+                info!(
+                    "Workspace {} is untrusted, running read-only",
+                    workspace.display()
+                );
+                Ok(SandboxLevel::ReadOnly)
+            }
+            None => match ask(&canonical(workspace)) {
+                Some(trusted) => {
+                    let decision = if trusted {
+                        TrustDecision::Trusted
+                    } else {
+                        TrustDecision::Untrusted
+                    };
+                    let path = self.record(workspace, decision);
+                    self.save()?;
+                    info!("Recorded workspace {} as {}", path.display(), decision);
+                    Ok(if trusted {
+                        requested
+                    } else {
+                        SandboxLevel::ReadOnly
+                    })
+                }
+                None => {
+                    warn!(
+                        "Workspace {} has not been trusted, running read-only",
+                        workspace.display()
+                    );
+                    Ok(SandboxLevel::ReadOnly)
+                }
+            },
+        }
+    }
+}
+
+/// `path` with symlinks resolved; a path that no longer exists is only
+/// made absolute, so its entry can still be removed
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| {
+        std::env::current_dir()
+            .map(|dir| dir.join(path))
+            .unwrap_or_else(|_| path.to_path_buf())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn never_asked(_: &Path) -> Option<bool> {
+        panic!("a decided workspace should not prompt");
+    }
+
+    #[test]
+    fn test_first_run_without_a_prompt_runs_read_only() {
+        let config = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let mut store = TrustStore::load(config.path()).unwrap();
+
+        let level = store
+            .sandbox_level(workspace.path(), SandboxLevel::WorkspaceWrite, |_| None)
+            .unwrap();
+        assert_eq!(level, SandboxLevel::ReadOnly);
+        assert!(store.entries().is_empty());
+        assert!(!config.path().join(TRUST_STORE_FILE).exists());
+
+        // Read-only needs no trust at all
+        let level = store
+            .sandbox_level(workspace.path(), SandboxLevel::ReadOnly, never_asked)
+            .unwrap();
+        assert_eq!(level, SandboxLevel::ReadOnly);
+    }
+
+    #[test]
+    fn test_answers_are_recorded_and_trusted_workspaces_skip_the_prompt() {
+        let config = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let mut store = TrustStore::load(config.path()).unwrap();
+
+        let level = store
+            .sandbox_level(workspace.path(), SandboxLevel::FullAccess, |path| {
+                assert_eq!(path, workspace.path().canonicalize().unwrap());
+                Some(true)
+            })
+            .unwrap();
+        assert_eq!(level, SandboxLevel::FullAccess);
+
+        let mut store = TrustStore::load(config.path()).unwrap();
+        assert!(store.is_trusted(workspace.path()));
+        let nested = workspace.path().join("src");
+        std::fs::create_dir(&nested).unwrap();
+        let level = store
+            .sandbox_level(&nested, SandboxLevel::WorkspaceWrite, never_asked)
+            .unwrap();
+        assert_eq!(level, SandboxLevel::WorkspaceWrite);
+
+        // A deeper decision overrides the trusted parent
+        store.record(&nested, TrustDecision::Untrusted);
+        let level = store
+            .sandbox_level(&nested, SandboxLevel::WorkspaceWrite, never_asked)
+            .unwrap();
+        assert_eq!(level, SandboxLevel::ReadOnly);
+    }
+
+    #[test]
+    fn test_declining_and_revoking_trust() {
+        let config = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let mut store = TrustStore::load(config.path()).unwrap();
+
+        let level = store
+            .sandbox_level(workspace.path(), SandboxLevel::WorkspaceWrite, |_| {
+                Some(false)
+            })
+            .unwrap();
+        assert_eq!(level, SandboxLevel::ReadOnly);
+        let mut store = TrustStore::load(config.path()).unwrap();
+        assert_eq!(
+            store.decision(workspace.path()),
+            Some(TrustDecision::Untrusted)
+        );
+        let level = store
+            .sandbox_level(workspace.path(), SandboxLevel::WorkspaceWrite, never_asked)
+            .unwrap();
+        assert_eq!(level, SandboxLevel::ReadOnly);
+
+        store.record(workspace.path(), TrustDecision::Trusted);
+        assert!(store.is_trusted(workspace.path()));
+        assert_eq!(store.entries().len(), 1);
+
+        assert!(store.remove(workspace.path()));
+        assert!(!store.remove(workspace.path()));
+        store.save().unwrap();
+        let mut store = TrustStore::load(config.path()).unwrap();
+        assert_eq!(store.decision(workspace.path()), None);
+        let level = store
+            .sandbox_level(workspace.path(), SandboxLevel::WorkspaceWrite, |_| None)
+            .unwrap();
+        assert_eq!(level, SandboxLevel::ReadOnly);
+    }
+
+    #[test]
+    fn test_invalid_store_is_an_error() {
+        let config = TempDir::new().unwrap();
+        std::fs::write(config.path().join(TRUST_STORE_FILE), "{not json").unwrap();
+        let error = TrustStore::load(config.path()).unwrap_err();
+        assert!(error.to_string().contains(TRUST_STORE_FILE));
+    }
+}
@@ -0,0 +1,304 @@
+//! Escape regression suite: every known way out of the workspace, run
+//! against real file operations. Adding a vector is one line in a table.
+
+use anyhow::Result;
+use fennec_security::{
+    AuditEvent, AuditEventData, AuditSystem, AuditableCommandExecutor, AuditableFileOperations,
+    PolicyResult, SandboxLevel, SandboxPolicy,
+};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+/// How an escape vector tries to leave the workspace
+#[derive(Debug, Clone, Copy)]
+enum Access {
+    Read,
+    Write,
+    Delete,
+    Shell,
+}
+
+/// One way out of the workspace. `target` is relative to the workspace
+/// unless absolute, with `{outside}` standing for a directory beside it; for
+/// shell vectors it is the command line.
+struct EscapeVector {
+    name: &'static str,
+    access: Access,
+    target: &'static str,
+}
+
+const fn vector(name: &'static str, access: Access, target: &'static str) -> EscapeVector {
+    EscapeVector {
+        name,
+        access,
+        target,
+    }
+}
+
+const ESCAPE_VECTORS: &[EscapeVector] = &[
+    vector("parent traversal", Access::Read, "../outside/secret.txt"),
+    vector(
+        "deep parent traversal",
+        Access::Read,
+        "../../../../../../../../etc/passwd",
+    ),
+    vector(
+        "traversal through a subdirectory",
+        Access::Write,
+        "src/../../outside/planted.txt",
+    ),
+    vector(
+        "absolute path write",
+        Access::Write,
+        "{outside}/planted.txt",
+    ),
+    vector(
+        "absolute path delete",
+        Access::Delete,
+        "{outside}/secret.txt",
+    ),
+    vector("home variable", Access::Read, "$HOME/.ssh/id_rsa"),
+    vector("braced variable", Access::Write, "${HOME}/.bashrc"),
+    vector("tilde expansion", Access::Read, "~/.bashrc"),
+    vector(
+        "windows variable",
+        Access::Read,
+        "%USERPROFILE%\\.ssh\\id_rsa",
+    ),
+    vector(
+        "redirection into /etc",
+        Access::Shell,
+        "echo pwned > /etc/hosts",
+    ),
+    vector(
+        "append through tee",
+        Access::Shell,
+        "echo pwned | tee -a /etc/passwd",
+    ),
+    vector(
+        "variable expansion in a shell",
+        Access::Shell,
+        "cat $HOME/.ssh/id_rsa",
+    ),
+];
+
+#[cfg(unix)]
+const PLATFORM_ESCAPE_VECTORS: &[EscapeVector] = &[
+    vector("absolute path read", Access::Read, "/etc/passwd"),
+    vector("symlinked file", Access::Read, "secret_link"),
+    vector(
+        "write through a symlinked file",
+        Access::Write,
+        "secret_link",
+    ),
+    vector(
+        "symlinked directory",
+        Access::Write,
+        "outside_link/planted.txt",
+    ),
+    vector(
+        "nested path under a symlinked directory",
+        Access::Write,
+        "outside_link/a/b/planted.txt",
+    ),
+    vector(
+        "parent of a symlinked directory",
+        Access::Write,
+        "outside_link/../planted.txt",
+    ),
+    vector(
+        "delete through a symlinked directory",
+        Access::Delete,
+        "outside_link/secret.txt",
+    ),
+    vector(
+        "write through a renamed parent",
+        Access::Write,
+        "reports/summary.md",
+    ),
+];
+
+#[cfg(windows)]
+const PLATFORM_ESCAPE_VECTORS: &[EscapeVector] = &[
+    vector("absolute path read", Access::Read, "C:\\Windows\\win.ini"),
+    vector(
+        "backslash traversal",
+        Access::Read,
+        "..\\outside\\secret.txt",
+    ),
+    vector("UNC path", Access::Write, "\\\\server\\share\\planted.txt"),
+];
+
+/// Workspace and a directory beside it holding a secret, with the links a
+/// vector may try to escape through
+struct EscapeFixture {
+    _root: TempDir,
+    workspace: PathBuf,
+    outside: PathBuf,
+}
+
+impl EscapeFixture {
+    fn new() -> Result<Self> {
+        let root = TempDir::new()?;
+        let workspace = root.path().join("workspace");
+        let outside = root.path().join("outside");
+        std::fs::create_dir_all(workspace.join("src"))?;
+        std::fs::create_dir_all(&outside)?;
+        std::fs::write(outside.join("secret.txt"), "secret")?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::symlink;
+            symlink(outside.join("secret.txt"), workspace.join("secret_link"))?;
+            symlink(&outside, workspace.join("outside_link"))?;
+            // A directory that was renamed away and replaced by a link out
+            std::fs::create_dir(workspace.join("reports"))?;
+            std::fs::rename(workspace.join("reports"), workspace.join("reports.old"))?;
+            symlink(&outside, workspace.join("reports"))?;
+        }
+
+        Ok(Self {
+            _root: root,
+            workspace,
+            outside,
+        })
+    }
+
+    fn target(&self, vector: &EscapeVector) -> String {
+        vector
+            .target
+            .replace("{outside}", &self.outside.to_string_lossy())
+    }
+
+    /// The path the file operation is handed: the workspace is where the
+    /// sandbox resolves relative paths, but variables are passed unexpanded
+    fn operation_path(&self, target: &str) -> String {
+        if target.starts_with(['$', '~', '%']) {
+            target.to_string()
+        } else {
+            self.workspace.join(target).to_string_lossy().into_owned()
+        }
+    }
+
+    /// Everything outside the workspace, to check nothing changed
+    fn outside_listing(&self) -> Result<Vec<PathBuf>> {
+        fn walk(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+            for entry in std::fs::read_dir(dir)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    walk(&path, paths)?;
+                }
+                paths.push(path);
+            }
+            Ok(())
+        }
+
+        let mut paths = Vec::new();
+        walk(&self.outside, &mut paths)?;
+        paths.sort();
+        Ok(paths)
+    }
+}
+
+/// Every escape vector is denied by the policy, refused by the file
+/// operations and recorded as a sandbox violation
+#[tokio::test]
+async fn test_sandbox_escape_vectors_are_denied_and_audited() -> Result<()> {
+    let fixture = EscapeFixture::new()?;
+    let audit_dir = TempDir::new()?;
+    let mut config = fennec_core::config::Config::default();
+    config.security.audit_log_enabled = true;
+    config.security.audit_log_path = Some(audit_dir.path().to_path_buf());
+    let audit_system = Arc::new(AuditSystem::new(&config).await?);
+    let session_id = uuid::Uuid::new_v4();
+    let manager = audit_system.start_session(session_id, None, None).await?;
+    let executor = Arc::new(AuditableCommandExecutor::new(
+        audit_system.clone(),
+        session_id,
+    ));
+
+    let policy = SandboxPolicy::new(
+        SandboxLevel::WorkspaceWrite,
+        fixture.workspace.clone(),
+        false,
+    );
+    let file_ops =
+        AuditableFileOperations::new(executor.clone()).with_sandbox_policy(policy.clone());
+    let outside_before = fixture.outside_listing()?;
+
+    for vector in ESCAPE_VECTORS.iter().chain(PLATFORM_ESCAPE_VECTORS) {
+        let target = fixture.target(vector);
+        let path = fixture.operation_path(&target);
+
+        let (policy_result, refused) = match vector.access {
+            Access::Read => (
+                policy.check_read_path(Path::new(&target)),
+                file_ops.read_file(&path).await.is_err(),
+            ),
+            Access::Write => (
+                policy.check_write_path(Path::new(&target)),
+                file_ops.write_file(&path, b"pwned", false).await.is_err(),
+            ),
+            Access::Delete => (
+                policy.check_write_path(Path::new(&target)),
+                file_ops.delete_file(&path, false).await.is_err(),
+            ),
+            Access::Shell => {
+                let result = executor.check_shell_command(&policy, &target).await?;
+                let refused = matches!(result, PolicyResult::Deny(_));
+                (result, refused)
+            }
+        };
+
+        assert!(
+            matches!(policy_result, PolicyResult::Deny(_)),
+            "escape vector '{}' ({:?} {}) was not denied: {:?}",
+            vector.name,
+            vector.access,
+            target,
+            policy_result
+        );
+        assert!(
+            refused,
+            "escape vector '{}' ({:?} {}) was carried out",
+            vector.name, vector.access, target
+        );
+    }
+
+    assert_eq!(
+        fixture.outside_listing()?,
+        outside_before,
+        "an escape vector changed files outside the workspace"
+    );
+    assert_eq!(
+        std::fs::read_to_string(fixture.outside.join("secret.txt"))?,
+        "secret"
+    );
+
+    // Each refusal is on the audit trail, naming what was attempted
+    let trail = tokio::fs::read_to_string(manager.file_path()).await?;
+    let violations: Vec<String> = trail
+        .lines()
+        .filter_map(|line| serde_json::from_str::<AuditEvent>(line).ok())
+        .filter_map(|event| match event.data {
+            AuditEventData::SandboxViolation(violation) => Some(violation.attempted_action),
+            _ => None,
+        })
+        .collect();
+    for vector in ESCAPE_VECTORS.iter().chain(PLATFORM_ESCAPE_VECTORS) {
+        let target = fixture.target(vector);
+        let attempted = match vector.access {
+            Access::Shell => target.clone(),
+            _ => fixture.operation_path(&target),
+        };
+        assert!(
+            violations.iter().any(|action| action.ends_with(&attempted)),
+            "escape vector '{}' left no SandboxViolation event for {}",
+            vector.name,
+            attempted
+        );
+    }
+
+    Ok(())
+}
@@ -3,6 +3,7 @@
 use crate::{Error, Result};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use tracing::Level;
 
@@ -23,6 +24,27 @@ pub struct TelemetryConfig {
 
     /// Privacy and security settings
     pub privacy: PrivacyConfig,
+
+    /// Open a span per command execution, with child spans for the provider
+    /// calls and memory operations it makes
+    #[serde(default = "default_trace_commands")]
+    pub trace_commands: bool,
+
+    /// Write a crash report to the log directory when Fennec panics
+    #[serde(default = "default_crash_reports")]
+    pub crash_reports: bool,
+
+    /// OpenTelemetry trace export
+    #[serde(default)]
+    pub otlp: OtlpConfig,
+}
+
+fn default_trace_commands() -> bool {
+    true
+}
+
+fn default_crash_reports() -> bool {
+    true
 }
 
 /// Logging-specific configuration
@@ -49,6 +71,14 @@ pub struct LoggingConfig {
     /// Maximum log file size before rotation (in MB)
     pub max_file_size_mb: u64,
 
+    /// Also rotate every hour or day, whichever of size and time comes first
+    #[serde(default)]
+    pub rotation_interval: Option<RotationInterval>,
+
+    /// Gzip log files once they are rotated
+    #[serde(default)]
+    pub compress_rotated: bool,
+
     /// Include timestamps in logs
     pub include_timestamps: bool,
 
@@ -74,11 +104,86 @@ pub struct MetricsConfig {
     /// Enable Prometheus metrics export
     pub prometheus_enabled: bool,
 
-    /// Prometheus metrics endpoint port
+    /// Prometheus metrics endpoint port, bound on localhost
     pub prometheus_port: u16,
 
+    /// Write the Prometheus metrics to this file for node_exporter's
+    /// textfile collector instead of serving them over HTTP
+    #[serde(default)]
+    pub prometheus_textfile: Option<PathBuf>,
+
     /// Metrics collection interval (in seconds)
     pub collection_interval_seconds: u64,
+
+    /// Durations beyond which operations are reported as slow
+    #[serde(default)]
+    pub slow_operations: SlowOperationThresholds,
+}
+
+/// Durations, in milliseconds, beyond which an operation is reported as
+/// slow with a warning
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SlowOperationThresholds {
+    /// Execution of a command
+    pub command_execution_ms: u64,
+
+    /// Request to a model provider, including retries
+    pub provider_request_ms: u64,
+
+    /// Search of memory
+    pub memory_search_ms: u64,
+
+    /// Write of a file in the workspace
+    pub file_write_ms: u64,
+}
+
+impl SlowOperationThresholds {
+    pub const DEFAULT: Self = Self {
+        command_execution_ms: 30_000,
+        provider_request_ms: 60_000,
+        memory_search_ms: 1_000,
+        file_write_ms: 1_000,
+    };
+}
+
+impl Default for SlowOperationThresholds {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Export of spans to an OpenTelemetry collector such as Jaeger or Tempo,
+/// which needs the `otel` feature
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OtlpConfig {
+    /// Enable OTLP trace export
+    pub enabled: bool,
+
+    /// OTLP/HTTP traces endpoint of the collector
+    pub endpoint: String,
+
+    /// Headers sent with every export, e.g. for authentication
+    pub headers: HashMap<String, String>,
+
+    /// `service.name` the spans are reported under
+    pub service_name: String,
+
+    /// Fraction of traces to export, from 0.0 to 1.0
+    pub sampling_ratio: f64,
+}
+
+impl Default for OtlpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: "http://localhost:4318/v1/traces".to_string(),
+            headers: HashMap::new(),
+            service_name: "fennec".to_string(),
+            sampling_ratio: 1.0,
+        }
+    }
 }
 
 /// Log retention and rotation configuration
@@ -106,9 +211,19 @@ pub struct PrivacyConfig {
     /// Enable automatic sanitization of sensitive data
     pub sanitize_enabled: bool,
 
-    /// Patterns to redact from logs (regex patterns)
+    /// Regex patterns to redact from logs, on top of the built-in rules.
+    /// A pattern with a capture group keeps the first group, e.g. a field
+    /// name, and redacts the rest of the match.
     pub redaction_patterns: Vec<String>,
 
+    /// Built-in sanitization rules to turn off by name, e.g. `home_dir`
+    #[serde(default)]
+    pub disabled_rules: Vec<String>,
+
+    /// Paths under these prefixes are never redacted
+    #[serde(default)]
+    pub allowed_path_prefixes: Vec<PathBuf>,
+
     /// Fields to always redact
     pub redacted_fields: Vec<String>,
 
@@ -123,16 +238,10 @@ impl Default for PrivacyConfig {
     fn default() -> Self {
         Self {
             sanitize_enabled: true,
-            redaction_patterns: vec![
-                // API keys and tokens
-                r"(?i)(api_?key|token|secret|password)\s*[:=]\s*['\x22]?([a-zA-Z0-9_\-\.]+)['\x22]?".to_string(),
-                // Credit card numbers
-                r"\b\d{4}[\s\-]?\d{4}[\s\-]?\d{4}[\s\-]?\d{4}\b".to_string(),
-                // Social security numbers
-                r"\b\d{3}-\d{2}-\d{4}\b".to_string(),
-                // Email addresses (partial redaction)
-                r"\b([a-zA-Z0-9._%+-]+)@([a-zA-Z0-9.-]+\.[a-zA-Z]{2,})\b".to_string(),
-            ],
+            // The built-in rules cover API keys, tokens, emails and the like
+            redaction_patterns: Vec::new(),
+            disabled_rules: Vec::new(),
+            allowed_path_prefixes: Vec::new(),
             redacted_fields: vec![
                 "password".to_string(),
                 "api_key".to_string(),
@@ -180,6 +289,13 @@ impl From<Level> for LogLevel {
     }
 }
 
+/// Period of time-based log rotation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RotationInterval {
+    Hourly,
+    Daily,
+}
+
 /// Log output format
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum LogFormat {
@@ -205,6 +321,8 @@ impl Default for TelemetryConfig {
                 log_dir: default_log_dir,
                 log_file_name: "fennec".to_string(),
                 max_file_size_mb: 100,
+                rotation_interval: None,
+                compress_rotated: false,
                 include_timestamps: true,
                 include_location: false,
                 include_thread_info: false,
@@ -215,7 +333,9 @@ impl Default for TelemetryConfig {
                 correlation_tracking: true,
                 prometheus_enabled: false,
                 prometheus_port: 9090,
+                prometheus_textfile: None,
                 collection_interval_seconds: 60,
+                slow_operations: SlowOperationThresholds::default(),
             },
             retention: RetentionConfig {
                 max_files: 10,
@@ -224,28 +344,10 @@ impl Default for TelemetryConfig {
                 cleanup_interval_hours: 24,
                 max_total_size_mb: 1024, // 1GB
             },
-            privacy: PrivacyConfig {
-                sanitize_enabled: true,
-                redaction_patterns: vec![
-                    // API keys and tokens
-                    r"(?i)(api_?key|token|secret|password)\s*[:=]\s*['\x22]?([a-zA-Z0-9_\-\.]+)['\x22]?".to_string(),
-                    // Credit card numbers
-                    r"\b\d{4}[\s\-]?\d{4}[\s\-]?\d{4}[\s\-]?\d{4}\b".to_string(),
-                    // Social security numbers
-                    r"\b\d{3}-\d{2}-\d{4}\b".to_string(),
-                    // Email addresses (partial redaction)
-                    r"\b([a-zA-Z0-9._%+-]+)@([a-zA-Z0-9.-]+\.[a-zA-Z]{2,})\b".to_string(),
-                ],
-                redacted_fields: vec![
-                    "password".to_string(),
-                    "api_key".to_string(),
-                    "secret".to_string(),
-                    "token".to_string(),
-                    "authorization".to_string(),
-                ],
-                audit_trail: true,
-                audit_log_path: None,
-            },
+            privacy: PrivacyConfig::default(),
+            trace_commands: true,
+            crash_reports: true,
+            otlp: OtlpConfig::default(),
         }
     }
 }
@@ -335,6 +437,19 @@ impl TelemetryConfig {
             self.metrics.enabled = enabled.parse().unwrap_or(self.metrics.enabled);
         }
 
+        // Command tracing
+        if let Ok(enabled) = std::env::var("FENNEC_TRACE_COMMANDS") {
+            self.trace_commands = enabled.parse().unwrap_or(self.trace_commands);
+        }
+
+        // OpenTelemetry export
+        if let Ok(enabled) = std::env::var("FENNEC_OTLP_ENABLED") {
+            self.otlp.enabled = enabled.parse().unwrap_or(self.otlp.enabled);
+        }
+        if let Ok(endpoint) = std::env::var("FENNEC_OTLP_ENDPOINT") {
+            self.otlp.endpoint = endpoint;
+        }
+
         // Privacy settings
         if let Ok(enabled) = std::env::var("FENNEC_SANITIZE_LOGS") {
             self.privacy.sanitize_enabled =
@@ -404,13 +519,15 @@ impl TelemetryConfig {
             });
         }
 
-        // Validate regex patterns
-        for pattern in &self.privacy.redaction_patterns {
-            regex::Regex::new(pattern).map_err(|e| Error::Config {
-                message: format!("Invalid redaction pattern '{}': {}", pattern, e),
-            })?;
+        if !(0.0..=1.0).contains(&self.otlp.sampling_ratio) {
+            return Err(Error::Config {
+                message: "otlp.sampling_ratio must be between 0.0 and 1.0".to_string(),
+            });
         }
 
+        // Validate regex patterns and rule names
+        crate::sanitization::DataSanitizer::new(&self.privacy)?;
+
         Ok(())
     }
 }
@@ -465,6 +582,67 @@ mod tests {
         std::env::remove_var("FENNEC_LOG_FORMAT");
     }
 
+    #[test]
+    fn test_trace_commands_defaults_on() {
+        let mut config = toml::Value::try_from(TelemetryConfig::default()).unwrap();
+        config.as_table_mut().unwrap().remove("trace_commands");
+
+        let config: TelemetryConfig = config.try_into().unwrap();
+
+        assert!(config.trace_commands);
+    }
+
+    #[test]
+    fn test_slow_operation_thresholds_parsing() {
+        let config: MetricsConfig = toml::from_str(
+            r#"
+            enabled = true
+            performance_timing = true
+            correlation_tracking = true
+            prometheus_enabled = false
+            prometheus_port = 9090
+            collection_interval_seconds = 60
+
+            [slow_operations]
+            provider_request_ms = 5000
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.slow_operations.provider_request_ms, 5000);
+        assert_eq!(
+            config.slow_operations.memory_search_ms,
+            SlowOperationThresholds::DEFAULT.memory_search_ms
+        );
+    }
+
+    #[test]
+    fn test_otlp_config_parsing() {
+        let config: OtlpConfig = toml::from_str(
+            r#"
+            enabled = true
+            endpoint = "http://tempo:4318/v1/traces"
+            sampling_ratio = 0.25
+
+            [headers]
+            authorization = "Bearer abc"
+            "#,
+        )
+        .unwrap();
+
+        assert!(config.enabled);
+        assert_eq!(config.endpoint, "http://tempo:4318/v1/traces");
+        assert_eq!(config.headers["authorization"], "Bearer abc");
+        assert_eq!(config.service_name, "fennec");
+        assert_eq!(config.sampling_ratio, 0.25);
+
+        // Configurations written before OTLP export existed still load
+        let mut config = toml::Value::try_from(TelemetryConfig::default()).unwrap();
+        config.as_table_mut().unwrap().remove("otlp");
+        let config: TelemetryConfig = config.try_into().unwrap();
+        assert!(!config.otlp.enabled);
+    }
+
     #[test]
     fn test_config_validation() {
         let mut config = TelemetryConfig::default();
@@ -476,5 +654,11 @@ mod tests {
         config.privacy.redaction_patterns = vec!["[invalid".to_string()];
 
         assert!(config.validate().is_err());
+
+        let mut config = TelemetryConfig::default();
+        config.logging.file_enabled = false;
+        config.otlp.sampling_ratio = 1.5;
+
+        assert!(config.validate().is_err());
     }
 }
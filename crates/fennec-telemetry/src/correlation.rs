@@ -65,6 +65,9 @@ pub struct RequestContext {
     /// User or session identifier
     pub user_id: Option<String>,
 
+    /// Session the request was made in
+    pub session_id: Option<String>,
+
     /// Additional context metadata
     pub metadata: HashMap<String, String>,
 
@@ -81,6 +84,7 @@ impl RequestContext {
             timestamp: SystemTime::now(),
             operation,
             user_id: None,
+            session_id: None,
             metadata: HashMap::new(),
             parent_id: None,
         }
@@ -94,6 +98,7 @@ impl RequestContext {
             timestamp: SystemTime::now(),
             operation,
             user_id: self.user_id.clone(),
+            session_id: self.session_id.clone(),
             metadata: self.metadata.clone(),
             parent_id: Some(self.correlation_id.clone()),
         }
@@ -105,6 +110,12 @@ impl RequestContext {
         self
     }
 
+    /// Set the session this request was made in
+    pub fn with_session_id(mut self, session_id: String) -> Self {
+        self.session_id = Some(session_id);
+        self
+    }
+
     /// Add metadata to the request context
     pub fn with_metadata(mut self, key: String, value: String) -> Self {
         self.metadata.insert(key, value);
@@ -522,6 +533,7 @@ mod tests {
 
         assert_eq!(child.operation, "child_op");
         assert_eq!(child.user_id, Some("user123".to_string()));
+        assert_eq!(child.session_id, parent.session_id);
         assert_eq!(child.parent_id, Some(parent.correlation_id.clone()));
     }
 
@@ -0,0 +1,359 @@
+//! Crash reports and diagnostic bundles
+//!
+//! [`RecentLogsLayer`] keeps the last [`RECENT_LOG_LINES`] log lines in
+//! memory. When Fennec panics, the hook installed by
+//! [`install_panic_hook`] first gives the terminal back - through the
+//! function the TUI registers with [`set_terminal_restore`] - then writes a
+//! report with the panic message, a backtrace, those log lines and a
+//! summary of the configuration with secrets redacted to the log directory,
+//! and prints its path. [`DiagnosticReporter::write_bundle`] writes the same
+//! report on demand.
+
+use crate::{config::TelemetryConfig, sanitization::DataSanitizer, Error, Result};
+use chrono::Utc;
+use std::backtrace::Backtrace;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, Once, OnceLock, RwLock};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::{layer::Context, Layer};
+
+/// Number of log lines kept for crash reports
+pub const RECENT_LOG_LINES: usize = 200;
+
+type RestoreFn = Box<dyn Fn() + Send + Sync>;
+
+static TERMINAL_RESTORE: Mutex<Option<RestoreFn>> = Mutex::new(None);
+static REPORTER: RwLock<Option<DiagnosticReporter>> = RwLock::new(None);
+static HOOK: Once = Once::new();
+static RECENT_LOGS: OnceLock<RecentLogs> = OnceLock::new();
+
+/// Register how to put the terminal back in a usable state, e.g. leaving
+/// raw mode and the alternate screen. Called before a crash report is
+/// printed; must be safe to call when the terminal is already restored.
+pub fn set_terminal_restore(restore: impl Fn() + Send + Sync + 'static) {
+    *TERMINAL_RESTORE.lock().unwrap_or_else(|e| e.into_inner()) = Some(Box::new(restore));
+}
+
+/// Forget the function registered with [`set_terminal_restore`]
+pub fn clear_terminal_restore() {
+    *TERMINAL_RESTORE.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+fn restore_terminal() {
+    if let Some(restore) = TERMINAL_RESTORE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+    {
+        restore();
+    }
+}
+
+/// Ring buffer of the most recent log lines
+#[derive(Clone)]
+pub struct RecentLogs {
+    lines: Arc<Mutex<VecDeque<String>>>,
+    capacity: usize,
+}
+
+impl RecentLogs {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lines: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    pub fn push(&self, line: String) {
+        let mut lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        if lines.len() == self.capacity {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+
+    /// Lines currently kept, oldest first
+    pub fn lines(&self) -> Vec<String> {
+        self.lines
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .cloned()
+            .collect()
+    }
+}
+
+/// Log lines shared by every subscriber the telemetry system builds, so
+/// the one installed first keeps feeding crash reports after a re-init
+pub fn recent_logs() -> RecentLogs {
+    RECENT_LOGS.get_or_init(RecentLogs::default).clone()
+}
+
+impl Default for RecentLogs {
+    fn default() -> Self {
+        Self::new(RECENT_LOG_LINES)
+    }
+}
+
+/// Layer recording every event it sees into [`RecentLogs`]
+pub struct RecentLogsLayer {
+    logs: RecentLogs,
+}
+
+impl RecentLogsLayer {
+    pub fn new(logs: RecentLogs) -> Self {
+        Self { logs }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for RecentLogsLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut fields = LineFields::default();
+        event.record(&mut fields);
+        let metadata = event.metadata();
+        self.logs.push(format!(
+            "{} {:>5} {}: {}{}",
+            Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+            metadata.level(),
+            metadata.target(),
+            fields.message,
+            fields.rest
+        ));
+    }
+}
+
+#[derive(Default)]
+struct LineFields {
+    message: String,
+    rest: String,
+}
+
+impl Visit for LineFields {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.rest, " {}={:?}", field.name(), value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.rest, " {}={}", field.name(), value);
+        }
+    }
+}
+
+/// Writes crash reports and diagnostic bundles
+#[derive(Clone)]
+pub struct DiagnosticReporter {
+    log_dir: PathBuf,
+    config_summary: String,
+    logs: RecentLogs,
+    sanitizer: Option<Arc<DataSanitizer>>,
+}
+
+impl DiagnosticReporter {
+    pub fn new(config: &TelemetryConfig, logs: RecentLogs) -> Result<Self> {
+        let sanitizer = if config.privacy.sanitize_enabled {
+            Some(Arc::new(DataSanitizer::new(&config.privacy)?))
+        } else {
+            None
+        };
+        Ok(Self {
+            log_dir: config.logging.log_dir.clone(),
+            config_summary: config_summary(config, sanitizer.as_deref())?,
+            logs,
+            sanitizer,
+        })
+    }
+
+    /// Write a diagnostic bundle to the log directory, returning its path
+    pub fn write_bundle(&self) -> Result<PathBuf> {
+        self.write_report("diagnostics", None)
+    }
+
+    /// Write a crash report for a panic to the log directory
+    pub fn write_crash_report(&self, info: &PanicHookInfo<'_>) -> Result<PathBuf> {
+        let thread = std::thread::current();
+        let mut panic = format!(
+            "Thread '{}' panicked at {}\n{}\n",
+            thread.name().unwrap_or("<unnamed>"),
+            info.location()
+                .map(|location| location.to_string())
+                .unwrap_or_else(|| "an unknown location".to_string()),
+            panic_message(info),
+        );
+        let _ = write!(panic, "\nBacktrace:\n{}\n", Backtrace::force_capture());
+        self.write_report("crash", Some(&panic))
+    }
+
+    fn write_report(&self, kind: &str, panic: Option<&str>) -> Result<PathBuf> {
+        let now = Utc::now();
+        let mut report = String::new();
+        let _ = writeln!(report, "Fennec {} report", kind);
+        let _ = writeln!(report, "Version: {}", env!("CARGO_PKG_VERSION"));
+        let _ = writeln!(report, "Time: {}", now.format("%Y-%m-%dT%H:%M:%S%.3fZ"));
+        let _ = writeln!(
+            report,
+            "Platform: {} {}",
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        );
+        if let Some(panic) = panic {
+            let _ = write!(report, "\n== Panic ==\n{}", panic);
+        }
+        let _ = write!(report, "\n== Configuration ==\n{}", self.config_summary);
+
+        let lines = self.logs.lines();
+        let _ = writeln!(report, "\n== Last {} log lines ==", lines.len());
+        for line in &lines {
+            match &self.sanitizer {
+                Some(sanitizer) => report.push_str(&sanitizer.sanitize_value(line)),
+                None => report.push_str(line),
+            }
+            report.push('\n');
+        }
+
+        std::fs::create_dir_all(&self.log_dir)?;
+        let path = unique_path(
+            &self.log_dir,
+            &format!("fennec-{}-{}", kind, now.format("%Y%m%d_%H%M%S")),
+        );
+        std::fs::write(&path, report)?;
+        Ok(path)
+    }
+}
+
+/// Make `reporter` the one used by the panic hook and for diagnostic
+/// bundles, installing the hook the first time
+pub fn install_panic_hook(reporter: DiagnosticReporter) {
+    *REPORTER.write().unwrap_or_else(|e| e.into_inner()) = Some(reporter);
+
+    HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+
+            let reporter = REPORTER.read().unwrap_or_else(|e| e.into_inner()).clone();
+            if let Some(reporter) = reporter {
+                match reporter.write_crash_report(info) {
+                    Ok(path) => eprintln!(
+                        "Fennec crashed. A crash report was written to {}",
+                        path.display()
+                    ),
+                    Err(e) => eprintln!(
+                        "Fennec crashed and the crash report could not be written: {}",
+                        e
+                    ),
+                }
+            }
+
+            previous(info);
+        }));
+    });
+}
+
+/// Write a diagnostic bundle with the reporter installed by
+/// [`install_panic_hook`]
+pub fn write_diagnostic_bundle() -> Result<PathBuf> {
+    let reporter = REPORTER
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .ok_or_else(|| Error::System {
+            message: "Telemetry system is not initialized".to_string(),
+        })?;
+    reporter.write_bundle()
+}
+
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    if let Some(message) = info.payload().downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = info.payload().downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    }
+}
+
+/// The configuration as TOML, with header values and anything the
+/// sanitizer catches redacted
+fn config_summary(config: &TelemetryConfig, sanitizer: Option<&DataSanitizer>) -> Result<String> {
+    let mut config = config.clone();
+    for value in config.otlp.headers.values_mut() {
+        *value = "[REDACTED]".to_string();
+    }
+    let summary = toml::to_string_pretty(&config).map_err(|e| Error::Config {
+        message: format!("Failed to serialize telemetry config: {}", e),
+    })?;
+    Ok(match sanitizer {
+        Some(sanitizer) => sanitizer.sanitize_value(&summary).into_owned(),
+        None => summary,
+    })
+}
+
+/// `<dir>/<stem>.txt`, numbered if a report was already written this second
+fn unique_path(dir: &Path, stem: &str) -> PathBuf {
+    let mut path = dir.join(format!("{}.txt", stem));
+    let mut attempt = 0;
+    while path.exists() {
+        attempt += 1;
+        path = dir.join(format!("{}_{}.txt", stem, attempt));
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_recent_logs_keep_the_last_lines() {
+        let logs = RecentLogs::new(3);
+        let subscriber = tracing_subscriber::registry().with(RecentLogsLayer::new(logs.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            for i in 0..5 {
+                tracing::info!(request = i, "handled");
+            }
+        });
+
+        let lines = logs.lines();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with("handled request=2"), "{}", lines[0]);
+        assert!(lines[2].contains(" INFO "), "{}", lines[2]);
+    }
+
+    #[test]
+    fn test_diagnostic_bundle_redacts_secrets() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = TelemetryConfig::default();
+        config.logging.log_dir = temp_dir.path().to_path_buf();
+        config
+            .otlp
+            .headers
+            .insert("x-api-key".to_string(), "sk-live-123456".to_string());
+        let logs = RecentLogs::default();
+        logs.push("connecting with api_key=sk-live-123456".to_string());
+
+        let reporter = DiagnosticReporter::new(&config, logs).unwrap();
+        let path = reporter.write_bundle().unwrap();
+        let report = std::fs::read_to_string(&path).unwrap();
+
+        assert!(path.starts_with(temp_dir.path()));
+        assert!(report.starts_with("Fennec diagnostics report"));
+        assert!(report.contains(env!("CARGO_PKG_VERSION")));
+        assert!(report.contains("== Last 1 log lines =="));
+        assert!(report.contains("connecting with api_key=[REDACTED]"));
+        assert!(!report.contains("sk-live-123456"));
+        assert!(!report.contains("== Panic =="));
+    }
+}
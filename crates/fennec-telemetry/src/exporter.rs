@@ -0,0 +1,162 @@
+//! Prometheus export of the metrics recorded through [`crate::metrics`]
+//!
+//! The exporter installs itself as the global metrics recorder and either
+//! serves the metrics on `127.0.0.1:<prometheus_port>`, which needs the
+//! `prometheus-http` feature, or writes them periodically to
+//! `prometheus_textfile` for node_exporter's textfile collector.
+
+use crate::{config::MetricsConfig, metrics::describe_metrics, Error, Result};
+use metrics_exporter_prometheus::{BuildError, Matcher, PrometheusBuilder, PrometheusHandle};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Buckets of the latency histograms, in seconds
+const LATENCY_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0,
+];
+
+/// Installed Prometheus exporter; the endpoint or textfile writer stops when
+/// it is dropped
+pub struct PrometheusExporter {
+    handle: PrometheusHandle,
+    textfile: Option<PathBuf>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl PrometheusExporter {
+    /// Install the exporter as the global metrics recorder. Must be called
+    /// from within a Tokio runtime.
+    pub fn install(config: &MetricsConfig) -> Result<Self> {
+        let builder = PrometheusBuilder::new()
+            .set_buckets_for_metric(Matcher::Suffix("_seconds".to_string()), LATENCY_BUCKETS)
+            .map_err(build_error)?;
+
+        let exporter = match &config.prometheus_textfile {
+            Some(path) => {
+                let handle = builder.install_recorder().map_err(build_error)?;
+                let interval = Duration::from_secs(config.collection_interval_seconds.max(1));
+                let task = spawn_textfile_writer(handle.clone(), path.clone(), interval);
+                info!("Writing Prometheus metrics to {}", path.display());
+                Self {
+                    handle,
+                    textfile: Some(path.clone()),
+                    task: Some(task),
+                }
+            }
+            None => {
+                let (handle, task) = serve_http(builder, config.prometheus_port)?;
+                Self {
+                    handle,
+                    textfile: None,
+                    task: Some(task),
+                }
+            }
+        };
+
+        describe_metrics();
+        Ok(exporter)
+    }
+
+    /// Current metrics in the Prometheus text format
+    pub fn render(&self) -> String {
+        self.handle.render()
+    }
+
+    /// Write the current metrics to `path`, replacing it atomically so
+    /// node_exporter never reads a partial file
+    pub fn write_textfile(&self, path: &Path) -> Result<()> {
+        write_textfile(&self.handle, path)
+    }
+}
+
+impl Drop for PrometheusExporter {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+        // Keep what was recorded since the last periodic write
+        if let Some(path) = &self.textfile {
+            if let Err(e) = write_textfile(&self.handle, path) {
+                warn!(
+                    "Failed to write Prometheus metrics to {}: {}",
+                    path.display(),
+                    e
+                );
+            }
+        }
+    }
+}
+
+#[cfg(feature = "prometheus-http")]
+fn serve_http(builder: PrometheusBuilder, port: u16) -> Result<(PrometheusHandle, JoinHandle<()>)> {
+    let (recorder, endpoint) = builder
+        .with_http_listener(([127, 0, 0, 1], port))
+        .build()
+        .map_err(build_error)?;
+    let handle = recorder.handle();
+    metrics::set_global_recorder(recorder).map_err(|e| Error::System {
+        message: format!("Failed to install metrics recorder: {}", e),
+    })?;
+
+    let task = tokio::spawn(async move {
+        if let Err(e) = endpoint.await {
+            warn!("Prometheus metrics endpoint stopped: {}", e);
+        }
+    });
+    info!(
+        "Serving Prometheus metrics on http://127.0.0.1:{}/metrics",
+        port
+    );
+    Ok((handle, task))
+}
+
+#[cfg(not(feature = "prometheus-http"))]
+fn serve_http(
+    _builder: PrometheusBuilder,
+    _port: u16,
+) -> Result<(PrometheusHandle, JoinHandle<()>)> {
+    Err(Error::Config {
+        message: "Serving Prometheus metrics over HTTP needs the prometheus-http feature; \
+                  set metrics.prometheus_textfile to write them to a file instead"
+            .to_string(),
+    })
+}
+
+fn spawn_textfile_writer(
+    handle: PrometheusHandle,
+    path: PathBuf,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(interval);
+        loop {
+            ticks.tick().await;
+            if let Err(e) = write_textfile(&handle, &path) {
+                warn!(
+                    "Failed to write Prometheus metrics to {}: {}",
+                    path.display(),
+                    e
+                );
+            }
+        }
+    })
+}
+
+fn write_textfile(handle: &PrometheusHandle, path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".tmp");
+    std::fs::write(&partial, handle.render())?;
+    std::fs::rename(&partial, path)?;
+    Ok(())
+}
+
+fn build_error(error: BuildError) -> Error {
+    Error::System {
+        message: format!("Failed to set up Prometheus exporter: {}", error),
+    }
+}
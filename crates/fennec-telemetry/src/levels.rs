@@ -0,0 +1,214 @@
+//! Runtime adjustment of the log level filter
+//!
+//! The filter is a list of `EnvFilter` directives such as `info` or
+//! `fennec_provider=debug`. [`LevelControl`] keeps the list and swaps the
+//! filter of the running subscriber through tracing-subscriber's reload
+//! handle whenever it changes, so verbosity can be raised without a restart.
+
+use crate::{config::LogLevel, Error, Result};
+use std::sync::Mutex;
+use tracing::Level;
+use tracing_subscriber::{filter::Directive, reload, EnvFilter, Registry};
+
+/// Filter layer whose directives [`LevelControl`] can change
+pub type ReloadableFilter = reload::Layer<EnvFilter, Registry>;
+
+/// Handle changing the directives of a [`ReloadableFilter`]
+pub struct LevelControl {
+    handle: reload::Handle<EnvFilter, Registry>,
+    directives: Mutex<Vec<String>>,
+}
+
+impl LevelControl {
+    /// Create a filter layer from `directives`, along with its control.
+    /// Directives that don't parse are dropped, as `EnvFilter::new` does.
+    pub fn new(directives: Vec<String>) -> (ReloadableFilter, Self) {
+        let directives: Vec<String> = directives
+            .into_iter()
+            .filter(|directive| directive.parse::<Directive>().is_ok())
+            .collect();
+        let (layer, handle) = reload::Layer::new(EnvFilter::new(directives.join(",")));
+        (
+            layer,
+            Self {
+                handle,
+                directives: Mutex::new(directives),
+            },
+        )
+    }
+
+    /// Set the level of `target`, e.g. `fennec_provider`, and of the modules
+    /// below it; or the default level when `target` is `None`
+    pub fn set_level(&self, target: Option<&str>, level: LogLevel) -> Result<()> {
+        let level = level_name(level);
+        let directive = match target {
+            Some(target) => {
+                validate_target(target)?;
+                format!("{}={}", target, level)
+            }
+            None => level.to_string(),
+        };
+
+        let mut directives = self.directives.lock().unwrap();
+        let mut updated = directives.clone();
+        // A directive replaces the earlier one for the same target
+        updated.retain(|existing| directive_target(existing) != target);
+        match target {
+            Some(_) => updated.push(directive),
+            None => updated.insert(0, directive),
+        }
+
+        let filter = build_filter(&updated)?;
+        self.handle.reload(filter).map_err(|e| Error::System {
+            message: format!("Failed to update log filter: {}", e),
+        })?;
+        *directives = updated;
+        Ok(())
+    }
+
+    /// Current directives, the default level first when there is one
+    pub fn directives(&self) -> Vec<String> {
+        self.directives.lock().unwrap().clone()
+    }
+
+    /// Current default level, if the directives set one
+    pub fn level(&self) -> Option<LogLevel> {
+        self.directives
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|directive| directive_target(directive).is_none())
+            .and_then(|directive| directive.parse::<Level>().ok())
+            .map(LogLevel::from)
+    }
+}
+
+fn build_filter(directives: &[String]) -> Result<EnvFilter> {
+    EnvFilter::try_new(directives.join(",")).map_err(|e| Error::Config {
+        message: format!("Invalid log filter '{}': {}", directives.join(","), e),
+    })
+}
+
+fn level_name(level: LogLevel) -> &'static str {
+    match level {
+        LogLevel::Trace => "trace",
+        LogLevel::Debug => "debug",
+        LogLevel::Info => "info",
+        LogLevel::Warn => "warn",
+        LogLevel::Error => "error",
+    }
+}
+
+/// Target a directive applies to, `None` for a bare level
+fn directive_target(directive: &str) -> Option<&str> {
+    directive.split_once('=').map(|(target, _)| target)
+}
+
+/// Targets are module paths such as `fennec_provider::openai`
+fn validate_target(target: &str) -> Result<()> {
+    let valid = target.split("::").all(|segment| {
+        !segment.is_empty()
+            && segment
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    });
+    if valid {
+        return Ok(());
+    }
+
+    let message = if target.contains('-') {
+        format!(
+            "Invalid log target '{}': module paths use underscores, e.g. '{}'",
+            target,
+            target.replace('-', "_")
+        )
+    } else {
+        format!("Invalid log target '{}': expected a module path", target)
+    };
+    Err(Error::Config { message })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tracing::{Event, Subscriber};
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+    use tracing_subscriber::Layer;
+
+    /// Layer recording the target of every event it sees
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<String>>>);
+
+    impl Capture {
+        fn take(&self) -> Vec<String> {
+            std::mem::take(&mut *self.0.lock().unwrap())
+        }
+    }
+
+    impl<S: Subscriber> Layer<S> for Capture {
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(event.metadata().target().to_string());
+        }
+    }
+
+    #[test]
+    fn test_levels_change_at_runtime() {
+        let (filter, control) = LevelControl::new(vec!["info".to_string()]);
+        let capture = Capture::default();
+        let subscriber = Registry::default().with(filter).with(capture.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug!(target: "fennec_core", "hidden");
+            assert!(capture.take().is_empty());
+
+            control.set_level(None, LogLevel::Debug).unwrap();
+            tracing::debug!(target: "fennec_core", "shown");
+            assert_eq!(capture.take(), ["fennec_core"]);
+
+            control
+                .set_level(Some("fennec_provider"), LogLevel::Trace)
+                .unwrap();
+            tracing::trace!(target: "fennec_provider::openai", "shown");
+            tracing::trace!(target: "fennec_core", "hidden");
+            assert_eq!(capture.take(), ["fennec_provider::openai"]);
+
+            control.set_level(None, LogLevel::Warn).unwrap();
+            tracing::info!(target: "fennec_core", "hidden");
+            tracing::trace!(target: "fennec_provider", "shown");
+            assert_eq!(capture.take(), ["fennec_provider"]);
+        });
+
+        assert_eq!(control.directives(), ["warn", "fennec_provider=trace"]);
+        assert!(matches!(control.level(), Some(LogLevel::Warn)));
+    }
+
+    #[test]
+    fn test_unparsable_directives_are_dropped() {
+        let (_filter, control) =
+            LevelControl::new(vec!["debug".to_string(), "hyper=loud".to_string()]);
+
+        assert_eq!(control.directives(), ["debug"]);
+        control.set_level(Some("hyper"), LogLevel::Warn).unwrap();
+        assert_eq!(control.directives(), ["debug", "hyper=warn"]);
+    }
+
+    #[test]
+    fn test_invalid_target_is_rejected() {
+        let (_filter, control) = LevelControl::new(vec!["info".to_string()]);
+
+        let error = control
+            .set_level(Some("fennec-provider"), LogLevel::Debug)
+            .unwrap_err();
+        assert!(error.to_string().contains("fennec_provider"), "{}", error);
+        assert!(control.set_level(Some(""), LogLevel::Debug).is_err());
+        assert!(control
+            .set_level(Some("fennec_provider=debug"), LogLevel::Debug)
+            .is_err());
+
+        assert_eq!(control.directives(), ["info"]);
+    }
+}
@@ -9,7 +9,13 @@
 //! - **Privacy & Security**: Automatic sanitization of sensitive data
 //! - **Performance Metrics**: Request tracing, timing, and correlation IDs
 //! - **Configurable**: Runtime log level adjustment and environment-based config
+//! - **Crash Reports**: Panic message, backtrace and recent logs written to
+//!   the log directory, also available on demand as a diagnostic bundle
 //! - **Retention Policies**: Automatic cleanup and archival of old logs
+//! - **Prometheus Export**: Command, provider, memory and approval metrics
+//!   served on localhost or written for node_exporter (`prometheus` feature)
+//! - **OpenTelemetry Export**: Spans shipped to an OTLP collector such as
+//!   Jaeger or Tempo (`otel` feature)
 //!
 //! ## Quick Start
 //!
@@ -28,19 +34,31 @@
 
 pub mod config;
 pub mod correlation;
+pub mod crash;
+#[cfg(feature = "prometheus")]
+pub mod exporter;
 pub mod filters;
 pub mod formatters;
+pub mod levels;
 pub mod metrics;
+#[cfg(feature = "otel")]
+pub mod otel;
 pub mod retention;
 pub mod rotation;
 pub mod sanitization;
+pub mod spans;
 pub mod system;
 
 #[cfg(test)]
 mod tests;
 
-pub use config::{LogFormat, LogLevel, TelemetryConfig};
+pub use config::{
+    LogFormat, LogLevel, OtlpConfig, RotationInterval, SlowOperationThresholds, TelemetryConfig,
+};
 pub use correlation::{CorrelationId, RequestContext};
+#[cfg(feature = "prometheus")]
+pub use exporter::PrometheusExporter;
+pub use spans::instrument_with_context;
 pub use system::{TelemetryGuard, TelemetrySystem};
 
 // Re-export commonly used tracing macros and types
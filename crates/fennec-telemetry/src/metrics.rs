@@ -1,14 +1,17 @@
 //! Performance metrics and instrumentation
 
-use crate::{config::MetricsConfig, Error, Result};
+use crate::{
+    config::{MetricsConfig, SlowOperationThresholds},
+    Error, Result,
+};
 use metrics::{
     counter, histogram, Counter, Gauge, Histogram, Key, KeyName, Metadata, Recorder, SharedString,
     Unit,
 };
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
-use tracing::{debug, info, Subscriber};
+use tracing::{debug, info, warn, Subscriber};
 use tracing_subscriber::{layer::Context, Layer};
 
 /// Layer that collects performance metrics from tracing events
@@ -475,6 +478,208 @@ impl MetricsUtil {
     }
 }
 
+/// Counter of command executions, by `command` and `outcome`
+pub const COMMAND_EXECUTIONS: &str = "fennec_command_executions_total";
+/// Histogram of command execution times, by `command`
+pub const COMMAND_DURATION: &str = "fennec_command_duration_seconds";
+/// Counter of provider requests, by `provider`, `model` and `outcome`
+pub const PROVIDER_REQUESTS: &str = "fennec_provider_requests_total";
+/// Histogram of provider request latencies, by `provider` and `model`
+pub const PROVIDER_LATENCY: &str = "fennec_provider_request_duration_seconds";
+/// Counter of tokens used, by `provider`, `model` and `kind`
+pub const PROVIDER_TOKENS: &str = "fennec_provider_tokens_total";
+/// Histogram of memory search latencies, by `kind`
+pub const MEMORY_SEARCH_LATENCY: &str = "fennec_memory_search_duration_seconds";
+/// Counter of approval decisions, by `risk_level` and `decision`
+pub const APPROVAL_DECISIONS: &str = "fennec_approval_decisions_total";
+/// Histogram of workspace file write times
+pub const FILE_WRITE_DURATION: &str = "fennec_file_write_duration_seconds";
+/// Counter of operations slower than their threshold, by `class`
+pub const SLOW_OPERATIONS: &str = "fennec_slow_operations_total";
+
+/// Thresholds the `record_*` functions compare durations against
+static SLOW_THRESHOLDS: RwLock<SlowOperationThresholds> =
+    RwLock::new(SlowOperationThresholds::DEFAULT);
+
+/// Describe the metrics recorded by the functions below to the installed
+/// recorder
+pub fn describe_metrics() {
+    metrics::describe_counter!(COMMAND_EXECUTIONS, "Commands executed");
+    metrics::describe_histogram!(
+        COMMAND_DURATION,
+        Unit::Seconds,
+        "Time taken to execute commands"
+    );
+    metrics::describe_counter!(PROVIDER_REQUESTS, "Requests sent to model providers");
+    metrics::describe_histogram!(
+        PROVIDER_LATENCY,
+        Unit::Seconds,
+        "Time taken by model provider requests, including retries"
+    );
+    metrics::describe_counter!(PROVIDER_TOKENS, "Tokens used by model provider requests");
+    metrics::describe_histogram!(
+        MEMORY_SEARCH_LATENCY,
+        Unit::Seconds,
+        "Time taken to search memory"
+    );
+    metrics::describe_counter!(APPROVAL_DECISIONS, "Decisions on approval requests");
+    metrics::describe_histogram!(
+        FILE_WRITE_DURATION,
+        Unit::Seconds,
+        "Time taken to write workspace files"
+    );
+    metrics::describe_counter!(SLOW_OPERATIONS, "Operations slower than their threshold");
+}
+
+fn outcome(success: bool) -> &'static str {
+    if success {
+        "success"
+    } else {
+        "failure"
+    }
+}
+
+/// Record the execution of a command
+pub fn record_command_execution(command: &str, success: bool, duration: Duration) {
+    counter!(
+        COMMAND_EXECUTIONS,
+        "command" => command.to_string(),
+        "outcome" => outcome(success)
+    )
+    .increment(1);
+    histogram!(COMMAND_DURATION, "command" => command.to_string()).record(duration.as_secs_f64());
+    check_slow_operation(OperationClass::CommandExecution, command, duration);
+}
+
+/// Record a request to a model provider
+pub fn record_provider_request(provider: &str, model: &str, success: bool, latency: Duration) {
+    counter!(
+        PROVIDER_REQUESTS,
+        "provider" => provider.to_string(),
+        "model" => model.to_string(),
+        "outcome" => outcome(success)
+    )
+    .increment(1);
+    histogram!(
+        PROVIDER_LATENCY,
+        "provider" => provider.to_string(),
+        "model" => model.to_string()
+    )
+    .record(latency.as_secs_f64());
+    check_slow_operation(
+        OperationClass::ProviderRequest,
+        &format!("{}/{}", provider, model),
+        latency,
+    );
+}
+
+/// Record the tokens a provider request used
+pub fn record_token_usage(provider: &str, model: &str, prompt_tokens: u64, completion_tokens: u64) {
+    for (kind, tokens) in [("prompt", prompt_tokens), ("completion", completion_tokens)] {
+        counter!(
+            PROVIDER_TOKENS,
+            "provider" => provider.to_string(),
+            "model" => model.to_string(),
+            "kind" => kind
+        )
+        .increment(tokens);
+    }
+}
+
+/// Record a memory search; `kind` tells apart the kinds of search
+pub fn record_memory_search(kind: &str, latency: Duration) {
+    histogram!(MEMORY_SEARCH_LATENCY, "kind" => kind.to_string()).record(latency.as_secs_f64());
+    check_slow_operation(OperationClass::MemorySearch, kind, latency);
+}
+
+/// Record a write of a file in the workspace
+pub fn record_file_write(path: &str, duration: Duration) {
+    histogram!(FILE_WRITE_DURATION).record(duration.as_secs_f64());
+    check_slow_operation(OperationClass::FileWrite, path, duration);
+}
+
+/// Record the decision on an approval request
+pub fn record_approval_decision(risk_level: &str, decision: &str) {
+    counter!(
+        APPROVAL_DECISIONS,
+        "risk_level" => risk_level.to_string(),
+        "decision" => decision.to_string()
+    )
+    .increment(1);
+}
+
+/// Kind of operation, each with its own slow threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationClass {
+    CommandExecution,
+    ProviderRequest,
+    MemorySearch,
+    FileWrite,
+}
+
+impl OperationClass {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OperationClass::CommandExecution => "command_execution",
+            OperationClass::ProviderRequest => "provider_request",
+            OperationClass::MemorySearch => "memory_search",
+            OperationClass::FileWrite => "file_write",
+        }
+    }
+}
+
+/// Replace all slow operation thresholds
+pub fn set_slow_thresholds(thresholds: SlowOperationThresholds) {
+    *SLOW_THRESHOLDS.write().unwrap_or_else(|e| e.into_inner()) = thresholds;
+}
+
+/// Change the slow threshold of one class of operations
+pub fn set_slow_threshold(class: OperationClass, threshold: Duration) {
+    let mut thresholds = SLOW_THRESHOLDS.write().unwrap_or_else(|e| e.into_inner());
+    let millis = u64::try_from(threshold.as_millis()).unwrap_or(u64::MAX);
+    match class {
+        OperationClass::CommandExecution => thresholds.command_execution_ms = millis,
+        OperationClass::ProviderRequest => thresholds.provider_request_ms = millis,
+        OperationClass::MemorySearch => thresholds.memory_search_ms = millis,
+        OperationClass::FileWrite => thresholds.file_write_ms = millis,
+    }
+}
+
+/// Current slow threshold of a class of operations
+pub fn slow_threshold(class: OperationClass) -> Duration {
+    let thresholds = SLOW_THRESHOLDS.read().unwrap_or_else(|e| e.into_inner());
+    Duration::from_millis(match class {
+        OperationClass::CommandExecution => thresholds.command_execution_ms,
+        OperationClass::ProviderRequest => thresholds.provider_request_ms,
+        OperationClass::MemorySearch => thresholds.memory_search_ms,
+        OperationClass::FileWrite => thresholds.file_write_ms,
+    })
+}
+
+/// Warn about `operation` and count it if it took longer than the threshold
+/// of its class. Returns whether it did.
+pub fn check_slow_operation(class: OperationClass, operation: &str, duration: Duration) -> bool {
+    let threshold = slow_threshold(class);
+    if duration <= threshold {
+        return false;
+    }
+
+    counter!(SLOW_OPERATIONS, "class" => class.as_str()).increment(1);
+    warn!(
+        telemetry.event = "slow_operation",
+        operation_class = class.as_str(),
+        operation = %operation,
+        duration_ms = duration.as_millis() as u64,
+        threshold_ms = threshold.as_millis() as u64,
+        correlation_id = %crate::spans::current_correlation_id(),
+        "Slow {}: {} took {}ms",
+        class.as_str().replace('_', " "),
+        operation,
+        duration.as_millis()
+    );
+    true
+}
+
 #[cfg(feature = "prometheus")]
 fn format_labels(labels: std::slice::Iter<'_, metrics::Label>) -> String {
     let label_pairs: Vec<String> = labels
@@ -0,0 +1,166 @@
+//! OpenTelemetry export of the spans recorded through `tracing`
+//!
+//! The layer built here sits alongside the other layers of the subscriber and
+//! hands finished spans to a tracer provider, which sends them in batches to
+//! an OTLP/HTTP collector such as Jaeger or Tempo. Export never blocks the
+//! application: spans are dropped while the collector is unreachable.
+
+use crate::{config::OtlpConfig, Error, Result};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig, WithHttpConfig};
+use opentelemetry_sdk::trace::{Sampler, SdkTracer, SdkTracerProvider};
+use opentelemetry_sdk::Resource;
+use std::time::Duration;
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::registry::LookupSpan;
+
+/// How long an export may take, so an unreachable collector cannot hold up
+/// shutdown for long
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long to wait for the collector when checking that it is reachable
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Build the tracer provider exporting to the collector of `config`
+pub fn tracer_provider(config: &OtlpConfig) -> Result<SdkTracerProvider> {
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(config.endpoint.clone())
+        .with_headers(config.headers.clone())
+        .with_timeout(EXPORT_TIMEOUT)
+        .build()
+        .map_err(|e| Error::Config {
+            message: format!("Failed to set up OTLP exporter: {}", e),
+        })?;
+
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+            config.sampling_ratio,
+        ))))
+        .with_resource(
+            Resource::builder()
+                .with_service_name(config.service_name.clone())
+                .build(),
+        )
+        .build())
+}
+
+/// Layer handing the spans of the subscriber to `provider`
+pub fn layer<S>(provider: &SdkTracerProvider) -> OpenTelemetryLayer<S, SdkTracer>
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+{
+    tracing_opentelemetry::layer().with_tracer(provider.tracer("fennec"))
+}
+
+/// Whether a connection to the collector at `endpoint` can be opened
+pub async fn collector_reachable(endpoint: &str) -> bool {
+    let Some(address) = socket_address(endpoint) else {
+        return false;
+    };
+    matches!(
+        tokio::time::timeout(CONNECT_TIMEOUT, tokio::net::TcpStream::connect(address)).await,
+        Ok(Ok(_))
+    )
+}
+
+/// `host:port` of an endpoint URL, with the scheme's default port if none
+fn socket_address(endpoint: &str) -> Option<String> {
+    let (scheme, rest) = endpoint.split_once("://")?;
+    let authority = rest.split('/').next().filter(|a| !a.is_empty())?;
+    let has_port = authority
+        .rsplit_once(':')
+        .is_some_and(|(_, port)| port.parse::<u16>().is_ok());
+    if has_port {
+        return Some(authority.to_string());
+    }
+    match scheme {
+        "http" => Some(format!("{}:80", authority)),
+        "https" => Some(format!("{}:443", authority)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry_sdk::trace::InMemorySpanExporter;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::{fmt, layer::SubscriberExt, Registry};
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_layer_exports_alongside_local_logging() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let logs = Buffer::default();
+        let writer = logs.clone();
+        let subscriber = Registry::default()
+            .with(
+                fmt::layer()
+                    .with_ansi(false)
+                    .with_writer(move || writer.clone()),
+            )
+            .with(layer(&provider));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = tracing::info_span!("command", command = "ask").entered();
+            tracing::info!("asking the model");
+        });
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].name, "command");
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        assert!(logs.contains("asking the model"), "{}", logs);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_collector_is_detected() {
+        // Take a free port and release it so nothing listens on it
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let config = OtlpConfig {
+            enabled: true,
+            endpoint: format!("http://127.0.0.1:{}/v1/traces", port),
+            ..Default::default()
+        };
+
+        // Building the provider does not need the collector
+        let provider = tracer_provider(&config).unwrap();
+        assert!(!collector_reachable(&config.endpoint).await);
+        provider.shutdown().unwrap();
+    }
+
+    #[test]
+    fn test_socket_address() {
+        assert_eq!(
+            socket_address("http://localhost:4318/v1/traces").as_deref(),
+            Some("localhost:4318")
+        );
+        assert_eq!(
+            socket_address("https://tempo.example.com/v1/traces").as_deref(),
+            Some("tempo.example.com:443")
+        );
+        assert_eq!(socket_address("localhost:4318"), None);
+    }
+}
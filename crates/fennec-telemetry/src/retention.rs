@@ -138,7 +138,11 @@ impl RetentionManager {
                 Ok(compressed_path) => {
                     compressed_count += 1;
 
-                    // Update the file info in our list
+                    // Update the file info in our list; the archive counts
+                    // toward the size limit with its compressed size
+                    if let Ok(metadata) = std::fs::metadata(&compressed_path) {
+                        log_files[index].size = metadata.len();
+                    }
                     log_files[index].path = compressed_path;
                 }
                 Err(e) => {
@@ -464,4 +468,38 @@ mod tests {
         assert_eq!(report.files_removed_by_age, 3);
         assert_eq!(report.final_file_count, 2);
     }
+
+    #[tokio::test]
+    async fn test_compressed_archives_count_toward_size_limit() {
+        let temp_dir = TempDir::new().unwrap();
+
+        // Three 600 KB archives, the first the oldest
+        for i in 0..3 {
+            let file_path = temp_dir.path().join(format!("test_{}.log.gz", i));
+            tokio::fs::write(&file_path, vec![b'x'; 600 * 1024])
+                .await
+                .unwrap();
+            let time = SystemTime::now() - Duration::from_secs((3 - i) * 3600);
+            filetime::set_file_mtime(&file_path, filetime::FileTime::from_system_time(time))
+                .unwrap();
+        }
+
+        let config = RetentionConfig {
+            max_files: 100,    // Don't remove by count
+            max_age_days: 365, // Don't remove by age
+            compress_old_files: false,
+            cleanup_interval_hours: 1,
+            max_total_size_mb: 1,
+        };
+
+        let manager = RetentionManager::new(config).await.unwrap();
+        let report = manager
+            .perform_cleanup(temp_dir.path(), "test")
+            .await
+            .unwrap();
+
+        assert_eq!(report.initial_file_count, 3);
+        assert_eq!(report.files_removed_by_size, 2);
+        assert!(temp_dir.path().join("test_2.log.gz").exists());
+    }
 }
@@ -1,22 +1,59 @@
 //! Log file rotation implementation
 
-use crate::Result;
-use chrono::{DateTime, Utc};
-use std::fs::OpenOptions;
+use crate::{config::RotationInterval, Result};
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+use std::fs::{File, OpenOptions};
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::SystemTime;
 use tracing_subscriber::fmt::MakeWriter;
 
-/// A writer that automatically rotates log files based on size
+/// Source of the current time for time-based rotation
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// Clock reading the system time
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+impl RotationInterval {
+    /// Start of the period after the one `time` falls in
+    fn next_boundary(&self, time: DateTime<Utc>) -> DateTime<Utc> {
+        let period = match self {
+            RotationInterval::Hourly => TimeDelta::hours(1),
+            RotationInterval::Daily => TimeDelta::days(1),
+        };
+        time.duration_trunc(period).unwrap_or(time) + period
+    }
+}
+
+/// Open log file shared by all clones of a writer
+#[derive(Default)]
+struct WriterState {
+    file: Option<File>,
+    size: u64,
+    /// When the open file is due for time-based rotation
+    rotate_at: Option<DateTime<Utc>>,
+}
+
+/// A writer that rotates log files once they reach a size or, with an
+/// interval set, at the start of every hour or day, whichever comes first.
+/// Rotated files can be gzipped.
 pub struct RotatingFileWriter {
     base_path: PathBuf,
     base_name: String,
     max_size_bytes: u64,
-    current_file: Arc<Mutex<Option<std::fs::File>>>,
-    current_size: Arc<Mutex<u64>>,
-    current_file_path: Arc<Mutex<Option<PathBuf>>>,
+    interval: Option<RotationInterval>,
+    compress: bool,
+    clock: Arc<dyn Clock>,
+    state: Arc<Mutex<WriterState>>,
 }
 
 impl RotatingFileWriter {
@@ -28,75 +65,104 @@ impl RotatingFileWriter {
             base_path: log_dir,
             base_name,
             max_size_bytes,
-            current_file: Arc::new(Mutex::new(None)),
-            current_size: Arc::new(Mutex::new(0)),
-            current_file_path: Arc::new(Mutex::new(None)),
+            interval: None,
+            compress: false,
+            clock: Arc::new(SystemClock),
+            state: Arc::new(Mutex::new(WriterState::default())),
         })
     }
 
+    /// Also rotate at the start of every period of `interval`
+    pub fn with_interval(mut self, interval: Option<RotationInterval>) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Gzip log files once they are rotated
+    pub fn with_compression(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    /// Read the time for time-based rotation from `clock`
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Get the current log file path
     fn current_log_path(&self) -> PathBuf {
         self.base_path.join(format!("{}.log", self.base_name))
     }
 
-    /// Generate a rotated file path with timestamp
+    /// Generate a rotated file path with timestamp, not taken by an earlier
+    /// rotation or its archive
     fn rotated_log_path(&self) -> PathBuf {
-        let now: DateTime<Utc> = SystemTime::now().into();
-        let timestamp = now.format("%Y%m%d_%H%M%S");
-        self.base_path
-            .join(format!("{}_{}.log", self.base_name, timestamp))
+        let timestamp = self.clock.now().format("%Y%m%d_%H%M%S");
+        let mut name = format!("{}_{}", self.base_name, timestamp);
+        let mut attempt = 0;
+        loop {
+            let path = self.base_path.join(format!("{}.log", name));
+            if !path.exists() && !path.with_extension("log.gz").exists() {
+                return path;
+            }
+            attempt += 1;
+            name = format!("{}_{}_{}", self.base_name, timestamp, attempt);
+        }
     }
 
-    /// Rotate the current log file if it exceeds the size limit
-    fn rotate_if_needed(&self) -> Result<()> {
-        let current_size = *self.current_size.lock().unwrap();
-
-        if current_size >= self.max_size_bytes {
-            self.rotate()?;
+    /// Whether the open file is due for rotation by size or time
+    fn should_rotate(&self, state: &WriterState) -> bool {
+        if state.size == 0 {
+            return false;
         }
-
-        Ok(())
+        state.size >= self.max_size_bytes
+            || state
+                .rotate_at
+                .is_some_and(|rotate_at| self.clock.now() >= rotate_at)
     }
 
-    /// Force rotation of the current log file
-    fn rotate(&self) -> Result<()> {
-        // Close current file
-        {
-            let mut current_file = self.current_file.lock().unwrap();
-            if let Some(file) = current_file.take() {
-                drop(file);
-            }
+    /// Close the open file and move it to a rotated name, returning that name
+    fn rotate(&self, state: &mut WriterState) -> Result<Option<PathBuf>> {
+        if let Some(mut file) = state.file.take() {
+            file.flush()?;
         }
+        state.size = 0;
+        state.rotate_at = None;
 
-        // Move current log to rotated name
         let current_path = self.current_log_path();
-        if current_path.exists() {
-            let rotated_path = self.rotated_log_path();
-            std::fs::rename(&current_path, &rotated_path)?;
-
-            tracing::info!(
-                telemetry.event = "log_rotated",
-                old_file = %current_path.display(),
-                new_file = %rotated_path.display(),
-                "Log file rotated"
-            );
+        if !current_path.exists() {
+            return Ok(None);
         }
+        let rotated_path = self.rotated_log_path();
+        std::fs::rename(&current_path, &rotated_path)?;
+        Ok(Some(rotated_path))
+    }
 
-        // Reset size counter
-        *self.current_size.lock().unwrap() = 0;
-
-        // Clear current file path
-        *self.current_file_path.lock().unwrap() = None;
+    /// Log and compress a rotated file. Runs without the state lock, so
+    /// other writers carry on with the new file meanwhile.
+    fn finish_rotation(&self, rotated_path: PathBuf) {
+        tracing::info!(
+            telemetry.event = "log_rotated",
+            old_file = %self.current_log_path().display(),
+            new_file = %rotated_path.display(),
+            "Log file rotated"
+        );
 
-        Ok(())
+        if self.compress {
+            if let Err(e) = LogFileManager::compress_log_file(&rotated_path) {
+                eprintln!(
+                    "Failed to compress rotated log {}: {}",
+                    rotated_path.display(),
+                    e
+                );
+            }
+        }
     }
 
     /// Ensure we have an open file handle
-    fn ensure_file_open(&self) -> Result<()> {
-        let mut current_file = self.current_file.lock().unwrap();
-        let mut current_file_path = self.current_file_path.lock().unwrap();
-
-        if current_file.is_none() {
+    fn ensure_file_open(&self, state: &mut WriterState) -> Result<()> {
+        if state.file.is_none() {
             let log_path = self.current_log_path();
 
             // Ensure parent directory exists
@@ -110,58 +176,53 @@ impl RotatingFileWriter {
                 .open(&log_path)?;
 
             // Get current file size
-            let metadata = file.metadata()?;
-            *self.current_size.lock().unwrap() = metadata.len();
-
-            *current_file = Some(file);
-            *current_file_path = Some(log_path.clone());
-
-            tracing::debug!(
-                telemetry.event = "log_file_opened",
-                file_path = %log_path.display(),
-                current_size = metadata.len(),
-                "Log file opened"
-            );
+            state.size = file.metadata()?.len();
+            state.rotate_at = self
+                .interval
+                .map(|interval| interval.next_boundary(self.clock.now()));
+            state.file = Some(file);
         }
 
         Ok(())
     }
-}
 
-impl Write for RotatingFileWriter {
-    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        // Check if rotation is needed
-        if let Err(e) = self.rotate_if_needed() {
-            eprintln!("Log rotation failed: {}", e);
+    /// Write `buf` to the current file, rotating first if it is due.
+    /// Returns the rotated file, if any.
+    fn write_locked(&self, buf: &[u8]) -> Result<Option<PathBuf>> {
+        let mut state = self.state.lock().unwrap();
+        self.ensure_file_open(&mut state)?;
+
+        let mut rotated = None;
+        if self.should_rotate(&state) {
+            match self.rotate(&mut state) {
+                Ok(path) => rotated = path,
+                Err(e) => eprintln!("Log rotation failed: {}", e),
+            }
+            self.ensure_file_open(&mut state)?;
         }
 
-        // Ensure file is open
-        if let Err(e) = self.ensure_file_open() {
-            return Err(io::Error::new(io::ErrorKind::Other, e));
+        // The whole buffer goes to one file, so no line is split by rotation
+        if let Some(file) = state.file.as_mut() {
+            file.write_all(buf)?;
         }
+        state.size += buf.len() as u64;
 
-        // Write to current file
-        let bytes_written = {
-            let mut current_file = self.current_file.lock().unwrap();
-            if let Some(ref mut file) = *current_file {
-                file.write(buf)?
-            } else {
-                return Err(io::Error::new(io::ErrorKind::Other, "No file open"));
-            }
-        };
+        Ok(rotated)
+    }
+}
 
-        // Update size counter
-        {
-            let mut current_size = self.current_size.lock().unwrap();
-            *current_size += bytes_written as u64;
+impl Write for RotatingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let rotated = self.write_locked(buf).map_err(io::Error::other)?;
+        if let Some(rotated_path) = rotated {
+            self.finish_rotation(rotated_path);
         }
-
-        Ok(bytes_written)
+        Ok(buf.len())
     }
 
     fn flush(&mut self) -> io::Result<()> {
-        let mut current_file = self.current_file.lock().unwrap();
-        if let Some(ref mut file) = *current_file {
+        let mut state = self.state.lock().unwrap();
+        if let Some(ref mut file) = state.file {
             file.flush()?;
         }
         Ok(())
@@ -175,9 +236,10 @@ impl Clone for RotatingFileWriter {
             base_path: self.base_path.clone(),
             base_name: self.base_name.clone(),
             max_size_bytes: self.max_size_bytes,
-            current_file: Arc::clone(&self.current_file),
-            current_size: Arc::clone(&self.current_size),
-            current_file_path: Arc::clone(&self.current_file_path),
+            interval: self.interval,
+            compress: self.compress,
+            clock: Arc::clone(&self.clock),
+            state: Arc::clone(&self.state),
         }
     }
 }
@@ -208,7 +270,9 @@ impl LogFileManager {
             let path = entry.path();
 
             if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
-                if file_name.starts_with(base_name) && file_name.ends_with(".log") {
+                // Compressed archives count toward the retention limits too
+                let is_log = file_name.ends_with(".log") || file_name.ends_with(".log.gz");
+                if file_name.starts_with(base_name) && is_log {
                     let metadata = entry.metadata()?;
                     let modified = metadata.modified()?;
                     let size = metadata.len();
@@ -299,9 +363,58 @@ impl LogFileInfo {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::io::Write;
+    use chrono::TimeZone;
+    use flate2::read::GzDecoder;
+    use std::io::{Read, Write};
     use tempfile::TempDir;
 
+    /// Clock standing still until moved
+    struct MockClock(Mutex<DateTime<Utc>>);
+
+    impl MockClock {
+        fn at(hour: u32, minute: u32) -> Arc<Self> {
+            Arc::new(Self(Mutex::new(
+                Utc.with_ymd_and_hms(2024, 3, 1, hour, minute, 0).unwrap(),
+            )))
+        }
+
+        fn advance(&self, delta: TimeDelta) {
+            *self.0.lock().unwrap() += delta;
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> DateTime<Utc> {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    /// Rotated files in `dir`, oldest first
+    fn rotated_files(dir: &Path) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.file_name().unwrap() != "test.log")
+            .collect();
+        files.sort();
+        files
+    }
+
+    fn read_log(path: &Path) -> String {
+        let mut content = String::new();
+        if path.extension().unwrap() == "gz" {
+            GzDecoder::new(File::open(path).unwrap())
+                .read_to_string(&mut content)
+                .unwrap();
+        } else {
+            File::open(path)
+                .unwrap()
+                .read_to_string(&mut content)
+                .unwrap();
+        }
+        content
+    }
+
     #[test]
     fn test_rotating_file_writer() {
         let temp_dir = TempDir::new().unwrap();
@@ -322,6 +435,96 @@ mod tests {
         assert!(log_file.exists());
     }
 
+    #[test]
+    fn test_time_rotation_compresses_rotated_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let clock = MockClock::at(10, 15);
+        let mut writer = RotatingFileWriter::new(temp_dir.path().to_path_buf(), "test".into(), 1)
+            .unwrap()
+            .with_interval(Some(RotationInterval::Hourly))
+            .with_compression(true)
+            .with_clock(clock.clone());
+
+        writer.write_all(b"line 1\n").unwrap();
+        writer.write_all(b"line 2\n").unwrap();
+        clock.advance(TimeDelta::minutes(40));
+        assert!(rotated_files(temp_dir.path()).is_empty());
+
+        // Past 11:00 the next write starts a new file
+        clock.advance(TimeDelta::minutes(10));
+        writer.write_all(b"line 3\n").unwrap();
+
+        let rotated = rotated_files(temp_dir.path());
+        assert_eq!(rotated.len(), 1);
+        assert!(rotated[0].ends_with("test_20240301_110500.log.gz"));
+        assert_eq!(read_log(&rotated[0]), "line 1\nline 2\n");
+        assert_eq!(read_log(&temp_dir.path().join("test.log")), "line 3\n");
+    }
+
+    #[test]
+    fn test_size_and_time_rotation_whichever_first() {
+        let temp_dir = TempDir::new().unwrap();
+        let clock = MockClock::at(23, 0);
+        let mut writer = RotatingFileWriter::new(temp_dir.path().to_path_buf(), "test".into(), 1)
+            .unwrap()
+            .with_interval(Some(RotationInterval::Daily))
+            .with_clock(clock.clone());
+
+        // Size triggers first, within the day
+        let line = vec![b'a'; 1023];
+        for _ in 0..1024 {
+            writer.write_all(&line).unwrap();
+            writer.write_all(b"\n").unwrap();
+        }
+        writer.write_all(b"after size\n").unwrap();
+        assert_eq!(rotated_files(temp_dir.path()).len(), 1);
+
+        // Then the day ends before the new file fills up
+        clock.advance(TimeDelta::hours(1));
+        writer.write_all(b"next day\n").unwrap();
+
+        let rotated = rotated_files(temp_dir.path());
+        assert_eq!(rotated.len(), 2);
+        assert_eq!(read_log(&rotated[1]), "after size\n");
+        assert_eq!(read_log(&temp_dir.path().join("test.log")), "next day\n");
+    }
+
+    #[test]
+    fn test_concurrent_writes_lose_no_lines_across_rotation() {
+        let temp_dir = TempDir::new().unwrap();
+        let writer = RotatingFileWriter::new(temp_dir.path().to_path_buf(), "test".into(), 1)
+            .unwrap()
+            .with_compression(true);
+
+        let threads: Vec<_> = (0..8)
+            .map(|thread| {
+                let mut writer = writer.clone();
+                std::thread::spawn(move || {
+                    for i in 0..2000 {
+                        let line = format!("thread {} line {:05} {}\n", thread, i, "x".repeat(50));
+                        writer.write_all(line.as_bytes()).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let rotated = rotated_files(temp_dir.path());
+        assert!(!rotated.is_empty());
+        let mut lines: Vec<String> = rotated
+            .iter()
+            .chain(std::iter::once(&temp_dir.path().join("test.log")))
+            .flat_map(|path| read_log(path).lines().map(String::from).collect::<Vec<_>>())
+            .collect();
+        assert_eq!(lines.len(), 16_000);
+        assert!(lines.iter().all(|line| line.ends_with(&"x".repeat(50))));
+        lines.sort();
+        lines.dedup();
+        assert_eq!(lines.len(), 16_000);
+    }
+
     #[test]
     fn test_log_file_discovery() {
         let temp_dir = TempDir::new().unwrap();
@@ -330,11 +533,13 @@ mod tests {
         std::fs::write(temp_dir.path().join("test.log"), "current").unwrap();
         std::fs::write(temp_dir.path().join("test_20231201_120000.log"), "old1").unwrap();
         std::fs::write(temp_dir.path().join("test_20231202_120000.log"), "old2").unwrap();
+        std::fs::write(temp_dir.path().join("test_20231130_120000.log.gz"), "old0").unwrap();
         std::fs::write(temp_dir.path().join("other.log"), "other").unwrap();
 
         let log_files = LogFileManager::find_log_files(temp_dir.path(), "test").unwrap();
 
-        assert_eq!(log_files.len(), 3);
+        assert_eq!(log_files.len(), 4);
+        assert_eq!(log_files.iter().filter(|f| f.is_compressed()).count(), 1);
         assert!(log_files.iter().any(|f| f.is_current));
     }
 
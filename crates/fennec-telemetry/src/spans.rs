@@ -0,0 +1,162 @@
+//! Span conventions tying together the logs of a single user action
+//!
+//! Executing a command opens a `command` span carrying the command name, the
+//! correlation id and the session id. Work done on its behalf opens child
+//! spans: `provider_request` for model provider calls and `memory` for
+//! memory operations, each carrying the command's correlation id. The
+//! command's [`RequestContext`] is available to everything it awaits through
+//! [`current_context`]; [`instrument_with_context`] carries the context and
+//! the current span over to spawned tasks, which would otherwise lose both.
+
+use crate::correlation::{CorrelationId, RequestContext};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::{Instrument, Span};
+
+static TRACE_COMMANDS: AtomicBool = AtomicBool::new(true);
+
+tokio::task_local! {
+    static CURRENT_CONTEXT: RequestContext;
+}
+
+/// Open the spans described above or not, following
+/// [`TelemetryConfig::trace_commands`](crate::TelemetryConfig::trace_commands)
+pub fn set_trace_commands(enabled: bool) {
+    TRACE_COMMANDS.store(enabled, Ordering::Relaxed);
+}
+
+pub fn trace_commands() -> bool {
+    TRACE_COMMANDS.load(Ordering::Relaxed)
+}
+
+/// Context of the command the current task works for, if any
+pub fn current_context() -> Option<RequestContext> {
+    CURRENT_CONTEXT.try_with(Clone::clone).ok()
+}
+
+/// Correlation id for a request made on behalf of the current command: a
+/// child of the command's, or a new one outside of any command
+pub fn correlation_id() -> CorrelationId {
+    current_context()
+        .map(|context| context.correlation_id.child())
+        .unwrap_or_default()
+}
+
+/// Span of an execution of `command`
+pub fn command_span(command: &str, context: &RequestContext) -> Span {
+    if !trace_commands() {
+        return Span::none();
+    }
+    tracing::info_span!(
+        "command",
+        command = %command,
+        correlation_id = %context.correlation_id,
+        session_id = %context.session_id.as_deref().unwrap_or("none"),
+    )
+}
+
+/// Span of a request to a model provider
+pub fn provider_span(provider: &str, model: &str) -> Span {
+    if !trace_commands() {
+        return Span::none();
+    }
+    tracing::info_span!(
+        "provider_request",
+        provider = %provider,
+        model = %model,
+        correlation_id = %current_correlation_id(),
+    )
+}
+
+/// Span of a memory operation such as `search`
+pub fn memory_span(operation: &str) -> Span {
+    if !trace_commands() {
+        return Span::none();
+    }
+    tracing::info_span!(
+        "memory",
+        operation = %operation,
+        correlation_id = %current_correlation_id(),
+    )
+}
+
+/// Run `future` as the execution of `command` for `context`, inside its
+/// command span
+pub async fn run_command_in_context<F: Future>(
+    command: &str,
+    context: RequestContext,
+    future: F,
+) -> F::Output {
+    let span = command_span(command, &context);
+    CURRENT_CONTEXT
+        .scope(context, future)
+        .instrument(span)
+        .await
+}
+
+/// Make `future` run in the current request context and span wherever it is
+/// polled, e.g. once handed to `tokio::spawn`
+pub fn instrument_with_context<F: Future>(future: F) -> impl Future<Output = F::Output> {
+    let span = Span::current();
+    let context = current_context();
+    async move {
+        match context {
+            Some(context) => {
+                CURRENT_CONTEXT
+                    .scope(context, future)
+                    .instrument(span)
+                    .await
+            }
+            None => future.instrument(span).await,
+        }
+    }
+}
+
+/// Correlation id of the current command, `none` outside of any
+pub(crate) fn current_correlation_id() -> String {
+    current_context()
+        .map(|context| context.correlation_id.to_string())
+        .unwrap_or_else(|| "none".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_context_follows_spawned_tasks() {
+        let context = RequestContext::new("command:test".to_string())
+            .with_session_id("session-1".to_string());
+        let correlation_id = context.correlation_id.clone();
+
+        let (inside, spawned, plain) = run_command_in_context("test", context, async {
+            let inside = current_context().map(|c| c.correlation_id);
+            let spawned = tokio::spawn(instrument_with_context(async {
+                current_context().map(|c| c.correlation_id)
+            }))
+            .await
+            .unwrap();
+            let plain = tokio::spawn(async { current_context().map(|c| c.correlation_id) })
+                .await
+                .unwrap();
+            (inside, spawned, plain)
+        })
+        .await;
+
+        assert_eq!(inside, Some(correlation_id.clone()));
+        assert_eq!(spawned, Some(correlation_id));
+        assert_eq!(plain, None);
+        assert!(current_context().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_child_correlation_ids_extend_the_command_id() {
+        let context = RequestContext::new("command:test".to_string());
+        let parent = context.correlation_id.clone();
+
+        let child = run_command_in_context("test", context, async { correlation_id() }).await;
+
+        assert!(child.as_str().starts_with(parent.as_str()));
+        assert_ne!(child, parent);
+    }
+}
@@ -1,20 +1,24 @@
 //! Main telemetry system implementation
 
 use crate::{
-    config::{LogFormat, TelemetryConfig},
+    config::{LogFormat, LogLevel, TelemetryConfig},
     correlation::CorrelationLayer,
+    crash::{DiagnosticReporter, RecentLogsLayer},
+    levels::LevelControl,
     metrics::MetricsLayer,
     retention::RetentionManager,
     rotation::RotatingFileWriter,
     sanitization::SanitizationLayer,
     Error, Result,
 };
-use std::sync::Arc;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
 use tokio::sync::RwLock;
 use tracing::Level;
-use tracing_subscriber::{
-    fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer, Registry,
-};
+use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, Layer, Registry};
+
+/// Control of the filter of the installed subscriber
+static LEVEL_CONTROL: OnceLock<LevelControl> = OnceLock::new();
 
 /// Main telemetry system that coordinates all telemetry components
 pub struct TelemetrySystem {
@@ -30,6 +34,9 @@ pub struct TelemetrySystem {
 /// Guard that ensures proper cleanup of telemetry resources
 pub struct TelemetryGuard {
     _inner: Box<dyn Send + Sync>,
+    /// Flushed on drop so the last spans reach the collector
+    #[cfg(feature = "otel")]
+    tracer_provider: Option<opentelemetry_sdk::trace::SdkTracerProvider>,
 }
 
 impl TelemetrySystem {
@@ -44,8 +51,31 @@ impl TelemetrySystem {
         // Create registry for all layers
         let registry = Registry::default();
 
-        // Build the subscriber with layers
-        let subscriber = registry.with(Self::build_env_filter(&config_read)?);
+        // Build the subscriber with layers; the filter can be changed later
+        // through `set_level`
+        let (filter, level_control) =
+            LevelControl::new(Self::build_filter_directives(&config_read));
+        // Keep the last log lines around for crash reports
+        let recent_logs = crate::crash::recent_logs();
+        let subscriber = registry
+            .with(filter)
+            .with(RecentLogsLayer::new(recent_logs.clone()));
+
+        // Export spans over OTLP next to the local logging. A failure is
+        // reported once the subscriber is installed, so it gets logged.
+        #[cfg(feature = "otel")]
+        let tracer_provider = if config_read.enabled && config_read.otlp.enabled {
+            Some(crate::otel::tracer_provider(&config_read.otlp))
+        } else {
+            None
+        };
+        #[cfg(feature = "otel")]
+        let subscriber = subscriber.with(
+            tracer_provider
+                .as_ref()
+                .and_then(|provider| provider.as_ref().ok())
+                .map(crate::otel::layer),
+        );
 
         // Initialize the global subscriber (allow failure if already set for tests)
         let _subscriber_initialized = if let Err(e) = subscriber.try_init() {
@@ -60,9 +90,42 @@ impl TelemetrySystem {
                 });
             }
         } else {
+            // Only the filter of the installed subscriber is worth controlling
+            let _ = LEVEL_CONTROL.set(level_control);
             true // Successfully initialized
         };
 
+        crate::spans::set_trace_commands(config_read.enabled && config_read.trace_commands);
+        crate::metrics::set_slow_thresholds(config_read.metrics.slow_operations.clone());
+
+        if config_read.crash_reports {
+            crate::crash::install_panic_hook(DiagnosticReporter::new(&config_read, recent_logs)?);
+        }
+
+        #[cfg(feature = "otel")]
+        let tracer_provider = match tracer_provider {
+            Some(Ok(provider)) => {
+                if !crate::otel::collector_reachable(&config_read.otlp.endpoint).await {
+                    tracing::warn!(
+                        "OpenTelemetry collector at {} is unreachable; spans will be dropped until it is up",
+                        config_read.otlp.endpoint
+                    );
+                }
+                Some(provider)
+            }
+            Some(Err(e)) => {
+                tracing::warn!("Continuing without OpenTelemetry export: {}", e);
+                None
+            }
+            None => None,
+        };
+        #[cfg(not(feature = "otel"))]
+        if config_read.enabled && config_read.otlp.enabled {
+            tracing::warn!(
+                "OpenTelemetry export is enabled but fennec was built without the otel feature"
+            );
+        }
+
         // Start retention manager if file logging is enabled
         let _retention_manager = if config_read.logging.file_enabled {
             Some(RetentionManager::new(config_read.retention.clone()).await?)
@@ -70,10 +133,32 @@ impl TelemetrySystem {
             None
         };
 
+        // Keep the exporter, if any, alive as long as the guard
+        #[cfg(feature = "prometheus")]
+        let exporter: Box<dyn Send + Sync> =
+            if config_read.enabled && config_read.metrics.prometheus_enabled {
+                Box::new(crate::exporter::PrometheusExporter::install(
+                    &config_read.metrics,
+                )?)
+            } else {
+                Box::new(())
+            };
+        #[cfg(not(feature = "prometheus"))]
+        let exporter: Box<dyn Send + Sync> = {
+            if config_read.enabled && config_read.metrics.prometheus_enabled {
+                tracing::warn!(
+                    "Prometheus export is enabled but fennec was built without the prometheus feature"
+                );
+            }
+            Box::new(())
+        };
+
         drop(config_read);
 
         let guard = TelemetryGuard {
-            _inner: Box::new(()),
+            _inner: exporter,
+            #[cfg(feature = "otel")]
+            tracer_provider,
         };
 
         tracing::info!(
@@ -89,6 +174,8 @@ impl TelemetrySystem {
     pub async fn update_config(config: TelemetryConfig) -> Result<()> {
         config.validate()?;
 
+        crate::metrics::set_slow_thresholds(config.metrics.slow_operations.clone());
+
         // For now, we log the configuration change
         // In a full implementation, we would rebuild the subscriber
         tracing::info!(
@@ -101,24 +188,59 @@ impl TelemetrySystem {
         Ok(())
     }
 
-    /// Build environment filter for log level filtering
-    fn build_env_filter(config: &TelemetryConfig) -> Result<EnvFilter> {
-        let level: Level = config.logging.level.into();
+    /// Set the log level of `target`, a module path such as
+    /// `fennec_provider`, or the default level when `target` is `None`.
+    /// Takes effect immediately.
+    pub fn set_level(target: Option<&str>, level: LogLevel) -> Result<()> {
+        LEVEL_CONTROL
+            .get()
+            .ok_or_else(|| Error::System {
+                message: "Telemetry system is not initialized".to_string(),
+            })?
+            .set_level(target, level)
+    }
 
-        // Start with the configured level
-        let mut filter = EnvFilter::new(level.to_string());
+    /// Current log filter directives, e.g. `["info", "fennec_provider=debug"]`
+    pub fn log_directives() -> Vec<String> {
+        LEVEL_CONTROL
+            .get()
+            .map(LevelControl::directives)
+            .unwrap_or_default()
+    }
 
-        // Add specific module filters if needed
-        filter = filter.add_directive("hyper=warn".parse().unwrap());
-        filter = filter.add_directive("reqwest=warn".parse().unwrap());
-        filter = filter.add_directive("h2=warn".parse().unwrap());
+    /// Current default log level, if the telemetry system is initialized
+    pub fn log_level() -> Option<LogLevel> {
+        LEVEL_CONTROL.get().and_then(LevelControl::level)
+    }
 
+    /// Write a diagnostic bundle - version, configuration with secrets
+    /// redacted and the last log lines - to the log directory, returning
+    /// its path
+    pub fn generate_diagnostic_bundle() -> Result<PathBuf> {
+        crate::crash::write_diagnostic_bundle()
+    }
+
+    /// Build the directives of the log level filter
+    fn build_filter_directives(config: &TelemetryConfig) -> Vec<String> {
         // Allow environment override
         if let Ok(env_filter) = std::env::var("RUST_LOG") {
-            filter = EnvFilter::new(env_filter);
+            return env_filter
+                .split(',')
+                .map(str::trim)
+                .filter(|directive| !directive.is_empty())
+                .map(str::to_string)
+                .collect();
         }
 
-        Ok(filter)
+        let level: Level = config.logging.level.into();
+
+        // Start with the configured level, quieting noisy dependencies
+        vec![
+            level.to_string().to_lowercase(),
+            "hyper=warn".to_string(),
+            "reqwest=warn".to_string(),
+            "h2=warn".to_string(),
+        ]
     }
 
     /// Build console logging layer
@@ -179,7 +301,9 @@ impl TelemetrySystem {
             config.logging.log_dir.clone(),
             config.logging.log_file_name.clone(),
             config.logging.max_file_size_mb,
-        )?;
+        )?
+        .with_interval(config.logging.rotation_interval)
+        .with_compression(config.logging.compress_rotated);
 
         let layer = match config.logging.format {
             LogFormat::Json => fmt::layer()
@@ -300,6 +424,13 @@ impl Drop for TelemetryGuard {
             telemetry.event = "system_shutdown",
             "Telemetry system shutting down"
         );
+
+        #[cfg(feature = "otel")]
+        if let Some(provider) = self.tracer_provider.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Failed to flush OpenTelemetry spans: {}", e);
+            }
+        }
     }
 }
 
@@ -347,4 +478,21 @@ mod tests {
         // Even when disabled, the system should initialize successfully
         tracing::info!("This message should be filtered out");
     }
+
+    #[tokio::test]
+    async fn test_otlp_export_degrades_without_collector() {
+        let mut config = TelemetryConfig::default();
+        config.logging.file_enabled = false;
+        config.otlp.enabled = true;
+        // Nothing listens on the discard port
+        config.otlp.endpoint = "http://127.0.0.1:9/v1/traces".to_string();
+
+        // Without the otel feature, or without a collector, init still succeeds
+        let guard = TelemetrySystem::init(config).await.unwrap();
+
+        tracing::info_span!("command", command = "test").in_scope(|| {
+            tracing::info!("Traced message");
+        });
+        drop(guard);
+    }
 }
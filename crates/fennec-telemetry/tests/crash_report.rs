@@ -0,0 +1,73 @@
+use fennec_telemetry::{crash, TelemetryConfig, TelemetrySystem};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+fn reports(dir: &Path, prefix: &str) -> Vec<PathBuf> {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with(prefix)
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_panic_writes_crash_report() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = TelemetryConfig::default();
+    config.logging.log_dir = temp_dir.path().to_path_buf();
+    config.logging.level = fennec_telemetry::LogLevel::Info;
+    config.otlp.headers.insert(
+        "authorization".to_string(),
+        "Bearer otlp-secret-42".to_string(),
+    );
+    let _guard = TelemetrySystem::init(config).await.unwrap();
+
+    let restored = Arc::new(AtomicBool::new(false));
+    let flag = restored.clone();
+    crash::set_terminal_restore(move || flag.store(true, Ordering::SeqCst));
+
+    for i in 0..250 {
+        tracing::info!(step = i, "working");
+    }
+    tracing::warn!("about to fail with api_key=sk-test-abcdef");
+
+    let error = tokio::spawn(async { panic!("controlled crash") })
+        .await
+        .unwrap_err();
+    assert!(error.is_panic());
+    assert!(restored.load(Ordering::SeqCst));
+
+    let crashes = reports(temp_dir.path(), "fennec-crash-");
+    assert_eq!(crashes.len(), 1, "{:?}", crashes);
+    let report = std::fs::read_to_string(&crashes[0]).unwrap();
+
+    assert!(report.starts_with("Fennec crash report"));
+    assert!(report.contains(&format!("Version: {}", env!("CARGO_PKG_VERSION"))));
+    assert!(report.contains("controlled crash"));
+    assert!(report.contains("tests/crash_report.rs"));
+    assert!(report.contains("Backtrace:"));
+    assert!(report.contains("== Last 200 log lines =="));
+    assert!(!report.contains("working step=49\n"));
+    assert!(report.contains("working step=249"));
+    assert!(report.contains("about to fail with api_key=[REDACTED]"));
+    assert!(!report.contains("sk-test-abcdef"));
+    assert!(report.contains("authorization = \"[REDACTED]\""));
+    assert!(!report.contains("otlp-secret-42"));
+
+    let bundle = TelemetrySystem::generate_diagnostic_bundle().unwrap();
+    assert_eq!(bundle.parent(), Some(temp_dir.path()));
+    let bundle = std::fs::read_to_string(bundle).unwrap();
+    assert!(bundle.starts_with("Fennec diagnostics report"));
+    assert!(!bundle.contains("== Panic =="));
+    assert!(bundle.contains("about to fail with api_key=[REDACTED]"));
+    assert!(!bundle.contains("otlp-secret-42"));
+
+    crash::clear_terminal_restore();
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "prometheus-http")]
+
+use fennec_telemetry::{config::TelemetryConfig, metrics, PrometheusExporter};
+use std::net::TcpListener;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+async fn scrape(port: u16) -> String {
+    // The listener is started by a spawned task, so give it a few tries
+    for _ in 0..50 {
+        if let Ok(mut stream) = TcpStream::connect(("127.0.0.1", port)).await {
+            stream
+                .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                .await
+                .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            return response;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("Prometheus endpoint on port {} never came up", port);
+}
+
+#[tokio::test]
+async fn test_endpoint_serves_recorded_metrics() {
+    let mut config = TelemetryConfig::default().metrics;
+    config.prometheus_enabled = true;
+    config.prometheus_port = free_port();
+    let _exporter = PrometheusExporter::install(&config).unwrap();
+
+    metrics::record_command_execution("read_file", true, Duration::from_millis(12));
+    metrics::record_command_execution("shell", false, Duration::from_millis(40));
+    metrics::record_provider_request("openai", "gpt-4o", true, Duration::from_millis(850));
+    metrics::record_token_usage("openai", "gpt-4o", 120, 30);
+    metrics::record_memory_search("transcripts", Duration::from_millis(3));
+    metrics::record_approval_decision("HIGH", "approved");
+
+    let response = scrape(config.prometheus_port).await;
+
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    for expected in [
+        r#"fennec_command_executions_total{command="read_file",outcome="success"} 1"#,
+        r#"fennec_command_executions_total{command="shell",outcome="failure"} 1"#,
+        r#"fennec_command_duration_seconds_bucket{command="read_file",le="0.025"} 1"#,
+        r#"fennec_provider_requests_total{provider="openai",model="gpt-4o",outcome="success"} 1"#,
+        r#"fennec_provider_request_duration_seconds_count{provider="openai",model="gpt-4o"} 1"#,
+        r#"fennec_provider_tokens_total{provider="openai",model="gpt-4o",kind="prompt"} 120"#,
+        r#"fennec_provider_tokens_total{provider="openai",model="gpt-4o",kind="completion"} 30"#,
+        r#"fennec_memory_search_duration_seconds_count{kind="transcripts"} 1"#,
+        r#"fennec_approval_decisions_total{risk_level="HIGH",decision="approved"} 1"#,
+        "# HELP fennec_command_executions_total Commands executed",
+    ] {
+        assert!(
+            response.contains(expected),
+            "missing {}\n{}",
+            expected,
+            response
+        );
+    }
+}
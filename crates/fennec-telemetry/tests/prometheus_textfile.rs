@@ -0,0 +1,44 @@
+#![cfg(feature = "prometheus")]
+
+use fennec_telemetry::{config::TelemetryConfig, metrics, PrometheusExporter};
+use std::time::Duration;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_textfile_holds_recorded_metrics() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("textfile").join("fennec.prom");
+    let mut config = TelemetryConfig::default().metrics;
+    config.prometheus_enabled = true;
+    config.prometheus_textfile = Some(path.clone());
+    let exporter = PrometheusExporter::install(&config).unwrap();
+
+    metrics::record_command_execution("search", true, Duration::from_millis(5));
+    metrics::record_approval_decision("LOW", "denied");
+    exporter.write_textfile(&path).unwrap();
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert!(
+        contents
+            .contains(r#"fennec_command_executions_total{command="search",outcome="success"} 1"#),
+        "{}",
+        contents
+    );
+    assert!(
+        contents
+            .contains(r#"fennec_approval_decisions_total{risk_level="LOW",decision="denied"} 1"#),
+        "{}",
+        contents
+    );
+
+    // Dropping the exporter writes what was recorded since
+    metrics::record_command_execution("search", true, Duration::from_millis(5));
+    drop(exporter);
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert!(
+        contents
+            .contains(r#"fennec_command_executions_total{command="search",outcome="success"} 2"#),
+        "{}",
+        contents
+    );
+}
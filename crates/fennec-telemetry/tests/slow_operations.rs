@@ -0,0 +1,145 @@
+use ::metrics::{Counter, Gauge, Histogram, Key, KeyName, Metadata, SharedString, Unit};
+use fennec_telemetry::config::SlowOperationThresholds;
+use fennec_telemetry::metrics::{self, OperationClass, SLOW_OPERATIONS};
+use fennec_telemetry::{spans, RequestContext};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::Layer;
+
+/// Layer recording the fields of every warning
+#[derive(Clone, Default)]
+struct Warnings(Arc<Mutex<Vec<HashMap<String, String>>>>);
+
+impl Warnings {
+    fn take(&self) -> Vec<HashMap<String, String>> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+#[derive(Default)]
+struct Fields(HashMap<String, String>);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+}
+
+impl<S: Subscriber> Layer<S> for Warnings {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if *event.metadata().level() == Level::WARN {
+            let mut fields = Fields::default();
+            event.record(&mut fields);
+            self.0.lock().unwrap().push(fields.0);
+        }
+    }
+}
+
+/// Write that takes at least `duration`, recorded like a real one
+async fn slow_write(duration: Duration) {
+    let started = Instant::now();
+    tokio::time::sleep(duration).await;
+    metrics::record_file_write("src/main.rs", started.elapsed());
+}
+
+/// Recorder counting slow operations, ignoring every other metric
+#[derive(Default)]
+struct SlowOperations(Arc<AtomicU64>);
+
+impl SlowOperations {
+    fn count(&self) -> u64 {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+impl ::metrics::Recorder for SlowOperations {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+        if key.name() == SLOW_OPERATIONS {
+            Counter::from_arc(self.0.clone())
+        } else {
+            Counter::noop()
+        }
+    }
+
+    fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+        Gauge::noop()
+    }
+
+    fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+        Histogram::noop()
+    }
+}
+
+#[tokio::test]
+async fn test_slow_operations_warn_once_each() {
+    let capture = Warnings::default();
+    let _subscriber =
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+    let recorder = SlowOperations::default();
+    metrics::set_slow_thresholds(SlowOperationThresholds {
+        file_write_ms: 20,
+        ..Default::default()
+    });
+    assert_eq!(
+        metrics::slow_threshold(OperationClass::FileWrite),
+        Duration::from_millis(20)
+    );
+
+    let context = RequestContext::new("command:edit".to_string());
+    let correlation_id = context.correlation_id.to_string();
+    spans::run_command_in_context("edit", context, async {
+        slow_write(Duration::from_millis(1)).await;
+        slow_write(Duration::from_millis(40)).await;
+        slow_write(Duration::from_millis(40)).await;
+    })
+    .await;
+    ::metrics::with_local_recorder(&recorder, || {
+        metrics::record_file_write("src/lib.rs", Duration::from_millis(30));
+    });
+
+    let warnings = capture.take();
+    assert_eq!(warnings.len(), 3, "{:?}", warnings);
+    for warning in &warnings[..2] {
+        assert_eq!(warning["telemetry.event"], "slow_operation");
+        assert_eq!(warning["operation_class"], "file_write");
+        assert_eq!(warning["operation"], "src/main.rs");
+        assert_eq!(warning["threshold_ms"], "20");
+        assert!(warning["duration_ms"].parse::<u64>().unwrap() >= 40);
+        assert_eq!(warning["correlation_id"], correlation_id);
+    }
+    assert_eq!(warnings[2]["operation"], "src/lib.rs");
+    assert_eq!(warnings[2]["duration_ms"], "30");
+    assert_eq!(warnings[2]["correlation_id"], "none");
+    assert_eq!(recorder.count(), 1);
+
+    // Raising the threshold at runtime silences the same operation
+    metrics::set_slow_threshold(OperationClass::FileWrite, Duration::from_secs(1));
+    ::metrics::with_local_recorder(&recorder, || {
+        metrics::record_file_write("src/lib.rs", Duration::from_millis(30));
+        assert!(!metrics::check_slow_operation(
+            OperationClass::MemorySearch,
+            "basic",
+            Duration::from_millis(30)
+        ));
+    });
+    assert!(capture.take().is_empty());
+    assert_eq!(recorder.count(), 1);
+}
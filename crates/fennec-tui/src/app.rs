@@ -1,24 +1,50 @@
-use crate::components::{
-    ChatView, InputField, Message, MessageRole, PopupDialog, PreviewPanel, StatusBar, StatusItem,
+use crate::approval_dialog::{ApprovalDialog, ChannelApprovalPrompt, PendingApproval};
+use crate::command_palette::{CommandPalette, PaletteItem};
+use crate::components::{Message, MessageRole, PopupDialog, PreviewPanel, StatusBar, StatusItem};
+use crate::conversation::ConversationPane;
+use crate::diff_review::{DiffReview, DiffReviewAction, WorkspaceHunkApplier};
+use crate::error::ErrorToast;
+use crate::events::{
+    pause_event_listener, resume_event_listener, spawn_event_listener, AppEvent, EventHandler,
+    InputMode, KeyAction, MouseAction,
 };
-use crate::events::{spawn_event_listener, AppEvent, EventHandler, InputMode, KeyAction};
-use crate::layout::{LayoutManager, Pane};
+use crate::input_editor::{ExternalEditor, InputEditor, PromptHistory, SystemEditor};
+use crate::keymap::Keymap;
+use crate::layout::{LayoutManager, Pane, ResizeDebouncer};
+use crate::sandbox_dialog::{sandbox_status_items, SandboxDialog};
+use crate::sessions::{SessionPickerAction, SessionRegistry, SessionTab};
+use crate::streaming_message::{
+    forward_stream, StreamStatus, StreamingMessageView, StreamingViewConfig,
+};
+use crate::terminal::{restore_stdout, TerminalGuard};
 use crate::theme::{ComponentType, ThemeManager};
+use crate::toasts::ToastStack;
 
+use fennec_commands::{ActionLog, CommandContext, CommandRegistry, Hunk};
+use fennec_core::error::{ErrorInfo, ErrorSeverity};
 use fennec_core::Result;
-use fennec_orchestration::SessionManager;
-use fennec_security::{ApprovalManager, SandboxLevel, SandboxPolicy};
-
-use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture, Event, KeyEvent, MouseEvent},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+use fennec_memory::MemoryService;
+use fennec_orchestration::{BudgetStatus, IdleEvent, SessionManager, UsageReport};
+use fennec_security::{
+    ApprovalEnrichment, ApprovalManager, ApprovalPrompt, SandboxLevel, SandboxPolicy,
+};
+use fennec_telemetry::{LogLevel, TelemetrySystem};
+
+use crossterm::event::{Event, KeyEvent, KeyEventKind, MouseEvent};
+use ratatui::{
+    backend::CrosstermBackend,
+    buffer::Buffer,
+    layout::Rect,
+    widgets::{Paragraph, Widget},
+    Frame, Terminal,
 };
-use ratatui::{backend::CrosstermBackend, buffer::Buffer, layout::Rect, Terminal};
 use std::{
     io::{self, Stdout},
+    sync::Arc,
     time::{Duration, Instant},
 };
+use tokio::sync::mpsc;
+use uuid::Uuid;
 
 use tracing::{debug, error, info, warn};
 
@@ -35,26 +61,45 @@ pub struct App {
     // Core dependencies
     session_manager: SessionManager,
     sandbox_policy: Option<SandboxPolicy>,
-    #[allow(dead_code)]
-    approval_manager: Option<ApprovalManager>,
+    approval_manager: Option<Arc<ApprovalManager>>,
+    /// Adds context to approval prompts; memory registers with it later
+    approval_enrichment: Option<Arc<ApprovalEnrichment>>,
+    approval_prompt: ChannelApprovalPrompt,
+    approval_requests: mpsc::UnboundedReceiver<PendingApproval>,
+    command_registry: Option<Arc<CommandRegistry>>,
+    memory_service: Option<Arc<MemoryService>>,
+    action_log: Arc<ActionLog>,
 
     // TUI components
     terminal: Terminal<CrosstermBackend<Stdout>>,
+    terminal_guard: TerminalGuard<Stdout>,
     event_handler: EventHandler,
     theme_manager: ThemeManager,
     layout_manager: LayoutManager,
+    resize: ResizeDebouncer,
 
     // UI components
-    chat_view: ChatView,
-    input_field: InputField,
+    sessions: SessionRegistry,
+    stream_config: StreamingViewConfig,
+    input_editor: InputEditor,
+    /// Editor Ctrl+E hands the input to
+    external_editor: Arc<dyn ExternalEditor>,
     status_bar: StatusBar,
     preview_panel: PreviewPanel,
+    approval_dialog: ApprovalDialog,
+    sandbox_dialog: SandboxDialog,
+    toasts: ToastStack,
+    command_palette: CommandPalette,
+    diff_review: Option<DiffReview>,
 
     // Application state
     state: AppState,
+    /// Alternates every tick, for flashing the full access warning
+    status_flash: bool,
     focused_pane: Pane,
     show_help: bool,
     current_popup: Option<PopupDialog>,
+    session_usage: Option<UsageReport>,
 
     // Performance tracking
     last_render: Instant,
@@ -65,19 +110,23 @@ impl App {
     /// Initialize terminal and basic components (shared between constructors)
     fn init_terminal_and_components() -> Result<(
         Terminal<CrosstermBackend<Stdout>>,
+        TerminalGuard<Stdout>,
         EventHandler,
         ThemeManager,
         LayoutManager,
-        ChatView,
-        InputField,
+        InputEditor,
         StatusBar,
         PreviewPanel,
     )> {
-        // Initialize terminal
-        enable_raw_mode()?;
-        let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-        let backend = CrosstermBackend::new(stdout);
+        // Initialize terminal; the guard puts it back if anything below fails
+        let mut terminal_guard = TerminalGuard::new(io::stdout());
+        terminal_guard.enter()?;
+        terminal_guard.set_mouse_capture(true)?;
+        terminal_guard.set_bracketed_paste(true)?;
+        terminal_guard.enable_keyboard_enhancement()?;
+        // A panic report is printed before the guard gets to run
+        fennec_telemetry::crash::set_terminal_restore(restore_stdout);
+        let backend = CrosstermBackend::new(io::stdout());
         let terminal = Terminal::new(backend)?;
 
         // Initialize event handling
@@ -86,18 +135,17 @@ impl App {
         // Initialize managers and components
         let theme_manager = ThemeManager::new();
         let layout_manager = LayoutManager::default();
-        let chat_view = ChatView::new();
-        let input_field = InputField::new();
+        let input_editor = InputEditor::new();
         let status_bar = StatusBar::new();
         let preview_panel = PreviewPanel::new();
 
         Ok((
             terminal,
+            terminal_guard,
             event_handler,
             theme_manager,
             layout_manager,
-            chat_view,
-            input_field,
+            input_editor,
             status_bar,
             preview_panel,
         ))
@@ -110,11 +158,11 @@ impl App {
 
         let (
             terminal,
+            terminal_guard,
             event_handler,
             theme_manager,
             layout_manager,
-            chat_view,
-            input_field,
+            input_editor,
             mut status_bar,
             preview_panel,
         ) = Self::init_terminal_and_components()?;
@@ -123,27 +171,50 @@ impl App {
         spawn_event_listener(event_handler.sender());
 
         // Setup initial status bar
-        Self::update_status_bar(&mut status_bar, InputMode::Normal, &sandbox_level, 0);
+        Self::update_status_bar(&mut status_bar, InputMode::Normal, &sandbox_level, 0, None);
+
+        let (approval_prompt, approval_requests) = ChannelApprovalPrompt::new();
+        let mut command_palette = CommandPalette::new();
+        command_palette.set_keymap(event_handler.keymap());
 
-        Ok(Self {
+        let mut app = Self {
             session_manager,
             sandbox_policy: None,
             approval_manager: None,
+            approval_enrichment: None,
+            approval_prompt,
+            approval_requests,
+            command_registry: None,
+            memory_service: None,
+            action_log: Arc::new(ActionLog::new()),
             terminal,
+            terminal_guard,
             event_handler,
             theme_manager,
             layout_manager,
-            chat_view,
-            input_field,
+            resize: ResizeDebouncer::default(),
+            sessions: SessionRegistry::new(),
+            stream_config: StreamingViewConfig::default(),
+            input_editor,
+            external_editor: Arc::new(SystemEditor::from_env()),
             status_bar,
             preview_panel,
+            approval_dialog: ApprovalDialog::new(),
+            sandbox_dialog: SandboxDialog::new(),
+            toasts: ToastStack::new(),
+            command_palette,
+            diff_review: None,
             state: AppState::Running,
+            status_flash: false,
             focused_pane: Pane::Chat,
             show_help: false,
             current_popup: None,
+            session_usage: None,
             last_render: Instant::now(),
             frame_count: 0,
-        })
+        };
+        app.open_resumed_sessions().await?;
+        Ok(app)
     }
 
     /// Create a new application instance with full security integration
@@ -159,11 +230,11 @@ impl App {
 
         let (
             terminal,
+            terminal_guard,
             event_handler,
             theme_manager,
             layout_manager,
-            chat_view,
-            input_field,
+            input_editor,
             mut status_bar,
             preview_panel,
         ) = Self::init_terminal_and_components()?;
@@ -177,27 +248,393 @@ impl App {
             InputMode::Normal,
             &sandbox_policy,
             0,
+            None,
+            false,
         );
 
-        Ok(Self {
+        // Ask for approvals through dialogs instead of the blocking
+        // terminal prompt, which would fight with the TUI for the terminal
+        let (approval_prompt, approval_requests) = ChannelApprovalPrompt::new();
+        let mut command_palette = CommandPalette::new();
+        command_palette.set_keymap(event_handler.keymap());
+        // Tell the user what approvals would touch: file counts, git
+        // status and recent edits logged by Fennec
+        let action_log = Arc::new(ActionLog::new());
+        let approval_enrichment =
+            Arc::new(ApprovalEnrichment::new(sandbox_policy.workspace_path()));
+        approval_enrichment.register(Arc::new(action_log.as_ref().clone()));
+        let approval_manager = approval_manager
+            .with_prompt(Arc::new(approval_prompt.clone()))
+            .with_enrichment(approval_enrichment.clone());
+
+        let mut app = Self {
             session_manager,
             sandbox_policy: Some(sandbox_policy),
-            approval_manager: Some(approval_manager),
+            approval_manager: Some(Arc::new(approval_manager)),
+            approval_enrichment: Some(approval_enrichment),
+            approval_prompt,
+            approval_requests,
+            command_registry: None,
+            memory_service: None,
+            action_log,
             terminal,
+            terminal_guard,
             event_handler,
             theme_manager,
             layout_manager,
-            chat_view,
-            input_field,
+            resize: ResizeDebouncer::default(),
+            sessions: SessionRegistry::new(),
+            stream_config: StreamingViewConfig::default(),
+            input_editor,
+            external_editor: Arc::new(SystemEditor::from_env()),
             status_bar,
             preview_panel,
+            approval_dialog: ApprovalDialog::new(),
+            sandbox_dialog: SandboxDialog::new(),
+            toasts: ToastStack::new(),
+            command_palette,
+            diff_review: None,
             state: AppState::Running,
+            status_flash: false,
             focused_pane: Pane::Chat,
             show_help: false,
             current_popup: None,
+            session_usage: None,
             last_render: Instant::now(),
             frame_count: 0,
-        })
+        };
+        app.open_resumed_sessions().await?;
+        Ok(app)
+    }
+
+    /// Approval manager that asks through this app's approval dialogs
+    pub fn approval_manager(&self) -> Option<Arc<ApprovalManager>> {
+        self.approval_manager.clone()
+    }
+
+    /// Prompt showing approval requests as dialogs in this app
+    pub fn approval_prompt(&self) -> Arc<dyn ApprovalPrompt> {
+        Arc::new(self.approval_prompt.clone())
+    }
+
+    /// Load key bindings from `keybindings.toml` in `config_dir`. Conflicts
+    /// and invalid files are reported as notifications; an invalid file
+    /// leaves the defaults in place.
+    pub fn load_keybindings(&mut self, config_dir: &std::path::Path) {
+        match Keymap::load(config_dir) {
+            Ok((keymap, conflicts)) => {
+                for conflict in conflicts {
+                    warn!("Key binding conflict: {}", conflict);
+                    self.toasts.warning(conflict.to_string());
+                }
+                self.event_handler.set_keymap(keymap);
+            }
+            Err(e) => {
+                warn!("{}", e);
+                self.toasts
+                    .push(ErrorToast::with_severity(e.to_string(), e.severity()));
+            }
+        }
+        self.command_palette.set_keymap(self.event_handler.keymap());
+    }
+
+    /// Add the themes in `<config_dir>/themes` and switch to `preferred`.
+    /// "default" keeps the built-in dark theme.
+    pub fn load_themes(&mut self, config_dir: &std::path::Path, preferred: &str) {
+        for e in self
+            .theme_manager
+            .load_themes_from_dir(&config_dir.join("themes"))
+        {
+            warn!("{}", e);
+            self.toasts
+                .push(ErrorToast::with_severity(e.to_string(), e.severity()));
+        }
+
+        if preferred != "default" {
+            if let Err(e) = self.theme_manager.set_theme(preferred) {
+                warn!("{}", e);
+                self.toasts.warning(e);
+            }
+        }
+    }
+
+    /// Offer the commands in `registry` in the command palette
+    pub async fn set_command_registry(&mut self, registry: Arc<CommandRegistry>) {
+        self.command_palette
+            .set_commands(&registry.list_commands().await);
+        self.command_registry = Some(registry);
+    }
+
+    /// Track sessions in `memory`, starting with those already open
+    pub async fn set_memory_service(&mut self, memory: Arc<MemoryService>) {
+        for session in self.session_manager.sessions().await {
+            if let Err(e) = memory.start_session(session).await {
+                warn!("Failed to start memory tracking: {}", e);
+            }
+        }
+        if let Some(enrichment) = &self.approval_enrichment {
+            enrichment.register(memory.approval_context_provider());
+        }
+        self.memory_service = Some(memory);
+    }
+
+    /// Settings for the view of streamed replies
+    pub fn set_streaming_config(&mut self, config: StreamingViewConfig) {
+        self.stream_config = config;
+    }
+
+    /// Capture the mouse for scrolling and clicking, or leave it to the
+    /// terminal for native text selection
+    pub fn set_mouse_capture(&mut self, enabled: bool) -> Result<()> {
+        self.terminal_guard.set_mouse_capture(enabled)?;
+        Ok(())
+    }
+
+    /// Use `editor` for editing the input instead of `$VISUAL`/`$EDITOR`
+    pub fn set_external_editor(&mut self, editor: Arc<dyn ExternalEditor>) {
+        self.external_editor = editor;
+    }
+
+    /// Conversation of the active session
+    fn conversation(&mut self) -> &mut ConversationPane {
+        &mut self
+            .sessions
+            .active_mut()
+            .expect("a session is always open")
+            .conversation
+    }
+
+    /// Start a session and switch to it, keeping the current one open
+    async fn open_session(&mut self) -> Result<()> {
+        let session_id = self.session_manager.start_session().await?;
+        let title = format!("Session {}", self.sessions.opened() + 1);
+        self.session_manager
+            .rename_session(session_id, title.clone())
+            .await?;
+        let mut tab = SessionTab::new(session_id, title);
+        if let Some(session) = self.session_manager.current_session().await {
+            tab.update_from(&session);
+            if let Some(memory) = &self.memory_service {
+                if let Err(e) = memory.start_session(session).await {
+                    warn!("Failed to start memory tracking: {}", e);
+                }
+            }
+        }
+
+        self.save_draft();
+        self.sessions.open(tab);
+        self.input_editor.clear();
+        self.session_usage = None;
+        Ok(())
+    }
+
+    /// Show the sessions resumed from a checkpoint with their scrollback,
+    /// or start one if there are none
+    async fn open_resumed_sessions(&mut self) -> Result<()> {
+        let sessions = self.session_manager.sessions().await;
+        if sessions.is_empty() {
+            return self.open_session().await;
+        }
+
+        for session in sessions {
+            let title = session
+                .title
+                .clone()
+                .unwrap_or_else(|| format!("Session {}", self.sessions.opened() + 1));
+            let mut tab = SessionTab::new(session.id, title);
+            tab.update_from(&session);
+            let messages = self
+                .session_manager
+                .transcript(session.id)
+                .await
+                .map(|transcript| transcript.messages)
+                .unwrap_or_default();
+            tab.history = PromptHistory::from_prompts(
+                messages
+                    .iter()
+                    .filter(|m| {
+                        !m.is_deleted()
+                            && matches!(m.role, fennec_core::transcript::MessageRole::User)
+                    })
+                    .map(|m| m.content.clone()),
+            );
+            for message in messages.into_iter().filter(|m| !m.is_deleted()) {
+                tab.conversation.add_message(Message {
+                    role: match message.role {
+                        fennec_core::transcript::MessageRole::User => MessageRole::User,
+                        fennec_core::transcript::MessageRole::Assistant => MessageRole::Assistant,
+                        fennec_core::transcript::MessageRole::System => MessageRole::System,
+                    },
+                    content: message.content,
+                    timestamp: message
+                        .timestamp
+                        .with_timezone(&chrono::Local)
+                        .format("%H:%M:%S")
+                        .to_string(),
+                });
+            }
+            self.sessions.open(tab);
+        }
+        if let Some(session_id) = self.session_manager.current_session_id().await {
+            self.sessions.activate(session_id);
+        }
+        Ok(())
+    }
+
+    /// Make `session_id` the active session, restoring its scrollback and
+    /// draft. Its reply keeps streaming if one was.
+    async fn switch_session(&mut self, session_id: Uuid) {
+        if self.sessions.active().map(|tab| tab.id) == Some(session_id) {
+            return;
+        }
+        if let Err(e) = self.session_manager.switch_session(session_id).await {
+            warn!("Failed to switch session: {}", e);
+            self.toasts
+                .error(format!("Failed to switch session: {}", e));
+            return;
+        }
+
+        self.save_draft();
+        self.sessions.activate(session_id);
+        let draft = self
+            .sessions
+            .active()
+            .map(|tab| tab.draft.clone())
+            .unwrap_or_default();
+        self.input_editor.set_content(draft);
+        self.session_usage = self.session_manager.session_usage().await;
+    }
+
+    /// End `session_id`, cancelling any reply still streaming. Another
+    /// session is opened first if it is the last one.
+    async fn close_session(&mut self, session_id: Uuid) {
+        if !self.leave_tab(session_id).await {
+            return;
+        }
+        self.end_session(session_id).await;
+        self.sessions.close(session_id);
+    }
+
+    /// Move away from the tab of `session_id` so it can be closed, opening
+    /// another session if it is the last one. Returns whether the tab can
+    /// be closed.
+    async fn leave_tab(&mut self, session_id: Uuid) -> bool {
+        if self.sessions.len() == 1 {
+            if let Err(e) = self.open_session().await {
+                self.toasts
+                    .error(format!("Failed to start a session: {}", e));
+                return false;
+            }
+        } else if self.sessions.active().map(|tab| tab.id) == Some(session_id) {
+            if let Some(neighbour) = self.sessions.neighbour(-1) {
+                self.switch_session(neighbour).await;
+            }
+        }
+
+        if let Some(stream) = self
+            .sessions
+            .get_mut(session_id)
+            .and_then(|tab| tab.stream.as_mut())
+        {
+            stream.cancel();
+        }
+        true
+    }
+
+    /// Warn about idle sessions and close the tabs of those the session
+    /// manager ended for being idle too long
+    async fn handle_idle_sessions(&mut self) {
+        let events = match self.session_manager.check_idle().await {
+            Ok(events) => events,
+            Err(e) => {
+                warn!("Failed to check for idle sessions: {}", e);
+                return;
+            }
+        };
+
+        for event in events {
+            match event {
+                IdleEvent::Warning {
+                    session_id,
+                    ends_in,
+                } => {
+                    let title = self
+                        .sessions
+                        .get_mut(session_id)
+                        .map(|tab| tab.title.clone())
+                        .unwrap_or_else(|| session_id.to_string());
+                    self.toasts.warning(format!(
+                        "{} is idle and will end in {} minutes",
+                        title,
+                        ends_in.as_secs().div_ceil(60)
+                    ));
+                    self.sessions.sync(&self.session_manager.sessions().await);
+                }
+                IdleEvent::Ended { session, .. } => {
+                    let session_id = session.id;
+                    if let Some(memory) = &self.memory_service {
+                        let project_id = memory.get_session_project_id(session_id);
+                        if let Err(e) = memory.end_session(session, project_id, None).await {
+                            warn!("Failed to stop memory tracking: {}", e);
+                        }
+                    }
+                    if self.sessions.get_mut(session_id).is_some()
+                        && self.leave_tab(session_id).await
+                    {
+                        self.sessions.close(session_id);
+                    }
+                }
+            }
+        }
+    }
+
+    /// End `session_id` in the session manager and memory service
+    async fn end_session(&self, session_id: Uuid) {
+        if let Err(e) = self.session_manager.close_session(session_id).await {
+            warn!("Failed to end session {}: {}", session_id, e);
+        }
+        if let Some(memory) = &self.memory_service {
+            if let Err(e) = memory.stop_session(session_id).await {
+                warn!("Failed to stop memory tracking: {}", e);
+            }
+        }
+    }
+
+    /// Keep the input typed so far with the active session
+    fn save_draft(&mut self) {
+        let draft = self.input_editor.content().to_string();
+        if let Some(tab) = self.sessions.active_mut() {
+            tab.draft = draft;
+        }
+    }
+
+    /// Record replies that finished streaming in any session
+    async fn handle_finished_streams(&mut self) {
+        let finished = self.sessions.poll_streams();
+        if finished.is_empty() {
+            return;
+        }
+
+        for stream in finished {
+            match stream.status {
+                StreamStatus::Failed(error) => {
+                    let error_message = format!("Failed to stream reply: {}", error);
+                    warn!("{}", error_message);
+                    self.toasts.error(error_message);
+                }
+                _ => {
+                    if let Err(e) = self
+                        .session_manager
+                        .record_streamed_reply(stream.session_id, stream.content)
+                        .await
+                    {
+                        warn!("Failed to record streamed reply: {}", e);
+                    }
+                }
+            }
+        }
+
+        self.session_usage = self.session_manager.session_usage().await;
     }
 
     /// Main application run loop
@@ -205,7 +642,7 @@ impl App {
         info!("Starting Fennec TUI main loop");
 
         // Add welcome message
-        self.chat_view.add_message(Message {
+        self.conversation().add_message(Message {
             role: MessageRole::System,
             content: "Welcome to Fennec! Type 'i' to start chatting or '?' for help.".to_string(),
             timestamp: Self::current_timestamp(),
@@ -213,6 +650,13 @@ impl App {
 
         // Main event loop
         while self.state == AppState::Running {
+            // Show approvals requested since the last iteration
+            self.approval_dialog.receive(&mut self.approval_requests);
+            let awaiting_approval = self.approval_dialog.is_active();
+            if let Some(tab) = self.sessions.active_mut() {
+                tab.awaiting_approval = awaiting_approval;
+            }
+
             // Handle events
             if let Some(event) = self.event_handler.next_event().await {
                 if let Err(e) = self.handle_event(event).await {
@@ -221,6 +665,13 @@ impl App {
                 }
             }
 
+            if let Err(e) = self.apply_settled_resize() {
+                error!("Resize error: {}", e);
+            }
+
+            self.session_manager.checkpoint_if_due().await;
+            self.handle_idle_sessions().await;
+
             // Render the interface
             if let Err(e) = self.render() {
                 error!("Render error: {}", e);
@@ -231,6 +682,10 @@ impl App {
             self.frame_count += 1;
         }
 
+        for tab in self.sessions.tabs() {
+            self.end_session(tab.id).await;
+        }
+
         self.cleanup()?;
 
         // Handle final state
@@ -253,7 +708,7 @@ impl App {
         match event {
             AppEvent::Input(input_event) => self.handle_input_event(input_event).await?,
             AppEvent::Tick => {
-                self.handle_tick();
+                self.handle_tick().await;
             }
             AppEvent::Resize(width, height) => {
                 self.handle_resize(width, height)?;
@@ -267,19 +722,25 @@ impl App {
                 }
             }
             AppEvent::NewMessage(content) => {
-                self.chat_view.add_message(Message {
+                self.conversation().add_message(Message {
                     role: MessageRole::Assistant,
                     content,
                     timestamp: Self::current_timestamp(),
                 });
             }
             AppEvent::Error(msg) => {
-                self.show_error_popup(msg);
+                self.toasts.error(msg);
             }
             AppEvent::SessionStateChanged => {
                 // Update UI based on session state changes
                 self.update_status_bar_info();
             }
+            AppEvent::SendMessage(content) => {
+                self.send_message(content).await;
+            }
+            AppEvent::RunCommand(name) => {
+                self.run_registry_command(name).await;
+            }
         }
 
         Ok(())
@@ -287,28 +748,146 @@ impl App {
 
     /// Handle terminal input events
     async fn handle_input_event(&mut self, event: Event) -> Result<()> {
+        if matches!(event, Event::Key(_) | Event::Mouse(_) | Event::Paste(_)) {
+            if let Some(tab) = self.sessions.active() {
+                self.session_manager.keep_alive(tab.id).await;
+            }
+        }
+
         match event {
             Event::Key(key_event) => self.handle_key_event(key_event).await?,
             Event::Mouse(mouse_event) => {
-                self.handle_mouse_event(mouse_event);
+                self.handle_mouse_event(mouse_event).await?;
             }
             Event::Resize(width, height) => {
                 self.handle_resize(width, height)?;
             }
+            Event::Paste(text) => self.handle_paste(&text),
             _ => {}
         }
 
         Ok(())
     }
 
+    /// Insert pasted text into the input whole, so a line break in it
+    /// doesn't send what was pasted so far
+    fn handle_paste(&mut self, text: &str) {
+        let overlay_open = self.approval_dialog.is_active()
+            || self.current_popup.is_some()
+            || self.diff_review.is_some()
+            || self.command_palette.is_open()
+            || self.sessions.is_picker_open()
+            || self.sandbox_dialog.is_open()
+            || self.toasts.is_history_visible()
+            || self.show_help;
+        if overlay_open {
+            return;
+        }
+
+        match self.event_handler.input_mode() {
+            // Commands and search queries are a single line
+            InputMode::Command | InputMode::Search => {
+                let line = text.lines().collect::<Vec<_>>().join(" ");
+                self.input_editor.insert_str(&line);
+                self.update_incremental_search();
+            }
+            InputMode::Normal => {
+                self.event_handler.set_input_mode(InputMode::Insert);
+                self.focused_pane = Pane::Input;
+                self.input_editor.insert_str(text);
+            }
+            InputMode::Insert => self.input_editor.insert_str(text),
+        }
+    }
+
     /// Handle keyboard input
     async fn handle_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
+        // Approval dialogs take every key until answered
+        if self.approval_dialog.is_active() {
+            if let Some(decision) = self.approval_dialog.handle_key(key_event) {
+                info!("Approval dialog answered: {:?}", decision);
+            }
+            return Ok(());
+        }
+
         // Close popup if one is open
         if self.current_popup.is_some() {
             self.current_popup = None;
             return Ok(());
         }
 
+        // A diff review takes keys until it is applied or cancelled
+        if let Some(review) = self.diff_review.as_mut() {
+            if key_event.kind == KeyEventKind::Release {
+                return Ok(());
+            }
+            match review.handle_key(key_event) {
+                Some(DiffReviewAction::Apply) => self.apply_diff_review().await,
+                Some(DiffReviewAction::Cancel) => {
+                    self.diff_review = None;
+                    self.toasts.info("Edit cancelled; no files were changed");
+                }
+                None => {}
+            }
+            return Ok(());
+        }
+
+        // The palette takes typing until an entry is chosen or it is closed
+        if self.command_palette.is_open() {
+            if key_event.kind == KeyEventKind::Release {
+                return Ok(());
+            }
+            match self.command_palette.handle_key(key_event) {
+                Some(PaletteItem::Action(action)) => self.handle_key_action(action).await?,
+                Some(PaletteItem::Command(name)) => self.run_registry_command(name).await,
+                None => {}
+            }
+            return Ok(());
+        }
+
+        // The session picker takes keys until a session is chosen or it is
+        // closed
+        if self.sessions.is_picker_open() {
+            if key_event.kind == KeyEventKind::Release {
+                return Ok(());
+            }
+            match self.sessions.handle_picker_key(key_event) {
+                Some(SessionPickerAction::Switch(session_id)) => {
+                    self.switch_session(session_id).await
+                }
+                Some(SessionPickerAction::New) => {
+                    if let Err(e) = self.open_session().await {
+                        self.toasts
+                            .error(format!("Failed to start a session: {}", e));
+                    }
+                }
+                Some(SessionPickerAction::Close(session_id)) => {
+                    self.close_session(session_id).await
+                }
+                None => {}
+            }
+            self.update_status_bar_info();
+            return Ok(());
+        }
+
+        // The sandbox dialog takes keys until a level is chosen or it is
+        // closed
+        if self.sandbox_dialog.is_open() {
+            if key_event.kind == KeyEventKind::Release {
+                return Ok(());
+            }
+            if let Some(level) = self.sandbox_dialog.handle_key(key_event) {
+                self.downgrade_sandbox(level).await;
+            }
+            return Ok(());
+        }
+
+        // The notification history keeps its own keys while open
+        if self.toasts.is_history_visible() {
+            self.toasts.handle_history_key(key_event);
+            return Ok(());
+        }
+
         // Close help if open
         if self.show_help {
             self.show_help = false;
@@ -330,6 +909,10 @@ impl App {
                 self.focused_pane = Pane::Input;
             }
             KeyAction::EnterNormal => {
+                if self.event_handler.input_mode() == InputMode::Search {
+                    self.conversation().cancel_search();
+                    self.input_editor.clear();
+                }
                 self.event_handler.set_input_mode(InputMode::Normal);
                 self.focused_pane = Pane::Chat;
             }
@@ -340,6 +923,8 @@ impl App {
             KeyAction::EnterSearch => {
                 self.event_handler.set_input_mode(InputMode::Search);
                 self.focused_pane = Pane::Input;
+                self.input_editor.clear();
+                self.conversation().begin_search();
             }
             KeyAction::MoveUp => {
                 self.handle_move_up();
@@ -365,23 +950,43 @@ impl App {
             KeyAction::GoToBottom => {
                 self.handle_go_to_bottom();
             }
+            KeyAction::NextMatch => {
+                self.conversation().next_match();
+            }
+            KeyAction::PreviousMatch => {
+                self.conversation().previous_match();
+            }
             KeyAction::Send => {
                 self.handle_send().await?;
             }
+            KeyAction::NewLine => {
+                self.input_editor.insert_newline();
+            }
+            KeyAction::OpenEditor => {
+                self.open_external_editor().await?;
+            }
             KeyAction::Clear => {
-                self.input_field.clear();
+                self.input_editor.clear();
+                self.update_incremental_search();
             }
             KeyAction::Delete => {
-                self.input_field.delete();
+                self.input_editor.delete();
+                self.update_incremental_search();
             }
             KeyAction::Backspace => {
-                self.input_field.backspace();
+                self.input_editor.backspace();
+                self.update_incremental_search();
             }
             KeyAction::InsertChar(c) => {
-                self.input_field.insert_char(c);
+                self.input_editor.insert_char(c);
+                self.update_incremental_search();
             }
             KeyAction::ToggleTheme => {
                 self.theme_manager.next_theme();
+                self.toasts.info(format!(
+                    "Theme: {}",
+                    self.theme_manager.current_theme().name
+                ));
             }
             KeyAction::FocusNext => {
                 let area = self.terminal.size()?;
@@ -404,6 +1009,42 @@ impl App {
             KeyAction::Refresh => {
                 // Force re-render
             }
+            KeyAction::OpenCommandPalette => {
+                self.command_palette.open();
+            }
+            KeyAction::DowngradeSandbox => match &self.sandbox_policy {
+                Some(policy) => self.sandbox_dialog.open(policy.level().clone()),
+                None => self
+                    .toasts
+                    .warning("The sandbox level was set at startup and can't be changed"),
+            },
+            KeyAction::OpenSessionPicker => {
+                self.sessions.sync(&self.session_manager.sessions().await);
+                self.sessions.open_picker();
+            }
+            KeyAction::NextSession => {
+                if let Some(session_id) = self.sessions.neighbour(1) {
+                    self.switch_session(session_id).await;
+                }
+            }
+            KeyAction::PreviousSession => {
+                if let Some(session_id) = self.sessions.neighbour(-1) {
+                    self.switch_session(session_id).await;
+                }
+            }
+            KeyAction::CycleLogLevel => {
+                self.cycle_log_level();
+            }
+            KeyAction::ToggleToastHistory => {
+                self.toasts.toggle_history();
+            }
+            KeyAction::ToastAction => {
+                if let Some(event) = self.toasts.take_action() {
+                    if let Err(e) = self.event_handler.send_event(event) {
+                        warn!("{}", e);
+                    }
+                }
+            }
             _ => {}
         }
 
@@ -413,56 +1054,159 @@ impl App {
         Ok(())
     }
 
+    /// Raise the default log level for a repro: info, debug, trace, then
+    /// back to info
+    fn cycle_log_level(&mut self) {
+        let level = match TelemetrySystem::log_level() {
+            Some(LogLevel::Debug) => LogLevel::Trace,
+            Some(LogLevel::Trace) => LogLevel::Info,
+            _ => LogLevel::Debug,
+        };
+        match TelemetrySystem::set_level(None, level) {
+            Ok(()) => self.toasts.info(format!("Log level: {:?}", level)),
+            Err(e) => self.toasts.warning(e.to_string()),
+        }
+    }
+
     /// Handle mouse events
-    fn handle_mouse_event(&mut self, _mouse_event: MouseEvent) {
-        // TODO: Implement mouse event handling for click-to-focus, etc.
+    async fn handle_mouse_event(&mut self, mouse_event: MouseEvent) -> Result<()> {
+        // Dialogs and overlays are driven from the keyboard
+        let overlay_open = self.approval_dialog.is_active()
+            || self.current_popup.is_some()
+            || self.diff_review.is_some()
+            || self.command_palette.is_open()
+            || self.sessions.is_picker_open()
+            || self.sandbox_dialog.is_open()
+            || self.toasts.is_history_visible()
+            || self.show_help;
+        if overlay_open {
+            return Ok(());
+        }
+
+        let area = self.terminal.size()?;
+        let layout = self.layout_manager.layout(area).clone();
+        match self.event_handler.handle_mouse_event(mouse_event, &layout) {
+            MouseAction::Scroll {
+                pane,
+                action,
+                lines,
+            } => self.scroll_pane(pane, action, lines),
+            MouseAction::Click {
+                pane: Pane::Input, ..
+            } => {
+                if self.event_handler.input_mode() == InputMode::Normal {
+                    self.handle_key_action(KeyAction::EnterInsert).await?;
+                }
+            }
+            MouseAction::Click { pane, .. } => {
+                // Leave typing so keys go to the pane clicked
+                if self.focused_pane == Pane::Input {
+                    self.handle_key_action(KeyAction::EnterNormal).await?;
+                }
+                self.focused_pane = pane;
+            }
+            MouseAction::None => {}
+        }
+        Ok(())
+    }
+
+    /// Scroll `pane` by `lines` in the direction of a move action
+    fn scroll_pane(&mut self, pane: Pane, action: KeyAction, lines: usize) {
+        match (pane, action) {
+            (Pane::Chat, KeyAction::MoveUp) => self.conversation().scroll_up(lines),
+            (Pane::Chat, KeyAction::MoveDown) => self.conversation().scroll_down(lines),
+            (Pane::Preview, KeyAction::MoveUp) => self.preview_panel.scroll_up(lines),
+            (Pane::Preview, KeyAction::MoveDown) => self.preview_panel.scroll_down(lines),
+            _ => {}
+        }
     }
 
-    /// Handle terminal resize
+    /// Handle terminal resize. Relayout waits until the size settles.
     fn handle_resize(&mut self, width: u16, height: u16) -> Result<()> {
         debug!("Terminal resized to {}x{}", width, height);
-        self.terminal.resize(Rect::new(0, 0, width, height))?;
+        self.resize.resized(width, height, Instant::now());
+        Ok(())
+    }
+
+    /// Lay out for the new terminal size once resizing has stopped
+    fn apply_settled_resize(&mut self) -> Result<()> {
+        let Some((width, height)) = self.resize.settled(Instant::now()) else {
+            return Ok(());
+        };
+        let area = Rect::new(0, 0, width, height);
+        self.terminal.resize(area)?;
+
+        // Focus can't stay on a pane the new layout hides
+        if !self.layout_manager.is_pane_visible(area, self.focused_pane) {
+            self.focused_pane = Pane::Chat;
+        }
         Ok(())
     }
 
     /// Handle tick events
-    fn handle_tick(&mut self) {
+    async fn handle_tick(&mut self) {
         // Update any time-based animations or periodic updates
+        self.toasts.tick();
+        self.handle_finished_streams().await;
+        self.status_flash = !self.status_flash;
         self.update_status_bar_info();
     }
 
     /// Handle movement actions based on focused pane
     fn handle_move_up(&mut self) {
         match self.focused_pane {
-            Pane::Chat => self.chat_view.scroll_up(1),
+            Pane::Chat => self.conversation().scroll_up(1),
             Pane::Preview => self.preview_panel.scroll_up(1),
+            // Past the first line, Up recalls earlier prompts
+            Pane::Input if !self.input_editor.move_up() => self.recall_prompt(true),
             _ => {}
         }
     }
 
     fn handle_move_down(&mut self) {
         match self.focused_pane {
-            Pane::Chat => self.chat_view.scroll_down(1),
+            Pane::Chat => self.conversation().scroll_down(1),
             Pane::Preview => self.preview_panel.scroll_down(1),
+            Pane::Input if !self.input_editor.move_down() => self.recall_prompt(false),
             _ => {}
         }
     }
 
+    /// Show the prompt sent before or after the one in the input, while
+    /// typing a message
+    fn recall_prompt(&mut self, older: bool) {
+        if self.event_handler.input_mode() != InputMode::Insert {
+            return;
+        }
+        let current = self.input_editor.content().to_string();
+        let Some(history) = self.sessions.active_mut().map(|tab| &mut tab.history) else {
+            return;
+        };
+        let prompt = if older {
+            history.older(&current)
+        } else {
+            history.newer()
+        };
+        if let Some(prompt) = prompt {
+            self.input_editor.set_content(prompt);
+        }
+    }
+
     fn handle_move_left(&mut self) {
         if self.focused_pane == Pane::Input {
-            self.input_field.move_cursor_left();
+            self.input_editor.move_left();
         }
     }
 
     fn handle_move_right(&mut self) {
         if self.focused_pane == Pane::Input {
-            self.input_field.move_cursor_right();
+            self.input_editor.move_right();
         }
     }
 
     fn handle_page_up(&mut self) {
         match self.focused_pane {
-            Pane::Chat => self.chat_view.scroll_up(10),
+            Pane::Chat => self.conversation().scroll_up(10),
             Pane::Preview => self.preview_panel.scroll_up(10),
             _ => {}
         }
@@ -470,7 +1214,7 @@ impl App {
 
     fn handle_page_down(&mut self) {
         match self.focused_pane {
-            Pane::Chat => self.chat_view.scroll_down(10),
+            Pane::Chat => self.conversation().scroll_down(10),
             Pane::Preview => self.preview_panel.scroll_down(10),
             _ => {}
         }
@@ -478,23 +1222,23 @@ impl App {
 
     fn handle_go_to_top(&mut self) {
         match self.focused_pane {
-            Pane::Chat => self.chat_view.scroll_to_top(),
-            Pane::Input => self.input_field.move_cursor_to_start(),
+            Pane::Chat => self.conversation().scroll_to_top(),
+            Pane::Input => self.input_editor.move_to_start(),
             _ => {}
         }
     }
 
     fn handle_go_to_bottom(&mut self) {
         match self.focused_pane {
-            Pane::Chat => self.chat_view.scroll_to_bottom(),
-            Pane::Input => self.input_field.move_cursor_to_end(),
+            Pane::Chat => self.conversation().scroll_to_bottom(),
+            Pane::Input => self.input_editor.move_to_end(),
             _ => {}
         }
     }
 
     /// Handle send action (Enter key)
     async fn handle_send(&mut self) -> Result<()> {
-        let content = self.input_field.content().trim().to_string();
+        let content = self.input_editor.content().trim().to_string();
 
         if content.is_empty() {
             return Ok(());
@@ -504,44 +1248,20 @@ impl App {
 
         match mode {
             InputMode::Insert => {
-                // Send message to chat
-                self.chat_view.add_message(Message {
-                    role: MessageRole::User,
-                    content: content.clone(),
-                    timestamp: Self::current_timestamp(),
-                });
-
-                // Forward to session manager / provider
-                match self.session_manager.send_message(content.clone()).await {
-                    Ok(response) => {
-                        self.chat_view.add_message(Message {
-                            role: MessageRole::Assistant,
-                            content: response,
-                            timestamp: Self::current_timestamp(),
-                        });
-                    }
-                    Err(err) => {
-                        let error_message = format!("Failed to send message: {}", err);
-                        warn!("{}", error_message);
-                        self.show_error_popup(error_message.clone());
-                        self.chat_view.add_message(Message {
-                            role: MessageRole::System,
-                            content: error_message,
-                            timestamp: Self::current_timestamp(),
-                        });
-                    }
+                if let Some(tab) = self.sessions.active_mut() {
+                    tab.history.push(content.clone());
                 }
-
-                self.input_field.clear();
+                self.send_message(content).await;
+                self.input_editor.clear();
             }
             InputMode::Command => {
                 self.handle_command(&content).await?;
-                self.input_field.clear();
+                self.input_editor.clear();
                 self.event_handler.set_input_mode(InputMode::Normal);
             }
             InputMode::Search => {
                 self.handle_search(&content);
-                self.input_field.clear();
+                self.input_editor.clear();
                 self.event_handler.set_input_mode(InputMode::Normal);
             }
             _ => {}
@@ -550,6 +1270,202 @@ impl App {
         Ok(())
     }
 
+    /// Hand the input to the external editor, which has the terminal to
+    /// itself until it exits. The input is kept if the editor fails.
+    async fn open_external_editor(&mut self) -> Result<()> {
+        pause_event_listener().await;
+        let edited = match self.terminal_guard.suspend() {
+            Ok(()) => self
+                .input_editor
+                .edit_with(self.external_editor.as_ref())
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        let resumed = self.terminal_guard.resume();
+        resume_event_listener();
+        resumed?;
+        // The editor drew over the screen
+        self.terminal.clear()?;
+
+        if let Err(e) = edited {
+            warn!("External editor failed: {}", e);
+            self.toasts
+                .error(format!("Failed to edit the input: {}", e));
+        }
+        Ok(())
+    }
+
+    /// Send a chat message and stream the reply into the active session. A
+    /// failure to send is offered for retry.
+    async fn send_message(&mut self, content: String) {
+        if self
+            .sessions
+            .active()
+            .is_some_and(|tab| tab.stream.is_some())
+        {
+            self.toasts
+                .warning("Wait for the reply to finish before sending another message");
+            return;
+        }
+
+        self.conversation().add_message(Message {
+            role: MessageRole::User,
+            content: content.clone(),
+            timestamp: Self::current_timestamp(),
+        });
+
+        // Forward to session manager / provider
+        match self
+            .session_manager
+            .send_message_stream(content.clone())
+            .await
+        {
+            Ok(stream) => {
+                let (tokens, _forwarder) = forward_stream(stream);
+                let view = StreamingMessageView::new(tokens, self.stream_config.clone());
+                if let Some(tab) = self.sessions.active_mut() {
+                    tab.stream = Some(view);
+                }
+            }
+            Err(err) => {
+                let error_message = format!("Failed to send message: {}", err);
+                warn!("{}", error_message);
+                self.toasts.push(
+                    ErrorToast::with_severity(error_message.clone(), ErrorSeverity::Error)
+                        .with_action("Retry", AppEvent::SendMessage(content)),
+                );
+                self.conversation().add_message(Message {
+                    role: MessageRole::System,
+                    content: error_message,
+                    timestamp: Self::current_timestamp(),
+                });
+            }
+        }
+
+        self.session_usage = self.session_manager.session_usage().await;
+        self.update_status_bar_info();
+    }
+
+    /// Run a registry command without arguments and show its output. A
+    /// failure is offered for retry.
+    async fn run_registry_command(&mut self, name: String) {
+        let Some(registry) = self.command_registry.clone() else {
+            self.toasts
+                .warning(format!("No command registry to run '{}'", name));
+            return;
+        };
+
+        let context = CommandContext {
+            session_id: self
+                .session_manager
+                .current_session_id()
+                .await
+                .unwrap_or_else(Uuid::new_v4),
+            user_id: None,
+            workspace_path: self
+                .sandbox_policy
+                .as_ref()
+                .map(|policy| policy.workspace_path().display().to_string()),
+            sandbox_level: self
+                .sandbox_policy
+                .as_ref()
+                .map_or(SandboxLevel::ReadOnly, |policy| policy.level().clone()),
+            dry_run: false,
+            preview_only: false,
+            cancellation_token: Default::default(),
+            action_log: Some(self.action_log.clone()),
+            timeout: None,
+            progress: None,
+        };
+
+        let failure = match registry
+            .execute_command(&name, &serde_json::json!({}), &context)
+            .await
+        {
+            Ok(result) if result.success => {
+                // Proposed hunks are reviewed before anything is written
+                let hunks = result
+                    .data
+                    .as_ref()
+                    .and_then(|data| data.get("hunks"))
+                    .and_then(|hunks| serde_json::from_value::<Vec<Hunk>>(hunks.clone()).ok());
+                if let Some(hunks) = hunks.filter(|hunks| !hunks.is_empty()) {
+                    self.diff_review = Some(DiffReview::new(hunks));
+                }
+                self.conversation().add_message(Message {
+                    role: MessageRole::System,
+                    content: result.output,
+                    timestamp: Self::current_timestamp(),
+                });
+                None
+            }
+            Ok(result) => Some(result.error.unwrap_or(result.output)),
+            Err(e) => Some(e.to_string()),
+        };
+
+        if let Some(error) = failure {
+            warn!("Command '{}' failed: {}", name, error);
+            self.toasts.push(
+                ErrorToast::with_severity(
+                    format!("{} failed: {}", name, error),
+                    ErrorSeverity::Error,
+                )
+                .with_action("Retry", AppEvent::RunCommand(name)),
+            );
+        }
+    }
+
+    /// Tighten the sandbox to `level` for the rest of the session. Commands
+    /// and reviewed edits check the new policy from now on, and the
+    /// orchestration layer caps the commands it runs.
+    async fn downgrade_sandbox(&mut self, level: SandboxLevel) {
+        let Some(policy) = &self.sandbox_policy else {
+            return;
+        };
+        let policy = match policy.downgraded(level.clone()) {
+            Ok(policy) => policy,
+            Err(e) => {
+                self.toasts.error(e.to_string());
+                return;
+            }
+        };
+
+        info!("Sandbox downgraded to {}", level);
+        self.sandbox_policy = Some(policy);
+        if let Err(e) = self.session_manager.set_sandbox_level(level.clone()).await {
+            warn!("Failed to record the sandbox downgrade: {}", e);
+        }
+        self.toasts.info(format!("Sandbox tightened to {}", level));
+        self.update_status_bar_info();
+    }
+
+    /// Write the hunks accepted in the open diff review
+    async fn apply_diff_review(&mut self) {
+        let Some(review) = self.diff_review.take() else {
+            return;
+        };
+        let applier = WorkspaceHunkApplier::new(
+            self.sandbox_policy
+                .as_ref()
+                .map_or(SandboxLevel::ReadOnly, |policy| policy.level().clone()),
+            self.sandbox_policy
+                .as_ref()
+                .map(|policy| policy.workspace_path().to_path_buf()),
+            Some(self.action_log.clone()),
+        );
+
+        match review.apply(&applier).await {
+            Ok(changed) => self
+                .toasts
+                .info(format!("Applied changes to {} file(s)", changed.len())),
+            Err(e) => {
+                warn!("Failed to apply reviewed hunks: {}", e);
+                self.toasts.error(format!("Failed to apply changes: {}", e));
+            }
+        }
+    }
+
     /// Handle command execution
     async fn handle_command(&mut self, command: &str) -> Result<()> {
         debug!("Executing command: {}", command);
@@ -559,7 +1475,7 @@ impl App {
                 self.state = AppState::Quitting;
             }
             "clear" => {
-                self.chat_view.clear();
+                self.conversation().clear();
             }
             "theme" => {
                 self.theme_manager.next_theme();
@@ -567,9 +1483,10 @@ impl App {
             cmd if cmd.starts_with("theme ") => {
                 let theme_name = cmd.strip_prefix("theme ").unwrap_or("");
                 if let Err(_e) = self.theme_manager.set_theme(theme_name) {
-                    self.show_error_popup(format!("Unknown theme: {}", theme_name));
+                    self.toasts
+                        .warning(format!("Unknown theme: {}", theme_name));
                 } else {
-                    self.chat_view.add_message(Message {
+                    self.conversation().add_message(Message {
                         role: MessageRole::System,
                         content: format!("Theme changed to: {}", theme_name),
                         timestamp: Self::current_timestamp(),
@@ -580,17 +1497,29 @@ impl App {
                 self.show_help = true;
             }
             _ => {
-                self.show_error_popup(format!("Unknown command: {}", command));
+                self.toasts.warning(format!("Unknown command: {}", command));
             }
         }
 
         Ok(())
     }
 
-    /// Handle search
-    fn handle_search(&mut self, _query: &str) {
-        // TODO: Implement search functionality
-        self.show_error_popup("Search functionality not yet implemented".to_string());
+    /// Handle search: keep the query typed so far and its matches for
+    /// `n`/`N`
+    fn handle_search(&mut self, query: &str) {
+        self.conversation().search(query);
+        self.conversation().finish_search();
+        if self.conversation().state().matches().is_empty() {
+            self.toasts.info(format!("No messages match: {}", query));
+        }
+    }
+
+    /// Re-run the search as it is typed
+    fn update_incremental_search(&mut self) {
+        if self.event_handler.input_mode() == InputMode::Search {
+            let query = self.input_editor.content().to_string();
+            self.conversation().search(&query);
+        }
     }
 
     /// Show an error popup
@@ -601,6 +1530,10 @@ impl App {
     /// Update status bar information
     fn update_status_bar_info(&mut self) {
         self.status_bar.clear();
+        let message_count = self
+            .sessions
+            .active()
+            .map_or(0, |tab| tab.conversation.messages().len());
 
         if let Some(ref sandbox_policy) = self.sandbox_policy {
             // Use security-aware status bar
@@ -608,7 +1541,9 @@ impl App {
                 &mut self.status_bar,
                 self.event_handler.input_mode(),
                 sandbox_policy,
-                self.chat_view.messages().len(),
+                message_count,
+                self.session_usage.as_ref(),
+                self.status_flash,
             );
         } else {
             // Fallback to legacy status bar
@@ -616,9 +1551,20 @@ impl App {
                 &mut self.status_bar,
                 self.event_handler.input_mode(),
                 &SandboxLevel::WorkspaceWrite, // Default fallback
-                self.chat_view.messages().len(),
+                message_count,
+                self.session_usage.as_ref(),
             );
         }
+
+        if self.sessions.len() > 1 {
+            if let Some(tab) = self.sessions.active() {
+                self.status_bar.add_left(StatusItem {
+                    label: "Session".to_string(),
+                    value: tab.title.clone(),
+                    style: ComponentType::Text,
+                });
+            }
+        }
     }
 
     /// Update status bar with current information (legacy method)
@@ -627,6 +1573,7 @@ impl App {
         mode: InputMode,
         sandbox_level: &SandboxLevel,
         message_count: usize,
+        usage: Option<&UsageReport>,
     ) {
         // Left side items
         let mode_text = match mode {
@@ -660,6 +1607,10 @@ impl App {
             style: ComponentType::Text,
         });
 
+        if let Some(usage) = usage {
+            status_bar.add_right(Self::usage_status_item(usage));
+        }
+
         status_bar.add_right(StatusItem {
             label: "Help".to_string(),
             value: "?".to_string(),
@@ -673,6 +1624,8 @@ impl App {
         mode: InputMode,
         sandbox_policy: &SandboxPolicy,
         message_count: usize,
+        usage: Option<&UsageReport>,
+        flash: bool,
     ) {
         // Left side items
         let mode_text = match mode {
@@ -693,26 +1646,9 @@ impl App {
             style: mode_style,
         });
 
-        // Security status with color coding
-        let (sandbox_display, sandbox_style) = match sandbox_policy.level() {
-            SandboxLevel::ReadOnly => ("🔒 READ-ONLY".to_string(), ComponentType::StatusActive),
-            SandboxLevel::WorkspaceWrite => ("📝 WORKSPACE".to_string(), ComponentType::Text),
-            SandboxLevel::FullAccess => ("⚠️ DANGER".to_string(), ComponentType::Error),
-        };
-
-        status_bar.add_left(StatusItem {
-            label: "Security".to_string(),
-            value: sandbox_display,
-            style: sandbox_style,
-        });
-
-        // Approval indicator
-        if sandbox_policy.requires_approval() {
-            status_bar.add_left(StatusItem {
-                label: "Approval".to_string(),
-                value: "🛡️ ON".to_string(),
-                style: ComponentType::StatusActive,
-            });
+        // Sandbox level, approval mode and workspace, always in view
+        for item in sandbox_status_items(sandbox_policy, flash) {
+            status_bar.add_left(item);
         }
 
         // Right side items
@@ -722,19 +1658,9 @@ impl App {
             style: ComponentType::Text,
         });
 
-        // Workspace indicator (abbreviated path)
-        let workspace_display = sandbox_policy
-            .workspace_path()
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("workspace")
-            .to_string();
-
-        status_bar.add_right(StatusItem {
-            label: "Workspace".to_string(),
-            value: workspace_display,
-            style: ComponentType::Muted,
-        });
+        if let Some(usage) = usage {
+            status_bar.add_right(Self::usage_status_item(usage));
+        }
 
         status_bar.add_right(StatusItem {
             label: "Help".to_string(),
@@ -743,77 +1669,46 @@ impl App {
         });
     }
 
+    /// Running token and cost total of the session, colored by budget
+    fn usage_status_item(usage: &UsageReport) -> StatusItem {
+        let style = match usage.budget {
+            BudgetStatus::WithinBudget => ComponentType::Muted,
+            BudgetStatus::SoftLimitReached { .. } => ComponentType::Warning,
+            BudgetStatus::HardLimitReached { .. } => ComponentType::Error,
+        };
+        StatusItem {
+            label: "Usage".to_string(),
+            value: usage.status_line(),
+            style,
+        }
+    }
+
     /// Render the application
     fn render(&mut self) -> Result<()> {
+        // Wait for the terminal to settle on a size before drawing it
+        if self.resize.is_pending() {
+            return Ok(());
+        }
         let start_time = Instant::now();
 
-        let result = {
-            let terminal = &mut self.terminal;
-            let layout_manager = &mut self.layout_manager;
-            let theme_manager = &self.theme_manager;
-            let chat_view = &mut self.chat_view;
-            let input_field = &self.input_field;
-            let preview_panel = &mut self.preview_panel;
-            let status_bar = &self.status_bar;
-            let focused_pane = self.focused_pane;
-            let input_mode = self.event_handler.input_mode();
-            let show_help = self.show_help;
-            let current_popup = &self.current_popup;
-
-            terminal.draw(|frame| {
-                let area = frame.size();
-
-                // Check minimum terminal size
-                if let Err(msg) = layout_manager.check_terminal_size(area) {
-                    let popup_area = crate::layout::utils::dialog_area(area);
-                    let dialog = PopupDialog::warning("Terminal Too Small".to_string(), msg);
-                    dialog.render(popup_area, frame.buffer_mut(), theme_manager);
-                    return;
-                }
-
-                let layout = layout_manager.layout(area).clone();
-
-                // Render main components
-                chat_view.render(
-                    layout.chat_area,
-                    frame.buffer_mut(),
-                    theme_manager,
-                    focused_pane == Pane::Chat,
-                );
-
-                input_field.render(
-                    layout.input_area,
-                    frame.buffer_mut(),
-                    theme_manager,
-                    input_mode,
-                );
-
-                if let Some(preview_area) = layout.preview_area {
-                    preview_panel.render(
-                        preview_area,
-                        frame.buffer_mut(),
-                        theme_manager,
-                        focused_pane == Pane::Preview,
-                    );
-                }
-
-                status_bar.render(layout.status_area, frame.buffer_mut(), theme_manager);
-
-                // Render help overlay if needed
-                if show_help {
-                    let help_area = crate::layout::utils::help_area(area);
-                    Self::render_help_static(help_area, frame.buffer_mut(), theme_manager);
-                }
-
-                // Render popup if needed
-                if let Some(popup) = current_popup {
-                    let popup_area = crate::layout::utils::dialog_area(area);
-                    popup.render(popup_area, frame.buffer_mut(), theme_manager);
-                }
-            })
+        let screen = Screen {
+            layout_manager: &mut self.layout_manager,
+            theme_manager: &self.theme_manager,
+            sessions: &mut self.sessions,
+            input_editor: &self.input_editor,
+            preview_panel: &mut self.preview_panel,
+            status_bar: &self.status_bar,
+            focused_pane: self.focused_pane,
+            input_mode: self.event_handler.input_mode(),
+            show_help: self.show_help,
+            current_popup: &self.current_popup,
+            approval_dialog: &self.approval_dialog,
+            sandbox_dialog: &mut self.sandbox_dialog,
+            toasts: &self.toasts,
+            command_palette: &mut self.command_palette,
+            diff_review: self.diff_review.as_ref(),
         };
-
-        result?;
+        self.terminal.draw(|frame| screen.draw(frame))?;
 
         let render_time = start_time.elapsed();
         if render_time > Duration::from_millis(16) {
@@ -837,13 +1732,21 @@ impl App {
             "  Tab / Shift+Tab  - Switch panes".to_string(),
             "  Ctrl+u/d        - Page up/down".to_string(),
             "  g/G             - Go to top/bottom".to_string(),
+            "  n/N             - Next/previous search match".to_string(),
+            "  Mouse           - Wheel scrolls, click focuses a pane".to_string(),
             "".to_string(),
             "Modes:".to_string(),
             "  i               - Insert mode (chat)".to_string(),
             "  :               - Command mode".to_string(),
-            "  /               - Search mode".to_string(),
+            "  /               - Search the conversation".to_string(),
             "  Esc             - Normal mode".to_string(),
             "".to_string(),
+            "Input:".to_string(),
+            "  Enter           - Send".to_string(),
+            "  Shift/Alt+Enter - New line".to_string(),
+            "  Up/Down         - Previous/next line or sent prompt".to_string(),
+            "  Ctrl+E          - Edit in $EDITOR".to_string(),
+            "".to_string(),
             "Commands:".to_string(),
             "  :quit           - Exit application".to_string(),
             "  :clear          - Clear chat history".to_string(),
@@ -853,6 +1756,12 @@ impl App {
             "Other:".to_string(),
             "  t               - Toggle theme".to_string(),
             "  p               - Toggle preview panel".to_string(),
+            "  Ctrl+K          - Command palette".to_string(),
+            "  s               - Switch, open or close sessions".to_string(),
+            "  S               - Tighten the sandbox".to_string(),
+            "  [ / ]           - Previous/next session".to_string(),
+            "  e               - Show notification history".to_string(),
+            "  r               - Take a notification's action (e.g. retry)".to_string(),
             "  q               - Quit".to_string(),
             "  ?               - Show/hide help".to_string(),
             "".to_string(),
@@ -880,17 +1789,135 @@ impl App {
     /// Cleanup terminal state
     fn cleanup(&mut self) -> Result<()> {
         info!("Cleaning up terminal state");
-        disable_raw_mode()?;
-        execute!(
-            self.terminal.backend_mut(),
-            LeaveAlternateScreen,
-            DisableMouseCapture
-        )?;
+        self.terminal_guard.restore()?;
         self.terminal.show_cursor()?;
         Ok(())
     }
 }
 
+/// Everything drawn in a frame, borrowed from the app
+struct Screen<'a> {
+    layout_manager: &'a mut LayoutManager,
+    theme_manager: &'a ThemeManager,
+    sessions: &'a mut SessionRegistry,
+    input_editor: &'a InputEditor,
+    preview_panel: &'a mut PreviewPanel,
+    status_bar: &'a StatusBar,
+    focused_pane: Pane,
+    input_mode: InputMode,
+    show_help: bool,
+    current_popup: &'a Option<PopupDialog>,
+    approval_dialog: &'a ApprovalDialog,
+    sandbox_dialog: &'a mut SandboxDialog,
+    toasts: &'a ToastStack,
+    command_palette: &'a mut CommandPalette,
+    diff_review: Option<&'a DiffReview>,
+}
+
+impl Screen<'_> {
+    fn draw(self, frame: &mut Frame) {
+        let area = frame.size();
+        let theme_manager = self.theme_manager;
+        let buf = frame.buffer_mut();
+        // The input box grows with its text, up to a third of the screen
+        let input_width = self.layout_manager.layout(area).input_area.width;
+        let input_height = self
+            .input_editor
+            .height(input_width)
+            .min((area.height / 3).max(3));
+        self.layout_manager.set_input_height(input_height);
+        let layout = self.layout_manager.layout(area).clone();
+
+        // Render main components, with a reply still streaming in below
+        // the conversation
+        if let Some(tab) = self.sessions.active_mut() {
+            let chat_focused = self.focused_pane == Pane::Chat;
+            match &tab.stream {
+                Some(stream) => {
+                    let rows = crate::layout::utils::equal_rows(layout.chat_area, 2);
+                    let (history_area, stream_area) = (rows[0], rows[1]);
+                    tab.conversation
+                        .render(history_area, buf, theme_manager, chat_focused);
+                    stream.render(stream_area, buf, theme_manager, chat_focused);
+                }
+                None => tab
+                    .conversation
+                    .render(layout.chat_area, buf, theme_manager, chat_focused),
+            }
+        }
+
+        self.input_editor
+            .render(layout.input_area, buf, theme_manager, self.input_mode);
+
+        if let Some(preview_area) = layout.preview_area {
+            self.preview_panel.render(
+                preview_area,
+                buf,
+                theme_manager,
+                self.focused_pane == Pane::Preview,
+            );
+        }
+
+        if let Some(status_area) = layout.status_area {
+            self.status_bar.render(status_area, buf, theme_manager);
+        }
+
+        // Below the minimum size, say how much room the full layout needs
+        if let Some(hint_area) = layout.hint_area {
+            Paragraph::new(self.layout_manager.compact_hint(area))
+                .style(theme_manager.get_style(ComponentType::Warning))
+                .render(hint_area, buf);
+        }
+
+        // Toasts stack over the chat so they never cover the input
+        self.toasts.render(layout.chat_area, buf, theme_manager);
+
+        if self.toasts.is_history_visible() {
+            let history_area = crate::layout::utils::help_area(area);
+            self.toasts.render_history(history_area, buf, theme_manager);
+        }
+
+        if self.sessions.is_picker_open() {
+            let picker_area = crate::layout::utils::popup_area(area, 60, 50);
+            self.sessions.render_picker(picker_area, buf, theme_manager);
+        }
+
+        if self.sandbox_dialog.is_open() {
+            let dialog_area = crate::layout::utils::popup_area(area, 50, 40);
+            self.sandbox_dialog.render(dialog_area, buf, theme_manager);
+        }
+
+        if self.command_palette.is_open() {
+            let palette_area = crate::layout::utils::popup_area(area, 60, 50);
+            self.command_palette
+                .render(palette_area, buf, theme_manager);
+        }
+
+        if let Some(review) = self.diff_review {
+            let review_area = crate::layout::utils::help_area(area);
+            review.render(review_area, buf, theme_manager);
+        }
+
+        // Render help overlay if needed
+        if self.show_help {
+            let help_area = crate::layout::utils::help_area(area);
+            App::render_help_static(help_area, buf, theme_manager);
+        }
+
+        // Render popup if needed
+        if let Some(popup) = self.current_popup {
+            let popup_area = crate::layout::utils::dialog_area(area);
+            popup.render(popup_area, buf, theme_manager);
+        }
+
+        // Approval dialogs stay on top of everything else
+        if self.approval_dialog.is_active() {
+            let dialog_area = crate::layout::utils::popup_area(area, 70, 60);
+            self.approval_dialog.render(dialog_area, buf, theme_manager);
+        }
+    }
+}
+
 /// Ensure cleanup happens even if the app panics
 impl Drop for App {
     fn drop(&mut self) {
@@ -899,3 +1926,158 @@ impl Drop for App {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+
+    /// Components drawn by [`Screen`], owned so a test can lend them out
+    struct Parts {
+        layout_manager: LayoutManager,
+        theme_manager: ThemeManager,
+        sessions: SessionRegistry,
+        input_editor: InputEditor,
+        preview_panel: PreviewPanel,
+        status_bar: StatusBar,
+        approval_dialog: ApprovalDialog,
+        sandbox_dialog: SandboxDialog,
+        toasts: ToastStack,
+        command_palette: CommandPalette,
+    }
+
+    impl Parts {
+        fn new() -> Self {
+            let mut sessions = SessionRegistry::new();
+            let mut tab = SessionTab::new(Uuid::new_v4(), "Session 1");
+            for i in 0..40 {
+                tab.conversation.add_message(Message {
+                    role: MessageRole::User,
+                    content: format!("message {} with enough words to wrap on narrow panes", i),
+                    timestamp: "12:00:00".to_string(),
+                });
+            }
+            sessions.open(tab);
+
+            let mut preview_panel = PreviewPanel::new();
+            preview_panel.set_content((0..40).map(|i| format!("preview {}", i)).collect());
+            let mut status_bar = StatusBar::new();
+            status_bar.add_left(StatusItem {
+                label: "Mode".to_string(),
+                value: "NORMAL".to_string(),
+                style: ComponentType::StatusActive,
+            });
+
+            Self {
+                layout_manager: LayoutManager::default(),
+                theme_manager: ThemeManager::new(),
+                sessions,
+                input_editor: InputEditor::new(),
+                preview_panel,
+                status_bar,
+                approval_dialog: ApprovalDialog::new(),
+                sandbox_dialog: SandboxDialog::new(),
+                toasts: ToastStack::new(),
+                command_palette: CommandPalette::new(),
+            }
+        }
+
+        fn draw(&mut self, terminal: &mut Terminal<TestBackend>) -> Buffer {
+            let screen = Screen {
+                layout_manager: &mut self.layout_manager,
+                theme_manager: &self.theme_manager,
+                sessions: &mut self.sessions,
+                input_editor: &self.input_editor,
+                preview_panel: &mut self.preview_panel,
+                status_bar: &self.status_bar,
+                focused_pane: Pane::Chat,
+                input_mode: InputMode::Normal,
+                show_help: false,
+                current_popup: &None,
+                approval_dialog: &self.approval_dialog,
+                sandbox_dialog: &mut self.sandbox_dialog,
+                toasts: &self.toasts,
+                command_palette: &mut self.command_palette,
+                diff_review: None,
+            };
+            terminal.draw(|frame| screen.draw(frame)).unwrap();
+            terminal.backend().buffer().clone()
+        }
+    }
+
+    fn text(buf: &Buffer) -> String {
+        let area = buf.area;
+        (area.top()..area.bottom())
+            .map(|y| {
+                (area.left()..area.right())
+                    .map(|x| buf.get(x, y).symbol.as_str())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn render_at(width: u16, height: u16) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        text(&Parts::new().draw(&mut terminal))
+    }
+
+    #[test]
+    fn test_large_terminal_shows_every_pane() {
+        let screen = render_at(200, 50);
+        assert!(screen.contains("Chat"));
+        assert!(screen.contains("Input"));
+        assert!(screen.contains("Preview"));
+        assert!(screen.contains("Mode: NORMAL"));
+        assert!(!screen.contains("enlarge"));
+    }
+
+    #[test]
+    fn test_minimum_size_keeps_full_layout() {
+        let screen = render_at(80, 24);
+        assert!(screen.contains("Preview"));
+        assert!(screen.contains("Mode: NORMAL"));
+        assert!(!screen.contains("enlarge"));
+    }
+
+    #[test]
+    fn test_small_terminal_falls_back_to_single_pane() {
+        let screen = render_at(50, 12);
+        assert!(screen.contains("Chat"));
+        assert!(screen.contains("Input"));
+        assert!(!screen.contains("Preview"));
+        assert!(!screen.contains("Mode: NORMAL"));
+        assert!(screen.contains("50x12 - enlarge to 80x24"));
+    }
+
+    #[test]
+    fn test_input_box_grows_with_its_lines() {
+        let mut parts = Parts::new();
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        parts.draw(&mut terminal);
+        let single_line = parts.layout_manager.config().input_height;
+
+        parts
+            .input_editor
+            .set_content("first line\nsecond line\nthird line");
+        let screen = text(&parts.draw(&mut terminal));
+        assert_eq!(parts.layout_manager.config().input_height, single_line + 2);
+        assert!(screen.contains("second line"));
+        assert!(screen.contains("third line"));
+
+        // Long prompts scroll rather than take over the screen
+        parts.input_editor.set_content("line\n".repeat(100));
+        parts.draw(&mut terminal);
+        assert_eq!(parts.layout_manager.config().input_height, 12);
+    }
+
+    #[test]
+    fn test_shrinking_and_tiny_terminals_do_not_panic() {
+        let mut parts = Parts::new();
+        parts.preview_panel.scroll_down(100);
+        for (width, height) in [(200, 50), (80, 24), (60, 15), (50, 12), (10, 3), (1, 1)] {
+            let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+            parts.draw(&mut terminal);
+        }
+    }
+}
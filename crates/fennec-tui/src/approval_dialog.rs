@@ -0,0 +1,527 @@
+//! Approval dialogs driven by the security approval workflow.
+//!
+//! [`ChannelApprovalPrompt`] is the [`ApprovalPrompt`] the TUI installs into
+//! the [`ApprovalManager`](fennec_security::ApprovalManager). Each prompt is
+//! sent to the event loop as a [`PendingApproval`] and awaited on a oneshot
+//! channel, so asking for approval never blocks rendering or input.
+//! [`ApprovalDialog`] queues the pending approvals, shows the oldest one and
+//! answers it from key presses.
+
+use crate::theme::{ComponentType, ThemeManager};
+use async_trait::async_trait;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use fennec_security::{ApprovalDecision, ApprovalPrompt, ApprovalRequest, RiskLevel};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Widget, Wrap},
+};
+use std::collections::VecDeque;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, warn};
+
+/// Approval request waiting for an answer from the user
+#[derive(Debug)]
+pub struct PendingApproval {
+    pub request: ApprovalRequest,
+    responder: oneshot::Sender<ApprovalDecision>,
+}
+
+impl PendingApproval {
+    pub fn new(request: ApprovalRequest) -> (Self, oneshot::Receiver<ApprovalDecision>) {
+        let (responder, receiver) = oneshot::channel();
+        (Self { request, responder }, receiver)
+    }
+
+    /// Whether the requester stopped waiting, e.g. after a timeout
+    pub fn is_abandoned(&self) -> bool {
+        self.responder.is_closed()
+    }
+
+    fn answer(self, decision: ApprovalDecision) {
+        debug!(
+            "Approval of '{}' answered with {:?}",
+            self.request.operation, decision
+        );
+        if self.responder.send(decision).is_err() {
+            warn!(
+                "Approval of '{}' answered after the requester stopped waiting",
+                self.request.operation
+            );
+        }
+    }
+}
+
+/// Approval prompt that hands requests to the TUI event loop
+#[derive(Debug, Clone)]
+pub struct ChannelApprovalPrompt {
+    sender: mpsc::UnboundedSender<PendingApproval>,
+}
+
+impl ChannelApprovalPrompt {
+    /// Prompt and the receiver the event loop takes pending approvals from
+    pub fn new() -> (Self, mpsc::UnboundedReceiver<PendingApproval>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (Self { sender }, receiver)
+    }
+}
+
+#[async_trait]
+impl ApprovalPrompt for ChannelApprovalPrompt {
+    async fn prompt(&self, request: &ApprovalRequest) -> anyhow::Result<ApprovalDecision> {
+        let (pending, decision) = PendingApproval::new(request.clone());
+        if self.sender.send(pending).is_err() {
+            warn!(
+                "No approval dialog to ask about '{}', denying",
+                request.operation
+            );
+            return Ok(ApprovalDecision::Deny);
+        }
+
+        // A dialog dropped without an answer denies the request
+        Ok(decision.await.unwrap_or(ApprovalDecision::Deny))
+    }
+}
+
+/// Stack of approval dialogs, answered oldest first
+#[derive(Debug, Default)]
+pub struct ApprovalDialog {
+    queue: VecDeque<PendingApproval>,
+    /// Lines scrolled in the body of the dialog on top
+    scroll: u16,
+}
+
+impl ApprovalDialog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a pending approval behind the ones already shown
+    pub fn push(&mut self, pending: PendingApproval) {
+        self.queue.push_back(pending);
+    }
+
+    /// Queue every pending approval waiting in `receiver`
+    pub fn receive(&mut self, receiver: &mut mpsc::UnboundedReceiver<PendingApproval>) {
+        while let Ok(pending) = receiver.try_recv() {
+            self.push(pending);
+        }
+        self.prune();
+    }
+
+    /// Whether a dialog is showing
+    pub fn is_active(&self) -> bool {
+        !self.queue.is_empty()
+    }
+
+    /// Number of dialogs stacked
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Request shown on top
+    pub fn current(&self) -> Option<&ApprovalRequest> {
+        self.queue.front().map(|pending| &pending.request)
+    }
+
+    /// Handle a key press while a dialog is showing. Returns the decision
+    /// when the key answered the dialog on top.
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<ApprovalDecision> {
+        let decision = match (key.modifiers, key.code) {
+            (KeyModifiers::NONE, KeyCode::Char('y')) | (KeyModifiers::NONE, KeyCode::Enter) => {
+                ApprovalDecision::Approve
+            }
+            (KeyModifiers::NONE, KeyCode::Char('a')) => ApprovalDecision::ApproveForSession,
+            (KeyModifiers::NONE, KeyCode::Char('n')) | (KeyModifiers::NONE, KeyCode::Esc) => {
+                ApprovalDecision::Deny
+            }
+            (KeyModifiers::NONE, KeyCode::Down) | (KeyModifiers::NONE, KeyCode::Char('j')) => {
+                self.scroll = self.scroll.saturating_add(1);
+                return None;
+            }
+            (KeyModifiers::NONE, KeyCode::Up) | (KeyModifiers::NONE, KeyCode::Char('k')) => {
+                self.scroll = self.scroll.saturating_sub(1);
+                return None;
+            }
+            _ => return None,
+        };
+
+        let pending = self.queue.pop_front()?;
+        pending.answer(decision);
+        self.scroll = 0;
+        self.prune();
+        Some(decision)
+    }
+
+    /// Drop dialogs whose requesters stopped waiting
+    fn prune(&mut self) {
+        let before = self.queue.len();
+        self.queue.retain(|pending| !pending.is_abandoned());
+        if self.queue.len() != before {
+            debug!(
+                "Dropped {} abandoned approval dialog(s)",
+                before - self.queue.len()
+            );
+            self.scroll = 0;
+        }
+    }
+
+    /// Render the dialog on top, with the edges of queued dialogs behind it
+    pub fn render(&self, area: Rect, buf: &mut Buffer, theme: &ThemeManager) {
+        let Some(request) = self.current() else {
+            return;
+        };
+
+        // Offset the top dialog by one cell per dialog behind it, up to two
+        let behind = (self.queue.len() - 1).min(2) as u16;
+        for depth in (1..=behind).rev() {
+            let shadow = Rect {
+                x: area.x + behind - depth,
+                y: area.y + behind - depth,
+                width: area.width.saturating_sub(behind),
+                height: area.height.saturating_sub(behind),
+            };
+            Clear.render(shadow, buf);
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(theme.get_style(ComponentType::Muted))
+                .render(shadow, buf);
+        }
+        let area = Rect {
+            x: area.x + behind,
+            y: area.y + behind,
+            width: area.width.saturating_sub(behind),
+            height: area.height.saturating_sub(behind),
+        };
+
+        let risk_style = risk_style(&request.risk_level, theme);
+        let title = if self.queue.len() > 1 {
+            format!(" Approval required (1 of {}) ", self.queue.len())
+        } else {
+            " Approval required ".to_string()
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .title_style(risk_style)
+            .border_style(risk_style);
+
+        Clear.render(area, buf);
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if inner.height < 2 {
+            return;
+        }
+
+        let body = Rect {
+            height: inner.height - 1,
+            ..inner
+        };
+        Paragraph::new(self.body_lines(request, theme))
+            .style(theme.get_style(ComponentType::Text))
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0))
+            .render(body, buf);
+
+        let footer = Rect {
+            y: inner.bottom() - 1,
+            height: 1,
+            ..inner
+        };
+        let key_style = theme.get_style(ComponentType::Highlight);
+        let muted = theme.get_style(ComponentType::Muted);
+        Paragraph::new(Line::from(vec![
+            Span::styled("[y]", key_style),
+            Span::styled(" approve  ", muted),
+            Span::styled("[a]", key_style),
+            Span::styled(" for session  ", muted),
+            Span::styled("[n]", key_style),
+            Span::styled(" deny", muted),
+        ]))
+        .render(footer, buf);
+    }
+
+    fn body_lines<'a>(&self, request: &'a ApprovalRequest, theme: &ThemeManager) -> Vec<Line<'a>> {
+        let label = theme.get_style(ComponentType::Title);
+        let mut lines = vec![
+            Line::from(vec![
+                Span::styled("Operation: ", label),
+                Span::raw(request.operation.as_str()),
+            ]),
+            Line::from(vec![
+                Span::styled("Risk: ", label),
+                Span::styled(
+                    request.risk_level.to_string(),
+                    risk_style(&request.risk_level, theme),
+                ),
+            ]),
+            Line::from(request.description.as_str()),
+        ];
+
+        if !request.details.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled("Details:", label)));
+            lines.extend(
+                request
+                    .details
+                    .iter()
+                    .map(|detail| Line::from(format!("  • {}", detail))),
+            );
+        }
+
+        if let Some(diff) = &request.diff {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled("Changes:", label)));
+            lines.extend(diff.lines().map(|line| diff_line(line, theme)));
+        }
+
+        lines
+    }
+}
+
+/// Style of a risk level, escalating from success to inverted error
+fn risk_style(risk_level: &RiskLevel, theme: &ThemeManager) -> Style {
+    match risk_level {
+        RiskLevel::Low => theme.get_style(ComponentType::Success),
+        RiskLevel::Medium => theme.get_style(ComponentType::Warning),
+        RiskLevel::High => theme.get_style(ComponentType::Error),
+        RiskLevel::Critical => theme
+            .get_style(ComponentType::Error)
+            .add_modifier(Modifier::REVERSED),
+    }
+}
+
+fn diff_line<'a>(line: &'a str, theme: &ThemeManager) -> Line<'a> {
+    let style = if line.starts_with("+++") || line.starts_with("---") {
+        theme.get_style(ComponentType::Muted)
+    } else if line.starts_with('+') {
+        theme.get_style(ComponentType::Success)
+    } else if line.starts_with('-') {
+        theme.get_style(ComponentType::Error)
+    } else if line.starts_with("@@") {
+        theme.get_style(ComponentType::Info)
+    } else {
+        theme.get_style(ComponentType::Text)
+    };
+    Line::from(Span::styled(line, style))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fennec_security::{ApprovalManager, ApprovalStatus};
+    use ratatui::{backend::TestBackend, Terminal};
+    use std::sync::Arc;
+
+    fn request(operation: &str, risk_level: RiskLevel) -> ApprovalRequest {
+        ApprovalRequest {
+            operation: operation.to_string(),
+            description: format!("Run {}", operation),
+            risk_level,
+            details: vec!["Command: cargo build".to_string()],
+            diff: None,
+            targets: Vec::new(),
+        }
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    /// Text of each row of the rendered dialog
+    fn snapshot(dialog: &ApprovalDialog, width: u16, height: u16) -> Vec<String> {
+        let theme = ThemeManager::new();
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal
+            .draw(|frame| {
+                let area = frame.size();
+                dialog.render(area, frame.buffer_mut(), &theme)
+            })
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| buffer.get(x, y).symbol.as_str())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_keys_answer_dialogs_in_order() {
+        let mut dialog = ApprovalDialog::new();
+        let mut receivers = Vec::new();
+        for operation in ["first", "second", "third"] {
+            let (pending, receiver) = PendingApproval::new(request(operation, RiskLevel::Medium));
+            dialog.push(pending);
+            receivers.push(receiver);
+        }
+
+        assert_eq!(dialog.len(), 3);
+        assert_eq!(dialog.current().unwrap().operation, "first");
+        assert_eq!(dialog.handle_key(key(KeyCode::Char('x'))), None);
+        assert_eq!(dialog.handle_key(key(KeyCode::Down)), None);
+        assert_eq!(dialog.len(), 3);
+
+        assert_eq!(
+            dialog.handle_key(key(KeyCode::Char('y'))),
+            Some(ApprovalDecision::Approve)
+        );
+        assert_eq!(
+            dialog.handle_key(key(KeyCode::Char('a'))),
+            Some(ApprovalDecision::ApproveForSession)
+        );
+        assert_eq!(
+            dialog.handle_key(key(KeyCode::Esc)),
+            Some(ApprovalDecision::Deny)
+        );
+        assert!(!dialog.is_active());
+        assert_eq!(dialog.handle_key(key(KeyCode::Char('y'))), None);
+
+        let decisions: Vec<ApprovalDecision> = receivers
+            .into_iter()
+            .map(|mut receiver| receiver.try_recv().unwrap())
+            .collect();
+        assert_eq!(
+            decisions,
+            [
+                ApprovalDecision::Approve,
+                ApprovalDecision::ApproveForSession,
+                ApprovalDecision::Deny
+            ]
+        );
+    }
+
+    #[test]
+    fn test_abandoned_requests_are_dropped() {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let (abandoned, abandoned_receiver) = PendingApproval::new(request("old", RiskLevel::Low));
+        let (waiting, _waiting_receiver) = PendingApproval::new(request("new", RiskLevel::Low));
+        sender.send(abandoned).unwrap();
+        sender.send(waiting).unwrap();
+        drop(abandoned_receiver);
+
+        let mut dialog = ApprovalDialog::new();
+        dialog.receive(&mut receiver);
+
+        assert_eq!(dialog.len(), 1);
+        assert_eq!(dialog.current().unwrap().operation, "new");
+    }
+
+    #[tokio::test]
+    async fn test_manager_waits_for_dialog_without_blocking() {
+        let (prompt, mut receiver) = ChannelApprovalPrompt::new();
+        let manager = Arc::new(ApprovalManager::new(false, true).with_prompt(Arc::new(prompt)));
+
+        let requester = {
+            let manager = manager.clone();
+            tokio::spawn(async move {
+                manager
+                    .request_approval_async(&request("run", RiskLevel::High))
+                    .await
+                    .unwrap()
+            })
+        };
+
+        // The event loop picks the request up on its next iteration
+        let mut dialog = ApprovalDialog::new();
+        while !dialog.is_active() {
+            tokio::task::yield_now().await;
+            dialog.receive(&mut receiver);
+        }
+        assert!(!requester.is_finished());
+
+        dialog.handle_key(key(KeyCode::Char('a')));
+        assert_eq!(requester.await.unwrap(), ApprovalStatus::Approved);
+
+        // Approved for the session, so no second dialog
+        let status = manager
+            .request_approval_async(&request("run", RiskLevel::High))
+            .await
+            .unwrap();
+        assert_eq!(status, ApprovalStatus::Approved);
+        dialog.receive(&mut receiver);
+        assert!(!dialog.is_active());
+    }
+
+    #[tokio::test]
+    async fn test_prompt_denies_when_dialog_is_gone() {
+        let (prompt, receiver) = ChannelApprovalPrompt::new();
+        drop(receiver);
+
+        let decision = prompt
+            .prompt(&request("run", RiskLevel::High))
+            .await
+            .unwrap();
+        assert_eq!(decision, ApprovalDecision::Deny);
+    }
+
+    #[test]
+    fn test_render_snapshot() {
+        let mut dialog = ApprovalDialog::new();
+        let mut edit = request("EDIT Command", RiskLevel::High);
+        edit.diff = Some("@@ -1 +1 @@\n-old line\n+new line".to_string());
+        let (pending, _receiver) = PendingApproval::new(edit);
+        dialog.push(pending);
+
+        assert_eq!(
+            snapshot(&dialog, 44, 14),
+            [
+                "┌ Approval required ───────────────────────┐",
+                "│Operation: EDIT Command                   │",
+                "│Risk: HIGH                                │",
+                "│Run EDIT Command                          │",
+                "│                                          │",
+                "│Details:                                  │",
+                "│  • Command: cargo build                  │",
+                "│                                          │",
+                "│Changes:                                  │",
+                "│@@ -1 +1 @@                               │",
+                "│-old line                                 │",
+                "│+new line                                 │",
+                "│[y] approve  [a] for session  [n] deny    │",
+                "└──────────────────────────────────────────┘",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_stacked_snapshot() {
+        let mut dialog = ApprovalDialog::new();
+        let mut receivers = Vec::new();
+        for operation in ["first", "second"] {
+            let (pending, receiver) = PendingApproval::new(request(operation, RiskLevel::Low));
+            dialog.push(pending);
+            receivers.push(receiver);
+        }
+
+        assert_eq!(
+            snapshot(&dialog, 44, 6),
+            [
+                "┌─────────────────────────────────────────┐",
+                "│┌ Approval required (1 of 2) ─────────────┐",
+                "││Operation: first                         │",
+                "││Risk: LOW                                │",
+                "└│[y] approve  [a] for session  [n] deny   │",
+                " └─────────────────────────────────────────┘",
+            ]
+        );
+
+        let theme = ThemeManager::new();
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 44, 6));
+        dialog.render(buffer.area, &mut buffer, &theme);
+        assert_eq!(
+            buffer.get(8, 3).fg,
+            theme.get_style(ComponentType::Success).fg.unwrap()
+        );
+    }
+}
@@ -0,0 +1,373 @@
+//! Command palette: every bound action and registry command, narrowed by a
+//! fuzzy filter as you type.
+
+use crate::events::KeyAction;
+use crate::keymap::Keymap;
+use crate::theme::{ComponentType, ThemeManager};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use fennec_commands::CommandDescriptor;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::{
+        Block, Borders, Clear, List, ListItem, ListState, Paragraph, StatefulWidget, Widget,
+    },
+};
+
+/// What choosing a palette entry does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteItem {
+    /// Perform a key action
+    Action(KeyAction),
+    /// Run a registry command
+    Command(String),
+}
+
+/// One line of the palette
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaletteEntry {
+    pub item: PaletteItem,
+    /// Text matched by the filter
+    pub label: String,
+    /// Keys for an action, description for a command
+    pub detail: String,
+}
+
+/// Command palette overlay
+#[derive(Debug, Default)]
+pub struct CommandPalette {
+    actions: Vec<PaletteEntry>,
+    commands: Vec<PaletteEntry>,
+    query: String,
+    /// Indices into the actions followed by the commands of the entries
+    /// matching the query, best first
+    matches: Vec<usize>,
+    selected: usize,
+    open: bool,
+    list_state: ListState,
+}
+
+impl CommandPalette {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// List the actions bound in `keymap`
+    pub fn set_keymap(&mut self, keymap: &Keymap) {
+        self.actions = KeyAction::named()
+            .filter(|(action, _, _)| *action != KeyAction::OpenCommandPalette)
+            .map(|(action, _, description)| {
+                let keys: Vec<_> = keymap
+                    .keys_for(action)
+                    .into_iter()
+                    .map(|(_, key)| key.to_string())
+                    .collect();
+                PaletteEntry {
+                    item: PaletteItem::Action(action),
+                    label: description.to_string(),
+                    detail: keys.join(", "),
+                }
+            })
+            .collect();
+        self.refilter();
+    }
+
+    /// List registry commands after the actions
+    pub fn set_commands(&mut self, commands: &[CommandDescriptor]) {
+        // Deprecated aliases still run, but aren't offered
+        self.commands = commands
+            .iter()
+            .filter(|command| command.alias_of.is_none())
+            .map(|command| PaletteEntry {
+                item: PaletteItem::Command(command.name.clone()),
+                label: format!("Run {}", command.name),
+                detail: command.description.clone(),
+            })
+            .collect();
+        self.commands.sort_by(|a, b| a.label.cmp(&b.label));
+        self.refilter();
+    }
+
+    fn entries(&self) -> impl Iterator<Item = &PaletteEntry> {
+        self.actions.iter().chain(&self.commands)
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Open with an empty query
+    pub fn open(&mut self) {
+        self.open = true;
+        self.query.clear();
+        self.refilter();
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn set_query(&mut self, query: &str) {
+        self.query = query.to_string();
+        self.refilter();
+    }
+
+    /// Entries matching the query, best first
+    pub fn filtered(&self) -> impl Iterator<Item = &PaletteEntry> {
+        self.matches.iter().map(|index| self.entry(*index))
+    }
+
+    pub fn selected(&self) -> Option<&PaletteEntry> {
+        self.matches
+            .get(self.selected)
+            .map(|index| self.entry(*index))
+    }
+
+    fn entry(&self, index: usize) -> &PaletteEntry {
+        self.actions
+            .get(index)
+            .unwrap_or_else(|| &self.commands[index - self.actions.len()])
+    }
+
+    /// Handle a key while open. Returns the chosen item when Enter is
+    /// pressed, closing the palette.
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<PaletteItem> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => self.close(),
+            KeyCode::Enter => {
+                let item = self.selected().map(|entry| entry.item.clone());
+                self.close();
+                return item;
+            }
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::Char('p') if ctrl => self.move_selection(-1),
+            KeyCode::Char('n') if ctrl => self.move_selection(1),
+            KeyCode::Char('u') if ctrl => self.set_query(""),
+            KeyCode::Backspace => {
+                let mut query = self.query.clone();
+                query.pop();
+                self.set_query(&query);
+            }
+            KeyCode::Char(c) if !ctrl => {
+                let query = format!("{}{}", self.query, c);
+                self.set_query(&query);
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let last = self.matches.len() - 1;
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+
+    fn refilter(&mut self) {
+        let mut scored: Vec<_> = self
+            .entries()
+            .enumerate()
+            .filter_map(|(index, entry)| {
+                let score = fuzzy_score(&self.query, &entry.label)
+                    .or_else(|| fuzzy_score(&self.query, &entry.detail).map(|s| s - 100))?;
+                Some((index, score))
+            })
+            .collect();
+        // Stable, so equal scores keep the listing order
+        scored.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+        self.matches = scored.into_iter().map(|(index, _)| index).collect();
+        self.selected = 0;
+    }
+
+    /// Render the palette
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, theme: &ThemeManager) {
+        Clear.render(area, buf);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(theme.get_style(ComponentType::Highlight))
+            .title(Span::styled(
+                " Command Palette ",
+                theme.get_style(ComponentType::Title),
+            ));
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(2), Constraint::Min(0)])
+            .split(inner);
+
+        Paragraph::new(Line::from(vec![
+            Span::styled("> ", theme.get_style(ComponentType::Highlight)),
+            Span::styled(self.query.clone(), theme.get_style(ComponentType::Text)),
+            Span::styled("▏", theme.get_style(ComponentType::Muted)),
+        ]))
+        .render(chunks[0], buf);
+
+        let items: Vec<ListItem> = self
+            .filtered()
+            .map(|entry| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(entry.label.clone(), theme.get_style(ComponentType::Text)),
+                    Span::raw("  "),
+                    Span::styled(entry.detail.clone(), theme.get_style(ComponentType::Muted)),
+                ]))
+            })
+            .collect();
+
+        if items.is_empty() {
+            Paragraph::new(Span::styled(
+                "No matching commands",
+                theme.get_style(ComponentType::Muted),
+            ))
+            .render(chunks[1], buf);
+            return;
+        }
+
+        self.list_state.select(Some(self.selected));
+        let list = List::new(items).highlight_style(theme.get_style(ComponentType::Selection));
+        StatefulWidget::render(list, chunks[1], buf, &mut self.list_state);
+    }
+}
+
+/// Score how well `text` matches `query` as a case-insensitive
+/// subsequence, or `None` if it doesn't. Consecutive characters and matches
+/// at word starts score higher; gaps cost a little.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let text: Vec<char> = text.chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+
+    for wanted in query.chars().filter(|c| !c.is_whitespace()) {
+        let wanted = wanted.to_lowercase().next().unwrap_or(wanted);
+        let found =
+            (position..text.len()).find(|&i| text[i].to_lowercase().next() == Some(wanted))?;
+
+        score += 1;
+        if previous == Some(found.wrapping_sub(1)) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        score -= (found - position) as i64;
+
+        previous = Some(found);
+        position = found + 1;
+    }
+
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fennec_security::SandboxLevel;
+
+    fn descriptor(name: &str, description: &str) -> CommandDescriptor {
+        CommandDescriptor {
+            name: name.to_string(),
+            description: description.to_string(),
+            version: "1.0.0".to_string(),
+            author: None,
+            capabilities_required: Vec::new(),
+            sandbox_level_required: SandboxLevel::ReadOnly,
+            supports_preview: false,
+            supports_dry_run: false,
+            timeout: None,
+            alias_of: None,
+        }
+    }
+
+    fn palette() -> CommandPalette {
+        let mut palette = CommandPalette::new();
+        palette.set_keymap(&Keymap::default());
+        palette.set_commands(&[
+            descriptor("plan", "Create an implementation plan"),
+            descriptor("diff", "Show changes"),
+        ]);
+        palette
+    }
+
+    fn labels(palette: &CommandPalette) -> Vec<&str> {
+        palette
+            .filtered()
+            .map(|entry| entry.label.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+        assert!(fuzzy_score("tgp", "Toggle preview panel").is_some());
+        assert!(fuzzy_score("xyz", "Toggle preview panel").is_none());
+        // Order matters
+        assert!(fuzzy_score("pt", "tp").is_none());
+        // Consecutive, word-start matches beat scattered ones
+        assert!(fuzzy_score("prev", "Toggle preview") > fuzzy_score("prev", "Open parser events"));
+    }
+
+    #[test]
+    fn test_palette_lists_actions_and_commands() {
+        let palette = palette();
+        let entries: Vec<_> = palette.filtered().collect();
+
+        let preview = entries
+            .iter()
+            .find(|entry| entry.item == PaletteItem::Action(KeyAction::TogglePreview))
+            .unwrap();
+        assert_eq!(preview.detail, "Ctrl+P, p");
+        assert!(entries
+            .iter()
+            .any(|entry| entry.item == PaletteItem::Command("plan".to_string())));
+        assert!(!entries
+            .iter()
+            .any(|entry| entry.item == PaletteItem::Action(KeyAction::OpenCommandPalette)));
+    }
+
+    #[test]
+    fn test_palette_filtering_and_selection() {
+        let mut palette = palette();
+        palette.open();
+        for c in "run d".chars() {
+            palette.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+        assert_eq!(labels(&palette)[0], "Run diff");
+
+        // Descriptions match too, below label matches
+        palette.set_query("implementation");
+        assert_eq!(labels(&palette), ["Run plan"]);
+
+        palette.set_query("nothing matches this");
+        assert!(labels(&palette).is_empty());
+        assert_eq!(
+            palette.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)),
+            None
+        );
+        assert!(!palette.is_open());
+
+        palette.open();
+        palette.set_query("theme");
+        palette.handle_key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        palette.handle_key(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE));
+        assert_eq!(
+            palette.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)),
+            Some(PaletteItem::Action(KeyAction::ToggleTheme))
+        );
+    }
+}
@@ -464,7 +464,10 @@ impl Default for StatusBar {
 pub struct PreviewPanel {
     title: String,
     content: Vec<String>,
-    scroll_state: ScrollbarState,
+    /// First visible line
+    scroll: usize,
+    /// Lines in view as of the last render
+    viewport: usize,
 }
 
 impl Default for PreviewPanel {
@@ -479,7 +482,8 @@ impl PreviewPanel {
         Self {
             title: "Preview".to_string(),
             content: Vec::new(),
-            scroll_state: ScrollbarState::default(),
+            scroll: 0,
+            viewport: 0,
         }
     }
 
@@ -490,35 +494,39 @@ impl PreviewPanel {
 
     /// Set the content
     pub fn set_content(&mut self, content: Vec<String>) {
-        let content_len = content.len();
         self.content = content;
-        self.scroll_state = ScrollbarState::default().content_length(content_len);
+        self.scroll = 0;
     }
 
     /// Add a line to the content
     pub fn add_line(&mut self, line: String) {
         self.content.push(line);
-        self.scroll_state = self.scroll_state.content_length(self.content.len());
     }
 
     /// Clear the content
     pub fn clear(&mut self) {
         self.content.clear();
-        self.scroll_state = ScrollbarState::default();
+        self.scroll = 0;
+    }
+
+    /// First visible line
+    pub fn scroll_offset(&self) -> usize {
+        self.scroll
     }
 
     /// Scroll up
     pub fn scroll_up(&mut self, lines: usize) {
-        for _ in 0..lines {
-            self.scroll_state.prev();
-        }
+        self.scroll = self.scroll.saturating_sub(lines);
     }
 
     /// Scroll down
     pub fn scroll_down(&mut self, lines: usize) {
-        for _ in 0..lines {
-            self.scroll_state.next();
-        }
+        self.scroll = (self.scroll + lines).min(self.max_scroll());
+    }
+
+    /// Furthest the content scrolls while still filling the view
+    fn max_scroll(&self) -> usize {
+        self.content.len().saturating_sub(self.viewport.max(1))
     }
 
     /// Render the preview panel
@@ -546,9 +554,14 @@ impl PreviewPanel {
             return;
         }
 
+        // A taller view may show lines that were scrolled past
+        self.viewport = inner.height as usize;
+        self.scroll = self.scroll.min(self.max_scroll());
+
         let items: Vec<ListItem> = self
             .content
             .iter()
+            .skip(self.scroll)
             .map(|line| {
                 ListItem::new(Line::from(Span::styled(
                     line,
@@ -570,7 +583,11 @@ impl PreviewPanel {
             .track_style(theme.get_style(ComponentType::ScrollbarTrack))
             .thumb_style(theme.get_style(ComponentType::ScrollbarThumb));
 
-        StatefulWidget::render(scrollbar, area, buf, &mut self.scroll_state);
+        let mut scroll_state = ScrollbarState::default()
+            .content_length(self.content.len())
+            .viewport_content_length(self.viewport)
+            .position(self.scroll);
+        StatefulWidget::render(scrollbar, area, buf, &mut scroll_state);
     }
 }
 
@@ -773,6 +790,27 @@ mod tests {
         assert_eq!(preview.content.len(), 0);
     }
 
+    #[test]
+    fn test_preview_scroll_clamps_when_view_changes() {
+        let theme = ThemeManager::new();
+        let mut preview = PreviewPanel::new();
+        preview.set_content((0..20).map(|i| format!("Line {}", i)).collect());
+
+        // 8 lines in view, so at most 12 lines can be scrolled past
+        let small = Rect::new(0, 0, 30, 10);
+        preview.render(small, &mut Buffer::empty(small), &theme, false);
+        preview.scroll_down(100);
+        assert_eq!(preview.scroll_offset(), 12);
+
+        // A taller view pulls the offset back so the view stays full
+        let tall = Rect::new(0, 0, 30, 20);
+        let mut buf = Buffer::empty(tall);
+        preview.render(tall, &mut buf, &theme, false);
+        assert_eq!(preview.scroll_offset(), 2);
+        let first_row: String = (1..9).map(|x| buf.get(x, 1).symbol.clone()).collect();
+        assert_eq!(first_row, "Line 2  ");
+    }
+
     #[test]
     fn test_progress_indicator() {
         let mut progress = ProgressIndicator::new("Loading".to_string());
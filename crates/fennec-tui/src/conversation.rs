@@ -0,0 +1,995 @@
+//! Scrollable conversation pane with incremental search.
+//!
+//! [`ConversationState`] is the scroll and search state machine. It works on
+//! any [`Scrollback`], so it can be driven without a terminal.
+//! [`ConversationPane`] owns the messages, wraps them to the pane width and
+//! renders only the lines in view.
+//!
+//! The viewport either follows the newest message or is pinned to a line of
+//! a particular message. Anchoring to a message rather than to an absolute
+//! line keeps the view in place when the pane is resized and lines re-wrap.
+//! While pinned, arriving messages are counted and shown as an indicator
+//! instead of moving the view.
+
+use crate::components::{Message, MessageRole};
+use crate::theme::{ComponentType, ThemeManager};
+use fennec_memory::match_spans;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget,
+        Widget, Wrap,
+    },
+};
+use std::ops::Range;
+
+/// Messages laid out as lines, as seen by [`ConversationState`]
+pub trait Scrollback {
+    /// Number of messages
+    fn len(&self) -> usize;
+
+    /// Number of lines message `index` occupies, at least one
+    fn height(&self, index: usize) -> usize;
+
+    /// Searchable text of message `index`
+    fn text(&self, index: usize) -> &str;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A line of a particular message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub message: usize,
+    pub line: usize,
+}
+
+/// A message matching the search query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchMatch {
+    pub message: usize,
+    /// Matched ranges of char indices in the message text
+    pub spans: Vec<Range<usize>>,
+}
+
+/// Scroll and search state of a conversation view
+#[derive(Debug, Clone, Default)]
+pub struct ConversationState {
+    /// First visible line, or `None` while following the newest message
+    top: Option<Position>,
+    /// Number of lines in view
+    viewport: usize,
+    /// Messages added since the view was pinned
+    unseen: usize,
+    query: String,
+    matches: Vec<SearchMatch>,
+    current: Option<usize>,
+    /// Where the view was when the search being typed started
+    search_origin: Option<Option<Position>>,
+}
+
+impl ConversationState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of lines in view
+    pub fn set_viewport(&mut self, lines: usize) {
+        self.viewport = lines;
+    }
+
+    pub fn viewport(&self) -> usize {
+        self.viewport
+    }
+
+    /// Whether the view follows the newest message
+    pub fn is_following(&self) -> bool {
+        self.top.is_none()
+    }
+
+    /// Number of messages that arrived while the view was pinned
+    pub fn unseen(&self) -> usize {
+        self.unseen
+    }
+
+    /// First visible line
+    pub fn top(&self, buffer: &dyn Scrollback) -> Position {
+        let max = self.max_offset(buffer);
+        let offset = match self.top {
+            Some(top) => offset_of(self.clamp(top, buffer), buffer).min(max),
+            None => max,
+        };
+        position_at(offset, buffer)
+    }
+
+    /// Offset of the first visible line from the first line of the buffer
+    pub fn offset(&self, buffer: &dyn Scrollback) -> usize {
+        offset_of(self.top(buffer), buffer)
+    }
+
+    pub fn scroll_up(&mut self, lines: usize, buffer: &dyn Scrollback) {
+        let offset = self.offset(buffer).saturating_sub(lines);
+        self.scroll_to(offset, buffer);
+    }
+
+    pub fn scroll_down(&mut self, lines: usize, buffer: &dyn Scrollback) {
+        if self.is_following() {
+            return;
+        }
+        let offset = self.offset(buffer) + lines;
+        self.scroll_to(offset, buffer);
+    }
+
+    /// Show the first message
+    pub fn jump_to_start(&mut self, buffer: &dyn Scrollback) {
+        self.scroll_to(0, buffer);
+    }
+
+    /// Show and follow the newest message
+    pub fn jump_to_end(&mut self) {
+        self.top = None;
+        self.unseen = 0;
+    }
+
+    /// Show message `index` at the top of the view, as far as the buffer
+    /// allows
+    pub fn jump_to_message(&mut self, index: usize, buffer: &dyn Scrollback) {
+        if index >= buffer.len() {
+            return;
+        }
+        let offset = offset_of(
+            Position {
+                message: index,
+                line: 0,
+            },
+            buffer,
+        );
+        self.scroll_to(offset, buffer);
+    }
+
+    /// Account for a message appended to `buffer`
+    pub fn message_added(&mut self, buffer: &dyn Scrollback) {
+        if !self.is_following() {
+            self.unseen += 1;
+        }
+
+        let index = buffer.len() - 1;
+        if !self.query.is_empty() {
+            if let Some(spans) = match_spans(buffer.text(index), &self.query) {
+                self.matches.push(SearchMatch {
+                    message: index,
+                    spans,
+                });
+            }
+        }
+    }
+
+    /// Forget all messages and searches
+    pub fn clear(&mut self) {
+        *self = Self {
+            viewport: self.viewport,
+            ..Self::default()
+        };
+    }
+
+    /// Start typing a search. The view returns here if it is cancelled.
+    pub fn begin_search(&mut self) {
+        self.search_origin = Some(self.top);
+        self.set_query(String::new());
+    }
+
+    /// Search for `query` and show the first match at or after the view,
+    /// wrapping around to the start
+    pub fn search(&mut self, query: &str, buffer: &dyn Scrollback) {
+        if let Some(origin) = self.search_origin {
+            self.top = origin;
+        }
+        self.set_query(query.to_string());
+        if query.is_empty() {
+            return;
+        }
+
+        self.matches = (0..buffer.len())
+            .filter_map(|message| {
+                match_spans(buffer.text(message), query).map(|spans| SearchMatch { message, spans })
+            })
+            .collect();
+
+        let from = self.top(buffer).message;
+        self.current = self
+            .matches
+            .iter()
+            .position(|m| m.message >= from)
+            .or((!self.matches.is_empty()).then_some(0));
+        self.reveal_current(buffer);
+    }
+
+    /// Keep the typed search and its matches
+    pub fn finish_search(&mut self) {
+        self.search_origin = None;
+    }
+
+    /// Drop the typed search and return to where it started
+    pub fn cancel_search(&mut self) {
+        if let Some(origin) = self.search_origin.take() {
+            self.top = origin;
+        }
+        self.set_query(String::new());
+    }
+
+    pub fn next_match(&mut self, buffer: &dyn Scrollback) {
+        if self.matches.is_empty() {
+            return;
+        }
+        self.current = Some(match self.current {
+            Some(current) => (current + 1) % self.matches.len(),
+            None => 0,
+        });
+        self.reveal_current(buffer);
+    }
+
+    pub fn previous_match(&mut self, buffer: &dyn Scrollback) {
+        if self.matches.is_empty() {
+            return;
+        }
+        self.current = Some(match self.current {
+            Some(0) | None => self.matches.len() - 1,
+            Some(current) => current - 1,
+        });
+        self.reveal_current(buffer);
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn matches(&self) -> &[SearchMatch] {
+        &self.matches
+    }
+
+    /// Index into [`matches`](Self::matches) of the match being shown
+    pub fn current_match(&self) -> Option<usize> {
+        self.current
+    }
+
+    /// Matched spans of message `index`, if it matches the query
+    pub fn spans_for(&self, index: usize) -> Option<&[Range<usize>]> {
+        self.matches
+            .binary_search_by_key(&index, |m| m.message)
+            .ok()
+            .map(|i| self.matches[i].spans.as_slice())
+    }
+
+    fn set_query(&mut self, query: String) {
+        self.query = query;
+        self.matches.clear();
+        self.current = None;
+    }
+
+    fn reveal_current(&mut self, buffer: &dyn Scrollback) {
+        if let Some(current) = self.current {
+            self.jump_to_message(self.matches[current].message, buffer);
+        }
+    }
+
+    /// Pin the view at `offset`, or follow the newest message once the
+    /// bottom is reached
+    fn scroll_to(&mut self, offset: usize, buffer: &dyn Scrollback) {
+        if offset >= self.max_offset(buffer) {
+            self.jump_to_end();
+        } else {
+            self.top = Some(position_at(offset, buffer));
+        }
+    }
+
+    fn max_offset(&self, buffer: &dyn Scrollback) -> usize {
+        total_lines(buffer).saturating_sub(self.viewport)
+    }
+
+    /// `position` moved onto an existing line, e.g. after re-wrapping
+    /// shortened its message
+    fn clamp(&self, position: Position, buffer: &dyn Scrollback) -> Position {
+        if buffer.is_empty() {
+            return Position {
+                message: 0,
+                line: 0,
+            };
+        }
+        let message = position.message.min(buffer.len() - 1);
+        Position {
+            message,
+            line: position.line.min(buffer.height(message) - 1),
+        }
+    }
+}
+
+fn total_lines(buffer: &dyn Scrollback) -> usize {
+    (0..buffer.len()).map(|i| buffer.height(i)).sum()
+}
+
+fn offset_of(position: Position, buffer: &dyn Scrollback) -> usize {
+    (0..position.message)
+        .map(|i| buffer.height(i))
+        .sum::<usize>()
+        + position.line
+}
+
+fn position_at(mut offset: usize, buffer: &dyn Scrollback) -> Position {
+    for message in 0..buffer.len() {
+        let height = buffer.height(message);
+        if offset < height {
+            return Position {
+                message,
+                line: offset,
+            };
+        }
+        offset -= height;
+    }
+    Position {
+        message: buffer.len().saturating_sub(1),
+        line: 0,
+    }
+}
+
+/// Messages with their content wrapped to a width
+#[derive(Debug, Clone, Default)]
+struct WrappedMessages {
+    messages: Vec<Message>,
+    /// Char ranges of the content lines of each message
+    lines: Vec<Vec<Range<usize>>>,
+    /// Width the content is wrapped to, 0 for unwrapped
+    width: usize,
+}
+
+impl WrappedMessages {
+    fn push(&mut self, message: Message) {
+        self.lines.push(wrap(&message.content, self.width));
+        self.messages.push(message);
+    }
+
+    fn set_width(&mut self, width: usize) {
+        if width != self.width {
+            self.width = width;
+            self.lines = self
+                .messages
+                .iter()
+                .map(|message| wrap(&message.content, width))
+                .collect();
+        }
+    }
+
+    fn clear(&mut self) {
+        self.messages.clear();
+        self.lines.clear();
+    }
+}
+
+impl Scrollback for WrappedMessages {
+    fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Header, content lines and a blank separator
+    fn height(&self, index: usize) -> usize {
+        self.lines[index].len() + 2
+    }
+
+    fn text(&self, index: usize) -> &str {
+        &self.messages[index].content
+    }
+}
+
+/// Split `content` into lines of at most `width` chars, breaking after
+/// whitespace where possible. Returns char ranges without the newlines.
+fn wrap(content: &str, width: usize) -> Vec<Range<usize>> {
+    let chars: Vec<char> = content.chars().collect();
+    let breaks = chars
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| **c == '\n')
+        .map(|(i, _)| i)
+        .chain(std::iter::once(chars.len()));
+
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for end in breaks {
+        while width > 0 && end - start > width {
+            let limit = start + width;
+            let split = chars[start..limit]
+                .iter()
+                .rposition(|c| c.is_whitespace())
+                .map(|i| start + i + 1)
+                .unwrap_or(limit);
+            lines.push(start..split);
+            start = split;
+        }
+        lines.push(start..end);
+        start = end + 1;
+    }
+    lines
+}
+
+/// Conversation history with scrollback, search and a new-message indicator
+#[derive(Debug, Clone, Default)]
+pub struct ConversationPane {
+    buffer: WrappedMessages,
+    state: ConversationState,
+}
+
+impl ConversationPane {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_message(&mut self, message: Message) {
+        self.buffer.push(message);
+        self.state.message_added(&self.buffer);
+    }
+
+    pub fn messages(&self) -> &[Message] {
+        &self.buffer.messages
+    }
+
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.state.clear();
+    }
+
+    pub fn state(&self) -> &ConversationState {
+        &self.state
+    }
+
+    pub fn scroll_up(&mut self, lines: usize) {
+        self.state.scroll_up(lines, &self.buffer);
+    }
+
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.state.scroll_down(lines, &self.buffer);
+    }
+
+    pub fn scroll_to_top(&mut self) {
+        self.state.jump_to_start(&self.buffer);
+    }
+
+    pub fn scroll_to_bottom(&mut self) {
+        self.state.jump_to_end();
+    }
+
+    pub fn jump_to_message(&mut self, index: usize) {
+        self.state.jump_to_message(index, &self.buffer);
+    }
+
+    pub fn begin_search(&mut self) {
+        self.state.begin_search();
+    }
+
+    pub fn search(&mut self, query: &str) {
+        self.state.search(query, &self.buffer);
+    }
+
+    pub fn finish_search(&mut self) {
+        self.state.finish_search();
+    }
+
+    pub fn cancel_search(&mut self) {
+        self.state.cancel_search();
+    }
+
+    pub fn next_match(&mut self) {
+        self.state.next_match(&self.buffer);
+    }
+
+    pub fn previous_match(&mut self) {
+        self.state.previous_match(&self.buffer);
+    }
+
+    /// Render the pane, laying messages out for the size of `area`
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, theme: &ThemeManager, focused: bool) {
+        let border_style = if focused {
+            theme.get_style(ComponentType::Highlight)
+        } else {
+            theme.get_style(ComponentType::Border)
+        };
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(self.title())
+            .title_style(theme.get_style(ComponentType::Title))
+            .border_style(border_style);
+
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        if self.buffer.is_empty() {
+            let empty_text = Paragraph::new("No messages yet. Type 'i' to start chatting.")
+                .style(theme.get_style(ComponentType::Muted))
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true });
+            empty_text.render(inner, buf);
+            return;
+        }
+
+        // Leave a column for the scrollbar
+        self.buffer
+            .set_width(inner.width.saturating_sub(1).max(1) as usize);
+        self.state.set_viewport(inner.height as usize);
+
+        let lines = self.visible_lines(theme);
+        let text_area = Rect {
+            width: inner.width.saturating_sub(1),
+            ..inner
+        };
+        Paragraph::new(lines)
+            .style(theme.get_style(ComponentType::Text))
+            .render(text_area, buf);
+
+        if !self.state.is_following() && self.state.unseen() > 0 {
+            self.render_unseen_indicator(inner, buf, theme);
+        }
+
+        let mut scroll_state = ScrollbarState::default()
+            .content_length(total_lines(&self.buffer))
+            .viewport_content_length(self.state.viewport())
+            .position(self.state.offset(&self.buffer));
+        let scrollbar = Scrollbar::default()
+            .orientation(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(Some("↑"))
+            .end_symbol(Some("↓"))
+            .track_style(theme.get_style(ComponentType::ScrollbarTrack))
+            .thumb_style(theme.get_style(ComponentType::ScrollbarThumb));
+        StatefulWidget::render(scrollbar, area, buf, &mut scroll_state);
+    }
+
+    fn title(&self) -> String {
+        let query = self.state.query();
+        if query.is_empty() {
+            return "Chat".to_string();
+        }
+        match self.state.current_match() {
+            Some(current) => format!(
+                "Chat - /{} ({}/{})",
+                query,
+                current + 1,
+                self.state.matches().len()
+            ),
+            None => format!("Chat - /{} (no matches)", query),
+        }
+    }
+
+    /// The lines in view, built from the top visible line onwards
+    fn visible_lines(&self, theme: &ThemeManager) -> Vec<Line<'_>> {
+        let top = self.state.top(&self.buffer);
+        let current = self
+            .state
+            .current_match()
+            .map(|i| self.state.matches()[i].message);
+
+        let mut lines = Vec::with_capacity(self.state.viewport());
+        let mut skip = top.line;
+        for index in top.message..self.buffer.len() {
+            let height = self.buffer.height(index);
+            for line in skip..height {
+                if lines.len() == self.state.viewport() {
+                    return lines;
+                }
+                lines.push(self.message_line(index, line, current == Some(index), theme));
+            }
+            skip = 0;
+        }
+        lines
+    }
+
+    /// Line `line` of message `index`: its header, a content line or the
+    /// blank separator
+    fn message_line(
+        &self,
+        index: usize,
+        line: usize,
+        is_current: bool,
+        theme: &ThemeManager,
+    ) -> Line<'_> {
+        let message = &self.buffer.messages[index];
+        let content_lines = &self.buffer.lines[index];
+
+        if line == 0 {
+            let (role_prefix, role_style) = match message.role {
+                MessageRole::User => ("[You]", theme.get_style(ComponentType::ChatUser)),
+                MessageRole::Assistant => {
+                    ("[Assistant]", theme.get_style(ComponentType::ChatAssistant))
+                }
+                MessageRole::System => ("[System]", theme.get_style(ComponentType::ChatSystem)),
+            };
+            return Line::from(vec![
+                Span::styled(role_prefix, role_style),
+                Span::styled(
+                    format!(" {}", message.timestamp),
+                    theme.get_style(ComponentType::Muted),
+                ),
+            ]);
+        }
+        let Some(range) = content_lines.get(line - 1) else {
+            return Line::from("");
+        };
+
+        let text_style = theme.get_style(ComponentType::Text);
+        let mut match_style = theme.get_style(ComponentType::Selection);
+        if is_current {
+            match_style = match_style.add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+        }
+        let spans = self.state.spans_for(index).unwrap_or_default();
+        highlight(
+            &message.content,
+            range.clone(),
+            spans,
+            text_style,
+            match_style,
+        )
+    }
+
+    fn render_unseen_indicator(&self, inner: Rect, buf: &mut Buffer, theme: &ThemeManager) {
+        let unseen = self.state.unseen();
+        let label = format!(
+            " {} new message{} - G to jump ",
+            unseen,
+            if unseen == 1 { "" } else { "s" }
+        );
+        let area = Rect {
+            y: inner.bottom().saturating_sub(1),
+            height: 1,
+            ..inner
+        };
+        Paragraph::new(label)
+            .style(
+                theme
+                    .get_style(ComponentType::Info)
+                    .add_modifier(Modifier::REVERSED),
+            )
+            .alignment(Alignment::Center)
+            .render(area, buf);
+    }
+}
+
+/// Spans for the chars `range` of `content`, styling the parts inside
+/// `matches` with `match_style`
+fn highlight<'a>(
+    content: &'a str,
+    range: Range<usize>,
+    matches: &[Range<usize>],
+    text_style: Style,
+    match_style: Style,
+) -> Line<'a> {
+    let byte_at = |char_index: usize| {
+        content
+            .char_indices()
+            .nth(char_index)
+            .map(|(byte, _)| byte)
+            .unwrap_or(content.len())
+    };
+
+    let mut spans = Vec::new();
+    let mut position = range.start;
+    for span in matches {
+        let start = span.start.max(range.start);
+        let end = span.end.min(range.end);
+        if start >= end {
+            continue;
+        }
+        if position < start {
+            spans.push(Span::styled(
+                &content[byte_at(position)..byte_at(start)],
+                text_style,
+            ));
+        }
+        spans.push(Span::styled(
+            &content[byte_at(start)..byte_at(end)],
+            match_style,
+        ));
+        position = end;
+    }
+    if position < range.end {
+        spans.push(Span::styled(
+            &content[byte_at(position)..byte_at(range.end)],
+            text_style,
+        ));
+    }
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    /// Scrollback with fixed message heights
+    struct FakeBuffer {
+        messages: Vec<(String, usize)>,
+    }
+
+    impl FakeBuffer {
+        /// `count` messages of `height` lines, named "message <i>"
+        fn new(count: usize, height: usize) -> Self {
+            Self {
+                messages: (0..count)
+                    .map(|i| (format!("message {i}"), height))
+                    .collect(),
+            }
+        }
+
+        fn push(&mut self, text: &str, height: usize) {
+            self.messages.push((text.to_string(), height));
+        }
+    }
+
+    impl Scrollback for FakeBuffer {
+        fn len(&self) -> usize {
+            self.messages.len()
+        }
+
+        fn height(&self, index: usize) -> usize {
+            self.messages[index].1
+        }
+
+        fn text(&self, index: usize) -> &str {
+            &self.messages[index].0
+        }
+    }
+
+    fn state(viewport: usize) -> ConversationState {
+        let mut state = ConversationState::new();
+        state.set_viewport(viewport);
+        state
+    }
+
+    fn at(message: usize, line: usize) -> Position {
+        Position { message, line }
+    }
+
+    #[test]
+    fn test_follows_bottom_until_scrolled() {
+        let buffer = FakeBuffer::new(10, 3);
+        let mut state = state(6);
+
+        assert!(state.is_following());
+        assert_eq!(state.top(&buffer), at(8, 0));
+
+        state.scroll_up(4, &buffer);
+        assert!(!state.is_following());
+        assert_eq!(state.top(&buffer), at(6, 2));
+
+        state.scroll_down(2, &buffer);
+        assert_eq!(state.top(&buffer), at(7, 1));
+
+        // Reaching the bottom resumes following
+        state.scroll_down(10, &buffer);
+        assert!(state.is_following());
+    }
+
+    #[test]
+    fn test_jump_to_start_and_end() {
+        let buffer = FakeBuffer::new(10, 3);
+        let mut state = state(6);
+
+        state.jump_to_start(&buffer);
+        assert_eq!(state.top(&buffer), at(0, 0));
+        state.scroll_up(5, &buffer);
+        assert_eq!(state.top(&buffer), at(0, 0));
+
+        state.jump_to_end();
+        assert!(state.is_following());
+        assert_eq!(state.top(&buffer), at(8, 0));
+    }
+
+    #[test]
+    fn test_short_buffer_never_scrolls() {
+        let buffer = FakeBuffer::new(2, 2);
+        let mut state = state(10);
+
+        state.scroll_up(3, &buffer);
+        state.jump_to_start(&buffer);
+        assert!(state.is_following());
+        assert_eq!(state.top(&buffer), at(0, 0));
+    }
+
+    #[test]
+    fn test_new_messages_while_scrolled_up_keep_view_pinned() {
+        let mut buffer = FakeBuffer::new(10, 3);
+        let mut state = state(6);
+        state.jump_to_message(2, &buffer);
+
+        buffer.push("late reply", 3);
+        state.message_added(&buffer);
+        buffer.push("another", 3);
+        state.message_added(&buffer);
+
+        assert_eq!(state.top(&buffer), at(2, 0));
+        assert_eq!(state.unseen(), 2);
+
+        state.jump_to_end();
+        assert_eq!(state.unseen(), 0);
+        assert_eq!(state.top(&buffer), at(10, 0));
+
+        // Following, so the next message is seen and scrolled to
+        buffer.push("seen", 3);
+        state.message_added(&buffer);
+        assert_eq!(state.unseen(), 0);
+        assert_eq!(state.top(&buffer), at(11, 0));
+    }
+
+    #[test]
+    fn test_resize_keeps_anchor_message() {
+        let mut buffer = FakeBuffer::new(10, 4);
+        let mut state = state(6);
+        state.jump_to_message(3, &buffer);
+        state.scroll_down(3, &buffer);
+        assert_eq!(state.top(&buffer), at(3, 3));
+
+        // Narrower lines wrap into more lines, wider ones into fewer
+        for message in &mut buffer.messages {
+            message.1 = 8;
+        }
+        assert_eq!(state.top(&buffer), at(3, 3));
+        for message in &mut buffer.messages {
+            message.1 = 2;
+        }
+        assert_eq!(state.top(&buffer), at(3, 1));
+
+        // A taller viewport shows the bottom without losing the anchor
+        state.set_viewport(30);
+        assert_eq!(state.top(&buffer), at(0, 0));
+        state.set_viewport(6);
+        assert_eq!(state.top(&buffer), at(3, 1));
+    }
+
+    #[test]
+    fn test_search_moves_between_matches() {
+        let mut buffer = FakeBuffer::new(0, 3);
+        for text in [
+            "deploy the sandbox",
+            "unrelated",
+            "sandbox policy",
+            "nothing",
+            "more nothing",
+            "sandbox again",
+        ] {
+            buffer.push(text, 3);
+        }
+        let mut state = state(3);
+
+        state.jump_to_message(1, &buffer);
+        state.begin_search();
+        state.search("sandbox", &buffer);
+        state.finish_search();
+
+        let matched: Vec<usize> = state.matches().iter().map(|m| m.message).collect();
+        assert_eq!(matched, [0, 2, 5]);
+        let spans = state.spans_for(2).unwrap();
+        assert_eq!((spans.len(), spans[0].clone()), (1, 0..7));
+        // Searching starts from the view
+        assert_eq!(state.current_match(), Some(1));
+        assert_eq!(state.top(&buffer), at(2, 0));
+
+        state.next_match(&buffer);
+        assert_eq!(state.top(&buffer), at(5, 0));
+        state.next_match(&buffer);
+        assert_eq!(state.top(&buffer), at(0, 0));
+        state.previous_match(&buffer);
+        assert_eq!(state.current_match(), Some(2));
+    }
+
+    #[test]
+    fn test_incremental_search_and_cancel() {
+        let buffer = FakeBuffer::new(20, 2);
+        let mut state = state(4);
+        state.jump_to_message(4, &buffer);
+
+        state.begin_search();
+        state.search("message 1", &buffer);
+        // Fuzzy matches "message 1", "message 10", ... from the view onwards
+        assert_eq!(state.top(&buffer).message, 10);
+        state.search("message 15", &buffer);
+        assert_eq!(state.top(&buffer).message, 15);
+
+        state.cancel_search();
+        assert_eq!(state.top(&buffer), at(4, 0));
+        assert!(state.matches().is_empty());
+        assert_eq!(state.query(), "");
+    }
+
+    #[test]
+    fn test_search_without_matches_keeps_view() {
+        let buffer = FakeBuffer::new(10, 2);
+        let mut state = state(4);
+        state.jump_to_message(3, &buffer);
+
+        state.begin_search();
+        state.search("zzz", &buffer);
+
+        assert_eq!(state.current_match(), None);
+        assert_eq!(state.top(&buffer), at(3, 0));
+        state.next_match(&buffer);
+        assert_eq!(state.top(&buffer), at(3, 0));
+    }
+
+    #[test]
+    fn test_new_messages_join_active_search() {
+        let mut buffer = FakeBuffer::new(3, 2);
+        let mut state = state(4);
+        state.begin_search();
+        state.search("deploy", &buffer);
+        state.finish_search();
+        assert!(state.matches().is_empty());
+
+        buffer.push("deploy finished", 2);
+        state.message_added(&buffer);
+
+        assert_eq!(state.matches().len(), 1);
+        assert_eq!(state.matches()[0].message, 3);
+    }
+
+    #[test]
+    fn test_wrap_breaks_at_whitespace_and_newlines() {
+        assert_eq!(wrap("hello world", 7), vec![0..6, 6..11]);
+        assert_eq!(wrap("abcdefghij", 4), vec![0..4, 4..8, 8..10]);
+        assert_eq!(wrap("one\ntwo", 10), vec![0..3, 4..7]);
+        assert_eq!(wrap("", 10), vec![0..0]);
+        assert_eq!(wrap("no wrapping", 0), vec![0..11]);
+    }
+
+    #[test]
+    fn test_pane_renders_indicator_and_highlights() {
+        let theme = ThemeManager::new();
+        let mut pane = ConversationPane::new();
+        for i in 0..20 {
+            pane.add_message(Message {
+                role: MessageRole::User,
+                content: format!("note {i}"),
+                timestamp: "12:00:00".to_string(),
+            });
+        }
+
+        let mut terminal = Terminal::new(TestBackend::new(40, 10)).unwrap();
+        let mut draw = |pane: &mut ConversationPane| {
+            terminal
+                .draw(|frame| {
+                    let area = frame.size();
+                    pane.render(area, frame.buffer_mut(), &theme, true)
+                })
+                .unwrap();
+            terminal.backend().buffer().clone()
+        };
+        let row = |buffer: &Buffer, y: u16| -> String {
+            (0..buffer.area.width)
+                .map(|x| buffer.get(x, y).symbol.clone())
+                .collect()
+        };
+
+        let rendered = draw(&mut pane);
+        assert!(row(&rendered, 7).contains("note 19"));
+
+        pane.scroll_to_top();
+        pane.add_message(Message {
+            role: MessageRole::Assistant,
+            content: "late".to_string(),
+            timestamp: "12:01:00".to_string(),
+        });
+        let rendered = draw(&mut pane);
+        assert!(row(&rendered, 2).contains("note 0"));
+        assert!(row(&rendered, 8).contains("1 new message - G to jump"));
+
+        pane.begin_search();
+        pane.search("note 7");
+        let rendered = draw(&mut pane);
+        assert!(row(&rendered, 0).contains("/note 7 (1/2)"));
+        let highlighted = rendered.get(1, 2);
+        assert_eq!(highlighted.symbol, "n");
+        assert_eq!(
+            highlighted.bg,
+            theme.get_style(ComponentType::Selection).bg.unwrap()
+        );
+    }
+}
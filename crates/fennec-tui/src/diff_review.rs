@@ -0,0 +1,513 @@
+//! Review of proposed edit hunks before anything is written.
+//!
+//! [`DiffReview`] takes the hunks an edit proposes, grouped by file, and lets
+//! the user accept or reject each one. Applying hands every file's hunks and
+//! their decisions to a [`HunkApplier`]; files with nothing accepted are left
+//! alone, and rejecting everything cancels the review without touching disk.
+
+use crate::theme::{ComponentType, ThemeManager};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use crossterm::event::{KeyCode, KeyEvent};
+use fennec_commands::{
+    apply_selected_hunks, Action, ActionLog, FileOperations, Hunk, HunkStatus, LineKind,
+    StructuredDiff,
+};
+use fennec_security::SandboxLevel;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Modifier,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Widget},
+};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Registry command edits are recorded under, so undo treats them alike
+const EDIT_COMMAND: &str = "edit";
+
+/// Outcome of a key press in the review
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffReviewAction {
+    /// Apply the accepted hunks
+    Apply,
+    /// Close the review without changing anything
+    Cancel,
+}
+
+/// Writes the accepted hunks of one file
+#[async_trait]
+pub trait HunkApplier: Send + Sync {
+    /// Apply `hunks` to `path`, where `selection[i]` decides `hunks[i]`
+    async fn apply(&self, path: &Path, hunks: &[Hunk], selection: &[HunkStatus]) -> Result<()>;
+}
+
+/// Applies hunks inside the workspace and records each file in the action
+/// log so the edit can be undone
+pub struct WorkspaceHunkApplier {
+    file_ops: FileOperations,
+    sandbox_level: SandboxLevel,
+    workspace_path: Option<PathBuf>,
+    action_log: Option<Arc<ActionLog>>,
+}
+
+impl WorkspaceHunkApplier {
+    pub fn new(
+        sandbox_level: SandboxLevel,
+        workspace_path: Option<PathBuf>,
+        action_log: Option<Arc<ActionLog>>,
+    ) -> Self {
+        Self {
+            file_ops: FileOperations::with_default_config(),
+            sandbox_level,
+            workspace_path,
+            action_log,
+        }
+    }
+}
+
+#[async_trait]
+impl HunkApplier for WorkspaceHunkApplier {
+    async fn apply(&self, path: &Path, hunks: &[Hunk], selection: &[HunkStatus]) -> Result<()> {
+        let workspace = self
+            .workspace_path
+            .as_ref()
+            .map(|workspace| workspace.display().to_string());
+        let path = self
+            .file_ops
+            .validate_file_path(path, &self.sandbox_level, workspace.as_deref())
+            .await?;
+
+        let original = self.file_ops.safe_read_file(&path).await?;
+        let updated = apply_selected_hunks(&original, hunks, selection)
+            .map_err(|e| anyhow!("Cannot apply hunks to {}: {}", path.display(), e))?;
+        self.file_ops.atomic_write_file(&path, &updated).await?;
+
+        if let Some(action_log) = &self.action_log {
+            let accepted = selection
+                .iter()
+                .filter(|status| **status == HunkStatus::Accepted)
+                .count();
+            let description = format!(
+                "Applied {} of {} hunks to {}",
+                accepted,
+                hunks.len(),
+                path.display()
+            );
+            action_log
+                .record(Action::file_modified(
+                    EDIT_COMMAND.to_string(),
+                    path,
+                    original.into_bytes(),
+                    updated.into_bytes(),
+                    description,
+                ))
+                .await;
+        }
+        Ok(())
+    }
+}
+
+/// Hunks proposed for one file
+#[derive(Debug, Clone)]
+struct FileHunks {
+    path: PathBuf,
+    hunks: Vec<Hunk>,
+}
+
+impl FileHunks {
+    /// Decisions to apply, with undecided hunks left out
+    fn selection(&self) -> Vec<HunkStatus> {
+        self.hunks
+            .iter()
+            .map(|hunk| match hunk.status {
+                HunkStatus::Accepted => HunkStatus::Accepted,
+                HunkStatus::Rejected | HunkStatus::Pending => HunkStatus::Rejected,
+            })
+            .collect()
+    }
+
+    fn any_accepted(&self) -> bool {
+        self.hunks
+            .iter()
+            .any(|hunk| hunk.status == HunkStatus::Accepted)
+    }
+}
+
+/// Accept or reject proposed hunks file by file
+#[derive(Debug, Clone, Default)]
+pub struct DiffReview {
+    files: Vec<FileHunks>,
+    file: usize,
+    hunk: usize,
+}
+
+impl DiffReview {
+    /// Review `hunks`, grouped by file in the order the files first appear
+    pub fn new(hunks: Vec<Hunk>) -> Self {
+        let mut files: Vec<FileHunks> = Vec::new();
+        for hunk in hunks {
+            match files.iter_mut().find(|file| file.path == hunk.file_path) {
+                Some(file) => file.hunks.push(hunk),
+                None => files.push(FileHunks {
+                    path: hunk.file_path.clone(),
+                    hunks: vec![hunk],
+                }),
+            }
+        }
+        Self {
+            files,
+            file: 0,
+            hunk: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Files under review
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(|file| file.path.as_path())
+    }
+
+    /// Hunks of `path` with their current decisions
+    pub fn hunks(&self, path: &Path) -> &[Hunk] {
+        self.files
+            .iter()
+            .find(|file| file.path == path)
+            .map_or(&[], |file| file.hunks.as_slice())
+    }
+
+    /// The file and hunk index shown as selected
+    pub fn selected(&self) -> Option<(&Path, usize)> {
+        self.files
+            .get(self.file)
+            .map(|file| (file.path.as_path(), self.hunk))
+    }
+
+    /// Whether applying would change anything
+    pub fn any_accepted(&self) -> bool {
+        self.files.iter().any(FileHunks::any_accepted)
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<DiffReviewAction> {
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => self.move_hunk(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_hunk(-1),
+            KeyCode::Tab | KeyCode::Char('l') => self.move_file(1),
+            KeyCode::BackTab | KeyCode::Char('h') => self.move_file(-1),
+            KeyCode::Char('a') => self.decide(Hunk::accept),
+            KeyCode::Char('r') => self.decide(Hunk::reject),
+            KeyCode::Char(' ') => self.decide(Hunk::toggle),
+            KeyCode::Char('A') => self.decide_file(Hunk::accept),
+            KeyCode::Char('R') => self.decide_file(Hunk::reject),
+            // With nothing accepted there is nothing to apply
+            KeyCode::Enter if self.any_accepted() => return Some(DiffReviewAction::Apply),
+            KeyCode::Enter | KeyCode::Esc | KeyCode::Char('q') => {
+                return Some(DiffReviewAction::Cancel)
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Apply the accepted hunks of every file that has some. Returns the
+    /// files changed; stops at the first file that fails.
+    pub async fn apply(&self, applier: &dyn HunkApplier) -> Result<Vec<PathBuf>> {
+        let mut changed = Vec::new();
+        for file in self.files.iter().filter(|file| file.any_accepted()) {
+            applier
+                .apply(&file.path, &file.hunks, &file.selection())
+                .await?;
+            changed.push(file.path.clone());
+        }
+        Ok(changed)
+    }
+
+    fn move_hunk(&mut self, offset: isize) {
+        if let Some(file) = self.files.get(self.file) {
+            let last = file.hunks.len().saturating_sub(1);
+            self.hunk = self.hunk.saturating_add_signed(offset).min(last);
+        }
+    }
+
+    fn move_file(&mut self, offset: isize) {
+        if self.files.is_empty() {
+            return;
+        }
+        let count = self.files.len() as isize;
+        self.file = (self.file as isize + offset).rem_euclid(count) as usize;
+        self.hunk = 0;
+    }
+
+    fn decide(&mut self, decision: fn(&mut Hunk)) {
+        if let Some(hunk) = self
+            .files
+            .get_mut(self.file)
+            .and_then(|file| file.hunks.get_mut(self.hunk))
+        {
+            decision(hunk);
+        }
+    }
+
+    fn decide_file(&mut self, decision: fn(&mut Hunk)) {
+        if let Some(file) = self.files.get_mut(self.file) {
+            file.hunks.iter_mut().for_each(decision);
+        }
+    }
+
+    pub fn render(&self, area: Rect, buf: &mut Buffer, theme: &ThemeManager) {
+        Clear.render(area, buf);
+
+        let title = match self.files.get(self.file) {
+            Some(file) => format!(
+                " Review changes - {} ({}/{}) ",
+                file.path.display(),
+                self.file + 1,
+                self.files.len()
+            ),
+            None => " Review changes ".to_string(),
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(theme.get_style(ComponentType::Border))
+            .title(Span::styled(title, theme.get_style(ComponentType::Title)));
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if inner.height == 0 {
+            return;
+        }
+
+        let help_area = Rect {
+            y: inner.bottom() - 1,
+            height: 1,
+            ..inner
+        };
+        let body_area = Rect {
+            height: inner.height - 1,
+            ..inner
+        };
+
+        let (lines, selected_line) = self.lines(theme);
+        // Keep the selected hunk's header in view
+        let height = body_area.height as usize;
+        let offset = selected_line
+            .saturating_sub(height.saturating_sub(1))
+            .min(lines.len().saturating_sub(height));
+        let visible: Vec<Line> = lines.into_iter().skip(offset).take(height).collect();
+        Paragraph::new(visible).render(body_area, buf);
+
+        Paragraph::new(Line::from(Span::styled(
+            "a accept  r reject  space toggle  A/R whole file  Tab file  Enter apply  Esc cancel",
+            theme.get_style(ComponentType::Muted),
+        )))
+        .render(help_area, buf);
+    }
+
+    /// Lines of the selected file, and the line of the selected hunk
+    fn lines(&self, theme: &ThemeManager) -> (Vec<Line<'static>>, usize) {
+        let mut lines = Vec::new();
+        let mut selected_line = 0;
+        let Some(file) = self.files.get(self.file) else {
+            return (lines, selected_line);
+        };
+
+        for (index, hunk) in file.hunks.iter().enumerate() {
+            let (marker, marker_style) = match hunk.status {
+                HunkStatus::Pending => ("[ ]", theme.get_style(ComponentType::Muted)),
+                HunkStatus::Accepted => ("[✓]", theme.get_style(ComponentType::Success)),
+                HunkStatus::Rejected => ("[✗]", theme.get_style(ComponentType::Error)),
+            };
+            let mut header_style = theme.get_style(ComponentType::Info);
+            if index == self.hunk {
+                selected_line = lines.len();
+                header_style = theme.get_style(ComponentType::ListSelected);
+            }
+            lines.push(Line::from(vec![
+                Span::styled(format!("{} ", marker), marker_style),
+                Span::styled(
+                    format!(
+                        "@@ lines {}-{} (-{} +{}) @@",
+                        hunk.start_line + 1,
+                        hunk.end_line.max(hunk.start_line + 1),
+                        hunk.old_content.len(),
+                        hunk.new_content.len()
+                    ),
+                    header_style.add_modifier(Modifier::BOLD),
+                ),
+            ]));
+
+            let context = theme.get_style(ComponentType::Muted);
+            lines.extend(
+                hunk.context_before
+                    .iter()
+                    .map(|line| Line::styled(format!("  {}", line), context)),
+            );
+            let diff = StructuredDiff::compute(
+                &with_newlines(&hunk.old_content),
+                &with_newlines(&hunk.new_content),
+                0,
+            );
+            for line in diff.hunks.iter().flat_map(|hunk| &hunk.lines) {
+                let (prefix, style) = match line.kind {
+                    LineKind::Delete => ("- ", theme.get_style(ComponentType::Error)),
+                    LineKind::Insert => ("+ ", theme.get_style(ComponentType::Success)),
+                    LineKind::Equal => ("  ", context),
+                };
+                lines.push(Line::styled(format!("{}{}", prefix, line.content), style));
+            }
+            lines.extend(
+                hunk.context_after
+                    .iter()
+                    .map(|line| Line::styled(format!("  {}", line), context)),
+            );
+            lines.push(Line::from(""));
+        }
+        (lines, selected_line)
+    }
+}
+
+fn with_newlines(lines: &[String]) -> String {
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fennec_commands::split_diff_into_hunks;
+    use std::sync::Mutex;
+
+    /// Applier that records what it was asked to apply
+    #[derive(Default)]
+    struct RecordingApplier {
+        calls: Mutex<Vec<(PathBuf, Vec<HunkStatus>)>>,
+    }
+
+    #[async_trait]
+    impl HunkApplier for RecordingApplier {
+        async fn apply(
+            &self,
+            path: &Path,
+            _hunks: &[Hunk],
+            selection: &[HunkStatus],
+        ) -> Result<()> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push((path.to_path_buf(), selection.to_vec()));
+            Ok(())
+        }
+    }
+
+    fn hunk(path: &str, start: usize) -> Hunk {
+        Hunk::new(
+            format!("{}:{}", path, start),
+            PathBuf::from(path),
+            start,
+            start + 1,
+            vec![format!("old {}", start)],
+            vec![format!("new {}", start)],
+        )
+    }
+
+    fn press(review: &mut DiffReview, keys: &str) -> Option<DiffReviewAction> {
+        keys.chars()
+            .map(|c| {
+                let code = match c {
+                    '\t' => KeyCode::Tab,
+                    '\n' => KeyCode::Enter,
+                    c => KeyCode::Char(c),
+                };
+                review.handle_key(KeyEvent::from(code))
+            })
+            .last()
+            .flatten()
+    }
+
+    #[tokio::test]
+    async fn test_scripted_review_applies_selection() {
+        let mut review = DiffReview::new(vec![
+            hunk("a.rs", 1),
+            hunk("b.rs", 4),
+            hunk("a.rs", 5),
+            hunk("a.rs", 9),
+            hunk("c.rs", 2),
+        ]);
+        assert_eq!(
+            review.files().collect::<Vec<_>>(),
+            [Path::new("a.rs"), Path::new("b.rs"), Path::new("c.rs")]
+        );
+
+        // a.rs: accept, reject, leave the last undecided; b.rs: reject all;
+        // c.rs: toggle on
+        assert_eq!(press(&mut review, "ajr\tR\t "), None);
+        assert_eq!(review.selected(), Some((Path::new("c.rs"), 0)));
+        assert_eq!(press(&mut review, "\n"), Some(DiffReviewAction::Apply));
+
+        let applier = RecordingApplier::default();
+        let changed = review.apply(&applier).await.unwrap();
+        assert_eq!(changed, [PathBuf::from("a.rs"), PathBuf::from("c.rs")]);
+        assert_eq!(
+            *applier.calls.lock().unwrap(),
+            [
+                (
+                    PathBuf::from("a.rs"),
+                    vec![
+                        HunkStatus::Accepted,
+                        HunkStatus::Rejected,
+                        HunkStatus::Rejected
+                    ]
+                ),
+                (PathBuf::from("c.rs"), vec![HunkStatus::Accepted]),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejecting_everything_cancels() {
+        let mut review = DiffReview::new(vec![hunk("a.rs", 1), hunk("b.rs", 3)]);
+        assert_eq!(press(&mut review, "aR\tr"), None);
+        assert!(!review.any_accepted());
+        assert_eq!(press(&mut review, "\n"), Some(DiffReviewAction::Cancel));
+
+        let applier = RecordingApplier::default();
+        assert!(review.apply(&applier).await.unwrap().is_empty());
+        assert!(applier.calls.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_workspace_applier_writes_and_records() {
+        let workspace = tempfile::tempdir().unwrap();
+        let path = workspace.path().join("lib.rs");
+        let original = "fn a() {}\nfn b() {}\nfn c() {}\nfn d() {}\nfn e() {}\n";
+        std::fs::write(&path, original).unwrap();
+
+        let hunks = split_diff_into_hunks(
+            path.clone(),
+            original,
+            "fn A() {}\nfn b() {}\nfn c() {}\nfn d() {}\nfn E() {}\n",
+            0,
+        );
+        let mut review = DiffReview::new(hunks);
+        press(&mut review, "rja");
+
+        let action_log = Arc::new(ActionLog::new());
+        let applier = WorkspaceHunkApplier::new(
+            SandboxLevel::WorkspaceWrite,
+            Some(workspace.path().to_path_buf()),
+            Some(action_log.clone()),
+        );
+        review.apply(&applier).await.unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "fn a() {}\nfn b() {}\nfn c() {}\nfn d() {}\nfn E() {}\n"
+        );
+        let history = action_log.get_history().await;
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].command, "edit");
+        assert!(history[0].description.starts_with("Applied 1 of 2 hunks"));
+    }
+}
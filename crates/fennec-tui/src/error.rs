@@ -1,4 +1,7 @@
-use fennec_core::error::{ErrorCategory, ErrorInfo, ErrorSeverity, RecoveryAction};
+use crate::events::AppEvent;
+use crate::theme::{ComponentType, ThemeManager};
+use chrono::{DateTime, Local};
+use fennec_core::error::{DomainError, ErrorCategory, ErrorInfo, ErrorSeverity, RecoveryAction};
 use ratatui::{
     prelude::*,
     style::{Color, Modifier, Style},
@@ -43,6 +46,9 @@ pub enum TuiError {
     #[error("Unsupported input: '{input}' not available in current mode")]
     UnsupportedInput { input: String, mode: String },
 
+    #[error("Invalid key bindings in {path}: {reason}")]
+    KeymapInvalid { path: String, reason: String },
+
     // Rendering and display errors
     #[error("Rendering failed for component '{component}': {reason}")]
     RenderingFailed { component: String, reason: String },
@@ -142,11 +148,53 @@ pub enum TuiError {
 }
 
 impl ErrorInfo for TuiError {
+    fn code(&self) -> &'static str {
+        match self {
+            TuiError::TerminalInitFailed { .. } => "FEN-6001",
+            TuiError::TerminalTooSmall { .. } => "FEN-6002",
+            TuiError::TerminalCapabilityMissing { .. } => "FEN-6003",
+            TuiError::TerminalRestoreFailed { .. } => "FEN-6004",
+            TuiError::InvalidInput { .. } => "FEN-6005",
+            TuiError::InputBufferFull => "FEN-6006",
+            TuiError::InputTimeout { .. } => "FEN-6007",
+            TuiError::UnsupportedInput { .. } => "FEN-6008",
+            TuiError::KeymapInvalid { .. } => "FEN-6009",
+            TuiError::RenderingFailed { .. } => "FEN-6010",
+            TuiError::LayoutFailed { .. } => "FEN-6011",
+            TuiError::ComponentStateCorrupted { .. } => "FEN-6012",
+            TuiError::DisplayBufferOverflow { .. } => "FEN-6013",
+            TuiError::ThemeNotFound { .. } => "FEN-6014",
+            TuiError::ThemeLoadFailed { .. } => "FEN-6015",
+            TuiError::InvalidColor { .. } => "FEN-6016",
+            TuiError::StyleParsingFailed { .. } => "FEN-6017",
+            TuiError::ComponentNotFound { .. } => "FEN-6018",
+            TuiError::ComponentInitFailed { .. } => "FEN-6019",
+            TuiError::WidgetConfigInvalid { .. } => "FEN-6020",
+            TuiError::ComponentUpdateFailed { .. } => "FEN-6021",
+            TuiError::AppStateCorrupted { .. } => "FEN-6022",
+            TuiError::InvalidStateTransition { .. } => "FEN-6023",
+            TuiError::StateSyncFailed { .. } => "FEN-6024",
+            TuiError::StatePersistenceFailed { .. } => "FEN-6025",
+            TuiError::EventProcessingFailed { .. } => "FEN-6026",
+            TuiError::EventQueueOverflow { .. } => "FEN-6027",
+            TuiError::EventTimeout { .. } => "FEN-6028",
+            TuiError::DataFormattingFailed { .. } => "FEN-6029",
+            TuiError::ContentTooLarge { .. } => "FEN-6030",
+            TuiError::PaginationFailed { .. } => "FEN-6031",
+            TuiError::ScrollingFailed { .. } => "FEN-6032",
+            TuiError::BackendService { .. } => "FEN-6033",
+            TuiError::CommandExecution { .. } => "FEN-6034",
+            TuiError::Io { .. } => "FEN-6035",
+            TuiError::Generic { .. } => "FEN-6036",
+        }
+    }
+
     fn category(&self) -> ErrorCategory {
         match self {
             // User errors
             TuiError::InvalidInput { .. }
             | TuiError::UnsupportedInput { .. }
+            | TuiError::KeymapInvalid { .. }
             | TuiError::ThemeNotFound { .. }
             | TuiError::InvalidColor { .. }
             | TuiError::ComponentNotFound { .. }
@@ -205,6 +253,7 @@ impl ErrorInfo for TuiError {
             TuiError::InputTimeout { .. }
             | TuiError::EventTimeout { .. }
             | TuiError::ContentTooLarge { .. }
+            | TuiError::KeymapInvalid { .. }
             | TuiError::DisplayBufferOverflow { .. } => ErrorSeverity::Warning,
 
             // Standard errors
@@ -242,6 +291,13 @@ impl ErrorInfo for TuiError {
                 ))]
             }
 
+            TuiError::KeymapInvalid { path, .. } => {
+                vec![RecoveryAction::CheckConfiguration(format!(
+                    "Fix or remove {}; default key bindings are used meanwhile",
+                    path
+                ))]
+            }
+
             TuiError::InputBufferFull => {
                 vec![RecoveryAction::ManualAction(
                     "Wait for current operation to complete or press Ctrl+C to cancel".to_string(),
@@ -310,6 +366,9 @@ impl ErrorInfo for TuiError {
             TuiError::InvalidInput { .. } => {
                 "Invalid input. Please check your input and try again.".to_string()
             }
+            TuiError::KeymapInvalid { .. } => {
+                "Custom key bindings could not be loaded. Using the defaults.".to_string()
+            }
             TuiError::InputBufferFull => {
                 "System is busy processing. Please wait a moment.".to_string()
             }
@@ -372,7 +431,7 @@ impl From<std::io::Error> for TuiError {
 
 impl From<TuiError> for fennec_core::FennecError {
     fn from(err: TuiError) -> Self {
-        fennec_core::FennecError::Tui(Box::new(err))
+        fennec_core::FennecError::Tui(Box::new(DomainError::new(err)))
     }
 }
 
@@ -393,64 +452,51 @@ pub struct ErrorDisplay {
     pub show_details: bool,
     /// Debug context information
     pub debug_context: Option<String>,
+    /// Stable code of the error, e.g. `FEN-1002`
+    pub code: Option<&'static str>,
+    /// What the user can do about the error
+    pub hint: Option<String>,
+    /// Full technical description, shown with the details
+    pub developer_message: Option<String>,
 }
 
 impl ErrorDisplay {
     /// Create a new error display from any error
     pub fn from_error(error: Box<dyn std::error::Error + Send + Sync>) -> Self {
-        let user_message =
-            if let Some(fennec_error) = error.downcast_ref::<fennec_core::FennecError>() {
-                fennec_error.user_message()
-            } else if let Some(tui_error) = error.downcast_ref::<TuiError>() {
-                tui_error.user_message()
-            } else {
-                "An unexpected error occurred. Please try again.".to_string()
-            };
-
-        let recovery_actions =
-            if let Some(fennec_error) = error.downcast_ref::<fennec_core::FennecError>() {
-                fennec_error.recovery_actions()
-            } else if let Some(tui_error) = error.downcast_ref::<TuiError>() {
-                tui_error.recovery_actions()
-            } else {
-                vec![RecoveryAction::Retry]
-            };
-
-        let category = if let Some(fennec_error) = error.downcast_ref::<fennec_core::FennecError>()
-        {
-            fennec_error.category()
-        } else if let Some(tui_error) = error.downcast_ref::<TuiError>() {
-            tui_error.category()
-        } else {
-            ErrorCategory::Internal
-        };
-
-        let severity = if let Some(fennec_error) = error.downcast_ref::<fennec_core::FennecError>()
-        {
-            fennec_error.severity()
-        } else if let Some(tui_error) = error.downcast_ref::<TuiError>() {
-            tui_error.severity()
-        } else {
-            ErrorSeverity::Error
-        };
-
-        let debug_context =
+        let info: Option<&dyn ErrorInfo> =
             if let Some(fennec_error) = error.downcast_ref::<fennec_core::FennecError>() {
-                fennec_error.debug_context()
+                Some(fennec_error)
             } else if let Some(tui_error) = error.downcast_ref::<TuiError>() {
-                tui_error.debug_context()
+                Some(tui_error)
             } else {
                 None
             };
 
+        let display = match info {
+            Some(info) => Self {
+                error: None,
+                user_message: info.user_message(),
+                recovery_actions: info.recovery_actions(),
+                category: info.category(),
+                severity: info.severity(),
+                show_details: false,
+                debug_context: info.debug_context(),
+                code: Some(info.code()),
+                hint: info.hint(),
+                developer_message: Some(info.developer_message()),
+            },
+            None => Self {
+                developer_message: Some(error.to_string()),
+                ..Self::from_message(
+                    "An unexpected error occurred. Please try again.".to_string(),
+                    ErrorCategory::Internal,
+                    ErrorSeverity::Error,
+                )
+            },
+        };
         Self {
             error: Some(error),
-            user_message,
-            recovery_actions,
-            category,
-            severity,
-            show_details: false,
-            debug_context,
+            ..display
         }
     }
 
@@ -464,6 +510,9 @@ impl ErrorDisplay {
             severity,
             show_details: false,
             debug_context: None,
+            code: None,
+            hint: None,
+            developer_message: None,
         }
     }
 
@@ -473,13 +522,8 @@ impl ErrorDisplay {
     }
 
     /// Get the appropriate color for the error severity
-    pub fn severity_color(&self) -> Color {
-        match self.severity {
-            ErrorSeverity::Info => Color::Blue,
-            ErrorSeverity::Warning => Color::Yellow,
-            ErrorSeverity::Error => Color::Red,
-            ErrorSeverity::Critical => Color::Magenta,
-        }
+    pub fn severity_color(&self, theme: &ThemeManager) -> Color {
+        theme.get_color(severity_component(self.severity))
     }
 
     /// Get the appropriate icon for the error severity
@@ -493,19 +537,19 @@ impl ErrorDisplay {
     }
 
     /// Render the error display widget
-    pub fn render(&self, area: Rect, buf: &mut Buffer) {
+    pub fn render(&self, area: Rect, buf: &mut Buffer, theme: &ThemeManager) {
         // Clear the area first
         Clear.render(area, buf);
 
         // Create the main container
+        let title = match self.code {
+            Some(code) => format!(" {} {} {} ", self.severity_icon(), self.category, code),
+            None => format!(" {} {} ", self.severity_icon(), self.category),
+        };
         let block = Block::default()
-            .title(format!(
-                " {} {} Error ",
-                self.severity_icon(),
-                self.category
-            ))
+            .title(title)
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(self.severity_color()));
+            .border_style(Style::default().fg(self.severity_color(theme)));
 
         let inner = block.inner(area);
         block.render(area, buf);
@@ -520,18 +564,18 @@ impl ErrorDisplay {
             .split(inner);
 
         // Render error message
-        let message_text = if self.show_details {
-            if let Some(ref error) = self.error {
-                format!("{}\n\nTechnical details: {}", self.user_message, error)
-            } else {
-                self.user_message.clone()
+        let mut message_text = self.user_message.clone();
+        if let Some(hint) = &self.hint {
+            message_text.push_str(&format!("\n\nHint: {}", hint));
+        }
+        if self.show_details {
+            if let Some(details) = &self.developer_message {
+                message_text.push_str(&format!("\n\nTechnical details: {}", details));
             }
-        } else {
-            self.user_message.clone()
-        };
+        }
 
         let message_paragraph = Paragraph::new(message_text)
-            .style(Style::default().fg(Color::White))
+            .style(theme.get_style(ComponentType::Text))
             .wrap(Wrap { trim: true });
         message_paragraph.render(chunks[0], buf);
 
@@ -539,40 +583,28 @@ impl ErrorDisplay {
         if !self.recovery_actions.is_empty() {
             let actions_title = "Suggested Actions:";
             let actions_items: Vec<ListItem> = std::iter::once(
-                ListItem::new(Text::from(actions_title)).style(
-                    Style::default()
-                        .fg(Color::Cyan)
-                        .add_modifier(Modifier::BOLD),
-                ),
+                ListItem::new(Text::from(actions_title))
+                    .style(theme.get_style(ComponentType::Highlight)),
             )
             .chain(self.recovery_actions.iter().enumerate().map(|(i, action)| {
                 ListItem::new(Text::from(format!("{}. {}", i + 1, action)))
-                    .style(Style::default().fg(Color::Gray))
+                    .style(theme.get_style(ComponentType::Muted))
             }))
             .collect();
 
             let actions_list = List::new(actions_items);
             actions_list.render(chunks[1], buf);
         }
-
-        // Show debug context if available and details are shown
-        if self.show_details {
-            if let Some(ref context) = self.debug_context {
-                let debug_text = format!("\nDebug: {}", context);
-                let debug_para =
-                    Paragraph::new(debug_text).style(Style::default().fg(Color::DarkGray));
-                // Render at bottom of message area
-                let debug_area = Rect {
-                    y: chunks[0].y + chunks[0].height.saturating_sub(2),
-                    height: 2,
-                    ..chunks[0]
-                };
-                debug_para.render(debug_area, buf);
-            }
-        }
     }
 }
 
+/// Action offered on a toast, dispatching `event` when taken
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToastAction {
+    pub label: String,
+    pub event: AppEvent,
+}
+
 /// Error toast notification for brief error messages
 #[derive(Debug, Clone)]
 pub struct ErrorToast {
@@ -580,6 +612,9 @@ pub struct ErrorToast {
     pub severity: ErrorSeverity,
     pub duration_ms: u64,
     pub start_time: std::time::Instant,
+    /// Wall-clock time the toast was raised, shown in the history
+    pub created_at: DateTime<Local>,
+    pub action: Option<ToastAction>,
 }
 
 impl ErrorToast {
@@ -589,34 +624,111 @@ impl ErrorToast {
             severity,
             duration_ms,
             start_time: std::time::Instant::now(),
+            created_at: Local::now(),
+            action: None,
+        }
+    }
+
+    /// Create a toast shown for the default time of its severity
+    pub fn with_severity(message: String, severity: ErrorSeverity) -> Self {
+        Self::new(message, severity, Self::default_duration_ms(severity))
+    }
+
+    /// How long toasts of a severity stay up: the more severe, the longer
+    pub fn default_duration_ms(severity: ErrorSeverity) -> u64 {
+        match severity {
+            ErrorSeverity::Info => 3000,
+            ErrorSeverity::Warning => 5000,
+            ErrorSeverity::Error => 8000,
+            ErrorSeverity::Critical => 15000,
         }
     }
 
+    /// Offer an action that dispatches `event`, e.g. a retry
+    pub fn with_action(mut self, label: impl Into<String>, event: AppEvent) -> Self {
+        self.action = Some(ToastAction {
+            label: label.into(),
+            event,
+        });
+        self
+    }
+
     pub fn is_expired(&self) -> bool {
-        self.start_time.elapsed().as_millis() > self.duration_ms as u128
+        self.is_expired_at(std::time::Instant::now())
     }
 
-    pub fn render(&self, area: Rect, buf: &mut Buffer) {
-        let color = match self.severity {
-            ErrorSeverity::Info => Color::Blue,
-            ErrorSeverity::Warning => Color::Yellow,
-            ErrorSeverity::Error => Color::Red,
-            ErrorSeverity::Critical => Color::Magenta,
-        };
+    pub fn is_expired_at(&self, now: std::time::Instant) -> bool {
+        now.saturating_duration_since(self.start_time).as_millis() > self.duration_ms as u128
+    }
+
+    pub fn severity_color(&self, theme: &ThemeManager) -> Color {
+        theme.get_color(severity_component(self.severity))
+    }
+
+    pub fn severity_icon(&self) -> &'static str {
+        match self.severity {
+            ErrorSeverity::Info => "ℹ",
+            ErrorSeverity::Warning => "⚠",
+            ErrorSeverity::Error => "✗",
+            ErrorSeverity::Critical => "🔥",
+        }
+    }
+
+    /// Rows needed to render the toast `width` columns wide
+    pub fn height(&self, width: u16) -> u16 {
+        let inner = width.saturating_sub(2).max(1) as usize;
+        let message_rows: usize = self
+            .message
+            .lines()
+            .map(|line| line.chars().count().max(1).div_ceil(inner))
+            .sum();
+        let action_rows = usize::from(self.action.is_some());
+        (message_rows.max(1) + action_rows + 2) as u16
+    }
+
+    pub fn render(&self, area: Rect, buf: &mut Buffer, theme: &ThemeManager) {
+        let color = self.severity_color(theme);
 
         let block = Block::default()
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(color));
+            .border_style(Style::default().fg(color))
+            .title(Span::styled(
+                format!(" {} ", self.severity_icon()),
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            ));
+
+        let mut lines: Vec<Line> = self
+            .message
+            .lines()
+            .map(|line| Line::from(line.to_string()))
+            .collect();
+        if let Some(action) = &self.action {
+            lines.push(Line::from(Span::styled(
+                format!("[r] {}", action.label),
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            )));
+        }
 
-        let paragraph = Paragraph::new(self.message.clone())
-            .style(Style::default().fg(Color::White))
+        let paragraph = Paragraph::new(lines)
+            .style(theme.get_style(ComponentType::Text))
             .block(block)
             .wrap(Wrap { trim: true });
 
+        Clear.render(area, buf);
         paragraph.render(area, buf);
     }
 }
 
+/// Theme component colouring messages of a severity
+pub fn severity_component(severity: ErrorSeverity) -> ComponentType {
+    match severity {
+        ErrorSeverity::Info => ComponentType::Info,
+        ErrorSeverity::Warning => ComponentType::Warning,
+        ErrorSeverity::Error => ComponentType::Error,
+        ErrorSeverity::Critical => ComponentType::Critical,
+    }
+}
+
 /// Helper functions for creating common error types
 pub fn terminal_too_small(width: u16, height: u16, min_width: u16, min_height: u16) -> TuiError {
     TuiError::TerminalTooSmall {
@@ -1022,8 +1134,50 @@ mod tests {
     #[test]
     fn test_fennec_error_conversion() {
         let tui_err = TuiError::InputBufferFull;
+        let code = tui_err.code();
         let fennec_err: fennec_core::FennecError = tui_err.into();
         assert!(matches!(fennec_err, fennec_core::FennecError::Tui(_)));
+        assert_eq!(fennec_err.code(), code);
+    }
+
+    #[test]
+    fn test_error_display_uses_codes_and_hints() {
+        let error = fennec_core::FennecError::ConfigInvalid {
+            issue: "timeout_seconds must be positive".to_string(),
+            suggestion: "Set provider.timeout_seconds above 0".to_string(),
+        };
+        let mut display = ErrorDisplay::from_error(Box::new(error));
+        assert_eq!(display.code, Some("FEN-1002"));
+        assert_eq!(
+            display.hint.as_deref(),
+            Some("Set provider.timeout_seconds above 0")
+        );
+        assert!(display
+            .developer_message
+            .as_deref()
+            .unwrap()
+            .starts_with("[FEN-1002] Invalid configuration"));
+
+        let theme = ThemeManager::new();
+        let area = Rect::new(0, 0, 80, 12);
+        let mut buf = Buffer::empty(area);
+        display.render(area, &mut buf, &theme);
+        let text: String = (0..area.height)
+            .flat_map(|y| (0..area.width).map(move |x| (x, y)))
+            .map(|(x, y)| buf.get(x, y).symbol.clone())
+            .collect();
+        assert!(text.contains("FEN-1002"));
+        assert!(text.contains("Hint: Set provider.timeout_seconds"));
+        assert!(!text.contains("Technical details"));
+
+        display.toggle_details();
+        let mut buf = Buffer::empty(area);
+        display.render(area, &mut buf, &theme);
+        let text: String = (0..area.height)
+            .flat_map(|y| (0..area.width).map(move |x| (x, y)))
+            .map(|(x, y)| buf.get(x, y).symbol.clone())
+            .collect();
+        assert!(text.contains("Technical details"));
     }
 
     // Test helper functions
@@ -1178,18 +1332,23 @@ mod tests {
 
     #[test]
     fn test_error_display_severity_color() {
+        let theme = ThemeManager::new();
         let cases = vec![
-            (ErrorSeverity::Info, Color::Blue),
-            (ErrorSeverity::Warning, Color::Yellow),
-            (ErrorSeverity::Error, Color::Red),
-            (ErrorSeverity::Critical, Color::Magenta),
+            (ErrorSeverity::Info, ComponentType::Info),
+            (ErrorSeverity::Warning, ComponentType::Warning),
+            (ErrorSeverity::Error, ComponentType::Error),
+            (ErrorSeverity::Critical, ComponentType::Critical),
         ];
 
-        for (severity, expected_color) in cases {
+        let mut colors = Vec::new();
+        for (severity, component) in cases {
             let display =
                 ErrorDisplay::from_message("Test".to_string(), ErrorCategory::Internal, severity);
-            assert_eq!(display.severity_color(), expected_color);
+            assert_eq!(display.severity_color(&theme), theme.get_color(component));
+            colors.push(display.severity_color(&theme));
         }
+        colors.dedup();
+        assert_eq!(colors.len(), 4);
     }
 
     #[test]
@@ -1,6 +1,7 @@
-use crossterm::event::{
-    Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEvent, MouseEventKind,
-};
+use crate::keymap::Keymap;
+use crate::layout::{AppLayout, Pane};
+use crossterm::event::{Event, KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Rect;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
 use std::time::{Duration, Instant};
@@ -26,6 +27,10 @@ pub enum AppEvent {
     Error(String),
     /// Session state change
     SessionStateChanged,
+    /// Send a chat message, e.g. when retrying one that failed
+    SendMessage(String),
+    /// Run a registry command by name
+    RunCommand(String),
 }
 
 /// Represents different input modes for the application
@@ -42,7 +47,7 @@ pub enum InputMode {
 }
 
 /// Key binding actions
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum KeyAction {
     /// No action
     None,
@@ -72,8 +77,16 @@ pub enum KeyAction {
     GoToTop,
     /// Go to bottom
     GoToBottom,
+    /// Jump to the next search match
+    NextMatch,
+    /// Jump to the previous search match
+    PreviousMatch,
     /// Send message/input
     Send,
+    /// Start a new line in the input
+    NewLine,
+    /// Edit the input in an external editor
+    OpenEditor,
     /// Clear input
     Clear,
     /// Delete character
@@ -98,6 +111,178 @@ pub enum KeyAction {
     ShowHelp,
     /// Refresh
     Refresh,
+    /// Show or hide the notification history
+    ToggleToastHistory,
+    /// Take the action offered by the newest toast
+    ToastAction,
+    /// Open the command palette
+    OpenCommandPalette,
+    /// Open the dialog for tightening the sandbox
+    DowngradeSandbox,
+    /// Open the session picker
+    OpenSessionPicker,
+    /// Switch to the next open session
+    NextSession,
+    /// Switch to the previous open session
+    PreviousSession,
+    /// Raise the log level, wrapping back to info after trace
+    CycleLogLevel,
+}
+
+impl KeyAction {
+    /// Actions that can be bound to keys, with their names in
+    /// `keybindings.toml` and descriptions for the command palette
+    const NAMED: &'static [(KeyAction, &'static str, &'static str)] = &[
+        (KeyAction::Quit, "quit", "Quit Fennec"),
+        (
+            KeyAction::EnterInsert,
+            "enter_insert",
+            "Start typing a message",
+        ),
+        (
+            KeyAction::EnterNormal,
+            "enter_normal",
+            "Return to normal mode",
+        ),
+        (KeyAction::EnterCommand, "enter_command", "Enter a command"),
+        (
+            KeyAction::EnterSearch,
+            "enter_search",
+            "Search the conversation",
+        ),
+        (KeyAction::MoveUp, "move_up", "Move up"),
+        (KeyAction::MoveDown, "move_down", "Move down"),
+        (KeyAction::MoveLeft, "move_left", "Move left"),
+        (KeyAction::MoveRight, "move_right", "Move right"),
+        (KeyAction::PageUp, "page_up", "Page up"),
+        (KeyAction::PageDown, "page_down", "Page down"),
+        (KeyAction::GoToTop, "go_to_top", "Go to top"),
+        (KeyAction::GoToBottom, "go_to_bottom", "Go to bottom"),
+        (KeyAction::NextMatch, "next_match", "Next search match"),
+        (
+            KeyAction::PreviousMatch,
+            "previous_match",
+            "Previous search match",
+        ),
+        (KeyAction::Send, "send", "Send input"),
+        (
+            KeyAction::NewLine,
+            "new_line",
+            "Start a new line in the input",
+        ),
+        (
+            KeyAction::OpenEditor,
+            "open_editor",
+            "Edit the input in $EDITOR",
+        ),
+        (KeyAction::Clear, "clear", "Clear input"),
+        (KeyAction::Delete, "delete", "Delete character"),
+        (
+            KeyAction::Backspace,
+            "backspace",
+            "Delete previous character",
+        ),
+        (
+            KeyAction::ToggleTheme,
+            "toggle_theme",
+            "Switch to the next theme",
+        ),
+        (KeyAction::FocusNext, "focus_next", "Focus next pane"),
+        (
+            KeyAction::FocusPrevious,
+            "focus_previous",
+            "Focus previous pane",
+        ),
+        (
+            KeyAction::TogglePreview,
+            "toggle_preview",
+            "Toggle preview panel",
+        ),
+        (KeyAction::Copy, "copy", "Copy selection"),
+        (KeyAction::Paste, "paste", "Paste"),
+        (KeyAction::ShowHelp, "show_help", "Show help"),
+        (KeyAction::Refresh, "refresh", "Refresh the screen"),
+        (
+            KeyAction::ToggleToastHistory,
+            "toggle_toast_history",
+            "Show notification history",
+        ),
+        (
+            KeyAction::ToastAction,
+            "toast_action",
+            "Take a notification's action",
+        ),
+        (
+            KeyAction::OpenCommandPalette,
+            "open_command_palette",
+            "Open the command palette",
+        ),
+        (
+            KeyAction::DowngradeSandbox,
+            "downgrade_sandbox",
+            "Tighten the sandbox",
+        ),
+        (
+            KeyAction::OpenSessionPicker,
+            "open_session_picker",
+            "Switch, open or close sessions",
+        ),
+        (KeyAction::NextSession, "next_session", "Next session"),
+        (
+            KeyAction::PreviousSession,
+            "previous_session",
+            "Previous session",
+        ),
+        (
+            KeyAction::CycleLogLevel,
+            "cycle_log_level",
+            "Raise log verbosity",
+        ),
+    ];
+
+    /// Every bindable action with its name and description
+    pub fn named() -> impl Iterator<Item = (KeyAction, &'static str, &'static str)> {
+        Self::NAMED.iter().copied()
+    }
+
+    /// Look up an action by its `keybindings.toml` name
+    pub fn from_name(name: &str) -> Option<KeyAction> {
+        Self::named()
+            .find(|(_, action_name, _)| *action_name == name)
+            .map(|(action, _, _)| action)
+    }
+
+    /// Name used in `keybindings.toml`, if the action can be bound
+    pub fn name(&self) -> Option<&'static str> {
+        Self::named()
+            .find(|(action, _, _)| action == self)
+            .map(|(_, name, _)| name)
+    }
+
+    pub fn description(&self) -> &'static str {
+        Self::named()
+            .find(|(action, _, _)| action == self)
+            .map_or("", |(_, _, description)| description)
+    }
+}
+
+/// Lines scrolled per notch of the mouse wheel
+pub const MOUSE_SCROLL_LINES: usize = 3;
+
+/// What a mouse event asks of the pane under the pointer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseAction {
+    /// Nothing to do
+    None,
+    /// Run a movement action on a pane without focusing it
+    Scroll {
+        pane: Pane,
+        action: KeyAction,
+        lines: usize,
+    },
+    /// Focus a pane. `row` is the clicked line inside its border, for
+    /// selecting list items; `None` when the border was clicked.
+    Click { pane: Pane, row: Option<u16> },
 }
 
 /// Event handler for managing input and application events
@@ -117,6 +302,8 @@ pub struct EventHandler {
     last_key_event: Option<(KeyEvent, Instant)>,
     /// Duplicate threshold for key events
     duplicate_threshold: Duration,
+    /// Maps keys to actions in each mode
+    keymap: Keymap,
 }
 
 impl EventHandler {
@@ -132,6 +319,7 @@ impl EventHandler {
             tick_rate,
             last_key_event: None,
             duplicate_threshold: Duration::from_millis(50), // 50ms threshold for duplicate detection
+            keymap: Keymap::default(),
         }
     }
 
@@ -150,6 +338,15 @@ impl EventHandler {
         self.input_mode = mode;
     }
 
+    pub fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
+    /// Replace the key bindings
+    pub fn set_keymap(&mut self, keymap: Keymap) {
+        self.keymap = keymap;
+    }
+
     /// Wait for the next event with timeout
     pub async fn next_event(&mut self) -> Option<AppEvent> {
         let timeout_duration = self
@@ -192,192 +389,86 @@ impl EventHandler {
 
         self.last_key_event = Some((key, now));
 
-        // Check for global keys that work in ANY mode FIRST
-        if let Some(global_action) = self.handle_global_key(key) {
-            return global_action;
-        }
-
-        // Then handle mode-specific keys
-        match self.input_mode {
-            InputMode::Normal => self.handle_normal_mode_key(key),
-            InputMode::Insert => self.handle_insert_mode_key(key),
-            InputMode::Command => self.handle_command_mode_key(key),
-            InputMode::Search => self.handle_search_mode_key(key),
-        }
+        self.keymap
+            .action_for(&self.input_mode, key)
+            .unwrap_or(KeyAction::None)
     }
 
-    /// Handle global keys that work in any mode
-    fn handle_global_key(&self, key: KeyEvent) -> Option<KeyAction> {
-        match (key.modifiers, key.code) {
-            // Global quit - works in any mode
-            (KeyModifiers::CONTROL, KeyCode::Char('c')) => Some(KeyAction::Quit),
-
-            // Global pane navigation - works in any mode
-            (KeyModifiers::NONE, KeyCode::Tab) => Some(KeyAction::FocusNext),
-            (KeyModifiers::SHIFT, KeyCode::BackTab) => Some(KeyAction::FocusPrevious),
-
-            // Global theme toggle - works in any mode (use Ctrl+T to avoid conflicts)
-            (KeyModifiers::CONTROL, KeyCode::Char('t')) => Some(KeyAction::ToggleTheme),
-
-            // Global preview toggle - works in any mode (use Ctrl+P to avoid conflicts)
-            (KeyModifiers::CONTROL, KeyCode::Char('p')) => Some(KeyAction::TogglePreview),
-
-            // Global help - works in any mode
-            (KeyModifiers::NONE, KeyCode::F(1)) => Some(KeyAction::ShowHelp),
-            (KeyModifiers::CONTROL, KeyCode::Char('?')) => Some(KeyAction::ShowHelp),
-
-            // Global quit in normal mode only (to avoid conflicts with insert mode)
-            (KeyModifiers::NONE, KeyCode::Char('q')) if self.input_mode == InputMode::Normal => {
-                Some(KeyAction::Quit)
-            }
-
-            _ => None,
-        }
-    }
-
-    /// Handle key events in normal mode
-    fn handle_normal_mode_key(&self, key: KeyEvent) -> KeyAction {
-        match (key.modifiers, key.code) {
-            // Mode switches (only work in normal mode)
-            (KeyModifiers::NONE, KeyCode::Char('i')) => KeyAction::EnterInsert,
-            (KeyModifiers::NONE, KeyCode::Char(':')) => KeyAction::EnterCommand,
-            (KeyModifiers::NONE, KeyCode::Char('/')) => KeyAction::EnterSearch,
-
-            // Navigation
-            (KeyModifiers::NONE, KeyCode::Up) | (KeyModifiers::NONE, KeyCode::Char('k')) => {
-                KeyAction::MoveUp
-            }
-            (KeyModifiers::NONE, KeyCode::Down) | (KeyModifiers::NONE, KeyCode::Char('j')) => {
-                KeyAction::MoveDown
-            }
-            (KeyModifiers::NONE, KeyCode::Left) | (KeyModifiers::NONE, KeyCode::Char('h')) => {
-                KeyAction::MoveLeft
-            }
-            (KeyModifiers::NONE, KeyCode::Right) | (KeyModifiers::NONE, KeyCode::Char('l')) => {
-                KeyAction::MoveRight
-            }
-
-            // Page navigation
-            (KeyModifiers::CONTROL, KeyCode::Char('u')) => KeyAction::PageUp,
-            (KeyModifiers::CONTROL, KeyCode::Char('d')) => KeyAction::PageDown,
-            (KeyModifiers::NONE, KeyCode::PageUp) => KeyAction::PageUp,
-            (KeyModifiers::NONE, KeyCode::PageDown) => KeyAction::PageDown,
-
-            // Jump to top/bottom
-            (KeyModifiers::NONE, KeyCode::Char('g')) => KeyAction::GoToTop,
-            (KeyModifiers::NONE, KeyCode::Char('G')) => KeyAction::GoToBottom,
-            (KeyModifiers::NONE, KeyCode::Home) => KeyAction::GoToTop,
-            (KeyModifiers::NONE, KeyCode::End) => KeyAction::GoToBottom,
-
-            // UI toggles (mode-specific, use single keys)
-            (KeyModifiers::NONE, KeyCode::Char('t')) => KeyAction::ToggleTheme,
-            (KeyModifiers::NONE, KeyCode::Char('p')) => KeyAction::TogglePreview,
-
-            // Copy/paste
-            (KeyModifiers::CONTROL, KeyCode::Char('y')) => KeyAction::Copy,
-            // Note: Ctrl+P is now global for preview toggle, so remove this line
-            // (KeyModifiers::CONTROL, KeyCode::Char('p')) => KeyAction::Paste,
-
-            // Utility
-            (KeyModifiers::NONE, KeyCode::Char('?')) => KeyAction::ShowHelp,
-            (KeyModifiers::NONE, KeyCode::F(5)) => KeyAction::Refresh,
-            (KeyModifiers::CONTROL, KeyCode::Char('r')) => KeyAction::Refresh,
-
-            _ => KeyAction::None,
-        }
-    }
-
-    /// Handle key events in insert mode
-    fn handle_insert_mode_key(&self, key: KeyEvent) -> KeyAction {
-        match (key.modifiers, key.code) {
-            // Exit insert mode (Esc only, Ctrl+C is handled globally)
-            (KeyModifiers::NONE, KeyCode::Esc) => KeyAction::EnterNormal,
-
-            // Send message
-            (KeyModifiers::NONE, KeyCode::Enter) => KeyAction::Send,
-            (KeyModifiers::CONTROL, KeyCode::Char('m')) => KeyAction::Send,
-
-            // Text editing
-            (KeyModifiers::NONE, KeyCode::Backspace) => KeyAction::Backspace,
-            (KeyModifiers::NONE, KeyCode::Delete) => KeyAction::Delete,
-            (KeyModifiers::CONTROL, KeyCode::Char('u')) => KeyAction::Clear,
-
-            // Navigation in insert mode
-            (KeyModifiers::CONTROL, KeyCode::Char('a')) => KeyAction::GoToTop,
-            (KeyModifiers::CONTROL, KeyCode::Char('e')) => KeyAction::GoToBottom,
-            (KeyModifiers::CONTROL, KeyCode::Left) => KeyAction::MoveLeft,
-            (KeyModifiers::CONTROL, KeyCode::Right) => KeyAction::MoveRight,
-
-            // Insert character
-            (KeyModifiers::NONE, KeyCode::Char(c)) => KeyAction::InsertChar(c),
-            (KeyModifiers::SHIFT, KeyCode::Char(c)) => KeyAction::InsertChar(c),
-
-            _ => KeyAction::None,
-        }
-    }
-
-    /// Handle key events in command mode
-    fn handle_command_mode_key(&self, key: KeyEvent) -> KeyAction {
-        match (key.modifiers, key.code) {
-            // Exit command mode (Esc only, Ctrl+C is handled globally)
-            (KeyModifiers::NONE, KeyCode::Esc) => KeyAction::EnterNormal,
-
-            // Execute command
-            (KeyModifiers::NONE, KeyCode::Enter) => KeyAction::Send,
-
-            // Text editing
-            (KeyModifiers::NONE, KeyCode::Backspace) => KeyAction::Backspace,
-            (KeyModifiers::NONE, KeyCode::Delete) => KeyAction::Delete,
-            (KeyModifiers::CONTROL, KeyCode::Char('u')) => KeyAction::Clear,
-
-            // Insert character
-            (KeyModifiers::NONE, KeyCode::Char(c)) => KeyAction::InsertChar(c),
-            (KeyModifiers::SHIFT, KeyCode::Char(c)) => KeyAction::InsertChar(c),
-
-            _ => KeyAction::None,
-        }
-    }
-
-    /// Handle key events in search mode
-    fn handle_search_mode_key(&self, key: KeyEvent) -> KeyAction {
-        match (key.modifiers, key.code) {
-            // Exit search mode (Esc only, Ctrl+C is handled globally)
-            (KeyModifiers::NONE, KeyCode::Esc) => KeyAction::EnterNormal,
-
-            // Execute search
-            (KeyModifiers::NONE, KeyCode::Enter) => KeyAction::Send,
-
-            // Text editing
-            (KeyModifiers::NONE, KeyCode::Backspace) => KeyAction::Backspace,
-            (KeyModifiers::NONE, KeyCode::Delete) => KeyAction::Delete,
-            (KeyModifiers::CONTROL, KeyCode::Char('u')) => KeyAction::Clear,
-
-            // Insert character
-            (KeyModifiers::NONE, KeyCode::Char(c)) => KeyAction::InsertChar(c),
-            (KeyModifiers::SHIFT, KeyCode::Char(c)) => KeyAction::InsertChar(c),
-
-            _ => KeyAction::None,
-        }
-    }
-
-    /// Handle mouse events
-    pub fn handle_mouse_event(&self, mouse: MouseEvent) -> KeyAction {
+    /// Translate a mouse event into an action on the pane under the
+    /// pointer in `layout`
+    pub fn handle_mouse_event(&self, mouse: MouseEvent, layout: &AppLayout) -> MouseAction {
+        let Some((pane, area)) = pane_at(layout, mouse.column, mouse.row) else {
+            return MouseAction::None;
+        };
         match mouse.kind {
-            MouseEventKind::ScrollUp => KeyAction::MoveUp,
-            MouseEventKind::ScrollDown => KeyAction::MoveDown,
-            MouseEventKind::Down(_) => {
-                // Handle click events for focus changes
-                KeyAction::None // For now, will be expanded when we have click targets
+            MouseEventKind::ScrollUp => MouseAction::Scroll {
+                pane,
+                action: KeyAction::MoveUp,
+                lines: MOUSE_SCROLL_LINES,
+            },
+            MouseEventKind::ScrollDown => MouseAction::Scroll {
+                pane,
+                action: KeyAction::MoveDown,
+                lines: MOUSE_SCROLL_LINES,
+            },
+            MouseEventKind::Down(MouseButton::Left) => {
+                let inside = mouse.row > area.y && mouse.row + 1 < area.bottom();
+                MouseAction::Click {
+                    pane,
+                    row: inside.then(|| mouse.row - area.y - 1),
+                }
             }
-            _ => KeyAction::None,
+            _ => MouseAction::None,
         }
     }
 }
 
+/// The pane of `layout` covering a cell, with its area. The status bar and
+/// hint line don't take mouse input.
+fn pane_at(layout: &AppLayout, column: u16, row: u16) -> Option<(Pane, Rect)> {
+    let contains = |area: &Rect| {
+        column >= area.x && column < area.right() && row >= area.y && row < area.bottom()
+    };
+    [
+        (Pane::Chat, Some(layout.chat_area)),
+        (Pane::Input, Some(layout.input_area)),
+        (Pane::Preview, layout.preview_area),
+    ]
+    .into_iter()
+    .find_map(|(pane, area)| area.filter(contains).map(|area| (pane, area)))
+}
+
 /// Global flag to prevent multiple event listeners from being spawned
 static EVENT_LISTENER_SPAWNED: AtomicBool = AtomicBool::new(false);
 static EVENT_LISTENER_LOCK: Mutex<()> = Mutex::new(());
 
+/// Set while another program owns the terminal, e.g. an external editor
+static EVENT_LISTENER_PAUSED: AtomicBool = AtomicBool::new(false);
+/// Set while a paused listener has stopped reading input
+static EVENT_LISTENER_IDLE: AtomicBool = AtomicBool::new(false);
+
+/// How long the listener waits for input before checking for a pause
+const EVENT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Stop reading terminal input so a program run in the foreground gets
+/// every key. Returns once the listener has stopped, or after a second if
+/// none is running.
+pub async fn pause_event_listener() {
+    EVENT_LISTENER_PAUSED.store(true, Ordering::SeqCst);
+    let deadline = Instant::now() + Duration::from_secs(1);
+    while EVENT_LISTENER_SPAWNED.load(Ordering::SeqCst)
+        && !EVENT_LISTENER_IDLE.load(Ordering::SeqCst)
+        && Instant::now() < deadline
+    {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+/// Read terminal input again after [`pause_event_listener`]
+pub fn resume_event_listener() {
+    EVENT_LISTENER_PAUSED.store(false, Ordering::SeqCst);
+}
+
 /// Spawns a background task to capture terminal events
 /// This function ensures that only one event listener is running at a time
 pub fn spawn_event_listener(sender: mpsc::UnboundedSender<AppEvent>) {
@@ -395,6 +486,33 @@ pub fn spawn_event_listener(sender: mpsc::UnboundedSender<AppEvent>) {
     tokio::task::spawn_blocking(move || {
         tracing::debug!("Starting terminal event listener");
         loop {
+            if EVENT_LISTENER_PAUSED.load(Ordering::SeqCst) {
+                EVENT_LISTENER_IDLE.store(true, Ordering::SeqCst);
+                std::thread::sleep(EVENT_POLL_INTERVAL);
+                continue;
+            }
+            EVENT_LISTENER_IDLE.store(false, Ordering::SeqCst);
+
+            // Poll rather than block, so a pause takes effect before the
+            // next key is read
+            match crossterm::event::poll(EVENT_POLL_INTERVAL) {
+                Ok(true) => {}
+                Ok(false) => {
+                    if sender.is_closed() {
+                        break;
+                    }
+                    continue;
+                }
+                Err(err) => {
+                    if sender
+                        .send(AppEvent::Error(format!("Input error: {}", err)))
+                        .is_err()
+                    {
+                        break;
+                    }
+                    continue;
+                }
+            }
             match crossterm::event::read() {
                 Ok(Event::Resize(w, h)) => {
                     if sender.send(AppEvent::Resize(w, h)).is_err() {
@@ -417,6 +535,7 @@ pub fn spawn_event_listener(sender: mpsc::UnboundedSender<AppEvent>) {
         }
 
         // Reset the flag when the event listener exits
+        EVENT_LISTENER_IDLE.store(false, Ordering::SeqCst);
         EVENT_LISTENER_SPAWNED.store(false, Ordering::SeqCst);
         tracing::debug!("Terminal event listener stopped");
     });
@@ -425,6 +544,7 @@ pub fn spawn_event_listener(sender: mpsc::UnboundedSender<AppEvent>) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crossterm::event::{KeyCode, KeyModifiers};
 
     #[test]
     fn test_event_handler_creation() {
@@ -459,6 +579,24 @@ mod tests {
         // Test navigation
         let up_key = KeyEvent::new(KeyCode::Char('k'), KeyModifiers::NONE);
         assert_eq!(handler.handle_key_event(up_key), KeyAction::MoveUp);
+
+        // Test search match navigation, with and without reported Shift
+        let next_key = KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE);
+        assert_eq!(handler.handle_key_event(next_key), KeyAction::NextMatch);
+        let previous_key = KeyEvent::new(KeyCode::Char('N'), KeyModifiers::SHIFT);
+        assert_eq!(
+            handler.handle_key_event(previous_key),
+            KeyAction::PreviousMatch
+        );
+
+        // Test notification keys
+        let history_key = KeyEvent::new(KeyCode::Char('e'), KeyModifiers::NONE);
+        assert_eq!(
+            handler.handle_key_event(history_key),
+            KeyAction::ToggleToastHistory
+        );
+        let action_key = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE);
+        assert_eq!(handler.handle_key_event(action_key), KeyAction::ToastAction);
     }
 
     #[test]
@@ -504,4 +642,89 @@ mod tests {
         let event = handler.next_event().await;
         assert_eq!(event, Some(AppEvent::Quit));
     }
+
+    #[test]
+    fn test_mouse_events_map_to_pane_actions() {
+        use crate::layout::LayoutManager;
+        use crossterm::event::KeyModifiers;
+
+        let handler = EventHandler::new(Duration::from_millis(250));
+        let mut layouts = LayoutManager::default();
+        let layout = layouts.layout(Rect::new(0, 0, 100, 30)).clone();
+        let mouse = |kind, column, row| MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+
+        // The wheel scrolls whatever pane it is over
+        let chat = layout.chat_area;
+        assert_eq!(
+            handler.handle_mouse_event(
+                mouse(MouseEventKind::ScrollUp, chat.x + 2, chat.y + 2),
+                &layout
+            ),
+            MouseAction::Scroll {
+                pane: Pane::Chat,
+                action: KeyAction::MoveUp,
+                lines: MOUSE_SCROLL_LINES
+            }
+        );
+        let preview = layout.preview_area.unwrap();
+        assert_eq!(
+            handler.handle_mouse_event(
+                mouse(MouseEventKind::ScrollDown, preview.x + 1, preview.y + 1),
+                &layout
+            ),
+            MouseAction::Scroll {
+                pane: Pane::Preview,
+                action: KeyAction::MoveDown,
+                lines: MOUSE_SCROLL_LINES
+            }
+        );
+
+        // Clicks report the row inside the border
+        let left = MouseEventKind::Down(MouseButton::Left);
+        let input = layout.input_area;
+        assert_eq!(
+            handler.handle_mouse_event(mouse(left, input.x + 5, input.y + 1), &layout),
+            MouseAction::Click {
+                pane: Pane::Input,
+                row: Some(0)
+            }
+        );
+        assert_eq!(
+            handler.handle_mouse_event(mouse(left, preview.x + 3, preview.y), &layout),
+            MouseAction::Click {
+                pane: Pane::Preview,
+                row: None
+            }
+        );
+
+        // The status bar, other buttons and moves are ignored
+        let status = layout.status_area.unwrap();
+        assert_eq!(
+            handler.handle_mouse_event(mouse(left, status.x, status.y), &layout),
+            MouseAction::None
+        );
+        assert_eq!(
+            handler.handle_mouse_event(
+                mouse(
+                    MouseEventKind::Down(MouseButton::Right),
+                    chat.x + 2,
+                    chat.y + 2
+                ),
+                &layout
+            ),
+            MouseAction::None
+        );
+        assert_eq!(
+            handler.handle_mouse_event(
+                mouse(MouseEventKind::Moved, chat.x + 2, chat.y + 2),
+                &layout
+            ),
+            MouseAction::None
+        );
+    }
 }
@@ -1,13 +1,25 @@
+use crate::theme::{ComponentType, ThemeManager};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use fennec_commands::git_integration::stream_status;
+use fennec_commands::GitFileStatus;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
-    style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, List, ListItem, ListState, StatefulWidget},
 };
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::debug;
+
+/// Quiet period after a file change before git status is refreshed
+const STATUS_REFRESH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+type StatusBatch = Vec<(PathBuf, GitFileStatus)>;
 
 /// Represents a node in the file tree
 #[derive(Debug, Clone)]
@@ -43,7 +55,9 @@ impl FileNode {
         })
     }
 
-    /// Load children for this directory node
+    /// Load children for this directory node, replacing any loaded before.
+    /// Ignored directories are loaded too; the browser decides whether to
+    /// show them.
     pub fn load_children(&mut self, show_hidden: bool, max_depth: usize) -> std::io::Result<()> {
         if !self.is_dir || self.depth >= max_depth {
             return Ok(());
@@ -65,13 +79,13 @@ impl FileNode {
             }
         });
 
+        self.children.clear();
         for entry in entries {
             let path = entry.path();
 
-            // Skip ignored directories
             if let Some(name) = path.file_name() {
                 let name_str = name.to_string_lossy();
-                if should_ignore(&name_str) {
+                if name_str == ".git" {
                     continue;
                 }
 
@@ -88,6 +102,22 @@ impl FileNode {
         Ok(())
     }
 
+    /// Load children of this node and all its descendant directories,
+    /// skipping ignored directories unless `include_ignored` is set
+    fn load_all(&mut self, show_hidden: bool, include_ignored: bool, max_depth: usize) {
+        if !self.is_dir {
+            return;
+        }
+        if self.children.is_empty() {
+            let _ = self.load_children(show_hidden, max_depth);
+        }
+        for child in &mut self.children {
+            if include_ignored || !should_ignore(&child.name) {
+                child.load_all(show_hidden, include_ignored, max_depth);
+            }
+        }
+    }
+
     /// Get total number of visible nodes (including descendants)
     pub fn count_visible(&self, expanded: &HashSet<PathBuf>) -> usize {
         let mut count = 1; // Count self
@@ -103,14 +133,33 @@ impl FileNode {
 }
 
 /// File tree browser component
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct FileTreeBrowser {
     root: FileNode,
     expanded: HashSet<PathBuf>,
     selected_index: usize,
     show_hidden: bool,
+    show_ignored: bool,
     max_depth: usize,
     list_state: ListState,
+    /// Paths containing this text (case-insensitively) are shown, with
+    /// their ancestors
+    filter: String,
+    /// Whether keys go to the filter box
+    filtering: bool,
+    /// Root as git reports it, for mapping status paths into the tree
+    canonical_root: PathBuf,
+    /// Git status of paths with one
+    git_status: HashMap<PathBuf, GitFileStatus>,
+    /// Most significant status below each directory
+    dir_status: HashMap<PathBuf, GitFileStatus>,
+    /// Paths reported by the refresh in progress; the rest are dropped when
+    /// it completes
+    status_seen: HashSet<PathBuf>,
+    status_batches: Option<mpsc::UnboundedReceiver<StatusBatch>>,
+    refresh_due: Option<Instant>,
+    watcher: Option<RecommendedWatcher>,
+    changes: Option<mpsc::UnboundedReceiver<()>>,
 }
 
 impl FileTreeBrowser {
@@ -118,6 +167,7 @@ impl FileTreeBrowser {
     pub fn new(root_path: PathBuf) -> std::io::Result<Self> {
         let mut root = FileNode::new(root_path.clone(), 0)?;
         root.load_children(false, 10)?;
+        let canonical_root = fs::canonicalize(&root_path).unwrap_or_else(|_| root_path.clone());
 
         let mut expanded = HashSet::new();
         expanded.insert(root_path); // Root is always expanded
@@ -127,8 +177,19 @@ impl FileTreeBrowser {
             expanded,
             selected_index: 0,
             show_hidden: false,
+            show_ignored: false,
             max_depth: 10,
             list_state: ListState::default(),
+            filter: String::new(),
+            filtering: false,
+            canonical_root,
+            git_status: HashMap::new(),
+            dir_status: HashMap::new(),
+            status_seen: HashSet::new(),
+            status_batches: None,
+            refresh_due: None,
+            watcher: None,
+            changes: None,
         })
     }
 
@@ -154,13 +215,16 @@ impl FileTreeBrowser {
                 }
             }
         }
+        self.clamp_selection();
     }
 
     /// Toggle showing hidden files
     pub fn toggle_hidden(&mut self) {
         self.show_hidden = !self.show_hidden;
-        // Reload all expanded nodes
-        let expanded_paths: Vec<_> = self.expanded.iter().cloned().collect();
+        // Reload all expanded nodes, parents before children so reloading a
+        // parent doesn't discard a child's fresh listing
+        let mut expanded_paths: Vec<_> = self.expanded.iter().cloned().collect();
+        expanded_paths.sort_by_key(|path| path.components().count());
         let show_hidden = self.show_hidden;
         let max_depth = self.max_depth;
 
@@ -169,6 +233,86 @@ impl FileTreeBrowser {
                 let _ = node.load_children(show_hidden, max_depth);
             }
         }
+        if !self.filter.is_empty() {
+            self.root
+                .load_all(self.show_hidden, self.show_ignored, self.max_depth);
+        }
+        self.clamp_selection();
+    }
+
+    /// Toggle showing ignored files: build output and dependency directories
+    /// as well as anything git ignores
+    pub fn toggle_ignored(&mut self) {
+        self.show_ignored = !self.show_ignored;
+        if self.show_ignored && !self.filter.is_empty() {
+            self.root
+                .load_all(self.show_hidden, self.show_ignored, self.max_depth);
+        }
+        self.clamp_selection();
+    }
+
+    pub fn filter(&self) -> &str {
+        &self.filter
+    }
+
+    /// Narrow the tree to paths containing `filter`. Directories not loaded
+    /// yet are loaded so the whole tree can be searched.
+    pub fn set_filter(&mut self, filter: &str) {
+        if filter == self.filter {
+            return;
+        }
+        if self.filter.is_empty() && !filter.is_empty() {
+            self.root
+                .load_all(self.show_hidden, self.show_ignored, self.max_depth);
+        }
+        self.filter = filter.to_string();
+        self.selected_index = 0;
+        self.clamp_selection();
+    }
+
+    /// Handle a key press. Returns whether the browser used it.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            return false;
+        }
+
+        if self.filtering {
+            match key.code {
+                KeyCode::Char(c) => {
+                    let filter = format!("{}{}", self.filter, c);
+                    self.set_filter(&filter);
+                }
+                KeyCode::Backspace => {
+                    let mut filter = self.filter.clone();
+                    filter.pop();
+                    self.set_filter(&filter);
+                }
+                KeyCode::Enter => self.filtering = false,
+                KeyCode::Esc => {
+                    self.filtering = false;
+                    self.set_filter("");
+                }
+                _ => return false,
+            }
+            return true;
+        }
+
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => self.move_down(),
+            KeyCode::Up | KeyCode::Char('k') => self.move_up(),
+            KeyCode::Home | KeyCode::Char('g') => self.move_to_top(),
+            KeyCode::End | KeyCode::Char('G') => self.move_to_bottom(),
+            KeyCode::Enter | KeyCode::Char(' ') | KeyCode::Char('l') => self.toggle_expand(),
+            KeyCode::Char('/') => self.filtering = true,
+            KeyCode::Char('.') => self.toggle_hidden(),
+            KeyCode::Char('i') => self.toggle_ignored(),
+            KeyCode::Esc if !self.filter.is_empty() => self.set_filter(""),
+            _ => return false,
+        }
+        true
     }
 
     /// Move selection up
@@ -180,7 +324,7 @@ impl FileTreeBrowser {
 
     /// Move selection down
     pub fn move_down(&mut self) {
-        let total = self.root.count_visible(&self.expanded);
+        let total = self.visible_rows().len();
         if self.selected_index < total.saturating_sub(1) {
             self.selected_index += 1;
         }
@@ -193,44 +337,257 @@ impl FileTreeBrowser {
 
     /// Move to last item
     pub fn move_to_bottom(&mut self) {
-        let total = self.root.count_visible(&self.expanded);
+        let total = self.visible_rows().len();
         self.selected_index = total.saturating_sub(1);
     }
 
+    /// Select the node shown on `row` of the list, counted from the first
+    /// row inside the border. Returns whether a node is there.
+    pub fn select_row(&mut self, row: u16) -> bool {
+        let index = self.list_state.offset() + row as usize;
+        if index >= self.visible_rows().len() {
+            return false;
+        }
+        self.selected_index = index;
+        true
+    }
+
     /// Get the currently selected path
     pub fn get_selected_path(&self) -> Option<PathBuf> {
         self.get_node_at_index(self.selected_index)
             .map(|node| node.path.clone())
     }
 
-    /// Get node at specific index (considering expanded state)
-    fn get_node_at_index(&self, index: usize) -> Option<&FileNode> {
-        let mut current_index = 0;
-        self.find_node_at_index(&self.root, index, &mut current_index)
-    }
-
-    /// Recursive helper to find node at index
-    fn find_node_at_index<'a>(
-        &self,
-        node: &'a FileNode,
-        target_index: usize,
-        current_index: &mut usize,
-    ) -> Option<&'a FileNode> {
-        if *current_index == target_index {
-            return Some(node);
+    /// Paths of the rows currently shown, in display order
+    pub fn visible_paths(&self) -> Vec<PathBuf> {
+        self.visible_rows()
+            .into_iter()
+            .map(|node| node.path.clone())
+            .collect()
+    }
+
+    /// Git status shown for `path`: its own, the most significant one below
+    /// it for a directory, or ignored when inside an ignored directory
+    pub fn git_status(&self, path: &Path) -> Option<GitFileStatus> {
+        if let Some(status) = self.git_status.get(path) {
+            return Some(*status);
+        }
+        if let Some(status) = self.dir_status.get(path) {
+            return Some(*status);
         }
+        path.ancestors()
+            .skip(1)
+            .take_while(|ancestor| ancestor.starts_with(&self.root.path))
+            .any(|ancestor| self.git_status.get(ancestor) == Some(&GitFileStatus::Ignored))
+            .then_some(GitFileStatus::Ignored)
+    }
 
-        *current_index += 1;
+    /// Watch the tree for changes, refreshing git status once they settle.
+    /// Changes are picked up by [`poll_git_status`](Self::poll_git_status).
+    pub fn watch(&mut self) -> notify::Result<()> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            let Ok(event) = res else {
+                return;
+            };
+            // Inside .git only the index and HEAD change what status shows
+            let relevant = event.paths.iter().any(|path| {
+                !path.components().any(|c| c.as_os_str() == ".git")
+                    || path.ends_with(".git/index")
+                    || path.ends_with(".git/HEAD")
+            });
+            if relevant {
+                let _ = sender.send(());
+            }
+        })?;
+        watcher.watch(&self.root.path, RecursiveMode::Recursive)?;
+        self.watcher = Some(watcher);
+        self.changes = Some(receiver);
+        Ok(())
+    }
 
-        if node.is_dir && self.expanded.contains(&node.path) {
-            for child in &node.children {
-                if let Some(found) = self.find_node_at_index(child, target_index, current_index) {
-                    return Some(found);
+    /// Note that files changed; git status is refreshed once no further
+    /// change arrives for the debounce interval
+    pub fn notify_changed(&mut self) {
+        self.refresh_due = Some(Instant::now() + STATUS_REFRESH_DEBOUNCE);
+    }
+
+    /// Start gathering git status in the background. A refresh already in
+    /// progress is followed by another once it completes.
+    pub fn refresh_git_status(&mut self) {
+        if self.status_batches.is_some() {
+            self.refresh_due = Some(Instant::now());
+            return;
+        }
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let root = self.root.path.clone();
+        tokio::spawn(async move {
+            if let Err(e) = stream_status(&root, true, sender).await {
+                debug!("No git status for {}: {}", root.display(), e);
+            }
+        });
+        self.status_seen.clear();
+        self.status_batches = Some(receiver);
+        self.refresh_due = None;
+    }
+
+    /// Apply git status gathered so far and start any refresh that is due,
+    /// without blocking. Returns whether decorations changed.
+    pub fn poll_git_status(&mut self) -> bool {
+        if let Some(changes) = self.changes.as_mut() {
+            let mut changed = false;
+            while changes.try_recv().is_ok() {
+                changed = true;
+            }
+            if changed {
+                self.notify_changed();
+            }
+        }
+
+        let mut updated = false;
+        if let Some(batches) = self.status_batches.as_mut() {
+            let mut received = Vec::new();
+            let finished = loop {
+                match batches.try_recv() {
+                    Ok(batch) => received.push(batch),
+                    Err(mpsc::error::TryRecvError::Empty) => break false,
+                    Err(mpsc::error::TryRecvError::Disconnected) => break true,
                 }
+            };
+            updated = !received.is_empty() || finished;
+            for batch in received {
+                self.apply_status_batch(batch);
+            }
+            if finished {
+                self.finish_status_refresh();
             }
         }
 
-        None
+        if self
+            .refresh_due
+            .is_some_and(|due| due <= Instant::now() && self.status_batches.is_none())
+        {
+            self.refresh_git_status();
+        }
+        updated
+    }
+
+    /// Wait until the refresh in progress has been fully applied
+    pub async fn wait_for_git_status(&mut self) {
+        while let Some(batches) = self.status_batches.as_mut() {
+            match batches.recv().await {
+                Some(batch) => self.apply_status_batch(batch),
+                None => self.finish_status_refresh(),
+            }
+        }
+    }
+
+    fn apply_status_batch(&mut self, batch: StatusBatch) {
+        for (path, status) in batch {
+            let Ok(relative) = path.strip_prefix(&self.canonical_root) else {
+                continue;
+            };
+            let path: PathBuf = self.root.path.join(relative).components().collect();
+            if status != GitFileStatus::Ignored {
+                self.mark_ancestors(&path, status);
+            }
+            self.status_seen.insert(path.clone());
+            self.git_status.insert(path, status);
+        }
+    }
+
+    fn mark_ancestors(&mut self, path: &Path, status: GitFileStatus) {
+        for ancestor in path.ancestors().skip(1) {
+            if !ancestor.starts_with(&self.root.path) {
+                break;
+            }
+            let entry = self
+                .dir_status
+                .entry(ancestor.to_path_buf())
+                .or_insert(status);
+            *entry = (*entry).max(status);
+        }
+    }
+
+    fn finish_status_refresh(&mut self) {
+        self.status_batches = None;
+        let seen = std::mem::take(&mut self.status_seen);
+        self.git_status.retain(|path, _| seen.contains(path));
+
+        self.dir_status.clear();
+        let statuses: Vec<_> = self
+            .git_status
+            .iter()
+            .filter(|(_, status)| **status != GitFileStatus::Ignored)
+            .map(|(path, status)| (path.clone(), *status))
+            .collect();
+        for (path, status) in statuses {
+            self.mark_ancestors(&path, status);
+        }
+    }
+
+    /// Nodes shown, in display order
+    fn visible_rows(&self) -> Vec<&FileNode> {
+        let mut rows = Vec::new();
+        self.collect_rows(&self.root, &mut rows);
+        rows
+    }
+
+    /// Push `node` and its shown descendants. Returns whether `node` is
+    /// shown.
+    fn collect_rows<'a>(&self, node: &'a FileNode, rows: &mut Vec<&'a FileNode>) -> bool {
+        let is_root = node.depth == 0;
+        if !is_root && !self.show_ignored && self.is_ignored(node) {
+            return false;
+        }
+
+        let start = rows.len();
+        rows.push(node);
+        let mut child_shown = false;
+        if node.is_dir && self.is_open(node) {
+            for child in &node.children {
+                child_shown |= self.collect_rows(child, rows);
+            }
+        }
+
+        if !is_root && !self.filter.is_empty() && !child_shown && !self.matches_filter(node) {
+            rows.truncate(start);
+            return false;
+        }
+        true
+    }
+
+    /// Whether a directory's children are listed: while filtering every
+    /// directory is searched
+    fn is_open(&self, node: &FileNode) -> bool {
+        !self.filter.is_empty() || self.expanded.contains(&node.path)
+    }
+
+    fn is_ignored(&self, node: &FileNode) -> bool {
+        should_ignore(&node.name)
+            || self.git_status.get(&node.path) == Some(&GitFileStatus::Ignored)
+    }
+
+    fn matches_filter(&self, node: &FileNode) -> bool {
+        let relative = node
+            .path
+            .strip_prefix(&self.root.path)
+            .unwrap_or(&node.path);
+        relative
+            .to_string_lossy()
+            .to_lowercase()
+            .contains(&self.filter.to_lowercase())
+    }
+
+    fn clamp_selection(&mut self) {
+        let total = self.visible_rows().len();
+        self.selected_index = self.selected_index.min(total.saturating_sub(1));
+    }
+
+    /// Get node at specific index (considering expanded state and filters)
+    fn get_node_at_index(&self, index: usize) -> Option<&FileNode> {
+        self.visible_rows().get(index).copied()
     }
 
     /// Get mutable reference to node at path
@@ -257,44 +614,53 @@ impl FileTreeBrowser {
     }
 
     /// Render the file tree
-    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, theme: &ThemeManager) {
         // Build title
-        let title = format!(
-            " Files {} ",
+        let mut title = format!(
+            " Files {}",
             if self.show_hidden {
-                "(showing hidden)"
+                "(showing hidden) "
             } else {
                 ""
             }
         );
+        if self.show_ignored {
+            title.push_str("(showing ignored) ");
+        }
+        if self.filtering || !self.filter.is_empty() {
+            title.push_str(&format!(
+                "/{}{} ",
+                self.filter,
+                if self.filtering { "▏" } else { "" }
+            ));
+        }
 
-        // Build items without borrowing self mutably
-        let mut items = Vec::new();
-        let selected_index = self.selected_index;
-        let expanded = &self.expanded;
-        Self::collect_items_helper(&self.root, &mut items, 0, selected_index, expanded);
+        let items: Vec<ListItem> = self
+            .visible_rows()
+            .into_iter()
+            .enumerate()
+            .map(|(index, node)| self.item(node, index == self.selected_index, theme))
+            .collect();
 
         // Update list state to show selection
         self.list_state.select(Some(self.selected_index));
 
-        let list = List::new(items).block(Block::default().borders(Borders::ALL).title(title));
+        let list = List::new(items).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(theme.get_style(ComponentType::Border))
+                .title(Span::styled(title, theme.get_style(ComponentType::Title))),
+        );
 
         StatefulWidget::render(list, area, buf, &mut self.list_state);
     }
 
-    /// Helper to collect items without mutable borrow
-    fn collect_items_helper(
-        node: &FileNode,
-        items: &mut Vec<ListItem>,
-        index: usize,
-        selected_index: usize,
-        expanded: &HashSet<PathBuf>,
-    ) {
-        // Build the display line
+    /// Build the display line of one node
+    fn item(&self, node: &FileNode, selected: bool, theme: &ThemeManager) -> ListItem<'static> {
         let indent = "  ".repeat(node.depth);
 
         let icon = if node.is_dir {
-            if expanded.contains(&node.path) {
+            if self.is_open(node) {
                 "📂"
             } else {
                 "📁"
@@ -303,32 +669,59 @@ impl FileTreeBrowser {
             get_file_icon(&node.name)
         };
 
+        let status = self.git_status(&node.path);
         let display_name = format!("{}{} {}", indent, icon, node.name);
 
-        // Style based on selection and type
-        let style = if index == selected_index {
-            Style::default()
-                .fg(Color::Black)
-                .bg(Color::Cyan)
-                .add_modifier(Modifier::BOLD)
-        } else if node.is_dir {
-            Style::default()
-                .fg(Color::Blue)
-                .add_modifier(Modifier::BOLD)
+        // Style based on selection, git status and type
+        let base = if node.is_dir {
+            theme.get_style(ComponentType::Directory)
         } else {
-            Style::default().fg(Color::White)
+            theme.get_style(ComponentType::Text)
+        };
+        let style = if selected {
+            base.patch(theme.get_style(ComponentType::ListSelected))
+        } else if let Some(status) = status {
+            base.fg(theme.get_color(status_component(status)))
+        } else {
+            base
         };
 
-        items.push(ListItem::new(Line::from(Span::styled(display_name, style))));
-
-        // Add children if expanded
-        if node.is_dir && expanded.contains(&node.path) {
-            let mut child_index = index + 1;
-            for child in &node.children {
-                Self::collect_items_helper(child, items, child_index, selected_index, expanded);
-                child_index += child.count_visible(expanded);
-            }
+        let mut spans = vec![Span::styled(display_name, style)];
+        if let Some(status) = status {
+            // Directories summarize what's below them with a dot
+            let marker = if node.is_dir && status != GitFileStatus::Ignored {
+                "•"
+            } else {
+                status_marker(status)
+            };
+            spans.push(Span::styled(
+                format!(" {}", marker),
+                theme.get_style(status_component(status)),
+            ));
         }
+        ListItem::new(Line::from(spans))
+    }
+}
+
+/// Letter shown after a file with a git status
+fn status_marker(status: GitFileStatus) -> &'static str {
+    match status {
+        GitFileStatus::Modified => "M",
+        GitFileStatus::Staged => "S",
+        GitFileStatus::Untracked => "?",
+        GitFileStatus::Conflicted => "!",
+        GitFileStatus::Ignored => "I",
+    }
+}
+
+/// Theme component colouring a git status
+fn status_component(status: GitFileStatus) -> ComponentType {
+    match status {
+        GitFileStatus::Modified => ComponentType::Warning,
+        GitFileStatus::Staged => ComponentType::Success,
+        GitFileStatus::Untracked => ComponentType::Info,
+        GitFileStatus::Conflicted => ComponentType::Error,
+        GitFileStatus::Ignored => ComponentType::Muted,
     }
 }
 
@@ -366,6 +759,7 @@ fn should_ignore(name: &str) -> bool {
 mod tests {
     use super::*;
     use std::fs;
+    use std::process::Command;
     use tempfile::TempDir;
 
     #[test]
@@ -393,6 +787,10 @@ mod tests {
         node.load_children(false, 10).unwrap();
 
         assert_eq!(node.children.len(), 3);
+
+        // Reloading replaces the listing
+        node.load_children(false, 10).unwrap();
+        assert_eq!(node.children.len(), 3);
     }
 
     #[test]
@@ -442,6 +840,29 @@ mod tests {
         assert_eq!(browser.selected_index, 0);
     }
 
+    #[test]
+    fn test_select_row_follows_list_scroll() {
+        let temp_dir = TempDir::new().unwrap();
+        for i in 0..10 {
+            fs::write(temp_dir.path().join(format!("file{}.txt", i)), "test").unwrap();
+        }
+        let mut browser = FileTreeBrowser::new(temp_dir.path().to_path_buf()).unwrap();
+        let theme = ThemeManager::new();
+
+        // Five rows in view with the last node selected scrolls the list
+        let area = Rect::new(0, 0, 40, 7);
+        browser.move_to_bottom();
+        browser.render(area, &mut Buffer::empty(area), &theme);
+
+        assert!(browser.select_row(0));
+        let paths = browser.visible_paths();
+        assert_eq!(
+            browser.get_selected_path(),
+            Some(paths[paths.len() - 5].clone())
+        );
+        assert!(!browser.select_row(5));
+    }
+
     #[test]
     fn test_get_selected_path() {
         let temp_dir = TempDir::new().unwrap();
@@ -451,4 +872,188 @@ mod tests {
         assert!(selected.is_some());
         assert_eq!(selected.unwrap(), temp_dir.path());
     }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    /// Visible paths relative to the root, which shows as ""
+    fn shown(browser: &FileTreeBrowser, root: &Path) -> Vec<String> {
+        browser
+            .visible_paths()
+            .iter()
+            .map(|path| path.strip_prefix(root).unwrap().display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_filter_keeps_ancestors_of_matches() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src/parser")).unwrap();
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::create_dir_all(root.join("target/debug")).unwrap();
+        fs::write(root.join("src/parser/lexer.rs"), "").unwrap();
+        fs::write(root.join("src/main.rs"), "").unwrap();
+        fs::write(root.join("docs/Lexer.md"), "").unwrap();
+        fs::write(root.join("target/debug/lexer.d"), "").unwrap();
+        fs::write(root.join("README.md"), "").unwrap();
+
+        let mut browser = FileTreeBrowser::new(root.to_path_buf()).unwrap();
+        assert_eq!(shown(&browser, root), ["", "docs", "src", "README.md"]);
+
+        browser.handle_key(key(KeyCode::Char('/')));
+        for c in "lexer".chars() {
+            browser.handle_key(key(KeyCode::Char(c)));
+        }
+        assert_eq!(
+            shown(&browser, root),
+            [
+                "",
+                "docs",
+                "docs/Lexer.md",
+                "src",
+                "src/parser",
+                "src/parser/lexer.rs"
+            ]
+        );
+
+        // Ignored directories only match once shown
+        browser.handle_key(key(KeyCode::Enter));
+        browser.handle_key(key(KeyCode::Char('i')));
+        assert!(shown(&browser, root).contains(&"target/debug/lexer.d".to_string()));
+        browser.handle_key(key(KeyCode::Char('i')));
+
+        // A matching directory shows everything below it
+        browser.set_filter("parser");
+        assert_eq!(
+            shown(&browser, root),
+            ["", "src", "src/parser", "src/parser/lexer.rs"]
+        );
+
+        browser.set_filter("nothing");
+        assert_eq!(shown(&browser, root), [""]);
+        assert_eq!(browser.get_selected_path().unwrap(), root);
+
+        browser.handle_key(key(KeyCode::Esc));
+        assert_eq!(browser.filter(), "");
+        assert_eq!(shown(&browser, root), ["", "docs", "src", "README.md"]);
+    }
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .current_dir(dir)
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(
+            status.status.success() || args[0] == "merge",
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&status.stderr)
+        );
+    }
+
+    /// Repository with one path in each status
+    fn repo_fixture() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        git(root, &["init", "-q"]);
+        fs::create_dir(root.join("src")).unwrap();
+        fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        fs::write(root.join("src/lib.rs"), "fn a() {}\n").unwrap();
+        fs::write(root.join("staged.txt"), "one\n").unwrap();
+        fs::write(root.join("conflict.txt"), "base\n").unwrap();
+        git(root, &["add", "."]);
+        git(root, &["commit", "-q", "-m", "base"]);
+
+        // Conflicting edits on two branches
+        git(root, &["checkout", "-q", "-b", "other"]);
+        fs::write(root.join("conflict.txt"), "other\n").unwrap();
+        git(root, &["commit", "-q", "-am", "other"]);
+        git(root, &["checkout", "-q", "-"]);
+        fs::write(root.join("conflict.txt"), "main\n").unwrap();
+        git(root, &["commit", "-q", "-am", "main"]);
+        git(root, &["merge", "-q", "other"]);
+
+        fs::write(root.join("src/lib.rs"), "fn b() {}\n").unwrap();
+        fs::write(root.join("staged.txt"), "two\n").unwrap();
+        git(root, &["add", "staged.txt"]);
+        fs::write(root.join("untracked.txt"), "new\n").unwrap();
+        fs::write(root.join("debug.log"), "noise\n").unwrap();
+        temp_dir
+    }
+
+    #[tokio::test]
+    async fn test_git_status_decorations() {
+        let temp_dir = repo_fixture();
+        let root = temp_dir.path();
+        let mut browser = FileTreeBrowser::new(root.to_path_buf()).unwrap();
+
+        browser.refresh_git_status();
+        browser.wait_for_git_status().await;
+
+        let status = |path: &str| browser.git_status(&root.join(path));
+        assert_eq!(status("src/lib.rs"), Some(GitFileStatus::Modified));
+        assert_eq!(status("staged.txt"), Some(GitFileStatus::Staged));
+        assert_eq!(status("untracked.txt"), Some(GitFileStatus::Untracked));
+        assert_eq!(status("conflict.txt"), Some(GitFileStatus::Conflicted));
+        assert_eq!(status("debug.log"), Some(GitFileStatus::Ignored));
+        assert_eq!(status(".gitignore"), None);
+        // Directories take the most significant status below them
+        assert_eq!(status("src"), Some(GitFileStatus::Modified));
+        assert_eq!(status(""), Some(GitFileStatus::Conflicted));
+
+        // Ignored files are hidden until toggled on
+        assert!(!shown(&browser, root).contains(&"debug.log".to_string()));
+        browser.toggle_ignored();
+        assert!(shown(&browser, root).contains(&"debug.log".to_string()));
+
+        // A refresh drops statuses that went away
+        git(root, &["add", "src/lib.rs"]);
+        fs::remove_file(root.join("untracked.txt")).unwrap();
+        browser.refresh_git_status();
+        browser.wait_for_git_status().await;
+        assert_eq!(
+            browser.git_status(&root.join("src/lib.rs")),
+            Some(GitFileStatus::Staged)
+        );
+        assert_eq!(browser.git_status(&root.join("untracked.txt")), None);
+        assert_eq!(
+            browser.git_status(&root.join("src")),
+            Some(GitFileStatus::Staged)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_changes_refresh_after_debounce() {
+        let temp_dir = repo_fixture();
+        let root = temp_dir.path();
+        let mut browser = FileTreeBrowser::new(root.to_path_buf()).unwrap();
+
+        browser.notify_changed();
+        assert!(!browser.poll_git_status());
+        assert!(browser.status_batches.is_none());
+
+        tokio::time::sleep(STATUS_REFRESH_DEBOUNCE).await;
+        browser.poll_git_status();
+        assert!(browser.status_batches.is_some());
+        browser.wait_for_git_status().await;
+        assert_eq!(
+            browser.git_status(&root.join("untracked.txt")),
+            Some(GitFileStatus::Untracked)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_outside_a_repository_has_no_status() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("file.txt"), "").unwrap();
+        let mut browser = FileTreeBrowser::new(temp_dir.path().to_path_buf()).unwrap();
+
+        browser.refresh_git_status();
+        browser.wait_for_git_status().await;
+        assert_eq!(browser.git_status(&temp_dir.path().join("file.txt")), None);
+    }
 }
@@ -0,0 +1,630 @@
+//! Multi-line prompt editing.
+//!
+//! [`InputEditor`] holds the prompt being written, soft wrapped to the width
+//! of the input box, which grows with it up to [`MAX_INPUT_LINES`]. Enter
+//! sends and Shift+Enter starts a new line (Alt+Enter too, for terminals
+//! that can't tell Shift+Enter apart); both are key bindings, so
+//! `keybindings.toml` can swap them. Pasted text arrives as one bracketed
+//! paste and is inserted whole, line breaks included.
+//!
+//! [`PromptHistory`] recalls the prompts sent earlier in a session, and an
+//! [`ExternalEditor`] such as `$EDITOR` can take over for long prompts.
+
+use crate::error::{Result, TuiError};
+use crate::events::InputMode;
+use crate::theme::{ComponentType, ThemeManager};
+use async_trait::async_trait;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Paragraph, Widget},
+};
+use std::path::Path;
+use tracing::debug;
+use uuid::Uuid;
+
+/// Most lines of text the input box grows to before it scrolls
+pub const MAX_INPUT_LINES: u16 = 10;
+
+/// Prompts kept in a session's history
+const HISTORY_LIMIT: usize = 100;
+
+/// Editor used when neither `$VISUAL` nor `$EDITOR` is set
+const FALLBACK_EDITOR: &str = "vi";
+
+/// Multi-line text input with a cursor
+#[derive(Debug, Clone)]
+pub struct InputEditor {
+    text: String,
+    /// Byte offset of the cursor, always on a character boundary
+    cursor: usize,
+    /// Column kept while moving through shorter lines
+    goal_column: Option<usize>,
+    placeholder: String,
+}
+
+impl Default for InputEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InputEditor {
+    pub fn new() -> Self {
+        Self {
+            text: String::new(),
+            cursor: 0,
+            goal_column: None,
+            placeholder: "Type your message...".to_string(),
+        }
+    }
+
+    pub fn with_placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    pub fn content(&self) -> &str {
+        &self.text
+    }
+
+    /// Replace the text, with the cursor at its end
+    pub fn set_content(&mut self, content: impl Into<String>) {
+        self.text = normalize_line_breaks(&content.into());
+        self.cursor = self.text.len();
+        self.goal_column = None;
+    }
+
+    pub fn clear(&mut self) {
+        self.set_content(String::new());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+        self.goal_column = None;
+    }
+
+    pub fn insert_newline(&mut self) {
+        self.insert_char('\n');
+    }
+
+    /// Insert `text` at the cursor, e.g. a paste, keeping its line breaks
+    pub fn insert_str(&mut self, text: &str) {
+        let text = normalize_line_breaks(text);
+        self.text.insert_str(self.cursor, &text);
+        self.cursor += text.len();
+        self.goal_column = None;
+    }
+
+    /// Delete the character before the cursor, joining lines at a line start
+    pub fn backspace(&mut self) {
+        if let Some(previous) = self.previous_boundary() {
+            self.text.drain(previous..self.cursor);
+            self.cursor = previous;
+            self.goal_column = None;
+        }
+    }
+
+    /// Delete the character at the cursor
+    pub fn delete(&mut self) {
+        if let Some(next) = self.next_boundary() {
+            self.text.drain(self.cursor..next);
+            self.goal_column = None;
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        if let Some(previous) = self.previous_boundary() {
+            self.cursor = previous;
+            self.goal_column = None;
+        }
+    }
+
+    pub fn move_right(&mut self) {
+        if let Some(next) = self.next_boundary() {
+            self.cursor = next;
+            self.goal_column = None;
+        }
+    }
+
+    pub fn move_to_start(&mut self) {
+        self.cursor = 0;
+        self.goal_column = None;
+    }
+
+    pub fn move_to_end(&mut self) {
+        self.cursor = self.text.len();
+        self.goal_column = None;
+    }
+
+    /// Move to the line above, keeping the column where it fits. Returns
+    /// false on the first line, where the cursor stays.
+    pub fn move_up(&mut self) -> bool {
+        let (line, column) = self.cursor_position();
+        if line == 0 {
+            return false;
+        }
+        self.move_to_line(line - 1, column);
+        true
+    }
+
+    /// Move to the line below, keeping the column where it fits. Returns
+    /// false on the last line, where the cursor stays.
+    pub fn move_down(&mut self) -> bool {
+        let (line, column) = self.cursor_position();
+        if line + 1 >= self.line_count() {
+            return false;
+        }
+        self.move_to_line(line + 1, column);
+        true
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.text.split('\n').count()
+    }
+
+    /// Line and column of the cursor, counted in characters from zero
+    pub fn cursor_position(&self) -> (usize, usize) {
+        let before = &self.text[..self.cursor];
+        let line = before.matches('\n').count();
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        (line, before[line_start..].chars().count())
+    }
+
+    fn move_to_line(&mut self, line: usize, column: usize) {
+        let goal = *self.goal_column.get_or_insert(column);
+        let line_start: usize = self
+            .text
+            .split('\n')
+            .take(line)
+            .map(|line| line.len() + 1)
+            .sum();
+        let line_text = self.text[line_start..].split('\n').next().unwrap_or("");
+        let offset = line_text
+            .char_indices()
+            .nth(goal)
+            .map_or(line_text.len(), |(i, _)| i);
+        self.cursor = line_start + offset;
+    }
+
+    fn previous_boundary(&self) -> Option<usize> {
+        self.text[..self.cursor]
+            .char_indices()
+            .next_back()
+            .map(|(i, _)| i)
+    }
+
+    fn next_boundary(&self) -> Option<usize> {
+        self.text[self.cursor..]
+            .chars()
+            .next()
+            .map(|c| self.cursor + c.len_utf8())
+    }
+
+    /// Rows of the text soft wrapped to `width` columns, with the row and
+    /// column the cursor is drawn at
+    pub fn wrap(&self, width: usize) -> (Vec<String>, (usize, usize)) {
+        let width = width.max(1);
+        let (cursor_line, cursor_column) = self.cursor_position();
+        let mut rows = Vec::new();
+        let mut cursor = (0, 0);
+
+        for (index, line) in self.text.split('\n').enumerate() {
+            let chars: Vec<char> = line.chars().collect();
+            let mut row_count = chars.len().div_ceil(width).max(1);
+            if index == cursor_line {
+                // A cursor after a full row goes to the start of the next
+                row_count = row_count.max(cursor_column / width + 1);
+                cursor = (rows.len() + cursor_column / width, cursor_column % width);
+            }
+            for row in 0..row_count {
+                let start = (row * width).min(chars.len());
+                let end = ((row + 1) * width).min(chars.len());
+                rows.push(chars[start..end].iter().collect());
+            }
+        }
+        (rows, cursor)
+    }
+
+    /// Height of the input box, borders included, for an area `width`
+    /// columns wide
+    pub fn height(&self, width: u16) -> u16 {
+        let (rows, _) = self.wrap(width.saturating_sub(2) as usize);
+        (rows.len() as u16).clamp(1, MAX_INPUT_LINES) + 2
+    }
+
+    /// Replace the text with what `editor` makes of it. The text is kept
+    /// when the editor fails.
+    pub async fn edit_with(&mut self, editor: &dyn ExternalEditor) -> Result<()> {
+        let edited = editor.edit(&self.text).await?;
+        self.set_content(edited);
+        Ok(())
+    }
+
+    pub fn render(&self, area: Rect, buf: &mut Buffer, theme: &ThemeManager, mode: InputMode) {
+        let (title, border_style) = match mode {
+            InputMode::Insert => ("Input (INSERT)", theme.get_style(ComponentType::Highlight)),
+            InputMode::Command => ("Command", theme.get_style(ComponentType::Warning)),
+            InputMode::Search => ("Search", theme.get_style(ComponentType::Info)),
+            InputMode::Normal => ("Input", theme.get_style(ComponentType::Border)),
+        };
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .title_style(theme.get_style(ComponentType::Title))
+            .border_style(border_style);
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if inner.width == 0 || inner.height == 0 {
+            return;
+        }
+
+        if self.text.is_empty() && mode == InputMode::Normal {
+            Paragraph::new(self.placeholder.as_str())
+                .style(theme.get_style(ComponentType::Muted))
+                .render(inner, buf);
+            return;
+        }
+
+        // Scroll just far enough to keep the cursor in view
+        let (rows, (cursor_row, cursor_column)) = self.wrap(inner.width as usize);
+        let scroll = (cursor_row + 1).saturating_sub(inner.height as usize);
+        let lines: Vec<Line> = rows
+            .into_iter()
+            .skip(scroll)
+            .take(inner.height as usize)
+            .map(Line::from)
+            .collect();
+        Paragraph::new(lines)
+            .style(theme.get_style(ComponentType::Text))
+            .render(inner, buf);
+
+        if mode != InputMode::Normal {
+            let cursor = Rect {
+                x: inner.x + cursor_column as u16,
+                y: inner.y + (cursor_row - scroll) as u16,
+                width: 1,
+                height: 1,
+            };
+            buf.set_style(cursor, Style::default().add_modifier(Modifier::REVERSED));
+        }
+    }
+}
+
+/// `\r\n` and lone `\r` as `\n`, as terminals and editors send either
+fn normalize_line_breaks(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+/// Prompts sent in a session, oldest first, browsed from the newest back
+#[derive(Debug, Clone, Default)]
+pub struct PromptHistory {
+    entries: Vec<String>,
+    /// Entry shown while browsing; `None` while writing a new prompt
+    position: Option<usize>,
+    /// What was being written when browsing started, given back after the
+    /// newest entry
+    draft: String,
+}
+
+impl PromptHistory {
+    /// History holding `prompts`, oldest first
+    pub fn from_prompts(prompts: impl IntoIterator<Item = String>) -> Self {
+        let mut history = Self::default();
+        for prompt in prompts {
+            history.push(prompt);
+        }
+        history
+    }
+
+    /// Record a sent prompt and stop browsing. Blank prompts and repeats of
+    /// the last one are not recorded.
+    pub fn push(&mut self, prompt: impl Into<String>) {
+        let prompt = prompt.into();
+        self.position = None;
+        self.draft.clear();
+        if prompt.trim().is_empty() || self.entries.last() == Some(&prompt) {
+            return;
+        }
+        self.entries.push(prompt);
+        if self.entries.len() > HISTORY_LIMIT {
+            self.entries.remove(0);
+        }
+    }
+
+    /// The prompt before the one shown, or `None` at the oldest. `current`
+    /// is the text being written, kept for when browsing comes back past
+    /// the newest entry.
+    pub fn older(&mut self, current: &str) -> Option<String> {
+        let position = match self.position {
+            None if self.entries.is_empty() => return None,
+            None => {
+                self.draft = current.to_string();
+                self.entries.len() - 1
+            }
+            Some(0) => return None,
+            Some(position) => position - 1,
+        };
+        self.position = Some(position);
+        Some(self.entries[position].clone())
+    }
+
+    /// The prompt after the one shown, then the text being written before
+    /// browsing started. `None` when not browsing.
+    pub fn newer(&mut self) -> Option<String> {
+        let position = self.position?;
+        if position + 1 < self.entries.len() {
+            self.position = Some(position + 1);
+            Some(self.entries[position + 1].clone())
+        } else {
+            self.position = None;
+            Some(std::mem::take(&mut self.draft))
+        }
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+}
+
+/// A program the prompt can be handed to for editing
+#[async_trait]
+pub trait ExternalEditor: Send + Sync {
+    /// Let the user edit `text`, returning it as saved
+    async fn edit(&self, text: &str) -> Result<String>;
+}
+
+/// The user's editor, run on a temporary file. The terminal must be handed
+/// over to it first.
+#[derive(Debug, Clone)]
+pub struct SystemEditor {
+    /// Program and arguments, e.g. `code --wait`
+    command: String,
+}
+
+impl SystemEditor {
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+        }
+    }
+
+    /// `$VISUAL`, then `$EDITOR`, then `vi`
+    pub fn from_env() -> Self {
+        let command = ["VISUAL", "EDITOR"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|command| !command.trim().is_empty())
+            .unwrap_or_else(|| FALLBACK_EDITOR.to_string());
+        Self::new(command)
+    }
+
+    async fn run(&self, path: &Path) -> Result<String> {
+        let mut words = self.command.split_whitespace();
+        let program = words.next().ok_or_else(|| TuiError::Generic {
+            message: "No editor is configured".to_string(),
+            context: Some("Set $VISUAL or $EDITOR".to_string()),
+        })?;
+        debug!("Opening {} in {}", path.display(), self.command);
+
+        let status = tokio::process::Command::new(program)
+            .args(words)
+            .arg(path)
+            .status()
+            .await
+            .map_err(|source| TuiError::Io {
+                operation: format!("start editor '{}'", program),
+                source,
+            })?;
+        if !status.success() {
+            return Err(TuiError::CommandExecution(
+                format!(
+                    "{} exited with {}; the prompt was not changed",
+                    program, status
+                )
+                .into(),
+            ));
+        }
+
+        let text = std::fs::read_to_string(path).map_err(|source| TuiError::Io {
+            operation: "read edited prompt".to_string(),
+            source,
+        })?;
+        // Editors end the file with a line break the prompt didn't have
+        let text = text.strip_suffix('\n').unwrap_or(&text);
+        Ok(text.strip_suffix('\r').unwrap_or(text).to_string())
+    }
+}
+
+#[async_trait]
+impl ExternalEditor for SystemEditor {
+    async fn edit(&self, text: &str) -> Result<String> {
+        let path = std::env::temp_dir().join(format!("fennec-prompt-{}.md", Uuid::new_v4()));
+        std::fs::write(&path, text).map_err(|source| TuiError::Io {
+            operation: "write prompt for editing".to_string(),
+            source,
+        })?;
+        let result = self.run(&path).await;
+        let _ = std::fs::remove_file(&path);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    fn editor(text: &str) -> InputEditor {
+        let mut editor = InputEditor::new();
+        editor.set_content(text);
+        editor
+    }
+
+    #[test]
+    fn test_editing_across_lines() {
+        let mut input = InputEditor::new();
+        for c in "fix it".chars() {
+            input.insert_char(c);
+        }
+        input.insert_newline();
+        input.insert_str("- add tests\r\n- café");
+        assert_eq!(input.content(), "fix it\n- add tests\n- café");
+        assert_eq!(input.cursor_position(), (2, 6));
+        assert_eq!(input.line_count(), 3);
+
+        // Multi-byte characters are removed whole
+        input.backspace();
+        input.insert_char('e');
+        assert_eq!(input.content(), "fix it\n- add tests\n- cafe");
+
+        // Backspace at a line start joins it to the line above
+        input.move_to_start();
+        assert!(input.move_down());
+        input.backspace();
+        assert_eq!(input.content(), "fix it- add tests\n- cafe");
+        assert_eq!(input.cursor_position(), (0, 6));
+
+        input.delete();
+        input.delete();
+        assert_eq!(input.content(), "fix itadd tests\n- cafe");
+
+        input.move_to_end();
+        input.move_right();
+        assert_eq!(input.cursor_position(), (1, 6));
+        input.clear();
+        assert!(input.is_empty());
+        input.backspace();
+        input.move_left();
+        assert_eq!(input.cursor_position(), (0, 0));
+    }
+
+    #[test]
+    fn test_up_and_down_keep_the_column() {
+        let mut input = editor("a long first line\nab\nanother long line");
+        assert_eq!(input.cursor_position(), (2, 17));
+        assert!(!input.move_down());
+
+        assert!(input.move_up());
+        assert_eq!(input.cursor_position(), (1, 2));
+        assert!(input.move_up());
+        assert_eq!(input.cursor_position(), (0, 17));
+        assert!(!input.move_up());
+        assert_eq!(input.cursor_position(), (0, 17));
+
+        // Moving sideways sets a new column
+        input.move_left();
+        assert!(input.move_down());
+        assert!(input.move_down());
+        assert_eq!(input.cursor_position(), (2, 16));
+    }
+
+    #[test]
+    fn test_soft_wrapping_and_height() {
+        let input = editor("abcdefgh\n\nxy");
+        let (rows, cursor) = input.wrap(3);
+        assert_eq!(rows, ["abc", "def", "gh", "", "xy"]);
+        assert_eq!(cursor, (4, 2));
+        assert_eq!(input.height(5), 7);
+
+        // A cursor after a full row starts the next one
+        let (rows, cursor) = editor("abcdef").wrap(3);
+        assert_eq!(rows, ["abc", "def", ""]);
+        assert_eq!(cursor, (2, 0));
+
+        assert_eq!(InputEditor::new().height(40), 3);
+        assert_eq!(editor(&"line\n".repeat(30)).height(40), MAX_INPUT_LINES + 2);
+    }
+
+    #[test]
+    fn test_history_navigation() {
+        let mut history = PromptHistory::default();
+        assert_eq!(history.older("draft"), None);
+        assert_eq!(history.newer(), None);
+
+        history.push("first");
+        history.push("second");
+        history.push("second");
+        history.push("  ");
+        history.push("third");
+        assert_eq!(history.entries(), ["first", "second", "third"]);
+
+        assert_eq!(history.older("half written").as_deref(), Some("third"));
+        assert_eq!(history.older("third").as_deref(), Some("second"));
+        assert_eq!(history.older("second").as_deref(), Some("first"));
+        assert_eq!(history.older("first"), None);
+        assert_eq!(history.newer().as_deref(), Some("second"));
+        assert_eq!(history.newer().as_deref(), Some("third"));
+        // Past the newest entry the draft comes back, then browsing stops
+        assert_eq!(history.newer().as_deref(), Some("half written"));
+        assert_eq!(history.newer(), None);
+
+        // Sending ends browsing
+        history.older("");
+        history.push("fourth");
+        assert_eq!(history.older("").as_deref(), Some("fourth"));
+    }
+
+    #[test]
+    fn test_history_is_capped() {
+        let history = PromptHistory::from_prompts((0..150).map(|i| format!("prompt {}", i)));
+        assert_eq!(history.entries().len(), HISTORY_LIMIT);
+        assert_eq!(history.entries()[0], "prompt 50");
+    }
+
+    /// Editor that records what it was given and answers from a script
+    struct FakeEditor {
+        reply: std::result::Result<String, String>,
+        seen: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl ExternalEditor for FakeEditor {
+        async fn edit(&self, text: &str) -> Result<String> {
+            self.seen.lock().unwrap().push(text.to_string());
+            self.reply.clone().map_err(|message| TuiError::Generic {
+                message,
+                context: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_external_editor_round_trip() {
+        let mut input = editor("draft");
+        let fake = FakeEditor {
+            reply: Ok("a longer\r\nprompt".to_string()),
+            seen: Mutex::new(Vec::new()),
+        };
+        input.edit_with(&fake).await.unwrap();
+        assert_eq!(fake.seen.lock().unwrap().as_slice(), ["draft"]);
+        assert_eq!(input.content(), "a longer\nprompt");
+        assert_eq!(input.cursor_position(), (1, 6));
+
+        let failing = FakeEditor {
+            reply: Err("editor crashed".to_string()),
+            seen: Mutex::new(Vec::new()),
+        };
+        assert!(input.edit_with(&failing).await.is_err());
+        assert_eq!(input.content(), "a longer\nprompt");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_system_editor_edits_a_temporary_file() {
+        // `sed -i` stands in for an interactive editor
+        let editor = SystemEditor::new("sed -i s/draft/prompt/");
+        assert_eq!(editor.edit("my draft").await.unwrap(), "my prompt");
+
+        let failing = SystemEditor::new("false");
+        assert!(failing.edit("my draft").await.is_err());
+    }
+}
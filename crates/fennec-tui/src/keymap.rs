@@ -0,0 +1,732 @@
+//! Key bindings: which [`KeyAction`] each key triggers in each input mode.
+//!
+//! Defaults are defined here and can be overridden from a
+//! `keybindings.toml` in the config directory, e.g.
+//!
+//! ```toml
+//! [global]
+//! open_command_palette = "ctrl+p"
+//!
+//! [normal]
+//! move_up = ["k", "up"]
+//! toggle_theme = []   # unbind
+//! ```
+//!
+//! Binding an action in a mode replaces its default keys in that mode, so
+//! making Enter start a new line and Ctrl+Enter send is
+//!
+//! ```toml
+//! [insert]
+//! send = "ctrl+enter"
+//! new_line = "enter"
+//! ```
+
+use crate::error::{Result, TuiError};
+use crate::events::{InputMode, KeyAction};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Name of the key bindings file in the config directory
+pub const KEYBINDINGS_FILE: &str = "keybindings.toml";
+
+/// Set of bindings a key is looked up in. Global bindings apply in every
+/// mode and take precedence over the mode's own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum KeymapMode {
+    Global,
+    Normal,
+    Insert,
+    Command,
+    Search,
+}
+
+impl KeymapMode {
+    const ALL: [KeymapMode; 5] = [
+        KeymapMode::Global,
+        KeymapMode::Normal,
+        KeymapMode::Insert,
+        KeymapMode::Command,
+        KeymapMode::Search,
+    ];
+
+    /// Section name in `keybindings.toml`
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeymapMode::Global => "global",
+            KeymapMode::Normal => "normal",
+            KeymapMode::Insert => "insert",
+            KeymapMode::Command => "command",
+            KeymapMode::Search => "search",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+}
+
+impl From<&InputMode> for KeymapMode {
+    fn from(mode: &InputMode) -> Self {
+        match mode {
+            InputMode::Normal => KeymapMode::Normal,
+            InputMode::Insert => KeymapMode::Insert,
+            InputMode::Command => KeymapMode::Command,
+            InputMode::Search => KeymapMode::Search,
+        }
+    }
+}
+
+impl fmt::Display for KeymapMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A key with its modifiers, e.g. `ctrl+p`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        // Shift is carried by the character itself, and terminals differ in
+        // whether they also report it
+        let modifiers = match code {
+            KeyCode::Char(_) | KeyCode::BackTab => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+        Self { code, modifiers }
+    }
+
+    fn key(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+
+    fn ctrl(c: char) -> Self {
+        Self::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    fn char(c: char) -> Self {
+        Self::key(KeyCode::Char(c))
+    }
+}
+
+impl From<KeyEvent> for KeyBinding {
+    fn from(key: KeyEvent) -> Self {
+        Self::new(key.code, key.modifiers)
+    }
+}
+
+impl FromStr for KeyBinding {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        // A trailing "+" after a separator is the plus key itself
+        let (modifier_names, key_name) = if s == "+" {
+            ("", s)
+        } else if let Some(modifiers) = s.strip_suffix("++") {
+            (modifiers, "+")
+        } else {
+            match s.rsplit_once('+') {
+                Some((_, "")) => return Err(format!("missing key in '{}'", s)),
+                Some((modifiers, key)) => (modifiers, key),
+                None => ("", s),
+            }
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for name in modifier_names.split('+').filter(|name| !name.is_empty()) {
+            modifiers |= match name.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" | "option" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                other => return Err(format!("unknown modifier '{}' in '{}'", other, s)),
+            };
+        }
+
+        let mut chars = key_name.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key_name.to_lowercase().as_str() {
+                "esc" | "escape" => KeyCode::Esc,
+                "enter" | "return" => KeyCode::Enter,
+                "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "space" => KeyCode::Char(' '),
+                name => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key '{}' in '{}'", key_name, s)),
+                },
+            },
+        };
+
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            f.write_str("Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            f.write_str("Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            f.write_str("Shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("Space"),
+            KeyCode::Char(c) if self.modifiers.contains(KeyModifiers::CONTROL) => {
+                write!(f, "{}", c.to_ascii_uppercase())
+            }
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::BackTab => f.write_str("Shift+Tab"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::PageUp => f.write_str("PgUp"),
+            KeyCode::PageDown => f.write_str("PgDn"),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+/// Why a binding in `keybindings.toml` doesn't do what it says
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeymapConflict {
+    /// The key was bound to two actions in the same mode; the later wins
+    Duplicate {
+        mode: KeymapMode,
+        key: KeyBinding,
+        replaced: KeyAction,
+        action: KeyAction,
+    },
+    /// The key is bound globally, so the mode's binding never fires
+    ShadowedByGlobal {
+        mode: KeymapMode,
+        key: KeyBinding,
+        action: KeyAction,
+        global: KeyAction,
+    },
+}
+
+impl fmt::Display for KeymapConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeymapConflict::Duplicate {
+                mode,
+                key,
+                replaced,
+                action,
+            } => write!(
+                f,
+                "{} is bound to both {} and {} in {} mode; using {}",
+                key,
+                replaced.name().unwrap_or_default(),
+                action.name().unwrap_or_default(),
+                mode,
+                action.name().unwrap_or_default()
+            ),
+            KeymapConflict::ShadowedByGlobal {
+                mode,
+                key,
+                action,
+                global,
+            } => write!(
+                f,
+                "{} for {} in {} mode is shadowed by global {}",
+                key,
+                action.name().unwrap_or_default(),
+                mode,
+                global.name().unwrap_or_default()
+            ),
+        }
+    }
+}
+
+/// One key or a list of keys for an action
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum KeySpec {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeySpec {
+    fn keys(&self) -> Vec<&str> {
+        match self {
+            KeySpec::One(key) => vec![key.as_str()],
+            KeySpec::Many(keys) => keys.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+/// Key bindings for every mode
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: HashMap<KeymapMode, HashMap<KeyBinding, KeyAction>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use KeyAction::*;
+
+        let mut keymap = Self {
+            bindings: HashMap::new(),
+        };
+
+        keymap.bind_all(
+            KeymapMode::Global,
+            [
+                (KeyBinding::ctrl('c'), Quit),
+                (KeyBinding::key(KeyCode::Tab), FocusNext),
+                (KeyBinding::key(KeyCode::BackTab), FocusPrevious),
+                (KeyBinding::ctrl('t'), ToggleTheme),
+                (KeyBinding::ctrl('p'), TogglePreview),
+                (KeyBinding::ctrl('k'), OpenCommandPalette),
+                (KeyBinding::key(KeyCode::F(1)), ShowHelp),
+                (KeyBinding::ctrl('?'), ShowHelp),
+            ],
+        );
+
+        keymap.bind_all(
+            KeymapMode::Normal,
+            [
+                (KeyBinding::char('q'), Quit),
+                (KeyBinding::char('i'), EnterInsert),
+                (KeyBinding::char(':'), EnterCommand),
+                (KeyBinding::char('/'), EnterSearch),
+                (KeyBinding::key(KeyCode::Up), MoveUp),
+                (KeyBinding::char('k'), MoveUp),
+                (KeyBinding::key(KeyCode::Down), MoveDown),
+                (KeyBinding::char('j'), MoveDown),
+                (KeyBinding::key(KeyCode::Left), MoveLeft),
+                (KeyBinding::char('h'), MoveLeft),
+                (KeyBinding::key(KeyCode::Right), MoveRight),
+                (KeyBinding::char('l'), MoveRight),
+                (KeyBinding::ctrl('u'), PageUp),
+                (KeyBinding::ctrl('d'), PageDown),
+                (KeyBinding::key(KeyCode::PageUp), PageUp),
+                (KeyBinding::key(KeyCode::PageDown), PageDown),
+                (KeyBinding::char('g'), GoToTop),
+                (KeyBinding::char('G'), GoToBottom),
+                (KeyBinding::key(KeyCode::Home), GoToTop),
+                (KeyBinding::key(KeyCode::End), GoToBottom),
+                (KeyBinding::char('n'), NextMatch),
+                (KeyBinding::char('N'), PreviousMatch),
+                (KeyBinding::char('t'), ToggleTheme),
+                (KeyBinding::char('p'), TogglePreview),
+                (KeyBinding::char('e'), ToggleToastHistory),
+                (KeyBinding::char('r'), ToastAction),
+                (KeyBinding::char('s'), OpenSessionPicker),
+                (KeyBinding::char('S'), DowngradeSandbox),
+                (KeyBinding::char(']'), NextSession),
+                (KeyBinding::char('['), PreviousSession),
+                (KeyBinding::char('V'), CycleLogLevel),
+                (KeyBinding::ctrl('y'), Copy),
+                (KeyBinding::char('?'), ShowHelp),
+                (KeyBinding::key(KeyCode::F(5)), Refresh),
+                (KeyBinding::ctrl('r'), Refresh),
+            ],
+        );
+
+        keymap.bind_all(
+            KeymapMode::Insert,
+            [
+                (KeyBinding::key(KeyCode::Esc), EnterNormal),
+                (KeyBinding::key(KeyCode::Enter), Send),
+                (KeyBinding::ctrl('m'), Send),
+                // Terminals without enhanced keys report Shift+Enter as
+                // Enter; Alt+Enter gets through everywhere
+                (
+                    KeyBinding::new(KeyCode::Enter, KeyModifiers::SHIFT),
+                    NewLine,
+                ),
+                (KeyBinding::new(KeyCode::Enter, KeyModifiers::ALT), NewLine),
+                (KeyBinding::key(KeyCode::Backspace), Backspace),
+                (KeyBinding::key(KeyCode::Delete), Delete),
+                (KeyBinding::ctrl('u'), Clear),
+                (KeyBinding::ctrl('a'), GoToTop),
+                (KeyBinding::key(KeyCode::Home), GoToTop),
+                (KeyBinding::key(KeyCode::End), GoToBottom),
+                (KeyBinding::ctrl('e'), OpenEditor),
+                (KeyBinding::key(KeyCode::Up), MoveUp),
+                (KeyBinding::key(KeyCode::Down), MoveDown),
+                (
+                    KeyBinding::new(KeyCode::Left, KeyModifiers::CONTROL),
+                    MoveLeft,
+                ),
+                (
+                    KeyBinding::new(KeyCode::Right, KeyModifiers::CONTROL),
+                    MoveRight,
+                ),
+            ],
+        );
+
+        // Command and search modes edit a line like insert mode does
+        for mode in [KeymapMode::Command, KeymapMode::Search] {
+            keymap.bind_all(
+                mode,
+                [
+                    (KeyBinding::key(KeyCode::Esc), EnterNormal),
+                    (KeyBinding::key(KeyCode::Enter), Send),
+                    (KeyBinding::key(KeyCode::Backspace), Backspace),
+                    (KeyBinding::key(KeyCode::Delete), Delete),
+                    (KeyBinding::ctrl('u'), Clear),
+                ],
+            );
+        }
+
+        keymap
+    }
+}
+
+impl Keymap {
+    fn bind_all(
+        &mut self,
+        mode: KeymapMode,
+        bindings: impl IntoIterator<Item = (KeyBinding, KeyAction)>,
+    ) {
+        self.bindings.entry(mode).or_default().extend(bindings);
+    }
+
+    /// Load the defaults overridden by `keybindings.toml` in `config_dir`,
+    /// if there is one
+    pub fn load(config_dir: &Path) -> Result<(Self, Vec<KeymapConflict>)> {
+        let path = config_dir.join(KEYBINDINGS_FILE);
+        if !path.exists() {
+            return Ok((Self::default(), Vec::new()));
+        }
+
+        let contents = std::fs::read_to_string(&path)?;
+        Self::from_toml(&contents).map_err(|e| match e {
+            TuiError::KeymapInvalid { reason, .. } => TuiError::KeymapInvalid {
+                path: path.display().to_string(),
+                reason,
+            },
+            other => other,
+        })
+    }
+
+    /// Apply the overrides in `contents` to the defaults. Unknown modes,
+    /// actions or keys are errors; bindings that clash are reported and
+    /// resolved in favour of the override.
+    pub fn from_toml(contents: &str) -> Result<(Self, Vec<KeymapConflict>)> {
+        let invalid = |reason: String| TuiError::KeymapInvalid {
+            path: KEYBINDINGS_FILE.to_string(),
+            reason,
+        };
+
+        // Ordered so conflicts come out the same way every time
+        let sections: BTreeMap<String, BTreeMap<String, KeySpec>> =
+            toml::from_str(contents).map_err(|e| invalid(e.to_string()))?;
+
+        let mut keymap = Self::default();
+        let mut conflicts = Vec::new();
+
+        for (mode_name, actions) in &sections {
+            let mode = KeymapMode::from_name(mode_name)
+                .ok_or_else(|| invalid(format!("unknown mode [{}]", mode_name)))?;
+
+            let mut overrides = Vec::new();
+            for (action_name, spec) in actions {
+                let action = KeyAction::from_name(action_name)
+                    .ok_or_else(|| invalid(format!("unknown action '{}'", action_name)))?;
+                for key in spec.keys() {
+                    let binding: KeyBinding = key.parse().map_err(invalid)?;
+                    overrides.push((binding, action));
+                }
+                keymap
+                    .bindings
+                    .entry(mode)
+                    .or_default()
+                    .retain(|_, bound| *bound != action);
+            }
+
+            let bindings = keymap.bindings.entry(mode).or_default();
+            for (key, action) in overrides {
+                if let Some(replaced) = bindings.insert(key, action) {
+                    if replaced != action {
+                        conflicts.push(KeymapConflict::Duplicate {
+                            mode,
+                            key,
+                            replaced,
+                            action,
+                        });
+                    }
+                }
+            }
+        }
+
+        conflicts.extend(keymap.shadowed());
+        Ok((keymap, conflicts))
+    }
+
+    /// Mode bindings that never fire because the key is bound globally
+    pub fn shadowed(&self) -> Vec<KeymapConflict> {
+        let Some(global) = self.bindings.get(&KeymapMode::Global) else {
+            return Vec::new();
+        };
+
+        let mut shadowed: Vec<_> =
+            KeymapMode::ALL
+                .into_iter()
+                .filter(|mode| *mode != KeymapMode::Global)
+                .flat_map(|mode| {
+                    self.bindings.get(&mode).into_iter().flatten().filter_map(
+                        move |(key, action)| {
+                            let global = *global.get(key)?;
+                            (global != *action).then_some(KeymapConflict::ShadowedByGlobal {
+                                mode,
+                                key: *key,
+                                action: *action,
+                                global,
+                            })
+                        },
+                    )
+                })
+                .collect();
+        shadowed.sort_by_key(|conflict| conflict.to_string());
+        shadowed
+    }
+
+    /// Action a key triggers in `mode`. Unbound characters are typed in the
+    /// modes that edit text.
+    pub fn action_for(&self, mode: &InputMode, key: KeyEvent) -> Option<KeyAction> {
+        let binding = KeyBinding::from(key);
+        let mode = KeymapMode::from(mode);
+
+        for lookup in [KeymapMode::Global, mode] {
+            if let Some(action) = self
+                .bindings
+                .get(&lookup)
+                .and_then(|bindings| bindings.get(&binding))
+            {
+                return Some(*action);
+            }
+        }
+
+        match (mode, binding.code) {
+            (KeymapMode::Normal, _) => None,
+            (_, KeyCode::Char(c)) if binding.modifiers.is_empty() => Some(KeyAction::InsertChar(c)),
+            _ => None,
+        }
+    }
+
+    /// Keys bound to `action`, by mode, in display order
+    pub fn keys_for(&self, action: KeyAction) -> Vec<(KeymapMode, KeyBinding)> {
+        let mut keys: Vec<_> = KeymapMode::ALL
+            .into_iter()
+            .flat_map(|mode| {
+                self.bindings
+                    .get(&mode)
+                    .into_iter()
+                    .flatten()
+                    .filter(move |(_, bound)| **bound == action)
+                    .map(move |(key, _)| (mode, *key))
+            })
+            .collect();
+        keys.sort_by_key(|(mode, key)| (*mode, key.to_string()));
+        keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_parse_key_bindings() {
+        let parse = |s: &str| s.parse::<KeyBinding>().unwrap();
+
+        assert_eq!(parse("ctrl+p"), KeyBinding::ctrl('p'));
+        assert_eq!(
+            parse("Ctrl+Alt+x").modifiers,
+            KeyModifiers::CONTROL | KeyModifiers::ALT
+        );
+        assert_eq!(parse("G"), KeyBinding::char('G'));
+        assert_eq!(parse("shift+G"), KeyBinding::char('G'));
+        assert_eq!(parse("shift+tab"), KeyBinding::key(KeyCode::BackTab));
+        assert_eq!(parse("f5"), KeyBinding::key(KeyCode::F(5)));
+        assert_eq!(parse("PageDown"), KeyBinding::key(KeyCode::PageDown));
+        assert_eq!(parse("space"), KeyBinding::char(' '));
+        assert_eq!(parse("+"), KeyBinding::char('+'));
+        assert_eq!(parse("ctrl++"), KeyBinding::ctrl('+'));
+
+        assert!("hyper+x".parse::<KeyBinding>().is_err());
+        assert!("f13".parse::<KeyBinding>().is_err());
+        assert!("ctrl+nope".parse::<KeyBinding>().is_err());
+
+        assert_eq!(parse("ctrl+p").to_string(), "Ctrl+P");
+        assert_eq!(parse("shift+tab").to_string(), "Shift+Tab");
+    }
+
+    #[test]
+    fn test_defaults_match_builtin_keys() {
+        let keymap = Keymap::default();
+        assert!(keymap.shadowed().is_empty());
+
+        let action = |mode, code, modifiers| keymap.action_for(&mode, key(code, modifiers));
+        assert_eq!(
+            action(InputMode::Normal, KeyCode::Char('G'), KeyModifiers::SHIFT),
+            Some(KeyAction::GoToBottom)
+        );
+        assert_eq!(
+            action(InputMode::Insert, KeyCode::Char('q'), KeyModifiers::NONE),
+            Some(KeyAction::InsertChar('q'))
+        );
+        assert_eq!(
+            action(InputMode::Search, KeyCode::Char('Q'), KeyModifiers::SHIFT),
+            Some(KeyAction::InsertChar('Q'))
+        );
+        assert_eq!(
+            action(InputMode::Insert, KeyCode::Char('c'), KeyModifiers::CONTROL),
+            Some(KeyAction::Quit)
+        );
+        assert_eq!(
+            action(InputMode::Normal, KeyCode::Char('z'), KeyModifiers::NONE),
+            None
+        );
+        assert_eq!(
+            action(InputMode::Insert, KeyCode::Enter, KeyModifiers::SHIFT),
+            Some(KeyAction::NewLine)
+        );
+        assert_eq!(
+            action(InputMode::Insert, KeyCode::Char('e'), KeyModifiers::CONTROL),
+            Some(KeyAction::OpenEditor)
+        );
+    }
+
+    #[test]
+    fn test_overrides_replace_defaults() {
+        let (keymap, conflicts) = Keymap::from_toml(
+            r#"
+            [global]
+            open_command_palette = "ctrl+o"
+
+            [normal]
+            move_up = ["w", "up"]
+            toggle_theme = []
+            "#,
+        )
+        .unwrap();
+        assert!(conflicts.is_empty(), "{:?}", conflicts);
+
+        let normal = |c| {
+            keymap.action_for(
+                &InputMode::Normal,
+                key(KeyCode::Char(c), KeyModifiers::NONE),
+            )
+        };
+        assert_eq!(normal('w'), Some(KeyAction::MoveUp));
+        assert_eq!(normal('k'), None);
+        assert_eq!(normal('t'), None);
+        assert_eq!(
+            keymap.keys_for(KeyAction::OpenCommandPalette),
+            [(KeymapMode::Global, KeyBinding::ctrl('o'))]
+        );
+    }
+
+    #[test]
+    fn test_conflicts_are_reported() {
+        let (keymap, conflicts) = Keymap::from_toml(
+            r#"
+            [global]
+            toggle_toast_history = "ctrl+a"
+
+            [normal]
+            quit = "t"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            conflicts,
+            [
+                KeymapConflict::Duplicate {
+                    mode: KeymapMode::Normal,
+                    key: KeyBinding::char('t'),
+                    replaced: KeyAction::ToggleTheme,
+                    action: KeyAction::Quit,
+                },
+                // Insert mode binds ctrl+a by default
+                KeymapConflict::ShadowedByGlobal {
+                    mode: KeymapMode::Insert,
+                    key: KeyBinding::ctrl('a'),
+                    action: KeyAction::GoToTop,
+                    global: KeyAction::ToggleToastHistory,
+                },
+            ]
+        );
+        assert_eq!(
+            conflicts[0].to_string(),
+            "t is bound to both toggle_theme and quit in normal mode; using quit"
+        );
+        assert_eq!(
+            keymap.action_for(
+                &InputMode::Normal,
+                key(KeyCode::Char('t'), KeyModifiers::NONE)
+            ),
+            Some(KeyAction::Quit)
+        );
+    }
+
+    #[test]
+    fn test_invalid_keymap_is_an_error() {
+        for contents in [
+            "[visual]\nquit = \"q\"",
+            "[normal]\nfly = \"f\"",
+            "[normal]\nquit = \"ctrl+\"",
+            "[normal]\nquit = 5",
+        ] {
+            let result = Keymap::from_toml(contents);
+            assert!(
+                matches!(result, Err(TuiError::KeymapInvalid { .. })),
+                "{} should be invalid",
+                contents
+            );
+        }
+    }
+
+    #[test]
+    fn test_load_missing_file_uses_defaults() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let (keymap, conflicts) = Keymap::load(dir.path()).unwrap();
+        assert!(conflicts.is_empty());
+        assert_eq!(
+            keymap.keys_for(KeyAction::OpenCommandPalette),
+            [(KeymapMode::Global, KeyBinding::ctrl('k'))]
+        );
+
+        std::fs::write(dir.path().join(KEYBINDINGS_FILE), "[normal]\nquit = \"Q\"").unwrap();
+        let (keymap, _) = Keymap::load(dir.path()).unwrap();
+        assert_eq!(
+            keymap.keys_for(KeyAction::Quit),
+            [
+                (KeymapMode::Global, KeyBinding::ctrl('c')),
+                (KeymapMode::Normal, KeyBinding::char('Q')),
+            ]
+        );
+    }
+}
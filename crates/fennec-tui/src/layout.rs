@@ -1,5 +1,9 @@
 use ratatui::layout::{Constraint, Direction, Layout, Margin, Rect};
 use ratatui::widgets::BorderType;
+use std::time::{Duration, Instant};
+
+/// How long the terminal size must stay put before the layout follows it
+pub const RESIZE_DEBOUNCE: Duration = Duration::from_millis(100);
 
 /// Represents the different panes in the application
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -15,9 +19,9 @@ pub enum Pane {
 pub struct LayoutConfig {
     /// Whether to show the preview panel
     pub show_preview: bool,
-    /// Minimum terminal width required
+    /// Minimum terminal width for the full layout
     pub min_width: u16,
-    /// Minimum terminal height required
+    /// Minimum terminal height for the full layout
     pub min_height: u16,
     /// Chat pane width percentage (when preview is shown)
     pub chat_width_percent: u16,
@@ -67,11 +71,14 @@ pub struct AppLayout {
     pub input_area: Rect,
     /// Preview panel area (if enabled)
     pub preview_area: Option<Rect>,
-    /// Status bar area
-    pub status_area: Rect,
+    /// Status bar area (hidden in compact mode)
+    pub status_area: Option<Rect>,
+    /// Line asking for a larger terminal (compact mode only)
+    pub hint_area: Option<Rect>,
     /// Currently focused pane
     pub focused_pane: Pane,
-    /// Whether the layout is in compact mode (small screen)
+    /// Whether the terminal is below the minimum size and only the
+    /// conversation and input are shown
     pub is_compact: bool,
 }
 
@@ -109,6 +116,14 @@ impl LayoutManager {
         self.current_layout = None;
     }
 
+    /// Set the height of the input area, e.g. as its text grows
+    pub fn set_input_height(&mut self, height: u16) {
+        if height != self.config.input_height {
+            self.config.input_height = height;
+            self.current_layout = None;
+        }
+    }
+
     /// Toggle preview panel visibility
     pub fn toggle_preview(&mut self) {
         self.config.show_preview = !self.config.show_preview;
@@ -188,26 +203,27 @@ impl LayoutManager {
             chat_area,
             input_area,
             preview_area,
-            status_area,
+            status_area: Some(status_area),
+            hint_area: None,
             focused_pane,
             is_compact,
         }
     }
 
-    /// Calculate layout for compact (small) screens
+    /// Calculate layout for terminals below the minimum size: only the
+    /// conversation and input, with a line asking for more room
     fn calculate_compact_layout(
         &self,
         area: Rect,
         focused_pane: Pane,
         is_compact: bool,
     ) -> AppLayout {
-        // In compact mode, we stack everything vertically and hide preview by default
         let vertical_layout = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
-                Constraint::Min(0),                                // Chat area (most space)
-                Constraint::Length(self.config.input_height),      // Input area
-                Constraint::Length(self.config.status_bar_height), // Status bar
+                Constraint::Min(1),                           // Chat area (most space)
+                Constraint::Length(self.config.input_height), // Input area
+                Constraint::Length(1),                        // Hint
             ])
             .split(area);
 
@@ -215,8 +231,9 @@ impl LayoutManager {
             full_area: area,
             chat_area: vertical_layout[0],
             input_area: vertical_layout[1],
-            preview_area: None, // Hidden in compact mode
-            status_area: vertical_layout[2],
+            preview_area: None,
+            status_area: None,
+            hint_area: Some(vertical_layout[2]),
             focused_pane,
             is_compact,
         }
@@ -229,7 +246,7 @@ impl LayoutManager {
             Pane::Chat => Some(layout.chat_area),
             Pane::Input => Some(layout.input_area),
             Pane::Preview => layout.preview_area,
-            Pane::StatusBar => Some(layout.status_area),
+            Pane::StatusBar => layout.status_area,
         }
     }
 
@@ -319,6 +336,57 @@ impl LayoutManager {
 
         Ok(())
     }
+
+    /// Text for the hint line shown in compact mode
+    pub fn compact_hint(&self, area: Rect) -> String {
+        format!(
+            "{}x{} - enlarge to {}x{} for all panes",
+            area.width, area.height, self.config.min_width, self.config.min_height
+        )
+    }
+}
+
+/// Holds back relayout while the terminal is being resized, so a drag
+/// doesn't relayout on every intermediate size
+#[derive(Debug, Clone)]
+pub struct ResizeDebouncer {
+    delay: Duration,
+    pending: Option<(u16, u16, Instant)>,
+}
+
+impl Default for ResizeDebouncer {
+    fn default() -> Self {
+        Self::new(RESIZE_DEBOUNCE)
+    }
+}
+
+impl ResizeDebouncer {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            pending: None,
+        }
+    }
+
+    /// Record a resize to `width` x `height` seen at `now`
+    pub fn resized(&mut self, width: u16, height: u16, now: Instant) {
+        self.pending = Some((width, height, now));
+    }
+
+    /// Whether a resize is waiting to settle
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// The size to lay out for, once no resize has arrived for the delay
+    pub fn settled(&mut self, now: Instant) -> Option<(u16, u16)> {
+        let (width, height, at) = self.pending?;
+        if now.duration_since(at) < self.delay {
+            return None;
+        }
+        self.pending = None;
+        Some((width, height))
+    }
 }
 
 /// Helper functions for common layout operations
@@ -455,7 +523,7 @@ mod tests {
         assert!(manager.is_pane_visible(compact_area, Pane::Chat));
         assert!(manager.is_pane_visible(compact_area, Pane::Input));
         assert!(!manager.is_pane_visible(compact_area, Pane::Preview));
-        assert!(manager.is_pane_visible(compact_area, Pane::StatusBar));
+        assert!(!manager.is_pane_visible(compact_area, Pane::StatusBar));
     }
 
     #[test]
@@ -527,4 +595,41 @@ mod tests {
             expected_x = expected_x.saturating_add(column.width);
         }
     }
+
+    #[test]
+    fn test_compact_layout_fits_tiny_terminals() {
+        let mut manager = LayoutManager::default();
+        for (width, height) in [(60, 15), (50, 12), (20, 5), (1, 1), (0, 0)] {
+            let area = Rect::new(0, 0, width, height);
+            let layout = manager.layout(area).clone();
+            assert!(layout.is_compact);
+            for pane in [
+                Some(layout.chat_area),
+                Some(layout.input_area),
+                layout.hint_area,
+            ] {
+                let pane = pane.unwrap();
+                assert_eq!(pane.intersection(area), pane, "{}x{}", width, height);
+            }
+            assert!(layout.chat_area.bottom() <= layout.input_area.top());
+        }
+    }
+
+    #[test]
+    fn test_resize_debouncer_waits_for_size_to_settle() {
+        let mut debouncer = ResizeDebouncer::new(Duration::from_millis(100));
+        let start = Instant::now();
+        assert_eq!(debouncer.settled(start), None);
+
+        debouncer.resized(100, 40, start);
+        debouncer.resized(90, 30, start + Duration::from_millis(60));
+        assert_eq!(debouncer.settled(start + Duration::from_millis(120)), None);
+        assert!(debouncer.is_pending());
+
+        assert_eq!(
+            debouncer.settled(start + Duration::from_millis(160)),
+            Some((90, 30))
+        );
+        assert!(!debouncer.is_pending());
+    }
 }
@@ -1,17 +1,76 @@
 pub mod app;
+pub mod approval_dialog;
+pub mod command_palette;
 pub mod components;
+pub mod conversation;
+pub mod diff_review;
 pub mod error;
 pub mod events;
 pub mod file_tree;
+pub mod input_editor;
+pub mod keymap;
 pub mod layout;
+pub mod memory_browser;
+pub mod plan_panel;
+pub mod sandbox_dialog;
+pub mod sessions;
+pub mod streaming_message;
 pub mod summary_panel;
+pub mod terminal;
 pub mod theme;
+pub mod toasts;
 
 // Re-export error types and components
-pub use error::{ErrorDisplay, ErrorToast, Result as TuiResult, TuiError};
+pub use error::{ErrorDisplay, ErrorToast, Result as TuiResult, ToastAction, TuiError};
+
+// Re-export toast notifications
+pub use toasts::{Clock, SystemClock, ToastStack};
+
+// Re-export approval dialog components
+pub use approval_dialog::{ApprovalDialog, ChannelApprovalPrompt, PendingApproval};
+
+// Re-export the command palette and key bindings
+pub use command_palette::{CommandPalette, PaletteEntry, PaletteItem};
+pub use keymap::{KeyBinding, Keymap, KeymapConflict, KeymapMode, KEYBINDINGS_FILE};
+
+// Re-export the input editor
+pub use input_editor::{ExternalEditor, InputEditor, PromptHistory, SystemEditor};
+
+// Re-export the conversation pane
+pub use conversation::{ConversationPane, ConversationState, Scrollback};
+
+// Re-export the diff review
+pub use diff_review::{DiffReview, DiffReviewAction, HunkApplier, WorkspaceHunkApplier};
+
+// Re-export the memory browser
+pub use memory_browser::{
+    MemoryBrowser, MemoryBrowserBackend, MemoryItem, MemoryItemId, MemoryServiceBackend, MemoryTab,
+};
+
+// Re-export the plan panel
+pub use plan_panel::{PlanActionRunner, PlanPanel, PlanPanelAction};
+
+// Re-export the sandbox status and dialog
+pub use sandbox_dialog::{sandbox_status_items, SandboxDialog};
+
+// Re-export the session switcher
+pub use sessions::{
+    FinishedStream, SessionPickerAction, SessionRegistry, SessionStatus, SessionTab,
+};
+
+// Re-export terminal setup
+pub use terminal::TerminalGuard;
+
+// Re-export the streaming message view
+pub use streaming_message::{
+    forward_stream, StreamStatus, StreamingMessageView, StreamingViewConfig,
+};
 
 // Re-export summary panel components
-pub use summary_panel::{SummaryGenerationStatus, SummaryPanel, SummaryPanelAction, SummaryTab};
+pub use summary_panel::{
+    CommandSummaryGenerator, ProgressReporter, SummaryExporter, SummaryGenerationStatus,
+    SummaryGenerator, SummaryPanel, SummaryPanelAction, SummaryProgress, SummaryTab,
+};
 
 // Re-export file tree components
 pub use file_tree::{FileNode, FileTreeBrowser};
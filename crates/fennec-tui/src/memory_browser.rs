@@ -0,0 +1,1198 @@
+//! Browser for what Fennec remembers: transcripts, notes, plans and memory
+//! files.
+//!
+//! All loading goes through a [`MemoryBrowserBackend`] on spawned tasks whose
+//! results come back over a channel, so the render loop only ever reads
+//! state that is already loaded. [`MemoryServiceBackend`] is the backend over
+//! the memory service and the note and plan stores.
+
+use crate::theme::{ComponentType, ThemeManager};
+use anyhow::Result;
+use async_trait::async_trait;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use fennec_memory::{
+    match_spans, ClineFileType, MemoryService, NoteSearchFilters, NotesStore, PlanStore,
+};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Modifier,
+    text::{Line, Span},
+    widgets::{
+        Block, Borders, List, ListItem, ListState, Paragraph, StatefulWidget, Tabs, Widget, Wrap,
+    },
+};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::{mpsc, RwLock};
+use uuid::Uuid;
+
+/// Tag added to a transcript when it is pinned
+pub const PINNED_TAG: &str = "pinned";
+
+const SPINNER_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// Maximum results requested per search
+const SEARCH_LIMIT: usize = 50;
+
+/// Cline-style files offered for every project
+const CLINE_FILES: [ClineFileType; 3] = [
+    ClineFileType::ProjectBrief,
+    ClineFileType::ActiveContext,
+    ClineFileType::Progress,
+];
+
+/// Tabs of the memory browser
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MemoryTab {
+    Transcripts,
+    Notes,
+    Plans,
+    MemoryFiles,
+}
+
+impl MemoryTab {
+    pub const ALL: [MemoryTab; 4] = [
+        MemoryTab::Transcripts,
+        MemoryTab::Notes,
+        MemoryTab::Plans,
+        MemoryTab::MemoryFiles,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            MemoryTab::Transcripts => "Transcripts",
+            MemoryTab::Notes => "Notes",
+            MemoryTab::Plans => "Plans",
+            MemoryTab::MemoryFiles => "Memory Files",
+        }
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|tab| *tab == self).unwrap_or(0)
+    }
+
+    fn next(self) -> Self {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+
+    fn previous(self) -> Self {
+        Self::ALL[(self.index() + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+/// Identity of a browsable item
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MemoryItemId {
+    /// Transcript of a session
+    Transcript(Uuid),
+    Note(Uuid),
+    Plan(Uuid),
+    MemoryFile(Uuid),
+    /// Cline-style file of a project
+    ClineFile(Uuid, ClineFileType),
+}
+
+/// One row of a tab's list
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemoryItem {
+    pub id: MemoryItemId,
+    pub title: String,
+    /// Secondary text shown after the title
+    pub detail: String,
+}
+
+/// Source of the data shown in the memory browser
+#[async_trait]
+pub trait MemoryBrowserBackend: Send + Sync {
+    /// Items of `tab`, narrowed to those matching `query` unless it is empty
+    async fn list(&self, tab: MemoryTab, query: &str) -> Result<Vec<MemoryItem>>;
+
+    /// Text shown in the preview pane for `id`
+    async fn preview(&self, id: &MemoryItemId) -> Result<String>;
+
+    async fn delete_note(&self, note_id: Uuid) -> Result<()>;
+
+    /// Add `tag` to the transcript of `session_id`
+    async fn tag_transcript(&self, session_id: Uuid, tag: &str) -> Result<()>;
+
+    /// Render a project's Cline-style file as markdown
+    async fn cline_markdown(&self, project_id: Uuid, file_type: ClineFileType) -> Result<String>;
+}
+
+/// Result of a task spawned by the browser
+#[derive(Debug)]
+enum Update {
+    Listed {
+        generation: u64,
+        items: Result<Vec<MemoryItem>, String>,
+    },
+    Preview {
+        id: MemoryItemId,
+        text: Result<String, String>,
+    },
+    Opened {
+        title: String,
+        markdown: Result<String, String>,
+    },
+    Acted {
+        message: String,
+        outcome: Result<(), String>,
+    },
+}
+
+/// Full-panel view of an opened markdown file
+#[derive(Debug)]
+struct Reader {
+    title: String,
+    markdown: String,
+    scroll: u16,
+}
+
+/// Memory browser panel
+pub struct MemoryBrowser {
+    backend: Arc<dyn MemoryBrowserBackend>,
+    tab: MemoryTab,
+    items: Vec<MemoryItem>,
+    list_state: ListState,
+    query: String,
+    /// Whether keys go to the search box
+    searching: bool,
+    /// Loaded previews; `None` while loading
+    previews: HashMap<MemoryItemId, Option<String>>,
+    reader: Option<Reader>,
+    /// Outcome of the last action or failed load
+    status: Option<String>,
+    /// Bumped on every list request so late results of older ones are dropped
+    generation: u64,
+    /// Spawned tasks whose results haven't been applied yet
+    pending: usize,
+    spinner: usize,
+    sender: mpsc::UnboundedSender<Update>,
+    updates: mpsc::UnboundedReceiver<Update>,
+}
+
+impl std::fmt::Debug for MemoryBrowser {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemoryBrowser")
+            .field("tab", &self.tab)
+            .field("items", &self.items.len())
+            .field("query", &self.query)
+            .field("pending", &self.pending)
+            .finish()
+    }
+}
+
+impl MemoryBrowser {
+    /// Create a browser on the Transcripts tab. Nothing is loaded until
+    /// [`reload`](Self::reload) is called from within a Tokio runtime.
+    pub fn new(backend: Arc<dyn MemoryBrowserBackend>) -> Self {
+        let (sender, updates) = mpsc::unbounded_channel();
+        Self {
+            backend,
+            tab: MemoryTab::Transcripts,
+            items: Vec::new(),
+            list_state: ListState::default(),
+            query: String::new(),
+            searching: false,
+            previews: HashMap::new(),
+            reader: None,
+            status: None,
+            generation: 0,
+            pending: 0,
+            spinner: 0,
+            sender,
+            updates,
+        }
+    }
+
+    pub fn tab(&self) -> MemoryTab {
+        self.tab
+    }
+
+    pub fn items(&self) -> &[MemoryItem] {
+        &self.items
+    }
+
+    pub fn selected_item(&self) -> Option<&MemoryItem> {
+        self.items.get(self.list_state.selected()?)
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn is_searching(&self) -> bool {
+        self.searching
+    }
+
+    /// Whether any load or action is still in flight
+    pub fn is_loading(&self) -> bool {
+        self.pending > 0
+    }
+
+    pub fn status(&self) -> Option<&str> {
+        self.status.as_deref()
+    }
+
+    /// Loaded preview of the selected item
+    pub fn preview(&self) -> Option<&str> {
+        let id = &self.selected_item()?.id;
+        self.previews.get(id)?.as_deref()
+    }
+
+    /// Markdown of the opened Cline-style file, if one is open
+    pub fn opened_markdown(&self) -> Option<&str> {
+        self.reader.as_ref().map(|reader| reader.markdown.as_str())
+    }
+
+    pub fn set_tab(&mut self, tab: MemoryTab) {
+        if tab == self.tab {
+            return;
+        }
+        self.tab = tab;
+        self.items.clear();
+        self.list_state.select(None);
+        self.reload();
+    }
+
+    /// Reload the current tab's list for the current query
+    pub fn reload(&mut self) {
+        self.generation += 1;
+        self.previews.clear();
+        let (backend, tab, query, generation) = (
+            self.backend.clone(),
+            self.tab,
+            self.query.clone(),
+            self.generation,
+        );
+        self.spawn(async move {
+            Update::Listed {
+                generation,
+                items: backend.list(tab, &query).await.map_err(|e| e.to_string()),
+            }
+        });
+    }
+
+    pub fn select_next(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+        let next = self
+            .list_state
+            .selected()
+            .map_or(0, |index| (index + 1).min(self.items.len() - 1));
+        self.select(Some(next));
+    }
+
+    pub fn select_previous(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+        let previous = self
+            .list_state
+            .selected()
+            .map_or(0, |index| index.saturating_sub(1));
+        self.select(Some(previous));
+    }
+
+    /// Advance the loading spinner; call once per render tick
+    pub fn tick(&mut self) {
+        if self.is_loading() {
+            self.spinner = (self.spinner + 1) % SPINNER_FRAMES.len();
+        }
+    }
+
+    /// Apply the results that have arrived without waiting. Returns whether
+    /// anything changed.
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+        while let Ok(update) = self.updates.try_recv() {
+            self.apply(update);
+            changed = true;
+        }
+        changed
+    }
+
+    /// Wait for the next result and apply it. Returns `false` when nothing
+    /// is in flight.
+    pub async fn next_update(&mut self) -> bool {
+        if self.pending == 0 {
+            return false;
+        }
+        match self.updates.recv().await {
+            Some(update) => {
+                self.apply(update);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Handle a key press. Returns whether the browser used it; Esc with
+    /// nothing to close is left to the caller.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            return false;
+        }
+
+        if let Some(reader) = self.reader.as_mut() {
+            match key.code {
+                KeyCode::Down | KeyCode::Char('j') => {
+                    reader.scroll = reader.scroll.saturating_add(1)
+                }
+                KeyCode::Up | KeyCode::Char('k') => reader.scroll = reader.scroll.saturating_sub(1),
+                KeyCode::Esc | KeyCode::Char('q') => self.reader = None,
+                _ => return false,
+            }
+            return true;
+        }
+
+        if self.searching {
+            match key.code {
+                KeyCode::Char(c) => {
+                    self.query.push(c);
+                    self.reload();
+                }
+                KeyCode::Backspace => {
+                    if self.query.pop().is_some() {
+                        self.reload();
+                    }
+                }
+                KeyCode::Enter => self.searching = false,
+                KeyCode::Esc => {
+                    self.searching = false;
+                    if !self.query.is_empty() {
+                        self.query.clear();
+                        self.reload();
+                    }
+                }
+                _ => return false,
+            }
+            return true;
+        }
+
+        match key.code {
+            KeyCode::Tab => self.set_tab(self.tab.next()),
+            KeyCode::BackTab => self.set_tab(self.tab.previous()),
+            KeyCode::Char(c @ '1'..='4') => {
+                self.set_tab(MemoryTab::ALL[c as usize - '1' as usize]);
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => self.select_previous(),
+            KeyCode::Home | KeyCode::Char('g') => {
+                if !self.items.is_empty() {
+                    self.select(Some(0));
+                }
+            }
+            KeyCode::End | KeyCode::Char('G') => {
+                if !self.items.is_empty() {
+                    self.select(Some(self.items.len() - 1));
+                }
+            }
+            KeyCode::Char('/') => self.searching = true,
+            KeyCode::Char('r') => self.reload(),
+            KeyCode::Char('d') => self.delete_selected_note(),
+            KeyCode::Char('p') => self.pin_selected_transcript(),
+            KeyCode::Char('o') | KeyCode::Enter => self.open_selected_cline_file(),
+            _ => return false,
+        }
+        true
+    }
+
+    fn delete_selected_note(&mut self) {
+        let Some(MemoryItem {
+            id: MemoryItemId::Note(note_id),
+            title,
+            ..
+        }) = self.selected_item().cloned()
+        else {
+            return;
+        };
+        let backend = self.backend.clone();
+        self.spawn(async move {
+            Update::Acted {
+                message: format!("Deleted note '{}'", title),
+                outcome: backend
+                    .delete_note(note_id)
+                    .await
+                    .map_err(|e| e.to_string()),
+            }
+        });
+    }
+
+    fn pin_selected_transcript(&mut self) {
+        let Some(MemoryItemId::Transcript(session_id)) =
+            self.selected_item().map(|item| item.id.clone())
+        else {
+            return;
+        };
+        let backend = self.backend.clone();
+        self.spawn(async move {
+            Update::Acted {
+                message: format!("Tagged transcript as '{}'", PINNED_TAG),
+                outcome: backend
+                    .tag_transcript(session_id, PINNED_TAG)
+                    .await
+                    .map_err(|e| e.to_string()),
+            }
+        });
+    }
+
+    fn open_selected_cline_file(&mut self) {
+        let Some(MemoryItem {
+            id: MemoryItemId::ClineFile(project_id, file_type),
+            title,
+            ..
+        }) = self.selected_item().cloned()
+        else {
+            return;
+        };
+        let backend = self.backend.clone();
+        self.spawn(async move {
+            Update::Opened {
+                title,
+                markdown: backend
+                    .cline_markdown(project_id, file_type)
+                    .await
+                    .map_err(|e| e.to_string()),
+            }
+        });
+    }
+
+    fn select(&mut self, index: Option<usize>) {
+        self.list_state.select(index);
+        let Some(id) = self.selected_item().map(|item| item.id.clone()) else {
+            return;
+        };
+        if self.previews.contains_key(&id) {
+            return;
+        }
+        self.previews.insert(id.clone(), None);
+        let backend = self.backend.clone();
+        self.spawn(async move {
+            let text = backend.preview(&id).await.map_err(|e| e.to_string());
+            Update::Preview { id, text }
+        });
+    }
+
+    fn spawn(&mut self, task: impl Future<Output = Update> + Send + 'static) {
+        self.pending += 1;
+        let sender = self.sender.clone();
+        tokio::spawn(async move {
+            // The browser may have been dropped meanwhile
+            let _ = sender.send(task.await);
+        });
+    }
+
+    fn apply(&mut self, update: Update) {
+        self.pending = self.pending.saturating_sub(1);
+        match update {
+            Update::Listed { generation, .. } if generation != self.generation => {}
+            Update::Listed { items, .. } => match items {
+                Ok(items) => {
+                    // Keep the selection on the same item when it survives
+                    let selected = self.selected_item().map(|item| item.id.clone());
+                    self.items = items;
+                    let index = selected
+                        .and_then(|id| self.items.iter().position(|item| item.id == id))
+                        .or_else(|| {
+                            self.list_state
+                                .selected()
+                                .map(|index| index.min(self.items.len().saturating_sub(1)))
+                        })
+                        .or(Some(0))
+                        .filter(|_| !self.items.is_empty());
+                    self.select(index);
+                }
+                Err(e) => {
+                    self.status = Some(format!("Failed to load {}: {}", self.tab.title(), e));
+                }
+            },
+            Update::Preview { id, text } => {
+                let text = text.unwrap_or_else(|e| format!("Failed to load preview: {}", e));
+                self.previews.insert(id, Some(text));
+            }
+            Update::Opened { title, markdown } => match markdown {
+                Ok(markdown) => {
+                    self.reader = Some(Reader {
+                        title,
+                        markdown,
+                        scroll: 0,
+                    })
+                }
+                Err(e) => self.status = Some(format!("Failed to open {}: {}", title, e)),
+            },
+            Update::Acted { message, outcome } => {
+                self.status = Some(match outcome {
+                    Ok(()) => message,
+                    Err(e) => format!("Failed: {}", e),
+                });
+                self.reload();
+            }
+        }
+    }
+
+    /// Render the browser
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, theme: &ThemeManager, focused: bool) {
+        let title = if self.is_loading() {
+            format!("Memory {}", SPINNER_FRAMES[self.spinner])
+        } else {
+            "Memory".to_string()
+        };
+        let border_style = if focused {
+            theme.get_style(ComponentType::Highlight)
+        } else {
+            theme.get_style(ComponentType::Border)
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .title_style(theme.get_style(ComponentType::Title))
+            .border_style(border_style);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        if let Some(reader) = &self.reader {
+            Paragraph::new(reader.markdown.as_str())
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(format!("{} - Esc to close", reader.title))
+                        .border_style(theme.get_style(ComponentType::Border)),
+                )
+                .style(theme.get_style(ComponentType::Text))
+                .wrap(Wrap { trim: false })
+                .scroll((reader.scroll, 0))
+                .render(inner, buf);
+            return;
+        }
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(2),
+                Constraint::Length(1),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ])
+            .split(inner);
+
+        Tabs::new(
+            MemoryTab::ALL
+                .iter()
+                .map(|tab| Line::from(tab.title()))
+                .collect(),
+        )
+        .block(Block::default().borders(Borders::BOTTOM))
+        .select(self.tab.index())
+        .style(theme.get_style(ComponentType::Tab))
+        .highlight_style(theme.get_style(ComponentType::TabSelected))
+        .render(chunks[0], buf);
+
+        self.render_search(chunks[1], buf, theme);
+
+        let body = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+            .split(chunks[2]);
+        self.render_list(body[0], buf, theme);
+        self.render_preview(body[1], buf, theme);
+
+        let footer = match &self.status {
+            Some(status) => Span::styled(status.as_str(), theme.get_style(ComponentType::Info)),
+            None => Span::styled(
+                "Tab switch  / search  d delete note  p pin  o open",
+                theme.get_style(ComponentType::Muted),
+            ),
+        };
+        Paragraph::new(Line::from(footer)).render(chunks[3], buf);
+    }
+
+    fn render_search(&self, area: Rect, buf: &mut Buffer, theme: &ThemeManager) {
+        let line = if self.searching || !self.query.is_empty() {
+            let cursor = if self.searching { "▏" } else { "" };
+            Line::from(vec![
+                Span::styled("/", theme.get_style(ComponentType::Highlight)),
+                Span::styled(
+                    format!("{}{}", self.query, cursor),
+                    theme.get_style(ComponentType::Text),
+                ),
+            ])
+        } else {
+            Line::from(Span::styled(
+                "Press / to search",
+                theme.get_style(ComponentType::Muted),
+            ))
+        };
+        Paragraph::new(line).render(area, buf);
+    }
+
+    fn render_list(&mut self, area: Rect, buf: &mut Buffer, theme: &ThemeManager) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(theme.get_style(ComponentType::Border));
+
+        if self.items.is_empty() {
+            let message = if self.is_loading() {
+                "Loading..."
+            } else if self.query.is_empty() {
+                "Nothing stored yet"
+            } else {
+                "No matches"
+            };
+            Paragraph::new(message)
+                .block(block)
+                .style(theme.get_style(ComponentType::Muted))
+                .render(area, buf);
+            return;
+        }
+
+        let items: Vec<ListItem> = self
+            .items
+            .iter()
+            .map(|item| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(item.title.as_str(), theme.get_style(ComponentType::Text)),
+                    Span::styled(
+                        format!(" {}", item.detail),
+                        theme.get_style(ComponentType::Muted),
+                    ),
+                ]))
+            })
+            .collect();
+        StatefulWidget::render(
+            List::new(items)
+                .block(block)
+                .highlight_style(
+                    theme
+                        .get_style(ComponentType::Selection)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .highlight_symbol("> "),
+            area,
+            buf,
+            &mut self.list_state,
+        );
+    }
+
+    fn render_preview(&self, area: Rect, buf: &mut Buffer, theme: &ThemeManager) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Preview")
+            .border_style(theme.get_style(ComponentType::Border));
+        let (text, style) = match self.selected_item().map(|item| self.previews.get(&item.id)) {
+            Some(Some(Some(text))) => (text.as_str(), theme.get_style(ComponentType::Text)),
+            Some(_) => ("Loading...", theme.get_style(ComponentType::Muted)),
+            None => ("", theme.get_style(ComponentType::Muted)),
+        };
+        Paragraph::new(text)
+            .block(block)
+            .style(style)
+            .wrap(Wrap { trim: false })
+            .render(area, buf);
+    }
+}
+
+/// [`MemoryBrowserBackend`] over the memory service and the note and plan
+/// stores, which the service doesn't own
+pub struct MemoryServiceBackend {
+    memory: Arc<MemoryService>,
+    notes: Arc<RwLock<NotesStore>>,
+    plans: Arc<RwLock<PlanStore>>,
+}
+
+impl MemoryServiceBackend {
+    pub fn new(
+        memory: Arc<MemoryService>,
+        notes: Arc<RwLock<NotesStore>>,
+        plans: Arc<RwLock<PlanStore>>,
+    ) -> Self {
+        Self {
+            memory,
+            notes,
+            plans,
+        }
+    }
+
+    async fn list_transcripts(&self, query: &str) -> Result<Vec<MemoryItem>> {
+        let item = |session_id: Uuid,
+                    created_at: chrono::DateTime<chrono::Utc>,
+                    message_count: usize| MemoryItem {
+            id: MemoryItemId::Transcript(session_id),
+            title: created_at.format("%Y-%m-%d %H:%M").to_string(),
+            detail: format!("{} messages", message_count),
+        };
+
+        if query.is_empty() {
+            let sessions = self.memory.list_sessions().await?;
+            return Ok(sessions
+                .into_iter()
+                .map(|meta| item(meta.session_id, meta.created_at, meta.message_count))
+                .collect());
+        }
+        let results = self.memory.search(query, Some(SEARCH_LIMIT)).await?;
+        Ok(results
+            .transcript_matches
+            .into_iter()
+            .map(|result| {
+                item(
+                    result.session_id,
+                    result.metadata.created_at,
+                    result.metadata.message_count,
+                )
+            })
+            .collect())
+    }
+
+    async fn list_notes(&self, query: &str) -> Result<Vec<MemoryItem>> {
+        let mut notes = self.notes.write().await;
+        let item = |id: Uuid, title: String, pinned: bool, tags: &[String]| MemoryItem {
+            id: MemoryItemId::Note(id),
+            title: if pinned {
+                format!("* {}", title)
+            } else {
+                title
+            },
+            detail: tags
+                .iter()
+                .map(|tag| format!("#{}", tag))
+                .collect::<Vec<_>>()
+                .join(" "),
+        };
+
+        if query.is_empty() {
+            return Ok(notes
+                .list_notes()
+                .await?
+                .into_iter()
+                .map(|note| item(note.id, note.title, note.is_pinned, &note.tags))
+                .collect());
+        }
+        let filters = NoteSearchFilters {
+            limit: Some(SEARCH_LIMIT),
+            ..Default::default()
+        };
+        Ok(notes
+            .search_notes(query, filters)
+            .await?
+            .into_iter()
+            .map(|note| item(note.note_id, note.title, note.is_pinned, &note.tags))
+            .collect())
+    }
+
+    async fn list_plans(&self, query: &str) -> Result<Vec<MemoryItem>> {
+        let mut plans = self.plans.write().await;
+        if query.is_empty() {
+            return Ok(plans
+                .list_plans()
+                .await?
+                .into_iter()
+                .map(|plan| MemoryItem {
+                    id: MemoryItemId::Plan(plan.id),
+                    title: plan.title,
+                    detail: format!("{:?}", plan.status),
+                })
+                .collect());
+        }
+        Ok(plans
+            .search_plans(query, Some(SEARCH_LIMIT))
+            .await?
+            .into_iter()
+            .map(|plan| MemoryItem {
+                id: MemoryItemId::Plan(plan.plan_id),
+                title: plan.title,
+                detail: format!("{:?}", plan.status),
+            })
+            .collect())
+    }
+
+    async fn list_memory_files(&self, query: &str) -> Result<Vec<MemoryItem>> {
+        let mut items: Vec<MemoryItem> = if query.is_empty() {
+            self.memory
+                .list_memory_files()
+                .await?
+                .into_iter()
+                .map(|file| MemoryItem {
+                    id: MemoryItemId::MemoryFile(file.id),
+                    title: file.name,
+                    detail: file.file_type.to_string(),
+                })
+                .collect()
+        } else {
+            self.memory
+                .search_memory_files(query, Some(SEARCH_LIMIT))
+                .await?
+                .into_iter()
+                .map(|file| MemoryItem {
+                    id: MemoryItemId::MemoryFile(file.id),
+                    title: file.name,
+                    detail: file.file_type.to_string(),
+                })
+                .collect()
+        };
+
+        for project_id in self.memory.list_projects().await? {
+            for file_type in CLINE_FILES {
+                let title = file_type.filename();
+                if query.is_empty() || match_spans(&title, query).is_some() {
+                    items.push(MemoryItem {
+                        id: MemoryItemId::ClineFile(project_id, file_type),
+                        title,
+                        detail: format!("project {}", &project_id.to_string()[..8]),
+                    });
+                }
+            }
+        }
+        Ok(items)
+    }
+}
+
+#[async_trait]
+impl MemoryBrowserBackend for MemoryServiceBackend {
+    async fn list(&self, tab: MemoryTab, query: &str) -> Result<Vec<MemoryItem>> {
+        match tab {
+            MemoryTab::Transcripts => self.list_transcripts(query).await,
+            MemoryTab::Notes => self.list_notes(query).await,
+            MemoryTab::Plans => self.list_plans(query).await,
+            MemoryTab::MemoryFiles => self.list_memory_files(query).await,
+        }
+    }
+
+    async fn preview(&self, id: &MemoryItemId) -> Result<String> {
+        let not_found = || anyhow::anyhow!("{:?} no longer exists", id);
+        match id {
+            MemoryItemId::Transcript(session_id) => {
+                let transcript = self
+                    .memory
+                    .load_transcript(*session_id)
+                    .await?
+                    .ok_or_else(not_found)?;
+                let mut text = String::new();
+                if let Some(summary) = &transcript.summary {
+                    text.push_str(&format!("{}\n\n", summary));
+                }
+                if !transcript.tags.is_empty() {
+                    text.push_str(&format!("Tags: {}\n\n", transcript.tags.join(", ")));
+                }
+                for message in &transcript.transcript.messages {
+                    text.push_str(&format!("{:?}: {}\n", message.role, message.content));
+                }
+                Ok(text)
+            }
+            MemoryItemId::Note(note_id) => {
+                let note = self
+                    .notes
+                    .write()
+                    .await
+                    .load_note(*note_id)
+                    .await?
+                    .ok_or_else(not_found)?;
+                Ok(format!("{}\n\n{}", note.title, note.content))
+            }
+            MemoryItemId::Plan(plan_id) => {
+                let plan = self
+                    .plans
+                    .write()
+                    .await
+                    .load_plan(*plan_id)
+                    .await?
+                    .ok_or_else(not_found)?;
+                let mut text = format!("{}\n\n", plan.description);
+                for step in &plan.steps {
+                    text.push_str(&format!(
+                        "{}. [{:?}] {}\n",
+                        step.order + 1,
+                        step.status,
+                        if step.title.is_empty() {
+                            &step.description
+                        } else {
+                            &step.title
+                        }
+                    ));
+                }
+                Ok(text)
+            }
+            MemoryItemId::MemoryFile(file_id) => Ok(self
+                .memory
+                .load_memory_file(*file_id)
+                .await?
+                .ok_or_else(not_found)?
+                .content),
+            MemoryItemId::ClineFile(project_id, file_type) => {
+                self.cline_markdown(*project_id, file_type.clone()).await
+            }
+        }
+    }
+
+    async fn delete_note(&self, note_id: Uuid) -> Result<()> {
+        self.notes.write().await.delete_note(note_id).await
+    }
+
+    async fn tag_transcript(&self, session_id: Uuid, tag: &str) -> Result<()> {
+        self.memory
+            .add_session_tags(session_id, vec![tag.to_string()])
+            .await
+    }
+
+    async fn cline_markdown(&self, project_id: Uuid, file_type: ClineFileType) -> Result<String> {
+        self.memory
+            .render_cline_file(project_id, file_type.clone())
+            .await?
+            .ok_or_else(|| {
+                anyhow::anyhow!("No {} for project {}", file_type.filename(), project_id)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+    use std::sync::Mutex;
+
+    /// Backend serving fixed items per tab and recording every call
+    #[derive(Default)]
+    struct MockBackend {
+        items: HashMap<MemoryTab, Vec<MemoryItem>>,
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl MockBackend {
+        fn calls(&self) -> Vec<String> {
+            self.calls.lock().unwrap().clone()
+        }
+
+        fn record(&self, call: String) {
+            self.calls.lock().unwrap().push(call);
+        }
+    }
+
+    #[async_trait]
+    impl MemoryBrowserBackend for MockBackend {
+        async fn list(&self, tab: MemoryTab, query: &str) -> Result<Vec<MemoryItem>> {
+            self.record(format!("list {} '{}'", tab.title(), query));
+            let items = self.items.get(&tab).cloned().unwrap_or_default();
+            Ok(items
+                .into_iter()
+                .filter(|item| item.title.contains(query))
+                .collect())
+        }
+
+        async fn preview(&self, id: &MemoryItemId) -> Result<String> {
+            Ok(format!("preview of {:?}", id))
+        }
+
+        async fn delete_note(&self, note_id: Uuid) -> Result<()> {
+            self.record(format!("delete_note {}", note_id));
+            Ok(())
+        }
+
+        async fn tag_transcript(&self, session_id: Uuid, tag: &str) -> Result<()> {
+            self.record(format!("tag_transcript {} {}", session_id, tag));
+            Ok(())
+        }
+
+        async fn cline_markdown(
+            &self,
+            project_id: Uuid,
+            file_type: ClineFileType,
+        ) -> Result<String> {
+            self.record(format!("cline_markdown {} {:?}", project_id, file_type));
+            Ok("# Project Brief".to_string())
+        }
+    }
+
+    fn item(id: MemoryItemId, title: &str) -> MemoryItem {
+        MemoryItem {
+            id,
+            title: title.to_string(),
+            detail: String::new(),
+        }
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    async fn settle(browser: &mut MemoryBrowser) {
+        while browser.next_update().await {}
+    }
+
+    fn fixture() -> (Arc<MockBackend>, Uuid, Uuid) {
+        let (session, note) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut backend = MockBackend::default();
+        backend.items.insert(
+            MemoryTab::Transcripts,
+            vec![
+                item(MemoryItemId::Transcript(session), "refactor session"),
+                item(MemoryItemId::Transcript(Uuid::new_v4()), "debug session"),
+                item(MemoryItemId::Transcript(Uuid::new_v4()), "release session"),
+            ],
+        );
+        backend.items.insert(
+            MemoryTab::Notes,
+            vec![item(MemoryItemId::Note(note), "todo list")],
+        );
+        backend.items.insert(
+            MemoryTab::MemoryFiles,
+            vec![item(
+                MemoryItemId::ClineFile(Uuid::new_v4(), ClineFileType::ProjectBrief),
+                "projectbrief.md",
+            )],
+        );
+        (Arc::new(backend), session, note)
+    }
+
+    #[tokio::test]
+    async fn test_list_navigation_loads_previews() {
+        let (backend, session, _) = fixture();
+        let mut browser = MemoryBrowser::new(backend);
+        browser.reload();
+        assert!(browser.is_loading());
+        settle(&mut browser).await;
+
+        assert_eq!(browser.items().len(), 3);
+        assert_eq!(
+            browser.selected_item().unwrap().id,
+            MemoryItemId::Transcript(session)
+        );
+        assert_eq!(
+            browser.preview(),
+            Some(format!("preview of Transcript({})", session).as_str())
+        );
+
+        browser.handle_key(key(KeyCode::Char('j')));
+        browser.handle_key(key(KeyCode::Down));
+        browser.handle_key(key(KeyCode::Down));
+        assert_eq!(browser.selected_item().unwrap().title, "release session");
+        assert_eq!(browser.preview(), None);
+        settle(&mut browser).await;
+        assert!(browser.preview().unwrap().starts_with("preview of"));
+
+        browser.handle_key(key(KeyCode::Char('g')));
+        assert_eq!(browser.selected_item().unwrap().title, "refactor session");
+    }
+
+    #[tokio::test]
+    async fn test_tab_switching_and_search_reload_the_list() {
+        let (backend, _, _) = fixture();
+        let mut browser = MemoryBrowser::new(backend.clone());
+        browser.reload();
+        settle(&mut browser).await;
+
+        browser.handle_key(key(KeyCode::Tab));
+        assert_eq!(browser.tab(), MemoryTab::Notes);
+        settle(&mut browser).await;
+        assert_eq!(browser.items()[0].title, "todo list");
+
+        browser.handle_key(key(KeyCode::BackTab));
+        browser.handle_key(key(KeyCode::Char('/')));
+        for c in "deb".chars() {
+            browser.handle_key(key(KeyCode::Char(c)));
+        }
+        // Results of the superseded searches are dropped
+        settle(&mut browser).await;
+        assert_eq!(browser.query(), "deb");
+        assert_eq!(browser.items().len(), 1);
+        assert_eq!(browser.items()[0].title, "debug session");
+
+        browser.handle_key(key(KeyCode::Esc));
+        assert!(!browser.is_searching());
+        settle(&mut browser).await;
+        assert_eq!(browser.items().len(), 3);
+
+        browser.handle_key(key(KeyCode::Char('4')));
+        settle(&mut browser).await;
+        assert_eq!(
+            backend.calls(),
+            [
+                "list Transcripts ''",
+                "list Notes ''",
+                "list Transcripts ''",
+                "list Transcripts 'd'",
+                "list Transcripts 'de'",
+                "list Transcripts 'deb'",
+                "list Transcripts ''",
+                "list Memory Files ''",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_actions_call_the_matching_service_method() {
+        let (backend, session, note) = fixture();
+        let mut browser = MemoryBrowser::new(backend.clone());
+        browser.reload();
+        settle(&mut browser).await;
+
+        // Deleting only applies to notes
+        browser.handle_key(key(KeyCode::Char('d')));
+        browser.handle_key(key(KeyCode::Char('p')));
+        settle(&mut browser).await;
+        assert_eq!(browser.status(), Some("Tagged transcript as 'pinned'"));
+
+        browser.set_tab(MemoryTab::Notes);
+        settle(&mut browser).await;
+        browser.handle_key(key(KeyCode::Char('p')));
+        browser.handle_key(key(KeyCode::Char('d')));
+        settle(&mut browser).await;
+        assert_eq!(browser.status(), Some("Deleted note 'todo list'"));
+
+        browser.set_tab(MemoryTab::MemoryFiles);
+        settle(&mut browser).await;
+        browser.handle_key(key(KeyCode::Char('o')));
+        settle(&mut browser).await;
+        assert_eq!(browser.opened_markdown(), Some("# Project Brief"));
+        browser.handle_key(key(KeyCode::Esc));
+        assert_eq!(browser.opened_markdown(), None);
+
+        let actions: Vec<_> = backend
+            .calls()
+            .into_iter()
+            .filter(|call| !call.starts_with("list"))
+            .collect();
+        assert_eq!(actions.len(), 3);
+        assert_eq!(actions[0], format!("tag_transcript {} pinned", session));
+        assert_eq!(actions[1], format!("delete_note {}", note));
+        assert!(actions[2].starts_with("cline_markdown") && actions[2].ends_with("ProjectBrief"));
+    }
+
+    #[tokio::test]
+    async fn test_render_shows_spinner_until_loaded() {
+        let (backend, _, _) = fixture();
+        let mut browser = MemoryBrowser::new(backend);
+        let theme = ThemeManager::new();
+        let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+        let title = |terminal: &Terminal<TestBackend>| {
+            let buffer = terminal.backend().buffer();
+            (1..12)
+                .map(|x| buffer.get(x, 0).symbol.as_str())
+                .collect::<String>()
+        };
+
+        browser.reload();
+        terminal
+            .draw(|frame| browser.render(frame.size(), frame.buffer_mut(), &theme, true))
+            .unwrap();
+        assert!(title(&terminal).starts_with("Memory ⠋"));
+
+        settle(&mut browser).await;
+        terminal
+            .draw(|frame| browser.render(frame.size(), frame.buffer_mut(), &theme, true))
+            .unwrap();
+        assert!(title(&terminal).starts_with("Memory─"));
+        let buffer = terminal.backend().buffer();
+        let row = |y: u16| {
+            (0..80)
+                .map(|x| buffer.get(x, y).symbol.as_str())
+                .collect::<String>()
+        };
+        assert!(row(1).contains("Transcripts") && row(1).contains("Memory Files"));
+        assert!(row(5).contains("> refactor session"));
+    }
+}
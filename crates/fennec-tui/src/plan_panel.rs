@@ -0,0 +1,921 @@
+//! Plan view panel with live step status.
+//!
+//! [`PlanPanel`] lists plans and their steps and follows a [`PlanStore`]
+//! through its event stream, so status changes made anywhere show up as they
+//! happen. Key presses on a step turn into [`PlanPanelAction`]s, which a
+//! [`PlanActionRunner`] carries out against the plan store and the command
+//! registry.
+
+use crate::theme::{ComponentType, ThemeManager};
+use anyhow::{Context, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use fennec_commands::{CommandContext, CommandExecutionResult, CommandRegistry};
+use fennec_memory::{
+    CommandPlan, PlanEvent, PlanExecutionResult, PlanMetrics, PlanStatus, PlanStore, StepStatus,
+};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Widget, Wrap},
+};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, RwLock};
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+/// Registry command that executes a step's suggested command
+const STEP_COMMAND: &str = "run";
+
+/// Change to a plan requested from the panel
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlanPanelAction {
+    /// Mark a step completed or skipped
+    SetStepStatus {
+        plan_id: Uuid,
+        step_id: Uuid,
+        status: StepStatus,
+    },
+    /// Execute the step's suggested command
+    ExecuteStep {
+        plan_id: Uuid,
+        step_id: Uuid,
+        command: String,
+    },
+}
+
+/// A row of the panel: a plan header or one of its steps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Row {
+    Plan(Uuid),
+    Step(Uuid, Uuid),
+}
+
+/// Panel listing plans with their steps
+#[derive(Debug, Default)]
+pub struct PlanPanel {
+    /// Plans shown, newest first
+    plans: Vec<CommandPlan>,
+    collapsed: HashSet<Uuid>,
+    /// Selected row, kept by identity so updates don't move the selection
+    selected: Option<Row>,
+    /// First row in view
+    offset: usize,
+    events: Option<broadcast::Receiver<PlanEvent>>,
+}
+
+impl PlanPanel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Follow the changes published on `events`
+    pub fn subscribe(&mut self, events: broadcast::Receiver<PlanEvent>) {
+        self.events = Some(events);
+    }
+
+    /// Show `plans`, e.g. those of the session loaded at startup
+    pub fn set_plans(&mut self, plans: Vec<CommandPlan>) {
+        self.plans.clear();
+        for plan in plans {
+            self.upsert(plan);
+        }
+        self.fix_selection(0);
+    }
+
+    /// Apply every change received since the last call. Returns whether
+    /// anything changed.
+    pub fn poll_events(&mut self) -> bool {
+        let mut received = Vec::new();
+        if let Some(events) = self.events.as_mut() {
+            loop {
+                match events.try_recv() {
+                    Ok(event) => received.push(event),
+                    Err(broadcast::error::TryRecvError::Lagged(missed)) => {
+                        // Later events carry whole plans, so the view catches up
+                        warn!("Plan panel missed {} plan events", missed);
+                    }
+                    Err(broadcast::error::TryRecvError::Empty) => break,
+                    Err(broadcast::error::TryRecvError::Closed) => {
+                        self.events = None;
+                        break;
+                    }
+                }
+            }
+        }
+
+        let changed = !received.is_empty();
+        for event in received {
+            self.apply_event(event);
+        }
+        changed
+    }
+
+    /// Apply one change to the plans shown
+    pub fn apply_event(&mut self, event: PlanEvent) {
+        let index = self.selected_index().unwrap_or(0);
+        match event {
+            PlanEvent::Created(plan) | PlanEvent::Updated(plan) => {
+                debug!("Plan panel showing {:?} of plan {}", plan.status, plan.id);
+                self.upsert(plan);
+            }
+            PlanEvent::Deleted(plan_id) => {
+                self.plans.retain(|plan| plan.id != plan_id);
+                self.collapsed.remove(&plan_id);
+            }
+        }
+        self.fix_selection(index);
+    }
+
+    pub fn plans(&self) -> &[CommandPlan] {
+        &self.plans
+    }
+
+    pub fn is_collapsed(&self, plan_id: Uuid) -> bool {
+        self.collapsed.contains(&plan_id)
+    }
+
+    pub fn toggle_collapsed(&mut self, plan_id: Uuid) {
+        if !self.collapsed.remove(&plan_id) {
+            self.collapsed.insert(plan_id);
+        }
+        let index = self.selected_index().unwrap_or(0);
+        self.fix_selection(index);
+    }
+
+    /// Plan and step of the selected row; the step is `None` on a plan
+    /// header
+    pub fn selected(&self) -> Option<(Uuid, Option<Uuid>)> {
+        self.selected.map(|row| match row {
+            Row::Plan(plan_id) => (plan_id, None),
+            Row::Step(plan_id, step_id) => (plan_id, Some(step_id)),
+        })
+    }
+
+    pub fn select_next(&mut self) {
+        let rows = self.rows();
+        if let Some(index) = self.selected_index() {
+            self.selected = rows.get(index + 1).or(rows.last()).copied();
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        let rows = self.rows();
+        if let Some(index) = self.selected_index() {
+            self.selected = rows.get(index.saturating_sub(1)).copied();
+        }
+    }
+
+    /// Handle a key press while the panel is focused. Returns the action
+    /// the key asks for, if any.
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<PlanPanelAction> {
+        if key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            return None;
+        }
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => self.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => self.select_previous(),
+            KeyCode::Home | KeyCode::Char('g') => {
+                self.selected = self.rows().first().copied();
+            }
+            KeyCode::End | KeyCode::Char('G') => {
+                self.selected = self.rows().last().copied();
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                if let Some(Row::Plan(plan_id)) = self.selected {
+                    self.toggle_collapsed(plan_id);
+                }
+            }
+            KeyCode::Left | KeyCode::Char('h') => {
+                if let Some((plan_id, _)) = self.selected() {
+                    if !self.is_collapsed(plan_id) {
+                        self.toggle_collapsed(plan_id);
+                    }
+                }
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                if let Some((plan_id, _)) = self.selected() {
+                    if self.is_collapsed(plan_id) {
+                        self.toggle_collapsed(plan_id);
+                    }
+                }
+            }
+            KeyCode::Char('c') => return self.step_action(StepStatus::Completed),
+            KeyCode::Char('s') => return self.step_action(StepStatus::Skipped),
+            KeyCode::Char('x') => {
+                let Some(Row::Step(plan_id, step_id)) = self.selected else {
+                    return None;
+                };
+                let command = self.step(plan_id, step_id)?.suggested_commands.first()?;
+                return Some(PlanPanelAction::ExecuteStep {
+                    plan_id,
+                    step_id,
+                    command: command.clone(),
+                });
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Render the panel, scrolling to keep the selected row in view
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, theme: &ThemeManager, focused: bool) {
+        let border_style = if focused {
+            theme.get_style(ComponentType::Highlight)
+        } else {
+            theme.get_style(ComponentType::Border)
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Plans")
+            .title_style(theme.get_style(ComponentType::Title))
+            .border_style(border_style);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        if self.plans.is_empty() {
+            Paragraph::new("No active plans. Create one with the plan command.")
+                .style(theme.get_style(ComponentType::Muted))
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true })
+                .render(inner, buf);
+            return;
+        }
+
+        let rows = self.rows();
+        let height = inner.height as usize;
+        if let Some(selected) = self.selected_index() {
+            if selected < self.offset {
+                self.offset = selected;
+            } else if height > 0 && selected >= self.offset + height {
+                self.offset = selected + 1 - height;
+            }
+        }
+        self.offset = self.offset.min(rows.len().saturating_sub(height.max(1)));
+
+        let lines: Vec<Line> = rows
+            .iter()
+            .skip(self.offset)
+            .take(height)
+            .map(|row| {
+                let mut line = self.row_line(*row, theme);
+                if Some(*row) == self.selected && focused {
+                    line.patch_style(theme.get_style(ComponentType::Selection));
+                }
+                line
+            })
+            .collect();
+        Paragraph::new(lines)
+            .style(theme.get_style(ComponentType::Text))
+            .render(inner, buf);
+    }
+
+    fn row_line(&self, row: Row, theme: &ThemeManager) -> Line<'_> {
+        match row {
+            Row::Plan(plan_id) => {
+                let Some(plan) = self.plan(plan_id) else {
+                    return Line::from("");
+                };
+                let done = plan
+                    .steps
+                    .iter()
+                    .filter(|step| {
+                        matches!(step.status, StepStatus::Completed | StepStatus::Skipped)
+                    })
+                    .count();
+                let marker = if self.is_collapsed(plan_id) {
+                    "▸"
+                } else {
+                    "▾"
+                };
+                let mut progress = format!(" {}/{} ", done, plan.steps.len());
+                let metrics = PlanMetrics::from_plan(plan);
+                if !metrics.total_estimated.is_zero() {
+                    progress.push_str(&format!(
+                        "{} of {} ",
+                        format_duration(metrics.total_actual),
+                        format_duration(metrics.total_estimated)
+                    ));
+                }
+                Line::from(vec![
+                    Span::raw(format!("{} ", marker)),
+                    Span::styled(plan.title.as_str(), theme.get_style(ComponentType::Title)),
+                    Span::styled(progress, theme.get_style(ComponentType::Muted)),
+                    Span::styled(
+                        format!("{:?}", plan.status),
+                        plan_status_style(&plan.status, theme),
+                    ),
+                ])
+            }
+            Row::Step(plan_id, step_id) => {
+                let Some(step) = self.step(plan_id, step_id) else {
+                    return Line::from("");
+                };
+                let title = if step.title.is_empty() {
+                    step.description.lines().next().unwrap_or_default()
+                } else {
+                    step.title.as_str()
+                };
+                Line::from(vec![
+                    Span::styled(
+                        format!("  {} ", step_status_icon(&step.status)),
+                        step_status_style(&step.status, theme),
+                    ),
+                    Span::raw(format!("{}. {}", step.order + 1, title)),
+                ])
+            }
+        }
+    }
+
+    fn step_action(&self, status: StepStatus) -> Option<PlanPanelAction> {
+        match self.selected {
+            Some(Row::Step(plan_id, step_id)) => Some(PlanPanelAction::SetStepStatus {
+                plan_id,
+                step_id,
+                status,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Insert or replace `plan`, dropping cancelled plans and collapsing
+    /// plans as they finish
+    fn upsert(&mut self, plan: CommandPlan) {
+        let previous = self.plans.iter().position(|p| p.id == plan.id);
+        let was_finished = previous
+            .map(|index| is_finished(&self.plans[index].status))
+            .unwrap_or(false);
+        if let Some(index) = previous {
+            self.plans.remove(index);
+        }
+        if plan.status == PlanStatus::Cancelled {
+            self.collapsed.remove(&plan.id);
+            return;
+        }
+
+        if is_finished(&plan.status) && !was_finished {
+            self.collapsed.insert(plan.id);
+        }
+        let index = self
+            .plans
+            .iter()
+            .position(|p| p.created_at < plan.created_at)
+            .unwrap_or(self.plans.len());
+        self.plans.insert(index, plan);
+    }
+
+    fn rows(&self) -> Vec<Row> {
+        let mut rows = Vec::new();
+        for plan in &self.plans {
+            rows.push(Row::Plan(plan.id));
+            if !self.is_collapsed(plan.id) {
+                let mut steps: Vec<_> = plan.steps.iter().collect();
+                steps.sort_by_key(|step| step.order);
+                rows.extend(steps.into_iter().map(|step| Row::Step(plan.id, step.id)));
+            }
+        }
+        rows
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        let selected = self.selected?;
+        self.rows().iter().position(|row| *row == selected)
+    }
+
+    /// Keep the selection on a row that exists, near `index` if it vanished.
+    /// A step hidden by collapsing its plan selects the plan.
+    fn fix_selection(&mut self, index: usize) {
+        let rows = self.rows();
+        if let Some(selected) = self.selected {
+            if rows.contains(&selected) {
+                return;
+            }
+            if let Row::Step(plan_id, _) = selected {
+                if rows.contains(&Row::Plan(plan_id)) {
+                    self.selected = Some(Row::Plan(plan_id));
+                    return;
+                }
+            }
+        }
+        self.selected = rows.get(index.min(rows.len().saturating_sub(1))).copied();
+    }
+
+    fn plan(&self, plan_id: Uuid) -> Option<&CommandPlan> {
+        self.plans.iter().find(|plan| plan.id == plan_id)
+    }
+
+    fn step(&self, plan_id: Uuid, step_id: Uuid) -> Option<&fennec_memory::PlanStep> {
+        self.plan(plan_id)?
+            .steps
+            .iter()
+            .find(|step| step.id == step_id)
+    }
+}
+
+/// Compact effort for the plan header, e.g. `1h 05m` or `40s`
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match (secs / 3600, secs % 3600 / 60) {
+        (0, 0) => format!("{}s", secs),
+        (0, minutes) => format!("{}m", minutes),
+        (hours, minutes) => format!("{}h {:02}m", hours, minutes),
+    }
+}
+
+fn is_finished(status: &PlanStatus) -> bool {
+    matches!(status, PlanStatus::Completed | PlanStatus::Failed)
+}
+
+fn step_status_icon(status: &StepStatus) -> &'static str {
+    match status {
+        StepStatus::Pending => "○",
+        StepStatus::InProgress => "◐",
+        StepStatus::Completed => "✓",
+        StepStatus::Failed => "✗",
+        StepStatus::Skipped => "↷",
+        StepStatus::Blocked => "⊘",
+    }
+}
+
+fn step_status_style(status: &StepStatus, theme: &ThemeManager) -> Style {
+    match status {
+        StepStatus::Pending | StepStatus::Skipped => theme.get_style(ComponentType::Muted),
+        StepStatus::InProgress => theme.get_style(ComponentType::Info),
+        StepStatus::Completed => theme.get_style(ComponentType::Success),
+        StepStatus::Failed => theme.get_style(ComponentType::Error),
+        StepStatus::Blocked => theme.get_style(ComponentType::Warning),
+    }
+}
+
+fn plan_status_style(status: &PlanStatus, theme: &ThemeManager) -> Style {
+    match status {
+        PlanStatus::Draft | PlanStatus::Ready | PlanStatus::OnHold => {
+            theme.get_style(ComponentType::Muted)
+        }
+        PlanStatus::InProgress => theme
+            .get_style(ComponentType::Info)
+            .add_modifier(Modifier::BOLD),
+        PlanStatus::Completed => theme.get_style(ComponentType::Success),
+        PlanStatus::Failed | PlanStatus::Cancelled => theme.get_style(ComponentType::Error),
+    }
+}
+
+/// Carries out [`PlanPanelAction`]s against a plan store and the command
+/// registry. The store publishes the resulting changes, which is how they
+/// reach the panel.
+pub struct PlanActionRunner {
+    store: Arc<RwLock<PlanStore>>,
+    registry: Arc<CommandRegistry>,
+}
+
+impl PlanActionRunner {
+    pub fn new(store: Arc<RwLock<PlanStore>>, registry: Arc<CommandRegistry>) -> Self {
+        Self { store, registry }
+    }
+
+    /// Apply `action`, returning the command result when it executed a step
+    pub async fn run(
+        &self,
+        action: PlanPanelAction,
+        context: &CommandContext,
+    ) -> Result<Option<CommandExecutionResult>> {
+        match action {
+            PlanPanelAction::SetStepStatus {
+                plan_id,
+                step_id,
+                status,
+            } => {
+                self.store
+                    .write()
+                    .await
+                    .update_step_status(plan_id, step_id, status)
+                    .await?;
+                Ok(None)
+            }
+            PlanPanelAction::ExecuteStep {
+                plan_id,
+                step_id,
+                command,
+            } => {
+                self.store
+                    .write()
+                    .await
+                    .update_step_status(plan_id, step_id, StepStatus::InProgress)
+                    .await?;
+
+                let args = serde_json::json!({ "command": command });
+                let result = self
+                    .registry
+                    .execute_command(STEP_COMMAND, &args, context)
+                    .await
+                    .with_context(|| format!("Failed to execute step command: {}", command));
+
+                let mut store = self.store.write().await;
+                let (status, outcome) = match &result {
+                    Ok(result) => (
+                        if result.success {
+                            StepStatus::Completed
+                        } else {
+                            StepStatus::Failed
+                        },
+                        PlanExecutionResult {
+                            success: result.success,
+                            summary: result
+                                .error
+                                .clone()
+                                .unwrap_or_else(|| result.output.clone()),
+                            details: None,
+                            metrics: HashMap::from([(
+                                "execution_time_ms".to_string(),
+                                result.execution_time_ms.to_string(),
+                            )]),
+                            artifacts: Vec::new(),
+                            follow_up_actions: Vec::new(),
+                        },
+                    ),
+                    Err(e) => (
+                        StepStatus::Failed,
+                        PlanExecutionResult {
+                            success: false,
+                            summary: e.to_string(),
+                            details: None,
+                            metrics: HashMap::new(),
+                            artifacts: Vec::new(),
+                            follow_up_actions: Vec::new(),
+                        },
+                    ),
+                };
+                store
+                    .associate_command(plan_id, step_id, command, outcome)
+                    .await?;
+                store.update_step_status(plan_id, step_id, status).await?;
+
+                result.map(Some)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use fennec_commands::{CommandDescriptor, CommandExecutor};
+    use fennec_core::command::{CommandPreview, CommandResult};
+    use fennec_memory::PlanStep;
+    use fennec_security::SandboxLevel;
+    use ratatui::{backend::TestBackend, Terminal};
+    use tempfile::TempDir;
+    use tokio_util::sync::CancellationToken;
+
+    fn step(order: u32, title: &str, commands: &[&str]) -> PlanStep {
+        PlanStep {
+            id: Uuid::new_v4(),
+            order,
+            title: title.to_string(),
+            description: String::new(),
+            status: StepStatus::Pending,
+            estimated_effort: None,
+            estimated_duration: None,
+            actual_duration: None,
+            dependencies: Vec::new(),
+            suggested_commands: commands.iter().map(|c| c.to_string()).collect(),
+            command_associations: Vec::new(),
+            notes: Vec::new(),
+            started_at: None,
+            completed_at: None,
+        }
+    }
+
+    fn plan(title: &str, steps: Vec<PlanStep>) -> CommandPlan {
+        let now = chrono::Utc::now();
+        CommandPlan {
+            id: Uuid::new_v4(),
+            session_id: Uuid::new_v4(),
+            title: title.to_string(),
+            description: String::new(),
+            steps,
+            status: PlanStatus::Ready,
+            created_at: now,
+            updated_at: now,
+            execution_results: Vec::new(),
+            templates_used: Vec::new(),
+            tags: Vec::new(),
+            priority: Default::default(),
+            estimated_effort: None,
+            actual_duration: None,
+        }
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    /// Text of each row inside the panel's border
+    fn rendered_rows(panel: &mut PlanPanel, width: u16, height: u16) -> Vec<String> {
+        let theme = ThemeManager::new();
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal
+            .draw(|frame| {
+                let area = frame.size();
+                panel.render(area, frame.buffer_mut(), &theme, true)
+            })
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        (1..height - 1)
+            .map(|y| {
+                (1..width - 1)
+                    .map(|x| buffer.get(x, y).symbol.as_str())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_empty_state() {
+        let mut panel = PlanPanel::new();
+        let rows = rendered_rows(&mut panel, 60, 5);
+        assert!(rows
+            .iter()
+            .any(|row| row.contains("No active plans. Create one with the plan command.")));
+    }
+
+    #[test]
+    fn test_status_events_update_rendered_rows() {
+        let (sender, receiver) = broadcast::channel(16);
+        let mut panel = PlanPanel::new();
+        panel.subscribe(receiver);
+
+        let mut refactor = plan(
+            "Refactor parser",
+            vec![step(0, "Write tests", &[]), step(1, "Split module", &[])],
+        );
+        sender.send(PlanEvent::Created(refactor.clone())).unwrap();
+        assert!(panel.poll_events());
+        assert_eq!(
+            rendered_rows(&mut panel, 40, 6)[..3],
+            [
+                "▾ Refactor parser 0/2 Ready",
+                "  ○ 1. Write tests",
+                "  ○ 2. Split module",
+            ]
+        );
+
+        refactor.status = PlanStatus::InProgress;
+        refactor.steps[0].status = StepStatus::Completed;
+        refactor.steps[1].status = StepStatus::InProgress;
+        sender.send(PlanEvent::Updated(refactor.clone())).unwrap();
+        assert!(panel.poll_events());
+        assert_eq!(
+            rendered_rows(&mut panel, 40, 6)[..3],
+            [
+                "▾ Refactor parser 1/2 InProgress",
+                "  ✓ 1. Write tests",
+                "  ◐ 2. Split module",
+            ]
+        );
+
+        // Finishing collapses the plan
+        refactor.status = PlanStatus::Completed;
+        refactor.steps[1].status = StepStatus::Skipped;
+        sender.send(PlanEvent::Updated(refactor.clone())).unwrap();
+        panel.poll_events();
+        assert_eq!(
+            rendered_rows(&mut panel, 40, 6)[..2],
+            ["▸ Refactor parser 2/2 Completed", ""]
+        );
+
+        sender.send(PlanEvent::Deleted(refactor.id)).unwrap();
+        panel.poll_events();
+        assert!(panel.plans().is_empty());
+        assert!(!panel.poll_events());
+    }
+
+    #[test]
+    fn test_header_shows_actual_against_estimated_effort() {
+        let mut panel = PlanPanel::new();
+        let mut migrate = plan(
+            "Migrate",
+            vec![step(0, "Dump", &[]), step(1, "Restore", &[])],
+        );
+        migrate.steps[0].estimated_duration = Some(Duration::from_secs(30 * 60));
+        migrate.steps[1].estimated_duration = Some(Duration::from_secs(60 * 60));
+        migrate.steps[0].status = StepStatus::Completed;
+        migrate.steps[0].actual_duration = Some(Duration::from_secs(45 * 60));
+        panel.set_plans(vec![migrate]);
+
+        assert_eq!(
+            rendered_rows(&mut panel, 50, 5)[0],
+            "▾ Migrate 1/2 45m of 1h 30m Ready"
+        );
+        assert_eq!(format_duration(Duration::from_secs(40)), "40s");
+        assert_eq!(format_duration(Duration::from_secs(3900)), "1h 05m");
+    }
+
+    #[test]
+    fn test_keys_act_on_selected_step() {
+        let deploy = plan(
+            "Deploy",
+            vec![
+                step(0, "Build", &["cargo build --release"]),
+                step(1, "Announce", &[]),
+            ],
+        );
+        let (plan_id, build, announce) = (deploy.id, deploy.steps[0].id, deploy.steps[1].id);
+        let mut panel = PlanPanel::new();
+        panel.set_plans(vec![deploy]);
+
+        // Plan header rows have no step to act on
+        assert_eq!(panel.selected(), Some((plan_id, None)));
+        assert_eq!(panel.handle_key(key(KeyCode::Char('c'))), None);
+
+        panel.handle_key(key(KeyCode::Char('j')));
+        assert_eq!(
+            panel.handle_key(key(KeyCode::Char('x'))),
+            Some(PlanPanelAction::ExecuteStep {
+                plan_id,
+                step_id: build,
+                command: "cargo build --release".to_string(),
+            })
+        );
+        assert_eq!(
+            panel.handle_key(key(KeyCode::Char('c'))),
+            Some(PlanPanelAction::SetStepStatus {
+                plan_id,
+                step_id: build,
+                status: StepStatus::Completed,
+            })
+        );
+
+        panel.handle_key(key(KeyCode::Down));
+        assert_eq!(panel.handle_key(key(KeyCode::Char('x'))), None);
+        assert_eq!(
+            panel.handle_key(key(KeyCode::Char('s'))),
+            Some(PlanPanelAction::SetStepStatus {
+                plan_id,
+                step_id: announce,
+                status: StepStatus::Skipped,
+            })
+        );
+    }
+
+    #[test]
+    fn test_collapse_and_scroll_keep_selection_visible() {
+        let older = plan("Older", (0..6).map(|i| step(i, "step", &[])).collect());
+        let mut newer = plan("Newer", vec![step(0, "only", &[])]);
+        newer.created_at = older.created_at + chrono::Duration::seconds(1);
+        let older_id = older.id;
+        let mut panel = PlanPanel::new();
+        panel.set_plans(vec![older, newer]);
+
+        // Newest first
+        panel.handle_key(key(KeyCode::End));
+        let rows = rendered_rows(&mut panel, 30, 6);
+        assert_eq!(rows.last().unwrap(), "  ○ 6. step");
+        assert!(!rows.iter().any(|row| row.contains("Newer")));
+
+        // Collapsing from a step selects its plan
+        panel.handle_key(key(KeyCode::Left));
+        assert_eq!(panel.selected(), Some((older_id, None)));
+        assert!(panel.is_collapsed(older_id));
+        let rows = rendered_rows(&mut panel, 30, 6);
+        assert_eq!(
+            rows,
+            ["▾ Newer 0/1 Ready", "  ○ 1. only", "▸ Older 0/6 Ready", ""]
+        );
+
+        panel.handle_key(key(KeyCode::Enter));
+        assert!(!panel.is_collapsed(older_id));
+    }
+
+    /// Stand-in for the shell command, failing commands containing "fail"
+    struct FakeRun {
+        descriptor: CommandDescriptor,
+    }
+
+    #[async_trait]
+    impl CommandExecutor for FakeRun {
+        fn descriptor(&self) -> &CommandDescriptor {
+            &self.descriptor
+        }
+
+        async fn preview(
+            &self,
+            _args: &serde_json::Value,
+            _context: &CommandContext,
+        ) -> anyhow::Result<CommandPreview> {
+            unreachable!()
+        }
+
+        async fn execute(
+            &self,
+            args: &serde_json::Value,
+            _context: &CommandContext,
+        ) -> anyhow::Result<CommandResult> {
+            let command = args["command"].as_str().unwrap_or_default();
+            let success = !command.contains("fail");
+            Ok(CommandResult {
+                command_id: Uuid::new_v4(),
+                success,
+                output: format!("ran {}", command),
+                error: (!success).then(|| "exit status 1".to_string()),
+                data: None,
+            })
+        }
+
+        fn validate_args(&self, _args: &serde_json::Value) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_runner_executes_steps_through_registry() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = PlanStore::with_storage_dir(temp_dir.path()).unwrap();
+        let mut panel = PlanPanel::new();
+        panel.subscribe(store.subscribe());
+
+        let release = plan(
+            "Release",
+            vec![
+                step(0, "Test", &["cargo test"]),
+                step(1, "Lint", &["fail lint"]),
+            ],
+        );
+        let (plan_id, test_step, lint_step) =
+            (release.id, release.steps[0].id, release.steps[1].id);
+        store.register_plan(release).await.unwrap();
+
+        let registry = Arc::new(CommandRegistry::new());
+        registry
+            .register_builtin(Arc::new(FakeRun {
+                descriptor: CommandDescriptor {
+                    name: STEP_COMMAND.to_string(),
+                    description: "Run a shell command".to_string(),
+                    version: "1.0.0".to_string(),
+                    author: None,
+                    capabilities_required: Vec::new(),
+                    sandbox_level_required: SandboxLevel::ReadOnly,
+                    supports_preview: false,
+                    supports_dry_run: false,
+                    timeout: None,
+                    alias_of: None,
+                },
+            }))
+            .await
+            .unwrap();
+        let runner = PlanActionRunner::new(Arc::new(RwLock::new(store)), registry);
+        let context = CommandContext {
+            session_id: Uuid::new_v4(),
+            user_id: None,
+            workspace_path: None,
+            sandbox_level: SandboxLevel::ReadOnly,
+            dry_run: false,
+            preview_only: false,
+            cancellation_token: CancellationToken::new(),
+            action_log: None,
+            timeout: None,
+            progress: None,
+        };
+
+        for step_id in [test_step, lint_step] {
+            let command = {
+                panel.poll_events();
+                panel.step(plan_id, step_id).unwrap().suggested_commands[0].clone()
+            };
+            runner
+                .run(
+                    PlanPanelAction::ExecuteStep {
+                        plan_id,
+                        step_id,
+                        command,
+                    },
+                    &context,
+                )
+                .await
+                .unwrap();
+        }
+
+        panel.poll_events();
+        let shown = &panel.plans()[0];
+        assert_eq!(shown.steps[0].status, StepStatus::Completed);
+        assert_eq!(
+            shown.steps[0].command_associations[0].result.summary,
+            "ran cargo test"
+        );
+        assert_eq!(shown.steps[1].status, StepStatus::Failed);
+        assert_eq!(
+            shown.steps[1].command_associations[0].result.summary,
+            "exit status 1"
+        );
+        assert_eq!(shown.status, PlanStatus::Failed);
+    }
+}
@@ -0,0 +1,345 @@
+//! The sandbox as the user sees it: a status bar segment that is always
+//! on screen, and a dialog for tightening the sandbox mid-session.
+//!
+//! The segment shows the level, color-coded and flashing at full access,
+//! whether approvals are asked for, and the workspace. The dialog offers
+//! only levels at or below the current one; loosening the sandbox takes a
+//! restart so it is never done by a stray key press.
+
+use crate::components::StatusItem;
+use crate::theme::{ComponentType, ThemeManager};
+use crossterm::event::{KeyCode, KeyEvent};
+use fennec_security::{SandboxLevel, SandboxPolicy};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, StatefulWidget, Widget},
+};
+use std::path::Path;
+
+/// Levels in the dialog, most restrictive first
+const LEVELS: [SandboxLevel; 3] = [
+    SandboxLevel::ReadOnly,
+    SandboxLevel::WorkspaceWrite,
+    SandboxLevel::FullAccess,
+];
+
+/// Longest workspace path shown before its leading directories are elided
+const MAX_WORKSPACE_LEN: usize = 40;
+
+/// Label and style of a sandbox level. Full access alternates between two
+/// styles with `flash` so it can't go unnoticed.
+fn level_display(level: &SandboxLevel, flash: bool) -> (&'static str, ComponentType) {
+    match level {
+        SandboxLevel::ReadOnly => ("🔒 READ-ONLY", ComponentType::Success),
+        SandboxLevel::WorkspaceWrite => ("📝 WORKSPACE", ComponentType::Info),
+        SandboxLevel::FullAccess if flash => ("⚠️ FULL ACCESS", ComponentType::Critical),
+        SandboxLevel::FullAccess => ("⚠️ FULL ACCESS", ComponentType::Warning),
+    }
+}
+
+/// Status bar items for `policy`: sandbox level, approval mode and
+/// workspace
+pub fn sandbox_status_items(policy: &SandboxPolicy, flash: bool) -> Vec<StatusItem> {
+    let (level, level_style) = level_display(policy.level(), flash);
+    let (approval, approval_style) = if policy.requires_approval() {
+        ("🛡️ ON", ComponentType::StatusActive)
+    } else {
+        ("OFF", ComponentType::Warning)
+    };
+
+    vec![
+        StatusItem {
+            label: "Sandbox".to_string(),
+            value: level.to_string(),
+            style: level_style,
+        },
+        StatusItem {
+            label: "Approval".to_string(),
+            value: approval.to_string(),
+            style: approval_style,
+        },
+        StatusItem {
+            label: "Workspace".to_string(),
+            value: workspace_label(policy.workspace_path(), std::env::var("HOME").ok()),
+            style: ComponentType::Muted,
+        },
+    ]
+}
+
+/// `path` with the home directory as `~`, and its leading directories
+/// elided when it is long
+fn workspace_label(path: &Path, home: Option<String>) -> String {
+    let path = path.display().to_string();
+    let path = match home.filter(|home| !home.is_empty()) {
+        Some(home) if path == home => "~".to_string(),
+        Some(home) => match path.strip_prefix(&format!("{}/", home.trim_end_matches('/'))) {
+            Some(rest) => format!("~/{}", rest),
+            None => path,
+        },
+        None => path,
+    };
+
+    let chars: Vec<char> = path.chars().collect();
+    if chars.len() <= MAX_WORKSPACE_LEN {
+        return path;
+    }
+    let tail: String = chars[chars.len() - (MAX_WORKSPACE_LEN - 1)..]
+        .iter()
+        .collect();
+    // Start at a directory boundary where there is one
+    match tail.find('/') {
+        Some(slash) => format!("…{}", &tail[slash..]),
+        None => format!("…{}", tail),
+    }
+}
+
+/// Dialog for lowering the sandbox level
+#[derive(Debug, Default)]
+pub struct SandboxDialog {
+    /// Level in force when the dialog opened; `None` while closed
+    current: Option<SandboxLevel>,
+    selected: usize,
+    /// Why the last choice was refused
+    notice: Option<String>,
+    list_state: ListState,
+}
+
+impl SandboxDialog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.current.is_some()
+    }
+
+    /// Open with `current` selected
+    pub fn open(&mut self, current: SandboxLevel) {
+        self.selected = LEVELS
+            .iter()
+            .position(|level| *level == current)
+            .unwrap_or(0);
+        self.current = Some(current);
+        self.notice = None;
+    }
+
+    pub fn close(&mut self) {
+        self.current = None;
+        self.notice = None;
+    }
+
+    /// Why the last choice was refused, if it was
+    pub fn notice(&self) -> Option<&str> {
+        self.notice.as_deref()
+    }
+
+    /// Handle a key press while open. Returns the level chosen when it is
+    /// lower than the current one; choosing a higher level is refused and
+    /// leaves the dialog open.
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<SandboxLevel> {
+        let current = self.current.clone()?;
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.close(),
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+                self.notice = None;
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(LEVELS.len() - 1);
+                self.notice = None;
+            }
+            KeyCode::Enter => {
+                let chosen = LEVELS[self.selected].clone();
+                if chosen > current {
+                    self.notice =
+                        Some(format!("Raising the sandbox to {} takes a restart", chosen));
+                    return None;
+                }
+                self.close();
+                return (chosen < current).then_some(chosen);
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Render the dialog
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, theme: &ThemeManager) {
+        let Some(current) = self.current.clone() else {
+            return;
+        };
+        Clear.render(area, buf);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(theme.get_style(ComponentType::Highlight))
+            .title(Span::styled(
+                " Tighten sandbox  [Enter] apply  [Esc] cancel ",
+                theme.get_style(ComponentType::Title),
+            ));
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let mut items: Vec<ListItem> = LEVELS
+            .iter()
+            .map(|level| {
+                let (label, style) = level_display(level, false);
+                let note = if *level == current {
+                    "  current"
+                } else if *level > current {
+                    "  restart required"
+                } else {
+                    ""
+                };
+                let label_style = if *level > current {
+                    theme.get_style(ComponentType::Muted)
+                } else {
+                    theme.get_style(style)
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(label, label_style),
+                    Span::styled(note, theme.get_style(ComponentType::Muted)),
+                ]))
+            })
+            .collect();
+        if let Some(notice) = &self.notice {
+            items.push(ListItem::new(""));
+            items.push(ListItem::new(Span::styled(
+                notice.clone(),
+                theme.get_style(ComponentType::Warning),
+            )));
+        }
+
+        self.list_state.select(Some(self.selected));
+        let list = List::new(items).highlight_style(theme.get_style(ComponentType::Selection));
+        StatefulWidget::render(list, inner, buf, &mut self.list_state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::StatusBar;
+    use crossterm::event::KeyModifiers;
+    use std::path::PathBuf;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn policy(level: SandboxLevel, require_approval: bool) -> SandboxPolicy {
+        SandboxPolicy::new(level, PathBuf::from("/work/fennec"), require_approval)
+    }
+
+    fn render(items: Vec<StatusItem>) -> String {
+        let mut status_bar = StatusBar::new();
+        for item in items {
+            status_bar.add_left(item);
+        }
+        let area = Rect::new(0, 0, 120, 1);
+        let mut buf = Buffer::empty(area);
+        status_bar.render(area, &mut buf, &ThemeManager::new());
+        (0..area.width)
+            .map(|x| buf.get(x, 0).symbol.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_status_items_per_level() {
+        let items = sandbox_status_items(&policy(SandboxLevel::ReadOnly, true), true);
+        assert_eq!(items[0].value, "🔒 READ-ONLY");
+        assert_eq!(items[0].style, ComponentType::Success);
+        assert_eq!(items[1].value, "🛡️ ON");
+
+        let items = sandbox_status_items(&policy(SandboxLevel::WorkspaceWrite, false), true);
+        assert_eq!(items[0].style, ComponentType::Info);
+        assert_eq!(items[1].value, "OFF");
+        assert_eq!(items[1].style, ComponentType::Warning);
+
+        // Full access flashes
+        let full = policy(SandboxLevel::FullAccess, false);
+        let on = sandbox_status_items(&full, true);
+        let off = sandbox_status_items(&full, false);
+        assert_eq!(on[0].value, "⚠️ FULL ACCESS");
+        assert_ne!(on[0].style, off[0].style);
+
+        // Wide symbols take two cells, so match the text around them
+        let screen = render(on);
+        assert!(screen.starts_with("Sandbox: ⚠️"), "{}", screen);
+        assert!(screen.contains("FULL ACCESS"), "{}", screen);
+        assert!(screen.contains("Approval: OFF"), "{}", screen);
+        assert!(screen.contains("Workspace: /work/fennec"), "{}", screen);
+    }
+
+    #[test]
+    fn test_workspace_label() {
+        let home = Some("/home/ada".to_string());
+        assert_eq!(workspace_label(Path::new("/home/ada"), home.clone()), "~");
+        assert_eq!(
+            workspace_label(Path::new("/home/ada/src/fennec"), home.clone()),
+            "~/src/fennec"
+        );
+        assert_eq!(
+            workspace_label(Path::new("/home/adam/src"), home.clone()),
+            "/home/adam/src"
+        );
+
+        let long = "/srv/builds/customers/acme-corporation/monorepo/services/billing";
+        let label = workspace_label(Path::new(long), None);
+        assert!(label.chars().count() <= MAX_WORKSPACE_LEN, "{}", label);
+        assert_eq!(label, "…/monorepo/services/billing");
+    }
+
+    #[test]
+    fn test_dialog_only_downgrades() {
+        let mut dialog = SandboxDialog::new();
+        assert_eq!(dialog.handle_key(key(KeyCode::Enter)), None);
+
+        // Raising the level is refused and the dialog stays open
+        dialog.open(SandboxLevel::WorkspaceWrite);
+        dialog.handle_key(key(KeyCode::Down));
+        assert_eq!(dialog.handle_key(key(KeyCode::Enter)), None);
+        assert!(dialog.is_open());
+        assert!(dialog.notice().unwrap().contains("restart"));
+
+        // Keeping the current level changes nothing
+        dialog.handle_key(key(KeyCode::Up));
+        assert_eq!(dialog.notice(), None);
+        assert_eq!(dialog.handle_key(key(KeyCode::Enter)), None);
+        assert!(!dialog.is_open());
+
+        dialog.open(SandboxLevel::FullAccess);
+        dialog.handle_key(key(KeyCode::Char('k')));
+        dialog.handle_key(key(KeyCode::Char('k')));
+        assert_eq!(
+            dialog.handle_key(key(KeyCode::Enter)),
+            Some(SandboxLevel::ReadOnly)
+        );
+        assert!(!dialog.is_open());
+
+        dialog.open(SandboxLevel::ReadOnly);
+        dialog.handle_key(key(KeyCode::Esc));
+        assert!(!dialog.is_open());
+    }
+
+    #[test]
+    fn test_dialog_marks_levels_needing_a_restart() {
+        let mut dialog = SandboxDialog::new();
+        dialog.open(SandboxLevel::WorkspaceWrite);
+        let area = Rect::new(0, 0, 60, 8);
+        let mut buf = Buffer::empty(area);
+        dialog.render(area, &mut buf, &ThemeManager::new());
+
+        let rows: Vec<String> = (0..area.height)
+            .map(|y| {
+                (0..area.width)
+                    .map(|x| buf.get(x, y).symbol.as_str())
+                    .collect()
+            })
+            .collect();
+        assert!(rows[2].contains("WORKSPACE") && rows[2].contains("current"));
+        assert!(rows[3].contains("FULL ACCESS") && rows[3].contains("restart required"));
+    }
+}
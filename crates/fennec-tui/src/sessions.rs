@@ -0,0 +1,533 @@
+//! Open conversations and the picker for switching between them.
+//!
+//! Each [`SessionTab`] keeps what the app shows for one session: its
+//! scrollback, the input typed but not yet sent, and any reply still
+//! streaming. Switching sessions puts all of it back as it was. Streams keep
+//! being polled while their session is in the background, so a reply that
+//! finishes there is waiting when the user switches back.
+
+use crate::components::{Message, MessageRole};
+use crate::conversation::ConversationPane;
+use crate::input_editor::PromptHistory;
+use crate::streaming_message::{StreamStatus, StreamingMessageView};
+use crate::theme::{ComponentType, ThemeManager};
+use crossterm::event::{KeyCode, KeyEvent};
+use fennec_core::session::{Session, SessionState};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, StatefulWidget, Widget},
+};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// What a session is doing, shown next to it in the picker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionStatus {
+    Idle,
+    /// A reply is streaming in
+    Streaming,
+    /// An operation is waiting for the user's approval
+    AwaitingApproval,
+}
+
+impl SessionStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SessionStatus::Idle => "idle",
+            SessionStatus::Streaming => "streaming",
+            SessionStatus::AwaitingApproval => "awaiting approval",
+        }
+    }
+
+    fn icon(&self) -> &'static str {
+        match self {
+            SessionStatus::Idle => "○",
+            SessionStatus::Streaming => "◐",
+            SessionStatus::AwaitingApproval => "!",
+        }
+    }
+
+    fn component(&self) -> ComponentType {
+        match self {
+            SessionStatus::Idle => ComponentType::Muted,
+            SessionStatus::Streaming => ComponentType::Info,
+            SessionStatus::AwaitingApproval => ComponentType::Warning,
+        }
+    }
+}
+
+/// What the app shows for one open session
+#[derive(Debug)]
+pub struct SessionTab {
+    pub id: Uuid,
+    pub title: String,
+    pub conversation: ConversationPane,
+    /// Input typed but not sent
+    pub draft: String,
+    /// Prompts sent, for recalling with Up and Down
+    pub history: PromptHistory,
+    /// Reply still streaming in
+    pub stream: Option<StreamingMessageView>,
+    pub awaiting_approval: bool,
+    pub workspace_path: Option<PathBuf>,
+    pub last_active_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub lifecycle: SessionState,
+}
+
+impl SessionTab {
+    pub fn new(id: Uuid, title: impl Into<String>) -> Self {
+        Self {
+            id,
+            title: title.into(),
+            conversation: ConversationPane::new(),
+            draft: String::new(),
+            history: PromptHistory::default(),
+            stream: None,
+            awaiting_approval: false,
+            workspace_path: None,
+            last_active_at: None,
+            lifecycle: SessionState::Active,
+        }
+    }
+
+    /// Take the title, workspace and lifecycle state of `session`
+    pub fn update_from(&mut self, session: &Session) {
+        if let Some(title) = &session.title {
+            self.title = title.clone();
+        }
+        self.workspace_path = session.workspace_path.clone();
+        self.last_active_at = Some(session.last_active_at);
+        self.lifecycle = session.state;
+    }
+
+    pub fn status(&self) -> SessionStatus {
+        if self.awaiting_approval {
+            SessionStatus::AwaitingApproval
+        } else if self.stream.is_some() {
+            SessionStatus::Streaming
+        } else {
+            SessionStatus::Idle
+        }
+    }
+}
+
+/// A reply that stopped streaming
+#[derive(Debug, Clone, PartialEq)]
+pub struct FinishedStream {
+    pub session_id: Uuid,
+    pub content: String,
+    pub status: StreamStatus,
+}
+
+/// What the user chose in the session picker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionPickerAction {
+    Switch(Uuid),
+    New,
+    Close(Uuid),
+}
+
+/// The open sessions, one of them active
+#[derive(Debug, Default)]
+pub struct SessionRegistry {
+    tabs: Vec<SessionTab>,
+    active: usize,
+    /// Sessions opened so far, including closed ones
+    opened: usize,
+    picker_open: bool,
+    picker_selected: usize,
+    list_state: ListState,
+}
+
+impl SessionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a session and make it active
+    pub fn open(&mut self, tab: SessionTab) {
+        self.tabs.push(tab);
+        self.opened += 1;
+        self.active = self.tabs.len() - 1;
+    }
+
+    /// Remove a session. The one before it becomes active if it was.
+    pub fn close(&mut self, id: Uuid) -> Option<SessionTab> {
+        let index = self.index_of(id)?;
+        let tab = self.tabs.remove(index);
+        if index < self.active || self.active >= self.tabs.len() {
+            self.active = self.active.saturating_sub(1);
+        }
+        self.picker_selected = self.picker_selected.min(self.tabs.len().saturating_sub(1));
+        Some(tab)
+    }
+
+    /// Make `id` the active session. Returns whether it is open.
+    pub fn activate(&mut self, id: Uuid) -> bool {
+        match self.index_of(id) {
+            Some(index) => {
+                self.active = index;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn index_of(&self, id: Uuid) -> Option<usize> {
+        self.tabs.iter().position(|tab| tab.id == id)
+    }
+
+    pub fn active(&self) -> Option<&SessionTab> {
+        self.tabs.get(self.active)
+    }
+
+    pub fn active_mut(&mut self) -> Option<&mut SessionTab> {
+        self.tabs.get_mut(self.active)
+    }
+
+    pub fn get_mut(&mut self, id: Uuid) -> Option<&mut SessionTab> {
+        self.tabs.iter_mut().find(|tab| tab.id == id)
+    }
+
+    /// Open sessions in the order they were opened
+    pub fn tabs(&self) -> &[SessionTab] {
+        &self.tabs
+    }
+
+    /// Refresh the tabs of `sessions` with their current details
+    pub fn sync(&mut self, sessions: &[Session]) {
+        for session in sessions {
+            if let Some(tab) = self.get_mut(session.id) {
+                tab.update_from(session);
+            }
+        }
+    }
+
+    /// Sessions opened so far, including closed ones
+    pub fn opened(&self) -> usize {
+        self.opened
+    }
+
+    pub fn len(&self) -> usize {
+        self.tabs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tabs.is_empty()
+    }
+
+    /// The session `offset` places after the active one, wrapping around
+    pub fn neighbour(&self, offset: isize) -> Option<Uuid> {
+        if self.tabs.is_empty() {
+            return None;
+        }
+        let len = self.tabs.len() as isize;
+        let index = (self.active as isize + offset).rem_euclid(len) as usize;
+        Some(self.tabs[index].id)
+    }
+
+    /// Poll the streams of every session, active or not. Streams that ended
+    /// are detached and their reply added to their session's conversation.
+    pub fn poll_streams(&mut self) -> Vec<FinishedStream> {
+        let mut finished = Vec::new();
+        for tab in &mut self.tabs {
+            let Some(stream) = tab.stream.as_mut() else {
+                continue;
+            };
+            stream.poll();
+            if stream.is_streaming() {
+                continue;
+            }
+
+            let status = stream.status().clone();
+            let content = stream.content().to_string();
+            tab.stream = None;
+
+            if !content.is_empty() {
+                tab.conversation.add_message(Message {
+                    role: MessageRole::Assistant,
+                    content: content.clone(),
+                    timestamp: chrono::Local::now().format("%H:%M:%S").to_string(),
+                });
+            }
+            finished.push(FinishedStream {
+                session_id: tab.id,
+                content,
+                status,
+            });
+        }
+        finished
+    }
+
+    pub fn is_picker_open(&self) -> bool {
+        self.picker_open
+    }
+
+    /// Open the picker on the active session
+    pub fn open_picker(&mut self) {
+        self.picker_open = true;
+        self.picker_selected = self.active;
+    }
+
+    pub fn close_picker(&mut self) {
+        self.picker_open = false;
+    }
+
+    /// Handle a key while the picker is open
+    pub fn handle_picker_key(&mut self, key: KeyEvent) -> Option<SessionPickerAction> {
+        let selected = self.tabs.get(self.picker_selected).map(|tab| tab.id);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.close_picker(),
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.picker_selected = self.picker_selected.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.picker_selected =
+                    (self.picker_selected + 1).min(self.tabs.len().saturating_sub(1));
+            }
+            KeyCode::Enter => {
+                self.close_picker();
+                return selected.map(SessionPickerAction::Switch);
+            }
+            KeyCode::Char('n') => {
+                self.close_picker();
+                return Some(SessionPickerAction::New);
+            }
+            KeyCode::Char('d') | KeyCode::Char('x') => {
+                return selected.map(SessionPickerAction::Close);
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Render the picker overlay
+    pub fn render_picker(&mut self, area: Rect, buf: &mut Buffer, theme: &ThemeManager) {
+        Clear.render(area, buf);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(theme.get_style(ComponentType::Highlight))
+            .title(Span::styled(
+                " Sessions  [Enter] switch  [n] new  [d] close ",
+                theme.get_style(ComponentType::Title),
+            ));
+
+        let items: Vec<ListItem> = self
+            .tabs
+            .iter()
+            .enumerate()
+            .map(|(index, tab)| {
+                let status = tab.status();
+                let marker = if index == self.active { "▸ " } else { "  " };
+                let mut details = format!(
+                    "  {} messages, {}",
+                    tab.conversation.messages().len(),
+                    status.label()
+                );
+                if tab.lifecycle == SessionState::Idle {
+                    details.push_str(", session idle");
+                }
+                if let Some(last_active) = tab.last_active_at {
+                    details.push_str(&format!(
+                        ", last active {}",
+                        last_active.with_timezone(&chrono::Local).format("%H:%M")
+                    ));
+                }
+                if let Some(workspace) = &tab.workspace_path {
+                    details.push_str(&format!("  {}", workspace.display()));
+                }
+                ListItem::new(Line::from(vec![
+                    Span::styled(marker, theme.get_style(ComponentType::Highlight)),
+                    Span::styled(
+                        format!("{} ", status.icon()),
+                        theme.get_style(status.component()),
+                    ),
+                    Span::styled(tab.title.clone(), theme.get_style(ComponentType::Text)),
+                    Span::styled(details, theme.get_style(ComponentType::Muted)),
+                ]))
+            })
+            .collect();
+
+        self.list_state.select(Some(self.picker_selected));
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(theme.get_style(ComponentType::Selection));
+        StatefulWidget::render(list, area, buf, &mut self.list_state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streaming_message::StreamingViewConfig;
+    use crossterm::event::KeyModifiers;
+    use tokio::sync::mpsc;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn message(content: &str) -> Message {
+        Message {
+            role: MessageRole::User,
+            content: content.to_string(),
+            timestamp: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_registry_keeps_each_sessions_state() {
+        let mut registry = SessionRegistry::new();
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+
+        registry.open(SessionTab::new(first, "First"));
+        let tab = registry.active_mut().unwrap();
+        tab.conversation.add_message(message("hello"));
+        tab.draft = "half typed".to_string();
+
+        registry.open(SessionTab::new(second, "Second"));
+        assert_eq!(registry.active().unwrap().id, second);
+        assert!(registry
+            .active()
+            .unwrap()
+            .conversation
+            .messages()
+            .is_empty());
+
+        assert!(registry.activate(first));
+        let tab = registry.active().unwrap();
+        assert_eq!(tab.conversation.messages().len(), 1);
+        assert_eq!(tab.draft, "half typed");
+        assert_eq!(registry.neighbour(1), Some(second));
+        assert_eq!(registry.neighbour(-1), Some(second));
+
+        // Closing the active session activates the one before it, if any
+        registry.activate(second);
+        registry.close(second);
+        assert_eq!(registry.active().unwrap().id, first);
+        registry.close(first);
+        assert!(registry.active().is_none());
+        assert!(!registry.activate(first));
+    }
+
+    #[test]
+    fn test_switching_keeps_stream_in_flight() {
+        let mut registry = SessionRegistry::new();
+        let streaming = Uuid::new_v4();
+        let other = Uuid::new_v4();
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let mut tab = SessionTab::new(streaming, "Streaming");
+        tab.stream = Some(StreamingMessageView::new(
+            receiver,
+            StreamingViewConfig::default(),
+        ));
+        registry.open(tab);
+        sender.send(Ok("Hello, ".to_string())).unwrap();
+        assert!(registry.poll_streams().is_empty());
+        assert_eq!(
+            registry.active().unwrap().status(),
+            SessionStatus::Streaming
+        );
+
+        // Switch away while the reply is still arriving
+        registry.open(SessionTab::new(other, "Other"));
+        sender.send(Ok("world".to_string())).unwrap();
+        assert!(registry.poll_streams().is_empty());
+        drop(sender);
+
+        let finished = registry.poll_streams();
+        assert_eq!(
+            finished,
+            [FinishedStream {
+                session_id: streaming,
+                content: "Hello, world".to_string(),
+                status: StreamStatus::Finished,
+            }]
+        );
+        assert!(registry
+            .active()
+            .unwrap()
+            .conversation
+            .messages()
+            .is_empty());
+
+        registry.activate(streaming);
+        let tab = registry.active().unwrap();
+        assert_eq!(tab.status(), SessionStatus::Idle);
+        assert_eq!(tab.conversation.messages()[0].content, "Hello, world");
+
+        // Failed streams are reported with what arrived
+        let (sender, receiver) = mpsc::unbounded_channel();
+        sender.send(Ok("partial".to_string())).unwrap();
+        sender
+            .send(Err(fennec_core::FennecError::SessionNotFound {
+                session_id: other.to_string(),
+            }))
+            .unwrap();
+        registry.get_mut(other).unwrap().stream = Some(StreamingMessageView::new(
+            receiver,
+            StreamingViewConfig::default(),
+        ));
+        let finished = registry.poll_streams();
+        assert_eq!(finished[0].session_id, other);
+        assert!(matches!(finished[0].status, StreamStatus::Failed(_)));
+    }
+
+    #[test]
+    fn test_picker_actions() {
+        let mut registry = SessionRegistry::new();
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+        registry.open(SessionTab::new(first, "First"));
+        registry.open(SessionTab::new(second, "Second"));
+
+        registry.open_picker();
+        assert_eq!(registry.handle_picker_key(key(KeyCode::Up)), None);
+        assert_eq!(
+            registry.handle_picker_key(key(KeyCode::Char('d'))),
+            Some(SessionPickerAction::Close(first))
+        );
+        assert!(registry.is_picker_open());
+        assert_eq!(
+            registry.handle_picker_key(key(KeyCode::Enter)),
+            Some(SessionPickerAction::Switch(first))
+        );
+        assert!(!registry.is_picker_open());
+
+        registry.open_picker();
+        assert_eq!(
+            registry.handle_picker_key(key(KeyCode::Char('n'))),
+            Some(SessionPickerAction::New)
+        );
+    }
+
+    #[test]
+    fn test_picker_shows_session_details() {
+        let mut session = Session::new()
+            .with_title("Refactor")
+            .with_workspace("/tmp/project");
+        session.mark_idle().unwrap();
+        let mut registry = SessionRegistry::new();
+        registry.open(SessionTab::new(session.id, "Session 1"));
+
+        registry.sync(&[session]);
+        let tab = registry.active().unwrap();
+        assert_eq!(tab.title, "Refactor");
+        assert_eq!(tab.lifecycle, SessionState::Idle);
+
+        let area = Rect::new(0, 0, 100, 4);
+        let mut buf = Buffer::empty(area);
+        registry.render_picker(area, &mut buf, &ThemeManager::new());
+        let row: String = (0..area.width)
+            .map(|x| buf.get(x, 1).symbol.clone())
+            .collect();
+        assert!(row.contains("Refactor"), "{}", row);
+        assert!(row.contains("session idle"), "{}", row);
+        assert!(row.contains("/tmp/project"), "{}", row);
+    }
+}
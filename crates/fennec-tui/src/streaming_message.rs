@@ -0,0 +1,932 @@
+//! Incremental rendering of a streamed assistant reply.
+//!
+//! Tokens are shown as soon as they arrive, but markdown is only re-parsed
+//! once per debounce interval. Completed lines are parsed once and kept; only
+//! the trailing partial line is re-formatted on each parse, and whatever
+//! arrived since the last parse is shown as plain text until the next one.
+
+use crate::theme::{ComponentType, ThemeManager};
+use fennec_core::config::TuiConfig;
+use futures::{Stream, StreamExt};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Widget},
+};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Default interval between markdown re-parses while streaming
+pub const DEFAULT_PARSE_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// Receiving end of a provider token stream
+pub type TokenReceiver = mpsc::UnboundedReceiver<fennec_core::Result<String>>;
+
+/// Forward the tokens of a provider stream to a channel the view can poll
+/// without blocking the render loop. The task stops when the stream ends or
+/// the receiver is dropped.
+pub fn forward_stream<S>(mut stream: S) -> (TokenReceiver, JoinHandle<()>)
+where
+    S: Stream<Item = fennec_core::Result<String>> + Unpin + Send + 'static,
+{
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let handle = tokio::spawn(async move {
+        while let Some(token) = stream.next().await {
+            if sender.send(token).is_err() {
+                break;
+            }
+        }
+    });
+    (receiver, handle)
+}
+
+/// Settings of a [`StreamingMessageView`]
+#[derive(Debug, Clone)]
+pub struct StreamingViewConfig {
+    /// Minimum time between markdown re-parses
+    pub parse_debounce: Duration,
+    /// Wrap long code lines instead of scrolling them horizontally
+    pub wrap_code_blocks: bool,
+}
+
+impl Default for StreamingViewConfig {
+    fn default() -> Self {
+        Self {
+            parse_debounce: DEFAULT_PARSE_DEBOUNCE,
+            wrap_code_blocks: false,
+        }
+    }
+}
+
+impl From<&TuiConfig> for StreamingViewConfig {
+    fn from(config: &TuiConfig) -> Self {
+        Self {
+            wrap_code_blocks: config.wrap_code_blocks,
+            ..Self::default()
+        }
+    }
+}
+
+/// State of the stream feeding the view
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamStatus {
+    Streaming,
+    Finished,
+    Cancelled,
+    Failed(String),
+}
+
+/// Markdown role of a piece of text, mapped to a style when rendering
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    /// Tokens received since the last parse
+    Raw,
+    Text,
+    Bold,
+    Italic,
+    InlineCode,
+    Heading,
+    Bullet,
+    Quote,
+    Fence,
+    Code,
+    Keyword,
+    Str,
+    Comment,
+    Number,
+}
+
+impl Token {
+    fn style(self, theme: &ThemeManager) -> Style {
+        let text = theme.get_style(ComponentType::Text);
+        match self {
+            Token::Raw | Token::Text | Token::Code => text,
+            Token::Bold => text.add_modifier(Modifier::BOLD),
+            Token::Italic => text.add_modifier(Modifier::ITALIC),
+            Token::InlineCode => theme.get_style(ComponentType::Info),
+            Token::Heading => theme
+                .get_style(ComponentType::Title)
+                .add_modifier(Modifier::BOLD),
+            Token::Bullet => theme.get_style(ComponentType::Highlight),
+            Token::Quote => theme
+                .get_style(ComponentType::Muted)
+                .add_modifier(Modifier::ITALIC),
+            Token::Fence => theme.get_style(ComponentType::Muted),
+            Token::Keyword => theme
+                .get_style(ComponentType::Highlight)
+                .add_modifier(Modifier::BOLD),
+            Token::Str => theme.get_style(ComponentType::Success),
+            Token::Comment => theme
+                .get_style(ComponentType::Muted)
+                .add_modifier(Modifier::ITALIC),
+            Token::Number => theme.get_style(ComponentType::Warning),
+        }
+    }
+}
+
+/// A parsed source line
+#[derive(Debug, Clone, PartialEq)]
+struct FormattedLine {
+    segments: Vec<(Token, String)>,
+    /// Inside a code block, so scrolled or hard-wrapped rather than
+    /// word-wrapped
+    code: bool,
+}
+
+impl FormattedLine {
+    fn new(code: bool) -> Self {
+        Self {
+            segments: Vec::new(),
+            code,
+        }
+    }
+
+    fn push(&mut self, token: Token, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        match self.segments.last_mut() {
+            Some((last, content)) if *last == token => content.push_str(text),
+            _ => self.segments.push((token, text.to_string())),
+        }
+    }
+}
+
+/// An open code fence
+#[derive(Debug, Clone)]
+struct Fence {
+    marker: &'static str,
+    language: String,
+}
+
+/// Line-by-line markdown parser that keeps code fence state between lines
+#[derive(Debug, Default)]
+struct MarkdownParser {
+    lines: Vec<FormattedLine>,
+    fence: Option<Fence>,
+}
+
+impl MarkdownParser {
+    /// Parse a complete line, updating the fence state
+    fn push_line(&mut self, line: &str) {
+        let trimmed = line.trim_start();
+        match &self.fence {
+            Some(fence) if trimmed.starts_with(fence.marker) && trimmed[3..].trim().is_empty() => {
+                self.lines.push(fence_line(line));
+                self.fence = None;
+            }
+            Some(_) => {
+                let formatted = self.format(line);
+                self.lines.push(formatted);
+            }
+            None => match fence_marker(trimmed) {
+                Some(marker) => {
+                    self.fence = Some(Fence {
+                        marker,
+                        language: trimmed[3..].trim().to_lowercase(),
+                    });
+                    self.lines.push(fence_line(line));
+                }
+                None => self.lines.push(format_prose(line)),
+            },
+        }
+    }
+
+    /// Format a line without changing state, for a line still being
+    /// streamed
+    fn format(&self, line: &str) -> FormattedLine {
+        match &self.fence {
+            Some(fence) => format_code(line, &fence.language),
+            None if fence_marker(line.trim_start()).is_some() => fence_line(line),
+            None => format_prose(line),
+        }
+    }
+}
+
+fn fence_marker(trimmed: &str) -> Option<&'static str> {
+    ["```", "~~~"]
+        .into_iter()
+        .find(|marker| trimmed.starts_with(marker))
+}
+
+fn fence_line(line: &str) -> FormattedLine {
+    let mut formatted = FormattedLine::new(true);
+    formatted.push(Token::Fence, line);
+    formatted
+}
+
+fn format_prose(line: &str) -> FormattedLine {
+    let mut formatted = FormattedLine::new(false);
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+
+    let hashes = trimmed.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&hashes) && trimmed[hashes..].starts_with(' ') {
+        formatted.push(Token::Heading, trimmed[hashes..].trim());
+        return formatted;
+    }
+
+    if let Some(rest) = trimmed.strip_prefix('>') {
+        formatted.push(Token::Quote, "│ ");
+        formatted.push(Token::Quote, rest.strip_prefix(' ').unwrap_or(rest));
+        return formatted;
+    }
+
+    let bullet = ["- ", "* ", "+ "]
+        .into_iter()
+        .find_map(|marker| {
+            trimmed
+                .strip_prefix(marker)
+                .map(|rest| ("• ".to_string(), rest))
+        })
+        .or_else(|| {
+            let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+            let rest = &trimmed[digits..];
+            (digits > 0 && (rest.starts_with(". ") || rest.starts_with(") ")))
+                .then(|| (format!("{}. ", &trimmed[..digits]), &rest[2..]))
+        });
+    match bullet {
+        Some((marker, rest)) => {
+            formatted.push(Token::Text, indent);
+            formatted.push(Token::Bullet, &marker);
+            format_inline(&mut formatted, rest);
+        }
+        None => format_inline(&mut formatted, line),
+    }
+    formatted
+}
+
+/// Inline code, bold and italic. Unclosed markers are kept as text, which
+/// also covers a line whose closing marker hasn't streamed in yet.
+fn format_inline(formatted: &mut FormattedLine, text: &str) {
+    let mut rest = text;
+    while !rest.is_empty() {
+        let Some(start) = rest.find(['`', '*', '_']) else {
+            formatted.push(Token::Text, rest);
+            return;
+        };
+        formatted.push(Token::Text, &rest[..start]);
+        rest = &rest[start..];
+
+        let (marker, token) = if rest.starts_with('`') {
+            ("`", Token::InlineCode)
+        } else if rest.starts_with("**") || rest.starts_with("__") {
+            (&rest[..2], Token::Bold)
+        } else {
+            (&rest[..1], Token::Italic)
+        };
+        let body = &rest[marker.len()..];
+        // Underscores inside words, like snake_case, aren't emphasis
+        let inside_word = marker.starts_with('_')
+            && text[..text.len() - rest.len()]
+                .chars()
+                .next_back()
+                .is_some_and(char::is_alphanumeric);
+        match body.find(marker) {
+            Some(end) if end > 0 && !inside_word && !body.starts_with(' ') => {
+                formatted.push(token, &body[..end]);
+                rest = &body[end + marker.len()..];
+            }
+            _ => {
+                formatted.push(Token::Text, marker);
+                rest = body;
+            }
+        }
+    }
+}
+
+fn keywords(language: &str) -> &'static [&'static str] {
+    match language {
+        "rust" | "rs" => &[
+            "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+            "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+            "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true",
+            "type", "unsafe", "use", "where", "while",
+        ],
+        "python" | "py" => &[
+            "and", "as", "async", "await", "break", "class", "continue", "def", "elif", "else",
+            "except", "False", "finally", "for", "from", "if", "import", "in", "is", "lambda",
+            "None", "not", "or", "pass", "raise", "return", "True", "try", "while", "with",
+            "yield",
+        ],
+        "javascript" | "js" | "typescript" | "ts" | "tsx" | "jsx" => &[
+            "async",
+            "await",
+            "break",
+            "case",
+            "class",
+            "const",
+            "continue",
+            "default",
+            "else",
+            "export",
+            "extends",
+            "false",
+            "for",
+            "from",
+            "function",
+            "if",
+            "import",
+            "interface",
+            "let",
+            "new",
+            "null",
+            "return",
+            "switch",
+            "this",
+            "throw",
+            "true",
+            "try",
+            "type",
+            "undefined",
+            "var",
+            "while",
+        ],
+        "sh" | "bash" | "shell" | "zsh" | "console" => &[
+            "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if",
+            "in", "local", "then", "while",
+        ],
+        "go" => &[
+            "break",
+            "case",
+            "chan",
+            "const",
+            "continue",
+            "default",
+            "defer",
+            "else",
+            "false",
+            "for",
+            "func",
+            "go",
+            "if",
+            "import",
+            "interface",
+            "map",
+            "nil",
+            "package",
+            "range",
+            "return",
+            "select",
+            "struct",
+            "switch",
+            "true",
+            "type",
+            "var",
+        ],
+        _ => &[],
+    }
+}
+
+fn comment_prefix(language: &str) -> Option<&'static str> {
+    match language {
+        "python" | "py" | "sh" | "bash" | "shell" | "zsh" | "toml" | "yaml" | "yml" => Some("#"),
+        "rust" | "rs" | "javascript" | "js" | "typescript" | "ts" | "tsx" | "jsx" | "go" | "c"
+        | "cpp" | "java" => Some("//"),
+        _ => None,
+    }
+}
+
+/// Keywords, strings, numbers and line comments of `language`
+fn format_code(line: &str, language: &str) -> FormattedLine {
+    let mut formatted = FormattedLine::new(true);
+    let keywords = keywords(language);
+    let comment = comment_prefix(language);
+    // Single quotes are lifetimes and chars in Rust
+    let quotes: &[char] = if matches!(language, "rust" | "rs") {
+        &['"']
+    } else {
+        &['"', '\'']
+    };
+
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        if comment.is_some_and(|prefix| rest.starts_with(prefix)) {
+            formatted.push(Token::Comment, rest);
+            break;
+        }
+        let (token, len) = if quotes.contains(&c) {
+            let mut escaped = false;
+            let end = rest[1..]
+                .char_indices()
+                .find(|&(_, ch)| {
+                    let closes = ch == c && !escaped;
+                    escaped = ch == '\\' && !escaped;
+                    closes
+                })
+                .map_or(rest.len(), |(i, ch)| 1 + i + ch.len_utf8());
+            (Token::Str, end)
+        } else if c.is_ascii_digit() {
+            let end = rest
+                .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '.' || ch == '_'))
+                .unwrap_or(rest.len());
+            (Token::Number, end)
+        } else if c.is_alphabetic() || c == '_' {
+            let end = rest
+                .find(|ch: char| !(ch.is_alphanumeric() || ch == '_'))
+                .unwrap_or(rest.len());
+            let token = if keywords.contains(&&rest[..end]) {
+                Token::Keyword
+            } else {
+                Token::Code
+            };
+            (token, end)
+        } else {
+            (Token::Code, c.len_utf8())
+        };
+        formatted.push(token, &rest[..len]);
+        rest = &rest[len..];
+    }
+    formatted
+}
+
+/// Streamed assistant reply rendered as markdown
+#[derive(Debug)]
+pub struct StreamingMessageView {
+    tokens: Option<TokenReceiver>,
+    config: StreamingViewConfig,
+    parser: MarkdownParser,
+    /// Everything received, unformatted
+    raw: String,
+    /// Trailing incomplete line as of the last parse
+    partial: String,
+    /// Tokens received since the last parse
+    unparsed: String,
+    last_parse: Option<Instant>,
+    status: StreamStatus,
+    /// First visual row shown, or `None` to follow the end of the reply
+    scroll: Option<usize>,
+    /// Columns code lines are scrolled by when not wrapped
+    horizontal_scroll: usize,
+}
+
+impl StreamingMessageView {
+    /// View the tokens arriving on `tokens`
+    pub fn new(tokens: TokenReceiver, config: StreamingViewConfig) -> Self {
+        Self {
+            tokens: Some(tokens),
+            config,
+            parser: MarkdownParser::default(),
+            raw: String::new(),
+            partial: String::new(),
+            unparsed: String::new(),
+            last_parse: None,
+            status: StreamStatus::Streaming,
+            scroll: None,
+            horizontal_scroll: 0,
+        }
+    }
+
+    pub fn status(&self) -> &StreamStatus {
+        &self.status
+    }
+
+    pub fn is_streaming(&self) -> bool {
+        self.status == StreamStatus::Streaming
+    }
+
+    /// Markdown source received so far
+    pub fn content(&self) -> &str {
+        &self.raw
+    }
+
+    /// Take the tokens that have arrived, re-parsing if the debounce interval
+    /// has passed. Returns whether anything changed.
+    pub fn poll(&mut self) -> bool {
+        self.poll_at(Instant::now())
+    }
+
+    fn poll_at(&mut self, now: Instant) -> bool {
+        let mut changed = false;
+        let mut ended = None;
+        if let Some(tokens) = self.tokens.as_mut() {
+            loop {
+                match tokens.try_recv() {
+                    Ok(Ok(token)) => {
+                        self.raw.push_str(&token);
+                        self.unparsed.push_str(&token);
+                        changed = true;
+                    }
+                    Ok(Err(e)) => {
+                        ended = Some(StreamStatus::Failed(e.to_string()));
+                        break;
+                    }
+                    Err(mpsc::error::TryRecvError::Empty) => break,
+                    Err(mpsc::error::TryRecvError::Disconnected) => {
+                        ended = Some(StreamStatus::Finished);
+                        break;
+                    }
+                }
+            }
+        }
+
+        if let Some(status) = ended {
+            self.finish(status, now);
+            return true;
+        }
+        let due = self
+            .last_parse
+            .is_none_or(|last| now.duration_since(last) >= self.config.parse_debounce);
+        if !self.unparsed.is_empty() && due {
+            self.parse(now);
+        }
+        changed
+    }
+
+    /// Stop consuming the stream and format what has arrived
+    pub fn cancel(&mut self) {
+        if self.is_streaming() {
+            self.finish(StreamStatus::Cancelled, Instant::now());
+        }
+    }
+
+    pub fn scroll_up(&mut self, rows: usize, width: u16, height: u16) {
+        let top = self.scroll.unwrap_or_else(|| self.bottom(width, height));
+        self.scroll = Some(top.saturating_sub(rows));
+    }
+
+    pub fn scroll_down(&mut self, rows: usize, width: u16, height: u16) {
+        if let Some(top) = self.scroll {
+            let bottom = self.bottom(width, height);
+            self.scroll = (top + rows < bottom).then_some(top + rows);
+        }
+    }
+
+    /// Scroll code lines left; no effect when code is wrapped
+    pub fn scroll_left(&mut self, columns: usize) {
+        self.horizontal_scroll = self.horizontal_scroll.saturating_sub(columns);
+    }
+
+    /// Scroll code lines right; no effect when code is wrapped
+    pub fn scroll_right(&mut self, columns: usize) {
+        if !self.config.wrap_code_blocks {
+            self.horizontal_scroll += columns;
+        }
+    }
+
+    fn finish(&mut self, status: StreamStatus, now: Instant) {
+        self.tokens = None;
+        self.parse(now);
+        if !self.partial.is_empty() {
+            let partial = std::mem::take(&mut self.partial);
+            self.parser.push_line(&partial);
+        }
+        // An unterminated fence just ends with the reply; its lines keep
+        // their code styling
+        self.parser.fence = None;
+        self.status = status;
+    }
+
+    fn parse(&mut self, now: Instant) {
+        self.partial.push_str(&std::mem::take(&mut self.unparsed));
+        if let Some(end) = self.partial.rfind('\n') {
+            let complete: String = self.partial.drain(..=end).collect();
+            for line in complete[..end].split('\n') {
+                self.parser.push_line(line);
+            }
+        }
+        self.last_parse = Some(now);
+    }
+
+    /// Source lines to display: parsed lines, the partial line as of the
+    /// last parse, and unparsed tokens as plain text
+    fn formatted_lines(&self) -> Vec<FormattedLine> {
+        let mut lines = self.parser.lines.clone();
+        if self.partial.is_empty() && self.unparsed.is_empty() {
+            return lines;
+        }
+
+        let mut last = self.parser.format(&self.partial);
+        let mut unparsed = self.unparsed.split('\n');
+        last.push(Token::Raw, unparsed.next().unwrap_or_default());
+        lines.push(last);
+        for line in unparsed {
+            let mut raw = FormattedLine::new(false);
+            raw.push(Token::Raw, line);
+            lines.push(raw);
+        }
+        lines
+    }
+
+    /// Rendered rows at `width` columns, before vertical scrolling
+    pub fn render_lines(&self, width: u16, theme: &ThemeManager) -> Vec<Line<'static>> {
+        let width = width.max(1) as usize;
+        let mut rows = Vec::new();
+        for line in self.formatted_lines() {
+            let chars: Vec<(char, Token)> = line
+                .segments
+                .iter()
+                .flat_map(|(token, text)| text.chars().map(move |c| (c, *token)))
+                .collect();
+            let ranges = if line.code && !self.config.wrap_code_blocks {
+                let start = self.horizontal_scroll.min(chars.len());
+                std::iter::once(start..(start + width).min(chars.len())).collect()
+            } else {
+                wrap_chars(&chars, width, !line.code)
+            };
+            rows.extend(
+                ranges
+                    .into_iter()
+                    .map(|range| to_line(&chars[range], theme)),
+            );
+        }
+        rows
+    }
+
+    /// Render the reply, following its end unless scrolled up
+    pub fn render(&self, area: Rect, buf: &mut Buffer, theme: &ThemeManager, focused: bool) {
+        let title = match &self.status {
+            StreamStatus::Streaming => "Assistant (streaming...)".to_string(),
+            StreamStatus::Finished => "Assistant".to_string(),
+            StreamStatus::Cancelled => "Assistant (cancelled)".to_string(),
+            StreamStatus::Failed(e) => format!("Assistant (failed: {})", e),
+        };
+        let border_style = if focused {
+            theme.get_style(ComponentType::Highlight)
+        } else {
+            theme.get_style(ComponentType::Border)
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .title_style(theme.get_style(ComponentType::ChatAssistant))
+            .border_style(border_style);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let rows = self.render_lines(inner.width, theme);
+        let height = inner.height as usize;
+        let top = self
+            .scroll
+            .unwrap_or_else(|| rows.len().saturating_sub(height))
+            .min(rows.len().saturating_sub(height));
+        let visible: Vec<Line> = rows.into_iter().skip(top).take(height).collect();
+        Paragraph::new(visible).render(inner, buf);
+    }
+
+    fn bottom(&self, width: u16, height: u16) -> usize {
+        let rows = self.render_lines(width, &ThemeManager::new()).len();
+        rows.saturating_sub(height as usize)
+    }
+}
+
+/// Split into rows of at most `width` chars, preferring to break after
+/// whitespace when `words` is set
+fn wrap_chars(chars: &[(char, Token)], width: usize, words: bool) -> Vec<std::ops::Range<usize>> {
+    let mut rows = Vec::new();
+    let mut start = 0;
+    while chars.len() - start > width {
+        let limit = start + width;
+        // The whitespace a row breaks at isn't shown
+        let space = words
+            .then(|| {
+                (limit..=limit)
+                    .chain((start..limit).rev())
+                    .find(|&i| chars[i].0.is_whitespace())
+            })
+            .flatten();
+        let (end, next) = space.map_or((limit, limit), |i| (i, i + 1));
+        rows.push(start..end);
+        start = next;
+    }
+    rows.push(start..chars.len());
+    rows
+}
+
+fn to_line(chars: &[(char, Token)], theme: &ThemeManager) -> Line<'static> {
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    let mut current: Option<(Token, String)> = None;
+    for &(c, token) in chars {
+        match current.as_mut() {
+            Some((last, text)) if *last == token => text.push(c),
+            _ => {
+                if let Some((last, text)) = current.take() {
+                    spans.push(Span::styled(text, last.style(theme)));
+                }
+                current = Some((token, c.to_string()));
+            }
+        }
+    }
+    if let Some((last, text)) = current {
+        spans.push(Span::styled(text, last.style(theme)));
+    }
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view(
+        config: StreamingViewConfig,
+    ) -> (
+        mpsc::UnboundedSender<fennec_core::Result<String>>,
+        StreamingMessageView,
+    ) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (sender, StreamingMessageView::new(receiver, config))
+    }
+
+    /// Feed `tokens`, end the stream and return the final rows as text
+    fn stream(tokens: &[&str], width: u16, config: StreamingViewConfig) -> Vec<String> {
+        let (sender, mut view) = view(config);
+        for token in tokens {
+            sender.send(Ok(token.to_string())).unwrap();
+            view.poll();
+        }
+        drop(sender);
+        view.poll();
+        assert_eq!(view.status(), &StreamStatus::Finished);
+        text_rows(&view, width)
+    }
+
+    fn text_rows(view: &StreamingMessageView, width: u16) -> Vec<String> {
+        view.render_lines(width, &ThemeManager::new())
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn token_at(view: &StreamingMessageView, row: usize, text: &str) -> Token {
+        view.formatted_lines()[row]
+            .segments
+            .iter()
+            .find(|(_, content)| content.trim() == text)
+            .unwrap_or_else(|| panic!("no segment {:?} on row {}", text, row))
+            .0
+    }
+
+    #[test]
+    fn test_streamed_markdown_final_rendering() {
+        let tokens = [
+            "## Fix",
+            "ing the ",
+            "parser\n\nThe **bug",
+            "** is in `par",
+            "se_line`",
+            " when *empty* lines\nappear:\n\n- first",
+            "\n- second\n1. ",
+            "numbered\n> quoted\n",
+            "```rust\nfn main",
+            "() {\n    let x = 4",
+            "2; // answer\n}\n``",
+            "`\nDone.",
+        ];
+        let rows = stream(&tokens, 40, StreamingViewConfig::default());
+        assert_eq!(
+            rows,
+            [
+                "Fixing the parser",
+                "",
+                "The bug is in parse_line when empty",
+                "lines",
+                "appear:",
+                "",
+                "• first",
+                "• second",
+                "1. numbered",
+                "│ quoted",
+                "```rust",
+                "fn main() {",
+                "    let x = 42; // answer",
+                "}",
+                "```",
+                "Done.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_styles_follow_markdown_roles() {
+        let (sender, mut view) = view(StreamingViewConfig::default());
+        sender
+            .send(Ok(
+                "# Title\n**bold** and `code`\n```python\nreturn \"s\" # c\n```\nsnake_case_name"
+                    .to_string(),
+            ))
+            .unwrap();
+        drop(sender);
+        view.poll();
+
+        assert_eq!(token_at(&view, 0, "Title"), Token::Heading);
+        assert_eq!(token_at(&view, 1, "bold"), Token::Bold);
+        assert_eq!(token_at(&view, 1, "code"), Token::InlineCode);
+        assert_eq!(token_at(&view, 2, "```python"), Token::Fence);
+        assert_eq!(token_at(&view, 3, "return"), Token::Keyword);
+        assert_eq!(token_at(&view, 3, "\"s\""), Token::Str);
+        assert_eq!(token_at(&view, 3, "# c"), Token::Comment);
+        assert_eq!(token_at(&view, 5, "snake_case_name"), Token::Text);
+    }
+
+    #[test]
+    fn test_reparse_is_debounced() {
+        let config = StreamingViewConfig {
+            parse_debounce: Duration::from_secs(60),
+            ..Default::default()
+        };
+        let (sender, mut view) = view(config);
+        let start = Instant::now();
+
+        sender.send(Ok("**bold**\n".to_string())).unwrap();
+        view.poll_at(start);
+        assert_eq!(token_at(&view, 0, "bold"), Token::Bold);
+
+        // Shown straight away, but as raw text until the next parse
+        sender.send(Ok("*it*".to_string())).unwrap();
+        assert!(view.poll_at(start + Duration::from_secs(1)));
+        assert_eq!(token_at(&view, 1, "*it*"), Token::Raw);
+
+        view.poll_at(start + Duration::from_secs(61));
+        assert_eq!(token_at(&view, 1, "it"), Token::Italic);
+    }
+
+    #[test]
+    fn test_unterminated_fence_and_cancellation() {
+        let (sender, mut view) = view(StreamingViewConfig {
+            parse_debounce: Duration::ZERO,
+            ..Default::default()
+        });
+        sender
+            .send(Ok(
+                "Run:\n```sh\nexport PATH=1\necho \"unfinished".to_string()
+            ))
+            .unwrap();
+        view.poll();
+        assert!(view.is_streaming());
+        // The partial line is styled as code while the fence is open
+        assert_eq!(token_at(&view, 3, "echo"), Token::Code);
+
+        view.cancel();
+        sender.send(Ok(" ignored".to_string())).unwrap_err();
+        assert_eq!(view.status(), &StreamStatus::Cancelled);
+        assert_eq!(
+            text_rows(&view, 40),
+            ["Run:", "```sh", "export PATH=1", "echo \"unfinished"]
+        );
+        assert_eq!(token_at(&view, 2, "export"), Token::Keyword);
+        assert_eq!(token_at(&view, 3, "\"unfinished"), Token::Str);
+    }
+
+    #[test]
+    fn test_long_code_lines_scroll_or_wrap() {
+        let tokens = [
+            "```\n",
+            "0123456789abcdef\n",
+            "```\n",
+            "prose that wraps here",
+        ];
+        assert_eq!(
+            stream(&tokens, 10, StreamingViewConfig::default()),
+            ["```", "0123456789", "```", "prose that", "wraps here"]
+        );
+        assert_eq!(
+            stream(
+                &tokens,
+                10,
+                StreamingViewConfig {
+                    wrap_code_blocks: true,
+                    ..Default::default()
+                }
+            ),
+            [
+                "```",
+                "0123456789",
+                "abcdef",
+                "```",
+                "prose that",
+                "wraps here"
+            ]
+        );
+
+        let (sender, mut view) = view(StreamingViewConfig::default());
+        sender.send(Ok(tokens.concat())).unwrap();
+        drop(sender);
+        view.poll();
+        view.scroll_right(6);
+        assert_eq!(text_rows(&view, 10)[1], "6789abcdef");
+        view.scroll_left(100);
+        assert_eq!(text_rows(&view, 10)[1], "0123456789");
+    }
+
+    #[tokio::test]
+    async fn test_forward_stream_feeds_the_view() {
+        let tokens: Vec<fennec_core::Result<String>> =
+            vec![Ok("Hello ".to_string()), Ok("**world**".to_string())];
+        let (receiver, handle) = forward_stream(futures::stream::iter(tokens));
+        handle.await.unwrap();
+
+        let mut view = StreamingMessageView::new(receiver, StreamingViewConfig::default());
+        view.poll();
+        assert_eq!(view.status(), &StreamStatus::Finished);
+        assert_eq!(view.content(), "Hello **world**");
+        assert_eq!(text_rows(&view, 40), ["Hello world"]);
+    }
+}
@@ -1,6 +1,20 @@
+//! Summary panel: generates summaries in the background, browses memory
+//! files and exports what it shows.
+//!
+//! Generation runs on a task spawned by [`SummaryPanel::start_generation`].
+//! The [`SummaryGenerator`] reports how many files it has processed, which
+//! the panel shows until the summary arrives or the user cancels with Esc.
+
 use crate::theme::{ComponentType, ThemeManager};
-use fennec_commands::{EnhancedSummarizeArgs, OutputDestination, SummaryDepth, SummaryType};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use crossterm::event::{KeyCode, KeyEvent};
+use fennec_commands::{
+    CommandContext, CommandRegistry, EnhancedSummarizeArgs, FileOperations, OutputDestination,
+    SummaryDepth, SummaryType,
+};
 use fennec_memory::{MemoryFileMetadata, MemoryFileType};
+use fennec_security::SandboxLevel;
 use ratatui::{
     buffer::Buffer,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
@@ -11,10 +25,100 @@ use ratatui::{
         ScrollbarState, StatefulWidget, Tabs, Widget, Wrap,
     },
 };
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
 use uuid::Uuid;
 
-/// Summary panel component for displaying and managing summaries
+/// File name offered when exporting
+const DEFAULT_EXPORT_PATH: &str = "summary.md";
+
+/// How far a summary generation has got
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SummaryProgress {
+    pub processed: usize,
+    pub total: usize,
+}
+
+/// Message from a generation task to the panel
+#[derive(Debug)]
+enum GenerationUpdate {
+    Progress(SummaryProgress),
+    Done(Result<String, String>),
+}
+
+/// Lets a [`SummaryGenerator`] report how many files it has processed
 #[derive(Debug, Clone)]
+pub struct ProgressReporter(mpsc::UnboundedSender<GenerationUpdate>);
+
+impl ProgressReporter {
+    pub fn report(&self, processed: usize, total: usize) {
+        // The panel may have cancelled the generation
+        let _ = self.0.send(GenerationUpdate::Progress(SummaryProgress {
+            processed,
+            total,
+        }));
+    }
+}
+
+/// Produces the summaries shown in the panel
+#[async_trait]
+pub trait SummaryGenerator: Send + Sync {
+    async fn generate(
+        &self,
+        args: EnhancedSummarizeArgs,
+        progress: ProgressReporter,
+    ) -> Result<String>;
+}
+
+/// [`SummaryGenerator`] running the `summarize_enhanced` registry command
+pub struct CommandSummaryGenerator {
+    registry: Arc<CommandRegistry>,
+    context: CommandContext,
+}
+
+impl CommandSummaryGenerator {
+    pub fn new(registry: Arc<CommandRegistry>, context: CommandContext) -> Self {
+        Self { registry, context }
+    }
+}
+
+#[async_trait]
+impl SummaryGenerator for CommandSummaryGenerator {
+    async fn generate(
+        &self,
+        args: EnhancedSummarizeArgs,
+        progress: ProgressReporter,
+    ) -> Result<String> {
+        progress.report(0, 1);
+        let result = self
+            .registry
+            .execute_command(
+                "summarize_enhanced",
+                &serde_json::to_value(&args)?,
+                &self.context,
+            )
+            .await?;
+        progress.report(1, 1);
+
+        if result.success {
+            Ok(result.output)
+        } else {
+            Err(anyhow!(result.error.unwrap_or(result.output)))
+        }
+    }
+}
+
+/// Generation running in the background
+#[derive(Debug)]
+struct GenerationTask {
+    updates: mpsc::UnboundedReceiver<GenerationUpdate>,
+    handle: JoinHandle<()>,
+}
+
+/// Summary panel component for displaying and managing summaries
+#[derive(Debug)]
 pub struct SummaryPanel {
     /// Current summary content being displayed
     pub current_summary: Option<String>,
@@ -42,6 +146,10 @@ pub struct SummaryPanel {
     pub last_updated: Option<chrono::DateTime<chrono::Utc>>,
     /// Summary generation status
     pub generation_status: SummaryGenerationStatus,
+    /// Generation in flight, if any
+    generation: Option<GenerationTask>,
+    /// Path typed so far when exporting
+    export_prompt: Option<String>,
 }
 
 /// Available tabs in the summary panel
@@ -53,12 +161,13 @@ pub enum SummaryTab {
 }
 
 /// Status of summary generation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum SummaryGenerationStatus {
     Idle,
-    Generating,
+    Generating(SummaryProgress),
     Success(String), // Success message
     Error(String),   // Error message
+    Cancelled,
 }
 
 impl Default for SummaryPanel {
@@ -87,6 +196,9 @@ impl SummaryPanel {
                 time_range_hours: Some(24),
                 save_to_memory: Some(false),
                 memory_tags: None,
+                recursive: None,
+                concurrency: None,
+                base_ref: None,
             },
             current_tab: SummaryTab::Summary,
             summary_scroll_state: ScrollbarState::default(),
@@ -96,6 +208,8 @@ impl SummaryPanel {
             is_loading: false,
             last_updated: None,
             generation_status: SummaryGenerationStatus::Idle,
+            generation: None,
+            export_prompt: None,
         }
     }
 
@@ -117,7 +231,8 @@ impl SummaryPanel {
     pub fn set_loading(&mut self, loading: bool) {
         self.is_loading = loading;
         if loading {
-            self.generation_status = SummaryGenerationStatus::Generating;
+            self.generation_status =
+                SummaryGenerationStatus::Generating(SummaryProgress::default());
             self.summary_content_length = 0;
             self.summary_scroll_position = 0;
             self.update_scrollbar_state();
@@ -126,15 +241,223 @@ impl SummaryPanel {
 
     /// Set generation status
     pub fn set_generation_status(&mut self, status: SummaryGenerationStatus) {
-        if matches!(
-            &status,
-            SummaryGenerationStatus::Success(_) | SummaryGenerationStatus::Error(_)
-        ) {
+        if !matches!(&status, SummaryGenerationStatus::Generating(_)) {
             self.is_loading = false;
         }
         self.generation_status = status;
     }
 
+    /// Generate a summary with `generator` in the background, replacing
+    /// any generation in flight
+    pub fn start_generation(
+        &mut self,
+        generator: Arc<dyn SummaryGenerator>,
+        args: EnhancedSummarizeArgs,
+    ) {
+        self.cancel_generation();
+        self.current_summary = None;
+        self.set_loading(true);
+
+        let (sender, updates) = mpsc::unbounded_channel();
+        let progress = ProgressReporter(sender.clone());
+        let handle = tokio::spawn(async move {
+            let result = generator
+                .generate(args, progress)
+                .await
+                .map_err(|e| e.to_string());
+            let _ = sender.send(GenerationUpdate::Done(result));
+        });
+        self.generation = Some(GenerationTask { updates, handle });
+    }
+
+    pub fn is_generating(&self) -> bool {
+        self.generation.is_some()
+    }
+
+    /// Stop the generation in flight. Returns whether there was one.
+    pub fn cancel_generation(&mut self) -> bool {
+        let Some(task) = self.generation.take() else {
+            return false;
+        };
+        task.handle.abort();
+        self.set_generation_status(SummaryGenerationStatus::Cancelled);
+        true
+    }
+
+    /// Apply the progress and result that have arrived without waiting.
+    /// Returns whether anything changed.
+    pub fn poll_generation(&mut self) -> bool {
+        let mut changed = false;
+        while let Some(task) = self.generation.as_mut() {
+            match task.updates.try_recv() {
+                Ok(update) => {
+                    self.apply_generation_update(update);
+                    changed = true;
+                }
+                Err(mpsc::error::TryRecvError::Empty) => break,
+                Err(mpsc::error::TryRecvError::Disconnected) => {
+                    self.apply_generation_update(GenerationUpdate::Done(Err(
+                        "Summary generation stopped unexpectedly".to_string(),
+                    )));
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+
+    /// Wait for the next progress report or result and apply it. Returns
+    /// `false` when nothing is in flight.
+    pub async fn next_generation_update(&mut self) -> bool {
+        let Some(task) = self.generation.as_mut() else {
+            return false;
+        };
+        let update = task.updates.recv().await.unwrap_or_else(|| {
+            GenerationUpdate::Done(Err("Summary generation stopped unexpectedly".to_string()))
+        });
+        self.apply_generation_update(update);
+        true
+    }
+
+    fn apply_generation_update(&mut self, update: GenerationUpdate) {
+        match update {
+            GenerationUpdate::Progress(progress) => {
+                self.generation_status = SummaryGenerationStatus::Generating(progress);
+            }
+            GenerationUpdate::Done(result) => {
+                self.generation = None;
+                match result {
+                    Ok(summary) => {
+                        self.set_summary(summary);
+                        self.set_generation_status(SummaryGenerationStatus::Success(
+                            "Summary generated".to_string(),
+                        ));
+                    }
+                    Err(e) => self.set_generation_status(SummaryGenerationStatus::Error(e)),
+                }
+            }
+        }
+    }
+
+    /// Text of the current tab, as exported
+    pub fn current_tab_content(&self) -> Option<String> {
+        match self.current_tab {
+            SummaryTab::Summary => self.current_summary.clone(),
+            SummaryTab::MemoryFiles => {
+                if self.memory_files.is_empty() {
+                    return None;
+                }
+                let lines: Vec<String> = self
+                    .memory_files
+                    .iter()
+                    .map(|file| {
+                        format!(
+                            "- {} ({:?}) tags: {}, updated {}",
+                            file.name,
+                            file.file_type,
+                            file.tags.join(", "),
+                            file.updated_at.format("%Y-%m-%d %H:%M")
+                        )
+                    })
+                    .collect();
+                Some(format!("# Memory Files\n\n{}\n", lines.join("\n")))
+            }
+            SummaryTab::Settings => Some(format!(
+                "# Summary Settings\n\n\
+                 - Target: {}\n\
+                 - Type: {:?}\n\
+                 - Depth: {:?}\n\
+                 - Time range: {} hours\n\
+                 - Save to memory: {}\n",
+                self.summary_args.target,
+                self.summary_args
+                    .summary_type
+                    .as_ref()
+                    .unwrap_or(&SummaryType::Session),
+                self.summary_args
+                    .depth_level
+                    .as_ref()
+                    .unwrap_or(&SummaryDepth::Standard),
+                self.summary_args.time_range_hours.unwrap_or(24),
+                self.summary_args.save_to_memory.unwrap_or(false)
+            )),
+        }
+    }
+
+    /// Path typed so far while the export prompt is open
+    pub fn export_prompt(&self) -> Option<&str> {
+        self.export_prompt.as_deref()
+    }
+
+    /// Handle a key press. Returns the action for the caller to carry out,
+    /// if the key asked for one.
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<SummaryPanelAction> {
+        if let Some(path) = self.export_prompt.as_mut() {
+            match key.code {
+                KeyCode::Char(c) => path.push(c),
+                KeyCode::Backspace => {
+                    path.pop();
+                }
+                KeyCode::Esc => self.export_prompt = None,
+                KeyCode::Enter => {
+                    let path = self.export_prompt.take().unwrap_or_default();
+                    let path = path.trim();
+                    if path.is_empty() {
+                        return None;
+                    }
+                    return self
+                        .current_tab_content()
+                        .map(|content| SummaryPanelAction::Export {
+                            path: path.to_string(),
+                            content,
+                        });
+                }
+                _ => {}
+            }
+            return None;
+        }
+
+        match key.code {
+            KeyCode::Esc => {
+                self.cancel_generation();
+            }
+            KeyCode::Tab => self.next_tab(),
+            KeyCode::BackTab => self.previous_tab(),
+            KeyCode::Up | KeyCode::Char('k') => match self.current_tab {
+                SummaryTab::Summary => self.scroll_summary_up(),
+                SummaryTab::MemoryFiles => self.select_previous_memory_file(),
+                SummaryTab::Settings => {}
+            },
+            KeyCode::Down | KeyCode::Char('j') => match self.current_tab {
+                SummaryTab::Summary => self.scroll_summary_down(),
+                SummaryTab::MemoryFiles => self.select_next_memory_file(),
+                SummaryTab::Settings => {}
+            },
+            KeyCode::Enter if self.current_tab == SummaryTab::MemoryFiles => {
+                return self
+                    .get_selected_memory_file()
+                    .map(|file| SummaryPanelAction::LoadMemoryFile(file.id));
+            }
+            KeyCode::Char('g') if !self.is_generating() => {
+                return Some(SummaryPanelAction::GenerateSummary(
+                    self.summary_args.clone(),
+                ));
+            }
+            KeyCode::Char('r') => return Some(SummaryPanelAction::RefreshMemoryFiles),
+            KeyCode::Char('x') => {
+                if self.current_tab_content().is_some() {
+                    self.export_prompt = Some(DEFAULT_EXPORT_PATH.to_string());
+                } else {
+                    self.set_generation_status(SummaryGenerationStatus::Error(
+                        "Nothing to export".to_string(),
+                    ));
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
     /// Update memory files list
     pub fn update_memory_files(&mut self, files: Vec<MemoryFileMetadata>) {
         self.memory_files = files;
@@ -335,7 +658,15 @@ impl SummaryPanel {
 
             scrollbar.render(chunks[1], buf, &mut self.summary_scroll_state);
         } else if self.is_loading {
-            let loading_text = "Generating summary...";
+            let loading_text = match &self.generation_status {
+                SummaryGenerationStatus::Generating(progress) if progress.total > 0 => format!(
+                    "Generating summary... {}/{} files ({}%)\n\nPress Esc to cancel.",
+                    progress.processed,
+                    progress.total,
+                    progress.processed * 100 / progress.total
+                ),
+                _ => "Generating summary...\n\nPress Esc to cancel.".to_string(),
+            };
             let paragraph = Paragraph::new(loading_text)
                 .block(
                     Block::default()
@@ -394,7 +725,7 @@ impl SummaryPanel {
             .memory_files
             .iter()
             .map(|file| {
-                let file_type_icon = match file.file_type {
+                let file_type_icon = match &file.file_type {
                     MemoryFileType::ProjectContext => "🏗️",
                     MemoryFileType::DebuggingPatterns => "🐛",
                     MemoryFileType::CodePatterns => "💻",
@@ -402,6 +733,11 @@ impl SummaryPanel {
                     MemoryFileType::Learning => "📚",
                     MemoryFileType::Knowledge => "🧠",
                     MemoryFileType::Templates => "📋",
+                    MemoryFileType::ArchitectureDecisions => "📐",
+                    MemoryFileType::Onboarding => "🧭",
+                    MemoryFileType::Runbook => "📖",
+                    MemoryFileType::Glossary => "🔤",
+                    MemoryFileType::Custom(_) => "📄",
                 };
 
                 let content = vec![
@@ -502,6 +838,8 @@ impl SummaryPanel {
             Line::from("  Tab - Switch tabs"),
             Line::from("  ↑/↓ - Navigate memory files"),
             Line::from("  Enter - View selected memory file"),
+            Line::from("  x - Export the current tab to a file"),
+            Line::from("  Esc - Cancel summary generation"),
         ];
 
         let paragraph = Paragraph::new(settings_lines)
@@ -519,17 +857,10 @@ impl SummaryPanel {
 
     /// Render status bar
     fn render_status_bar(&self, area: Rect, buf: &mut Buffer, theme: &ThemeManager) {
-        let status_text = match &self.generation_status {
-            SummaryGenerationStatus::Idle => {
-                if let Some(updated) = self.last_updated {
-                    format!("Last updated: {}", updated.format("%H:%M:%S"))
-                } else {
-                    "Ready".to_string()
-                }
-            }
-            SummaryGenerationStatus::Generating => "Generating summary...".to_string(),
-            SummaryGenerationStatus::Success(msg) => format!("✅ {}", msg),
-            SummaryGenerationStatus::Error(msg) => format!("❌ {}", msg),
+        let status_text = if let Some(path) = &self.export_prompt {
+            format!("Export to: {}▏", path)
+        } else {
+            self.status_text()
         };
 
         let paragraph = Paragraph::new(status_text)
@@ -543,6 +874,26 @@ impl SummaryPanel {
 
         paragraph.render(area, buf);
     }
+
+    fn status_text(&self) -> String {
+        match &self.generation_status {
+            SummaryGenerationStatus::Idle => {
+                if let Some(updated) = self.last_updated {
+                    format!("Last updated: {}", updated.format("%H:%M:%S"))
+                } else {
+                    "Ready".to_string()
+                }
+            }
+            SummaryGenerationStatus::Generating(progress) if progress.total > 0 => format!(
+                "Generating summary... {}/{} files",
+                progress.processed, progress.total
+            ),
+            SummaryGenerationStatus::Generating(_) => "Generating summary...".to_string(),
+            SummaryGenerationStatus::Success(msg) => format!("✅ {}", msg),
+            SummaryGenerationStatus::Error(msg) => format!("❌ {}", msg),
+            SummaryGenerationStatus::Cancelled => "Summary generation cancelled".to_string(),
+        }
+    }
 }
 
 /// Summary panel events and actions
@@ -558,8 +909,46 @@ pub enum SummaryPanelAction {
     SaveToMemory(String), // filename
     /// Update settings
     UpdateSettings(EnhancedSummarizeArgs),
-    /// Export summary to file
-    ExportSummary(String), // file path
+    /// Write the current tab's content to a file
+    Export { path: String, content: String },
+}
+
+/// Writes exported summaries through the sandboxed file operations
+pub struct SummaryExporter {
+    file_ops: FileOperations,
+    sandbox_level: SandboxLevel,
+    workspace_path: Option<PathBuf>,
+}
+
+impl SummaryExporter {
+    pub fn new(sandbox_level: SandboxLevel, workspace_path: Option<PathBuf>) -> Self {
+        Self {
+            file_ops: FileOperations::with_default_config(),
+            sandbox_level,
+            workspace_path,
+        }
+    }
+
+    /// Write `content` to `path`, relative to the workspace unless
+    /// absolute. Returns the path written.
+    pub async fn export(&self, path: &str, content: &str) -> Result<PathBuf> {
+        let path = Path::new(path);
+        let path = match &self.workspace_path {
+            Some(workspace) if path.is_relative() => workspace.join(path),
+            _ => path.to_path_buf(),
+        };
+        let workspace = self
+            .workspace_path
+            .as_ref()
+            .map(|workspace| workspace.display().to_string());
+
+        let path = self
+            .file_ops
+            .validate_file_path(&path, &self.sandbox_level, workspace.as_deref())
+            .await?;
+        self.file_ops.atomic_write_file(&path, content).await?;
+        Ok(path)
+    }
 }
 
 /// Helper functions for summary panel integration
@@ -578,6 +967,9 @@ impl SummaryPanel {
             time_range_hours: Some(24),
             save_to_memory: Some(false),
             memory_tags: Some(vec!["session".to_string(), "tui".to_string()]),
+            recursive: None,
+            concurrency: None,
+            base_ref: None,
         }
     }
 
@@ -595,6 +987,9 @@ impl SummaryPanel {
             time_range_hours: Some(24),
             save_to_memory: Some(false),
             memory_tags: Some(vec!["project".to_string(), "tui".to_string()]),
+            recursive: None,
+            concurrency: None,
+            base_ref: None,
         }
     }
 
@@ -638,6 +1033,165 @@ impl SummaryPanel {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crossterm::event::KeyModifiers;
+    use tempfile::TempDir;
+    use tokio::sync::Semaphore;
+
+    /// Generator summarizing `files` files, one per permit added to `gate`
+    struct MockGenerator {
+        files: usize,
+        gate: Arc<Semaphore>,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl SummaryGenerator for MockGenerator {
+        async fn generate(
+            &self,
+            args: EnhancedSummarizeArgs,
+            progress: ProgressReporter,
+        ) -> Result<String> {
+            progress.report(0, self.files);
+            for processed in 1..=self.files {
+                self.gate.acquire().await?.forget();
+                progress.report(processed, self.files);
+            }
+            if self.fail {
+                return Err(anyhow!("provider unavailable"));
+            }
+            Ok(format!("Summary of {}", args.target))
+        }
+    }
+
+    fn mock_generator(files: usize, fail: bool) -> (Arc<dyn SummaryGenerator>, Arc<Semaphore>) {
+        let gate = Arc::new(Semaphore::new(0));
+        let generator = MockGenerator {
+            files,
+            gate: gate.clone(),
+            fail,
+        };
+        (Arc::new(generator), gate)
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn progress(processed: usize, total: usize) -> SummaryGenerationStatus {
+        SummaryGenerationStatus::Generating(SummaryProgress { processed, total })
+    }
+
+    #[tokio::test]
+    async fn test_generation_reports_progress_across_tab_switches() {
+        let mut panel = SummaryPanel::new();
+        let (generator, gate) = mock_generator(3, false);
+
+        let Some(SummaryPanelAction::GenerateSummary(args)) =
+            panel.handle_key(key(KeyCode::Char('g')))
+        else {
+            panic!("expected a generate action");
+        };
+        panel.start_generation(generator, args);
+        assert!(panel.is_loading);
+        assert_eq!(panel.generation_status, progress(0, 0));
+
+        assert!(panel.next_generation_update().await);
+        assert_eq!(panel.generation_status, progress(0, 3));
+        gate.add_permits(1);
+        assert!(panel.next_generation_update().await);
+        assert_eq!(panel.generation_status, progress(1, 3));
+
+        // Generating again is refused while one is in flight
+        assert!(panel.handle_key(key(KeyCode::Char('g'))).is_none());
+
+        panel.handle_key(key(KeyCode::Tab));
+        assert_eq!(panel.current_tab, SummaryTab::MemoryFiles);
+        gate.add_permits(2);
+        while panel.is_generating() {
+            panel.next_generation_update().await;
+        }
+        panel.handle_key(key(KeyCode::BackTab));
+
+        assert_eq!(
+            panel.generation_status,
+            SummaryGenerationStatus::Success("Summary generated".to_string())
+        );
+        assert_eq!(panel.current_summary.as_deref(), Some("Summary of current"));
+        assert!(!panel.is_loading);
+    }
+
+    #[tokio::test]
+    async fn test_generation_cancel_and_failure() {
+        let mut panel = SummaryPanel::new();
+        let (generator, _gate) = mock_generator(2, false);
+        panel.start_generation(generator, panel.create_session_summary_args());
+        panel.next_generation_update().await;
+
+        panel.handle_key(key(KeyCode::Esc));
+        assert_eq!(panel.generation_status, SummaryGenerationStatus::Cancelled);
+        assert!(!panel.is_generating());
+        assert!(!panel.is_loading);
+        assert!(!panel.poll_generation());
+
+        let (generator, gate) = mock_generator(1, true);
+        gate.add_permits(1);
+        panel.start_generation(generator, panel.create_session_summary_args());
+        while panel.is_generating() {
+            panel.next_generation_update().await;
+        }
+        assert_eq!(
+            panel.generation_status,
+            SummaryGenerationStatus::Error("provider unavailable".to_string())
+        );
+        assert!(panel.current_summary.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_export_prompt_writes_current_tab() {
+        let workspace = TempDir::new().unwrap();
+        let mut panel = SummaryPanel::new();
+
+        // Nothing to export before a summary exists
+        assert!(panel.handle_key(key(KeyCode::Char('x'))).is_none());
+        assert!(panel.export_prompt().is_none());
+
+        panel.set_summary("# Session\n\nDone".to_string());
+        panel.handle_key(key(KeyCode::Char('x')));
+        assert_eq!(panel.export_prompt(), Some(DEFAULT_EXPORT_PATH));
+        for _ in 0..DEFAULT_EXPORT_PATH.len() {
+            panel.handle_key(key(KeyCode::Backspace));
+        }
+        for c in "notes.md".chars() {
+            panel.handle_key(key(KeyCode::Char(c)));
+        }
+        let action = panel.handle_key(key(KeyCode::Enter));
+        assert!(panel.export_prompt().is_none());
+        let Some(SummaryPanelAction::Export { path, content }) = action else {
+            panic!("expected an export action, got {:?}", action);
+        };
+        assert_eq!(path, "notes.md");
+
+        let exporter = SummaryExporter::new(
+            SandboxLevel::WorkspaceWrite,
+            Some(workspace.path().to_path_buf()),
+        );
+        let written = exporter.export(&path, &content).await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&written).unwrap(),
+            "# Session\n\nDone"
+        );
+
+        // The sandbox still applies
+        let outside = TempDir::new().unwrap();
+        let outside_path = outside.path().join("notes.md");
+        assert!(exporter
+            .export(outside_path.to_str().unwrap(), &content)
+            .await
+            .is_err());
+        let read_only =
+            SummaryExporter::new(SandboxLevel::ReadOnly, Some(workspace.path().to_path_buf()));
+        assert!(read_only.export(&path, &content).await.is_err());
+    }
 
     #[test]
     fn test_summary_panel_creation() {
@@ -0,0 +1,298 @@
+//! Terminal setup that is undone however the app exits.
+//!
+//! [`TerminalGuard`] tracks what it switched on - raw mode, the alternate
+//! screen, mouse capture, bracketed paste, enhanced key reporting - and
+//! switches it off again when dropped, so an error or panic doesn't leave
+//! the shell unusable. It can also hand the terminal to another program,
+//! such as an external editor, and take it back afterwards.
+
+use crossterm::{
+    event::{
+        DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
+    execute,
+    terminal::{
+        disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
+};
+use std::io::{self, Write};
+
+/// What a guard has switched on
+#[derive(Debug, Clone, Copy, Default)]
+struct Modes {
+    raw_mode: bool,
+    alternate_screen: bool,
+    mouse_capture: bool,
+    bracketed_paste: bool,
+    keyboard_enhancement: bool,
+}
+
+/// Restores the terminal when dropped
+#[derive(Debug)]
+pub struct TerminalGuard<W: Write> {
+    writer: W,
+    raw_mode: bool,
+    alternate_screen: bool,
+    mouse_capture: bool,
+    bracketed_paste: bool,
+    keyboard_enhancement: bool,
+    /// Modes to switch back on in [`Self::resume`]
+    suspended: Option<Modes>,
+}
+
+impl<W: Write> TerminalGuard<W> {
+    /// A guard for `writer` with nothing switched on yet
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            raw_mode: false,
+            alternate_screen: false,
+            mouse_capture: false,
+            bracketed_paste: false,
+            keyboard_enhancement: false,
+            suspended: None,
+        }
+    }
+
+    /// Switch to raw mode on the alternate screen
+    pub fn enter(&mut self) -> io::Result<()> {
+        enable_raw_mode()?;
+        self.raw_mode = true;
+        execute!(self.writer, EnterAlternateScreen)?;
+        self.alternate_screen = true;
+        Ok(())
+    }
+
+    pub fn is_mouse_captured(&self) -> bool {
+        self.mouse_capture
+    }
+
+    /// Start or stop capturing mouse events
+    pub fn set_mouse_capture(&mut self, enabled: bool) -> io::Result<()> {
+        if enabled == self.mouse_capture {
+            return Ok(());
+        }
+        if enabled {
+            execute!(self.writer, EnableMouseCapture)?;
+        } else {
+            execute!(self.writer, DisableMouseCapture)?;
+        }
+        self.mouse_capture = enabled;
+        Ok(())
+    }
+
+    /// Start or stop receiving pastes as one event rather than as keys,
+    /// so a pasted line break doesn't send the input
+    pub fn set_bracketed_paste(&mut self, enabled: bool) -> io::Result<()> {
+        if enabled == self.bracketed_paste {
+            return Ok(());
+        }
+        if enabled {
+            execute!(self.writer, EnableBracketedPaste)?;
+        } else {
+            execute!(self.writer, DisableBracketedPaste)?;
+        }
+        self.bracketed_paste = enabled;
+        Ok(())
+    }
+
+    /// Ask the terminal to report keys it otherwise can't tell apart, such
+    /// as Shift+Enter. Does nothing on terminals without support.
+    pub fn enable_keyboard_enhancement(&mut self) -> io::Result<()> {
+        if self.keyboard_enhancement || !supports_keyboard_enhancement().unwrap_or(false) {
+            return Ok(());
+        }
+        execute!(
+            self.writer,
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+        )?;
+        self.keyboard_enhancement = true;
+        Ok(())
+    }
+
+    /// Give the terminal back as it was before the app started, e.g. to run
+    /// an editor in it, until [`Self::resume`]
+    pub fn suspend(&mut self) -> io::Result<()> {
+        let modes = Modes {
+            raw_mode: self.raw_mode,
+            alternate_screen: self.alternate_screen,
+            mouse_capture: self.mouse_capture,
+            bracketed_paste: self.bracketed_paste,
+            keyboard_enhancement: self.keyboard_enhancement,
+        };
+        self.restore()?;
+        self.suspended = Some(modes);
+        Ok(())
+    }
+
+    /// Switch back on what [`Self::suspend`] switched off
+    pub fn resume(&mut self) -> io::Result<()> {
+        let Some(modes) = self.suspended.take() else {
+            return Ok(());
+        };
+        if modes.raw_mode {
+            enable_raw_mode()?;
+            self.raw_mode = true;
+        }
+        if modes.alternate_screen {
+            execute!(self.writer, EnterAlternateScreen)?;
+            self.alternate_screen = true;
+        }
+        self.set_mouse_capture(modes.mouse_capture)?;
+        self.set_bracketed_paste(modes.bracketed_paste)?;
+        if modes.keyboard_enhancement {
+            execute!(
+                self.writer,
+                PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+            )?;
+            self.keyboard_enhancement = true;
+        }
+        Ok(())
+    }
+
+    /// Undo everything switched on. Safe to call more than once.
+    pub fn restore(&mut self) -> io::Result<()> {
+        if self.keyboard_enhancement {
+            self.keyboard_enhancement = false;
+            execute!(self.writer, PopKeyboardEnhancementFlags)?;
+        }
+        if self.bracketed_paste {
+            self.bracketed_paste = false;
+            execute!(self.writer, DisableBracketedPaste)?;
+        }
+        if self.mouse_capture {
+            self.mouse_capture = false;
+            execute!(self.writer, DisableMouseCapture)?;
+        }
+        if self.alternate_screen {
+            self.alternate_screen = false;
+            execute!(self.writer, LeaveAlternateScreen)?;
+        }
+        if self.raw_mode {
+            self.raw_mode = false;
+            disable_raw_mode()?;
+        }
+        Ok(())
+    }
+}
+
+/// Leave the alternate screen and raw mode on stdout without a guard. For
+/// the panic hook, which runs before the guard is dropped and would
+/// otherwise print the crash report onto the alternate screen.
+pub fn restore_stdout() {
+    let _ = execute!(
+        io::stdout(),
+        PopKeyboardEnhancementFlags,
+        DisableBracketedPaste,
+        DisableMouseCapture,
+        LeaveAlternateScreen
+    );
+    let _ = disable_raw_mode();
+}
+
+impl<W: Write> Drop for TerminalGuard<W> {
+    fn drop(&mut self) {
+        if let Err(e) = self.restore() {
+            eprintln!("Failed to restore terminal: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Writer whose output can be read after the guard is gone
+    #[derive(Clone, Default)]
+    struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedOutput {
+        fn take(&self) -> Vec<u8> {
+            std::mem::take(&mut self.0.lock().unwrap())
+        }
+    }
+
+    fn disable_sequence() -> Vec<u8> {
+        let mut expected = Vec::new();
+        execute!(expected, DisableMouseCapture).unwrap();
+        expected
+    }
+
+    #[test]
+    fn test_drop_disables_mouse_capture_once() {
+        let output = SharedOutput::default();
+        let mut guard = TerminalGuard::new(output.clone());
+        guard.set_mouse_capture(true).unwrap();
+        assert!(guard.is_mouse_captured());
+        output.take();
+
+        guard.restore().unwrap();
+        assert_eq!(output.take(), disable_sequence());
+
+        // Nothing left to undo when the guard goes
+        drop(guard);
+        assert!(output.take().is_empty());
+    }
+
+    #[test]
+    fn test_panic_still_disables_mouse_capture() {
+        let output = SharedOutput::default();
+        let writer = output.clone();
+        let result = std::panic::catch_unwind(move || {
+            let mut guard = TerminalGuard::new(writer);
+            guard.set_mouse_capture(true).unwrap();
+            panic!("render failed");
+        });
+
+        assert!(result.is_err());
+        assert!(output.take().ends_with(&disable_sequence()));
+    }
+
+    #[test]
+    fn test_capture_left_off_writes_nothing() {
+        let output = SharedOutput::default();
+        let mut guard = TerminalGuard::new(output.clone());
+        guard.set_mouse_capture(false).unwrap();
+        drop(guard);
+        assert!(output.take().is_empty());
+    }
+
+    #[test]
+    fn test_suspend_and_resume_switch_modes_off_and_on() {
+        let output = SharedOutput::default();
+        let mut guard = TerminalGuard::new(output.clone());
+        guard.set_mouse_capture(true).unwrap();
+        guard.set_bracketed_paste(true).unwrap();
+        output.take();
+
+        guard.suspend().unwrap();
+        let mut expected = Vec::new();
+        execute!(expected, DisableBracketedPaste, DisableMouseCapture).unwrap();
+        assert_eq!(output.take(), expected);
+        assert!(!guard.is_mouse_captured());
+
+        guard.resume().unwrap();
+        let mut expected = Vec::new();
+        execute!(expected, EnableMouseCapture, EnableBracketedPaste).unwrap();
+        assert_eq!(output.take(), expected);
+        assert!(guard.is_mouse_captured());
+
+        // Resuming twice switches nothing on again
+        guard.resume().unwrap();
+        assert!(output.take().is_empty());
+    }
+}
@@ -1,7 +1,12 @@
+use crate::error::TuiError;
 use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::str::FromStr;
 
 /// Represents different UI component types for theming
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum ComponentType {
     Background,
     Border,
@@ -25,145 +30,355 @@ pub enum ComponentType {
     Tab,
     TabSelected,
     ListSelected,
+    Critical,
+    Directory,
 }
 
-/// Color theme configuration
-#[derive(Debug, Clone)]
-pub struct ColorTheme {
+impl ComponentType {
+    /// Every component with its key in theme files. A theme must style all
+    /// of them.
+    pub const ALL: [(ComponentType, &'static str); 24] = [
+        (ComponentType::Background, "background"),
+        (ComponentType::Border, "border"),
+        (ComponentType::Title, "title"),
+        (ComponentType::Text, "text"),
+        (ComponentType::Highlight, "highlight"),
+        (ComponentType::Selection, "selection"),
+        (ComponentType::Error, "error"),
+        (ComponentType::Warning, "warning"),
+        (ComponentType::Info, "info"),
+        (ComponentType::Success, "success"),
+        (ComponentType::Muted, "muted"),
+        (ComponentType::ChatUser, "chat_user"),
+        (ComponentType::ChatAssistant, "chat_assistant"),
+        (ComponentType::ChatSystem, "chat_system"),
+        (ComponentType::StatusActive, "status_active"),
+        (ComponentType::StatusInactive, "status_inactive"),
+        (ComponentType::PreviewBorder, "preview_border"),
+        (ComponentType::ScrollbarThumb, "scrollbar_thumb"),
+        (ComponentType::ScrollbarTrack, "scrollbar_track"),
+        (ComponentType::Tab, "tab"),
+        (ComponentType::TabSelected, "tab_selected"),
+        (ComponentType::ListSelected, "list_selected"),
+        (ComponentType::Critical, "critical"),
+        (ComponentType::Directory, "directory"),
+    ];
+
+    /// Key in theme files
+    pub fn key(&self) -> &'static str {
+        Self::ALL
+            .iter()
+            .find(|(component, _)| component == self)
+            .map_or("", |(_, key)| key)
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|(_, component_key)| *component_key == key)
+            .map(|(component, _)| *component)
+    }
+
+    /// Style of the component in `color` when a theme gives only a color
+    fn style_for(&self, color: Color) -> Style {
+        match self {
+            ComponentType::Title
+            | ComponentType::Highlight
+            | ComponentType::Error
+            | ComponentType::Critical
+            | ComponentType::ChatUser
+            | ComponentType::TabSelected
+            | ComponentType::Directory => Style::default().fg(color).add_modifier(Modifier::BOLD),
+            ComponentType::ChatSystem => Style::default().fg(color).add_modifier(Modifier::ITALIC),
+            ComponentType::Selection | ComponentType::ListSelected => Style::default().bg(color),
+            _ => Style::default().fg(color),
+        }
+    }
+}
+
+/// Styles for every component of the interface
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
     pub name: String,
-    pub background: Color,
-    pub border: Color,
-    pub title: Color,
-    pub text: Color,
-    pub highlight: Color,
-    pub selection: Color,
-    pub error: Color,
-    pub warning: Color,
-    pub info: Color,
-    pub success: Color,
-    pub muted: Color,
-    pub chat_user: Color,
-    pub chat_assistant: Color,
-    pub chat_system: Color,
-    pub status_active: Color,
-    pub status_inactive: Color,
-    pub preview_border: Color,
-    pub scrollbar_thumb: Color,
-    pub scrollbar_track: Color,
-    pub tab: Color,
-    pub tab_selected: Color,
-    pub list_selected: Color,
+    styles: HashMap<ComponentType, Style>,
 }
 
-impl Default for ColorTheme {
+/// Former name of [`Theme`]
+pub type ColorTheme = Theme;
+
+impl Default for Theme {
     fn default() -> Self {
         Self::default_dark()
     }
 }
 
-impl ColorTheme {
-    /// Default dark theme
-    pub fn default_dark() -> Self {
+/// Component styles as written in a theme file: a color, or a table of
+/// colors and modifiers
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum StyleSpec {
+    Color(String),
+    Style {
+        fg: Option<String>,
+        bg: Option<String>,
+        #[serde(default)]
+        bold: bool,
+        #[serde(default)]
+        italic: bool,
+        #[serde(default)]
+        underline: bool,
+        #[serde(default)]
+        dim: bool,
+        #[serde(default)]
+        reversed: bool,
+    },
+}
+
+/// A theme file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeFile {
+    name: String,
+    /// Built-in theme supplying the styles the file leaves out
+    #[serde(default)]
+    extends: Option<String>,
+    #[serde(default)]
+    styles: BTreeMap<String, StyleSpec>,
+}
+
+impl Theme {
+    /// Build a theme giving each component a color in its usual style
+    fn from_colors(name: &str, colors: [(ComponentType, Color); 24]) -> Self {
         Self {
-            name: "dark".to_string(),
-            background: Color::Rgb(26, 27, 38),
-            border: Color::Rgb(68, 71, 90),
-            title: Color::Rgb(199, 146, 234),
-            text: Color::Rgb(192, 202, 245),
-            highlight: Color::Rgb(137, 180, 250),
-            selection: Color::Rgb(49, 50, 68),
-            error: Color::Rgb(243, 139, 168),
-            warning: Color::Rgb(249, 226, 175),
-            info: Color::Rgb(116, 199, 236),
-            success: Color::Rgb(166, 227, 161),
-            muted: Color::Rgb(108, 112, 134),
-            chat_user: Color::Rgb(148, 226, 213),
-            chat_assistant: Color::Rgb(203, 166, 247),
-            chat_system: Color::Rgb(250, 179, 135),
-            status_active: Color::Rgb(166, 227, 161),
-            status_inactive: Color::Rgb(108, 112, 134),
-            preview_border: Color::Rgb(137, 180, 250),
-            scrollbar_thumb: Color::Rgb(108, 112, 134),
-            scrollbar_track: Color::Rgb(49, 50, 68),
-            tab: Color::Rgb(137, 180, 250),
-            tab_selected: Color::Rgb(166, 227, 161),
-            list_selected: Color::Rgb(49, 50, 68),
+            name: name.to_string(),
+            styles: colors
+                .into_iter()
+                .map(|(component, color)| (component, component.style_for(color)))
+                .collect(),
         }
     }
 
+    /// Default dark theme
+    pub fn default_dark() -> Self {
+        use ComponentType::*;
+        Self::from_colors(
+            "dark",
+            [
+                (Background, Color::Rgb(26, 27, 38)),
+                (Border, Color::Rgb(68, 71, 90)),
+                (Title, Color::Rgb(199, 146, 234)),
+                (Text, Color::Rgb(192, 202, 245)),
+                (Highlight, Color::Rgb(137, 180, 250)),
+                (Selection, Color::Rgb(49, 50, 68)),
+                (Error, Color::Rgb(243, 139, 168)),
+                (Warning, Color::Rgb(249, 226, 175)),
+                (Info, Color::Rgb(116, 199, 236)),
+                (Success, Color::Rgb(166, 227, 161)),
+                (Muted, Color::Rgb(108, 112, 134)),
+                (ChatUser, Color::Rgb(148, 226, 213)),
+                (ChatAssistant, Color::Rgb(203, 166, 247)),
+                (ChatSystem, Color::Rgb(250, 179, 135)),
+                (StatusActive, Color::Rgb(166, 227, 161)),
+                (StatusInactive, Color::Rgb(108, 112, 134)),
+                (PreviewBorder, Color::Rgb(137, 180, 250)),
+                (ScrollbarThumb, Color::Rgb(108, 112, 134)),
+                (ScrollbarTrack, Color::Rgb(49, 50, 68)),
+                (Tab, Color::Rgb(137, 180, 250)),
+                (TabSelected, Color::Rgb(166, 227, 161)),
+                (ListSelected, Color::Rgb(49, 50, 68)),
+                (Critical, Color::Rgb(235, 111, 146)),
+                (Directory, Color::Rgb(137, 180, 250)),
+            ],
+        )
+    }
+
     /// Default light theme
     pub fn default_light() -> Self {
-        Self {
-            name: "light".to_string(),
-            background: Color::Rgb(239, 241, 245),
-            border: Color::Rgb(140, 143, 161),
-            title: Color::Rgb(136, 57, 239),
-            text: Color::Rgb(76, 79, 105),
-            highlight: Color::Rgb(30, 102, 245),
-            selection: Color::Rgb(220, 224, 232),
-            error: Color::Rgb(210, 15, 57),
-            warning: Color::Rgb(254, 100, 11),
-            info: Color::Rgb(4, 165, 229),
-            success: Color::Rgb(64, 160, 43),
-            muted: Color::Rgb(156, 160, 176),
-            chat_user: Color::Rgb(23, 146, 229),
-            chat_assistant: Color::Rgb(136, 57, 239),
-            chat_system: Color::Rgb(254, 100, 11),
-            status_active: Color::Rgb(64, 160, 43),
-            status_inactive: Color::Rgb(156, 160, 176),
-            preview_border: Color::Rgb(30, 102, 245),
-            scrollbar_thumb: Color::Rgb(156, 160, 176),
-            scrollbar_track: Color::Rgb(220, 224, 232),
-            tab: Color::Rgb(30, 102, 245),
-            tab_selected: Color::Rgb(64, 160, 43),
-            list_selected: Color::Rgb(220, 224, 232),
+        use ComponentType::*;
+        Self::from_colors(
+            "light",
+            [
+                (Background, Color::Rgb(239, 241, 245)),
+                (Border, Color::Rgb(140, 143, 161)),
+                (Title, Color::Rgb(136, 57, 239)),
+                (Text, Color::Rgb(76, 79, 105)),
+                (Highlight, Color::Rgb(30, 102, 245)),
+                (Selection, Color::Rgb(220, 224, 232)),
+                (Error, Color::Rgb(210, 15, 57)),
+                (Warning, Color::Rgb(254, 100, 11)),
+                (Info, Color::Rgb(4, 165, 229)),
+                (Success, Color::Rgb(64, 160, 43)),
+                (Muted, Color::Rgb(156, 160, 176)),
+                (ChatUser, Color::Rgb(23, 146, 229)),
+                (ChatAssistant, Color::Rgb(136, 57, 239)),
+                (ChatSystem, Color::Rgb(254, 100, 11)),
+                (StatusActive, Color::Rgb(64, 160, 43)),
+                (StatusInactive, Color::Rgb(156, 160, 176)),
+                (PreviewBorder, Color::Rgb(30, 102, 245)),
+                (ScrollbarThumb, Color::Rgb(156, 160, 176)),
+                (ScrollbarTrack, Color::Rgb(220, 224, 232)),
+                (Tab, Color::Rgb(30, 102, 245)),
+                (TabSelected, Color::Rgb(64, 160, 43)),
+                (ListSelected, Color::Rgb(220, 224, 232)),
+                (Critical, Color::Rgb(230, 69, 83)),
+                (Directory, Color::Rgb(30, 102, 245)),
+            ],
+        )
+    }
+
+    /// High-contrast theme using only the basic terminal colors
+    pub fn high_contrast() -> Self {
+        use ComponentType::*;
+        let mut theme = Self::from_colors(
+            "high-contrast",
+            [
+                (Background, Color::Black),
+                (Border, Color::White),
+                (Title, Color::LightYellow),
+                (Text, Color::White),
+                (Highlight, Color::LightCyan),
+                (Selection, Color::Blue),
+                (Error, Color::LightRed),
+                (Warning, Color::LightYellow),
+                (Info, Color::LightCyan),
+                (Success, Color::LightGreen),
+                (Muted, Color::Gray),
+                (ChatUser, Color::LightGreen),
+                (ChatAssistant, Color::LightCyan),
+                (ChatSystem, Color::LightYellow),
+                (StatusActive, Color::LightGreen),
+                (StatusInactive, Color::Gray),
+                (PreviewBorder, Color::LightCyan),
+                (ScrollbarThumb, Color::White),
+                (ScrollbarTrack, Color::DarkGray),
+                (Tab, Color::White),
+                (TabSelected, Color::LightYellow),
+                (ListSelected, Color::Blue),
+                (Critical, Color::LightMagenta),
+                (Directory, Color::LightBlue),
+            ],
+        );
+        // Selected rows also need their text to stand out
+        for component in [Selection, ListSelected] {
+            theme.styles.insert(
+                component,
+                Style::default()
+                    .fg(Color::White)
+                    .bg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            );
         }
+        theme
+    }
+
+    /// The built-in themes
+    pub fn builtin() -> Vec<Theme> {
+        vec![
+            Self::default_dark(),
+            Self::default_light(),
+            Self::high_contrast(),
+        ]
+    }
+
+    /// Parse a theme file. Styles it leaves out come from the built-in
+    /// theme it `extends`, dark by default.
+    pub fn from_toml(contents: &str) -> Result<Self, TuiError> {
+        let file: ThemeFile = toml::from_str(contents).map_err(|e| TuiError::ThemeLoadFailed {
+            theme: "<unnamed>".to_string(),
+            reason: e.to_string(),
+        })?;
+        let failed = |reason: String| TuiError::ThemeLoadFailed {
+            theme: file.name.clone(),
+            reason,
+        };
+
+        let base = file.extends.as_deref().unwrap_or("dark");
+        let mut theme = Self::builtin()
+            .into_iter()
+            .find(|theme| theme.name == base)
+            .ok_or_else(|| failed(format!("unknown base theme '{}'", base)))?;
+        theme.name = file.name.clone();
+
+        for (key, spec) in &file.styles {
+            let component = ComponentType::from_key(key)
+                .ok_or_else(|| failed(format!("unknown style '{}'", key)))?;
+            theme.styles.insert(component, spec.to_style(component)?);
+        }
+
+        Ok(theme)
+    }
+
+    /// Load a theme file
+    pub fn load(path: &Path) -> Result<Self, TuiError> {
+        let contents = std::fs::read_to_string(path)?;
+        Self::from_toml(&contents)
     }
 
-    /// Get color for a specific component type
+    /// Components this theme has no style for
+    pub fn missing_components(&self) -> Vec<ComponentType> {
+        ComponentType::ALL
+            .iter()
+            .map(|(component, _)| *component)
+            .filter(|component| !self.styles.contains_key(component))
+            .collect()
+    }
+
+    /// Get color for a specific component type: its foreground, or its
+    /// background for components styled only by background
     pub fn get_color(&self, component: ComponentType) -> Color {
+        let style = self.get_style(component);
         match component {
-            ComponentType::Background => self.background,
-            ComponentType::Border => self.border,
-            ComponentType::Title => self.title,
-            ComponentType::Text => self.text,
-            ComponentType::Highlight => self.highlight,
-            ComponentType::Selection => self.selection,
-            ComponentType::Error => self.error,
-            ComponentType::Warning => self.warning,
-            ComponentType::Info => self.info,
-            ComponentType::Success => self.success,
-            ComponentType::Muted => self.muted,
-            ComponentType::ChatUser => self.chat_user,
-            ComponentType::ChatAssistant => self.chat_assistant,
-            ComponentType::ChatSystem => self.chat_system,
-            ComponentType::StatusActive => self.status_active,
-            ComponentType::StatusInactive => self.status_inactive,
-            ComponentType::PreviewBorder => self.preview_border,
-            ComponentType::ScrollbarThumb => self.scrollbar_thumb,
-            ComponentType::ScrollbarTrack => self.scrollbar_track,
-            ComponentType::Tab => self.tab,
-            ComponentType::TabSelected => self.tab_selected,
-            ComponentType::ListSelected => self.list_selected,
+            ComponentType::Selection | ComponentType::ListSelected => style.bg.or(style.fg),
+            _ => style.fg.or(style.bg),
         }
+        .unwrap_or(Color::Reset)
     }
 
     /// Get style for a specific component type
     pub fn get_style(&self, component: ComponentType) -> Style {
-        let color = self.get_color(component);
-        match component {
-            ComponentType::Title => Style::default().fg(color).add_modifier(Modifier::BOLD),
-            ComponentType::Highlight => Style::default().fg(color).add_modifier(Modifier::BOLD),
-            ComponentType::Selection => Style::default().bg(color),
-            ComponentType::Error => Style::default().fg(color).add_modifier(Modifier::BOLD),
-            ComponentType::Warning => Style::default().fg(color),
-            ComponentType::ChatUser => Style::default().fg(color).add_modifier(Modifier::BOLD),
-            ComponentType::ChatAssistant => Style::default().fg(color),
-            ComponentType::ChatSystem => Style::default().fg(color).add_modifier(Modifier::ITALIC),
-            ComponentType::Tab => Style::default().fg(color),
-            ComponentType::TabSelected => Style::default().fg(color).add_modifier(Modifier::BOLD),
-            ComponentType::ListSelected => Style::default().bg(color),
-            _ => Style::default().fg(color),
+        self.styles.get(&component).copied().unwrap_or_default()
+    }
+}
+
+impl StyleSpec {
+    fn to_style(&self, component: ComponentType) -> Result<Style, TuiError> {
+        let color = |spec: &str| {
+            Color::from_str(spec).map_err(|_| TuiError::InvalidColor {
+                color: spec.to_string(),
+            })
+        };
+
+        match self {
+            StyleSpec::Color(spec) => Ok(component.style_for(color(spec)?)),
+            StyleSpec::Style {
+                fg,
+                bg,
+                bold,
+                italic,
+                underline,
+                dim,
+                reversed,
+            } => {
+                let mut style = Style::default();
+                if let Some(fg) = fg {
+                    style = style.fg(color(fg)?);
+                }
+                if let Some(bg) = bg {
+                    style = style.bg(color(bg)?);
+                }
+                for (enabled, modifier) in [
+                    (bold, Modifier::BOLD),
+                    (italic, Modifier::ITALIC),
+                    (underline, Modifier::UNDERLINED),
+                    (dim, Modifier::DIM),
+                    (reversed, Modifier::REVERSED),
+                ] {
+                    if *enabled {
+                        style = style.add_modifier(modifier);
+                    }
+                }
+                Ok(style)
+            }
         }
     }
 }
@@ -171,8 +386,8 @@ impl ColorTheme {
 /// Theme manager for handling multiple themes and theme switching
 #[derive(Debug, Clone)]
 pub struct ThemeManager {
-    current_theme: ColorTheme,
-    available_themes: Vec<ColorTheme>,
+    current_theme: Theme,
+    available_themes: Vec<Theme>,
 }
 
 impl Default for ThemeManager {
@@ -184,7 +399,7 @@ impl Default for ThemeManager {
 impl ThemeManager {
     /// Create a new theme manager with default themes
     pub fn new() -> Self {
-        let available_themes = vec![ColorTheme::default_dark(), ColorTheme::default_light()];
+        let available_themes = Theme::builtin();
 
         Self {
             current_theme: available_themes[0].clone(),
@@ -193,12 +408,12 @@ impl ThemeManager {
     }
 
     /// Get the current active theme
-    pub fn current_theme(&self) -> &ColorTheme {
+    pub fn current_theme(&self) -> &Theme {
         &self.current_theme
     }
 
     /// Get all available themes
-    pub fn available_themes(&self) -> &[ColorTheme] {
+    pub fn available_themes(&self) -> &[Theme] {
         &self.available_themes
     }
 
@@ -246,12 +461,38 @@ impl ThemeManager {
     }
 
     /// Add a custom theme
-    pub fn add_theme(&mut self, theme: ColorTheme) {
+    pub fn add_theme(&mut self, theme: Theme) {
         // Remove existing theme with same name if it exists
         self.available_themes.retain(|t| t.name != theme.name);
+        if self.current_theme.name == theme.name {
+            self.current_theme = theme.clone();
+        }
         self.available_themes.push(theme);
     }
 
+    /// Add every `*.toml` theme in `dir`. Returns the files that failed to
+    /// load; the rest are added regardless.
+    pub fn load_themes_from_dir(&mut self, dir: &Path) -> Vec<TuiError> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        paths.sort();
+
+        let mut errors = Vec::new();
+        for path in paths {
+            match Theme::load(&path) {
+                Ok(theme) => self.add_theme(theme),
+                Err(e) => errors.push(e),
+            }
+        }
+        errors
+    }
+
     /// Get color for component type from current theme
     pub fn get_color(&self, component: ComponentType) -> Color {
         self.current_theme.get_color(component)
@@ -270,7 +511,7 @@ mod tests {
     #[test]
     fn test_theme_manager_creation() {
         let manager = ThemeManager::new();
-        assert_eq!(manager.available_themes().len(), 2);
+        assert_eq!(manager.available_themes().len(), 3);
         assert_eq!(manager.current_theme().name, "dark");
     }
 
@@ -331,4 +572,91 @@ mod tests {
         let title_style = theme.get_style(ComponentType::Title);
         assert!(title_style.add_modifier.contains(Modifier::BOLD));
     }
+
+    #[test]
+    fn test_builtin_themes_define_every_component() {
+        for theme in Theme::builtin() {
+            assert!(
+                theme.missing_components().is_empty(),
+                "{} is missing {:?}",
+                theme.name,
+                theme.missing_components()
+            );
+        }
+
+        for (component, key) in ComponentType::ALL {
+            assert_eq!(ComponentType::from_key(key), Some(component));
+            assert_eq!(component.key(), key);
+        }
+    }
+
+    #[test]
+    fn test_load_custom_theme() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("ocean.toml"),
+            r##"
+            name = "ocean"
+            extends = "light"
+
+            [styles]
+            text = "#112233"
+            error = "red"
+            title = { fg = "#0000ff", bg = "black", italic = true, underline = true }
+            selection = { bg = "blue" }
+            "##,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not a theme").unwrap();
+
+        let mut manager = ThemeManager::new();
+        assert!(manager.load_themes_from_dir(dir.path()).is_empty());
+        manager.set_theme("ocean").unwrap();
+        let theme = manager.current_theme();
+        assert!(theme.missing_components().is_empty());
+
+        // A bare color gets the component's usual modifiers
+        assert_eq!(
+            theme.get_style(ComponentType::Text),
+            Style::default().fg(Color::Rgb(0x11, 0x22, 0x33))
+        );
+        assert_eq!(
+            theme.get_style(ComponentType::Error),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+        );
+        // A table is taken as written
+        assert_eq!(
+            theme.get_style(ComponentType::Title),
+            Style::default()
+                .fg(Color::Rgb(0, 0, 255))
+                .bg(Color::Black)
+                .add_modifier(Modifier::ITALIC | Modifier::UNDERLINED)
+        );
+        assert_eq!(theme.get_color(ComponentType::Selection), Color::Blue);
+        // Missing styles fall back to the base theme
+        assert_eq!(
+            theme.get_style(ComponentType::Muted),
+            Theme::default_light().get_style(ComponentType::Muted)
+        );
+    }
+
+    #[test]
+    fn test_invalid_themes_are_rejected() {
+        for contents in [
+            "name = \"bad\"\n[styles]\ntext = \"not-a-color\"",
+            "name = \"bad\"\n[styles]\nsparkles = \"red\"",
+            "name = \"bad\"\nextends = \"neon\"",
+            "name = \"bad\"\nunknown = 1",
+            "[styles]\ntext = \"red\"",
+        ] {
+            assert!(Theme::from_toml(contents).is_err(), "{}", contents);
+        }
+
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("bad.toml"), "name = 5").unwrap();
+        let mut manager = ThemeManager::new();
+        let errors = manager.load_themes_from_dir(dir.path());
+        assert_eq!(errors.len(), 1);
+        assert_eq!(manager.available_themes().len(), 3);
+    }
 }
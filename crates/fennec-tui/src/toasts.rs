@@ -0,0 +1,396 @@
+//! Stack of toast notifications with a history of past ones.
+//!
+//! At most [`MAX_VISIBLE_TOASTS`] toasts are shown at once; more wait in a
+//! queue and appear as earlier ones expire. Every toast is kept in the
+//! history overlay, newest first.
+
+use crate::error::ErrorToast;
+use crate::events::AppEvent;
+use crate::theme::{ComponentType, ThemeManager};
+use crossterm::event::{KeyCode, KeyEvent};
+use fennec_core::error::ErrorSeverity;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Widget},
+};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Toasts shown at the same time
+pub const MAX_VISIBLE_TOASTS: usize = 3;
+
+/// Toasts kept in the history
+const TOAST_HISTORY_LIMIT: usize = 50;
+
+/// Width of a toast, shrunk to fit narrow areas
+const TOAST_WIDTH: u16 = 44;
+
+/// Source of the current time for toast expiry
+pub trait Clock: fmt::Debug + Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// The real clock
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Toast notifications stacked in the corner of an area
+#[derive(Debug)]
+pub struct ToastStack {
+    clock: Arc<dyn Clock>,
+    /// Toasts on screen, oldest first
+    visible: Vec<ErrorToast>,
+    /// Toasts waiting for room
+    queued: VecDeque<ErrorToast>,
+    /// Every toast raised, newest first
+    history: VecDeque<ErrorToast>,
+    show_history: bool,
+    history_scroll: usize,
+}
+
+impl Default for ToastStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ToastStack {
+    pub fn new() -> Self {
+        Self::with_clock(Arc::new(SystemClock))
+    }
+
+    /// Create a stack timing expiry with `clock`
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            clock,
+            visible: Vec::new(),
+            queued: VecDeque::new(),
+            history: VecDeque::new(),
+            show_history: false,
+            history_scroll: 0,
+        }
+    }
+
+    /// Show a toast, or queue it when the stack is full
+    pub fn push(&mut self, toast: ErrorToast) {
+        self.history.push_front(toast.clone());
+        self.history.truncate(TOAST_HISTORY_LIMIT);
+        self.queued.push_back(toast);
+        self.promote(self.clock.now());
+    }
+
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.push(ErrorToast::with_severity(
+            message.into(),
+            ErrorSeverity::Info,
+        ));
+    }
+
+    pub fn warning(&mut self, message: impl Into<String>) {
+        self.push(ErrorToast::with_severity(
+            message.into(),
+            ErrorSeverity::Warning,
+        ));
+    }
+
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.push(ErrorToast::with_severity(
+            message.into(),
+            ErrorSeverity::Error,
+        ));
+    }
+
+    /// Drop expired toasts and show queued ones in their place. Returns
+    /// whether anything changed.
+    pub fn tick(&mut self) -> bool {
+        let now = self.clock.now();
+        let before = self.visible.len();
+        self.visible.retain(|toast| !toast.is_expired_at(now));
+        let expired = self.visible.len() != before;
+        expired | self.promote(now)
+    }
+
+    /// Take the action of the newest toast offering one, dismissing it.
+    /// Returns the event to dispatch.
+    pub fn take_action(&mut self) -> Option<AppEvent> {
+        let index = self
+            .visible
+            .iter()
+            .rposition(|toast| toast.action.is_some())?;
+        let toast = self.visible.remove(index);
+        self.promote(self.clock.now());
+        toast.action.map(|action| action.event)
+    }
+
+    /// Dismiss every toast on screen and in the queue
+    pub fn clear(&mut self) {
+        self.visible.clear();
+        self.queued.clear();
+    }
+
+    pub fn visible(&self) -> &[ErrorToast] {
+        &self.visible
+    }
+
+    pub fn queued_len(&self) -> usize {
+        self.queued.len()
+    }
+
+    /// Past toasts, newest first
+    pub fn history(&self) -> impl Iterator<Item = &ErrorToast> {
+        self.history.iter()
+    }
+
+    pub fn is_history_visible(&self) -> bool {
+        self.show_history
+    }
+
+    pub fn toggle_history(&mut self) {
+        self.show_history = !self.show_history;
+        self.history_scroll = 0;
+    }
+
+    /// Handle a key while the history is shown. Returns whether it was used.
+    pub fn handle_history_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.history_scroll =
+                    (self.history_scroll + 1).min(self.history.len().saturating_sub(1));
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.history_scroll = self.history_scroll.saturating_sub(1);
+            }
+            KeyCode::Esc | KeyCode::Char('e') | KeyCode::Char('q') => self.toggle_history(),
+            _ => return false,
+        }
+        true
+    }
+
+    fn promote(&mut self, now: Instant) -> bool {
+        let mut promoted = false;
+        while self.visible.len() < MAX_VISIBLE_TOASTS {
+            let Some(mut toast) = self.queued.pop_front() else {
+                break;
+            };
+            // Time on screen starts when the toast is shown
+            toast.start_time = now;
+            self.visible.push(toast);
+            promoted = true;
+        }
+        promoted
+    }
+
+    /// Areas of the visible toasts, stacked down from the top right corner
+    /// of `area`. Toasts that don't fit are left out.
+    pub fn layout(&self, area: Rect) -> Vec<Rect> {
+        let width = TOAST_WIDTH.min(area.width);
+        let x = area.x + area.width - width;
+        let mut y = area.y;
+        let mut areas = Vec::new();
+
+        for toast in &self.visible {
+            let height = toast.height(width);
+            if y + height > area.y + area.height {
+                break;
+            }
+            areas.push(Rect::new(x, y, width, height));
+            y += height;
+        }
+        areas
+    }
+
+    /// Render the visible toasts inside `area`
+    pub fn render(&self, area: Rect, buf: &mut Buffer, theme: &ThemeManager) {
+        for (toast, toast_area) in self.visible.iter().zip(self.layout(area)) {
+            toast.render(toast_area, buf, theme);
+        }
+    }
+
+    /// Render the history overlay
+    pub fn render_history(&self, area: Rect, buf: &mut Buffer, theme: &ThemeManager) {
+        Clear.render(area, buf);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(theme.get_style(ComponentType::Border))
+            .title(Span::styled(
+                format!(" Notifications ({}) ", self.history.len()),
+                theme.get_style(ComponentType::Title),
+            ));
+
+        let lines: Vec<Line> = if self.history.is_empty() {
+            vec![Line::from(Span::styled(
+                "No notifications yet",
+                theme.get_style(ComponentType::Muted),
+            ))]
+        } else {
+            self.history
+                .iter()
+                .skip(self.history_scroll)
+                .map(|toast| {
+                    let mut spans = vec![
+                        Span::styled(
+                            toast.created_at.format("%H:%M:%S ").to_string(),
+                            theme.get_style(ComponentType::Muted),
+                        ),
+                        Span::styled(
+                            format!("{} ", toast.severity_icon()),
+                            Style::default()
+                                .fg(toast.severity_color(theme))
+                                .add_modifier(Modifier::BOLD),
+                        ),
+                        Span::styled(
+                            toast.message.lines().next().unwrap_or_default().to_string(),
+                            theme.get_style(ComponentType::Text),
+                        ),
+                    ];
+                    if let Some(action) = &toast.action {
+                        spans.push(Span::styled(
+                            format!(" [{}]", action.label),
+                            theme.get_style(ComponentType::Muted),
+                        ));
+                    }
+                    Line::from(spans)
+                })
+                .collect()
+        };
+
+        Paragraph::new(lines).block(block).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    /// Clock that only moves when told to
+    #[derive(Debug)]
+    struct ManualClock(Mutex<Instant>);
+
+    impl ManualClock {
+        fn new() -> Arc<Self> {
+            Arc::new(Self(Mutex::new(Instant::now())))
+        }
+
+        fn advance(&self, by: Duration) {
+            *self.0.lock().unwrap() += by;
+        }
+    }
+
+    impl Clock for ManualClock {
+        fn now(&self) -> Instant {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    fn messages(stack: &ToastStack) -> Vec<&str> {
+        stack
+            .visible()
+            .iter()
+            .map(|toast| toast.message.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_toasts_queue_beyond_visible_limit() {
+        let mut stack = ToastStack::new();
+        for i in 0..5 {
+            stack.info(format!("toast {}", i));
+        }
+
+        assert_eq!(messages(&stack), ["toast 0", "toast 1", "toast 2"]);
+        assert_eq!(stack.queued_len(), 2);
+        let history: Vec<_> = stack.history().map(|t| t.message.as_str()).collect();
+        assert_eq!(history[0], "toast 4");
+        assert_eq!(history.len(), 5);
+
+        stack.clear();
+        assert!(stack.visible().is_empty());
+        assert_eq!(stack.queued_len(), 0);
+        assert_eq!(stack.history().count(), 5);
+    }
+
+    #[test]
+    fn test_expiry_follows_severity_and_clock() {
+        let clock = ManualClock::new();
+        let mut stack = ToastStack::with_clock(clock.clone());
+        stack.info("info");
+        stack.warning("warning");
+        stack.error("error");
+        stack.info("queued");
+
+        clock.advance(Duration::from_millis(2999));
+        assert!(!stack.tick());
+        assert_eq!(messages(&stack), ["info", "warning", "error"]);
+
+        // Info expires first, making room for the queued toast
+        clock.advance(Duration::from_millis(2));
+        assert!(stack.tick());
+        assert_eq!(messages(&stack), ["warning", "error", "queued"]);
+
+        // The promoted toast gets its full time from when it appeared
+        clock.advance(Duration::from_millis(2000));
+        stack.tick();
+        assert_eq!(messages(&stack), ["error", "queued"]);
+        clock.advance(Duration::from_millis(1001));
+        stack.tick();
+        assert_eq!(messages(&stack), ["error"]);
+
+        clock.advance(Duration::from_millis(3000));
+        stack.tick();
+        assert!(stack.visible().is_empty());
+    }
+
+    #[test]
+    fn test_retry_action_emits_event() {
+        let mut stack = ToastStack::new();
+        assert_eq!(stack.take_action(), None);
+
+        stack.push(
+            ErrorToast::with_severity("Send failed".to_string(), ErrorSeverity::Error)
+                .with_action("Retry", AppEvent::SendMessage("hello".to_string())),
+        );
+        stack.info("unrelated");
+
+        assert_eq!(
+            stack.take_action(),
+            Some(AppEvent::SendMessage("hello".to_string()))
+        );
+        assert_eq!(messages(&stack), ["unrelated"]);
+        assert_eq!(stack.take_action(), None);
+    }
+
+    #[test]
+    fn test_toasts_stack_without_overlapping() {
+        let mut stack = ToastStack::new();
+        stack.error("first");
+        stack.push(
+            ErrorToast::with_severity("second".to_string(), ErrorSeverity::Warning)
+                .with_action("Retry", AppEvent::Quit),
+        );
+        stack.info("third");
+
+        let area = Rect::new(0, 0, 60, 8);
+        let areas = stack.layout(area);
+        // The third toast doesn't fit below the first two
+        assert_eq!(areas, [Rect::new(16, 0, 44, 3), Rect::new(16, 3, 44, 4)]);
+
+        let mut buf = Buffer::empty(area);
+        stack.render(area, &mut buf, &ThemeManager::new());
+        let row: String = (16..60).map(|x| buf.get(x, 5).symbol.clone()).collect();
+        assert!(row.contains("[r] Retry"));
+    }
+}
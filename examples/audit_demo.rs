@@ -119,6 +119,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         success: true,
         output: format!("Successfully processed {} bytes", read_data.len()),
         error: None,
+        data: None,
     };
 
     context.complete_execution(&result).await?;